@@ -0,0 +1,11 @@
+-- Pages that failed to extract or decode while being served
+CREATE TABLE IF NOT EXISTS page_errors (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    chapter_id INTEGER NOT NULL REFERENCES chapters(id) ON DELETE CASCADE,
+    page_index INTEGER NOT NULL,
+    error TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE(chapter_id, page_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_page_errors_chapter ON page_errors(chapter_id);
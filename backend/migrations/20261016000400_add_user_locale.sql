@@ -0,0 +1,2 @@
+-- Preferred locale for text generated for the user; NULL uses the server locale
+ALTER TABLE users ADD COLUMN locale TEXT;
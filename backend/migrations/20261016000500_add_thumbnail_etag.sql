@@ -0,0 +1,2 @@
+-- Version tag of the stored thumbnail; changes whenever the thumbnail is regenerated
+ALTER TABLE contents ADD COLUMN thumbnail_etag TEXT;
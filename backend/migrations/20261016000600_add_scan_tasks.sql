@@ -0,0 +1,11 @@
+-- Scan tasks saved at shutdown so queued and interrupted scans survive a restart
+CREATE TABLE IF NOT EXISTS scan_tasks (
+    id TEXT PRIMARY KEY,
+    library_id INTEGER NOT NULL,
+    status TEXT NOT NULL,
+    -- 1 if the task should run again after the restart
+    resume INTEGER NOT NULL DEFAULT 0,
+    -- The task serialized as JSON
+    data TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
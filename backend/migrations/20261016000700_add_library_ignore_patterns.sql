@@ -0,0 +1,16 @@
+-- Per-library name patterns for files and folders skipped by scans
+CREATE TABLE IF NOT EXISTS library_ignore_patterns (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    library_id INTEGER NOT NULL REFERENCES libraries(id) ON DELETE CASCADE,
+    pattern TEXT NOT NULL,
+    UNIQUE(library_id, pattern)
+);
+
+-- Existing libraries get the defaults new libraries start with: Synology
+-- thumbnail and recycle bin folders, and hidden files and folders
+INSERT INTO library_ignore_patterns (library_id, pattern)
+SELECT id, '@eaDir' FROM libraries;
+INSERT INTO library_ignore_patterns (library_id, pattern)
+SELECT id, '#recycle' FROM libraries;
+INSERT INTO library_ignore_patterns (library_id, pattern)
+SELECT id, '.*' FROM libraries;
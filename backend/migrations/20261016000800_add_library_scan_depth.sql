@@ -0,0 +1,2 @@
+-- Number of folder levels below a scan path searched for content folders
+ALTER TABLE libraries ADD COLUMN scan_depth INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,2 @@
+-- Whether archive files directly in a scan path are imported as contents
+ALTER TABLE libraries ADD COLUMN standalone_archives INTEGER NOT NULL DEFAULT 0;
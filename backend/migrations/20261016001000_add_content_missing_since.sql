@@ -0,0 +1,5 @@
+-- When a content's folder was found missing from disk; NULL while present.
+-- Missing content stays in the trash until purged.
+ALTER TABLE contents ADD COLUMN missing_since TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_contents_missing_since ON contents(missing_since);
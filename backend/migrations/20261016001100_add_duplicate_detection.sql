@@ -0,0 +1,9 @@
+-- SHA-1 of a chapter's first page, computed when looking for duplicates.
+ALTER TABLE chapters ADD COLUMN page_hash TEXT;
+
+-- The content a duplicate was merged into; merged content is hidden from
+-- listings but kept so scans do not import its folder again.
+ALTER TABLE contents ADD COLUMN merged_into INTEGER REFERENCES contents(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_chapters_page_hash ON chapters(page_hash);
+CREATE INDEX IF NOT EXISTS idx_contents_merged_into ON contents(merged_into);
@@ -0,0 +1,12 @@
+-- Per-library metadata providers, tried in ascending position when scraping
+CREATE TABLE IF NOT EXISTS library_metadata_providers (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    library_id INTEGER NOT NULL REFERENCES libraries(id) ON DELETE CASCADE,
+    provider TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    UNIQUE(library_id, provider)
+);
+
+-- Existing libraries keep scraping from Bangumi only
+INSERT INTO library_metadata_providers (library_id, provider, position)
+SELECT id, 'bangumi', 0 FROM libraries;
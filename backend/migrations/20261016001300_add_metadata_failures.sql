@@ -0,0 +1,10 @@
+-- Content whose metadata could not be scraped, kept until retried
+-- successfully, matched by hand or dismissed
+CREATE TABLE IF NOT EXISTS metadata_failures (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    content_id INTEGER NOT NULL UNIQUE REFERENCES contents(id) ON DELETE CASCADE,
+    error TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,5 @@
+-- Title derived from the folder name, before normalization; `title` holds
+-- the cleaned display title. Existing content was never normalized.
+ALTER TABLE contents ADD COLUMN folder_title TEXT NOT NULL DEFAULT '';
+
+UPDATE contents SET folder_title = title;
@@ -0,0 +1,4 @@
+-- Settings each thumbnail was generated with, so thumbnails made with older
+-- settings can be regenerated. Existing thumbnails have no spec and count as
+-- outdated.
+ALTER TABLE contents ADD COLUMN thumbnail_spec TEXT;
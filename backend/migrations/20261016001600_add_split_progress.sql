@@ -0,0 +1,13 @@
+-- Reading position inside each virtual chapter of a split .txt novel.
+-- The chapter's own progress records which split is being read.
+CREATE TABLE IF NOT EXISTS split_progress (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    chapter_id INTEGER NOT NULL REFERENCES chapters(id) ON DELETE CASCADE,
+    split_index INTEGER NOT NULL,
+    position INTEGER NOT NULL DEFAULT 0,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE(user_id, chapter_id, split_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_split_progress_chapter ON split_progress(chapter_id);
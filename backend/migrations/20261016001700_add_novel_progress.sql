@@ -0,0 +1,5 @@
+-- Place in reflowable text for novels: section index and character offset,
+-- with an optional EPUB CFI. NULL when the reader only sent a position.
+ALTER TABLE reading_progress ADD COLUMN novel_section INTEGER;
+ALTER TABLE reading_progress ADD COLUMN novel_offset INTEGER;
+ALTER TABLE reading_progress ADD COLUMN novel_cfi TEXT;
@@ -0,0 +1,3 @@
+-- Recently added and recently updated feeds read contents newest first
+CREATE INDEX IF NOT EXISTS idx_contents_created_at ON contents(created_at);
+CREATE INDEX IF NOT EXISTS idx_contents_updated_at ON contents(updated_at);
@@ -0,0 +1,13 @@
+-- Per-user favorite flag and reading status (reading, plan_to_read, ...)
+-- of a content. Independent of reading progress.
+CREATE TABLE IF NOT EXISTS user_content_status (
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    content_id INTEGER NOT NULL REFERENCES contents(id) ON DELETE CASCADE,
+    favorite INTEGER NOT NULL DEFAULT 0,
+    status TEXT,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (user_id, content_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_content_status_status ON user_content_status(user_id, status);
+CREATE INDEX IF NOT EXISTS idx_user_content_status_content ON user_content_status(content_id);
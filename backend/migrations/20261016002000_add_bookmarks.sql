@@ -0,0 +1,17 @@
+-- Bookmarks and notes a user places in a chapter. `position` is a page
+-- for comics; novels may also store a place in the text.
+CREATE TABLE IF NOT EXISTS bookmarks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    chapter_id INTEGER NOT NULL REFERENCES chapters(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL DEFAULT 0,
+    novel_section INTEGER,
+    novel_offset INTEGER,
+    novel_cfi TEXT,
+    note TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_bookmarks_user ON bookmarks(user_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_bookmarks_chapter ON bookmarks(chapter_id);
@@ -0,0 +1,15 @@
+-- Volume and chapter number parsed from chapter names; filled in for
+-- existing chapters on their next scan.
+ALTER TABLE chapters ADD COLUMN volume INTEGER;
+ALTER TABLE chapters ADD COLUMN number REAL;
+
+-- Related contents, e.g. the seasons or spin-offs of a series.
+CREATE TABLE IF NOT EXISTS series_groups (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    created_at TEXT NOT NULL
+);
+
+ALTER TABLE contents ADD COLUMN series_group_id INTEGER REFERENCES series_groups(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_contents_series_group ON contents(series_group_id);
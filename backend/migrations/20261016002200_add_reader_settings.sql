@@ -0,0 +1,18 @@
+-- Reader settings (reading direction, page layout, webtoon mode). A library
+-- holds the defaults for its contents; a content overrides them. NULL
+-- columns inherit from the level above.
+CREATE TABLE IF NOT EXISTS library_reader_settings (
+    library_id INTEGER PRIMARY KEY REFERENCES libraries(id) ON DELETE CASCADE,
+    reading_direction TEXT,
+    page_layout TEXT,
+    webtoon INTEGER,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS content_reader_settings (
+    content_id INTEGER PRIMARY KEY REFERENCES contents(id) ON DELETE CASCADE,
+    reading_direction TEXT,
+    page_layout TEXT,
+    webtoon INTEGER,
+    updated_at TEXT NOT NULL
+);
@@ -0,0 +1,18 @@
+-- Metadata edited by hand, kept apart from the scraped metadata blob on
+-- contents. NULL values fall back to the scraped metadata; a rescrape
+-- clears the values whose lock is not set. Tags and authors are JSON arrays.
+CREATE TABLE IF NOT EXISTS content_metadata_edits (
+    content_id INTEGER PRIMARY KEY REFERENCES contents(id) ON DELETE CASCADE,
+    title_lock INTEGER NOT NULL DEFAULT 0,
+    summary TEXT,
+    summary_lock INTEGER NOT NULL DEFAULT 0,
+    status TEXT,
+    status_lock INTEGER NOT NULL DEFAULT 0,
+    language TEXT,
+    language_lock INTEGER NOT NULL DEFAULT 0,
+    tags TEXT,
+    tags_lock INTEGER NOT NULL DEFAULT 0,
+    authors TEXT,
+    authors_lock INTEGER NOT NULL DEFAULT 0,
+    updated_at TEXT NOT NULL
+);
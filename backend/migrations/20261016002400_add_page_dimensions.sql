@@ -0,0 +1,11 @@
+-- Page sizes of image chapters, read from the page headers on first use.
+-- file_size is the chapter size they were read from; rows of a chapter
+-- whose file changed are stale. Unreadable pages are stored as 0x0.
+CREATE TABLE IF NOT EXISTS page_dimensions (
+    chapter_id INTEGER NOT NULL REFERENCES chapters(id) ON DELETE CASCADE,
+    page_index INTEGER NOT NULL,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    file_size INTEGER NOT NULL,
+    PRIMARY KEY (chapter_id, page_index)
+);
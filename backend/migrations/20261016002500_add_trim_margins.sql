@@ -0,0 +1,4 @@
+-- Trim uniform white or black margins off pages before serving them. NULL
+-- inherits from the level above, like the other reader settings.
+ALTER TABLE library_reader_settings ADD COLUMN trim_margins INTEGER;
+ALTER TABLE content_reader_settings ADD COLUMN trim_margins INTEGER;
@@ -0,0 +1,5 @@
+-- BlurHash placeholders. Thumbnails get theirs when they are stored, or
+-- from a backfill at startup for thumbnails stored before this column;
+-- pages get theirs when first served, if enabled.
+ALTER TABLE contents ADD COLUMN thumbnail_blurhash TEXT;
+ALTER TABLE page_dimensions ADD COLUMN blurhash TEXT;
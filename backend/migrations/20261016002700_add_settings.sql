@@ -0,0 +1,8 @@
+-- Server settings changed at runtime by administrators. Each value is
+-- stored as JSON under its key; keys without a row use the value from the
+-- environment or configuration file.
+CREATE TABLE IF NOT EXISTS settings (
+    key TEXT PRIMARY KEY NOT NULL,
+    value TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
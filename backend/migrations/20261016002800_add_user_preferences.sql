@@ -0,0 +1,7 @@
+-- Interface preferences of each user, stored as one JSON object. The
+-- preferred locale stays in users.locale, where generated text reads it.
+CREATE TABLE IF NOT EXISTS user_preferences (
+    user_id INTEGER PRIMARY KEY NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    preferences TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
@@ -0,0 +1,8 @@
+-- Whether the file of a chapter could be read at the last scan: 'ready',
+-- 'error' when it cannot be opened or has no pages, or 'unsupported' for
+-- formats that cannot be read. Chapters that are not ready keep the error
+-- message and count no pages.
+ALTER TABLE chapters ADD COLUMN status TEXT NOT NULL DEFAULT 'ready';
+ALTER TABLE chapters ADD COLUMN error TEXT;
+
+CREATE INDEX idx_chapters_status ON chapters(status) WHERE status != 'ready';
@@ -0,0 +1,4 @@
+-- When deletion of the library started. A library being deleted is hidden
+-- while a background task removes its chapters and contents in batches;
+-- the library row itself is removed last.
+ALTER TABLE libraries ADD COLUMN deleting_since TEXT;
@@ -0,0 +1,12 @@
+-- Last place each user reached in a book on a Kobo e-reader, in the
+-- device's own location format. The percentage read is kept in
+-- reading_progress with the progress from other readers.
+CREATE TABLE IF NOT EXISTS kobo_reading_states (
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    chapter_id INTEGER NOT NULL REFERENCES chapters(id) ON DELETE CASCADE,
+    location_value TEXT,
+    location_type TEXT,
+    location_source TEXT,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (user_id, chapter_id)
+);
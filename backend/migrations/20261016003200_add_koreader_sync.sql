@@ -0,0 +1,18 @@
+-- Partial MD5 KOReader identifies a chapter's file by, computed when a
+-- device syncs a document no chapter is known to match.
+ALTER TABLE chapters ADD COLUMN koreader_hash TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_chapters_koreader_hash ON chapters(koreader_hash);
+
+-- Last position each user's KOReader devices sent for a document, as they
+-- sent it. Documents matching a chapter also update reading_progress.
+CREATE TABLE IF NOT EXISTS koreader_progress (
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    document TEXT NOT NULL,
+    progress TEXT NOT NULL,
+    percentage REAL NOT NULL,
+    device TEXT NOT NULL,
+    device_id TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (user_id, document)
+);
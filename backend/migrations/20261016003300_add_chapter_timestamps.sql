@@ -0,0 +1,11 @@
+-- When each chapter was first found and when its file last changed, so
+-- clients can tell which chapters are new. Existing chapters take the
+-- times of their content.
+ALTER TABLE chapters ADD COLUMN created_at TEXT;
+ALTER TABLE chapters ADD COLUMN updated_at TEXT;
+
+UPDATE chapters SET
+    created_at = (SELECT c.created_at FROM contents c WHERE c.id = chapters.content_id),
+    updated_at = (SELECT c.updated_at FROM contents c WHERE c.id = chapters.content_id);
+
+CREATE INDEX IF NOT EXISTS idx_chapters_created_at ON chapters(created_at);
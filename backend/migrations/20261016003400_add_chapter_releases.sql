@@ -0,0 +1,11 @@
+-- Chapters a scan found in content that was already in a library, feeding
+-- each user's list of new chapters in the series they follow.
+CREATE TABLE IF NOT EXISTS chapter_releases (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    content_id INTEGER NOT NULL REFERENCES contents(id) ON DELETE CASCADE,
+    chapter_id INTEGER NOT NULL REFERENCES chapters(id) ON DELETE CASCADE,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_chapter_releases_content ON chapter_releases(content_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_chapter_releases_chapter ON chapter_releases(chapter_id);
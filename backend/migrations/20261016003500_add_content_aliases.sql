@@ -0,0 +1,35 @@
+-- Alternative titles of each content, from scraped metadata or added by
+-- hand, searched along with the title and matched against renamed folders.
+CREATE TABLE IF NOT EXISTS content_aliases (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    content_id INTEGER NOT NULL REFERENCES contents(id) ON DELETE CASCADE,
+    title TEXT NOT NULL,
+    source TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    UNIQUE(content_id, title)
+);
+
+CREATE INDEX IF NOT EXISTS idx_content_aliases_title ON content_aliases(title COLLATE NOCASE);
+
+-- Names in the metadata scraped so far
+INSERT OR IGNORE INTO content_aliases (content_id, title, source, created_at)
+SELECT id, trim(json_extract(CAST(metadata AS TEXT), '$.name')), 'metadata', updated_at
+FROM contents
+WHERE json_valid(CAST(metadata AS TEXT))
+    AND trim(coalesce(json_extract(CAST(metadata AS TEXT), '$.name'), '')) != '';
+
+INSERT OR IGNORE INTO content_aliases (content_id, title, source, created_at)
+SELECT id, trim(json_extract(CAST(metadata AS TEXT), '$.name_cn')), 'metadata', updated_at
+FROM contents
+WHERE json_valid(CAST(metadata AS TEXT))
+    AND trim(coalesce(json_extract(CAST(metadata AS TEXT), '$.name_cn'), '')) != '';
+
+INSERT OR IGNORE INTO content_aliases (content_id, title, source, created_at)
+SELECT c.id,
+    trim(CASE v.type WHEN 'object' THEN json_extract(v.value, '$.v') ELSE v.value END),
+    'metadata', c.updated_at
+FROM contents c, json_each(CAST(c.metadata AS TEXT), '$.infobox') i, json_each(i.value, '$.value') v
+WHERE json_valid(CAST(c.metadata AS TEXT))
+    AND json_extract(i.value, '$.key') = '别名'
+    AND v.type IN ('object', 'text')
+    AND trim(coalesce(CASE v.type WHEN 'object' THEN json_extract(v.value, '$.v') ELSE v.value END, '')) != '';
@@ -0,0 +1,10 @@
+-- Age rating of each content, extracted from its metadata or set by hand,
+-- and the highest rating each user may see (unrestricted when NULL).
+ALTER TABLE contents ADD COLUMN age_rating INTEGER;
+ALTER TABLE content_metadata_edits ADD COLUMN age_rating_lock INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE users ADD COLUMN max_age_rating INTEGER;
+
+-- Metadata scraped so far that is marked as adult only
+UPDATE contents SET age_rating = 18
+WHERE json_valid(CAST(metadata AS TEXT))
+    AND json_extract(CAST(metadata AS TEXT), '$.nsfw') = 1;
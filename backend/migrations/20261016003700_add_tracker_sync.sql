@@ -0,0 +1,36 @@
+-- AniList and MyAnimeList accounts users linked, with the OAuth tokens the
+-- background sync pushes their progress with.
+CREATE TABLE IF NOT EXISTS tracker_accounts (
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    tracker TEXT NOT NULL,
+    remote_username TEXT NOT NULL,
+    access_token TEXT NOT NULL,
+    refresh_token TEXT,
+    expires_at TEXT,
+    -- Progress changed before this was already looked at
+    last_synced_at TEXT,
+    last_error TEXT,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (user_id, tracker)
+);
+
+-- Number of chapters last pushed for each series, so unchanged series are
+-- not sent again.
+CREATE TABLE IF NOT EXISTS tracker_sync_state (
+    user_id INTEGER NOT NULL,
+    tracker TEXT NOT NULL,
+    content_id INTEGER NOT NULL REFERENCES contents(id) ON DELETE CASCADE,
+    remote_id TEXT NOT NULL,
+    chapters_read INTEGER NOT NULL,
+    synced_at TEXT NOT NULL,
+    PRIMARY KEY (user_id, tracker, content_id),
+    FOREIGN KEY (user_id, tracker) REFERENCES tracker_accounts(user_id, tracker) ON DELETE CASCADE
+);
+
+-- Series a user keeps off their trackers.
+CREATE TABLE IF NOT EXISTS tracker_opt_outs (
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    content_id INTEGER NOT NULL REFERENCES contents(id) ON DELETE CASCADE,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (user_id, content_id)
+);
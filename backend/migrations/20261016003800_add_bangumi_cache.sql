@@ -0,0 +1,15 @@
+-- Bangumi search results and subjects, reused until they expire so that
+-- rescans do not query Bangumi for every series, and kept afterwards to
+-- fall back on while Bangumi cannot be reached.
+CREATE TABLE IF NOT EXISTS bangumi_cache (
+    -- `search` or `subject`
+    kind TEXT NOT NULL,
+    -- Search query or subject ID
+    cache_key TEXT NOT NULL,
+    -- Whether the response was fetched with an access token, which can
+    -- reveal subjects hidden from anonymous requests
+    authorized INTEGER NOT NULL,
+    response TEXT NOT NULL,
+    fetched_at TEXT NOT NULL,
+    PRIMARY KEY (kind, cache_key, authorized)
+);
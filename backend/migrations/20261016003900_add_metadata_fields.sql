@@ -0,0 +1,68 @@
+-- Key fields of each content's metadata, with manual edits applied, copied
+-- out of the JSON blob so listings can filter and sort by them in SQL.
+ALTER TABLE contents ADD COLUMN summary TEXT;
+ALTER TABLE contents ADD COLUMN publisher TEXT;
+ALTER TABLE contents ADD COLUMN release_date TEXT;
+ALTER TABLE contents ADD COLUMN language TEXT;
+ALTER TABLE contents ADD COLUMN rating REAL;
+
+CREATE INDEX IF NOT EXISTS idx_contents_rating ON contents(rating);
+
+CREATE TABLE IF NOT EXISTS content_authors (
+    content_id INTEGER NOT NULL REFERENCES contents(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    role TEXT NOT NULL,
+    PRIMARY KEY (content_id, name, role)
+);
+
+CREATE INDEX IF NOT EXISTS idx_content_authors_name ON content_authors(name COLLATE NOCASE);
+
+-- Metadata scraped so far
+UPDATE contents SET
+    summary = nullif(trim(json_extract(CAST(metadata AS TEXT), '$.summary')), ''),
+    publisher = (
+        SELECT trim(CASE v.type WHEN 'object' THEN json_extract(v.value, '$.v') ELSE v.value END)
+        FROM json_each(CAST(contents.metadata AS TEXT), '$.infobox') i, json_each(i.value, '$.value') v
+        WHERE json_extract(i.value, '$.key') = '出版社'
+            AND v.type IN ('object', 'text')
+            AND trim(coalesce(CASE v.type WHEN 'object' THEN json_extract(v.value, '$.v') ELSE v.value END, '')) != ''
+        LIMIT 1
+    ),
+    release_date = nullif(trim(CAST(json_extract(CAST(metadata AS TEXT), '$.date') AS TEXT)), ''),
+    language = coalesce(nullif(trim(json_extract(CAST(metadata AS TEXT), '$.language')), ''), 'ja'),
+    rating = CASE WHEN json_extract(CAST(metadata AS TEXT), '$.rating.score') > 0
+        THEN json_extract(CAST(metadata AS TEXT), '$.rating.score') END
+WHERE json_valid(CAST(metadata AS TEXT));
+
+INSERT OR IGNORE INTO content_authors (content_id, name, role)
+SELECT c.id,
+    trim(CASE v.type WHEN 'object' THEN json_extract(v.value, '$.v') ELSE v.value END),
+    CASE json_extract(i.value, '$.key') WHEN '作者' THEN 'writer' ELSE 'penciller' END
+FROM contents c, json_each(CAST(c.metadata AS TEXT), '$.infobox') i, json_each(i.value, '$.value') v
+WHERE json_valid(CAST(c.metadata AS TEXT))
+    AND json_extract(i.value, '$.key') IN ('作者', '作画')
+    AND v.type IN ('object', 'text')
+    AND trim(coalesce(CASE v.type WHEN 'object' THEN json_extract(v.value, '$.v') ELSE v.value END, '')) != ''
+ORDER BY c.id, json_extract(i.value, '$.key') != '作者', i.id, v.id;
+
+-- Edits made by hand win over it
+UPDATE contents SET
+    summary = coalesce(
+        (SELECT e.summary FROM content_metadata_edits e WHERE e.content_id = contents.id),
+        summary
+    ),
+    language = coalesce(
+        (SELECT e.language FROM content_metadata_edits e WHERE e.content_id = contents.id),
+        language
+    )
+WHERE id IN (SELECT content_id FROM content_metadata_edits);
+
+DELETE FROM content_authors
+WHERE content_id IN (SELECT content_id FROM content_metadata_edits WHERE authors IS NOT NULL);
+
+INSERT OR IGNORE INTO content_authors (content_id, name, role)
+SELECT e.content_id, json_extract(a.value, '$.name'),
+    coalesce(json_extract(a.value, '$.role'), 'writer')
+FROM content_metadata_edits e, json_each(e.authors) a
+WHERE e.authors IS NOT NULL AND json_valid(e.authors)
+    AND json_extract(a.value, '$.name') IS NOT NULL;
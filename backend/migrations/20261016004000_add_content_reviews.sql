@@ -0,0 +1,14 @@
+-- Ratings from 1 to 10 given to contents by users, each with an optional
+-- short review. A user rates a content at most once.
+CREATE TABLE IF NOT EXISTS content_reviews (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    content_id INTEGER NOT NULL REFERENCES contents(id) ON DELETE CASCADE,
+    rating INTEGER NOT NULL CHECK (rating BETWEEN 1 AND 10),
+    review TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE(user_id, content_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_content_reviews_content ON content_reviews(content_id);
@@ -0,0 +1,13 @@
+-- Daily transfer and request limits of each user, overriding the server
+-- defaults when set; 0 means unlimited.
+ALTER TABLE users ADD COLUMN quota_daily_mb INTEGER;
+ALTER TABLE users ADD COLUMN quota_daily_requests INTEGER;
+
+-- Bytes sent and received and requests made by each user per UTC day.
+CREATE TABLE IF NOT EXISTS user_usage (
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    day TEXT NOT NULL,
+    bytes INTEGER NOT NULL DEFAULT 0,
+    requests INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (user_id, day)
+);
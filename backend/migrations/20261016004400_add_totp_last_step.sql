@@ -0,0 +1,3 @@
+-- Time step of the last TOTP code accepted for each user. Codes from this
+-- step or earlier are rejected, so an intercepted code cannot be replayed.
+ALTER TABLE users ADD COLUMN totp_last_step INTEGER;
@@ -0,0 +1,1501 @@
+//! Layered server configuration.
+//!
+//! Settings are resolved in three layers, later ones overriding earlier ones:
+//! 1. Built-in defaults
+//! 2. A TOML file named by the `RYURI_CONFIG` environment variable
+//! 3. Individual environment variables (`PORT`, `DATABASE_URL`, ...)
+//!
+//! The result is validated once at startup and every problem found is
+//! reported together, before any service is started.
+
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use axum::http::{HeaderName, HeaderValue, Method};
+use serde::Deserialize;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use tracing_subscriber::EnvFilter;
+
+use crate::db::DbConfig;
+use crate::extractors::txt::ChapterSplitter;
+use crate::handlers::content::NEXT_CURSOR_HEADER;
+use crate::middlewares::client_ip::ClientIpConfig;
+use crate::middlewares::http_limits::HttpLimitsConfig;
+use crate::middlewares::rate_limit::LoginRateLimitConfig;
+use crate::middlewares::request_id::REQUEST_ID_HEADER;
+use crate::middlewares::security_headers::SecurityHeadersConfig;
+use crate::middlewares::timeout::RequestTimeoutConfig;
+use crate::services::auth::{AuthConfig, PasswordPolicy};
+use crate::services::backup::BackupConfig;
+use crate::services::bangumi::DEFAULT_BANGUMI_CACHE_TTL_HOURS;
+use crate::services::content::{DEFAULT_ARCHIVE_CACHE_SIZE, MAX_ARCHIVE_CACHE_SIZE};
+use crate::services::oidc::OidcConfig;
+use crate::services::quota::QuotaConfig;
+use crate::services::resource_guard::ScanResourceConfig;
+use crate::services::scan_queue::{
+    DEFAULT_HISTORY_MAX_ENTRIES, DEFAULT_HISTORY_RETENTION_HOURS, DEFAULT_IMPORT_CONCURRENCY,
+    DEFAULT_TRASH_RETENTION_DAYS, HistoryRetention,
+};
+use crate::services::tracker::{TrackerClientConfig, TrackerConfig};
+use crate::state::AppConfig;
+use crate::t;
+use crate::utils::thumbnail::{
+    DEFAULT_THUMBNAIL_HEIGHT, DEFAULT_THUMBNAIL_QUALITY, DEFAULT_THUMBNAIL_WIDTH,
+    MAX_THUMBNAIL_SIZE, MIN_THUMBNAIL_SIZE, ThumbnailFormat, ThumbnailOptions,
+};
+use crate::utils::title::TitleNormalizer;
+
+/// Environment variable naming the configuration file.
+pub const CONFIG_PATH_ENV: &str = "RYURI_CONFIG";
+
+/// Upper bound for concurrent scans.
+pub const MAX_SCAN_CONCURRENCY: usize = 16;
+
+/// Complete server configuration.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerSettings,
+    pub database: DatabaseSettings,
+    pub auth: AuthSettings,
+    pub password_policy: PasswordPolicy,
+    pub login_rate_limit: LoginRateLimitConfig,
+    pub oidc: OidcSettings,
+    pub tracking: TrackingSettings,
+    pub metadata: MetadataSettings,
+    pub scan: ScanSettings,
+    pub thumbnails: ThumbnailSettings,
+    pub reader: ReaderSettings,
+    pub filesystem: FilesystemSettings,
+    pub cors: CorsConfig,
+    pub client_ip: ClientIpConfig,
+    pub security_headers: SecurityHeadersConfig,
+    pub http: HttpLimitsConfig,
+    pub logging: LoggingSettings,
+    pub timeouts: RequestTimeoutConfig,
+    pub backup: BackupConfig,
+    pub quota: QuotaConfig,
+}
+
+/// Listen address.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerSettings {
+    pub host: String,
+    pub port: u16,
+    /// Seconds allowed for each step of a graceful shutdown.
+    pub shutdown_timeout_secs: u64,
+    /// Unix domain socket to listen on instead of `host` and `port`.
+    pub unix_socket: Option<PathBuf>,
+    /// PEM certificate chain. Together with `tls_key`, serves HTTPS.
+    pub tls_cert: Option<PathBuf>,
+    /// PEM private key of `tls_cert`.
+    pub tls_key: Option<PathBuf>,
+}
+
+impl Default for ServerSettings {
+    fn default() -> Self {
+        Self {
+            host: "0.0.0.0".to_string(),
+            port: 3000,
+            shutdown_timeout_secs: 30,
+            unix_socket: None,
+            tls_cert: None,
+            tls_key: None,
+        }
+    }
+}
+
+/// Where the server accepts connections.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Listen {
+    /// Plain HTTP over TCP.
+    Http(SocketAddr),
+    /// HTTPS over TCP.
+    Https {
+        addr: SocketAddr,
+        cert: PathBuf,
+        key: PathBuf,
+    },
+    /// Plain HTTP over a Unix domain socket.
+    Unix(PathBuf),
+}
+
+/// Database connection settings.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseSettings {
+    pub url: String,
+    pub max_connections: u32,
+}
+
+impl Default for DatabaseSettings {
+    fn default() -> Self {
+        Self {
+            url: "sqlite:ryuri.db?mode=rwc".to_string(),
+            max_connections: 5,
+        }
+    }
+}
+
+/// JWT settings. A random secret is generated when none is configured.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthSettings {
+    pub jwt_secret: Option<String>,
+    pub jwt_expiration_hours: i64,
+}
+
+impl Default for AuthSettings {
+    fn default() -> Self {
+        Self {
+            jwt_secret: None,
+            jwt_expiration_hours: 24,
+        }
+    }
+}
+
+/// OIDC provider settings.
+///
+/// OIDC login is enabled when the issuer, client ID, client secret and
+/// redirect URL are all set; setting only some of them is an error.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OidcSettings {
+    pub issuer_url: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    pub redirect_url: Option<String>,
+    pub scopes: Option<String>,
+    pub frontend_redirect: Option<String>,
+    pub auto_provision: bool,
+}
+
+impl OidcSettings {
+    /// Build the provider configuration, or `Ok(None)` when OIDC is not configured.
+    fn resolve(&self) -> std::result::Result<Option<OidcConfig>, String> {
+        let mut config = match (
+            &self.issuer_url,
+            &self.client_id,
+            &self.client_secret,
+            &self.redirect_url,
+        ) {
+            (None, None, None, None) => return Ok(None),
+            (Some(issuer_url), Some(client_id), Some(client_secret), Some(redirect_url)) => {
+                OidcConfig::new(
+                    issuer_url.clone(),
+                    client_id.clone(),
+                    client_secret.clone(),
+                    redirect_url.clone(),
+                )
+            }
+            _ => {
+                let missing: Vec<&str> = [
+                    ("oidc.issuer_url", &self.issuer_url),
+                    ("oidc.client_id", &self.client_id),
+                    ("oidc.client_secret", &self.client_secret),
+                    ("oidc.redirect_url", &self.redirect_url),
+                ]
+                .iter()
+                .filter(|(_, value)| value.is_none())
+                .map(|(key, _)| *key)
+                .collect();
+                return Err(t!("config.oidc_incomplete", missing = missing.join(", ")).to_string());
+            }
+        };
+
+        if let Some(scopes) = &self.scopes {
+            config.scopes = scopes.clone();
+        }
+        if let Some(frontend_redirect) = &self.frontend_redirect {
+            config.frontend_redirect = frontend_redirect.clone();
+        }
+        config.auto_provision = self.auto_provision;
+        Ok(Some(config))
+    }
+}
+
+/// AniList, MyAnimeList and Bangumi sync settings.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrackingSettings {
+    pub anilist: TrackerClientSettings,
+    pub myanimelist: TrackerClientSettings,
+    pub bangumi: TrackerClientSettings,
+    /// Frontend URL to return to after linking an account.
+    pub frontend_redirect: Option<String>,
+    /// Minutes between background syncs (0 = only when users ask).
+    pub sync_interval_minutes: u64,
+}
+
+impl Default for TrackingSettings {
+    fn default() -> Self {
+        Self {
+            anilist: TrackerClientSettings::default(),
+            myanimelist: TrackerClientSettings::default(),
+            bangumi: TrackerClientSettings::default(),
+            frontend_redirect: None,
+            sync_interval_minutes: TrackerConfig::default().sync_interval_minutes,
+        }
+    }
+}
+
+/// OAuth client registered at a tracker.
+///
+/// A tracker can be linked when the client ID, client secret and redirect
+/// URL are all set; setting only some of them is an error.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrackerClientSettings {
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    pub redirect_url: Option<String>,
+}
+
+impl TrackerClientSettings {
+    /// Build the client, or `Ok(None)` when the tracker is not configured.
+    /// `section` names the settings in errors, e.g. `tracking.anilist`.
+    fn resolve(&self, section: &str) -> std::result::Result<Option<TrackerClientConfig>, String> {
+        match (&self.client_id, &self.client_secret, &self.redirect_url) {
+            (None, None, None) => Ok(None),
+            (Some(client_id), Some(client_secret), Some(redirect_url)) => {
+                Ok(Some(TrackerClientConfig {
+                    client_id: client_id.clone(),
+                    client_secret: client_secret.clone(),
+                    redirect_url: redirect_url.clone(),
+                }))
+            }
+            _ => {
+                let missing: Vec<String> = [
+                    ("client_id", &self.client_id),
+                    ("client_secret", &self.client_secret),
+                    ("redirect_url", &self.redirect_url),
+                ]
+                .iter()
+                .filter(|(_, value)| value.is_none())
+                .map(|(key, _)| format!("{}.{}", section, key))
+                .collect();
+                Err(t!(
+                    "config.tracker_incomplete",
+                    section = section,
+                    missing = missing.join(", ")
+                )
+                .to_string())
+            }
+        }
+    }
+}
+
+impl TrackingSettings {
+    /// Build the tracker configuration, with only the trackers whose
+    /// client is fully configured.
+    fn resolve(&self) -> TrackerConfig {
+        let mut config = TrackerConfig {
+            anilist: self.anilist.resolve("tracking.anilist").ok().flatten(),
+            myanimelist: self
+                .myanimelist
+                .resolve("tracking.myanimelist")
+                .ok()
+                .flatten(),
+            bangumi: self.bangumi.resolve("tracking.bangumi").ok().flatten(),
+            sync_interval_minutes: self.sync_interval_minutes,
+            ..TrackerConfig::default()
+        };
+        if let Some(frontend_redirect) = &self.frontend_redirect {
+            config.frontend_redirect = frontend_redirect.clone();
+        }
+        config
+    }
+}
+
+/// Metadata scraping settings.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetadataSettings {
+    /// Bangumi access token scans scrape with, unless administrators save
+    /// another one in the runtime settings.
+    pub bangumi_api_key: Option<String>,
+    /// Hours Bangumi responses are reused before Bangumi is asked again
+    /// (0 = no cache).
+    pub bangumi_cache_ttl_hours: u32,
+}
+
+impl Default for MetadataSettings {
+    fn default() -> Self {
+        Self {
+            bangumi_api_key: None,
+            bangumi_cache_ttl_hours: DEFAULT_BANGUMI_CACHE_TTL_HOURS,
+        }
+    }
+}
+
+/// Background scan settings.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScanSettings {
+    /// Number of libraries scanned at the same time.
+    pub concurrency: usize,
+    /// Number of new content folders inspected at the same time per scan.
+    pub import_concurrency: usize,
+    /// Run scan IO with reduced CPU/IO priority.
+    pub low_priority: bool,
+    /// Maximum filesystem operations per second during scans (0 = unlimited).
+    pub fs_ops_per_second: u32,
+    /// Days content whose folder went missing stays in the trash before it
+    /// is deleted (0 = until purged by an administrator).
+    pub trash_retention_days: u32,
+    /// Hours finished scan tasks stay in the task history (0 = no limit).
+    pub history_retention_hours: u32,
+    /// Number of finished scan tasks kept in the task history (0 = no limit).
+    pub history_max_entries: usize,
+    /// Regular expressions removed from folder names to get the display
+    /// title of new content, which is also what metadata is searched by.
+    /// Unset uses the built-in rules; an empty list keeps folder names as
+    /// they are. Only settable in the configuration file.
+    pub title_rules: Option<Vec<String>>,
+}
+
+impl Default for ScanSettings {
+    fn default() -> Self {
+        Self {
+            concurrency: 1,
+            import_concurrency: DEFAULT_IMPORT_CONCURRENCY,
+            low_priority: false,
+            fs_ops_per_second: 0,
+            trash_retention_days: DEFAULT_TRASH_RETENTION_DAYS,
+            history_retention_hours: DEFAULT_HISTORY_RETENTION_HOURS,
+            history_max_entries: DEFAULT_HISTORY_MAX_ENTRIES,
+            title_rules: None,
+        }
+    }
+}
+
+/// Thumbnail generation settings.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThumbnailSettings {
+    /// Directory for files unpacked while generating thumbnails and serving
+    /// pages. Defaults to the system temp directory.
+    pub cache_dir: Option<PathBuf>,
+    /// Largest thumbnail width in pixels.
+    pub width: u32,
+    /// Largest thumbnail height in pixels.
+    pub height: u32,
+    /// Format of stored thumbnails: `jpeg`, `webp` or `avif`.
+    pub format: ThumbnailFormat,
+    /// Encoding quality from 1 to 100 for JPEG and AVIF.
+    pub quality: u8,
+}
+
+impl Default for ThumbnailSettings {
+    fn default() -> Self {
+        Self {
+            cache_dir: None,
+            width: DEFAULT_THUMBNAIL_WIDTH,
+            height: DEFAULT_THUMBNAIL_HEIGHT,
+            format: ThumbnailFormat::default(),
+            quality: DEFAULT_THUMBNAIL_QUALITY,
+        }
+    }
+}
+
+/// Page serving settings.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReaderSettings {
+    /// Number of comic archives kept open between page requests
+    /// (0 = reopen the archive for every page).
+    pub archive_cache_size: usize,
+    /// Regular expressions matching the chapter heading lines `.txt` novels
+    /// are split at. Unset uses the built-in patterns. Only settable in the
+    /// configuration file.
+    pub txt_chapter_patterns: Option<Vec<String>>,
+    /// Compute a BlurHash placeholder of each comic page when it is first
+    /// served, listed by the page list endpoint.
+    pub page_blurhash: bool,
+}
+
+impl Default for ReaderSettings {
+    fn default() -> Self {
+        Self {
+            archive_cache_size: DEFAULT_ARCHIVE_CACHE_SIZE,
+            txt_chapter_patterns: None,
+            page_blurhash: false,
+        }
+    }
+}
+
+/// Server filesystem browsing settings.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FilesystemSettings {
+    /// Directories administrators may browse and add as scan paths. Empty
+    /// allows the whole filesystem.
+    pub allowed_roots: Vec<PathBuf>,
+}
+
+/// Cross-origin request settings.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorsConfig {
+    /// Allowed origins such as `https://reader.example.com`. Empty or `*`
+    /// allows any origin.
+    pub allowed_origins: Vec<String>,
+    /// Allowed request methods such as `GET`. Empty or `*` allows any method.
+    pub allowed_methods: Vec<String>,
+    /// Allowed request headers such as `Authorization`. Empty or `*` allows
+    /// any header.
+    pub allowed_headers: Vec<String>,
+}
+
+impl CorsConfig {
+    /// Build the CORS layer for the router.
+    pub fn layer(&self) -> CorsLayer {
+        let layer = CorsLayer::new()
+            .expose_headers([REQUEST_ID_HEADER.clone(), NEXT_CURSOR_HEADER.clone()]);
+
+        let layer = if is_wildcard(&self.allowed_methods) {
+            layer.allow_methods(Any)
+        } else {
+            let methods: Vec<Method> = self
+                .allowed_methods
+                .iter()
+                .filter_map(|method| Method::from_str(method).ok())
+                .collect();
+            layer.allow_methods(methods)
+        };
+
+        let layer = if is_wildcard(&self.allowed_headers) {
+            layer.allow_headers(Any)
+        } else {
+            let headers: Vec<HeaderName> = self
+                .allowed_headers
+                .iter()
+                .filter_map(|name| HeaderName::from_str(name).ok())
+                .collect();
+            layer.allow_headers(headers)
+        };
+
+        if is_wildcard(&self.allowed_origins) {
+            return layer.allow_origin(Any);
+        }
+
+        let origins: Vec<HeaderValue> = self
+            .allowed_origins
+            .iter()
+            .filter_map(|origin| HeaderValue::from_str(origin).ok())
+            .collect();
+        layer.allow_origin(AllowOrigin::list(origins))
+    }
+}
+
+/// Whether a CORS list allows anything: empty or containing `*`.
+fn is_wildcard(values: &[String]) -> bool {
+    values.is_empty() || values.iter().any(|value| value == "*")
+}
+
+/// Log output settings. `RUST_LOG`, when set, takes precedence over `level`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingSettings {
+    /// Filter directive, e.g. `info` or `backend=debug,sqlx=warn`.
+    pub level: String,
+    pub format: LogFormat,
+}
+
+impl Default for LoggingSettings {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            format: LogFormat::default(),
+        }
+    }
+}
+
+/// Log line layout.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Compact,
+    Full,
+    Pretty,
+}
+
+impl FromStr for LogFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "compact" => Ok(Self::Compact),
+            "full" => Ok(Self::Full),
+            "pretty" => Ok(Self::Pretty),
+            _ => Err(()),
+        }
+    }
+}
+
+/// All problems found while loading the configuration.
+#[derive(Debug)]
+pub struct ConfigError(pub Vec<String>);
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", t!("config.invalid"))?;
+        for error in &self.0 {
+            write!(f, "\n  - {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Load the configuration from `RYURI_CONFIG` and the process environment.
+    pub fn load() -> std::result::Result<Self, ConfigError> {
+        Self::load_with(|key| std::env::var(key).ok())
+    }
+
+    /// Load the configuration using `env` to look up environment variables.
+    pub fn load_with(
+        env: impl Fn(&str) -> Option<String>,
+    ) -> std::result::Result<Self, ConfigError> {
+        let mut config = match non_empty(env(CONFIG_PATH_ENV)) {
+            Some(path) => Self::from_file(Path::new(&path)).map_err(|e| ConfigError(vec![e]))?,
+            None => Self::default(),
+        };
+
+        let mut errors = Vec::new();
+        config.apply_env(&mut EnvOverrides {
+            env: &env,
+            errors: &mut errors,
+        });
+        config.validate(&mut errors);
+
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(ConfigError(errors))
+        }
+    }
+
+    /// Read a TOML configuration file. Missing keys keep their defaults.
+    pub fn from_file(path: &Path) -> std::result::Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| t!("config.read_failed", path = path.display(), error = e).to_string())?;
+        toml::from_str(&text)
+            .map_err(|e| t!("config.parse_failed", path = path.display(), error = e).to_string())
+    }
+
+    fn apply_env<F: Fn(&str) -> Option<String>>(&mut self, env: &mut EnvOverrides<'_, F>) {
+        env.string("HOST", &mut self.server.host);
+        env.parse("PORT", &mut self.server.port);
+        env.parse(
+            "SHUTDOWN_TIMEOUT_SECONDS",
+            &mut self.server.shutdown_timeout_secs,
+        );
+        if let Some(path) = env.value("UNIX_SOCKET") {
+            self.server.unix_socket = Some(PathBuf::from(path));
+        }
+        if let Some(path) = env.value("TLS_CERT") {
+            self.server.tls_cert = Some(PathBuf::from(path));
+        }
+        if let Some(path) = env.value("TLS_KEY") {
+            self.server.tls_key = Some(PathBuf::from(path));
+        }
+
+        env.string("DATABASE_URL", &mut self.database.url);
+        env.parse(
+            "DATABASE_MAX_CONNECTIONS",
+            &mut self.database.max_connections,
+        );
+
+        env.optional("JWT_SECRET", &mut self.auth.jwt_secret);
+        env.parse("JWT_EXPIRATION_HOURS", &mut self.auth.jwt_expiration_hours);
+        env.parse("PASSWORD_MIN_LENGTH", &mut self.password_policy.min_length);
+        env.parse("PASSWORD_MIN_SCORE", &mut self.password_policy.min_score);
+
+        let login = &mut self.login_rate_limit;
+        env.parse("LOGIN_RATE_LIMIT_IP_PER_MINUTE", &mut login.ip_per_minute);
+        env.parse(
+            "LOGIN_RATE_LIMIT_USER_PER_MINUTE",
+            &mut login.username_per_minute,
+        );
+        env.parse("LOGIN_MAX_FAILURES", &mut login.max_failures);
+        env.parse("LOGIN_LOCKOUT_SECONDS", &mut login.lockout_secs);
+
+        let oidc = &mut self.oidc;
+        env.optional("OIDC_ISSUER_URL", &mut oidc.issuer_url);
+        env.optional("OIDC_CLIENT_ID", &mut oidc.client_id);
+        env.optional("OIDC_CLIENT_SECRET", &mut oidc.client_secret);
+        env.optional("OIDC_REDIRECT_URL", &mut oidc.redirect_url);
+        env.optional("OIDC_SCOPES", &mut oidc.scopes);
+        env.optional("OIDC_FRONTEND_REDIRECT", &mut oidc.frontend_redirect);
+        env.flag("OIDC_AUTO_PROVISION", &mut oidc.auto_provision);
+
+        let tracking = &mut self.tracking;
+        env.optional("ANILIST_CLIENT_ID", &mut tracking.anilist.client_id);
+        env.optional("ANILIST_CLIENT_SECRET", &mut tracking.anilist.client_secret);
+        env.optional("ANILIST_REDIRECT_URL", &mut tracking.anilist.redirect_url);
+        env.optional("MYANIMELIST_CLIENT_ID", &mut tracking.myanimelist.client_id);
+        env.optional(
+            "MYANIMELIST_CLIENT_SECRET",
+            &mut tracking.myanimelist.client_secret,
+        );
+        env.optional(
+            "MYANIMELIST_REDIRECT_URL",
+            &mut tracking.myanimelist.redirect_url,
+        );
+        env.optional("BANGUMI_CLIENT_ID", &mut tracking.bangumi.client_id);
+        env.optional("BANGUMI_CLIENT_SECRET", &mut tracking.bangumi.client_secret);
+        env.optional("BANGUMI_REDIRECT_URL", &mut tracking.bangumi.redirect_url);
+        env.optional(
+            "TRACKING_FRONTEND_REDIRECT",
+            &mut tracking.frontend_redirect,
+        );
+        env.parse(
+            "TRACKING_SYNC_INTERVAL_MINUTES",
+            &mut tracking.sync_interval_minutes,
+        );
+
+        env.optional("BANGUMI_API_KEY", &mut self.metadata.bangumi_api_key);
+        env.parse(
+            "BANGUMI_CACHE_TTL_HOURS",
+            &mut self.metadata.bangumi_cache_ttl_hours,
+        );
+
+        env.parse("SCAN_CONCURRENCY", &mut self.scan.concurrency);
+        env.parse("SCAN_IMPORT_CONCURRENCY", &mut self.scan.import_concurrency);
+        env.flag("SCAN_LOW_PRIORITY", &mut self.scan.low_priority);
+        env.parse("SCAN_FS_OPS_PER_SECOND", &mut self.scan.fs_ops_per_second);
+        env.parse(
+            "SCAN_TRASH_RETENTION_DAYS",
+            &mut self.scan.trash_retention_days,
+        );
+        env.parse(
+            "SCAN_HISTORY_RETENTION_HOURS",
+            &mut self.scan.history_retention_hours,
+        );
+        env.parse(
+            "SCAN_HISTORY_MAX_ENTRIES",
+            &mut self.scan.history_max_entries,
+        );
+
+        if let Some(dir) = env.value("THUMBNAIL_CACHE_DIR") {
+            self.thumbnails.cache_dir = Some(PathBuf::from(dir));
+        }
+        env.parse("THUMBNAIL_WIDTH", &mut self.thumbnails.width);
+        env.parse("THUMBNAIL_HEIGHT", &mut self.thumbnails.height);
+        env.parse("THUMBNAIL_FORMAT", &mut self.thumbnails.format);
+        env.parse("THUMBNAIL_QUALITY", &mut self.thumbnails.quality);
+
+        env.parse(
+            "READER_ARCHIVE_CACHE_SIZE",
+            &mut self.reader.archive_cache_size,
+        );
+        env.flag("READER_PAGE_BLURHASH", &mut self.reader.page_blurhash);
+
+        if let Some(roots) = env.value("FILESYSTEM_ALLOWED_ROOTS") {
+            self.filesystem.allowed_roots = roots
+                .split(',')
+                .map(str::trim)
+                .filter(|root| !root.is_empty())
+                .map(PathBuf::from)
+                .collect();
+        }
+
+        env.list("CORS_ALLOWED_ORIGINS", &mut self.cors.allowed_origins);
+        env.list("CORS_ALLOWED_METHODS", &mut self.cors.allowed_methods);
+        env.list("CORS_ALLOWED_HEADERS", &mut self.cors.allowed_headers);
+
+        env.list("IP_ALLOW_LIST", &mut self.client_ip.allow);
+        env.list("IP_DENY_LIST", &mut self.client_ip.deny);
+        env.list("TRUSTED_PROXIES", &mut self.client_ip.trusted_proxies);
+
+        env.flag("SECURITY_HEADERS", &mut self.security_headers.enabled);
+        env.string(
+            "REFERRER_POLICY",
+            &mut self.security_headers.referrer_policy,
+        );
+        env.string(
+            "CONTENT_SECURITY_POLICY",
+            &mut self.security_headers.content_security_policy,
+        );
+
+        env.flag("COMPRESSION", &mut self.http.compression);
+        env.parse("MAX_BODY_SIZE_MB", &mut self.http.max_body_size_mb);
+
+        env.string("LOG_LEVEL", &mut self.logging.level);
+        env.parse("LOG_FORMAT", &mut self.logging.format);
+
+        env.parse("REQUEST_TIMEOUT_SECONDS", &mut self.timeouts.api_secs);
+        env.parse("PAGE_REQUEST_TIMEOUT_SECONDS", &mut self.timeouts.page_secs);
+        env.parse(
+            "UPLOAD_REQUEST_TIMEOUT_SECONDS",
+            &mut self.timeouts.upload_secs,
+        );
+
+        if let Some(dir) = env.value("BACKUP_DIR") {
+            self.backup.dir = PathBuf::from(dir);
+        }
+        env.parse_optional("BACKUP_NIGHTLY_HOUR", &mut self.backup.nightly_hour);
+        env.parse("BACKUP_KEEP", &mut self.backup.keep);
+        env.parse(
+            "BACKUP_MAX_RESTORE_SIZE_MB",
+            &mut self.backup.max_restore_size_mb,
+        );
+
+        env.parse("QUOTA_DAILY_MB", &mut self.quota.daily_mb);
+        env.parse("QUOTA_DAILY_REQUESTS", &mut self.quota.daily_requests);
+    }
+
+    fn validate(&self, errors: &mut Vec<String>) {
+        if let Err(e) = self.listen_addr() {
+            errors.push(e);
+        }
+        match (&self.server.tls_cert, &self.server.tls_key) {
+            (Some(_), None) | (None, Some(_)) => {
+                errors.push(t!("config.tls_incomplete").to_string());
+            }
+            (Some(cert), Some(key)) => {
+                for path in [cert, key] {
+                    if !path.is_file() {
+                        errors.push(t!("config.not_a_file", path = path.display()).to_string());
+                    }
+                }
+                if self.server.unix_socket.is_some() {
+                    errors.push(t!("config.unix_socket_tls").to_string());
+                }
+            }
+            (None, None) => {}
+        }
+        if cfg!(not(unix)) && self.server.unix_socket.is_some() {
+            errors.push(t!("config.unix_socket_unsupported").to_string());
+        }
+
+        if self.database.url.trim().is_empty() {
+            errors.push(t!("config.required", key = "database.url").to_string());
+        }
+        if self.server.shutdown_timeout_secs == 0 {
+            errors.push(
+                t!(
+                    "config.at_least",
+                    key = "server.shutdown_timeout_secs",
+                    min = 1
+                )
+                .to_string(),
+            );
+        }
+        if self.database.max_connections == 0 {
+            errors
+                .push(t!("config.at_least", key = "database.max_connections", min = 1).to_string());
+        }
+
+        if self.auth.jwt_expiration_hours < 1 {
+            errors.push(
+                t!(
+                    "config.at_least",
+                    key = "auth.jwt_expiration_hours",
+                    min = 1
+                )
+                .to_string(),
+            );
+        }
+        if self.password_policy.min_length == 0 {
+            errors.push(
+                t!(
+                    "config.at_least",
+                    key = "password_policy.min_length",
+                    min = 1
+                )
+                .to_string(),
+            );
+        }
+        if self.password_policy.min_score > 4 {
+            errors.push(
+                t!(
+                    "config.out_of_range",
+                    key = "password_policy.min_score",
+                    min = 0,
+                    max = 4
+                )
+                .to_string(),
+            );
+        }
+
+        match self.oidc.resolve() {
+            Ok(Some(oidc)) => {
+                for (key, value) in [
+                    ("oidc.issuer_url", &oidc.issuer_url),
+                    ("oidc.redirect_url", &oidc.redirect_url),
+                ] {
+                    if reqwest::Url::parse(value).is_err() {
+                        errors.push(t!("config.invalid_url", key = key, value = value).to_string());
+                    }
+                }
+            }
+            Ok(None) => {}
+            Err(e) => errors.push(e),
+        }
+
+        for (section, client) in [
+            ("tracking.anilist", &self.tracking.anilist),
+            ("tracking.myanimelist", &self.tracking.myanimelist),
+            ("tracking.bangumi", &self.tracking.bangumi),
+        ] {
+            match client.resolve(section) {
+                Ok(Some(client)) => {
+                    if reqwest::Url::parse(&client.redirect_url).is_err() {
+                        let key = format!("{}.redirect_url", section);
+                        errors.push(
+                            t!("config.invalid_url", key = key, value = client.redirect_url)
+                                .to_string(),
+                        );
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => errors.push(e),
+            }
+        }
+
+        if !(1..=MAX_SCAN_CONCURRENCY).contains(&self.scan.concurrency) {
+            errors.push(
+                t!(
+                    "config.out_of_range",
+                    key = "scan.concurrency",
+                    min = 1,
+                    max = MAX_SCAN_CONCURRENCY
+                )
+                .to_string(),
+            );
+        }
+        if !(1..=MAX_SCAN_CONCURRENCY).contains(&self.scan.import_concurrency) {
+            errors.push(
+                t!(
+                    "config.out_of_range",
+                    key = "scan.import_concurrency",
+                    min = 1,
+                    max = MAX_SCAN_CONCURRENCY
+                )
+                .to_string(),
+            );
+        }
+
+        for rule in self.scan.title_rules.iter().flatten() {
+            if let Err(e) = regex::Regex::new(rule) {
+                errors.push(t!("config.invalid_title_rule", value = rule, error = e).to_string());
+            }
+        }
+
+        if let Some(dir) = &self.thumbnails.cache_dir
+            && dir.exists()
+            && !dir.is_dir()
+        {
+            errors.push(t!("config.not_a_directory", path = dir.display()).to_string());
+        }
+        for (key, value) in [
+            ("thumbnails.width", self.thumbnails.width),
+            ("thumbnails.height", self.thumbnails.height),
+        ] {
+            if !(MIN_THUMBNAIL_SIZE..=MAX_THUMBNAIL_SIZE).contains(&value) {
+                errors.push(
+                    t!(
+                        "config.out_of_range",
+                        key = key,
+                        min = MIN_THUMBNAIL_SIZE,
+                        max = MAX_THUMBNAIL_SIZE
+                    )
+                    .to_string(),
+                );
+            }
+        }
+        if !(1..=100).contains(&self.thumbnails.quality) {
+            errors.push(
+                t!(
+                    "config.out_of_range",
+                    key = "thumbnails.quality",
+                    min = 1,
+                    max = 100
+                )
+                .to_string(),
+            );
+        }
+
+        if self.reader.archive_cache_size > MAX_ARCHIVE_CACHE_SIZE {
+            errors.push(
+                t!(
+                    "config.out_of_range",
+                    key = "reader.archive_cache_size",
+                    min = 0,
+                    max = MAX_ARCHIVE_CACHE_SIZE
+                )
+                .to_string(),
+            );
+        }
+
+        for pattern in self.reader.txt_chapter_patterns.iter().flatten() {
+            if let Err(e) = regex::Regex::new(pattern) {
+                errors.push(
+                    t!(
+                        "config.invalid_txt_chapter_pattern",
+                        value = pattern,
+                        error = e
+                    )
+                    .to_string(),
+                );
+            }
+        }
+
+        for root in &self.filesystem.allowed_roots {
+            if !root.is_dir() {
+                errors.push(t!("config.not_a_directory", path = root.display()).to_string());
+            }
+        }
+
+        for origin in &self.cors.allowed_origins {
+            let valid = origin == "*"
+                || ((origin.starts_with("http://") || origin.starts_with("https://"))
+                    && HeaderValue::from_str(origin).is_ok());
+            if !valid {
+                errors.push(t!("config.invalid_origin", value = origin).to_string());
+            }
+        }
+        for method in &self.cors.allowed_methods {
+            if method != "*" && Method::from_str(method).is_err() {
+                errors.push(t!("config.invalid_cors_method", value = method).to_string());
+            }
+        }
+        for name in &self.cors.allowed_headers {
+            if name != "*" && HeaderName::from_str(name).is_err() {
+                errors.push(t!("config.invalid_cors_header", value = name).to_string());
+            }
+        }
+        for entry in self.client_ip.invalid_entries() {
+            errors.push(t!("config.invalid_ip_network", value = entry).to_string());
+        }
+        for (key, value) in [
+            (
+                "security_headers.referrer_policy",
+                &self.security_headers.referrer_policy,
+            ),
+            (
+                "security_headers.content_security_policy",
+                &self.security_headers.content_security_policy,
+            ),
+        ] {
+            if HeaderValue::from_str(value.trim()).is_err() {
+                errors.push(t!("config.invalid_env", key = key, value = value).to_string());
+            }
+        }
+
+        if self.backup.dir.exists() && !self.backup.dir.is_dir() {
+            errors.push(t!("config.not_a_directory", path = self.backup.dir.display()).to_string());
+        }
+        if let Some(hour) = self.backup.nightly_hour
+            && hour > 23
+        {
+            errors.push(
+                t!(
+                    "config.out_of_range",
+                    key = "backup.nightly_hour",
+                    min = 0,
+                    max = 23
+                )
+                .to_string(),
+            );
+        }
+        if self.http.max_body_size_mb == 0 {
+            errors.push(t!("config.at_least", key = "http.max_body_size_mb", min = 1).to_string());
+        }
+        if self.backup.keep == 0 {
+            errors.push(t!("config.at_least", key = "backup.keep", min = 1).to_string());
+        }
+
+        if let Err(e) = EnvFilter::try_new(&self.logging.level) {
+            errors.push(
+                t!(
+                    "config.invalid_log_level",
+                    value = self.logging.level,
+                    error = e
+                )
+                .to_string(),
+            );
+        }
+    }
+
+    /// Listen address; valid once the configuration has been validated.
+    pub fn listen_addr(&self) -> std::result::Result<SocketAddr, String> {
+        let address = format!("{}:{}", self.server.host, self.server.port);
+        address
+            .parse()
+            .map_err(|_| t!("config.invalid_address", value = address).to_string())
+    }
+
+    /// Where to accept connections; valid once the configuration has been
+    /// validated.
+    pub fn listen(&self) -> std::result::Result<Listen, String> {
+        if let Some(path) = &self.server.unix_socket {
+            return Ok(Listen::Unix(path.clone()));
+        }
+        let addr = self.listen_addr()?;
+        match (&self.server.tls_cert, &self.server.tls_key) {
+            (Some(cert), Some(key)) => Ok(Listen::Https {
+                addr,
+                cert: cert.clone(),
+                key: key.clone(),
+            }),
+            _ => Ok(Listen::Http(addr)),
+        }
+    }
+
+    /// Database pool configuration.
+    pub fn db_config(&self) -> DbConfig {
+        DbConfig {
+            database_url: self.database.url.clone(),
+            max_connections: self.database.max_connections,
+        }
+    }
+
+    /// Normalizer for the titles of new content.
+    pub fn title_normalizer(&self) -> TitleNormalizer {
+        match &self.scan.title_rules {
+            // Rules were checked by `validate`
+            Some(rules) => TitleNormalizer::new(rules).unwrap_or_default(),
+            None => TitleNormalizer::default(),
+        }
+    }
+
+    /// Splitter for the virtual chapters of `.txt` novels.
+    pub fn chapter_splitter(&self) -> ChapterSplitter {
+        match &self.reader.txt_chapter_patterns {
+            // Patterns were checked by `validate`
+            Some(patterns) => ChapterSplitter::new(patterns).unwrap_or_default(),
+            None => ChapterSplitter::default(),
+        }
+    }
+
+    /// Application configuration, signing tokens with `jwt_secret`.
+    pub fn app_config(&self, jwt_secret: String) -> AppConfig {
+        AppConfig {
+            auth: AuthConfig {
+                jwt_secret,
+                jwt_expiration_hours: self.auth.jwt_expiration_hours,
+                password_policy: self.password_policy,
+            },
+            scan: ScanResourceConfig {
+                low_priority: self.scan.low_priority,
+                fs_ops_per_second: self.scan.fs_ops_per_second,
+            },
+            scan_concurrency: self.scan.concurrency,
+            scan_import_concurrency: self.scan.import_concurrency,
+            trash_retention_days: self.scan.trash_retention_days,
+            task_history: HistoryRetention {
+                max_age_hours: self.scan.history_retention_hours,
+                max_entries: self.scan.history_max_entries,
+            },
+            title_normalizer: self.title_normalizer(),
+            filesystem_roots: self.filesystem.allowed_roots.clone(),
+            thumbnail_options: ThumbnailOptions {
+                width: self.thumbnails.width,
+                height: self.thumbnails.height,
+                format: self.thumbnails.format,
+                quality: self.thumbnails.quality,
+            },
+            login_rate_limit: self.login_rate_limit.clone(),
+            oidc: self.oidc.resolve().ok().flatten(),
+            tracking: self.tracking.resolve(),
+            bangumi_api_key: self.metadata.bangumi_api_key.clone(),
+            bangumi_cache_ttl_hours: self.metadata.bangumi_cache_ttl_hours,
+            request_timeouts: self.timeouts,
+            cors: self.cors.clone(),
+            client_ip: self.client_ip.clone(),
+            security_headers: self.security_headers.clone(),
+            http_limits: self.http,
+            backup: self.backup.clone(),
+            quota: self.quota,
+            shutdown_timeout: Duration::from_secs(self.server.shutdown_timeout_secs),
+        }
+    }
+}
+
+/// Applies environment variables on top of file values, collecting parse errors.
+struct EnvOverrides<'a, F> {
+    env: &'a F,
+    errors: &'a mut Vec<String>,
+}
+
+impl<F: Fn(&str) -> Option<String>> EnvOverrides<'_, F> {
+    /// Trimmed value of a variable; unset and blank are treated alike.
+    fn value(&self, key: &str) -> Option<String> {
+        non_empty((self.env)(key))
+    }
+
+    fn string(&mut self, key: &str, target: &mut String) {
+        if let Some(value) = self.value(key) {
+            *target = value;
+        }
+    }
+
+    /// Comma-separated list, dropping empty items.
+    fn list(&mut self, key: &str, target: &mut Vec<String>) {
+        if let Some(value) = self.value(key) {
+            *target = value
+                .split(',')
+                .map(|item| item.trim().to_string())
+                .filter(|item| !item.is_empty())
+                .collect();
+        }
+    }
+
+    fn optional(&mut self, key: &str, target: &mut Option<String>) {
+        if let Some(value) = self.value(key) {
+            *target = Some(value);
+        }
+    }
+
+    fn parse<T: FromStr>(&mut self, key: &str, target: &mut T) {
+        if let Some(value) = self.value(key) {
+            match value.parse() {
+                Ok(parsed) => *target = parsed,
+                Err(_) => self.invalid(key, &value),
+            }
+        }
+    }
+
+    fn parse_optional<T: FromStr>(&mut self, key: &str, target: &mut Option<T>) {
+        if let Some(value) = self.value(key) {
+            match value.parse() {
+                Ok(parsed) => *target = Some(parsed),
+                Err(_) => self.invalid(key, &value),
+            }
+        }
+    }
+
+    fn flag(&mut self, key: &str, target: &mut bool) {
+        if let Some(value) = self.value(key) {
+            match value.to_lowercase().as_str() {
+                "1" | "true" | "yes" | "on" => *target = true,
+                "0" | "false" | "no" | "off" => *target = false,
+                _ => self.invalid(key, &value),
+            }
+        }
+    }
+
+    fn invalid(&mut self, key: &str, value: &str) {
+        self.errors
+            .push(t!("config.invalid_env", key = key, value = value).to_string());
+    }
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io::Write;
+
+    fn load(vars: &[(&str, &str)]) -> std::result::Result<Config, ConfigError> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        Config::load_with(|key| vars.get(key).cloned())
+    }
+
+    #[test]
+    fn test_defaults_are_valid() {
+        let config = load(&[]).unwrap();
+        assert_eq!(config.server.port, 3000);
+        assert_eq!(config.database.max_connections, 5);
+        assert_eq!(config.scan.concurrency, 1);
+        assert!(config.oidc.resolve().unwrap().is_none());
+        assert!(config.tracking.resolve().anilist.is_none());
+    }
+
+    #[test]
+    fn test_env_overrides_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            "[server]\nport = 4000\n\n[database]\nmax_connections = 8\n\n[scan]\nconcurrency = 2"
+        )
+        .unwrap();
+        let path = file.path().to_str().unwrap();
+
+        let config = load(&[(CONFIG_PATH_ENV, path), ("PORT", "5000")]).unwrap();
+        assert_eq!(config.server.port, 5000);
+        assert_eq!(config.database.max_connections, 8);
+        assert_eq!(config.scan.concurrency, 2);
+    }
+
+    #[test]
+    fn test_unknown_file_key_is_rejected() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "[server]\nprot = 4000").unwrap();
+        let path = file.path().to_str().unwrap();
+
+        assert!(load(&[(CONFIG_PATH_ENV, path)]).is_err());
+    }
+
+    #[test]
+    fn test_all_errors_are_reported() {
+        let err = load(&[
+            ("PORT", "not-a-port"),
+            ("SCAN_CONCURRENCY", "0"),
+            ("SCAN_LOW_PRIORITY", "maybe"),
+            ("OIDC_CLIENT_ID", "ryuri"),
+        ])
+        .unwrap_err();
+        assert_eq!(err.0.len(), 4, "{}", err);
+    }
+
+    #[test]
+    fn test_tracker_clients() {
+        let config = load(&[
+            ("MYANIMELIST_CLIENT_ID", "ryuri"),
+            ("MYANIMELIST_CLIENT_SECRET", "secret"),
+            (
+                "MYANIMELIST_REDIRECT_URL",
+                "https://ryuri.example.com/api/trackers/myanimelist/callback",
+            ),
+            ("TRACKING_SYNC_INTERVAL_MINUTES", "30"),
+        ])
+        .unwrap();
+        let tracking = config.tracking.resolve();
+        assert!(tracking.anilist.is_none());
+        assert_eq!(tracking.myanimelist.unwrap().client_id, "ryuri");
+        assert_eq!(tracking.sync_interval_minutes, 30);
+
+        let err = load(&[("ANILIST_CLIENT_ID", "ryuri")]).unwrap_err();
+        assert_eq!(err.0.len(), 1, "{}", err);
+
+        let err = load(&[
+            ("BANGUMI_CLIENT_ID", "ryuri"),
+            ("BANGUMI_CLIENT_SECRET", "secret"),
+            ("BANGUMI_REDIRECT_URL", "not a url"),
+        ])
+        .unwrap_err();
+        assert_eq!(err.0.len(), 1, "{}", err);
+    }
+
+    #[test]
+    fn test_listen_modes() {
+        let config = load(&[("PORT", "4000")]).unwrap();
+        assert_eq!(
+            config.listen().unwrap(),
+            Listen::Http("0.0.0.0:4000".parse().unwrap())
+        );
+
+        let cert = tempfile::NamedTempFile::new().unwrap();
+        let key = tempfile::NamedTempFile::new().unwrap();
+        let cert_path = cert.path().to_str().unwrap();
+        let key_path = key.path().to_str().unwrap();
+        let config = load(&[("TLS_CERT", cert_path), ("TLS_KEY", key_path)]).unwrap();
+        assert!(matches!(config.listen().unwrap(), Listen::Https { .. }));
+
+        assert!(load(&[("TLS_CERT", cert_path)]).is_err());
+        assert!(load(&[("TLS_CERT", cert_path), ("TLS_KEY", "/nonexistent/key.pem")]).is_err());
+
+        #[cfg(unix)]
+        {
+            let config = load(&[("UNIX_SOCKET", "/run/ryuri.sock")]).unwrap();
+            assert_eq!(
+                config.listen().unwrap(),
+                Listen::Unix(PathBuf::from("/run/ryuri.sock"))
+            );
+            assert!(
+                load(&[
+                    ("UNIX_SOCKET", "/run/ryuri.sock"),
+                    ("TLS_CERT", cert_path),
+                    ("TLS_KEY", key_path),
+                ])
+                .is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn test_backup_settings() {
+        let config = load(&[("BACKUP_NIGHTLY_HOUR", "3"), ("BACKUP_KEEP", "14")]).unwrap();
+        assert_eq!(config.backup.nightly_hour, Some(3));
+        assert_eq!(config.backup.keep, 14);
+
+        assert!(load(&[("BACKUP_NIGHTLY_HOUR", "24")]).is_err());
+        assert!(load(&[("BACKUP_KEEP", "0")]).is_err());
+    }
+
+    #[test]
+    fn test_client_ip_settings() {
+        let config = load(&[
+            ("IP_ALLOW_LIST", "192.168.0.0/16, fd00::/8"),
+            ("TRUSTED_PROXIES", "127.0.0.1"),
+        ])
+        .unwrap();
+        assert_eq!(config.client_ip.allow, ["192.168.0.0/16", "fd00::/8"]);
+        assert_eq!(config.client_ip.trusted_proxies, ["127.0.0.1"]);
+
+        assert!(load(&[("IP_DENY_LIST", "10.0.0.0/33")]).is_err());
+        assert!(load(&[("TRUSTED_PROXIES", "proxy.local")]).is_err());
+    }
+
+    #[test]
+    fn test_quota_settings() {
+        let config = load(&[("QUOTA_DAILY_MB", "2048"), ("QUOTA_DAILY_REQUESTS", "0")]).unwrap();
+        assert_eq!(config.quota.daily_mb, 2048);
+        assert_eq!(config.quota.daily_requests, 0);
+
+        assert!(load(&[("QUOTA_DAILY_MB", "-1")]).is_err());
+    }
+
+    #[test]
+    fn test_password_policy_settings() {
+        let config = load(&[("PASSWORD_MIN_LENGTH", "12"), ("PASSWORD_MIN_SCORE", "3")]).unwrap();
+        assert_eq!(config.password_policy.min_length, 12);
+        assert_eq!(config.password_policy.min_score, 3);
+
+        assert!(load(&[("PASSWORD_MIN_LENGTH", "0")]).is_err());
+        assert!(load(&[("PASSWORD_MIN_SCORE", "5")]).is_err());
+    }
+
+    #[test]
+    fn test_title_rules_from_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "[scan]\ntitle_rules = ['(?i)\\bcomplete\\b']").unwrap();
+        let path = file.path().to_str().unwrap();
+        let config = load(&[(CONFIG_PATH_ENV, path)]).unwrap();
+        assert_eq!(
+            config
+                .title_normalizer()
+                .normalize("[Group] Title Complete"),
+            "[Group] Title"
+        );
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "[scan]\ntitle_rules = ['(unclosed']").unwrap();
+        let path = file.path().to_str().unwrap();
+        assert!(load(&[(CONFIG_PATH_ENV, path)]).is_err());
+    }
+
+    #[test]
+    fn test_thumbnail_size_from_env() {
+        let config = load(&[("THUMBNAIL_WIDTH", "600")]).unwrap();
+        assert_eq!(config.thumbnails.width, 600);
+        assert_eq!(config.thumbnails.height, DEFAULT_THUMBNAIL_HEIGHT);
+
+        assert!(load(&[("THUMBNAIL_HEIGHT", "0")]).is_err());
+    }
+
+    #[test]
+    fn test_archive_cache_size_from_env() {
+        let config = load(&[]).unwrap();
+        assert_eq!(config.reader.archive_cache_size, DEFAULT_ARCHIVE_CACHE_SIZE);
+
+        let config = load(&[("READER_ARCHIVE_CACHE_SIZE", "0")]).unwrap();
+        assert_eq!(config.reader.archive_cache_size, 0);
+
+        assert!(load(&[("READER_ARCHIVE_CACHE_SIZE", "100000")]).is_err());
+    }
+
+    #[test]
+    fn test_txt_chapter_patterns_from_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "[reader]\ntxt_chapter_patterns = ['^Part \\d+$']").unwrap();
+        let path = file.path().to_str().unwrap();
+        let config = load(&[(CONFIG_PATH_ENV, path)]).unwrap();
+        let splits = config.chapter_splitter().split("Part 1\none\nPart 2\ntwo");
+        assert_eq!(splits.len(), 2);
+        assert_eq!(splits[1].title.as_deref(), Some("Part 2"));
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "[reader]\ntxt_chapter_patterns = ['(unclosed']").unwrap();
+        let path = file.path().to_str().unwrap();
+        assert!(load(&[(CONFIG_PATH_ENV, path)]).is_err());
+    }
+
+    #[test]
+    fn test_thumbnail_format_from_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "[thumbnails]\nformat = \"webp\"\nquality = 70").unwrap();
+        let path = file.path().to_str().unwrap();
+        let config = load(&[(CONFIG_PATH_ENV, path)]).unwrap();
+        assert_eq!(config.thumbnails.format, ThumbnailFormat::Webp);
+        assert_eq!(config.thumbnails.quality, 70);
+
+        assert!(load(&[("THUMBNAIL_FORMAT", "gif")]).is_err());
+        assert!(load(&[("THUMBNAIL_QUALITY", "0")]).is_err());
+    }
+
+    #[test]
+    fn test_filesystem_roots_from_env() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_str().unwrap();
+        let config = load(&[("FILESYSTEM_ALLOWED_ROOTS", &format!("{root}, ,{root}"))]).unwrap();
+        assert_eq!(
+            config.filesystem.allowed_roots,
+            vec![dir.path().to_path_buf(), dir.path().to_path_buf()]
+        );
+
+        let missing = dir.path().join("missing");
+        assert!(load(&[("FILESYSTEM_ALLOWED_ROOTS", missing.to_str().unwrap())]).is_err());
+    }
+
+    #[test]
+    fn test_cors_origins_from_env() {
+        let config = load(&[(
+            "CORS_ALLOWED_ORIGINS",
+            "https://a.example.com, https://b.example.com",
+        )])
+        .unwrap();
+        assert_eq!(config.cors.allowed_origins.len(), 2);
+
+        assert!(load(&[("CORS_ALLOWED_ORIGINS", "a.example.com")]).is_err());
+    }
+
+    #[test]
+    fn test_cors_methods_and_headers_from_env() {
+        let config = load(&[
+            ("CORS_ALLOWED_METHODS", "GET, POST"),
+            ("CORS_ALLOWED_HEADERS", "Authorization,Content-Type"),
+        ])
+        .unwrap();
+        assert_eq!(config.cors.allowed_methods, ["GET", "POST"]);
+        assert_eq!(config.cors.allowed_headers.len(), 2);
+
+        assert!(load(&[("CORS_ALLOWED_METHODS", "GET POST")]).is_err());
+        assert!(load(&[("CORS_ALLOWED_HEADERS", "bad header")]).is_err());
+    }
+
+    #[test]
+    fn test_http_limits_from_env() {
+        let config = load(&[("COMPRESSION", "false"), ("MAX_BODY_SIZE_MB", "8")]).unwrap();
+        assert!(!config.http.compression);
+        assert_eq!(config.http.max_body_size_mb, 8);
+
+        assert!(load(&[("MAX_BODY_SIZE_MB", "0")]).is_err());
+    }
+
+    #[test]
+    fn test_security_headers_from_env() {
+        let config = load(&[]).unwrap();
+        assert!(config.security_headers.enabled);
+
+        let config = load(&[
+            ("SECURITY_HEADERS", "off"),
+            ("REFERRER_POLICY", "no-referrer"),
+        ])
+        .unwrap();
+        assert!(!config.security_headers.enabled);
+        assert_eq!(config.security_headers.referrer_policy, "no-referrer");
+
+        assert!(load(&[("CONTENT_SECURITY_POLICY", "default-src\n'self'")]).is_err());
+    }
+}
@@ -3,8 +3,22 @@
 //! This module handles SQLite database setup, including creating tables
 //! and running migrations when the application starts.
 
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use tracing::info;
+
 use crate::error::{AppError, Result};
-use sqlx::{Pool, Sqlite, sqlite::SqlitePoolOptions};
+use crate::t;
+use sqlx::{
+    Pool, Sqlite,
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous},
+};
+
+/// How long a connection waits for a lock held by another connection before
+/// failing with "database is locked".
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// Database configuration options.
 #[derive(Debug, Clone)]
@@ -19,10 +33,24 @@ pub struct DbConfig {
 ///
 /// This function creates the database file if it doesn't exist,
 /// establishes a connection pool, and creates all required tables.
+///
+/// Connections use WAL journaling so readers are not blocked while a scan
+/// is writing, with `synchronous = NORMAL` which cannot corrupt the
+/// database in WAL mode.
 pub async fn init_db(config: &DbConfig) -> Result<Pool<Sqlite>> {
+    let options =
+        SqliteConnectOptions::from_str(&config.database_url).map_err(AppError::Database)?;
+    apply_pending_restore(options.get_filename())?;
+
+    let options = options
+        .journal_mode(SqliteJournalMode::Wal)
+        .synchronous(SqliteSynchronous::Normal)
+        .busy_timeout(BUSY_TIMEOUT)
+        .foreign_keys(true);
+
     let pool = SqlitePoolOptions::new()
         .max_connections(config.max_connections)
-        .connect(&config.database_url)
+        .connect_with(options)
         .await
         .map_err(AppError::Database)?;
 
@@ -32,6 +60,38 @@ pub async fn init_db(config: &DbConfig) -> Result<Pool<Sqlite>> {
     Ok(pool)
 }
 
+/// Path a restored backup is staged at until the next start.
+pub fn pending_restore_path(database_path: &Path) -> PathBuf {
+    let mut path = database_path.as_os_str().to_owned();
+    path.push(".restore");
+    PathBuf::from(path)
+}
+
+/// Replace the database file with a staged backup, if there is one.
+///
+/// Runs before any connection is opened. The WAL and shared-memory files
+/// belong to the replaced database and are removed with it.
+fn apply_pending_restore(database_path: &Path) -> Result<()> {
+    let staged = pending_restore_path(database_path);
+    if !staged.is_file() {
+        return Ok(());
+    }
+
+    for suffix in ["-wal", "-shm"] {
+        let mut path = database_path.as_os_str().to_owned();
+        path.push(suffix);
+        if let Err(e) = std::fs::remove_file(PathBuf::from(path))
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            return Err(e.into());
+        }
+    }
+    std::fs::rename(&staged, database_path)?;
+
+    info!(path = %database_path.display(), "{}", t!("backup.restore_applied"));
+    Ok(())
+}
+
 // Run database migrations to create tables
 async fn run_migrations(pool: &Pool<Sqlite>) -> Result<()> {
     sqlx::migrate!("./migrations")
@@ -89,4 +149,78 @@ mod tests {
             "reading_progress table should exist"
         );
     }
+
+    #[tokio::test]
+    async fn test_init_db_sets_pragmas() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = DbConfig {
+            database_url: format!("sqlite:{}?mode=rwc", dir.path().join("test.db").display()),
+            max_connections: 2,
+        };
+
+        let pool = init_db(&config)
+            .await
+            .expect("Failed to initialize database");
+
+        let (journal_mode,): (String,) = sqlx::query_as("PRAGMA journal_mode")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(journal_mode, "wal");
+
+        let (synchronous,): (i64,) = sqlx::query_as("PRAGMA synchronous")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(synchronous, 1, "synchronous should be NORMAL");
+
+        let (foreign_keys,): (i64,) = sqlx::query_as("PRAGMA foreign_keys")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(foreign_keys, 1);
+
+        let (busy_timeout,): (i64,) = sqlx::query_as("PRAGMA busy_timeout")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(busy_timeout, BUSY_TIMEOUT.as_millis() as i64);
+    }
+
+    #[tokio::test]
+    async fn test_init_db_applies_pending_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let database_path = dir.path().join("test.db");
+        let config = DbConfig {
+            database_url: format!("sqlite:{}?mode=rwc", database_path.display()),
+            max_connections: 1,
+        };
+
+        // Stage a copy of a database that has an extra table
+        let pool = init_db(&config).await.unwrap();
+        sqlx::query("CREATE TABLE restored (id INTEGER)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let staged = pending_restore_path(&database_path);
+        sqlx::query("VACUUM INTO ?")
+            .bind(staged.to_string_lossy().to_string())
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DROP TABLE restored")
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+
+        let pool = init_db(&config).await.unwrap();
+        let (count,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM sqlite_master WHERE name = 'restored'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(count, 1);
+        assert!(!staged.exists());
+    }
 }
@@ -4,12 +4,15 @@
 
 use axum::{
     Json,
-    http::StatusCode,
+    http::{StatusCode, header},
     response::{IntoResponse, Response},
 };
 use serde::Serialize;
+use serde_json::{Value, json};
 use thiserror::Error;
-use rust_i18n::t;
+
+use crate::middlewares::request_id::current_request_id;
+use crate::t;
 
 /// Unified error type for the application.
 #[derive(Debug, Error)]
@@ -23,6 +26,19 @@ pub enum AppError {
     #[error("Unauthorized: {0}")]
     Unauthorized(String),
 
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Too many requests: {message}")]
+    TooManyRequests {
+        message: String,
+        /// Seconds the client should wait before retrying.
+        retry_after_secs: u64,
+    },
+
+    #[error("Request cancelled")]
+    Cancelled,
+
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
 
@@ -37,11 +53,159 @@ pub enum AppError {
 
     #[error("Internal error: {0}")]
     Axum(#[from] axum::http::Error),
+
+    /// Another error with a more specific code, see [`AppError::with_code`].
+    #[error("{source}")]
+    Coded {
+        code: ErrorCode,
+        details: Option<Value>,
+        source: Box<AppError>,
+    },
 }
 
-/// Error response body structure.
+/// Machine-readable error code, so clients can branch on errors without
+/// matching messages, which are translated.
+///
+/// Every error has one of the generic codes of its kind; errors clients
+/// commonly handle get a specific code instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    NotFound,
+    BadRequest,
+    Unauthorized,
+    Forbidden,
+    TooManyRequests,
+    RequestCancelled,
+    DatabaseError,
+    FileSystemError,
+    ArchiveError,
+    InternalError,
+    LibraryNotFound,
+    ContentNotFound,
+    ChapterNotFound,
+    PageNotFound,
+    ThumbnailNotFound,
+    ScanTaskNotFound,
+    TaskNotCancellable,
+    JobNotFound,
+    UserNotFound,
+    BookmarkNotFound,
+    SeriesGroupNotFound,
+    ApiKeyNotFound,
+    BackupNotFound,
+    UsernameTaken,
+    InvalidCredentials,
+    InvalidToken,
+    InvalidTotpCode,
+    AdminRequired,
+    SetupCompleted,
+    PathNotAllowed,
+    UnsupportedFormat,
+    InvalidCursor,
+}
+
+impl ErrorCode {
+    /// Every code, in declaration order.
+    pub const ALL: [ErrorCode; 32] = [
+        ErrorCode::NotFound,
+        ErrorCode::BadRequest,
+        ErrorCode::Unauthorized,
+        ErrorCode::Forbidden,
+        ErrorCode::TooManyRequests,
+        ErrorCode::RequestCancelled,
+        ErrorCode::DatabaseError,
+        ErrorCode::FileSystemError,
+        ErrorCode::ArchiveError,
+        ErrorCode::InternalError,
+        ErrorCode::LibraryNotFound,
+        ErrorCode::ContentNotFound,
+        ErrorCode::ChapterNotFound,
+        ErrorCode::PageNotFound,
+        ErrorCode::ThumbnailNotFound,
+        ErrorCode::ScanTaskNotFound,
+        ErrorCode::TaskNotCancellable,
+        ErrorCode::JobNotFound,
+        ErrorCode::UserNotFound,
+        ErrorCode::BookmarkNotFound,
+        ErrorCode::SeriesGroupNotFound,
+        ErrorCode::ApiKeyNotFound,
+        ErrorCode::BackupNotFound,
+        ErrorCode::UsernameTaken,
+        ErrorCode::InvalidCredentials,
+        ErrorCode::InvalidToken,
+        ErrorCode::InvalidTotpCode,
+        ErrorCode::AdminRequired,
+        ErrorCode::SetupCompleted,
+        ErrorCode::PathNotAllowed,
+        ErrorCode::UnsupportedFormat,
+        ErrorCode::InvalidCursor,
+    ];
+
+    /// The code as sent to clients.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::NotFound => "NOT_FOUND",
+            ErrorCode::BadRequest => "BAD_REQUEST",
+            ErrorCode::Unauthorized => "UNAUTHORIZED",
+            ErrorCode::Forbidden => "FORBIDDEN",
+            ErrorCode::TooManyRequests => "TOO_MANY_REQUESTS",
+            ErrorCode::RequestCancelled => "REQUEST_CANCELLED",
+            ErrorCode::DatabaseError => "DATABASE_ERROR",
+            ErrorCode::FileSystemError => "FILE_SYSTEM_ERROR",
+            ErrorCode::ArchiveError => "ARCHIVE_ERROR",
+            ErrorCode::InternalError => "INTERNAL_ERROR",
+            ErrorCode::LibraryNotFound => "LIBRARY_NOT_FOUND",
+            ErrorCode::ContentNotFound => "CONTENT_NOT_FOUND",
+            ErrorCode::ChapterNotFound => "CHAPTER_NOT_FOUND",
+            ErrorCode::PageNotFound => "PAGE_NOT_FOUND",
+            ErrorCode::ThumbnailNotFound => "THUMBNAIL_NOT_FOUND",
+            ErrorCode::ScanTaskNotFound => "SCAN_TASK_NOT_FOUND",
+            ErrorCode::TaskNotCancellable => "TASK_NOT_CANCELLABLE",
+            ErrorCode::JobNotFound => "JOB_NOT_FOUND",
+            ErrorCode::UserNotFound => "USER_NOT_FOUND",
+            ErrorCode::BookmarkNotFound => "BOOKMARK_NOT_FOUND",
+            ErrorCode::SeriesGroupNotFound => "SERIES_GROUP_NOT_FOUND",
+            ErrorCode::ApiKeyNotFound => "API_KEY_NOT_FOUND",
+            ErrorCode::BackupNotFound => "BACKUP_NOT_FOUND",
+            ErrorCode::UsernameTaken => "USERNAME_TAKEN",
+            ErrorCode::InvalidCredentials => "INVALID_CREDENTIALS",
+            ErrorCode::InvalidToken => "INVALID_TOKEN",
+            ErrorCode::InvalidTotpCode => "INVALID_TOTP_CODE",
+            ErrorCode::AdminRequired => "ADMIN_REQUIRED",
+            ErrorCode::SetupCompleted => "SETUP_COMPLETED",
+            ErrorCode::PathNotAllowed => "PATH_NOT_ALLOWED",
+            ErrorCode::UnsupportedFormat => "UNSUPPORTED_FORMAT",
+            ErrorCode::InvalidCursor => "INVALID_CURSOR",
+        }
+    }
+}
+
+/// Media type of error responses.
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// Error response body, an RFC 7807 problem document sent as
+/// `application/problem+json`.
+///
+/// `error` repeats the status and message in the shape of earlier versions,
+/// for clients that still read it.
 #[derive(Debug, Serialize)]
 pub struct ErrorResponse {
+    /// Always `about:blank`; `code` tells problems apart.
+    #[serde(rename = "type")]
+    pub problem_type: &'static str,
+    /// Reason phrase of the status.
+    pub title: &'static str,
+    pub status: u16,
+    /// Human-readable message in the request locale.
+    pub detail: String,
+    pub code: ErrorCode,
+    /// Extra data about the error, such as the ID that was not found.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Value>,
+    /// ID of the request, to find it in the server logs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
     pub error: ErrorDetail,
 }
 
@@ -59,11 +223,87 @@ impl AppError {
             AppError::NotFound(_) => StatusCode::NOT_FOUND,
             AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
             AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
+            AppError::Cancelled => StatusCode::REQUEST_TIMEOUT,
             AppError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
             AppError::FileSystem(_) => StatusCode::INTERNAL_SERVER_ERROR,
             AppError::Archive(_) => StatusCode::UNPROCESSABLE_ENTITY,
             AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
             AppError::Axum(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::Coded { source, .. } => source.status_code(),
+        }
+    }
+
+    /// Returns the machine-readable code for this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AppError::NotFound(_) => ErrorCode::NotFound,
+            AppError::BadRequest(_) => ErrorCode::BadRequest,
+            AppError::Unauthorized(_) => ErrorCode::Unauthorized,
+            AppError::Forbidden(_) => ErrorCode::Forbidden,
+            AppError::TooManyRequests { .. } => ErrorCode::TooManyRequests,
+            AppError::Cancelled => ErrorCode::RequestCancelled,
+            AppError::Database(_) => ErrorCode::DatabaseError,
+            AppError::FileSystem(_) => ErrorCode::FileSystemError,
+            AppError::Archive(_) => ErrorCode::ArchiveError,
+            AppError::Internal(_) => ErrorCode::InternalError,
+            AppError::Axum(_) => ErrorCode::InternalError,
+            AppError::Coded { code, .. } => *code,
+        }
+    }
+
+    /// Returns the details of this error, if any were attached.
+    pub fn details(&self) -> Option<Value> {
+        match self {
+            AppError::TooManyRequests {
+                retry_after_secs, ..
+            } => Some(json!({ "retry_after_secs": retry_after_secs })),
+            AppError::Coded { details, .. } => details.clone(),
+            _ => None,
+        }
+    }
+
+    /// Give this error a more specific code. The status and message stay
+    /// those of the error.
+    pub fn with_code(self, code: ErrorCode) -> Self {
+        match self {
+            AppError::Coded {
+                details, source, ..
+            } => AppError::Coded {
+                code,
+                details,
+                source,
+            },
+            error => AppError::Coded {
+                code,
+                details: None,
+                source: Box::new(error),
+            },
+        }
+    }
+
+    /// Attach details for clients, e.g. the ID that was not found.
+    pub fn with_details(self, details: Value) -> Self {
+        match self {
+            AppError::Coded { code, source, .. } => AppError::Coded {
+                code,
+                details: Some(details),
+                source,
+            },
+            error => AppError::Coded {
+                code: error.code(),
+                details: Some(details),
+                source: Box::new(error),
+            },
+        }
+    }
+
+    /// The error without its specific code, to match on its kind.
+    pub fn kind(&self) -> &AppError {
+        match self {
+            AppError::Coded { source, .. } => source.kind(),
+            error => error,
         }
     }
 
@@ -73,20 +313,33 @@ impl AppError {
             AppError::NotFound(msg) => msg.clone(),
             AppError::BadRequest(msg) => msg.clone(),
             AppError::Unauthorized(msg) => msg.clone(),
+            AppError::Forbidden(msg) => msg.clone(),
+            AppError::TooManyRequests { message, .. } => message.clone(),
+            AppError::Cancelled => t!("error.cancelled").to_string(),
             AppError::Database(_) => t!("error.database").to_string(),
             AppError::FileSystem(_) => t!("error.filesystem").to_string(),
             AppError::Archive(msg) => msg.clone(),
             AppError::Internal(msg) => msg.clone(),
             AppError::Axum(_) => t!("error.http").to_string(),
+            AppError::Coded { source, .. } => source.error_message(),
         }
     }
 
     /// Converts the error into an ErrorResponse structure.
     pub fn to_error_response(&self) -> ErrorResponse {
+        let status = self.status_code();
+        let message = self.error_message();
         ErrorResponse {
+            problem_type: "about:blank",
+            title: status.canonical_reason().unwrap_or("Error"),
+            status: status.as_u16(),
+            detail: message.clone(),
+            code: self.code(),
+            details: self.details(),
+            request_id: current_request_id(),
             error: ErrorDetail {
-                code: self.status_code().as_u16(),
-                message: self.error_message(),
+                code: status.as_u16(),
+                message,
             },
         }
     }
@@ -96,7 +349,22 @@ impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let status = self.status_code();
         let body = Json(self.to_error_response());
-        (status, body).into_response()
+        let content_type = [(header::CONTENT_TYPE, PROBLEM_JSON)];
+
+        if let AppError::TooManyRequests {
+            retry_after_secs, ..
+        } = self.kind()
+        {
+            return (
+                status,
+                content_type,
+                [(header::RETRY_AFTER, retry_after_secs.to_string())],
+                body,
+            )
+                .into_response();
+        }
+
+        (status, content_type, body).into_response()
     }
 }
 
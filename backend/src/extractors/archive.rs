@@ -4,22 +4,96 @@
 //! Supported formats:
 //! - ZIP/CBZ: Standard ZIP archives (CBZ is just ZIP with a different extension)
 //! - CBR/RAR: RAR archives
+//! - Image folders: plain directories of image files, read like an archive
+//!
+//! Entries whose name leads outside the archive and symbolic links are
+//! never listed or read, so a crafted archive cannot expose other files.
 
-use crate::error::{AppError, Result};
-use rust_i18n::t;
+use crate::error::{AppError, ErrorCode, Result};
+use crate::t;
+use crate::utils::cancel::CancellationFlag;
 use std::fs::File;
 use std::io::Read;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use super::{is_safe_entry_name, natural_sort_key};
+
+/// Chunk size used when reading archive entries.
+const READ_CHUNK_SIZE: usize = 256 * 1024;
 
-use super::natural_sort_key;
+/// Name prefix of the per-entry directories RAR entries are unpacked into.
+const EXTRACT_DIR_PREFIX: &str = "comic_extract_";
 
 /// Supported image extensions for comics.
 const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "gif", "webp", "bmp"];
 
+/// Directory for files unpacked from RAR archives, if configured.
+static SCRATCH_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Images of an archive, with the entries left out because they are unsafe
+/// to read.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArchiveEntries {
+    /// Image files, sorted by filename.
+    pub files: Vec<String>,
+    /// Entries whose name leads outside the archive, such as `../001.jpg`
+    /// or an absolute path, and symbolic links.
+    pub suspicious: Vec<String>,
+}
+
+impl ArchiveEntries {
+    /// Sort the image files using natural sort order.
+    fn sorted(mut self) -> Self {
+        self.files.sort_by_key(|a| natural_sort_key(a));
+        self
+    }
+}
+
 /// Archive extractor supporting ZIP, CBZ, CBR, and RAR formats.
 pub struct ArchiveExtractor;
 
 impl ArchiveExtractor {
+    /// Sets the directory RAR entries are unpacked into while generating
+    /// thumbnails and serving pages. Defaults to the system temp directory;
+    /// only the first call takes effect.
+    pub fn set_scratch_dir(dir: PathBuf) {
+        let _ = SCRATCH_DIR.set(dir);
+    }
+
+    fn scratch_dir() -> PathBuf {
+        SCRATCH_DIR
+            .get()
+            .cloned()
+            .unwrap_or_else(std::env::temp_dir)
+    }
+
+    /// Removes extraction directories left behind by an interrupted run and
+    /// returns how many were removed. Call before any extraction starts.
+    ///
+    /// Only a configured scratch directory is cleaned; the system temp
+    /// directory may be shared with other running instances.
+    pub fn clean_scratch_dir() -> usize {
+        let Some(dir) = SCRATCH_DIR.get() else {
+            return 0;
+        };
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return 0;
+        };
+
+        entries
+            .flatten()
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with(EXTRACT_DIR_PREFIX)
+                    && entry.file_type().is_ok_and(|t| t.is_dir())
+            })
+            .filter(|entry| std::fs::remove_dir_all(entry.path()).is_ok())
+            .count()
+    }
+
     /// Returns the supported archive extensions.
     pub fn supported_extensions() -> &'static [&'static str] {
         &["zip", "cbz", "cbr", "rar"]
@@ -33,8 +107,49 @@ impl ArchiveExtractor {
             .unwrap_or(false)
     }
 
+    /// Checks if a path is a directory directly containing image files.
+    pub fn is_image_folder(path: &Path) -> bool {
+        if !path.is_dir() {
+            return false;
+        }
+        let Ok(entries) = std::fs::read_dir(path) else {
+            return false;
+        };
+
+        entries.flatten().any(|entry| {
+            entry.file_type().is_ok_and(|t| t.is_file())
+                && Self::is_image_file(&entry.file_name().to_string_lossy())
+        })
+    }
+
     /// Lists all image files in the archive, sorted by filename.
     pub fn list_files(archive_path: &Path) -> Result<Vec<String>> {
+        Self::list_files_cancellable(archive_path, &CancellationFlag::new())
+    }
+
+    /// Lists all image files in the archive, stopping early once `cancel` is raised.
+    pub fn list_files_cancellable(
+        archive_path: &Path,
+        cancel: &CancellationFlag,
+    ) -> Result<Vec<String>> {
+        Ok(Self::entries_cancellable(archive_path, cancel)?.files)
+    }
+
+    /// Lists the image files in the archive along with its suspicious entries.
+    pub fn entries(archive_path: &Path) -> Result<ArchiveEntries> {
+        Self::entries_cancellable(archive_path, &CancellationFlag::new())
+    }
+
+    /// Lists the image files in the archive along with its suspicious
+    /// entries, stopping early once `cancel` is raised.
+    pub fn entries_cancellable(
+        archive_path: &Path,
+        cancel: &CancellationFlag,
+    ) -> Result<ArchiveEntries> {
+        if archive_path.is_dir() {
+            return Self::list_folder_files(archive_path, cancel);
+        }
+
         let ext = archive_path
             .extension()
             .and_then(|e| e.to_str())
@@ -42,16 +157,30 @@ impl ArchiveExtractor {
             .unwrap_or_default();
 
         match ext.as_str() {
-            "zip" | "cbz" => Self::list_zip_files(archive_path),
-            "cbr" | "rar" => Self::list_rar_files(archive_path),
+            "zip" | "cbz" => Self::list_zip_files(archive_path, cancel),
+            "cbr" | "rar" => Self::list_rar_files(archive_path, cancel),
             _ => Err(AppError::Archive(
                 t!("archive.unsupported_comic_format", extension = ext).to_string(),
-            )),
+            )
+            .with_code(ErrorCode::UnsupportedFormat)),
         }
     }
 
     /// Extracts a specific file from the archive.
     pub fn extract_file(archive_path: &Path, file_name: &str) -> Result<Vec<u8>> {
+        Self::extract_file_cancellable(archive_path, file_name, &CancellationFlag::new())
+    }
+
+    /// Extracts a specific file from the archive, stopping early once `cancel` is raised.
+    pub fn extract_file_cancellable(
+        archive_path: &Path,
+        file_name: &str,
+        cancel: &CancellationFlag,
+    ) -> Result<Vec<u8>> {
+        if archive_path.is_dir() {
+            return Self::extract_folder_file(archive_path, file_name);
+        }
+
         let ext = archive_path
             .extension()
             .and_then(|e| e.to_str())
@@ -59,11 +188,12 @@ impl ArchiveExtractor {
             .unwrap_or_default();
 
         match ext.as_str() {
-            "zip" | "cbz" => Self::extract_zip_file(archive_path, file_name),
-            "cbr" | "rar" => Self::extract_rar_file(archive_path, file_name),
+            "zip" | "cbz" => Self::extract_zip_file(archive_path, file_name, cancel),
+            "cbr" | "rar" => Self::extract_rar_file(archive_path, file_name, cancel),
             _ => Err(AppError::Archive(
                 t!("archive.unsupported_comic_format", extension = ext).to_string(),
-            )),
+            )
+            .with_code(ErrorCode::UnsupportedFormat)),
         }
     }
 
@@ -83,74 +213,132 @@ impl ArchiveExtractor {
     }
 
     // ZIP/CBZ implementation
-    fn list_zip_files(archive_path: &Path) -> Result<Vec<String>> {
+    fn list_zip_files(archive_path: &Path, cancel: &CancellationFlag) -> Result<ArchiveEntries> {
+        let mut archive = Self::open_zip(archive_path)?;
+        Self::zip_image_files(&mut archive, cancel)
+    }
+
+    fn extract_zip_file(
+        archive_path: &Path,
+        file_name: &str,
+        cancel: &CancellationFlag,
+    ) -> Result<Vec<u8>> {
+        let mut archive = Self::open_zip(archive_path)?;
+        Self::read_zip_entry(&mut archive, file_name, cancel)
+    }
+
+    fn open_zip(archive_path: &Path) -> Result<zip::ZipArchive<File>> {
         let file = File::open(archive_path)?;
-        let mut archive = zip::ZipArchive::new(file)
-            .map_err(|e| AppError::Archive(t!("archive.zip_open_failed", error = e).to_string()))?;
+        zip::ZipArchive::new(file)
+            .map_err(|e| AppError::Archive(t!("archive.zip_open_failed", error = e).to_string()))
+    }
 
-        let mut files: Vec<String> = Vec::new();
+    fn zip_image_files(
+        archive: &mut zip::ZipArchive<File>,
+        cancel: &CancellationFlag,
+    ) -> Result<ArchiveEntries> {
+        let mut entries = ArchiveEntries::default();
         for i in 0..archive.len() {
+            cancel.check()?;
             let entry = archive.by_index(i).map_err(|e| {
                 AppError::Archive(t!("archive.zip_read_entry_failed", error = e).to_string())
             })?;
             let name = entry.name().to_string();
-            if Self::is_image_file(&name) {
-                files.push(name);
+            if !is_safe_entry_name(&name) || entry.is_symlink() {
+                entries.suspicious.push(name);
+            } else if Self::is_image_file(&name) {
+                entries.files.push(name);
             }
         }
 
-        // Sort files using natural sort order
-        files.sort_by_key(|a| natural_sort_key(a));
-        Ok(files)
+        Ok(entries.sorted())
     }
 
-    fn extract_zip_file(archive_path: &Path, file_name: &str) -> Result<Vec<u8>> {
-        let file = File::open(archive_path)?;
-        let mut archive = zip::ZipArchive::new(file)
-            .map_err(|e| AppError::Archive(t!("archive.zip_open_failed", error = e).to_string()))?;
-
-        let mut entry = archive.by_name(file_name).map_err(|_| {
-            AppError::Archive(t!("archive.file_not_found", file = file_name).to_string())
-        })?;
+    fn read_zip_entry(
+        archive: &mut zip::ZipArchive<File>,
+        file_name: &str,
+        cancel: &CancellationFlag,
+    ) -> Result<Vec<u8>> {
+        let not_found =
+            || AppError::Archive(t!("archive.file_not_found", file = file_name).to_string());
+        if !is_safe_entry_name(file_name) {
+            return Err(not_found());
+        }
+        let mut entry = archive.by_name(file_name).map_err(|_| not_found())?;
+        if entry.is_symlink() {
+            return Err(not_found());
+        }
 
-        let mut buffer = Vec::new();
-        entry.read_to_end(&mut buffer).map_err(|e| {
-            AppError::Archive(t!("archive.file_read_failed", error = e).to_string())
-        })?;
+        // Read in chunks so large entries can be abandoned midway
+        let mut buffer = Vec::with_capacity(entry.size().min(64 * 1024 * 1024) as usize);
+        let mut chunk = [0u8; READ_CHUNK_SIZE];
+        loop {
+            cancel.check()?;
+            let n = entry.read(&mut chunk).map_err(|e| {
+                AppError::Archive(t!("archive.file_read_failed", error = e).to_string())
+            })?;
+            if n == 0 {
+                break;
+            }
+            buffer.extend_from_slice(&chunk[..n]);
+        }
 
         Ok(buffer)
     }
 
     // RAR/CBR implementation
 
-    fn list_rar_files(archive_path: &Path) -> Result<Vec<String>> {
+    fn list_rar_files(archive_path: &Path, cancel: &CancellationFlag) -> Result<ArchiveEntries> {
         let archive = unrar::Archive::new(archive_path)
             .open_for_listing()
             .map_err(|e| AppError::Archive(t!("archive.rar_open_failed", error = e).to_string()))?;
 
-        let mut files: Vec<String> = Vec::new();
+        let mut listing = ArchiveEntries::default();
         let entries = archive
             .into_iter()
+            .take_while(|_| !cancel.is_cancelled())
             .collect::<std::result::Result<Vec<_>, _>>()
             .map_err(|e| {
                 AppError::Archive(t!("archive.rar_read_entries_failed", error = e).to_string())
             })?;
 
+        cancel.check()?;
+
         for entry in entries {
             let name = entry.filename.to_string_lossy().to_string();
-            if Self::is_image_file(&name) {
-                files.push(name);
+            if !is_safe_entry_name(&name) || Self::is_rar_symlink(&entry) {
+                listing.suspicious.push(name);
+            } else if Self::is_image_file(&name) {
+                listing.files.push(name);
             }
         }
 
-        // Sort files using natural sort order
-        files.sort_by_key(|a| natural_sort_key(a));
-        Ok(files)
+        Ok(listing.sorted())
+    }
+
+    /// Whether a RAR entry is a symbolic link, by the Unix file type bits
+    /// archives made on Unix store in its attributes.
+    fn is_rar_symlink(entry: &unrar::FileHeader) -> bool {
+        const S_IFMT: u32 = 0o170000;
+        const S_IFLNK: u32 = 0o120000;
+        entry.file_attr & S_IFMT == S_IFLNK
     }
 
-    fn extract_rar_file(archive_path: &Path, file_name: &str) -> Result<Vec<u8>> {
+    fn extract_rar_file(
+        archive_path: &Path,
+        file_name: &str,
+        cancel: &CancellationFlag,
+    ) -> Result<Vec<u8>> {
+        // Entries left out of the listing are never unpacked
+        if !is_safe_entry_name(file_name) {
+            return Err(AppError::Archive(
+                t!("archive.file_not_found", file = file_name).to_string(),
+            ));
+        }
+
         // Create a temporary directory for extraction
-        let temp_dir = std::env::temp_dir().join(format!("comic_extract_{}", uuid::Uuid::new_v4()));
+        let temp_dir =
+            Self::scratch_dir().join(format!("{}{}", EXTRACT_DIR_PREFIX, uuid::Uuid::new_v4()));
         std::fs::create_dir_all(&temp_dir)?;
 
         let archive = unrar::Archive::new(archive_path)
@@ -160,6 +348,11 @@ impl ArchiveExtractor {
         // Process entries to find and extract the target file
         let mut current = archive;
         loop {
+            if cancel.is_cancelled() {
+                let _ = std::fs::remove_dir_all(&temp_dir);
+                return Err(AppError::Cancelled);
+            }
+
             match current.read_header() {
                 Ok(Some(header)) => {
                     let name = header.entry().filename.to_string_lossy().to_string();
@@ -171,16 +364,26 @@ impl ArchiveExtractor {
                             )
                         })?;
 
-                        // Read the extracted file
+                        // Read the extracted file, unless it was unpacked as a link
                         let extracted_path = temp_dir.join(&name);
-                        let content = std::fs::read(&extracted_path).map_err(|e| {
-                            AppError::Archive(t!("archive.file_read_failed", error = e).to_string())
-                        })?;
+                        let is_file = std::fs::symlink_metadata(&extracted_path)
+                            .is_ok_and(|metadata| metadata.is_file());
+                        let content = if is_file {
+                            std::fs::read(&extracted_path).map_err(|e| {
+                                AppError::Archive(
+                                    t!("archive.file_read_failed", error = e).to_string(),
+                                )
+                            })
+                        } else {
+                            Err(AppError::Archive(
+                                t!("archive.file_not_found", file = file_name).to_string(),
+                            ))
+                        };
 
                         // Clean up temp directory
                         let _ = std::fs::remove_dir_all(&temp_dir);
 
-                        return Ok(content);
+                        return content;
                     } else {
                         // Skip this entry
                         current = header.skip().map_err(|e| {
@@ -206,6 +409,57 @@ impl ArchiveExtractor {
         ))
     }
 
+    // Image folder implementation
+
+    fn list_folder_files(folder_path: &Path, cancel: &CancellationFlag) -> Result<ArchiveEntries> {
+        let mut listing = ArchiveEntries::default();
+        for entry in std::fs::read_dir(folder_path)? {
+            cancel.check()?;
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let file_type = entry.file_type()?;
+            if file_type.is_symlink() {
+                listing.suspicious.push(name);
+            } else if file_type.is_file() && Self::is_image_file(&name) {
+                listing.files.push(name);
+            }
+        }
+
+        Ok(listing.sorted())
+    }
+
+    fn extract_folder_file(folder_path: &Path, file_name: &str) -> Result<Vec<u8>> {
+        // Only plain image names directly inside the folder can be read
+        let mut components = Path::new(file_name).components();
+        let is_plain_name = matches!(
+            (components.next(), components.next()),
+            (Some(std::path::Component::Normal(_)), None)
+        );
+        let path = folder_path.join(file_name);
+        let is_file = std::fs::symlink_metadata(&path).is_ok_and(|metadata| metadata.is_file());
+        if !is_plain_name || !Self::is_image_file(file_name) || !is_file {
+            return Err(AppError::Archive(
+                t!("archive.file_not_found", file = file_name).to_string(),
+            ));
+        }
+
+        std::fs::read(&path)
+            .map_err(|e| AppError::Archive(t!("archive.file_read_failed", error = e).to_string()))
+    }
+
+    /// Total size in bytes of the images in an image folder.
+    pub fn folder_size(folder_path: &Path) -> Result<u64> {
+        let mut size = 0;
+        for entry in std::fs::read_dir(folder_path)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_file() && Self::is_image_file(&entry.file_name().to_string_lossy()) {
+                size += metadata.len();
+            }
+        }
+        Ok(size)
+    }
+
     /// Checks if a filename is an image file based on extension.
     fn is_image_file(name: &str) -> bool {
         let lower = name.to_lowercase();
@@ -213,6 +467,56 @@ impl ArchiveExtractor {
     }
 }
 
+/// An archive opened for repeated reads.
+///
+/// The sorted page list is read once on open and, for ZIP/CBZ files, the
+/// parsed central directory is kept so each page read only seeks to its
+/// entry. RAR entries are still unpacked one at a time.
+pub struct ArchiveReader {
+    path: PathBuf,
+    files: Vec<String>,
+    zip: Option<zip::ZipArchive<File>>,
+}
+
+impl ArchiveReader {
+    /// Opens an archive or image folder and lists its images.
+    pub fn open(archive_path: &Path, cancel: &CancellationFlag) -> Result<Self> {
+        let is_zip = !archive_path.is_dir()
+            && archive_path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| e.eq_ignore_ascii_case("zip") || e.eq_ignore_ascii_case("cbz"));
+
+        let (files, zip) = if is_zip {
+            let mut archive = ArchiveExtractor::open_zip(archive_path)?;
+            let files = ArchiveExtractor::zip_image_files(&mut archive, cancel)?.files;
+            (files, Some(archive))
+        } else {
+            let files = ArchiveExtractor::list_files_cancellable(archive_path, cancel)?;
+            (files, None)
+        };
+
+        Ok(Self {
+            path: archive_path.to_path_buf(),
+            files,
+            zip,
+        })
+    }
+
+    /// Image files in the archive, sorted by filename.
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+
+    /// Reads a file from the archive, stopping early once `cancel` is raised.
+    pub fn read_file(&mut self, file_name: &str, cancel: &CancellationFlag) -> Result<Vec<u8>> {
+        match &mut self.zip {
+            Some(archive) => ArchiveExtractor::read_zip_entry(archive, file_name, cancel),
+            None => ArchiveExtractor::extract_file_cancellable(&self.path, file_name, cancel),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -226,6 +530,74 @@ mod tests {
         assert!(!ArchiveExtractor::is_image_file("test.xml"));
     }
 
+    #[test]
+    fn test_image_folder() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("page10.jpg"), b"10").unwrap();
+        std::fs::write(dir.path().join("page2.png"), b"2").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), b"notes").unwrap();
+
+        assert!(ArchiveExtractor::is_image_folder(dir.path()));
+        assert_eq!(
+            ArchiveExtractor::list_files(dir.path()).unwrap(),
+            vec!["page2.png", "page10.jpg"]
+        );
+        assert_eq!(
+            ArchiveExtractor::extract_file(dir.path(), "page10.jpg").unwrap(),
+            b"10"
+        );
+        assert_eq!(ArchiveExtractor::folder_size(dir.path()).unwrap(), 3);
+        assert!(ArchiveExtractor::extract_file(dir.path(), "notes.txt").is_err());
+        assert!(ArchiveExtractor::extract_file(dir.path(), "../page10.jpg").is_err());
+
+        let empty = tempfile::TempDir::new().unwrap();
+        assert!(!ArchiveExtractor::is_image_folder(empty.path()));
+    }
+
+    #[test]
+    fn test_unsafe_zip_entries_are_never_read() {
+        use std::io::Write;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("crafted.cbz");
+        let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        for name in [
+            "001.jpg",
+            "../002.jpg",
+            "/tmp/003.jpg",
+            "ch1\\..\\..\\004.jpg",
+        ] {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(name.as_bytes()).unwrap();
+        }
+        zip.add_symlink("005.jpg", "/etc/passwd", options).unwrap();
+        zip.finish().unwrap();
+
+        let entries = ArchiveExtractor::entries(&path).unwrap();
+        assert_eq!(entries.files, vec!["001.jpg"]);
+        assert_eq!(entries.suspicious.len(), 4);
+        for name in &entries.suspicious {
+            assert!(ArchiveExtractor::extract_file(&path, name).is_err());
+        }
+        assert_eq!(ArchiveExtractor::page_count(&path).unwrap(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_image_folder_symlinks_are_never_read() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let secret = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(dir.path().join("001.jpg"), b"1").unwrap();
+        std::os::unix::fs::symlink(secret.path(), dir.path().join("002.jpg")).unwrap();
+
+        let entries = ArchiveExtractor::entries(dir.path()).unwrap();
+        assert_eq!(entries.files, vec!["001.jpg"]);
+        assert_eq!(entries.suspicious, vec!["002.jpg"]);
+        assert!(ArchiveExtractor::extract_file(dir.path(), "002.jpg").is_err());
+    }
+
     #[test]
     fn test_supported_extensions() {
         let exts = ArchiveExtractor::supported_extensions();
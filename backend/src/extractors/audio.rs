@@ -0,0 +1,759 @@
+//! Audiobook extractor for M4B/M4A and MP3 files.
+//!
+//! Only the tags are read, never the audio itself. For MP4 files that is the
+//! `moov` box: the duration from `mvhd`, Nero chapter markers from `chpl`,
+//! and the title and cover from the iTunes `ilst` list. For MP3 files it is
+//! the ID3v2 tag (`TIT2` title, `APIC` cover, `CHAP` chapter markers), and
+//! the duration from the Xing/VBRI header of the first frame or, for
+//! constant bitrate files, from the file size.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use encoding_rs::{UTF_16BE, UTF_16LE, WINDOWS_1252};
+use serde::Serialize;
+
+use crate::error::{AppError, Result};
+use crate::t;
+
+/// Largest `moov` box or ID3 tag read into memory.
+const MAX_TAG_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Bytes searched for the first MPEG frame after the ID3 tag.
+const FRAME_SEARCH_LEN: usize = 64 * 1024;
+
+/// Chapter start times in `chpl` boxes are in 100 ns units.
+const CHPL_UNITS_PER_MS: u64 = 10_000;
+
+/// ID3 picture type of the front cover.
+const APIC_FRONT_COVER: u8 = 3;
+
+/// Layer III bitrates in kbit/s by bitrate index, for MPEG-1 and MPEG-2/2.5.
+const BITRATES_V1: [u64; 15] = [
+    0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+];
+const BITRATES_V2: [u64; 15] = [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+
+/// Duration, chapter markers and title of an audiobook file.
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioInfo {
+    /// Title from the file's tags.
+    pub title: Option<String>,
+    /// Length in milliseconds.
+    pub duration_ms: u64,
+    /// Media type the file is streamed with.
+    pub media_type: &'static str,
+    /// Chapter markers in playback order. Files without markers have a
+    /// single untitled marker covering the whole file.
+    pub markers: Vec<AudioMarker>,
+}
+
+/// A chapter marker.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AudioMarker {
+    /// Position of the marker (0-based).
+    pub index: usize,
+    pub title: Option<String>,
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+/// Audiobook extractor supporting .m4b, .m4a and .mp3 files.
+pub struct AudioExtractor;
+
+impl AudioExtractor {
+    /// Returns the supported extensions for audiobooks.
+    pub fn supported_extensions() -> &'static [&'static str] {
+        &["m4b", "m4a", "mp3"]
+    }
+
+    /// Checks if a file extension is supported.
+    pub fn is_supported(path: &Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| Self::supported_extensions().contains(&ext.to_lowercase().as_str()))
+            .unwrap_or(false)
+    }
+
+    /// Media type of an audio file, by its extension.
+    pub fn media_type(path: &Path) -> &'static str {
+        if is_mp3(path) {
+            "audio/mpeg"
+        } else {
+            "audio/mp4"
+        }
+    }
+
+    /// Reads the duration, chapter markers and title of a file.
+    pub fn probe(path: &Path) -> Result<AudioInfo> {
+        let tags = AudioTags::read(path)?;
+        Ok(AudioInfo {
+            markers: tags.markers(),
+            title: tags.title,
+            duration_ms: tags.duration_ms,
+            media_type: Self::media_type(path),
+        })
+    }
+
+    /// Gets the number of chapter markers (at least one).
+    pub fn chapter_count(path: &Path) -> Result<usize> {
+        Ok(AudioTags::read(path)?.markers().len())
+    }
+
+    /// Extracts the embedded cover image, if the file has one.
+    pub fn extract_cover(path: &Path) -> Result<Option<Vec<u8>>> {
+        Ok(AudioTags::read(path)?.cover)
+    }
+}
+
+fn is_mp3(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("mp3"))
+}
+
+/// Tags read from an audio file.
+#[derive(Debug, Default)]
+struct AudioTags {
+    title: Option<String>,
+    duration_ms: u64,
+    /// Chapter start times and titles, in any order.
+    chapters: Vec<(u64, Option<String>)>,
+    cover: Option<Vec<u8>>,
+}
+
+impl AudioTags {
+    fn read(path: &Path) -> Result<Self> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        if is_mp3(path) {
+            read_mp3(&mut file, len)
+        } else {
+            read_mp4(&mut file, len)
+        }
+    }
+
+    /// Chapter markers, ending where the next one starts.
+    fn markers(&self) -> Vec<AudioMarker> {
+        let mut chapters: Vec<&(u64, Option<String>)> = self
+            .chapters
+            .iter()
+            .filter(|(start, _)| *start < self.duration_ms.max(1))
+            .collect();
+        chapters.sort_by_key(|(start, _)| *start);
+        chapters.dedup_by_key(|(start, _)| *start);
+
+        if chapters.is_empty() {
+            return vec![AudioMarker {
+                index: 0,
+                title: None,
+                start_ms: 0,
+                end_ms: self.duration_ms,
+            }];
+        }
+
+        chapters
+            .iter()
+            .enumerate()
+            .map(|(index, (start, title))| AudioMarker {
+                index,
+                title: title.clone(),
+                // Audio before the first marker belongs to it
+                start_ms: if index == 0 { 0 } else { *start },
+                end_ms: chapters
+                    .get(index + 1)
+                    .map_or(self.duration_ms, |(next, _)| *next),
+            })
+            .collect()
+    }
+}
+
+// ============================================================================
+// MP4
+// ============================================================================
+
+/// Read the tags of an MP4 file from its `moov` box.
+fn read_mp4<R: Read + Seek>(reader: &mut R, len: u64) -> Result<AudioTags> {
+    let mut pos = 0;
+    while pos + 8 <= len {
+        reader.seek(SeekFrom::Start(pos))?;
+        let mut header = [0u8; 8];
+        reader.read_exact(&mut header)?;
+        let mut size = u64::from(u32::from_be_bytes([
+            header[0], header[1], header[2], header[3],
+        ]));
+        let mut header_len = 8;
+        if size == 1 {
+            let mut large = [0u8; 8];
+            reader.read_exact(&mut large)?;
+            size = u64::from_be_bytes(large);
+            header_len = 16;
+        } else if size == 0 {
+            size = len - pos;
+        }
+        if size < header_len {
+            break;
+        }
+
+        if &header[4..8] == b"moov" {
+            let body_len = size - header_len;
+            if body_len > MAX_TAG_SIZE {
+                return Err(invalid("moov box too large"));
+            }
+            let mut body = vec![0u8; body_len as usize];
+            reader.read_exact(&mut body)?;
+            return Ok(parse_moov(&body));
+        }
+        pos = pos.saturating_add(size);
+    }
+    Err(invalid("no moov box"))
+}
+
+fn parse_moov(moov: &[u8]) -> AudioTags {
+    let mut tags = AudioTags::default();
+
+    for (kind, body) in Mp4Boxes::new(moov) {
+        match &kind {
+            b"mvhd" => tags.duration_ms = parse_mvhd(body).unwrap_or(0),
+            b"udta" => {
+                for (kind, body) in Mp4Boxes::new(body) {
+                    match &kind {
+                        b"chpl" => tags.chapters = parse_chpl(body).unwrap_or_default(),
+                        b"meta" => parse_meta(body, &mut tags),
+                        _ => {}
+                    }
+                }
+            }
+            b"meta" => parse_meta(body, &mut tags),
+            _ => {}
+        }
+    }
+    tags
+}
+
+/// Duration in milliseconds from a movie header.
+fn parse_mvhd(body: &[u8]) -> Option<u64> {
+    let (timescale, duration) = if *body.first()? == 1 {
+        (be_u32(body, 20)?, be_u64(body, 24)?)
+    } else {
+        (be_u32(body, 12)?, u64::from(be_u32(body, 16)?))
+    };
+    (timescale > 0).then(|| duration.saturating_mul(1000) / u64::from(timescale))
+}
+
+/// Nero chapter list: start times in 100 ns units and titles.
+fn parse_chpl(body: &[u8]) -> Option<Vec<(u64, Option<String>)>> {
+    let mut pos = if *body.first()? == 0 { 4 } else { 8 };
+    let count = *body.get(pos)?;
+    pos += 1;
+
+    let mut chapters = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let start = be_u64(body, pos)? / CHPL_UNITS_PER_MS;
+        let title_len = *body.get(pos + 8)? as usize;
+        let title = body.get(pos + 9..pos + 9 + title_len)?;
+        chapters.push((
+            start,
+            non_empty(String::from_utf8_lossy(title).into_owned()),
+        ));
+        pos += 9 + title_len;
+    }
+    Some(chapters)
+}
+
+/// iTunes metadata: the `©nam` title and `covr` cover.
+fn parse_meta(body: &[u8], tags: &mut AudioTags) {
+    // A full box in MP4 files, a plain box in QuickTime files
+    let body = if body.get(4..8) == Some(b"hdlr".as_slice()) {
+        body
+    } else {
+        body.get(4..).unwrap_or_default()
+    };
+
+    for (kind, ilst) in Mp4Boxes::new(body) {
+        if &kind != b"ilst" {
+            continue;
+        }
+        for (kind, item) in Mp4Boxes::new(ilst) {
+            // Each item holds a `data` box: type, locale, then the value
+            let Some(value) = Mp4Boxes::new(item)
+                .find(|(kind, _)| kind == b"data")
+                .and_then(|(_, data)| data.get(8..))
+            else {
+                continue;
+            };
+            match &kind {
+                b"\xa9nam" => tags.title = non_empty(String::from_utf8_lossy(value).into_owned()),
+                b"covr" if tags.cover.is_none() && !value.is_empty() => {
+                    tags.cover = Some(value.to_vec());
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Iterator over the boxes in the body of an MP4 box.
+struct Mp4Boxes<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Mp4Boxes<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+}
+
+impl<'a> Iterator for Mp4Boxes<'a> {
+    type Item = ([u8; 4], &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.pos;
+        let size = be_u32(self.data, start)? as usize;
+        let kind: [u8; 4] = self.data.get(start + 4..start + 8)?.try_into().ok()?;
+
+        let (header_len, size) = match size {
+            0 => (8, self.data.len() - start),
+            1 => (16, usize::try_from(be_u64(self.data, start + 8)?).ok()?),
+            size => (8, size),
+        };
+        if size < header_len {
+            return None;
+        }
+
+        let body = self
+            .data
+            .get(start + header_len..start.checked_add(size)?)?;
+        self.pos = start + size;
+        Some((kind, body))
+    }
+}
+
+// ============================================================================
+// MP3
+// ============================================================================
+
+/// Read the ID3v2 tag and duration of an MP3 file.
+fn read_mp3<R: Read + Seek>(reader: &mut R, len: u64) -> Result<AudioTags> {
+    let mut tags = AudioTags::default();
+    let mut audio_start = 0;
+
+    let mut header = [0u8; 10];
+    reader.read_exact(&mut header)?;
+    if &header[0..3] == b"ID3" {
+        let major = header[3];
+        let flags = header[5];
+        let size = u64::from(syncsafe(&header[6..10]));
+        if size > MAX_TAG_SIZE {
+            return Err(invalid("ID3 tag too large"));
+        }
+
+        let mut tag = vec![0u8; size as usize];
+        reader.read_exact(&mut tag)?;
+        parse_id3(&tag, major, flags, &mut tags);
+
+        // The footer flag adds a copy of the header at the end
+        audio_start = 10 + size + if flags & 0x10 != 0 { 10 } else { 0 };
+    }
+
+    reader.seek(SeekFrom::Start(audio_start))?;
+    let mut frames = Vec::with_capacity(FRAME_SEARCH_LEN);
+    reader
+        .take(FRAME_SEARCH_LEN as u64)
+        .read_to_end(&mut frames)?;
+    tags.duration_ms = mp3_duration(&frames, len.saturating_sub(audio_start))
+        .ok_or_else(|| invalid("no MPEG audio frame"))?;
+
+    Ok(tags)
+}
+
+/// Read the frames of an ID3v2.3 or 2.4 tag. Other versions are skipped.
+fn parse_id3(tag: &[u8], major: u8, flags: u8, tags: &mut AudioTags) {
+    if !matches!(major, 3 | 4) {
+        return;
+    }
+
+    let mut pos = 0;
+    if flags & 0x40 != 0 {
+        // Extended header; its size excludes itself in 2.3
+        pos = match major {
+            3 => be_u32(tag, 0).map_or(tag.len(), |size| size as usize + 4),
+            _ => tag.get(0..4).map_or(tag.len(), |b| syncsafe(b) as usize),
+        };
+    }
+
+    let mut cover: Option<(u8, Vec<u8>)> = None;
+    for (id, body) in Id3Frames::new(tag.get(pos..).unwrap_or_default(), major) {
+        match &id {
+            b"TIT2" => tags.title = decode_id3_text(body),
+            b"APIC" => {
+                if let Some((kind, image)) = parse_apic(body)
+                    && cover
+                        .as_ref()
+                        .is_none_or(|(k, _)| *k != APIC_FRONT_COVER && kind == APIC_FRONT_COVER)
+                {
+                    cover = Some((kind, image.to_vec()));
+                }
+            }
+            b"CHAP" => tags.chapters.extend(parse_chap(body, major)),
+            _ => {}
+        }
+    }
+    tags.cover = cover.map(|(_, image)| image);
+}
+
+/// A `CHAP` frame: element ID, start and end times, byte offsets, then
+/// sub-frames holding its title.
+fn parse_chap(body: &[u8], major: u8) -> Option<(u64, Option<String>)> {
+    let id_end = body.iter().position(|&b| b == 0)?;
+    let start = be_u32(body, id_end + 1)?;
+    let title = Id3Frames::new(body.get(id_end + 17..)?, major)
+        .find(|(id, _)| id == b"TIT2")
+        .and_then(|(_, body)| decode_id3_text(body));
+    Some((u64::from(start), title))
+}
+
+/// An `APIC` frame: text encoding, MIME type, picture type, description,
+/// then the image.
+fn parse_apic(body: &[u8]) -> Option<(u8, &[u8])> {
+    let encoding = *body.first()?;
+    let mime_end = 1 + body.get(1..)?.iter().position(|&b| b == 0)?;
+    let kind = *body.get(mime_end + 1)?;
+    let description = body.get(mime_end + 2..)?;
+
+    let image_start = if matches!(encoding, 1 | 2) {
+        // UTF-16 descriptions end with two zero bytes on a code unit boundary
+        description
+            .chunks(2)
+            .position(|unit| unit == [0, 0])
+            .map(|i| i * 2 + 2)?
+    } else {
+        description.iter().position(|&b| b == 0)? + 1
+    };
+    Some((kind, description.get(image_start..)?))
+}
+
+/// Decode a text frame: an encoding byte followed by the text.
+fn decode_id3_text(body: &[u8]) -> Option<String> {
+    let (&encoding, text) = body.split_first()?;
+    let text = match encoding {
+        0 => WINDOWS_1252.decode(text).0.into_owned(),
+        // Encoding 1 starts with a byte order mark, which decode() honours
+        1 => UTF_16LE.decode(text).0.into_owned(),
+        2 => UTF_16BE.decode(text).0.into_owned(),
+        _ => String::from_utf8_lossy(text).into_owned(),
+    };
+    // 2.4 allows several null-separated values; keep the first
+    non_empty(text.split('\0').next().unwrap_or_default().to_string())
+}
+
+/// Iterator over the frames of an ID3v2 tag.
+struct Id3Frames<'a> {
+    data: &'a [u8],
+    pos: usize,
+    major: u8,
+}
+
+impl<'a> Id3Frames<'a> {
+    fn new(data: &'a [u8], major: u8) -> Self {
+        Self {
+            data,
+            pos: 0,
+            major,
+        }
+    }
+}
+
+impl<'a> Iterator for Id3Frames<'a> {
+    type Item = ([u8; 4], &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let header = self.data.get(self.pos..self.pos + 10)?;
+        // Padding after the last frame
+        if header[0] == 0 {
+            return None;
+        }
+        let id: [u8; 4] = header[0..4].try_into().ok()?;
+        let size = if self.major == 4 {
+            syncsafe(&header[4..8]) as usize
+        } else {
+            be_u32(header, 4)? as usize
+        };
+
+        let start = self.pos + 10;
+        let body = self.data.get(start..start.checked_add(size)?)?;
+        self.pos = start + size;
+        Some((id, body))
+    }
+}
+
+/// Duration of MPEG Layer III audio, from the first frame found in `data`.
+/// `audio_len` is the number of bytes from the start of `data` to the end of
+/// the file.
+fn mp3_duration(data: &[u8], audio_len: u64) -> Option<u64> {
+    let (offset, frame) = (0..data.len().saturating_sub(4))
+        .find_map(|i| MpegFrame::parse(&data[i..]).map(|frame| (i, frame)))?;
+    let header = &data[offset..];
+
+    // Xing/Info header of VBR files, after the side information
+    let side_info = match (frame.mpeg1, frame.mono) {
+        (true, false) => 32,
+        (true, true) | (false, false) => 17,
+        (false, true) => 9,
+    };
+    let xing = 4 + side_info;
+    let frames = if matches!(header.get(xing..xing + 4), Some(b"Xing" | b"Info"))
+        && be_u32(header, xing + 4).is_some_and(|flags| flags & 1 != 0)
+    {
+        be_u32(header, xing + 8)
+    } else if header.get(36..40) == Some(b"VBRI".as_slice()) {
+        be_u32(header, 36 + 14)
+    } else {
+        None
+    };
+
+    if let Some(frames) = frames.filter(|&frames| frames > 0) {
+        let samples = u64::from(frames) * frame.samples_per_frame();
+        return Some(samples * 1000 / frame.sample_rate);
+    }
+
+    // Constant bitrate: kbit/s is bits per millisecond
+    let bytes = audio_len.saturating_sub(offset as u64);
+    Some(bytes * 8 / frame.bitrate_kbps)
+}
+
+/// Fields of an MPEG audio frame header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct MpegFrame {
+    mpeg1: bool,
+    mono: bool,
+    bitrate_kbps: u64,
+    sample_rate: u64,
+}
+
+impl MpegFrame {
+    /// Parse a Layer III frame header at the start of `data`.
+    fn parse(data: &[u8]) -> Option<Self> {
+        let [sync, b1, b2, b3, ..] = *data else {
+            return None;
+        };
+        if sync != 0xFF || b1 & 0xE0 != 0xE0 {
+            return None;
+        }
+
+        let version = (b1 >> 3) & 0x3;
+        let layer = (b1 >> 1) & 0x3;
+        if version == 1 || layer != 1 {
+            return None;
+        }
+        let mpeg1 = version == 3;
+
+        let bitrate_index = (b2 >> 4) as usize;
+        let bitrates = if mpeg1 { &BITRATES_V1 } else { &BITRATES_V2 };
+        let bitrate_kbps = *bitrates.get(bitrate_index)?;
+        let base_rate = [44100, 48000, 32000].get(((b2 >> 2) & 0x3) as usize)?;
+        let sample_rate = match version {
+            3 => *base_rate,
+            2 => base_rate / 2,
+            _ => base_rate / 4,
+        };
+        if bitrate_kbps == 0 {
+            return None;
+        }
+
+        Some(Self {
+            mpeg1,
+            mono: b3 >> 6 == 3,
+            bitrate_kbps,
+            sample_rate,
+        })
+    }
+
+    fn samples_per_frame(&self) -> u64 {
+        if self.mpeg1 { 1152 } else { 576 }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn be_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_be_bytes(bytes.try_into().ok()?))
+}
+
+fn be_u64(data: &[u8], offset: usize) -> Option<u64> {
+    let bytes = data.get(offset..offset.checked_add(8)?)?;
+    Some(u64::from_be_bytes(bytes.try_into().ok()?))
+}
+
+/// A 28-bit integer stored in the low 7 bits of four bytes.
+fn syncsafe(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .take(4)
+        .fold(0, |value, &b| (value << 7) | u32::from(b & 0x7F))
+}
+
+fn non_empty(text: String) -> Option<String> {
+    let text = text.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+fn invalid(reason: &str) -> AppError {
+    AppError::Archive(t!("archive.audio_invalid", error = reason).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn mp4_box(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        [(body.len() as u32 + 8).to_be_bytes().as_slice(), kind, body].concat()
+    }
+
+    fn id3_frame(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        [
+            id.as_slice(),
+            &(body.len() as u32).to_be_bytes(),
+            &[0, 0],
+            body,
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_read_mp4() {
+        // Version 0 mvhd: 1000 units per second, 90 seconds
+        let mut mvhd = vec![0u8; 20];
+        mvhd[12..16].copy_from_slice(&1000u32.to_be_bytes());
+        mvhd[16..20].copy_from_slice(&90_000u32.to_be_bytes());
+
+        let mut chpl = vec![0u8, 0, 0, 0, 2];
+        for (start_ms, title) in [(0u64, "Intro"), (30_000, "Part 1")] {
+            chpl.extend((start_ms * CHPL_UNITS_PER_MS).to_be_bytes());
+            chpl.push(title.len() as u8);
+            chpl.extend(title.as_bytes());
+        }
+
+        let data_box = |value: &[u8]| mp4_box(b"data", &[[0u8; 8].as_slice(), value].concat());
+        let ilst = [
+            mp4_box(b"\xa9nam", &data_box(b"The Book")),
+            mp4_box(b"covr", &data_box(&[0xFF, 0xD8, 0xFF])),
+        ]
+        .concat();
+        let meta = mp4_box(
+            b"meta",
+            &[[0u8; 4].as_slice(), &mp4_box(b"ilst", &ilst)].concat(),
+        );
+        let udta = mp4_box(b"udta", &[mp4_box(b"chpl", &chpl), meta].concat());
+        let moov = mp4_box(b"moov", &[mp4_box(b"mvhd", &mvhd), udta].concat());
+
+        let file = [mp4_box(b"ftyp", b"M4B "), mp4_box(b"mdat", &[0; 32]), moov].concat();
+        let tags = read_mp4(&mut Cursor::new(&file), file.len() as u64).unwrap();
+
+        assert_eq!(tags.duration_ms, 90_000);
+        assert_eq!(tags.title.as_deref(), Some("The Book"));
+        assert_eq!(tags.cover, Some(vec![0xFF, 0xD8, 0xFF]));
+        assert_eq!(
+            tags.markers(),
+            vec![
+                AudioMarker {
+                    index: 0,
+                    title: Some("Intro".to_string()),
+                    start_ms: 0,
+                    end_ms: 30_000,
+                },
+                AudioMarker {
+                    index: 1,
+                    title: Some("Part 1".to_string()),
+                    start_ms: 30_000,
+                    end_ms: 90_000,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_mp3() {
+        let title = |text: &str| id3_frame(b"TIT2", &[[3u8].as_slice(), text.as_bytes()].concat());
+        let chap = |id: &str, start: u32, name: &str| {
+            let times = [start, start + 1000, u32::MAX, u32::MAX];
+            let times: Vec<u8> = times.iter().flat_map(|t| t.to_be_bytes()).collect();
+            id3_frame(
+                b"CHAP",
+                &[id.as_bytes(), &[0], &times, &title(name)].concat(),
+            )
+        };
+        let apic = |kind: u8, image: &[u8]| {
+            id3_frame(
+                b"APIC",
+                &[b"\0image/png\0".as_slice(), &[kind], b"desc\0", image].concat(),
+            )
+        };
+
+        let frames = [
+            title("Audio Book"),
+            apic(0, b"other"),
+            apic(APIC_FRONT_COVER, b"front"),
+            chap("ch2", 60_000, "Two"),
+            chap("ch1", 0, "One"),
+            vec![0; 16],
+        ]
+        .concat();
+        let size = frames.len() as u32;
+        let size = [21, 14, 7, 0].map(|shift| ((size >> shift) & 0x7F) as u8);
+
+        // MPEG-1 Layer III, 128 kbit/s, 44.1 kHz, stereo, with a Xing
+        // header counting 4000 frames
+        let mut frame = vec![0u8; 417];
+        frame[..4].copy_from_slice(&[0xFF, 0xFB, 0x90, 0x00]);
+        frame[36..40].copy_from_slice(b"Xing");
+        frame[40..44].copy_from_slice(&1u32.to_be_bytes());
+        frame[44..48].copy_from_slice(&4000u32.to_be_bytes());
+
+        let file = [b"ID3\x03\x00\x00".as_slice(), &size, &frames, &frame].concat();
+        let tags = read_mp3(&mut Cursor::new(&file), file.len() as u64).unwrap();
+
+        assert_eq!(tags.title.as_deref(), Some("Audio Book"));
+        assert_eq!(tags.cover.as_deref(), Some(b"front".as_slice()));
+        // 4000 frames of 1152 samples at 44.1 kHz
+        assert_eq!(tags.duration_ms, 104_489);
+        let markers = tags.markers();
+        assert_eq!(markers.len(), 2);
+        assert_eq!(markers[0].title.as_deref(), Some("One"));
+        assert_eq!(markers[1].start_ms, 60_000);
+        assert_eq!(markers[1].end_ms, 104_489);
+    }
+
+    #[test]
+    fn test_cbr_duration_and_single_marker() {
+        // MPEG-1 Layer III, 128 kbit/s: 16000 bytes per second
+        let mut file = vec![0u8; 160_000];
+        file[..4].copy_from_slice(&[0xFF, 0xFB, 0x90, 0x00]);
+        let tags = read_mp3(&mut Cursor::new(&file), file.len() as u64).unwrap();
+
+        assert_eq!(tags.duration_ms, 10_000);
+        assert_eq!(
+            tags.markers(),
+            vec![AudioMarker {
+                index: 0,
+                title: None,
+                start_ms: 0,
+                end_ms: 10_000,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_rejects_other_files() {
+        let data = b"not an audio file at all";
+        assert!(read_mp3(&mut Cursor::new(data), data.len() as u64).is_err());
+        assert!(read_mp4(&mut Cursor::new(data), data.len() as u64).is_err());
+    }
+}
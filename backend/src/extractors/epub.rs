@@ -1,10 +1,100 @@
 //! EPUB extractor for electronic publication format.
 //!
-//! This module provides functionality to extract text content from EPUB files.
+//! This module provides functionality to extract text content from EPUB files,
+//! and to read them section by section with sanitized HTML for the reader.
+
+use crate::error::{AppError, ErrorCode, Result};
+use crate::t;
+use crate::utils::html::{rewrite_css_urls, sanitize_html, stylesheet_links};
+use epub::doc::EpubDoc;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+/// Reading order and table of contents of an EPUB.
+#[derive(Debug, Clone, Serialize)]
+pub struct EpubManifest {
+    /// Title from the package metadata.
+    pub title: Option<String>,
+    /// Spine items in reading order.
+    pub sections: Vec<EpubSectionInfo>,
+    /// Table of contents from the NCX, empty if the book has none.
+    pub toc: Vec<EpubTocEntry>,
+}
+
+/// A spine item.
+#[derive(Debug, Clone, Serialize)]
+pub struct EpubSectionInfo {
+    /// Position in the spine (0-based), used to fetch the section.
+    pub index: usize,
+    /// Manifest ID of the item.
+    pub id: String,
+    /// Path of the item inside the EPUB.
+    pub path: String,
+    /// Media type from the manifest.
+    pub media_type: String,
+    /// False for auxiliary content outside the main reading order.
+    pub linear: bool,
+}
+
+/// A table of contents entry.
+#[derive(Debug, Clone, Serialize)]
+pub struct EpubTocEntry {
+    /// Entry label.
+    pub label: String,
+    /// Spine index of the section the entry points to, if it is in the spine.
+    pub section: Option<usize>,
+    /// Element ID within the section.
+    pub fragment: Option<String>,
+    /// Nested entries.
+    pub children: Vec<EpubTocEntry>,
+}
+
+/// A spine item prepared for display.
+#[derive(Debug, Clone, Serialize)]
+pub struct EpubSection {
+    /// Position in the spine (0-based).
+    pub index: usize,
+    /// Sanitized inner HTML of the section body.
+    pub html: String,
+    /// URLs of the stylesheets the section links to.
+    pub stylesheets: Vec<String>,
+}
+
+/// URLs that references inside an EPUB are rewritten to.
+#[derive(Debug, Clone)]
+pub struct EpubUrls {
+    /// URL of the EPUB endpoints of the chapter, without a trailing slash.
+    pub base: String,
+    /// Access token appended to resource URLs, so they load from `<img>`
+    /// and `<link>` elements that cannot send headers.
+    pub token: Option<String>,
+}
 
-use crate::error::{AppError, Result};
-use rust_i18n::t;
-use std::path::Path;
+impl EpubUrls {
+    /// URL of a section, keeping the fragment of the original link.
+    pub fn section(&self, index: usize, fragment: Option<&str>) -> String {
+        match fragment {
+            Some(fragment) => format!("{}/sections/{}#{}", self.base, index, fragment),
+            None => format!("{}/sections/{}", self.base, index),
+        }
+    }
+
+    /// URL of a resource by its path inside the EPUB.
+    pub fn resource(&self, path: &str) -> String {
+        let path: Vec<String> = path
+            .split('/')
+            .map(|segment| urlencoding::encode(segment).into_owned())
+            .collect();
+        let url = format!("{}/resources/{}", self.base, path.join("/"));
+        match &self.token {
+            Some(token) => format!("{}?token={}", url, urlencoding::encode(token)),
+            None => url,
+        }
+    }
+}
 
 /// EPUB extractor supporting .epub files.
 pub struct EpubExtractor;
@@ -35,7 +125,8 @@ impl EpubExtractor {
             "epub" => Self::list_epub_files(archive_path),
             _ => Err(AppError::Archive(
                 t!("archive.unsupported_novel_format", extension = ext).to_string(),
-            )),
+            )
+            .with_code(ErrorCode::UnsupportedFormat)),
         }
     }
 
@@ -51,7 +142,8 @@ impl EpubExtractor {
             "epub" => Self::extract_epub_file(archive_path, file_name),
             _ => Err(AppError::Archive(
                 t!("archive.unsupported_novel_format", extension = ext).to_string(),
-            )),
+            )
+            .with_code(ErrorCode::UnsupportedFormat)),
         }
     }
 
@@ -77,6 +169,116 @@ impl EpubExtractor {
         Ok(files.len())
     }
 
+    /// Reads the spine and table of contents of an EPUB.
+    pub fn manifest(archive_path: &Path) -> Result<EpubManifest> {
+        let book = EpubBook::open(archive_path)?;
+
+        let sections = book
+            .doc
+            .spine
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                let resource = book.doc.resources.get(&item.idref);
+                EpubSectionInfo {
+                    index,
+                    id: item.idref.clone(),
+                    path: resource.map(|r| entry_key(&r.path)).unwrap_or_default(),
+                    media_type: resource.map(|r| r.mime.clone()).unwrap_or_default(),
+                    linear: item.linear,
+                }
+            })
+            .collect();
+
+        Ok(EpubManifest {
+            title: book.doc.get_title(),
+            sections,
+            toc: book.toc_entries(&book.doc.toc),
+        })
+    }
+
+    /// Reads a spine item as sanitized HTML.
+    ///
+    /// Links to other sections point to their section URLs and references
+    /// to images, stylesheets and fonts in the manifest point to resource
+    /// URLs. Other references into the book are dropped, and external URLs
+    /// are only kept for links.
+    pub fn section(archive_path: &Path, index: usize, urls: &EpubUrls) -> Result<EpubSection> {
+        let mut book = EpubBook::open(archive_path)?;
+
+        let not_found =
+            || AppError::NotFound(t!("epub.section_not_found", index = index).to_string());
+        let resource = book
+            .doc
+            .spine
+            .get(index)
+            .and_then(|item| book.doc.resources.get(&item.idref))
+            .ok_or_else(not_found)?;
+        let (entry, path) = (resource.path.clone(), entry_key(&resource.path));
+        let html = book
+            .doc
+            .get_resource_str_by_path(&entry)
+            .ok_or_else(not_found)?;
+
+        let stylesheets = stylesheet_links(&html)
+            .iter()
+            .filter_map(|href| resolve_href(&path, href))
+            .filter(|(target, _)| book.is_resource(target))
+            .map(|(target, _)| urls.resource(&target))
+            .collect();
+        let html = sanitize_html(&html, |element, url| {
+            book.rewrite_url(&path, element, url, urls)
+        });
+
+        Ok(EpubSection {
+            index,
+            html,
+            stylesheets,
+        })
+    }
+
+    /// Reads an image, stylesheet, font or other non-document resource
+    /// listed in the manifest, returning its data and media type.
+    ///
+    /// Stylesheets have their `url(...)` references rewritten like the
+    /// references in sections.
+    pub fn resource(
+        archive_path: &Path,
+        resource_path: &str,
+        urls: &EpubUrls,
+    ) -> Result<(Vec<u8>, String)> {
+        let mut book = EpubBook::open(archive_path)?;
+
+        let path = normalize_path(resource_path);
+        let not_found =
+            || AppError::NotFound(t!("epub.resource_not_found", path = resource_path).to_string());
+        let (entry, media_type) = book
+            .resources
+            .get(&path)
+            .filter(|_| book.is_resource(&path))
+            .cloned()
+            .ok_or_else(not_found)?;
+        let data = book
+            .doc
+            .get_resource_by_path(&entry)
+            .ok_or_else(not_found)?;
+
+        if media_type != "text/css" {
+            return Ok((data, media_type));
+        }
+
+        let css = String::from_utf8_lossy(&data);
+        let css = rewrite_css_urls(&css, |url| {
+            if url.starts_with("data:") {
+                return Some(url.to_string());
+            }
+            resolve_href(&path, url)
+                .filter(|(target, _)| book.is_resource(target))
+                .map(|(target, _)| urls.resource(&target))
+        });
+        Ok((css.into_bytes(), media_type))
+    }
+
     // EPUB implementation
     // EPUB files are essentially ZIP files with a specific structure
 
@@ -116,7 +318,7 @@ impl EpubExtractor {
     }
 
     /// Strips HTML tags from content, returning plain text.
-    fn strip_html(html: &str) -> String {
+    pub(crate) fn strip_html(html: &str) -> String {
         let mut result = String::new();
         let mut in_tag = false;
         let mut in_script = false;
@@ -165,6 +367,152 @@ impl EpubExtractor {
     }
 }
 
+/// An open EPUB with its manifest indexed by normalized path.
+struct EpubBook {
+    doc: EpubDoc<BufReader<File>>,
+    /// Archive entry and media type of each manifest item.
+    resources: HashMap<String, (PathBuf, String)>,
+    /// Spine index of each manifest item in the spine.
+    sections: HashMap<String, usize>,
+}
+
+impl EpubBook {
+    fn open(archive_path: &Path) -> Result<Self> {
+        let doc = EpubDoc::new(archive_path).map_err(|e| {
+            AppError::Archive(t!("archive.epub_open_failed", error = e).to_string())
+        })?;
+
+        let resources: HashMap<String, (PathBuf, String)> = doc
+            .resources
+            .values()
+            .map(|r| (entry_key(&r.path), (r.path.clone(), r.mime.clone())))
+            .collect();
+
+        let mut sections = HashMap::new();
+        for (index, item) in doc.spine.iter().enumerate() {
+            if let Some(resource) = doc.resources.get(&item.idref) {
+                sections.entry(entry_key(&resource.path)).or_insert(index);
+            }
+        }
+
+        Ok(Self {
+            doc,
+            resources,
+            sections,
+        })
+    }
+
+    /// Whether `path` is a manifest item that may be served as a resource.
+    /// Documents are only served as sanitized sections.
+    fn is_resource(&self, path: &str) -> bool {
+        self.resources
+            .get(path)
+            .is_some_and(|(_, media_type)| !is_document(media_type))
+    }
+
+    /// Rewrite a URL attribute of the section at `base`.
+    fn rewrite_url(&self, base: &str, element: &str, url: &str, urls: &EpubUrls) -> Option<String> {
+        if url.starts_with('#') || url.starts_with("data:") {
+            return Some(url.to_string());
+        }
+
+        let Some((path, fragment)) = resolve_href(base, url) else {
+            // External links stay clickable; nothing external is loaded
+            let lower = url.to_lowercase();
+            let external = ["http://", "https://", "mailto:"]
+                .iter()
+                .any(|scheme| lower.starts_with(scheme));
+            return (element == "a" && external).then(|| url.to_string());
+        };
+
+        if let Some(&index) = self.sections.get(&path) {
+            return Some(urls.section(index, fragment.as_deref()));
+        }
+        self.is_resource(&path).then(|| urls.resource(&path))
+    }
+
+    fn toc_entries(&self, points: &[epub::doc::NavPoint]) -> Vec<EpubTocEntry> {
+        points
+            .iter()
+            .map(|point| {
+                let content = point.content.to_string_lossy();
+                let (path, fragment) = match content.split_once('#') {
+                    Some((path, fragment)) => (path, Some(fragment.to_string())),
+                    None => (content.as_ref(), None),
+                };
+                EpubTocEntry {
+                    label: point.label.trim().to_string(),
+                    section: self.sections.get(&entry_key(Path::new(path))).copied(),
+                    fragment,
+                    children: self.toc_entries(&point.children),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Whether a media type is an (X)HTML document.
+fn is_document(media_type: &str) -> bool {
+    matches!(media_type, "application/xhtml+xml" | "text/html")
+}
+
+/// Normalized, percent-decoded path of a manifest item, as references
+/// resolve to with [`resolve_href`].
+fn entry_key(path: &Path) -> String {
+    let path = normalize_path(&path.to_string_lossy());
+    urlencoding::decode(&path)
+        .map(|decoded| decoded.into_owned())
+        .unwrap_or(path)
+}
+
+/// Normalize a path inside the archive, resolving `.` and `..` segments.
+fn normalize_path(path: &str) -> String {
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split(['/', '\\']) {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+/// Resolve a reference in the file at `base` to a normalized archive path
+/// and fragment. Returns `None` for URLs with a scheme or host.
+fn resolve_href(base: &str, href: &str) -> Option<(String, Option<String>)> {
+    let (href, fragment) = match href.split_once('#') {
+        Some((href, fragment)) => (href, Some(fragment.to_string())),
+        None => (href, None),
+    };
+    let href = href.split('?').next().unwrap_or_default();
+
+    let scheme = href.split_once(':').is_some_and(|(scheme, _)| {
+        scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    });
+    if scheme || href.starts_with("//") {
+        return None;
+    }
+
+    let href = urlencoding::decode(href).ok()?;
+    let path = if href.is_empty() {
+        base.to_string()
+    } else if let Some(absolute) = href.strip_prefix('/') {
+        absolute.to_string()
+    } else {
+        match base.rsplit_once('/') {
+            Some((dir, _)) => format!("{}/{}", dir, href),
+            None => href.into_owned(),
+        }
+    };
+    Some((normalize_path(&path), fragment))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -198,6 +546,54 @@ mod tests {
         assert_eq!(text, "BeforeAfter");
     }
 
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!(
+            normalize_path("OEBPS/Text/../Images/a.png"),
+            "OEBPS/Images/a.png"
+        );
+        assert_eq!(normalize_path("./OEBPS//a.css"), "OEBPS/a.css");
+        assert_eq!(normalize_path("../../a.css"), "a.css");
+    }
+
+    #[test]
+    fn test_resolve_href() {
+        let base = "OEBPS/Text/ch1.xhtml";
+        assert_eq!(
+            resolve_href(base, "../Images/cover%20art.jpg"),
+            Some(("OEBPS/Images/cover art.jpg".to_string(), None))
+        );
+        assert_eq!(
+            resolve_href(base, "ch2.xhtml#note-1"),
+            Some((
+                "OEBPS/Text/ch2.xhtml".to_string(),
+                Some("note-1".to_string())
+            ))
+        );
+        assert_eq!(
+            resolve_href(base, "/OEBPS/style.css"),
+            Some(("OEBPS/style.css".to_string(), None))
+        );
+        assert_eq!(resolve_href(base, "https://example.com/a.png"), None);
+        assert_eq!(resolve_href(base, "//example.com/a.png"), None);
+    }
+
+    #[test]
+    fn test_resource_url() {
+        let urls = EpubUrls {
+            base: "/api/contents/1/chapters/2/epub".to_string(),
+            token: Some("a b".to_string()),
+        };
+        assert_eq!(
+            urls.resource("OEBPS/Images/cover art.jpg"),
+            "/api/contents/1/chapters/2/epub/resources/OEBPS/Images/cover%20art.jpg?token=a%20b"
+        );
+        assert_eq!(
+            urls.section(3, Some("s1")),
+            "/api/contents/1/chapters/2/epub/sections/3#s1"
+        );
+    }
+
     #[test]
     fn test_clean_text() {
         let text = "  Hello\r\nWorld\r  ";
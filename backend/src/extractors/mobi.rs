@@ -0,0 +1,516 @@
+//! MOBI and AZW3 extractor for Kindle books.
+//!
+//! Both formats are Palm database files: record 0 holds the PalmDOC and MOBI
+//! headers, followed by the compressed text records and then the images.
+//! The text is one HTML document, split into sections at MOBI page breaks
+//! (`<mbp:pagebreak/>`) or, in KF8 (AZW3) books, at the start of each of the
+//! concatenated files. Books that combine a MOBI and a KF8 part are read
+//! from the MOBI part, which holds the whole text as well. DRM-protected
+//! books and HUFF/CDIC compression are not supported.
+
+use std::path::Path;
+use std::sync::LazyLock;
+
+use encoding_rs::WINDOWS_1252;
+use regex::Regex;
+
+use super::{EpubExtractor, media_type_from_data};
+use crate::error::{AppError, ErrorCode, Result};
+use crate::t;
+
+/// Database type and creator of MOBI and AZW3 files.
+const MOBI_MAGIC: &[u8] = b"BOOKMOBI";
+
+/// Size of the Palm database header, before the record list.
+const PDB_HEADER_LEN: usize = 78;
+
+/// EXTH record holding the cover's offset from the first image record.
+const EXTH_COVER_OFFSET: u32 = 201;
+
+/// Text encoding value for UTF-8; anything else is Windows-1252.
+const ENCODING_UTF8: u32 = 65001;
+
+/// Record index used for "none" in MOBI headers.
+const NO_INDEX: u32 = 0xFFFF_FFFF;
+
+/// Where sections start: MOBI page breaks, and the start of each file in
+/// KF8 text.
+static SECTION_BREAK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)<mbp:pagebreak[^>]*>|<\?xml[^>]*>|<html\b").unwrap());
+
+/// Block ends, kept as line breaks in extracted text.
+static BLOCK_END: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)</(?:p|div|h[1-6]|li|blockquote)\s*>|<br\s*/?>").unwrap());
+
+/// MOBI extractor supporting .mobi, .azw3 and .azw files.
+pub struct MobiExtractor;
+
+impl MobiExtractor {
+    /// Returns the supported extensions for Kindle books.
+    pub fn supported_extensions() -> &'static [&'static str] {
+        &["mobi", "azw3", "azw"]
+    }
+
+    /// Checks if a file extension is supported.
+    pub fn is_supported(path: &Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| Self::supported_extensions().contains(&ext.to_lowercase().as_str()))
+            .unwrap_or(false)
+    }
+
+    /// Gets the number of sections in the book.
+    pub fn chapter_count(path: &Path) -> Result<usize> {
+        Ok(MobiBook::open(path)?.sections()?.len())
+    }
+
+    /// Extracts the plain text of one section.
+    pub fn extract_section(path: &Path, index: usize) -> Result<String> {
+        let sections = MobiBook::open(path)?.sections()?;
+        let html = sections.get(index).ok_or_else(|| {
+            AppError::NotFound(t!("mobi.section_not_found", index = index).to_string())
+        })?;
+        Ok(to_text(html))
+    }
+
+    /// Extracts all text content of the book as a single string.
+    pub fn extract_all_text(path: &Path) -> Result<String> {
+        let sections = MobiBook::open(path)?.sections()?;
+        Ok(sections
+            .iter()
+            .map(|html| to_text(html))
+            .collect::<Vec<_>>()
+            .join("\n\n"))
+    }
+
+    /// Extracts the cover image: the image the EXTH cover offset points to,
+    /// or the first image in the book. `None` if the book has no images.
+    pub fn extract_cover(path: &Path) -> Result<Option<Vec<u8>>> {
+        let book = MobiBook::open(path)?;
+        Ok(book.cover().map(<[u8]>::to_vec))
+    }
+}
+
+/// A parsed MOBI file.
+struct MobiBook {
+    data: Vec<u8>,
+    /// Start offset of each record.
+    records: Vec<usize>,
+    header: MobiHeader,
+}
+
+/// Fields of record 0 used when reading.
+#[derive(Debug, Default)]
+struct MobiHeader {
+    compression: u16,
+    text_records: usize,
+    encryption: u16,
+    utf8: bool,
+    first_image: Option<usize>,
+    /// Flags for the trailing entries appended to each text record.
+    extra_flags: u16,
+    exth: Vec<(u32, Vec<u8>)>,
+}
+
+impl MobiBook {
+    fn open(path: &Path) -> Result<Self> {
+        Self::parse(std::fs::read(path)?)
+    }
+
+    fn parse(data: Vec<u8>) -> Result<Self> {
+        if data.len() < PDB_HEADER_LEN || &data[60..68] != MOBI_MAGIC {
+            return Err(invalid("not a MOBI file"));
+        }
+
+        let count = read_u16(&data, 76)? as usize;
+        let records = (0..count)
+            .map(|i| read_u32(&data, PDB_HEADER_LEN + i * 8).map(|offset| offset as usize))
+            .collect::<Result<Vec<_>>>()?;
+        if records.is_empty() || records.iter().any(|&offset| offset > data.len()) {
+            return Err(invalid("bad record list"));
+        }
+
+        let mut book = Self {
+            data,
+            records,
+            header: MobiHeader::default(),
+        };
+        book.header = MobiHeader::parse(book.record(0)?)?;
+        Ok(book)
+    }
+
+    /// Bytes of record `index`.
+    fn record(&self, index: usize) -> Result<&[u8]> {
+        let start = *self
+            .records
+            .get(index)
+            .ok_or_else(|| invalid("missing record"))?;
+        let end = self
+            .records
+            .get(index + 1)
+            .copied()
+            .unwrap_or(self.data.len());
+        self.data
+            .get(start..end)
+            .ok_or_else(|| invalid("bad record offsets"))
+    }
+
+    /// The decompressed HTML text of the book.
+    fn text(&self) -> Result<String> {
+        if self.header.encryption != 0 {
+            return Err(AppError::Archive(t!("archive.mobi_encrypted").to_string())
+                .with_code(ErrorCode::UnsupportedFormat));
+        }
+
+        let mut text = Vec::new();
+        for index in 1..=self.header.text_records {
+            let record = strip_trailing_entries(self.record(index)?, self.header.extra_flags);
+            match self.header.compression {
+                1 => text.extend_from_slice(record),
+                2 => palmdoc_decompress(record, &mut text)?,
+                compression => {
+                    return Err(AppError::Archive(
+                        t!(
+                            "archive.mobi_unsupported_compression",
+                            compression = compression
+                        )
+                        .to_string(),
+                    )
+                    .with_code(ErrorCode::UnsupportedFormat));
+                }
+            }
+        }
+
+        Ok(self.decode(&text))
+    }
+
+    /// The HTML of each section, without empty sections.
+    fn sections(&self) -> Result<Vec<String>> {
+        let text = self.text()?;
+        let mut sections = Vec::new();
+        let mut start = 0;
+
+        for m in SECTION_BREAK.find_iter(&text) {
+            sections.push(&text[start..m.start()]);
+            // Page breaks are dropped; file starts stay with their file
+            start = if m.as_str().to_lowercase().starts_with("<mbp") {
+                m.end()
+            } else {
+                m.start()
+            };
+        }
+        sections.push(&text[start..]);
+
+        let mut sections: Vec<String> = sections
+            .into_iter()
+            .filter(|html| !to_text(html).is_empty())
+            .map(str::to_string)
+            .collect();
+        if sections.is_empty() {
+            sections.push(String::new());
+        }
+        Ok(sections)
+    }
+
+    fn cover(&self) -> Option<&[u8]> {
+        let first = self.header.first_image?;
+        let offset = self
+            .exth(EXTH_COVER_OFFSET)
+            .and_then(|data| data.try_into().ok())
+            .map(u32::from_be_bytes)
+            .filter(|&offset| offset != NO_INDEX);
+
+        if let Some(image) = offset
+            .and_then(|offset| self.record(first + offset as usize).ok())
+            .filter(|data| media_type_from_data(data).is_some())
+        {
+            return Some(image);
+        }
+
+        (first..self.records.len())
+            .filter_map(|index| self.record(index).ok())
+            .find(|data| media_type_from_data(data).is_some())
+    }
+
+    fn exth(&self, kind: u32) -> Option<&[u8]> {
+        self.header
+            .exth
+            .iter()
+            .find(|(k, _)| *k == kind)
+            .map(|(_, data)| data.as_slice())
+    }
+
+    fn decode(&self, data: &[u8]) -> String {
+        if self.header.utf8 {
+            String::from_utf8_lossy(data).into_owned()
+        } else {
+            WINDOWS_1252.decode(data).0.into_owned()
+        }
+    }
+}
+
+impl MobiHeader {
+    fn parse(record: &[u8]) -> Result<Self> {
+        let mut header = Self {
+            compression: read_u16(record, 0)?,
+            text_records: read_u16(record, 8)? as usize,
+            encryption: read_u16(record, 12)?,
+            ..Self::default()
+        };
+
+        // Plain PalmDOC files have no MOBI header
+        if record.get(16..20) != Some(b"MOBI".as_slice()) {
+            return Ok(header);
+        }
+
+        let header_len = read_u32(record, 20)? as usize;
+        header.utf8 = read_u32(record, 28)? == ENCODING_UTF8;
+
+        let first_image = read_u32(record, 108)?;
+        if first_image != NO_INDEX && first_image > 0 {
+            header.first_image = Some(first_image as usize);
+        }
+
+        if header_len >= 0xE4 {
+            header.extra_flags = read_u16(record, 0xF2)?;
+        }
+
+        // EXTH block follows the MOBI header when flag 0x40 is set
+        if read_u32(record, 128)? & 0x40 != 0 {
+            header.exth = parse_exth(record, 16 + header_len).unwrap_or_default();
+        }
+
+        Ok(header)
+    }
+}
+
+/// Read the records of an EXTH block.
+fn parse_exth(record: &[u8], start: usize) -> Option<Vec<(u32, Vec<u8>)>> {
+    if record.get(start..start + 4)? != b"EXTH" {
+        return None;
+    }
+    let count = read_u32(record, start + 8).ok()?;
+    let mut offset = start + 12;
+    let mut entries = Vec::new();
+
+    for _ in 0..count {
+        let kind = read_u32(record, offset).ok()?;
+        let len = read_u32(record, offset + 4).ok()? as usize;
+        let data = record.get(offset + 8..offset.checked_add(len)?)?;
+        entries.push((kind, data.to_vec()));
+        offset += len.max(8);
+    }
+    Some(entries)
+}
+
+/// Remove the trailing entries the extra data flags say are appended to a
+/// text record: one sized entry per flag bit above bit 0, then the
+/// multibyte character overlap if bit 0 is set.
+fn strip_trailing_entries(record: &[u8], flags: u16) -> &[u8] {
+    let mut end = record.len();
+
+    for bit in 1..16 {
+        if flags & (1 << bit) != 0 {
+            let size = trailing_entry_size(&record[..end]);
+            end = end.saturating_sub(size);
+        }
+    }
+    if flags & 1 != 0 && end > 0 {
+        let size = (record[end - 1] & 0x3) as usize + 1;
+        end = end.saturating_sub(size);
+    }
+
+    &record[..end]
+}
+
+/// Size of a trailing entry, stored as a backward-encoded variable-width
+/// integer in its last bytes.
+fn trailing_entry_size(data: &[u8]) -> usize {
+    let mut size = 0;
+    for &byte in &data[data.len().saturating_sub(4)..] {
+        if byte & 0x80 != 0 {
+            size = 0;
+        }
+        size = (size << 7) | (byte & 0x7F) as usize;
+    }
+    size
+}
+
+/// Decompress a PalmDOC (LZ77) record, appending to `out`.
+fn palmdoc_decompress(input: &[u8], out: &mut Vec<u8>) -> Result<()> {
+    let start = out.len();
+    let mut i = 0;
+
+    while i < input.len() {
+        let byte = input[i];
+        i += 1;
+        match byte {
+            // Literal run
+            0x01..=0x08 => {
+                let end = (i + byte as usize).min(input.len());
+                out.extend_from_slice(&input[i..end]);
+                i = end;
+            }
+            0x00 | 0x09..=0x7F => out.push(byte),
+            // Back reference: 11 bits of distance, 3 bits of length
+            0x80..=0xBF => {
+                let next = *input.get(i).ok_or_else(|| invalid("truncated text"))?;
+                i += 1;
+                let pair = ((u16::from(byte) << 8) | u16::from(next)) & 0x3FFF;
+                let distance = (pair >> 3) as usize;
+                let length = (pair & 0x7) as usize + 3;
+                if distance == 0 || distance > out.len() - start {
+                    return Err(invalid("bad back reference"));
+                }
+                for _ in 0..length {
+                    out.push(out[out.len() - distance]);
+                }
+            }
+            // A space followed by a character
+            0xC0..=0xFF => {
+                out.push(b' ');
+                out.push(byte ^ 0x80);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Plain text of a section, keeping block ends as line breaks.
+fn to_text(html: &str) -> String {
+    let html = BLOCK_END.replace_all(html, "$0\n");
+    let text = EpubExtractor::strip_html(&html);
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    lines.join("\n")
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or_else(|| invalid("truncated header"))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| invalid("truncated header"))
+}
+
+fn invalid(reason: &str) -> AppError {
+    AppError::Archive(t!("archive.mobi_invalid", error = reason).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a MOBI file with uncompressed UTF-8 text records and the given
+    /// image records.
+    fn build_mobi(text: &[&str], images: &[&[u8]], cover: Option<u32>) -> Vec<u8> {
+        let exth: Vec<u8> = match cover {
+            Some(offset) => [
+                b"EXTH".as_slice(),
+                &24u32.to_be_bytes(),
+                &1u32.to_be_bytes(),
+                &EXTH_COVER_OFFSET.to_be_bytes(),
+                &12u32.to_be_bytes(),
+                &offset.to_be_bytes(),
+            ]
+            .concat(),
+            None => Vec::new(),
+        };
+
+        let mut record0 = vec![0u8; 16 + 0xE8];
+        record0[0..2].copy_from_slice(&1u16.to_be_bytes());
+        record0[8..10].copy_from_slice(&(text.len() as u16).to_be_bytes());
+        record0[16..20].copy_from_slice(b"MOBI");
+        record0[20..24].copy_from_slice(&0xE8u32.to_be_bytes());
+        record0[28..32].copy_from_slice(&ENCODING_UTF8.to_be_bytes());
+        record0[108..112].copy_from_slice(&(text.len() as u32 + 1).to_be_bytes());
+        if cover.is_some() {
+            record0[128..132].copy_from_slice(&0x40u32.to_be_bytes());
+        }
+        record0.extend(exth);
+
+        let records: Vec<&[u8]> = std::iter::once(record0.as_slice())
+            .chain(text.iter().map(|t| t.as_bytes()))
+            .chain(images.iter().copied())
+            .collect();
+
+        let mut data = vec![0u8; PDB_HEADER_LEN];
+        data[60..68].copy_from_slice(MOBI_MAGIC);
+        data[76..78].copy_from_slice(&(records.len() as u16).to_be_bytes());
+        let mut offset = PDB_HEADER_LEN + records.len() * 8 + 2;
+        for record in &records {
+            data.extend((offset as u32).to_be_bytes());
+            data.extend([0u8; 4]);
+            offset += record.len();
+        }
+        data.extend([0u8; 2]);
+        for record in records {
+            data.extend(record);
+        }
+        data
+    }
+
+    #[test]
+    fn test_sections_split_at_page_breaks() {
+        let data = build_mobi(
+            &[
+                "<html><body><p>Cover</p><mbp:pagebreak/><h1>One</h1><p>First",
+                " line</p><mbp:pagebreak /><mbp:pagebreak/><p>Two</p></body></html>",
+            ],
+            &[],
+            None,
+        );
+        let book = MobiBook::parse(data).unwrap();
+        let sections: Vec<String> = book
+            .sections()
+            .unwrap()
+            .iter()
+            .map(|s| to_text(s))
+            .collect();
+
+        assert_eq!(sections, vec!["Cover", "One\nFirst line", "Two"]);
+    }
+
+    #[test]
+    fn test_cover_uses_exth_offset() {
+        let png: &[u8] = &[0x89, 0x50, 0x4E, 0x47, 1];
+        let jpeg: &[u8] = &[0xFF, 0xD8, 0xFF, 2];
+        let book = MobiBook::parse(build_mobi(&["<p>a</p>"], &[png, jpeg], Some(1))).unwrap();
+        assert_eq!(book.cover(), Some(jpeg));
+
+        let book = MobiBook::parse(build_mobi(&["<p>a</p>"], &[png, jpeg], None)).unwrap();
+        assert_eq!(book.cover(), Some(png));
+    }
+
+    #[test]
+    fn test_palmdoc_decompress() {
+        // "abc" + back reference (distance 3, length 3) + " d" + literal run
+        let input = [b'a', b'b', b'c', 0x80, 0x18, 0xE4, 0x02, b'!', b'?'];
+        let mut out = Vec::new();
+        palmdoc_decompress(&input, &mut out).unwrap();
+        assert_eq!(out, b"abcabc d!?");
+
+        assert!(palmdoc_decompress(&[0x80, 0x18], &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_strip_trailing_entries() {
+        // Text, one byte of multibyte overlap, then a 3-byte trailing entry
+        let record = [b'h', b'i', 0x00, 0xAA, 0xBB, 0x83];
+        assert_eq!(strip_trailing_entries(&record, 0b11), b"hi");
+        assert_eq!(strip_trailing_entries(&record, 0), &record[..]);
+    }
+
+    #[test]
+    fn test_rejects_other_files() {
+        assert!(MobiBook::parse(b"not a mobi".to_vec()).is_err());
+        assert!(MobiBook::parse(vec![0u8; 100]).is_err());
+    }
+}
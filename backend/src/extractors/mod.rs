@@ -1,15 +1,81 @@
-//! Archive extractors for reading comic, novel, and PDF files.
+//! Archive extractors for reading comic, novel, audiobook and PDF files.
 //!
 //! This module contains implementations for extracting content from various file formats
-//! including ZIP, CBZ, CBR, RAR for compressed archives, EPUB for novels, and PDF.
+//! including ZIP, CBZ, CBR, RAR for compressed archives, EPUB, MOBI/AZW3 and plain text for
+//! novels, M4B/MP3 for audiobooks, and PDF.
 
 pub mod archive;
+pub mod audio;
 pub mod epub;
+pub mod mobi;
 pub mod pdf;
+pub mod txt;
 
-pub use archive::ArchiveExtractor;
+pub use archive::{ArchiveEntries, ArchiveExtractor, ArchiveReader};
+pub use audio::AudioExtractor;
 pub use epub::EpubExtractor;
+pub use mobi::MobiExtractor;
 pub use pdf::PdfExtractor;
+pub use txt::TxtExtractor;
+
+/// Media type used when a page's format cannot be determined.
+pub const UNKNOWN_MEDIA_TYPE: &str = "application/octet-stream";
+
+/// Media type of an image file, by the extension of its name.
+pub fn media_type_from_name(name: &str) -> Option<&'static str> {
+    let extension = std::path::Path::new(name).extension()?.to_str()?;
+    match extension.to_lowercase().as_str() {
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "png" => Some("image/png"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        "bmp" => Some("image/bmp"),
+        "avif" => Some("image/avif"),
+        _ => None,
+    }
+}
+
+/// Media type of image data, by its magic bytes.
+pub fn media_type_from_data(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if data.starts_with(&[0x89, 0x50, 0x4E, 0x47]) {
+        Some("image/png")
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else if data.starts_with(b"BM") {
+        Some("image/bmp")
+    } else if data.len() >= 12
+        && &data[4..8] == b"ftyp"
+        && matches!(&data[8..12], b"avif" | b"avis")
+    {
+        Some("image/avif")
+    } else {
+        None
+    }
+}
+
+/// Media type of a page: from its content, then its file name. Content
+/// wins because archives often hold images with the wrong extension.
+pub fn page_media_type(name: &str, data: &[u8]) -> &'static str {
+    media_type_from_data(data)
+        .or_else(|| media_type_from_name(name))
+        .unwrap_or(UNKNOWN_MEDIA_TYPE)
+}
+
+/// Whether an archive entry name stays inside the archive when unpacked:
+/// relative, without `..` components and without a drive letter.
+/// Backslashes count as separators, as archives made on Windows use them.
+pub fn is_safe_entry_name(name: &str) -> bool {
+    let bytes = name.as_bytes();
+    let has_drive = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+    !name.is_empty()
+        && !name.starts_with(['/', '\\'])
+        && !has_drive
+        && name.split(['/', '\\']).all(|part| part != "..")
+}
 
 /// Generates a natural sort key for a string.
 /// This handles numeric portions correctly (e.g., "page2" < "page10").
@@ -74,6 +140,43 @@ mod tests {
         assert!(key2 < key10);
     }
 
+    #[test]
+    fn test_page_media_type() {
+        assert_eq!(media_type_from_name("001.PNG"), Some("image/png"));
+        assert_eq!(media_type_from_name("dir/001.jpeg"), Some("image/jpeg"));
+        assert_eq!(media_type_from_name("notes.txt"), None);
+
+        let webp = b"RIFF\0\0\0\0WEBPVP8 ";
+        assert_eq!(page_media_type("001.jpg", webp), "image/webp");
+        assert_eq!(page_media_type("001.gif", b"not an image"), "image/gif");
+        assert_eq!(page_media_type("001", b"not an image"), UNKNOWN_MEDIA_TYPE);
+    }
+
+    #[test]
+    fn test_is_safe_entry_name() {
+        for name in [
+            "001.jpg",
+            "ch1/001.jpg",
+            "ch1\\001.jpg",
+            "..001.jpg",
+            "a/.../b.jpg",
+        ] {
+            assert!(is_safe_entry_name(name), "{name}");
+        }
+        for name in [
+            "",
+            "../001.jpg",
+            "ch1/../../001.jpg",
+            "ch1\\..\\..\\001.jpg",
+            "/etc/passwd",
+            "\\\\server\\share\\001.jpg",
+            "C:\\Windows\\001.jpg",
+            "c:001.jpg",
+        ] {
+            assert!(!is_safe_entry_name(name), "{name}");
+        }
+    }
+
     #[test]
     fn test_natural_sort_key_mixed() {
         let key1 = natural_sort_key("chapter1_page01.jpg");
@@ -5,7 +5,7 @@
 //! they are page-based.
 
 use crate::error::{AppError, Result};
-use rust_i18n::t;
+use crate::t;
 use std::path::Path;
 
 /// PDF extractor supporting .pdf files.
@@ -0,0 +1,290 @@
+//! Plain text novel extractor.
+//!
+//! A `.txt` novel holds the whole book in one file. It is split into
+//! virtual chapters at lines that look like chapter headings, so it can be
+//! read and tracked one chapter at a time. Each heading pattern is matched
+//! against a whole line with surrounding whitespace removed.
+
+use std::path::Path;
+use std::sync::{LazyLock, RwLock};
+
+use encoding_rs::{Encoding, GB18030, UTF_8};
+use regex::Regex;
+use serde::Serialize;
+
+use crate::error::{AppError, Result};
+use crate::t;
+
+/// Patterns used when none are configured: numbered Chinese chapters
+/// (`第12章 标题`), `Chapter 12` or `Chapter XII`, and unnumbered front and
+/// back matter.
+pub const DEFAULT_CHAPTER_PATTERNS: &[&str] = &[
+    r"^第\s*[0-9０-９零〇一二两三四五六七八九十百千万]+\s*[章回节卷集部篇](?:\s+.*)?$",
+    r"(?i)^chapter\s+(?:\d+|[ivxlcdm]+)\b.*$",
+    r"^(?:序章|序言|楔子|引子|尾声|后记)(?:\s+.*)?$",
+];
+
+/// Smallest `.txt` file imported as a novel, so notes such as `readme.txt`
+/// next to other chapters are left alone.
+pub const MIN_NOVEL_SIZE: u64 = 16 * 1024;
+
+/// Lines longer than this (in characters) are never headings.
+const MAX_HEADING_CHARS: usize = 60;
+
+/// Splitter used for all `.txt` chapters.
+static SPLITTER: LazyLock<RwLock<ChapterSplitter>> =
+    LazyLock::new(|| RwLock::new(ChapterSplitter::default()));
+
+/// Finds chapter headings in plain text.
+#[derive(Debug, Clone)]
+pub struct ChapterSplitter {
+    patterns: Vec<Regex>,
+}
+
+impl ChapterSplitter {
+    /// Build a splitter, failing on the first pattern that is not a valid
+    /// regular expression.
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> std::result::Result<Self, regex::Error> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| Regex::new(pattern.as_ref()))
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(Self { patterns })
+    }
+
+    /// Split text at its chapter headings.
+    ///
+    /// Text before the first heading becomes an untitled split. Splits
+    /// without any text are dropped, so a table of contents listing the
+    /// headings at the start of the file does not produce empty chapters.
+    /// Text without headings is returned as a single untitled split.
+    pub fn split(&self, text: &str) -> Vec<TxtSplit> {
+        let mut splits = Vec::new();
+        let mut title = None;
+        let mut start = 0;
+        let mut offset = 0;
+
+        for line in text.split_inclusive('\n') {
+            let heading = line.trim();
+            if self.is_heading(heading) {
+                splits.extend(TxtSplit::new(title.take(), text, start, offset));
+                title = Some(heading.to_string());
+                start = offset + line.len();
+            }
+            offset += line.len();
+        }
+        splits.extend(TxtSplit::new(title, text, start, text.len()));
+
+        if splits.is_empty() {
+            splits.push(TxtSplit {
+                title: None,
+                start: 0,
+                end: text.len(),
+            });
+        }
+        splits
+    }
+
+    fn is_heading(&self, line: &str) -> bool {
+        !line.is_empty()
+            && line.chars().count() <= MAX_HEADING_CHARS
+            && self.patterns.iter().any(|pattern| pattern.is_match(line))
+    }
+}
+
+impl Default for ChapterSplitter {
+    fn default() -> Self {
+        Self::new(DEFAULT_CHAPTER_PATTERNS).expect("default chapter patterns are valid")
+    }
+}
+
+/// A virtual chapter: its heading and the byte range of its text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxtSplit {
+    pub title: Option<String>,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl TxtSplit {
+    /// The split of `text[start..end]` with surrounding blank lines removed,
+    /// or `None` if it holds no text.
+    fn new(title: Option<String>, text: &str, start: usize, end: usize) -> Option<Self> {
+        let body = &text[start..end];
+        let trimmed = body.trim_start_matches(['\r', '\n']);
+        let start = start + (body.len() - trimmed.len());
+        let end = start + trimmed.trim_end().len();
+
+        (!text[start..end].trim().is_empty()).then_some(Self { title, start, end })
+    }
+}
+
+/// A virtual chapter as listed by the API.
+#[derive(Debug, Clone, Serialize)]
+pub struct TxtSplitInfo {
+    /// Position of the split (0-based), used to fetch its text.
+    pub index: usize,
+    /// Heading line, or `None` for text before the first heading.
+    pub title: Option<String>,
+    /// Length of the text in characters.
+    pub length: usize,
+}
+
+/// The text of a virtual chapter.
+#[derive(Debug, Clone, Serialize)]
+pub struct TxtSplitText {
+    pub index: usize,
+    pub title: Option<String>,
+    pub text: String,
+}
+
+/// Plain text extractor supporting .txt files.
+pub struct TxtExtractor;
+
+impl TxtExtractor {
+    /// Returns the supported extensions for plain text novels.
+    pub fn supported_extensions() -> &'static [&'static str] {
+        &["txt"]
+    }
+
+    /// Checks if a file extension is supported.
+    pub fn is_supported(path: &Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| Self::supported_extensions().contains(&ext.to_lowercase().as_str()))
+            .unwrap_or(false)
+    }
+
+    /// Whether a file is a supported text file large enough to be a novel.
+    pub fn is_novel(path: &Path) -> bool {
+        Self::is_supported(path)
+            && std::fs::metadata(path).is_ok_and(|m| m.is_file() && m.len() >= MIN_NOVEL_SIZE)
+    }
+
+    /// Replace the splitter used for all `.txt` chapters.
+    pub fn set_splitter(splitter: ChapterSplitter) {
+        *SPLITTER.write().unwrap_or_else(|e| e.into_inner()) = splitter;
+    }
+
+    /// Reads a text file, detecting its encoding.
+    pub fn read_text(path: &Path) -> Result<String> {
+        let data = std::fs::read(path)?;
+        Ok(decode(&data))
+    }
+
+    /// Lists the virtual chapters of a text file.
+    pub fn list_splits(path: &Path) -> Result<Vec<TxtSplitInfo>> {
+        let text = Self::read_text(path)?;
+        Ok(Self::split(&text)
+            .into_iter()
+            .enumerate()
+            .map(|(index, split)| TxtSplitInfo {
+                index,
+                length: text[split.start..split.end].chars().count(),
+                title: split.title,
+            })
+            .collect())
+    }
+
+    /// Reads the text of one virtual chapter.
+    pub fn extract_split(path: &Path, index: usize) -> Result<TxtSplitText> {
+        let text = Self::read_text(path)?;
+        let split = Self::split(&text).into_iter().nth(index).ok_or_else(|| {
+            AppError::NotFound(t!("txt.split_not_found", index = index).to_string())
+        })?;
+
+        Ok(TxtSplitText {
+            index,
+            text: text[split.start..split.end].to_string(),
+            title: split.title,
+        })
+    }
+
+    /// Gets the number of virtual chapters in a text file.
+    pub fn chapter_count(path: &Path) -> Result<usize> {
+        let text = Self::read_text(path)?;
+        Ok(Self::split(&text).len())
+    }
+
+    fn split(text: &str) -> Vec<TxtSplit> {
+        SPLITTER
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .split(text)
+    }
+}
+
+/// Decode a text file. A byte order mark is honoured; otherwise UTF-8 is
+/// used when the data is valid UTF-8 and GB18030 (a superset of GBK, the
+/// usual encoding of older Chinese novels) when it is not.
+fn decode(data: &[u8]) -> String {
+    let text = if Encoding::for_bom(data).is_some() || std::str::from_utf8(data).is_ok() {
+        // Sniffs the byte order mark, so UTF-16 files decode too
+        UTF_8.decode(data).0
+    } else {
+        GB18030.decode(data).0
+    };
+    text.replace("\r\n", "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn titles(splits: &[TxtSplit]) -> Vec<Option<&str>> {
+        splits.iter().map(|s| s.title.as_deref()).collect()
+    }
+
+    #[test]
+    fn test_split_chinese_headings() {
+        let text = "作者的话\n\n第一章 开端\n正文一\n\n  第2章 继续  \n正文二\n";
+        let splits = ChapterSplitter::default().split(text);
+
+        assert_eq!(
+            titles(&splits),
+            vec![None, Some("第一章 开端"), Some("第2章 继续")]
+        );
+        assert_eq!(&text[splits[0].start..splits[0].end], "作者的话");
+        assert_eq!(&text[splits[1].start..splits[1].end], "正文一");
+        assert_eq!(&text[splits[2].start..splits[2].end], "正文二");
+    }
+
+    #[test]
+    fn test_table_of_contents_is_skipped() {
+        let text = "Chapter 1\nChapter 2\n\nChapter 1\nOne.\nChapter 2\nTwo.\n";
+        let splits = ChapterSplitter::default().split(text);
+
+        assert_eq!(titles(&splits), vec![Some("Chapter 1"), Some("Chapter 2")]);
+        assert_eq!(&text[splits[0].start..splits[0].end], "One.");
+    }
+
+    #[test]
+    fn test_long_lines_are_not_headings() {
+        let line = format!("第一章{}", "很长".repeat(40));
+        let text = format!("{}\nbody", line);
+        let splits = ChapterSplitter::default().split(&text);
+
+        assert_eq!(splits.len(), 1);
+        assert_eq!(splits[0].title, None);
+    }
+
+    #[test]
+    fn test_custom_patterns() {
+        let splitter = ChapterSplitter::new(&[r"^=== .+ ===$"]).unwrap();
+        let splits = splitter.split("=== Part A ===\na\n=== Part B ===\nb");
+        assert_eq!(
+            titles(&splits),
+            vec![Some("=== Part A ==="), Some("=== Part B ===")]
+        );
+
+        assert!(ChapterSplitter::new(&["(unclosed"]).is_err());
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode("第一章\r\n".as_bytes()), "第一章\n");
+        assert_eq!(decode(b"\xEF\xBB\xBFabc"), "abc");
+        // "第一章" in GBK
+        assert_eq!(decode(&[0xB5, 0xDA, 0xD2, 0xBB, 0xD5, 0xC2]), "第一章");
+    }
+}
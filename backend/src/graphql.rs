@@ -0,0 +1,568 @@
+//! GraphQL API.
+//!
+//! A read-only schema over libraries, contents, chapters, reading progress
+//! and scan tasks, built with the `graphql` cargo feature and served at
+//! `POST /api/graphql`. Nested fields are batched through dataloaders, so a
+//! query for every library with its contents, chapters and progress costs a
+//! fixed number of database queries however many items it returns.
+
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock};
+
+use async_graphql::dataloader::{DataLoader, Loader};
+use async_graphql::{
+    ComplexObject, Context, EmptyMutation, EmptySubscription, Enum, Json, Object, Request, Schema,
+    SimpleObject,
+};
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Sqlite};
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::models::{
+    Chapter, Content, ContentResponse, LibraryWithStats, ReadingProgress, ScanTask, TaskKind,
+    TaskPriority, TaskStatus, age_rating_allowed,
+};
+use crate::repository::content::{ChapterRepository, ContentRepository};
+use crate::repository::progress::ProgressRepository;
+use crate::services::library::LibraryService;
+use crate::state::AppState;
+
+/// Default number of finished scan tasks returned by `scanTasks`.
+const DEFAULT_TASK_HISTORY: usize = 20;
+
+/// Deepest nesting a query may have. Contents link back to their library,
+/// so without a limit a query could nest without end; the introspection
+/// query of common clients still fits.
+const MAX_QUERY_DEPTH: usize = 15;
+
+/// Most fields a query may select in total, counting each field once.
+const MAX_QUERY_COMPLEXITY: usize = 500;
+
+/// The GraphQL schema.
+pub type RyuriSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// The schema is the same for every request; request data carries the
+/// state and the user.
+pub static SCHEMA: LazyLock<RyuriSchema> = LazyLock::new(|| {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_QUERY_DEPTH)
+        .limit_complexity(MAX_QUERY_COMPLEXITY)
+        .finish()
+});
+
+/// The user a request is executed for.
+struct Viewer {
+    /// Locale of scan task summaries.
+    locale: String,
+    /// Highest age rating of the contents the user may see.
+    max_age_rating: Option<i32>,
+}
+
+/// Attach the state, the user and fresh dataloaders to a request.
+///
+/// Loaders are created per request so nothing loaded for one user or one
+/// moment leaks into another request.
+pub fn prepare_request(
+    request: Request,
+    state: &AppState,
+    user_id: i64,
+    locale: String,
+    max_age_rating: Option<i32>,
+) -> Request {
+    let pool = state.pool.clone();
+    request
+        .data(state.clone())
+        .data(Viewer {
+            locale,
+            max_age_rating,
+        })
+        .data(DataLoader::new(
+            LibraryLoader(state.library_service.clone()),
+            tokio::spawn,
+        ))
+        .data(DataLoader::new(
+            LibraryContentsLoader(pool.clone()),
+            tokio::spawn,
+        ))
+        .data(DataLoader::new(
+            ContentChaptersLoader(pool.clone()),
+            tokio::spawn,
+        ))
+        .data(DataLoader::new(
+            ChapterProgressLoader { pool, user_id },
+            tokio::spawn,
+        ))
+}
+
+/// Root of all queries.
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// All libraries.
+    async fn libraries(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<LibraryNode>> {
+        let state = ctx.data_unchecked::<AppState>();
+        let libraries = state.library_service.list().await?;
+        Ok(libraries.into_iter().map(LibraryNode::from).collect())
+    }
+
+    /// A library by ID.
+    async fn library(
+        &self,
+        ctx: &Context<'_>,
+        id: i64,
+    ) -> async_graphql::Result<Option<LibraryNode>> {
+        let loader = ctx.data_unchecked::<DataLoader<LibraryLoader>>();
+        Ok(loader.load_one(id).await?.map(LibraryNode::from))
+    }
+
+    /// A content by ID, including contents in the trash.
+    async fn content(
+        &self,
+        ctx: &Context<'_>,
+        id: i64,
+    ) -> async_graphql::Result<Option<ContentNode>> {
+        let state = ctx.data_unchecked::<AppState>();
+        let viewer = ctx.data_unchecked::<Viewer>();
+        let content = ContentRepository::find_by_id(&state.pool, id).await?;
+        Ok(content
+            .filter(|c| age_rating_allowed(viewer.max_age_rating, c.age_rating))
+            .map(ContentNode::from))
+    }
+
+    /// A chapter by ID.
+    async fn chapter(
+        &self,
+        ctx: &Context<'_>,
+        id: i64,
+    ) -> async_graphql::Result<Option<ChapterNode>> {
+        let state = ctx.data_unchecked::<AppState>();
+        let chapter = ChapterRepository::find_by_id(&state.pool, id).await?;
+        Ok(chapter.map(ChapterNode::from))
+    }
+
+    /// Running, queued and recently finished scan tasks.
+    async fn scan_tasks(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Number of finished tasks to return.")] history_limit: Option<usize>,
+    ) -> ScanTasks {
+        let state = ctx.data_unchecked::<AppState>();
+        let locale = &ctx.data_unchecked::<Viewer>().locale;
+        let queue = &state.scan_queue_service;
+        let history = queue
+            .list_history(history_limit.unwrap_or(DEFAULT_TASK_HISTORY))
+            .await;
+
+        ScanTasks {
+            processing: queue
+                .list_processing()
+                .await
+                .into_iter()
+                .map(ScanTaskNode::from)
+                .collect(),
+            pending: queue
+                .list_pending()
+                .await
+                .into_iter()
+                .map(ScanTaskNode::from)
+                .collect(),
+            history: history
+                .into_iter()
+                .map(|task| ScanTaskNode::from(task.with_summary(locale)))
+                .collect(),
+        }
+    }
+
+    /// A scan task by ID.
+    async fn scan_task(&self, ctx: &Context<'_>, id: Uuid) -> Option<ScanTaskNode> {
+        let state = ctx.data_unchecked::<AppState>();
+        let locale = &ctx.data_unchecked::<Viewer>().locale;
+        let task = state.scan_queue_service.get_task(id).await?;
+        Some(ScanTaskNode::from(task.with_summary(locale)))
+    }
+}
+
+/// A library.
+#[derive(SimpleObject)]
+#[graphql(name = "Library", complex)]
+pub struct LibraryNode {
+    id: i64,
+    name: String,
+    /// Automatic scan interval in minutes, 0 when disabled.
+    scan_interval: i32,
+    watch_mode: bool,
+    scan_depth: i32,
+    path_count: i64,
+    content_count: i64,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<LibraryWithStats> for LibraryNode {
+    fn from(stats: LibraryWithStats) -> Self {
+        let library = stats.library;
+        Self {
+            id: library.id,
+            name: library.name,
+            scan_interval: library.scan_interval,
+            watch_mode: library.watch_mode,
+            scan_depth: library.scan_depth,
+            path_count: stats.path_count,
+            content_count: stats.content_count,
+            created_at: library.created_at,
+            updated_at: library.updated_at,
+        }
+    }
+}
+
+#[ComplexObject]
+impl LibraryNode {
+    /// Contents of the library, by title. Contents in the trash or above the
+    /// user's age rating cap are left out.
+    async fn contents(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<ContentNode>> {
+        let loader = ctx.data_unchecked::<DataLoader<LibraryContentsLoader>>();
+        let viewer = ctx.data_unchecked::<Viewer>();
+        let contents = loader.load_one(self.id).await?.unwrap_or_default();
+        Ok(contents
+            .into_iter()
+            .filter(|c| age_rating_allowed(viewer.max_age_rating, c.age_rating))
+            .map(ContentNode::from)
+            .collect())
+    }
+}
+
+/// A content (comic series, novel or audiobook).
+#[derive(SimpleObject)]
+#[graphql(name = "Content", complex)]
+pub struct ContentNode {
+    id: i64,
+    library_id: i64,
+    title: String,
+    chapter_count: i32,
+    has_thumbnail: bool,
+    /// Version of the thumbnail, appended to its URL as `?v=`.
+    thumbnail_etag: Option<String>,
+    thumbnail_blurhash: Option<String>,
+    /// Scraped metadata, as stored.
+    metadata: Option<Json<serde_json::Value>>,
+    /// Set while the content is in the trash.
+    missing_since: Option<DateTime<Utc>>,
+    series_group_id: Option<i64>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<Content> for ContentNode {
+    fn from(content: Content) -> Self {
+        let content = ContentResponse::from(content);
+        Self {
+            id: content.id,
+            library_id: content.library_id,
+            title: content.title,
+            chapter_count: content.chapter_count,
+            has_thumbnail: content.has_thumbnail,
+            thumbnail_etag: content.thumbnail_etag,
+            thumbnail_blurhash: content.thumbnail_blurhash,
+            metadata: content.metadata.map(Json),
+            missing_since: content.missing_since,
+            series_group_id: content.series_group_id,
+            created_at: content.created_at,
+            updated_at: content.updated_at,
+        }
+    }
+}
+
+#[ComplexObject]
+impl ContentNode {
+    /// Library the content is in.
+    async fn library(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<LibraryNode>> {
+        let loader = ctx.data_unchecked::<DataLoader<LibraryLoader>>();
+        Ok(loader
+            .load_one(self.library_id)
+            .await?
+            .map(LibraryNode::from))
+    }
+
+    /// Chapters of the content, in reading order.
+    async fn chapters(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<ChapterNode>> {
+        let loader = ctx.data_unchecked::<DataLoader<ContentChaptersLoader>>();
+        let chapters = loader.load_one(self.id).await?.unwrap_or_default();
+        Ok(chapters.into_iter().map(ChapterNode::from).collect())
+    }
+}
+
+/// A chapter of a content.
+#[derive(SimpleObject)]
+#[graphql(name = "Chapter", complex)]
+pub struct ChapterNode {
+    id: i64,
+    content_id: i64,
+    title: String,
+    /// File extension, or `folder` for a folder of images.
+    file_type: String,
+    sort_order: i32,
+    /// Number of pages, 0 if not yet known.
+    page_count: i32,
+    size: i64,
+    volume: Option<i32>,
+    number: Option<f32>,
+}
+
+impl From<Chapter> for ChapterNode {
+    fn from(chapter: Chapter) -> Self {
+        Self {
+            id: chapter.id,
+            content_id: chapter.content_id,
+            title: chapter.title,
+            file_type: chapter.file_type,
+            sort_order: chapter.sort_order,
+            page_count: chapter.page_count,
+            size: chapter.size,
+            volume: chapter.volume,
+            number: chapter.number,
+        }
+    }
+}
+
+#[ComplexObject]
+impl ChapterNode {
+    /// The requesting user's progress in the chapter, if they opened it.
+    async fn progress(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<ProgressNode>> {
+        let loader = ctx.data_unchecked::<DataLoader<ChapterProgressLoader>>();
+        Ok(loader.load_one(self.id).await?.map(ProgressNode::from))
+    }
+}
+
+/// Reading progress of the requesting user in a chapter.
+#[derive(SimpleObject)]
+#[graphql(name = "Progress")]
+pub struct ProgressNode {
+    chapter_id: i64,
+    /// Page number, character offset or playback second.
+    position: i32,
+    /// Progress within the chapter, 0 to 100.
+    percentage: f32,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<ReadingProgress> for ProgressNode {
+    fn from(progress: ReadingProgress) -> Self {
+        Self {
+            chapter_id: progress.chapter_id,
+            position: progress.position,
+            percentage: progress.percentage,
+            updated_at: progress.updated_at,
+        }
+    }
+}
+
+/// Scan tasks by state.
+#[derive(SimpleObject)]
+pub struct ScanTasks {
+    processing: Vec<ScanTaskNode>,
+    pending: Vec<ScanTaskNode>,
+    /// Most recently finished first.
+    history: Vec<ScanTaskNode>,
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+#[graphql(name = "TaskKind", remote = "TaskKind")]
+enum GqlTaskKind {
+    Scan,
+    Thumbnails,
+    PageCounts,
+    MetadataRefresh,
+    Backup,
+    LibraryDeletion,
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+#[graphql(name = "TaskPriority", remote = "TaskPriority")]
+enum GqlTaskPriority {
+    Low,
+    Normal,
+    High,
+}
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+#[graphql(name = "TaskStatus", remote = "TaskStatus")]
+enum GqlTaskStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// A scan or thumbnail task.
+#[derive(SimpleObject)]
+#[graphql(name = "ScanTask", complex)]
+pub struct ScanTaskNode {
+    id: Uuid,
+    kind: GqlTaskKind,
+    library_id: i64,
+    /// The only scan path scanned, or null for the whole library.
+    scan_path_id: Option<i64>,
+    priority: GqlTaskPriority,
+    status: GqlTaskStatus,
+    created_at: DateTime<Utc>,
+    started_at: Option<DateTime<Utc>>,
+    completed_at: Option<DateTime<Utc>>,
+    /// Paths or contents processed so far, while running.
+    processed: Option<i32>,
+    /// Paths or contents to process, while running.
+    total: Option<i32>,
+    error: Option<String>,
+    /// Summary of a finished task in the requesting user's locale.
+    summary: Option<String>,
+}
+
+impl From<ScanTask> for ScanTaskNode {
+    fn from(task: ScanTask) -> Self {
+        Self {
+            id: task.id,
+            kind: task.kind.into(),
+            library_id: task.library_id,
+            scan_path_id: task.scan_path_id,
+            priority: task.priority.into(),
+            status: task.status.into(),
+            created_at: task.created_at,
+            started_at: task.started_at,
+            completed_at: task.completed_at,
+            processed: task.progress.as_ref().map(|p| p.scanned_paths),
+            total: task.progress.as_ref().map(|p| p.total_paths),
+            error: task.error,
+            summary: task.summary,
+        }
+    }
+}
+
+#[ComplexObject]
+impl ScanTaskNode {
+    /// Library being scanned.
+    async fn library(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<LibraryNode>> {
+        let loader = ctx.data_unchecked::<DataLoader<LibraryLoader>>();
+        Ok(loader
+            .load_one(self.library_id)
+            .await?
+            .map(LibraryNode::from))
+    }
+}
+
+/// Loads libraries with their statistics by ID.
+pub struct LibraryLoader(Arc<LibraryService>);
+
+impl Loader<i64> for LibraryLoader {
+    type Value = LibraryWithStats;
+    type Error = Arc<AppError>;
+
+    async fn load(&self, keys: &[i64]) -> Result<HashMap<i64, Self::Value>, Self::Error> {
+        let libraries = self.0.list().await?;
+        Ok(libraries
+            .into_iter()
+            .filter(|library| keys.contains(&library.library.id))
+            .map(|library| (library.library.id, library))
+            .collect())
+    }
+}
+
+/// Loads the contents of libraries by library ID.
+pub struct LibraryContentsLoader(Pool<Sqlite>);
+
+impl Loader<i64> for LibraryContentsLoader {
+    type Value = Vec<Content>;
+    type Error = Arc<AppError>;
+
+    async fn load(&self, keys: &[i64]) -> Result<HashMap<i64, Self::Value>, Self::Error> {
+        let mut contents: HashMap<i64, Vec<Content>> = HashMap::new();
+        for content in ContentRepository::list_by_libraries(&self.0, keys).await? {
+            contents
+                .entry(content.library_id)
+                .or_default()
+                .push(content);
+        }
+        Ok(contents)
+    }
+}
+
+/// Loads the chapters of contents by content ID.
+pub struct ContentChaptersLoader(Pool<Sqlite>);
+
+impl Loader<i64> for ContentChaptersLoader {
+    type Value = Vec<Chapter>;
+    type Error = Arc<AppError>;
+
+    async fn load(&self, keys: &[i64]) -> Result<HashMap<i64, Self::Value>, Self::Error> {
+        let mut chapters: HashMap<i64, Vec<Chapter>> = HashMap::new();
+        for chapter in ChapterRepository::list_by_contents(&self.0, keys).await? {
+            chapters
+                .entry(chapter.content_id)
+                .or_default()
+                .push(chapter);
+        }
+        Ok(chapters)
+    }
+}
+
+/// Loads one user's progress by chapter ID.
+pub struct ChapterProgressLoader {
+    pool: Pool<Sqlite>,
+    user_id: i64,
+}
+
+impl Loader<i64> for ChapterProgressLoader {
+    type Value = ReadingProgress;
+    type Error = Arc<AppError>;
+
+    async fn load(&self, keys: &[i64]) -> Result<HashMap<i64, Self::Value>, Self::Error> {
+        let progress =
+            ProgressRepository::find_by_user_and_chapters(&self.pool, self.user_id, keys).await?;
+        Ok(progress
+            .into_iter()
+            .map(|progress| (progress.chapter_id, progress))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Messages of the errors a query is rejected with.
+    async fn errors(query: String) -> Vec<String> {
+        SCHEMA
+            .execute(query)
+            .await
+            .errors
+            .into_iter()
+            .map(|e| e.message)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_deep_queries_are_rejected() {
+        assert!(errors("{ __typename }".to_string()).await.is_empty());
+
+        // Contents and libraries link to each other, so they nest forever
+        let mut selection = "id".to_string();
+        for _ in 0..MAX_QUERY_DEPTH {
+            selection = format!("library {{ contents {{ {} }} }}", selection);
+        }
+        let query = format!("{{ content(id: 1) {{ {} }} }}", selection);
+        let errors = errors(query).await;
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("nested too deep"), "{:?}", errors);
+    }
+
+    #[tokio::test]
+    async fn test_complex_queries_are_rejected() {
+        let fields: Vec<String> = (0..MAX_QUERY_COMPLEXITY)
+            .map(|i| format!("l{}: libraries {{ id }}", i))
+            .collect();
+        let errors = errors(format!("{{ {} }}", fields.join(" "))).await;
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("too complex"), "{:?}", errors);
+    }
+}
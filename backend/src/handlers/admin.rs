@@ -0,0 +1,279 @@
+//! Administration handlers.
+//!
+//! This module provides HTTP handlers for administrator-only endpoints:
+//! - GET /api/admin/audit - List audit log entries
+//! - GET /api/admin/backups - List database backups
+//! - POST /api/admin/backups - Create a database backup
+//! - GET /api/admin/backups/{name} - Download a database backup
+//! - POST /api/admin/backups/restore - Restore the database from an uploaded backup
+//! - GET /api/admin/settings - Get the runtime server settings
+//! - PATCH /api/admin/settings - Change runtime server settings
+//! - GET /api/admin/shares - List every user's share links
+//! - DELETE /api/admin/shares/{id} - Revoke any share link
+//! - PUT /api/admin/users/{id}/age-rating - Cap the age rating a user may see
+//! - POST /api/admin/users/{id}/password-reset - Issue a one-time password reset token
+//! - GET /api/admin/users/{id}/quota - Get the daily quota and usage of a user
+//! - PUT /api/admin/users/{id}/quota - Set the daily quota of a user
+
+use axum::{
+    Json,
+    body::{Body, Bytes},
+    extract::{Path, Query, Request, State},
+    http::{HeaderValue, StatusCode, header},
+    response::Response,
+};
+use tower::ServiceExt;
+use tower_http::services::ServeFile;
+
+use crate::error::{AppError, ErrorCode, Result};
+use crate::middlewares::{auth::AdminUser, client_ip::ClientIp};
+use crate::models::{
+    AuditAction, AuditLogPage, AuditLogQuery, BackupInfo, BackupRestoreResponse, NewAuditLogEntry,
+    PasswordResetResponse, RuntimeSettingsResponse, ShareLink, UpdateRuntimeSettingsRequest,
+    UpdateUserAgeRatingRequest, UpdateUserQuotaRequest, UserQuota, UserResponse,
+};
+use crate::services::age_rating::AgeRatingService;
+use crate::services::share::ShareService;
+use crate::state::AppState;
+use crate::t;
+
+/// GET /api/admin/audit
+///
+/// Returns a page of audit log entries, newest first.
+/// Supports filtering by `user_id`, `action` and a `from`/`to` date range
+/// (RFC 3339), with `page` and `page_size` for paging.
+pub async fn list_audit_log(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Query(query): Query<AuditLogQuery>,
+) -> Result<Json<AuditLogPage>> {
+    let page = state.audit_service.list(&query).await?;
+    Ok(Json(page))
+}
+
+/// GET /api/admin/backups
+///
+/// Lists database backups, newest first.
+pub async fn list_backups(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+) -> Result<Json<Vec<BackupInfo>>> {
+    Ok(Json(state.backup_service.list().await?))
+}
+
+/// POST /api/admin/backups
+///
+/// Writes a snapshot of the database to the backup directory.
+pub async fn create_backup(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    ClientIp(ip): ClientIp,
+) -> Result<Json<BackupInfo>> {
+    let backup = state.backup_service.create().await?;
+
+    let entry = NewAuditLogEntry::new(AuditAction::BackupCreated)
+        .user(admin.user_id, &admin.username)
+        .target("backup", &backup.name)
+        .ip(ip);
+    state.audit_service.record(entry).await;
+
+    Ok(Json(backup))
+}
+
+/// GET /api/admin/backups/{name}
+///
+/// Downloads a backup file.
+pub async fn download_backup(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Path(name): Path<String>,
+    request: Request,
+) -> Result<Response> {
+    let path = state.backup_service.path(&name).await?;
+
+    let response = ServeFile::new(path)
+        .oneshot(request)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let mut response = response.map(Body::new);
+    if let Ok(value) = HeaderValue::from_str(&format!("attachment; filename=\"{}\"", name)) {
+        response
+            .headers_mut()
+            .insert(header::CONTENT_DISPOSITION, value);
+    }
+    Ok(response)
+}
+
+/// POST /api/admin/backups/restore
+///
+/// Restores the database from a backup file sent as the request body.
+/// The upload is checked for integrity and compatibility, the current
+/// database is backed up, and the restored database is used after the
+/// server restarts.
+pub async fn restore_backup(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    ClientIp(ip): ClientIp,
+    body: Bytes,
+) -> Result<Json<BackupRestoreResponse>> {
+    let response = state.backup_service.restore(&body).await?;
+
+    let entry = NewAuditLogEntry::new(AuditAction::BackupRestored)
+        .user(admin.user_id, &admin.username)
+        .details(format!("safety_backup={}", response.safety_backup.name))
+        .ip(ip);
+    state.audit_service.record(entry).await;
+
+    Ok(Json(response))
+}
+
+/// GET /api/admin/settings
+///
+/// Returns the runtime server settings in effect, with the defaults from
+/// the environment and configuration file.
+pub async fn get_settings(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+) -> Json<RuntimeSettingsResponse> {
+    Json(state.settings_service.response())
+}
+
+/// PATCH /api/admin/settings
+///
+/// Changes runtime server settings. Fields left out keep their value; the
+/// changes are stored in the database and apply without a restart, except
+/// for `scan_concurrency`.
+pub async fn update_settings(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    ClientIp(ip): ClientIp,
+    Json(request): Json<UpdateRuntimeSettingsRequest>,
+) -> Result<Json<RuntimeSettingsResponse>> {
+    let details = serde_json::to_string(&request).unwrap_or_default();
+    let response = state.settings_service.update(request).await?;
+
+    let entry = NewAuditLogEntry::new(AuditAction::SettingsUpdated)
+        .user(admin.user_id, &admin.username)
+        .details(details)
+        .ip(ip);
+    state.audit_service.record(entry).await;
+
+    Ok(Json(response))
+}
+
+/// GET /api/admin/shares
+///
+/// Lists the share links of every user, newest first, including expired
+/// ones.
+pub async fn list_share_links(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+) -> Result<Json<Vec<ShareLink>>> {
+    let links = ShareService::list(&state.pool, None).await?;
+    Ok(Json(links))
+}
+
+/// DELETE /api/admin/shares/{id}
+///
+/// Revokes a share link of any user.
+pub async fn revoke_share_link(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    ClientIp(ip): ClientIp,
+    Path(id): Path<i64>,
+) -> Result<StatusCode> {
+    ShareService::revoke(&state.pool, id, None).await?;
+
+    let entry = NewAuditLogEntry::new(AuditAction::ShareLinkRevoked)
+        .user(admin.user_id, &admin.username)
+        .target("share_link", id)
+        .ip(ip);
+    state.audit_service.record(entry).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// PUT /api/admin/users/{id}/age-rating
+///
+/// Caps the age rating of the contents a user may see, or lifts the cap
+/// with `null`. Contents without a rating stay visible.
+pub async fn set_user_age_rating(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    ClientIp(ip): ClientIp,
+    Path(user_id): Path<i64>,
+    Json(request): Json<UpdateUserAgeRatingRequest>,
+) -> Result<Json<UserResponse>> {
+    AgeRatingService::set_max_for_user(&state.pool, user_id, request.max_age_rating).await?;
+    let user = state.auth_service.get_user(user_id).await?.ok_or_else(|| {
+        AppError::NotFound(t!("auth.user_not_found", id = user_id).to_string())
+            .with_code(ErrorCode::UserNotFound)
+    })?;
+
+    let entry = NewAuditLogEntry::new(AuditAction::UserUpdated)
+        .user(admin.user_id, &admin.username)
+        .target("user", user_id)
+        .details(serde_json::to_string(&request).unwrap_or_default())
+        .ip(ip);
+    state.audit_service.record(entry).await;
+
+    Ok(Json(UserResponse::from(user)))
+}
+
+/// POST /api/admin/users/{id}/password-reset
+///
+/// Issues a one-time token the user can set a new password with at
+/// POST /api/auth/password-reset, replacing any earlier one. The token is
+/// only shown once.
+pub async fn create_password_reset(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    ClientIp(ip): ClientIp,
+    Path(user_id): Path<i64>,
+) -> Result<Json<PasswordResetResponse>> {
+    let reset = state.auth_service.create_password_reset(user_id).await?;
+
+    let entry = NewAuditLogEntry::new(AuditAction::PasswordResetIssued)
+        .user(admin.user_id, &admin.username)
+        .target("user", user_id)
+        .ip(ip);
+    state.audit_service.record(entry).await;
+
+    Ok(Json(reset))
+}
+
+/// GET /api/admin/users/{id}/quota
+///
+/// Returns the daily limits of a user, those in effect, and their usage
+/// today.
+pub async fn get_user_quota(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Path(user_id): Path<i64>,
+) -> Result<Json<UserQuota>> {
+    Ok(Json(state.quota_service.get(user_id).await?))
+}
+
+/// PUT /api/admin/users/{id}/quota
+///
+/// Sets the daily transfer (MB) and request limits of a user. `null` uses
+/// the server default and `0` removes the limit.
+pub async fn set_user_quota(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    ClientIp(ip): ClientIp,
+    Path(user_id): Path<i64>,
+    Json(request): Json<UpdateUserQuotaRequest>,
+) -> Result<Json<UserQuota>> {
+    let details = serde_json::to_string(&request).unwrap_or_default();
+    let quota = state.quota_service.set_limits(user_id, request).await?;
+
+    let entry = NewAuditLogEntry::new(AuditAction::UserUpdated)
+        .user(admin.user_id, &admin.username)
+        .target("user", user_id)
+        .details(details)
+        .ip(ip);
+    state.audit_service.record(entry).await;
+
+    Ok(Json(quota))
+}
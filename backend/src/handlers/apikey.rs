@@ -1,16 +1,16 @@
 use axum::{
+    Json,
     extract::{Path, State},
     http::StatusCode,
-    Json,
 };
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use rust_i18n::t;
 
+use crate::t;
 use crate::{
-    error::{AppError, Result},
-    middlewares::auth::AuthUser,
-    models::{ApiKey, NewApiKey},
+    error::{AppError, ErrorCode, Result},
+    middlewares::{auth::AuthUser, client_ip::ClientIp},
+    models::{ApiKey, AuditAction, NewApiKey, NewAuditLogEntry},
     repository::apikey::ApiKeyRepository,
     state::AppState,
 };
@@ -43,6 +43,7 @@ impl From<ApiKey> for ApiKeyResponse {
 pub async fn create_api_key(
     State(state): State<AppState>,
     user: AuthUser,
+    ClientIp(ip): ClientIp,
     Json(payload): Json<CreateApiKeyRequest>,
 ) -> Result<(StatusCode, Json<ApiKeyResponse>)> {
     let api_key_string = Uuid::new_v4().to_string();
@@ -55,6 +56,13 @@ pub async fn create_api_key(
 
     let created_key = ApiKeyRepository::create(&state.pool, new_key).await?;
 
+    let entry = NewAuditLogEntry::new(AuditAction::ApiKeyCreated)
+        .user(user.user_id, &user.username)
+        .target("api_key", created_key.id)
+        .ip(ip)
+        .details(created_key.name.clone());
+    state.audit_service.record(entry).await;
+
     Ok((StatusCode::CREATED, Json(created_key.into())))
 }
 
@@ -72,15 +80,23 @@ pub async fn list_api_keys(
 pub async fn delete_api_key(
     State(state): State<AppState>,
     user: AuthUser,
+    ClientIp(ip): ClientIp,
     Path(id): Path<i64>,
 ) -> Result<StatusCode> {
     // Verify ownership
     let keys = ApiKeyRepository::list_by_user(&state.pool, user.user_id).await?;
     if !keys.iter().any(|k| k.id == id) {
-        return Err(AppError::NotFound(t!("auth.api_key_not_found").to_string()));
+        return Err(AppError::NotFound(t!("auth.api_key_not_found").to_string())
+            .with_code(ErrorCode::ApiKeyNotFound));
     }
 
     ApiKeyRepository::delete(&state.pool, id).await?;
 
+    let entry = NewAuditLogEntry::new(AuditAction::ApiKeyDeleted)
+        .user(user.user_id, &user.username)
+        .target("api_key", id)
+        .ip(ip);
+    state.audit_service.record(entry).await;
+
     Ok(StatusCode::NO_CONTENT)
 }
@@ -2,29 +2,109 @@
 //!
 //! This module provides HTTP handlers for authentication endpoints:
 //! - POST /api/auth/login - User login
+//! - POST /api/auth/login/2fa - Finish login with a TOTP or recovery code
 //! - GET /api/auth/me - Get current user
 //! - PUT /api/auth/me - Update current user
-//! - PUT /api/auth/password - Update password
+//! - POST /api/auth/password-reset - Set a new password with a reset token
+//! - POST /api/users/me/password - Change password
+//! - POST /api/auth/2fa/setup - Start TOTP enrollment
+//! - POST /api/auth/2fa/enable - Confirm TOTP enrollment
+//! - POST /api/auth/2fa/disable - Disable TOTP
+//! - POST /api/auth/2fa/recovery-codes - Regenerate recovery codes
 
-use axum::{Json, extract::State};
-use rust_i18n::t;
+use axum::{Json, extract::State, http::StatusCode};
 
-use crate::error::Result;
+use crate::error::{AppError, ErrorCode, Result};
 use crate::middlewares::auth::AuthUser;
+use crate::middlewares::client_ip::ClientIp;
 use crate::models::{
-    LoginRequest, LoginResponse, UpdateUserRequest, UserResponse,
+    AuditAction, ChangePasswordRequest, DisableTotpRequest, LoginRequest, LoginResponse,
+    LoginResult, NewAuditLogEntry, RecoveryCodesResponse, ResetPasswordRequest, TotpCodeRequest,
+    TotpSetupResponse, TwoFactorChallengeResponse, TwoFactorLoginRequest, UpdateUserRequest,
+    UserResponse,
 };
+use crate::services::auth::LoginOutcome;
 use crate::state::AppState;
+use crate::t;
 
 /// POST /api/auth/login
 ///
 /// Authenticates a user with username and password.
-/// Returns the user information and a JWT token on success.
+/// Returns the user information and a JWT token on success, or a challenge
+/// token when the user has two-factor authentication enabled.
 pub async fn login(
     State(state): State<AppState>,
+    ClientIp(ip): ClientIp,
     Json(req): Json<LoginRequest>,
+) -> Result<Json<LoginResult>> {
+    let username = req.username.clone();
+    let outcome = match state.auth_service.login(req.username, req.password).await {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            if matches!(e.kind(), AppError::Unauthorized(_)) {
+                let entry = NewAuditLogEntry::new(AuditAction::LoginFailed)
+                    .username(username)
+                    .ip(ip);
+                state.audit_service.record(entry).await;
+            }
+            return Err(e);
+        }
+    };
+
+    let result = match outcome {
+        LoginOutcome::Authenticated { user, token } => {
+            let entry = NewAuditLogEntry::new(AuditAction::Login)
+                .user(user.id, &user.username)
+                .ip(ip);
+            state.audit_service.record(entry).await;
+            LoginResult::Success(LoginResponse {
+                user: UserResponse::from(user),
+                token,
+            })
+        }
+        LoginOutcome::TwoFactorRequired { challenge_token } => {
+            LoginResult::TwoFactorRequired(TwoFactorChallengeResponse {
+                two_factor_required: true,
+                challenge_token,
+            })
+        }
+    };
+    Ok(Json(result))
+}
+
+/// POST /api/auth/login/2fa
+///
+/// Completes a two-factor login using the challenge token from
+/// POST /api/auth/login and a TOTP or recovery code.
+pub async fn login_two_factor(
+    State(state): State<AppState>,
+    ClientIp(ip): ClientIp,
+    Json(req): Json<TwoFactorLoginRequest>,
 ) -> Result<Json<LoginResponse>> {
-    let (user, token) = state.auth_service.login(req.username, req.password).await?;
+    let result = state
+        .auth_service
+        .complete_two_factor_login(&req.challenge_token, &req.code)
+        .await;
+
+    let (user, token) = match result {
+        Ok(authenticated) => authenticated,
+        Err(e) => {
+            if matches!(e.kind(), AppError::Unauthorized(_)) {
+                let entry = NewAuditLogEntry::new(AuditAction::LoginFailed)
+                    .ip(ip)
+                    .details("two-factor");
+                state.audit_service.record(entry).await;
+            }
+            return Err(e);
+        }
+    };
+
+    let entry = NewAuditLogEntry::new(AuditAction::Login)
+        .user(user.id, &user.username)
+        .ip(ip)
+        .details("two-factor");
+    state.audit_service.record(entry).await;
+
     Ok(Json(LoginResponse {
         user: UserResponse::from(user),
         token,
@@ -43,7 +123,10 @@ pub async fn get_me(
         .auth_service
         .get_user(auth_user.user_id)
         .await?
-        .ok_or_else(|| crate::error::AppError::NotFound(t!("auth.user_not_found_generic").to_string()))?;
+        .ok_or_else(|| {
+            AppError::NotFound(t!("auth.user_not_found_generic").to_string())
+                .with_code(ErrorCode::UserNotFound)
+        })?;
     Ok(Json(UserResponse::from(user)))
 }
 
@@ -53,11 +136,152 @@ pub async fn get_me(
 pub async fn update_me(
     State(state): State<AppState>,
     auth_user: AuthUser,
+    ClientIp(ip): ClientIp,
     Json(req): Json<UpdateUserRequest>,
 ) -> Result<Json<UserResponse>> {
+    let changed = [
+        req.username.as_ref().map(|_| "username"),
+        req.bangumi_api_key.as_ref().map(|_| "bangumi_api_key"),
+        req.locale.as_ref().map(|_| "locale"),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(",");
+
     let user = state
         .auth_service
         .update_user(auth_user.user_id, req)
         .await?;
+
+    let entry = NewAuditLogEntry::new(AuditAction::UserUpdated)
+        .user(auth_user.user_id, &auth_user.username)
+        .target("user", user.id)
+        .ip(ip)
+        .details(changed);
+    state.audit_service.record(entry).await;
+
     Ok(Json(UserResponse::from(user)))
 }
+
+/// POST /api/users/me/password
+///
+/// Changes the current user's password. Requires the current password.
+/// Signs out every other session and returns a fresh token for this one.
+pub async fn change_password(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    ClientIp(ip): ClientIp,
+    Json(req): Json<ChangePasswordRequest>,
+) -> Result<Json<LoginResponse>> {
+    let (user, token) = state
+        .auth_service
+        .change_password(auth_user.user_id, &req.current_password, &req.new_password)
+        .await?;
+
+    let entry = NewAuditLogEntry::new(AuditAction::PasswordChanged)
+        .user(user.id, &user.username)
+        .target("user", user.id)
+        .ip(ip);
+    state.audit_service.record(entry).await;
+
+    Ok(Json(LoginResponse {
+        user: UserResponse::from(user),
+        token,
+    }))
+}
+
+/// POST /api/auth/password-reset
+///
+/// Sets a new password with a one-time token issued by an administrator.
+/// Signs out every session; the user then logs in with the new password.
+pub async fn reset_password(
+    State(state): State<AppState>,
+    ClientIp(ip): ClientIp,
+    Json(req): Json<ResetPasswordRequest>,
+) -> Result<StatusCode> {
+    let user = state
+        .auth_service
+        .reset_password(&req.token, &req.new_password)
+        .await?;
+
+    let entry = NewAuditLogEntry::new(AuditAction::PasswordChanged)
+        .user(user.id, &user.username)
+        .target("user", user.id)
+        .ip(ip)
+        .details("reset");
+    state.audit_service.record(entry).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/auth/2fa/setup
+///
+/// Generates a new TOTP secret and returns it with an `otpauth://` URI
+/// for QR code display. Two-factor stays disabled until confirmed.
+pub async fn setup_totp(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<TotpSetupResponse>> {
+    let setup = state.auth_service.setup_totp(auth_user.user_id).await?;
+    Ok(Json(setup))
+}
+
+/// POST /api/auth/2fa/enable
+///
+/// Confirms enrollment with a code from the authenticator app and returns
+/// one-time recovery codes.
+pub async fn enable_totp(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    ClientIp(ip): ClientIp,
+    Json(req): Json<TotpCodeRequest>,
+) -> Result<Json<RecoveryCodesResponse>> {
+    let recovery_codes = state
+        .auth_service
+        .enable_totp(auth_user.user_id, &req.code)
+        .await?;
+
+    let entry = NewAuditLogEntry::new(AuditAction::TwoFactorEnabled)
+        .user(auth_user.user_id, &auth_user.username)
+        .ip(ip);
+    state.audit_service.record(entry).await;
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
+
+/// POST /api/auth/2fa/disable
+///
+/// Disables two-factor authentication. Requires the current password and
+/// a TOTP or recovery code.
+pub async fn disable_totp(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    ClientIp(ip): ClientIp,
+    Json(req): Json<DisableTotpRequest>,
+) -> Result<StatusCode> {
+    state
+        .auth_service
+        .disable_totp(auth_user.user_id, &req.password, &req.code)
+        .await?;
+
+    let entry = NewAuditLogEntry::new(AuditAction::TwoFactorDisabled)
+        .user(auth_user.user_id, &auth_user.username)
+        .ip(ip);
+    state.audit_service.record(entry).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/auth/2fa/recovery-codes
+///
+/// Replaces all recovery codes. Requires a current TOTP code.
+pub async fn regenerate_recovery_codes(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(req): Json<TotpCodeRequest>,
+) -> Result<Json<RecoveryCodesResponse>> {
+    let recovery_codes = state
+        .auth_service
+        .regenerate_recovery_codes(auth_user.user_id, &req.code)
+        .await?;
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
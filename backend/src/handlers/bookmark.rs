@@ -0,0 +1,93 @@
+//! Bookmark handlers.
+//!
+//! This module provides HTTP handlers for bookmark endpoints:
+//! - GET /api/bookmarks - List the current user's bookmarks
+//! - PUT /api/bookmarks/{id} - Change the note of a bookmark
+//! - DELETE /api/bookmarks/{id} - Delete a bookmark
+//! - POST /api/chapters/{id}/bookmarks - Create a bookmark in a chapter
+//! - GET /api/contents/{id}/bookmarks - List bookmarks in a content
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+
+use crate::error::Result;
+use crate::middlewares::auth::AuthUser;
+use crate::models::{
+    BookmarkListQuery, BookmarkResponse, CreateBookmarkRequest, UpdateBookmarkRequest,
+};
+use crate::services::bookmark::BookmarkService;
+use crate::state::AppState;
+
+/// GET /api/bookmarks
+///
+/// Returns the current user's bookmarks, newest first. `content_id` keeps
+/// only bookmarks in one content.
+pub async fn list_bookmarks(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<BookmarkListQuery>,
+) -> Result<Json<Vec<BookmarkResponse>>> {
+    let bookmarks = BookmarkService::list(&state.pool, auth_user.user_id, query.content_id).await?;
+    Ok(Json(
+        bookmarks.into_iter().map(BookmarkResponse::from).collect(),
+    ))
+}
+
+/// GET /api/contents/{id}/bookmarks
+///
+/// Returns the current user's bookmarks in a content in reading order.
+pub async fn list_content_bookmarks(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(content_id): Path<i64>,
+) -> Result<Json<Vec<BookmarkResponse>>> {
+    let bookmarks =
+        BookmarkService::list_for_content(&state.pool, auth_user.user_id, content_id).await?;
+    Ok(Json(
+        bookmarks.into_iter().map(BookmarkResponse::from).collect(),
+    ))
+}
+
+/// POST /api/chapters/{id}/bookmarks
+///
+/// Creates a bookmark at a position in a chapter. Novels may also send a
+/// `novel` position, and any bookmark may carry a `note`.
+pub async fn create_bookmark(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(chapter_id): Path<i64>,
+    Json(request): Json<CreateBookmarkRequest>,
+) -> Result<(StatusCode, Json<BookmarkResponse>)> {
+    let bookmark =
+        BookmarkService::create(&state.pool, auth_user.user_id, chapter_id, request).await?;
+    Ok((StatusCode::CREATED, Json(BookmarkResponse::from(bookmark))))
+}
+
+/// PUT /api/bookmarks/{id}
+///
+/// Replaces the note of a bookmark. An empty note removes it.
+pub async fn update_bookmark(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(bookmark_id): Path<i64>,
+    Json(request): Json<UpdateBookmarkRequest>,
+) -> Result<Json<BookmarkResponse>> {
+    let bookmark =
+        BookmarkService::update(&state.pool, auth_user.user_id, bookmark_id, request).await?;
+    Ok(Json(BookmarkResponse::from(bookmark)))
+}
+
+/// DELETE /api/bookmarks/{id}
+///
+/// Deletes a bookmark.
+pub async fn delete_bookmark(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(bookmark_id): Path<i64>,
+) -> Result<StatusCode> {
+    BookmarkService::delete(&state.pool, auth_user.user_id, bookmark_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
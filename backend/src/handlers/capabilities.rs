@@ -0,0 +1,162 @@
+//! Server capability discovery.
+//!
+//! This module provides an HTTP handler describing what the server supports,
+//! so clients can adapt their UI instead of probing endpoints:
+//! - GET /api/capabilities - List enabled features, formats and auth methods
+
+use axum::{Json, extract::State};
+use serde::Serialize;
+
+use crate::extractors::{
+    ArchiveExtractor, AudioExtractor, EpubExtractor, MobiExtractor, PdfExtractor, TxtExtractor,
+};
+use crate::state::AppState;
+
+/// Optional features and whether they are enabled.
+#[derive(Debug, Serialize)]
+pub struct FeatureFlags {
+    /// Komga-compatible API under `/komga`.
+    pub komga: bool,
+    /// Kobo sync API under `/kobo`.
+    pub kobo: bool,
+    /// KOReader sync API under `/koreader`.
+    pub koreader: bool,
+    /// Public share links under `/api/shared`.
+    pub sharing: bool,
+    /// OPDS catalog feed. Not served yet, so always `false`.
+    pub opds: bool,
+    /// Login through an external OIDC provider.
+    pub oidc: bool,
+    /// TOTP two-factor authentication.
+    pub two_factor: bool,
+    /// API keys via the `X-API-Key` header.
+    pub api_keys: bool,
+    /// Audit log for administrators.
+    pub audit_log: bool,
+    /// GraphQL API at `/api/graphql`, built with the `graphql` feature.
+    pub graphql: bool,
+}
+
+/// File extensions recognized when scanning libraries.
+#[derive(Debug, Serialize)]
+pub struct SupportedFormats {
+    /// Image-based formats (comics).
+    pub comic: Vec<&'static str>,
+    /// Text-based formats (novels).
+    pub novel: Vec<&'static str>,
+    /// Audio formats (audiobooks).
+    pub audio: Vec<&'static str>,
+}
+
+/// Response for GET /api/capabilities.
+#[derive(Debug, Serialize)]
+pub struct CapabilitiesResponse {
+    /// Server version.
+    pub version: &'static str,
+    pub features: FeatureFlags,
+    pub formats: SupportedFormats,
+    /// Accepted login methods: `password`, `totp`, `api_key`, `oidc`.
+    pub auth_methods: Vec<&'static str>,
+    /// Trackers reading progress can be synced to: `anilist`,
+    /// `myanimelist`, `bangumi`.
+    pub trackers: Vec<&'static str>,
+    /// Largest request body accepted by most endpoints, in bytes, as
+    /// configured with `MAX_BODY_SIZE_MB`.
+    pub max_upload_size: u64,
+}
+
+/// GET /api/capabilities
+///
+/// Returns the features, formats and authentication methods supported by
+/// this server. Does not require authentication.
+pub async fn get_capabilities(State(state): State<AppState>) -> Json<CapabilitiesResponse> {
+    let oidc = state.oidc_service.is_some();
+
+    let mut auth_methods = vec!["password", "totp", "api_key"];
+    if oidc {
+        auth_methods.push("oidc");
+    }
+
+    let comic = ArchiveExtractor::supported_extensions()
+        .iter()
+        .chain(PdfExtractor::supported_extensions())
+        .copied()
+        .collect();
+    let novel = EpubExtractor::supported_extensions()
+        .iter()
+        .chain(MobiExtractor::supported_extensions())
+        .chain(TxtExtractor::supported_extensions())
+        .copied()
+        .collect();
+
+    Json(CapabilitiesResponse {
+        version: env!("RYURI_VERSION"),
+        features: FeatureFlags {
+            komga: state.settings_service.komga_enabled(),
+            kobo: state.settings_service.kobo_enabled(),
+            koreader: state.settings_service.koreader_enabled(),
+            sharing: state.settings_service.sharing_enabled(),
+            opds: false,
+            oidc,
+            two_factor: true,
+            api_keys: true,
+            audit_log: true,
+            graphql: cfg!(feature = "graphql"),
+        },
+        formats: SupportedFormats {
+            comic,
+            novel,
+            audio: AudioExtractor::supported_extensions().to_vec(),
+        },
+        auth_methods,
+        trackers: state
+            .tracker_service
+            .configured()
+            .iter()
+            .map(|tracker| tracker.as_str())
+            .collect(),
+        max_upload_size: state.http_limits.max_body_size(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middlewares::http_limits::HttpLimitsConfig;
+    use crate::models::UpdateRuntimeSettingsRequest;
+    use crate::state::AppConfig;
+    use crate::test_fixtures::create_test_db;
+
+    #[tokio::test]
+    async fn test_reports_runtime_settings_and_body_limit() {
+        let config = AppConfig {
+            http_limits: HttpLimitsConfig {
+                max_body_size_mb: 5,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let state = AppState::new(create_test_db().await, config);
+
+        let Json(capabilities) = get_capabilities(State(state.clone())).await;
+        let features = &capabilities.features;
+        assert!(features.komga && features.kobo && features.koreader);
+        assert!(!features.sharing);
+        assert_eq!(capabilities.max_upload_size, 5 * 1024 * 1024);
+
+        // Features switched in the admin settings are reported at once
+        let request = UpdateRuntimeSettingsRequest {
+            komga_enabled: Some(false),
+            kobo_enabled: Some(false),
+            koreader_enabled: Some(false),
+            sharing_enabled: Some(true),
+            ..Default::default()
+        };
+        state.settings_service.update(request).await.unwrap();
+
+        let Json(capabilities) = get_capabilities(State(state)).await;
+        let features = &capabilities.features;
+        assert!(!features.komga && !features.kobo && !features.koreader);
+        assert!(features.sharing);
+    }
+}
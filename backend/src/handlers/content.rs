@@ -2,38 +2,156 @@
 //!
 //! This module provides HTTP handlers for content management endpoints:
 //! - GET /api/libraries/{id}/contents - List all contents in a library
-//! - GET /api/libraries/{id}/search - Search contents by title
+//! - GET /api/libraries/{id}/search - Search contents by title or alternative title
+//! - GET /api/contents/recent - List recently added contents
+//! - GET /api/contents/updated - List recently updated contents
+//! - GET /api/contents/status - List the current user's favorites and reading statuses
 //! - GET /api/contents/{id} - Get a content by ID
+//! - POST /api/contents/thumbnails - Get small previews of several thumbnails
+//! - GET /api/contents/{id}/status - Get the current user's favorite flag and reading status
+//! - PUT /api/contents/{id}/status - Set the current user's favorite flag and reading status
 //! - DELETE /api/contents/{id} - Delete a content
+//! - GET /api/contents/trash - List content whose folder went missing (admin)
+//! - DELETE /api/contents/trash - Delete all content in the trash (admin)
+//! - POST /api/contents/{id}/restore - Take a content out of the trash (admin)
+//! - POST /api/contents/{id}/move - Move a content to another library or scan path (admin)
 //! - PUT /api/contents/{id}/metadata - Update content metadata
 //! - GET /api/contents/{id}/chapters - List chapters for a content
+//! - GET /api/contents/{id}/chapters/{chapter}/pages - List page sizes and double-page spreads
 //! - GET /api/contents/{id}/chapters/{chapter}/pages/{page} - Get a comic page
+//! - GET /api/contents/{id}/search - Search the text of a novel's chapters
 //! - GET /api/contents/{id}/chapters/{chapter}/text - Get novel chapter text
+//! - GET /api/contents/{id}/chapters/{chapter}/epub - Get the spine and TOC of an EPUB
+//! - GET /api/contents/{id}/chapters/{chapter}/epub/sections/{index} - Get a sanitized EPUB section
+//! - GET /api/contents/{id}/chapters/{chapter}/epub/resources/{path} - Get an EPUB resource
+//! - GET /api/contents/{id}/chapters/{chapter}/splits - List the virtual chapters of a .txt novel
+//! - GET /api/contents/{id}/chapters/{chapter}/splits/{index} - Get a virtual chapter's text
+//! - GET /api/contents/{id}/chapters/{chapter}/audio - Get the duration and markers of an audiobook
+//! - GET /api/contents/{id}/chapters/{chapter}/audio/stream - Stream an audiobook file
+//! - GET /api/contents/{id}/chapters/{chapter}/download - Download a chapter file
+//! - GET /api/contents/{id}/download - Download all chapters as a ZIP
+//! - GET /api/contents/{id}/chapters/{chapter}/page-errors - List pages that failed to load
+//! - DELETE /api/contents/{id}/chapters/{chapter}/page-errors - Clear recorded page failures
+
+use std::collections::HashMap;
 
 use axum::{
     Json,
     body::Body,
-    extract::{Path, Query, State},
-    http::{Response, StatusCode, header},
+    extract::{Path, Query, Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, Response, StatusCode, header},
     response::IntoResponse,
 };
 use serde::{Deserialize, Serialize};
+use tokio_util::io::ReaderStream;
+use tower::ServiceExt;
+use tower_http::services::ServeFile;
 
-use crate::error::Result;
-use crate::models::{Chapter, ContentResponse};
+use crate::error::{AppError, Result};
+use crate::extractors::AudioExtractor;
+use crate::extractors::audio::AudioInfo;
+use crate::extractors::epub::{EpubManifest, EpubSection, EpubUrls};
+use crate::extractors::txt::{TxtSplitInfo, TxtSplitText};
+use crate::middlewares::{
+    auth::{AdminUser, AuthUser},
+    client_ip::ClientIp,
+};
+use crate::models::{
+    AuditAction, Chapter, ContentCursorQuery, ContentFeed, ContentFeedQuery, ContentPage,
+    ContentResponse, ContentStatus, ContentStatusFilter, MoveContentRequest, NewAuditLogEntry,
+    PageError, PageInfo, PageInfoQuery, PageQuery, TextSearchMatch, ThumbnailBatchRequest,
+    ThumbnailPreview, UpdateContentStatusRequest,
+};
+use crate::services::age_rating::AgeRatingService;
 use crate::services::content::ContentService;
+use crate::services::content_status::ContentStatusService;
+use crate::services::download::{ChapterDownload, DownloadFormat, DownloadService};
+use crate::services::review::ReviewService;
 use crate::state::AppState;
+use crate::utils::range::ByteRange;
+
+/// Header with the cursor of the next page of a paged content listing.
+pub static NEXT_CURSOR_HEADER: HeaderName = HeaderName::from_static("x-next-cursor");
 
 /// GET /api/libraries/{id}/contents
 ///
-/// Returns all contents in a library.
+/// Returns all contents in a library that the current user's age rating
+/// cap allows, with their average ratings. `status` and `favorite` keep
+/// only contents the current user marked that way.
+///
+/// With `limit` or `after`, returns one page by title instead, and the
+/// cursor of the next page in the `X-Next-Cursor` header. Filters apply
+/// within the page, so a filtered page can be shorter than `limit`.
 pub async fn list(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Path(library_id): Path<i64>,
-) -> Result<Json<Vec<ContentResponse>>> {
-    let contents = ContentService::list_contents(&state.pool, library_id).await?;
-    let responses: Vec<ContentResponse> = contents.into_iter().map(ContentResponse::from).collect();
-    Ok(Json(responses))
+    Query(filter): Query<ContentStatusFilter>,
+    Query(cursor): Query<ContentCursorQuery>,
+) -> Result<(HeaderMap, Json<Vec<ContentResponse>>)> {
+    let mut headers = HeaderMap::new();
+    let contents = if cursor.is_paged() {
+        let (contents, next_cursor) = ContentService::list_contents_page(
+            &state.pool,
+            library_id,
+            cursor.after.as_deref(),
+            cursor.limit,
+        )
+        .await?;
+        if let Some(value) = next_cursor.and_then(|c| HeaderValue::from_str(&c).ok()) {
+            headers.insert(NEXT_CURSOR_HEADER.clone(), value);
+        }
+        contents
+    } else {
+        ContentService::list_contents(&state.pool, library_id).await?
+    };
+    let contents =
+        AgeRatingService::filter_contents(&state.pool, auth_user.user_id, contents).await?;
+    let contents =
+        ContentStatusService::filter_contents(&state.pool, auth_user.user_id, &filter, contents)
+            .await?;
+    let mut responses: Vec<ContentResponse> =
+        contents.into_iter().map(ContentResponse::from).collect();
+    ContentService::fill_unread_counts(&state.pool, auth_user.user_id, &mut responses).await?;
+    ReviewService::fill_ratings(&state.pool, &mut responses).await?;
+    Ok((headers, Json(responses)))
+}
+
+/// GET /api/contents/recent
+///
+/// Returns a page of contents across all libraries, most recently added
+/// first. Accepts `library_id`, `page` and `page_size`, or `after` with the
+/// `next_cursor` of the previous page.
+pub async fn list_recent(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<ContentFeedQuery>,
+) -> Result<Json<ContentPage>> {
+    let max_age_rating = AgeRatingService::max_for_user(&state.pool, auth_user.user_id).await?;
+    let mut page =
+        ContentService::list_feed(&state.pool, ContentFeed::Added, &query, max_age_rating).await?;
+    ContentService::fill_unread_counts(&state.pool, auth_user.user_id, &mut page.items).await?;
+    ReviewService::fill_ratings(&state.pool, &mut page.items).await?;
+    Ok(Json(page))
+}
+
+/// GET /api/contents/updated
+///
+/// Returns a page of contents across all libraries, most recently changed
+/// by a scan or edit first. Accepts `library_id`, `page` and `page_size`, or
+/// `after` with the `next_cursor` of the previous page.
+pub async fn list_updated(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<ContentFeedQuery>,
+) -> Result<Json<ContentPage>> {
+    let max_age_rating = AgeRatingService::max_for_user(&state.pool, auth_user.user_id).await?;
+    let mut page =
+        ContentService::list_feed(&state.pool, ContentFeed::Updated, &query, max_age_rating)
+            .await?;
+    ContentService::fill_unread_counts(&state.pool, auth_user.user_id, &mut page.items).await?;
+    ReviewService::fill_ratings(&state.pool, &mut page.items).await?;
+    Ok(Json(page))
 }
 
 /// Query parameters for search.
@@ -45,26 +163,89 @@ pub struct SearchQuery {
 
 /// GET /api/libraries/{id}/search
 ///
-/// Searches contents by title within a library.
+/// Searches contents by title or alternative title within a library. Accepts
+/// the same `status` and `favorite` filters as the content list.
 pub async fn search(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Path(library_id): Path<i64>,
     Query(query): Query<SearchQuery>,
+    Query(filter): Query<ContentStatusFilter>,
 ) -> Result<Json<Vec<ContentResponse>>> {
     let contents = ContentService::search_contents(&state.pool, library_id, &query.q).await?;
-    let responses: Vec<ContentResponse> = contents.into_iter().map(ContentResponse::from).collect();
+    let contents =
+        AgeRatingService::filter_contents(&state.pool, auth_user.user_id, contents).await?;
+    let contents =
+        ContentStatusService::filter_contents(&state.pool, auth_user.user_id, &filter, contents)
+            .await?;
+    let mut responses: Vec<ContentResponse> =
+        contents.into_iter().map(ContentResponse::from).collect();
+    ContentService::fill_unread_counts(&state.pool, auth_user.user_id, &mut responses).await?;
+    ReviewService::fill_ratings(&state.pool, &mut responses).await?;
     Ok(Json(responses))
 }
 
+/// GET /api/contents/status
+///
+/// Lists the current user's favorites and reading statuses, most recently
+/// changed first. Accepts `status` and `favorite` filters.
+pub async fn list_statuses(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(filter): Query<ContentStatusFilter>,
+) -> Result<Json<Vec<ContentStatus>>> {
+    let statuses = ContentStatusService::list(&state.pool, auth_user.user_id, &filter).await?;
+    Ok(Json(statuses))
+}
+
+/// GET /api/contents/{id}/status
+///
+/// Returns whether the current user marked a content as a favorite and the
+/// reading status they gave it.
+pub async fn get_status(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(content_id): Path<i64>,
+) -> Result<Json<ContentStatus>> {
+    let status = ContentStatusService::get(&state.pool, auth_user.user_id, content_id).await?;
+    Ok(Json(status))
+}
+
+/// PUT /api/contents/{id}/status
+///
+/// Replaces the current user's favorite flag and reading status for a
+/// content. Sending `favorite: false` without a status clears both.
+pub async fn update_status(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(content_id): Path<i64>,
+    Json(request): Json<UpdateContentStatusRequest>,
+) -> Result<Json<ContentStatus>> {
+    let status =
+        ContentStatusService::set(&state.pool, auth_user.user_id, content_id, request).await?;
+    Ok(Json(status))
+}
+
 /// GET /api/contents/{id}
 ///
-/// Returns a content by its ID.
+/// Returns a content by its ID, with how many of its chapters the current
+/// user has not read to the end and the average rating users gave it.
 pub async fn get(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Path(content_id): Path<i64>,
 ) -> Result<Json<ContentResponse>> {
     let content = ContentService::get_content(&state.pool, content_id).await?;
-    Ok(Json(ContentResponse::from(content)))
+    AgeRatingService::check(&state.pool, auth_user.user_id, &content).await?;
+    let mut response = ContentResponse::from(content);
+    ContentService::fill_unread_counts(
+        &state.pool,
+        auth_user.user_id,
+        std::slice::from_mut(&mut response),
+    )
+    .await?;
+    ReviewService::fill_ratings(&state.pool, std::slice::from_mut(&mut response)).await?;
+    Ok(Json(response))
 }
 
 /// DELETE /api/contents/{id}
@@ -72,19 +253,112 @@ pub async fn get(
 /// Deletes a content and all associated chapters.
 pub async fn delete(
     State(state): State<AppState>,
+    auth_user: AuthUser,
+    ClientIp(ip): ClientIp,
     Path(content_id): Path<i64>,
 ) -> Result<Json<()>> {
+    let content = ContentService::get_content(&state.pool, content_id).await?;
     ContentService::delete_content(&state.pool, content_id).await?;
+
+    let entry = NewAuditLogEntry::new(AuditAction::ContentDeleted)
+        .user(auth_user.user_id, &auth_user.username)
+        .target("content", content_id)
+        .ip(ip)
+        .details(content.title);
+    state.audit_service.record(entry).await;
+
     Ok(Json(()))
 }
 
+/// GET /api/contents/trash
+///
+/// Returns the content whose folder was found missing during a scan, oldest
+/// first. Such content is hidden from listings until it is restored, its
+/// folder reappears, or it is purged. Requires admin.
+pub async fn list_trash(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+) -> Result<Json<Vec<ContentResponse>>> {
+    let contents = ContentService::list_trash(&state.pool).await?;
+    let responses: Vec<ContentResponse> = contents.into_iter().map(ContentResponse::from).collect();
+    Ok(Json(responses))
+}
+
+/// Response for purging the trash.
+#[derive(Debug, Clone, Serialize)]
+pub struct PurgeTrashResponse {
+    /// Number of contents deleted.
+    pub purged: u64,
+}
+
+/// DELETE /api/contents/trash
+///
+/// Deletes all content in the trash with its chapters and reading progress.
+/// Requires admin.
+pub async fn purge_trash(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    ClientIp(ip): ClientIp,
+) -> Result<Json<PurgeTrashResponse>> {
+    let purged = ContentService::purge_trash(&state.pool).await?;
+
+    let entry = NewAuditLogEntry::new(AuditAction::TrashPurged)
+        .user(admin.user_id, &admin.username)
+        .ip(ip)
+        .details(purged.to_string());
+    state.audit_service.record(entry).await;
+
+    Ok(Json(PurgeTrashResponse { purged }))
+}
+
+/// POST /api/contents/{id}/restore
+///
+/// Takes a content out of the trash. Requires admin.
+pub async fn restore(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Path(content_id): Path<i64>,
+) -> Result<Json<ContentResponse>> {
+    let content = ContentService::restore_content(&state.pool, content_id).await?;
+    Ok(Json(ContentResponse::from(content)))
+}
+
+/// POST /api/contents/{id}/move
+///
+/// Moves a content to another scan path, possibly of another library,
+/// keeping its chapters, reading progress and metadata. With `move_files`
+/// its folder is moved into the scan path on disk; otherwise it must have
+/// been moved there already. Requires admin.
+pub async fn move_content(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    ClientIp(ip): ClientIp,
+    Path(content_id): Path<i64>,
+    Json(req): Json<MoveContentRequest>,
+) -> Result<Json<ContentResponse>> {
+    let content =
+        ContentService::move_content(&state.pool, content_id, req.scan_path_id, req.move_files)
+            .await?;
+
+    let entry = NewAuditLogEntry::new(AuditAction::ContentMoved)
+        .user(admin.user_id, &admin.username)
+        .target("content", content_id)
+        .ip(ip)
+        .details(content.folder_path.clone());
+    state.audit_service.record(entry).await;
+
+    Ok(Json(ContentResponse::from(content)))
+}
+
 /// GET /api/contents/{id}/chapters
 ///
 /// Returns all chapters for a content.
 pub async fn list_chapters(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Path(content_id): Path<i64>,
 ) -> Result<Json<Vec<Chapter>>> {
+    AgeRatingService::check_id(&state.pool, auth_user.user_id, content_id).await?;
     let chapters = ContentService::list_chapters(&state.pool, content_id).await?;
     Ok(Json(chapters))
 }
@@ -100,43 +374,213 @@ pub struct PageParams {
     pub page: i64,
 }
 
+/// GET /api/contents/{id}/chapters/{chapter}/pages
+///
+/// Lists the pages of a comic chapter with their sizes, marking landscape
+/// double-page spreads and tall strips. With `split=true` each spread is
+/// listed as two virtual pages in reading order, to be requested with
+/// `half`; with `slice=true` each tall page is listed as its tiles, to be
+/// requested with `tile`.
+pub async fn list_pages(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(params): Path<ChapterParams>,
+    Query(query): Query<PageInfoQuery>,
+) -> Result<Json<Vec<PageInfo>>> {
+    AgeRatingService::check_id(&state.pool, auth_user.user_id, params.content_id).await?;
+    let pages = ContentService::list_page_info(
+        &state.pool,
+        params.content_id,
+        params.chapter_id,
+        query.split,
+        query.slice,
+    )
+    .await?;
+    Ok(Json(pages))
+}
+
 /// GET /api/contents/{id}/chapters/{chapter}/pages/{page}
 ///
-/// Returns a page image from a comic chapter.
+/// Returns a page image from a comic chapter, only its left or right half
+/// with `half=left|right`, or only one tile of a tall page with `tile=N`.
+/// `trim=true|false` trims uniform page margins, overriding the reader
+/// settings of the content.
+/// Broken pages are replaced by a placeholder image marked with the
+/// `X-Page-Placeholder` header.
 pub async fn get_page(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Path(params): Path<PageParams>,
+    Query(query): Query<PageQuery>,
 ) -> Result<impl IntoResponse> {
-    let image_data = ContentService::get_page(
+    AgeRatingService::check_id(&state.pool, auth_user.user_id, params.content_id).await?;
+    let page = ContentService::get_requested_page(
         &state.pool,
         params.content_id,
         params.chapter_id,
         params.page,
+        query,
     )
     .await?;
 
-    // Detect image type from magic bytes
-    let content_type = detect_image_type(&image_data);
+    if page.is_placeholder {
+        return Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, page.media_type)
+            .header(header::CACHE_CONTROL, "no-store")
+            .header("X-Page-Placeholder", "true")
+            .body(Body::from(page.data).into_data_stream())?);
+    }
 
     Ok(Response::builder()
         .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, content_type)
-        .body(Body::from(image_data).into_data_stream())?)
-}
-
-/// Detect image type from magic bytes.
-fn detect_image_type(data: &[u8]) -> &'static str {
-    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
-        "image/jpeg"
-    } else if data.starts_with(&[0x89, 0x50, 0x4E, 0x47]) {
-        "image/png"
-    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
-        "image/gif"
-    } else if data.starts_with(b"RIFF") && data.len() > 12 && &data[8..12] == b"WEBP" {
-        "image/webp"
-    } else {
-        "application/octet-stream"
+        .header(header::CONTENT_TYPE, page.media_type)
+        .body(Body::from(page.data).into_data_stream())?)
+}
+
+/// Path parameters for chapter-level requests.
+#[derive(Debug, Deserialize)]
+pub struct ChapterParams {
+    /// The content ID.
+    pub content_id: i64,
+    /// The chapter ID.
+    pub chapter_id: i64,
+}
+
+/// GET /api/contents/{id}/chapters/{chapter}/page-errors
+///
+/// Returns the pages of a chapter that failed to extract or decode.
+pub async fn list_page_errors(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(params): Path<ChapterParams>,
+) -> Result<Json<Vec<PageError>>> {
+    AgeRatingService::check_id(&state.pool, auth_user.user_id, params.content_id).await?;
+    let errors =
+        ContentService::list_page_errors(&state.pool, params.content_id, params.chapter_id).await?;
+    Ok(Json(errors))
+}
+
+/// DELETE /api/contents/{id}/chapters/{chapter}/page-errors
+///
+/// Clears the recorded page failures of a chapter.
+pub async fn clear_page_errors(
+    State(state): State<AppState>,
+    Path(params): Path<ChapterParams>,
+) -> Result<StatusCode> {
+    ContentService::clear_page_errors(&state.pool, params.content_id, params.chapter_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Query parameters for chapter downloads.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DownloadQuery {
+    /// `original` (default) or `cbz`.
+    #[serde(default)]
+    pub format: DownloadFormat,
+}
+
+/// GET /api/contents/{id}/chapters/{chapter}/download
+///
+/// Downloads the chapter file as stored. With `format=cbz`, RAR archives
+/// are repacked into CBZ while they are sent; image folders are always sent
+/// as CBZ. Files sent as stored support range requests.
+pub async fn download_chapter(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(params): Path<ChapterParams>,
+    Query(query): Query<DownloadQuery>,
+    request: Request,
+) -> Result<Response<Body>> {
+    AgeRatingService::check_id(&state.pool, auth_user.user_id, params.content_id).await?;
+    let download = DownloadService::chapter(
+        &state.pool,
+        params.content_id,
+        params.chapter_id,
+        query.format,
+    )
+    .await?;
+    send_chapter(download, request).await
+}
+
+/// GET /api/contents/{id}/download
+///
+/// Downloads all chapters of a content as one ZIP, written while it is sent
+/// so memory use does not grow with the series. With `format=cbz`, RAR
+/// chapters and image folders are stored as CBZ files; otherwise chapter
+/// files are stored as they are and image folders as folders. The archive
+/// is built while it is sent, so it cannot be fetched in ranges.
+pub async fn download_series(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(content_id): Path<i64>,
+    Query(query): Query<DownloadQuery>,
+) -> Result<Response<Body>> {
+    AgeRatingService::check_id(&state.pool, auth_user.user_id, content_id).await?;
+    let download = DownloadService::series(&state.pool, content_id, query.format).await?;
+    let disposition = attachment_disposition(&download.file_name);
+    let reader = DownloadService::stream_series(download);
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/zip")
+        .header(header::CONTENT_DISPOSITION, disposition)
+        .header(header::ACCEPT_RANGES, "none")
+        .body(Body::from_stream(ReaderStream::new(reader)))?)
+}
+
+/// Send a chapter download named by its `Content-Disposition` header.
+pub(crate) async fn send_chapter(
+    download: ChapterDownload,
+    request: Request,
+) -> Result<Response<Body>> {
+    let disposition = attachment_disposition(download.file_name());
+
+    let mut response = match download {
+        ChapterDownload::File { path, .. } => ServeFile::new(path)
+            .oneshot(request)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?
+            .map(Body::new),
+        ChapterDownload::Repack { path, .. } => {
+            let reader = DownloadService::stream_cbz(path);
+            Response::builder()
+                .status(StatusCode::OK)
+                .header(header::CONTENT_TYPE, "application/vnd.comicbook+zip")
+                .header(header::ACCEPT_RANGES, "none")
+                .body(Body::from_stream(ReaderStream::new(reader)))?
+        }
+    };
+
+    if response.status().is_success() {
+        response
+            .headers_mut()
+            .insert(header::CONTENT_DISPOSITION, disposition);
     }
+    Ok(response)
+}
+
+/// `Content-Disposition` value offering a download named `file_name`.
+///
+/// The name is sent UTF-8 encoded (RFC 6266) with an ASCII fallback for
+/// older clients.
+pub(crate) fn attachment_disposition(file_name: &str) -> HeaderValue {
+    let fallback: String = file_name
+        .chars()
+        .map(|c| {
+            if c == ' ' || (c.is_ascii_graphic() && c != '"' && c != '\\') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let value = format!(
+        "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+        fallback,
+        urlencoding::encode(file_name)
+    );
+    HeaderValue::from_str(&value).unwrap_or_else(|_| HeaderValue::from_static("attachment"))
 }
 
 /// Path parameters for chapter text requests.
@@ -160,13 +604,287 @@ pub struct ChapterTextResponse {
 /// Returns the text content of a novel chapter.
 pub async fn get_chapter_text(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Path(params): Path<ChapterTextParams>,
 ) -> Result<Json<ChapterTextResponse>> {
+    AgeRatingService::check_id(&state.pool, auth_user.user_id, params.content_id).await?;
     let text =
         ContentService::get_chapter_text(&state.pool, params.content_id, params.chapter_id).await?;
     Ok(Json(ChapterTextResponse { text }))
 }
 
+/// Query parameters for searching the text of a novel.
+#[derive(Debug, Deserialize)]
+pub struct TextSearchQuery {
+    /// Text to look for, ignoring case.
+    pub q: String,
+    /// Most matches to return.
+    pub limit: Option<usize>,
+}
+
+/// GET /api/contents/{id}/search
+///
+/// Searches the text of a novel's chapters and returns the chapter, the
+/// character offset and a snippet of each match, in reading order.
+pub async fn search_text(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(content_id): Path<i64>,
+    Query(query): Query<TextSearchQuery>,
+) -> Result<Json<Vec<TextSearchMatch>>> {
+    AgeRatingService::check_id(&state.pool, auth_user.user_id, content_id).await?;
+    let matches =
+        ContentService::search_text(&state.pool, content_id, &query.q, query.limit).await?;
+    Ok(Json(matches))
+}
+
+/// GET /api/contents/{id}/chapters/{chapter}/epub
+///
+/// Returns the spine (reading order) and table of contents of an EPUB
+/// chapter.
+pub async fn get_epub_manifest(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(params): Path<ChapterParams>,
+) -> Result<Json<EpubManifest>> {
+    AgeRatingService::check_id(&state.pool, auth_user.user_id, params.content_id).await?;
+    let manifest =
+        ContentService::get_epub_manifest(&state.pool, params.content_id, params.chapter_id)
+            .await?;
+    Ok(Json(manifest))
+}
+
+/// Path parameters for EPUB section requests.
+#[derive(Debug, Deserialize)]
+pub struct EpubSectionParams {
+    /// The content ID.
+    pub content_id: i64,
+    /// The chapter ID.
+    pub chapter_id: i64,
+    /// Spine index of the section (0-based).
+    pub index: usize,
+}
+
+/// Path parameters for EPUB resource requests.
+#[derive(Debug, Deserialize)]
+pub struct EpubResourceParams {
+    /// The content ID.
+    pub content_id: i64,
+    /// The chapter ID.
+    pub chapter_id: i64,
+    /// Path of the resource inside the EPUB.
+    pub path: String,
+}
+
+/// Query parameters for EPUB sections and resources.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EpubQuery {
+    /// Access token, when not sent in the `Authorization` header.
+    pub token: Option<String>,
+}
+
+/// GET /api/contents/{id}/chapters/{chapter}/epub/sections/{index}
+///
+/// Returns a section of an EPUB chapter as sanitized HTML. Scripts, frames,
+/// forms and event handlers are removed; images, stylesheets and fonts
+/// point to the resource endpoint and links to other sections point to the
+/// section endpoint. Resource URLs carry the token the section was
+/// requested with, so they load from `<img>` and `<link>` elements.
+pub async fn get_epub_section(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(params): Path<EpubSectionParams>,
+    Query(query): Query<EpubQuery>,
+    headers: HeaderMap,
+) -> Result<Json<EpubSection>> {
+    AgeRatingService::check_id(&state.pool, auth_user.user_id, params.content_id).await?;
+    let token = query.token.or_else(|| {
+        headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::to_string)
+    });
+    let urls = epub_urls(params.content_id, params.chapter_id, token);
+
+    let section = ContentService::get_epub_section(
+        &state.pool,
+        params.content_id,
+        params.chapter_id,
+        params.index,
+        urls,
+    )
+    .await?;
+    Ok(Json(section))
+}
+
+/// GET /api/contents/{id}/chapters/{chapter}/epub/resources/{path}
+///
+/// Returns an image, stylesheet, font or other file listed in the EPUB
+/// manifest. (X)HTML documents are only available as sections. Responses
+/// are sandboxed so SVG images cannot run script. A single byte range may
+/// be requested, so large media such as embedded audio can be seeked.
+pub async fn get_epub_resource(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(params): Path<EpubResourceParams>,
+    Query(query): Query<EpubQuery>,
+    headers: HeaderMap,
+) -> Result<Response<Body>> {
+    AgeRatingService::check_id(&state.pool, auth_user.user_id, params.content_id).await?;
+    let urls = epub_urls(params.content_id, params.chapter_id, query.token);
+    let (data, media_type) = ContentService::get_epub_resource(
+        &state.pool,
+        params.content_id,
+        params.chapter_id,
+        params.path,
+        urls,
+    )
+    .await?;
+
+    let response = Response::builder()
+        .header(header::CONTENT_TYPE, media_type)
+        .header(
+            header::CONTENT_SECURITY_POLICY,
+            "sandbox; default-src 'none'",
+        )
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+        .header(header::CACHE_CONTROL, "private, max-age=3600");
+    send_ranged(response, &headers, data)
+}
+
+/// Finish a response holding `data`, honouring a single `Range` header.
+///
+/// `If-Range` cannot be checked without a validator, so a conditional
+/// range request is answered with the whole body.
+fn send_ranged(
+    response: axum::http::response::Builder,
+    headers: &HeaderMap,
+    data: Vec<u8>,
+) -> Result<Response<Body>> {
+    let range = if headers.contains_key(header::IF_RANGE) {
+        None
+    } else {
+        headers.get(header::RANGE).and_then(|v| v.to_str().ok())
+    };
+    let len = data.len() as u64;
+    let response = response.header(header::ACCEPT_RANGES, "bytes");
+
+    Ok(match ByteRange::parse(range, len) {
+        ByteRange::Full => response.status(StatusCode::OK).body(Body::from(data))?,
+        ByteRange::Partial(range) => {
+            let content_range = format!("bytes {}-{}/{}", range.start(), range.end(), len);
+            let part = data[*range.start() as usize..=*range.end() as usize].to_vec();
+            response
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(header::CONTENT_RANGE, content_range)
+                .body(Body::from(part))?
+        }
+        ByteRange::Unsatisfiable => response
+            .status(StatusCode::RANGE_NOT_SATISFIABLE)
+            .header(header::CONTENT_RANGE, format!("bytes */{}", len))
+            .body(Body::empty())?,
+    })
+}
+
+/// URLs of the EPUB endpoints of a chapter.
+fn epub_urls(content_id: i64, chapter_id: i64, token: Option<String>) -> EpubUrls {
+    EpubUrls {
+        base: format!("/api/contents/{}/chapters/{}/epub", content_id, chapter_id),
+        token,
+    }
+}
+
+/// GET /api/contents/{id}/chapters/{chapter}/splits
+///
+/// Lists the virtual chapters a `.txt` novel is split into at its chapter
+/// headings.
+pub async fn list_txt_splits(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(params): Path<ChapterParams>,
+) -> Result<Json<Vec<TxtSplitInfo>>> {
+    AgeRatingService::check_id(&state.pool, auth_user.user_id, params.content_id).await?;
+    let splits =
+        ContentService::list_txt_splits(&state.pool, params.content_id, params.chapter_id).await?;
+    Ok(Json(splits))
+}
+
+/// Path parameters for virtual chapter requests.
+#[derive(Debug, Deserialize)]
+pub struct TxtSplitParams {
+    /// The content ID.
+    pub content_id: i64,
+    /// The chapter ID.
+    pub chapter_id: i64,
+    /// Index of the virtual chapter (0-based).
+    pub index: usize,
+}
+
+/// GET /api/contents/{id}/chapters/{chapter}/splits/{index}
+///
+/// Returns the text of a virtual chapter of a `.txt` novel, without its
+/// heading line.
+pub async fn get_txt_split(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(params): Path<TxtSplitParams>,
+) -> Result<Json<TxtSplitText>> {
+    AgeRatingService::check_id(&state.pool, auth_user.user_id, params.content_id).await?;
+    let split = ContentService::get_txt_split(
+        &state.pool,
+        params.content_id,
+        params.chapter_id,
+        params.index,
+    )
+    .await?;
+    Ok(Json(split))
+}
+
+/// GET /api/contents/{id}/chapters/{chapter}/audio
+///
+/// Returns the duration, chapter markers and title of an audiobook chapter.
+/// Progress on audiobooks is the playback position in seconds.
+pub async fn get_audio_info(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(params): Path<ChapterParams>,
+) -> Result<Json<AudioInfo>> {
+    AgeRatingService::check_id(&state.pool, auth_user.user_id, params.content_id).await?;
+    let info =
+        ContentService::get_audio_info(&state.pool, params.content_id, params.chapter_id).await?;
+    Ok(Json(info))
+}
+
+/// GET /api/contents/{id}/chapters/{chapter}/audio/stream
+///
+/// Streams an audiobook file. Range requests are answered with partial
+/// content, so players can seek without downloading the whole file.
+pub async fn stream_audio(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(params): Path<ChapterParams>,
+    request: Request,
+) -> Result<Response<Body>> {
+    AgeRatingService::check_id(&state.pool, auth_user.user_id, params.content_id).await?;
+    let path =
+        ContentService::find_audio(&state.pool, params.content_id, params.chapter_id).await?;
+    let media_type = AudioExtractor::media_type(&path);
+
+    let mut response = ServeFile::new(path)
+        .oneshot(request)
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .map(Body::new);
+
+    if response.status().is_success() {
+        response
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static(media_type));
+    }
+    Ok(response)
+}
+
 /// Request body for content update.
 #[derive(Debug, Clone, Deserialize)]
 pub struct UpdateContentRequest {
@@ -192,18 +910,78 @@ pub async fn update(
     Ok(Json(ContentResponse::from(content)))
 }
 
+/// Query parameters for thumbnail requests.
+#[derive(Debug, Deserialize)]
+pub struct ThumbnailQuery {
+    /// Thumbnail version tag from `ContentResponse::thumbnail_etag`.
+    pub v: Option<String>,
+}
+
+/// POST /api/contents/thumbnails
+///
+/// Returns small JPEG previews of the thumbnails of up to 200 contents,
+/// Base64 encoded and keyed by content ID, so a library grid loads its
+/// covers in one request. Contents without a thumbnail are left out.
+pub async fn get_thumbnails(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(req): Json<ThumbnailBatchRequest>,
+) -> Result<Json<HashMap<i64, ThumbnailPreview>>> {
+    let ids = AgeRatingService::filter_ids(&state.pool, auth_user.user_id, req.ids).await?;
+    let previews = ContentService::thumbnail_previews(
+        &state.pool,
+        &ids,
+        req.width,
+        state.settings_service.thumbnail_options().quality,
+    )
+    .await?;
+    Ok(Json(previews))
+}
+
+/// `Accept` header of a request, if it is valid text.
+pub(crate) fn accept_header(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
 /// GET /api/contents/{id}/thumbnail
 ///
-/// Returns the thumbnail image for a content.
+/// Returns the thumbnail image for a content. When `v` matches the current
+/// thumbnail version the response is marked immutable, since a regenerated
+/// thumbnail is requested under a different URL.
+///
+/// WebP and AVIF thumbnails are sent only when the `Accept` header lists
+/// them; otherwise they are converted to JPEG.
 pub async fn get_thumbnail(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Path(content_id): Path<i64>,
+    Query(query): Query<ThumbnailQuery>,
+    headers: HeaderMap,
 ) -> Result<impl IntoResponse> {
-    let thumbnail_data = ContentService::get_thumbnail(&state.pool, content_id).await?;
+    AgeRatingService::check_id(&state.pool, auth_user.user_id, content_id).await?;
+    let (thumbnail_data, etag) = ContentService::get_thumbnail(&state.pool, content_id).await?;
+    let cache_control = if query.v.as_deref() == Some(etag.as_str()) {
+        "public, max-age=31536000, immutable"
+    } else {
+        "public, max-age=86400"
+    };
+    let thumbnail = ContentService::negotiate_thumbnail(
+        thumbnail_data,
+        etag,
+        accept_header(&headers),
+        state.settings_service.thumbnail_options().quality,
+    )
+    .await?;
+
     Ok(Response::builder()
         .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, "image/jpeg")
-        .header(header::CACHE_CONTROL, "public, max-age=86400")
-        .body(Body::from(thumbnail_data))
+        .header(header::CONTENT_TYPE, thumbnail.mime_type)
+        .header(header::CACHE_CONTROL, cache_control)
+        .header(header::VARY, "Accept")
+        .header(header::ETAG, format!("\"{}\"", thumbnail.etag))
+        .body(Body::from(thumbnail.data))
         .unwrap())
 }
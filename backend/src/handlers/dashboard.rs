@@ -0,0 +1,87 @@
+//! Dashboard handlers.
+//!
+//! This module provides the HTTP handler of the home page summary:
+//! - GET /api/dashboard - Get libraries, recent and in-progress content, active tasks and errors
+
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+
+use crate::error::Result;
+use crate::middlewares::auth::AuthUser;
+use crate::models::{
+    ContentFeed, ContentFeedQuery, Dashboard, DashboardQuery, ScanTaskHistoryQuery, TaskStatus,
+    age_rating_allowed,
+};
+use crate::repository::dashboard::DashboardRepository;
+use crate::services::age_rating::AgeRatingService;
+use crate::services::content::ContentService;
+use crate::state::AppState;
+
+/// Contents per list when the client does not ask for a number.
+const DEFAULT_LIMIT: i64 = 10;
+
+/// Most contents per list a client can ask for.
+const MAX_LIMIT: i64 = 50;
+
+/// GET /api/dashboard
+///
+/// Returns what the home page shows in one response: the libraries with
+/// their counts, recently added content, the content the current user read
+/// last, running and pending tasks, and for administrators the counts of
+/// metadata failures, media errors, trashed content and failed tasks.
+pub async fn get(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<DashboardQuery>,
+) -> Result<Json<Dashboard>> {
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let libraries = state.library_service.list().await?;
+    let max_age_rating = AgeRatingService::max_for_user(&state.pool, auth_user.user_id).await?;
+    let feed_query = ContentFeedQuery {
+        library_id: None,
+        page: None,
+        page_size: Some(limit),
+        after: None,
+    };
+    let recently_added =
+        ContentService::list_feed(&state.pool, ContentFeed::Added, &feed_query, max_age_rating)
+            .await?
+            .items;
+    let mut continue_reading = state
+        .progress_service
+        .get_recent_contents(auth_user.user_id, limit)
+        .await?;
+    continue_reading.retain(|c| age_rating_allowed(max_age_rating, c.age_rating));
+
+    let mut active_tasks = state.scan_queue_service.list_processing().await;
+    active_tasks.extend(state.scan_queue_service.list_pending().await);
+
+    let is_admin = state
+        .auth_service
+        .get_user(auth_user.user_id)
+        .await?
+        .is_some_and(|user| user.is_admin);
+    let errors = if is_admin {
+        let mut errors = DashboardRepository::count_errors(&state.pool).await?;
+        let failed = ScanTaskHistoryQuery {
+            status: Some(TaskStatus::Failed),
+            page_size: Some(1),
+            ..ScanTaskHistoryQuery::default()
+        };
+        errors.failed_tasks = state.scan_queue_service.history_page(&failed).await.total as i64;
+        Some(errors)
+    } else {
+        None
+    };
+
+    Ok(Json(Dashboard {
+        libraries,
+        recently_added,
+        continue_reading,
+        active_tasks,
+        errors,
+    }))
+}
@@ -0,0 +1,336 @@
+//! Extension API handlers.
+//!
+//! This module provides a compact JSON API for a dedicated Tachiyomi/Mihon
+//! extension, versioned under `/api/ext/v1` so it can change without
+//! breaking installed extensions:
+//! - GET /api/ext/v1 - Get the API version and the filters it accepts
+//! - GET /api/ext/v1/series - List series with pagination and filters
+//! - GET /api/ext/v1/series/{id} - Get a series
+//! - GET /api/ext/v1/series/{id}/chapters - List the chapters of a series
+//! - GET /api/ext/v1/chapters/{id}/pages - List the page URLs of a chapter
+
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, ErrorCode, Result};
+use crate::handlers::komga::{apply_metadata_edits, extract_bangumi_metadata};
+use crate::middlewares::AuthUser;
+use crate::models::{Content, ContentFilter, ContentSort, MetadataEdits};
+use crate::repository::content::{ChapterRepository, ContentRepository};
+use crate::repository::library::LibraryRepository;
+use crate::repository::metadata_edits::MetadataEditsRepository;
+use crate::repository::progress::ProgressRepository;
+use crate::services::age_rating::AgeRatingService;
+use crate::services::content::ContentService;
+use crate::state::AppState;
+use crate::t;
+
+/// Version of the extension API, raised on breaking changes.
+pub const EXT_API_VERSION: u32 = 1;
+
+/// Default number of series per page.
+const DEFAULT_PAGE_SIZE: i64 = 20;
+
+/// Largest number of series per page.
+const MAX_PAGE_SIZE: i64 = 100;
+
+// --- DTOs ---
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtLibraryDto {
+    pub id: i64,
+    pub name: String,
+}
+
+/// Response for GET /api/ext/v1.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtInfoResponse {
+    /// Version of the extension API.
+    pub api_version: u32,
+    /// Server version.
+    pub server_version: String,
+    /// Libraries to filter series by.
+    pub libraries: Vec<ExtLibraryDto>,
+    /// Accepted values of `sort`.
+    pub sorts: Vec<String>,
+    /// Largest accepted `page_size`.
+    pub max_page_size: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtSeriesDto {
+    pub id: i64,
+    pub library_id: i64,
+    pub title: String,
+    /// Path of the thumbnail, which can be cached until it changes.
+    pub thumbnail_url: Option<String>,
+    pub description: String,
+    pub authors: Vec<String>,
+    pub genres: Vec<String>,
+    pub chapter_count: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A page of series.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtSeriesPage {
+    pub items: Vec<ExtSeriesDto>,
+    pub total: i64,
+    /// 1-based page number.
+    pub page: i64,
+    pub page_size: i64,
+    pub has_next: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtChapterDto {
+    pub id: i64,
+    pub title: String,
+    /// Chapter number, else volume, else position in the series.
+    pub number: f32,
+    pub volume: Option<i32>,
+    /// File extension, or `folder` for folders of images.
+    pub file_type: String,
+    pub page_count: i32,
+    /// When the chapter was added to the server.
+    pub uploaded_at: DateTime<Utc>,
+    /// Whether the current user finished the chapter.
+    pub read: bool,
+    /// Last page the current user reached (0-based), if they started it.
+    pub last_page_read: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtPageDto {
+    /// 0-based page index.
+    pub index: i64,
+    /// Path of the page image.
+    pub url: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+// --- Query Parameters ---
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ExtSeriesQuery {
+    /// Only series whose title contains this text.
+    pub q: Option<String>,
+    /// Only series in this library.
+    pub library_id: Option<i64>,
+    /// Only series by an author of this name, ignoring case.
+    pub author: Option<String>,
+    /// Only series scored at least this much out of 10.
+    pub min_rating: Option<f64>,
+    #[serde(default)]
+    pub sort: ContentSort,
+    /// 1-based page number (default 1).
+    pub page: Option<i64>,
+    /// Series per page (default 20, max 100).
+    pub page_size: Option<i64>,
+}
+
+// --- Handlers ---
+
+/// GET /api/ext/v1
+///
+/// Returns the version of the extension API and the values its filters
+/// accept, so the extension can check compatibility and build its filters.
+pub async fn get_info(State(state): State<AppState>) -> Result<Json<ExtInfoResponse>> {
+    let libraries = LibraryRepository::list(&state.pool)
+        .await?
+        .into_iter()
+        .map(|l| ExtLibraryDto {
+            id: l.id,
+            name: l.name,
+        })
+        .collect();
+
+    Ok(Json(ExtInfoResponse {
+        api_version: EXT_API_VERSION,
+        server_version: env!("RYURI_VERSION").to_string(),
+        libraries,
+        sorts: ["title", "added", "updated", "rating", "user_rating"]
+            .map(String::from)
+            .to_vec(),
+        max_page_size: MAX_PAGE_SIZE,
+    }))
+}
+
+/// GET /api/ext/v1/series
+///
+/// Lists series, by title unless `sort=added|updated` asks for the newest
+/// first or `sort=rating|user_rating` for the best rated by their metadata
+/// or by users, optionally only those in `library_id`, whose title contains
+/// `q`, by `author` or scored at least `min_rating`. Series above the
+/// current user's age rating cap are left out.
+pub async fn list_series(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<ExtSeriesQuery>,
+) -> Result<Json<ExtSeriesPage>> {
+    let pool = &state.pool;
+    let page = query.page.unwrap_or(1).max(1);
+    let page_size = query
+        .page_size
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    let filter = ContentFilter {
+        library_id: query.library_id,
+        max_age_rating: AgeRatingService::max_for_user(pool, auth_user.user_id).await?,
+        search: query.q.as_deref().map(str::trim).filter(|q| !q.is_empty()),
+        author: query
+            .author
+            .as_deref()
+            .map(str::trim)
+            .filter(|a| !a.is_empty()),
+        min_rating: query.min_rating,
+    };
+
+    let contents = ContentRepository::list_matching(
+        pool,
+        &filter,
+        query.sort,
+        page_size,
+        (page - 1) * page_size,
+    )
+    .await?;
+    let total = ContentRepository::count_matching(pool, &filter).await?;
+
+    let edits = MetadataEditsRepository::list(pool).await?;
+    let items = contents.iter().map(|c| series_dto(c, &edits)).collect();
+
+    Ok(Json(ExtSeriesPage {
+        items,
+        total,
+        page,
+        page_size,
+        has_next: page * page_size < total,
+    }))
+}
+
+/// GET /api/ext/v1/series/{id}
+///
+/// Returns a series with its description, authors and genres.
+pub async fn get_series(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<Json<ExtSeriesDto>> {
+    let content = ContentService::get_content(&state.pool, id).await?;
+    AgeRatingService::check(&state.pool, auth_user.user_id, &content).await?;
+    let edits = MetadataEditsRepository::find(&state.pool, id)
+        .await?
+        .map(|e| HashMap::from([(id, e)]))
+        .unwrap_or_default();
+    Ok(Json(series_dto(&content, &edits)))
+}
+
+/// GET /api/ext/v1/series/{id}/chapters
+///
+/// Lists the chapters of a series, latest first as Tachiyomi shows them,
+/// with the current user's read markers.
+pub async fn list_chapters(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<ExtChapterDto>>> {
+    let pool = &state.pool;
+    let content = ContentService::get_content(pool, id).await?;
+    AgeRatingService::check(pool, auth_user.user_id, &content).await?;
+    let chapters = ChapterRepository::list_by_content(pool, id).await?;
+    let progress: HashMap<i64, (f32, i32)> =
+        ProgressRepository::find_by_user_and_content(pool, auth_user.user_id, id)
+            .await?
+            .into_iter()
+            .map(|p| (p.chapter_id, (p.percentage, p.position)))
+            .collect();
+
+    let items = chapters
+        .into_iter()
+        .rev()
+        .map(|chapter| {
+            let progress = progress.get(&chapter.id);
+            ExtChapterDto {
+                id: chapter.id,
+                number: chapter.number_sort(),
+                volume: chapter.volume,
+                page_count: chapter.page_count,
+                uploaded_at: chapter.created_at.unwrap_or(content.created_at),
+                read: progress.is_some_and(|(percentage, _)| *percentage >= 100.0),
+                last_page_read: progress.map(|(_, position)| *position),
+                title: chapter.title,
+                file_type: chapter.file_type,
+            }
+        })
+        .collect();
+
+    Ok(Json(items))
+}
+
+/// GET /api/ext/v1/chapters/{id}/pages
+///
+/// Lists the pages of a comic chapter with the path of each image.
+pub async fn list_pages(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<ExtPageDto>>> {
+    let chapter = ChapterRepository::find_by_id(&state.pool, id)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(t!("content.chapter_not_found", id = id).to_string())
+                .with_code(ErrorCode::ChapterNotFound)
+        })?;
+    AgeRatingService::check_id(&state.pool, auth_user.user_id, chapter.content_id).await?;
+    let pages =
+        ContentService::list_page_info(&state.pool, chapter.content_id, id, false, false).await?;
+
+    Ok(Json(
+        pages
+            .into_iter()
+            .map(|page| ExtPageDto {
+                url: format!(
+                    "/api/contents/{}/chapters/{}/pages/{}",
+                    chapter.content_id, id, page.index
+                ),
+                index: page.index,
+                width: page.width,
+                height: page.height,
+            })
+            .collect(),
+    ))
+}
+
+// --- Helpers ---
+
+fn series_dto(content: &Content, edits: &HashMap<i64, MetadataEdits>) -> ExtSeriesDto {
+    let mut meta = extract_bangumi_metadata(&content.metadata);
+    if let Some(edits) = edits.get(&content.id) {
+        apply_metadata_edits(&mut meta, edits);
+    }
+
+    ExtSeriesDto {
+        id: content.id,
+        library_id: content.library_id,
+        title: content.title.clone(),
+        thumbnail_url: content.thumbnail.as_ref().map(|_| {
+            let url = format!("/api/contents/{}/thumbnail", content.id);
+            match &content.thumbnail_etag {
+                Some(etag) => format!("{url}?v={etag}"),
+                None => url,
+            }
+        }),
+        description: meta.summary,
+        authors: meta.authors.into_iter().map(|a| a.name).collect(),
+        genres: meta.tags,
+        chapter_count: content.chapter_count,
+        updated_at: content.updated_at,
+    }
+}
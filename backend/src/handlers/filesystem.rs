@@ -1,118 +1,85 @@
-use axum::{extract::Query, Json};
-use serde::{Deserialize, Serialize};
-use std::path::Path;
-use std::fs;
-use rust_i18n::t;
-use crate::error::{Result, AppError};
+//! Filesystem handlers.
+//!
+//! This module provides the HTTP handler of the directory picker for scan paths:
+//! - GET /api/filesystem - List the entries of a server directory
+
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use serde::Deserialize;
+
+use crate::error::Result;
+use crate::middlewares::auth::AdminUser;
+use crate::models::DirectoryEntry;
+use crate::services::filesystem::ListOptions;
+use crate::state::AppState;
 
 #[derive(Debug, Deserialize)]
 pub struct ListDirectoriesQuery {
     path: Option<String>,
+    /// Include hidden entries.
+    #[serde(default)]
+    hidden: bool,
+    /// Include files, not only directories.
+    #[serde(default)]
+    files: bool,
 }
 
-#[derive(Debug, Serialize)]
-pub struct DirectoryEntry {
-    name: String,
-    path: String,
-    parent: Option<String>,
-}
-
+/// GET /api/filesystem
+///
+/// Lists the directories in `path`, and its files with `files=true`. Without
+/// a path, lists the allowed roots, or the top of the filesystem when no
+/// roots are configured. Paths outside the roots are rejected with 403.
+/// Requires administrator rights.
 pub async fn list_directories(
+    State(state): State<AppState>,
+    _admin: AdminUser,
     Query(query): Query<ListDirectoriesQuery>,
 ) -> Result<Json<Vec<DirectoryEntry>>> {
-    let path_str = query.path.unwrap_or_default();
-    
-    // Windows logic: if path is empty, list drives
-    if cfg!(target_os = "windows") && path_str.is_empty() {
-        let mut drives = Vec::new();
-        for c in b'A'..=b'Z' {
-            let drive_root = format!("{}:\\", c as char);
-            if Path::new(&drive_root).exists() {
-                drives.push(DirectoryEntry {
-                    name: drive_root.clone(),
-                    path: drive_root,
-                    parent: None,
-                });
-            }
-        }
-        return Ok(Json(drives));
-    }
-
-    // Determine the path to list
-    // If path string is empty (and not Windows root case handled above), assume root "/"
-    // This primarily handles the Unix case where empty string -> root
-    let path = if path_str.is_empty() {
-        Path::new("/")
-    } else {
-        Path::new(&path_str)
+    let options = ListOptions {
+        hidden: query.hidden,
+        files: query.files,
     };
-
-    if !path.exists() {
-        return Err(AppError::NotFound(t!("filesystem.path_not_found", path = path.display()).to_string()));
-    }
-
-    // Check if it's a directory
-    if !path.is_dir() {
-        return Err(AppError::BadRequest(t!("filesystem.path_not_dir", path = path.display()).to_string()));
-    }
-
-    let parent = path.parent().map(|p| p.to_string_lossy().to_string());
-
-    let mut dirs = Vec::new();
-
-    match fs::read_dir(path) {
-        Ok(entries) => {
-            for entry in entries.flatten() {
-                if let Ok(file_type) = entry.file_type()
-                    && file_type.is_dir() {
-                        let name = entry.file_name().to_string_lossy().to_string();
-                        dirs.push(DirectoryEntry {
-                            name,
-                            path: entry.path().to_string_lossy().to_string(),
-                            parent: parent.clone(),
-                        });
-                    }
-            }
-        }
-        Err(e) => {
-             // If we can't read the directory (permission denied, etc.), just return error
-             return Err(AppError::FileSystem(e));
-        }
-    }
-    
-    // Sort by name case-insensitively for better UX
-    dirs.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-
-    Ok(Json(dirs))
+    let entries = state
+        .filesystem_service
+        .list(query.path.as_deref(), options)?;
+    Ok(Json(entries))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
-    use std::fs;
-    use tempfile::tempdir;
+    use axum::{Router, body::Body, http::Request, http::StatusCode, routing::get};
+    use tower::ServiceExt;
 
-    #[tokio::test]
-    async fn test_list_directories() {
-        let dir = tempdir().unwrap();
-        let dir_path = dir.path();
-        
-        // Create subdirectories
-        fs::create_dir(dir_path.join("sub1")).unwrap();
-        fs::create_dir(dir_path.join("sub2")).unwrap();
-        fs::write(dir_path.join("file.txt"), "content").unwrap();
+    use super::*;
+    use crate::middlewares::auth::AuthUser;
+    use crate::state::AppConfig;
+    use crate::test_fixtures::{create_test_admin, create_test_db, create_test_user};
 
-        let query = ListDirectoriesQuery {
-            path: Some(dir_path.to_string_lossy().to_string()),
-        };
+    async fn list_as(state: &AppState, user_id: i64) -> StatusCode {
+        let app = Router::new()
+            .route("/api/filesystem", get(list_directories))
+            .with_state(state.clone());
+        let request = Request::builder()
+            .uri("/api/filesystem")
+            .extension(AuthUser {
+                user_id,
+                username: format!("user{user_id}"),
+            })
+            .body(Body::empty())
+            .unwrap();
+        app.oneshot(request).await.unwrap().status()
+    }
 
-        let result = list_directories(Query(query)).await.unwrap();
-        let entries = result.0;
+    #[tokio::test]
+    async fn test_listing_requires_admin() {
+        let pool = create_test_db().await;
+        create_test_admin(&pool, 1, "admin").await;
+        create_test_user(&pool, 2, "reader").await;
+        let state = AppState::new(pool, AppConfig::default());
 
-        assert_eq!(entries.len(), 2);
-        assert!(entries.iter().any(|e| e.name == "sub1"));
-        assert!(entries.iter().any(|e| e.name == "sub2"));
-        // Ensure files are ignored
-        assert!(!entries.iter().any(|e| e.name == "file.txt"));
+        assert_eq!(list_as(&state, 1).await, StatusCode::OK);
+        assert_eq!(list_as(&state, 2).await, StatusCode::FORBIDDEN);
     }
 }
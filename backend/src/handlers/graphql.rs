@@ -0,0 +1,29 @@
+//! GraphQL handler.
+//!
+//! This module provides the HTTP handler of the GraphQL API, built with the
+//! `graphql` cargo feature:
+//! - POST /api/graphql - Execute a GraphQL query, see [`crate::graphql`]
+
+use axum::{Json, extract::State};
+
+use crate::error::Result;
+use crate::graphql::{SCHEMA, prepare_request};
+use crate::middlewares::auth::AuthUser;
+use crate::services::age_rating::AgeRatingService;
+use crate::state::AppState;
+
+/// POST /api/graphql
+///
+/// Executes a GraphQL query for the current user. Errors inside the query
+/// are reported in the `errors` field of the response, as GraphQL clients
+/// expect.
+pub async fn execute(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(request): Json<async_graphql::Request>,
+) -> Result<Json<async_graphql::Response>> {
+    let locale = state.auth_service.user_locale(auth_user.user_id).await?;
+    let max_age_rating = AgeRatingService::max_for_user(&state.pool, auth_user.user_id).await?;
+    let request = prepare_request(request, &state, auth_user.user_id, locale, max_age_rating);
+    Ok(Json(SCHEMA.execute(request).await))
+}
@@ -0,0 +1,81 @@
+//! Import handlers.
+//!
+//! This module provides endpoints importing data from other readers and
+//! servers:
+//! - POST /api/import/tachiyomi - Import reading progress from a Tachiyomi backup
+//! - POST /api/import/{source} - Import a Komga or Kavita database (admin only)
+
+use axum::{
+    Json,
+    body::Bytes,
+    extract::{Path, Query, State},
+};
+
+use crate::error::{AppError, Result};
+use crate::middlewares::{
+    auth::{AdminUser, AuthUser},
+    client_ip::ClientIp,
+};
+use crate::models::{
+    AuditAction, ImportSource, NewAuditLogEntry, ServerImportQuery, ServerImportResult,
+    TachiyomiImportQuery, TachiyomiImportResult,
+};
+use crate::services::server_import::ServerImportService;
+use crate::services::tachiyomi::TachiyomiService;
+use crate::state::AppState;
+use crate::t;
+
+/// POST /api/import/tachiyomi
+///
+/// Imports the read chapters of a Tachiyomi or Mihon backup, sent as the
+/// request body, as the current user's progress. Entries are matched to
+/// contents by title or alternative title; `dry_run=true` only reports the
+/// matches and what would be imported.
+pub async fn import_tachiyomi(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<TachiyomiImportQuery>,
+    body: Bytes,
+) -> Result<Json<TachiyomiImportResult>> {
+    let result =
+        TachiyomiService::import(&state.pool, auth_user.user_id, &body, query.dry_run).await?;
+    Ok(Json(result))
+}
+
+/// POST /api/import/{source}
+///
+/// Imports the database file of a Komga (`database.sqlite`) or Kavita
+/// (`kavita.db`) server, sent as the request body: series metadata, the
+/// progress of users with the same name here, and collections as series
+/// groups. `dry_run=true` only reports what would be imported.
+pub async fn import_server(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    ClientIp(ip): ClientIp,
+    Path(source): Path<String>,
+    Query(query): Query<ServerImportQuery>,
+    body: Bytes,
+) -> Result<Json<ServerImportResult>> {
+    let source = ImportSource::from_name(&source).ok_or_else(|| {
+        AppError::NotFound(t!("server_import.unknown_source", source = source).to_string())
+    })?;
+    let result = ServerImportService::import(&state.pool, source, &body, query.dry_run).await?;
+
+    if !result.dry_run {
+        let entry = NewAuditLogEntry::new(AuditAction::ServerImported)
+            .user(admin.user_id, &admin.username)
+            .target("import", source.as_str())
+            .ip(ip)
+            .details(format!(
+                "matched={} unmatched={} metadata={} progress_imported={} grouped={}",
+                result.matched_series,
+                result.unmatched_series.len(),
+                result.metadata_imported,
+                result.progress_imported,
+                result.contents_grouped
+            ));
+        state.audit_service.record(entry).await;
+    }
+
+    Ok(Json(result))
+}
@@ -0,0 +1,86 @@
+//! Background job handlers.
+//!
+//! This module provides a single API over all background job kinds:
+//! - GET /api/jobs - List running, pending and recent jobs
+//! - GET /api/jobs/{id} - Get a job
+//! - POST /api/jobs/{id}/cancel - Cancel a pending or running job (admin)
+//! - POST /api/jobs/{id}/retry - Run a failed or cancelled job again (admin)
+//! - PUT /api/jobs/{id}/priority - Change the priority of a pending job (admin)
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use uuid::Uuid;
+
+use crate::error::Result;
+use crate::middlewares::auth::{AdminUser, AuthUser};
+use crate::models::{Job, JobListQuery, SetJobPriorityRequest};
+use crate::state::AppState;
+
+/// GET /api/jobs
+///
+/// Lists jobs of all kinds, optionally filtered by `kind` and `status`.
+pub async fn list_jobs(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<JobListQuery>,
+) -> Result<Json<Vec<Job>>> {
+    let locale = state.auth_service.user_locale(auth_user.user_id).await?;
+    Ok(Json(state.job_service.list(&query, &locale).await))
+}
+
+/// GET /api/jobs/{id}
+///
+/// Returns a single job.
+pub async fn get_job(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(job_id): Path<Uuid>,
+) -> Result<Json<Job>> {
+    let locale = state.auth_service.user_locale(auth_user.user_id).await?;
+    Ok(Json(state.job_service.get(job_id, &locale).await?))
+}
+
+/// POST /api/jobs/{id}/cancel
+///
+/// Cancels a pending or running job and returns it.
+pub async fn cancel_job(
+    State(state): State<AppState>,
+    AdminUser(auth_user): AdminUser,
+    Path(job_id): Path<Uuid>,
+) -> Result<Json<Job>> {
+    state.job_service.cancel(job_id).await?;
+
+    let locale = state.auth_service.user_locale(auth_user.user_id).await?;
+    Ok(Json(state.job_service.get(job_id, &locale).await?))
+}
+
+/// POST /api/jobs/{id}/retry
+///
+/// Queues a failed or cancelled job again and returns the new job.
+pub async fn retry_job(
+    State(state): State<AppState>,
+    AdminUser(auth_user): AdminUser,
+    Path(job_id): Path<Uuid>,
+) -> Result<Json<Job>> {
+    let new_job_id = state.job_service.retry(job_id).await?;
+
+    let locale = state.auth_service.user_locale(auth_user.user_id).await?;
+    Ok(Json(state.job_service.get(new_job_id, &locale).await?))
+}
+
+/// PUT /api/jobs/{id}/priority
+///
+/// Changes the priority of a pending job and returns it.
+pub async fn set_job_priority(
+    State(state): State<AppState>,
+    AdminUser(auth_user): AdminUser,
+    Path(job_id): Path<Uuid>,
+    Json(req): Json<SetJobPriorityRequest>,
+) -> Result<Json<Job>> {
+    state.job_service.set_priority(job_id, req.priority).await?;
+
+    let locale = state.auth_service.user_locale(auth_user.user_id).await?;
+    Ok(Json(state.job_service.get(job_id, &locale).await?))
+}
@@ -0,0 +1,771 @@
+//! Kobo sync handlers.
+//!
+//! This module implements the part of the Kobo store API that Kobo
+//! e-readers use to sync books and reading progress, so the EPUB chapters
+//! of the server can be read on the device. The store URL of the device is
+//! set to `{server}/kobo/{api_key}`:
+//! - GET /kobo/{key}/v1/initialization - Point the device at this server
+//! - POST /kobo/{key}/v1/auth/device - Authenticate the device
+//! - GET /kobo/{key}/v1/library/sync - Sync new books and reading states
+//! - GET /kobo/{key}/v1/library/{book}/metadata - Get the metadata of a book
+//! - GET /kobo/{key}/v1/library/{book}/state - Get the reading state of a book
+//! - PUT /kobo/{key}/v1/library/{book}/state - Update the reading state of a book
+//! - DELETE /kobo/{key}/v1/library/{book} - Remove a book from the device
+//! - GET /kobo/{key}/download/{book}/{format} - Download a book
+//! - GET /kobo/{key}/{image}/{width}/{height}/{quality}/{greyscale}/image.jpg - Get a cover
+//! - Any other /kobo/{key}/v1 request - Answered with an empty object
+
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Path, Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::error::{AppError, ErrorCode, Result};
+use crate::handlers::content::send_chapter;
+use crate::handlers::komga::{apply_metadata_edits, extract_bangumi_metadata};
+use crate::middlewares::AuthUser;
+use crate::models::{
+    Chapter, Content, KoboLocation, KoboReadingState, KoboSyncToken, MetadataEdits,
+};
+use crate::repository::kobo::KoboRepository;
+use crate::repository::metadata_edits::MetadataEditsRepository;
+use crate::services::age_rating::AgeRatingService;
+use crate::services::content::ContentService;
+use crate::services::download::{DownloadFormat, DownloadService};
+use crate::services::kobo::{KoboService, KoboStateUpdate};
+use crate::state::AppState;
+use crate::t;
+
+/// Header carrying the sync token from one sync to the next.
+static SYNC_TOKEN_HEADER: HeaderName = HeaderName::from_static("x-kobo-synctoken");
+
+/// Header telling the device that another sync is needed for the rest.
+static SYNC_HEADER: HeaderName = HeaderName::from_static("x-kobo-sync");
+
+/// Category and genre the Kobo store files imported books under.
+const IMPORTED_CATEGORY: &str = "00000000-0000-0000-0000-000000000001";
+
+/// Prefix of the UUIDs standing for chapter IDs.
+const BOOK_UUID_PREFIX: &str = "00000000-0000-0000-0000-";
+
+/// Prefix of the UUIDs standing for content IDs, as series.
+const SERIES_UUID_PREFIX: &str = "00000000-0000-0000-0001-";
+
+// --- DTOs ---
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ActivePeriodDto {
+    pub from: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BookEntitlementDto {
+    pub accessibility: String,
+    pub active_period: ActivePeriodDto,
+    pub created: String,
+    pub cross_revision_id: String,
+    pub id: String,
+    pub is_hidden_from_archive: bool,
+    pub is_locked: bool,
+    pub is_removed: bool,
+    pub last_modified: String,
+    pub origin_category: String,
+    pub revision_id: String,
+    pub status: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContributorRoleDto {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PriceDto {
+    pub currency_code: String,
+    pub total_amount: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DownloadUrlDto {
+    pub format: String,
+    pub size: i64,
+    pub url: String,
+    pub platform: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PublisherDto {
+    pub imprint: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SeriesDto {
+    pub name: String,
+    pub number: String,
+    pub number_float: f32,
+    pub id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BookMetadataDto {
+    pub categories: Vec<String>,
+    pub contributor_roles: Vec<ContributorRoleDto>,
+    pub contributors: Vec<String>,
+    pub cover_image_id: String,
+    pub cross_revision_id: String,
+    pub current_display_price: PriceDto,
+    pub description: String,
+    pub download_urls: Vec<DownloadUrlDto>,
+    pub entitlement_id: String,
+    pub external_ids: Vec<String>,
+    pub genre: String,
+    pub is_eligible_for_kobo_love: bool,
+    pub is_internet_archive: bool,
+    pub is_pre_order: bool,
+    pub is_social_enabled: bool,
+    pub language: String,
+    pub phonetic_pronunciations: HashMap<String, String>,
+    pub publication_date: Option<String>,
+    pub publisher: PublisherDto,
+    pub revision_id: String,
+    pub series: SeriesDto,
+    pub title: String,
+    pub work_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct StatusInfoDto {
+    pub last_modified: String,
+    /// `ReadyToRead`, `Reading` or `Finished`.
+    pub status: String,
+    pub times_started_reading: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct StatisticsDto {
+    pub last_modified: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LocationDto {
+    pub value: Option<String>,
+    #[serde(rename = "Type")]
+    pub kind: Option<String>,
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BookmarkDto {
+    pub last_modified: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress_percent: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<LocationDto>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ReadingStateDto {
+    pub entitlement_id: String,
+    pub created: String,
+    pub last_modified: String,
+    pub priority_timestamp: String,
+    pub status_info: StatusInfoDto,
+    pub statistics: StatisticsDto,
+    pub current_bookmark: BookmarkDto,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct EntitlementDto {
+    pub book_entitlement: BookEntitlementDto,
+    pub book_metadata: BookMetadataDto,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reading_state: Option<ReadingStateDto>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ChangedReadingStateDto {
+    pub reading_state: ReadingStateDto,
+}
+
+/// Entry of a library sync, named after its kind.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SyncItemDto {
+    NewEntitlement(EntitlementDto),
+    ChangedReadingState(ChangedReadingStateDto),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DeviceAuthRequest {
+    #[serde(default)]
+    pub user_key: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DeviceAuthResponse {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub token_type: String,
+    pub tracking_id: String,
+    pub user_key: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BookmarkUpdateDto {
+    pub progress_percent: Option<f32>,
+    pub location: Option<LocationDto>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct StatusInfoUpdateDto {
+    pub status: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ReadingStateUpdateDto {
+    #[serde(default)]
+    pub current_bookmark: Option<BookmarkUpdateDto>,
+    #[serde(default)]
+    pub status_info: Option<StatusInfoUpdateDto>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct UpdateReadingStatesRequest {
+    pub reading_states: Vec<ReadingStateUpdateDto>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct RequestResultDto {
+    pub result: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct UpdateResultDto {
+    pub entitlement_id: String,
+    pub current_bookmark_result: RequestResultDto,
+    pub statistics_result: RequestResultDto,
+    pub status_info_result: RequestResultDto,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct UpdateReadingStatesResponse {
+    pub request_result: String,
+    pub update_results: Vec<UpdateResultDto>,
+}
+
+// --- Path parameters ---
+
+#[derive(Debug, Deserialize)]
+pub struct KoboParams {
+    pub key: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KoboBookParams {
+    pub key: String,
+    pub book_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KoboDownloadParams {
+    pub key: String,
+    pub book_id: String,
+    pub format: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KoboCoverParams {
+    pub image_id: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+// --- Handlers ---
+
+/// GET /kobo/{key}/v1/initialization
+///
+/// Returns the URLs of the store resources, pointing the ones used for
+/// syncing at this server.
+pub async fn initialization(
+    Path(params): Path<KoboParams>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let base = kobo_url(&headers, &params.key);
+    let resources = json!({
+        "Resources": {
+            "image_host": base,
+            "image_url_template": format!("{base}/{{ImageId}}/{{Width}}/{{Height}}/false/image.jpg"),
+            "image_url_quality_template": format!(
+                "{base}/{{ImageId}}/{{Width}}/{{Height}}/{{Quality}}/{{IsGreyscale}}/image.jpg"
+            ),
+            "library_sync": format!("{base}/v1/library/sync"),
+            "library_items": format!("{base}/v1/user/library"),
+            "library_book": format!("{base}/v1/user/library/books/{{LibraryItemId}}"),
+            "library_metadata": format!("{base}/v1/library/{{Ids}}/metadata"),
+            "reading_state": format!("{base}/v1/library/{{Ids}}/state"),
+            "device_auth": format!("{base}/v1/auth/device"),
+            "device_refresh": format!("{base}/v1/auth/refresh"),
+            "user_profile": format!("{base}/v1/user/profile"),
+            "user_loyalty_benefits": format!("{base}/v1/user/loyalty/benefits"),
+            "user_wishlist": format!("{base}/v1/user/wishlist"),
+            "tags": format!("{base}/v1/library/tags"),
+        }
+    });
+
+    let mut response = Json(resources).into_response();
+    // An empty JSON object, which the device expects on initialization
+    response.headers_mut().insert(
+        HeaderName::from_static("x-kobo-apitoken"),
+        HeaderValue::from_static("e30="),
+    );
+    Ok(response)
+}
+
+/// POST /kobo/{key}/v1/auth/device
+///
+/// Hands the device tokens it sends back with its requests. The API key in
+/// the path is what authenticates it, so the tokens are not checked.
+pub async fn auth_device(Json(request): Json<DeviceAuthRequest>) -> Json<DeviceAuthResponse> {
+    Json(DeviceAuthResponse {
+        access_token: Uuid::new_v4().to_string(),
+        refresh_token: Uuid::new_v4().to_string(),
+        token_type: "Bearer".to_string(),
+        tracking_id: Uuid::new_v4().to_string(),
+        user_key: request.user_key,
+    })
+}
+
+/// GET /kobo/{key}/v1/library/sync
+///
+/// Returns the EPUB chapters added and the reading states changed since the
+/// sync token sent by the device, with the token for the next sync. Asks
+/// for another sync when more changes are left than fit in one.
+pub async fn library_sync(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(params): Path<KoboParams>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let token = headers
+        .get(&SYNC_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(KoboSyncToken::decode)
+        .unwrap_or_default();
+
+    let sync = KoboService::sync(&state.pool, auth_user.user_id, token).await?;
+    let base = kobo_url(&headers, &params.key);
+    let edits = MetadataEditsRepository::list(&state.pool).await?;
+
+    let mut states: HashMap<i64, KoboReadingState> =
+        sync.states.into_iter().map(|s| (s.chapter_id, s)).collect();
+    let mut items = Vec::new();
+    for (chapter, content) in &sync.books {
+        let edits = edits.get(&content.id).cloned().unwrap_or_default();
+        items.push(SyncItemDto::NewEntitlement(EntitlementDto {
+            book_entitlement: book_entitlement(chapter, content),
+            book_metadata: book_metadata(chapter, content, &edits, &base),
+            reading_state: states
+                .remove(&chapter.id)
+                .map(|s| reading_state(chapter.id, Some(&s))),
+        }));
+    }
+    let mut changed: Vec<KoboReadingState> = states.into_values().collect();
+    changed.sort_by(|a, b| a.updated_at.cmp(&b.updated_at));
+    items.extend(changed.iter().map(|s| {
+        SyncItemDto::ChangedReadingState(ChangedReadingStateDto {
+            reading_state: reading_state(s.chapter_id, Some(s)),
+        })
+    }));
+
+    let mut response = Json(items).into_response();
+    if let Ok(value) = HeaderValue::from_str(&sync.token.encode()) {
+        response
+            .headers_mut()
+            .insert(SYNC_TOKEN_HEADER.clone(), value);
+    }
+    if sync.more {
+        response
+            .headers_mut()
+            .insert(SYNC_HEADER.clone(), HeaderValue::from_static("continue"));
+    }
+    Ok(response)
+}
+
+/// GET /kobo/{key}/v1/library/{book}/metadata
+///
+/// Returns the metadata of a book, as sent with it on sync.
+pub async fn get_metadata(
+    State(state): State<AppState>,
+    Path(params): Path<KoboBookParams>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<BookMetadataDto>>> {
+    let chapter_id = parse_book_uuid(&params.book_id)?;
+    let (chapter, content) = KoboService::get_book(&state.pool, chapter_id).await?;
+    let edits = MetadataEditsRepository::find(&state.pool, content.id)
+        .await?
+        .unwrap_or_default();
+    let base = kobo_url(&headers, &params.key);
+    Ok(Json(vec![book_metadata(&chapter, &content, &edits, &base)]))
+}
+
+/// GET /kobo/{key}/v1/library/{book}/state
+///
+/// Returns the user's reading state of a book.
+pub async fn get_state(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(params): Path<KoboBookParams>,
+) -> Result<Json<Vec<ReadingStateDto>>> {
+    let chapter_id = parse_book_uuid(&params.book_id)?;
+    KoboService::get_book(&state.pool, chapter_id).await?;
+    let reading = KoboRepository::find_state(&state.pool, auth_user.user_id, chapter_id).await?;
+    Ok(Json(vec![reading_state(chapter_id, reading.as_ref())]))
+}
+
+/// PUT /kobo/{key}/v1/library/{book}/state
+///
+/// Stores the reading state sent by the device: the percentage read as the
+/// user's progress, and the device's location to send back on other
+/// devices.
+pub async fn update_state(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(params): Path<KoboBookParams>,
+    Json(request): Json<UpdateReadingStatesRequest>,
+) -> Result<Json<UpdateReadingStatesResponse>> {
+    let chapter_id = parse_book_uuid(&params.book_id)?;
+
+    for reading in request.reading_states {
+        let bookmark = reading.current_bookmark.unwrap_or_default();
+        let location = bookmark.location.unwrap_or_default();
+        let update = KoboStateUpdate {
+            percentage: bookmark.progress_percent,
+            finished: reading.status_info.is_some_and(|s| s.status == "Finished"),
+            location: KoboLocation {
+                value: location.value,
+                kind: location.kind,
+                source: location.source,
+            },
+        };
+        KoboService::update_state(
+            &state.pool,
+            &state.progress_service,
+            auth_user.user_id,
+            chapter_id,
+            update,
+        )
+        .await?;
+    }
+
+    let success = || RequestResultDto {
+        result: "Success".to_string(),
+    };
+    Ok(Json(UpdateReadingStatesResponse {
+        request_result: "Success".to_string(),
+        update_results: vec![UpdateResultDto {
+            entitlement_id: params.book_id,
+            current_bookmark_result: success(),
+            statistics_result: RequestResultDto {
+                result: "Ignored".to_string(),
+            },
+            status_info_result: success(),
+        }],
+    }))
+}
+
+/// DELETE /kobo/{key}/v1/library/{book}
+///
+/// Acknowledges a book removed on the device. Books stay on the server.
+pub async fn archive_book(Path(params): Path<KoboBookParams>) -> Result<StatusCode> {
+    parse_book_uuid(&params.book_id)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /kobo/{key}/download/{book}/{format}
+///
+/// Downloads the EPUB file of a book.
+pub async fn download(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(params): Path<KoboDownloadParams>,
+    request: Request,
+) -> Result<Response> {
+    let chapter_id = parse_book_uuid(&params.book_id)?;
+    if !params.format.eq_ignore_ascii_case("epub") {
+        return Err(AppError::NotFound(
+            t!("kobo.book_not_found", id = chapter_id).to_string(),
+        ));
+    }
+    let (chapter, content) = KoboService::get_book(&state.pool, chapter_id).await?;
+    AgeRatingService::check(&state.pool, auth_user.user_id, &content).await?;
+    let download = DownloadService::plan(&chapter, DownloadFormat::Original);
+    send_chapter(download, request).await
+}
+
+/// GET /kobo/{key}/{image}/{width}/{height}/{quality}/{greyscale}/image.jpg
+///
+/// Returns the cover of a book as a JPEG of the size the device asks for.
+/// The cover is the thumbnail of the book's content.
+pub async fn get_cover(
+    State(state): State<AppState>,
+    Path(params): Path<KoboCoverParams>,
+) -> Result<Response> {
+    let chapter_id = parse_book_uuid(&params.image_id)?;
+    let (_, content) = KoboService::get_book(&state.pool, chapter_id).await?;
+    let data = ContentService::thumbnail_jpeg(
+        &state.pool,
+        content.id,
+        params.width,
+        params.height,
+        state.settings_service.thumbnail_options().quality,
+    )
+    .await?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("image/jpeg"));
+    headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("max-age=86400"),
+    );
+    Ok((headers, data).into_response())
+}
+
+/// Any other request to /kobo/{key}/v1
+///
+/// Answers the store features this server does not have with an empty
+/// object, which the device accepts.
+pub async fn fallback() -> Json<Value> {
+    Json(json!({}))
+}
+
+// Helpers
+
+/// URL of the Kobo API for the device's key, on the host the device
+/// connected to.
+fn kobo_url(headers: &HeaderMap, key: &str) -> String {
+    let host = headers
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("localhost");
+    let scheme = headers
+        .get("x-forwarded-proto")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("http");
+    format!("{scheme}://{host}/kobo/{key}")
+}
+
+/// UUID standing for a chapter, as Kobo identifies books by UUID.
+fn book_uuid(chapter_id: i64) -> String {
+    format!("{BOOK_UUID_PREFIX}{chapter_id:012}")
+}
+
+/// Chapter ID of a UUID made by [`book_uuid`].
+fn parse_book_uuid(uuid: &str) -> Result<i64> {
+    uuid.strip_prefix(BOOK_UUID_PREFIX)
+        .and_then(|id| id.parse().ok())
+        .ok_or_else(|| {
+            AppError::NotFound(t!("kobo.book_not_found", id = uuid).to_string())
+                .with_code(ErrorCode::ChapterNotFound)
+        })
+}
+
+/// Timestamp in the format of the Kobo store.
+fn kobo_time(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+/// Timestamp of a stored reading state in the format of the Kobo store.
+fn stored_time(time: &str) -> String {
+    let time = DateTime::parse_from_rfc3339(time)
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now());
+    kobo_time(time)
+}
+
+/// Title of a book: the content's, with the chapter's when there are several.
+fn book_title(chapter: &Chapter, content: &Content) -> String {
+    if content.chapter_count > 1 {
+        format!("{} - {}", content.title, chapter.title)
+    } else {
+        content.title.clone()
+    }
+}
+
+fn book_entitlement(chapter: &Chapter, content: &Content) -> BookEntitlementDto {
+    let id = book_uuid(chapter.id);
+    BookEntitlementDto {
+        accessibility: "Full".to_string(),
+        active_period: ActivePeriodDto {
+            from: kobo_time(Utc::now()),
+        },
+        created: kobo_time(content.created_at),
+        cross_revision_id: id.clone(),
+        id: id.clone(),
+        is_hidden_from_archive: false,
+        is_locked: false,
+        is_removed: false,
+        last_modified: kobo_time(content.updated_at),
+        origin_category: "Imported".to_string(),
+        revision_id: id,
+        status: "Active".to_string(),
+    }
+}
+
+fn book_metadata(
+    chapter: &Chapter,
+    content: &Content,
+    edits: &MetadataEdits,
+    base: &str,
+) -> BookMetadataDto {
+    // Scraped metadata, with manual edits winning over it
+    let mut meta = extract_bangumi_metadata(&content.metadata);
+    apply_metadata_edits(&mut meta, edits);
+
+    let id = book_uuid(chapter.id);
+    let authors: Vec<String> = meta.authors.into_iter().map(|a| a.name).collect();
+    let number = chapter.number_sort();
+    BookMetadataDto {
+        categories: vec![IMPORTED_CATEGORY.to_string()],
+        contributor_roles: authors
+            .iter()
+            .map(|name| ContributorRoleDto { name: name.clone() })
+            .collect(),
+        contributors: authors,
+        cover_image_id: id.clone(),
+        cross_revision_id: id.clone(),
+        current_display_price: PriceDto {
+            currency_code: "USD".to_string(),
+            total_amount: 0,
+        },
+        description: meta.summary,
+        download_urls: vec![DownloadUrlDto {
+            format: "EPUB".to_string(),
+            size: chapter.size,
+            url: format!("{base}/download/{id}/epub"),
+            platform: "Generic".to_string(),
+        }],
+        entitlement_id: id.clone(),
+        external_ids: vec![],
+        genre: IMPORTED_CATEGORY.to_string(),
+        is_eligible_for_kobo_love: false,
+        is_internet_archive: false,
+        is_pre_order: false,
+        is_social_enabled: true,
+        language: meta.language,
+        phonetic_pronunciations: HashMap::new(),
+        publication_date: meta.release_date,
+        publisher: PublisherDto {
+            imprint: String::new(),
+            name: meta.publisher,
+        },
+        revision_id: id.clone(),
+        series: SeriesDto {
+            name: content.title.clone(),
+            number: number.to_string(),
+            number_float: number,
+            id: format!("{SERIES_UUID_PREFIX}{:012}", content.id),
+        },
+        title: book_title(chapter, content),
+        work_id: id,
+    }
+}
+
+/// Reading state of a book, or the state of an unread book without one.
+fn reading_state(chapter_id: i64, reading: Option<&KoboReadingState>) -> ReadingStateDto {
+    let modified = reading
+        .map(|r| stored_time(&r.updated_at))
+        .unwrap_or_else(|| kobo_time(Utc::now()));
+    let percentage = reading.map(|r| r.percentage);
+    let status = match percentage {
+        Some(p) if p >= 100.0 => "Finished",
+        Some(p) if p > 0.0 => "Reading",
+        _ => "ReadyToRead",
+    };
+    let location = reading
+        .map(|r| &r.location)
+        .filter(|l| l.value.is_some() || l.source.is_some())
+        .map(|l| LocationDto {
+            value: l.value.clone(),
+            kind: l.kind.clone(),
+            source: l.source.clone(),
+        });
+
+    ReadingStateDto {
+        entitlement_id: book_uuid(chapter_id),
+        created: modified.clone(),
+        last_modified: modified.clone(),
+        priority_timestamp: modified.clone(),
+        status_info: StatusInfoDto {
+            last_modified: modified.clone(),
+            status: status.to_string(),
+            times_started_reading: i32::from(status != "ReadyToRead"),
+        },
+        statistics: StatisticsDto {
+            last_modified: modified.clone(),
+        },
+        current_bookmark: BookmarkDto {
+            last_modified: modified,
+            progress_percent: percentage,
+            location,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_book_uuid_round_trip() {
+        let uuid = book_uuid(42);
+        assert_eq!(uuid, "00000000-0000-0000-0000-000000000042");
+        assert_eq!(parse_book_uuid(&uuid).unwrap(), 42);
+        assert!(parse_book_uuid("5f1f7a2c-0000-0000-0000-000000000042").is_err());
+    }
+
+    #[test]
+    fn test_sync_items_are_named_after_their_kind() {
+        let item = SyncItemDto::ChangedReadingState(ChangedReadingStateDto {
+            reading_state: reading_state(7, None),
+        });
+        let value = serde_json::to_value(&item).unwrap();
+        let state = &value["ChangedReadingState"]["ReadingState"];
+        assert_eq!(
+            state["EntitlementId"],
+            "00000000-0000-0000-0000-000000000007"
+        );
+        assert_eq!(state["StatusInfo"]["Status"], "ReadyToRead");
+    }
+}
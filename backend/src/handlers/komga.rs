@@ -1,22 +1,40 @@
 use axum::{
     Json,
-    extract::{Path, Query, State},
-    http::{HeaderMap, header},
+    extract::{Path, Query, Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
     response::{IntoResponse, Response},
 };
 use chrono::{DateTime, Utc};
-use rust_i18n::t;
 use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
 
+use crate::t;
 use crate::{
-    error::{AppError, Result},
-    extractors::ArchiveExtractor,
-    models::{Chapter, Content},
+    error::{AppError, ErrorCode, Result},
+    extractors::{ArchiveExtractor, UNKNOWN_MEDIA_TYPE, media_type_from_name},
+    handlers::content::{accept_header, send_chapter},
+    middlewares::AuthUser,
+    models::{
+        Chapter, ChapterReadCounts, ChapterStatus, Content, ContentAlias, ContentFeed,
+        DEFAULT_METADATA_LANGUAGE, MetadataAuthor, MetadataEdits, MetadataFields, PageQuery,
+        ReaderSettings, SeriesStatus, UpdateMetadataEditsRequest, thumbnail_etag,
+    },
     repository::{
+        alias::AliasRepository,
         content::{ChapterRepository, ContentRepository},
         library::LibraryRepository,
+        metadata_edits::MetadataEditsRepository,
+        progress::ProgressRepository,
+    },
+    services::{
+        age_rating::AgeRatingService,
+        content::ContentService,
+        download::{DownloadFormat, DownloadService},
+        metadata_edits::MetadataEditsService,
+        reader_settings::ReaderSettingsService,
     },
     state::AppState,
+    utils::{patch::nullable, thumbnail::Thumbnail},
 };
 
 // --- DTOs ---
@@ -76,6 +94,12 @@ pub struct SeriesDto {
     pub file_last_modified: DateTime<Utc>,
     #[serde(rename = "booksCount")]
     pub books_count: i32,
+    #[serde(rename = "booksReadCount")]
+    pub books_read_count: i64,
+    #[serde(rename = "booksUnreadCount")]
+    pub books_unread_count: i64,
+    #[serde(rename = "booksInProgressCount")]
+    pub books_in_progress_count: i64,
     pub metadata: SeriesMetadataDto,
     #[serde(rename = "booksMetadata")]
     pub books_metadata: SeriesBooksMetadataDto,
@@ -84,10 +108,14 @@ pub struct SeriesDto {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SeriesMetadataDto {
     pub status: String,
+    #[serde(rename = "statusLock")]
+    pub status_lock: bool,
     pub created: DateTime<Utc>,
     #[serde(rename = "lastModified")]
     pub last_modified: DateTime<Utc>,
     pub title: String,
+    #[serde(rename = "titleLock")]
+    pub title_lock: bool,
     #[serde(rename = "titleSort")]
     pub title_sort: String,
     pub summary: String,
@@ -115,6 +143,16 @@ pub struct SeriesMetadataDto {
     pub tags_lock: bool,
     #[serde(rename = "totalBookCount")]
     pub total_book_count: Option<i32>,
+    #[serde(rename = "alternateTitles")]
+    pub alternate_titles: Vec<AlternateTitleDto>,
+    #[serde(rename = "alternateTitlesLock")]
+    pub alternate_titles_lock: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlternateTitleDto {
+    pub label: String,
+    pub title: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -137,6 +175,81 @@ pub struct AuthorDto {
     pub role: String,
 }
 
+/// Body of a series metadata update. Missing fields are left as they are
+/// and `null` clears an edit.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesMetadataUpdateDto {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub title_lock: Option<bool>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub summary: Option<Option<String>>,
+    #[serde(default)]
+    pub summary_lock: Option<bool>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub status: Option<Option<String>>,
+    #[serde(default)]
+    pub status_lock: Option<bool>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub language: Option<Option<String>>,
+    #[serde(default)]
+    pub language_lock: Option<bool>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub tags: Option<Option<Vec<String>>>,
+    #[serde(default)]
+    pub tags_lock: Option<bool>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub authors: Option<Option<Vec<AuthorDto>>>,
+    #[serde(default)]
+    pub authors_lock: Option<bool>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub age_rating: Option<Option<i32>>,
+    #[serde(default)]
+    pub age_rating_lock: Option<bool>,
+}
+
+impl SeriesMetadataUpdateDto {
+    fn into_request(self) -> Result<UpdateMetadataEditsRequest> {
+        let status = match self.status {
+            Some(Some(name)) => Some(Some(SeriesStatus::from_komga_name(&name).ok_or_else(
+                || AppError::BadRequest(t!("metadata.invalid_status", status = name).to_string()),
+            )?)),
+            Some(None) => Some(None),
+            None => None,
+        };
+        let authors = self.authors.map(|authors| {
+            authors.map(|authors| {
+                authors
+                    .into_iter()
+                    .map(|author| MetadataAuthor {
+                        name: author.name,
+                        role: author.role,
+                    })
+                    .collect()
+            })
+        });
+
+        Ok(UpdateMetadataEditsRequest {
+            title: self.title,
+            title_lock: self.title_lock,
+            summary: self.summary,
+            summary_lock: self.summary_lock,
+            status,
+            status_lock: self.status_lock,
+            language: self.language,
+            language_lock: self.language_lock,
+            tags: self.tags,
+            tags_lock: self.tags_lock,
+            authors,
+            authors_lock: self.authors_lock,
+            age_rating: self.age_rating,
+            age_rating_lock: self.age_rating_lock,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BookDto {
     pub id: String,
@@ -160,6 +273,7 @@ pub struct BookDto {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MediaDto {
+    /// `READY`, `ERROR` or `UNSUPPORTED`.
     pub status: String,
     #[serde(rename = "mediaType")]
     pub media_type: String,
@@ -169,6 +283,8 @@ pub struct MediaDto {
     pub media_profile: String,
     #[serde(rename = "epubDivinaCompatible")]
     pub epub_divina_compatible: bool,
+    /// Why the file cannot be read, empty when it is ready.
+    pub comment: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -239,6 +355,7 @@ pub struct BookSearchQuery {
 
 pub async fn get_series_list(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Query(query): Query<SeriesSearchQuery>,
 ) -> Result<Json<PageWrapperDto<SeriesDto>>> {
     let pool = &state.pool;
@@ -256,12 +373,20 @@ pub async fn get_series_list(
         }
         all_content
     };
+    let contents = AgeRatingService::filter_contents(pool, auth_user.user_id, contents).await?;
 
-    // Filter by search
+    // Filter by search, on the title or an alternative title
     let filtered_contents: Vec<Content> = if let Some(search) = &query.search {
+        let ids: Vec<i64> = contents.iter().map(|c| c.id).collect();
+        let aliases = AliasRepository::list_by_contents(pool, &ids).await?;
         contents
             .into_iter()
-            .filter(|c| c.title.contains(search))
+            .filter(|c| {
+                c.title.contains(search)
+                    || aliases
+                        .get(&c.id)
+                        .is_some_and(|a| a.iter().any(|alias| alias.title.contains(search)))
+            })
             .collect()
     } else {
         contents
@@ -281,10 +406,7 @@ pub async fn get_series_list(
         Vec::new()
     };
 
-    let series_dtos: Vec<SeriesDto> = paged_contents
-        .into_iter()
-        .map(content_to_series_dto)
-        .collect();
+    let series_dtos = contents_to_series_dtos(pool, auth_user.user_id, paged_contents).await?;
 
     Ok(Json(PageWrapperDto::new(
         series_dtos,
@@ -294,8 +416,59 @@ pub async fn get_series_list(
     )))
 }
 
+/// Series newest first by when they were added.
+pub async fn get_series_new(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<SeriesSearchQuery>,
+) -> Result<Json<PageWrapperDto<SeriesDto>>> {
+    series_feed(&state, auth_user.user_id, ContentFeed::Added, query).await
+}
+
+/// Series newest first by when they last changed.
+pub async fn get_series_latest(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<SeriesSearchQuery>,
+) -> Result<Json<PageWrapperDto<SeriesDto>>> {
+    series_feed(&state, auth_user.user_id, ContentFeed::Updated, query).await
+}
+
+async fn series_feed(
+    state: &AppState,
+    user_id: i64,
+    feed: ContentFeed,
+    query: SeriesSearchQuery,
+) -> Result<Json<PageWrapperDto<SeriesDto>>> {
+    let pool = &state.pool;
+    let page = query.page.unwrap_or(0);
+    let size = query.size.unwrap_or(20).clamp(1, 100);
+    let library_id = query.library_id.and_then(|id| id.parse::<i64>().ok());
+    let max_age_rating = AgeRatingService::max_for_user(pool, user_id).await?;
+
+    let contents = ContentRepository::list_feed(
+        pool,
+        feed,
+        library_id,
+        max_age_rating,
+        size as i64,
+        (page * size) as i64,
+    )
+    .await?;
+    let total_elements = ContentRepository::count_visible(pool, library_id, max_age_rating).await?;
+
+    let series_dtos = contents_to_series_dtos(pool, user_id, contents).await?;
+    Ok(Json(PageWrapperDto::new(
+        series_dtos,
+        page,
+        size,
+        total_elements as usize,
+    )))
+}
+
 pub async fn get_series(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Path(series_id): Path<i64>,
 ) -> Result<Json<SeriesDto>> {
     let pool = &state.pool;
@@ -303,28 +476,49 @@ pub async fn get_series(
         .await?
         .ok_or_else(|| {
             AppError::NotFound(t!("komga.series_not_found", id = series_id).to_string())
+                .with_code(ErrorCode::ContentNotFound)
         })?;
+    AgeRatingService::check(pool, auth_user.user_id, &content).await?;
 
-    Ok(Json(content_to_series_dto(content)))
+    let mut series = contents_to_series_dtos(pool, auth_user.user_id, vec![content]).await?;
+    Ok(Json(series.remove(0)))
+}
+
+/// Edit series metadata by hand. Edited fields are locked unless the body
+/// unlocks them, so rescrapes keep them.
+pub async fn patch_series_metadata(
+    State(state): State<AppState>,
+    Path(series_id): Path<i64>,
+    Json(update): Json<SeriesMetadataUpdateDto>,
+) -> Result<StatusCode> {
+    MetadataEditsService::update(&state.pool, series_id, update.into_request()?).await?;
+    Ok(StatusCode::NO_CONTENT)
 }
 
 pub async fn get_series_thumbnail(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Path(series_id): Path<i64>,
+    request_headers: HeaderMap,
 ) -> Result<Response> {
     let pool = &state.pool;
     let content = ContentRepository::find_by_id(pool, series_id)
         .await?
         .ok_or_else(|| {
             AppError::NotFound(t!("komga.series_not_found", id = series_id).to_string())
+                .with_code(ErrorCode::ContentNotFound)
         })?;
+    AgeRatingService::check(pool, auth_user.user_id, &content).await?;
 
     if let Some(thumb) = content.thumbnail {
+        let thumb =
+            negotiate_thumbnail(&state, thumb, content.thumbnail_etag, &request_headers).await?;
         let mut headers = HeaderMap::new();
-        headers.insert(header::CONTENT_TYPE, "image/jpeg".parse().unwrap());
+        headers.insert(header::CONTENT_TYPE, thumb.mime_type.parse().unwrap());
+        headers.insert(header::VARY, "Accept".parse().unwrap());
         // Simple cache control
         headers.insert(header::CACHE_CONTROL, "max-age=86400".parse().unwrap());
-        Ok((headers, thumb).into_response())
+        Ok((headers, thumb.data).into_response())
     } else {
         Err(AppError::NotFound(
             t!("komga.thumbnail_not_found").to_string(),
@@ -334,6 +528,7 @@ pub async fn get_series_thumbnail(
 
 pub async fn get_books(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Path(series_id): Path<i64>,
     Query(query): Query<BookSearchQuery>,
 ) -> Result<Json<PageWrapperDto<BookDto>>> {
@@ -344,7 +539,9 @@ pub async fn get_books(
         .await?
         .ok_or_else(|| {
             AppError::NotFound(t!("komga.series_not_found", id = series_id).to_string())
+                .with_code(ErrorCode::ContentNotFound)
         })?;
+    AgeRatingService::check(pool, auth_user.user_id, &content).await?;
 
     let chapters = ChapterRepository::list_by_content(pool, series_id).await?;
 
@@ -383,39 +580,57 @@ pub async fn get_books(
 
 pub async fn get_book(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Path(book_id): Path<i64>,
 ) -> Result<Json<BookDto>> {
     let pool = &state.pool;
     let chapter = ChapterRepository::find_by_id(pool, book_id)
         .await?
-        .ok_or_else(|| AppError::NotFound(t!("komga.book_not_found", id = book_id).to_string()))?;
+        .ok_or_else(|| {
+            AppError::NotFound(t!("komga.book_not_found", id = book_id).to_string())
+                .with_code(ErrorCode::ChapterNotFound)
+        })?;
 
     let content = ContentRepository::find_by_id(pool, chapter.content_id)
         .await?
         .ok_or_else(|| {
             AppError::NotFound(t!("komga.content_for_book_not_found", id = book_id).to_string())
+                .with_code(ErrorCode::ContentNotFound)
         })?;
+    AgeRatingService::check(pool, auth_user.user_id, &content).await?;
 
     Ok(Json(chapter_to_book_dto(chapter, &content)))
 }
 
 pub async fn get_book_thumbnail(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Path(book_id): Path<i64>,
+    request_headers: HeaderMap,
 ) -> Result<Response> {
     let pool = &state.pool;
     let chapter = ChapterRepository::find_by_id(pool, book_id)
         .await?
-        .ok_or_else(|| AppError::NotFound(t!("komga.book_not_found", id = book_id).to_string()))?;
+        .ok_or_else(|| {
+            AppError::NotFound(t!("komga.book_not_found", id = book_id).to_string())
+                .with_code(ErrorCode::ChapterNotFound)
+        })?;
 
     let content = ContentRepository::find_by_id(pool, chapter.content_id)
         .await?
-        .ok_or_else(|| AppError::NotFound(t!("komga.content_not_found").to_string()))?;
+        .ok_or_else(|| {
+            AppError::NotFound(t!("komga.content_not_found").to_string())
+                .with_code(ErrorCode::ContentNotFound)
+        })?;
+    AgeRatingService::check(pool, auth_user.user_id, &content).await?;
 
     if let Some(thumb) = content.thumbnail {
+        let thumb =
+            negotiate_thumbnail(&state, thumb, content.thumbnail_etag, &request_headers).await?;
         let mut headers = HeaderMap::new();
-        headers.insert(header::CONTENT_TYPE, "image/jpeg".parse().unwrap());
-        Ok((headers, thumb).into_response())
+        headers.insert(header::CONTENT_TYPE, thumb.mime_type.parse().unwrap());
+        headers.insert(header::VARY, "Accept".parse().unwrap());
+        Ok((headers, thumb.data).into_response())
     } else {
         Err(AppError::NotFound(
             t!("komga.thumbnail_not_found").to_string(),
@@ -423,14 +638,55 @@ pub async fn get_book_thumbnail(
     }
 }
 
+/// Convert a stored thumbnail to a format the client accepts.
+async fn negotiate_thumbnail(
+    state: &AppState,
+    thumbnail: Vec<u8>,
+    etag: Option<String>,
+    request_headers: &HeaderMap,
+) -> Result<Thumbnail> {
+    let etag = etag.unwrap_or_else(|| thumbnail_etag(&thumbnail));
+    ContentService::negotiate_thumbnail(
+        thumbnail,
+        etag,
+        accept_header(request_headers),
+        state.settings_service.thumbnail_options().quality,
+    )
+    .await
+}
+
+/// Download the book file as stored; image folders are sent as CBZ.
+pub async fn get_book_file(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(book_id): Path<i64>,
+    request: Request,
+) -> Result<Response> {
+    let chapter = ChapterRepository::find_by_id(&state.pool, book_id)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(t!("komga.book_not_found", id = book_id).to_string())
+                .with_code(ErrorCode::ChapterNotFound)
+        })?;
+    AgeRatingService::check_id(&state.pool, auth_user.user_id, chapter.content_id).await?;
+
+    let download = DownloadService::plan(&chapter, DownloadFormat::Original);
+    send_chapter(download, request).await
+}
+
 pub async fn get_page_list(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Path(book_id): Path<i64>,
 ) -> Result<Json<Vec<PageDto>>> {
     let pool = &state.pool;
     let chapter = ChapterRepository::find_by_id(pool, book_id)
         .await?
-        .ok_or_else(|| AppError::NotFound(t!("komga.book_not_found", id = book_id).to_string()))?;
+        .ok_or_else(|| {
+            AppError::NotFound(t!("komga.book_not_found", id = book_id).to_string())
+                .with_code(ErrorCode::ChapterNotFound)
+        })?;
+    AgeRatingService::check_id(pool, auth_user.user_id, chapter.content_id).await?;
 
     use std::path::Path;
     let archive_path = Path::new(&chapter.file_path);
@@ -444,7 +700,9 @@ pub async fn get_page_list(
                 pages.push(PageDto {
                     number: (i + 1) as i32,
                     file_name: name.clone(),
-                    media_type: "image/jpeg".to_string(), // Can guess from extension, but jpeg is safe default for list
+                    media_type: media_type_from_name(name)
+                        .unwrap_or(UNKNOWN_MEDIA_TYPE)
+                        .to_string(),
                 });
             }
         }
@@ -465,6 +723,7 @@ pub async fn get_page_list(
 
 pub async fn get_page(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Path((book_id, page_number)): Path<(i64, i32)>,
 ) -> Result<Response> {
     // Reuse existing logic from content handler if possible
@@ -485,35 +744,42 @@ pub async fn get_page(
     let pool = &state.pool;
     let chapter = ChapterRepository::find_by_id(pool, book_id)
         .await?
-        .ok_or_else(|| AppError::NotFound(t!("komga.book_not_found", id = book_id).to_string()))?;
+        .ok_or_else(|| {
+            AppError::NotFound(t!("komga.book_not_found", id = book_id).to_string())
+                .with_code(ErrorCode::ChapterNotFound)
+        })?;
+    AgeRatingService::check_id(pool, auth_user.user_id, chapter.content_id).await?;
 
     if page_number < 1 {
         return Err(AppError::BadRequest(
             t!("komga.page_must_be_positive").to_string(),
         ));
     }
-    let page_index = (page_number - 1) as usize;
-
-    use std::path::Path;
-    let archive_path = Path::new(&chapter.file_path);
-
-    // We need to list files to get the name at index.
-    let images = ArchiveExtractor::list_files(archive_path)?;
-
-    if page_index >= images.len() {
-        return Err(AppError::NotFound(
-            t!("komga.page_not_found", page = page_number).to_string(),
-        ));
-    }
-
-    let image_name = &images[page_index];
-    let image_data = ArchiveExtractor::extract_file(archive_path, image_name)?;
+    let page_index = (page_number - 1) as i64;
+
+    // Broken pages are served as placeholders so readers can skip past them,
+    // and margins are trimmed if the reader settings ask for it
+    let page = ContentService::get_requested_page(
+        pool,
+        chapter.content_id,
+        book_id,
+        page_index,
+        PageQuery::default(),
+    )
+    .await?;
 
     let mut headers = HeaderMap::new();
-    headers.insert(header::CONTENT_TYPE, "image/jpeg".parse().unwrap());
-    headers.insert(header::CACHE_CONTROL, "max-age=86400".parse().unwrap());
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(page.media_type),
+    );
+    if page.is_placeholder {
+        headers.insert(header::CACHE_CONTROL, "no-store".parse().unwrap());
+    } else {
+        headers.insert(header::CACHE_CONTROL, "max-age=86400".parse().unwrap());
+    }
 
-    Ok((headers, image_data).into_response())
+    Ok((headers, page.data).into_response())
 }
 
 // Libraries
@@ -535,9 +801,41 @@ pub async fn get_libraries(State(state): State<AppState>) -> Result<Json<Vec<Lib
 
 // Helpers
 
-fn content_to_series_dto(content: Content) -> SeriesDto {
-    // Extract metadata fields from Bangumi JSON if available
-    let meta = extract_bangumi_metadata(&content.metadata);
+/// Convert contents to series, with the reader settings in effect for each
+/// and how many of their books the user has read.
+async fn contents_to_series_dtos(
+    pool: &Pool<Sqlite>,
+    user_id: i64,
+    contents: Vec<Content>,
+) -> Result<Vec<SeriesDto>> {
+    let settings = ReaderSettingsService::resolve_for_contents(pool, &contents).await?;
+    let edits = MetadataEditsRepository::list(pool).await?;
+    let ids: Vec<i64> = contents.iter().map(|c| c.id).collect();
+    let counts = ProgressRepository::count_by_contents(pool, user_id, &ids).await?;
+    let mut aliases = AliasRepository::list_by_contents(pool, &ids).await?;
+    Ok(contents
+        .into_iter()
+        .map(|content| {
+            let settings = settings.get(&content.id).copied().unwrap_or_default();
+            let edits = edits.get(&content.id).cloned().unwrap_or_default();
+            let counts = counts.get(&content.id).copied().unwrap_or_default();
+            let aliases = aliases.remove(&content.id).unwrap_or_default();
+            content_to_series_dto(content, &settings, &edits, &counts, aliases)
+        })
+        .collect())
+}
+
+fn content_to_series_dto(
+    content: Content,
+    settings: &ReaderSettings,
+    edits: &MetadataEdits,
+    counts: &ChapterReadCounts,
+    aliases: Vec<ContentAlias>,
+) -> SeriesDto {
+    // Extract metadata fields from Bangumi JSON if available, then let
+    // manual edits win over them
+    let mut meta = extract_bangumi_metadata(&content.metadata);
+    apply_metadata_edits(&mut meta, edits);
 
     SeriesDto {
         id: content.id.to_string(),
@@ -547,27 +845,44 @@ fn content_to_series_dto(content: Content) -> SeriesDto {
         last_modified: Some(content.updated_at),
         file_last_modified: content.updated_at, // approximation
         books_count: content.chapter_count,
+        books_read_count: counts.read,
+        books_unread_count: counts.unread,
+        books_in_progress_count: counts.in_progress,
         metadata: SeriesMetadataDto {
-            status: "ONGOING".to_string(), // Default
+            status: edits
+                .status
+                .map(|status| status.komga_name())
+                .unwrap_or_else(|| "ONGOING".to_string()),
+            status_lock: edits.status_lock,
             created: content.created_at,
             last_modified: content.updated_at,
             title: content.title.clone(),
+            title_lock: edits.title_lock,
             title_sort: content.title.clone(),
             summary: meta.summary.clone(),
-            summary_lock: false,
-            reading_direction: "RIGHT_TO_LEFT".to_string(), // Manga default
+            summary_lock: edits.summary_lock,
+            reading_direction: settings.komga_reading_direction().to_string(),
             reading_direction_lock: false,
             publisher: meta.publisher.clone(),
             publisher_lock: false,
-            age_rating: None,
-            age_rating_lock: false,
+            age_rating: content.age_rating,
+            age_rating_lock: edits.age_rating_lock,
             language: meta.language.clone(),
-            language_lock: false,
+            language_lock: edits.language_lock,
             genres: vec![],
             genres_lock: false,
             tags: meta.tags.clone(),
-            tags_lock: false,
+            tags_lock: edits.tags_lock,
             total_book_count: Some(content.chapter_count),
+            alternate_titles: aliases
+                .into_iter()
+                .filter(|alias| alias.title != content.title)
+                .map(|alias| AlternateTitleDto {
+                    label: alias.source.as_str().to_string(),
+                    title: alias.title,
+                })
+                .collect(),
+            alternate_titles_lock: false,
         },
         books_metadata: SeriesBooksMetadataDto {
             authors: meta.authors,
@@ -581,14 +896,36 @@ fn content_to_series_dto(content: Content) -> SeriesDto {
     }
 }
 
+/// Replace scraped values with the ones edited by hand.
+pub(crate) fn apply_metadata_edits(meta: &mut BangumiMetadata, edits: &MetadataEdits) {
+    if let Some(summary) = &edits.summary {
+        meta.summary = summary.clone();
+    }
+    if let Some(language) = &edits.language {
+        meta.language = language.clone();
+    }
+    if let Some(tags) = &edits.tags {
+        meta.tags = tags.clone();
+    }
+    if let Some(authors) = &edits.authors {
+        meta.authors = authors
+            .iter()
+            .map(|author| AuthorDto {
+                name: author.name.clone(),
+                role: author.role.clone(),
+            })
+            .collect();
+    }
+}
+
 /// Extracted metadata from Bangumi API JSON
-struct BangumiMetadata {
-    summary: String,
-    tags: Vec<String>,
-    authors: Vec<AuthorDto>,
-    publisher: String,
-    release_date: Option<String>,
-    language: String,
+pub(crate) struct BangumiMetadata {
+    pub(crate) summary: String,
+    pub(crate) tags: Vec<String>,
+    pub(crate) authors: Vec<AuthorDto>,
+    pub(crate) publisher: String,
+    pub(crate) release_date: Option<String>,
+    pub(crate) language: String,
 }
 
 impl Default for BangumiMetadata {
@@ -599,13 +936,13 @@ impl Default for BangumiMetadata {
             authors: vec![],
             publisher: String::new(),
             release_date: None,
-            language: "ja".to_string(),
+            language: DEFAULT_METADATA_LANGUAGE.to_string(),
         }
     }
 }
 
 /// Extract metadata from Bangumi API JSON blob
-fn extract_bangumi_metadata(metadata: &Option<Vec<u8>>) -> BangumiMetadata {
+pub(crate) fn extract_bangumi_metadata(metadata: &Option<Vec<u8>>) -> BangumiMetadata {
     let Some(bytes) = metadata else {
         return BangumiMetadata::default();
     };
@@ -614,12 +951,7 @@ fn extract_bangumi_metadata(metadata: &Option<Vec<u8>>) -> BangumiMetadata {
         return BangumiMetadata::default();
     };
 
-    // Extract summary
-    let summary = meta
-        .get("summary")
-        .and_then(|v| v.as_str())
-        .unwrap_or("")
-        .to_string();
+    let fields = MetadataFields::from_metadata(&meta);
 
     // Extract tags (top tags by count)
     let tags: Vec<String> = meta
@@ -634,57 +966,22 @@ fn extract_bangumi_metadata(metadata: &Option<Vec<u8>>) -> BangumiMetadata {
         })
         .unwrap_or_default();
 
-    // Extract author from infobox
-    let authors: Vec<AuthorDto> = meta
-        .get("infobox")
-        .and_then(|v| v.as_array())
-        .map(|arr| {
-            arr.iter()
-                .filter(|item| {
-                    item.get("key")
-                        .and_then(|k| k.as_str())
-                        .map(|k| k == "作者")
-                        .unwrap_or(false)
-                })
-                .filter_map(|item| item.get("value").and_then(|v| v.as_str()))
-                .map(|name| AuthorDto {
-                    name: name.to_string(),
-                    role: "writer".to_string(),
-                })
-                .collect()
-        })
-        .unwrap_or_default();
-
-    // Extract publisher from infobox
-    let publisher = meta
-        .get("infobox")
-        .and_then(|v| v.as_array())
-        .and_then(|arr| {
-            arr.iter()
-                .find(|item| {
-                    item.get("key")
-                        .and_then(|k| k.as_str())
-                        .map(|k| k == "出版社")
-                        .unwrap_or(false)
-                })
-                .and_then(|item| item.get("value").and_then(|v| v.as_str()))
-        })
-        .unwrap_or("")
-        .to_string();
-
-    // Extract release date
-    let release_date = meta.get("date").and_then(|v| v.as_str()).map(String::from);
-
-    // Default to Japanese for Bangumi content
-    let language = "ja".to_string();
-
     BangumiMetadata {
-        summary,
+        summary: fields.summary.unwrap_or_default(),
         tags,
-        authors,
-        publisher,
-        release_date,
-        language,
+        authors: fields
+            .authors
+            .into_iter()
+            .map(|author| AuthorDto {
+                name: author.name,
+                role: author.role,
+            })
+            .collect(),
+        publisher: fields.publisher.unwrap_or_default(),
+        release_date: fields.release_date,
+        language: fields
+            .language
+            .unwrap_or_else(|| DEFAULT_METADATA_LANGUAGE.to_string()),
     }
 }
 
@@ -706,23 +1003,43 @@ fn format_size(size: i64) -> String {
     }
 }
 
+/// Chapter number as shown by Komga clients: `12`, or `12.5` for extras.
+fn format_number(number: f32) -> String {
+    if number.fract() == 0.0 {
+        format!("{}", number as i64)
+    } else {
+        number.to_string()
+    }
+}
+
 fn chapter_to_book_dto(chapter: Chapter, content: &Content) -> BookDto {
     BookDto {
         id: chapter.id.to_string(),
         series_id: content.id.to_string(),
         series_title: content.title.clone(),
         name: chapter.title.clone(),
-        number: chapter.sort_order as f32, // approximation
-        created: None,                     // Chapter doesn't have created_at in struct
-        last_modified: None,
-        file_last_modified: Utc::now(), // default
+        // Komga's book number is the position in the series
+        number: (chapter.sort_order + 1) as f32,
+        created: chapter.created_at,
+        last_modified: chapter.updated_at,
+        file_last_modified: chapter.updated_at.unwrap_or_else(Utc::now),
         size_bytes: chapter.size,
         size: format_size(chapter.size),
         media: MediaDto {
-            status: "READY".to_string(),
+            status: match chapter.status {
+                ChapterStatus::Ready => "READY",
+                ChapterStatus::Error => "ERROR",
+                ChapterStatus::Unsupported => "UNSUPPORTED",
+            }
+            .to_string(),
             media_type: match chapter.file_type.as_str() {
                 "epub" => "application/epub+zip".to_string(),
                 "pdf" => "application/pdf".to_string(),
+                "mobi" | "azw" => "application/x-mobipocket-ebook".to_string(),
+                "azw3" => "application/vnd.amazon.ebook".to_string(),
+                "txt" => "text/plain".to_string(),
+                "m4b" | "m4a" => "audio/mp4".to_string(),
+                "mp3" => "audio/mpeg".to_string(),
                 _ => "application/zip".to_string(),
             },
             pages_count: chapter.page_count,
@@ -731,15 +1048,16 @@ fn chapter_to_book_dto(chapter: Chapter, content: &Content) -> BookDto {
                 _ => "DIVINA".to_string(),
             },
             epub_divina_compatible: false,
+            comment: chapter.error.clone().unwrap_or_default(),
         },
         metadata: BookMetadataDto {
             title: chapter.title.clone(),
             title_lock: false,
             summary: "".to_string(),
             summary_lock: false,
-            number: chapter.sort_order.to_string(),
+            number: format_number(chapter.number_sort()),
             number_lock: false,
-            number_sort: chapter.sort_order as f32,
+            number_sort: chapter.number_sort(),
             number_sort_lock: false,
             release_date: None,
             release_date_lock: false,
@@ -0,0 +1,124 @@
+//! KOReader sync handlers.
+//!
+//! This module implements the kosync protocol of KOReader's progress sync
+//! plugin, whose custom sync server is set to `{server}/koreader`. Devices
+//! log in with the username and an API key as the password:
+//! - POST /koreader/users/create - Register a device's credentials
+//! - GET /koreader/users/auth - Check a device's credentials
+//! - PUT /koreader/syncs/progress - Push the position reached in a document
+//! - GET /koreader/syncs/progress/{document} - Pull the latest position in a document
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::error::{AppError, Result};
+use crate::middlewares::AuthUser;
+use crate::models::UpdateKoreaderProgress;
+use crate::services::koreader::KoreaderService;
+use crate::state::AppState;
+use crate::t;
+
+// --- DTOs ---
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateUserRequest {
+    pub username: String,
+    /// MD5 of the password.
+    pub password: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateUserResponse {
+    pub username: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthorizedResponse {
+    /// Always `OK`.
+    pub authorized: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateProgressResponse {
+    pub document: String,
+    /// When the position was stored, as a Unix timestamp.
+    pub timestamp: i64,
+}
+
+// --- Handlers ---
+
+/// POST /koreader/users/create
+///
+/// Users are created in Ryuri, so registering only succeeds with the
+/// credentials of an existing user, which lets devices log in with either
+/// button of the plugin.
+pub async fn create_user(
+    State(state): State<AppState>,
+    Json(request): Json<CreateUserRequest>,
+) -> Result<(StatusCode, Json<CreateUserResponse>)> {
+    if !state.settings_service.koreader_enabled() {
+        return Err(AppError::NotFound(t!("koreader.disabled").to_string()));
+    }
+
+    let user =
+        KoreaderService::authenticate(&state.pool, &request.username, &request.password).await?;
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateUserResponse {
+            username: user.username,
+        }),
+    ))
+}
+
+/// GET /koreader/users/auth
+///
+/// Confirms the credentials checked by the middleware.
+pub async fn authorize() -> Json<AuthorizedResponse> {
+    Json(AuthorizedResponse {
+        authorized: "OK".to_string(),
+    })
+}
+
+/// PUT /koreader/syncs/progress
+///
+/// Stores the position a device reached in a document, and as the user's
+/// progress on the chapter whose file it is.
+pub async fn update_progress(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(request): Json<UpdateKoreaderProgress>,
+) -> Result<Json<UpdateProgressResponse>> {
+    let progress = KoreaderService::update_progress(
+        &state.pool,
+        &state.progress_service,
+        auth_user.user_id,
+        request,
+    )
+    .await?;
+
+    Ok(Json(UpdateProgressResponse {
+        document: progress.document,
+        timestamp: progress.updated_at.timestamp(),
+    }))
+}
+
+/// GET /koreader/syncs/progress/{document}
+///
+/// Returns the latest position in a document, or an empty object when it
+/// was never read.
+pub async fn get_progress(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(document): Path<String>,
+) -> Result<Json<Value>> {
+    let progress = KoreaderService::get_progress(&state.pool, auth_user.user_id, &document).await?;
+    Ok(Json(match progress {
+        Some(progress) => json!(progress),
+        None => json!({}),
+    }))
+}
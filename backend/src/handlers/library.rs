@@ -5,24 +5,40 @@
 //! - POST /api/libraries - Create a new library
 //! - GET /api/libraries/{id} - Get a library by ID
 //! - PUT /api/libraries/{id} - Update a library
-//! - DELETE /api/libraries/{id} - Delete a library
+//! - DELETE /api/libraries/{id} - Delete a library in the background
 //! - GET /api/libraries/{id}/paths - List scan paths for a library
 //! - POST /api/libraries/{id}/paths - Add a scan path to a library
 //! - DELETE /api/libraries/{id}/paths/{path_id} - Remove a scan path from a library
+//! - GET /api/libraries/{id}/ignore-patterns - List ignore patterns for a library
+//! - PUT /api/libraries/{id}/ignore-patterns - Replace ignore patterns for a library
+//! - GET /api/libraries/{id}/metadata-providers - List metadata providers for a library
+//! - PUT /api/libraries/{id}/metadata-providers - Replace metadata providers for a library
+//! - GET /api/libraries/{id}/export - Export contents, metadata and progress as JSON
+//! - POST /api/libraries/{id}/import - Import an export into a library
 
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
 };
 use serde::Deserialize;
 use tracing::warn;
-use rust_i18n::t;
 
-use crate::error::Result;
+use crate::error::{AppError, ErrorCode, Result};
+use crate::handlers::scan_queue::SubmitScanResponse;
+use crate::middlewares::{
+    auth::{AdminUser, AuthUser},
+    client_ip::ClientIp,
+};
 use crate::models::{
-    CreateLibraryRequest, Library, LibraryWithStats, ScanPath, UpdateLibraryRequest,
+    AuditAction, CreateLibraryRequest, IgnorePatterns, Library, LibraryExport, LibraryExportQuery,
+    LibraryImportResult, LibraryWithStats, MetadataProviders, NewAuditLogEntry, ScanPath,
+    TaskPriority, UpdateLibraryRequest,
 };
+use crate::services::export::ExportService;
 use crate::state::AppState;
+use crate::t;
 
 /// GET /api/libraries
 ///
@@ -75,6 +91,7 @@ pub async fn get(
         .await?
         .ok_or_else(|| {
             crate::error::AppError::NotFound(t!("library.not_found", id = library_id).to_string())
+                .with_code(ErrorCode::LibraryNotFound)
         })?;
     Ok(Json(library))
 }
@@ -95,7 +112,11 @@ pub async fn update(
     // Update scheduler if scan_interval changed
     if let Some(interval) = new_scan_interval {
         if interval > 0 {
-            if let Err(e) = state.scheduler_service.schedule_scan(library_id, interval).await {
+            if let Err(e) = state
+                .scheduler_service
+                .schedule_scan(library_id, interval)
+                .await
+            {
                 warn!(library_id = library_id, error = %e, "{}", t!("library.update_schedule_failed"));
             }
         } else if let Err(e) = state.scheduler_service.cancel_scan(library_id).await {
@@ -119,8 +140,17 @@ pub async fn update(
 
 /// DELETE /api/libraries/{id}
 ///
-/// Deletes a library and all associated scan paths and contents.
-pub async fn delete(State(state): State<AppState>, Path(library_id): Path<i64>) -> Result<Json<()>> {
+/// Hides a library and submits a background task that deletes it with all
+/// associated scan paths and contents. Progress counts removed chapters and
+/// contents. Other tasks of the library are cancelled.
+pub async fn delete(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    ClientIp(ip): ClientIp,
+    Path(library_id): Path<i64>,
+) -> Result<Json<SubmitScanResponse>> {
+    state.library_service.mark_deleting(library_id).await?;
+
     // Stop scheduler before deleting
     if let Err(e) = state.scheduler_service.cancel_scan(library_id).await {
         warn!(library_id, error = %e, "{}", t!("library.cancel_scan_failed"));
@@ -131,8 +161,27 @@ pub async fn delete(State(state): State<AppState>, Path(library_id): Path<i64>)
         warn!(library_id, error = %e, "{}", t!("library.stop_watch_failed"));
     }
 
-    state.library_service.delete(library_id).await?;
-    Ok(Json(()))
+    state
+        .scan_queue_service
+        .cancel_library_tasks(library_id)
+        .await;
+    let task_id = state
+        .scan_queue_service
+        .submit_library_deletion_task(library_id, TaskPriority::Normal)
+        .await;
+
+    let entry = NewAuditLogEntry::new(AuditAction::LibraryDeleted)
+        .user(auth_user.user_id, &auth_user.username)
+        .target("library", library_id)
+        .ip(ip);
+    state.audit_service.record(entry).await;
+
+    let task = state
+        .scan_queue_service
+        .get_task(task_id)
+        .await
+        .ok_or_else(|| AppError::Internal("Failed to retrieve submitted task".to_string()))?;
+    Ok(Json(SubmitScanResponse { task_id, task }))
 }
 
 /// Request to add a scan path.
@@ -196,9 +245,121 @@ pub async fn remove_path(
         .await?;
 
     // Refresh watch service to remove the path
-    if let Err(e) = state.watch_service.refresh_watching(params.library_id).await {
+    if let Err(e) = state
+        .watch_service
+        .refresh_watching(params.library_id)
+        .await
+    {
         warn!(library_id = params.library_id, error = %e, "{}", t!("library.refresh_watch_failed"));
     }
 
     Ok(Json(()))
 }
+
+/// GET /api/libraries/{id}/ignore-patterns
+///
+/// Returns the name patterns of files and folders skipped when scanning a library.
+pub async fn list_ignore_patterns(
+    State(state): State<AppState>,
+    Path(library_id): Path<i64>,
+) -> Result<Json<IgnorePatterns>> {
+    let patterns = state
+        .library_service
+        .list_ignore_patterns(library_id)
+        .await?;
+    Ok(Json(IgnorePatterns { patterns }))
+}
+
+/// PUT /api/libraries/{id}/ignore-patterns
+///
+/// Replaces the ignore patterns of a library. They apply from the next scan;
+/// content in folders that become ignored is removed by that scan.
+pub async fn set_ignore_patterns(
+    State(state): State<AppState>,
+    Path(library_id): Path<i64>,
+    Json(req): Json<IgnorePatterns>,
+) -> Result<Json<IgnorePatterns>> {
+    let patterns = state
+        .library_service
+        .set_ignore_patterns(library_id, req.patterns)
+        .await?;
+    Ok(Json(IgnorePatterns { patterns }))
+}
+
+/// GET /api/libraries/{id}/metadata-providers
+///
+/// Returns the metadata providers of a library in the order they are tried
+/// when scraping new content.
+pub async fn list_metadata_providers(
+    State(state): State<AppState>,
+    Path(library_id): Path<i64>,
+) -> Result<Json<MetadataProviders>> {
+    let providers = state
+        .library_service
+        .list_metadata_providers(library_id)
+        .await?;
+    Ok(Json(MetadataProviders { providers }))
+}
+
+/// PUT /api/libraries/{id}/metadata-providers
+///
+/// Replaces the metadata providers of a library. An empty list disables
+/// scraping for new content.
+pub async fn set_metadata_providers(
+    State(state): State<AppState>,
+    Path(library_id): Path<i64>,
+    Json(req): Json<MetadataProviders>,
+) -> Result<Json<MetadataProviders>> {
+    let providers = state
+        .library_service
+        .set_metadata_providers(library_id, req.providers)
+        .await?;
+    Ok(Json(MetadataProviders { providers }))
+}
+
+/// GET /api/libraries/{id}/export
+///
+/// Downloads the contents of a library with their chapters and metadata as
+/// JSON. With `include_progress=true`, the reading progress of all users
+/// is included.
+pub async fn export(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Path(library_id): Path<i64>,
+    Query(query): Query<LibraryExportQuery>,
+) -> Result<Response> {
+    let export =
+        ExportService::export_library(&state.pool, library_id, query.include_progress).await?;
+
+    let disposition = format!("attachment; filename=\"library-{}.json\"", library_id);
+    Ok(([(header::CONTENT_DISPOSITION, disposition)], Json(export)).into_response())
+}
+
+/// POST /api/libraries/{id}/import
+///
+/// Imports an export from another server into a library that has already
+/// been scanned. Contents are matched by folder path, path relative to a
+/// scan path or first page; users are matched by name.
+pub async fn import(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    ClientIp(ip): ClientIp,
+    Path(library_id): Path<i64>,
+    Json(export): Json<LibraryExport>,
+) -> Result<Json<LibraryImportResult>> {
+    let result = ExportService::import_library(&state.pool, library_id, &export).await?;
+
+    let entry = NewAuditLogEntry::new(AuditAction::LibraryImported)
+        .user(admin.user_id, &admin.username)
+        .target("library", library_id)
+        .ip(ip)
+        .details(format!(
+            "matched={} unmatched={} progress_imported={}",
+            result.matched_contents,
+            result.unmatched_contents.len(),
+            result.progress_imported
+        ));
+    state.audit_service.record(entry).await;
+
+    Ok(Json(result))
+}
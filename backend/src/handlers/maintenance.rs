@@ -0,0 +1,191 @@
+//! Maintenance handlers.
+//!
+//! This module provides HTTP handlers for administrator-only maintenance endpoints:
+//! - GET /api/maintenance/duplicates - List likely duplicate contents
+//! - POST /api/maintenance/duplicates/merge - Merge duplicates into one content
+//! - GET /api/maintenance/media-errors - List chapters whose files cannot be read
+//! - POST /api/maintenance/page-counts - Count the pages of chapters that have none
+//! - POST /api/maintenance/metadata-refresh - Fetch the metadata of content again
+//! - POST /api/maintenance/backup - Back up the database in the background
+
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::handlers::scan_queue::SubmitScanResponse;
+use crate::middlewares::{auth::AdminUser, client_ip::ClientIp};
+use crate::models::{
+    AuditAction, DuplicateGroup, MediaError, MediaErrorQuery, MergeDuplicatesRequest,
+    MergeDuplicatesResponse, NewAuditLogEntry, TaskPriority,
+};
+use crate::services::content::ContentService;
+use crate::services::duplicate::DuplicateService;
+use crate::state::AppState;
+
+/// GET /api/maintenance/duplicates
+///
+/// Returns groups of contents, across scan paths and libraries, whose
+/// first pages are identical. The first call hashes one page per content
+/// and may take a while on large libraries; if it times out, calling again
+/// continues where it stopped.
+pub async fn list_duplicates(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+) -> Result<Json<Vec<DuplicateGroup>>> {
+    Ok(Json(DuplicateService::find_duplicates(&state.pool).await?))
+}
+
+/// POST /api/maintenance/duplicates/merge
+///
+/// Merges duplicates into the content to keep. Reading progress of all
+/// users moves to the kept content, and the merged contents are hidden.
+pub async fn merge_duplicates(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    ClientIp(ip): ClientIp,
+    Json(req): Json<MergeDuplicatesRequest>,
+) -> Result<Json<MergeDuplicatesResponse>> {
+    let response = DuplicateService::merge(&state.pool, req.keep_id, &req.merge_ids).await?;
+
+    let entry = NewAuditLogEntry::new(AuditAction::DuplicatesMerged)
+        .user(admin.user_id, &admin.username)
+        .target("content", req.keep_id)
+        .ip(ip)
+        .details(format!(
+            "merged={:?} progress_moved={}",
+            response.merged, response.progress_moved
+        ));
+    state.audit_service.record(entry).await;
+
+    Ok(Json(response))
+}
+
+/// GET /api/maintenance/media-errors
+///
+/// Returns the chapters, optionally of one library, whose file could not
+/// be read at the last scan or had pages fail to load, with the error.
+/// Rescanning after replacing a file clears its status.
+pub async fn list_media_errors(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Query(query): Query<MediaErrorQuery>,
+) -> Result<Json<Vec<MediaError>>> {
+    let errors = ContentService::list_media_errors(&state.pool, query.library_id).await?;
+    Ok(Json(errors))
+}
+
+/// Request body for counting missing page counts.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RecalculatePageCountsRequest {
+    /// The library to count; all libraries when unset.
+    #[serde(default)]
+    pub library_id: Option<i64>,
+}
+
+/// POST /api/maintenance/page-counts
+///
+/// Submits background tasks that count the pages of chapters that have
+/// none, e.g. because their file could not be read when it was scanned,
+/// one task per library. Progress counts chapters. Libraries that already
+/// have such a task active return that task.
+pub async fn recalculate_page_counts(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Json(req): Json<RecalculatePageCountsRequest>,
+) -> Result<Json<Vec<SubmitScanResponse>>> {
+    let mut submitted = Vec::new();
+    for library_id in library_ids(&state, req.library_id).await? {
+        // Normal priority, so manual scans run first
+        let task_id = state
+            .scan_queue_service
+            .submit_page_count_task(library_id, TaskPriority::Normal)
+            .await;
+        submitted.push(submitted_task(&state, task_id).await?);
+    }
+
+    Ok(Json(submitted))
+}
+
+/// Request body for refreshing metadata.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RefreshMetadataRequest {
+    /// The library to refresh; all libraries when unset.
+    #[serde(default)]
+    pub library_id: Option<i64>,
+}
+
+/// POST /api/maintenance/metadata-refresh
+///
+/// Submits background tasks that fetch the metadata of content again from
+/// its library's providers, one task per library. Content keeps its series
+/// at the provider it came from; locked manual edits are kept. Progress
+/// counts contents. Libraries that already have such a task active return
+/// that task.
+pub async fn refresh_metadata(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+    Json(req): Json<RefreshMetadataRequest>,
+) -> Result<Json<Vec<SubmitScanResponse>>> {
+    let mut submitted = Vec::new();
+    for library_id in library_ids(&state, req.library_id).await? {
+        // Low priority, as providers are queried for every content
+        let task_id = state
+            .scan_queue_service
+            .submit_metadata_refresh_task(library_id, TaskPriority::Low)
+            .await;
+        submitted.push(submitted_task(&state, task_id).await?);
+    }
+
+    Ok(Json(submitted))
+}
+
+/// POST /api/maintenance/backup
+///
+/// Submits a background task that writes a backup of the database, like
+/// the nightly backup. If a backup task is already active, returns that
+/// task. The task's result names the backup file.
+pub async fn submit_backup(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    ClientIp(ip): ClientIp,
+) -> Result<Json<SubmitScanResponse>> {
+    let task_id = state
+        .scan_queue_service
+        .submit_backup_task(TaskPriority::Normal)
+        .await;
+
+    let entry = NewAuditLogEntry::new(AuditAction::BackupCreated)
+        .user(admin.user_id, &admin.username)
+        .target("task", task_id)
+        .ip(ip);
+    state.audit_service.record(entry).await;
+
+    Ok(Json(submitted_task(&state, task_id).await?))
+}
+
+/// The given library, checked to exist, or all libraries.
+async fn library_ids(state: &AppState, library_id: Option<i64>) -> Result<Vec<i64>> {
+    Ok(match library_id {
+        Some(library_id) => vec![state.library_service.get_or_error(library_id).await?.id],
+        None => state
+            .library_service
+            .list()
+            .await?
+            .into_iter()
+            .map(|l| l.library.id)
+            .collect(),
+    })
+}
+
+async fn submitted_task(state: &AppState, task_id: Uuid) -> Result<SubmitScanResponse> {
+    let task = state
+        .scan_queue_service
+        .get_task(task_id)
+        .await
+        .ok_or_else(|| AppError::Internal("Failed to retrieve submitted task".to_string()))?;
+    Ok(SubmitScanResponse { task_id, task })
+}
@@ -0,0 +1,231 @@
+//! Metadata handlers.
+//!
+//! This module provides HTTP handlers for fixing content metadata by hand:
+//! - GET /api/metadata/search - Search metadata providers by title
+//! - GET /api/contents/{id}/metadata - Get the metadata edited by hand
+//! - PATCH /api/contents/{id}/metadata - Edit metadata by hand
+//! - POST /api/contents/{id}/metadata/match - Replace metadata with a series from a provider
+//! - GET /api/contents/{id}/aliases - List the alternative titles of a content
+//! - POST /api/contents/{id}/aliases - Add an alternative title
+//! - DELETE /api/contents/{id}/aliases/{alias_id} - Remove an alternative title
+//! - GET /api/metadata/failures - List content whose metadata could not be scraped
+//! - POST /api/metadata/failures/{id}/retry - Scrape a failed content again
+//! - DELETE /api/metadata/failures/{id} - Dismiss a failure
+//! - POST /api/metadata/bangumi/check-key - Check a Bangumi access token
+//!
+//! Searches, matches and retries started by a user scrape Bangumi with the
+//! access token saved in their profile, if they set one, instead of the
+//! server's. Matches and retries take `refresh_cache` to skip cached
+//! provider responses.
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+
+use crate::error::{AppError, Result};
+use crate::middlewares::auth::AuthUser;
+use crate::models::{
+    BangumiKeyCheck, BangumiKeyCheckRequest, ContentAlias, ContentResponse, CreateAliasRequest,
+    MetadataEdits, MetadataFailure, MetadataMatchRequest, MetadataProviderKind, MetadataRetryQuery,
+    MetadataRetryResult, MetadataSearchQuery, MetadataSearchResult, UpdateMetadataEditsRequest,
+};
+use crate::services::alias::AliasService;
+use crate::services::content::ContentService;
+use crate::services::metadata::{MetadataFailureService, ScrapeOptions};
+use crate::services::metadata_edits::MetadataEditsService;
+use crate::state::AppState;
+use crate::t;
+
+/// GET /api/metadata/search
+///
+/// Searches metadata providers by title, or only `provider` when given.
+/// Results of each provider are in its own relevance order.
+pub async fn search(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<MetadataSearchQuery>,
+) -> Result<Json<Vec<MetadataSearchResult>>> {
+    let providers = match query.provider {
+        Some(provider) => vec![provider],
+        None => MetadataProviderKind::ALL.to_vec(),
+    };
+    let api_key = user_api_key(&state, auth_user.user_id).await?;
+    let options = ScrapeOptions {
+        api_key: api_key.as_deref(),
+        refresh_cache: false,
+    };
+    let results = state
+        .metadata_manager
+        .search_as(&providers, &query.q, options)
+        .await?;
+    Ok(Json(results))
+}
+
+/// GET /api/contents/{id}/metadata
+///
+/// Returns the metadata of a content edited by hand, with the lock of each
+/// field. Fields without an edit show the scraped metadata.
+pub async fn get_edits(
+    State(state): State<AppState>,
+    Path(content_id): Path<i64>,
+) -> Result<Json<MetadataEdits>> {
+    Ok(Json(
+        MetadataEditsService::get(&state.pool, content_id).await?,
+    ))
+}
+
+/// PATCH /api/contents/{id}/metadata
+///
+/// Edits the title, summary, status, language, tags or authors of a
+/// content. Edited fields are locked unless the request unlocks them, so
+/// rescrapes keep them; `null` goes back to the scraped value.
+pub async fn update_edits(
+    State(state): State<AppState>,
+    Path(content_id): Path<i64>,
+    Json(req): Json<UpdateMetadataEditsRequest>,
+) -> Result<Json<MetadataEdits>> {
+    Ok(Json(
+        MetadataEditsService::update(&state.pool, content_id, req).await?,
+    ))
+}
+
+/// POST /api/contents/{id}/metadata/match
+///
+/// Fetches a series from a provider and overwrites the metadata of a
+/// content with it, for when automatic scraping picked the wrong series.
+/// The thumbnail is replaced by the series cover when it has one. With
+/// `refresh_cache` the provider is asked again even if it answered
+/// recently.
+pub async fn match_content(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(content_id): Path<i64>,
+    Json(req): Json<MetadataMatchRequest>,
+) -> Result<Json<ContentResponse>> {
+    // Verify content exists before asking the provider
+    ContentService::get_content(&state.pool, content_id).await?;
+
+    let api_key = user_api_key(&state, auth_user.user_id).await?;
+    let options = ScrapeOptions {
+        api_key: api_key.as_deref(),
+        refresh_cache: req.refresh_cache,
+    };
+    let metadata = state
+        .metadata_manager
+        .fetch_as(req.provider, &req.id, options)
+        .await?;
+    let content =
+        ContentService::update_content(&state.pool, content_id, None, Some(metadata)).await?;
+    Ok(Json(ContentResponse::from(content)))
+}
+
+/// GET /api/contents/{id}/aliases
+///
+/// Returns the alternative titles of a content: those scraped from its
+/// metadata and those added by hand.
+pub async fn list_aliases(
+    State(state): State<AppState>,
+    Path(content_id): Path<i64>,
+) -> Result<Json<Vec<ContentAlias>>> {
+    Ok(Json(AliasService::list(&state.pool, content_id).await?))
+}
+
+/// POST /api/contents/{id}/aliases
+///
+/// Adds an alternative title by hand. Titles added by hand are kept when
+/// the metadata changes.
+pub async fn add_alias(
+    State(state): State<AppState>,
+    Path(content_id): Path<i64>,
+    Json(req): Json<CreateAliasRequest>,
+) -> Result<(StatusCode, Json<ContentAlias>)> {
+    let alias = AliasService::add(&state.pool, content_id, req).await?;
+    Ok((StatusCode::CREATED, Json(alias)))
+}
+
+/// DELETE /api/contents/{id}/aliases/{alias_id}
+///
+/// Removes an alternative title. A title scraped from metadata comes back
+/// the next time the metadata is scraped.
+pub async fn delete_alias(
+    State(state): State<AppState>,
+    Path((content_id, alias_id)): Path<(i64, i64)>,
+) -> Result<StatusCode> {
+    AliasService::delete(&state.pool, content_id, alias_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/metadata/failures
+///
+/// Returns content whose metadata could not be scraped when it was
+/// imported, most recent first. A failure goes away once the content is
+/// retried successfully, matched by hand or dismissed.
+pub async fn list_failures(State(state): State<AppState>) -> Result<Json<Vec<MetadataFailure>>> {
+    Ok(Json(MetadataFailureService::list(&state.pool).await?))
+}
+
+/// POST /api/metadata/failures/{id}/retry
+///
+/// Scrapes the metadata of a failed content again with its library's
+/// providers. With `?refresh_cache=true` providers are asked again even if
+/// they answered recently.
+pub async fn retry_failure(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(failure_id): Path<i64>,
+    Query(query): Query<MetadataRetryQuery>,
+) -> Result<Json<MetadataRetryResult>> {
+    let api_key = user_api_key(&state, auth_user.user_id).await?;
+    let options = ScrapeOptions {
+        api_key: api_key.as_deref(),
+        refresh_cache: query.refresh_cache,
+    };
+    let result =
+        MetadataFailureService::retry(&state.pool, &state.metadata_manager, failure_id, options)
+            .await?;
+    Ok(Json(result))
+}
+
+/// DELETE /api/metadata/failures/{id}
+///
+/// Dismisses a failure, leaving the content without metadata.
+pub async fn dismiss_failure(
+    State(state): State<AppState>,
+    Path(failure_id): Path<i64>,
+) -> Result<StatusCode> {
+    MetadataFailureService::dismiss(&state.pool, failure_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/metadata/bangumi/check-key
+///
+/// Checks an access token with Bangumi, by default the one saved in the
+/// current user's profile, and returns the account it belongs to.
+pub async fn check_bangumi_key(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(req): Json<BangumiKeyCheckRequest>,
+) -> Result<Json<BangumiKeyCheck>> {
+    let api_key = match req.api_key.map(|key| key.trim().to_string()) {
+        Some(key) if !key.is_empty() => key,
+        _ => user_api_key(&state, auth_user.user_id)
+            .await?
+            .ok_or_else(|| AppError::BadRequest(t!("bangumi.no_api_key").to_string()))?,
+    };
+
+    let username = state.bangumi_service.check_key(&api_key).await?;
+    Ok(Json(BangumiKeyCheck {
+        valid: username.is_some(),
+        username,
+    }))
+}
+
+/// Bangumi access token saved in a user's profile.
+async fn user_api_key(state: &AppState, user_id: i64) -> Result<Option<String>> {
+    let user = state.auth_service.get_user(user_id).await?;
+    Ok(user
+        .and_then(|user| user.bangumi_api_key)
+        .filter(|key| !key.is_empty()))
+}
@@ -3,12 +3,35 @@
 //! This module contains all the Axum handlers that process incoming HTTP requests
 //! and return appropriate responses.
 
-pub mod auth;
+pub mod admin;
 pub mod apikey;
+pub mod auth;
+pub mod bookmark;
+pub mod capabilities;
 pub mod content;
+pub mod dashboard;
+pub mod ext;
 pub mod filesystem;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+pub mod import;
+pub mod jobs;
+pub mod kobo;
 pub mod komga;
+pub mod koreader;
 pub mod library;
+pub mod maintenance;
+pub mod metadata;
+pub mod oidc;
+pub mod openapi;
+pub mod preferences;
 pub mod progress;
+pub mod reader_settings;
+pub mod release;
+pub mod review;
 pub mod scan_queue;
+pub mod series_group;
+pub mod setup;
+pub mod share;
 pub mod static_files;
+pub mod tracker;
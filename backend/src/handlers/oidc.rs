@@ -0,0 +1,160 @@
+//! OIDC login handlers.
+//!
+//! This module provides HTTP handlers for logging in through an external
+//! OpenID Connect provider:
+//! - GET /api/auth/oidc/login - Redirect to the provider
+//! - GET /api/auth/oidc/callback - Handle the provider's redirect back
+//! - POST /api/auth/oidc/link - Start linking the provider identity to the current user
+//!
+//! After the callback the browser is sent to the configured frontend URL
+//! with the result in the fragment: `#token=<jwt>` on login,
+//! `#oidc_linked=1` after linking, or `#error=<message>` on failure.
+
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::{HeaderMap, header},
+    response::{IntoResponse, Redirect, Response},
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::error::{AppError, Result};
+use crate::middlewares::auth::AuthUser;
+use crate::middlewares::client_ip::ClientIp;
+use crate::models::{AuditAction, NewAuditLogEntry, OidcLinkResponse};
+use crate::services::oidc::{OidcOutcome, OidcService};
+use crate::state::AppState;
+use crate::t;
+use crate::utils::oauth_state::{self, read_cookie};
+
+/// Cookie holding the state token between login and callback.
+const STATE_COOKIE: &str = "ryuri_oidc_state";
+
+/// Query parameters sent by the provider to the callback.
+#[derive(Debug, Deserialize)]
+pub struct CallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
+
+/// GET /api/auth/oidc/login
+///
+/// Redirects the browser to the provider's authorization page.
+pub async fn login(State(state): State<AppState>) -> Result<Response> {
+    let oidc = require_oidc(&state)?;
+    let authorization = oidc.authorize(None).await?;
+
+    Ok((
+        [(header::SET_COOKIE, state_cookie(oidc, &authorization.state))],
+        Redirect::to(&authorization.url),
+    )
+        .into_response())
+}
+
+/// POST /api/auth/oidc/link
+///
+/// Returns the provider authorization URL for linking the provider identity
+/// to the current user. The frontend navigates to it; the callback then
+/// links instead of logging in.
+pub async fn link(State(state): State<AppState>, auth_user: AuthUser) -> Result<Response> {
+    let oidc = require_oidc(&state)?;
+    let authorization = oidc.authorize(Some(auth_user.user_id)).await?;
+
+    Ok((
+        [(header::SET_COOKIE, state_cookie(oidc, &authorization.state))],
+        Json(OidcLinkResponse {
+            authorization_url: authorization.url,
+        }),
+    )
+        .into_response())
+}
+
+/// GET /api/auth/oidc/callback
+///
+/// Verifies the provider response, maps the identity to a local user and
+/// redirects to the frontend with a JWT.
+pub async fn callback(
+    State(state): State<AppState>,
+    ClientIp(ip): ClientIp,
+    headers: HeaderMap,
+    Query(query): Query<CallbackQuery>,
+) -> Result<Response> {
+    let oidc = require_oidc(&state)?;
+
+    let fragment = match complete(&state, oidc, &headers, query, &ip).await {
+        Ok(fragment) => fragment,
+        Err(e) => {
+            warn!(error = %e, "{}", t!("oidc.callback_failed_log"));
+            if matches!(e.kind(), AppError::Unauthorized(_)) {
+                let entry = NewAuditLogEntry::new(AuditAction::LoginFailed)
+                    .ip(ip)
+                    .details("oidc");
+                state.audit_service.record(entry).await;
+            }
+            format!("error={}", urlencoding::encode(&e.error_message()))
+        }
+    };
+
+    let target = format!("{}#{}", oidc.frontend_redirect(), fragment);
+
+    Ok((
+        [(header::SET_COOKIE, state_cookie(oidc, ""))],
+        Redirect::to(&target),
+    )
+        .into_response())
+}
+
+/// Run the callback and return the fragment to hand to the frontend.
+async fn complete(
+    state: &AppState,
+    oidc: &OidcService,
+    headers: &HeaderMap,
+    query: CallbackQuery,
+    ip: &str,
+) -> Result<String> {
+    if let Some(error) = query.error {
+        let message = query.error_description.unwrap_or(error);
+        return Err(AppError::Unauthorized(
+            t!("oidc.provider_error", error = message).to_string(),
+        ));
+    }
+
+    let (Some(code), Some(state_param)) = (query.code, query.state) else {
+        return Err(AppError::BadRequest(t!("oidc.missing_code").to_string()));
+    };
+
+    let cookie_state = read_cookie(headers, STATE_COOKIE);
+    match oidc
+        .complete(&code, &state_param, cookie_state.as_deref())
+        .await?
+    {
+        OidcOutcome::LoggedIn(user) => {
+            let token = state.auth_service.issue_token(&user)?;
+            let entry = NewAuditLogEntry::new(AuditAction::Login)
+                .user(user.id, &user.username)
+                .ip(ip)
+                .details("oidc");
+            state.audit_service.record(entry).await;
+            Ok(format!("token={}", urlencoding::encode(&token)))
+        }
+        OidcOutcome::Linked(_) => Ok("oidc_linked=1".to_string()),
+    }
+}
+
+/// Get the OIDC service or fail with NotFound when OIDC is not configured.
+fn require_oidc(state: &AppState) -> Result<&Arc<OidcService>> {
+    state
+        .oidc_service
+        .as_ref()
+        .ok_or_else(|| AppError::NotFound(t!("oidc.not_configured").to_string()))
+}
+
+/// Build the `Set-Cookie` value storing the state token, or removing it
+/// when `value` is empty.
+fn state_cookie(oidc: &OidcService, value: &str) -> String {
+    oauth_state::state_cookie(STATE_COOKIE, "/api/auth/oidc", value, oidc.secure_cookies())
+}
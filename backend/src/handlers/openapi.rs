@@ -0,0 +1,18 @@
+//! OpenAPI document.
+//!
+//! This module provides an HTTP handler serving the API description, so
+//! clients can be generated from it:
+//! - GET /api/openapi.json - OpenAPI 3.1 document of every API route
+
+use axum::Json;
+use serde_json::Value;
+
+use crate::openapi;
+
+/// GET /api/openapi.json
+///
+/// Returns the OpenAPI document of the routes in this build. Does not
+/// require authentication.
+pub async fn get_openapi() -> Json<Value> {
+    Json(openapi::document())
+}
@@ -0,0 +1,38 @@
+//! User preference handlers.
+//!
+//! This module provides HTTP handlers for the preferences of the current user:
+//! - GET /api/users/me/preferences - Get the preferences of the current user
+//! - PATCH /api/users/me/preferences - Update the preferences of the current user
+
+use axum::{Json, extract::State};
+
+use crate::error::Result;
+use crate::middlewares::AuthUser;
+use crate::models::{UpdatePreferencesRequest, UserPreferences};
+use crate::services::preferences::PreferencesService;
+use crate::state::AppState;
+
+/// GET /api/users/me/preferences
+///
+/// Returns the locale, theme, reading defaults and page size of the current
+/// user, with defaults for anything not set yet.
+pub async fn get_preferences(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<UserPreferences>> {
+    let preferences = PreferencesService::get(&state.pool, auth_user.user_id).await?;
+    Ok(Json(preferences))
+}
+
+/// PATCH /api/users/me/preferences
+///
+/// Updates the preferences of the current user. Omitted fields are kept and
+/// a `null` locale goes back to the server locale.
+pub async fn update_preferences(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(request): Json<UpdatePreferencesRequest>,
+) -> Result<Json<UserPreferences>> {
+    let preferences = PreferencesService::update(&state.pool, auth_user.user_id, request).await?;
+    Ok(Json(preferences))
+}
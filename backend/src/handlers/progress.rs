@@ -4,6 +4,8 @@
 //! - GET /api/contents/{id}/progress - Get overall content progress
 //! - GET /api/chapters/{id}/progress - Get chapter progress
 //! - PUT /api/chapters/{id}/progress - Update chapter progress
+//! - GET /api/chapters/{id}/splits/progress - Get positions in a .txt chapter's virtual chapters
+//! - PUT /api/chapters/{id}/splits/{index}/progress - Update the position in a virtual chapter
 
 use axum::{
     Json,
@@ -13,7 +15,7 @@ use serde::Deserialize;
 
 use crate::error::Result;
 use crate::middlewares::auth::AuthUser;
-use crate::models::{ContentResponse, ProgressResponse};
+use crate::models::{ContentResponse, NovelPosition, ProgressResponse, SplitProgressResponse};
 use crate::state::AppState;
 
 /// Query parameters for recent progress.
@@ -39,7 +41,9 @@ pub async fn get_content_progress(
         .progress_service
         .get_content_progress(auth_user.user_id, content_id)
         .await?;
-    Ok(Json(progress.into_iter().map(ProgressResponse::from).collect()))
+    Ok(Json(
+        progress.into_iter().map(ProgressResponse::from).collect(),
+    ))
 }
 
 /// GET /api/progress/recent
@@ -75,23 +79,40 @@ pub async fn get_chapter_progress(
 /// Request body for updating chapter progress with optional percentage.
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct UpdateProgressWithPercentageRequest {
-    /// Current position within the chapter (page number or character offset).
+    /// Current position within the chapter (page number, character offset, or
+    /// seconds played for audiobooks).
     pub position: i32,
     /// Optional percentage (0.0 to 100.0). If not provided, defaults to 0.0.
     #[serde(default)]
     pub percentage: Option<f32>,
+    /// Place in the text of a novel chapter, stored alongside the position.
+    #[serde(default)]
+    pub novel: Option<NovelPosition>,
 }
 
 /// PUT /api/chapters/{id}/progress
 ///
-/// Updates the reading progress for a specific chapter.
+/// Updates the reading progress for a specific chapter. Novel chapters may
+/// also send a `novel` position (section and character offset, optionally
+/// with an EPUB CFI) that is returned with the progress.
 pub async fn update_chapter_progress(
     State(state): State<AppState>,
     auth_user: AuthUser,
     Path(chapter_id): Path<i64>,
     Json(req): Json<UpdateProgressWithPercentageRequest>,
 ) -> Result<Json<ProgressResponse>> {
-    let progress = if let Some(percentage) = req.percentage {
+    let progress = if let Some(novel) = req.novel {
+        state
+            .progress_service
+            .update_novel_progress(
+                auth_user.user_id,
+                chapter_id,
+                req.position,
+                req.percentage,
+                novel,
+            )
+            .await?
+    } else if let Some(percentage) = req.percentage {
         state
             .progress_service
             .update_progress_with_percentage(
@@ -109,3 +130,44 @@ pub async fn update_chapter_progress(
     };
     Ok(Json(ProgressResponse::from(progress)))
 }
+
+/// GET /api/chapters/{id}/splits/progress
+///
+/// Returns the reading positions inside the virtual chapters of a `.txt`
+/// chapter. Which virtual chapter is being read is the chapter's own
+/// progress position.
+pub async fn get_split_progress(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(chapter_id): Path<i64>,
+) -> Result<Json<Vec<SplitProgressResponse>>> {
+    let progress = state
+        .progress_service
+        .get_split_progress(auth_user.user_id, chapter_id)
+        .await?;
+    Ok(Json(progress))
+}
+
+/// Request body for updating the position in a virtual chapter.
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateSplitProgressRequest {
+    /// Character offset within the virtual chapter.
+    pub position: i32,
+}
+
+/// PUT /api/chapters/{id}/splits/{index}/progress
+///
+/// Updates the reading position inside a virtual chapter of a `.txt`
+/// chapter.
+pub async fn update_split_progress(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path((chapter_id, split_index)): Path<(i64, i32)>,
+    Json(req): Json<UpdateSplitProgressRequest>,
+) -> Result<Json<SplitProgressResponse>> {
+    let progress = state
+        .progress_service
+        .update_split_progress(auth_user.user_id, chapter_id, split_index, req.position)
+        .await?;
+    Ok(Json(progress))
+}
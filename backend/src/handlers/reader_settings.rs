@@ -0,0 +1,69 @@
+//! Reader settings handlers.
+//!
+//! This module provides HTTP handlers for reader settings endpoints:
+//! - GET /api/libraries/{id}/settings - Get the reader defaults of a library
+//! - PATCH /api/libraries/{id}/settings - Update the reader defaults of a library
+//! - GET /api/contents/{id}/settings - Get the reader settings of a content
+//! - PATCH /api/contents/{id}/settings - Update the reader settings of a content
+
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+
+use crate::error::Result;
+use crate::models::{ReaderSettingsResponse, UpdateReaderSettingsRequest};
+use crate::services::reader_settings::ReaderSettingsService;
+use crate::state::AppState;
+
+/// GET /api/libraries/{id}/settings
+///
+/// Returns the reader defaults of a library, which apply to its contents
+/// unless a content overrides them.
+pub async fn get_library_settings(
+    State(state): State<AppState>,
+    Path(library_id): Path<i64>,
+) -> Result<Json<ReaderSettingsResponse>> {
+    let settings = ReaderSettingsService::get_for_library(&state.pool, library_id).await?;
+    Ok(Json(settings))
+}
+
+/// PATCH /api/libraries/{id}/settings
+///
+/// Updates the reader defaults of a library. Omitted fields are kept and
+/// `null` fields go back to the built-in defaults.
+pub async fn update_library_settings(
+    State(state): State<AppState>,
+    Path(library_id): Path<i64>,
+    Json(request): Json<UpdateReaderSettingsRequest>,
+) -> Result<Json<ReaderSettingsResponse>> {
+    let settings =
+        ReaderSettingsService::update_for_library(&state.pool, library_id, request).await?;
+    Ok(Json(settings))
+}
+
+/// GET /api/contents/{id}/settings
+///
+/// Returns the reader settings of a content: those in effect, those set on
+/// the content itself and those inherited from its library.
+pub async fn get_content_settings(
+    State(state): State<AppState>,
+    Path(content_id): Path<i64>,
+) -> Result<Json<ReaderSettingsResponse>> {
+    let settings = ReaderSettingsService::get_for_content(&state.pool, content_id).await?;
+    Ok(Json(settings))
+}
+
+/// PATCH /api/contents/{id}/settings
+///
+/// Updates the reader settings of a content. Omitted fields are kept and
+/// `null` fields go back to the library defaults.
+pub async fn update_content_settings(
+    State(state): State<AppState>,
+    Path(content_id): Path<i64>,
+    Json(request): Json<UpdateReaderSettingsRequest>,
+) -> Result<Json<ReaderSettingsResponse>> {
+    let settings =
+        ReaderSettingsService::update_for_content(&state.pool, content_id, request).await?;
+    Ok(Json(settings))
+}
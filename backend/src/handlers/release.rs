@@ -0,0 +1,30 @@
+//! New chapter release handlers.
+//!
+//! This module provides HTTP handlers for the new chapters of followed series:
+//! - GET /api/users/me/updates - List new chapters in the series the current user follows
+
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+
+use crate::error::Result;
+use crate::middlewares::AuthUser;
+use crate::models::{UpdatesPage, UpdatesQuery};
+use crate::services::release::ReleaseService;
+use crate::state::AppState;
+
+/// GET /api/users/me/updates
+///
+/// Returns a page of the chapters scans added to series the current user
+/// marked as a favorite or gave a reading status other than dropped,
+/// newest first. Accepts `limit`, and `after` with the `next_cursor` of the
+/// previous page.
+pub async fn list_updates(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<UpdatesQuery>,
+) -> Result<Json<UpdatesPage>> {
+    let page = ReleaseService::list_updates(&state.pool, auth_user.user_id, &query).await?;
+    Ok(Json(page))
+}
@@ -0,0 +1,71 @@
+//! Rating and review handlers.
+//!
+//! This module provides HTTP handlers for rating contents:
+//! - GET /api/contents/{id}/reviews - List the ratings of a content
+//! - GET /api/contents/{id}/reviews/me - Get the current user's rating
+//! - PUT /api/contents/{id}/reviews/me - Rate a content
+//! - DELETE /api/contents/{id}/reviews/me - Remove the current user's rating
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+
+use crate::error::Result;
+use crate::middlewares::auth::AuthUser;
+use crate::models::{ContentReview, ContentReviews, SaveReviewRequest};
+use crate::services::review::ReviewService;
+use crate::state::AppState;
+
+/// GET /api/contents/{id}/reviews
+///
+/// Returns every user's rating of a content, most recently changed first,
+/// with their average.
+pub async fn list_reviews(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(content_id): Path<i64>,
+) -> Result<Json<ContentReviews>> {
+    let reviews = ReviewService::list(&state.pool, auth_user.user_id, content_id).await?;
+    Ok(Json(reviews))
+}
+
+/// GET /api/contents/{id}/reviews/me
+///
+/// Returns the current user's rating of a content, or 404 if they have not
+/// rated it.
+pub async fn get_my_review(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(content_id): Path<i64>,
+) -> Result<Json<ContentReview>> {
+    let review = ReviewService::get(&state.pool, auth_user.user_id, content_id).await?;
+    Ok(Json(review))
+}
+
+/// PUT /api/contents/{id}/reviews/me
+///
+/// Rates a content from 1 to 10, optionally with a short `review`,
+/// replacing the current user's earlier rating.
+pub async fn save_my_review(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(content_id): Path<i64>,
+    Json(request): Json<SaveReviewRequest>,
+) -> Result<Json<ContentReview>> {
+    let review = ReviewService::save(&state.pool, auth_user.user_id, content_id, request).await?;
+    Ok(Json(review))
+}
+
+/// DELETE /api/contents/{id}/reviews/me
+///
+/// Removes the current user's rating of a content.
+pub async fn delete_my_review(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(content_id): Path<i64>,
+) -> Result<StatusCode> {
+    ReviewService::delete(&state.pool, auth_user.user_id, content_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -2,9 +2,20 @@
 //!
 //! This module provides HTTP handlers for scan queue management endpoints:
 //! - POST /api/libraries/{id}/scan - Submit a scan task (High priority)
+//! - POST /api/libraries/{id}/scan/preview - Show what a scan would change
+//! - POST /api/libraries/{id}/thumbnails/regenerate - Submit a thumbnail task (Normal priority)
+//! - POST /api/scan-tasks - Submit a scan task for one scan path (High priority)
 //! - GET /api/scan-tasks/{id} - Get task status
 //! - GET /api/scan-tasks - List all tasks (pending + recent history)
+//! - GET /api/scan-tasks/history - Page through the task history
 //! - DELETE /api/scan-tasks/{id} - Cancel a task
+//! - POST /api/scan-tasks/{id}/move-to-front - Run a pending task next
+//! - GET /api/scan-queue/state - Get the state of the workers and task counts
+//! - POST /api/scan-queue/pause - Stop starting pending tasks
+//! - POST /api/scan-queue/resume - Start pending tasks again
+//! - POST /api/scan-queue/clear - Cancel all pending tasks
+//!
+//! Finished tasks carry a `summary` in the requesting user's locale.
 
 use axum::{
     Json,
@@ -12,11 +23,14 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use rust_i18n::t;
 
-use crate::error::{AppError, Result};
-use crate::models::{ScanTask, TaskPriority};
+use crate::error::{AppError, ErrorCode, Result};
+use crate::middlewares::auth::{AdminUser, AuthUser};
+use crate::models::{
+    ScanPreview, ScanQueueState, ScanTask, ScanTaskHistoryPage, ScanTaskHistoryQuery, TaskPriority,
+};
 use crate::state::AppState;
+use crate::t;
 
 /// Response for submitting a scan task.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,7 +54,10 @@ pub async fn submit_scan(
     // Verify library exists
     let library = state.library_service.get(library_id).await?;
     if library.is_none() {
-        return Err(AppError::NotFound(t!("library.id_not_found", id = library_id).to_string()));
+        return Err(
+            AppError::NotFound(t!("library.id_not_found", id = library_id).to_string())
+                .with_code(ErrorCode::LibraryNotFound),
+        );
     }
 
     // Submit task with High priority (manual scan)
@@ -59,6 +76,102 @@ pub async fn submit_scan(
     Ok(Json(SubmitScanResponse { task_id, task }))
 }
 
+/// POST /api/libraries/{id}/scan/preview
+///
+/// Runs discovery for a library without writing to the database and returns
+/// the content that a scan would add, remove, relink or update, so folder
+/// structure and ignore patterns can be checked before a real scan.
+pub async fn preview_scan(
+    State(state): State<AppState>,
+    Path(library_id): Path<i64>,
+) -> Result<Json<ScanPreview>> {
+    // Verify library exists
+    let library = state.library_service.get(library_id).await?;
+    if library.is_none() {
+        return Err(
+            AppError::NotFound(t!("library.id_not_found", id = library_id).to_string())
+                .with_code(ErrorCode::LibraryNotFound),
+        );
+    }
+
+    let preview = state.scan_service.preview_library(library_id).await?;
+    Ok(Json(preview))
+}
+
+/// POST /api/libraries/{id}/thumbnails/regenerate
+///
+/// Submits a background task that regenerates the library's missing
+/// thumbnails and those made with other thumbnail settings. Progress counts
+/// contents. If a thumbnail task is already active for the library, returns
+/// that task.
+pub async fn regenerate_thumbnails(
+    State(state): State<AppState>,
+    Path(library_id): Path<i64>,
+) -> Result<Json<SubmitScanResponse>> {
+    // Verify library exists
+    let library = state.library_service.get(library_id).await?;
+    if library.is_none() {
+        return Err(
+            AppError::NotFound(t!("library.id_not_found", id = library_id).to_string())
+                .with_code(ErrorCode::LibraryNotFound),
+        );
+    }
+
+    // Normal priority, so manual scans run first
+    let task_id = state
+        .scan_queue_service
+        .submit_thumbnail_task(library_id, TaskPriority::Normal)
+        .await;
+
+    let task = state
+        .scan_queue_service
+        .get_task(task_id)
+        .await
+        .ok_or_else(|| AppError::Internal("Failed to retrieve submitted task".to_string()))?;
+
+    Ok(Json(SubmitScanResponse { task_id, task }))
+}
+
+/// Request body for submitting a scan of a single scan path.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubmitScanPathRequest {
+    /// The scan path to scan.
+    pub scan_path_id: i64,
+}
+
+/// POST /api/scan-tasks
+///
+/// Submits a scan task for a single scan path with High priority, so a new
+/// folder can be imported without rescanning the whole library.
+/// If a task already covers the scan path, returns the existing task.
+pub async fn submit_scan_path_scan(
+    State(state): State<AppState>,
+    Json(req): Json<SubmitScanPathRequest>,
+) -> Result<Json<SubmitScanResponse>> {
+    let scan_path = state
+        .library_service
+        .get_scan_path(req.scan_path_id)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(
+                t!("scan_queue.scan_path_not_found", id = req.scan_path_id).to_string(),
+            )
+        })?;
+
+    let task_id = state
+        .scan_queue_service
+        .submit_scan_path_task(scan_path.library_id, scan_path.id, TaskPriority::High)
+        .await;
+
+    let task = state
+        .scan_queue_service
+        .get_task(task_id)
+        .await
+        .ok_or_else(|| AppError::Internal("Failed to retrieve submitted task".to_string()))?;
+
+    Ok(Json(SubmitScanResponse { task_id, task }))
+}
+
 /// GET /api/scan-tasks/{id}
 ///
 /// Returns the status and details of a scan task.
@@ -66,15 +179,20 @@ pub async fn submit_scan(
 /// Requirements: 2.1
 pub async fn get_task(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Path(task_id): Path<Uuid>,
 ) -> Result<Json<ScanTask>> {
     let task = state
         .scan_queue_service
         .get_task(task_id)
         .await
-        .ok_or_else(|| AppError::NotFound(t!("scan_queue.task_not_found", id = task_id).to_string()))?;
+        .ok_or_else(|| {
+            AppError::NotFound(t!("scan_queue.task_not_found", id = task_id).to_string())
+                .with_code(ErrorCode::ScanTaskNotFound)
+        })?;
 
-    Ok(Json(task))
+    let locale = state.auth_service.user_locale(auth_user.user_id).await?;
+    Ok(Json(task.with_summary(&locale)))
 }
 
 /// Query parameters for listing tasks.
@@ -107,12 +225,20 @@ pub struct ListTasksResponse {
 /// Requirements: 2.3
 pub async fn list_tasks(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Query(query): Query<ListTasksQuery>,
 ) -> Result<Json<ListTasksResponse>> {
     let processing = state.scan_queue_service.list_processing().await;
     let pending = state.scan_queue_service.list_pending().await;
     let history = state.scan_queue_service.list_history(query.limit).await;
 
+    // Only finished tasks have a summary
+    let locale = state.auth_service.user_locale(auth_user.user_id).await?;
+    let history = history
+        .into_iter()
+        .map(|task| task.with_summary(&locale))
+        .collect();
+
     Ok(Json(ListTasksResponse {
         pending,
         processing,
@@ -120,6 +246,26 @@ pub async fn list_tasks(
     }))
 }
 
+/// GET /api/scan-tasks/history
+///
+/// Returns a page of finished tasks, most recently finished first, filtered
+/// by status and by when they finished.
+pub async fn list_task_history(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<ScanTaskHistoryQuery>,
+) -> Result<Json<ScanTaskHistoryPage>> {
+    let mut page = state.scan_queue_service.history_page(&query).await;
+
+    let locale = state.auth_service.user_locale(auth_user.user_id).await?;
+    page.items = page
+        .items
+        .into_iter()
+        .map(|task| task.with_summary(&locale))
+        .collect();
+    Ok(Json(page))
+}
+
 /// DELETE /api/scan-tasks/{id}
 ///
 /// Cancels a pending or running scan task.
@@ -127,6 +273,7 @@ pub async fn list_tasks(
 /// Requirements: 3.1
 pub async fn cancel_task(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Path(task_id): Path<Uuid>,
 ) -> Result<Json<ScanTask>> {
     // Cancel the task
@@ -137,7 +284,81 @@ pub async fn cancel_task(
         .scan_queue_service
         .get_task(task_id)
         .await
-        .ok_or_else(|| AppError::NotFound(t!("scan_queue.task_not_found", id = task_id).to_string()))?;
+        .ok_or_else(|| {
+            AppError::NotFound(t!("scan_queue.task_not_found", id = task_id).to_string())
+                .with_code(ErrorCode::ScanTaskNotFound)
+        })?;
+
+    let locale = state.auth_service.user_locale(auth_user.user_id).await?;
+    Ok(Json(task.with_summary(&locale)))
+}
+
+/// POST /api/scan-tasks/{id}/move-to-front
+///
+/// Moves a pending task to the front of the queue, so it runs before any
+/// other pending task whatever their priority.
+pub async fn move_task_to_front(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,
+    Path(task_id): Path<Uuid>,
+) -> Result<Json<ScanTask>> {
+    state.scan_queue_service.move_to_front(task_id).await?;
+
+    let task = state
+        .scan_queue_service
+        .get_task(task_id)
+        .await
+        .ok_or_else(|| {
+            AppError::NotFound(t!("scan_queue.task_not_found", id = task_id).to_string())
+                .with_code(ErrorCode::ScanTaskNotFound)
+        })?;
+
+    let locale = state.auth_service.user_locale(admin.user_id).await?;
+    Ok(Json(task.with_summary(&locale)))
+}
+
+/// GET /api/scan-queue/state
+///
+/// Returns whether the workers are running or paused, the number of tasks
+/// in each status and the running tasks with their elapsed time.
+pub async fn get_queue_state(State(state): State<AppState>) -> Json<ScanQueueState> {
+    Json(state.scan_queue_service.state().await)
+}
+
+/// POST /api/scan-queue/pause
+///
+/// Pauses the queue: running tasks finish, but pending tasks are not started
+/// until the queue is resumed. Returns the queue state.
+pub async fn pause_queue(State(state): State<AppState>, _admin: AdminUser) -> Json<ScanQueueState> {
+    state.scan_queue_service.pause();
+    Json(state.scan_queue_service.state().await)
+}
+
+/// POST /api/scan-queue/resume
+///
+/// Resumes a paused queue and returns its state.
+pub async fn resume_queue(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+) -> Json<ScanQueueState> {
+    state.scan_queue_service.resume();
+    Json(state.scan_queue_service.state().await)
+}
 
-    Ok(Json(task))
+/// Response for clearing the queue.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClearQueueResponse {
+    /// Number of pending tasks cancelled.
+    pub cancelled: usize,
+}
+
+/// POST /api/scan-queue/clear
+///
+/// Cancels every pending task. Running tasks are left to finish.
+pub async fn clear_queue(
+    State(state): State<AppState>,
+    _admin: AdminUser,
+) -> Json<ClearQueueResponse> {
+    let cancelled = state.scan_queue_service.clear_pending().await;
+    Json(ClearQueueResponse { cancelled })
 }
@@ -0,0 +1,96 @@
+//! Series group handlers.
+//!
+//! This module provides HTTP handlers for series group endpoints:
+//! - GET /api/series-groups - List series groups
+//! - POST /api/series-groups - Create a series group
+//! - GET /api/series-groups/{id} - Get a series group
+//! - DELETE /api/series-groups/{id} - Delete a series group
+//! - GET /api/series-groups/{id}/contents - List the contents of a series group
+//! - PUT /api/contents/{id}/series-group - Put a content into a series group
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+
+use crate::error::Result;
+use crate::middlewares::AuthUser;
+use crate::models::{
+    ContentResponse, CreateSeriesGroupRequest, SeriesGroup, SetSeriesGroupRequest,
+};
+use crate::services::age_rating::AgeRatingService;
+use crate::services::series_group::SeriesGroupService;
+use crate::state::AppState;
+
+/// GET /api/series-groups
+///
+/// Returns all series groups by name.
+pub async fn list(State(state): State<AppState>) -> Result<Json<Vec<SeriesGroup>>> {
+    let groups = SeriesGroupService::list(&state.pool).await?;
+    Ok(Json(groups))
+}
+
+/// POST /api/series-groups
+///
+/// Creates a series group. Names are unique.
+pub async fn create(
+    State(state): State<AppState>,
+    Json(request): Json<CreateSeriesGroupRequest>,
+) -> Result<(StatusCode, Json<SeriesGroup>)> {
+    let group = SeriesGroupService::create(&state.pool, &request.name).await?;
+    Ok((StatusCode::CREATED, Json(group)))
+}
+
+/// GET /api/series-groups/{id}
+///
+/// Returns a series group by its ID.
+pub async fn get(
+    State(state): State<AppState>,
+    Path(group_id): Path<i64>,
+) -> Result<Json<SeriesGroup>> {
+    let group = SeriesGroupService::get(&state.pool, group_id).await?;
+    Ok(Json(group))
+}
+
+/// DELETE /api/series-groups/{id}
+///
+/// Deletes a series group. Its contents are kept outside any group.
+pub async fn delete(
+    State(state): State<AppState>,
+    Path(group_id): Path<i64>,
+) -> Result<StatusCode> {
+    SeriesGroupService::delete(&state.pool, group_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/series-groups/{id}/contents
+///
+/// Returns the contents of a series group by title, leaving out those
+/// above the current user's age rating cap.
+pub async fn list_contents(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(group_id): Path<i64>,
+) -> Result<Json<Vec<ContentResponse>>> {
+    let contents = SeriesGroupService::list_contents(&state.pool, group_id).await?;
+    let contents =
+        AgeRatingService::filter_contents(&state.pool, auth_user.user_id, contents).await?;
+    Ok(Json(
+        contents.into_iter().map(ContentResponse::from).collect(),
+    ))
+}
+
+/// PUT /api/contents/{id}/series-group
+///
+/// Puts a content into a series group, replacing its current group, or
+/// takes it out of its group when `group_id` is `null`.
+pub async fn set_content_group(
+    State(state): State<AppState>,
+    Path(content_id): Path<i64>,
+    Json(request): Json<SetSeriesGroupRequest>,
+) -> Result<Json<ContentResponse>> {
+    let content =
+        SeriesGroupService::set_content_group(&state.pool, content_id, request.group_id).await?;
+    Ok(Json(ContentResponse::from(content)))
+}
@@ -0,0 +1,106 @@
+//! First-run setup handlers.
+//!
+//! A fresh install has no usable account. Until the first administrator
+//! has been created, these public endpoints let the first visitor create
+//! one, then they are locked:
+//! - GET /api/setup/status - Whether setup is still required
+//! - POST /api/setup/admin - Create the first administrator and library
+
+use axum::{Json, extract::State};
+
+use crate::error::{AppError, ErrorCode, Result};
+use crate::middlewares::client_ip::ClientIp;
+use crate::models::{
+    AuditAction, CreateLibraryRequest, NewAuditLogEntry, SetupRequest, SetupResponse, SetupStatus,
+    TaskPriority, UserResponse,
+};
+use crate::state::AppState;
+use crate::t;
+
+/// GET /api/setup/status
+///
+/// Returns whether the first administrator still has to be created, and
+/// the locales to offer.
+pub async fn get_status(State(state): State<AppState>) -> Result<Json<SetupStatus>> {
+    let setup_required = state.auth_service.setup_required().await?;
+    let locales = rust_i18n::available_locales!()
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    Ok(Json(SetupStatus {
+        setup_required,
+        locales,
+    }))
+}
+
+/// POST /api/setup/admin
+///
+/// Creates the first administrator with their preferred locale, and
+/// optionally a first library, scanning its folder right away. Returns a
+/// token so the administrator is logged in. Fails with 403 once setup is
+/// done.
+pub async fn create_admin(
+    State(state): State<AppState>,
+    ClientIp(ip): ClientIp,
+    Json(req): Json<SetupRequest>,
+) -> Result<Json<SetupResponse>> {
+    if !state.auth_service.setup_required().await? {
+        return Err(AppError::Forbidden(t!("setup.completed").to_string())
+            .with_code(ErrorCode::SetupCompleted));
+    }
+    if let Some(library) = &req.library {
+        if library.name.trim().is_empty() {
+            return Err(AppError::BadRequest(t!("library.name_empty").to_string()));
+        }
+        if library
+            .path
+            .as_deref()
+            .is_some_and(|path| path.trim().is_empty())
+        {
+            return Err(AppError::BadRequest(t!("library.path_empty").to_string()));
+        }
+    }
+
+    let user = state
+        .auth_service
+        .create_first_admin(req.username, req.password, req.locale.as_deref())
+        .await?;
+    let entry = NewAuditLogEntry::new(AuditAction::SetupCompleted)
+        .user(user.id, &user.username)
+        .ip(ip);
+    state.audit_service.record(entry).await;
+
+    let library = match req.library {
+        Some(setup) => {
+            let library = state
+                .library_service
+                .create(CreateLibraryRequest {
+                    name: setup.name,
+                    scan_interval: None,
+                    watch_mode: None,
+                    scan_depth: None,
+                    standalone_archives: None,
+                })
+                .await?;
+            if let Some(path) = setup.path {
+                state
+                    .library_service
+                    .add_scan_path(library.id, path)
+                    .await?;
+                state
+                    .scan_queue_service
+                    .submit_task(library.id, TaskPriority::Normal)
+                    .await;
+            }
+            Some(library)
+        }
+        None => None,
+    };
+
+    let token = state.auth_service.issue_token(&user)?;
+    Ok(Json(SetupResponse {
+        user: UserResponse::from(user),
+        token,
+        library,
+    }))
+}
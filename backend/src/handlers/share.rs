@@ -0,0 +1,183 @@
+//! Share link handlers.
+//!
+//! This module provides HTTP handlers for public share links:
+//! - POST /api/contents/{id}/shares - Share a content or one of its chapters
+//! - GET /api/shares - List the current user's share links
+//! - DELETE /api/shares/{id} - Revoke a share link
+//! - GET /api/shared/{token} - Get a shared content (no login)
+//! - GET /api/shared/{token}/thumbnail - Get its thumbnail (no login)
+//! - GET /api/shared/{token}/chapters/{chapter}/pages - List shared pages (no login)
+//! - GET /api/shared/{token}/chapters/{chapter}/pages/{page} - Get a shared page (no login)
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use serde::Deserialize;
+
+use crate::error::{AppError, Result};
+use crate::handlers::content::{self, ChapterParams, PageParams, ThumbnailQuery};
+use crate::middlewares::{auth::AuthUser, client_ip::ClientIp};
+use crate::models::{
+    AuditAction, CreateShareLinkRequest, NewAuditLogEntry, PageInfo, PageInfoQuery, PageQuery,
+    ShareLink, SharedContent,
+};
+use crate::services::share::ShareService;
+use crate::state::AppState;
+use crate::t;
+
+/// POST /api/contents/{id}/shares
+///
+/// Creates a link to a content, or only to the chapter given as
+/// `chapter_id`, that expires after `expires_in_hours` (72 by default, at
+/// most 720). Fails with 403 while sharing is disabled in the server
+/// settings.
+pub async fn create_share(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    ClientIp(ip): ClientIp,
+    Path(content_id): Path<i64>,
+    Json(request): Json<CreateShareLinkRequest>,
+) -> Result<(StatusCode, Json<ShareLink>)> {
+    if !state.settings_service.sharing_enabled() {
+        return Err(AppError::Forbidden(t!("share.disabled").to_string()));
+    }
+    let link = ShareService::create(&state.pool, auth_user.user_id, content_id, request).await?;
+
+    let entry = NewAuditLogEntry::new(AuditAction::ShareLinkCreated)
+        .user(auth_user.user_id, &auth_user.username)
+        .target("share_link", link.id)
+        .details(link.content_title.clone())
+        .ip(ip);
+    state.audit_service.record(entry).await;
+
+    Ok((StatusCode::CREATED, Json(link)))
+}
+
+/// GET /api/shares
+///
+/// Lists the share links created by the current user, newest first,
+/// including expired ones.
+pub async fn list_my_shares(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<ShareLink>>> {
+    let links = ShareService::list(&state.pool, Some(auth_user.user_id)).await?;
+    Ok(Json(links))
+}
+
+/// DELETE /api/shares/{id}
+///
+/// Revokes one of the current user's share links.
+pub async fn revoke_share(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    ClientIp(ip): ClientIp,
+    Path(id): Path<i64>,
+) -> Result<StatusCode> {
+    ShareService::revoke(&state.pool, id, Some(auth_user.user_id)).await?;
+
+    let entry = NewAuditLogEntry::new(AuditAction::ShareLinkRevoked)
+        .user(auth_user.user_id, &auth_user.username)
+        .target("share_link", id)
+        .ip(ip);
+    state.audit_service.record(entry).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/shared/{token}
+///
+/// Returns the title, thumbnail version and readable chapters of a shared
+/// content. Needs no login; answers 404 once the link has expired or been
+/// revoked, or while sharing is disabled.
+pub async fn get_shared(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<Json<SharedContent>> {
+    let link = resolve(&state, &token).await?;
+    let shared = ShareService::shared_content(&state.pool, &link).await?;
+    Ok(Json(shared))
+}
+
+/// GET /api/shared/{token}/thumbnail
+///
+/// Returns the thumbnail of a shared content, like
+/// `GET /api/contents/{id}/thumbnail`. Needs no login.
+pub async fn get_shared_thumbnail(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    query: Query<ThumbnailQuery>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse> {
+    let link = resolve(&state, &token).await?;
+    content::get_thumbnail(State(state), Path(link.content_id), query, headers).await
+}
+
+/// Path parameters for shared chapter requests.
+#[derive(Debug, Deserialize)]
+pub struct SharedChapterParams {
+    /// The share link token.
+    pub token: String,
+    /// The chapter ID.
+    pub chapter_id: i64,
+}
+
+/// GET /api/shared/{token}/chapters/{chapter}/pages
+///
+/// Lists the pages of a shared comic chapter, like
+/// `GET /api/contents/{id}/chapters/{chapter}/pages`. Needs no login.
+pub async fn list_shared_pages(
+    State(state): State<AppState>,
+    Path(params): Path<SharedChapterParams>,
+    query: Query<PageInfoQuery>,
+) -> Result<Json<Vec<PageInfo>>> {
+    let link = resolve(&state, &params.token).await?;
+    ShareService::check_chapter(&state.pool, &link, params.chapter_id).await?;
+    let params = ChapterParams {
+        content_id: link.content_id,
+        chapter_id: params.chapter_id,
+    };
+    content::list_pages(State(state), Path(params), query).await
+}
+
+/// Path parameters for shared page requests.
+#[derive(Debug, Deserialize)]
+pub struct SharedPageParams {
+    /// The share link token.
+    pub token: String,
+    /// The chapter ID.
+    pub chapter_id: i64,
+    /// The page index (0-based).
+    pub page: i64,
+}
+
+/// GET /api/shared/{token}/chapters/{chapter}/pages/{page}
+///
+/// Returns a page image of a shared comic chapter, like
+/// `GET /api/contents/{id}/chapters/{chapter}/pages/{page}`. Needs no
+/// login.
+pub async fn get_shared_page(
+    State(state): State<AppState>,
+    Path(params): Path<SharedPageParams>,
+    query: Query<PageQuery>,
+) -> Result<impl IntoResponse> {
+    let link = resolve(&state, &params.token).await?;
+    ShareService::check_chapter(&state.pool, &link, params.chapter_id).await?;
+    let params = PageParams {
+        content_id: link.content_id,
+        chapter_id: params.chapter_id,
+        page: params.page,
+    };
+    content::get_page(State(state), Path(params), query).await
+}
+
+/// Link of a token, while sharing is enabled.
+async fn resolve(state: &AppState, token: &str) -> Result<ShareLink> {
+    if !state.settings_service.sharing_enabled() {
+        return Err(AppError::NotFound(t!("share.disabled").to_string()));
+    }
+    ShareService::resolve(&state.pool, token).await
+}
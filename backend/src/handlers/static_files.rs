@@ -1,52 +1,139 @@
+//! Frontend files embedded in the binary.
+//!
+//! Vite names the files under `assets/` after a hash of their content, so
+//! browsers may cache them for good. Everything else, `index.html` in
+//! particular, is revalidated on each use through its ETag. Unknown paths
+//! outside the APIs get `index.html`, leaving them to the frontend router.
+
 use axum::{
     body::Body,
     extract::Path,
-    http::{HeaderValue, StatusCode, header},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
     response::{IntoResponse, Response},
 };
-use rust_embed::RustEmbed;
+use rust_embed::{EmbeddedFile, RustEmbed};
 
 #[derive(RustEmbed)]
 #[folder = "../frontend/dist"]
 struct Assets;
 
-pub async fn serve_index() -> impl IntoResponse {
-    serve_asset("index.html")
-}
-
-pub async fn serve_static(Path(path): Path<String>) -> impl IntoResponse {
-    serve_asset(&path)
-}
-
-fn serve_asset(path: &str) -> Response {
-    match Assets::get(path) {
-        Some(content) => {
-            let mime = mime_guess::from_path(path).first_or_octet_stream();
-            (
-                [(
-                    header::CONTENT_TYPE,
-                    HeaderValue::from_str(mime.as_ref()).unwrap(),
-                )],
-                Body::from(content.data),
-            )
-                .into_response()
-        }
-        None => {
-            // If the path starts with "api/", return 404 to avoid serving index.html for API errors
-            if path.starts_with("api/") {
-                return (StatusCode::NOT_FOUND, "404 Not Found").into_response();
-            }
-
-            // SPA Fallback: If file not found, serve index.html
-            if let Some(index) = Assets::get("index.html") {
-                (
-                    [(header::CONTENT_TYPE, HeaderValue::from_static("text/html"))],
-                    Body::from(index.data),
-                )
-                    .into_response()
-            } else {
-                (StatusCode::NOT_FOUND, "404 Not Found").into_response()
-            }
-        }
+/// Cache policy of files with a content hash in their name.
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+
+/// Cache policy of files that keep their name across releases.
+const REVALIDATE: &str = "no-cache";
+
+pub async fn serve_index(headers: HeaderMap) -> Response {
+    serve_asset("index.html", &headers)
+}
+
+pub async fn serve_static(Path(path): Path<String>, headers: HeaderMap) -> Response {
+    serve_asset(&path, &headers)
+}
+
+fn serve_asset(path: &str, headers: &HeaderMap) -> Response {
+    if let Some(file) = Assets::get(path) {
+        return file_response(path, file, headers);
+    }
+
+    // Unknown API paths are errors, not pages of the frontend
+    if is_api_path(path) {
+        return (StatusCode::NOT_FOUND, "404 Not Found").into_response();
+    }
+
+    // SPA Fallback: If file not found, serve index.html
+    match Assets::get("index.html") {
+        Some(index) => file_response("index.html", index, headers),
+        None => (StatusCode::NOT_FOUND, "404 Not Found").into_response(),
+    }
+}
+
+fn file_response(path: &str, file: EmbeddedFile, headers: &HeaderMap) -> Response {
+    let hash: String = file
+        .metadata
+        .sha256_hash()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    let etag = format!("\"{}\"", hash);
+    let cache_control = if is_hashed_asset(path) {
+        IMMUTABLE
+    } else {
+        REVALIDATE
+    };
+    let cache_headers = [
+        (header::ETAG, HeaderValue::from_str(&etag).unwrap()),
+        (
+            header::CACHE_CONTROL,
+            HeaderValue::from_static(cache_control),
+        ),
+    ];
+
+    if etag_matches(headers, &etag) {
+        return (StatusCode::NOT_MODIFIED, cache_headers).into_response();
+    }
+
+    let mime = mime_guess::from_path(path).first_or_octet_stream();
+    (
+        cache_headers,
+        [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_str(mime.as_ref()).unwrap(),
+        )],
+        Body::from(file.data),
+    )
+        .into_response()
+}
+
+/// Whether a path belongs to the HTTP APIs rather than the frontend.
+fn is_api_path(path: &str) -> bool {
+    ["api", "komga"]
+        .iter()
+        .any(|prefix| path == *prefix || path.starts_with(&format!("{}/", prefix)))
+}
+
+/// Whether Vite put a content hash in the file name.
+fn is_hashed_asset(path: &str) -> bool {
+    path.starts_with("assets/")
+}
+
+/// Whether `If-None-Match` lists `etag`, so the cached copy is current.
+fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|tag| tag.trim())
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_paths_are_not_spa_routes() {
+        assert!(is_api_path("api/libraries/1"));
+        assert!(is_api_path("komga/api/v1/series"));
+        assert!(is_api_path("api"));
+        assert!(!is_api_path("apis"));
+        assert!(!is_api_path("library/1"));
+    }
+
+    #[test]
+    fn test_etag_matches() {
+        let mut headers = HeaderMap::new();
+        assert!(!etag_matches(&headers, "\"abc\""));
+
+        headers.insert(
+            header::IF_NONE_MATCH,
+            HeaderValue::from_static("\"xyz\", W/\"abc\""),
+        );
+        assert!(etag_matches(&headers, "\"abc\""));
+        assert!(!etag_matches(&headers, "\"def\""));
+
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_static("*"));
+        assert!(etag_matches(&headers, "\"def\""));
     }
 }
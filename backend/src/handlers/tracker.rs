@@ -0,0 +1,238 @@
+//! Progress tracker handlers.
+//!
+//! This module provides HTTP handlers for syncing reading progress to
+//! AniList, MyAnimeList and Bangumi:
+//! - GET /api/users/me/trackers - Get the sync status of every tracker
+//! - POST /api/users/me/trackers/sync - Push progress to linked trackers now
+//! - POST /api/users/me/trackers/{tracker}/link - Start linking an account
+//! - DELETE /api/users/me/trackers/{tracker} - Unlink an account
+//! - POST /api/users/me/trackers/{tracker}/pull - Favorite the series in a Bangumi collection
+//! - GET /api/trackers/{tracker}/callback - Handle the tracker's redirect back
+//! - GET /api/contents/{id}/tracking - Get whether a content is synced
+//! - PUT /api/contents/{id}/tracking - Opt a content in or out of sync
+//!
+//! After the callback the browser is sent to the configured frontend URL
+//! with the result in the fragment: `#tracker_linked=<tracker>` on success
+//! or `#error=<message>` on failure.
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Redirect, Response},
+};
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::error::{AppError, Result};
+use crate::middlewares::auth::AuthUser;
+use crate::models::{
+    ContentTracking, TrackerKind, TrackerLinkResponse, TrackerPullResult, TrackerStatusResponse,
+    UpdateTrackingRequest,
+};
+use crate::services::tracker::TrackerService;
+use crate::state::AppState;
+use crate::t;
+use crate::utils::oauth_state::{self, read_cookie};
+
+/// Cookie holding the state token between linking and callback.
+const STATE_COOKIE: &str = "ryuri_tracker_state";
+
+/// Query parameters sent by the tracker to the callback.
+#[derive(Debug, Deserialize)]
+pub struct CallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
+
+/// GET /api/users/me/trackers
+///
+/// Returns the sync status of every tracker and the contents the current
+/// user keeps off them.
+pub async fn get_status(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<TrackerStatusResponse>> {
+    let status = state.tracker_service.status(auth_user.user_id).await?;
+    Ok(Json(status))
+}
+
+/// POST /api/users/me/trackers/sync
+///
+/// Pushes the current user's changed progress to their linked trackers
+/// without waiting for the background job, and returns the status
+/// afterwards.
+pub async fn sync(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<TrackerStatusResponse>> {
+    let status = state.tracker_service.sync_user(auth_user.user_id).await?;
+    Ok(Json(status))
+}
+
+/// POST /api/users/me/trackers/{tracker}/link
+///
+/// Returns the tracker authorization URL for linking an account to the
+/// current user. The frontend navigates to it.
+pub async fn link(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(tracker): Path<String>,
+) -> Result<Response> {
+    let tracker = parse_tracker(&tracker)?;
+    let service = &state.tracker_service;
+    let authorization = service.authorize(auth_user.user_id, tracker)?;
+
+    Ok((
+        [(
+            header::SET_COOKIE,
+            state_cookie(service, tracker, &authorization.state),
+        )],
+        Json(TrackerLinkResponse {
+            authorization_url: authorization.url,
+        }),
+    )
+        .into_response())
+}
+
+/// DELETE /api/users/me/trackers/{tracker}
+///
+/// Unlinks the current user's account on a tracker. Progress already pushed
+/// stays on the tracker.
+pub async fn unlink(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(tracker): Path<String>,
+) -> Result<StatusCode> {
+    let tracker = parse_tracker(&tracker)?;
+    state
+        .tracker_service
+        .unlink(auth_user.user_id, tracker)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/users/me/trackers/{tracker}/pull
+///
+/// Marks the contents in the current user's collection on the tracker as
+/// favorites, and gives them the reading status of their entry unless the
+/// user picked one. Only Bangumi collections can be pulled.
+pub async fn pull(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(tracker): Path<String>,
+) -> Result<Json<TrackerPullResult>> {
+    let tracker = parse_tracker(&tracker)?;
+    let result = state
+        .tracker_service
+        .pull_collection(auth_user.user_id, tracker)
+        .await?;
+    Ok(Json(result))
+}
+
+/// GET /api/trackers/{tracker}/callback
+///
+/// Verifies the tracker response, stores the account's tokens and redirects
+/// to the frontend.
+pub async fn callback(
+    State(state): State<AppState>,
+    Path(tracker): Path<String>,
+    headers: HeaderMap,
+    Query(query): Query<CallbackQuery>,
+) -> Result<Response> {
+    let tracker = parse_tracker(&tracker)?;
+    let service = &state.tracker_service;
+
+    let fragment = match complete(service, tracker, &headers, query).await {
+        Ok(()) => format!("tracker_linked={}", tracker.as_str()),
+        Err(e) => {
+            warn!(
+                tracker = tracker.as_str(),
+                error = %e,
+                "{}",
+                t!("tracker.callback_failed_log")
+            );
+            format!("error={}", urlencoding::encode(&e.error_message()))
+        }
+    };
+
+    let target = format!("{}#{}", service.frontend_redirect(), fragment);
+
+    Ok((
+        [(header::SET_COOKIE, state_cookie(service, tracker, ""))],
+        Redirect::to(&target),
+    )
+        .into_response())
+}
+
+/// GET /api/contents/{id}/tracking
+///
+/// Returns whether the current user's progress on a content is pushed to
+/// their trackers.
+pub async fn get_tracking(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(content_id): Path<i64>,
+) -> Result<Json<ContentTracking>> {
+    let tracking = state
+        .tracker_service
+        .get_tracking(auth_user.user_id, content_id)
+        .await?;
+    Ok(Json(tracking))
+}
+
+/// PUT /api/contents/{id}/tracking
+///
+/// Opts a content in or out of the current user's tracker sync.
+pub async fn update_tracking(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(content_id): Path<i64>,
+    Json(request): Json<UpdateTrackingRequest>,
+) -> Result<Json<ContentTracking>> {
+    let tracking = state
+        .tracker_service
+        .set_tracking(auth_user.user_id, content_id, request.enabled)
+        .await?;
+    Ok(Json(tracking))
+}
+
+/// Run the callback against the tracker.
+async fn complete(
+    service: &TrackerService,
+    tracker: TrackerKind,
+    headers: &HeaderMap,
+    query: CallbackQuery,
+) -> Result<()> {
+    if let Some(error) = query.error {
+        let message = query.error_description.unwrap_or(error);
+        return Err(AppError::Unauthorized(
+            t!("tracker.provider_error", error = message).to_string(),
+        ));
+    }
+
+    let (Some(code), Some(state_param)) = (query.code, query.state) else {
+        return Err(AppError::BadRequest(t!("tracker.missing_code").to_string()));
+    };
+
+    let cookie_state = read_cookie(headers, STATE_COOKIE);
+    service
+        .complete(tracker, &code, &state_param, cookie_state.as_deref())
+        .await?;
+    Ok(())
+}
+
+/// Parse a tracker path segment, failing with NotFound for unknown ones.
+fn parse_tracker(name: &str) -> Result<TrackerKind> {
+    TrackerKind::from_name(name)
+        .ok_or_else(|| AppError::NotFound(t!("tracker.unknown", tracker = name).to_string()))
+}
+
+/// Build the `Set-Cookie` value storing the state token, or removing it
+/// when `value` is empty.
+fn state_cookie(service: &TrackerService, tracker: TrackerKind, value: &str) -> String {
+    let secure = service.secure_cookies(tracker);
+    oauth_state::state_cookie(STATE_COOKIE, "/api/trackers", value, secure)
+}
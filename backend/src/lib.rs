@@ -4,16 +4,38 @@ pub fn set_lib_locale(locale: &str) {
     rust_i18n::set_locale(locale);
 }
 
+pub mod config;
 pub mod db;
 pub mod error;
 pub mod extractors;
+#[cfg(feature = "graphql")]
+pub mod graphql;
 pub mod handlers;
 pub mod middlewares;
 pub mod models;
+pub mod openapi;
 pub mod repository;
 pub mod router;
 pub mod services;
 pub mod state;
+#[cfg(test)]
+mod test_fixtures;
 /// Utils module.
 pub mod utils;
 
+/// Translate a message like [`rust_i18n::t!`], into the locale of the
+/// request being handled unless a `locale` is given.
+///
+/// See [`utils::locale::current_locale`].
+macro_rules! t {
+    ($key:expr) => {
+        rust_i18n::t!($key, locale = &crate::utils::locale::current_locale())
+    };
+    ($key:expr, locale = $locale:expr $(, $($args:tt)*)?) => {
+        rust_i18n::t!($key, locale = $locale $(, $($args)*)?)
+    };
+    ($key:expr, $($args:tt)+) => {
+        rust_i18n::t!($key, locale = &crate::utils::locale::current_locale(), $($args)+)
+    };
+}
+pub(crate) use t;
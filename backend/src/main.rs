@@ -3,15 +3,21 @@
 //! This is the main entry point for the comic reader backend server.
 //! It initializes the database, creates all services, and starts the HTTP server.
 
-use std::env;
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
 
 use argon2::password_hash::rand_core::{OsRng, RngCore};
-use backend::db::{DbConfig, init_db};
+use axum::Router;
+use axum_server::{Handle, tls_rustls::RustlsConfig};
+use backend::config::{Config, Listen, LogFormat, LoggingSettings};
+use backend::db::init_db;
 use backend::error::AppError;
+use backend::extractors::{ArchiveExtractor, TxtExtractor};
 use backend::router::create_router_with_layers;
-use backend::services::auth::AuthConfig;
-use backend::state::{AppConfig, AppState};
+use backend::services::consistency::ConsistencyService;
+use backend::services::content::ContentService;
+use backend::state::AppState;
 use backend::utils;
 use clap::Parser;
 use rust_i18n::t;
@@ -41,73 +47,27 @@ fn generate_random_secret_hex(byte_len: usize) -> String {
     out
 }
 
-/// Server configuration loaded from environment variables.
-struct ServerConfig {
-    host: String,
-    port: u16,
-    db: DbConfig,
-    app: AppConfig,
-}
-
-impl ServerConfig {
-    fn from_env() -> Self {
-        let host = env::var("HOST").unwrap_or_else(|_| "0.0.0.0".to_string());
-        let port = env::var("PORT")
-            .ok()
-            .and_then(|p| p.parse().ok())
-            .unwrap_or(3000);
-
-        let database_url =
-            env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite:ryuri.db?mode=rwc".to_string());
-
-        let jwt_secret = match env::var("JWT_SECRET") {
-            Ok(v) if !v.trim().is_empty() => v,
-            _ => {
-                let secret = generate_random_secret_hex(32);
-                warn!("{}", t!("server.jwt_secret_not_set"));
-                secret
-            }
-        };
-
-        let jwt_expiration_hours = env::var("JWT_EXPIRATION_HOURS")
-            .ok()
-            .and_then(|h| h.parse().ok())
-            .unwrap_or(24);
-
-        Self {
-            host,
-            port,
-            db: DbConfig {
-                database_url,
-                max_connections: 5,
-            },
-            app: AppConfig {
-                auth: AuthConfig {
-                    jwt_secret,
-                    jwt_expiration_hours,
-                },
-            },
-        }
-    }
-}
-
 /// Initialize the tracing subscriber with env-filter support.
 ///
-/// Log levels can be controlled via the RUST_LOG environment variable:
+/// The log level comes from the configuration (`logging.level` or
+/// `LOG_LEVEL`); RUST_LOG, when set, takes precedence:
 /// - RUST_LOG=debug - Detailed debug information
 /// - RUST_LOG=info - Normal operation information (default)
 /// - RUST_LOG=warn - Warnings and errors only
 /// - RUST_LOG=backend=debug,sqlx=warn - Module-level control
-fn init_tracing() {
-    tracing_subscriber::registry()
-        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
-        .with(
-            tracing_subscriber::fmt::layer()
-                .with_target(false)
-                .with_timer(ChronoLocal::new(String::from("%x %X")))
-                .compact(),
-        )
-        .init();
+fn init_tracing(logging: &LoggingSettings) {
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&logging.level));
+    let layer = tracing_subscriber::fmt::layer()
+        .with_target(false)
+        .with_timer(ChronoLocal::new(String::from("%x %X")));
+
+    let registry = tracing_subscriber::registry().with(filter);
+    match logging.format {
+        LogFormat::Compact => registry.with(layer.compact()).init(),
+        LogFormat::Full => registry.with(layer).init(),
+        LogFormat::Pretty => registry.with(layer.pretty()).init(),
+    }
 }
 
 #[tokio::main]
@@ -115,25 +75,86 @@ async fn main() -> Result<(), AppError> {
     // Parse CLI arguments
     let _args = Args::parse();
 
-    // Initialize tracing first
-    init_tracing();
-
-    // Initialize i18n
+    // Initialize i18n first so configuration errors are localized
     utils::init_i18n();
 
-    let config = ServerConfig::from_env();
+    // Load configuration before tracing, which depends on it
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    init_tracing(&config.logging);
 
     info!("{}", t!("server.starting", version = env!("RYURI_VERSION")));
-    debug!(host = %config.host, port = %config.port, database = %config.db.database_url, "{}", t!("server.config_loaded"));
+    debug!(host = %config.server.host, port = %config.server.port, database = %config.database.url, "{}", t!("server.config_loaded"));
+
+    if let Some(cache_dir) = &config.thumbnails.cache_dir {
+        std::fs::create_dir_all(cache_dir).map_err(|e| {
+            AppError::Internal(
+                t!(
+                    "config.cache_dir_failed",
+                    path = cache_dir.display(),
+                    error = e
+                )
+                .to_string(),
+            )
+        })?;
+        ArchiveExtractor::set_scratch_dir(cache_dir.clone());
+    }
+    ContentService::set_archive_cache_size(config.reader.archive_cache_size);
+    ContentService::set_page_blurhash(config.reader.page_blurhash);
+    TxtExtractor::set_splitter(config.chapter_splitter());
+
+    let jwt_secret = config.auth.jwt_secret.clone().unwrap_or_else(|| {
+        warn!("{}", t!("server.jwt_secret_not_set"));
+        generate_random_secret_hex(32)
+    });
 
     info!("{}", t!("server.init_db"));
-    let pool = init_db(&config.db).await?;
+    let pool = init_db(&config.db_config()).await?;
     info!("{}", t!("server.db_initialized"));
 
     info!("{}", t!("server.create_services"));
-    let state = AppState::new(pool, config.app);
+    let state = AppState::new(pool, config.app_config(jwt_secret));
     info!("{}", t!("server.services_created"));
 
+    // Repair drift left by an unclean shutdown before any scan runs
+    if let Err(e) = ConsistencyService::check_and_repair(&state.pool, &state.audit_service).await {
+        warn!(error = %e, "{}", t!("consistency.check_failed"));
+    }
+
+    // Thumbnails stored by older versions have no BlurHash yet
+    let pool = state.pool.clone();
+    tokio::spawn(async move {
+        if let Err(e) = ContentService::backfill_thumbnail_blurhashes(&pool).await {
+            warn!(error = %e, "{}", t!("content.thumbnail_blurhash_backfill_failed"));
+        }
+    });
+
+    // Settings saved by administrators override the configured defaults
+    match state.settings_service.load().await {
+        Ok(settings) => state.apply_settings(&settings),
+        Err(e) => warn!(error = %e, "{}", t!("settings.load_failed")),
+    }
+    let mut settings_changes = state.settings_service.subscribe();
+    let settings_state = state.clone();
+    tokio::spawn(async move {
+        while settings_changes.changed().await.is_ok() {
+            let settings = settings_changes.borrow_and_update().clone();
+            settings_state.apply_settings(&settings);
+            info!("{}", t!("settings.applied"));
+        }
+    });
+
+    // Queue scans saved at the last shutdown before the worker starts
+    if let Err(e) = state.scan_queue_service.restore_tasks(&state.pool).await {
+        warn!(error = %e, "{}", t!("scan_queue.restore_failed"));
+    }
+
     // Start the scan queue worker to process submitted scan tasks
     info!("{}", t!("server.start_worker"));
     state.scan_queue_service.start_worker().await;
@@ -141,29 +162,122 @@ async fn main() -> Result<(), AppError> {
     // Restore scheduled scans
     state.scheduler_service.restore_schedules(&state.pool).await;
 
+    // Schedule the nightly database backup
+    if let Some(hour) = config.backup.nightly_hour {
+        state.scheduler_service.schedule_backups(hour).await;
+    }
+
+    // Push reading progress to linked tracker accounts
+    let tracker_sync_interval = state.tracker_service.sync_interval_minutes();
+    if tracker_sync_interval > 0 && !state.tracker_service.configured().is_empty() {
+        state
+            .scheduler_service
+            .schedule_tracker_sync(Arc::clone(&state.tracker_service), tracker_sync_interval)
+            .await;
+    }
+
     // Restore file watchers for libraries with watch_mode enabled
     state.watch_service.restore_watchers().await;
 
-    let app = create_router_with_layers(state);
+    // Request shutdown on SIGINT/SIGTERM; the HTTP server drains first
+    let shutdown = Arc::clone(&state.shutdown);
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        shutdown.request();
+    });
+    let shutdown_requested = state.shutdown.requested();
+    let shutdown = Arc::clone(&state.shutdown);
 
-    let addr: SocketAddr = format!("{}:{}", config.host, config.port)
-        .parse()
-        .map_err(|e| AppError::Internal(format!("Invalid address: {}", e)))?;
+    let app = create_router_with_layers(state);
 
-    info!(%addr, "{}", t!("server.starting_server"));
-    let listener = tokio::net::TcpListener::bind(addr)
-        .await
-        .map_err(|e| AppError::Internal(format!("Failed to bind: {}", e)))?;
+    let listen = config.listen().map_err(AppError::Internal)?;
+    let shutdown_timeout = Duration::from_secs(config.server.shutdown_timeout_secs);
+    serve(app, listen, shutdown_requested, shutdown_timeout).await?;
 
-    info!("{}", t!("server.server_running"));
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await
-        .map_err(|e| AppError::Internal(format!("Server error: {}", e)))?;
+    // Stop background services and save scan task state
+    shutdown.shutdown().await;
 
     Ok(())
 }
 
+/// Serve `app` until `shutdown_requested` completes.
+async fn serve(
+    app: Router,
+    listen: Listen,
+    shutdown_requested: impl Future<Output = ()> + Send + 'static,
+    shutdown_timeout: Duration,
+) -> Result<(), AppError> {
+    let bind_error = |e: std::io::Error| AppError::Internal(format!("Failed to bind: {}", e));
+    let server_error = |e: std::io::Error| AppError::Internal(format!("Server error: {}", e));
+
+    match listen {
+        Listen::Http(addr) => {
+            info!(%addr, "{}", t!("server.starting_server"));
+            let listener = tokio::net::TcpListener::bind(addr)
+                .await
+                .map_err(bind_error)?;
+
+            info!("{}", t!("server.server_running"));
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .with_graceful_shutdown(shutdown_requested)
+            .await
+            .map_err(server_error)
+        }
+        Listen::Https { addr, cert, key } => {
+            info!(%addr, tls = true, "{}", t!("server.starting_server"));
+            let tls = RustlsConfig::from_pem_file(&cert, &key)
+                .await
+                .map_err(|e| {
+                    AppError::Internal(format!("Failed to load TLS certificate: {}", e))
+                })?;
+
+            // axum-server stops through a handle rather than a future
+            let handle = Handle::new();
+            let shutdown_handle = handle.clone();
+            tokio::spawn(async move {
+                shutdown_requested.await;
+                shutdown_handle.graceful_shutdown(Some(shutdown_timeout));
+            });
+
+            info!("{}", t!("server.server_running"));
+            axum_server::bind_rustls(addr, tls)
+                .handle(handle)
+                .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+                .await
+                .map_err(server_error)
+        }
+        #[cfg(unix)]
+        Listen::Unix(path) => {
+            use std::os::unix::fs::FileTypeExt;
+
+            info!(path = %path.display(), "{}", t!("server.starting_server"));
+            // A socket left behind by an unclean shutdown blocks binding
+            if let Ok(metadata) = std::fs::symlink_metadata(&path)
+                && metadata.file_type().is_socket()
+            {
+                info!(path = %path.display(), "{}", t!("server.removing_stale_socket"));
+                std::fs::remove_file(&path).map_err(bind_error)?;
+            }
+            let listener = tokio::net::UnixListener::bind(&path).map_err(bind_error)?;
+
+            // No peer address over a Unix socket; clients are read from X-Forwarded-For
+            info!("{}", t!("server.server_running"));
+            let result = axum::serve(listener, app)
+                .with_graceful_shutdown(shutdown_requested)
+                .await;
+            let _ = std::fs::remove_file(&path);
+            result.map_err(server_error)
+        }
+        #[cfg(not(unix))]
+        Listen::Unix(_) => Err(AppError::Internal(
+            t!("config.unix_socket_unsupported").to_string(),
+        )),
+    }
+}
+
 async fn shutdown_signal() {
     let ctrl_c = async {
         signal::ctrl_c()
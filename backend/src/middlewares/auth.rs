@@ -13,12 +13,13 @@ use axum::{
     response::Response,
 };
 use std::borrow::Cow;
-use rust_i18n::t;
 
-use crate::error::AppError;
+use crate::error::{AppError, ErrorCode};
+use crate::middlewares::locale::run_in_user_locale;
 use crate::models::{JwtClaims, User};
 use crate::repository::{apikey::ApiKeyRepository, user::UserRepository};
 use crate::state::AppState;
+use crate::t;
 
 /// Authenticated user information extracted from JWT token.
 ///
@@ -77,19 +78,25 @@ pub async fn auth_middleware(
         .and_then(|value| value.to_str().ok())
     {
         if let Some(api_key) = ApiKeyRepository::get_by_key(&state.pool, api_key_header).await?
-            && let Some(user) = UserRepository::find_by_id(&state.pool, api_key.user_id).await? {
-                let auth_user = AuthUser::from(user);
-                req.extensions_mut().insert(auth_user);
-                return Ok(next.run(req).await);
-            }
+            && let Some(user) = UserRepository::find_by_id(&state.pool, api_key.user_id).await?
+        {
+            let locale = user.locale.clone();
+            let auth_user = AuthUser::from(user);
+            req.extensions_mut().insert(auth_user);
+            return Ok(run_in_user_locale(locale.as_deref(), req, next).await);
+        }
         // If API key is invalid, we don't return error immediately, we fall back to JWT check
         // or maybe we should return error? Usually if explicit auth method is provided and fails, we fail.
         // But for now let's strict fail if header is present but invalid.
-        return Err(AppError::Unauthorized(t!("auth.invalid_api_key").to_string()));
+        return Err(
+            AppError::Unauthorized(t!("auth.invalid_api_key").to_string())
+                .with_code(ErrorCode::InvalidToken),
+        );
     }
 
     // 2. Prefer Authorization: Bearer <token>. If absent, optionally accept `?token=`
-    // for image resources so the frontend can use <img src> (progressive loading).
+    // for image resources so the frontend can use <img src> (progressive loading),
+    // for EPUB images and stylesheets, and for downloads started from plain links.
     let token: Cow<'_, str> = if let Some(auth_header) = req
         .headers()
         .get(AUTHORIZATION)
@@ -99,28 +106,33 @@ pub async fn auth_middleware(
         Cow::Borrowed(auth_header.strip_prefix("Bearer ").ok_or_else(|| {
             tracing::warn!("{}", t!("auth.invalid_auth_header_format"));
             AppError::Unauthorized(t!("auth.invalid_auth_header_format_error").to_string())
+                .with_code(ErrorCode::InvalidToken)
         })?)
     } else {
-        // Only allow query token for safe, cacheable-ish image reads.
-        // We intentionally scope this to image endpoints to avoid broad token-in-URL usage.
+        // Only allow query token for safe, cacheable-ish image reads and downloads.
+        // We intentionally scope this to these endpoints to avoid broad token-in-URL usage.
         let method = req.method().clone();
         let path = req.uri().path();
-        let is_image_resource = path.starts_with("/api/contents/")
-            && (path.contains("/pages/") || path.ends_with("/thumbnail"));
-
-        if !matches!(method, Method::GET | Method::HEAD) || !is_image_resource {
+        let is_file_resource = path.starts_with("/api/contents/")
+            && (path.contains("/pages/")
+                || path.contains("/epub/resources/")
+                || path.ends_with("/audio/stream")
+                || path.ends_with("/thumbnail")
+                || path.ends_with("/download"));
+
+        if !matches!(method, Method::GET | Method::HEAD) || !is_file_resource {
             tracing::warn!("{}", t!("auth.missing_auth_header_log"));
-            return Err(AppError::Unauthorized(
-                t!("auth.missing_auth_header").to_string(),
-            ));
+            return Err(
+                AppError::Unauthorized(t!("auth.missing_auth_header").to_string())
+                    .with_code(ErrorCode::InvalidToken),
+            );
         }
 
         let query = req.uri().query().unwrap_or("");
         let token = extract_query_param(query, "token").ok_or_else(|| {
-            tracing::warn!(
-                "{}", t!("auth.missing_auth_header_and_token_log")
-            );
+            tracing::warn!("{}", t!("auth.missing_auth_header_and_token_log"));
             AppError::Unauthorized(t!("auth.missing_auth_header").to_string())
+                .with_code(ErrorCode::InvalidToken)
         })?;
 
         Cow::Owned(token)
@@ -135,12 +147,25 @@ pub async fn auth_middleware(
             e
         })?;
 
+    // Tokens issued before the last password change are revoked
+    let session = UserRepository::find_session(&state.pool, claims.sub).await?;
+    if let Some((_, token_version)) = session
+        && token_version != claims.ver
+    {
+        tracing::warn!(user_id = claims.sub, "{}", t!("auth.session_revoked_log"));
+        return Err(
+            AppError::Unauthorized(t!("auth.session_revoked").to_string())
+                .with_code(ErrorCode::InvalidToken),
+        );
+    }
+    let locale = session.and_then(|(locale, _)| locale);
+
     // Convert claims to AuthUser and store in request extensions
     let auth_user = AuthUser::from(claims);
     req.extensions_mut().insert(auth_user);
 
-    // Continue to the next middleware or handler
-    Ok(next.run(req).await)
+    // Continue in the user's locale to the next middleware or handler
+    Ok(run_in_user_locale(locale.as_deref(), req, next).await)
 }
 
 /// Extract a query parameter value by key from a raw query string.
@@ -179,10 +204,37 @@ where
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
         // Extract AuthUser from request extensions
-        parts.extensions.get::<AuthUser>().cloned().ok_or_else(|| {
-            AppError::Unauthorized(
-                t!("auth.missing_authentication").to_string(),
-            )
-        })
+        parts
+            .extensions
+            .get::<AuthUser>()
+            .cloned()
+            .ok_or_else(|| AppError::Unauthorized(t!("auth.missing_authentication").to_string()))
     }
-}
\ No newline at end of file
+}
+/// Extractor for an authenticated user with administrator rights.
+///
+/// Loads the user to check the admin flag, so revoking admin rights takes
+/// effect without waiting for tokens to expire. Returns 403 for other users.
+#[derive(Debug, Clone)]
+pub struct AdminUser(pub AuthUser);
+
+impl FromRequestParts<AppState> for AdminUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let auth_user = AuthUser::from_request_parts(parts, state).await?;
+
+        let is_admin = UserRepository::find_by_id(&state.pool, auth_user.user_id)
+            .await?
+            .is_some_and(|user| user.is_admin);
+        if !is_admin {
+            return Err(AppError::Forbidden(t!("auth.admin_required").to_string())
+                .with_code(ErrorCode::AdminRequired));
+        }
+
+        Ok(Self(auth_user))
+    }
+}
@@ -0,0 +1,260 @@
+//! Client IP address resolution and filtering.
+//!
+//! The client IP is the peer address of the connection, unless the peer is
+//! a trusted reverse proxy: then it is read from `X-Forwarded-For`, from the
+//! right, skipping the trusted proxies the request went through. Over a Unix
+//! socket every peer is a local process, so the header is always trusted.
+//! The resolved address is what rate limiting and the audit log record, and
+//! what the allow and deny lists are checked against.
+
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use axum::{
+    extract::{ConnectInfo, FromRequestParts, Request, State},
+    http::{Extensions, HeaderMap, HeaderName, request::Parts},
+    middleware::Next,
+    response::Response,
+};
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::error::AppError;
+use crate::middlewares::request_id::redacted_path;
+use crate::t;
+use crate::utils::ip_network::IpNetwork;
+
+/// Header listing the addresses a request was forwarded for.
+pub static X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+
+/// IP address of the connected client, or `"unknown"` when the server was
+/// not started with connection info (e.g. in tests).
+#[derive(Debug, Clone)]
+pub struct ClientIp(pub String);
+
+impl ClientIp {
+    /// Resolve the client IP from request extensions: the address set by
+    /// [`client_ip_middleware`], or else the peer address.
+    pub fn from_extensions(extensions: &Extensions) -> Self {
+        if let Some(ip) = extensions.get::<ClientIp>() {
+            return ip.clone();
+        }
+        let ip = extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip().to_canonical().to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        Self(ip)
+    }
+}
+
+impl<S> FromRequestParts<S> for ClientIp
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_extensions(&parts.extensions))
+    }
+}
+
+/// Client IP settings. Entries are addresses or networks in CIDR notation
+/// such as `192.168.1.0/24`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClientIpConfig {
+    /// Clients allowed to connect. Empty allows every client.
+    pub allow: Vec<String>,
+    /// Clients refused even when allowed.
+    pub deny: Vec<String>,
+    /// Reverse proxies whose `X-Forwarded-For` header is trusted.
+    pub trusted_proxies: Vec<String>,
+}
+
+impl ClientIpConfig {
+    /// Entries that are not valid addresses or networks.
+    pub fn invalid_entries(&self) -> impl Iterator<Item = &String> {
+        self.allow
+            .iter()
+            .chain(&self.deny)
+            .chain(&self.trusted_proxies)
+            .filter(|entry| entry.parse::<IpNetwork>().is_err())
+    }
+
+    /// Parse the lists into the rules used by [`client_ip_middleware`].
+    /// Invalid entries are skipped; the configuration rejects them.
+    pub fn rules(&self) -> Arc<ClientIpRules> {
+        let parse = |entries: &[String]| -> Vec<IpNetwork> {
+            entries.iter().filter_map(|e| e.parse().ok()).collect()
+        };
+        Arc::new(ClientIpRules {
+            allow: parse(&self.allow),
+            deny: parse(&self.deny),
+            trusted_proxies: parse(&self.trusted_proxies),
+        })
+    }
+}
+
+/// Parsed allow, deny and trusted proxy lists.
+#[derive(Debug, Default)]
+pub struct ClientIpRules {
+    allow: Vec<IpNetwork>,
+    deny: Vec<IpNetwork>,
+    trusted_proxies: Vec<IpNetwork>,
+}
+
+impl ClientIpRules {
+    /// Address of the client, given the peer address of the connection
+    /// (`None` over a Unix socket).
+    pub fn resolve(&self, peer: Option<IpAddr>, headers: &HeaderMap) -> Option<IpAddr> {
+        let peer = peer.map(|ip| ip.to_canonical());
+        if peer.is_some_and(|ip| !self.is_trusted_proxy(ip)) {
+            return peer;
+        }
+
+        let hops: Vec<&str> = headers
+            .get_all(&X_FORWARDED_FOR)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .collect();
+        let mut client = peer;
+        for hop in hops.into_iter().rev() {
+            let Ok(ip) = hop.trim().parse::<IpAddr>() else {
+                break;
+            };
+            client = Some(ip.to_canonical());
+            if !self.is_trusted_proxy(ip) {
+                break;
+            }
+        }
+        client
+    }
+
+    /// Whether a client may connect. Unknown clients are only allowed when
+    /// there is no allow list.
+    pub fn allows(&self, ip: Option<IpAddr>) -> bool {
+        match ip {
+            Some(ip) => {
+                !self.deny.iter().any(|net| net.contains(ip))
+                    && (self.allow.is_empty() || self.allow.iter().any(|net| net.contains(ip)))
+            }
+            None => self.allow.is_empty(),
+        }
+    }
+
+    fn is_trusted_proxy(&self, ip: IpAddr) -> bool {
+        self.trusted_proxies.iter().any(|net| net.contains(ip))
+    }
+}
+
+/// Middleware resolving the client IP, available to handlers through the
+/// [`ClientIp`] extractor, and refusing clients not allowed to connect
+/// with 403.
+pub async fn client_ip_middleware(
+    State(rules): State<Arc<ClientIpRules>>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    let ip = rules.resolve(peer, req.headers());
+
+    if !rules.allows(ip) {
+        let path = redacted_path(req.uri().path());
+        warn!(ip = ?ip, path = %path, "{}", t!("client_ip.denied_log"));
+        return Err(AppError::Forbidden(t!("client_ip.denied").to_string()));
+    }
+
+    let ip = ip.map_or_else(|| "unknown".to_string(), |ip| ip.to_string());
+    req.extensions_mut().insert(ClientIp(ip));
+    Ok(next.run(req).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn rules(allow: &[&str], deny: &[&str], trusted_proxies: &[&str]) -> Arc<ClientIpRules> {
+        let list = |entries: &[&str]| entries.iter().map(|e| e.to_string()).collect();
+        ClientIpConfig {
+            allow: list(allow),
+            deny: list(deny),
+            trusted_proxies: list(trusted_proxies),
+        }
+        .rules()
+    }
+
+    fn forwarded_for(values: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append(&X_FORWARDED_FOR, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    fn ip(s: &str) -> Option<IpAddr> {
+        Some(s.parse().unwrap())
+    }
+
+    #[test]
+    fn test_forwarded_for_is_only_trusted_from_proxies() {
+        let rules = rules(&[], &[], &["10.0.0.0/8"]);
+        let headers = forwarded_for(&["203.0.113.7, 10.0.0.2"]);
+
+        // Direct clients cannot spoof their address
+        assert_eq!(
+            rules.resolve(ip("198.51.100.1"), &headers),
+            ip("198.51.100.1")
+        );
+        // Trusted proxies are skipped from the right
+        assert_eq!(rules.resolve(ip("10.0.0.1"), &headers), ip("203.0.113.7"));
+        assert_eq!(
+            rules.resolve(ip("::ffff:10.0.0.1"), &headers),
+            ip("203.0.113.7")
+        );
+        // Addresses left of the first untrusted one may be forged
+        let headers = forwarded_for(&["1.1.1.1", "203.0.113.7"]);
+        assert_eq!(rules.resolve(ip("10.0.0.1"), &headers), ip("203.0.113.7"));
+        // Without the header the proxy is the client
+        assert_eq!(
+            rules.resolve(ip("10.0.0.1"), &HeaderMap::new()),
+            ip("10.0.0.1")
+        );
+        let headers = forwarded_for(&["garbage"]);
+        assert_eq!(rules.resolve(ip("10.0.0.1"), &headers), ip("10.0.0.1"));
+        // Unix socket peers are local proxies
+        let headers = forwarded_for(&["203.0.113.7"]);
+        assert_eq!(rules.resolve(None, &headers), ip("203.0.113.7"));
+        assert_eq!(rules.resolve(None, &HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn test_allow_and_deny_lists() {
+        let open = rules(&[], &[], &[]);
+        assert!(open.allows(ip("203.0.113.7")));
+        assert!(open.allows(None));
+
+        let rules = rules(&["192.168.0.0/16", "fd00::/8"], &["192.168.1.13"], &[]);
+        assert!(rules.allows(ip("192.168.1.12")));
+        assert!(rules.allows(ip("fd00::1")));
+        assert!(!rules.allows(ip("192.168.1.13")));
+        assert!(!rules.allows(ip("203.0.113.7")));
+        assert!(!rules.allows(None));
+    }
+
+    #[test]
+    fn test_invalid_entries() {
+        let config = ClientIpConfig {
+            allow: vec!["10.0.0.0/8".to_string()],
+            deny: vec!["10.0.0.0/40".to_string()],
+            trusted_proxies: vec!["proxy".to_string()],
+        };
+        let invalid: Vec<&String> = config.invalid_entries().collect();
+        assert_eq!(invalid, ["10.0.0.0/40", "proxy"]);
+    }
+}
@@ -0,0 +1,167 @@
+//! Response compression and request body limits.
+//!
+//! JSON, HTML and the frontend's scripts and styles are compressed with
+//! gzip or Brotli, whichever the client accepts. Images, audio, video and
+//! book downloads are already compressed and are sent as they are.
+
+use axum::extract::DefaultBodyLimit;
+use serde::Deserialize;
+use tower_http::compression::{
+    CompressionLayer, DefaultPredicate, Predicate,
+    predicate::{And, NotForContentType},
+};
+
+/// Content types that gain nothing from compression, besides the images
+/// and event streams skipped by [`DefaultPredicate`]. Types are matched by
+/// prefix, so `audio/` covers every audio type.
+const PRECOMPRESSED_TYPES: [&str; 8] = [
+    "audio/",
+    "video/",
+    "application/zip",
+    "application/epub+zip",
+    "application/vnd.comicbook+zip",
+    "application/pdf",
+    "application/octet-stream",
+    "application/x-mobipocket-ebook",
+];
+
+/// Responses worth compressing.
+pub type CompressionPredicate = And<DefaultPredicate, NotForContentTypes>;
+
+/// Skips responses of any of [`PRECOMPRESSED_TYPES`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NotForContentTypes;
+
+impl Predicate for NotForContentTypes {
+    fn should_compress<B>(&self, response: &axum::http::Response<B>) -> bool
+    where
+        B: axum::body::HttpBody,
+    {
+        PRECOMPRESSED_TYPES.iter().all(|&content_type| {
+            NotForContentType::const_new(content_type).should_compress(response)
+        })
+    }
+}
+
+/// Compression and body size settings.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HttpLimitsConfig {
+    /// Compress responses for clients that accept it.
+    pub compression: bool,
+    /// Largest request body accepted by most endpoints, in megabytes.
+    /// Backup restore and library import have their own limit.
+    pub max_body_size_mb: u64,
+}
+
+impl Default for HttpLimitsConfig {
+    fn default() -> Self {
+        Self {
+            compression: true,
+            max_body_size_mb: 2,
+        }
+    }
+}
+
+impl HttpLimitsConfig {
+    /// Layer compressing responses, passing them through unchanged when
+    /// compression is disabled.
+    pub fn compression_layer(&self) -> CompressionLayer<CompressionPredicate> {
+        CompressionLayer::new()
+            .gzip(self.compression)
+            .br(self.compression)
+            .compress_when(DefaultPredicate::new().and(NotForContentTypes))
+    }
+
+    /// Largest request body accepted by most endpoints, in bytes.
+    pub fn max_body_size(&self) -> u64 {
+        self.max_body_size_mb * 1024 * 1024
+    }
+
+    /// Layer limiting request bodies to `max_body_size_mb`.
+    pub fn body_limit_layer(&self) -> DefaultBodyLimit {
+        DefaultBodyLimit::max(self.max_body_size() as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Router,
+        body::Body,
+        extract::Request,
+        http::{StatusCode, header},
+        routing::{get, post},
+    };
+    use tower::ServiceExt;
+
+    fn app(config: HttpLimitsConfig) -> Router {
+        let text = "a".repeat(4096);
+        let zip = text.clone();
+        let audio = text.clone();
+        Router::new()
+            .route("/text", get(move || async move { text }))
+            .route(
+                "/zip",
+                get(move || async move { ([(header::CONTENT_TYPE, "application/zip")], zip) }),
+            )
+            .route(
+                "/audio",
+                get(move || async move { ([(header::CONTENT_TYPE, "audio/mp4")], audio) }),
+            )
+            .route(
+                "/upload",
+                post(|body: String| async move { body.len().to_string() }),
+            )
+            .layer(config.body_limit_layer())
+            .layer(config.compression_layer())
+    }
+
+    async fn encoding(app: Router, uri: &str) -> Option<String> {
+        let request = Request::builder()
+            .uri(uri)
+            .header(header::ACCEPT_ENCODING, "br")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        response
+            .headers()
+            .get(header::CONTENT_ENCODING)
+            .map(|value| value.to_str().unwrap().to_string())
+    }
+
+    #[tokio::test]
+    async fn test_compression() {
+        let config = HttpLimitsConfig::default();
+        assert_eq!(encoding(app(config), "/text").await.as_deref(), Some("br"));
+        assert_eq!(encoding(app(config), "/zip").await, None);
+        assert_eq!(encoding(app(config), "/audio").await, None);
+
+        let config = HttpLimitsConfig {
+            compression: false,
+            ..Default::default()
+        };
+        assert_eq!(encoding(app(config), "/text").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_body_limit() {
+        let config = HttpLimitsConfig {
+            max_body_size_mb: 1,
+            ..Default::default()
+        };
+        let upload = |size: usize| {
+            Request::builder()
+                .method("POST")
+                .uri("/upload")
+                .body(Body::from(vec![b'a'; size]))
+                .unwrap()
+        };
+
+        let response = app(config).oneshot(upload(1024)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app(config).oneshot(upload(2 * 1024 * 1024)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
@@ -0,0 +1,54 @@
+//! Authentication and runtime toggle of the Kobo sync API.
+//!
+//! Kobo e-readers cannot send custom headers, so the API key of the user
+//! goes into the store URL configured on the device:
+//! `/kobo/{api_key}/v1/...`.
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::error::{AppError, ErrorCode, Result};
+use crate::middlewares::{AuthUser, locale::run_in_user_locale};
+use crate::repository::{apikey::ApiKeyRepository, user::UserRepository};
+use crate::state::AppState;
+use crate::t;
+
+/// Middleware answering 404 for the Kobo sync API while it is disabled in
+/// the server settings, and authenticating the API key in the path
+/// otherwise.
+pub async fn kobo_auth_middleware(
+    State(state): State<AppState>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response> {
+    if !state.settings_service.kobo_enabled() {
+        return Err(AppError::NotFound(t!("kobo.disabled").to_string()));
+    }
+
+    let key = req
+        .uri()
+        .path()
+        .strip_prefix("/kobo/")
+        .and_then(|rest| rest.split('/').next())
+        .unwrap_or_default();
+    let Some(api_key) = ApiKeyRepository::get_by_key(&state.pool, key).await? else {
+        tracing::warn!("{}", t!("kobo.invalid_key_log"));
+        return Err(
+            AppError::Unauthorized(t!("auth.invalid_api_key").to_string())
+                .with_code(ErrorCode::InvalidToken),
+        );
+    };
+    let user = UserRepository::find_by_id(&state.pool, api_key.user_id)
+        .await?
+        .ok_or_else(|| {
+            AppError::Unauthorized(t!("auth.invalid_api_key").to_string())
+                .with_code(ErrorCode::InvalidToken)
+        })?;
+
+    let locale = user.locale.clone();
+    req.extensions_mut().insert(AuthUser::from(user));
+    Ok(run_in_user_locale(locale.as_deref(), req, next).await)
+}
@@ -0,0 +1,24 @@
+//! Runtime toggle of the Komga-compatible API.
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::error::{AppError, Result};
+use crate::state::AppState;
+use crate::t;
+
+/// Middleware answering 404 for the Komga-compatible API while it is
+/// disabled in the server settings.
+pub async fn komga_enabled_middleware(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Result<Response> {
+    if !state.settings_service.komga_enabled() {
+        return Err(AppError::NotFound(t!("komga.disabled").to_string()));
+    }
+    Ok(next.run(req).await)
+}
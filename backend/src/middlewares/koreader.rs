@@ -0,0 +1,43 @@
+//! Authentication and runtime toggle of the KOReader sync API.
+//!
+//! KOReader devices send the username in `x-auth-user` and the MD5 of the
+//! password in `x-auth-key`. Passwords are only stored as Argon2 hashes, so
+//! devices log in with one of the user's API keys as the password.
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::error::{AppError, Result};
+use crate::middlewares::{AuthUser, locale::run_in_user_locale};
+use crate::services::koreader::KoreaderService;
+use crate::state::AppState;
+use crate::t;
+
+/// Middleware answering 404 for the KOReader sync API while it is disabled
+/// in the server settings, and authenticating the device otherwise.
+pub async fn koreader_auth_middleware(
+    State(state): State<AppState>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response> {
+    if !state.settings_service.koreader_enabled() {
+        return Err(AppError::NotFound(t!("koreader.disabled").to_string()));
+    }
+
+    let header = |name: &str| {
+        req.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let (username, key) = (header("x-auth-user"), header("x-auth-key"));
+    let user = KoreaderService::authenticate(&state.pool, &username, &key).await?;
+
+    let locale = user.locale.clone();
+    req.extensions_mut().insert(AuthUser::from(user));
+    Ok(run_in_user_locale(locale.as_deref(), req, next).await)
+}
@@ -0,0 +1,73 @@
+//! Per-request locale.
+//!
+//! Messages produced while handling a request, error messages included,
+//! are translated into the locale negotiated from its `Accept-Language`
+//! header, or the server locale when none is supported. Once a request is
+//! authenticated, the preferred locale of the user takes precedence.
+
+use axum::{extract::Request, http::header, middleware::Next, response::Response};
+
+use crate::utils::locale::{negotiate_locale, supported_locale, with_locale};
+
+/// Middleware translating the messages of a request into the locale asked
+/// for by the client.
+pub async fn locale_middleware(req: Request, next: Next) -> Response {
+    let locale = req
+        .headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(negotiate_locale)
+        .map(str::to_string)
+        .unwrap_or_else(|| rust_i18n::locale().to_string());
+    with_locale(locale, next.run(req)).await
+}
+
+/// Run the rest of the request in the preferred locale of the signed-in
+/// user, if they set a supported one.
+pub async fn run_in_user_locale(preferred: Option<&str>, req: Request, next: Next) -> Response {
+    match preferred.and_then(supported_locale) {
+        Some(locale) => with_locale(locale.to_string(), next.run(req)).await,
+        None => next.run(req).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, middleware, routing::get};
+    use tower::ServiceExt;
+
+    use crate::error::AppError;
+    use crate::t;
+
+    async fn fail() -> Result<(), AppError> {
+        Err(AppError::BadRequest(
+            t!("auth.unsupported_locale", tag = "xx").to_string(),
+        ))
+    }
+
+    async fn detail(accept_language: Option<&str>) -> String {
+        let app = Router::new()
+            .route("/", get(fail))
+            .layer(middleware::from_fn(locale_middleware));
+        let mut request = Request::builder().uri("/");
+        if let Some(value) = accept_language {
+            request = request.header(header::ACCEPT_LANGUAGE, value);
+        }
+        let response = app
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        json["detail"].as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_errors_follow_accept_language() {
+        assert_eq!(detail(Some("zh-CN,en;q=0.5")).await, "不支持的语言：xx");
+        assert_eq!(detail(Some("en-US")).await, "Unsupported locale: xx");
+    }
+}
@@ -1,6 +1,26 @@
 //! Middleware modules for the application.
 
 pub mod auth;
+pub mod client_ip;
+pub mod http_limits;
+pub mod kobo;
+pub mod komga;
+pub mod koreader;
+pub mod locale;
+pub mod quota;
+pub mod rate_limit;
+pub mod request_id;
+pub mod security_headers;
+pub mod timeout;
 
 // Re-export commonly used items
-pub use auth::{AuthUser, auth_middleware};
+pub use auth::{AdminUser, AuthUser, auth_middleware};
+pub use client_ip::{ClientIp, client_ip_middleware};
+pub use kobo::kobo_auth_middleware;
+pub use komga::komga_enabled_middleware;
+pub use koreader::koreader_auth_middleware;
+pub use locale::locale_middleware;
+pub use quota::quota_middleware;
+pub use rate_limit::login_rate_limit_middleware;
+pub use request_id::request_id_middleware;
+pub use security_headers::security_headers_middleware;
@@ -0,0 +1,95 @@
+//! Per-user daily quotas.
+//!
+//! Runs after authentication: each request of the authenticated user counts
+//! against their daily request limit, and the bytes of the request body (as
+//! announced by `Content-Length`) and of the response body against their
+//! transfer limit. Requests made with an API key count for the user owning
+//! the key.
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{Request, State},
+    http::header::CONTENT_LENGTH,
+    middleware::Next,
+    response::Response,
+};
+use http_body::{Body as HttpBody, Frame, SizeHint};
+
+use crate::error::Result;
+use crate::middlewares::AuthUser;
+use crate::services::quota::QuotaService;
+use crate::state::AppState;
+
+/// Middleware rejecting requests of users over their daily quota with 429
+/// and counting the usage of the others.
+pub async fn quota_middleware(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Result<Response> {
+    let Some(user_id) = req.extensions().get::<AuthUser>().map(|user| user.user_id) else {
+        return Ok(next.run(req).await);
+    };
+    state.quota_service.begin(user_id).await?;
+
+    let request_bytes = req
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or_default();
+    let response = next.run(req).await;
+    Ok(response.map(|body| {
+        Body::new(CountedBody {
+            inner: body,
+            quota: state.quota_service.clone(),
+            user_id,
+            bytes: request_bytes,
+        })
+    }))
+}
+
+/// Response body counting the bytes sent, recorded once it is dropped so
+/// that interrupted downloads only count what was sent.
+struct CountedBody {
+    inner: Body,
+    quota: Arc<QuotaService>,
+    user_id: i64,
+    bytes: u64,
+}
+
+impl HttpBody for CountedBody {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<std::result::Result<Frame<Bytes>, axum::Error>>> {
+        let poll = Pin::new(&mut self.inner).poll_frame(cx);
+        if let Poll::Ready(Some(Ok(frame))) = &poll
+            && let Some(data) = frame.data_ref()
+        {
+            self.bytes += data.len() as u64;
+        }
+        poll
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl Drop for CountedBody {
+    fn drop(&mut self) {
+        self.quota.record_bytes(self.user_id, self.bytes);
+    }
+}
@@ -0,0 +1,394 @@
+//! Login rate limiting and brute-force lockout.
+//!
+//! Login attempts are limited per client IP and per username with token
+//! buckets. Repeated failed logins lock the IP or username out for a while.
+//! Two-factor attempts count against the user the challenge was issued to.
+//! State is kept in memory and resets on restart.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use axum::{
+    body::{Body, to_bytes},
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+};
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::error::AppError;
+use crate::middlewares::client_ip::ClientIp;
+use crate::state::AppState;
+use crate::t;
+use crate::utils::token_bucket::TokenBucket;
+
+/// Largest login body the middleware will buffer to read the username.
+const MAX_LOGIN_BODY_BYTES: usize = 64 * 1024;
+
+/// Number of tracked keys above which idle entries are pruned.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Configuration for login rate limiting.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoginRateLimitConfig {
+    /// Login attempts allowed per minute from one IP.
+    pub ip_per_minute: u32,
+    /// Login attempts allowed per minute for one username.
+    pub username_per_minute: u32,
+    /// Consecutive failures before a temporary lockout.
+    pub max_failures: u32,
+    /// Lockout duration in seconds.
+    pub lockout_secs: u64,
+}
+
+impl Default for LoginRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            ip_per_minute: 30,
+            username_per_minute: 10,
+            max_failures: 5,
+            lockout_secs: 300,
+        }
+    }
+}
+
+/// Rate limiting state for a single IP or username.
+#[derive(Debug)]
+struct KeyState {
+    bucket: TokenBucket,
+    failures: u32,
+    locked_until: Option<Instant>,
+}
+
+impl KeyState {
+    /// Returns true if the entry carries no information worth keeping.
+    fn is_idle(&mut self, now: Instant) -> bool {
+        self.failures == 0
+            && self.locked_until.is_none_or(|until| until <= now)
+            && self.bucket.is_full(now)
+    }
+}
+
+#[derive(Debug, Default)]
+struct LimiterState {
+    ips: HashMap<String, KeyState>,
+    usernames: HashMap<String, KeyState>,
+}
+
+/// In-memory limiter for login attempts.
+pub struct LoginRateLimiter {
+    config: LoginRateLimitConfig,
+    state: Mutex<LimiterState>,
+}
+
+impl LoginRateLimiter {
+    /// Create a new limiter.
+    pub fn new(config: LoginRateLimitConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(LimiterState::default()),
+        }
+    }
+
+    /// Check whether an attempt is allowed and consume a token if so.
+    ///
+    /// Returns how long to wait when the attempt is rejected.
+    pub fn check(&self, ip: &str, username: Option<&str>, now: Instant) -> Result<(), Duration> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let state = &mut *state;
+
+        if state.ips.len() + state.usernames.len() > PRUNE_THRESHOLD {
+            state.ips.retain(|_, s| !s.is_idle(now));
+            state.usernames.retain(|_, s| !s.is_idle(now));
+        }
+
+        let ip_state = state
+            .ips
+            .entry(ip.to_string())
+            .or_insert_with(|| Self::new_key(self.config.ip_per_minute, now));
+        Self::check_key(ip_state, now)?;
+
+        if let Some(username) = username {
+            let user_state = state
+                .usernames
+                .entry(username.to_string())
+                .or_insert_with(|| Self::new_key(self.config.username_per_minute, now));
+            Self::check_key(user_state, now)?;
+        }
+
+        Ok(())
+    }
+
+    /// Record a failed login, locking out the IP and username after too many failures.
+    pub fn record_failure(&self, ip: &str, username: Option<&str>, now: Instant) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let state = &mut *state;
+        let lockout = Duration::from_secs(self.config.lockout_secs);
+
+        let mut keys = vec![(state.ips.get_mut(ip), ip)];
+        if let Some(username) = username {
+            keys.push((state.usernames.get_mut(username), username));
+        }
+
+        for (key_state, key) in keys {
+            let Some(key_state) = key_state else {
+                continue;
+            };
+            key_state.failures += 1;
+            if key_state.failures >= self.config.max_failures {
+                key_state.failures = 0;
+                key_state.locked_until = Some(now + lockout);
+                warn!(
+                    key = %key,
+                    lockout_secs = self.config.lockout_secs,
+                    "{}",
+                    t!("auth.login_locked_out_log")
+                );
+            }
+        }
+    }
+
+    /// Record a successful login, clearing failure counters.
+    pub fn record_success(&self, ip: &str, username: Option<&str>) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        if let Some(key_state) = state.ips.get_mut(ip) {
+            key_state.failures = 0;
+        }
+        if let Some(key_state) = username.and_then(|u| state.usernames.get_mut(u)) {
+            key_state.failures = 0;
+        }
+    }
+
+    fn new_key(per_minute: u32, now: Instant) -> KeyState {
+        KeyState {
+            bucket: TokenBucket::per_minute(per_minute, now),
+            failures: 0,
+            locked_until: None,
+        }
+    }
+
+    fn check_key(key_state: &mut KeyState, now: Instant) -> Result<(), Duration> {
+        if let Some(until) = key_state.locked_until {
+            if until > now {
+                return Err(until - now);
+            }
+            key_state.locked_until = None;
+        }
+
+        match key_state.bucket.try_take(now) {
+            None => Ok(()),
+            Some(wait) => Err(wait),
+        }
+    }
+}
+
+impl Default for LoginRateLimiter {
+    fn default() -> Self {
+        Self::new(LoginRateLimitConfig::default())
+    }
+}
+
+/// Fields naming the account in login and two-factor login bodies.
+#[derive(Deserialize)]
+struct LoginAccount {
+    username: Option<String>,
+    challenge_token: Option<String>,
+}
+
+/// Read the lowercased username a login body is for.
+///
+/// Two-factor bodies only carry the challenge token, so their username is
+/// the one of the user the challenge was issued to. TOTP guesses then share
+/// the per-username limit and lockout of the password step.
+async fn login_username(state: &AppState, bytes: &[u8]) -> Option<String> {
+    let account = serde_json::from_slice::<LoginAccount>(bytes).ok()?;
+    let username = match (account.username, account.challenge_token) {
+        (Some(username), _) => username,
+        (None, Some(token)) => state.auth_service.challenge_username(&token).await.ok()?,
+        (None, None) => return None,
+    };
+    Some(username.trim().to_lowercase())
+}
+
+/// Rate limiting middleware for login endpoints.
+///
+/// Rejects requests with 429 and a `Retry-After` header when the client IP
+/// or username is over its limit or locked out. Responses with 401 count as
+/// failed attempts; successful responses reset the failure counters.
+pub async fn login_rate_limit_middleware(
+    State(state): State<AppState>,
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response, AppError> {
+    let ClientIp(ip) = ClientIp::from_extensions(req.extensions());
+
+    // Buffer the body to read the username, then hand it on unchanged
+    let (parts, body) = req.into_parts();
+    let bytes = to_bytes(body, MAX_LOGIN_BODY_BYTES)
+        .await
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let username = login_username(&state, &bytes).await;
+    let req = Request::from_parts(parts, Body::from(bytes));
+
+    let limiter = &state.login_rate_limiter;
+    if let Err(wait) = limiter.check(&ip, username.as_deref(), Instant::now()) {
+        let retry_after_secs = (wait.as_secs_f64().ceil() as u64).max(1);
+        warn!(
+            ip = %ip,
+            username = ?username,
+            retry_after_secs,
+            "{}",
+            t!("auth.login_rate_limited_log")
+        );
+        return Err(AppError::TooManyRequests {
+            message: t!("auth.too_many_login_attempts", seconds = retry_after_secs).to_string(),
+            retry_after_secs,
+        });
+    }
+
+    let response = next.run(req).await;
+
+    if response.status() == StatusCode::UNAUTHORIZED {
+        limiter.record_failure(&ip, username.as_deref(), Instant::now());
+    } else if response.status().is_success() {
+        limiter.record_success(&ip, username.as_deref());
+    }
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, routing::post};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::handlers::auth;
+    use crate::repository::user::UserRepository;
+    use crate::services::auth::LoginOutcome;
+    use crate::state::AppConfig;
+    use crate::test_fixtures::create_test_db;
+
+    fn limiter(per_minute: u32, max_failures: u32) -> LoginRateLimiter {
+        LoginRateLimiter::new(LoginRateLimitConfig {
+            ip_per_minute: per_minute,
+            username_per_minute: per_minute,
+            max_failures,
+            lockout_secs: 60,
+        })
+    }
+
+    #[test]
+    fn test_rejects_after_bucket_is_empty() {
+        let limiter = limiter(2, 100);
+        let now = Instant::now();
+
+        assert!(limiter.check("1.2.3.4", Some("admin"), now).is_ok());
+        assert!(limiter.check("1.2.3.4", Some("admin"), now).is_ok());
+        assert!(limiter.check("1.2.3.4", Some("admin"), now).is_err());
+
+        // A different IP is limited on the shared username
+        assert!(limiter.check("5.6.7.8", Some("admin"), now).is_err());
+        assert!(limiter.check("5.6.7.8", Some("other"), now).is_ok());
+    }
+
+    #[test]
+    fn test_locks_out_after_repeated_failures() {
+        let limiter = limiter(100, 3);
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            assert!(limiter.check("1.2.3.4", Some("admin"), now).is_ok());
+            limiter.record_failure("1.2.3.4", Some("admin"), now);
+        }
+
+        let wait = limiter
+            .check("1.2.3.4", Some("admin"), now)
+            .expect_err("should be locked out");
+        assert!(wait <= Duration::from_secs(60));
+
+        let later = now + Duration::from_secs(61);
+        assert!(limiter.check("1.2.3.4", Some("admin"), later).is_ok());
+    }
+
+    #[test]
+    fn test_success_resets_failures() {
+        let limiter = limiter(100, 2);
+        let now = Instant::now();
+
+        assert!(limiter.check("1.2.3.4", Some("admin"), now).is_ok());
+        limiter.record_failure("1.2.3.4", Some("admin"), now);
+        limiter.record_success("1.2.3.4", Some("admin"));
+        assert!(limiter.check("1.2.3.4", Some("admin"), now).is_ok());
+        limiter.record_failure("1.2.3.4", Some("admin"), now);
+
+        assert!(limiter.check("1.2.3.4", Some("admin"), now).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_two_factor_attempts_count_against_the_user() {
+        let state = AppState::new(create_test_db().await, AppConfig::default());
+        let user = state
+            .auth_service
+            .register("reader".to_string(), "secret1".to_string())
+            .await
+            .unwrap();
+        state.auth_service.setup_totp(user.id).await.unwrap();
+        UserRepository::set_totp_enabled(&state.pool, user.id, true)
+            .await
+            .unwrap();
+        let LoginOutcome::TwoFactorRequired { challenge_token } = state
+            .auth_service
+            .login("reader".to_string(), "secret1".to_string())
+            .await
+            .unwrap()
+        else {
+            panic!("TOTP should be required");
+        };
+
+        let app = Router::new()
+            .route("/api/auth/login", post(auth::login))
+            .route("/api/auth/login/2fa", post(auth::login_two_factor))
+            .layer(axum::middleware::from_fn_with_state(
+                state.clone(),
+                login_rate_limit_middleware,
+            ))
+            .with_state(state);
+        let send = |ip: String, uri: &str, body: serde_json::Value| {
+            let request = Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .extension(ClientIp(ip))
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+
+        // Each guess comes from another IP, so only the user's limit applies
+        let guess = serde_json::json!({ "challenge_token": challenge_token, "code": "wrong!" });
+        let max_failures = LoginRateLimitConfig::default().max_failures;
+        for i in 0..max_failures {
+            let response = send(format!("10.0.0.{i}"), "/api/auth/login/2fa", guess.clone());
+            assert_eq!(response.await.unwrap().status(), StatusCode::UNAUTHORIZED);
+        }
+        let response = send("10.0.1.1".to_string(), "/api/auth/login/2fa", guess);
+        assert_eq!(
+            response.await.unwrap().status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+
+        // The password step of the same user is locked out too
+        let login = serde_json::json!({ "username": "Reader", "password": "secret1" });
+        let response = send("10.0.1.2".to_string(), "/api/auth/login", login);
+        assert_eq!(
+            response.await.unwrap().status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+    }
+}
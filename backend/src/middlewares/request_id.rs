@@ -0,0 +1,196 @@
+//! Request IDs and request logging.
+//!
+//! Every request gets an ID, taken from its `X-Request-Id` header when the
+//! client or a proxy sent a usable one and generated otherwise. The ID is
+//! recorded on the tracing span of the request, returned in the
+//! `X-Request-Id` response header and included in error bodies, so an error
+//! seen in the frontend can be found in the server logs.
+//!
+//! Paths are logged with the secrets some clients send in them masked, see
+//! [`redacted_path`].
+
+use std::borrow::Cow;
+use std::time::Instant;
+
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::Instrument;
+use uuid::Uuid;
+
+use crate::t;
+
+/// Header carrying the request ID in both directions.
+pub static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest accepted incoming request ID.
+const MAX_REQUEST_ID_LEN: usize = 64;
+
+/// Path prefixes followed by a secret segment: Kobo devices send their API
+/// key as the first segment of every path, and share links carry their
+/// token.
+const SECRET_PATH_PREFIXES: &[&str] = &["/kobo/", "/api/shared/"];
+
+tokio::task_local! {
+    static CURRENT_REQUEST_ID: String;
+}
+
+/// ID of the request, available as a request extension.
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// ID of the request being handled by the current task, if any.
+pub fn current_request_id() -> Option<String> {
+    CURRENT_REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+/// Middleware assigning the request ID and logging each request when it
+/// completes, with its method, path, status and latency.
+pub async fn request_id_middleware(mut req: Request, next: Next) -> Response {
+    let id = req
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| is_valid_request_id(value))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().simple().to_string());
+    req.extensions_mut().insert(RequestId(id.clone()));
+
+    let method = req.method().clone();
+    let path = redacted_path(req.uri().path()).into_owned();
+    let span = tracing::error_span!("request", id = %id, %method, %path);
+    let started = Instant::now();
+
+    let mut response = CURRENT_REQUEST_ID
+        .scope(id.clone(), next.run(req))
+        .instrument(span.clone())
+        .await;
+
+    let status = response.status().as_u16();
+    let latency_ms = started.elapsed().as_millis() as u64;
+    span.in_scope(|| {
+        if response.status().is_server_error() {
+            tracing::warn!(status, latency_ms, "{}", t!("request.completed"));
+        } else {
+            tracing::info!(status, latency_ms, "{}", t!("request.completed"));
+        }
+    });
+
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response
+            .headers_mut()
+            .insert(REQUEST_ID_HEADER.clone(), value);
+    }
+    response
+}
+
+/// Path of a request as it may be logged, with the secret segment after
+/// each of [`SECRET_PATH_PREFIXES`] replaced by `***`.
+pub fn redacted_path(path: &str) -> Cow<'_, str> {
+    for prefix in SECRET_PATH_PREFIXES {
+        if let Some(rest) = path.strip_prefix(prefix) {
+            let end = rest.find('/').unwrap_or(rest.len());
+            if end > 0 {
+                return Cow::Owned(format!("{}***{}", prefix, &rest[end..]));
+            }
+        }
+    }
+    Cow::Borrowed(path)
+}
+
+/// Whether an incoming ID can be reused: short, and only characters that
+/// are safe to log and echo back.
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, middleware, routing::get};
+    use tower::ServiceExt;
+
+    use crate::error::AppError;
+
+    async fn fail() -> Result<(), AppError> {
+        Err(AppError::NotFound("missing".to_string()))
+    }
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/ok",
+                get(|| async { current_request_id().unwrap_or_default() }),
+            )
+            .route("/fail", get(fail))
+            .layer(middleware::from_fn(request_id_middleware))
+    }
+
+    #[test]
+    fn test_is_valid_request_id() {
+        assert!(is_valid_request_id("3f2a-b_c.1"));
+        assert!(!is_valid_request_id(""));
+        assert!(!is_valid_request_id("has space"));
+        assert!(!is_valid_request_id("line\nbreak"));
+        assert!(!is_valid_request_id(&"a".repeat(MAX_REQUEST_ID_LEN + 1)));
+    }
+
+    #[test]
+    fn test_redacted_path() {
+        assert_eq!(
+            redacted_path("/kobo/secret-key/v1/library/sync"),
+            "/kobo/***/v1/library/sync"
+        );
+        assert_eq!(redacted_path("/kobo/secret-key"), "/kobo/***");
+        assert_eq!(redacted_path("/kobo/"), "/kobo/");
+        assert_eq!(
+            redacted_path("/api/shared/0123abcd/chapters/4/pages/2"),
+            "/api/shared/***/chapters/4/pages/2"
+        );
+        assert_eq!(redacted_path("/api/contents/1"), "/api/contents/1");
+    }
+
+    #[tokio::test]
+    async fn test_incoming_id_is_propagated() {
+        let request = Request::builder()
+            .uri("/ok")
+            .header(&REQUEST_ID_HEADER, "client-id-1")
+            .body(Body::empty())
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+
+        assert_eq!(response.headers()[&REQUEST_ID_HEADER], "client-id-1");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"client-id-1");
+    }
+
+    #[tokio::test]
+    async fn test_generated_id_is_in_error_body() {
+        let request = Request::builder()
+            .uri("/fail")
+            .header(&REQUEST_ID_HEADER, "bad id")
+            .body(Body::empty())
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+
+        let id = response.headers()[&REQUEST_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert_ne!(id, "bad id");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["request_id"], id);
+    }
+}
@@ -0,0 +1,170 @@
+//! Security headers added to responses.
+//!
+//! Every response gets `X-Content-Type-Options: nosniff` and the configured
+//! `Referrer-Policy`. The bundled frontend is additionally served with a
+//! `Content-Security-Policy`, which would only get in the way of API
+//! clients. Headers already set by a handler are left alone.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderName, HeaderValue, header},
+    middleware::Next,
+    response::Response,
+};
+use serde::Deserialize;
+
+/// Default policy of the bundled frontend. Images may come from any HTTPS
+/// host because metadata providers link their covers directly.
+pub const DEFAULT_CONTENT_SECURITY_POLICY: &str = "default-src 'self'; \
+    img-src 'self' data: blob: https:; style-src 'self' 'unsafe-inline'; \
+    font-src 'self' data:; object-src 'none'; base-uri 'self'; \
+    frame-ancestors 'none'";
+
+/// Headers added by [`security_headers_middleware`].
+pub type SecurityHeaders = Arc<[(HeaderName, HeaderValue)]>;
+
+/// Security header settings.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SecurityHeadersConfig {
+    /// Whether to add the headers at all, for deployments where a reverse
+    /// proxy sets them.
+    pub enabled: bool,
+    /// `Referrer-Policy` of every response. Empty leaves it unset.
+    pub referrer_policy: String,
+    /// `Content-Security-Policy` of the bundled frontend. Empty leaves it
+    /// unset.
+    pub content_security_policy: String,
+}
+
+impl Default for SecurityHeadersConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            referrer_policy: "same-origin".to_string(),
+            content_security_policy: DEFAULT_CONTENT_SECURITY_POLICY.to_string(),
+        }
+    }
+}
+
+impl SecurityHeadersConfig {
+    /// Headers added to every response.
+    pub fn headers(&self) -> SecurityHeaders {
+        let mut headers = vec![(
+            header::X_CONTENT_TYPE_OPTIONS,
+            HeaderValue::from_static("nosniff"),
+        )];
+        headers.extend(value(&self.referrer_policy).map(|v| (header::REFERRER_POLICY, v)));
+        self.enabled_or_empty(headers)
+    }
+
+    /// Headers added to the files of the bundled frontend.
+    pub fn static_file_headers(&self) -> SecurityHeaders {
+        let headers = value(&self.content_security_policy)
+            .map(|v| (header::CONTENT_SECURITY_POLICY, v))
+            .into_iter()
+            .collect();
+        self.enabled_or_empty(headers)
+    }
+
+    fn enabled_or_empty(&self, headers: Vec<(HeaderName, HeaderValue)>) -> SecurityHeaders {
+        if self.enabled {
+            headers.into()
+        } else {
+            Arc::new([])
+        }
+    }
+}
+
+/// Header value of a setting, `None` when empty or invalid. Invalid values
+/// are rejected when the configuration is loaded.
+fn value(setting: &str) -> Option<HeaderValue> {
+    let setting = setting.trim();
+    if setting.is_empty() {
+        return None;
+    }
+    HeaderValue::from_str(setting).ok()
+}
+
+/// Middleware adding the given headers to responses that do not set them.
+pub async fn security_headers_middleware(
+    State(headers): State<SecurityHeaders>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(req).await;
+    for (name, value) in headers.iter() {
+        response
+            .headers_mut()
+            .entry(name)
+            .or_insert_with(|| value.clone());
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, middleware, routing::get};
+    use tower::ServiceExt;
+
+    fn app(headers: SecurityHeaders) -> Router {
+        Router::new()
+            .route("/", get(|| async { "ok" }))
+            .route(
+                "/framed",
+                get(|| async { ([(header::REFERRER_POLICY, "no-referrer")], "ok") }),
+            )
+            .layer(middleware::from_fn_with_state(
+                headers,
+                security_headers_middleware,
+            ))
+    }
+
+    async fn get_headers(app: Router, uri: &str) -> axum::http::HeaderMap {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        app.oneshot(request).await.unwrap().headers().clone()
+    }
+
+    #[tokio::test]
+    async fn test_headers_are_added() {
+        let config = SecurityHeadersConfig::default();
+        let headers = get_headers(app(config.headers()), "/").await;
+        assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+        assert_eq!(headers[header::REFERRER_POLICY], "same-origin");
+        assert!(!headers.contains_key(header::CONTENT_SECURITY_POLICY));
+
+        let headers = get_headers(app(config.static_file_headers()), "/").await;
+        assert_eq!(
+            headers[header::CONTENT_SECURITY_POLICY],
+            DEFAULT_CONTENT_SECURITY_POLICY
+        );
+    }
+
+    #[tokio::test]
+    async fn test_handler_headers_are_kept() {
+        let config = SecurityHeadersConfig::default();
+        let headers = get_headers(app(config.headers()), "/framed").await;
+        assert_eq!(headers[header::REFERRER_POLICY], "no-referrer");
+    }
+
+    #[test]
+    fn test_disabled_and_empty_settings() {
+        let config = SecurityHeadersConfig {
+            enabled: false,
+            ..Default::default()
+        };
+        assert!(config.headers().is_empty());
+        assert!(config.static_file_headers().is_empty());
+
+        let config = SecurityHeadersConfig {
+            referrer_policy: String::new(),
+            content_security_policy: " ".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(config.headers().len(), 1);
+        assert!(config.static_file_headers().is_empty());
+    }
+}
@@ -0,0 +1,96 @@
+//! Per-route request timeouts.
+//!
+//! When a timeout fires the handler future is dropped, which also cancels
+//! blocking extraction started with
+//! [`spawn_cancellable`](crate::utils::cancel::spawn_cancellable).
+
+use std::time::Duration;
+
+use axum::http::StatusCode;
+use serde::Deserialize;
+use tower_http::timeout::TimeoutLayer;
+
+/// Request timeouts in seconds. A value of 0 disables the timeout.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RequestTimeoutConfig {
+    /// Timeout for API requests without a more specific limit.
+    pub api_secs: u64,
+    /// Timeout for page image requests, which extract from archives.
+    pub page_secs: u64,
+    /// Timeout for backup restores and imports, whose uploads can be large.
+    pub upload_secs: u64,
+}
+
+impl Default for RequestTimeoutConfig {
+    fn default() -> Self {
+        Self {
+            api_secs: 60,
+            page_secs: 30,
+            upload_secs: 3600,
+        }
+    }
+}
+
+impl RequestTimeoutConfig {
+    /// Layer applying the general API timeout.
+    pub fn api_layer(&self) -> TimeoutLayer {
+        timeout_layer(self.api_secs)
+    }
+
+    /// Layer applying the page request timeout.
+    pub fn page_layer(&self) -> TimeoutLayer {
+        timeout_layer(self.page_secs)
+    }
+
+    /// Layer applying the upload timeout.
+    pub fn upload_layer(&self) -> TimeoutLayer {
+        timeout_layer(self.upload_secs)
+    }
+}
+
+/// Build a timeout layer responding with 408 Request Timeout.
+fn timeout_layer(secs: u64) -> TimeoutLayer {
+    // Duration::MAX never fires; tokio clamps it to the far future
+    let timeout = if secs == 0 {
+        Duration::MAX
+    } else {
+        Duration::from_secs(secs)
+    };
+    TimeoutLayer::with_status_code(StatusCode::REQUEST_TIMEOUT, timeout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, extract::Request, routing::post};
+    use tower::ServiceExt;
+
+    async fn status(app: Router, uri: &str) -> StatusCode {
+        let request = Request::post(uri).body(Body::empty()).unwrap();
+        app.oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_uploads_outlast_api_timeout() {
+        let config = RequestTimeoutConfig {
+            api_secs: 1,
+            page_secs: 1,
+            upload_secs: 5,
+        };
+        let slow = || async { tokio::time::sleep(Duration::from_millis(1500)).await };
+        let uploads = Router::new()
+            .route("/upload", post(slow))
+            .layer(config.upload_layer());
+        let app = Router::new()
+            .route("/api", post(slow))
+            .layer(config.api_layer())
+            .merge(uploads);
+
+        assert_eq!(
+            status(app.clone(), "/api").await,
+            StatusCode::REQUEST_TIMEOUT
+        );
+        assert_eq!(status(app, "/upload").await, StatusCode::OK);
+    }
+}
@@ -0,0 +1,59 @@
+//! Age rating models.
+//!
+//! Contents carry the minimum age of their readers, extracted from scraped
+//! metadata or set by hand, and administrators can cap the rating each user
+//! sees. Contents without a rating are shown to everyone.
+
+use serde::{Deserialize, Serialize};
+
+/// Highest age rating accepted, in years.
+pub const MAX_AGE_RATING: i32 = 99;
+
+/// Rating of metadata marked as adult only.
+pub const ADULT_AGE_RATING: i32 = 18;
+
+/// Age rating of Bangumi-style metadata, from its `nsfw` flag. Metadata
+/// that is not marked as adult only has no rating.
+pub fn metadata_age_rating(metadata: &serde_json::Value) -> Option<i32> {
+    metadata
+        .get("nsfw")
+        .and_then(|v| v.as_bool())
+        .is_some_and(|nsfw| nsfw)
+        .then_some(ADULT_AGE_RATING)
+}
+
+/// Whether a user whose ratings are capped at `max` may see a content
+/// rated `rating`.
+pub fn age_rating_allowed(max: Option<i32>, rating: Option<i32>) -> bool {
+    match (max, rating) {
+        (Some(max), Some(rating)) => rating <= max,
+        _ => true,
+    }
+}
+
+/// Request to cap the age rating a user may see; `null` lifts the cap.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateUserAgeRatingRequest {
+    pub max_age_rating: Option<i32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_metadata_age_rating() {
+        assert_eq!(metadata_age_rating(&json!({ "nsfw": true })), Some(18));
+        assert_eq!(metadata_age_rating(&json!({ "nsfw": false })), None);
+        assert_eq!(metadata_age_rating(&json!({ "name": "A" })), None);
+    }
+
+    #[test]
+    fn test_age_rating_allowed() {
+        assert!(age_rating_allowed(None, Some(18)));
+        assert!(age_rating_allowed(Some(12), None));
+        assert!(age_rating_allowed(Some(18), Some(18)));
+        assert!(!age_rating_allowed(Some(16), Some(18)));
+    }
+}
@@ -0,0 +1,124 @@
+//! Alternative title models.
+//!
+//! Contents can be known by several titles: the original and translated
+//! names scraped from metadata providers, and titles users add by hand.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Longest alternative title accepted from users, in characters.
+pub const MAX_ALIAS_LEN: usize = 255;
+
+/// Where an alternative title comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AliasSource {
+    /// Scraped metadata, replaced whenever the metadata changes.
+    Metadata,
+    /// Added by hand, kept until removed by hand.
+    Manual,
+}
+
+impl AliasSource {
+    /// All sources.
+    pub const ALL: [AliasSource; 2] = [AliasSource::Metadata, AliasSource::Manual];
+
+    /// Identifier stored in the database and used in the API.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AliasSource::Metadata => "metadata",
+            AliasSource::Manual => "manual",
+        }
+    }
+
+    /// Parse an identifier returned by [`Self::as_str`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|source| source.as_str() == name)
+    }
+}
+
+impl TryFrom<String> for AliasSource {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        Self::from_name(&name).ok_or_else(|| format!("unknown alias source: {name}"))
+    }
+}
+
+/// An alternative title of a content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ContentAlias {
+    pub id: i64,
+    pub content_id: i64,
+    pub title: String,
+    #[sqlx(try_from = "String")]
+    pub source: AliasSource,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request to add an alternative title to a content.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateAliasRequest {
+    pub title: String,
+}
+
+/// Alternative titles in Bangumi-style metadata: the original name, the
+/// Chinese name and the `别名` entries of the infobox, without duplicates.
+pub fn metadata_aliases(metadata: &serde_json::Value) -> Vec<String> {
+    let names = ["name", "name_cn"]
+        .into_iter()
+        .filter_map(|key| metadata.get(key).and_then(|v| v.as_str()));
+
+    // Infobox values are either a string or a list of `{"v": ...}` entries
+    let infobox = metadata
+        .get("infobox")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|item| item.get("key").and_then(|k| k.as_str()) == Some("别名"))
+        .filter_map(|item| item.get("value"))
+        .flat_map(|value| match value {
+            serde_json::Value::Array(entries) => entries
+                .iter()
+                .filter_map(|e| e.get("v").and_then(|v| v.as_str()))
+                .collect::<Vec<_>>(),
+            value => value.as_str().into_iter().collect(),
+        });
+
+    let mut aliases: Vec<String> = Vec::new();
+    for alias in names.chain(infobox).map(str::trim) {
+        if !alias.is_empty() && !aliases.iter().any(|a| a == alias) {
+            aliases.push(alias.to_string());
+        }
+    }
+    aliases
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_metadata_aliases() {
+        let metadata = json!({
+            "name": "進撃の巨人",
+            "name_cn": "进击的巨人",
+            "infobox": [
+                { "key": "作者", "value": "諫山創" },
+                { "key": "别名", "value": [{ "v": "Attack on Titan" }, { "v": "进击的巨人" }] },
+                { "key": "别名", "value": " Shingeki no Kyojin " },
+            ],
+        });
+        assert_eq!(
+            metadata_aliases(&metadata),
+            [
+                "進撃の巨人",
+                "进击的巨人",
+                "Attack on Titan",
+                "Shingeki no Kyojin"
+            ]
+        );
+        assert!(metadata_aliases(&json!({ "name": "" })).is_empty());
+    }
+}
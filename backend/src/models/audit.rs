@@ -0,0 +1,188 @@
+//! Audit log data models.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Security-relevant or destructive action recorded in the audit log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    Login,
+    LoginFailed,
+    LibraryDeleted,
+    ContentDeleted,
+    ContentMoved,
+    UserUpdated,
+    ApiKeyCreated,
+    ApiKeyDeleted,
+    TwoFactorEnabled,
+    TwoFactorDisabled,
+    BackupCreated,
+    BackupRestored,
+    ConsistencyRepaired,
+    TrashPurged,
+    DuplicatesMerged,
+    LibraryImported,
+    ServerImported,
+    SetupCompleted,
+    SettingsUpdated,
+    ShareLinkCreated,
+    ShareLinkRevoked,
+    PasswordChanged,
+    PasswordResetIssued,
+}
+
+impl AuditAction {
+    /// Identifier stored in the `action` column.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Login => "login",
+            AuditAction::LoginFailed => "login_failed",
+            AuditAction::LibraryDeleted => "library_deleted",
+            AuditAction::ContentDeleted => "content_deleted",
+            AuditAction::ContentMoved => "content_moved",
+            AuditAction::UserUpdated => "user_updated",
+            AuditAction::ApiKeyCreated => "api_key_created",
+            AuditAction::ApiKeyDeleted => "api_key_deleted",
+            AuditAction::TwoFactorEnabled => "two_factor_enabled",
+            AuditAction::TwoFactorDisabled => "two_factor_disabled",
+            AuditAction::BackupCreated => "backup_created",
+            AuditAction::BackupRestored => "backup_restored",
+            AuditAction::ConsistencyRepaired => "consistency_repaired",
+            AuditAction::TrashPurged => "trash_purged",
+            AuditAction::DuplicatesMerged => "duplicates_merged",
+            AuditAction::LibraryImported => "library_imported",
+            AuditAction::ServerImported => "server_imported",
+            AuditAction::SetupCompleted => "setup_completed",
+            AuditAction::SettingsUpdated => "settings_updated",
+            AuditAction::ShareLinkCreated => "share_link_created",
+            AuditAction::ShareLinkRevoked => "share_link_revoked",
+            AuditAction::PasswordChanged => "password_changed",
+            AuditAction::PasswordResetIssued => "password_reset_issued",
+        }
+    }
+}
+
+/// A recorded audit log entry.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct AuditLogEntry {
+    pub id: i64,
+    /// User who performed the action, if known.
+    pub user_id: Option<i64>,
+    /// Username at the time of the action; for failed logins, the attempted name.
+    pub username: Option<String>,
+    pub action: String,
+    /// Kind of object acted on, e.g. `"library"`.
+    pub target_type: Option<String>,
+    pub target_id: Option<String>,
+    /// Client IP address.
+    pub ip: Option<String>,
+    /// Free-form details.
+    pub details: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Data for recording a new audit log entry.
+#[derive(Debug, Clone)]
+pub struct NewAuditLogEntry {
+    pub user_id: Option<i64>,
+    pub username: Option<String>,
+    pub action: AuditAction,
+    pub target_type: Option<String>,
+    pub target_id: Option<String>,
+    pub ip: Option<String>,
+    pub details: Option<String>,
+}
+
+impl NewAuditLogEntry {
+    /// Start an entry for an action.
+    pub fn new(action: AuditAction) -> Self {
+        Self {
+            user_id: None,
+            username: None,
+            action,
+            target_type: None,
+            target_id: None,
+            ip: None,
+            details: None,
+        }
+    }
+
+    /// Set the acting user.
+    pub fn user(mut self, user_id: i64, username: impl Into<String>) -> Self {
+        self.user_id = Some(user_id);
+        self.username = Some(username.into());
+        self
+    }
+
+    /// Set the username without a known user ID, e.g. for failed logins.
+    pub fn username(mut self, username: impl Into<String>) -> Self {
+        self.username = Some(username.into());
+        self
+    }
+
+    /// Set the object acted on.
+    pub fn target(mut self, target_type: &str, target_id: impl ToString) -> Self {
+        self.target_type = Some(target_type.to_string());
+        self.target_id = Some(target_id.to_string());
+        self
+    }
+
+    /// Set the client IP address.
+    pub fn ip(mut self, ip: impl Into<String>) -> Self {
+        self.ip = Some(ip.into());
+        self
+    }
+
+    /// Set free-form details.
+    pub fn details(mut self, details: impl Into<String>) -> Self {
+        self.details = Some(details.into());
+        self
+    }
+}
+
+/// Filters for listing the audit log.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditLogQuery {
+    /// Only entries by this user.
+    pub user_id: Option<i64>,
+    /// Only entries with this action, e.g. `login_failed`.
+    pub action: Option<String>,
+    /// Only entries at or after this time.
+    pub from: Option<DateTime<Utc>>,
+    /// Only entries before this time.
+    pub to: Option<DateTime<Utc>>,
+    /// 1-based page number (default 1).
+    pub page: Option<i64>,
+    /// Entries per page (default 50, max 200).
+    pub page_size: Option<i64>,
+}
+
+/// A page of audit log entries, newest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditLogPage {
+    pub items: Vec<AuditLogEntry>,
+    pub total: i64,
+    pub page: i64,
+    pub page_size: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_entry_builder() {
+        let entry = NewAuditLogEntry::new(AuditAction::LibraryDeleted)
+            .user(1, "admin")
+            .target("library", 42)
+            .ip("127.0.0.1");
+
+        assert_eq!(entry.action.as_str(), "library_deleted");
+        assert_eq!(entry.user_id, Some(1));
+        assert_eq!(entry.username.as_deref(), Some("admin"));
+        assert_eq!(entry.target_type.as_deref(), Some("library"));
+        assert_eq!(entry.target_id.as_deref(), Some("42"));
+        assert_eq!(entry.ip.as_deref(), Some("127.0.0.1"));
+        assert_eq!(entry.details, None);
+    }
+}
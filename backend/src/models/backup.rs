@@ -0,0 +1,23 @@
+//! Database backup models.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A backup file in the backup directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupInfo {
+    /// File name, used to download the backup.
+    pub name: String,
+    /// Size in bytes.
+    pub size: u64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Response for a staged restore.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupRestoreResponse {
+    /// Backup of the database as it was before the restore.
+    pub safety_backup: BackupInfo,
+    /// The restored database is used after the server restarts.
+    pub restart_required: bool,
+}
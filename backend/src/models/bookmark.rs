@@ -0,0 +1,166 @@
+//! Bookmark data models.
+//!
+//! A bookmark marks a place in a chapter, optionally with a note such as a
+//! quote from a novel. Users can keep any number of bookmarks per chapter.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::NovelPosition;
+
+/// Longest note accepted on a bookmark, in characters.
+pub const MAX_BOOKMARK_NOTE_CHARS: usize = 4000;
+
+/// A bookmark as stored, with the content its chapter belongs to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Bookmark {
+    pub id: i64,
+    pub user_id: i64,
+    pub content_id: i64,
+    pub chapter_id: i64,
+    /// Page for comics, section for novels, seconds for audiobooks; the
+    /// same meaning as the reading progress position.
+    pub position: i32,
+    pub novel_section: Option<i32>,
+    pub novel_offset: Option<i64>,
+    pub novel_cfi: Option<String>,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Bookmark {
+    /// The novel position, if one was stored with the bookmark.
+    pub fn novel_position(&self) -> Option<NovelPosition> {
+        Some(NovelPosition {
+            section: self.novel_section?,
+            offset: self.novel_offset?,
+            cfi: self.novel_cfi.clone(),
+        })
+    }
+}
+
+/// Data for creating a new bookmark.
+#[derive(Debug, Clone)]
+pub struct NewBookmark {
+    pub user_id: i64,
+    pub chapter_id: i64,
+    pub position: i32,
+    pub novel: Option<NovelPosition>,
+    pub note: Option<String>,
+}
+
+/// Request to create a bookmark in a chapter.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateBookmarkRequest {
+    pub position: i32,
+    /// Place in the text, for novels.
+    #[serde(default)]
+    pub novel: Option<NovelPosition>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// Request to change the note of a bookmark; `null` or an empty note
+/// removes it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateBookmarkRequest {
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// Query parameters for listing a user's bookmarks.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct BookmarkListQuery {
+    /// Only bookmarks in this content.
+    pub content_id: Option<i64>,
+}
+
+/// Response for bookmark APIs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkResponse {
+    pub id: i64,
+    pub content_id: i64,
+    pub chapter_id: i64,
+    pub position: i32,
+    #[serde(default)]
+    pub novel: Option<NovelPosition>,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Bookmark> for BookmarkResponse {
+    fn from(bookmark: Bookmark) -> Self {
+        Self {
+            novel: bookmark.novel_position(),
+            id: bookmark.id,
+            content_id: bookmark.content_id,
+            chapter_id: bookmark.chapter_id,
+            position: bookmark.position,
+            note: bookmark.note,
+            created_at: bookmark.created_at,
+            updated_at: bookmark.updated_at,
+        }
+    }
+}
+
+/// Trim a bookmark note, treating a blank note as none.
+pub fn normalize_bookmark_note(note: Option<String>) -> Result<Option<String>, &'static str> {
+    let Some(note) = note.map(|note| note.trim().to_string()) else {
+        return Ok(None);
+    };
+    if note.is_empty() {
+        return Ok(None);
+    }
+    if note.chars().count() > MAX_BOOKMARK_NOTE_CHARS {
+        return Err("Bookmark note is too long");
+    }
+    Ok(Some(note))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_note() {
+        assert_eq!(normalize_bookmark_note(None), Ok(None));
+        assert_eq!(normalize_bookmark_note(Some("  \n".to_string())), Ok(None));
+        assert_eq!(
+            normalize_bookmark_note(Some(" 「名言」 ".to_string())),
+            Ok(Some("「名言」".to_string()))
+        );
+        let long = "字".repeat(MAX_BOOKMARK_NOTE_CHARS + 1);
+        assert!(normalize_bookmark_note(Some(long)).is_err());
+    }
+
+    #[test]
+    fn test_response_carries_novel_position() {
+        let now = Utc::now();
+        let bookmark = Bookmark {
+            id: 1,
+            user_id: 2,
+            content_id: 3,
+            chapter_id: 4,
+            position: 5,
+            novel_section: Some(5),
+            novel_offset: Some(120),
+            novel_cfi: None,
+            note: Some("quote".to_string()),
+            created_at: now,
+            updated_at: now,
+        };
+
+        let response = BookmarkResponse::from(bookmark);
+        assert_eq!(
+            response.novel,
+            Some(NovelPosition {
+                section: 5,
+                offset: 120,
+                cfi: None,
+            })
+        );
+        assert_eq!(response.note.as_deref(), Some("quote"));
+    }
+}
@@ -2,6 +2,11 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+
+use crate::models::RatingSummary;
+use crate::utils::numbering::ChapterNumbering;
+use crate::utils::page_image::SpreadHalf;
 
 /// A content item.
 ///
@@ -15,8 +20,13 @@ pub struct Content {
     pub library_id: i64,
     /// ID of the scan path this content was imported from.
     pub scan_path_id: i64,
-    /// Title of the content (derived from folder name).
+    /// Display title of the content (the folder name after title
+    /// normalization, unless edited).
     pub title: String,
+    /// Title derived from the folder name, before normalization.
+    #[sqlx(default)]
+    #[serde(default)]
+    pub folder_title: String,
     /// Path to the content folder on the file system.
     pub folder_path: String,
     /// Number of chapters in this content.
@@ -24,9 +34,29 @@ pub struct Content {
     /// Compressed thumbnail image data.
     #[sqlx(default)]
     pub thumbnail: Option<Vec<u8>>,
+    /// Version tag of the thumbnail, see [`thumbnail_etag`].
+    #[sqlx(default)]
+    pub thumbnail_etag: Option<String>,
+    /// BlurHash placeholder of the thumbnail.
+    #[sqlx(default)]
+    #[serde(default)]
+    pub thumbnail_blurhash: Option<String>,
     /// Metadata from Bangumi API (stored as JSON blob).
     #[sqlx(default)]
     pub metadata: Option<Vec<u8>>,
+    /// When the content's folder was found missing; such content is in the
+    /// trash until it is restored or purged.
+    #[sqlx(default)]
+    #[serde(default)]
+    pub missing_since: Option<DateTime<Utc>>,
+    /// Group of related contents (seasons, spin-offs) this content is in.
+    #[sqlx(default)]
+    #[serde(default)]
+    pub series_group_id: Option<i64>,
+    /// Minimum age of readers, from the metadata or set by hand.
+    #[sqlx(default)]
+    #[serde(default)]
+    pub age_rating: Option<i32>,
     /// Timestamp when the content was imported.
     pub created_at: DateTime<Utc>,
     /// Timestamp when the content was last updated.
@@ -44,10 +74,12 @@ impl Content {
         NewContent {
             library_id,
             scan_path_id,
+            folder_title: title.clone(),
             title,
             folder_path,
             chapter_count: 0,
             thumbnail: None,
+            thumbnail_spec: None,
             metadata: None,
         }
     }
@@ -59,12 +91,60 @@ pub struct NewContent {
     pub library_id: i64,
     pub scan_path_id: i64,
     pub title: String,
+    pub folder_title: String,
     pub folder_path: String,
     pub chapter_count: i32,
     pub thumbnail: Option<Vec<u8>>,
+    /// Settings the thumbnail was generated with, see
+    /// [`ThumbnailOptions::spec`](crate::utils::thumbnail::ThumbnailOptions::spec).
+    pub thumbnail_spec: Option<String>,
     pub metadata: Option<serde_json::Value>,
 }
 
+/// Whether the file of a chapter could be read at the last scan.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChapterStatus {
+    #[default]
+    Ready,
+    /// The file could not be opened or has no pages.
+    Error,
+    /// The file is in a format or variant that cannot be read, e.g. an
+    /// encrypted book.
+    Unsupported,
+}
+
+impl ChapterStatus {
+    /// All statuses.
+    pub const ALL: [ChapterStatus; 3] = [
+        ChapterStatus::Ready,
+        ChapterStatus::Error,
+        ChapterStatus::Unsupported,
+    ];
+
+    /// Identifier stored in the database and used in the API.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChapterStatus::Ready => "ready",
+            ChapterStatus::Error => "error",
+            ChapterStatus::Unsupported => "unsupported",
+        }
+    }
+
+    /// Parse an identifier returned by [`Self::as_str`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|status| status.as_str() == name)
+    }
+}
+
+impl TryFrom<String> for ChapterStatus {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        Self::from_name(&name).ok_or_else(|| format!("unknown chapter status: {name}"))
+    }
+}
+
 /// A chapter within a content item.
 ///
 /// Chapters represent individual archive files (volumes, issues, etc.)
@@ -79,7 +159,8 @@ pub struct Chapter {
     pub title: String,
     /// Path to the chapter archive file.
     pub file_path: String,
-    /// File type (extension) of the chapter file (e.g. "cbz", "pdf", "epub").
+    /// File type (extension) of the chapter file (e.g. "cbz", "pdf", "epub", "txt", "mp3"),
+    /// or [`IMAGE_FOLDER_FILE_TYPE`] for a folder of images.
     pub file_type: String,
     /// Sort order for displaying chapters.
     pub sort_order: i32,
@@ -89,6 +170,31 @@ pub struct Chapter {
     /// File size in bytes.
     #[sqlx(default)]
     pub size: i64,
+    /// Volume number parsed from the chapter name.
+    #[sqlx(default)]
+    #[serde(default)]
+    pub volume: Option<i32>,
+    /// Chapter number parsed from the chapter name, e.g. 12.5.
+    #[sqlx(default)]
+    #[serde(default)]
+    pub number: Option<f32>,
+    /// Whether the file could be read at the last scan. Chapters that
+    /// could not count no pages.
+    #[sqlx(default, try_from = "String")]
+    #[serde(default)]
+    pub status: ChapterStatus,
+    /// Why the file could not be read, unless the chapter is ready.
+    #[sqlx(default)]
+    #[serde(default)]
+    pub error: Option<String>,
+    /// When the chapter was first found by a scan.
+    #[sqlx(default)]
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    /// When a scan last found the chapter's file changed.
+    #[sqlx(default)]
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
 }
 
 impl Chapter {
@@ -102,6 +208,7 @@ impl Chapter {
         page_count: i32,
         size: i64,
     ) -> NewChapter {
+        let numbering = ChapterNumbering::parse(&title);
         NewChapter {
             content_id,
             title,
@@ -110,21 +217,39 @@ impl Chapter {
             sort_order,
             page_count,
             size,
+            volume: numbering.volume,
+            number: numbering.number,
+            status: ChapterStatus::Ready,
+            error: None,
         }
     }
 
-    /// Returns true if this chapter is a text-based format (epub).
-    pub fn is_text_based(&self) -> bool {
-        self.file_type == "epub"
+    /// Number to sort the chapter by: its parsed chapter number, else its
+    /// volume, else its position in the content (1-based).
+    pub fn number_sort(&self) -> f32 {
+        self.number
+            .or(self.volume.map(|volume| volume as f32))
+            .unwrap_or((self.sort_order + 1) as f32)
     }
 
-    /// Returns true if this chapter is an image-based format (zip, cbz, cbr, rar, pdf).
-    pub fn is_image_based(&self) -> bool {
+    /// Returns true if this chapter is a text-based format (epub, mobi, azw3, azw, txt).
+    pub fn is_text_based(&self) -> bool {
         matches!(
             self.file_type.as_str(),
-            "zip" | "cbz" | "cbr" | "rar" | "pdf"
+            "epub" | "mobi" | "azw3" | "azw" | "txt"
         )
     }
+
+    /// Returns true if this chapter is an audiobook file (m4b, m4a, mp3).
+    pub fn is_audio(&self) -> bool {
+        matches!(self.file_type.as_str(), "m4b" | "m4a" | "mp3")
+    }
+
+    /// Returns true if this chapter is an image-based format (zip, cbz, cbr, rar, pdf)
+    /// or an image folder.
+    pub fn is_image_based(&self) -> bool {
+        is_image_file_type(&self.file_type)
+    }
 }
 
 /// Data for creating a new chapter.
@@ -137,6 +262,106 @@ pub struct NewChapter {
     pub sort_order: i32,
     pub page_count: i32,
     pub size: i64,
+    /// See [`Chapter::volume`].
+    pub volume: Option<i32>,
+    /// See [`Chapter::number`].
+    pub number: Option<f32>,
+    /// See [`Chapter::status`].
+    pub status: ChapterStatus,
+    /// See [`Chapter::error`].
+    pub error: Option<String>,
+}
+
+/// A page that failed to extract or decode when it was requested.
+///
+/// Recorded so broken archives can be found and verified later.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct PageError {
+    pub id: i64,
+    pub chapter_id: i64,
+    /// 0-based index of the failed page.
+    pub page_index: i64,
+    /// Error message from the failed attempt.
+    pub error: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A chapter whose file cannot be read, or had pages that failed to load.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct MediaError {
+    pub chapter_id: i64,
+    pub content_id: i64,
+    pub library_id: i64,
+    pub content_title: String,
+    pub chapter_title: String,
+    pub file_path: String,
+    pub file_type: String,
+    #[sqlx(try_from = "String")]
+    pub status: ChapterStatus,
+    /// Why the file could not be read at the last scan.
+    pub error: Option<String>,
+    /// Number of pages recorded as failed when they were requested.
+    pub page_errors: i64,
+}
+
+/// Query parameters for listing media errors.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MediaErrorQuery {
+    /// Only list the chapters of this library.
+    pub library_id: Option<i64>,
+}
+
+/// Size of a page of an image chapter.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PageInfo {
+    /// 0-based index of the page to request.
+    pub index: i64,
+    /// Width in pixels, 0 if the page could not be read.
+    pub width: u32,
+    /// Height in pixels, 0 if the page could not be read.
+    pub height: u32,
+    /// The page is a landscape double-page spread.
+    pub spread: bool,
+    /// Half of the spread this entry shows, when spreads are split into
+    /// two virtual pages. Request it with `?half=` on the page endpoint.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub half: Option<SpreadHalf>,
+    /// The page is a tall strip, sliced into tiles on request.
+    pub tall: bool,
+    /// Tile of the tall page this entry shows, when tall pages are sliced
+    /// into virtual pages. Request it with `?tile=` on the page endpoint.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tile: Option<u32>,
+    /// BlurHash placeholder of the whole page, once it has been served.
+    /// Only computed when enabled in the configuration.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blurhash: Option<String>,
+}
+
+/// Query parameters for the page list of a chapter.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct PageInfoQuery {
+    /// List each spread as two virtual pages, in reading order.
+    #[serde(default)]
+    pub split: bool,
+    /// List each tall page as its tiles, top to bottom.
+    #[serde(default)]
+    pub slice: bool,
+}
+
+/// Query parameters for page requests.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct PageQuery {
+    /// Return only this half of the page.
+    #[serde(default)]
+    pub half: Option<SpreadHalf>,
+    /// Return only this tile of a tall page, counted from the top.
+    #[serde(default)]
+    pub tile: Option<u32>,
+    /// Trim uniform white or black margins. Defaults to the reader settings
+    /// of the content.
+    #[serde(default)]
+    pub trim: Option<bool>,
 }
 
 /// Response structure for content list API.
@@ -147,8 +372,29 @@ pub struct ContentResponse {
     pub title: String,
     pub chapter_count: i32,
     pub has_thumbnail: bool,
+    /// Changes whenever the thumbnail is regenerated. Clients append it to
+    /// the thumbnail URL (`?v=...`) so the image can be cached indefinitely.
+    pub thumbnail_etag: Option<String>,
+    /// BlurHash of the thumbnail, for a placeholder shown while it loads.
+    pub thumbnail_blurhash: Option<String>,
     pub metadata: Option<serde_json::Value>,
+    /// Set while the content is in the trash.
+    pub missing_since: Option<DateTime<Utc>>,
+    /// Group of related contents this content is in.
+    pub series_group_id: Option<i64>,
+    /// Minimum age of readers, if known.
+    #[serde(default)]
+    pub age_rating: Option<i32>,
     pub created_at: DateTime<Utc>,
+    /// Last time a scan or edit changed the content.
+    pub updated_at: DateTime<Utc>,
+    /// Chapters the current user has not read to the end, where the
+    /// endpoint counts them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unread_count: Option<i64>,
+    /// Average rating users gave, where the endpoint computes it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ratings: Option<RatingSummary>,
 }
 
 impl From<Content> for ContentResponse {
@@ -159,14 +405,172 @@ impl From<Content> for ContentResponse {
             title: content.title,
             chapter_count: content.chapter_count,
             has_thumbnail: content.thumbnail.is_some(),
+            // Rows written before the column existed have no stored tag
+            thumbnail_etag: content.thumbnail.as_deref().map(|data| {
+                content
+                    .thumbnail_etag
+                    .clone()
+                    .unwrap_or_else(|| thumbnail_etag(data))
+            }),
+            thumbnail_blurhash: content.thumbnail_blurhash,
             metadata: content
                 .metadata
                 .and_then(|bytes| serde_json::from_slice(&bytes).ok()),
+            missing_since: content.missing_since,
+            series_group_id: content.series_group_id,
+            age_rating: content.age_rating,
             created_at: content.created_at,
+            updated_at: content.updated_at,
+            unread_count: None,
+            ratings: None,
         }
     }
 }
 
+/// Which timestamp a content feed is ordered by, newest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentFeed {
+    /// Content by the time it was first imported.
+    Added,
+    /// Content by the time a scan or edit last changed it.
+    Updated,
+}
+
+impl ContentFeed {
+    /// Name of the feed's order in its cursors.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ContentFeed::Added => "added",
+            ContentFeed::Updated => "updated",
+        }
+    }
+}
+
+/// Order of a content listing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentSort {
+    /// Alphabetically by title.
+    #[default]
+    Title,
+    /// Newest first by the time it was first imported.
+    Added,
+    /// Newest first by the time a scan or edit last changed it.
+    Updated,
+    /// Best rated first by the score in its metadata, unrated last.
+    Rating,
+    /// Best rated first by the average rating users gave, unrated last.
+    UserRating,
+}
+
+/// Conditions of a content listing. Unset fields match every content.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ContentFilter<'a> {
+    /// Only content in this library.
+    pub library_id: Option<i64>,
+    /// Only content rated up to this age, or unrated.
+    pub max_age_rating: Option<i32>,
+    /// Only content whose title or an alternative title contains this text.
+    pub search: Option<&'a str>,
+    /// Only content by an author of this name, ignoring case.
+    pub author: Option<&'a str>,
+    /// Only content scored at least this much out of 10.
+    pub min_rating: Option<f64>,
+}
+
+/// Query parameters for the content feeds.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ContentFeedQuery {
+    /// Only content in this library.
+    pub library_id: Option<i64>,
+    /// 1-based page number (default 1). Ignored with `after`.
+    pub page: Option<i64>,
+    /// Contents per page (default 20, max 100).
+    #[serde(alias = "limit")]
+    pub page_size: Option<i64>,
+    /// Cursor from `next_cursor` of the previous page; the page starts after
+    /// it instead of at an offset.
+    pub after: Option<String>,
+}
+
+/// A page of a content feed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentPage {
+    pub items: Vec<ContentResponse>,
+    pub total: i64,
+    pub page: i64,
+    pub page_size: i64,
+    /// Cursor of the next page, unless this page is the last.
+    pub next_cursor: Option<String>,
+}
+
+/// Cursor query parameters for a library's content listing.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ContentCursorQuery {
+    /// Cursor from the `X-Next-Cursor` header of the previous page.
+    pub after: Option<String>,
+    /// Contents per page (default 100, max 500).
+    pub limit: Option<i64>,
+}
+
+impl ContentCursorQuery {
+    /// Whether a page was asked for, rather than the whole listing.
+    pub fn is_paged(&self) -> bool {
+        self.after.is_some() || self.limit.is_some()
+    }
+}
+
+/// Request to move a content to another scan path, possibly of another
+/// library.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MoveContentRequest {
+    pub scan_path_id: i64,
+    /// Move the folder on disk into the scan path. Without it, the folder
+    /// must already be there.
+    #[serde(default)]
+    pub move_files: bool,
+}
+
+/// Request for the thumbnails of several contents at once.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ThumbnailBatchRequest {
+    pub ids: Vec<i64>,
+    /// Largest width of the previews, in pixels (default 96, max 320).
+    pub width: Option<u32>,
+}
+
+/// Small JPEG preview of a content's thumbnail.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThumbnailPreview {
+    pub mime_type: String,
+    /// Version tag, distinct for each preview width.
+    pub etag: String,
+    /// Base64 encoded image.
+    pub data: String,
+}
+
+/// Occurrence of a search query in the text of a novel chapter.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextSearchMatch {
+    pub chapter_id: i64,
+    /// 0-based index of the chapter, as used by the chapter text endpoint.
+    pub chapter_index: i32,
+    pub chapter_title: String,
+    /// Offset of the match in the chapter text, in characters.
+    pub offset: usize,
+    /// The match with some text around it.
+    pub snippet: String,
+}
+
+/// Compute the version tag of a thumbnail from its bytes.
+///
+/// The tag is a truncated SHA-1 of the image, so regenerating an identical
+/// thumbnail keeps client caches valid.
+pub fn thumbnail_etag(data: &[u8]) -> String {
+    let digest = Sha1::digest(data);
+    digest[..8].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
 /// Helper to extract file type (extension) from a path.
 pub fn file_type_from_path(path: &std::path::Path) -> String {
     path.extension()
@@ -175,5 +579,18 @@ pub fn file_type_from_path(path: &std::path::Path) -> String {
         .unwrap_or_default()
 }
 
+/// File type of chapters that are plain folders of images.
+pub const IMAGE_FOLDER_FILE_TYPE: &str = "folder";
+
+/// Whether chapters of a file type are read as pages of images.
+pub fn is_image_file_type(file_type: &str) -> bool {
+    matches!(
+        file_type,
+        "zip" | "cbz" | "cbr" | "rar" | "pdf" | IMAGE_FOLDER_FILE_TYPE
+    )
+}
+
 /// All supported archive extensions.
-pub const ALL_SUPPORTED_EXTENSIONS: &[&str] = &["zip", "cbz", "cbr", "rar", "pdf", "epub"];
+pub const ALL_SUPPORTED_EXTENSIONS: &[&str] = &[
+    "zip", "cbz", "cbr", "rar", "pdf", "epub", "mobi", "azw3", "azw", "txt", "m4b", "m4a", "mp3",
+];
@@ -0,0 +1,136 @@
+//! Per-user content status models.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// What a user intends to do with a content, set by the user rather than
+/// derived from reading progress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadingStatus {
+    Reading,
+    PlanToRead,
+    Completed,
+    OnHold,
+    Dropped,
+}
+
+impl ReadingStatus {
+    /// All statuses, in the order offered to users.
+    pub const ALL: [ReadingStatus; 5] = [
+        ReadingStatus::Reading,
+        ReadingStatus::PlanToRead,
+        ReadingStatus::Completed,
+        ReadingStatus::OnHold,
+        ReadingStatus::Dropped,
+    ];
+
+    /// Identifier stored in the database and used in the API.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReadingStatus::Reading => "reading",
+            ReadingStatus::PlanToRead => "plan_to_read",
+            ReadingStatus::Completed => "completed",
+            ReadingStatus::OnHold => "on_hold",
+            ReadingStatus::Dropped => "dropped",
+        }
+    }
+
+    /// Parse an identifier returned by [`Self::as_str`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|status| status.as_str() == name)
+    }
+}
+
+/// A user's favorite flag and reading status for a content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentStatus {
+    pub content_id: i64,
+    pub favorite: bool,
+    /// `None` when the user has not picked a status.
+    pub status: Option<ReadingStatus>,
+    /// `None` when nothing has been set yet.
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl ContentStatus {
+    /// The status of a content the user has not marked.
+    pub fn unset(content_id: i64) -> Self {
+        Self {
+            content_id,
+            favorite: false,
+            status: None,
+            updated_at: None,
+        }
+    }
+}
+
+/// Request to replace a user's status for a content.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpdateContentStatusRequest {
+    #[serde(default)]
+    pub favorite: bool,
+    /// Omit or send `null` to clear the status.
+    #[serde(default)]
+    pub status: Option<ReadingStatus>,
+}
+
+/// Filters on the current user's status, for content listings.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct ContentStatusFilter {
+    /// Only content with this status.
+    pub status: Option<ReadingStatus>,
+    /// Only favorites (`true`) or only non-favorites (`false`).
+    pub favorite: Option<bool>,
+}
+
+impl ContentStatusFilter {
+    /// Whether the filter lets every content through.
+    pub fn is_empty(&self) -> bool {
+        self.status.is_none() && self.favorite.is_none()
+    }
+
+    /// Whether a content with this status passes the filter.
+    pub fn matches(&self, status: &ContentStatus) -> bool {
+        self.status
+            .is_none_or(|wanted| status.status == Some(wanted))
+            && self.favorite.is_none_or(|wanted| status.favorite == wanted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_names_round_trip() {
+        for status in ReadingStatus::ALL {
+            assert_eq!(ReadingStatus::from_name(status.as_str()), Some(status));
+            let json = serde_json::to_string(&status).unwrap();
+            assert_eq!(json, format!("\"{}\"", status.as_str()));
+        }
+        assert_eq!(ReadingStatus::from_name("unknown"), None);
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        let mut status = ContentStatus::unset(1);
+        assert!(ContentStatusFilter::default().matches(&status));
+
+        let reading = ContentStatusFilter {
+            status: Some(ReadingStatus::Reading),
+            favorite: None,
+        };
+        let favorites = ContentStatusFilter {
+            status: None,
+            favorite: Some(true),
+        };
+        assert!(!reading.matches(&status));
+        assert!(!favorites.matches(&status));
+
+        status.status = Some(ReadingStatus::Reading);
+        status.favorite = true;
+        assert!(reading.matches(&status));
+        assert!(favorites.matches(&status));
+    }
+}
@@ -0,0 +1,39 @@
+//! Home page dashboard models.
+
+use serde::{Deserialize, Serialize};
+
+use super::{ContentResponse, LibraryWithStats, ScanTask};
+
+/// Query parameters for the dashboard.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DashboardQuery {
+    /// Contents per list (default 10, max 50).
+    pub limit: Option<i64>,
+}
+
+/// Everything the home page shows, in one response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dashboard {
+    pub libraries: Vec<LibraryWithStats>,
+    /// Most recently added contents, newest first.
+    pub recently_added: Vec<ContentResponse>,
+    /// Contents the current user read most recently.
+    pub continue_reading: Vec<ContentResponse>,
+    /// Running tasks, then pending tasks in queue order.
+    pub active_tasks: Vec<ScanTask>,
+    /// Counts of problems to look into; only sent to administrators.
+    pub errors: Option<DashboardErrors>,
+}
+
+/// Counts of problems needing an administrator.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DashboardErrors {
+    /// Contents whose metadata could not be scraped.
+    pub metadata_failures: i64,
+    /// Chapters that cannot be read or had pages fail to load.
+    pub media_errors: i64,
+    /// Contents in the trash.
+    pub trash: i64,
+    /// Failed tasks still in the task history.
+    pub failed_tasks: i64,
+}
@@ -0,0 +1,41 @@
+//! Duplicate content models.
+
+use serde::{Deserialize, Serialize};
+
+/// A content item that is likely a duplicate of others in its group.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DuplicateContent {
+    pub content_id: i64,
+    pub library_id: i64,
+    pub scan_path_id: i64,
+    pub title: String,
+    pub folder_path: String,
+    pub chapter_count: i32,
+}
+
+/// Contents whose first pages are identical.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    /// SHA-1 of the first page of the first chapter.
+    pub page_hash: String,
+    /// Contents in the group, ordered by ID.
+    pub contents: Vec<DuplicateContent>,
+}
+
+/// Request to merge duplicates into one content.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MergeDuplicatesRequest {
+    /// The content to keep.
+    pub keep_id: i64,
+    /// The contents merged into it.
+    pub merge_ids: Vec<i64>,
+}
+
+/// Result of merging duplicates.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MergeDuplicatesResponse {
+    pub kept: i64,
+    pub merged: Vec<i64>,
+    /// Number of reading progress entries moved to the kept content.
+    pub progress_moved: u64,
+}
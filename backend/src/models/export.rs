@@ -0,0 +1,83 @@
+//! Library export and import models.
+//!
+//! An export is a JSON document describing the contents of a library, so
+//! metadata and reading progress can be moved to another server that has
+//! scanned the same files.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::NovelPosition;
+
+/// Version of the export format written by this server.
+pub const LIBRARY_EXPORT_VERSION: u32 = 1;
+
+/// A library export.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LibraryExport {
+    /// Format version, see [`LIBRARY_EXPORT_VERSION`].
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    /// Name of the exported library.
+    pub library_name: String,
+    /// Scan paths of the exported library, used to match contents by their
+    /// path relative to a scan path.
+    pub scan_paths: Vec<String>,
+    pub contents: Vec<ExportedContent>,
+}
+
+/// A content item in an export.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedContent {
+    pub title: String,
+    pub folder_path: String,
+    /// SHA-1 of the first page of the first chapter, if it could be read.
+    pub page_hash: Option<String>,
+    pub metadata: Option<serde_json::Value>,
+    pub chapters: Vec<ExportedChapter>,
+}
+
+/// A chapter in an export.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedChapter {
+    pub title: String,
+    pub file_path: String,
+    pub sort_order: i32,
+    /// Reading progress of all users; empty unless progress was exported.
+    #[serde(default)]
+    pub progress: Vec<ExportedProgress>,
+}
+
+/// Reading progress of one user on a chapter in an export.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedProgress {
+    /// Users are matched by name on import.
+    pub username: String,
+    pub position: i32,
+    pub percentage: f32,
+    pub updated_at: DateTime<Utc>,
+    /// Place in the text for novels. Missing in exports from older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub novel: Option<NovelPosition>,
+}
+
+/// Query parameters for exporting a library.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LibraryExportQuery {
+    /// Include the reading progress of all users.
+    #[serde(default)]
+    pub include_progress: bool,
+}
+
+/// Result of importing an export into a library.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LibraryImportResult {
+    /// Number of exported contents matched to a content of the library.
+    pub matched_contents: usize,
+    /// Folder paths of exported contents without a match.
+    pub unmatched_contents: Vec<String>,
+    /// Number of progress entries written.
+    pub progress_imported: u64,
+    /// Exported users without an account on this server.
+    pub unknown_users: Vec<String>,
+}
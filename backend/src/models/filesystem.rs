@@ -0,0 +1,32 @@
+//! Server filesystem browsing models.
+
+use serde::{Deserialize, Serialize};
+
+/// Kind of a listed filesystem entry; directories sort first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    Directory,
+    File,
+}
+
+/// Entry of a browsed directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectoryEntry {
+    pub name: String,
+    /// Path to browse or add as a scan path.
+    pub path: String,
+    /// Parent of the browsed directory; unset at the top, where going up
+    /// leads back to the list of roots.
+    pub parent: Option<String>,
+    pub kind: EntryKind,
+    /// Size in bytes, for files.
+    pub size: Option<u64>,
+    /// Whether the entry is a symbolic link. Links leading outside the
+    /// allowed roots are not listed.
+    pub symlink: bool,
+    /// Whether the server can open the entry.
+    pub readable: bool,
+    /// Whether the entry is marked read-only.
+    pub readonly: bool,
+}
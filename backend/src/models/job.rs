@@ -0,0 +1,132 @@
+//! Unified background job models.
+//!
+//! Every kind of background work is presented to clients as a [`Job`] so
+//! the UI can show a single task list with common controls.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::scan_queue::{ScanTask, TaskKind, TaskPriority, TaskStatus};
+
+/// Kind of background job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// Library scan.
+    Scan,
+    /// Regeneration of a library's thumbnails.
+    Thumbnails,
+    /// Counting the pages of a library's chapters that have none.
+    PageCounts,
+    /// Fetching the metadata of a library's content again.
+    MetadataRefresh,
+    /// Backup of the database.
+    Backup,
+    /// Removal of a deleted library's contents.
+    LibraryDeletion,
+}
+
+/// Progress of a running job.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobProgress {
+    /// Units of work finished.
+    pub done: i64,
+    /// Total units of work.
+    pub total: i64,
+}
+
+/// Controls currently available for a job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobActions {
+    /// The job can be cancelled.
+    pub cancel: bool,
+    /// The job can be run again.
+    pub retry: bool,
+    /// The job's priority can be changed.
+    pub set_priority: bool,
+}
+
+/// A background job of any kind.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Job {
+    /// Unique identifier, shared with the underlying task.
+    pub id: Uuid,
+    /// Kind of job.
+    pub kind: JobKind,
+    /// Current status.
+    pub status: TaskStatus,
+    /// Queue priority.
+    pub priority: TaskPriority,
+    /// Library the job works on, if any.
+    pub library_id: Option<i64>,
+    /// Scan path the job works on, if limited to one.
+    pub scan_path_id: Option<i64>,
+    /// Progress while running.
+    pub progress: Option<JobProgress>,
+    /// Timestamp when the job was created.
+    pub created_at: DateTime<Utc>,
+    /// Timestamp when the job started.
+    pub started_at: Option<DateTime<Utc>>,
+    /// Timestamp when the job finished.
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Error message for failed jobs.
+    pub error: Option<String>,
+    /// Summary of a finished job in the requesting user's locale.
+    pub summary: Option<String>,
+    /// Controls available for the job.
+    pub actions: JobActions,
+}
+
+impl From<ScanTask> for Job {
+    fn from(task: ScanTask) -> Self {
+        let actions = JobActions {
+            cancel: matches!(task.status, TaskStatus::Pending | TaskStatus::Running),
+            retry: matches!(task.status, TaskStatus::Failed | TaskStatus::Cancelled),
+            set_priority: task.status == TaskStatus::Pending,
+        };
+
+        Self {
+            id: task.id,
+            kind: match task.kind {
+                TaskKind::Scan => JobKind::Scan,
+                TaskKind::Thumbnails => JobKind::Thumbnails,
+                TaskKind::PageCounts => JobKind::PageCounts,
+                TaskKind::MetadataRefresh => JobKind::MetadataRefresh,
+                TaskKind::Backup => JobKind::Backup,
+                TaskKind::LibraryDeletion => JobKind::LibraryDeletion,
+            },
+            status: task.status,
+            priority: task.priority,
+            library_id: task.kind.targets_library().then_some(task.library_id),
+            scan_path_id: task.scan_path_id,
+            progress: task.progress.map(|p| JobProgress {
+                done: p.scanned_paths as i64,
+                total: p.total_paths as i64,
+            }),
+            created_at: task.created_at,
+            started_at: task.started_at,
+            completed_at: task.completed_at,
+            error: task.error,
+            summary: task.summary,
+            actions,
+        }
+    }
+}
+
+/// Query parameters for listing jobs.
+#[derive(Debug, Clone, Deserialize)]
+pub struct JobListQuery {
+    /// Only return jobs of this kind.
+    pub kind: Option<JobKind>,
+    /// Only return jobs with this status.
+    pub status: Option<TaskStatus>,
+    /// Maximum number of finished jobs to return (default: 50).
+    pub limit: Option<usize>,
+}
+
+/// Request body for changing a job's priority.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetJobPriorityRequest {
+    pub priority: TaskPriority,
+}
@@ -0,0 +1,56 @@
+//! Kobo sync data models.
+
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+use serde::{Deserialize, Serialize};
+
+/// Place in a book as a Kobo e-reader reports it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct KoboLocation {
+    /// Location within the section, e.g. a `kobo.12.3` span ID.
+    #[sqlx(rename = "location_value")]
+    pub value: Option<String>,
+    /// Kind of location, usually `KoboSpan`.
+    #[sqlx(rename = "location_type")]
+    pub kind: Option<String>,
+    /// Path of the EPUB section the location is in.
+    #[sqlx(rename = "location_source")]
+    pub source: Option<String>,
+}
+
+/// Reading state of a book synced to Kobo e-readers: the progress shared
+/// with other readers and the device's own location.
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+pub struct KoboReadingState {
+    pub chapter_id: i64,
+    /// Percentage read (0.0 to 100.0).
+    pub percentage: f32,
+    /// When the progress last changed, as stored.
+    pub updated_at: String,
+    #[sqlx(flatten)]
+    pub location: KoboLocation,
+}
+
+/// Where a device's last library sync stopped, returned to it as an opaque
+/// token and sent back with the next sync.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KoboSyncToken {
+    /// Largest chapter ID already synced.
+    #[serde(rename = "b")]
+    pub last_chapter_id: i64,
+    /// Time of the latest reading state already synced, as stored.
+    #[serde(rename = "s", default, skip_serializing_if = "Option::is_none")]
+    pub states_since: Option<String>,
+}
+
+impl KoboSyncToken {
+    /// Encode the token for the `X-Kobo-SyncToken` header.
+    pub fn encode(&self) -> String {
+        BASE64_URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    /// Decode a token from the `X-Kobo-SyncToken` header. `None` if it was
+    /// not made by [`Self::encode`], e.g. by the Kobo store.
+    pub fn decode(token: &str) -> Option<Self> {
+        serde_json::from_slice(&BASE64_URL_SAFE_NO_PAD.decode(token).ok()?).ok()
+    }
+}
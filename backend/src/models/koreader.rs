@@ -0,0 +1,42 @@
+//! KOReader sync data models.
+//!
+//! These types follow the JSON of the kosync protocol, so they are sent to
+//! and read from devices as they are.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Position in a document as a KOReader device sent it, or as the server
+/// derived it from the progress of other readers.
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct KoreaderProgress {
+    /// Partial MD5 of the document's file, see
+    /// [`crate::services::koreader::partial_md5`].
+    pub document: String,
+    /// XPointer of the place in reflowable documents, e.g.
+    /// `/body/DocFragment[12]/body/p[3]/text().0`, or the page number
+    /// (1-based) in others.
+    pub progress: String,
+    /// Fraction of the document read (0.0 to 1.0).
+    pub percentage: f32,
+    /// Name of the device.
+    pub device: String,
+    /// Identifier of the device.
+    pub device_id: String,
+    /// When the position was sent, as a Unix timestamp.
+    #[serde(rename = "timestamp", with = "chrono::serde::ts_seconds")]
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Position in a document sent by a KOReader device.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpdateKoreaderProgress {
+    pub document: String,
+    pub progress: String,
+    /// Fraction of the document read (0.0 to 1.0).
+    pub percentage: f32,
+    #[serde(default)]
+    pub device: String,
+    #[serde(default)]
+    pub device_id: String,
+}
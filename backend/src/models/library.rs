@@ -3,6 +3,12 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+/// Default number of folder levels searched for content below a scan path.
+pub const DEFAULT_SCAN_DEPTH: i32 = 1;
+
+/// Maximum number of folder levels searched for content below a scan path.
+pub const MAX_SCAN_DEPTH: i32 = 8;
+
 /// A content library that can contain multiple scan paths.
 ///
 /// Libraries are the top-level organizational unit for content.
@@ -18,6 +24,12 @@ pub struct Library {
     pub scan_interval: i32,
     /// Whether file system watching is enabled for real-time updates.
     pub watch_mode: bool,
+    /// How many folder levels below a scan path are searched for content.
+    /// 1 means only immediate subdirectories.
+    pub scan_depth: i32,
+    /// Whether archive files lying directly in a scan path are imported as
+    /// single-chapter contents.
+    pub standalone_archives: bool,
     /// Timestamp when the library was created.
     pub created_at: DateTime<Utc>,
     /// Timestamp when the library was last updated.
@@ -31,6 +43,8 @@ impl Library {
             name,
             scan_interval,
             watch_mode,
+            scan_depth: DEFAULT_SCAN_DEPTH,
+            standalone_archives: false,
         }
     }
 }
@@ -41,6 +55,8 @@ pub struct NewLibrary {
     pub name: String,
     pub scan_interval: i32,
     pub watch_mode: bool,
+    pub scan_depth: i32,
+    pub standalone_archives: bool,
 }
 
 /// A scan path associated with a library.
@@ -97,6 +113,12 @@ pub struct CreateLibraryRequest {
     pub scan_interval: Option<i32>,
     /// Optional watch mode setting (defaults to false).
     pub watch_mode: Option<bool>,
+    /// Optional scan depth, 1 to [`MAX_SCAN_DEPTH`] (defaults to 1).
+    #[serde(default)]
+    pub scan_depth: Option<i32>,
+    /// Optional standalone archive import setting (defaults to false).
+    #[serde(default)]
+    pub standalone_archives: Option<bool>,
 }
 
 /// Request to update an existing library.
@@ -108,4 +130,19 @@ pub struct UpdateLibraryRequest {
     pub scan_interval: Option<i32>,
     /// New watch mode setting.
     pub watch_mode: Option<bool>,
+    /// New scan depth.
+    #[serde(default)]
+    pub scan_depth: Option<i32>,
+    /// New standalone archive import setting.
+    #[serde(default)]
+    pub standalone_archives: Option<bool>,
+}
+
+/// Name patterns for files and folders a library's scans skip.
+///
+/// Used both as the request to replace the patterns and as the response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IgnorePatterns {
+    /// Patterns with `*` and `?` wildcards, e.g. `@eaDir`, `.*` or `*_raw`.
+    pub patterns: Vec<String>,
 }
@@ -0,0 +1,137 @@
+//! Metadata provider models.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// An external source of content metadata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetadataProviderKind {
+    /// Bangumi.tv, strongest for Chinese and Japanese titles.
+    Bangumi,
+    /// AniList, for English and romanized titles.
+    AniList,
+    /// MangaUpdates (Baka-Updates), with broad coverage of scanlated series.
+    MangaUpdates,
+}
+
+impl MetadataProviderKind {
+    /// All providers, in the order offered to users.
+    pub const ALL: [MetadataProviderKind; 3] = [
+        MetadataProviderKind::Bangumi,
+        MetadataProviderKind::AniList,
+        MetadataProviderKind::MangaUpdates,
+    ];
+
+    /// Identifier stored in the database and used in the API.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetadataProviderKind::Bangumi => "bangumi",
+            MetadataProviderKind::AniList => "anilist",
+            MetadataProviderKind::MangaUpdates => "mangaupdates",
+        }
+    }
+
+    /// Parse an identifier returned by [`Self::as_str`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == name)
+    }
+}
+
+/// Providers new libraries scrape metadata from, in order.
+pub const DEFAULT_METADATA_PROVIDERS: &[MetadataProviderKind] = &[MetadataProviderKind::Bangumi];
+
+/// A search result from a metadata provider.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetadataSearchResult {
+    /// Provider the result comes from.
+    pub provider: MetadataProviderKind,
+    /// ID of the series at the provider.
+    pub id: String,
+    /// Main title.
+    pub title: String,
+    /// Alternative title, e.g. the Chinese or native title.
+    pub alt_title: Option<String>,
+    pub summary: Option<String>,
+    /// Cover image URL.
+    pub image: Option<String>,
+}
+
+/// Metadata providers of a library, tried in order when scraping.
+///
+/// Used both as the request to replace the list and as the response. An
+/// empty list disables automatic scraping for the library.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetadataProviders {
+    pub providers: Vec<MetadataProviderKind>,
+}
+
+/// Query parameters for searching metadata providers.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MetadataSearchQuery {
+    /// Title to search for.
+    pub q: String,
+    /// Provider to search; all providers when omitted.
+    #[serde(default)]
+    pub provider: Option<MetadataProviderKind>,
+}
+
+/// Request to replace a content's metadata with a series from a provider.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MetadataMatchRequest {
+    pub provider: MetadataProviderKind,
+    /// ID of the series at the provider.
+    pub id: String,
+    /// Fetch the series again instead of using a cached response.
+    #[serde(default)]
+    pub refresh_cache: bool,
+}
+
+/// Query parameters for retrying a failed scrape.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MetadataRetryQuery {
+    /// Query providers again instead of using cached responses.
+    #[serde(default)]
+    pub refresh_cache: bool,
+}
+
+/// A content whose metadata could not be scraped.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct MetadataFailure {
+    pub id: i64,
+    pub content_id: i64,
+    pub library_id: i64,
+    /// Title of the content.
+    pub title: String,
+    /// Error message from the latest attempt.
+    pub error: String,
+    /// Number of failed attempts, including the scan that imported the content.
+    pub attempts: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Result of retrying a failed scrape.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetadataRetryResult {
+    /// Whether metadata was found; the failure is removed if so.
+    pub resolved: bool,
+    /// The failure after the attempt, if it is still unresolved.
+    pub failure: Option<MetadataFailure>,
+}
+
+/// Request to check a Bangumi access token.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct BangumiKeyCheckRequest {
+    /// Token to check. Defaults to the one saved in the user's profile.
+    #[serde(default)]
+    pub api_key: Option<String>,
+}
+
+/// Whether Bangumi accepts an access token.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BangumiKeyCheck {
+    pub valid: bool,
+    /// Bangumi account the token belongs to, when valid.
+    pub username: Option<String>,
+}
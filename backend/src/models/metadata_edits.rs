@@ -0,0 +1,344 @@
+//! Manual metadata edit models.
+//!
+//! Scraped metadata is stored as a provider blob on the content and is
+//! replaced wholesale by every rescrape. Edits made by hand are kept apart
+//! from it and take precedence over it. Each field has a lock: a rescrape
+//! drops unlocked edits in favour of the new scraped values and keeps
+//! locked ones.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::patch::nullable;
+
+/// Publication status of a series.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeriesStatus {
+    Ongoing,
+    Ended,
+    Hiatus,
+    Abandoned,
+}
+
+impl SeriesStatus {
+    /// All statuses, in the order offered to users.
+    pub const ALL: [SeriesStatus; 4] = [
+        SeriesStatus::Ongoing,
+        SeriesStatus::Ended,
+        SeriesStatus::Hiatus,
+        SeriesStatus::Abandoned,
+    ];
+
+    /// Identifier stored in the database and used in the API.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SeriesStatus::Ongoing => "ongoing",
+            SeriesStatus::Ended => "ended",
+            SeriesStatus::Hiatus => "hiatus",
+            SeriesStatus::Abandoned => "abandoned",
+        }
+    }
+
+    /// Parse an identifier returned by [`Self::as_str`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|status| status.as_str() == name)
+    }
+
+    /// Status as named by Komga clients, e.g. `ONGOING`.
+    pub fn komga_name(&self) -> String {
+        self.as_str().to_ascii_uppercase()
+    }
+
+    /// Parse a Komga status name.
+    pub fn from_komga_name(name: &str) -> Option<Self> {
+        Self::from_name(&name.to_ascii_lowercase())
+    }
+}
+
+/// An author and their role, e.g. `writer` or `penciller`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetadataAuthor {
+    pub name: String,
+    #[serde(default = "default_author_role")]
+    pub role: String,
+}
+
+fn default_author_role() -> String {
+    "writer".to_string()
+}
+
+/// Metadata edited by hand for a content. `None` values fall back to the
+/// scraped metadata.
+///
+/// The title and age rating are stored on the content itself, as they are
+/// what listings sort, search and filter by; only their locks are kept here.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetadataEdits {
+    pub title_lock: bool,
+    pub summary: Option<String>,
+    pub summary_lock: bool,
+    pub status: Option<SeriesStatus>,
+    pub status_lock: bool,
+    pub language: Option<String>,
+    pub language_lock: bool,
+    pub tags: Option<Vec<String>>,
+    pub tags_lock: bool,
+    pub authors: Option<Vec<MetadataAuthor>>,
+    pub authors_lock: bool,
+    pub age_rating_lock: bool,
+    /// `None` when nothing has been edited yet.
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl MetadataEdits {
+    /// Whether nothing is edited or locked.
+    pub fn is_empty(&self) -> bool {
+        !self.title_lock
+            && !self.summary_lock
+            && !self.status_lock
+            && !self.language_lock
+            && !self.tags_lock
+            && !self.authors_lock
+            && !self.age_rating_lock
+            && self.summary.is_none()
+            && self.status.is_none()
+            && self.language.is_none()
+            && self.tags.is_none()
+            && self.authors.is_none()
+    }
+
+    /// Apply a partial update.
+    ///
+    /// A field given a value is locked unless the request says otherwise,
+    /// since it was typed in by hand; a field cleared with `null` is
+    /// unlocked unless the request says otherwise.
+    pub fn apply(&mut self, request: UpdateMetadataEditsRequest) {
+        if let Some(lock) = request.title_lock {
+            self.title_lock = lock;
+        } else if request.title.is_some() {
+            self.title_lock = true;
+        }
+        if let Some(lock) = request.age_rating_lock {
+            self.age_rating_lock = lock;
+        } else if let Some(age_rating) = request.age_rating {
+            self.age_rating_lock = age_rating.is_some();
+        }
+        edit(
+            &mut self.summary,
+            &mut self.summary_lock,
+            request.summary.map(|v| v.and_then(normalize_text)),
+            request.summary_lock,
+        );
+        edit(
+            &mut self.status,
+            &mut self.status_lock,
+            request.status,
+            request.status_lock,
+        );
+        edit(
+            &mut self.language,
+            &mut self.language_lock,
+            request.language.map(|v| v.and_then(normalize_text)),
+            request.language_lock,
+        );
+        edit(
+            &mut self.tags,
+            &mut self.tags_lock,
+            request.tags.map(|v| v.map(normalize_tags)),
+            request.tags_lock,
+        );
+        edit(
+            &mut self.authors,
+            &mut self.authors_lock,
+            request.authors.map(|v| v.map(normalize_authors)),
+            request.authors_lock,
+        );
+    }
+
+    /// Drop the edits that are not locked, after a rescrape replaced the
+    /// scraped metadata.
+    pub fn clear_unlocked(&mut self) {
+        if !self.summary_lock {
+            self.summary = None;
+        }
+        if !self.status_lock {
+            self.status = None;
+        }
+        if !self.language_lock {
+            self.language = None;
+        }
+        if !self.tags_lock {
+            self.tags = None;
+        }
+        if !self.authors_lock {
+            self.authors = None;
+        }
+    }
+}
+
+/// Set or clear one field and its lock.
+fn edit<T>(
+    value: &mut Option<T>,
+    lock: &mut bool,
+    new_value: Option<Option<T>>,
+    new_lock: Option<bool>,
+) {
+    match new_value {
+        Some(new_value) => {
+            *lock = new_lock.unwrap_or(new_value.is_some());
+            *value = new_value;
+        }
+        None => {
+            if let Some(new_lock) = new_lock {
+                *lock = new_lock;
+            }
+        }
+    }
+}
+
+/// Trim text, treating blank text as no value.
+fn normalize_text(text: String) -> Option<String> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Trim tags and drop blank and repeated ones, keeping their order.
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags.into_iter().filter_map(normalize_text) {
+        if !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
+/// Trim author names and roles and drop authors without a name.
+fn normalize_authors(authors: Vec<MetadataAuthor>) -> Vec<MetadataAuthor> {
+    authors
+        .into_iter()
+        .filter_map(|author| {
+            Some(MetadataAuthor {
+                name: normalize_text(author.name)?,
+                role: normalize_text(author.role).unwrap_or_else(default_author_role),
+            })
+        })
+        .collect()
+}
+
+/// Partial update of a content's metadata. A missing field is left as is,
+/// `null` clears the edit so the scraped value shows again.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpdateMetadataEditsRequest {
+    /// New title. The title cannot be cleared, only replaced.
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub title_lock: Option<bool>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub summary: Option<Option<String>>,
+    #[serde(default)]
+    pub summary_lock: Option<bool>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub status: Option<Option<SeriesStatus>>,
+    #[serde(default)]
+    pub status_lock: Option<bool>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub language: Option<Option<String>>,
+    #[serde(default)]
+    pub language_lock: Option<bool>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub tags: Option<Option<Vec<String>>>,
+    #[serde(default)]
+    pub tags_lock: Option<bool>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub authors: Option<Option<Vec<MetadataAuthor>>>,
+    #[serde(default)]
+    pub authors_lock: Option<bool>,
+    /// Minimum age of readers; `null` goes back to the rating in the
+    /// metadata.
+    #[serde(default, deserialize_with = "nullable")]
+    pub age_rating: Option<Option<i32>>,
+    #[serde(default)]
+    pub age_rating_lock: Option<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(json: &str) -> UpdateMetadataEditsRequest {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_edits_lock_by_default() {
+        let mut edits = MetadataEdits::default();
+        edits.apply(request(
+            r#"{"summary": " Hand written ", "tags": ["a", " b ", "a", ""], "tags_lock": false}"#,
+        ));
+
+        assert_eq!(edits.summary.as_deref(), Some("Hand written"));
+        assert!(edits.summary_lock);
+        assert_eq!(edits.tags, Some(vec!["a".to_string(), "b".to_string()]));
+        assert!(!edits.tags_lock);
+        assert!(edits.status.is_none());
+        assert!(!edits.status_lock);
+    }
+
+    #[test]
+    fn test_null_clears_and_unlocks() {
+        let mut edits = MetadataEdits {
+            language: Some("ja".to_string()),
+            language_lock: true,
+            status: Some(SeriesStatus::Ended),
+            status_lock: true,
+            ..Default::default()
+        };
+        edits.apply(request(r#"{"language": null}"#));
+
+        assert_eq!(edits.language, None);
+        assert!(!edits.language_lock);
+        assert_eq!(edits.status, Some(SeriesStatus::Ended));
+        assert!(edits.status_lock);
+    }
+
+    #[test]
+    fn test_rescrape_keeps_locked_edits() {
+        let mut edits = MetadataEdits::default();
+        edits.apply(request(
+            r#"{"summary": "Mine", "authors": [{"name": "A"}], "authors_lock": false}"#,
+        ));
+        edits.clear_unlocked();
+
+        assert_eq!(edits.summary.as_deref(), Some("Mine"));
+        assert_eq!(edits.authors, None);
+    }
+
+    #[test]
+    fn test_empty_edits() {
+        let mut edits = MetadataEdits::default();
+        assert!(edits.is_empty());
+        edits.apply(request(r#"{"title": "New", "summary": null}"#));
+        assert!(edits.title_lock);
+        assert!(!edits.is_empty());
+        edits.apply(request(r#"{"title_lock": false}"#));
+        assert!(edits.is_empty());
+    }
+
+    #[test]
+    fn test_komga_status_names() {
+        for status in SeriesStatus::ALL {
+            assert_eq!(
+                SeriesStatus::from_komga_name(&status.komga_name()),
+                Some(status)
+            );
+        }
+        assert_eq!(
+            SeriesStatus::from_komga_name("ENDED"),
+            Some(SeriesStatus::Ended)
+        );
+        assert_eq!(SeriesStatus::from_komga_name("DONE"), None);
+    }
+}
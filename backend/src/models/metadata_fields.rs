@@ -0,0 +1,177 @@
+//! Denormalized metadata field models.
+//!
+//! Scraped metadata is kept as a Bangumi-style JSON blob, which every
+//! provider converts its data to. The fields listings filter and sort by are
+//! copied out of it, with the manual edits applied, into columns of the
+//! content and the `content_authors` table whenever either changes.
+
+use crate::models::{MetadataAuthor, MetadataEdits};
+
+/// Infobox keys of the people credited, with the role they are given.
+const AUTHOR_KEYS: [(&str, &str); 2] = [("作者", "writer"), ("作画", "penciller")];
+
+/// Infobox key of the publisher.
+const PUBLISHER_KEY: &str = "出版社";
+
+/// Language assumed for scraped metadata that does not name one, as
+/// Bangumi lists manga by their Japanese edition.
+pub const DEFAULT_METADATA_LANGUAGE: &str = "ja";
+
+/// Key fields of the metadata of a content.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetadataFields {
+    pub summary: Option<String>,
+    pub authors: Vec<MetadataAuthor>,
+    pub publisher: Option<String>,
+    /// Release date as given by the provider: a full date or only a year.
+    pub release_date: Option<String>,
+    pub language: Option<String>,
+    /// Average score out of 10.
+    pub rating: Option<f64>,
+}
+
+impl MetadataFields {
+    /// Fields of Bangumi-style metadata.
+    pub fn from_metadata(metadata: &serde_json::Value) -> Self {
+        let text = |key: &str| {
+            metadata
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(String::from)
+        };
+
+        let mut authors: Vec<MetadataAuthor> = Vec::new();
+        for (key, role) in AUTHOR_KEYS {
+            for name in infobox_values(metadata, key) {
+                if !authors.iter().any(|a| a.name == name && a.role == role) {
+                    authors.push(MetadataAuthor {
+                        name,
+                        role: role.to_string(),
+                    });
+                }
+            }
+        }
+
+        // MangaUpdates gives the year as a number
+        let release_date = text("date").or_else(|| {
+            metadata
+                .get("date")
+                .and_then(|v| v.as_i64())
+                .map(|year| year.to_string())
+        });
+
+        Self {
+            summary: text("summary"),
+            authors,
+            publisher: infobox_values(metadata, PUBLISHER_KEY).into_iter().next(),
+            release_date,
+            language: text("language").or_else(|| Some(DEFAULT_METADATA_LANGUAGE.to_string())),
+            // Bangumi scores subjects nobody rated 0
+            rating: metadata
+                .pointer("/rating/score")
+                .and_then(|v| v.as_f64())
+                .filter(|score| *score > 0.0),
+        }
+    }
+
+    /// Replace scraped values with the ones edited by hand.
+    pub fn apply_edits(&mut self, edits: &MetadataEdits) {
+        if let Some(summary) = &edits.summary {
+            self.summary = Some(summary.clone());
+        }
+        if let Some(language) = &edits.language {
+            self.language = Some(language.clone());
+        }
+        if let Some(authors) = &edits.authors {
+            self.authors = authors.clone();
+        }
+    }
+}
+
+/// Values of an infobox entry, which is either a string or a list of
+/// `{"v": ...}` entries, trimmed and without empty ones.
+fn infobox_values(metadata: &serde_json::Value, key: &str) -> Vec<String> {
+    metadata
+        .get("infobox")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|item| item.get("key").and_then(|k| k.as_str()) == Some(key))
+        .filter_map(|item| item.get("value"))
+        .flat_map(|value| match value {
+            serde_json::Value::Array(entries) => entries
+                .iter()
+                .filter_map(|e| e.get("v").and_then(|v| v.as_str()))
+                .collect::<Vec<_>>(),
+            value => value.as_str().into_iter().collect(),
+        })
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn author(name: &str, role: &str) -> MetadataAuthor {
+        MetadataAuthor {
+            name: name.to_string(),
+            role: role.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_from_metadata() {
+        let fields = MetadataFields::from_metadata(&json!({
+            "summary": " A story ",
+            "date": "2009-09-09",
+            "infobox": [
+                { "key": "作者", "value": "諫山創" },
+                { "key": "作画", "value": [{ "v": "諫山創" }, { "v": " " }] },
+                { "key": "出版社", "value": "講談社" },
+            ],
+            "rating": { "score": 8.4 },
+        }));
+        assert_eq!(
+            fields,
+            MetadataFields {
+                summary: Some("A story".to_string()),
+                authors: vec![author("諫山創", "writer"), author("諫山創", "penciller")],
+                publisher: Some("講談社".to_string()),
+                release_date: Some("2009-09-09".to_string()),
+                language: Some("ja".to_string()),
+                rating: Some(8.4),
+            }
+        );
+
+        let fields = MetadataFields::from_metadata(&json!({
+            "summary": "",
+            "date": 2009,
+            "rating": { "score": 0 },
+        }));
+        assert_eq!(fields.summary, None);
+        assert_eq!(fields.release_date.as_deref(), Some("2009"));
+        assert_eq!(fields.rating, None);
+    }
+
+    #[test]
+    fn test_apply_edits() {
+        let mut fields = MetadataFields::from_metadata(&json!({
+            "summary": "Scraped",
+            "infobox": [{ "key": "作者", "value": "A" }],
+        }));
+        fields.apply_edits(&MetadataEdits {
+            language: Some("en".to_string()),
+            authors: Some(vec![author("B", "artist")]),
+            ..Default::default()
+        });
+        assert_eq!(fields.summary.as_deref(), Some("Scraped"));
+        assert_eq!(fields.language.as_deref(), Some("en"));
+        assert_eq!(fields.authors, [author("B", "artist")]);
+    }
+}
@@ -3,16 +3,72 @@
 //! This module contains all the data structures used throughout the application,
 //! including database models and API request/response types.
 
+mod age_rating;
+mod alias;
+mod apikey;
+mod audit;
+mod backup;
+mod bookmark;
 mod content;
+mod content_status;
+mod dashboard;
+mod duplicate;
+mod export;
+mod filesystem;
+mod job;
+mod kobo;
+mod koreader;
 mod library;
+mod metadata;
+mod metadata_edits;
+mod metadata_fields;
+mod preferences;
 mod progress;
+mod quota;
+mod reader_settings;
+mod release;
+mod review;
 mod scan_queue;
+mod series_group;
+mod server_import;
+mod settings;
+mod setup;
+mod share;
+mod tachiyomi;
+mod tracker;
 mod user;
-mod apikey;
 
+pub use age_rating::*;
+pub use alias::*;
+pub use apikey::*;
+pub use audit::*;
+pub use backup::*;
+pub use bookmark::*;
 pub use content::*;
+pub use content_status::*;
+pub use dashboard::*;
+pub use duplicate::*;
+pub use export::*;
+pub use filesystem::*;
+pub use job::*;
+pub use kobo::*;
+pub use koreader::*;
 pub use library::*;
+pub use metadata::*;
+pub use metadata_edits::*;
+pub use metadata_fields::*;
+pub use preferences::*;
 pub use progress::*;
+pub use quota::*;
+pub use reader_settings::*;
+pub use release::*;
+pub use review::*;
 pub use scan_queue::*;
+pub use series_group::*;
+pub use server_import::*;
+pub use settings::*;
+pub use setup::*;
+pub use share::*;
+pub use tachiyomi::*;
+pub use tracker::*;
 pub use user::*;
-pub use apikey::*;
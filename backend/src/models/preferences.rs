@@ -0,0 +1,128 @@
+//! Per-user preference models.
+
+use serde::{Deserialize, Serialize};
+
+use super::ReadingDirection;
+use crate::utils::patch::nullable;
+
+/// Default number of items per page in lists.
+pub const DEFAULT_ITEMS_PER_PAGE: u32 = 50;
+
+/// Largest number of items per page a user may choose.
+pub const MAX_ITEMS_PER_PAGE: u32 = 200;
+
+/// Color theme of the web interface.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Theme {
+    /// Follow the operating system.
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+/// How pages are scaled to the screen in the reader.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PageFit {
+    /// Whole page visible.
+    #[default]
+    Screen,
+    /// Page as wide as the screen.
+    Width,
+    /// Page as tall as the screen.
+    Height,
+    /// Page at its own size.
+    Original,
+}
+
+/// Interface preferences, stored as one JSON object per user. Fields
+/// missing from the stored object take their defaults, so preferences
+/// added later apply to existing users.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InterfacePreferences {
+    pub theme: Theme,
+    /// Reading direction of contents without reader settings of their own.
+    pub reading_direction: ReadingDirection,
+    pub page_fit: PageFit,
+    /// Number of items per page in lists.
+    pub items_per_page: u32,
+}
+
+impl Default for InterfacePreferences {
+    fn default() -> Self {
+        Self {
+            theme: Theme::default(),
+            reading_direction: ReadingDirection::RightToLeft,
+            page_fit: PageFit::default(),
+            items_per_page: DEFAULT_ITEMS_PER_PAGE,
+        }
+    }
+}
+
+/// Preferences of a user.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserPreferences {
+    /// Preferred locale; the server locale when unset.
+    pub locale: Option<String>,
+    #[serde(flatten)]
+    pub interface: InterfacePreferences,
+}
+
+/// Partial update of a user's preferences. A missing field is left as is;
+/// `null` clears the locale.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdatePreferencesRequest {
+    #[serde(default, deserialize_with = "nullable")]
+    pub locale: Option<Option<String>>,
+    pub theme: Option<Theme>,
+    pub reading_direction: Option<ReadingDirection>,
+    pub page_fit: Option<PageFit>,
+    pub items_per_page: Option<u32>,
+}
+
+impl UpdatePreferencesRequest {
+    /// Apply the update to stored interface preferences.
+    pub fn apply(&self, preferences: InterfacePreferences) -> InterfacePreferences {
+        InterfacePreferences {
+            theme: self.theme.unwrap_or(preferences.theme),
+            reading_direction: self
+                .reading_direction
+                .unwrap_or(preferences.reading_direction),
+            page_fit: self.page_fit.unwrap_or(preferences.page_fit),
+            items_per_page: self.items_per_page.unwrap_or(preferences.items_per_page),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_fields_take_defaults() {
+        let stored: InterfacePreferences = serde_json::from_str(r#"{"theme":"dark"}"#).unwrap();
+        assert_eq!(
+            stored,
+            InterfacePreferences {
+                theme: Theme::Dark,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_preferences_are_flat() {
+        let preferences = UserPreferences {
+            locale: Some("zh-CN".to_string()),
+            interface: InterfacePreferences::default(),
+        };
+        let json = serde_json::to_value(&preferences).unwrap();
+        assert_eq!(json["locale"], "zh-CN");
+        assert_eq!(json["reading_direction"], "right_to_left");
+        assert_eq!(json["items_per_page"], DEFAULT_ITEMS_PER_PAGE);
+    }
+}
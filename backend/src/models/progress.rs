@@ -6,11 +6,17 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+/// Longest EPUB CFI accepted in a [`NovelPosition`].
+pub const MAX_CFI_LEN: usize = 1024;
+
 /// Reading progress for a user on a specific chapter.
 ///
 /// Tracks where a user left off reading a particular chapter.
 /// For comics, position represents the page number.
-/// For novels, position represents the character offset.
+/// For novels, position represents the section (EPUB spine item or `.txt`
+/// virtual chapter), and the `novel_*` fields hold the exact place in the
+/// reflowable text when the reader sends one.
+/// For audiobooks, position is the playback position in seconds.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
 pub struct ReadingProgress {
     /// Unique identifier for the progress record.
@@ -25,6 +31,18 @@ pub struct ReadingProgress {
     pub percentage: f32,
     /// Timestamp when the progress was last updated.
     pub updated_at: DateTime<Utc>,
+    /// Section of the novel position, see [`NovelPosition`].
+    #[sqlx(default)]
+    #[serde(default)]
+    pub novel_section: Option<i32>,
+    /// Character offset of the novel position within its section.
+    #[sqlx(default)]
+    #[serde(default)]
+    pub novel_offset: Option<i64>,
+    /// EPUB CFI of the novel position.
+    #[sqlx(default)]
+    #[serde(default)]
+    pub novel_cfi: Option<String>,
 }
 
 impl ReadingProgress {
@@ -35,9 +53,19 @@ impl ReadingProgress {
             chapter_id,
             position,
             percentage: 0.0,
+            novel: None,
         }
     }
 
+    /// The novel position, if one was stored with the progress.
+    pub fn novel_position(&self) -> Option<NovelPosition> {
+        Some(NovelPosition {
+            section: self.novel_section?,
+            offset: self.novel_offset?,
+            cfi: self.novel_cfi.clone(),
+        })
+    }
+
     /// Validates that the progress values are within acceptable ranges.
     pub fn validate(&self) -> Result<(), &'static str> {
         if self.position < 0 {
@@ -57,6 +85,56 @@ pub struct NewReadingProgress {
     pub chapter_id: i64,
     pub position: i32,
     pub percentage: f32,
+    /// Replaces any stored novel position; `None` clears it.
+    pub novel: Option<NovelPosition>,
+}
+
+/// A place in reflowable text, which a page index cannot describe.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NovelPosition {
+    /// Index of the section: the EPUB spine item or `.txt` virtual chapter
+    /// (0-based).
+    pub section: i32,
+    /// Character offset within the section.
+    pub offset: i64,
+    /// EPUB CFI of the same place, e.g. `epubcfi(/6/4!/4/2/1:120)`, for
+    /// readers that locate text by CFI.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cfi: Option<String>,
+}
+
+impl NovelPosition {
+    /// Validates the section, offset and CFI syntax.
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.section < 0 {
+            return Err("Section cannot be negative");
+        }
+        if self.offset < 0 {
+            return Err("Offset cannot be negative");
+        }
+        if let Some(cfi) = &self.cfi
+            && !is_valid_cfi(cfi)
+        {
+            return Err("Invalid EPUB CFI");
+        }
+        Ok(())
+    }
+}
+
+/// Whether a string looks like an EPUB CFI: `epubcfi(` followed by a path
+/// of `/`-separated steps and a closing parenthesis. Only the outer shape
+/// is checked; the reader that wrote it is the one that resolves it.
+fn is_valid_cfi(cfi: &str) -> bool {
+    let Some(path) = cfi
+        .strip_prefix("epubcfi(")
+        .and_then(|rest| rest.strip_suffix(')'))
+    else {
+        return false;
+    };
+    cfi.len() <= MAX_CFI_LEN
+        && path.starts_with('/')
+        && path[1..].starts_with(|c: char| c.is_ascii_digit())
+        && !path.chars().any(char::is_control)
 }
 
 /// Request to update reading progress for a chapter.
@@ -73,11 +151,15 @@ pub struct ProgressResponse {
     pub position: i32,
     pub percentage: f32,
     pub updated_at: DateTime<Utc>,
+    /// Place in the text for novels, if the reader sent one.
+    #[serde(default)]
+    pub novel: Option<NovelPosition>,
 }
 
 impl From<ReadingProgress> for ProgressResponse {
     fn from(progress: ReadingProgress) -> Self {
         Self {
+            novel: progress.novel_position(),
             chapter_id: progress.chapter_id,
             position: progress.position,
             percentage: progress.percentage,
@@ -85,3 +167,86 @@ impl From<ReadingProgress> for ProgressResponse {
         }
     }
 }
+
+/// Reading position inside a virtual chapter of a split `.txt` chapter.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SplitProgress {
+    pub id: i64,
+    pub user_id: i64,
+    pub chapter_id: i64,
+    /// Index of the virtual chapter (0-based).
+    pub split_index: i32,
+    /// Character offset within the virtual chapter.
+    pub position: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Response for split reading progress API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitProgressResponse {
+    pub split_index: i32,
+    pub position: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<SplitProgress> for SplitProgressResponse {
+    fn from(progress: SplitProgress) -> Self {
+        Self {
+            split_index: progress.split_index,
+            position: progress.position,
+            updated_at: progress.updated_at,
+        }
+    }
+}
+
+/// How many chapters of a content a user has read, started or not opened.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ChapterReadCounts {
+    pub content_id: i64,
+    /// Chapters read to the end.
+    pub read: i64,
+    /// Chapters started but not finished.
+    pub in_progress: i64,
+    /// Chapters never opened.
+    pub unread: i64,
+}
+
+impl ChapterReadCounts {
+    /// Chapters not read to the end, started or not.
+    pub fn unfinished(&self) -> i64 {
+        self.in_progress + self.unread
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(cfi: Option<&str>) -> NovelPosition {
+        NovelPosition {
+            section: 2,
+            offset: 150,
+            cfi: cfi.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_novel_position_validation() {
+        assert!(position(None).validate().is_ok());
+        assert!(
+            position(Some("epubcfi(/6/4!/4/2/1:120)"))
+                .validate()
+                .is_ok()
+        );
+
+        assert!(position(Some("/6/4!/4")).validate().is_err());
+        assert!(position(Some("epubcfi(6/4)")).validate().is_err());
+        assert!(position(Some("epubcfi(/x)")).validate().is_err());
+        let long = format!("epubcfi(/6{})", "/4".repeat(MAX_CFI_LEN));
+        assert!(position(Some(&long)).validate().is_err());
+
+        let mut negative = position(None);
+        negative.offset = -1;
+        assert!(negative.validate().is_err());
+    }
+}
@@ -0,0 +1,54 @@
+//! Per-user quota models.
+//!
+//! Each user may transfer a number of megabytes and make a number of
+//! requests per UTC day. The server defaults apply to users without limits
+//! of their own; administrators are only limited by their own.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// Daily limits in effect for a user, `None` for unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaLimits {
+    pub daily_mb: Option<u64>,
+    pub daily_requests: Option<u64>,
+}
+
+impl QuotaLimits {
+    /// Daily transfer limit in bytes.
+    pub fn daily_bytes(&self) -> Option<u64> {
+        self.daily_mb.map(|mb| mb.saturating_mul(1024 * 1024))
+    }
+}
+
+/// Transfer and requests of a user on one UTC day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaUsage {
+    pub day: NaiveDate,
+    /// Bytes of request and response bodies.
+    pub bytes: u64,
+    pub requests: u64,
+}
+
+/// Limits and usage of a user, returned by the admin quota endpoints.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserQuota {
+    pub user_id: i64,
+    /// Limits set for this user; `None` uses the server default.
+    pub daily_mb: Option<u64>,
+    pub daily_requests: Option<u64>,
+    /// Limits in effect.
+    pub limits: QuotaLimits,
+    /// Usage today.
+    pub usage: QuotaUsage,
+}
+
+/// Request setting the limits of a user. `null` uses the server default
+/// and `0` removes the limit.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateUserQuotaRequest {
+    #[serde(default)]
+    pub daily_mb: Option<u64>,
+    #[serde(default)]
+    pub daily_requests: Option<u64>,
+}
@@ -0,0 +1,254 @@
+//! Reader settings models.
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::patch::nullable;
+
+/// Direction pages are turned in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadingDirection {
+    LeftToRight,
+    RightToLeft,
+    Vertical,
+}
+
+impl ReadingDirection {
+    /// All directions, in the order offered to users.
+    pub const ALL: [ReadingDirection; 3] = [
+        ReadingDirection::LeftToRight,
+        ReadingDirection::RightToLeft,
+        ReadingDirection::Vertical,
+    ];
+
+    /// Identifier stored in the database and used in the API.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReadingDirection::LeftToRight => "left_to_right",
+            ReadingDirection::RightToLeft => "right_to_left",
+            ReadingDirection::Vertical => "vertical",
+        }
+    }
+
+    /// Parse an identifier returned by [`Self::as_str`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|direction| direction.as_str() == name)
+    }
+}
+
+/// How many pages are shown at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PageLayout {
+    /// One page at a time.
+    Single,
+    /// Two facing pages side by side.
+    Double,
+}
+
+impl PageLayout {
+    /// All layouts, in the order offered to users.
+    pub const ALL: [PageLayout; 2] = [PageLayout::Single, PageLayout::Double];
+
+    /// Identifier stored in the database and used in the API.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PageLayout::Single => "single",
+            PageLayout::Double => "double",
+        }
+    }
+
+    /// Parse an identifier returned by [`Self::as_str`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|layout| layout.as_str() == name)
+    }
+}
+
+/// Resolved reader settings of a content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReaderSettings {
+    pub reading_direction: ReadingDirection,
+    pub page_layout: PageLayout,
+    /// Pages are shown as one continuous vertical strip.
+    pub webtoon: bool,
+    /// Uniform white or black page margins are trimmed off on the server.
+    pub trim_margins: bool,
+}
+
+impl Default for ReaderSettings {
+    /// Built-in defaults, used where neither the content nor its library
+    /// sets a value. Right to left, as most libraries hold manga.
+    fn default() -> Self {
+        Self {
+            reading_direction: ReadingDirection::RightToLeft,
+            page_layout: PageLayout::Single,
+            webtoon: false,
+            trim_margins: false,
+        }
+    }
+}
+
+impl ReaderSettings {
+    /// Reading direction as named by Komga clients. Webtoon mode wins over
+    /// the page direction.
+    pub fn komga_reading_direction(&self) -> &'static str {
+        if self.webtoon {
+            return "WEBTOON";
+        }
+        match self.reading_direction {
+            ReadingDirection::LeftToRight => "LEFT_TO_RIGHT",
+            ReadingDirection::RightToLeft => "RIGHT_TO_LEFT",
+            ReadingDirection::Vertical => "VERTICAL",
+        }
+    }
+}
+
+/// Settings stored for a library or a content. `None` inherits from the
+/// level above.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReaderSettingsOverrides {
+    pub reading_direction: Option<ReadingDirection>,
+    pub page_layout: Option<PageLayout>,
+    pub webtoon: Option<bool>,
+    pub trim_margins: Option<bool>,
+}
+
+impl ReaderSettingsOverrides {
+    /// Whether nothing is overridden.
+    pub fn is_empty(&self) -> bool {
+        self.reading_direction.is_none()
+            && self.page_layout.is_none()
+            && self.webtoon.is_none()
+            && self.trim_margins.is_none()
+    }
+
+    /// Apply these overrides on top of inherited settings.
+    pub fn resolve(&self, inherited: ReaderSettings) -> ReaderSettings {
+        ReaderSettings {
+            reading_direction: self
+                .reading_direction
+                .unwrap_or(inherited.reading_direction),
+            page_layout: self.page_layout.unwrap_or(inherited.page_layout),
+            webtoon: self.webtoon.unwrap_or(inherited.webtoon),
+            trim_margins: self.trim_margins.unwrap_or(inherited.trim_margins),
+        }
+    }
+}
+
+/// Partial update of stored reader settings. A missing field is left as is,
+/// `null` goes back to inheriting.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct UpdateReaderSettingsRequest {
+    #[serde(default, deserialize_with = "nullable")]
+    pub reading_direction: Option<Option<ReadingDirection>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub page_layout: Option<Option<PageLayout>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub webtoon: Option<Option<bool>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub trim_margins: Option<Option<bool>>,
+}
+
+impl UpdateReaderSettingsRequest {
+    /// Apply the update to stored overrides.
+    pub fn apply(&self, overrides: ReaderSettingsOverrides) -> ReaderSettingsOverrides {
+        ReaderSettingsOverrides {
+            reading_direction: self
+                .reading_direction
+                .unwrap_or(overrides.reading_direction),
+            page_layout: self.page_layout.unwrap_or(overrides.page_layout),
+            webtoon: self.webtoon.unwrap_or(overrides.webtoon),
+            trim_margins: self.trim_margins.unwrap_or(overrides.trim_margins),
+        }
+    }
+}
+
+/// Reader settings of a library or a content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ReaderSettingsResponse {
+    /// Settings in effect.
+    pub settings: ReaderSettings,
+    /// Values set at this level.
+    pub overrides: ReaderSettingsOverrides,
+    /// Settings that apply where nothing is set at this level: the library
+    /// defaults for a content, the built-in defaults for a library.
+    pub inherited: ReaderSettings,
+}
+
+impl ReaderSettingsResponse {
+    pub fn new(overrides: ReaderSettingsOverrides, inherited: ReaderSettings) -> Self {
+        Self {
+            settings: overrides.resolve(inherited),
+            overrides,
+            inherited,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_round_trip() {
+        for direction in ReadingDirection::ALL {
+            assert_eq!(
+                ReadingDirection::from_name(direction.as_str()),
+                Some(direction)
+            );
+        }
+        for layout in PageLayout::ALL {
+            assert_eq!(PageLayout::from_name(layout.as_str()), Some(layout));
+        }
+        assert_eq!(ReadingDirection::from_name("diagonal"), None);
+    }
+
+    #[test]
+    fn test_overrides_resolve_over_inherited() {
+        let library = ReaderSettingsOverrides {
+            reading_direction: Some(ReadingDirection::LeftToRight),
+            page_layout: Some(PageLayout::Double),
+            webtoon: None,
+            trim_margins: Some(true),
+        };
+        let content = ReaderSettingsOverrides {
+            page_layout: Some(PageLayout::Single),
+            ..Default::default()
+        };
+
+        let settings = content.resolve(library.resolve(ReaderSettings::default()));
+        assert_eq!(settings.reading_direction, ReadingDirection::LeftToRight);
+        assert_eq!(settings.page_layout, PageLayout::Single);
+        assert!(!settings.webtoon);
+        assert!(settings.trim_margins);
+    }
+
+    #[test]
+    fn test_update_distinguishes_missing_and_null() {
+        let stored = ReaderSettingsOverrides {
+            reading_direction: Some(ReadingDirection::Vertical),
+            page_layout: Some(PageLayout::Double),
+            webtoon: None,
+            trim_margins: None,
+        };
+        let request: UpdateReaderSettingsRequest =
+            serde_json::from_str(r#"{"page_layout": null, "webtoon": true}"#).unwrap();
+
+        let updated = request.apply(stored);
+        assert_eq!(updated.reading_direction, Some(ReadingDirection::Vertical));
+        assert_eq!(updated.page_layout, None);
+        assert_eq!(updated.webtoon, Some(true));
+    }
+
+    #[test]
+    fn test_komga_reading_direction() {
+        let mut settings = ReaderSettings::default();
+        assert_eq!(settings.komga_reading_direction(), "RIGHT_TO_LEFT");
+        settings.reading_direction = ReadingDirection::LeftToRight;
+        assert_eq!(settings.komga_reading_direction(), "LEFT_TO_RIGHT");
+        settings.webtoon = true;
+        assert_eq!(settings.komga_reading_direction(), "WEBTOON");
+    }
+}
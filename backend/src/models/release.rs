@@ -0,0 +1,37 @@
+//! New chapter release models.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A chapter a scan found in a content that was already in a library.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ChapterRelease {
+    pub id: i64,
+    pub content_id: i64,
+    pub content_title: String,
+    pub chapter_id: i64,
+    pub chapter_title: String,
+    /// Volume number parsed from the chapter name.
+    pub volume: Option<i32>,
+    /// Chapter number parsed from the chapter name.
+    pub number: Option<f32>,
+    /// When the scan found the chapter.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Query parameters for the new chapters of followed series.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpdatesQuery {
+    /// Releases per page (default 20, max 100).
+    pub limit: Option<i64>,
+    /// Cursor from `next_cursor` of the previous page.
+    pub after: Option<String>,
+}
+
+/// A page of new chapters, newest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdatesPage {
+    pub items: Vec<ChapterRelease>,
+    /// Cursor of the next page, unless this page is the last.
+    pub next_cursor: Option<String>,
+}
@@ -0,0 +1,57 @@
+//! Rating and review models.
+//!
+//! Users rate contents from 1 to 10 and may add a short review. Each user
+//! rates a content at most once; the average of all ratings is shown in
+//! content listings.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Lowest rating a user can give.
+pub const MIN_REVIEW_RATING: i32 = 1;
+
+/// Highest rating a user can give.
+pub const MAX_REVIEW_RATING: i32 = 10;
+
+/// Longest review accepted, in characters.
+pub const MAX_REVIEW_CHARS: usize = 2000;
+
+/// A user's rating of a content, with the name of the user.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ContentReview {
+    pub id: i64,
+    pub content_id: i64,
+    pub user_id: i64,
+    pub username: String,
+    pub rating: i32,
+    /// Text written with the rating, if any.
+    pub review: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request to rate a content, replacing the current user's earlier rating.
+/// A `null` or empty review leaves only the rating.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SaveReviewRequest {
+    pub rating: i32,
+    #[serde(default)]
+    pub review: Option<String>,
+}
+
+/// Average rating of a content.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RatingSummary {
+    /// `None` until someone rates the content.
+    pub average_rating: Option<f64>,
+    pub rating_count: i64,
+}
+
+/// Response for GET /api/contents/{id}/reviews.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContentReviews {
+    #[serde(flatten)]
+    pub summary: RatingSummary,
+    /// All ratings, most recently changed first.
+    pub reviews: Vec<ContentReview>,
+}
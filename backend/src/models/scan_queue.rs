@@ -8,16 +8,22 @@ use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use uuid::Uuid;
 
+use crate::t;
+use crate::utils::locale::{format_datetime, t_plural};
+
 /// Task priority for scan operations.
 ///
 /// Higher priority tasks are processed before lower priority tasks.
-/// Manual scans have High priority, scheduled scans have Normal priority.
+/// Manual scans have High priority, maintenance tasks Normal priority and
+/// scheduled scans Low priority.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TaskPriority {
-    /// Normal priority for scheduled scans.
-    Normal = 0,
+    /// Low priority for scheduled scans.
+    Low = 0,
+    /// Normal priority for maintenance tasks.
+    Normal = 1,
     /// High priority for manual scans.
-    High = 1,
+    High = 2,
 }
 
 impl PartialOrd for TaskPriority {
@@ -47,12 +53,53 @@ pub enum TaskStatus {
     Cancelled,
 }
 
-/// Progress information for a running scan task.
+/// Kind of work a task does.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TaskKind {
+    /// Import new content and update existing content from disk.
+    #[default]
+    Scan,
+    /// Regenerate thumbnails that are missing or were generated with other
+    /// thumbnail settings.
+    Thumbnails,
+    /// Count the pages of chapters that have none, e.g. because their file
+    /// could not be read when it was scanned.
+    PageCounts,
+    /// Fetch the metadata of a library's content again from its providers.
+    MetadataRefresh,
+    /// Write a backup of the database. Not tied to a library.
+    Backup,
+    /// Remove a library hidden for deletion with its contents.
+    LibraryDeletion,
+}
+
+impl TaskKind {
+    /// Prefix of the translation keys of reports for this kind of task.
+    fn report_prefix(self) -> &'static str {
+        match self {
+            TaskKind::Scan => "scan_report",
+            TaskKind::Thumbnails => "thumbnail_report",
+            TaskKind::PageCounts => "page_count_report",
+            TaskKind::MetadataRefresh => "metadata_refresh_report",
+            TaskKind::Backup => "backup_report",
+            TaskKind::LibraryDeletion => "library_deletion_report",
+        }
+    }
+
+    /// Whether tasks of this kind work on a library. Other tasks have a
+    /// `library_id` of 0.
+    pub fn targets_library(self) -> bool {
+        !matches!(self, TaskKind::Backup)
+    }
+}
+
+/// Progress information for a running task.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TaskProgress {
-    /// Number of paths that have been scanned.
+    /// Number of paths that have been scanned, or of contents processed by
+    /// a thumbnail task, or of chapters counted by a page count task.
     pub scanned_paths: i32,
-    /// Total number of paths to scan.
+    /// Total number of paths to scan, or of contents to process.
     pub total_paths: i32,
 }
 
@@ -76,19 +123,226 @@ pub struct AddedChapter {
     pub path: String,
 }
 
-/// Result information for a completed scan task.
+/// A chapter archive with entries left out of the scan because they lead
+/// outside the archive or are symbolic links.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SuspiciousArchive {
+    /// Path to the chapter file.
+    pub path: String,
+    /// Names of the entries left out.
+    pub entries: Vec<String>,
+}
+
+/// Statistics of one scan path in a scan task.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanPathStats {
+    pub scan_path_id: i64,
+    pub path: String,
+    /// Number of content folders found on disk.
+    pub folders_discovered: i32,
+    /// Number of content items added.
+    pub added: i32,
+    /// Number of content items moved to the trash.
+    pub removed: i32,
+    /// Number of folders that could not be imported or rescanned.
+    pub errors: i32,
+    /// Time the scan path took in milliseconds.
+    pub duration_ms: u64,
+    /// Error that stopped the scan of the path, e.g. its folder is not
+    /// available.
+    pub error: Option<String>,
+}
+
+/// A folder a scan could not import or rescan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FolderError {
+    pub scan_path_id: i64,
+    /// Path to the folder.
+    pub path: String,
+    pub error: String,
+}
+
+/// Result information for a completed task.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TaskResult {
     /// Number of content items added during the scan.
     pub added_count: i32,
     /// Number of content items removed during the scan.
     pub removed_count: i32,
+    /// Number of content items relinked to a renamed or moved folder.
+    #[serde(default)]
+    pub moved_count: i32,
     /// Number of items that failed to scrape metadata.
     pub failed_scrape_count: i32,
     /// List of added contents.
     pub added_contents: Vec<AddedContent>,
     /// List of added chapters.
     pub added_chapters: Vec<AddedChapter>,
+    /// Number of thumbnails regenerated by a thumbnail task.
+    #[serde(default)]
+    pub thumbnails_regenerated: i32,
+    /// Number of thumbnails a thumbnail task failed to regenerate.
+    #[serde(default)]
+    pub thumbnails_failed: i32,
+    /// Number of chapters a page count task found pages in.
+    #[serde(default)]
+    pub page_counts_updated: i32,
+    /// Number of chapters a page count task still could not read.
+    #[serde(default)]
+    pub page_counts_failed: i32,
+    /// Chapter archives holding entries that were never read.
+    #[serde(default)]
+    pub suspicious_archives: Vec<SuspiciousArchive>,
+    /// Statistics of each scanned scan path.
+    #[serde(default)]
+    pub scan_paths: Vec<ScanPathStats>,
+    /// Folders that could not be imported or rescanned.
+    #[serde(default)]
+    pub folder_errors: Vec<FolderError>,
+    /// Number of contents a metadata refresh task updated.
+    #[serde(default)]
+    pub metadata_refreshed: i32,
+    /// Number of contents whose metadata a refresh task could not fetch.
+    #[serde(default)]
+    pub metadata_failed: i32,
+    /// File name of the backup written by a backup task.
+    #[serde(default)]
+    pub backup_name: Option<String>,
+    /// Number of chapters removed by a library deletion task, which counts
+    /// its removed contents as removed items.
+    #[serde(default)]
+    pub chapters_removed: i32,
+}
+
+impl TaskResult {
+    /// Describe the changes made by the scan in `locale`.
+    pub fn report(&self, locale: &str) -> String {
+        let parts: Vec<String> = [
+            ("scan_report.contents_added", self.added_count as i64),
+            ("scan_report.contents_removed", self.removed_count as i64),
+            ("scan_report.contents_moved", self.moved_count as i64),
+            (
+                "scan_report.chapters_added",
+                self.added_chapters.len() as i64,
+            ),
+            ("scan_report.scrape_failed", self.failed_scrape_count as i64),
+            (
+                "scan_report.suspicious_archives",
+                self.suspicious_archives.len() as i64,
+            ),
+            (
+                "thumbnail_report.regenerated",
+                self.thumbnails_regenerated as i64,
+            ),
+            ("thumbnail_report.errors", self.thumbnails_failed as i64),
+            ("page_count_report.updated", self.page_counts_updated as i64),
+            ("page_count_report.errors", self.page_counts_failed as i64),
+            (
+                "metadata_refresh_report.refreshed",
+                self.metadata_refreshed as i64,
+            ),
+            (
+                "metadata_refresh_report.errors",
+                self.metadata_failed as i64,
+            ),
+            (
+                "library_deletion_report.chapters_removed",
+                self.chapters_removed as i64,
+            ),
+            ("scan_report.folder_errors", self.folder_errors.len() as i64),
+            (
+                "scan_report.scan_paths_failed",
+                self.failed_scan_paths().count() as i64,
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(key, count)| t_plural(key, locale, count))
+        .chain(
+            self.backup_name
+                .as_ref()
+                .map(|name| t!("backup_report.written", locale = locale, name = name).to_string()),
+        )
+        .collect();
+
+        if parts.is_empty() {
+            t!("scan_report.no_changes", locale = locale).to_string()
+        } else {
+            parts.join(&t!("scan_report.separator", locale = locale))
+        }
+    }
+
+    /// Scan paths whose scan was stopped by an error.
+    pub fn failed_scan_paths(&self) -> impl Iterator<Item = &ScanPathStats> {
+        self.scan_paths.iter().filter(|stats| stats.error.is_some())
+    }
+
+    /// Errors of the failed scan paths, each after its path, or None when
+    /// every scan path was scanned.
+    pub fn scan_path_error(&self) -> Option<String> {
+        let errors: Vec<String> = self
+            .failed_scan_paths()
+            .map(|stats| format!("{}: {}", stats.path, stats.error.as_deref().unwrap_or("")))
+            .collect();
+        (!errors.is_empty()).then(|| errors.join("; "))
+    }
+}
+
+/// Changes a scan of a library would make, computed without writing to the
+/// database.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanPreview {
+    /// Folders that would be imported as new content.
+    pub added: Vec<PreviewContent>,
+    /// Content that would be moved to the trash.
+    pub removed: Vec<PreviewContent>,
+    /// Content that would be relinked to a renamed or moved folder.
+    pub moved: Vec<PreviewMove>,
+    /// Content whose chapters would change, or that would leave the trash.
+    pub updated: Vec<PreviewUpdate>,
+}
+
+/// A content item that would be added or removed by a scan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreviewContent {
+    /// ID of the content, if it is already in the database.
+    pub content_id: Option<i64>,
+    /// Name/Title of the content.
+    pub title: String,
+    /// Path to the content folder.
+    pub path: String,
+    /// Number of chapters.
+    pub chapter_count: i32,
+}
+
+/// A content item that would be relinked to a new folder.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreviewMove {
+    /// ID of the content.
+    pub content_id: i64,
+    /// Title the content would have after the move.
+    pub title: String,
+    /// Current folder of the content.
+    pub from: String,
+    /// Folder the content would be relinked to.
+    pub to: String,
+}
+
+/// A content item whose chapters would change.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreviewUpdate {
+    /// ID of the content.
+    pub content_id: i64,
+    /// Name/Title of the content.
+    pub title: String,
+    /// Path to the content folder.
+    pub path: String,
+    /// Whether the content would be restored from the trash.
+    pub restored: bool,
+    /// Titles of chapters that would be added.
+    pub added_chapters: Vec<String>,
+    /// Titles of chapters that are no longer on disk.
+    pub removed_chapters: Vec<String>,
 }
 
 /// A scan task representing a queued or executed scan operation.
@@ -96,10 +350,20 @@ pub struct TaskResult {
 pub struct ScanTask {
     /// Unique identifier for the task.
     pub id: Uuid,
+    /// Kind of work the task does.
+    #[serde(default)]
+    pub kind: TaskKind,
     /// ID of the library being scanned.
     pub library_id: i64,
+    /// ID of the only scan path to scan, or None to scan the whole library.
+    #[serde(default)]
+    pub scan_path_id: Option<i64>,
     /// Priority of the task.
     pub priority: TaskPriority,
+    /// When an administrator moved the pending task to the front of the
+    /// queue; such tasks run before any other, the latest moved first.
+    #[serde(default)]
+    pub moved_to_front_at: Option<DateTime<Utc>>,
     /// Current status of the task.
     pub status: TaskStatus,
     /// Timestamp when the task was created.
@@ -114,6 +378,9 @@ pub struct ScanTask {
     pub result: Option<TaskResult>,
     /// Error message for failed tasks.
     pub error: Option<String>,
+    /// Summary of a finished task in the requesting user's locale.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
 }
 
 impl ScanTask {
@@ -121,8 +388,11 @@ impl ScanTask {
     pub fn new(library_id: i64, priority: TaskPriority) -> Self {
         Self {
             id: Uuid::new_v4(),
+            kind: TaskKind::Scan,
             library_id,
+            scan_path_id: None,
             priority,
+            moved_to_front_at: None,
             status: TaskStatus::Pending,
             created_at: Utc::now(),
             started_at: None,
@@ -130,18 +400,178 @@ impl ScanTask {
             progress: None,
             result: None,
             error: None,
+            summary: None,
+        }
+    }
+
+    /// Creates a new pending scan task for a single scan path of a library.
+    pub fn for_scan_path(library_id: i64, scan_path_id: i64, priority: TaskPriority) -> Self {
+        Self {
+            scan_path_id: Some(scan_path_id),
+            ..Self::new(library_id, priority)
+        }
+    }
+
+    /// Creates a new pending task regenerating the thumbnails of a library.
+    pub fn thumbnails(library_id: i64, priority: TaskPriority) -> Self {
+        Self {
+            kind: TaskKind::Thumbnails,
+            ..Self::new(library_id, priority)
+        }
+    }
+
+    /// Creates a new pending task counting the pages of a library's chapters
+    /// that have none.
+    pub fn page_counts(library_id: i64, priority: TaskPriority) -> Self {
+        Self {
+            kind: TaskKind::PageCounts,
+            ..Self::new(library_id, priority)
+        }
+    }
+
+    /// Creates a new pending task fetching the metadata of a library's
+    /// content again.
+    pub fn metadata_refresh(library_id: i64, priority: TaskPriority) -> Self {
+        Self {
+            kind: TaskKind::MetadataRefresh,
+            ..Self::new(library_id, priority)
         }
     }
+
+    /// Creates a new pending task removing a library hidden for deletion.
+    pub fn library_deletion(library_id: i64, priority: TaskPriority) -> Self {
+        Self {
+            kind: TaskKind::LibraryDeletion,
+            ..Self::new(library_id, priority)
+        }
+    }
+
+    /// Creates a new pending task writing a backup of the database.
+    pub fn backup(priority: TaskPriority) -> Self {
+        Self {
+            kind: TaskKind::Backup,
+            ..Self::new(0, priority)
+        }
+    }
+
+    /// Human-readable summary of a finished task in `locale`, or None while
+    /// the task is still pending or running.
+    pub fn report(&self, locale: &str) -> Option<String> {
+        let time = format_datetime(locale, self.completed_at.unwrap_or(self.created_at));
+        let prefix = self.kind.report_prefix();
+        match self.status {
+            TaskStatus::Pending | TaskStatus::Running => None,
+            TaskStatus::Completed => {
+                let details = self
+                    .result
+                    .as_ref()
+                    .map(|result| result.report(locale))
+                    .unwrap_or_else(|| t!("scan_report.no_changes", locale = locale).to_string());
+                Some(
+                    t!(
+                        format!("{prefix}.completed").as_str(),
+                        locale = locale,
+                        time = time,
+                        details = details
+                    )
+                    .to_string(),
+                )
+            }
+            TaskStatus::Failed => Some(
+                t!(
+                    format!("{prefix}.failed").as_str(),
+                    locale = locale,
+                    time = time,
+                    error = self.error.as_deref().unwrap_or_default()
+                )
+                .to_string(),
+            ),
+            TaskStatus::Cancelled => Some(
+                t!(
+                    format!("{prefix}.cancelled").as_str(),
+                    locale = locale,
+                    time = time
+                )
+                .to_string(),
+            ),
+        }
+    }
+
+    /// Fill in [`summary`](Self::summary) for `locale`.
+    pub fn with_summary(mut self, locale: &str) -> Self {
+        self.summary = self.report(locale);
+        self
+    }
+}
+
+/// Filters for paging through the task history.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ScanTaskHistoryQuery {
+    /// Only tasks with this status.
+    pub status: Option<TaskStatus>,
+    /// Only tasks finished at or after this time.
+    pub from: Option<DateTime<Utc>>,
+    /// Only tasks finished before this time.
+    pub to: Option<DateTime<Utc>>,
+    /// 1-based page number (default 1).
+    pub page: Option<usize>,
+    /// Tasks per page (default 50, max 200).
+    pub page_size: Option<usize>,
+}
+
+/// A page of the task history, most recently finished first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanTaskHistoryPage {
+    pub items: Vec<ScanTask>,
+    pub total: usize,
+    pub page: usize,
+    pub page_size: usize,
+}
+
+/// Number of tasks in each status, including the history.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskStatusCounts {
+    pub pending: usize,
+    pub running: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub cancelled: usize,
+}
+
+/// A running task with the time it has been running for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunningTask {
+    #[serde(flatten)]
+    pub task: ScanTask,
+    /// Seconds since the task started.
+    pub elapsed_secs: i64,
+}
+
+/// State of the whole scan queue.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanQueueState {
+    /// Whether the background workers are running.
+    pub worker_running: bool,
+    /// Whether the workers were paused: running tasks finish, but no
+    /// pending task is started until the queue is resumed.
+    pub paused: bool,
+    /// Number of tasks that may run at the same time.
+    pub worker_count: usize,
+    pub counts: TaskStatusCounts,
+    /// Running tasks, the earliest started first.
+    pub current: Vec<RunningTask>,
 }
 
 /// A queued task entry for priority queue ordering.
 ///
 /// This struct is used internally by the queue to maintain
-/// proper ordering based on priority and creation time.
+/// proper ordering based on moves to the front, priority and creation time.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct QueuedTask {
     /// Task identifier.
     pub task_id: Uuid,
+    /// When the task was moved to the front of the queue.
+    pub moved_to_front_at: Option<DateTime<Utc>>,
     /// Task priority.
     pub priority: TaskPriority,
     /// Task creation timestamp.
@@ -153,6 +583,7 @@ impl QueuedTask {
     pub fn new(task_id: Uuid, priority: TaskPriority, created_at: DateTime<Utc>) -> Self {
         Self {
             task_id,
+            moved_to_front_at: None,
             priority,
             created_at,
         }
@@ -162,6 +593,7 @@ impl QueuedTask {
     pub fn from_scan_task(task: &ScanTask) -> Self {
         Self {
             task_id: task.id,
+            moved_to_front_at: task.moved_to_front_at,
             priority: task.priority,
             created_at: task.created_at,
         }
@@ -177,10 +609,13 @@ impl PartialOrd for QueuedTask {
 impl Ord for QueuedTask {
     fn cmp(&self, other: &Self) -> Ordering {
         // For BinaryHeap (max-heap), "greater" elements are popped first.
-        // We want: High priority before Normal, and earlier times before later times.
-        // So: High > Normal (natural order), and earlier > later (reversed time order)
-        self.priority
-            .cmp(&other.priority)
+        // We want: tasks moved to the front first, the latest moved first, then
+        // higher priorities, then earlier times before later times.
+        // So: Some > None and later > earlier moves (natural order), High > Normal
+        // > Low (natural order), and earlier > later (reversed time order)
+        self.moved_to_front_at
+            .cmp(&other.moved_to_front_at)
+            .then_with(|| self.priority.cmp(&other.priority))
             .then_with(|| other.created_at.cmp(&self.created_at))
     }
 }
@@ -193,6 +628,7 @@ mod tests {
     #[test]
     fn test_task_priority_ordering() {
         assert!(TaskPriority::High > TaskPriority::Normal);
+        assert!(TaskPriority::Normal > TaskPriority::Low);
     }
 
     #[test]
@@ -224,6 +660,123 @@ mod tests {
         assert!(task.progress.is_none());
         assert!(task.result.is_none());
         assert!(task.error.is_none());
+        assert!(task.scan_path_id.is_none());
+    }
+
+    #[test]
+    fn test_scan_task_for_scan_path() {
+        let task = ScanTask::for_scan_path(1, 7, TaskPriority::Normal);
+        assert_eq!(task.library_id, 1);
+        assert_eq!(task.scan_path_id, Some(7));
+        assert_eq!(task.status, TaskStatus::Pending);
+
+        // Tasks saved before scan paths could be targeted scan the whole library
+        let mut json = serde_json::to_value(&task).unwrap();
+        json.as_object_mut().unwrap().remove("scan_path_id");
+        let task: ScanTask = serde_json::from_value(json).unwrap();
+        assert_eq!(task.scan_path_id, None);
+    }
+
+    #[test]
+    fn test_thumbnail_task_kind() {
+        let task = ScanTask::thumbnails(1, TaskPriority::Normal);
+        assert_eq!(task.kind, TaskKind::Thumbnails);
+        assert_eq!(task.scan_path_id, None);
+
+        // Tasks saved before task kinds existed are scans
+        let mut json = serde_json::to_value(&task).unwrap();
+        json.as_object_mut().unwrap().remove("kind");
+        let task: ScanTask = serde_json::from_value(json).unwrap();
+        assert_eq!(task.kind, TaskKind::Scan);
+    }
+
+    #[test]
+    fn test_page_count_task_report() {
+        let mut task = ScanTask::page_counts(1, TaskPriority::Normal);
+        assert_eq!(task.kind, TaskKind::PageCounts);
+        assert_eq!(task.report("en"), None);
+
+        task.status = TaskStatus::Completed;
+        task.completed_at = Some(Utc::now());
+        task.result = Some(TaskResult {
+            page_counts_updated: 2,
+            page_counts_failed: 1,
+            ..TaskResult::default()
+        });
+        let report = task.report("en").unwrap();
+        assert!(report.starts_with("Page count recalculation completed"));
+        assert!(report.contains("2 chapters counted"));
+        assert!(report.contains("1 chapter still unreadable"));
+    }
+
+    #[test]
+    fn test_maintenance_task_reports() {
+        let mut task = ScanTask::metadata_refresh(1, TaskPriority::Low);
+        assert!(task.kind.targets_library());
+        task.status = TaskStatus::Completed;
+        task.result = Some(TaskResult {
+            metadata_refreshed: 3,
+            ..TaskResult::default()
+        });
+        let report = task.report("en").unwrap();
+        assert!(report.starts_with("Metadata refresh completed"));
+        assert!(report.contains("3 contents refreshed"));
+
+        let mut task = ScanTask::backup(TaskPriority::Normal);
+        assert!(!task.kind.targets_library());
+        assert_eq!(task.library_id, 0);
+        task.status = TaskStatus::Completed;
+        task.result = Some(TaskResult {
+            backup_name: Some("ryuri-20261016-030000.db".to_string()),
+            ..TaskResult::default()
+        });
+        let report = task.report("en").unwrap();
+        assert!(report.starts_with("Database backup completed"));
+        assert!(report.contains("ryuri-20261016-030000.db"));
+    }
+
+    #[test]
+    fn test_scan_path_breakdown() {
+        let result = TaskResult {
+            added_count: 1,
+            scan_paths: vec![
+                ScanPathStats {
+                    scan_path_id: 1,
+                    path: "/comics".to_string(),
+                    folders_discovered: 2,
+                    added: 1,
+                    errors: 1,
+                    ..ScanPathStats::default()
+                },
+                ScanPathStats {
+                    scan_path_id: 2,
+                    path: "/nas/comics".to_string(),
+                    error: Some("Path not found".to_string()),
+                    ..ScanPathStats::default()
+                },
+            ],
+            folder_errors: vec![FolderError {
+                scan_path_id: 1,
+                path: "/comics/Broken".to_string(),
+                error: "No chapters".to_string(),
+            }],
+            ..TaskResult::default()
+        };
+        assert_eq!(
+            result.scan_path_error().as_deref(),
+            Some("/nas/comics: Path not found")
+        );
+        let report = result.report("en");
+        assert!(report.contains("1 folder could not be read"));
+        assert!(report.contains("1 scan path failed"));
+
+        // Results saved before the breakdown existed have none
+        let mut json = serde_json::to_value(&result).unwrap();
+        let object = json.as_object_mut().unwrap();
+        object.remove("scan_paths");
+        object.remove("folder_errors");
+        let result: TaskResult = serde_json::from_value(json).unwrap();
+        assert_eq!(result.scan_path_error(), None);
     }
 
     #[test]
@@ -248,6 +801,24 @@ mod tests {
         assert!(earlier_task > later_task);
     }
 
+    #[test]
+    fn test_queued_task_ordering_moved_to_front() {
+        let now = Utc::now();
+        let high_task = QueuedTask::new(Uuid::new_v4(), TaskPriority::High, now);
+        let moved = QueuedTask {
+            moved_to_front_at: Some(now),
+            ..QueuedTask::new(Uuid::new_v4(), TaskPriority::Low, now)
+        };
+        let moved_later = QueuedTask {
+            moved_to_front_at: Some(now + Duration::seconds(1)),
+            ..QueuedTask::new(Uuid::new_v4(), TaskPriority::Low, now)
+        };
+
+        // Moved tasks run before any priority, the last moved first
+        assert!(moved > high_task);
+        assert!(moved_later > moved);
+    }
+
     #[test]
     fn test_queued_task_from_scan_task() {
         let scan_task = ScanTask::new(1, TaskPriority::High);
@@ -0,0 +1,31 @@
+//! Series group models.
+//!
+//! A series group ties related contents together, such as the seasons,
+//! sequels or spin-offs of one series. A content is in at most one group.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A group of related contents.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SeriesGroup {
+    pub id: i64,
+    pub name: String,
+    /// Number of contents outside the trash in the group.
+    pub content_count: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request to create a series group.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateSeriesGroupRequest {
+    pub name: String,
+}
+
+/// Request to put a content into a series group, or take it out with
+/// `null`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetSeriesGroupRequest {
+    #[serde(default)]
+    pub group_id: Option<i64>,
+}
@@ -0,0 +1,65 @@
+//! Komga and Kavita import models.
+//!
+//! Both servers keep everything in a SQLite database file. Importing one
+//! moves series metadata, the reading progress of every user and the
+//! collections of series onto the matching local contents, so a server can
+//! be replaced without losing what was curated and read on it.
+
+use serde::{Deserialize, Serialize};
+
+/// Server an import reads the database of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportSource {
+    /// Komga's `database.sqlite`.
+    Komga,
+    /// Kavita's `kavita.db`.
+    Kavita,
+}
+
+impl ImportSource {
+    /// All sources.
+    pub const ALL: [ImportSource; 2] = [ImportSource::Komga, ImportSource::Kavita];
+
+    /// Identifier used in the API.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImportSource::Komga => "komga",
+            ImportSource::Kavita => "kavita",
+        }
+    }
+
+    /// Parse an identifier returned by [`Self::as_str`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|source| source.as_str() == name)
+    }
+}
+
+/// Query parameters for importing a server database.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ServerImportQuery {
+    /// Only report what would be imported.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Result of importing a Komga or Kavita database.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerImportResult {
+    pub source: ImportSource,
+    pub dry_run: bool,
+    /// Number of series matched to a content.
+    pub matched_series: usize,
+    /// Names of series without a match, or matching several contents.
+    pub unmatched_series: Vec<String>,
+    /// Number of contents whose metadata was edited.
+    pub metadata_imported: usize,
+    /// Number of progress entries written.
+    pub progress_imported: u64,
+    /// Users of the other server without an account here.
+    pub unknown_users: Vec<String>,
+    /// Number of contents put into the series group of a collection.
+    pub contents_grouped: usize,
+    /// Number of series groups created for collections.
+    pub groups_created: usize,
+}
@@ -0,0 +1,90 @@
+//! Runtime server settings models.
+
+use serde::{Deserialize, Serialize};
+
+use super::MetadataProviderKind;
+use crate::utils::thumbnail::{ThumbnailFormat, ThumbnailOptions};
+
+/// Server settings administrators can change without a restart.
+///
+/// The environment and configuration file only provide the defaults; values
+/// saved through the API are stored in the `settings` table under the name
+/// of each field and take precedence.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeSettings {
+    /// Largest width of generated thumbnails.
+    pub thumbnail_width: u32,
+    /// Largest height of generated thumbnails.
+    pub thumbnail_height: u32,
+    /// Format of generated thumbnails.
+    pub thumbnail_format: ThumbnailFormat,
+    /// Encoding quality of thumbnails from 1 to 100, for JPEG and AVIF.
+    pub thumbnail_quality: u8,
+    /// Number of libraries scanned at the same time. Takes effect after a
+    /// restart.
+    pub scan_concurrency: usize,
+    /// Providers new libraries scrape metadata from, in order.
+    pub metadata_providers: Vec<MetadataProviderKind>,
+    /// Whether the Komga-compatible API under `/komga` is served.
+    pub komga_enabled: bool,
+    /// Whether the Kobo sync API under `/kobo` is served.
+    pub kobo_enabled: bool,
+    /// Whether the KOReader sync API under `/koreader` is served.
+    pub koreader_enabled: bool,
+    /// Whether users can create public share links, and existing ones can
+    /// be opened.
+    pub sharing_enabled: bool,
+    /// Bangumi access token metadata is scraped with. Users can set their
+    /// own for the rescrapes they start.
+    pub bangumi_api_key: Option<String>,
+}
+
+impl RuntimeSettings {
+    /// Thumbnail size, format and quality.
+    pub fn thumbnail_options(&self) -> ThumbnailOptions {
+        ThumbnailOptions {
+            width: self.thumbnail_width,
+            height: self.thumbnail_height,
+            format: self.thumbnail_format,
+            quality: self.thumbnail_quality,
+        }
+    }
+}
+
+/// Request changing runtime settings. Fields left out keep their value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateRuntimeSettingsRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail_width: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail_height: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail_format: Option<ThumbnailFormat>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail_quality: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scan_concurrency: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata_providers: Option<Vec<MetadataProviderKind>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub komga_enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kobo_enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub koreader_enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sharing_enabled: Option<bool>,
+    /// An empty string clears the key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bangumi_api_key: Option<String>,
+}
+
+/// Runtime settings with the defaults they override.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeSettingsResponse {
+    /// Settings in effect.
+    pub settings: RuntimeSettings,
+    /// Values from the environment or configuration file.
+    pub defaults: RuntimeSettings,
+}
@@ -0,0 +1,45 @@
+//! First-run setup models.
+
+use serde::{Deserialize, Serialize};
+
+use super::{Library, UserResponse};
+
+/// Response of the setup status endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetupStatus {
+    /// Whether the first administrator still has to be created. Once false,
+    /// the setup endpoints are locked.
+    pub setup_required: bool,
+    /// Locales the administrator can choose from.
+    pub locales: Vec<String>,
+}
+
+/// Request creating the first administrator.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetupRequest {
+    pub username: String,
+    pub password: String,
+    /// Preferred locale of the administrator, e.g. `zh-CN`.
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// First library to create, if any.
+    #[serde(default)]
+    pub library: Option<SetupLibrary>,
+}
+
+/// First library created during setup.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetupLibrary {
+    pub name: String,
+    /// Folder to scan; when set, a scan starts right away.
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+/// Response of a completed setup, logging the administrator in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetupResponse {
+    pub user: UserResponse,
+    pub token: String,
+    pub library: Option<Library>,
+}
@@ -0,0 +1,82 @@
+//! Share link models.
+//!
+//! A share link gives anyone holding its token read-only access to the
+//! pages and thumbnail of a content, or of only one of its chapters, until
+//! it expires or is revoked.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::Chapter;
+
+/// Lifetime of a share link when none is requested, in hours.
+pub const DEFAULT_SHARE_HOURS: i64 = 72;
+
+/// Longest lifetime of a share link, in hours.
+pub const MAX_SHARE_HOURS: i64 = 24 * 30;
+
+/// A share link, with the names of its creator and content.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ShareLink {
+    pub id: i64,
+    /// Secret part of the link, used in `/api/shared/{token}`.
+    pub token: String,
+    pub user_id: i64,
+    pub username: String,
+    pub content_id: i64,
+    pub content_title: String,
+    /// The only chapter shared, or `None` for every chapter of the content.
+    pub chapter_id: Option<i64>,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ShareLink {
+    /// Whether the link no longer grants access.
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+}
+
+/// Request to share a content or one of its chapters.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CreateShareLinkRequest {
+    /// Share only this chapter of the content.
+    #[serde(default)]
+    pub chapter_id: Option<i64>,
+    /// Hours until the link expires, [`DEFAULT_SHARE_HOURS`] if left out.
+    #[serde(default)]
+    pub expires_in_hours: Option<i64>,
+}
+
+/// What a share link shows to the people it was sent to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedContent {
+    pub title: String,
+    /// Thumbnail version tag, `None` if the content has no thumbnail.
+    pub thumbnail_etag: Option<String>,
+    /// Chapters that can be read through the link.
+    pub chapters: Vec<SharedChapter>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A chapter of a shared content, without the details of where it is
+/// stored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SharedChapter {
+    pub id: i64,
+    pub title: String,
+    pub file_type: String,
+    pub page_count: i32,
+}
+
+impl From<Chapter> for SharedChapter {
+    fn from(chapter: Chapter) -> Self {
+        Self {
+            id: chapter.id,
+            title: chapter.title,
+            file_type: chapter.file_type,
+            page_count: chapter.page_count,
+        }
+    }
+}
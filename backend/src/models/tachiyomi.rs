@@ -0,0 +1,118 @@
+//! Tachiyomi backup import models.
+//!
+//! Tachiyomi and its forks (Mihon and others) write backups as gzipped
+//! protobuf. Only the fields needed to import reading progress are kept:
+//! the title of each entry, its chapters with their read markers, and the
+//! history of when chapters were last read. The JSON form has the same
+//! fields, named as in the protobuf schema.
+
+use serde::{Deserialize, Serialize};
+
+/// A Tachiyomi backup.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TachiyomiBackup {
+    #[serde(default)]
+    pub backup_manga: Vec<TachiyomiManga>,
+}
+
+/// An entry of the library in a backup.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TachiyomiManga {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub chapters: Vec<TachiyomiChapter>,
+    #[serde(default)]
+    pub history: Vec<TachiyomiHistory>,
+}
+
+/// A chapter of an entry in a backup.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TachiyomiChapter {
+    /// Identifies the chapter within its source.
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub read: bool,
+    /// Last page reached (0-based) in a chapter that was not finished.
+    #[serde(default)]
+    pub last_page_read: i64,
+    /// Chapter number, negative when unknown.
+    #[serde(default)]
+    pub chapter_number: f32,
+    /// When the chapter last changed, in seconds since the epoch.
+    #[serde(default)]
+    pub last_modified_at: i64,
+}
+
+impl TachiyomiChapter {
+    /// Whether the chapter was finished or started.
+    pub fn has_progress(&self) -> bool {
+        self.read || self.last_page_read > 0
+    }
+}
+
+/// When a chapter was last read.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TachiyomiHistory {
+    /// URL of the chapter.
+    #[serde(default)]
+    pub url: String,
+    /// In milliseconds since the epoch.
+    #[serde(default)]
+    pub last_read: i64,
+}
+
+/// Query parameters for importing a Tachiyomi backup.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TachiyomiImportQuery {
+    /// Only report what would be imported.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// How an entry of a backup was matched to a content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TachiyomiMatch {
+    /// Its title is the title or an alternative title of one content.
+    Matched,
+    /// No content has its title.
+    Unmatched,
+    /// Several contents have its title, so none is picked.
+    Ambiguous,
+}
+
+/// Import report of an entry of a backup.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TachiyomiImportItem {
+    /// Title of the entry in the backup.
+    pub title: String,
+    #[serde(rename = "match")]
+    pub matched: TachiyomiMatch,
+    pub content_id: Option<i64>,
+    pub content_title: Option<String>,
+    /// Chapters of the entry that were read or started.
+    pub chapters_with_progress: usize,
+    /// Names of those chapters without a matching local chapter.
+    pub unmatched_chapters: Vec<String>,
+    /// Progress entries written, or that would be written in a dry run.
+    pub progress_imported: u64,
+}
+
+/// Result of importing a Tachiyomi backup.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TachiyomiImportResult {
+    pub dry_run: bool,
+    /// Number of entries matched to a content.
+    pub matched: usize,
+    /// Number of progress entries written, or that would be written.
+    pub progress_imported: u64,
+    pub items: Vec<TachiyomiImportItem>,
+}
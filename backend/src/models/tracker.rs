@@ -0,0 +1,197 @@
+//! Progress tracker models.
+//!
+//! Users can link an AniList, MyAnimeList or Bangumi account. The number
+//! of chapters they read of each series matched to an entry there is then
+//! pushed to it by a background job, except for series they opted out of.
+//! A Bangumi collection can also be pulled to favorite the series in it.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Site reading progress is pushed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TrackerKind {
+    #[serde(rename = "anilist")]
+    AniList,
+    #[serde(rename = "myanimelist")]
+    MyAnimeList,
+    #[serde(rename = "bangumi")]
+    Bangumi,
+}
+
+impl TrackerKind {
+    /// All trackers, in the order offered to users.
+    pub const ALL: [TrackerKind; 3] = [
+        TrackerKind::AniList,
+        TrackerKind::MyAnimeList,
+        TrackerKind::Bangumi,
+    ];
+
+    /// Identifier stored in the database and used in the API.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TrackerKind::AniList => "anilist",
+            TrackerKind::MyAnimeList => "myanimelist",
+            TrackerKind::Bangumi => "bangumi",
+        }
+    }
+
+    /// Parse an identifier returned by [`Self::as_str`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == name)
+    }
+}
+
+/// A tracker account linked by a user, with its OAuth tokens.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackerAccount {
+    pub user_id: i64,
+    pub tracker: TrackerKind,
+    /// Name of the account on the tracker.
+    pub remote_username: String,
+    pub access_token: String,
+    /// AniList does not hand out refresh tokens.
+    pub refresh_token: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Progress changed before this time has been synced.
+    pub last_synced_at: Option<DateTime<Utc>>,
+    /// Why the last sync failed, if it did.
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TrackerAccount {
+    /// Whether the access token expires within a minute of `now`.
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= now + Duration::minutes(1))
+    }
+}
+
+/// OAuth tokens issued by a tracker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackerToken {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Claims of the state token carried through a tracker's authorization
+/// page while linking an account.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrackerStateClaims {
+    /// User linking the account.
+    pub user_id: i64,
+    /// Tracker being linked, as in [`TrackerKind::as_str`].
+    pub tracker: String,
+    /// PKCE code verifier, required by MyAnimeList.
+    pub verifier: String,
+}
+
+/// Response for starting to link a tracker account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackerLinkResponse {
+    /// Tracker authorization URL to navigate to.
+    pub authorization_url: String,
+}
+
+/// Sync status of one tracker for the current user.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrackerStatus {
+    pub tracker: TrackerKind,
+    /// Whether the server has OAuth credentials for the tracker.
+    pub configured: bool,
+    pub linked: bool,
+    pub remote_username: Option<String>,
+    pub linked_at: Option<DateTime<Utc>>,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    /// Number of series whose progress was pushed.
+    pub synced_series: i64,
+}
+
+/// Response for GET /api/users/me/trackers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrackerStatusResponse {
+    pub trackers: Vec<TrackerStatus>,
+    /// IDs of the contents kept off all trackers.
+    pub opted_out: Vec<i64>,
+}
+
+/// Result of pulling a user's collection from a tracker.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackerPullResult {
+    pub tracker: TrackerKind,
+    /// Entries in the collection.
+    pub collected: usize,
+    /// Entries matched to a local content.
+    pub matched: usize,
+    /// Matched contents newly marked as favorites.
+    pub favorited: usize,
+    /// Matched contents given the reading status of their entry, because
+    /// the user had not picked one.
+    pub statuses_set: usize,
+}
+
+/// Request to opt a content in or out of tracker sync.
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateTrackingRequest {
+    pub enabled: bool,
+}
+
+/// Whether a content's progress is pushed to the user's trackers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentTracking {
+    pub content_id: i64,
+    pub enabled: bool,
+}
+
+/// Chapters of a content a user read to the end.
+#[derive(Debug, Clone, Copy, PartialEq, sqlx::FromRow)]
+pub struct TrackedReadCount {
+    pub content_id: i64,
+    /// Number of chapters read to the end.
+    pub read: i64,
+    /// Highest chapter number among them, if any is numbered.
+    pub max_number: Option<f64>,
+}
+
+impl TrackedReadCount {
+    /// Chapter count to report to a tracker: the number of the furthest
+    /// chapter read when chapters are numbered, as trackers count from the
+    /// first chapter of the series, else the number of chapters read.
+    pub fn chapters_read(&self) -> i64 {
+        self.max_number
+            .map(|number| (number.floor() as i64).max(0))
+            .unwrap_or(self.read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracker_names() {
+        for kind in TrackerKind::ALL {
+            assert_eq!(TrackerKind::from_name(kind.as_str()), Some(kind));
+            assert_eq!(
+                serde_json::to_value(kind).unwrap(),
+                serde_json::json!(kind.as_str())
+            );
+        }
+        assert_eq!(TrackerKind::from_name("kitsu"), None);
+    }
+
+    #[test]
+    fn test_chapters_read() {
+        let count = |read, max_number| TrackedReadCount {
+            content_id: 1,
+            read,
+            max_number,
+        };
+        assert_eq!(count(3, None).chapters_read(), 3);
+        assert_eq!(count(2, Some(12.5)).chapters_read(), 12);
+        assert_eq!(count(1, Some(0.5)).chapters_read(), 0);
+    }
+}
@@ -18,6 +18,30 @@ pub struct User {
     pub password_hash: String,
     /// Optional Bangumi API key for metadata scraping.
     pub bangumi_api_key: Option<String>,
+    /// Base32-encoded TOTP secret (never serialized to JSON).
+    ///
+    /// Set during enrollment; only enforced once `totp_enabled` is true.
+    #[serde(skip_serializing, default)]
+    pub totp_secret: Option<String>,
+    /// Whether TOTP two-factor authentication is required at login.
+    #[serde(default)]
+    pub totp_enabled: bool,
+    /// Whether the user has administrator rights.
+    #[serde(default)]
+    pub is_admin: bool,
+    /// Preferred locale for generated text; the server locale when unset.
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// Highest age rating of the contents the user may see; unrestricted
+    /// when unset.
+    #[sqlx(default)]
+    #[serde(default)]
+    pub max_age_rating: Option<i32>,
+    /// Bumped on every password change; access tokens carry the version
+    /// they were issued for.
+    #[sqlx(default)]
+    #[serde(skip_serializing, default)]
+    pub token_version: i64,
     /// Timestamp when the user was created.
     pub created_at: DateTime<Utc>,
     /// Timestamp when the user was last updated.
@@ -57,6 +81,61 @@ pub struct JwtClaims {
     pub exp: i64,
     /// Issued at timestamp (Unix epoch seconds).
     pub iat: i64,
+    /// Token version of the user when the token was issued; the token is
+    /// rejected once the password has changed since.
+    #[serde(default)]
+    pub ver: i64,
+}
+
+/// Claims for the short-lived token issued after the password step of a
+/// two-factor login. It cannot be used as a regular access token.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TwoFactorChallengeClaims {
+    /// Subject (user ID).
+    pub sub: i64,
+    /// Token purpose, always `"2fa_challenge"`.
+    pub purpose: String,
+    /// Expiration timestamp (Unix epoch seconds).
+    pub exp: i64,
+    /// Issued at timestamp (Unix epoch seconds).
+    pub iat: i64,
+}
+
+/// A hashed single-use recovery code for two-factor authentication.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RecoveryCode {
+    pub id: i64,
+    pub user_id: i64,
+    pub code_hash: String,
+    pub used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An external identity (OIDC issuer and subject) linked to a local user.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct UserIdentity {
+    pub id: i64,
+    pub user_id: i64,
+    pub issuer: String,
+    pub subject: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Claims for the signed `state` parameter of an OIDC login.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OidcStateClaims {
+    /// Nonce expected in the ID token.
+    pub nonce: String,
+    /// Local user to link the identity to, when started from account settings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link_user_id: Option<i64>,
+}
+
+/// Response for starting an identity link from account settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OidcLinkResponse {
+    /// Provider authorization URL to navigate to.
+    pub authorization_url: String,
 }
 
 /// Request to update user information.
@@ -64,12 +143,31 @@ pub struct JwtClaims {
 pub struct UpdateUserRequest {
     /// New username (optional).
     pub username: Option<String>,
-    /// New password (optional).
-    pub password: Option<String>,
-    /// Old password (required if changing password).
-    pub old_password: Option<String>,
     /// New Bangumi API key (optional).
     pub bangumi_api_key: Option<String>,
+    /// New preferred locale (optional, empty string clears it).
+    pub locale: Option<String>,
+}
+
+/// Request to change the password of the current user.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+/// One-time password reset token issued by an administrator.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasswordResetResponse {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Request to set a new password with a reset token.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub new_password: String,
 }
 
 /// Request for user login.
@@ -93,12 +191,76 @@ pub struct LoginResponse {
     pub token: String,
 }
 
+/// Response when the password was correct but a second factor is required.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwoFactorChallengeResponse {
+    /// Always true; lets clients tell this apart from [`LoginResponse`].
+    pub two_factor_required: bool,
+    /// Token to send back with the TOTP or recovery code.
+    pub challenge_token: String,
+}
+
+/// Result of the password step of a login.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum LoginResult {
+    /// Login completed and a JWT was issued.
+    Success(LoginResponse),
+    /// A TOTP or recovery code is required to finish logging in.
+    TwoFactorRequired(TwoFactorChallengeResponse),
+}
+
+/// Request to finish a two-factor login.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TwoFactorLoginRequest {
+    pub challenge_token: String,
+    /// 6-digit TOTP code or a recovery code.
+    pub code: String,
+}
+
+/// Response for TOTP enrollment.
+#[derive(Debug, Clone, Serialize)]
+pub struct TotpSetupResponse {
+    /// Base32-encoded secret for manual entry.
+    pub secret: String,
+    /// `otpauth://` URI to render as a QR code.
+    pub provisioning_uri: String,
+}
+
+/// Request carrying a TOTP code.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TotpCodeRequest {
+    pub code: String,
+}
+
+/// Request to disable two-factor authentication.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DisableTotpRequest {
+    pub password: String,
+    /// 6-digit TOTP code or a recovery code.
+    pub code: String,
+}
+
+/// Freshly generated recovery codes. Only returned once.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
+
 /// User data for API responses (without sensitive fields).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserResponse {
     pub id: i64,
     pub username: String,
     pub bangumi_api_key: Option<String>,
+    #[serde(default)]
+    pub totp_enabled: bool,
+    #[serde(default)]
+    pub is_admin: bool,
+    #[serde(default)]
+    pub locale: Option<String>,
+    #[serde(default)]
+    pub max_age_rating: Option<i32>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -108,6 +270,10 @@ impl From<User> for UserResponse {
             id: user.id,
             username: user.username,
             bangumi_api_key: user.bangumi_api_key,
+            totp_enabled: user.totp_enabled,
+            is_admin: user.is_admin,
+            locale: user.locale,
+            max_age_rating: user.max_age_rating,
             created_at: user.created_at,
         }
     }
@@ -0,0 +1,1179 @@
+//! OpenAPI description of the HTTP API.
+//!
+//! Every route of [`crate::router`] is listed in [`OPERATIONS`] and served
+//! as an OpenAPI 3.1 document at `GET /api/openapi.json`, so API clients can
+//! be generated from it. A test compares the list with the router, so a
+//! route cannot be added without describing it here.
+
+use serde_json::{Map, Value, json};
+
+use crate::error::{ErrorCode, PROBLEM_JSON};
+
+/// One method of one route.
+#[derive(Debug, Clone, Copy)]
+pub struct Operation {
+    /// Lowercase HTTP method.
+    pub method: &'static str,
+    /// Route path in axum syntax.
+    pub path: &'static str,
+    /// Handler as `module::function` under [`crate::handlers`]. The module
+    /// is the tag of the operation and both make up its operation ID.
+    pub handler: &'static str,
+    pub summary: &'static str,
+    /// Whether the operation works without authentication.
+    pub public: bool,
+}
+
+impl Operation {
+    const fn new(
+        method: &'static str,
+        path: &'static str,
+        handler: &'static str,
+        summary: &'static str,
+    ) -> Self {
+        Self {
+            method,
+            path,
+            handler,
+            summary,
+            public: false,
+        }
+    }
+
+    const fn public(self) -> Self {
+        Self {
+            public: true,
+            ..self
+        }
+    }
+
+    /// Tag of the operation, the handler module.
+    pub fn tag(&self) -> &'static str {
+        self.handler.split("::").next().unwrap_or(self.handler)
+    }
+
+    /// Unique name of the operation in generated clients.
+    pub fn operation_id(&self) -> String {
+        self.handler.replace("::", "_")
+    }
+}
+
+const fn get(path: &'static str, handler: &'static str, summary: &'static str) -> Operation {
+    Operation::new("get", path, handler, summary)
+}
+
+const fn post(path: &'static str, handler: &'static str, summary: &'static str) -> Operation {
+    Operation::new("post", path, handler, summary)
+}
+
+const fn put(path: &'static str, handler: &'static str, summary: &'static str) -> Operation {
+    Operation::new("put", path, handler, summary)
+}
+
+const fn patch(path: &'static str, handler: &'static str, summary: &'static str) -> Operation {
+    Operation::new("patch", path, handler, summary)
+}
+
+const fn delete(path: &'static str, handler: &'static str, summary: &'static str) -> Operation {
+    Operation::new("delete", path, handler, summary)
+}
+
+/// Every API operation, in router order.
+pub const OPERATIONS: &[Operation] = &[
+    post(
+        "/api/auth/login",
+        "auth::login",
+        "Log in with a username and password",
+    )
+    .public(),
+    post(
+        "/api/auth/login/2fa",
+        "auth::login_two_factor",
+        "Complete a two-factor login with the challenge token from login",
+    )
+    .public(),
+    post(
+        "/api/auth/password-reset",
+        "auth::reset_password",
+        "Set a new password with a reset token issued by an administrator",
+    )
+    .public(),
+    get(
+        "/api/auth/oidc/login",
+        "oidc::login",
+        "Redirect to the OIDC provider's authorization page",
+    )
+    .public(),
+    get(
+        "/api/auth/oidc/callback",
+        "oidc::callback",
+        "Finish an OIDC login and redirect to the frontend with a JWT",
+    )
+    .public(),
+    get(
+        "/api/trackers/{tracker}/callback",
+        "tracker::callback",
+        "Finish linking a tracker account and redirect to the frontend",
+    )
+    .public(),
+    get(
+        "/api/capabilities",
+        "capabilities::get_capabilities",
+        "Get the features, formats and login methods of the server",
+    )
+    .public(),
+    get(
+        "/api/openapi.json",
+        "openapi::get_openapi",
+        "Get this OpenAPI document",
+    )
+    .public(),
+    get(
+        "/api/setup/status",
+        "setup::get_status",
+        "Check whether the first administrator still has to be created",
+    )
+    .public(),
+    post(
+        "/api/setup/admin",
+        "setup::create_admin",
+        "Create the first administrator and library on a fresh install",
+    )
+    .public(),
+    get(
+        "/api/shared/{token}",
+        "share::get_shared",
+        "Get a shared content and its readable chapters",
+    )
+    .public(),
+    get(
+        "/api/shared/{token}/thumbnail",
+        "share::get_shared_thumbnail",
+        "Get the thumbnail of a shared content",
+    )
+    .public(),
+    get(
+        "/api/shared/{token}/chapters/{chapter_id}/pages",
+        "share::list_shared_pages",
+        "List the pages of a shared comic chapter",
+    )
+    .public(),
+    get(
+        "/api/shared/{token}/chapters/{chapter_id}/pages/{page}",
+        "share::get_shared_page",
+        "Get a page image of a shared comic chapter",
+    )
+    .public(),
+    // Komga compatibility
+    get(
+        "/komga/api/v1/series",
+        "komga::get_series_list",
+        "List series",
+    ),
+    get(
+        "/komga/api/v1/series/new",
+        "komga::get_series_new",
+        "List series, newest added first",
+    ),
+    get(
+        "/komga/api/v1/series/latest",
+        "komga::get_series_latest",
+        "List series, most recently changed first",
+    ),
+    get(
+        "/komga/api/v1/series/{seriesId}",
+        "komga::get_series",
+        "Get a series",
+    ),
+    patch(
+        "/komga/api/v1/series/{seriesId}/metadata",
+        "komga::patch_series_metadata",
+        "Edit series metadata",
+    ),
+    get(
+        "/komga/api/v1/series/{seriesId}/thumbnail",
+        "komga::get_series_thumbnail",
+        "Get the thumbnail of a series",
+    ),
+    get(
+        "/komga/api/v1/series/{seriesId}/books",
+        "komga::get_books",
+        "List the books of a series",
+    ),
+    get(
+        "/komga/api/v1/books/{bookId}",
+        "komga::get_book",
+        "Get a book",
+    ),
+    get(
+        "/komga/api/v1/books/{bookId}/thumbnail",
+        "komga::get_book_thumbnail",
+        "Get the thumbnail of a book",
+    ),
+    get(
+        "/komga/api/v1/books/{bookId}/file",
+        "komga::get_book_file",
+        "Download a book file",
+    ),
+    get(
+        "/komga/api/v1/books/{bookId}/pages",
+        "komga::get_page_list",
+        "List the pages of a book",
+    ),
+    get(
+        "/komga/api/v1/books/{bookId}/pages/{pageNumber}",
+        "komga::get_page",
+        "Get a page image of a book",
+    ),
+    get(
+        "/komga/api/v1/libraries",
+        "komga::get_libraries",
+        "List libraries",
+    ),
+    // Authenticated
+    get("/api/auth/me", "auth::get_me", "Get the current user"),
+    put("/api/auth/me", "auth::update_me", "Update the current user"),
+    post(
+        "/api/auth/2fa/setup",
+        "auth::setup_totp",
+        "Generate a TOTP secret for two-factor login",
+    ),
+    post(
+        "/api/auth/2fa/enable",
+        "auth::enable_totp",
+        "Enable two-factor login and get recovery codes",
+    ),
+    post(
+        "/api/auth/2fa/disable",
+        "auth::disable_totp",
+        "Disable two-factor login",
+    ),
+    post(
+        "/api/auth/2fa/recovery-codes",
+        "auth::regenerate_recovery_codes",
+        "Replace the two-factor recovery codes",
+    ),
+    post(
+        "/api/auth/oidc/link",
+        "oidc::link",
+        "Get the URL linking an OIDC identity to the current user",
+    ),
+    post(
+        "/api/users/me/password",
+        "auth::change_password",
+        "Change the password of the current user",
+    ),
+    get(
+        "/api/users/me/preferences",
+        "preferences::get_preferences",
+        "Get the preferences of the current user",
+    ),
+    patch(
+        "/api/users/me/preferences",
+        "preferences::update_preferences",
+        "Update the preferences of the current user",
+    ),
+    get(
+        "/api/users/me/updates",
+        "release::list_updates",
+        "List new chapters in the series the current user follows",
+    ),
+    get(
+        "/api/users/me/trackers",
+        "tracker::get_status",
+        "Get the tracker sync status of the current user",
+    ),
+    post(
+        "/api/users/me/trackers/sync",
+        "tracker::sync",
+        "Push the current user's reading progress to linked trackers now",
+    ),
+    delete(
+        "/api/users/me/trackers/{tracker}",
+        "tracker::unlink",
+        "Unlink a tracker account from the current user",
+    ),
+    post(
+        "/api/users/me/trackers/{tracker}/link",
+        "tracker::link",
+        "Get the URL linking a tracker account to the current user",
+    ),
+    post(
+        "/api/users/me/trackers/{tracker}/pull",
+        "tracker::pull",
+        "Favorite the series in the current user's Bangumi collection",
+    ),
+    get(
+        "/api/libraries",
+        "library::list",
+        "List libraries with their statistics",
+    ),
+    post("/api/libraries", "library::create", "Create a library"),
+    get(
+        "/api/libraries/{library_id}",
+        "library::get",
+        "Get a library with its statistics",
+    ),
+    put(
+        "/api/libraries/{library_id}",
+        "library::update",
+        "Update a library",
+    ),
+    delete(
+        "/api/libraries/{library_id}",
+        "library::delete",
+        "Delete a library with its scan paths and contents in the background",
+    ),
+    get(
+        "/api/libraries/{library_id}/paths",
+        "library::list_paths",
+        "List the scan paths of a library",
+    ),
+    post(
+        "/api/libraries/{library_id}/paths",
+        "library::add_path",
+        "Add a scan path to a library",
+    ),
+    delete(
+        "/api/libraries/{library_id}/paths/{path_id}",
+        "library::remove_path",
+        "Remove a scan path from a library",
+    ),
+    get(
+        "/api/libraries/{library_id}/ignore-patterns",
+        "library::list_ignore_patterns",
+        "List the ignore patterns of a library",
+    ),
+    put(
+        "/api/libraries/{library_id}/ignore-patterns",
+        "library::set_ignore_patterns",
+        "Replace the ignore patterns of a library",
+    ),
+    get(
+        "/api/libraries/{library_id}/metadata-providers",
+        "library::list_metadata_providers",
+        "List the metadata providers of a library in order",
+    ),
+    put(
+        "/api/libraries/{library_id}/metadata-providers",
+        "library::set_metadata_providers",
+        "Replace the metadata providers of a library",
+    ),
+    get(
+        "/api/libraries/{library_id}/settings",
+        "reader_settings::get_library_settings",
+        "Get the reader defaults of a library",
+    ),
+    patch(
+        "/api/libraries/{library_id}/settings",
+        "reader_settings::update_library_settings",
+        "Update the reader defaults of a library",
+    ),
+    get(
+        "/api/libraries/{library_id}/contents",
+        "content::list",
+        "List the contents of a library",
+    ),
+    post(
+        "/api/libraries/{library_id}/scan",
+        "scan_queue::submit_scan",
+        "Queue a scan of a library",
+    ),
+    post(
+        "/api/libraries/{library_id}/scan/preview",
+        "scan_queue::preview_scan",
+        "Show what a scan of a library would change",
+    ),
+    post(
+        "/api/libraries/{library_id}/thumbnails/regenerate",
+        "scan_queue::regenerate_thumbnails",
+        "Queue thumbnail regeneration for a library",
+    ),
+    get(
+        "/api/libraries/{library_id}/search",
+        "content::search",
+        "Search the contents of a library by title",
+    ),
+    get(
+        "/api/libraries/{library_id}/export",
+        "library::export",
+        "Export the contents of a library as JSON",
+    ),
+    post(
+        "/api/libraries/{library_id}/import",
+        "library::import",
+        "Import an export into a library",
+    ),
+    get(
+        "/api/scan-tasks",
+        "scan_queue::list_tasks",
+        "List queued, running and recent scan tasks",
+    ),
+    post(
+        "/api/scan-tasks",
+        "scan_queue::submit_scan_path_scan",
+        "Queue a scan of one scan path",
+    ),
+    get(
+        "/api/scan-tasks/history",
+        "scan_queue::list_task_history",
+        "Page through finished scan tasks",
+    ),
+    get(
+        "/api/scan-tasks/{task_id}",
+        "scan_queue::get_task",
+        "Get a scan task",
+    ),
+    delete(
+        "/api/scan-tasks/{task_id}",
+        "scan_queue::cancel_task",
+        "Cancel a scan task",
+    ),
+    post(
+        "/api/scan-tasks/{task_id}/move-to-front",
+        "scan_queue::move_task_to_front",
+        "Run a pending scan task before any other",
+    ),
+    get(
+        "/api/scan-queue/state",
+        "scan_queue::get_queue_state",
+        "Get the state of the scan queue workers and task counts",
+    ),
+    post(
+        "/api/scan-queue/pause",
+        "scan_queue::pause_queue",
+        "Stop starting pending scan tasks",
+    ),
+    post(
+        "/api/scan-queue/resume",
+        "scan_queue::resume_queue",
+        "Start pending scan tasks again",
+    ),
+    post(
+        "/api/scan-queue/clear",
+        "scan_queue::clear_queue",
+        "Cancel all pending scan tasks",
+    ),
+    get("/api/jobs", "jobs::list_jobs", "List background jobs"),
+    get(
+        "/api/jobs/{job_id}",
+        "jobs::get_job",
+        "Get a background job",
+    ),
+    post(
+        "/api/jobs/{job_id}/cancel",
+        "jobs::cancel_job",
+        "Cancel a background job",
+    ),
+    post(
+        "/api/jobs/{job_id}/retry",
+        "jobs::retry_job",
+        "Queue a failed or cancelled job again",
+    ),
+    put(
+        "/api/jobs/{job_id}/priority",
+        "jobs::set_job_priority",
+        "Change the priority of a pending job",
+    ),
+    get(
+        "/api/dashboard",
+        "dashboard::get",
+        "Get everything the home page shows in one response",
+    ),
+    get(
+        "/api/contents/recent",
+        "content::list_recent",
+        "List contents, most recently added first",
+    ),
+    get(
+        "/api/contents/updated",
+        "content::list_updated",
+        "List contents, most recently changed first",
+    ),
+    get(
+        "/api/contents/status",
+        "content::list_statuses",
+        "List the current user's favorites and reading statuses",
+    ),
+    post(
+        "/api/contents/thumbnails",
+        "content::get_thumbnails",
+        "Get small previews of the thumbnails of several contents",
+    ),
+    get(
+        "/api/contents/trash",
+        "content::list_trash",
+        "List contents in the trash",
+    ),
+    delete(
+        "/api/contents/trash",
+        "content::purge_trash",
+        "Delete all contents in the trash",
+    ),
+    get(
+        "/api/contents/{content_id}",
+        "content::get",
+        "Get a content",
+    ),
+    put(
+        "/api/contents/{content_id}",
+        "content::update",
+        "Update a content",
+    ),
+    delete(
+        "/api/contents/{content_id}",
+        "content::delete",
+        "Delete a content with its chapters",
+    ),
+    post(
+        "/api/contents/{content_id}/restore",
+        "content::restore",
+        "Take a content out of the trash",
+    ),
+    post(
+        "/api/contents/{content_id}/move",
+        "content::move_content",
+        "Move a content to another library or scan path",
+    ),
+    put(
+        "/api/contents/{content_id}/series-group",
+        "series_group::set_content_group",
+        "Put a content into a series group or take it out",
+    ),
+    get(
+        "/api/contents/{content_id}/status",
+        "content::get_status",
+        "Get the current user's favorite flag and reading status of a content",
+    ),
+    put(
+        "/api/contents/{content_id}/status",
+        "content::update_status",
+        "Replace the current user's favorite flag and reading status of a content",
+    ),
+    get(
+        "/api/contents/{content_id}/settings",
+        "reader_settings::get_content_settings",
+        "Get the reader settings of a content",
+    ),
+    patch(
+        "/api/contents/{content_id}/settings",
+        "reader_settings::update_content_settings",
+        "Update the reader settings of a content",
+    ),
+    get(
+        "/api/contents/{content_id}/metadata",
+        "metadata::get_edits",
+        "Get the manual metadata edits of a content",
+    ),
+    patch(
+        "/api/contents/{content_id}/metadata",
+        "metadata::update_edits",
+        "Edit the metadata of a content by hand",
+    ),
+    post(
+        "/api/contents/{content_id}/metadata/match",
+        "metadata::match_content",
+        "Replace the metadata of a content with a chosen series",
+    ),
+    get(
+        "/api/contents/{content_id}/aliases",
+        "metadata::list_aliases",
+        "List the alternative titles of a content",
+    ),
+    post(
+        "/api/contents/{content_id}/aliases",
+        "metadata::add_alias",
+        "Add an alternative title to a content",
+    ),
+    delete(
+        "/api/contents/{content_id}/aliases/{alias_id}",
+        "metadata::delete_alias",
+        "Remove an alternative title from a content",
+    ),
+    get(
+        "/api/contents/{content_id}/thumbnail",
+        "content::get_thumbnail",
+        "Get the thumbnail of a content",
+    ),
+    get(
+        "/api/contents/{content_id}/chapters",
+        "content::list_chapters",
+        "List the chapters of a content",
+    ),
+    get(
+        "/api/contents/{content_id}/progress",
+        "progress::get_content_progress",
+        "Get the reading progress of every chapter of a content",
+    ),
+    get(
+        "/api/contents/{content_id}/tracking",
+        "tracker::get_tracking",
+        "Get whether progress on a content is pushed to trackers",
+    ),
+    put(
+        "/api/contents/{content_id}/tracking",
+        "tracker::update_tracking",
+        "Opt a content in or out of tracker sync",
+    ),
+    get(
+        "/api/contents/{content_id}/reviews",
+        "review::list_reviews",
+        "List the ratings and reviews of a content",
+    ),
+    get(
+        "/api/contents/{content_id}/reviews/me",
+        "review::get_my_review",
+        "Get the current user's rating of a content",
+    ),
+    put(
+        "/api/contents/{content_id}/reviews/me",
+        "review::save_my_review",
+        "Rate and review a content",
+    ),
+    delete(
+        "/api/contents/{content_id}/reviews/me",
+        "review::delete_my_review",
+        "Remove the current user's rating of a content",
+    ),
+    post(
+        "/api/contents/{content_id}/shares",
+        "share::create_share",
+        "Create a public share link to a content or chapter",
+    ),
+    get(
+        "/api/contents/{content_id}/chapters/{chapter_id}/pages",
+        "content::list_pages",
+        "List the pages of a comic chapter",
+    ),
+    get(
+        "/api/contents/{content_id}/chapters/{chapter_id}/pages/{page}",
+        "content::get_page",
+        "Get a page image of a comic chapter",
+    ),
+    get(
+        "/api/contents/{content_id}/search",
+        "content::search_text",
+        "Search the text of a novel's chapters",
+    ),
+    get(
+        "/api/contents/{content_id}/chapters/{chapter_id}/text",
+        "content::get_chapter_text",
+        "Get the text of a novel chapter",
+    ),
+    get(
+        "/api/contents/{content_id}/chapters/{chapter_id}/epub",
+        "content::get_epub_manifest",
+        "Get the reading order and table of contents of an EPUB chapter",
+    ),
+    get(
+        "/api/contents/{content_id}/chapters/{chapter_id}/epub/sections/{index}",
+        "content::get_epub_section",
+        "Get a section of an EPUB chapter as sanitized HTML",
+    ),
+    get(
+        "/api/contents/{content_id}/chapters/{chapter_id}/epub/resources/{*path}",
+        "content::get_epub_resource",
+        "Get a file from the manifest of an EPUB chapter",
+    ),
+    get(
+        "/api/contents/{content_id}/chapters/{chapter_id}/splits",
+        "content::list_txt_splits",
+        "List the virtual chapters of a .txt chapter",
+    ),
+    get(
+        "/api/contents/{content_id}/chapters/{chapter_id}/splits/{index}",
+        "content::get_txt_split",
+        "Get the text of a virtual chapter of a .txt chapter",
+    ),
+    get(
+        "/api/contents/{content_id}/chapters/{chapter_id}/audio",
+        "content::get_audio_info",
+        "Get the duration, markers and title of an audiobook chapter",
+    ),
+    get(
+        "/api/contents/{content_id}/chapters/{chapter_id}/audio/stream",
+        "content::stream_audio",
+        "Stream an audiobook chapter",
+    ),
+    get(
+        "/api/contents/{content_id}/chapters/{chapter_id}/page-errors",
+        "content::list_page_errors",
+        "List the pages of a chapter that failed to load",
+    ),
+    delete(
+        "/api/contents/{content_id}/chapters/{chapter_id}/page-errors",
+        "content::clear_page_errors",
+        "Clear the page failures of a chapter",
+    ),
+    get(
+        "/api/contents/{content_id}/download",
+        "content::download_series",
+        "Download all chapters of a content as a ZIP",
+    ),
+    get(
+        "/api/contents/{content_id}/chapters/{chapter_id}/download",
+        "content::download_chapter",
+        "Download a chapter file",
+    ),
+    get(
+        "/api/metadata/search",
+        "metadata::search",
+        "Search metadata providers by title",
+    ),
+    get(
+        "/api/metadata/failures",
+        "metadata::list_failures",
+        "List contents whose metadata could not be scraped",
+    ),
+    delete(
+        "/api/metadata/failures/{failure_id}",
+        "metadata::dismiss_failure",
+        "Dismiss a metadata failure",
+    ),
+    post(
+        "/api/metadata/failures/{failure_id}/retry",
+        "metadata::retry_failure",
+        "Scrape the metadata of a failed content again",
+    ),
+    post(
+        "/api/metadata/bangumi/check-key",
+        "metadata::check_bangumi_key",
+        "Check a Bangumi access token",
+    ),
+    get(
+        "/api/progress/recent",
+        "progress::get_recent_progress",
+        "List the most recently read contents",
+    ),
+    get(
+        "/api/chapters/{chapter_id}/progress",
+        "progress::get_chapter_progress",
+        "Get the reading progress of every chapter of the content of a chapter",
+    ),
+    put(
+        "/api/chapters/{chapter_id}/progress",
+        "progress::update_chapter_progress",
+        "Update the reading progress of a chapter",
+    ),
+    get(
+        "/api/chapters/{chapter_id}/splits/progress",
+        "progress::get_split_progress",
+        "Get the reading positions in the virtual chapters of a .txt chapter",
+    ),
+    put(
+        "/api/chapters/{chapter_id}/splits/{index}/progress",
+        "progress::update_split_progress",
+        "Update the reading position in a virtual chapter of a .txt chapter",
+    ),
+    post(
+        "/api/import/tachiyomi",
+        "import::import_tachiyomi",
+        "Import reading progress from a Tachiyomi backup",
+    ),
+    post(
+        "/api/import/{source}",
+        "import::import_server",
+        "Import the database of a Komga or Kavita server",
+    ),
+    get(
+        "/api/series-groups",
+        "series_group::list",
+        "List series groups",
+    ),
+    post(
+        "/api/series-groups",
+        "series_group::create",
+        "Create a series group",
+    ),
+    get(
+        "/api/series-groups/{group_id}",
+        "series_group::get",
+        "Get a series group",
+    ),
+    delete(
+        "/api/series-groups/{group_id}",
+        "series_group::delete",
+        "Delete a series group",
+    ),
+    get(
+        "/api/series-groups/{group_id}/contents",
+        "series_group::list_contents",
+        "List the contents of a series group",
+    ),
+    get(
+        "/api/bookmarks",
+        "bookmark::list_bookmarks",
+        "List the current user's bookmarks",
+    ),
+    put(
+        "/api/bookmarks/{bookmark_id}",
+        "bookmark::update_bookmark",
+        "Replace the note of a bookmark",
+    ),
+    delete(
+        "/api/bookmarks/{bookmark_id}",
+        "bookmark::delete_bookmark",
+        "Delete a bookmark",
+    ),
+    post(
+        "/api/chapters/{chapter_id}/bookmarks",
+        "bookmark::create_bookmark",
+        "Bookmark a position in a chapter",
+    ),
+    get(
+        "/api/contents/{content_id}/bookmarks",
+        "bookmark::list_content_bookmarks",
+        "List the current user's bookmarks in a content",
+    ),
+    get(
+        "/api/api-keys",
+        "apikey::list_api_keys",
+        "List the current user's API keys",
+    ),
+    post(
+        "/api/api-keys",
+        "apikey::create_api_key",
+        "Create an API key",
+    ),
+    delete(
+        "/api/api-keys/{id}",
+        "apikey::delete_api_key",
+        "Delete an API key",
+    ),
+    get(
+        "/api/shares",
+        "share::list_my_shares",
+        "List the current user's share links",
+    ),
+    delete(
+        "/api/shares/{id}",
+        "share::revoke_share",
+        "Revoke a share link",
+    ),
+    get(
+        "/api/filesystem",
+        "filesystem::list_directories",
+        "List the entries of a server directory",
+    ),
+    get(
+        "/api/ext/v1",
+        "ext::get_info",
+        "Get the extension API version and the values its filters accept",
+    ),
+    get(
+        "/api/ext/v1/series",
+        "ext::list_series",
+        "List series for the Tachiyomi extension",
+    ),
+    get(
+        "/api/ext/v1/series/{id}",
+        "ext::get_series",
+        "Get a series for the Tachiyomi extension",
+    ),
+    get(
+        "/api/ext/v1/series/{id}/chapters",
+        "ext::list_chapters",
+        "List the chapters of a series with the current user's read markers",
+    ),
+    get(
+        "/api/ext/v1/chapters/{id}/pages",
+        "ext::list_pages",
+        "List the page URLs of a comic chapter",
+    ),
+    get(
+        "/api/admin/audit",
+        "admin::list_audit_log",
+        "List audit log entries",
+    ),
+    get(
+        "/api/admin/backups",
+        "admin::list_backups",
+        "List database backups",
+    ),
+    post(
+        "/api/admin/backups",
+        "admin::create_backup",
+        "Back up the database",
+    ),
+    post(
+        "/api/admin/backups/restore",
+        "admin::restore_backup",
+        "Restore the database from a backup",
+    ),
+    get(
+        "/api/admin/backups/{name}",
+        "admin::download_backup",
+        "Download a database backup",
+    ),
+    get(
+        "/api/admin/settings",
+        "admin::get_settings",
+        "Get the runtime server settings",
+    ),
+    patch(
+        "/api/admin/settings",
+        "admin::update_settings",
+        "Change runtime server settings",
+    ),
+    get(
+        "/api/admin/shares",
+        "admin::list_share_links",
+        "List every user's share links",
+    ),
+    delete(
+        "/api/admin/shares/{id}",
+        "admin::revoke_share_link",
+        "Revoke any share link",
+    ),
+    put(
+        "/api/admin/users/{id}/age-rating",
+        "admin::set_user_age_rating",
+        "Cap the age rating a user may see",
+    ),
+    post(
+        "/api/admin/users/{id}/password-reset",
+        "admin::create_password_reset",
+        "Issue a one-time password reset token for a user",
+    ),
+    get(
+        "/api/admin/users/{id}/quota",
+        "admin::get_user_quota",
+        "Get the daily quota and usage of a user",
+    ),
+    put(
+        "/api/admin/users/{id}/quota",
+        "admin::set_user_quota",
+        "Set the daily quota of a user",
+    ),
+    get(
+        "/api/maintenance/duplicates",
+        "maintenance::list_duplicates",
+        "List groups of duplicate contents",
+    ),
+    post(
+        "/api/maintenance/duplicates/merge",
+        "maintenance::merge_duplicates",
+        "Merge duplicate contents",
+    ),
+    get(
+        "/api/maintenance/media-errors",
+        "maintenance::list_media_errors",
+        "List chapters whose files cannot be read",
+    ),
+    post(
+        "/api/maintenance/page-counts",
+        "maintenance::recalculate_page_counts",
+        "Count the pages of chapters that have none",
+    ),
+    post(
+        "/api/maintenance/metadata-refresh",
+        "maintenance::refresh_metadata",
+        "Fetch the metadata of content again",
+    ),
+    post(
+        "/api/maintenance/backup",
+        "maintenance::submit_backup",
+        "Back up the database in the background",
+    ),
+];
+
+/// The GraphQL endpoint, only routed with the `graphql` feature.
+pub const GRAPHQL_OPERATION: Operation = post(
+    "/api/graphql",
+    "graphql::execute",
+    "Execute a GraphQL query",
+);
+
+/// Operations routed in this build.
+pub fn operations() -> impl Iterator<Item = &'static Operation> {
+    let graphql = cfg!(feature = "graphql").then_some(&GRAPHQL_OPERATION);
+    OPERATIONS.iter().chain(graphql)
+}
+
+/// Build the OpenAPI document of the routes in this build.
+pub fn document() -> Value {
+    let mut paths = Map::new();
+    for operation in operations() {
+        let mut item = json!({
+            "operationId": operation.operation_id(),
+            "summary": operation.summary,
+            "tags": [operation.tag()],
+            "responses": {
+                "default": {
+                    "description": "Error",
+                    "content": {
+                        PROBLEM_JSON: {
+                            "schema": { "$ref": "#/components/schemas/Error" }
+                        }
+                    }
+                }
+            }
+        });
+        let parameters: Vec<Value> = path_parameters(operation.path)
+            .map(|name| {
+                json!({
+                    "name": name,
+                    "in": "path",
+                    "required": true,
+                    "schema": parameter_schema(name),
+                })
+            })
+            .collect();
+        if !parameters.is_empty() {
+            item["parameters"] = Value::Array(parameters);
+        }
+        if operation.public {
+            item["security"] = json!([]);
+        }
+
+        let path = paths
+            .entry(openapi_path(operation.path))
+            .or_insert_with(|| json!({}));
+        path[operation.method] = item;
+    }
+
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "Ryuri",
+            "version": env!("RYURI_VERSION"),
+        },
+        "paths": paths,
+        "components": {
+            "securitySchemes": {
+                "bearer": { "type": "http", "scheme": "bearer", "bearerFormat": "JWT" },
+                "apiKey": { "type": "apiKey", "in": "header", "name": "X-API-Key" },
+            },
+            "schemas": {
+                "Error": {
+                    "type": "object",
+                    "required": ["type", "title", "status", "detail", "code", "error"],
+                    "properties": {
+                        "type": { "type": "string" },
+                        "title": { "type": "string" },
+                        "status": { "type": "integer" },
+                        "detail": { "type": "string" },
+                        "code": {
+                            "type": "string",
+                            "enum": ErrorCode::ALL.map(ErrorCode::as_str),
+                        },
+                        "details": { "type": "object" },
+                        "request_id": { "type": "string" },
+                        "error": {
+                            "type": "object",
+                            "required": ["code", "message"],
+                            "properties": {
+                                "code": { "type": "integer" },
+                                "message": { "type": "string" },
+                            },
+                        },
+                    },
+                },
+            },
+        },
+        "security": [{ "bearer": [] }, { "apiKey": [] }],
+    })
+}
+
+/// Names of the parameters in an axum route path.
+fn path_parameters(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter_map(|segment| {
+        let name = segment.strip_prefix('{')?.strip_suffix('}')?;
+        Some(name.trim_start_matches('*'))
+    })
+}
+
+/// Convert an axum route path to OpenAPI syntax, which has no wildcards.
+fn openapi_path(path: &str) -> String {
+    path.replace("{*", "{")
+}
+
+/// Schema of a path parameter. IDs are integers, except those of tasks
+/// and jobs, which are UUIDs.
+fn parameter_schema(name: &str) -> Value {
+    match name {
+        "task_id" | "job_id" => json!({ "type": "string", "format": "uuid" }),
+        "name" | "path" | "source" | "token" | "tracker" => json!({ "type": "string" }),
+        _ => json!({ "type": "integer", "format": "int64" }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use regex::Regex;
+
+    use super::*;
+
+    /// `(method, path, handler)` of every API route in the router source.
+    fn routed() -> HashSet<(String, String, String)> {
+        let source = include_str!("router.rs");
+        let source = &source[..source.find("pub fn create_router_with_layers").unwrap()];
+        let path = Regex::new(r#"^"([^"]+)""#).unwrap();
+        let method = Regex::new(r"\b(get|post|put|patch|delete)\(([\w:]+)\)").unwrap();
+
+        let mut routes = HashSet::new();
+        for segment in source.split(".route(").skip(1) {
+            let segment = segment.trim_start();
+            let path = &path.captures(segment).unwrap()[1];
+            if !path.starts_with("/api/") && !path.starts_with("/komga/") {
+                continue;
+            }
+            for captures in method.captures_iter(segment) {
+                let handler = captures[2].trim_start_matches("crate::handlers::");
+                routes.insert((
+                    captures[1].to_string(),
+                    path.to_string(),
+                    handler.to_string(),
+                ));
+            }
+        }
+        routes
+    }
+
+    #[test]
+    fn test_every_route_is_described() {
+        let described: HashSet<_> = OPERATIONS
+            .iter()
+            .chain([&GRAPHQL_OPERATION])
+            .map(|op| {
+                (
+                    op.method.to_string(),
+                    op.path.to_string(),
+                    op.handler.to_string(),
+                )
+            })
+            .collect();
+        let routed = routed();
+
+        let missing: Vec<_> = routed.difference(&described).collect();
+        assert!(
+            missing.is_empty(),
+            "routes missing from the spec: {missing:?}"
+        );
+        let stale: Vec<_> = described.difference(&routed).collect();
+        assert!(stale.is_empty(), "spec operations not routed: {stale:?}");
+    }
+
+    #[test]
+    fn test_document() {
+        let document = document();
+        let paths = document["paths"].as_object().unwrap();
+        for operation in operations() {
+            let item = &paths[&openapi_path(operation.path)][operation.method];
+            assert_eq!(item["summary"], operation.summary);
+        }
+
+        let ids: Vec<_> = operations().map(Operation::operation_id).collect();
+        let unique: HashSet<_> = ids.iter().collect();
+        assert_eq!(ids.len(), unique.len());
+
+        let page = &paths["/api/contents/{content_id}/chapters/{chapter_id}/pages/{page}"]["get"];
+        assert_eq!(page["parameters"].as_array().unwrap().len(), 3);
+        let resource =
+            &paths["/api/contents/{content_id}/chapters/{chapter_id}/epub/resources/{path}"];
+        assert_eq!(resource["get"]["parameters"][2]["schema"]["type"], "string");
+        assert_eq!(paths["/api/auth/login"]["post"]["security"], json!([]));
+    }
+}
@@ -0,0 +1,138 @@
+//! Alternative title repository for database operations.
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use sqlx::{Pool, Sqlite, SqliteConnection};
+
+use crate::error::{AppError, Result};
+use crate::models::{AliasSource, ContentAlias};
+
+/// Repository for alternative title database operations.
+pub struct AliasRepository;
+
+impl AliasRepository {
+    /// List the alternative titles of a content.
+    pub async fn list_by_content(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+    ) -> Result<Vec<ContentAlias>> {
+        sqlx::query_as::<_, ContentAlias>(
+            r#"
+            SELECT id, content_id, title, source, created_at
+            FROM content_aliases
+            WHERE content_id = ?
+            ORDER BY id
+            "#,
+        )
+        .bind(content_id)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// List the alternative titles of several contents, by content ID.
+    pub async fn list_by_contents(
+        pool: &Pool<Sqlite>,
+        content_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<ContentAlias>>> {
+        let aliases = sqlx::query_as::<_, ContentAlias>(
+            r#"
+            SELECT id, content_id, title, source, created_at
+            FROM content_aliases
+            WHERE content_id IN (SELECT value FROM json_each(?))
+            ORDER BY id
+            "#,
+        )
+        .bind(serde_json::to_string(content_ids).unwrap_or_default())
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        let mut by_content: HashMap<i64, Vec<ContentAlias>> = HashMap::new();
+        for alias in aliases {
+            by_content.entry(alias.content_id).or_default().push(alias);
+        }
+        Ok(by_content)
+    }
+
+    /// Add a title by hand. A title already scraped from metadata becomes a
+    /// manual one, so new metadata no longer removes it.
+    pub async fn add_manual(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        title: &str,
+    ) -> Result<ContentAlias> {
+        sqlx::query(
+            r#"
+            INSERT INTO content_aliases (content_id, title, source, created_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(content_id, title) DO UPDATE SET source = excluded.source
+            "#,
+        )
+        .bind(content_id)
+        .bind(title)
+        .bind(AliasSource::Manual.as_str())
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        sqlx::query_as::<_, ContentAlias>(
+            r#"
+            SELECT id, content_id, title, source, created_at
+            FROM content_aliases
+            WHERE content_id = ? AND title = ?
+            "#,
+        )
+        .bind(content_id)
+        .bind(title)
+        .fetch_one(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Delete an alternative title of a content. Returns whether it existed.
+    pub async fn delete(pool: &Pool<Sqlite>, content_id: i64, id: i64) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM content_aliases WHERE id = ? AND content_id = ?")
+            .bind(id)
+            .bind(content_id)
+            .execute(pool)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Replace the titles scraped from metadata for a content. Titles added
+    /// by hand are kept.
+    pub async fn replace_from_metadata(
+        conn: &mut SqliteConnection,
+        content_id: i64,
+        titles: &[String],
+    ) -> Result<()> {
+        sqlx::query("DELETE FROM content_aliases WHERE content_id = ? AND source = ?")
+            .bind(content_id)
+            .bind(AliasSource::Metadata.as_str())
+            .execute(&mut *conn)
+            .await
+            .map_err(AppError::Database)?;
+
+        let now = Utc::now().to_rfc3339();
+        for title in titles {
+            sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO content_aliases (content_id, title, source, created_at)
+                VALUES (?, ?, ?, ?)
+                "#,
+            )
+            .bind(content_id)
+            .bind(title)
+            .bind(AliasSource::Metadata.as_str())
+            .bind(&now)
+            .execute(&mut *conn)
+            .await
+            .map_err(AppError::Database)?;
+        }
+        Ok(())
+    }
+}
@@ -4,10 +4,10 @@
 
 use chrono::Utc;
 use sqlx::{Pool, Sqlite};
-use rust_i18n::t;
 
 use crate::error::{AppError, Result};
 use crate::models::{ApiKey, NewApiKey};
+use crate::t;
 
 /// Repository for API key database operations.
 pub struct ApiKeyRepository;
@@ -0,0 +1,101 @@
+//! Audit log repository for database operations.
+
+use chrono::Utc;
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, Result};
+use crate::models::{AuditLogEntry, AuditLogQuery, NewAuditLogEntry};
+
+/// Repository for audit log database operations.
+pub struct AuditLogRepository;
+
+impl AuditLogRepository {
+    /// Insert a new audit log entry.
+    pub async fn insert(pool: &Pool<Sqlite>, entry: &NewAuditLogEntry) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO audit_log
+                (user_id, username, action, target_type, target_id, ip, details, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(entry.user_id)
+        .bind(&entry.username)
+        .bind(entry.action.as_str())
+        .bind(&entry.target_type)
+        .bind(&entry.target_id)
+        .bind(&entry.ip)
+        .bind(&entry.details)
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+
+    /// List entries matching the filters, newest first.
+    pub async fn list(
+        pool: &Pool<Sqlite>,
+        query: &AuditLogQuery,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<AuditLogEntry>> {
+        let (from, to) = Self::range(query);
+
+        sqlx::query_as::<_, AuditLogEntry>(
+            r#"
+            SELECT id, user_id, username, action, target_type, target_id, ip, details, created_at
+            FROM audit_log
+            WHERE (?1 IS NULL OR user_id = ?1)
+              AND (?2 IS NULL OR action = ?2)
+              AND (?3 IS NULL OR created_at >= ?3)
+              AND (?4 IS NULL OR created_at < ?4)
+            ORDER BY created_at DESC, id DESC
+            LIMIT ?5 OFFSET ?6
+            "#,
+        )
+        .bind(query.user_id)
+        .bind(&query.action)
+        .bind(from)
+        .bind(to)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Count entries matching the filters.
+    pub async fn count(pool: &Pool<Sqlite>, query: &AuditLogQuery) -> Result<i64> {
+        let (from, to) = Self::range(query);
+
+        let (count,): (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*)
+            FROM audit_log
+            WHERE (?1 IS NULL OR user_id = ?1)
+              AND (?2 IS NULL OR action = ?2)
+              AND (?3 IS NULL OR created_at >= ?3)
+              AND (?4 IS NULL OR created_at < ?4)
+            "#,
+        )
+        .bind(query.user_id)
+        .bind(&query.action)
+        .bind(from)
+        .bind(to)
+        .fetch_one(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(count)
+    }
+
+    /// Date range bounds formatted like the stored timestamps.
+    fn range(query: &AuditLogQuery) -> (Option<String>, Option<String>) {
+        (
+            query.from.map(|t| t.to_rfc3339()),
+            query.to.map(|t| t.to_rfc3339()),
+        )
+    }
+}
@@ -0,0 +1,65 @@
+//! Bangumi response cache repository.
+//!
+//! Search results and subjects are stored as JSON in `bangumi_cache`, one
+//! row per query or subject ID and whether an access token was sent.
+
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, Result};
+
+/// Repository for the Bangumi response cache.
+pub struct BangumiCacheRepository;
+
+impl BangumiCacheRepository {
+    /// Find a cached response with the time it was fetched.
+    pub async fn find(
+        pool: &Pool<Sqlite>,
+        kind: &str,
+        key: &str,
+        authorized: bool,
+    ) -> Result<Option<(String, DateTime<Utc>)>> {
+        sqlx::query_as(
+            r#"
+            SELECT response, fetched_at
+            FROM bangumi_cache
+            WHERE kind = ? AND cache_key = ? AND authorized = ?
+            "#,
+        )
+        .bind(kind)
+        .bind(key)
+        .bind(authorized)
+        .fetch_optional(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Store a response fetched now, replacing the cached one.
+    pub async fn save(
+        pool: &Pool<Sqlite>,
+        kind: &str,
+        key: &str,
+        authorized: bool,
+        response: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO bangumi_cache (kind, cache_key, authorized, response, fetched_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(kind, cache_key, authorized) DO UPDATE SET
+                response = excluded.response,
+                fetched_at = excluded.fetched_at
+            "#,
+        )
+        .bind(kind)
+        .bind(key)
+        .bind(authorized)
+        .bind(response)
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+}
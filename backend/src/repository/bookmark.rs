@@ -0,0 +1,142 @@
+//! Bookmark repository for database operations.
+
+use chrono::Utc;
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, Result};
+use crate::models::{Bookmark, NewBookmark};
+
+/// Repository for bookmark database operations.
+pub struct BookmarkRepository;
+
+impl BookmarkRepository {
+    /// Create a new bookmark.
+    pub async fn create(pool: &Pool<Sqlite>, bookmark: NewBookmark) -> Result<Bookmark> {
+        let now = Utc::now().to_rfc3339();
+        let novel = bookmark.novel.as_ref();
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO bookmarks (
+                user_id, chapter_id, position, novel_section, novel_offset, novel_cfi,
+                note, created_at, updated_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(bookmark.user_id)
+        .bind(bookmark.chapter_id)
+        .bind(bookmark.position)
+        .bind(novel.map(|n| n.section))
+        .bind(novel.map(|n| n.offset))
+        .bind(novel.and_then(|n| n.cfi.as_deref()))
+        .bind(&bookmark.note)
+        .bind(&now)
+        .bind(&now)
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Self::find(pool, bookmark.user_id, result.last_insert_rowid())
+            .await?
+            .ok_or_else(|| AppError::Internal("Failed to retrieve created bookmark".to_string()))
+    }
+
+    /// Find a bookmark of a user by ID.
+    pub async fn find(pool: &Pool<Sqlite>, user_id: i64, id: i64) -> Result<Option<Bookmark>> {
+        sqlx::query_as::<_, Bookmark>(
+            r#"
+            SELECT b.id, b.user_id, ch.content_id, b.chapter_id, b.position,
+                b.novel_section, b.novel_offset, b.novel_cfi, b.note, b.created_at, b.updated_at
+            FROM bookmarks b
+            JOIN chapters ch ON ch.id = b.chapter_id
+            WHERE b.id = ? AND b.user_id = ?
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// List a user's bookmarks, newest first, optionally within one content.
+    pub async fn list_by_user(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        content_id: Option<i64>,
+    ) -> Result<Vec<Bookmark>> {
+        sqlx::query_as::<_, Bookmark>(
+            r#"
+            SELECT b.id, b.user_id, ch.content_id, b.chapter_id, b.position,
+                b.novel_section, b.novel_offset, b.novel_cfi, b.note, b.created_at, b.updated_at
+            FROM bookmarks b
+            JOIN chapters ch ON ch.id = b.chapter_id
+            WHERE b.user_id = ?1 AND (?2 IS NULL OR ch.content_id = ?2)
+            ORDER BY b.created_at DESC, b.id DESC
+            "#,
+        )
+        .bind(user_id)
+        .bind(content_id)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// List a user's bookmarks in a content in reading order.
+    pub async fn list_by_content(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        content_id: i64,
+    ) -> Result<Vec<Bookmark>> {
+        sqlx::query_as::<_, Bookmark>(
+            r#"
+            SELECT b.id, b.user_id, ch.content_id, b.chapter_id, b.position,
+                b.novel_section, b.novel_offset, b.novel_cfi, b.note, b.created_at, b.updated_at
+            FROM bookmarks b
+            JOIN chapters ch ON ch.id = b.chapter_id
+            WHERE b.user_id = ? AND ch.content_id = ?
+            ORDER BY ch.sort_order, b.position, b.novel_section, b.novel_offset, b.id
+            "#,
+        )
+        .bind(user_id)
+        .bind(content_id)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Replace the note of a user's bookmark. Returns false if the bookmark
+    /// does not exist.
+    pub async fn update_note(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        id: i64,
+        note: Option<&str>,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE bookmarks SET note = ?, updated_at = ? WHERE id = ? AND user_id = ?",
+        )
+        .bind(note)
+        .bind(Utc::now().to_rfc3339())
+        .bind(id)
+        .bind(user_id)
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Delete a user's bookmark. Returns false if it did not exist.
+    pub async fn delete(pool: &Pool<Sqlite>, user_id: i64, id: i64) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM bookmarks WHERE id = ? AND user_id = ?")
+            .bind(id)
+            .bind(user_id)
+            .execute(pool)
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,110 @@
+//! Consistency check repository.
+//!
+//! Queries that find and repair rows that disagree with each other, for
+//! example after the server was killed in the middle of a scan.
+
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, Result};
+
+/// Repository for consistency checks.
+pub struct ConsistencyRepository;
+
+impl ConsistencyRepository {
+    /// IDs of contents whose `chapter_count` differs from their chapter rows.
+    pub async fn find_chapter_count_mismatches(pool: &Pool<Sqlite>) -> Result<Vec<i64>> {
+        let rows: Vec<(i64,)> = sqlx::query_as(
+            r#"
+            SELECT id FROM contents
+            WHERE chapter_count != (SELECT COUNT(*) FROM chapters WHERE content_id = contents.id)
+            ORDER BY id
+            "#,
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(rows.into_iter().map(|(id,)| id).collect())
+    }
+
+    /// Set `chapter_count` of every content from its chapter rows.
+    pub async fn fix_chapter_counts(pool: &Pool<Sqlite>) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE contents
+            SET chapter_count = (SELECT COUNT(*) FROM chapters WHERE content_id = contents.id)
+            WHERE chapter_count != (SELECT COUNT(*) FROM chapters WHERE content_id = contents.id)
+            "#,
+        )
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// IDs of contents whose scan path no longer exists.
+    pub async fn find_contents_without_scan_path(pool: &Pool<Sqlite>) -> Result<Vec<i64>> {
+        let rows: Vec<(i64,)> = sqlx::query_as(
+            r#"
+            SELECT c.id FROM contents c
+            LEFT JOIN scan_paths sp ON sp.id = c.scan_path_id
+            WHERE sp.id IS NULL
+            ORDER BY c.id
+            "#,
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(rows.into_iter().map(|(id,)| id).collect())
+    }
+
+    /// IDs of contents filed under a different library than their scan path.
+    pub async fn find_library_mismatches(pool: &Pool<Sqlite>) -> Result<Vec<i64>> {
+        let rows: Vec<(i64,)> = sqlx::query_as(
+            r#"
+            SELECT c.id FROM contents c
+            JOIN scan_paths sp ON sp.id = c.scan_path_id
+            WHERE c.library_id != sp.library_id
+            ORDER BY c.id
+            "#,
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(rows.into_iter().map(|(id,)| id).collect())
+    }
+
+    /// Move contents to the library of their scan path.
+    pub async fn fix_library_mismatches(pool: &Pool<Sqlite>) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE contents
+            SET library_id = (SELECT library_id FROM scan_paths WHERE id = contents.scan_path_id)
+            WHERE library_id != (SELECT library_id FROM scan_paths WHERE id = contents.scan_path_id)
+            "#,
+        )
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Delete chapters whose content no longer exists.
+    pub async fn delete_orphaned_chapters(pool: &Pool<Sqlite>) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM chapters
+            WHERE NOT EXISTS (SELECT 1 FROM contents WHERE id = chapters.content_id)
+            "#,
+        )
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected())
+    }
+}
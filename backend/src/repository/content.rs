@@ -2,12 +2,20 @@
 //!
 //! This module provides database access for content and chapter operations.
 
-use chrono::Utc;
-use rust_i18n::t;
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
 use sqlx::{Pool, Sqlite};
 
 use crate::error::{AppError, Result};
-use crate::models::{Chapter, Content, NewChapter, NewContent};
+use crate::models::{
+    Chapter, ChapterStatus, Content, ContentFeed, ContentFilter, ContentSort, MediaError,
+    NewChapter, NewContent, PageError, metadata_age_rating, metadata_aliases, thumbnail_etag,
+};
+use crate::repository::alias::AliasRepository;
+use crate::repository::metadata_fields::MetadataFieldsRepository;
+use crate::t;
+use crate::utils::blurhash;
 
 /// Repository for content database operations.
 pub struct ContentRepository;
@@ -19,17 +27,22 @@ impl ContentRepository {
 
         let result = sqlx::query(
             r#"
-            INSERT INTO contents (library_id, scan_path_id, title, folder_path, chapter_count, thumbnail, metadata, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO contents (library_id, scan_path_id, title, folder_title, folder_path, chapter_count, thumbnail, thumbnail_etag, thumbnail_blurhash, thumbnail_spec, metadata, age_rating, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(new_content.library_id)
         .bind(new_content.scan_path_id)
         .bind(&new_content.title)
+        .bind(&new_content.folder_title)
         .bind(&new_content.folder_path)
         .bind(new_content.chapter_count)
         .bind(&new_content.thumbnail)
+        .bind(new_content.thumbnail.as_deref().map(thumbnail_etag))
+        .bind(new_content.thumbnail.as_deref().and_then(blurhash::from_image_data))
+        .bind(&new_content.thumbnail_spec)
         .bind(new_content.metadata.as_ref().and_then(|m| serde_json::to_vec(m).ok()))
+        .bind(age_rating_of(new_content.metadata.as_ref()))
         .bind(&now)
         .bind(&now)
         .execute(pool)
@@ -38,6 +51,11 @@ impl ContentRepository {
         match result {
             Ok(res) => {
                 let id = res.last_insert_rowid();
+                let aliases = aliases_of(new_content.metadata.as_ref());
+                let mut conn = pool.acquire().await.map_err(AppError::Database)?;
+                AliasRepository::replace_from_metadata(&mut conn, id, &aliases).await?;
+                MetadataFieldsRepository::refresh(&mut conn, id).await?;
+                drop(conn);
                 Self::find_by_id(pool, id).await?.ok_or_else(|| {
                     AppError::Internal("Failed to retrieve created content".to_string())
                 })
@@ -58,11 +76,93 @@ impl ContentRepository {
         }
     }
 
+    /// Create contents together with their chapters in one transaction.
+    ///
+    /// The `content_id` of each chapter is replaced by the ID of the content
+    /// it is paired with. Contents are returned in input order.
+    pub async fn create_batch_with_chapters(
+        pool: &Pool<Sqlite>,
+        items: Vec<(NewContent, Vec<NewChapter>)>,
+    ) -> Result<Vec<Content>> {
+        let now = Utc::now().to_rfc3339();
+        let mut tx = pool.begin().await.map_err(AppError::Database)?;
+        let mut ids = Vec::with_capacity(items.len());
+
+        for (new_content, chapters) in items {
+            let content_id = sqlx::query(
+                r#"
+                INSERT INTO contents (library_id, scan_path_id, title, folder_title, folder_path, chapter_count, thumbnail, thumbnail_etag, thumbnail_blurhash, thumbnail_spec, metadata, age_rating, created_at, updated_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(new_content.library_id)
+            .bind(new_content.scan_path_id)
+            .bind(&new_content.title)
+            .bind(&new_content.folder_title)
+            .bind(&new_content.folder_path)
+            .bind(new_content.chapter_count)
+            .bind(&new_content.thumbnail)
+            .bind(new_content.thumbnail.as_deref().map(thumbnail_etag))
+            .bind(new_content.thumbnail.as_deref().and_then(blurhash::from_image_data))
+            .bind(&new_content.thumbnail_spec)
+            .bind(new_content.metadata.as_ref().and_then(|m| serde_json::to_vec(m).ok()))
+            .bind(age_rating_of(new_content.metadata.as_ref()))
+            .bind(&now)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?
+            .last_insert_rowid();
+
+            let aliases = aliases_of(new_content.metadata.as_ref());
+            AliasRepository::replace_from_metadata(&mut tx, content_id, &aliases).await?;
+            MetadataFieldsRepository::refresh(&mut tx, content_id).await?;
+
+            for chapter in chapters {
+                sqlx::query(
+                    r#"
+                    INSERT INTO chapters (content_id, title, file_path, file_type, sort_order, page_count, size, volume, number, status, error, created_at, updated_at)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    "#,
+                )
+                .bind(content_id)
+                .bind(&chapter.title)
+                .bind(&chapter.file_path)
+                .bind(&chapter.file_type)
+                .bind(chapter.sort_order)
+                .bind(chapter.page_count)
+                .bind(chapter.size)
+                .bind(chapter.volume)
+                .bind(chapter.number)
+                .bind(chapter.status.as_str())
+                .bind(&chapter.error)
+                .bind(&now)
+                .bind(&now)
+                .execute(&mut *tx)
+                .await
+                .map_err(AppError::Database)?;
+            }
+
+            ids.push(content_id);
+        }
+
+        tx.commit().await.map_err(AppError::Database)?;
+
+        let mut created = Vec::with_capacity(ids.len());
+        for id in ids {
+            let content = Self::find_by_id(pool, id).await?.ok_or_else(|| {
+                AppError::Internal("Failed to retrieve created content".to_string())
+            })?;
+            created.push(content);
+        }
+        Ok(created)
+    }
+
     /// Find a content by ID.
     pub async fn find_by_id(pool: &Pool<Sqlite>, id: i64) -> Result<Option<Content>> {
         sqlx::query_as::<_, Content>(
             r#"
-            SELECT id, library_id, scan_path_id, title, folder_path, chapter_count, thumbnail, metadata, created_at, updated_at
+            SELECT id, library_id, scan_path_id, title, folder_title, folder_path, chapter_count, thumbnail, thumbnail_etag, thumbnail_blurhash, metadata, missing_since, series_group_id, age_rating, created_at, updated_at
             FROM contents
             WHERE id = ?
             "#,
@@ -81,7 +181,7 @@ impl ContentRepository {
     ) -> Result<Option<Content>> {
         sqlx::query_as::<_, Content>(
             r#"
-            SELECT id, library_id, scan_path_id, title, folder_path, chapter_count, thumbnail, metadata, created_at, updated_at
+            SELECT id, library_id, scan_path_id, title, folder_title, folder_path, chapter_count, thumbnail, thumbnail_etag, thumbnail_blurhash, metadata, missing_since, series_group_id, age_rating, created_at, updated_at
             FROM contents
             WHERE library_id = ? AND folder_path = ?
             "#,
@@ -97,9 +197,9 @@ impl ContentRepository {
     pub async fn list_by_library(pool: &Pool<Sqlite>, library_id: i64) -> Result<Vec<Content>> {
         sqlx::query_as::<_, Content>(
             r#"
-            SELECT id, library_id, scan_path_id, title, folder_path, chapter_count, thumbnail, metadata, created_at, updated_at
+            SELECT id, library_id, scan_path_id, title, folder_title, folder_path, chapter_count, thumbnail, thumbnail_etag, thumbnail_blurhash, metadata, missing_since, series_group_id, age_rating, created_at, updated_at
             FROM contents
-            WHERE library_id = ?
+            WHERE library_id = ? AND missing_since IS NULL AND merged_into IS NULL
             ORDER BY title
             "#,
         )
@@ -109,13 +209,61 @@ impl ContentRepository {
         .map_err(AppError::Database)
     }
 
+    /// List up to `limit` contents of a library by title and ID, starting
+    /// after the `(title, id)` of `after` if given.
+    pub async fn list_by_library_after(
+        pool: &Pool<Sqlite>,
+        library_id: i64,
+        after: Option<(&str, i64)>,
+        limit: i64,
+    ) -> Result<Vec<Content>> {
+        let (after_title, after_id) = after.unzip();
+        sqlx::query_as::<_, Content>(
+            r#"
+            SELECT id, library_id, scan_path_id, title, folder_title, folder_path, chapter_count, thumbnail, thumbnail_etag, thumbnail_blurhash, metadata, missing_since, series_group_id, age_rating, created_at, updated_at
+            FROM contents
+            WHERE library_id = ?1 AND missing_since IS NULL AND merged_into IS NULL
+                AND (?2 IS NULL OR (title, id) > (?2, ?3))
+            ORDER BY title, id
+            LIMIT ?4
+            "#,
+        )
+        .bind(library_id)
+        .bind(after_title)
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// List all contents of several libraries, ordered by title.
+    pub async fn list_by_libraries(
+        pool: &Pool<Sqlite>,
+        library_ids: &[i64],
+    ) -> Result<Vec<Content>> {
+        sqlx::query_as::<_, Content>(
+            r#"
+            SELECT id, library_id, scan_path_id, title, folder_title, folder_path, chapter_count, thumbnail, thumbnail_etag, thumbnail_blurhash, metadata, missing_since, series_group_id, age_rating, created_at, updated_at
+            FROM contents
+            WHERE library_id IN (SELECT value FROM json_each(?))
+                AND missing_since IS NULL AND merged_into IS NULL
+            ORDER BY title
+            "#,
+        )
+        .bind(serde_json::to_string(library_ids).unwrap_or_default())
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
     /// List all contents for a scan path.
     pub async fn list_by_scan_path(pool: &Pool<Sqlite>, scan_path_id: i64) -> Result<Vec<Content>> {
         sqlx::query_as::<_, Content>(
             r#"
-            SELECT id, library_id, scan_path_id, title, folder_path, chapter_count, thumbnail, metadata, created_at, updated_at
+            SELECT id, library_id, scan_path_id, title, folder_title, folder_path, chapter_count, thumbnail, thumbnail_etag, thumbnail_blurhash, metadata, missing_since, series_group_id, age_rating, created_at, updated_at
             FROM contents
-            WHERE scan_path_id = ?
+            WHERE scan_path_id = ? AND missing_since IS NULL AND merged_into IS NULL
             ORDER BY title
             "#,
         )
@@ -125,7 +273,7 @@ impl ContentRepository {
         .map_err(AppError::Database)
     }
 
-    /// Search contents by title within a library.
+    /// Search contents by title or alternative title within a library.
     pub async fn search_by_title(
         pool: &Pool<Sqlite>,
         library_id: i64,
@@ -134,9 +282,13 @@ impl ContentRepository {
         let search_pattern = format!("%{}%", query);
         sqlx::query_as::<_, Content>(
             r#"
-            SELECT id, library_id, scan_path_id, title, folder_path, chapter_count, thumbnail, metadata, created_at, updated_at
+            SELECT id, library_id, scan_path_id, title, folder_title, folder_path, chapter_count, thumbnail, thumbnail_etag, thumbnail_blurhash, metadata, missing_since, series_group_id, age_rating, created_at, updated_at
             FROM contents
-            WHERE library_id = ? AND title LIKE ?
+            WHERE library_id = ?1 AND missing_since IS NULL AND merged_into IS NULL
+                AND (title LIKE ?2 OR EXISTS (
+                    SELECT 1 FROM content_aliases a
+                    WHERE a.content_id = contents.id AND a.title LIKE ?2
+                ))
             ORDER BY title
             "#,
         )
@@ -147,6 +299,185 @@ impl ContentRepository {
         .map_err(AppError::Database)
     }
 
+    /// List a page of content outside the trash, newest first by the feed's
+    /// timestamp, optionally within one library and up to an age rating.
+    pub async fn list_feed(
+        pool: &Pool<Sqlite>,
+        feed: ContentFeed,
+        library_id: Option<i64>,
+        max_age_rating: Option<i32>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Content>> {
+        // Both columns are indexed, so the newest rows are read in order
+        let order = match feed {
+            ContentFeed::Added => "created_at DESC, id DESC",
+            ContentFeed::Updated => "updated_at DESC, id DESC",
+        };
+        let sql = format!(
+            r#"
+            SELECT id, library_id, scan_path_id, title, folder_title, folder_path, chapter_count, thumbnail, thumbnail_etag, thumbnail_blurhash, metadata, missing_since, series_group_id, age_rating, created_at, updated_at
+            FROM contents
+            WHERE (?1 IS NULL OR library_id = ?1) AND missing_since IS NULL AND merged_into IS NULL
+                AND (?4 IS NULL OR age_rating IS NULL OR age_rating <= ?4)
+            ORDER BY {}
+            LIMIT ?2 OFFSET ?3
+            "#,
+            order
+        );
+
+        sqlx::query_as::<_, Content>(&sql)
+            .bind(library_id)
+            .bind(limit)
+            .bind(offset)
+            .bind(max_age_rating)
+            .fetch_all(pool)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// List content outside the trash after the one with sort key `key` and
+    /// ID `after_id`, newest first by the feed's timestamp, optionally within
+    /// one library and up to an age rating. The key is the raw timestamp, see
+    /// [`Self::feed_sort_key`].
+    pub async fn list_feed_after(
+        pool: &Pool<Sqlite>,
+        feed: ContentFeed,
+        library_id: Option<i64>,
+        max_age_rating: Option<i32>,
+        key: &str,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<Content>> {
+        let column = match feed {
+            ContentFeed::Added => "created_at",
+            ContentFeed::Updated => "updated_at",
+        };
+        let sql = format!(
+            r#"
+            SELECT id, library_id, scan_path_id, title, folder_title, folder_path, chapter_count, thumbnail, thumbnail_etag, thumbnail_blurhash, metadata, missing_since, series_group_id, age_rating, created_at, updated_at
+            FROM contents
+            WHERE (?1 IS NULL OR library_id = ?1) AND missing_since IS NULL AND merged_into IS NULL
+                AND (?5 IS NULL OR age_rating IS NULL OR age_rating <= ?5)
+                AND ({column}, id) < (?2, ?3)
+            ORDER BY {column} DESC, id DESC
+            LIMIT ?4
+            "#
+        );
+
+        sqlx::query_as::<_, Content>(&sql)
+            .bind(library_id)
+            .bind(key)
+            .bind(after_id)
+            .bind(limit)
+            .bind(max_age_rating)
+            .fetch_all(pool)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// The timestamp a feed orders a content by, as stored, for a cursor.
+    pub async fn feed_sort_key(
+        pool: &Pool<Sqlite>,
+        feed: ContentFeed,
+        id: i64,
+    ) -> Result<Option<String>> {
+        let sql = match feed {
+            ContentFeed::Added => "SELECT created_at FROM contents WHERE id = ?",
+            ContentFeed::Updated => "SELECT updated_at FROM contents WHERE id = ?",
+        };
+        let key: Option<(String,)> = sqlx::query_as(sql)
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(key.map(|(key,)| key))
+    }
+
+    /// Count content outside the trash, optionally within one library and
+    /// up to an age rating.
+    pub async fn count_visible(
+        pool: &Pool<Sqlite>,
+        library_id: Option<i64>,
+        max_age_rating: Option<i32>,
+    ) -> Result<i64> {
+        let (count,): (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*)
+            FROM contents
+            WHERE (?1 IS NULL OR library_id = ?1) AND missing_since IS NULL AND merged_into IS NULL
+                AND (?2 IS NULL OR age_rating IS NULL OR age_rating <= ?2)
+            "#,
+        )
+        .bind(library_id)
+        .bind(max_age_rating)
+        .fetch_one(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(count)
+    }
+
+    /// List a page of content outside the trash that matches a filter, in
+    /// the given order.
+    pub async fn list_matching(
+        pool: &Pool<Sqlite>,
+        filter: &ContentFilter<'_>,
+        sort: ContentSort,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Content>> {
+        let order = match sort {
+            ContentSort::Title => "title COLLATE NOCASE, id",
+            ContentSort::Added => "created_at DESC, id DESC",
+            ContentSort::Updated => "updated_at DESC, id DESC",
+            ContentSort::Rating => "rating IS NULL, rating DESC, title COLLATE NOCASE, id",
+            ContentSort::UserRating => {
+                "(SELECT AVG(r.rating) FROM content_reviews r WHERE r.content_id = contents.id) \
+                 DESC, title COLLATE NOCASE, id"
+            }
+        };
+        let sql = format!(
+            r#"
+            SELECT id, library_id, scan_path_id, title, folder_title, folder_path, chapter_count, thumbnail, thumbnail_etag, thumbnail_blurhash, metadata, missing_since, series_group_id, age_rating, created_at, updated_at
+            FROM contents
+            WHERE {MATCHES_FILTER}
+            ORDER BY {}
+            LIMIT ?6 OFFSET ?7
+            "#,
+            order
+        );
+
+        sqlx::query_as::<_, Content>(&sql)
+            .bind(filter.library_id)
+            .bind(filter.search)
+            .bind(filter.max_age_rating)
+            .bind(filter.author)
+            .bind(filter.min_rating)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(pool)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// Count content outside the trash that matches a filter.
+    pub async fn count_matching(pool: &Pool<Sqlite>, filter: &ContentFilter<'_>) -> Result<i64> {
+        let (count,): (i64,) = sqlx::query_as(&format!(
+            "SELECT COUNT(*) FROM contents WHERE {MATCHES_FILTER}"
+        ))
+        .bind(filter.library_id)
+        .bind(filter.search)
+        .bind(filter.max_age_rating)
+        .bind(filter.author)
+        .bind(filter.min_rating)
+        .fetch_one(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(count)
+    }
+
     /// Update content metadata.
     pub async fn update_metadata(
         pool: &Pool<Sqlite>,
@@ -156,20 +487,31 @@ impl ContentRepository {
     ) -> Result<Content> {
         let now = Utc::now().to_rfc3339();
 
-        sqlx::query(
+        let sql = format!(
             r#"
             UPDATE contents
-            SET metadata = ?, thumbnail = ?, updated_at = ?
+            SET metadata = ?, {SET_SCRAPED_AGE_RATING}, thumbnail = ?, thumbnail_etag = ?,
+                thumbnail_blurhash = ?, thumbnail_spec = NULL, updated_at = ?
             WHERE id = ?
-            "#,
-        )
-        .bind(metadata.as_ref().and_then(|m| serde_json::to_vec(m).ok()))
-        .bind(&thumbnail)
-        .bind(&now)
-        .bind(id)
-        .execute(pool)
-        .await
-        .map_err(AppError::Database)?;
+            "#
+        );
+        sqlx::query(&sql)
+            .bind(metadata.as_ref().and_then(|m| serde_json::to_vec(m).ok()))
+            .bind(age_rating_of(metadata.as_ref()))
+            .bind(&thumbnail)
+            .bind(thumbnail.as_deref().map(thumbnail_etag))
+            .bind(thumbnail.as_deref().and_then(blurhash::from_image_data))
+            .bind(&now)
+            .bind(id)
+            .execute(pool)
+            .await
+            .map_err(AppError::Database)?;
+
+        let mut conn = pool.acquire().await.map_err(AppError::Database)?;
+        AliasRepository::replace_from_metadata(&mut conn, id, &aliases_of(metadata.as_ref()))
+            .await?;
+        MetadataFieldsRepository::refresh(&mut conn, id).await?;
+        drop(conn);
 
         Self::find_by_id(pool, id)
             .await?
@@ -196,14 +538,23 @@ impl ContentRepository {
             let _ = args.add(t);
         }
 
+        let aliases = metadata.as_ref().map(|m| aliases_of(m.as_ref()));
         if let Some(m_opt) = metadata {
-            query.push_str(", metadata = ?");
+            query.push_str(", metadata = ?, ");
+            query.push_str(SET_SCRAPED_AGE_RATING);
+            let age_rating = age_rating_of(m_opt.as_ref());
             let _ = args.add(m_opt.and_then(|v| serde_json::to_vec(&v).ok()));
+            let _ = args.add(age_rating);
         }
 
         if let Some(t_opt) = thumbnail {
-            query.push_str(", thumbnail = ?");
+            query.push_str(", thumbnail = ?, thumbnail_etag = ?, thumbnail_blurhash = ?");
+            query.push_str(", thumbnail_spec = NULL");
+            let etag = t_opt.as_deref().map(thumbnail_etag);
+            let hash = t_opt.as_deref().and_then(blurhash::from_image_data);
             let _ = args.add(t_opt);
+            let _ = args.add(etag);
+            let _ = args.add(hash);
         }
 
         query.push_str(" WHERE id = ?");
@@ -214,27 +565,78 @@ impl ContentRepository {
             .await
             .map_err(AppError::Database)?;
 
+        if let Some(aliases) = aliases {
+            let mut conn = pool.acquire().await.map_err(AppError::Database)?;
+            AliasRepository::replace_from_metadata(&mut conn, id, &aliases).await?;
+            MetadataFieldsRepository::refresh(&mut conn, id).await?;
+        }
+
         Self::find_by_id(pool, id)
             .await?
             .ok_or_else(|| AppError::NotFound(format!("Content with id {} not found", id)))
     }
 
-    /// Update content thumbnail.
+    /// Age rating of a content, without loading the rest of the row.
+    /// The outer `None` means no content has this ID.
+    pub async fn find_age_rating(pool: &Pool<Sqlite>, id: i64) -> Result<Option<Option<i32>>> {
+        sqlx::query_scalar("SELECT age_rating FROM contents WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// Age ratings of the given contents, as `(id, age rating)`.
+    pub async fn list_age_ratings(
+        pool: &Pool<Sqlite>,
+        ids: &[i64],
+    ) -> Result<Vec<(i64, Option<i32>)>> {
+        sqlx::query_as(
+            "SELECT id, age_rating FROM contents WHERE id IN (SELECT value FROM json_each(?))",
+        )
+        .bind(serde_json::to_string(ids).unwrap_or_default())
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Set the age rating of a content by hand.
+    pub async fn set_age_rating(
+        pool: &Pool<Sqlite>,
+        id: i64,
+        age_rating: Option<i32>,
+    ) -> Result<()> {
+        sqlx::query("UPDATE contents SET age_rating = ?, updated_at = ? WHERE id = ?")
+            .bind(age_rating)
+            .bind(Utc::now().to_rfc3339())
+            .bind(id)
+            .execute(pool)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// Update content thumbnail and the settings it was generated with.
     pub async fn update_thumbnail(
         pool: &Pool<Sqlite>,
         id: i64,
         thumbnail: Option<Vec<u8>>,
+        spec: Option<&str>,
     ) -> Result<()> {
         let now = Utc::now().to_rfc3339();
 
         sqlx::query(
             r#"
             UPDATE contents
-            SET thumbnail = ?, updated_at = ?
+            SET thumbnail = ?, thumbnail_etag = ?, thumbnail_blurhash = ?, thumbnail_spec = ?,
+                updated_at = ?
             WHERE id = ?
             "#,
         )
         .bind(&thumbnail)
+        .bind(thumbnail.as_deref().map(thumbnail_etag))
+        .bind(thumbnail.as_deref().and_then(blurhash::from_image_data))
+        .bind(spec)
         .bind(&now)
         .bind(id)
         .execute(pool)
@@ -244,6 +646,89 @@ impl ContentRepository {
         Ok(())
     }
 
+    /// Thumbnails of the given contents, as `(id, thumbnail etag, thumbnail)`.
+    /// Contents without a thumbnail are left out.
+    pub async fn list_thumbnails(
+        pool: &Pool<Sqlite>,
+        ids: &[i64],
+    ) -> Result<Vec<(i64, Option<String>, Vec<u8>)>> {
+        sqlx::query_as(
+            r#"
+            SELECT id, thumbnail_etag, thumbnail FROM contents
+            WHERE id IN (SELECT value FROM json_each(?)) AND thumbnail IS NOT NULL
+            "#,
+        )
+        .bind(serde_json::to_string(ids).unwrap_or_default())
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Contents after `after_id` that have a thumbnail but no BlurHash, as
+    /// `(id, thumbnail etag, thumbnail)`, by ID.
+    pub async fn list_missing_blurhashes(
+        pool: &Pool<Sqlite>,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<(i64, Option<String>, Vec<u8>)>> {
+        sqlx::query_as(
+            r#"
+            SELECT id, thumbnail_etag, thumbnail FROM contents
+            WHERE id > ? AND thumbnail IS NOT NULL AND thumbnail_blurhash IS NULL
+            ORDER BY id
+            LIMIT ?
+            "#,
+        )
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Store the BlurHash of a content's thumbnail, unless the thumbnail was
+    /// replaced since it was read.
+    pub async fn set_thumbnail_blurhash(
+        pool: &Pool<Sqlite>,
+        id: i64,
+        etag: Option<&str>,
+        blurhash: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE contents SET thumbnail_blurhash = ? WHERE id = ? AND thumbnail_etag IS ?",
+        )
+        .bind(blurhash)
+        .bind(id)
+        .bind(etag)
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+
+    /// IDs of a library's visible content whose thumbnail is missing or was
+    /// not generated with `spec`.
+    pub async fn list_outdated_thumbnails(
+        pool: &Pool<Sqlite>,
+        library_id: i64,
+        spec: &str,
+    ) -> Result<Vec<i64>> {
+        sqlx::query_scalar(
+            r#"
+            SELECT id FROM contents
+            WHERE library_id = ? AND missing_since IS NULL AND merged_into IS NULL
+              AND (thumbnail IS NULL OR thumbnail_spec IS NULL OR thumbnail_spec != ?)
+            ORDER BY id
+            "#,
+        )
+        .bind(library_id)
+        .bind(spec)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
     /// Update content chapter count.
     pub async fn update_chapter_count(
         pool: &Pool<Sqlite>,
@@ -269,6 +754,176 @@ impl ContentRepository {
         Ok(())
     }
 
+    /// Point content and its chapters at a new location, keeping their IDs
+    /// so reading progress and metadata stay attached. Content in the trash
+    /// is restored.
+    ///
+    /// `chapter_paths` holds the new file path of each moved chapter.
+    pub async fn relocate(
+        pool: &Pool<Sqlite>,
+        id: i64,
+        folder_path: &str,
+        title: &str,
+        folder_title: &str,
+        chapter_paths: &[(i64, String)],
+    ) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        let mut tx = pool.begin().await.map_err(AppError::Database)?;
+
+        sqlx::query(
+            r#"
+            UPDATE contents
+            SET folder_path = ?, title = ?, folder_title = ?, missing_since = NULL, updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(folder_path)
+        .bind(title)
+        .bind(folder_title)
+        .bind(&now)
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+
+        for (chapter_id, file_path) in chapter_paths {
+            sqlx::query("UPDATE chapters SET file_path = ? WHERE id = ? AND content_id = ?")
+                .bind(file_path)
+                .bind(chapter_id)
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .map_err(AppError::Database)?;
+        }
+
+        tx.commit().await.map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// Reassign content and its chapters to another scan path, possibly of
+    /// another library, keeping their IDs. Content in the trash is restored.
+    ///
+    /// `chapter_paths` holds the new file path of each chapter.
+    pub async fn move_to_scan_path(
+        pool: &Pool<Sqlite>,
+        id: i64,
+        library_id: i64,
+        scan_path_id: i64,
+        folder_path: &str,
+        chapter_paths: &[(i64, String)],
+    ) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        let mut tx = pool.begin().await.map_err(AppError::Database)?;
+
+        sqlx::query(
+            r#"
+            UPDATE contents
+            SET library_id = ?, scan_path_id = ?, folder_path = ?, missing_since = NULL,
+                updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(library_id)
+        .bind(scan_path_id)
+        .bind(folder_path)
+        .bind(&now)
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            if e.to_string().contains("UNIQUE constraint failed") {
+                AppError::BadRequest(
+                    t!("content.folder_path_exists_msg", path = folder_path).to_string(),
+                )
+            } else {
+                AppError::Database(e)
+            }
+        })?;
+
+        for (chapter_id, file_path) in chapter_paths {
+            sqlx::query("UPDATE chapters SET file_path = ? WHERE id = ? AND content_id = ?")
+                .bind(file_path)
+                .bind(chapter_id)
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .map_err(AppError::Database)?;
+        }
+
+        tx.commit().await.map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// Move content to the trash, keeping the time it first went missing.
+    pub async fn mark_missing(pool: &Pool<Sqlite>, id: i64) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+
+        sqlx::query(
+            r#"
+            UPDATE contents
+            SET missing_since = ?
+            WHERE id = ? AND missing_since IS NULL
+            "#,
+        )
+        .bind(&now)
+        .bind(id)
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+
+    /// Take content out of the trash. Returns false if it was not in the trash.
+    pub async fn restore(pool: &Pool<Sqlite>, id: i64) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE contents SET missing_since = NULL WHERE id = ? AND missing_since IS NOT NULL",
+        )
+        .bind(id)
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// List content in the trash, oldest first.
+    pub async fn list_missing(pool: &Pool<Sqlite>) -> Result<Vec<Content>> {
+        sqlx::query_as::<_, Content>(
+            r#"
+            SELECT id, library_id, scan_path_id, title, folder_title, folder_path, chapter_count, thumbnail, thumbnail_etag, thumbnail_blurhash, metadata, missing_since, series_group_id, age_rating, created_at, updated_at
+            FROM contents
+            WHERE missing_since IS NOT NULL
+            ORDER BY missing_since
+            "#,
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Delete content that has been in the trash since before `cutoff`, or
+    /// all of the trash when `cutoff` is None. Returns the number deleted.
+    pub async fn purge_missing(pool: &Pool<Sqlite>, cutoff: Option<DateTime<Utc>>) -> Result<u64> {
+        let result =
+            match cutoff {
+                Some(cutoff) => sqlx::query(
+                    "DELETE FROM contents WHERE missing_since IS NOT NULL AND missing_since <= ?",
+                )
+                .bind(cutoff.to_rfc3339())
+                .execute(pool)
+                .await,
+                None => {
+                    sqlx::query("DELETE FROM contents WHERE missing_since IS NOT NULL")
+                        .execute(pool)
+                        .await
+                }
+            }
+            .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected())
+    }
+
     /// Delete a content by ID.
     pub async fn delete(pool: &Pool<Sqlite>, id: i64) -> Result<()> {
         let result = sqlx::query("DELETE FROM contents WHERE id = ?")
@@ -314,16 +969,48 @@ impl ContentRepository {
     }
 }
 
+/// Assignment of the age rating found in new metadata, bound to one
+/// parameter, which keeps a rating set by hand while it is locked.
+const SET_SCRAPED_AGE_RATING: &str = "age_rating = CASE WHEN EXISTS (
+    SELECT 1 FROM content_metadata_edits e WHERE e.content_id = contents.id AND e.age_rating_lock
+) THEN age_rating ELSE ? END";
+
+/// Condition on `contents` for a [`ContentFilter`], bound to parameters 1
+/// to 5: library, search, age rating, author and rating.
+const MATCHES_FILTER: &str = "(?1 IS NULL OR library_id = ?1)
+    AND (?2 IS NULL OR title LIKE '%' || ?2 || '%' OR EXISTS (
+        SELECT 1 FROM content_aliases a
+        WHERE a.content_id = contents.id AND a.title LIKE '%' || ?2 || '%'
+    ))
+    AND missing_since IS NULL AND merged_into IS NULL
+    AND (?3 IS NULL OR age_rating IS NULL OR age_rating <= ?3)
+    AND (?4 IS NULL OR EXISTS (
+        SELECT 1 FROM content_authors au
+        WHERE au.content_id = contents.id AND au.name = ?4 COLLATE NOCASE
+    ))
+    AND (?5 IS NULL OR rating >= ?5)";
+
+/// Age rating found in the metadata of a content.
+fn age_rating_of(metadata: Option<&serde_json::Value>) -> Option<i32> {
+    metadata.and_then(metadata_age_rating)
+}
+
+/// Alternative titles found in the metadata of a content.
+fn aliases_of(metadata: Option<&serde_json::Value>) -> Vec<String> {
+    metadata.map(metadata_aliases).unwrap_or_default()
+}
+
 /// Repository for chapter database operations.
 pub struct ChapterRepository;
 
 impl ChapterRepository {
     /// Create a new chapter in the database.
     pub async fn create(pool: &Pool<Sqlite>, new_chapter: NewChapter) -> Result<Chapter> {
+        let now = Utc::now().to_rfc3339();
         let result = sqlx::query(
             r#"
-            INSERT INTO chapters (content_id, title, file_path, file_type, sort_order, page_count, size)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO chapters (content_id, title, file_path, file_type, sort_order, page_count, size, volume, number, status, error, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(new_chapter.content_id)
@@ -333,6 +1020,12 @@ impl ChapterRepository {
         .bind(new_chapter.sort_order)
         .bind(new_chapter.page_count)
         .bind(new_chapter.size)
+        .bind(new_chapter.volume)
+        .bind(new_chapter.number)
+        .bind(new_chapter.status.as_str())
+        .bind(&new_chapter.error)
+        .bind(&now)
+        .bind(&now)
         .execute(pool)
         .await;
 
@@ -376,7 +1069,7 @@ impl ChapterRepository {
     pub async fn find_by_id(pool: &Pool<Sqlite>, id: i64) -> Result<Option<Chapter>> {
         sqlx::query_as::<_, Chapter>(
             r#"
-            SELECT id, content_id, title, file_path, file_type, sort_order, page_count, size
+            SELECT id, content_id, title, file_path, file_type, sort_order, page_count, size, volume, number, status, error, created_at, updated_at
             FROM chapters
             WHERE id = ?
             "#,
@@ -391,7 +1084,7 @@ impl ChapterRepository {
     pub async fn list_by_content(pool: &Pool<Sqlite>, content_id: i64) -> Result<Vec<Chapter>> {
         sqlx::query_as::<_, Chapter>(
             r#"
-            SELECT id, content_id, title, file_path, file_type, sort_order, page_count, size
+            SELECT id, content_id, title, file_path, file_type, sort_order, page_count, size, volume, number, status, error, created_at, updated_at
             FROM chapters
             WHERE content_id = ?
             ORDER BY sort_order
@@ -403,6 +1096,89 @@ impl ChapterRepository {
         .map_err(AppError::Database)
     }
 
+    /// List all chapters of several contents, ordered by sort_order.
+    pub async fn list_by_contents(
+        pool: &Pool<Sqlite>,
+        content_ids: &[i64],
+    ) -> Result<Vec<Chapter>> {
+        sqlx::query_as::<_, Chapter>(
+            r#"
+            SELECT id, content_id, title, file_path, file_type, sort_order, page_count, size, volume, number, status, error, created_at, updated_at
+            FROM chapters
+            WHERE content_id IN (SELECT value FROM json_each(?))
+            ORDER BY content_id, sort_order
+            "#,
+        )
+        .bind(serde_json::to_string(content_ids).unwrap_or_default())
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// List chapters of a library's visible contents that have no pages.
+    pub async fn list_without_pages(pool: &Pool<Sqlite>, library_id: i64) -> Result<Vec<Chapter>> {
+        sqlx::query_as::<_, Chapter>(
+            r#"
+            SELECT ch.id, ch.content_id, ch.title, ch.file_path, ch.file_type, ch.sort_order, ch.page_count, ch.size, ch.volume, ch.number, ch.status, ch.error, ch.created_at, ch.updated_at
+            FROM chapters ch
+            JOIN contents c ON c.id = ch.content_id
+            WHERE c.library_id = ? AND ch.page_count = 0
+                AND c.missing_since IS NULL AND c.merged_into IS NULL
+            ORDER BY ch.id
+            "#,
+        )
+        .bind(library_id)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Update the page count and status of a chapter.
+    pub async fn update_page_count(
+        pool: &Pool<Sqlite>,
+        id: i64,
+        page_count: i32,
+        status: ChapterStatus,
+        error: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query("UPDATE chapters SET page_count = ?, status = ?, error = ? WHERE id = ?")
+            .bind(page_count)
+            .bind(status.as_str())
+            .bind(error)
+            .bind(id)
+            .execute(pool)
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+
+    /// List chapters of visible contents that are not ready or had pages
+    /// fail to load, by library, content title and chapter.
+    pub async fn list_media_errors(
+        pool: &Pool<Sqlite>,
+        library_id: Option<i64>,
+    ) -> Result<Vec<MediaError>> {
+        sqlx::query_as::<_, MediaError>(
+            r#"
+            SELECT ch.id AS chapter_id, ch.content_id, c.library_id, c.title AS content_title,
+                   ch.title AS chapter_title, ch.file_path, ch.file_type, ch.status, ch.error,
+                   (SELECT COUNT(*) FROM page_errors pe WHERE pe.chapter_id = ch.id) AS page_errors
+            FROM chapters ch
+            JOIN contents c ON c.id = ch.content_id
+            WHERE (ch.status != 'ready'
+                    OR EXISTS (SELECT 1 FROM page_errors pe WHERE pe.chapter_id = ch.id))
+                AND c.missing_since IS NULL AND c.merged_into IS NULL
+                AND (?1 IS NULL OR c.library_id = ?1)
+            ORDER BY c.library_id, c.title, ch.sort_order
+            "#,
+        )
+        .bind(library_id)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
     /// Delete all chapters for a content.
     pub async fn delete_by_content(pool: &Pool<Sqlite>, content_id: i64) -> Result<u64> {
         let result = sqlx::query("DELETE FROM chapters WHERE content_id = ?")
@@ -425,3 +1201,198 @@ impl ChapterRepository {
         Ok(result.0)
     }
 }
+
+/// Repository for page failures recorded while serving chapters.
+pub struct PageErrorRepository;
+
+impl PageErrorRepository {
+    /// Record a failure for a page, replacing any earlier error for it.
+    pub async fn record(
+        pool: &Pool<Sqlite>,
+        chapter_id: i64,
+        page_index: i64,
+        error: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO page_errors (chapter_id, page_index, error, created_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(chapter_id, page_index)
+            DO UPDATE SET error = excluded.error, created_at = excluded.created_at
+            "#,
+        )
+        .bind(chapter_id)
+        .bind(page_index)
+        .bind(error)
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+
+    /// List recorded failures for a chapter, ordered by page.
+    pub async fn list_by_chapter(pool: &Pool<Sqlite>, chapter_id: i64) -> Result<Vec<PageError>> {
+        sqlx::query_as::<_, PageError>(
+            r#"
+            SELECT id, chapter_id, page_index, error, created_at
+            FROM page_errors
+            WHERE chapter_id = ?
+            ORDER BY page_index
+            "#,
+        )
+        .bind(chapter_id)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Remove all recorded failures for a chapter.
+    pub async fn delete_by_chapter(pool: &Pool<Sqlite>, chapter_id: i64) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM page_errors WHERE chapter_id = ?")
+            .bind(chapter_id)
+            .execute(pool)
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// Repository for the page sizes of image chapters.
+pub struct PageDimensionRepository;
+
+impl PageDimensionRepository {
+    /// List the page sizes of a chapter as `(width, height)` by page, if
+    /// they were read from the file at its current size.
+    pub async fn list_by_chapter(
+        pool: &Pool<Sqlite>,
+        chapter_id: i64,
+        file_size: i64,
+    ) -> Result<Vec<(u32, u32)>> {
+        let rows: Vec<(u32, u32)> = sqlx::query_as(
+            r#"
+            SELECT width, height
+            FROM page_dimensions
+            WHERE chapter_id = ? AND file_size = ?
+            ORDER BY page_index
+            "#,
+        )
+        .bind(chapter_id)
+        .bind(file_size)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(rows)
+    }
+
+    /// Replace the page sizes of a chapter.
+    pub async fn replace(
+        pool: &Pool<Sqlite>,
+        chapter_id: i64,
+        file_size: i64,
+        dimensions: &[(u32, u32)],
+    ) -> Result<()> {
+        let mut tx = pool.begin().await.map_err(AppError::Database)?;
+
+        sqlx::query("DELETE FROM page_dimensions WHERE chapter_id = ?")
+            .bind(chapter_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+
+        for (index, (width, height)) in dimensions.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO page_dimensions (chapter_id, page_index, width, height, file_size)
+                VALUES (?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(chapter_id)
+            .bind(index as i64)
+            .bind(width)
+            .bind(height)
+            .bind(file_size)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+        }
+
+        tx.commit().await.map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// BlurHashes of the pages of a chapter that have one, by page index,
+    /// if they were computed from the file at its current size.
+    pub async fn list_blurhashes(
+        pool: &Pool<Sqlite>,
+        chapter_id: i64,
+        file_size: i64,
+    ) -> Result<HashMap<i64, String>> {
+        let rows: Vec<(i64, String)> = sqlx::query_as(
+            r#"
+            SELECT page_index, blurhash
+            FROM page_dimensions
+            WHERE chapter_id = ? AND file_size = ? AND blurhash IS NOT NULL
+            "#,
+        )
+        .bind(chapter_id)
+        .bind(file_size)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(rows.into_iter().collect())
+    }
+
+    /// Whether a page was listed from the file at its current size but has
+    /// no BlurHash yet.
+    pub async fn needs_blurhash(
+        pool: &Pool<Sqlite>,
+        chapter_id: i64,
+        page_index: i64,
+        file_size: i64,
+    ) -> Result<bool> {
+        let row: Option<i64> = sqlx::query_scalar(
+            r#"
+            SELECT 1 FROM page_dimensions
+            WHERE chapter_id = ? AND page_index = ? AND file_size = ? AND blurhash IS NULL
+            "#,
+        )
+        .bind(chapter_id)
+        .bind(page_index)
+        .bind(file_size)
+        .fetch_optional(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(row.is_some())
+    }
+
+    /// Store the BlurHash of a page.
+    pub async fn set_blurhash(
+        pool: &Pool<Sqlite>,
+        chapter_id: i64,
+        page_index: i64,
+        file_size: i64,
+        blurhash: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE page_dimensions SET blurhash = ?
+            WHERE chapter_id = ? AND page_index = ? AND file_size = ?
+            "#,
+        )
+        .bind(blurhash)
+        .bind(chapter_id)
+        .bind(page_index)
+        .bind(file_size)
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+}
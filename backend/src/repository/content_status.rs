@@ -0,0 +1,122 @@
+//! Per-user content status repository for database operations.
+
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, Result};
+use crate::models::{ContentStatus, ContentStatusFilter, ReadingStatus};
+
+/// Row of `user_content_status`: content ID, favorite flag, status name and
+/// update time.
+type ContentStatusRow = (i64, bool, Option<String>, DateTime<Utc>);
+
+/// Repository for per-user content status database operations.
+pub struct ContentStatusRepository;
+
+impl ContentStatusRepository {
+    /// Find a user's status for a content.
+    pub async fn find(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        content_id: i64,
+    ) -> Result<Option<ContentStatus>> {
+        let row: Option<ContentStatusRow> = sqlx::query_as(
+            r#"
+            SELECT content_id, favorite, status, updated_at
+            FROM user_content_status
+            WHERE user_id = ? AND content_id = ?
+            "#,
+        )
+        .bind(user_id)
+        .bind(content_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(row.map(Self::from_row))
+    }
+
+    /// List a user's statuses matching the filter, most recently changed
+    /// first.
+    pub async fn list_by_user(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        filter: &ContentStatusFilter,
+    ) -> Result<Vec<ContentStatus>> {
+        let rows: Vec<ContentStatusRow> = sqlx::query_as(
+            r#"
+            SELECT content_id, favorite, status, updated_at
+            FROM user_content_status
+            WHERE user_id = ?1
+              AND (?2 IS NULL OR status = ?2)
+              AND (?3 IS NULL OR favorite = ?3)
+            ORDER BY updated_at DESC, content_id
+            "#,
+        )
+        .bind(user_id)
+        .bind(filter.status.map(|status| status.as_str()))
+        .bind(filter.favorite)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(rows.into_iter().map(Self::from_row).collect())
+    }
+
+    /// Replace a user's status for a content.
+    pub async fn upsert(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        content_id: i64,
+        favorite: bool,
+        status: Option<ReadingStatus>,
+    ) -> Result<ContentStatus> {
+        let now = Utc::now();
+
+        sqlx::query(
+            r#"
+            INSERT INTO user_content_status (user_id, content_id, favorite, status, updated_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(user_id, content_id) DO UPDATE SET
+                favorite = excluded.favorite,
+                status = excluded.status,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(content_id)
+        .bind(favorite)
+        .bind(status.map(|status| status.as_str()))
+        .bind(now.to_rfc3339())
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Self::find(pool, user_id, content_id)
+            .await?
+            .ok_or_else(|| AppError::Internal("Failed to store content status".to_string()))
+    }
+
+    /// Remove a user's status for a content. Returns false if none was set.
+    pub async fn delete(pool: &Pool<Sqlite>, user_id: i64, content_id: i64) -> Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM user_content_status WHERE user_id = ? AND content_id = ?")
+                .bind(user_id)
+                .bind(content_id)
+                .execute(pool)
+                .await
+                .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    fn from_row((content_id, favorite, status, updated_at): ContentStatusRow) -> ContentStatus {
+        ContentStatus {
+            content_id,
+            favorite,
+            // Names written by a newer version read as no status
+            status: status.as_deref().and_then(ReadingStatus::from_name),
+            updated_at: Some(updated_at),
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! Dashboard repository.
+//!
+//! This module provides the counts shown on the home page dashboard.
+
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, Result};
+use crate::models::DashboardErrors;
+
+/// Repository for dashboard database operations.
+pub struct DashboardRepository;
+
+impl DashboardRepository {
+    /// Count metadata failures, chapters with media errors and content in
+    /// the trash, the way their listings do. Failed tasks are left at 0,
+    /// they are not stored here.
+    pub async fn count_errors(pool: &Pool<Sqlite>) -> Result<DashboardErrors> {
+        let (metadata_failures, media_errors, trash): (i64, i64, i64) = sqlx::query_as(
+            r#"
+            SELECT
+                (SELECT COUNT(*)
+                 FROM metadata_failures f
+                 JOIN contents c ON c.id = f.content_id
+                 WHERE c.missing_since IS NULL AND c.merged_into IS NULL),
+                (SELECT COUNT(*)
+                 FROM chapters ch
+                 JOIN contents c ON c.id = ch.content_id
+                 WHERE (ch.status != 'ready'
+                         OR EXISTS (SELECT 1 FROM page_errors pe WHERE pe.chapter_id = ch.id))
+                     AND c.missing_since IS NULL AND c.merged_into IS NULL),
+                (SELECT COUNT(*) FROM contents WHERE missing_since IS NOT NULL)
+            "#,
+        )
+        .fetch_one(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(DashboardErrors {
+            metadata_failures,
+            media_errors,
+            trash,
+            failed_tasks: 0,
+        })
+    }
+}
@@ -0,0 +1,218 @@
+//! Duplicate content repository.
+//!
+//! Contents are compared by the hash of the first page of their first
+//! chapter, stored in `chapters.page_hash`.
+
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, Result};
+use crate::models::{Chapter, DuplicateContent};
+
+/// Repository for finding and merging duplicate contents.
+pub struct DuplicateRepository;
+
+impl DuplicateRepository {
+    /// First chapters of visible contents whose first page is not hashed yet.
+    pub async fn first_chapters_without_hash(pool: &Pool<Sqlite>) -> Result<Vec<Chapter>> {
+        sqlx::query_as::<_, Chapter>(
+            r#"
+            SELECT ch.id, ch.content_id, ch.title, ch.file_path, ch.file_type, ch.sort_order, ch.page_count, ch.size, ch.volume, ch.number, ch.status, ch.error, ch.created_at, ch.updated_at
+            FROM chapters ch
+            JOIN contents c ON c.id = ch.content_id
+            WHERE ch.page_hash IS NULL
+                AND c.missing_since IS NULL AND c.merged_into IS NULL
+                AND ch.id = (
+                    SELECT id FROM chapters WHERE content_id = c.id ORDER BY sort_order, id LIMIT 1
+                )
+            ORDER BY ch.id
+            "#,
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Store the hash of a chapter's first page.
+    pub async fn set_page_hash(pool: &Pool<Sqlite>, chapter_id: i64, hash: &str) -> Result<()> {
+        sqlx::query("UPDATE chapters SET page_hash = ? WHERE id = ?")
+            .bind(hash)
+            .bind(chapter_id)
+            .execute(pool)
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+
+    /// Hash of the first page of a content's first chapter, if computed.
+    pub async fn first_page_hash(pool: &Pool<Sqlite>, content_id: i64) -> Result<Option<String>> {
+        let row: Option<(Option<String>,)> = sqlx::query_as(
+            r#"
+            SELECT page_hash FROM chapters
+            WHERE content_id = ?
+            ORDER BY sort_order, id
+            LIMIT 1
+            "#,
+        )
+        .bind(content_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(row.and_then(|(hash,)| hash))
+    }
+
+    /// Visible contents sharing the hash of their first page with another
+    /// content, ordered by hash and content ID.
+    pub async fn find_duplicates(pool: &Pool<Sqlite>) -> Result<Vec<(String, DuplicateContent)>> {
+        let rows: Vec<(String, i64, i64, i64, String, String, i32)> = sqlx::query_as(
+            r#"
+            WITH firsts AS (
+                SELECT ch.page_hash, c.id AS content_id, c.library_id, c.scan_path_id, c.title, c.folder_path, c.chapter_count
+                FROM chapters ch
+                JOIN contents c ON c.id = ch.content_id
+                WHERE ch.page_hash IS NOT NULL
+                    AND c.missing_since IS NULL AND c.merged_into IS NULL
+                    AND ch.id = (
+                        SELECT id FROM chapters WHERE content_id = c.id ORDER BY sort_order, id LIMIT 1
+                    )
+            )
+            SELECT page_hash, content_id, library_id, scan_path_id, title, folder_path, chapter_count
+            FROM firsts
+            WHERE page_hash IN (SELECT page_hash FROM firsts GROUP BY page_hash HAVING COUNT(*) > 1)
+            ORDER BY page_hash, content_id
+            "#,
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(
+                    hash,
+                    content_id,
+                    library_id,
+                    scan_path_id,
+                    title,
+                    folder_path,
+                    chapter_count,
+                )| {
+                    let content = DuplicateContent {
+                        content_id,
+                        library_id,
+                        scan_path_id,
+                        title,
+                        folder_path,
+                        chapter_count,
+                    };
+                    (hash, content)
+                },
+            )
+            .collect())
+    }
+
+    /// Whether a content has been merged into another.
+    pub async fn is_merged(pool: &Pool<Sqlite>, content_id: i64) -> Result<bool> {
+        let row: Option<(Option<i64>,)> =
+            sqlx::query_as("SELECT merged_into FROM contents WHERE id = ?")
+                .bind(content_id)
+                .fetch_optional(pool)
+                .await
+                .map_err(AppError::Database)?;
+
+        Ok(matches!(row, Some((Some(_),))))
+    }
+
+    /// Merge a content into another.
+    ///
+    /// Reading progress on each chapter of `chapter_map` is moved to its
+    /// counterpart in the kept content; where a user already has progress on
+    /// the counterpart, the more recent entry wins. Favorites and reading
+    /// statuses move to the kept content the same way. The merged content is
+    /// hidden by pointing `merged_into` at the kept content. Returns the
+    /// number of progress entries moved.
+    pub async fn merge(
+        pool: &Pool<Sqlite>,
+        keep_id: i64,
+        merge_id: i64,
+        chapter_map: &[(i64, i64)],
+    ) -> Result<u64> {
+        let mut tx = pool.begin().await.map_err(AppError::Database)?;
+        let mut moved = 0;
+
+        for (from, to) in chapter_map {
+            sqlx::query(
+                r#"
+                INSERT INTO reading_progress (
+                    user_id, chapter_id, position, percentage, updated_at,
+                    novel_section, novel_offset, novel_cfi
+                )
+                SELECT user_id, ?, position, percentage, updated_at,
+                    novel_section, novel_offset, novel_cfi
+                FROM reading_progress
+                WHERE chapter_id = ?
+                ON CONFLICT(user_id, chapter_id) DO UPDATE SET
+                    position = excluded.position,
+                    percentage = excluded.percentage,
+                    updated_at = excluded.updated_at,
+                    novel_section = excluded.novel_section,
+                    novel_offset = excluded.novel_offset,
+                    novel_cfi = excluded.novel_cfi
+                WHERE excluded.updated_at > reading_progress.updated_at
+                "#,
+            )
+            .bind(to)
+            .bind(from)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+
+            let result = sqlx::query("DELETE FROM reading_progress WHERE chapter_id = ?")
+                .bind(from)
+                .execute(&mut *tx)
+                .await
+                .map_err(AppError::Database)?;
+            moved += result.rows_affected();
+        }
+
+        // Favorites and reading statuses follow the same rule as progress
+        sqlx::query(
+            r#"
+            INSERT INTO user_content_status (user_id, content_id, favorite, status, updated_at)
+            SELECT user_id, ?, favorite, status, updated_at
+            FROM user_content_status
+            WHERE content_id = ?
+            ON CONFLICT(user_id, content_id) DO UPDATE SET
+                favorite = excluded.favorite,
+                status = excluded.status,
+                updated_at = excluded.updated_at
+            WHERE excluded.updated_at > user_content_status.updated_at
+            "#,
+        )
+        .bind(keep_id)
+        .bind(merge_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+
+        sqlx::query("DELETE FROM user_content_status WHERE content_id = ?")
+            .bind(merge_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+
+        // Contents merged into the merged content earlier follow it
+        sqlx::query("UPDATE contents SET merged_into = ? WHERE id = ? OR merged_into = ?")
+            .bind(keep_id)
+            .bind(merge_id)
+            .bind(merge_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+
+        tx.commit().await.map_err(AppError::Database)?;
+        Ok(moved)
+    }
+}
@@ -0,0 +1,127 @@
+//! Kobo sync repository.
+//!
+//! This module provides the queries behind the Kobo sync endpoints: the
+//! EPUB chapters offered to devices and the reading states kept for them.
+
+use chrono::Utc;
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, Result};
+use crate::models::{Chapter, KoboLocation, KoboReadingState};
+
+/// Repository for Kobo sync database operations.
+pub struct KoboRepository;
+
+impl KoboRepository {
+    /// List up to `limit` EPUB chapters with an ID above `after_id`, by ID.
+    /// Chapters of content in the trash or of libraries being deleted are
+    /// left out.
+    pub async fn list_books_after(
+        pool: &Pool<Sqlite>,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<Chapter>> {
+        sqlx::query_as::<_, Chapter>(
+            r#"
+            SELECT ch.id, ch.content_id, ch.title, ch.file_path, ch.file_type, ch.sort_order, ch.page_count, ch.size, ch.volume, ch.number, ch.status, ch.error, ch.created_at, ch.updated_at
+            FROM chapters ch
+            INNER JOIN contents c ON c.id = ch.content_id
+            INNER JOIN libraries l ON l.id = c.library_id
+            WHERE ch.id > ? AND ch.file_type = 'epub'
+                AND c.missing_since IS NULL AND l.deleting_since IS NULL
+            ORDER BY ch.id
+            LIMIT ?
+            "#,
+        )
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// List a user's reading states of EPUB chapters that changed after
+    /// `since`, or all of them, oldest change first.
+    pub async fn list_states_since(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        since: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<KoboReadingState>> {
+        sqlx::query_as::<_, KoboReadingState>(
+            r#"
+            SELECT rp.chapter_id, rp.percentage, rp.updated_at,
+                k.location_value, k.location_type, k.location_source
+            FROM reading_progress rp
+            INNER JOIN chapters ch ON ch.id = rp.chapter_id
+            LEFT JOIN kobo_reading_states k
+                ON k.user_id = rp.user_id AND k.chapter_id = rp.chapter_id
+            WHERE rp.user_id = ?1 AND ch.file_type = 'epub'
+                AND (?2 IS NULL OR rp.updated_at > ?2)
+            ORDER BY rp.updated_at
+            LIMIT ?3
+            "#,
+        )
+        .bind(user_id)
+        .bind(since)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Get a user's reading state of a chapter, if they started it.
+    pub async fn find_state(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        chapter_id: i64,
+    ) -> Result<Option<KoboReadingState>> {
+        sqlx::query_as::<_, KoboReadingState>(
+            r#"
+            SELECT rp.chapter_id, rp.percentage, rp.updated_at,
+                k.location_value, k.location_type, k.location_source
+            FROM reading_progress rp
+            LEFT JOIN kobo_reading_states k
+                ON k.user_id = rp.user_id AND k.chapter_id = rp.chapter_id
+            WHERE rp.user_id = ? AND rp.chapter_id = ?
+            "#,
+        )
+        .bind(user_id)
+        .bind(chapter_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Store the place a Kobo device reached in a chapter.
+    pub async fn save_location(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        chapter_id: i64,
+        location: &KoboLocation,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO kobo_reading_states (
+                user_id, chapter_id, location_value, location_type, location_source, updated_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT(user_id, chapter_id) DO UPDATE SET
+                location_value = excluded.location_value,
+                location_type = excluded.location_type,
+                location_source = excluded.location_source,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(chapter_id)
+        .bind(&location.value)
+        .bind(&location.kind)
+        .bind(&location.source)
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+//! KOReader sync repository.
+//!
+//! KOReader identifies documents by a partial MD5 of their file, stored in
+//! `chapters.koreader_hash`, and the positions devices send are kept in
+//! `koreader_progress`.
+
+use chrono::Utc;
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, Result};
+use crate::models::{Chapter, IMAGE_FOLDER_FILE_TYPE, KoreaderProgress, UpdateKoreaderProgress};
+
+/// Repository for KOReader sync database operations.
+pub struct KoreaderRepository;
+
+impl KoreaderRepository {
+    /// Get the chapter whose file has the given KOReader hash.
+    pub async fn find_chapter_by_hash(pool: &Pool<Sqlite>, hash: &str) -> Result<Option<Chapter>> {
+        sqlx::query_as::<_, Chapter>(
+            r#"
+            SELECT ch.id, ch.content_id, ch.title, ch.file_path, ch.file_type, ch.sort_order, ch.page_count, ch.size, ch.volume, ch.number, ch.status, ch.error, ch.created_at, ch.updated_at
+            FROM chapters ch
+            INNER JOIN contents c ON c.id = ch.content_id
+            WHERE ch.koreader_hash = ? AND c.missing_since IS NULL
+            ORDER BY ch.id
+            LIMIT 1
+            "#,
+        )
+        .bind(hash)
+        .fetch_optional(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// List up to `limit` chapters with an ID above `after_id` whose file
+    /// is not hashed yet, by ID. Folders of images have no file to hash.
+    pub async fn list_chapters_without_hash(
+        pool: &Pool<Sqlite>,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<Chapter>> {
+        sqlx::query_as::<_, Chapter>(
+            r#"
+            SELECT ch.id, ch.content_id, ch.title, ch.file_path, ch.file_type, ch.sort_order, ch.page_count, ch.size, ch.volume, ch.number, ch.status, ch.error, ch.created_at, ch.updated_at
+            FROM chapters ch
+            INNER JOIN contents c ON c.id = ch.content_id
+            WHERE ch.id > ? AND ch.koreader_hash IS NULL AND ch.file_type != ?
+                AND c.missing_since IS NULL
+            ORDER BY ch.id
+            LIMIT ?
+            "#,
+        )
+        .bind(after_id)
+        .bind(IMAGE_FOLDER_FILE_TYPE)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Store the KOReader hash of a chapter's file.
+    pub async fn set_hash(pool: &Pool<Sqlite>, chapter_id: i64, hash: &str) -> Result<()> {
+        sqlx::query("UPDATE chapters SET koreader_hash = ? WHERE id = ?")
+            .bind(hash)
+            .bind(chapter_id)
+            .execute(pool)
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+
+    /// Get the last position a user's devices sent for a document.
+    pub async fn find_progress(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        document: &str,
+    ) -> Result<Option<KoreaderProgress>> {
+        sqlx::query_as::<_, KoreaderProgress>(
+            r#"
+            SELECT document, progress, percentage, device, device_id, updated_at
+            FROM koreader_progress
+            WHERE user_id = ? AND document = ?
+            "#,
+        )
+        .bind(user_id)
+        .bind(document)
+        .fetch_optional(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Store the position a device sent for a document, replacing the
+    /// previous one.
+    pub async fn save_progress(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        update: &UpdateKoreaderProgress,
+    ) -> Result<KoreaderProgress> {
+        sqlx::query(
+            r#"
+            INSERT INTO koreader_progress (
+                user_id, document, progress, percentage, device, device_id, updated_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(user_id, document) DO UPDATE SET
+                progress = excluded.progress,
+                percentage = excluded.percentage,
+                device = excluded.device,
+                device_id = excluded.device_id,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(&update.document)
+        .bind(&update.progress)
+        .bind(update.percentage)
+        .bind(&update.device)
+        .bind(&update.device_id)
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Self::find_progress(pool, user_id, &update.document)
+            .await?
+            .ok_or_else(|| AppError::Internal("Failed to retrieve KOReader progress".to_string()))
+    }
+}
@@ -4,10 +4,12 @@
 
 use chrono::Utc;
 use sqlx::{Pool, Sqlite};
-use rust_i18n::t;
 
 use crate::error::{AppError, Result};
-use crate::models::{Library, LibraryWithStats, NewLibrary, NewScanPath, ScanPath};
+use crate::models::{
+    Library, LibraryWithStats, MetadataProviderKind, NewLibrary, NewScanPath, ScanPath,
+};
+use crate::t;
 
 /// Repository for library database operations.
 pub struct LibraryRepository;
@@ -19,13 +21,15 @@ impl LibraryRepository {
 
         let result = sqlx::query(
             r#"
-            INSERT INTO libraries (name, scan_interval, watch_mode, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?)
+            INSERT INTO libraries (name, scan_interval, watch_mode, scan_depth, standalone_archives, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&new_library.name)
         .bind(new_library.scan_interval)
         .bind(new_library.watch_mode)
+        .bind(new_library.scan_depth)
+        .bind(new_library.standalone_archives)
         .bind(&now)
         .bind(&now)
         .execute(pool)
@@ -38,13 +42,13 @@ impl LibraryRepository {
             .ok_or_else(|| AppError::Internal("Failed to retrieve created library".to_string()))
     }
 
-    /// Find a library by ID.
+    /// Find a library by ID. Libraries being deleted are not found.
     pub async fn find_by_id(pool: &Pool<Sqlite>, id: i64) -> Result<Option<Library>> {
         sqlx::query_as::<_, Library>(
             r#"
-            SELECT id, name, scan_interval, watch_mode, created_at, updated_at
+            SELECT id, name, scan_interval, watch_mode, scan_depth, standalone_archives, created_at, updated_at
             FROM libraries
-            WHERE id = ?
+            WHERE id = ? AND deleting_since IS NULL
             "#,
         )
         .bind(id)
@@ -53,12 +57,13 @@ impl LibraryRepository {
         .map_err(AppError::Database)
     }
 
-    /// List all libraries.
+    /// List all libraries, except those being deleted.
     pub async fn list(pool: &Pool<Sqlite>) -> Result<Vec<Library>> {
         sqlx::query_as::<_, Library>(
             r#"
-            SELECT id, name, scan_interval, watch_mode, created_at, updated_at
+            SELECT id, name, scan_interval, watch_mode, scan_depth, standalone_archives, created_at, updated_at
             FROM libraries
+            WHERE deleting_since IS NULL
             ORDER BY name
             "#,
         )
@@ -92,6 +97,8 @@ impl LibraryRepository {
         name: Option<String>,
         scan_interval: Option<i32>,
         watch_mode: Option<bool>,
+        scan_depth: Option<i32>,
+        standalone_archives: Option<bool>,
     ) -> Result<Library> {
         let existing = Self::find_by_id(pool, id)
             .await?
@@ -101,17 +108,22 @@ impl LibraryRepository {
         let new_name = name.unwrap_or(existing.name);
         let new_scan_interval = scan_interval.unwrap_or(existing.scan_interval);
         let new_watch_mode = watch_mode.unwrap_or(existing.watch_mode);
+        let new_scan_depth = scan_depth.unwrap_or(existing.scan_depth);
+        let new_standalone_archives = standalone_archives.unwrap_or(existing.standalone_archives);
 
         sqlx::query(
             r#"
             UPDATE libraries
-            SET name = ?, scan_interval = ?, watch_mode = ?, updated_at = ?
+            SET name = ?, scan_interval = ?, watch_mode = ?, scan_depth = ?, standalone_archives = ?,
+                updated_at = ?
             WHERE id = ?
             "#,
         )
         .bind(&new_name)
         .bind(new_scan_interval)
         .bind(new_watch_mode)
+        .bind(new_scan_depth)
+        .bind(new_standalone_archives)
         .bind(&now)
         .bind(id)
         .execute(pool)
@@ -142,6 +154,104 @@ impl LibraryRepository {
         Ok(())
     }
 
+    /// Mark a library as being deleted, hiding it. Returns false if the
+    /// library does not exist; a library already being deleted keeps the
+    /// time its deletion started.
+    pub async fn mark_deleting(pool: &Pool<Sqlite>, id: i64) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE libraries SET deleting_since = COALESCE(deleting_since, ?) WHERE id = ?",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(id)
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// IDs of the libraries being deleted.
+    pub async fn list_deleting(pool: &Pool<Sqlite>) -> Result<Vec<i64>> {
+        sqlx::query_scalar("SELECT id FROM libraries WHERE deleting_since IS NOT NULL ORDER BY id")
+            .fetch_all(pool)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// Count the chapters of all contents of a library, including missing
+    /// and merged ones.
+    pub async fn count_all_chapters(pool: &Pool<Sqlite>, library_id: i64) -> Result<i64> {
+        sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM chapters
+            JOIN contents ON contents.id = chapters.content_id
+            WHERE contents.library_id = ?
+            "#,
+        )
+        .bind(library_id)
+        .fetch_one(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Count all contents of a library, including missing and merged ones.
+    pub async fn count_all_contents(pool: &Pool<Sqlite>, library_id: i64) -> Result<i64> {
+        sqlx::query_scalar("SELECT COUNT(*) FROM contents WHERE library_id = ?")
+            .bind(library_id)
+            .fetch_one(pool)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// Delete up to `limit` chapters of a library's contents, with their
+    /// reading progress, bookmarks and page data. Returns the number deleted.
+    pub async fn delete_chapter_batch(
+        pool: &Pool<Sqlite>,
+        library_id: i64,
+        limit: i64,
+    ) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM chapters WHERE id IN (
+                SELECT chapters.id FROM chapters
+                JOIN contents ON contents.id = chapters.content_id
+                WHERE contents.library_id = ?
+                LIMIT ?
+            )
+            "#,
+        )
+        .bind(library_id)
+        .bind(limit)
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Delete up to `limit` contents of a library, with the rows that belong
+    /// to them. Returns the number deleted.
+    pub async fn delete_content_batch(
+        pool: &Pool<Sqlite>,
+        library_id: i64,
+        limit: i64,
+    ) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM contents WHERE id IN (
+                SELECT id FROM contents WHERE library_id = ? LIMIT ?
+            )
+            "#,
+        )
+        .bind(library_id)
+        .bind(limit)
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected())
+    }
+
     /// Count scan paths for a library.
     pub async fn count_scan_paths(pool: &Pool<Sqlite>, library_id: i64) -> Result<i64> {
         let result: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM scan_paths WHERE library_id = ?")
@@ -155,13 +265,118 @@ impl LibraryRepository {
 
     /// Count contents for a library.
     pub async fn count_contents(pool: &Pool<Sqlite>, library_id: i64) -> Result<i64> {
-        let result: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM contents WHERE library_id = ?")
+        let result: (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*) FROM contents
+            WHERE library_id = ? AND missing_since IS NULL AND merged_into IS NULL
+            "#,
+        )
+        .bind(library_id)
+        .fetch_one(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(result.0)
+    }
+}
+
+/// Repository for library ignore pattern database operations.
+pub struct IgnorePatternRepository;
+
+impl IgnorePatternRepository {
+    /// List the ignore patterns of a library in the order they were added.
+    pub async fn list_by_library(pool: &Pool<Sqlite>, library_id: i64) -> Result<Vec<String>> {
+        let rows: Vec<(String,)> = sqlx::query_as(
+            "SELECT pattern FROM library_ignore_patterns WHERE library_id = ? ORDER BY id",
+        )
+        .bind(library_id)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(rows.into_iter().map(|(pattern,)| pattern).collect())
+    }
+
+    /// Replace all ignore patterns of a library.
+    pub async fn replace(pool: &Pool<Sqlite>, library_id: i64, patterns: &[String]) -> Result<()> {
+        let mut tx = pool.begin().await.map_err(AppError::Database)?;
+
+        sqlx::query("DELETE FROM library_ignore_patterns WHERE library_id = ?")
             .bind(library_id)
-            .fetch_one(pool)
+            .execute(&mut *tx)
             .await
             .map_err(AppError::Database)?;
 
-        Ok(result.0)
+        for pattern in patterns {
+            sqlx::query("INSERT INTO library_ignore_patterns (library_id, pattern) VALUES (?, ?)")
+                .bind(library_id)
+                .bind(pattern)
+                .execute(&mut *tx)
+                .await
+                .map_err(AppError::Database)?;
+        }
+
+        tx.commit().await.map_err(AppError::Database)
+    }
+}
+
+/// Repository for library metadata provider database operations.
+pub struct MetadataProviderRepository;
+
+impl MetadataProviderRepository {
+    /// List the metadata providers of a library in priority order.
+    pub async fn list_by_library(
+        pool: &Pool<Sqlite>,
+        library_id: i64,
+    ) -> Result<Vec<MetadataProviderKind>> {
+        let rows: Vec<(String,)> = sqlx::query_as(
+            r#"
+            SELECT provider FROM library_metadata_providers
+            WHERE library_id = ?
+            ORDER BY position
+            "#,
+        )
+        .bind(library_id)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|(provider,)| MetadataProviderKind::from_name(provider))
+            .collect())
+    }
+
+    /// Replace all metadata providers of a library.
+    pub async fn replace(
+        pool: &Pool<Sqlite>,
+        library_id: i64,
+        providers: &[MetadataProviderKind],
+    ) -> Result<()> {
+        let mut tx = pool.begin().await.map_err(AppError::Database)?;
+
+        sqlx::query("DELETE FROM library_metadata_providers WHERE library_id = ?")
+            .bind(library_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+
+        for (position, provider) in providers.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO library_metadata_providers (library_id, provider, position)
+                VALUES (?, ?, ?)
+                "#,
+            )
+            .bind(library_id)
+            .bind(provider.as_str())
+            .bind(position as i64)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+        }
+
+        tx.commit().await.map_err(AppError::Database)
     }
 }
 
@@ -0,0 +1,97 @@
+//! Metadata failure repository.
+//!
+//! This module provides database operations for content whose metadata
+//! could not be scraped.
+
+use chrono::Utc;
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, Result};
+use crate::models::MetadataFailure;
+
+/// Repository for metadata failure database operations.
+pub struct MetadataFailureRepository;
+
+impl MetadataFailureRepository {
+    /// Record a failed scrape of a content, counting repeated failures.
+    pub async fn record(pool: &Pool<Sqlite>, content_id: i64, error: &str) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+
+        sqlx::query(
+            r#"
+            INSERT INTO metadata_failures (content_id, error, attempts, created_at, updated_at)
+            VALUES (?, ?, 1, ?, ?)
+            ON CONFLICT(content_id) DO UPDATE SET
+                error = excluded.error,
+                attempts = attempts + 1,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(content_id)
+        .bind(error)
+        .bind(&now)
+        .bind(&now)
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+
+    /// List all failures, most recent first. Content in the trash or merged
+    /// into another content is left out.
+    pub async fn list(pool: &Pool<Sqlite>) -> Result<Vec<MetadataFailure>> {
+        sqlx::query_as::<_, MetadataFailure>(
+            r#"
+            SELECT f.id, f.content_id, c.library_id, c.title, f.error, f.attempts,
+                   f.created_at, f.updated_at
+            FROM metadata_failures f
+            JOIN contents c ON c.id = f.content_id
+            WHERE c.missing_since IS NULL AND c.merged_into IS NULL
+            ORDER BY f.updated_at DESC, f.id DESC
+            "#,
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Find a failure by ID.
+    pub async fn find_by_id(pool: &Pool<Sqlite>, id: i64) -> Result<Option<MetadataFailure>> {
+        sqlx::query_as::<_, MetadataFailure>(
+            r#"
+            SELECT f.id, f.content_id, c.library_id, c.title, f.error, f.attempts,
+                   f.created_at, f.updated_at
+            FROM metadata_failures f
+            JOIN contents c ON c.id = f.content_id
+            WHERE f.id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Delete a failure. Returns whether it existed.
+    pub async fn delete(pool: &Pool<Sqlite>, id: i64) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM metadata_failures WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Delete the failure of a content, once it has metadata.
+    pub async fn delete_by_content(pool: &Pool<Sqlite>, content_id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM metadata_failures WHERE content_id = ?")
+            .bind(content_id)
+            .execute(pool)
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+//! Manual metadata edit repository for database operations.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, Pool, Sqlite, SqliteConnection};
+
+use crate::error::{AppError, Result};
+use crate::models::{MetadataEdits, SeriesStatus};
+use crate::repository::metadata_fields::MetadataFieldsRepository;
+
+/// Row of `content_metadata_edits`. Tags and authors are JSON arrays.
+#[derive(FromRow)]
+struct MetadataEditsRow {
+    content_id: i64,
+    title_lock: bool,
+    summary: Option<String>,
+    summary_lock: bool,
+    status: Option<String>,
+    status_lock: bool,
+    language: Option<String>,
+    language_lock: bool,
+    tags: Option<String>,
+    tags_lock: bool,
+    authors: Option<String>,
+    authors_lock: bool,
+    age_rating_lock: bool,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<MetadataEditsRow> for MetadataEdits {
+    fn from(row: MetadataEditsRow) -> Self {
+        Self {
+            title_lock: row.title_lock,
+            summary: row.summary,
+            summary_lock: row.summary_lock,
+            // Names written by a newer version read as no status
+            status: row.status.as_deref().and_then(SeriesStatus::from_name),
+            status_lock: row.status_lock,
+            language: row.language,
+            language_lock: row.language_lock,
+            tags: row.tags.and_then(|tags| serde_json::from_str(&tags).ok()),
+            tags_lock: row.tags_lock,
+            authors: row
+                .authors
+                .and_then(|authors| serde_json::from_str(&authors).ok()),
+            authors_lock: row.authors_lock,
+            age_rating_lock: row.age_rating_lock,
+            updated_at: Some(row.updated_at),
+        }
+    }
+}
+
+/// Repository for manual metadata edit database operations.
+pub struct MetadataEditsRepository;
+
+impl MetadataEditsRepository {
+    /// Find the edits of a content, if any were made.
+    pub async fn find(pool: &Pool<Sqlite>, content_id: i64) -> Result<Option<MetadataEdits>> {
+        let mut conn = pool.acquire().await.map_err(AppError::Database)?;
+        Self::find_on(&mut conn, content_id).await
+    }
+
+    /// Find the edits of a content on a connection, e.g. inside a
+    /// transaction.
+    pub async fn find_on(
+        conn: &mut SqliteConnection,
+        content_id: i64,
+    ) -> Result<Option<MetadataEdits>> {
+        let row = sqlx::query_as::<_, MetadataEditsRow>(
+            r#"
+            SELECT content_id, title_lock, summary, summary_lock, status, status_lock,
+                language, language_lock, tags, tags_lock, authors, authors_lock,
+                age_rating_lock, updated_at
+            FROM content_metadata_edits
+            WHERE content_id = ?
+            "#,
+        )
+        .bind(content_id)
+        .fetch_optional(conn)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(row.map(MetadataEdits::from))
+    }
+
+    /// List the edits of every content that has any, by content ID.
+    pub async fn list(pool: &Pool<Sqlite>) -> Result<HashMap<i64, MetadataEdits>> {
+        let rows = sqlx::query_as::<_, MetadataEditsRow>(
+            r#"
+            SELECT content_id, title_lock, summary, summary_lock, status, status_lock,
+                language, language_lock, tags, tags_lock, authors, authors_lock,
+                age_rating_lock, updated_at
+            FROM content_metadata_edits
+            "#,
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.content_id, MetadataEdits::from(row)))
+            .collect())
+    }
+
+    /// Store the edits of a content. Empty edits remove the row.
+    ///
+    /// The metadata fields of the content are copied out again, as edits
+    /// take precedence over the scraped values.
+    pub async fn save(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        edits: &MetadataEdits,
+    ) -> Result<Option<MetadataEdits>> {
+        let mut conn = pool.acquire().await.map_err(AppError::Database)?;
+
+        if edits.is_empty() {
+            sqlx::query("DELETE FROM content_metadata_edits WHERE content_id = ?")
+                .bind(content_id)
+                .execute(&mut *conn)
+                .await
+                .map_err(AppError::Database)?;
+            MetadataFieldsRepository::refresh(&mut conn, content_id).await?;
+            return Ok(None);
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO content_metadata_edits (
+                content_id, title_lock, summary, summary_lock, status, status_lock,
+                language, language_lock, tags, tags_lock, authors, authors_lock,
+                age_rating_lock, updated_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(content_id) DO UPDATE SET
+                title_lock = excluded.title_lock,
+                summary = excluded.summary,
+                summary_lock = excluded.summary_lock,
+                status = excluded.status,
+                status_lock = excluded.status_lock,
+                language = excluded.language,
+                language_lock = excluded.language_lock,
+                tags = excluded.tags,
+                tags_lock = excluded.tags_lock,
+                authors = excluded.authors,
+                authors_lock = excluded.authors_lock,
+                age_rating_lock = excluded.age_rating_lock,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(content_id)
+        .bind(edits.title_lock)
+        .bind(&edits.summary)
+        .bind(edits.summary_lock)
+        .bind(edits.status.map(|status| status.as_str()))
+        .bind(edits.status_lock)
+        .bind(&edits.language)
+        .bind(edits.language_lock)
+        .bind(
+            edits
+                .tags
+                .as_ref()
+                .and_then(|tags| serde_json::to_string(tags).ok()),
+        )
+        .bind(edits.tags_lock)
+        .bind(
+            edits
+                .authors
+                .as_ref()
+                .and_then(|authors| serde_json::to_string(authors).ok()),
+        )
+        .bind(edits.authors_lock)
+        .bind(edits.age_rating_lock)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&mut *conn)
+        .await
+        .map_err(AppError::Database)?;
+
+        MetadataFieldsRepository::refresh(&mut conn, content_id).await?;
+        Self::find_on(&mut conn, content_id).await
+    }
+}
@@ -0,0 +1,74 @@
+//! Denormalized metadata field repository.
+//!
+//! The key fields of each content's metadata, with manual edits applied,
+//! are kept in columns of `contents` and its authors in `content_authors`,
+//! so listings can filter and sort by them in SQL.
+
+use sqlx::SqliteConnection;
+
+use crate::error::{AppError, Result};
+use crate::models::MetadataFields;
+use crate::repository::metadata_edits::MetadataEditsRepository;
+
+/// Repository for denormalized metadata field database operations.
+pub struct MetadataFieldsRepository;
+
+impl MetadataFieldsRepository {
+    /// Copy the fields of a content's metadata and edits out again. Called
+    /// whenever either of them is written.
+    pub async fn refresh(conn: &mut SqliteConnection, content_id: i64) -> Result<()> {
+        let row: Option<(Option<Vec<u8>>,)> =
+            sqlx::query_as("SELECT metadata FROM contents WHERE id = ?")
+                .bind(content_id)
+                .fetch_optional(&mut *conn)
+                .await
+                .map_err(AppError::Database)?;
+        let Some((metadata,)) = row else {
+            return Ok(());
+        };
+
+        let mut fields = metadata
+            .and_then(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes).ok())
+            .map(|metadata| MetadataFields::from_metadata(&metadata))
+            .unwrap_or_default();
+        if let Some(edits) = MetadataEditsRepository::find_on(conn, content_id).await? {
+            fields.apply_edits(&edits);
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE contents
+            SET summary = ?, publisher = ?, release_date = ?, language = ?, rating = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&fields.summary)
+        .bind(&fields.publisher)
+        .bind(&fields.release_date)
+        .bind(&fields.language)
+        .bind(fields.rating)
+        .bind(content_id)
+        .execute(&mut *conn)
+        .await
+        .map_err(AppError::Database)?;
+
+        sqlx::query("DELETE FROM content_authors WHERE content_id = ?")
+            .bind(content_id)
+            .execute(&mut *conn)
+            .await
+            .map_err(AppError::Database)?;
+
+        for author in &fields.authors {
+            sqlx::query(
+                "INSERT OR IGNORE INTO content_authors (content_id, name, role) VALUES (?, ?, ?)",
+            )
+            .bind(content_id)
+            .bind(&author.name)
+            .bind(&author.role)
+            .execute(&mut *conn)
+            .await
+            .map_err(AppError::Database)?;
+        }
+        Ok(())
+    }
+}
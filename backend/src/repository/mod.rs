@@ -2,8 +2,31 @@
 //!
 //! This module contains repository implementations for interacting with the SQLite database.
 
+pub mod alias;
+pub mod apikey;
+pub mod audit;
+pub mod bangumi;
+pub mod bookmark;
+pub mod consistency;
 pub mod content;
+pub mod content_status;
+pub mod dashboard;
+pub mod duplicate;
+pub mod kobo;
+pub mod koreader;
 pub mod library;
+pub mod metadata;
+pub mod metadata_edits;
+pub mod metadata_fields;
+pub mod preferences;
 pub mod progress;
+pub mod quota;
+pub mod reader_settings;
+pub mod release;
+pub mod review;
+pub mod scan_task;
+pub mod series_group;
+pub mod settings;
+pub mod share;
+pub mod tracker;
 pub mod user;
-pub mod apikey;
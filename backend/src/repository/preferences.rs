@@ -0,0 +1,55 @@
+//! User preferences repository for database operations.
+
+use chrono::Utc;
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, Result};
+use crate::models::InterfacePreferences;
+
+/// Repository for user preferences database operations.
+pub struct PreferencesRepository;
+
+impl PreferencesRepository {
+    /// Find the interface preferences of a user, the defaults when none
+    /// are stored.
+    pub async fn find(pool: &Pool<Sqlite>, user_id: i64) -> Result<InterfacePreferences> {
+        let stored: Option<(String,)> =
+            sqlx::query_as("SELECT preferences FROM user_preferences WHERE user_id = ?")
+                .bind(user_id)
+                .fetch_optional(pool)
+                .await
+                .map_err(AppError::Database)?;
+
+        // An object the current version cannot read is treated as unset
+        Ok(stored
+            .and_then(|(json,)| serde_json::from_str(&json).ok())
+            .unwrap_or_default())
+    }
+
+    /// Store the interface preferences of a user.
+    pub async fn save(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        preferences: &InterfacePreferences,
+    ) -> Result<()> {
+        let json =
+            serde_json::to_string(preferences).map_err(|e| AppError::Internal(e.to_string()))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO user_preferences (user_id, preferences, updated_at)
+            VALUES (?, ?, ?)
+            ON CONFLICT(user_id)
+            DO UPDATE SET preferences = excluded.preferences, updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(json)
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+}
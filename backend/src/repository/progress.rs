@@ -2,12 +2,30 @@
 //!
 //! This module provides database access for reading progress operations.
 
-use chrono::Utc;
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
 use sqlx::{Pool, Sqlite};
 
 use crate::error::{AppError, Result};
 use crate::models::Content;
-use crate::models::{NewReadingProgress, ReadingProgress};
+use crate::models::{
+    ChapterReadCounts, ExportedProgress, NewReadingProgress, NovelPosition, ReadingProgress,
+    SplitProgress,
+};
+
+/// Row of [`ProgressRepository::list_by_content_with_usernames`]: chapter
+/// ID, username, position, percentage, update time and novel position.
+type ExportedProgressRow = (
+    i64,
+    String,
+    i32,
+    f32,
+    DateTime<Utc>,
+    Option<i32>,
+    Option<i64>,
+    Option<String>,
+);
 
 /// Repository for reading progress database operations.
 pub struct ProgressRepository;
@@ -25,12 +43,18 @@ impl ProgressRepository {
         // Use INSERT OR REPLACE with the unique constraint on (user_id, chapter_id)
         sqlx::query(
             r#"
-            INSERT INTO reading_progress (user_id, chapter_id, position, percentage, updated_at)
-            VALUES (?, ?, ?, ?, ?)
+            INSERT INTO reading_progress (
+                user_id, chapter_id, position, percentage, updated_at,
+                novel_section, novel_offset, novel_cfi
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
             ON CONFLICT(user_id, chapter_id) DO UPDATE SET
                 position = excluded.position,
                 percentage = excluded.percentage,
-                updated_at = excluded.updated_at
+                updated_at = excluded.updated_at,
+                novel_section = excluded.novel_section,
+                novel_offset = excluded.novel_offset,
+                novel_cfi = excluded.novel_cfi
             "#,
         )
         .bind(progress.user_id)
@@ -38,6 +62,9 @@ impl ProgressRepository {
         .bind(progress.position)
         .bind(progress.percentage)
         .bind(&now)
+        .bind(progress.novel.as_ref().map(|n| n.section))
+        .bind(progress.novel.as_ref().map(|n| n.offset))
+        .bind(progress.novel.as_ref().and_then(|n| n.cfi.as_deref()))
         .execute(pool)
         .await
         .map_err(AppError::Database)?;
@@ -56,7 +83,8 @@ impl ProgressRepository {
     ) -> Result<Option<ReadingProgress>> {
         sqlx::query_as::<_, ReadingProgress>(
             r#"
-            SELECT id, user_id, chapter_id, position, percentage, updated_at
+            SELECT id, user_id, chapter_id, position, percentage, updated_at,
+                novel_section, novel_offset, novel_cfi
             FROM reading_progress
             WHERE user_id = ? AND chapter_id = ?
             "#,
@@ -68,6 +96,27 @@ impl ProgressRepository {
         .map_err(AppError::Database)
     }
 
+    /// Find reading progress of a user on several chapters.
+    pub async fn find_by_user_and_chapters(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        chapter_ids: &[i64],
+    ) -> Result<Vec<ReadingProgress>> {
+        sqlx::query_as::<_, ReadingProgress>(
+            r#"
+            SELECT id, user_id, chapter_id, position, percentage, updated_at,
+                novel_section, novel_offset, novel_cfi
+            FROM reading_progress
+            WHERE user_id = ? AND chapter_id IN (SELECT value FROM json_each(?))
+            "#,
+        )
+        .bind(user_id)
+        .bind(serde_json::to_string(chapter_ids).unwrap_or_default())
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
     /// Find all reading progress for a user on a specific content.
     ///
     /// Returns progress for all chapters of the content that the user has read.
@@ -78,7 +127,8 @@ impl ProgressRepository {
     ) -> Result<Vec<ReadingProgress>> {
         sqlx::query_as::<_, ReadingProgress>(
             r#"
-            SELECT rp.id, rp.user_id, rp.chapter_id, rp.position, rp.percentage, rp.updated_at
+            SELECT rp.id, rp.user_id, rp.chapter_id, rp.position, rp.percentage, rp.updated_at,
+                rp.novel_section, rp.novel_offset, rp.novel_cfi
             FROM reading_progress rp
             INNER JOIN chapters c ON rp.chapter_id = c.id
             WHERE rp.user_id = ? AND c.content_id = ?
@@ -102,7 +152,8 @@ impl ProgressRepository {
     ) -> Result<Option<ReadingProgress>> {
         sqlx::query_as::<_, ReadingProgress>(
             r#"
-            SELECT rp.id, rp.user_id, rp.chapter_id, rp.position, rp.percentage, rp.updated_at
+            SELECT rp.id, rp.user_id, rp.chapter_id, rp.position, rp.percentage, rp.updated_at,
+                rp.novel_section, rp.novel_offset, rp.novel_cfi
             FROM reading_progress rp
             INNER JOIN chapters c ON rp.chapter_id = c.id
             WHERE rp.user_id = ? AND c.content_id = ?
@@ -136,6 +187,7 @@ impl ProgressRepository {
                 WHERE rp.user_id = ?
                 GROUP BY ch.content_id
             ) recent ON c.id = recent.content_id
+            WHERE c.missing_since IS NULL AND c.merged_into IS NULL
             ORDER BY recent.max_updated_at DESC
             LIMIT ?
             "#,
@@ -176,6 +228,90 @@ impl ProgressRepository {
         Ok(result.rows_affected())
     }
 
+    /// List the reading progress of all users on a content's chapters, by
+    /// chapter ID.
+    pub async fn list_by_content_with_usernames(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+    ) -> Result<Vec<(i64, ExportedProgress)>> {
+        let rows: Vec<ExportedProgressRow> = sqlx::query_as(
+            r#"
+            SELECT rp.chapter_id, u.username, rp.position, rp.percentage, rp.updated_at,
+                rp.novel_section, rp.novel_offset, rp.novel_cfi
+            FROM reading_progress rp
+            INNER JOIN chapters c ON rp.chapter_id = c.id
+            INNER JOIN users u ON rp.user_id = u.id
+            WHERE c.content_id = ?
+            ORDER BY c.sort_order, u.username
+            "#,
+        )
+        .bind(content_id)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(chapter_id, username, position, percentage, updated_at, section, offset, cfi)| {
+                    let novel = section.zip(offset).map(|(section, offset)| NovelPosition {
+                        section,
+                        offset,
+                        cfi,
+                    });
+                    let progress = ExportedProgress {
+                        username,
+                        position,
+                        percentage,
+                        updated_at,
+                        novel,
+                    };
+                    (chapter_id, progress)
+                },
+            )
+            .collect())
+    }
+
+    /// Write imported progress unless the user has more recent progress on
+    /// the chapter. Returns true if the progress was written.
+    pub async fn import(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        chapter_id: i64,
+        progress: &ExportedProgress,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO reading_progress (
+                user_id, chapter_id, position, percentage, updated_at,
+                novel_section, novel_offset, novel_cfi
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(user_id, chapter_id) DO UPDATE SET
+                position = excluded.position,
+                percentage = excluded.percentage,
+                updated_at = excluded.updated_at,
+                novel_section = excluded.novel_section,
+                novel_offset = excluded.novel_offset,
+                novel_cfi = excluded.novel_cfi
+            WHERE excluded.updated_at > reading_progress.updated_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(chapter_id)
+        .bind(progress.position)
+        .bind(progress.percentage)
+        .bind(progress.updated_at.to_rfc3339())
+        .bind(progress.novel.as_ref().map(|n| n.section))
+        .bind(progress.novel.as_ref().map(|n| n.offset))
+        .bind(progress.novel.as_ref().and_then(|n| n.cfi.as_deref()))
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Count chapters with progress for a user on a content.
     pub async fn count_chapters_with_progress(
         pool: &Pool<Sqlite>,
@@ -221,4 +357,94 @@ impl ProgressRepository {
 
         Ok(result.0)
     }
+
+    /// Count the chapters of each content a user has read, started and not
+    /// opened. Contents without chapters are left out.
+    pub async fn count_by_contents(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        content_ids: &[i64],
+    ) -> Result<HashMap<i64, ChapterReadCounts>> {
+        let counts = sqlx::query_as::<_, ChapterReadCounts>(
+            r#"
+            SELECT c.content_id,
+                COALESCE(SUM(rp.percentage >= 100.0), 0) AS read,
+                COALESCE(SUM(rp.percentage < 100.0), 0) AS in_progress,
+                SUM(rp.id IS NULL) AS unread
+            FROM chapters c
+            LEFT JOIN reading_progress rp ON rp.chapter_id = c.id AND rp.user_id = ?
+            WHERE c.content_id IN (SELECT value FROM json_each(?))
+            GROUP BY c.content_id
+            "#,
+        )
+        .bind(user_id)
+        .bind(serde_json::to_string(content_ids).unwrap_or_default())
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(counts.into_iter().map(|c| (c.content_id, c)).collect())
+    }
+
+    /// Create or update the reading position inside a virtual chapter.
+    pub async fn upsert_split(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        chapter_id: i64,
+        split_index: i32,
+        position: i32,
+    ) -> Result<SplitProgress> {
+        sqlx::query(
+            r#"
+            INSERT INTO split_progress (user_id, chapter_id, split_index, position, updated_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(user_id, chapter_id, split_index) DO UPDATE SET
+                position = excluded.position,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(chapter_id)
+        .bind(split_index)
+        .bind(position)
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        sqlx::query_as::<_, SplitProgress>(
+            r#"
+            SELECT id, user_id, chapter_id, split_index, position, updated_at
+            FROM split_progress
+            WHERE user_id = ? AND chapter_id = ? AND split_index = ?
+            "#,
+        )
+        .bind(user_id)
+        .bind(chapter_id)
+        .bind(split_index)
+        .fetch_one(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// List a user's reading positions in the virtual chapters of a chapter.
+    pub async fn list_splits(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        chapter_id: i64,
+    ) -> Result<Vec<SplitProgress>> {
+        sqlx::query_as::<_, SplitProgress>(
+            r#"
+            SELECT id, user_id, chapter_id, split_index, position, updated_at
+            FROM split_progress
+            WHERE user_id = ? AND chapter_id = ?
+            ORDER BY split_index
+            "#,
+        )
+        .bind(user_id)
+        .bind(chapter_id)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
 }
@@ -0,0 +1,88 @@
+//! Per-user quota repository for database operations.
+
+use chrono::NaiveDate;
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, Result};
+
+/// Limits set for a user and whether they are an administrator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::FromRow)]
+pub struct UserQuotaRow {
+    pub is_admin: bool,
+    pub quota_daily_mb: Option<i64>,
+    pub quota_daily_requests: Option<i64>,
+}
+
+/// Repository for per-user quota database operations.
+pub struct QuotaRepository;
+
+impl QuotaRepository {
+    /// Limits set for a user, `None` if the user does not exist.
+    pub async fn find_limits(pool: &Pool<Sqlite>, user_id: i64) -> Result<Option<UserQuotaRow>> {
+        sqlx::query_as::<_, UserQuotaRow>(
+            "SELECT is_admin, quota_daily_mb, quota_daily_requests FROM users WHERE id = ?",
+        )
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Set the limits of a user. Returns whether the user exists.
+    pub async fn set_limits(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        daily_mb: Option<i64>,
+        daily_requests: Option<i64>,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE users SET quota_daily_mb = ?, quota_daily_requests = ? WHERE id = ?",
+        )
+        .bind(daily_mb)
+        .bind(daily_requests)
+        .bind(user_id)
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Bytes and requests of a user on a day.
+    pub async fn usage(pool: &Pool<Sqlite>, user_id: i64, day: NaiveDate) -> Result<(i64, i64)> {
+        let row: Option<(i64, i64)> =
+            sqlx::query_as("SELECT bytes, requests FROM user_usage WHERE user_id = ? AND day = ?")
+                .bind(user_id)
+                .bind(day.to_string())
+                .fetch_optional(pool)
+                .await
+                .map_err(AppError::Database)?;
+        Ok(row.unwrap_or_default())
+    }
+
+    /// Add bytes and requests to the usage of a user on a day.
+    pub async fn add_usage(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        day: NaiveDate,
+        bytes: i64,
+        requests: i64,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO user_usage (user_id, day, bytes, requests)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(user_id, day) DO UPDATE SET
+                bytes = bytes + excluded.bytes,
+                requests = requests + excluded.requests
+            "#,
+        )
+        .bind(user_id)
+        .bind(day.to_string())
+        .bind(bytes)
+        .bind(requests)
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,167 @@
+//! Reader settings repository for database operations.
+//!
+//! Library defaults and content overrides live in two tables of the same
+//! shape, keyed by library and content ID.
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, Result};
+use crate::models::{PageLayout, ReaderSettingsOverrides, ReadingDirection};
+
+/// Row of a reader settings table: owner ID, direction name, layout name,
+/// webtoon flag and margin trim flag.
+type ReaderSettingsRow = (
+    i64,
+    Option<String>,
+    Option<String>,
+    Option<bool>,
+    Option<bool>,
+);
+
+const LIBRARY_TABLE: (&str, &str) = ("library_reader_settings", "library_id");
+const CONTENT_TABLE: (&str, &str) = ("content_reader_settings", "content_id");
+
+/// Setting columns shared by both tables, in row order.
+const SETTING_COLUMNS: &str = "reading_direction, page_layout, webtoon, trim_margins";
+
+/// Repository for reader settings database operations.
+pub struct ReaderSettingsRepository;
+
+impl ReaderSettingsRepository {
+    /// Find the defaults stored for a library.
+    pub async fn find_for_library(
+        pool: &Pool<Sqlite>,
+        library_id: i64,
+    ) -> Result<ReaderSettingsOverrides> {
+        Self::find(pool, LIBRARY_TABLE, library_id).await
+    }
+
+    /// Find the overrides stored for a content.
+    pub async fn find_for_content(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+    ) -> Result<ReaderSettingsOverrides> {
+        Self::find(pool, CONTENT_TABLE, content_id).await
+    }
+
+    /// List the defaults of every library that has any, by library ID.
+    pub async fn list_for_libraries(
+        pool: &Pool<Sqlite>,
+    ) -> Result<HashMap<i64, ReaderSettingsOverrides>> {
+        Self::list(pool, LIBRARY_TABLE).await
+    }
+
+    /// List the overrides of every content that has any, by content ID.
+    pub async fn list_for_contents(
+        pool: &Pool<Sqlite>,
+    ) -> Result<HashMap<i64, ReaderSettingsOverrides>> {
+        Self::list(pool, CONTENT_TABLE).await
+    }
+
+    /// Store the defaults of a library. Empty overrides remove the row.
+    pub async fn save_for_library(
+        pool: &Pool<Sqlite>,
+        library_id: i64,
+        overrides: &ReaderSettingsOverrides,
+    ) -> Result<()> {
+        Self::save(pool, LIBRARY_TABLE, library_id, overrides).await
+    }
+
+    /// Store the overrides of a content. Empty overrides remove the row.
+    pub async fn save_for_content(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        overrides: &ReaderSettingsOverrides,
+    ) -> Result<()> {
+        Self::save(pool, CONTENT_TABLE, content_id, overrides).await
+    }
+
+    async fn find(
+        pool: &Pool<Sqlite>,
+        (table, key): (&str, &str),
+        id: i64,
+    ) -> Result<ReaderSettingsOverrides> {
+        let row: Option<ReaderSettingsRow> = sqlx::query_as(&format!(
+            "SELECT {key}, {SETTING_COLUMNS} FROM {table} WHERE {key} = ?"
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(row.map(|row| Self::from_row(row).1).unwrap_or_default())
+    }
+
+    async fn list(
+        pool: &Pool<Sqlite>,
+        (table, key): (&str, &str),
+    ) -> Result<HashMap<i64, ReaderSettingsOverrides>> {
+        let rows: Vec<ReaderSettingsRow> =
+            sqlx::query_as(&format!("SELECT {key}, {SETTING_COLUMNS} FROM {table}"))
+                .fetch_all(pool)
+                .await
+                .map_err(AppError::Database)?;
+
+        Ok(rows.into_iter().map(Self::from_row).collect())
+    }
+
+    async fn save(
+        pool: &Pool<Sqlite>,
+        (table, key): (&str, &str),
+        id: i64,
+        overrides: &ReaderSettingsOverrides,
+    ) -> Result<()> {
+        if overrides.is_empty() {
+            sqlx::query(&format!("DELETE FROM {table} WHERE {key} = ?"))
+                .bind(id)
+                .execute(pool)
+                .await
+                .map_err(AppError::Database)?;
+            return Ok(());
+        }
+
+        sqlx::query(&format!(
+            r#"
+            INSERT INTO {table} ({key}, {SETTING_COLUMNS}, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT({key}) DO UPDATE SET
+                reading_direction = excluded.reading_direction,
+                page_layout = excluded.page_layout,
+                webtoon = excluded.webtoon,
+                trim_margins = excluded.trim_margins,
+                updated_at = excluded.updated_at
+            "#
+        ))
+        .bind(id)
+        .bind(
+            overrides
+                .reading_direction
+                .map(|direction| direction.as_str()),
+        )
+        .bind(overrides.page_layout.map(|layout| layout.as_str()))
+        .bind(overrides.webtoon)
+        .bind(overrides.trim_margins)
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+
+    fn from_row(
+        (id, direction, layout, webtoon, trim_margins): ReaderSettingsRow,
+    ) -> (i64, ReaderSettingsOverrides) {
+        let overrides = ReaderSettingsOverrides {
+            // Names written by a newer version read as inherited
+            reading_direction: direction.as_deref().and_then(ReadingDirection::from_name),
+            page_layout: layout.as_deref().and_then(PageLayout::from_name),
+            webtoon,
+            trim_margins,
+        };
+        (id, overrides)
+    }
+}
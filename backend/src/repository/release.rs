@@ -0,0 +1,71 @@
+//! New chapter release repository.
+//!
+//! This module records the chapters scans add to existing content and lists
+//! them for the users following that content.
+
+use chrono::Utc;
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, Result};
+use crate::models::ChapterRelease;
+
+/// Repository for new chapter release database operations.
+pub struct ReleaseRepository;
+
+impl ReleaseRepository {
+    /// Record chapters a scan added to a content.
+    pub async fn record(pool: &Pool<Sqlite>, content_id: i64, chapter_ids: &[i64]) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        for chapter_id in chapter_ids {
+            sqlx::query(
+                "INSERT INTO chapter_releases (content_id, chapter_id, created_at) VALUES (?, ?, ?)",
+            )
+            .bind(content_id)
+            .bind(chapter_id)
+            .bind(&now)
+            .execute(pool)
+            .await
+            .map_err(AppError::Database)?;
+        }
+        Ok(())
+    }
+
+    /// List up to `limit` releases in content a user follows, newest first,
+    /// starting after the release with ID `after_id`.
+    ///
+    /// A user follows content they marked as a favorite or gave a reading
+    /// status other than dropped. Content in the trash, in libraries being
+    /// deleted or rated above the user's age rating cap is left out.
+    pub async fn list_followed(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        after_id: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<ChapterRelease>> {
+        sqlx::query_as::<_, ChapterRelease>(
+            r#"
+            SELECT r.id, r.content_id, c.title AS content_title, r.chapter_id,
+                ch.title AS chapter_title, ch.volume, ch.number, r.created_at
+            FROM chapter_releases r
+            INNER JOIN chapters ch ON ch.id = r.chapter_id
+            INNER JOIN contents c ON c.id = r.content_id
+            INNER JOIN libraries l ON l.id = c.library_id
+            INNER JOIN user_content_status s ON s.content_id = r.content_id AND s.user_id = ?1
+            INNER JOIN users u ON u.id = s.user_id
+            WHERE (s.favorite = 1 OR s.status != 'dropped')
+              AND c.missing_since IS NULL AND l.deleting_since IS NULL
+              AND (u.max_age_rating IS NULL OR c.age_rating IS NULL
+                  OR c.age_rating <= u.max_age_rating)
+              AND (?2 IS NULL OR r.id < ?2)
+            ORDER BY r.id DESC
+            LIMIT ?3
+            "#,
+        )
+        .bind(user_id)
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+}
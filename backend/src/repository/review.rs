@@ -0,0 +1,136 @@
+//! Rating and review repository for database operations.
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, Result};
+use crate::models::{ContentReview, RatingSummary};
+
+const REVIEW_COLUMNS: &str = "r.id, r.content_id, r.user_id, u.username, r.rating, r.review, \
+    r.created_at, r.updated_at";
+
+/// Repository for rating and review database operations.
+pub struct ReviewRepository;
+
+impl ReviewRepository {
+    /// Find a user's rating of a content.
+    pub async fn find(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        content_id: i64,
+    ) -> Result<Option<ContentReview>> {
+        sqlx::query_as::<_, ContentReview>(&format!(
+            r#"
+            SELECT {REVIEW_COLUMNS}
+            FROM content_reviews r
+            JOIN users u ON u.id = r.user_id
+            WHERE r.user_id = ? AND r.content_id = ?
+            "#
+        ))
+        .bind(user_id)
+        .bind(content_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// List the ratings of a content, most recently changed first.
+    pub async fn list_by_content(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+    ) -> Result<Vec<ContentReview>> {
+        sqlx::query_as::<_, ContentReview>(&format!(
+            r#"
+            SELECT {REVIEW_COLUMNS}
+            FROM content_reviews r
+            JOIN users u ON u.id = r.user_id
+            WHERE r.content_id = ?
+            ORDER BY r.updated_at DESC, r.id DESC
+            "#
+        ))
+        .bind(content_id)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Rate a content, replacing the user's earlier rating of it.
+    pub async fn save(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        content_id: i64,
+        rating: i32,
+        review: Option<&str>,
+    ) -> Result<ContentReview> {
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT INTO content_reviews
+                (user_id, content_id, rating, review, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT(user_id, content_id) DO UPDATE SET
+                rating = excluded.rating,
+                review = excluded.review,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(content_id)
+        .bind(rating)
+        .bind(review)
+        .bind(&now)
+        .bind(&now)
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Self::find(pool, user_id, content_id)
+            .await?
+            .ok_or_else(|| AppError::Internal("Failed to retrieve saved review".to_string()))
+    }
+
+    /// Delete a user's rating of a content. Returns whether it existed.
+    pub async fn delete(pool: &Pool<Sqlite>, user_id: i64, content_id: i64) -> Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM content_reviews WHERE user_id = ? AND content_id = ?")
+                .bind(user_id)
+                .bind(content_id)
+                .execute(pool)
+                .await
+                .map_err(AppError::Database)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Average rating of several contents, by content ID. Contents nobody
+    /// rated are left out.
+    pub async fn summaries(
+        pool: &Pool<Sqlite>,
+        content_ids: &[i64],
+    ) -> Result<HashMap<i64, RatingSummary>> {
+        let rows: Vec<(i64, f64, i64)> = sqlx::query_as(
+            r#"
+            SELECT content_id, AVG(rating), COUNT(*)
+            FROM content_reviews
+            WHERE content_id IN (SELECT value FROM json_each(?))
+            GROUP BY content_id
+            "#,
+        )
+        .bind(serde_json::to_string(content_ids).unwrap_or_default())
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(content_id, average, count)| {
+                let summary = RatingSummary {
+                    average_rating: Some(average),
+                    rating_count: count,
+                };
+                (content_id, summary)
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,73 @@
+//! Scan task repository for database operations.
+//!
+//! Scan tasks live in memory while the server runs; this table only holds
+//! the snapshot written at shutdown and read back at startup.
+
+use sqlx::{Pool, Sqlite};
+use tracing::warn;
+
+use crate::error::{AppError, Result};
+use crate::models::ScanTask;
+use crate::t;
+
+/// Repository for persisted scan tasks.
+pub struct ScanTaskRepository;
+
+impl ScanTaskRepository {
+    /// Replace the stored snapshot with `tasks`.
+    ///
+    /// Each task is paired with whether it should run again after a restart.
+    pub async fn replace_all(pool: &Pool<Sqlite>, tasks: &[(ScanTask, bool)]) -> Result<()> {
+        let mut tx = pool.begin().await.map_err(AppError::Database)?;
+
+        sqlx::query("DELETE FROM scan_tasks")
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+
+        for (task, resume) in tasks {
+            let data =
+                serde_json::to_string(task).map_err(|e| AppError::Internal(e.to_string()))?;
+            sqlx::query(
+                r#"
+                INSERT INTO scan_tasks (id, library_id, status, resume, data, created_at)
+                VALUES (?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(task.id.to_string())
+            .bind(task.library_id)
+            .bind(format!("{:?}", task.status))
+            .bind(resume)
+            .bind(data)
+            .bind(task.created_at.to_rfc3339())
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+        }
+
+        tx.commit().await.map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// Load the stored snapshot, oldest task first.
+    ///
+    /// Rows that no longer deserialize are skipped.
+    pub async fn list(pool: &Pool<Sqlite>) -> Result<Vec<(ScanTask, bool)>> {
+        let rows: Vec<(String, String, bool)> =
+            sqlx::query_as("SELECT id, data, resume FROM scan_tasks ORDER BY created_at")
+                .fetch_all(pool)
+                .await
+                .map_err(AppError::Database)?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(id, data, resume)| match serde_json::from_str(&data) {
+                Ok(task) => Some((task, resume)),
+                Err(e) => {
+                    warn!(task_id = %id, error = %e, "{}", t!("scan_queue.unreadable_task"));
+                    None
+                }
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,115 @@
+//! Series group repository for database operations.
+
+use chrono::Utc;
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, Result};
+use crate::models::{Content, SeriesGroup};
+use crate::t;
+
+/// Repository for series group database operations.
+pub struct SeriesGroupRepository;
+
+impl SeriesGroupRepository {
+    /// Create a new series group.
+    pub async fn create(pool: &Pool<Sqlite>, name: &str) -> Result<SeriesGroup> {
+        let result = sqlx::query("INSERT INTO series_groups (name, created_at) VALUES (?, ?)")
+            .bind(name)
+            .bind(Utc::now().to_rfc3339())
+            .execute(pool)
+            .await
+            .map_err(|e| {
+                if e.to_string().contains("UNIQUE constraint failed") {
+                    AppError::BadRequest(t!("series_group.name_exists", name = name).to_string())
+                } else {
+                    AppError::Database(e)
+                }
+            })?;
+
+        Self::find_by_id(pool, result.last_insert_rowid())
+            .await?
+            .ok_or_else(|| {
+                AppError::Internal("Failed to retrieve created series group".to_string())
+            })
+    }
+
+    /// Find a series group by ID.
+    pub async fn find_by_id(pool: &Pool<Sqlite>, id: i64) -> Result<Option<SeriesGroup>> {
+        sqlx::query_as::<_, SeriesGroup>(
+            r#"
+            SELECT g.id, g.name, g.created_at,
+                (SELECT COUNT(*) FROM contents c
+                 WHERE c.series_group_id = g.id
+                   AND c.missing_since IS NULL AND c.merged_into IS NULL) AS content_count
+            FROM series_groups g
+            WHERE g.id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// List all series groups by name.
+    pub async fn list(pool: &Pool<Sqlite>) -> Result<Vec<SeriesGroup>> {
+        sqlx::query_as::<_, SeriesGroup>(
+            r#"
+            SELECT g.id, g.name, g.created_at,
+                (SELECT COUNT(*) FROM contents c
+                 WHERE c.series_group_id = g.id
+                   AND c.missing_since IS NULL AND c.merged_into IS NULL) AS content_count
+            FROM series_groups g
+            ORDER BY g.name
+            "#,
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Delete a series group. Its contents stay, outside any group.
+    /// Returns false if the group did not exist.
+    pub async fn delete(pool: &Pool<Sqlite>, id: i64) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM series_groups WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// List the contents of a series group outside the trash by title.
+    pub async fn list_contents(pool: &Pool<Sqlite>, id: i64) -> Result<Vec<Content>> {
+        sqlx::query_as::<_, Content>(
+            r#"
+            SELECT id, library_id, scan_path_id, title, folder_title, folder_path, chapter_count, thumbnail, thumbnail_etag, thumbnail_blurhash, metadata, missing_since, series_group_id, age_rating, created_at, updated_at
+            FROM contents
+            WHERE series_group_id = ? AND missing_since IS NULL AND merged_into IS NULL
+            ORDER BY title
+            "#,
+        )
+        .bind(id)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Put a content into a group, or take it out of its group with `None`.
+    pub async fn set_content_group(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        group_id: Option<i64>,
+    ) -> Result<()> {
+        sqlx::query("UPDATE contents SET series_group_id = ?, updated_at = ? WHERE id = ?")
+            .bind(group_id)
+            .bind(Utc::now().to_rfc3339())
+            .bind(content_id)
+            .execute(pool)
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+}
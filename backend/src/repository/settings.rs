@@ -0,0 +1,48 @@
+//! Runtime settings repository for database operations.
+//!
+//! Settings are stored one row per key, with the value encoded as JSON.
+
+use chrono::Utc;
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, Result};
+
+/// Repository for runtime settings database operations.
+pub struct SettingsRepository;
+
+impl SettingsRepository {
+    /// List every stored setting as `(key, JSON value)`.
+    pub async fn list(pool: &Pool<Sqlite>) -> Result<Vec<(String, String)>> {
+        sqlx::query_as::<_, (String, String)>("SELECT key, value FROM settings ORDER BY key")
+            .fetch_all(pool)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// Store the given `(key, JSON value)` pairs in one transaction,
+    /// replacing earlier values of the same keys.
+    pub async fn save(pool: &Pool<Sqlite>, values: &[(String, String)]) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        let mut tx = pool.begin().await.map_err(AppError::Database)?;
+
+        for (key, value) in values {
+            sqlx::query(
+                r#"
+                INSERT INTO settings (key, value, updated_at)
+                VALUES (?, ?, ?)
+                ON CONFLICT(key)
+                DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at
+                "#,
+            )
+            .bind(key)
+            .bind(value)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+        }
+
+        tx.commit().await.map_err(AppError::Database)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+//! Share link repository for database operations.
+
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, Result};
+use crate::models::ShareLink;
+
+const SHARE_COLUMNS: &str = "s.id, s.token, s.user_id, u.username, s.content_id, \
+    c.title AS content_title, s.chapter_id, s.expires_at, s.created_at";
+
+const SHARE_JOINS: &str = "JOIN users u ON u.id = s.user_id JOIN contents c ON c.id = s.content_id";
+
+/// Repository for share link database operations.
+pub struct ShareRepository;
+
+impl ShareRepository {
+    /// Create a share link.
+    pub async fn create(
+        pool: &Pool<Sqlite>,
+        token: &str,
+        user_id: i64,
+        content_id: i64,
+        chapter_id: Option<i64>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<ShareLink> {
+        sqlx::query(
+            r#"
+            INSERT INTO share_links
+                (token, user_id, content_id, chapter_id, expires_at, created_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(token)
+        .bind(user_id)
+        .bind(content_id)
+        .bind(chapter_id)
+        .bind(expires_at.to_rfc3339())
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Self::find_by_token(pool, token)
+            .await?
+            .ok_or_else(|| AppError::Internal("Failed to retrieve created share link".to_string()))
+    }
+
+    /// Find a share link by its token.
+    pub async fn find_by_token(pool: &Pool<Sqlite>, token: &str) -> Result<Option<ShareLink>> {
+        sqlx::query_as::<_, ShareLink>(&format!(
+            "SELECT {SHARE_COLUMNS} FROM share_links s {SHARE_JOINS} WHERE s.token = ?"
+        ))
+        .bind(token)
+        .fetch_optional(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// List share links, newest first, only those of one user if given.
+    pub async fn list(pool: &Pool<Sqlite>, user_id: Option<i64>) -> Result<Vec<ShareLink>> {
+        sqlx::query_as::<_, ShareLink>(&format!(
+            r#"
+            SELECT {SHARE_COLUMNS}
+            FROM share_links s {SHARE_JOINS}
+            WHERE ?1 IS NULL OR s.user_id = ?1
+            ORDER BY s.created_at DESC, s.id DESC
+            "#
+        ))
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Delete a share link, only if it belongs to the given user when one is
+    /// given. Returns whether it existed.
+    pub async fn delete(pool: &Pool<Sqlite>, id: i64, user_id: Option<i64>) -> Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM share_links WHERE id = ?1 AND (?2 IS NULL OR user_id = ?2)")
+                .bind(id)
+                .bind(user_id)
+                .execute(pool)
+                .await
+                .map_err(AppError::Database)?;
+        Ok(result.rows_affected() > 0)
+    }
+}
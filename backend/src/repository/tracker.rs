@@ -0,0 +1,375 @@
+//! Progress tracker repository.
+//!
+//! Linked accounts are kept in `tracker_accounts`, the chapter count last
+//! pushed for each series in `tracker_sync_state`, and the series users
+//! keep off their trackers in `tracker_opt_outs`.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, Result};
+use crate::models::{TrackedReadCount, TrackerAccount, TrackerKind, TrackerToken};
+
+/// Row of `tracker_accounts`.
+type TrackerAccountRow = (
+    i64,
+    String,
+    String,
+    String,
+    Option<String>,
+    Option<DateTime<Utc>>,
+    Option<DateTime<Utc>>,
+    Option<String>,
+    DateTime<Utc>,
+);
+
+const ACCOUNT_COLUMNS: &str = "user_id, tracker, remote_username, access_token, refresh_token, \
+    expires_at, last_synced_at, last_error, created_at";
+
+/// Repository for tracker database operations.
+pub struct TrackerRepository;
+
+impl TrackerRepository {
+    /// Find the account a user linked on a tracker.
+    pub async fn find_account(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        tracker: TrackerKind,
+    ) -> Result<Option<TrackerAccount>> {
+        let row: Option<TrackerAccountRow> = sqlx::query_as(&format!(
+            "SELECT {ACCOUNT_COLUMNS} FROM tracker_accounts WHERE user_id = ? AND tracker = ?"
+        ))
+        .bind(user_id)
+        .bind(tracker.as_str())
+        .fetch_optional(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(row.and_then(Self::from_row))
+    }
+
+    /// List linked accounts, of one user or of all users.
+    pub async fn list_accounts(
+        pool: &Pool<Sqlite>,
+        user_id: Option<i64>,
+    ) -> Result<Vec<TrackerAccount>> {
+        let rows: Vec<TrackerAccountRow> = sqlx::query_as(&format!(
+            r#"
+            SELECT {ACCOUNT_COLUMNS}
+            FROM tracker_accounts
+            WHERE ?1 IS NULL OR user_id = ?1
+            ORDER BY user_id, tracker
+            "#
+        ))
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(rows.into_iter().filter_map(Self::from_row).collect())
+    }
+
+    /// Link an account, replacing any account the user linked on the same
+    /// tracker. The next sync pushes all series again.
+    pub async fn upsert_account(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        tracker: TrackerKind,
+        remote_username: &str,
+        token: &TrackerToken,
+    ) -> Result<TrackerAccount> {
+        let mut tx = pool.begin().await.map_err(AppError::Database)?;
+
+        sqlx::query("DELETE FROM tracker_accounts WHERE user_id = ? AND tracker = ?")
+            .bind(user_id)
+            .bind(tracker.as_str())
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO tracker_accounts (
+                user_id, tracker, remote_username, access_token, refresh_token, expires_at,
+                created_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(user_id)
+        .bind(tracker.as_str())
+        .bind(remote_username)
+        .bind(&token.access_token)
+        .bind(&token.refresh_token)
+        .bind(token.expires_at.map(|expires_at| expires_at.to_rfc3339()))
+        .bind(Utc::now().to_rfc3339())
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+
+        tx.commit().await.map_err(AppError::Database)?;
+
+        Self::find_account(pool, user_id, tracker)
+            .await?
+            .ok_or_else(|| AppError::Internal("Failed to store tracker account".to_string()))
+    }
+
+    /// Replace the tokens of an account after refreshing them.
+    pub async fn update_token(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        tracker: TrackerKind,
+        token: &TrackerToken,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE tracker_accounts
+            SET access_token = ?, refresh_token = COALESCE(?, refresh_token), expires_at = ?
+            WHERE user_id = ? AND tracker = ?
+            "#,
+        )
+        .bind(&token.access_token)
+        .bind(&token.refresh_token)
+        .bind(token.expires_at.map(|expires_at| expires_at.to_rfc3339()))
+        .bind(user_id)
+        .bind(tracker.as_str())
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+
+    /// Unlink an account. Returns false if none was linked.
+    pub async fn delete_account(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        tracker: TrackerKind,
+    ) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM tracker_accounts WHERE user_id = ? AND tracker = ?")
+            .bind(user_id)
+            .bind(tracker.as_str())
+            .execute(pool)
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record the outcome of a sync: on success progress changed before
+    /// `synced_at` is done, on failure the error is kept and the same
+    /// progress is looked at again next time.
+    pub async fn finish_sync(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        tracker: TrackerKind,
+        synced_at: DateTime<Utc>,
+        error: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE tracker_accounts
+            SET last_synced_at = CASE WHEN ?1 IS NULL THEN ?2 ELSE last_synced_at END,
+                last_error = ?1
+            WHERE user_id = ?3 AND tracker = ?4
+            "#,
+        )
+        .bind(error)
+        .bind(synced_at.to_rfc3339())
+        .bind(user_id)
+        .bind(tracker.as_str())
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+
+    /// Count the chapters a user read to the end of each content whose
+    /// progress changed after `since`, or of every content when `since` is
+    /// not set.
+    pub async fn list_read_counts(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<TrackedReadCount>> {
+        sqlx::query_as::<_, TrackedReadCount>(
+            r#"
+            SELECT c.content_id,
+                COALESCE(SUM(rp.percentage >= 100.0), 0) AS read,
+                MAX(CASE WHEN rp.percentage >= 100.0 THEN c.number END) AS max_number
+            FROM reading_progress rp
+            INNER JOIN chapters c ON c.id = rp.chapter_id
+            WHERE rp.user_id = ?1
+            GROUP BY c.content_id
+            HAVING ?2 IS NULL OR MAX(julianday(rp.updated_at)) > julianday(?2)
+            ORDER BY c.content_id
+            "#,
+        )
+        .bind(user_id)
+        .bind(since.map(|since| since.to_rfc3339()))
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Remote ID and chapter count last pushed for each content, by
+    /// content ID.
+    pub async fn list_sync_state(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        tracker: TrackerKind,
+    ) -> Result<HashMap<i64, (String, i64)>> {
+        let rows: Vec<(i64, String, i64)> = sqlx::query_as(
+            r#"
+            SELECT content_id, remote_id, chapters_read
+            FROM tracker_sync_state
+            WHERE user_id = ? AND tracker = ?
+            "#,
+        )
+        .bind(user_id)
+        .bind(tracker.as_str())
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(content_id, remote_id, chapters_read)| (content_id, (remote_id, chapters_read)))
+            .collect())
+    }
+
+    /// Count the series whose progress was pushed to a tracker.
+    pub async fn count_synced(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        tracker: TrackerKind,
+    ) -> Result<i64> {
+        let (count,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM tracker_sync_state WHERE user_id = ? AND tracker = ?",
+        )
+        .bind(user_id)
+        .bind(tracker.as_str())
+        .fetch_one(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(count)
+    }
+
+    /// Record the chapter count pushed for a content.
+    pub async fn save_sync_state(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        tracker: TrackerKind,
+        content_id: i64,
+        remote_id: &str,
+        chapters_read: i64,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO tracker_sync_state
+                (user_id, tracker, content_id, remote_id, chapters_read, synced_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT(user_id, tracker, content_id) DO UPDATE SET
+                remote_id = excluded.remote_id,
+                chapters_read = excluded.chapters_read,
+                synced_at = excluded.synced_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(tracker.as_str())
+        .bind(content_id)
+        .bind(remote_id)
+        .bind(chapters_read)
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+
+    /// IDs of the contents a user keeps off their trackers.
+    pub async fn list_opt_outs(pool: &Pool<Sqlite>, user_id: i64) -> Result<Vec<i64>> {
+        let rows: Vec<(i64,)> = sqlx::query_as(
+            "SELECT content_id FROM tracker_opt_outs WHERE user_id = ? ORDER BY content_id",
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(rows.into_iter().map(|(content_id,)| content_id).collect())
+    }
+
+    /// Whether a user keeps a content off their trackers.
+    pub async fn is_opted_out(pool: &Pool<Sqlite>, user_id: i64, content_id: i64) -> Result<bool> {
+        let row: Option<(i64,)> = sqlx::query_as(
+            "SELECT content_id FROM tracker_opt_outs WHERE user_id = ? AND content_id = ?",
+        )
+        .bind(user_id)
+        .bind(content_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(row.is_some())
+    }
+
+    /// Keep a content off a user's trackers, or push it again.
+    pub async fn set_opt_out(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        content_id: i64,
+        opted_out: bool,
+    ) -> Result<()> {
+        let query = if opted_out {
+            sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO tracker_opt_outs (user_id, content_id, created_at)
+                VALUES (?, ?, ?)
+                "#,
+            )
+            .bind(user_id)
+            .bind(content_id)
+            .bind(Utc::now().to_rfc3339())
+        } else {
+            sqlx::query("DELETE FROM tracker_opt_outs WHERE user_id = ? AND content_id = ?")
+                .bind(user_id)
+                .bind(content_id)
+        };
+        query.execute(pool).await.map_err(AppError::Database)?;
+
+        Ok(())
+    }
+
+    fn from_row(row: TrackerAccountRow) -> Option<TrackerAccount> {
+        let (
+            user_id,
+            tracker,
+            remote_username,
+            access_token,
+            refresh_token,
+            expires_at,
+            last_synced_at,
+            last_error,
+            created_at,
+        ) = row;
+
+        // Trackers written by a newer version are left out
+        Some(TrackerAccount {
+            user_id,
+            tracker: TrackerKind::from_name(&tracker)?,
+            remote_username,
+            access_token,
+            refresh_token,
+            expires_at,
+            last_synced_at,
+            last_error,
+            created_at,
+        })
+    }
+}
@@ -2,12 +2,18 @@
 //!
 //! This module provides database access for user-related operations.
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use sqlx::{Pool, Sqlite};
-use rust_i18n::t;
 
-use crate::error::{AppError, Result};
-use crate::models::{NewUser, User};
+use crate::error::{AppError, ErrorCode, Result};
+use crate::models::{NewUser, RecoveryCode, User, UserIdentity};
+use crate::t;
+
+/// Password hash of the `admin` account seeded by the initial migration.
+/// While it is unchanged, anyone who knows the default password can log in,
+/// so the account is treated as not set up.
+pub const SEEDED_ADMIN_PASSWORD_HASH: &str =
+    "$argon2id$v=19$m=16,t=2,p=1$dGVzdHRlc3Q$e1JfAUgszO1txSZmW/Eu7w";
 
 /// Repository for user database operations.
 pub struct UserRepository;
@@ -55,7 +61,8 @@ impl UserRepository {
     pub async fn find_by_id(pool: &Pool<Sqlite>, id: i64) -> Result<Option<User>> {
         sqlx::query_as::<_, User>(
             r#"
-            SELECT id, username, password_hash, bangumi_api_key, created_at, updated_at
+            SELECT id, username, password_hash, bangumi_api_key, totp_secret, totp_enabled,
+                   is_admin, locale, max_age_rating, token_version, created_at, updated_at
             FROM users
             WHERE id = ?
             "#,
@@ -66,11 +73,55 @@ impl UserRepository {
         .map_err(AppError::Database)
     }
 
+    /// Find the preferred locale and token version of a user, checked
+    /// together on every authenticated request.
+    pub async fn find_session(
+        pool: &Pool<Sqlite>,
+        id: i64,
+    ) -> Result<Option<(Option<String>, i64)>> {
+        sqlx::query_as::<_, (Option<String>, i64)>(
+            "SELECT locale, token_version FROM users WHERE id = ?",
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Find the highest age rating a user may see, if it is capped.
+    pub async fn find_max_age_rating(pool: &Pool<Sqlite>, id: i64) -> Result<Option<i32>> {
+        sqlx::query_scalar::<_, Option<i32>>("SELECT max_age_rating FROM users WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+            .map(Option::flatten)
+            .map_err(AppError::Database)
+    }
+
+    /// Cap the age rating a user may see, or lift the cap with `None`.
+    /// Returns false when the user does not exist.
+    pub async fn set_max_age_rating(
+        pool: &Pool<Sqlite>,
+        id: i64,
+        max_age_rating: Option<i32>,
+    ) -> Result<bool> {
+        let result =
+            sqlx::query("UPDATE users SET max_age_rating = ?, updated_at = ? WHERE id = ?")
+                .bind(max_age_rating)
+                .bind(Utc::now().to_rfc3339())
+                .bind(id)
+                .execute(pool)
+                .await
+                .map_err(AppError::Database)?;
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Find a user by username.
     pub async fn find_by_username(pool: &Pool<Sqlite>, username: &str) -> Result<Option<User>> {
         sqlx::query_as::<_, User>(
             r#"
-            SELECT id, username, password_hash, bangumi_api_key, created_at, updated_at
+            SELECT id, username, password_hash, bangumi_api_key, totp_secret, totp_enabled,
+                   is_admin, locale, max_age_rating, token_version, created_at, updated_at
             FROM users
             WHERE username = ?
             "#,
@@ -89,6 +140,7 @@ impl UserRepository {
         username: Option<String>,
         password_hash: Option<String>,
         bangumi_api_key: Option<Option<String>>,
+        locale: Option<Option<String>>,
     ) -> Result<User> {
         use sqlx::Arguments;
         let mut query = "UPDATE users SET updated_at = ?".to_string();
@@ -101,7 +153,8 @@ impl UserRepository {
         }
 
         if let Some(p) = password_hash {
-            query.push_str(", password_hash = ?");
+            // Signs the user out of every other session
+            query.push_str(", password_hash = ?, token_version = token_version + 1");
             let _ = args.add(p);
         }
 
@@ -110,6 +163,11 @@ impl UserRepository {
             let _ = args.add(k_opt);
         }
 
+        if let Some(l_opt) = locale {
+            query.push_str(", locale = ?");
+            let _ = args.add(l_opt);
+        }
+
         query.push_str(" WHERE id = ?");
         let _ = args.add(user_id);
 
@@ -119,15 +177,17 @@ impl UserRepository {
             .map_err(|e| {
                 if e.to_string().contains("UNIQUE constraint failed") {
                     AppError::BadRequest(t!("auth.username_exists").to_string())
+                        .with_code(ErrorCode::UsernameTaken)
                 } else {
                     AppError::Database(e)
                 }
             })?;
 
         if result.rows_affected() == 0 {
-            return Err(AppError::NotFound(
-                t!("auth.user_not_found", id = user_id).to_string(),
-            ));
+            return Err(
+                AppError::NotFound(t!("auth.user_not_found", id = user_id).to_string())
+                    .with_code(ErrorCode::UserNotFound),
+            );
         }
 
         Self::find_by_id(pool, user_id)
@@ -135,6 +195,78 @@ impl UserRepository {
             .ok_or_else(|| AppError::Internal("Failed to retrieve updated user".to_string()))
     }
 
+    /// Whether no account has been set up: there are no users, or only the
+    /// seeded `admin` account with its initial password.
+    pub async fn setup_required(pool: &Pool<Sqlite>) -> Result<bool> {
+        sqlx::query_scalar(
+            r#"
+            SELECT NOT EXISTS (SELECT 1 FROM users WHERE password_hash != ?)
+            "#,
+        )
+        .bind(SEEDED_ADMIN_PASSWORD_HASH)
+        .fetch_one(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Create the first administrator if setup is still required, taking
+    /// over the seeded `admin` account when it is unchanged.
+    ///
+    /// Each statement checks the condition itself, so of concurrent calls
+    /// only one succeeds. Returns `None` when setup was already done.
+    pub async fn create_first_admin(
+        pool: &Pool<Sqlite>,
+        new_user: NewUser,
+        locale: Option<&str>,
+    ) -> Result<Option<User>> {
+        let now = Utc::now().to_rfc3339();
+
+        let updated = sqlx::query(
+            r#"
+            UPDATE users
+            SET username = ?2, password_hash = ?3, is_admin = 1, locale = ?4, updated_at = ?5
+            WHERE password_hash = ?1
+              AND NOT EXISTS (SELECT 1 FROM users WHERE password_hash != ?1)
+            "#,
+        )
+        .bind(SEEDED_ADMIN_PASSWORD_HASH)
+        .bind(&new_user.username)
+        .bind(&new_user.password_hash)
+        .bind(locale)
+        .bind(&now)
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?
+        .rows_affected();
+
+        let inserted = if updated == 0 {
+            sqlx::query(
+                r#"
+                INSERT INTO users
+                    (username, password_hash, is_admin, locale, created_at, updated_at)
+                SELECT ?, ?, 1, ?, ?, ?
+                WHERE NOT EXISTS (SELECT 1 FROM users)
+                "#,
+            )
+            .bind(&new_user.username)
+            .bind(&new_user.password_hash)
+            .bind(locale)
+            .bind(&now)
+            .bind(&now)
+            .execute(pool)
+            .await
+            .map_err(AppError::Database)?
+            .rows_affected()
+        } else {
+            0
+        };
+
+        if updated == 0 && inserted == 0 {
+            return Ok(None);
+        }
+        Self::find_by_username(pool, &new_user.username).await
+    }
+
     /// Check if a username already exists.
     pub async fn username_exists(pool: &Pool<Sqlite>, username: &str) -> Result<bool> {
         let result: (i64,) = sqlx::query_as(
@@ -149,4 +281,253 @@ impl UserRepository {
 
         Ok(result.0 > 0)
     }
+
+    /// Store or clear a user's TOTP secret, forgetting the last accepted
+    /// time step.
+    pub async fn set_totp_secret(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        secret: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE users SET totp_secret = ?, totp_last_step = NULL, updated_at = ? WHERE id = ?",
+        )
+        .bind(secret)
+        .bind(Utc::now().to_rfc3339())
+        .bind(user_id)
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+
+    /// Record the time step of an accepted TOTP code. Returns false when a
+    /// code from this step or a later one was already accepted.
+    pub async fn accept_totp_step(pool: &Pool<Sqlite>, user_id: i64, step: i64) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE users SET totp_last_step = ?
+            WHERE id = ? AND (totp_last_step IS NULL OR totp_last_step < ?)
+            "#,
+        )
+        .bind(step)
+        .bind(user_id)
+        .bind(step)
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Enable or disable TOTP for a user.
+    pub async fn set_totp_enabled(pool: &Pool<Sqlite>, user_id: i64, enabled: bool) -> Result<()> {
+        sqlx::query("UPDATE users SET totp_enabled = ?, updated_at = ? WHERE id = ?")
+            .bind(enabled)
+            .bind(Utc::now().to_rfc3339())
+            .bind(user_id)
+            .execute(pool)
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+}
+
+/// Repository for two-factor recovery codes.
+pub struct RecoveryCodeRepository;
+
+impl RecoveryCodeRepository {
+    /// Replace all recovery codes of a user with a new set of hashes.
+    pub async fn replace_for_user(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        code_hashes: Vec<String>,
+    ) -> Result<()> {
+        let mut tx = pool.begin().await.map_err(AppError::Database)?;
+        let now = Utc::now().to_rfc3339();
+
+        sqlx::query("DELETE FROM user_recovery_codes WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+
+        for code_hash in code_hashes {
+            sqlx::query(
+                r#"
+                INSERT INTO user_recovery_codes (user_id, code_hash, created_at)
+                VALUES (?, ?, ?)
+                "#,
+            )
+            .bind(user_id)
+            .bind(&code_hash)
+            .bind(&now)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+        }
+
+        tx.commit().await.map_err(AppError::Database)?;
+
+        Ok(())
+    }
+
+    /// List the recovery codes of a user that have not been used yet.
+    pub async fn list_unused(pool: &Pool<Sqlite>, user_id: i64) -> Result<Vec<RecoveryCode>> {
+        sqlx::query_as::<_, RecoveryCode>(
+            r#"
+            SELECT id, user_id, code_hash, used_at, created_at
+            FROM user_recovery_codes
+            WHERE user_id = ? AND used_at IS NULL
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Mark a recovery code as used.
+    ///
+    /// Returns false if the code was already used concurrently.
+    pub async fn mark_used(pool: &Pool<Sqlite>, id: i64) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE user_recovery_codes SET used_at = ? WHERE id = ? AND used_at IS NULL",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(id)
+        .execute(pool)
+        .await
+        .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Delete all recovery codes of a user.
+    pub async fn delete_by_user(pool: &Pool<Sqlite>, user_id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM user_recovery_codes WHERE user_id = ?")
+            .bind(user_id)
+            .execute(pool)
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(())
+    }
+}
+
+/// Repository for external identities linked to users.
+pub struct UserIdentityRepository;
+
+impl UserIdentityRepository {
+    /// Find the identity for an issuer and subject.
+    pub async fn find(
+        pool: &Pool<Sqlite>,
+        issuer: &str,
+        subject: &str,
+    ) -> Result<Option<UserIdentity>> {
+        sqlx::query_as::<_, UserIdentity>(
+            r#"
+            SELECT id, user_id, issuer, subject, created_at
+            FROM user_identities
+            WHERE issuer = ? AND subject = ?
+            "#,
+        )
+        .bind(issuer)
+        .bind(subject)
+        .fetch_optional(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Link an external identity to a user.
+    pub async fn create(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        issuer: &str,
+        subject: &str,
+    ) -> Result<()> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO user_identities (user_id, issuer, subject, created_at)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(user_id)
+        .bind(issuer)
+        .bind(subject)
+        .bind(Utc::now().to_rfc3339())
+        .execute(pool)
+        .await;
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) if e.to_string().contains("UNIQUE constraint failed") => Err(
+                AppError::BadRequest(t!("oidc.identity_already_linked").to_string()),
+            ),
+            Err(e) => Err(AppError::Database(e)),
+        }
+    }
+}
+
+/// Repository for one-time password reset tokens.
+pub struct PasswordResetRepository;
+
+impl PasswordResetRepository {
+    /// Replace the reset tokens of a user with a new one.
+    pub async fn replace_for_user(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        token: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<()> {
+        let mut tx = pool.begin().await.map_err(AppError::Database)?;
+
+        sqlx::query("DELETE FROM password_reset_tokens WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::Database)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO password_reset_tokens (token, user_id, expires_at, created_at)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(token)
+        .bind(user_id)
+        .bind(expires_at.to_rfc3339())
+        .bind(Utc::now().to_rfc3339())
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::Database)?;
+
+        tx.commit().await.map_err(AppError::Database)?;
+
+        Ok(())
+    }
+
+    /// Find the user and expiry of a reset token.
+    pub async fn find(pool: &Pool<Sqlite>, token: &str) -> Result<Option<(i64, DateTime<Utc>)>> {
+        sqlx::query_as::<_, (i64, DateTime<Utc>)>(
+            "SELECT user_id, expires_at FROM password_reset_tokens WHERE token = ?",
+        )
+        .bind(token)
+        .fetch_optional(pool)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// Delete a reset token. Returns false when it was already used.
+    pub async fn consume(pool: &Pool<Sqlite>, token: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM password_reset_tokens WHERE token = ?")
+            .bind(token)
+            .execute(pool)
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
 }
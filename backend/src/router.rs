@@ -3,25 +3,31 @@
 //! This module provides the router configuration for the Axum web server.
 
 use axum::{
-    Router, middleware,
-    routing::{delete, get, post},
+    Router,
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{any, delete, get, patch, post, put},
 };
-use tower_http::{
-    cors::{Any, CorsLayer},
-    trace::{DefaultMakeSpan, DefaultOnRequest, TraceLayer},
-};
-use tracing::Level;
 
 use crate::handlers::{
-    apikey, auth, content, filesystem, komga, library, progress, scan_queue, static_files,
+    admin, apikey, auth, bookmark, capabilities, content, dashboard, ext, filesystem, import, jobs,
+    kobo, komga, koreader, library, maintenance, metadata, oidc, openapi, preferences, progress,
+    reader_settings, release, review, scan_queue, series_group, setup, share, static_files,
+    tracker,
+};
+use crate::middlewares::{
+    auth_middleware, client_ip_middleware, kobo_auth_middleware, komga_enabled_middleware,
+    koreader_auth_middleware, locale_middleware, login_rate_limit_middleware, quota_middleware,
+    request_id_middleware, security_headers_middleware,
 };
-use crate::middlewares::auth_middleware;
 use crate::state::AppState;
 
 /// Create the application router with all routes configured.
 ///
 /// This function separates routes into public and protected groups:
-/// - Public routes: /api/auth/login (no authentication required)
+/// - Public routes: /api/auth/login, password resets, the OIDC flow, tracker callbacks,
+///   /api/capabilities, /api/openapi.json and share links under
+///   /api/shared (no authentication required)
 /// - Protected routes: All other routes (require authentication via middleware)
 ///
 /// # Arguments
@@ -35,13 +41,50 @@ use crate::state::AppState;
 /// - 4.2: Process requests without authentication for routes without middleware
 /// - 4.3: Support nesting routers with and without authentication
 pub fn create_router(state: AppState) -> Router {
+    let timeouts = state.request_timeouts;
+    let max_restore_bytes =
+        state.backup_service.config().max_restore_size_mb as usize * 1024 * 1024;
+
     // Public routes - no authentication required
-    let public_routes = Router::new().route("/api/auth/login", post(auth::login));
+    let public_routes = Router::new()
+        .route("/api/auth/login", post(auth::login))
+        .route("/api/auth/login/2fa", post(auth::login_two_factor))
+        .route("/api/auth/password-reset", post(auth::reset_password))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            login_rate_limit_middleware,
+        ))
+        .route("/api/auth/oidc/login", get(oidc::login))
+        .route("/api/auth/oidc/callback", get(oidc::callback))
+        .route("/api/trackers/{tracker}/callback", get(tracker::callback))
+        .route("/api/capabilities", get(capabilities::get_capabilities))
+        .route("/api/openapi.json", get(openapi::get_openapi))
+        .route("/api/setup/status", get(setup::get_status))
+        .route("/api/setup/admin", post(setup::create_admin))
+        .route("/api/shared/{token}", get(share::get_shared))
+        .route(
+            "/api/shared/{token}/thumbnail",
+            get(share::get_shared_thumbnail),
+        )
+        .route(
+            "/api/shared/{token}/chapters/{chapter_id}/pages",
+            get(share::list_shared_pages),
+        )
+        .route(
+            "/api/shared/{token}/chapters/{chapter_id}/pages/{page}",
+            get(share::get_shared_page).layer(timeouts.page_layer()),
+        );
 
     // Komga compatibility routes - no authentication for now
     let komga_routes = Router::new()
         .route("/komga/api/v1/series", get(komga::get_series_list))
+        .route("/komga/api/v1/series/new", get(komga::get_series_new))
+        .route("/komga/api/v1/series/latest", get(komga::get_series_latest))
         .route("/komga/api/v1/series/{seriesId}", get(komga::get_series))
+        .route(
+            "/komga/api/v1/series/{seriesId}/metadata",
+            patch(komga::patch_series_metadata),
+        )
         .route(
             "/komga/api/v1/series/{seriesId}/thumbnail",
             get(komga::get_series_thumbnail),
@@ -55,24 +98,113 @@ pub fn create_router(state: AppState) -> Router {
             "/komga/api/v1/books/{bookId}/thumbnail",
             get(komga::get_book_thumbnail),
         )
+        .route(
+            "/komga/api/v1/books/{bookId}/file",
+            get(komga::get_book_file),
+        )
         .route(
             "/komga/api/v1/books/{bookId}/pages",
             get(komga::get_page_list),
         )
         .route(
             "/komga/api/v1/books/{bookId}/pages/{pageNumber}",
-            get(komga::get_page),
+            get(komga::get_page).layer(timeouts.page_layer()),
         )
         .route("/komga/api/v1/libraries", get(komga::get_libraries))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            quota_middleware,
+        ))
         .layer(middleware::from_fn_with_state(
             state.clone(),
             auth_middleware,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            komga_enabled_middleware,
         ));
 
+    // Kobo sync routes - authenticated by the API key in the path
+    let kobo_routes = Router::new()
+        .route("/kobo/{key}/v1/initialization", get(kobo::initialization))
+        .route("/kobo/{key}/v1/auth/device", post(kobo::auth_device))
+        .route("/kobo/{key}/v1/library/sync", get(kobo::library_sync))
+        .route(
+            "/kobo/{key}/v1/library/{book_id}",
+            delete(kobo::archive_book),
+        )
+        .route(
+            "/kobo/{key}/v1/library/{book_id}/metadata",
+            get(kobo::get_metadata),
+        )
+        .route(
+            "/kobo/{key}/v1/library/{book_id}/state",
+            get(kobo::get_state).put(kobo::update_state),
+        )
+        .route("/kobo/{key}/v1/{*rest}", any(kobo::fallback))
+        .route(
+            "/kobo/{key}/download/{book_id}/{format}",
+            get(kobo::download),
+        )
+        .route(
+            "/kobo/{key}/{image_id}/{width}/{height}/{greyscale}/image.jpg",
+            get(kobo::get_cover),
+        )
+        .route(
+            "/kobo/{key}/{image_id}/{width}/{height}/{quality}/{greyscale}/image.jpg",
+            get(kobo::get_cover),
+        )
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            quota_middleware,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            kobo_auth_middleware,
+        ));
+
+    // KOReader sync routes - authenticated by the device's credentials,
+    // except registering which checks them itself
+    let koreader_routes = Router::new()
+        .route("/koreader/users/auth", get(koreader::authorize))
+        .route("/koreader/syncs/progress", put(koreader::update_progress))
+        .route(
+            "/koreader/syncs/progress/{document}",
+            get(koreader::get_progress),
+        )
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            quota_middleware,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            koreader_auth_middleware,
+        ))
+        .route("/koreader/users/create", post(koreader::create_user));
+
     // Protected routes - authentication required
     let protected_routes = Router::new()
         // Auth routes (except login)
         .route("/api/auth/me", get(auth::get_me).put(auth::update_me))
+        .route("/api/auth/2fa/setup", post(auth::setup_totp))
+        .route("/api/auth/2fa/enable", post(auth::enable_totp))
+        .route("/api/auth/2fa/disable", post(auth::disable_totp))
+        .route(
+            "/api/auth/2fa/recovery-codes",
+            post(auth::regenerate_recovery_codes),
+        )
+        .route("/api/auth/oidc/link", post(oidc::link))
+        .route("/api/users/me/password", post(auth::change_password))
+        .route(
+            "/api/users/me/preferences",
+            get(preferences::get_preferences).patch(preferences::update_preferences),
+        )
+        .route("/api/users/me/updates", get(release::list_updates))
+        .route("/api/users/me/trackers", get(tracker::get_status))
+        .route("/api/users/me/trackers/sync", post(tracker::sync))
+        .route("/api/users/me/trackers/{tracker}", delete(tracker::unlink))
+        .route("/api/users/me/trackers/{tracker}/link", post(tracker::link))
+        .route("/api/users/me/trackers/{tracker}/pull", post(tracker::pull))
         // Library routes
         .route("/api/libraries", get(library::list).post(library::create))
         .route(
@@ -89,25 +221,112 @@ pub fn create_router(state: AppState) -> Router {
             "/api/libraries/{library_id}/paths/{path_id}",
             delete(library::remove_path),
         )
+        .route(
+            "/api/libraries/{library_id}/ignore-patterns",
+            get(library::list_ignore_patterns).put(library::set_ignore_patterns),
+        )
+        .route(
+            "/api/libraries/{library_id}/metadata-providers",
+            get(library::list_metadata_providers).put(library::set_metadata_providers),
+        )
+        .route(
+            "/api/libraries/{library_id}/settings",
+            get(reader_settings::get_library_settings)
+                .patch(reader_settings::update_library_settings),
+        )
         .route("/api/libraries/{library_id}/contents", get(content::list))
         .route(
             "/api/libraries/{library_id}/scan",
             post(scan_queue::submit_scan),
         )
+        .route(
+            "/api/libraries/{library_id}/scan/preview",
+            post(scan_queue::preview_scan),
+        )
+        .route(
+            "/api/libraries/{library_id}/thumbnails/regenerate",
+            post(scan_queue::regenerate_thumbnails),
+        )
         .route("/api/libraries/{library_id}/search", get(content::search))
+        .route("/api/libraries/{library_id}/export", get(library::export))
         // Scan queue routes
-        .route("/api/scan-tasks", get(scan_queue::list_tasks))
+        .route(
+            "/api/scan-tasks",
+            get(scan_queue::list_tasks).post(scan_queue::submit_scan_path_scan),
+        )
+        .route(
+            "/api/scan-tasks/history",
+            get(scan_queue::list_task_history),
+        )
         .route(
             "/api/scan-tasks/{task_id}",
             get(scan_queue::get_task).delete(scan_queue::cancel_task),
         )
+        .route(
+            "/api/scan-tasks/{task_id}/move-to-front",
+            post(scan_queue::move_task_to_front),
+        )
+        .route("/api/scan-queue/state", get(scan_queue::get_queue_state))
+        .route("/api/scan-queue/pause", post(scan_queue::pause_queue))
+        .route("/api/scan-queue/resume", post(scan_queue::resume_queue))
+        .route("/api/scan-queue/clear", post(scan_queue::clear_queue))
+        // Background job routes
+        .route("/api/jobs", get(jobs::list_jobs))
+        .route("/api/jobs/{job_id}", get(jobs::get_job))
+        .route("/api/jobs/{job_id}/cancel", post(jobs::cancel_job))
+        .route("/api/jobs/{job_id}/retry", post(jobs::retry_job))
+        .route("/api/jobs/{job_id}/priority", put(jobs::set_job_priority))
+        // Dashboard route
+        .route("/api/dashboard", get(dashboard::get))
         // Content routes
+        .route("/api/contents/recent", get(content::list_recent))
+        .route("/api/contents/updated", get(content::list_updated))
+        .route("/api/contents/status", get(content::list_statuses))
+        .route("/api/contents/thumbnails", post(content::get_thumbnails))
+        .route(
+            "/api/contents/trash",
+            get(content::list_trash).delete(content::purge_trash),
+        )
         .route(
             "/api/contents/{content_id}",
             get(content::get)
                 .put(content::update)
                 .delete(content::delete),
         )
+        .route("/api/contents/{content_id}/restore", post(content::restore))
+        .route(
+            "/api/contents/{content_id}/move",
+            post(content::move_content),
+        )
+        .route(
+            "/api/contents/{content_id}/series-group",
+            put(series_group::set_content_group),
+        )
+        .route(
+            "/api/contents/{content_id}/status",
+            get(content::get_status).put(content::update_status),
+        )
+        .route(
+            "/api/contents/{content_id}/settings",
+            get(reader_settings::get_content_settings)
+                .patch(reader_settings::update_content_settings),
+        )
+        .route(
+            "/api/contents/{content_id}/metadata",
+            get(metadata::get_edits).patch(metadata::update_edits),
+        )
+        .route(
+            "/api/contents/{content_id}/metadata/match",
+            post(metadata::match_content),
+        )
+        .route(
+            "/api/contents/{content_id}/aliases",
+            get(metadata::list_aliases).post(metadata::add_alias),
+        )
+        .route(
+            "/api/contents/{content_id}/aliases/{alias_id}",
+            delete(metadata::delete_alias),
+        )
         .route(
             "/api/contents/{content_id}/thumbnail",
             get(content::get_thumbnail),
@@ -120,14 +339,95 @@ pub fn create_router(state: AppState) -> Router {
             "/api/contents/{content_id}/progress",
             get(progress::get_content_progress),
         )
+        .route(
+            "/api/contents/{content_id}/tracking",
+            get(tracker::get_tracking).put(tracker::update_tracking),
+        )
+        .route(
+            "/api/contents/{content_id}/reviews",
+            get(review::list_reviews),
+        )
+        .route(
+            "/api/contents/{content_id}/reviews/me",
+            get(review::get_my_review)
+                .put(review::save_my_review)
+                .delete(review::delete_my_review),
+        )
+        .route(
+            "/api/contents/{content_id}/shares",
+            post(share::create_share),
+        )
+        .route(
+            "/api/contents/{content_id}/chapters/{chapter_id}/pages",
+            get(content::list_pages),
+        )
         .route(
             "/api/contents/{content_id}/chapters/{chapter_id}/pages/{page}",
-            get(content::get_page),
+            get(content::get_page).layer(timeouts.page_layer()),
+        )
+        .route(
+            "/api/contents/{content_id}/search",
+            get(content::search_text),
         )
         .route(
             "/api/contents/{content_id}/chapters/{chapter_id}/text",
             get(content::get_chapter_text),
         )
+        .route(
+            "/api/contents/{content_id}/chapters/{chapter_id}/epub",
+            get(content::get_epub_manifest),
+        )
+        .route(
+            "/api/contents/{content_id}/chapters/{chapter_id}/epub/sections/{index}",
+            get(content::get_epub_section),
+        )
+        .route(
+            "/api/contents/{content_id}/chapters/{chapter_id}/epub/resources/{*path}",
+            get(content::get_epub_resource),
+        )
+        .route(
+            "/api/contents/{content_id}/chapters/{chapter_id}/splits",
+            get(content::list_txt_splits),
+        )
+        .route(
+            "/api/contents/{content_id}/chapters/{chapter_id}/splits/{index}",
+            get(content::get_txt_split),
+        )
+        .route(
+            "/api/contents/{content_id}/chapters/{chapter_id}/audio",
+            get(content::get_audio_info),
+        )
+        .route(
+            "/api/contents/{content_id}/chapters/{chapter_id}/audio/stream",
+            get(content::stream_audio),
+        )
+        .route(
+            "/api/contents/{content_id}/chapters/{chapter_id}/page-errors",
+            get(content::list_page_errors).delete(content::clear_page_errors),
+        )
+        .route(
+            "/api/contents/{content_id}/download",
+            get(content::download_series),
+        )
+        .route(
+            "/api/contents/{content_id}/chapters/{chapter_id}/download",
+            get(content::download_chapter),
+        )
+        // Metadata routes
+        .route("/api/metadata/search", get(metadata::search))
+        .route("/api/metadata/failures", get(metadata::list_failures))
+        .route(
+            "/api/metadata/failures/{failure_id}",
+            delete(metadata::dismiss_failure),
+        )
+        .route(
+            "/api/metadata/failures/{failure_id}/retry",
+            post(metadata::retry_failure),
+        )
+        .route(
+            "/api/metadata/bangumi/check-key",
+            post(metadata::check_bangumi_key),
+        )
         // Progress routes
         .route("/api/progress/recent", get(progress::get_recent_progress))
         // Chapter progress routes
@@ -135,30 +435,161 @@ pub fn create_router(state: AppState) -> Router {
             "/api/chapters/{chapter_id}/progress",
             get(progress::get_chapter_progress).put(progress::update_chapter_progress),
         )
+        .route(
+            "/api/chapters/{chapter_id}/splits/progress",
+            get(progress::get_split_progress),
+        )
+        .route(
+            "/api/chapters/{chapter_id}/splits/{index}/progress",
+            put(progress::update_split_progress),
+        )
+        // Series group routes
+        .route(
+            "/api/series-groups",
+            get(series_group::list).post(series_group::create),
+        )
+        .route(
+            "/api/series-groups/{group_id}",
+            get(series_group::get).delete(series_group::delete),
+        )
+        .route(
+            "/api/series-groups/{group_id}/contents",
+            get(series_group::list_contents),
+        )
+        // Bookmark routes
+        .route("/api/bookmarks", get(bookmark::list_bookmarks))
+        .route(
+            "/api/bookmarks/{bookmark_id}",
+            put(bookmark::update_bookmark).delete(bookmark::delete_bookmark),
+        )
+        .route(
+            "/api/chapters/{chapter_id}/bookmarks",
+            post(bookmark::create_bookmark),
+        )
+        .route(
+            "/api/contents/{content_id}/bookmarks",
+            get(bookmark::list_content_bookmarks),
+        )
         // API Key routes
         .route(
             "/api/api-keys",
             get(apikey::list_api_keys).post(apikey::create_api_key),
         )
         .route("/api/api-keys/{id}", delete(apikey::delete_api_key))
+        // Share link routes
+        .route("/api/shares", get(share::list_my_shares))
+        .route("/api/shares/{id}", delete(share::revoke_share))
         // Filesystem routes
         .route("/api/filesystem", get(filesystem::list_directories))
-        // Apply authentication middleware to all protected routes
+        // Extension API
+        .route("/api/ext/v1", get(ext::get_info))
+        .route("/api/ext/v1/series", get(ext::list_series))
+        .route("/api/ext/v1/series/{id}", get(ext::get_series))
+        .route("/api/ext/v1/series/{id}/chapters", get(ext::list_chapters))
+        .route("/api/ext/v1/chapters/{id}/pages", get(ext::list_pages))
+        // Admin routes
+        .route("/api/admin/audit", get(admin::list_audit_log))
+        .route(
+            "/api/admin/backups",
+            get(admin::list_backups).post(admin::create_backup),
+        )
+        .route("/api/admin/backups/{name}", get(admin::download_backup))
+        .route(
+            "/api/admin/settings",
+            get(admin::get_settings).patch(admin::update_settings),
+        )
+        .route("/api/admin/shares", get(admin::list_share_links))
+        .route("/api/admin/shares/{id}", delete(admin::revoke_share_link))
+        .route(
+            "/api/admin/users/{id}/age-rating",
+            put(admin::set_user_age_rating),
+        )
+        .route(
+            "/api/admin/users/{id}/password-reset",
+            post(admin::create_password_reset),
+        )
+        .route(
+            "/api/admin/users/{id}/quota",
+            get(admin::get_user_quota).put(admin::set_user_quota),
+        )
+        // Maintenance routes
+        .route(
+            "/api/maintenance/duplicates",
+            get(maintenance::list_duplicates),
+        )
+        .route(
+            "/api/maintenance/duplicates/merge",
+            post(maintenance::merge_duplicates),
+        )
+        .route(
+            "/api/maintenance/media-errors",
+            get(maintenance::list_media_errors),
+        )
+        .route(
+            "/api/maintenance/page-counts",
+            post(maintenance::recalculate_page_counts),
+        )
+        .route(
+            "/api/maintenance/metadata-refresh",
+            post(maintenance::refresh_metadata),
+        )
+        .route("/api/maintenance/backup", post(maintenance::submit_backup));
+
+    // GraphQL API, only in builds with the `graphql` feature
+    #[cfg(feature = "graphql")]
+    let protected_routes =
+        protected_routes.route("/api/graphql", post(crate::handlers::graphql::execute));
+
+    // Apply authentication and quota middleware to all protected routes
+    let protected_routes = protected_routes
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            quota_middleware,
+        ))
         .layer(middleware::from_fn_with_state(
             state.clone(),
             auth_middleware,
         ));
 
+    // Upload routes - large bodies with their own size limit and timeout
+    let upload_routes = Router::new()
+        .route("/api/libraries/{library_id}/import", post(library::import))
+        .route("/api/import/tachiyomi", post(import::import_tachiyomi))
+        .route("/api/import/{source}", post(import::import_server))
+        .route("/api/admin/backups/restore", post(admin::restore_backup))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            quota_middleware,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth_middleware,
+        ))
+        .layer(DefaultBodyLimit::max(max_restore_bytes))
+        .layer(timeouts.upload_layer());
+
     // Merge public and protected routers
     let api_router = Router::new()
         .merge(public_routes)
         .merge(komga_routes)
-        .merge(protected_routes);
+        .merge(kobo_routes)
+        .merge(koreader_routes)
+        .merge(protected_routes)
+        .layer(state.http_limits.body_limit_layer())
+        .layer(timeouts.api_layer())
+        .merge(upload_routes);
 
-    Router::new()
-        .merge(api_router)
+    let static_routes = Router::new()
         .route("/", get(static_files::serve_index))
         .route("/{*path}", get(static_files::serve_static))
+        .layer(middleware::from_fn_with_state(
+            state.security_headers.static_file_headers(),
+            security_headers_middleware,
+        ));
+
+    Router::new()
+        .merge(api_router)
+        .merge(static_routes)
         .with_state(state)
 }
 
@@ -170,34 +601,30 @@ pub fn create_router(state: AppState) -> Router {
 /// # Returns
 /// A configured Axum router with Layers enabled
 pub fn create_router_with_layers(state: AppState) -> Router {
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+    let cors = state.cors.layer();
+    let security_headers = state.security_headers.headers();
+    let compression = state.http_limits.compression_layer();
+    let client_ip_rules = state.client_ip_rules.clone();
 
-    let router = create_router(state).layer(cors);
+    let router = create_router(state)
+        .layer(middleware::from_fn_with_state(
+            client_ip_rules,
+            client_ip_middleware,
+        ))
+        .layer(compression)
+        .layer(cors)
+        .layer(middleware::from_fn_with_state(
+            security_headers,
+            security_headers_middleware,
+        ))
+        // Translate messages into the locale of each request
+        .layer(middleware::from_fn(locale_middleware));
 
-    // Add tracing layer with request logging always enabled
-    // Response logging only when dev feature is enabled
+    // Assign request IDs and log every request when it completes
     add_tracing_layer(router)
 }
 
-/// Add tracing layer with request logging only (no response logging in production).
+/// Add the request ID layer, which also logs method, path, status and latency.
 fn add_tracing_layer(router: Router) -> Router {
-    use tower_http::{LatencyUnit, trace::DefaultOnFailure};
-
-    let tracing = TraceLayer::new_for_http()
-        .on_request(DefaultOnRequest::new().level(Level::INFO))
-        .on_failure(
-            DefaultOnFailure::new()
-                .level(Level::WARN)
-                .latency_unit(LatencyUnit::Millis),
-        )
-        .make_span_with(
-            DefaultMakeSpan::new()
-                .level(Level::ERROR)
-                .include_headers(false),
-        );
-
-    router.layer(tracing)
+    router.layer(middleware::from_fn(request_id_middleware))
 }
@@ -0,0 +1,53 @@
+//! Alternative title service.
+//!
+//! Titles scraped from metadata are kept in sync by the content repository;
+//! this service manages the ones added by hand.
+
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, Result};
+use crate::models::{ContentAlias, CreateAliasRequest, MAX_ALIAS_LEN};
+use crate::repository::alias::AliasRepository;
+use crate::services::content::ContentService;
+use crate::t;
+
+/// Service for alternative titles of contents.
+pub struct AliasService;
+
+impl AliasService {
+    /// List the alternative titles of a content.
+    pub async fn list(pool: &Pool<Sqlite>, content_id: i64) -> Result<Vec<ContentAlias>> {
+        ContentService::get_content(pool, content_id).await?;
+        AliasRepository::list_by_content(pool, content_id).await
+    }
+
+    /// Add an alternative title to a content by hand.
+    pub async fn add(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        request: CreateAliasRequest,
+    ) -> Result<ContentAlias> {
+        let title = request.title.trim();
+        if title.is_empty() {
+            return Err(AppError::BadRequest(t!("alias.empty").to_string()));
+        }
+        if title.chars().count() > MAX_ALIAS_LEN {
+            return Err(AppError::BadRequest(
+                t!("alias.too_long", max = MAX_ALIAS_LEN).to_string(),
+            ));
+        }
+
+        ContentService::get_content(pool, content_id).await?;
+        AliasRepository::add_manual(pool, content_id, title).await
+    }
+
+    /// Remove an alternative title from a content.
+    pub async fn delete(pool: &Pool<Sqlite>, content_id: i64, id: i64) -> Result<()> {
+        if !AliasRepository::delete(pool, content_id, id).await? {
+            return Err(AppError::NotFound(
+                t!("alias.not_found", id = id).to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,387 @@
+//! AniList metadata service.
+//!
+//! This module searches and fetches manga from the AniList GraphQL API and
+//! converts them to the Bangumi-shaped metadata used throughout the app.
+
+use reqwest::Client;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+
+use crate::error::{AppError, Result};
+use crate::models::{MetadataProviderKind, MetadataSearchResult};
+use crate::services::metadata::{MetadataProvider, ProviderFuture, tag_source};
+use crate::t;
+
+/// Endpoint of the AniList GraphQL API.
+const ANILIST_API: &str = "https://graphql.anilist.co";
+
+/// User agent for API requests.
+const USER_AGENT: &str = "ryuri/0.1.1 (https://github.com/tnzzzhlp/ryuri)";
+
+const SEARCH_QUERY: &str = r#"
+query ($search: String) {
+  Page(perPage: 10) {
+    media(search: $search, type: MANGA) {
+      id
+      title { romaji english native }
+      description(asHtml: false)
+      coverImage { extraLarge large }
+    }
+  }
+}
+"#;
+
+const MEDIA_QUERY: &str = r#"
+query ($id: Int) {
+  Media(id: $id, type: MANGA) {
+    id
+    idMal
+    title { romaji english native }
+    description(asHtml: false)
+    startDate { year month day }
+    genres
+    tags { name rank }
+    staff(perPage: 10) { edges { role node { name { full } } } }
+    coverImage { extraLarge large }
+    volumes
+    averageScore
+    isAdult
+    siteUrl
+  }
+}
+"#;
+
+/// GraphQL response envelope.
+#[derive(Debug, Deserialize)]
+struct GraphQlResponse<T> {
+    data: Option<T>,
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQlError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchData {
+    #[serde(rename = "Page")]
+    page: SearchPage,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchPage {
+    #[serde(default)]
+    media: Vec<AniListMedia>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MediaData {
+    #[serde(rename = "Media")]
+    media: Option<AniListMedia>,
+}
+
+/// A manga from the AniList API. Fields only requested by the detail query
+/// are empty in search results.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AniListMedia {
+    id: i64,
+    /// ID of the same manga on MyAnimeList.
+    #[serde(default)]
+    id_mal: Option<i64>,
+    title: AniListTitle,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    start_date: Option<FuzzyDate>,
+    #[serde(default)]
+    genres: Vec<String>,
+    #[serde(default)]
+    tags: Vec<AniListTag>,
+    #[serde(default)]
+    staff: Option<StaffConnection>,
+    #[serde(default)]
+    cover_image: Option<CoverImage>,
+    #[serde(default)]
+    volumes: Option<i64>,
+    #[serde(default)]
+    average_score: Option<i64>,
+    #[serde(default)]
+    is_adult: bool,
+    #[serde(default)]
+    site_url: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct AniListTitle {
+    romaji: Option<String>,
+    english: Option<String>,
+    native: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FuzzyDate {
+    year: Option<i32>,
+    month: Option<u32>,
+    day: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AniListTag {
+    name: String,
+    #[serde(default)]
+    rank: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct StaffConnection {
+    #[serde(default)]
+    edges: Vec<StaffEdge>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StaffEdge {
+    role: String,
+    node: StaffNode,
+}
+
+#[derive(Debug, Deserialize)]
+struct StaffNode {
+    name: StaffName,
+}
+
+#[derive(Debug, Deserialize)]
+struct StaffName {
+    full: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CoverImage {
+    extra_large: Option<String>,
+    large: Option<String>,
+}
+
+impl AniListMedia {
+    /// English title if there is one, else the romanized title.
+    fn display_title(&self) -> String {
+        self.title
+            .english
+            .clone()
+            .or_else(|| self.title.romaji.clone())
+            .or_else(|| self.title.native.clone())
+            .unwrap_or_default()
+    }
+
+    fn cover(&self) -> Option<String> {
+        self.cover_image
+            .as_ref()
+            .and_then(|c| c.extra_large.clone().or_else(|| c.large.clone()))
+    }
+
+    /// Convert to Bangumi-shaped metadata.
+    fn into_metadata(self) -> serde_json::Value {
+        let name = self.display_title();
+        let cover = self.cover();
+
+        // Staff roles look like "Story & Art" or "Art (assistant)"
+        let mut infobox = Vec::new();
+        for edge in self.staff.map(|s| s.edges).unwrap_or_default() {
+            let Some(person) = edge.node.name.full else {
+                continue;
+            };
+            let key = if edge.role.starts_with("Story") || edge.role.starts_with("Original") {
+                "作者"
+            } else if edge.role.starts_with("Art") {
+                "作画"
+            } else {
+                continue;
+            };
+            infobox.push(json!({ "key": key, "value": person }));
+        }
+
+        let date = self.start_date.and_then(|d| {
+            chrono::NaiveDate::from_ymd_opt(d.year?, d.month.unwrap_or(1), d.day.unwrap_or(1))
+        });
+        let tags: Vec<serde_json::Value> = self
+            .genres
+            .iter()
+            .map(|genre| json!({ "name": genre, "count": 100 }))
+            .chain(
+                self.tags
+                    .iter()
+                    .map(|tag| json!({ "name": tag.name, "count": tag.rank })),
+            )
+            .collect();
+
+        json!({
+            "id": self.id,
+            "name": self.title.native.clone().unwrap_or_else(|| name.clone()),
+            "name_en": name,
+            "summary": self.description.unwrap_or_default(),
+            "date": date.map(|d| d.format("%Y-%m-%d").to_string()),
+            "tags": tags,
+            "infobox": infobox,
+            "images": { "common": cover, "large": cover },
+            "rating": { "score": self.average_score.map(|s| s as f64 / 10.0) },
+            "volumes": self.volumes.unwrap_or(0),
+            "nsfw": self.is_adult,
+            "url": self.site_url,
+            "mal_id": self.id_mal,
+        })
+    }
+}
+
+/// Service for fetching metadata from the AniList API.
+pub struct AniListService {
+    client: Client,
+}
+
+impl AniListService {
+    /// Create a new AniListService instance.
+    pub fn new() -> Self {
+        let client = Client::builder()
+            .user_agent(USER_AGENT)
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self { client }
+    }
+
+    /// Run a GraphQL query.
+    async fn query<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<T> {
+        let response = self
+            .client
+            .post(ANILIST_API)
+            .json(&json!({ "query": query, "variables": variables }))
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to query AniList: {}", e)))?;
+
+        // AniList reports unknown IDs as 404 with a GraphQL error body
+        let status = response.status();
+        let body: GraphQlResponse<T> = response
+            .json()
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to parse AniList response: {}", e)))?;
+
+        match body.data {
+            Some(data) if body.errors.is_empty() => Ok(data),
+            _ if status.as_u16() == 404 => Err(AppError::NotFound(
+                t!("metadata.not_found", provider = "anilist").to_string(),
+            )),
+            _ => Err(AppError::Internal(format!(
+                "AniList API returned error: {} {}",
+                status,
+                body.errors
+                    .iter()
+                    .map(|e| e.message.as_str())
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ))),
+        }
+    }
+}
+
+impl Default for AniListService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetadataProvider for AniListService {
+    fn kind(&self) -> MetadataProviderKind {
+        MetadataProviderKind::AniList
+    }
+
+    fn search<'a>(&'a self, query: &'a str) -> ProviderFuture<'a, Vec<MetadataSearchResult>> {
+        Box::pin(async move {
+            if query.trim().is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let data: SearchData = self.query(SEARCH_QUERY, json!({ "search": query })).await?;
+            Ok(data
+                .page
+                .media
+                .into_iter()
+                .map(|media| MetadataSearchResult {
+                    provider: MetadataProviderKind::AniList,
+                    id: media.id.to_string(),
+                    title: media.display_title(),
+                    alt_title: media.title.native.clone(),
+                    image: media.cover(),
+                    summary: media.description,
+                })
+                .collect())
+        })
+    }
+
+    fn fetch<'a>(&'a self, id: &'a str) -> ProviderFuture<'a, serde_json::Value> {
+        Box::pin(async move {
+            let media_id: i64 = id.parse().map_err(|_| {
+                AppError::BadRequest(t!("metadata.invalid_id", id = id).to_string())
+            })?;
+
+            let data: MediaData = self.query(MEDIA_QUERY, json!({ "id": media_id })).await?;
+            let media = data.media.ok_or_else(|| {
+                AppError::NotFound(t!("metadata.not_found", provider = "anilist").to_string())
+            })?;
+
+            let mut metadata = media.into_metadata();
+            tag_source(&mut metadata, MetadataProviderKind::AniList, id);
+            Ok(metadata)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_media_into_metadata() {
+        let media: AniListMedia = serde_json::from_value(json!({
+            "id": 53390,
+            "idMal": 23390,
+            "title": {
+                "romaji": "Shingeki no Kyojin",
+                "english": "Attack on Titan",
+                "native": "進撃の巨人"
+            },
+            "description": "Humanity fights titans.",
+            "startDate": { "year": 2009, "month": 9, "day": 9 },
+            "genres": ["Action"],
+            "tags": [{ "name": "Survival", "rank": 90 }],
+            "staff": { "edges": [
+                { "role": "Story & Art", "node": { "name": { "full": "Hajime Isayama" } } },
+                { "role": "Translator", "node": { "name": { "full": "Someone" } } }
+            ] },
+            "coverImage": { "extraLarge": "https://example.com/xl.jpg", "large": null },
+            "volumes": 34,
+            "averageScore": 84
+        }))
+        .unwrap();
+
+        let metadata = media.into_metadata();
+        assert_eq!(metadata["name"], "進撃の巨人");
+        assert_eq!(metadata["name_en"], "Attack on Titan");
+        assert_eq!(metadata["date"], "2009-09-09");
+        assert_eq!(metadata["images"]["common"], "https://example.com/xl.jpg");
+        assert_eq!(
+            metadata["infobox"],
+            json!([{ "key": "作者", "value": "Hajime Isayama" }])
+        );
+        assert_eq!(metadata["tags"][1]["name"], "Survival");
+        assert_eq!(metadata["rating"]["score"], 8.4);
+        assert_eq!(metadata["volumes"], 34);
+        assert_eq!(metadata["mal_id"], 23390);
+    }
+}
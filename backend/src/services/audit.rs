@@ -0,0 +1,57 @@
+//! Audit log service.
+//!
+//! Records security-relevant and destructive actions. Recording never fails
+//! the request that triggered it; errors are only logged.
+
+use sqlx::{Pool, Sqlite};
+use tracing::warn;
+
+use crate::error::Result;
+use crate::models::{AuditLogPage, AuditLogQuery, NewAuditLogEntry};
+use crate::repository::audit::AuditLogRepository;
+use crate::t;
+
+/// Default number of entries per page.
+const DEFAULT_PAGE_SIZE: i64 = 50;
+
+/// Largest allowed page size.
+const MAX_PAGE_SIZE: i64 = 200;
+
+/// Service for recording and querying the audit log.
+pub struct AuditService {
+    pool: Pool<Sqlite>,
+}
+
+impl AuditService {
+    /// Create a new audit service.
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+
+    /// Record an entry, logging instead of failing on errors.
+    pub async fn record(&self, entry: NewAuditLogEntry) {
+        if let Err(e) = AuditLogRepository::insert(&self.pool, &entry).await {
+            warn!(action = entry.action.as_str(), error = %e, "{}", t!("audit.record_failed"));
+        }
+    }
+
+    /// List entries matching the query, newest first.
+    pub async fn list(&self, query: &AuditLogQuery) -> Result<AuditLogPage> {
+        let page = query.page.unwrap_or(1).max(1);
+        let page_size = query
+            .page_size
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE);
+
+        let items =
+            AuditLogRepository::list(&self.pool, query, page_size, (page - 1) * page_size).await?;
+        let total = AuditLogRepository::count(&self.pool, query).await?;
+
+        Ok(AuditLogPage {
+            items,
+            total,
+            page,
+            page_size,
+        })
+    }
+}
@@ -5,17 +5,40 @@
 
 use argon2::{
     Argon2,
-    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng},
+    password_hash::{
+        PasswordHash, PasswordHasher, PasswordVerifier, SaltString,
+        rand_core::{OsRng, RngCore},
+    },
 };
 use chrono::{Duration, Utc};
+use hmac::{Hmac, Mac};
 use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use serde::Deserialize;
+use sha1::Sha1;
 use sqlx::{Pool, Sqlite};
-use tracing::instrument;
-use rust_i18n::t;
+use tracing::{info, instrument};
+use uuid::Uuid;
 
-use crate::error::{AppError, Result};
-use crate::models::{JwtClaims, NewUser, UpdateUserRequest, User};
-use crate::repository::user::UserRepository;
+use crate::error::{AppError, ErrorCode, Result};
+use crate::models::{
+    JwtClaims, NewUser, PasswordResetResponse, TotpSetupResponse, TwoFactorChallengeClaims,
+    UpdateUserRequest, User,
+};
+use crate::repository::user::{PasswordResetRepository, RecoveryCodeRepository, UserRepository};
+use crate::t;
+use crate::utils::locale::{resolve_locale, supported_locale};
+
+/// Purpose marker for two-factor challenge tokens.
+const CHALLENGE_PURPOSE: &str = "2fa_challenge";
+
+/// How long a two-factor challenge token stays valid, in minutes.
+const CHALLENGE_EXPIRATION_MINUTES: i64 = 5;
+
+/// Number of recovery codes generated on enrollment.
+const RECOVERY_CODE_COUNT: usize = 10;
+
+/// How long an administrator-issued password reset token stays valid, in hours.
+const PASSWORD_RESET_HOURS: i64 = 24;
 
 /// Configuration for the authentication service.
 #[derive(Debug, Clone)]
@@ -24,6 +47,8 @@ pub struct AuthConfig {
     pub jwt_secret: String,
     /// JWT token expiration time in hours.
     pub jwt_expiration_hours: i64,
+    /// Requirements new passwords must meet.
+    pub password_policy: PasswordPolicy,
 }
 
 impl Default for AuthConfig {
@@ -31,7 +56,57 @@ impl Default for AuthConfig {
         Self {
             jwt_secret: "default-secret-change-in-production".to_string(),
             jwt_expiration_hours: 24,
+            password_policy: PasswordPolicy::default(),
+        }
+    }
+}
+
+/// Requirements new passwords must meet.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PasswordPolicy {
+    /// Minimum number of characters.
+    pub min_length: usize,
+    /// Minimum zxcvbn strength score, from 0 (anything goes) to 4.
+    pub min_score: u8,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 6,
+            min_score: 0,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    /// Check a new password, estimating its strength with zxcvbn.
+    ///
+    /// `user_inputs` are words a guesser would try first, such as the
+    /// username.
+    pub fn check(&self, password: &str, user_inputs: &[&str]) -> Result<()> {
+        if password.chars().count() < self.min_length {
+            return Err(AppError::BadRequest(
+                t!("auth.password_too_short", min = self.min_length).to_string(),
+            ));
         }
+        if self.min_score > 0 {
+            let score = zxcvbn::zxcvbn(password, user_inputs)
+                .map(|entropy| entropy.score())
+                .unwrap_or(0);
+            if score < self.min_score {
+                return Err(AppError::BadRequest(
+                    t!(
+                        "auth.password_too_weak",
+                        score = score,
+                        min = self.min_score
+                    )
+                    .to_string(),
+                ));
+            }
+        }
+        Ok(())
     }
 }
 
@@ -82,8 +157,13 @@ impl JwtService {
         }
     }
 
-    /// Generate a JWT token for a user.
-    pub fn generate_token(&self, user_id: i64, username: &str) -> Result<String> {
+    /// Generate a JWT token for a user at the given token version.
+    pub fn generate_token(
+        &self,
+        user_id: i64,
+        username: &str,
+        token_version: i64,
+    ) -> Result<String> {
         let now = Utc::now();
         let exp = now + Duration::hours(self.expiration_hours);
 
@@ -92,6 +172,7 @@ impl JwtService {
             username: username.to_string(),
             exp: exp.timestamp(),
             iat: now.timestamp(),
+            ver: token_version,
         };
 
         encode(&Header::default(), &claims, &self.encoding_key)
@@ -106,14 +187,206 @@ impl JwtService {
 
         decode::<JwtClaims>(token, &self.decoding_key, &validation)
             .map(|data| data.claims)
-            .map_err(|e| AppError::Unauthorized(t!("auth.invalid_token", error = e).to_string()))
+            .map_err(|e| {
+                AppError::Unauthorized(t!("auth.invalid_token", error = e).to_string())
+                    .with_code(ErrorCode::InvalidToken)
+            })
+    }
+
+    /// Generate a short-lived challenge token for the second login step.
+    pub fn generate_challenge_token(&self, user_id: i64) -> Result<String> {
+        let now = Utc::now();
+        let exp = now + Duration::minutes(CHALLENGE_EXPIRATION_MINUTES);
+
+        let claims = TwoFactorChallengeClaims {
+            sub: user_id,
+            purpose: CHALLENGE_PURPOSE.to_string(),
+            exp: exp.timestamp(),
+            iat: now.timestamp(),
+        };
+
+        encode(&Header::default(), &claims, &self.encoding_key)
+            .map_err(|e| AppError::Internal(format!("Token generation failed: {}", e)))
+    }
+
+    /// Verify a challenge token and return the user ID it was issued for.
+    pub fn verify_challenge_token(&self, token: &str) -> Result<i64> {
+        let validation = Validation::default();
+
+        let claims = decode::<TwoFactorChallengeClaims>(token, &self.decoding_key, &validation)
+            .map(|data| data.claims)
+            .map_err(|_| AppError::Unauthorized(t!("auth.invalid_challenge").to_string()))?;
+
+        if claims.purpose != CHALLENGE_PURPOSE {
+            return Err(AppError::Unauthorized(
+                t!("auth.invalid_challenge").to_string(),
+            ));
+        }
+
+        Ok(claims.sub)
     }
 }
 
+/// TOTP (RFC 6238) utilities using HMAC-SHA1, 6 digits and 30 second steps,
+/// the defaults understood by common authenticator apps.
+pub struct TotpService;
+
+impl TotpService {
+    /// Time step in seconds.
+    const STEP: u64 = 30;
+    /// Number of digits in a code.
+    const DIGITS: u32 = 6;
+    /// Accepted clock drift in steps on either side.
+    const SKEW: u64 = 1;
+    /// RFC 4648 base32 alphabet.
+    const BASE32_ALPHABET: &'static [u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+    /// Generate a new random base32-encoded secret (160 bits).
+    pub fn generate_secret() -> String {
+        let mut bytes = [0u8; 20];
+        OsRng.fill_bytes(&mut bytes);
+        Self::base32_encode(&bytes)
+    }
+
+    /// Build an `otpauth://` provisioning URI for QR code enrollment.
+    pub fn provisioning_uri(secret: &str, username: &str, issuer: &str) -> String {
+        format!(
+            "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+            urlencoding::encode(issuer),
+            urlencoding::encode(username),
+            secret,
+            urlencoding::encode(issuer),
+            Self::DIGITS,
+            Self::STEP
+        )
+    }
+
+    /// Compute the code for a raw secret at a given counter value.
+    pub fn code_at(secret: &[u8], counter: u64) -> Result<String> {
+        let mut mac = Hmac::<Sha1>::new_from_slice(secret)
+            .map_err(|e| AppError::Internal(format!("Invalid TOTP key: {}", e)))?;
+        mac.update(&counter.to_be_bytes());
+        let hash = mac.finalize().into_bytes();
+
+        // Dynamic truncation (RFC 4226, section 5.3)
+        let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+        let binary = u32::from_be_bytes([
+            hash[offset] & 0x7f,
+            hash[offset + 1],
+            hash[offset + 2],
+            hash[offset + 3],
+        ]);
+
+        Ok(format!(
+            "{:0width$}",
+            binary % 10u32.pow(Self::DIGITS),
+            width = Self::DIGITS as usize
+        ))
+    }
+
+    /// Verify a code against a base32 secret at the given Unix time,
+    /// allowing one step of clock drift.
+    pub fn verify(secret: &str, code: &str, unix_time: u64) -> Result<bool> {
+        Ok(Self::matching_step(secret, code, unix_time)?.is_some())
+    }
+
+    /// Time step a code was generated for, if it is valid at the given Unix
+    /// time. Callers reject steps at or before the last accepted one.
+    pub fn matching_step(secret: &str, code: &str, unix_time: u64) -> Result<Option<u64>> {
+        let code = code.trim().replace(' ', "");
+        if code.len() != Self::DIGITS as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+            return Ok(None);
+        }
+
+        let key = Self::base32_decode(secret)
+            .ok_or_else(|| AppError::Internal("Invalid TOTP secret encoding".to_string()))?;
+        let counter = unix_time / Self::STEP;
+
+        for candidate in counter.saturating_sub(Self::SKEW)..=counter + Self::SKEW {
+            if Self::code_at(&key, candidate)? == code {
+                return Ok(Some(candidate));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Generate a set of human-friendly recovery codes (`xxxxx-xxxxx`).
+    pub fn generate_recovery_codes(count: usize) -> Vec<String> {
+        (0..count)
+            .map(|_| {
+                let mut bytes = [0u8; 10];
+                OsRng.fill_bytes(&mut bytes);
+                let chars: String = bytes
+                    .iter()
+                    .map(|b| Self::BASE32_ALPHABET[(b & 0x1f) as usize] as char)
+                    .collect::<String>()
+                    .to_lowercase();
+                format!("{}-{}", &chars[..5], &chars[5..])
+            })
+            .collect()
+    }
+
+    /// Normalize a recovery code for hashing and comparison.
+    pub fn normalize_recovery_code(code: &str) -> String {
+        code.trim().to_lowercase().replace(' ', "")
+    }
+
+    fn base32_encode(data: &[u8]) -> String {
+        let mut out = String::with_capacity(data.len().div_ceil(5) * 8);
+        let mut buffer: u32 = 0;
+        let mut bits = 0;
+
+        for &byte in data {
+            buffer = (buffer << 8) | byte as u32;
+            bits += 8;
+            while bits >= 5 {
+                bits -= 5;
+                out.push(Self::BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+            }
+        }
+
+        if bits > 0 {
+            out.push(Self::BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+        }
+
+        out
+    }
+
+    fn base32_decode(input: &str) -> Option<Vec<u8>> {
+        let mut out = Vec::with_capacity(input.len() * 5 / 8);
+        let mut buffer: u32 = 0;
+        let mut bits = 0;
+
+        for c in input.trim_end_matches('=').chars() {
+            let c = c.to_ascii_uppercase();
+            let value = Self::BASE32_ALPHABET.iter().position(|&a| a as char == c)? as u32;
+            buffer = (buffer << 5) | value;
+            bits += 5;
+            if bits >= 8 {
+                bits -= 8;
+                out.push((buffer >> bits) as u8);
+            }
+        }
+
+        Some(out)
+    }
+}
+
+/// Outcome of the password step of a login.
+#[derive(Debug)]
+pub enum LoginOutcome {
+    /// The user is fully authenticated.
+    Authenticated { user: User, token: String },
+    /// The user has TOTP enabled and must complete the second step.
+    TwoFactorRequired { challenge_token: String },
+}
+
 /// Authentication service for user management.
 pub struct AuthService {
     pool: Pool<Sqlite>,
     jwt_service: JwtService,
+    password_policy: PasswordPolicy,
 }
 
 impl AuthService {
@@ -122,6 +395,7 @@ impl AuthService {
         Self {
             pool,
             jwt_service: JwtService::new(&config.jwt_secret, config.jwt_expiration_hours),
+            password_policy: config.password_policy,
         }
     }
 
@@ -134,11 +408,7 @@ impl AuthService {
         if username.trim().is_empty() {
             return Err(AppError::BadRequest(t!("auth.username_empty").to_string()));
         }
-        if password.len() < 6 {
-            return Err(AppError::BadRequest(
-                t!("auth.password_too_short").to_string(),
-            ));
-        }
+        self.password_policy.check(&password, &[&username])?;
 
         // Hash the password
         let password_hash = PasswordHashService::hash_password(&password)?;
@@ -153,63 +423,297 @@ impl AuthService {
         UserRepository::create(&self.pool, new_user).await
     }
 
+    /// Whether the first administrator still has to be set up.
+    pub async fn setup_required(&self) -> Result<bool> {
+        UserRepository::setup_required(&self.pool).await
+    }
+
+    /// Create the first administrator during setup, with an optional
+    /// preferred locale.
+    ///
+    /// Fails with 403 Forbidden once an administrator has been set up.
+    pub async fn create_first_admin(
+        &self,
+        username: String,
+        password: String,
+        locale: Option<&str>,
+    ) -> Result<User> {
+        let username = username.trim().to_string();
+        if username.is_empty() {
+            return Err(AppError::BadRequest(t!("auth.username_empty").to_string()));
+        }
+        self.password_policy.check(&password, &[&username])?;
+        let locale = match locale.map(str::trim).filter(|tag| !tag.is_empty()) {
+            Some(tag) => Some(supported_locale(tag).ok_or_else(|| {
+                AppError::BadRequest(t!("auth.unsupported_locale", tag = tag).to_string())
+            })?),
+            None => None,
+        };
+
+        let new_user = NewUser {
+            username,
+            password_hash: PasswordHashService::hash_password(&password)?,
+            bangumi_api_key: None,
+        };
+        UserRepository::create_first_admin(&self.pool, new_user, locale)
+            .await?
+            .ok_or_else(|| {
+                AppError::Forbidden(t!("setup.completed").to_string())
+                    .with_code(ErrorCode::SetupCompleted)
+            })
+    }
+
     /// Login a user with username and password.
     ///
-    /// Returns the user and a JWT token on success.
+    /// Returns the user and a JWT token on success, or a challenge token if
+    /// the user has two-factor authentication enabled.
     #[instrument(skip(self, password), fields(username = %username))]
-    pub async fn login(&self, username: String, password: String) -> Result<(User, String)> {
+    pub async fn login(&self, username: String, password: String) -> Result<LoginOutcome> {
         // Find the user
         let user = UserRepository::find_by_username(&self.pool, &username)
             .await?
-            .ok_or_else(|| AppError::Unauthorized(t!("auth.invalid_credentials").to_string()))?;
+            .ok_or_else(|| {
+                AppError::Unauthorized(t!("auth.invalid_credentials").to_string())
+                    .with_code(ErrorCode::InvalidCredentials)
+            })?;
 
         // Verify the password
         let is_valid = PasswordHashService::verify_password(&password, &user.password_hash)?;
         if !is_valid {
-            return Err(AppError::Unauthorized(
-                t!("auth.invalid_credentials").to_string(),
-            ));
+            return Err(
+                AppError::Unauthorized(t!("auth.invalid_credentials").to_string())
+                    .with_code(ErrorCode::InvalidCredentials),
+            );
+        }
+
+        if user.totp_enabled {
+            let challenge_token = self.jwt_service.generate_challenge_token(user.id)?;
+            return Ok(LoginOutcome::TwoFactorRequired { challenge_token });
         }
 
         // Generate JWT token
-        let token = self.jwt_service.generate_token(user.id, &user.username)?;
+        let token = self
+            .jwt_service
+            .generate_token(user.id, &user.username, user.token_version)?;
+
+        Ok(LoginOutcome::Authenticated { user, token })
+    }
+
+    /// Return the username a two-factor challenge token was issued for.
+    pub async fn challenge_username(&self, challenge_token: &str) -> Result<String> {
+        let user_id = self.jwt_service.verify_challenge_token(challenge_token)?;
+        Ok(self.require_user(user_id).await?.username)
+    }
+
+    /// Finish a two-factor login with a TOTP or recovery code.
+    ///
+    /// Returns the user and a JWT token on success.
+    #[instrument(skip(self, challenge_token, code))]
+    pub async fn complete_two_factor_login(
+        &self,
+        challenge_token: &str,
+        code: &str,
+    ) -> Result<(User, String)> {
+        let user_id = self.jwt_service.verify_challenge_token(challenge_token)?;
+
+        let user = UserRepository::find_by_id(&self.pool, user_id)
+            .await?
+            .ok_or_else(|| AppError::Unauthorized(t!("auth.invalid_challenge").to_string()))?;
+
+        if !user.totp_enabled || !self.verify_second_factor(&user, code).await? {
+            return Err(
+                AppError::Unauthorized(t!("auth.invalid_totp_code").to_string())
+                    .with_code(ErrorCode::InvalidTotpCode),
+            );
+        }
+
+        let token = self
+            .jwt_service
+            .generate_token(user.id, &user.username, user.token_version)?;
 
         Ok((user, token))
     }
 
+    /// Start TOTP enrollment by generating a new secret.
+    ///
+    /// The secret is stored but not enforced until confirmed with
+    /// [`enable_totp`](Self::enable_totp).
+    pub async fn setup_totp(&self, user_id: i64) -> Result<TotpSetupResponse> {
+        let user = self.require_user(user_id).await?;
+
+        if user.totp_enabled {
+            return Err(AppError::BadRequest(
+                t!("auth.totp_already_enabled").to_string(),
+            ));
+        }
+
+        let secret = TotpService::generate_secret();
+        UserRepository::set_totp_secret(&self.pool, user_id, Some(&secret)).await?;
+
+        Ok(TotpSetupResponse {
+            provisioning_uri: TotpService::provisioning_uri(&secret, &user.username, "Ryuri"),
+            secret,
+        })
+    }
+
+    /// Confirm TOTP enrollment with a code from the authenticator app.
+    ///
+    /// Returns freshly generated recovery codes, which are only shown once.
+    pub async fn enable_totp(&self, user_id: i64, code: &str) -> Result<Vec<String>> {
+        let user = self.require_user(user_id).await?;
+
+        if user.totp_enabled {
+            return Err(AppError::BadRequest(
+                t!("auth.totp_already_enabled").to_string(),
+            ));
+        }
+
+        let secret = user
+            .totp_secret
+            .as_deref()
+            .ok_or_else(|| AppError::BadRequest(t!("auth.totp_not_set_up").to_string()))?;
+
+        if !self.verify_totp(user_id, secret, code).await? {
+            return Err(
+                AppError::BadRequest(t!("auth.invalid_totp_code").to_string())
+                    .with_code(ErrorCode::InvalidTotpCode),
+            );
+        }
+
+        let recovery_codes = self.replace_recovery_codes(user_id).await?;
+        UserRepository::set_totp_enabled(&self.pool, user_id, true).await?;
+        info!(user_id, "{}", t!("auth.totp_enabled_log"));
+
+        Ok(recovery_codes)
+    }
+
+    /// Disable two-factor authentication after re-checking the password and a code.
+    pub async fn disable_totp(&self, user_id: i64, password: &str, code: &str) -> Result<()> {
+        let user = self.require_user(user_id).await?;
+
+        if !user.totp_enabled {
+            return Err(AppError::BadRequest(
+                t!("auth.totp_not_enabled").to_string(),
+            ));
+        }
+
+        if !PasswordHashService::verify_password(password, &user.password_hash)? {
+            return Err(
+                AppError::Unauthorized(t!("auth.incorrect_password").to_string())
+                    .with_code(ErrorCode::InvalidCredentials),
+            );
+        }
+
+        if !self.verify_second_factor(&user, code).await? {
+            return Err(
+                AppError::Unauthorized(t!("auth.invalid_totp_code").to_string())
+                    .with_code(ErrorCode::InvalidTotpCode),
+            );
+        }
+
+        UserRepository::set_totp_enabled(&self.pool, user_id, false).await?;
+        UserRepository::set_totp_secret(&self.pool, user_id, None).await?;
+        RecoveryCodeRepository::delete_by_user(&self.pool, user_id).await?;
+        info!(user_id, "{}", t!("auth.totp_disabled_log"));
+
+        Ok(())
+    }
+
+    /// Replace the recovery codes of a user after verifying a TOTP code.
+    pub async fn regenerate_recovery_codes(&self, user_id: i64, code: &str) -> Result<Vec<String>> {
+        let user = self.require_user(user_id).await?;
+
+        let secret = match (user.totp_enabled, user.totp_secret.as_deref()) {
+            (true, Some(secret)) => secret,
+            _ => {
+                return Err(AppError::BadRequest(
+                    t!("auth.totp_not_enabled").to_string(),
+                ));
+            }
+        };
+
+        if !self.verify_totp(user_id, secret, code).await? {
+            return Err(
+                AppError::Unauthorized(t!("auth.invalid_totp_code").to_string())
+                    .with_code(ErrorCode::InvalidTotpCode),
+            );
+        }
+
+        self.replace_recovery_codes(user_id).await
+    }
+
+    /// Check a TOTP code, falling back to consuming a recovery code.
+    async fn verify_second_factor(&self, user: &User, code: &str) -> Result<bool> {
+        if let Some(secret) = user.totp_secret.as_deref()
+            && self.verify_totp(user.id, secret, code).await?
+        {
+            return Ok(true);
+        }
+
+        let normalized = TotpService::normalize_recovery_code(code);
+        for recovery_code in RecoveryCodeRepository::list_unused(&self.pool, user.id).await? {
+            if PasswordHashService::verify_password(&normalized, &recovery_code.code_hash)? {
+                return RecoveryCodeRepository::mark_used(&self.pool, recovery_code.id).await;
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Check a TOTP code and record its time step, so each code is accepted
+    /// only once.
+    async fn verify_totp(&self, user_id: i64, secret: &str, code: &str) -> Result<bool> {
+        match TotpService::matching_step(secret, code, Utc::now().timestamp() as u64)? {
+            Some(step) => UserRepository::accept_totp_step(&self.pool, user_id, step as i64).await,
+            None => Ok(false),
+        }
+    }
+
+    /// Generate, hash and store a new set of recovery codes.
+    async fn replace_recovery_codes(&self, user_id: i64) -> Result<Vec<String>> {
+        let codes = TotpService::generate_recovery_codes(RECOVERY_CODE_COUNT);
+        let hashes = codes
+            .iter()
+            .map(|c| PasswordHashService::hash_password(&TotpService::normalize_recovery_code(c)))
+            .collect::<Result<Vec<_>>>()?;
+
+        RecoveryCodeRepository::replace_for_user(&self.pool, user_id, hashes).await?;
+
+        Ok(codes)
+    }
+
+    /// Load a user or fail with NotFound.
+    async fn require_user(&self, user_id: i64) -> Result<User> {
+        UserRepository::find_by_id(&self.pool, user_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound(t!("auth.user_not_found", id = user_id).to_string())
+                    .with_code(ErrorCode::UserNotFound)
+            })
+    }
+
+    /// Issue a JWT for a user authenticated by other means, such as OIDC.
+    pub fn issue_token(&self, user: &User) -> Result<String> {
+        self.jwt_service
+            .generate_token(user.id, &user.username, user.token_version)
+    }
+
     /// Verify a JWT token and return the claims.
     pub fn verify_token(&self, token: &str) -> Result<JwtClaims> {
         self.jwt_service.verify_token(token)
     }
 
-    /// Update user information.
+    /// Update user information. Passwords are changed with
+    /// [`Self::change_password`] instead.
     pub async fn update_user(&self, user_id: i64, req: UpdateUserRequest) -> Result<User> {
         // Get the current user
         let user = UserRepository::find_by_id(&self.pool, user_id)
             .await?
-            .ok_or_else(|| AppError::NotFound(t!("auth.user_not_found", id = user_id).to_string()))?;
-
-        // Validate Password if changing (requires old_password)
-        let password_hash = if let Some(new_password) = &req.password {
-            // Check if old_password is provided
-            let old_password = req.old_password.as_ref().ok_or_else(|| {
-                AppError::BadRequest(t!("auth.current_password_required").to_string())
+            .ok_or_else(|| {
+                AppError::NotFound(t!("auth.user_not_found", id = user_id).to_string())
+                    .with_code(ErrorCode::UserNotFound)
             })?;
 
-            // Verify old password
-            let is_valid = PasswordHashService::verify_password(old_password, &user.password_hash)?;
-            if !is_valid {
-                return Err(AppError::Unauthorized(
-                    t!("auth.incorrect_password").to_string(),
-                ));
-            }
-
-            // Hash new password
-            Some(PasswordHashService::hash_password(new_password)?)
-        } else {
-            None
-        };
-
         // Validate Username if changing
         let username = if let Some(new_username) = &req.username {
             if new_username.trim().is_empty() {
@@ -236,21 +740,372 @@ impl AuthService {
             None // No change
         };
 
+        let locale_update = match req.locale.as_deref().map(str::trim) {
+            Some("") => Some(None), // Clear
+            Some(tag) => {
+                let locale = supported_locale(tag).ok_or_else(|| {
+                    AppError::BadRequest(t!("auth.unsupported_locale", tag = tag).to_string())
+                })?;
+                Some(Some(locale.to_string()))
+            }
+            None => None,
+        };
+
         UserRepository::update(
             &self.pool,
             user_id,
             username,
-            password_hash,
+            None,
             bangumi_api_key_update,
+            locale_update,
         )
         .await
     }
 
+    /// Change the password of a user after checking their current one.
+    ///
+    /// Every token issued before is revoked; returns the user with a fresh
+    /// token for the session making the change.
+    #[instrument(skip(self, current_password, new_password))]
+    pub async fn change_password(
+        &self,
+        user_id: i64,
+        current_password: &str,
+        new_password: &str,
+    ) -> Result<(User, String)> {
+        let user = self.require_user(user_id).await?;
+
+        if !PasswordHashService::verify_password(current_password, &user.password_hash)? {
+            return Err(
+                AppError::Unauthorized(t!("auth.incorrect_password").to_string())
+                    .with_code(ErrorCode::InvalidCredentials),
+            );
+        }
+        self.password_policy
+            .check(new_password, &[&user.username])?;
+
+        let user = self.set_password(&user, new_password).await?;
+        let token = self.issue_token(&user)?;
+
+        Ok((user, token))
+    }
+
+    /// Issue a one-time password reset token for a user, replacing any
+    /// earlier one.
+    pub async fn create_password_reset(&self, user_id: i64) -> Result<PasswordResetResponse> {
+        self.require_user(user_id).await?;
+
+        let token = Uuid::new_v4().simple().to_string();
+        let expires_at = Utc::now() + Duration::hours(PASSWORD_RESET_HOURS);
+        PasswordResetRepository::replace_for_user(&self.pool, user_id, &token, expires_at).await?;
+
+        Ok(PasswordResetResponse { token, expires_at })
+    }
+
+    /// Set a new password with a reset token, consuming it.
+    ///
+    /// Every token issued before is revoked. No token is returned: the user
+    /// logs in again, going through two-factor authentication if enabled.
+    #[instrument(skip(self, token, new_password))]
+    pub async fn reset_password(&self, token: &str, new_password: &str) -> Result<User> {
+        let invalid = || {
+            AppError::Unauthorized(t!("auth.invalid_reset_token").to_string())
+                .with_code(ErrorCode::InvalidToken)
+        };
+
+        let (user_id, expires_at) = PasswordResetRepository::find(&self.pool, token)
+            .await?
+            .ok_or_else(invalid)?;
+        if expires_at <= Utc::now() {
+            return Err(invalid());
+        }
+        let user = UserRepository::find_by_id(&self.pool, user_id)
+            .await?
+            .ok_or_else(invalid)?;
+        self.password_policy
+            .check(new_password, &[&user.username])?;
+
+        if !PasswordResetRepository::consume(&self.pool, token).await? {
+            return Err(invalid());
+        }
+        self.set_password(&user, new_password).await
+    }
+
+    /// Store a new password, which revokes every issued token. Callers
+    /// check it against the password policy first.
+    async fn set_password(&self, user: &User, new_password: &str) -> Result<User> {
+        let password_hash = PasswordHashService::hash_password(new_password)?;
+
+        let user =
+            UserRepository::update(&self.pool, user.id, None, Some(password_hash), None, None)
+                .await?;
+        info!(user_id = user.id, "{}", t!("auth.password_changed_log"));
+
+        Ok(user)
+    }
+
     /// Get a user by ID.
     pub async fn get_user(&self, user_id: i64) -> Result<Option<User>> {
         UserRepository::find_by_id(&self.pool, user_id).await
     }
+
+    /// Locale to render generated text in for a user.
+    ///
+    /// Falls back to the server locale when the user has no preference.
+    pub async fn user_locale(&self, user_id: i64) -> Result<String> {
+        let user = UserRepository::find_by_id(&self.pool, user_id).await?;
+        Ok(resolve_locale(
+            user.as_ref().and_then(|u| u.locale.as_deref()),
+        ))
+    }
 }
 
 // Re-export for convenience
 pub use crate::models::{LoginRequest, LoginResponse, RegisterRequest, UserResponse};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{DbConfig, init_db};
+
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn test_totp_matches_rfc6238_vectors() {
+        // RFC 6238 Appendix B (SHA1), truncated to 6 digits
+        assert_eq!(TotpService::code_at(RFC_SECRET, 59 / 30).unwrap(), "287082");
+        assert_eq!(
+            TotpService::code_at(RFC_SECRET, 1111111109 / 30).unwrap(),
+            "081804"
+        );
+        assert_eq!(
+            TotpService::code_at(RFC_SECRET, 1234567890 / 30).unwrap(),
+            "005924"
+        );
+    }
+
+    #[test]
+    fn test_totp_verify_allows_one_step_skew() {
+        let secret = TotpService::base32_encode(RFC_SECRET);
+        assert!(TotpService::verify(&secret, "081804", 1111111109).unwrap());
+        assert!(TotpService::verify(&secret, "081804", 1111111109 + 30).unwrap());
+        assert!(!TotpService::verify(&secret, "081804", 1111111109 + 90).unwrap());
+        assert!(!TotpService::verify(&secret, "abc", 1111111109).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_totp_codes_cannot_be_replayed() {
+        let service = auth_service().await;
+        let user = service
+            .create_first_admin("owner".to_string(), "secret1".to_string(), None)
+            .await
+            .unwrap();
+        let setup = service.setup_totp(user.id).await.unwrap();
+        let key = TotpService::base32_decode(&setup.secret).unwrap();
+        let step = Utc::now().timestamp() as u64 / TotpService::STEP;
+        let code = TotpService::code_at(&key, step).unwrap();
+
+        let recovery_codes = service.enable_totp(user.id, &code).await.unwrap();
+        let err = service
+            .regenerate_recovery_codes(user.id, &code)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidTotpCode);
+
+        let LoginOutcome::TwoFactorRequired { challenge_token } = service
+            .login("owner".to_string(), "secret1".to_string())
+            .await
+            .unwrap()
+        else {
+            panic!("TOTP should be required");
+        };
+        let err = service
+            .complete_two_factor_login(&challenge_token, &code)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidTotpCode);
+        // Recovery codes still work
+        assert!(
+            service
+                .complete_two_factor_login(&challenge_token, &recovery_codes[0])
+                .await
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_base32_round_trip() {
+        let secret = TotpService::generate_secret();
+        assert_eq!(secret.len(), 32);
+        let decoded = TotpService::base32_decode(&secret).unwrap();
+        assert_eq!(TotpService::base32_encode(&decoded), secret);
+        assert_eq!(
+            TotpService::base32_encode(RFC_SECRET),
+            "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ"
+        );
+    }
+
+    #[test]
+    fn test_challenge_token_is_not_an_access_token() {
+        let jwt_service = JwtService::new("secret", 24);
+        let challenge = jwt_service.generate_challenge_token(7).unwrap();
+        assert_eq!(jwt_service.verify_challenge_token(&challenge).unwrap(), 7);
+        assert!(jwt_service.verify_token(&challenge).is_err());
+
+        let access = jwt_service.generate_token(7, "user", 0).unwrap();
+        assert!(jwt_service.verify_challenge_token(&access).is_err());
+    }
+
+    async fn auth_service() -> AuthService {
+        let config = DbConfig {
+            database_url: "sqlite::memory:".to_string(),
+            max_connections: 1,
+        };
+        let pool = init_db(&config).await.unwrap();
+        AuthService::new(pool, AuthConfig::default())
+    }
+
+    #[tokio::test]
+    async fn test_first_admin_takes_over_seeded_account() {
+        let service = auth_service().await;
+        assert!(service.setup_required().await.unwrap());
+
+        let user = service
+            .create_first_admin("owner".to_string(), "secret1".to_string(), Some("zh"))
+            .await
+            .unwrap();
+        assert!(user.is_admin);
+        assert_eq!(user.username, "owner");
+        assert_eq!(user.locale.as_deref(), Some("zh-CN"));
+        assert!(
+            UserRepository::find_by_username(&service.pool, "admin")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        assert!(!service.setup_required().await.unwrap());
+        let err = service
+            .create_first_admin("other".to_string(), "secret2".to_string(), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::SetupCompleted);
+    }
+
+    #[tokio::test]
+    async fn test_first_admin_without_users() {
+        let service = auth_service().await;
+        sqlx::query("DELETE FROM users")
+            .execute(&service.pool)
+            .await
+            .unwrap();
+        assert!(service.setup_required().await.unwrap());
+
+        assert!(
+            service
+                .create_first_admin(" ".to_string(), "secret1".to_string(), None)
+                .await
+                .is_err()
+        );
+        let user = service
+            .create_first_admin("owner".to_string(), "secret1".to_string(), None)
+            .await
+            .unwrap();
+        assert!(user.is_admin);
+        assert!(!service.setup_required().await.unwrap());
+    }
+
+    #[test]
+    fn test_password_policy() {
+        let policy = PasswordPolicy {
+            min_length: 8,
+            min_score: 3,
+        };
+        assert!(policy.check("short", &[]).is_err());
+        assert!(policy.check("password", &[]).is_err());
+        assert!(policy.check("alice1234", &["alice"]).is_err());
+        assert!(policy.check("correct horse battery staple", &[]).is_ok());
+
+        let default = PasswordPolicy::default();
+        assert!(default.check("12345", &[]).is_err());
+        assert!(default.check("123456", &[]).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_change_password_revokes_earlier_tokens() {
+        let service = auth_service().await;
+        let user = service
+            .create_first_admin("owner".to_string(), "secret1".to_string(), None)
+            .await
+            .unwrap();
+        let old_token = service.issue_token(&user).unwrap();
+
+        let err = service
+            .change_password(user.id, "wrong", "secret2")
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidCredentials);
+        assert!(
+            service
+                .change_password(user.id, "secret1", "s")
+                .await
+                .is_err()
+        );
+
+        let (user, token) = service
+            .change_password(user.id, "secret1", "secret2")
+            .await
+            .unwrap();
+        assert_eq!(user.token_version, 1);
+        assert_eq!(service.verify_token(&old_token).unwrap().ver, 0);
+        assert_eq!(service.verify_token(&token).unwrap().ver, 1);
+        assert!(
+            service
+                .login("owner".to_string(), "secret2".to_string())
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_password_reset_token_is_single_use() {
+        let service = auth_service().await;
+        let user = service
+            .create_first_admin("owner".to_string(), "secret1".to_string(), None)
+            .await
+            .unwrap();
+        assert!(service.create_password_reset(user.id + 1).await.is_err());
+
+        let reset = service.create_password_reset(user.id).await.unwrap();
+        assert!(reset.expires_at > Utc::now());
+        // A weak password leaves the token usable
+        assert!(service.reset_password(&reset.token, "s").await.is_err());
+
+        let user = service
+            .reset_password(&reset.token, "secret2")
+            .await
+            .unwrap();
+        assert_eq!(user.token_version, 1);
+        let err = service
+            .reset_password(&reset.token, "secret3")
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidToken);
+
+        // Issuing a new token replaces the previous one
+        let first = service.create_password_reset(user.id).await.unwrap();
+        let second = service.create_password_reset(user.id).await.unwrap();
+        assert!(
+            service
+                .reset_password(&first.token, "secret3")
+                .await
+                .is_err()
+        );
+        assert!(
+            service
+                .reset_password(&second.token, "secret3")
+                .await
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,381 @@
+//! Database backup and restore.
+//!
+//! Backups are consistent snapshots taken with `VACUUM INTO` while the
+//! server keeps running. A restore cannot replace the database under open
+//! connections, so an uploaded backup is checked, staged next to the
+//! database file and swapped in by [`crate::db::init_db`] on the next start.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{Connection, Pool, Sqlite, SqliteConnection};
+use tracing::{info, warn};
+
+use crate::db::pending_restore_path;
+use crate::error::{AppError, ErrorCode, Result};
+use crate::models::{BackupInfo, BackupRestoreResponse};
+use crate::t;
+
+/// File name prefix shared by all backups.
+const BACKUP_PREFIX: &str = "ryuri-";
+
+/// File name extension shared by all backups.
+const BACKUP_EXTENSION: &str = ".db";
+
+/// First bytes of every SQLite database file.
+pub const SQLITE_HEADER: &[u8] = b"SQLite format 3\0";
+
+/// Backup settings.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackupConfig {
+    /// Directory backups are written to.
+    pub dir: PathBuf,
+    /// Hour of the day (UTC) for the automatic nightly backup; disabled when unset.
+    pub nightly_hour: Option<u32>,
+    /// Number of backups kept; older ones are deleted after each new backup.
+    pub keep: usize,
+    /// Largest accepted restore upload in megabytes.
+    pub max_restore_size_mb: u64,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("backups"),
+            nightly_hour: None,
+            keep: 7,
+            max_restore_size_mb: 1024,
+        }
+    }
+}
+
+/// Service for creating, listing and restoring database backups.
+pub struct BackupService {
+    pool: Pool<Sqlite>,
+    config: BackupConfig,
+}
+
+impl BackupService {
+    /// Create a new backup service.
+    pub fn new(pool: Pool<Sqlite>, config: BackupConfig) -> Self {
+        Self { pool, config }
+    }
+
+    /// Backup settings.
+    pub fn config(&self) -> &BackupConfig {
+        &self.config
+    }
+
+    /// Write a snapshot of the live database to the backup directory.
+    ///
+    /// Backups beyond the configured number to keep are deleted afterwards.
+    pub async fn create(&self) -> Result<BackupInfo> {
+        let name = format!(
+            "{}{}{}",
+            BACKUP_PREFIX,
+            Utc::now().format("%Y%m%d-%H%M%S"),
+            BACKUP_EXTENSION
+        );
+        let info = self.snapshot(&name).await?;
+        self.prune().await;
+        Ok(info)
+    }
+
+    /// List existing backups, newest first.
+    pub async fn list(&self) -> Result<Vec<BackupInfo>> {
+        let mut backups = Vec::new();
+
+        let mut entries = match tokio::fs::read_dir(&self.config.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(backups),
+            Err(e) => return Err(AppError::FileSystem(e)),
+        };
+
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !is_backup_name(&name) {
+                continue;
+            }
+            let metadata = entry.metadata().await?;
+            if !metadata.is_file() {
+                continue;
+            }
+            backups.push(BackupInfo {
+                name,
+                size: metadata.len(),
+                created_at: metadata
+                    .modified()
+                    .map(DateTime::<Utc>::from)
+                    .unwrap_or_else(|_| Utc::now()),
+            });
+        }
+
+        backups.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.name.cmp(&a.name)));
+        Ok(backups)
+    }
+
+    /// Path of an existing backup, for downloading.
+    pub async fn path(&self, name: &str) -> Result<PathBuf> {
+        let not_found = || {
+            AppError::NotFound(t!("backup.not_found", name = name).to_string())
+                .with_code(ErrorCode::BackupNotFound)
+        };
+
+        // Only plain backup file names are accepted, never paths
+        if !is_backup_name(name) {
+            return Err(not_found());
+        }
+
+        let path = self.config.dir.join(name);
+        match tokio::fs::metadata(&path).await {
+            Ok(metadata) if metadata.is_file() => Ok(path),
+            _ => Err(not_found()),
+        }
+    }
+
+    /// Stage an uploaded backup to replace the database on the next start.
+    ///
+    /// The upload must be an intact SQLite database created by a version of
+    /// Ryuri no newer than this one and contain at least one user. A backup
+    /// of the current database is taken first so the restore can be undone.
+    pub async fn restore(&self, data: &[u8]) -> Result<BackupRestoreResponse> {
+        let database_path = self.database_path()?;
+
+        if data.len() as u64 > self.config.max_restore_size_mb * 1024 * 1024 {
+            return Err(AppError::BadRequest(
+                t!("backup.too_large", max = self.config.max_restore_size_mb).to_string(),
+            ));
+        }
+        if !data.starts_with(SQLITE_HEADER) {
+            return Err(AppError::BadRequest(t!("backup.not_sqlite").to_string()));
+        }
+
+        // Check the upload in a scratch file before touching anything else
+        let staged = pending_restore_path(&database_path);
+        let upload = staged.with_extension("upload");
+        tokio::fs::write(&upload, data).await?;
+        if let Err(e) = verify_backup(&upload).await {
+            let _ = tokio::fs::remove_file(&upload).await;
+            return Err(e);
+        }
+
+        let name = format!(
+            "{}{}-pre-restore{}",
+            BACKUP_PREFIX,
+            Utc::now().format("%Y%m%d-%H%M%S"),
+            BACKUP_EXTENSION
+        );
+        let safety_backup = match self.snapshot(&name).await {
+            Ok(info) => info,
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&upload).await;
+                return Err(e);
+            }
+        };
+
+        tokio::fs::rename(&upload, &staged).await?;
+        info!(path = %staged.display(), "{}", t!("backup.restore_staged"));
+
+        Ok(BackupRestoreResponse {
+            safety_backup,
+            restart_required: true,
+        })
+    }
+
+    /// Write a snapshot of the live database to `name` in the backup directory.
+    async fn snapshot(&self, name: &str) -> Result<BackupInfo> {
+        tokio::fs::create_dir_all(&self.config.dir).await?;
+
+        let path = self.config.dir.join(name);
+        if tokio::fs::try_exists(&path).await? {
+            return Err(AppError::BadRequest(
+                t!("backup.already_exists", name = name).to_string(),
+            ));
+        }
+
+        sqlx::query("VACUUM INTO ?")
+            .bind(path.to_string_lossy().to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(AppError::Database)?;
+
+        let metadata = tokio::fs::metadata(&path).await?;
+        info!(path = %path.display(), size = metadata.len(), "{}", t!("backup.created"));
+
+        Ok(BackupInfo {
+            name: name.to_string(),
+            size: metadata.len(),
+            created_at: Utc::now(),
+        })
+    }
+
+    /// Delete the oldest backups beyond the number to keep.
+    async fn prune(&self) {
+        let backups = match self.list().await {
+            Ok(backups) => backups,
+            Err(e) => {
+                warn!(error = %e, "{}", t!("backup.prune_failed"));
+                return;
+            }
+        };
+
+        for backup in backups.iter().skip(self.config.keep.max(1)) {
+            if let Err(e) = tokio::fs::remove_file(self.config.dir.join(&backup.name)).await {
+                warn!(name = %backup.name, error = %e, "{}", t!("backup.prune_failed"));
+            }
+        }
+    }
+
+    /// Path of the live database file.
+    fn database_path(&self) -> Result<PathBuf> {
+        // In-memory databases have no file to replace
+        let path = self.pool.connect_options().get_filename().to_path_buf();
+        if !path.is_file() {
+            return Err(AppError::BadRequest(t!("backup.in_memory").to_string()));
+        }
+        Ok(path)
+    }
+}
+
+/// Whether `name` is a file name this service could have written.
+fn is_backup_name(name: &str) -> bool {
+    name.starts_with(BACKUP_PREFIX)
+        && name.ends_with(BACKUP_EXTENSION)
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+        && !name.contains("..")
+}
+
+/// Check that the file at `path` is a database this server can run on.
+async fn verify_backup(path: &Path) -> Result<()> {
+    let invalid =
+        |reason: String| AppError::BadRequest(t!("backup.invalid", reason = reason).to_string());
+
+    let options = SqliteConnectOptions::new().filename(path).read_only(true);
+    let mut conn = SqliteConnection::connect_with(&options)
+        .await
+        .map_err(|e| invalid(e.to_string()))?;
+
+    let result = verify_connection(&mut conn).await.map_err(invalid);
+    let _ = conn.close().await;
+    result
+}
+
+/// Integrity, schema version and user checks, returning the reason on failure.
+async fn verify_connection(conn: &mut SqliteConnection) -> std::result::Result<(), String> {
+    let (integrity,): (String,) = sqlx::query_as("PRAGMA integrity_check")
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    if integrity != "ok" {
+        return Err(integrity);
+    }
+
+    let applied: Vec<(i64,)> = sqlx::query_as("SELECT version FROM _sqlx_migrations")
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|_| t!("backup.no_migrations").to_string())?;
+    let migrator = sqlx::migrate!("./migrations");
+    if let Some((version,)) = applied
+        .iter()
+        .find(|(version,)| !migrator.iter().any(|m| m.version == *version))
+    {
+        return Err(t!("backup.newer_schema", version = version).to_string());
+    }
+
+    let (users,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users")
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    if users == 0 {
+        return Err(t!("backup.no_users").to_string());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{DbConfig, init_db};
+
+    async fn setup(dir: &Path) -> BackupService {
+        let config = DbConfig {
+            database_url: format!("sqlite:{}?mode=rwc", dir.join("ryuri.db").display()),
+            max_connections: 2,
+        };
+        let pool = init_db(&config).await.unwrap();
+        BackupService::new(
+            pool,
+            BackupConfig {
+                dir: dir.join("backups"),
+                keep: 2,
+                ..BackupConfig::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_backup_names() {
+        assert!(is_backup_name("ryuri-20260101-030000.db"));
+        assert!(is_backup_name("ryuri-20260101-030000-pre-restore.db"));
+        assert!(!is_backup_name("ryuri.db"));
+        assert!(!is_backup_name("ryuri-../../etc/passwd.db"));
+        assert!(!is_backup_name("ryuri-x/y.db"));
+    }
+
+    #[tokio::test]
+    async fn test_create_and_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = setup(dir.path()).await;
+
+        let backup = service.create().await.unwrap();
+        let backups = service.list().await.unwrap();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backups[0].name, backup.name);
+        assert!(service.path(&backup.name).await.is_ok());
+        assert!(service.path("ryuri-missing.db").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_restore_rejects_invalid_upload() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = setup(dir.path()).await;
+
+        assert!(matches!(
+            service.restore(b"not a database").await,
+            Err(AppError::BadRequest(_))
+        ));
+
+        // A valid schema without any user would lock everyone out
+        sqlx::query("DELETE FROM users")
+            .execute(&service.pool)
+            .await
+            .unwrap();
+        let backup = service.create().await.unwrap();
+        let data = std::fs::read(service.path(&backup.name).await.unwrap()).unwrap();
+        assert!(matches!(
+            service.restore(&data).await,
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(!pending_restore_path(&dir.path().join("ryuri.db")).exists());
+    }
+
+    #[tokio::test]
+    async fn test_restore_stages_valid_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = setup(dir.path()).await;
+
+        let backup = service.create().await.unwrap();
+        let data = std::fs::read(service.path(&backup.name).await.unwrap()).unwrap();
+
+        let response = service.restore(&data).await.unwrap();
+        assert!(response.restart_required);
+        assert!(response.safety_backup.name.ends_with("-pre-restore.db"));
+        assert!(pending_restore_path(&dir.path().join("ryuri.db")).exists());
+    }
+}
@@ -3,11 +3,23 @@
 //! This module provides functionality to search and fetch metadata from
 //! the Bangumi.tv API for content items.
 
-use reqwest::Client;
-use rust_i18n::t;
+use std::future::Future;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use reqwest::header::{self, HeaderMap};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+use tracing::warn;
 
 use crate::error::{AppError, Result};
+use crate::models::{MetadataProviderKind, MetadataSearchResult};
+use crate::repository::bangumi::BangumiCacheRepository;
+use crate::services::metadata::{MetadataProvider, ProviderFuture, ScrapeOptions, tag_source};
+use crate::t;
 
 /// Base URL for the Bangumi API.
 const BANGUMI_API_BASE: &str = "https://api.bgm.tv";
@@ -15,6 +27,27 @@ const BANGUMI_API_BASE: &str = "https://api.bgm.tv";
 /// User agent for API requests.
 const USER_AGENT: &str = "ryuri/0.1.1 (https://github.com/tnzzzhlp/ryuri)";
 
+/// Shortest time between two requests to Bangumi.
+const MIN_REQUEST_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Times a request answered with 429 is retried.
+const MAX_RETRIES: u32 = 3;
+
+/// Wait before the first retry when Bangumi gives no `Retry-After`.
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// Longest wait before a retry.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Hours search results and subjects are reused by default.
+pub const DEFAULT_BANGUMI_CACHE_TTL_HOURS: u32 = 24 * 7;
+
+/// Cache kind of search results, keyed by query.
+const SEARCH_CACHE: &str = "search";
+
+/// Cache kind of subjects, keyed by subject ID.
+const SUBJECT_CACHE: &str = "subject";
+
 /// Search result from Bangumi API.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BangumiSearchResult {
@@ -82,9 +115,26 @@ impl From<BangumiSearchItem> for BangumiSearchResult {
 }
 
 /// Service for fetching metadata from Bangumi.tv API.
+///
+/// Requests are spaced out and retried when Bangumi answers with 429.
+/// Search results and subjects are cached in the database: they are reused
+/// until they expire, and afterwards still returned while Bangumi cannot be
+/// reached, so rescans neither hammer Bangumi nor need it to be up.
 pub struct BangumiService {
     client: Client,
-    api_key: Option<String>,
+    /// Access token from the runtime settings.
+    api_key: RwLock<Option<String>>,
+    /// Earliest time the next request may be sent.
+    next_request: tokio::sync::Mutex<Instant>,
+    /// Response cache, if enabled.
+    cache: Option<ResponseCache>,
+}
+
+/// Database cache of Bangumi responses.
+struct ResponseCache {
+    pool: Pool<Sqlite>,
+    /// How long a response is reused before Bangumi is asked again.
+    ttl: chrono::Duration,
 }
 
 impl BangumiService {
@@ -95,7 +145,33 @@ impl BangumiService {
             .build()
             .expect("Failed to create HTTP client");
 
-        Self { client, api_key }
+        Self {
+            client,
+            api_key: RwLock::new(api_key),
+            next_request: tokio::sync::Mutex::new(Instant::now()),
+            cache: None,
+        }
+    }
+
+    /// Cache responses in the database for `ttl_hours` (0 = no cache).
+    pub fn set_cache(&mut self, pool: Pool<Sqlite>, ttl_hours: u32) {
+        self.cache = (ttl_hours > 0).then(|| ResponseCache {
+            pool,
+            ttl: chrono::Duration::hours(ttl_hours as i64),
+        });
+    }
+
+    /// Access token requests are sent with unless a user's own is given.
+    pub fn api_key(&self) -> Option<String> {
+        self.api_key
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Replace the access token, after the runtime settings changed.
+    pub fn set_api_key(&self, api_key: Option<String>) {
+        *self.api_key.write().unwrap_or_else(|e| e.into_inner()) = api_key;
     }
 
     /// Search for subjects on Bangumi by keyword.
@@ -108,10 +184,61 @@ impl BangumiService {
     ///
     /// Requirements: 8.4
     pub async fn search(&self, query: &str) -> Result<Vec<BangumiSearchResult>> {
+        self.search_as(query, ScrapeOptions::default()).await
+    }
+
+    /// [`Self::search`] with the options of a user's scrape: their access
+    /// token instead of the configured one, and whether to skip the cache.
+    pub async fn search_as(
+        &self,
+        query: &str,
+        options: ScrapeOptions<'_>,
+    ) -> Result<Vec<BangumiSearchResult>> {
         if query.trim().is_empty() {
             return Ok(Vec::new());
         }
 
+        let api_key = self.resolve_key(options.api_key);
+        let fetch = self.fetch_search(query, api_key.as_deref());
+        self.cached(SEARCH_CACHE, query, api_key.is_some(), options, fetch)
+            .await
+    }
+
+    /// Get detailed subject information from Bangumi.
+    ///
+    /// # Arguments
+    /// * `bangumi_id` - The Bangumi subject ID
+    ///
+    /// # Returns
+    /// The raw JSON response from Bangumi API, stored as-is for flexibility.
+    ///
+    /// Requirements: 8.4
+    pub async fn get_subject(&self, bangumi_id: i64) -> Result<serde_json::Value> {
+        self.get_subject_as(bangumi_id, ScrapeOptions::default())
+            .await
+    }
+
+    /// [`Self::get_subject`] with the options of a user's scrape: their
+    /// access token instead of the configured one, and whether to skip the
+    /// cache.
+    pub async fn get_subject_as(
+        &self,
+        bangumi_id: i64,
+        options: ScrapeOptions<'_>,
+    ) -> Result<serde_json::Value> {
+        let api_key = self.resolve_key(options.api_key);
+        let key = bangumi_id.to_string();
+        let fetch = self.fetch_subject(bangumi_id, api_key.as_deref());
+        self.cached(SUBJECT_CACHE, &key, api_key.is_some(), options, fetch)
+            .await
+    }
+
+    /// Search Bangumi without the cache.
+    async fn fetch_search(
+        &self,
+        query: &str,
+        api_key: Option<&str>,
+    ) -> Result<Vec<BangumiSearchResult>> {
         // Use the v0 search API with type filter for books (type=1) and anime (type=2)
         // We search both to cover manga and light novels
         let url = format!(
@@ -120,21 +247,18 @@ impl BangumiService {
             urlencoding::encode(query)
         );
 
-        let mut request = self.client.get(&url).query(&[
-            ("type", "1"), // Books (manga, novels)
-            ("responseGroup", "small"),
-            ("max_results", "10"),
-        ]);
-
-        // Add API key if available
-        if let Some(ref key) = self.api_key {
-            request = request.header("Authorization", format!("Bearer {}", key));
-        }
-
-        let response = request
-            .send()
-            .await
-            .map_err(|e| AppError::Internal(format!("Failed to search Bangumi: {}", e)))?;
+        let response = self
+            .send(
+                || {
+                    self.client.get(&url).query(&[
+                        ("type", "1"), // Books (manga, novels)
+                        ("responseGroup", "small"),
+                        ("max_results", "10"),
+                    ])
+                },
+                api_key,
+            )
+            .await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -160,29 +284,15 @@ impl BangumiService {
             .collect())
     }
 
-    /// Get detailed subject information from Bangumi.
-    ///
-    /// # Arguments
-    /// * `bangumi_id` - The Bangumi subject ID
-    ///
-    /// # Returns
-    /// The raw JSON response from Bangumi API, stored as-is for flexibility.
-    ///
-    /// Requirements: 8.4
-    pub async fn get_subject(&self, bangumi_id: i64) -> Result<serde_json::Value> {
+    /// Fetch a subject from Bangumi without the cache.
+    async fn fetch_subject(
+        &self,
+        bangumi_id: i64,
+        api_key: Option<&str>,
+    ) -> Result<serde_json::Value> {
         let url = format!("{}/v0/subjects/{}", BANGUMI_API_BASE, bangumi_id);
 
-        let mut request = self.client.get(&url);
-
-        // Add API key if available
-        if let Some(ref key) = self.api_key {
-            request = request.header("Authorization", format!("Bearer {}", key));
-        }
-
-        let response = request
-            .send()
-            .await
-            .map_err(|e| AppError::Internal(format!("Failed to fetch Bangumi subject: {}", e)))?;
+        let response = self.send(|| self.client.get(&url), api_key).await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -205,38 +315,218 @@ impl BangumiService {
         Ok(subject)
     }
 
-    /// Automatically scrape metadata for content based on its title.
-    ///
-    /// This method searches Bangumi using the content title and fetches
-    /// detailed metadata from the first matching result.
-    ///
-    /// # Arguments
-    /// * `title` - The content title to search for
+    /// Return the cached response for `key` while it has not expired,
+    /// otherwise run `fetch` and cache what it returns.
     ///
-    /// # Returns
-    /// The metadata JSON blob if found, or None if no match was found.
+    /// When `fetch` fails for any other reason than Bangumi not knowing
+    /// the subject, an expired response is returned instead, if any.
+    async fn cached<T: Serialize + DeserializeOwned>(
+        &self,
+        kind: &str,
+        key: &str,
+        authorized: bool,
+        options: ScrapeOptions<'_>,
+        fetch: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        let Some(cache) = &self.cache else {
+            return fetch.await;
+        };
+
+        // Responses that no longer parse are fetched again
+        let mut expired = None;
+        let cached = BangumiCacheRepository::find(&cache.pool, kind, key, authorized).await?;
+        if let Some((response, fetched_at)) = cached
+            && let Ok(value) = serde_json::from_str::<T>(&response)
+        {
+            if !options.refresh_cache && Utc::now() - fetched_at < cache.ttl {
+                return Ok(value);
+            }
+            expired = Some(value);
+        }
+
+        match fetch.await {
+            Ok(value) => {
+                let saved = match serde_json::to_string(&value) {
+                    Ok(response) => {
+                        BangumiCacheRepository::save(&cache.pool, kind, key, authorized, &response)
+                            .await
+                    }
+                    Err(e) => Err(AppError::Internal(e.to_string())),
+                };
+                if let Err(e) = saved {
+                    warn!(kind, key, error = %e, "{}", t!("bangumi.cache_save_failed"));
+                }
+                Ok(value)
+            }
+            Err(e) => match expired {
+                Some(value) if !matches!(e, AppError::NotFound(_)) => {
+                    warn!(kind, key, error = %e, "{}", t!("bangumi.cache_fallback"));
+                    Ok(value)
+                }
+                _ => Err(e),
+            },
+        }
+    }
+
+    /// Check an access token with Bangumi.
     ///
-    /// Requirements: 8.1
-    pub async fn auto_scrape(&self, title: &str) -> Result<Option<serde_json::Value>> {
-        // Search for the title
-        let results = self.search(title).await?;
+    /// Returns the name of the account the token belongs to, or None if
+    /// Bangumi rejects it.
+    pub async fn check_key(&self, api_key: &str) -> Result<Option<String>> {
+        let url = format!("{}/v0/me", BANGUMI_API_BASE);
+
+        let response = self.send(|| self.client.get(&url), Some(api_key)).await?;
 
-        // If no results, return None
-        if results.is_empty() {
+        let status = response.status();
+        if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
             return Ok(None);
         }
+        if !status.is_success() {
+            return Err(AppError::Internal(format!(
+                "Bangumi API returned error: {}",
+                status
+            )));
+        }
+
+        let me: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to parse Bangumi user: {}", e)))?;
+        Ok(Some(
+            me.get("username")
+                .and_then(|name| name.as_str())
+                .unwrap_or_default()
+                .to_string(),
+        ))
+    }
+
+    /// The given access token, else the configured one.
+    fn resolve_key(&self, api_key: Option<&str>) -> Option<String> {
+        api_key
+            .filter(|key| !key.is_empty())
+            .map(str::to_string)
+            .or_else(|| self.api_key())
+    }
+
+    /// Send a request built by `build`, spaced from the previous request
+    /// and retried while Bangumi answers with 429.
+    async fn send(
+        &self,
+        build: impl Fn() -> RequestBuilder,
+        api_key: Option<&str>,
+    ) -> Result<Response> {
+        let mut attempt = 0;
+        loop {
+            self.wait_turn().await;
+
+            let mut request = build();
+            if let Some(key) = api_key {
+                request = request.header("Authorization", format!("Bearer {}", key));
+            }
+            let response = request
+                .send()
+                .await
+                .map_err(|e| AppError::Internal(format!("Failed to reach Bangumi: {}", e)))?;
+
+            if response.status() != StatusCode::TOO_MANY_REQUESTS {
+                return Ok(response);
+            }
+            if attempt == MAX_RETRIES {
+                return Err(AppError::Internal(t!("bangumi.rate_limited").to_string()));
+            }
+
+            let delay = retry_delay(response.headers(), attempt);
+            warn!(
+                attempt = attempt + 1,
+                delay_ms = delay.as_millis() as u64,
+                "{}",
+                t!("bangumi.rate_limited_retry")
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Wait until at least [`MIN_REQUEST_INTERVAL`] has passed since the
+    /// previous request.
+    async fn wait_turn(&self) {
+        let mut next_request = self.next_request.lock().await;
+        let now = Instant::now();
+        if *next_request > now {
+            tokio::time::sleep(*next_request - now).await;
+        }
+        *next_request = Instant::now() + MIN_REQUEST_INTERVAL;
+    }
+}
+
+/// How long to wait before retrying a request Bangumi answered with 429:
+/// its `Retry-After` header when given in seconds, else a delay doubling
+/// with each attempt. Never longer than [`MAX_RETRY_DELAY`].
+fn retry_delay(headers: &HeaderMap, attempt: u32) -> Duration {
+    headers
+        .get(header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or_else(|| RETRY_BASE_DELAY * 2u32.pow(attempt))
+        .min(MAX_RETRY_DELAY)
+}
+
+impl MetadataProvider for BangumiService {
+    fn kind(&self) -> MetadataProviderKind {
+        MetadataProviderKind::Bangumi
+    }
+
+    fn search<'a>(&'a self, query: &'a str) -> ProviderFuture<'a, Vec<MetadataSearchResult>> {
+        MetadataProvider::search_as(self, query, ScrapeOptions::default())
+    }
+
+    fn fetch<'a>(&'a self, id: &'a str) -> ProviderFuture<'a, serde_json::Value> {
+        MetadataProvider::fetch_as(self, id, ScrapeOptions::default())
+    }
 
-        // Get detailed info for the first result
-        let first_result = &results[0];
-        let metadata = self.get_subject(first_result.id).await?;
+    fn search_as<'a>(
+        &'a self,
+        query: &'a str,
+        options: ScrapeOptions<'a>,
+    ) -> ProviderFuture<'a, Vec<MetadataSearchResult>> {
+        Box::pin(async move {
+            let results = BangumiService::search_as(self, query, options).await?;
+            Ok(results
+                .into_iter()
+                .map(|result| MetadataSearchResult {
+                    provider: MetadataProviderKind::Bangumi,
+                    id: result.id.to_string(),
+                    title: result.name,
+                    alt_title: result.name_cn.filter(|name| !name.is_empty()),
+                    summary: result.summary,
+                    image: result.image,
+                })
+                .collect())
+        })
+    }
 
-        Ok(Some(metadata))
+    fn fetch_as<'a>(
+        &'a self,
+        id: &'a str,
+        options: ScrapeOptions<'a>,
+    ) -> ProviderFuture<'a, serde_json::Value> {
+        Box::pin(async move {
+            let subject_id: i64 = id.parse().map_err(|_| {
+                AppError::BadRequest(t!("metadata.invalid_id", id = id).to_string())
+            })?;
+            let mut metadata = self.get_subject_as(subject_id, options).await?;
+            tag_source(&mut metadata, MetadataProviderKind::Bangumi, id);
+            Ok(metadata)
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_fixtures::create_test_db;
+    use serde_json::json;
 
     #[test]
     fn test_bangumi_search_result_from_item() {
@@ -302,9 +592,113 @@ mod tests {
     #[test]
     fn test_bangumi_service_new() {
         let service = BangumiService::new(None);
-        assert!(service.api_key.is_none());
+        assert!(service.api_key().is_none());
 
         let service_with_key = BangumiService::new(Some("test_key".to_string()));
-        assert_eq!(service_with_key.api_key, Some("test_key".to_string()));
+        assert_eq!(service_with_key.api_key(), Some("test_key".to_string()));
+    }
+
+    #[test]
+    fn test_user_key_overrides_configured_key() {
+        let service = BangumiService::new(Some("server".to_string()));
+        assert_eq!(service.resolve_key(None).as_deref(), Some("server"));
+        assert_eq!(service.resolve_key(Some("user")).as_deref(), Some("user"));
+
+        service.set_api_key(None);
+        assert_eq!(service.resolve_key(Some("")), None);
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_delay(&headers, 0), Duration::from_secs(1));
+        assert_eq!(retry_delay(&headers, 2), Duration::from_secs(4));
+
+        headers.insert(header::RETRY_AFTER, "7".parse().unwrap());
+        assert_eq!(retry_delay(&headers, 2), Duration::from_secs(7));
+
+        headers.insert(header::RETRY_AFTER, "3600".parse().unwrap());
+        assert_eq!(retry_delay(&headers, 0), MAX_RETRY_DELAY);
+    }
+
+    async fn cached_service() -> BangumiService {
+        let mut service = BangumiService::new(None);
+        service.set_cache(create_test_db().await, 24);
+        service
+    }
+
+    fn offline() -> AppError {
+        AppError::Internal("offline".to_string())
+    }
+
+    #[tokio::test]
+    async fn test_cache_reuses_responses_until_refreshed() {
+        let service = cached_service().await;
+        let options = ScrapeOptions::default();
+        let fetch = |value: i64| async move { Ok(json!({ "id": value })) };
+
+        let first: serde_json::Value = service
+            .cached(SUBJECT_CACHE, "1", false, options, fetch(1))
+            .await
+            .unwrap();
+        assert_eq!(first, json!({ "id": 1 }));
+
+        // Fresh responses are reused without asking Bangumi
+        let cached: serde_json::Value = service
+            .cached(SUBJECT_CACHE, "1", false, options, async { Err(offline()) })
+            .await
+            .unwrap();
+        assert_eq!(cached, first);
+
+        // Responses fetched with a token are cached apart
+        let authorized: serde_json::Value = service
+            .cached(SUBJECT_CACHE, "1", true, options, fetch(2))
+            .await
+            .unwrap();
+        assert_eq!(authorized, json!({ "id": 2 }));
+
+        let refresh = ScrapeOptions {
+            refresh_cache: true,
+            ..options
+        };
+        let refreshed: serde_json::Value = service
+            .cached(SUBJECT_CACHE, "1", false, refresh, fetch(3))
+            .await
+            .unwrap();
+        assert_eq!(refreshed, json!({ "id": 3 }));
+    }
+
+    #[tokio::test]
+    async fn test_cache_falls_back_while_bangumi_is_unreachable() {
+        let service = cached_service().await;
+        let options = ScrapeOptions::default();
+        let pool = &service.cache.as_ref().unwrap().pool;
+
+        service
+            .cached(SEARCH_CACHE, "title", false, options, async {
+                Ok(vec!["result".to_string()])
+            })
+            .await
+            .unwrap();
+        sqlx::query("UPDATE bangumi_cache SET fetched_at = '2026-01-01T00:00:00Z'")
+            .execute(pool)
+            .await
+            .unwrap();
+
+        let fallback: Vec<String> = service
+            .cached(SEARCH_CACHE, "title", false, options, async {
+                Err(offline())
+            })
+            .await
+            .unwrap();
+        assert_eq!(fallback, vec!["result".to_string()]);
+
+        // A subject Bangumi no longer knows is not served from the cache
+        let missing = service
+            .cached::<Vec<String>>(SEARCH_CACHE, "title", false, options, async {
+                Err(AppError::NotFound("gone".to_string()))
+            })
+            .await;
+        assert!(missing.is_err());
     }
 }
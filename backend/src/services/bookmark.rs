@@ -0,0 +1,125 @@
+//! Bookmarks and notes.
+//!
+//! Users mark places in a chapter with a bookmark, optionally holding a
+//! note or a quote. Bookmarks are private to the user who made them.
+
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, ErrorCode, Result};
+use crate::models::{
+    Bookmark, CreateBookmarkRequest, NewBookmark, UpdateBookmarkRequest, normalize_bookmark_note,
+};
+use crate::repository::bookmark::BookmarkRepository;
+use crate::repository::content::ChapterRepository;
+use crate::services::content::ContentService;
+use crate::t;
+
+/// Service for bookmark operations.
+pub struct BookmarkService;
+
+impl BookmarkService {
+    /// Create a bookmark in a chapter.
+    pub async fn create(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        chapter_id: i64,
+        request: CreateBookmarkRequest,
+    ) -> Result<Bookmark> {
+        if request.position < 0 {
+            return Err(AppError::BadRequest(
+                "Position cannot be negative".to_string(),
+            ));
+        }
+        let note = normalize_bookmark_note(request.note)
+            .map_err(|reason| AppError::BadRequest(reason.to_string()))?;
+
+        let chapter = ChapterRepository::find_by_id(pool, chapter_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound(t!("content.chapter_not_found", id = chapter_id).to_string())
+                    .with_code(ErrorCode::ChapterNotFound)
+            })?;
+
+        if let Some(novel) = &request.novel {
+            novel
+                .validate()
+                .map_err(|reason| AppError::BadRequest(reason.to_string()))?;
+            if !chapter.is_text_based() {
+                return Err(AppError::BadRequest(
+                    t!("progress.novel_not_text", id = chapter_id).to_string(),
+                ));
+            }
+            if chapter.page_count > 0 && novel.section >= chapter.page_count {
+                return Err(AppError::BadRequest(
+                    t!(
+                        "progress.novel_section_out_of_range",
+                        section = novel.section
+                    )
+                    .to_string(),
+                ));
+            }
+        }
+
+        BookmarkRepository::create(
+            pool,
+            NewBookmark {
+                user_id,
+                chapter_id,
+                position: request.position,
+                novel: request.novel,
+                note,
+            },
+        )
+        .await
+    }
+
+    /// List a user's bookmarks, newest first, optionally within one content.
+    pub async fn list(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        content_id: Option<i64>,
+    ) -> Result<Vec<Bookmark>> {
+        BookmarkRepository::list_by_user(pool, user_id, content_id).await
+    }
+
+    /// List a user's bookmarks in a content in reading order.
+    pub async fn list_for_content(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        content_id: i64,
+    ) -> Result<Vec<Bookmark>> {
+        ContentService::get_content(pool, content_id).await?;
+        BookmarkRepository::list_by_content(pool, user_id, content_id).await
+    }
+
+    /// Change the note of a bookmark.
+    pub async fn update(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        id: i64,
+        request: UpdateBookmarkRequest,
+    ) -> Result<Bookmark> {
+        let note = normalize_bookmark_note(request.note)
+            .map_err(|reason| AppError::BadRequest(reason.to_string()))?;
+
+        if !BookmarkRepository::update_note(pool, user_id, id, note.as_deref()).await? {
+            return Err(Self::not_found(id));
+        }
+        BookmarkRepository::find(pool, user_id, id)
+            .await?
+            .ok_or_else(|| Self::not_found(id))
+    }
+
+    /// Delete a bookmark.
+    pub async fn delete(pool: &Pool<Sqlite>, user_id: i64, id: i64) -> Result<()> {
+        if !BookmarkRepository::delete(pool, user_id, id).await? {
+            return Err(Self::not_found(id));
+        }
+        Ok(())
+    }
+
+    fn not_found(id: i64) -> AppError {
+        AppError::NotFound(t!("bookmark.not_found", id = id).to_string())
+            .with_code(ErrorCode::BookmarkNotFound)
+    }
+}
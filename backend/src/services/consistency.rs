@@ -0,0 +1,241 @@
+//! Startup consistency check.
+//!
+//! A crash in the middle of a scan can leave rows that disagree with each
+//! other. Left alone, such drift accumulates silently, so every start
+//! verifies the invariants below, repairs what it safely can and records
+//! what it found in the audit log:
+//! - `contents.chapter_count` matches the number of chapter rows
+//! - every content belongs to an existing scan path, in that path's library
+//! - no chapter outlives its content
+//! - no scratch directory of an interrupted extraction is left behind in
+//!   the configured scratch directory
+//!
+//! Scan tasks saved at shutdown are requeued separately by
+//! `ScanQueueService::restore_tasks`.
+
+use sqlx::{Pool, Sqlite};
+use tracing::{info, warn};
+
+use crate::error::Result;
+use crate::extractors::ArchiveExtractor;
+use crate::models::{AuditAction, NewAuditLogEntry};
+use crate::repository::consistency::ConsistencyRepository;
+use crate::repository::content::ContentRepository;
+use crate::services::audit::AuditService;
+use crate::t;
+
+/// Discrepancies found and repaired by [`ConsistencyService::check_and_repair`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConsistencyReport {
+    /// Contents whose chapter count was corrected.
+    pub chapter_counts_fixed: Vec<i64>,
+    /// Contents deleted because their scan path no longer exists.
+    pub orphaned_contents_removed: Vec<i64>,
+    /// Contents moved to the library of their scan path.
+    pub library_mismatches_fixed: Vec<i64>,
+    /// Number of chapters deleted because their content no longer exists.
+    pub orphaned_chapters_removed: u64,
+    /// Number of leftover extraction directories removed.
+    pub scratch_dirs_removed: usize,
+}
+
+impl ConsistencyReport {
+    /// Whether nothing needed repairing.
+    pub fn is_clean(&self) -> bool {
+        self.chapter_counts_fixed.is_empty()
+            && self.orphaned_contents_removed.is_empty()
+            && self.library_mismatches_fixed.is_empty()
+            && self.orphaned_chapters_removed == 0
+            && self.scratch_dirs_removed == 0
+    }
+
+    /// Summary for the audit log.
+    pub fn details(&self) -> String {
+        format!(
+            "chapter_counts_fixed={:?} orphaned_contents_removed={:?} \
+             library_mismatches_fixed={:?} orphaned_chapters_removed={} scratch_dirs_removed={}",
+            self.chapter_counts_fixed,
+            self.orphaned_contents_removed,
+            self.library_mismatches_fixed,
+            self.orphaned_chapters_removed,
+            self.scratch_dirs_removed
+        )
+    }
+}
+
+/// Service for the startup consistency check.
+pub struct ConsistencyService;
+
+impl ConsistencyService {
+    /// Verify database invariants, repair discrepancies and record them.
+    ///
+    /// Must run before the scan worker starts.
+    pub async fn check_and_repair(
+        pool: &Pool<Sqlite>,
+        audit_service: &AuditService,
+    ) -> Result<ConsistencyReport> {
+        let mut report = ConsistencyReport::default();
+
+        // Contents left behind by a removed scan path; deleting them also
+        // deletes their chapters and reading progress, as the cascade would have
+        for content_id in ConsistencyRepository::find_contents_without_scan_path(pool).await? {
+            ContentRepository::delete(pool, content_id).await?;
+            report.orphaned_contents_removed.push(content_id);
+        }
+
+        report.library_mismatches_fixed =
+            ConsistencyRepository::find_library_mismatches(pool).await?;
+        if !report.library_mismatches_fixed.is_empty() {
+            ConsistencyRepository::fix_library_mismatches(pool).await?;
+        }
+
+        report.orphaned_chapters_removed =
+            ConsistencyRepository::delete_orphaned_chapters(pool).await?;
+
+        // Counted last, after chapters may have been removed above
+        report.chapter_counts_fixed =
+            ConsistencyRepository::find_chapter_count_mismatches(pool).await?;
+        if !report.chapter_counts_fixed.is_empty() {
+            ConsistencyRepository::fix_chapter_counts(pool).await?;
+        }
+
+        report.scratch_dirs_removed = ArchiveExtractor::clean_scratch_dir();
+
+        if report.is_clean() {
+            info!("{}", t!("consistency.clean"));
+        } else {
+            warn!(details = %report.details(), "{}", t!("consistency.repaired"));
+            let entry =
+                NewAuditLogEntry::new(AuditAction::ConsistencyRepaired).details(report.details());
+            audit_service.record(entry).await;
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{
+        TIMESTAMP, create_test_db, create_test_library, create_test_scan_path,
+    };
+
+    async fn setup() -> Pool<Sqlite> {
+        let pool = create_test_db().await;
+        create_test_library(&pool, 1, "First").await;
+        create_test_library(&pool, 2, "Second").await;
+        create_test_scan_path(&pool, 1, 1, "/a").await;
+        pool
+    }
+
+    /// Insert a content as is, so tests can build the drift being repaired.
+    async fn insert_content(pool: &Pool<Sqlite>, id: i64, library_id: i64, scan_path_id: i64) {
+        sqlx::query(
+            r#"
+            INSERT INTO contents
+                (id, library_id, scan_path_id, title, folder_path, chapter_count, created_at, updated_at)
+            VALUES (?, ?, ?, 'Title', ?, 0, ?, ?)
+            "#,
+        )
+        .bind(id)
+        .bind(library_id)
+        .bind(scan_path_id)
+        .bind(format!("/a/{}", id))
+        .bind(TIMESTAMP)
+        .bind(TIMESTAMP)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    /// Insert a chapter without counting it in its content.
+    async fn insert_chapter(pool: &Pool<Sqlite>, content_id: i64, sort_order: i32) {
+        sqlx::query(
+            r#"
+            INSERT INTO chapters (content_id, title, file_path, sort_order)
+            VALUES (?, 'Chapter', ?, ?)
+            "#,
+        )
+        .bind(content_id)
+        .bind(format!("/a/{}/{}.cbz", content_id, sort_order))
+        .bind(sort_order)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_clean_database_needs_no_repair() {
+        let pool = setup().await;
+        insert_content(&pool, 1, 1, 1).await;
+        let audit = AuditService::new(pool.clone());
+
+        let report = ConsistencyService::check_and_repair(&pool, &audit)
+            .await
+            .unwrap();
+        assert_eq!(report.chapter_counts_fixed, Vec::<i64>::new());
+        assert!(report.orphaned_contents_removed.is_empty());
+        assert_eq!(report.orphaned_chapters_removed, 0);
+    }
+
+    #[tokio::test]
+    async fn test_repairs_drift() {
+        let pool = setup().await;
+        insert_content(&pool, 1, 1, 1).await;
+        insert_chapter(&pool, 1, 1).await;
+        insert_chapter(&pool, 1, 2).await;
+        insert_content(&pool, 2, 2, 1).await;
+
+        // Rows that foreign keys would normally prevent
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&pool)
+            .await
+            .unwrap();
+        insert_content(&pool, 3, 1, 99).await;
+        insert_chapter(&pool, 42, 1).await;
+        sqlx::query("PRAGMA foreign_keys = ON")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let audit = AuditService::new(pool.clone());
+        let report = ConsistencyService::check_and_repair(&pool, &audit)
+            .await
+            .unwrap();
+
+        assert_eq!(report.chapter_counts_fixed, vec![1]);
+        assert_eq!(report.orphaned_contents_removed, vec![3]);
+        assert_eq!(report.library_mismatches_fixed, vec![2]);
+        assert_eq!(report.orphaned_chapters_removed, 1);
+
+        let (chapter_count,): (i64,) =
+            sqlx::query_as("SELECT chapter_count FROM contents WHERE id = 1")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(chapter_count, 2);
+
+        let (library_id,): (i64,) = sqlx::query_as("SELECT library_id FROM contents WHERE id = 2")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(library_id, 1);
+
+        let (entries,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM audit_log WHERE action = 'consistency_repaired'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(entries, 1);
+
+        // A second run finds nothing left to repair
+        let report = ConsistencyService::check_and_repair(&pool, &audit)
+            .await
+            .unwrap();
+        assert!(report.chapter_counts_fixed.is_empty());
+        assert!(report.orphaned_contents_removed.is_empty());
+        assert!(report.library_mismatches_fixed.is_empty());
+        assert_eq!(report.orphaned_chapters_removed, 0);
+    }
+}
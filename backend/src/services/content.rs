@@ -3,14 +3,129 @@
 //! This module provides the business logic for content operations including
 //! retrieval, listing, searching, deletion, and chapter management.
 
-use rust_i18n::t;
+use base64::{Engine, prelude::BASE64_STANDARD};
 use sqlx::{Pool, Sqlite};
-use std::path::Path;
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, TryLockError};
+use std::time::SystemTime;
+use tracing::{info, warn};
 
-use crate::error::{AppError, Result};
-use crate::extractors::{ArchiveExtractor, EpubExtractor, PdfExtractor};
-use crate::models::{Chapter, Content};
-use crate::repository::content::{ChapterRepository, ContentRepository};
+use crate::error::{AppError, ErrorCode, Result};
+use crate::extractors::audio::AudioInfo;
+use crate::extractors::epub::{EpubManifest, EpubSection, EpubUrls};
+use crate::extractors::txt::{TxtSplitInfo, TxtSplitText};
+use crate::extractors::{
+    ArchiveReader, AudioExtractor, EpubExtractor, MobiExtractor, PdfExtractor, TxtExtractor,
+    page_media_type,
+};
+use crate::models::{
+    Chapter, Content, ContentFeed, ContentFeedQuery, ContentPage, ContentResponse, MediaError,
+    PageError, PageInfo, PageQuery, ReadingDirection, TextSearchMatch, ThumbnailPreview,
+    thumbnail_etag,
+};
+use crate::repository::content::{
+    ChapterRepository, ContentRepository, PageDimensionRepository, PageErrorRepository,
+};
+use crate::repository::library::{LibraryRepository, ScanPathRepository};
+use crate::repository::metadata::MetadataFailureRepository;
+use crate::repository::metadata_edits::MetadataEditsRepository;
+use crate::repository::progress::ProgressRepository;
+use crate::services::reader_settings::ReaderSettingsService;
+use crate::t;
+use crate::utils::blurhash;
+use crate::utils::cancel::{CancellationFlag, spawn_cancellable};
+use crate::utils::cursor::Cursor;
+use crate::utils::page_cache::{CachedPage, DEFAULT_PAGE_CACHE_BYTES, PageCache};
+use crate::utils::page_image::{self, PagePart, SpreadHalf};
+use crate::utils::placeholder::render_page_placeholder;
+use crate::utils::text_index::{DEFAULT_TEXT_INDEX_BYTES, TextIndexCache};
+use crate::utils::thumbnail::{
+    MAX_THUMBNAIL_SIZE, MIN_THUMBNAIL_SIZE, Thumbnail, ThumbnailFormat, ThumbnailOptions,
+};
+
+/// Default number of open archives kept for page reads.
+pub const DEFAULT_ARCHIVE_CACHE_SIZE: usize = 16;
+
+/// Upper bound for the archive reader cache size.
+pub const MAX_ARCHIVE_CACHE_SIZE: usize = 256;
+
+/// Contents per page of a feed when the client does not ask for a size.
+const DEFAULT_FEED_PAGE_SIZE: i64 = 20;
+
+/// Largest page of a feed a client can ask for.
+const MAX_FEED_PAGE_SIZE: i64 = 100;
+
+/// Width of thumbnail previews when the client does not ask for one.
+const DEFAULT_PREVIEW_WIDTH: u32 = 96;
+
+/// Largest thumbnail preview width a client can ask for.
+const MAX_PREVIEW_WIDTH: u32 = 320;
+
+/// Most thumbnails a client can ask for at once.
+const MAX_THUMBNAIL_BATCH: usize = 200;
+
+/// Contents per page of a library listing when the client asks for pages
+/// without a size.
+const DEFAULT_LIST_PAGE_SIZE: i64 = 100;
+
+/// Largest page of a library listing a client can ask for.
+const MAX_LIST_PAGE_SIZE: i64 = 500;
+
+/// Name of the title order of library listings in their cursors.
+const TITLE_ORDER: &str = "title";
+
+/// Matches returned by a text search when no limit is given.
+const DEFAULT_TEXT_SEARCH_RESULTS: usize = 50;
+
+/// Largest number of matches returned by a text search.
+const MAX_TEXT_SEARCH_RESULTS: usize = 500;
+
+/// Characters of text on each side of a match in its snippet.
+const TEXT_SEARCH_CONTEXT: usize = 40;
+
+/// Extracted chapter texts shared by all text searches.
+static TEXT_INDEXES: LazyLock<TextIndexCache> =
+    LazyLock::new(|| TextIndexCache::new(DEFAULT_TEXT_INDEX_BYTES));
+
+/// Open archives shared by all page requests.
+static ARCHIVE_READERS: LazyLock<ArchiveReaderCache> =
+    LazyLock::new(|| ArchiveReaderCache::new(DEFAULT_ARCHIVE_CACHE_SIZE));
+
+/// Whether pages get a BlurHash when first served.
+static PAGE_BLURHASH: AtomicBool = AtomicBool::new(false);
+
+/// Thumbnails read at a time while backfilling their BlurHashes.
+const BLURHASH_BACKFILL_BATCH: i64 = 100;
+
+/// Halves, tiles and trimmed pages, shared by all page requests.
+static PROCESSED_PAGES: LazyLock<PageCache<ProcessedPageKey>> =
+    LazyLock::new(|| PageCache::new(DEFAULT_PAGE_CACHE_BYTES));
+
+/// Identifies a processed page. The chapter file size is part of the key
+/// so pages of a replaced file are not served.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ProcessedPageKey {
+    chapter_id: i64,
+    chapter_size: i64,
+    page_index: i64,
+    /// `None` for the whole page.
+    part: Option<PagePart>,
+    trim: bool,
+}
+
+/// Page data ready to be served.
+#[derive(Debug, Clone)]
+pub struct PageImage {
+    /// Raw page bytes.
+    pub data: Vec<u8>,
+    /// MIME type of `data`.
+    pub media_type: &'static str,
+    /// True if the page could not be read and `data` is a generated PNG placeholder.
+    pub is_placeholder: bool,
+}
 
 /// Service for content management operations.
 pub struct ContentService;
@@ -20,7 +135,10 @@ impl ContentService {
     pub async fn get_content(pool: &Pool<Sqlite>, id: i64) -> Result<Content> {
         ContentRepository::find_by_id(pool, id)
             .await?
-            .ok_or_else(|| AppError::NotFound(t!("content.id_not_found", id = id).to_string()))
+            .ok_or_else(|| {
+                AppError::NotFound(t!("content.id_not_found", id = id).to_string())
+                    .with_code(ErrorCode::ContentNotFound)
+            })
     }
 
     /// List all contents for a library.
@@ -28,7 +146,119 @@ impl ContentService {
         ContentRepository::list_by_library(pool, library_id).await
     }
 
-    /// Search contents by title within a library.
+    /// Get a page of a library's contents by title, starting after the
+    /// cursor `after` if given. Returns the page and the cursor of the next
+    /// one, unless the page is the last.
+    pub async fn list_contents_page(
+        pool: &Pool<Sqlite>,
+        library_id: i64,
+        after: Option<&str>,
+        limit: Option<i64>,
+    ) -> Result<(Vec<Content>, Option<String>)> {
+        let limit = limit
+            .unwrap_or(DEFAULT_LIST_PAGE_SIZE)
+            .clamp(1, MAX_LIST_PAGE_SIZE);
+        let after = after
+            .map(|cursor| Cursor::decode(cursor, TITLE_ORDER))
+            .transpose()?;
+
+        let contents = ContentRepository::list_by_library_after(
+            pool,
+            library_id,
+            after.as_ref().map(|c| (c.key.as_str(), c.id)),
+            limit,
+        )
+        .await?;
+        let next_cursor = contents
+            .last()
+            .filter(|_| contents.len() as i64 == limit)
+            .map(|last| Cursor::new(TITLE_ORDER, last.title.as_str(), last.id).encode());
+
+        Ok((contents, next_cursor))
+    }
+
+    /// Fill in how many chapters of each content the user has not read to
+    /// the end.
+    pub async fn fill_unread_counts(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        responses: &mut [ContentResponse],
+    ) -> Result<()> {
+        let ids: Vec<i64> = responses.iter().map(|r| r.id).collect();
+        let counts = ProgressRepository::count_by_contents(pool, user_id, &ids).await?;
+        for response in responses {
+            response.unread_count = Some(counts.get(&response.id).map_or(0, |c| c.unfinished()));
+        }
+        Ok(())
+    }
+
+    /// Get a page of recently added or recently updated contents across all
+    /// libraries, or one library when the query names it, leaving out those
+    /// rated above `max_age_rating`.
+    ///
+    /// Pages are found by offset, or after the cursor of the previous page
+    /// when the query has one, which stays fast however deep the page.
+    pub async fn list_feed(
+        pool: &Pool<Sqlite>,
+        feed: ContentFeed,
+        query: &ContentFeedQuery,
+        max_age_rating: Option<i32>,
+    ) -> Result<ContentPage> {
+        let page = query.page.unwrap_or(1).max(1);
+        let page_size = query
+            .page_size
+            .unwrap_or(DEFAULT_FEED_PAGE_SIZE)
+            .clamp(1, MAX_FEED_PAGE_SIZE);
+
+        let contents = match &query.after {
+            Some(after) => {
+                let cursor = Cursor::decode(after, feed.as_str())?;
+                ContentRepository::list_feed_after(
+                    pool,
+                    feed,
+                    query.library_id,
+                    max_age_rating,
+                    &cursor.key,
+                    cursor.id,
+                    page_size,
+                )
+                .await?
+            }
+            None => {
+                ContentRepository::list_feed(
+                    pool,
+                    feed,
+                    query.library_id,
+                    max_age_rating,
+                    page_size,
+                    (page - 1) * page_size,
+                )
+                .await?
+            }
+        };
+        let total =
+            ContentRepository::count_visible(pool, query.library_id, max_age_rating).await?;
+
+        // Only a full page can be followed by another
+        let next_cursor = match contents.last() {
+            Some(last) if contents.len() as i64 == page_size => {
+                ContentRepository::feed_sort_key(pool, feed, last.id)
+                    .await?
+                    .map(|key| Cursor::new(feed.as_str(), key, last.id).encode())
+            }
+            _ => None,
+        };
+
+        Ok(ContentPage {
+            items: contents.into_iter().map(ContentResponse::from).collect(),
+            total,
+            page,
+            page_size,
+            next_cursor,
+        })
+    }
+
+    /// Search contents by title or alternative title within a library.
     pub async fn search_contents(
         pool: &Pool<Sqlite>,
         library_id: i64,
@@ -47,6 +277,139 @@ impl ContentService {
         ContentRepository::delete(pool, id).await
     }
 
+    /// List content in the trash: content whose folder was found missing.
+    pub async fn list_trash(pool: &Pool<Sqlite>) -> Result<Vec<Content>> {
+        ContentRepository::list_missing(pool).await
+    }
+
+    /// Take content out of the trash so it is listed again.
+    ///
+    /// If its folder is still missing, the next scan moves it back.
+    pub async fn restore_content(pool: &Pool<Sqlite>, id: i64) -> Result<Content> {
+        let _content = Self::get_content(pool, id).await?;
+
+        if !ContentRepository::restore(pool, id).await? {
+            return Err(AppError::BadRequest(
+                t!("content.not_in_trash", id = id).to_string(),
+            ));
+        }
+
+        Self::get_content(pool, id).await
+    }
+
+    /// Delete all content in the trash. Returns the number deleted.
+    pub async fn purge_trash(pool: &Pool<Sqlite>) -> Result<u64> {
+        ContentRepository::purge_missing(pool, None).await
+    }
+
+    /// Move a content to another scan path, possibly of another library.
+    ///
+    /// The content keeps its ID, so its chapters, reading progress and
+    /// metadata stay attached. Its folder keeps its name inside the scan
+    /// path: with `move_files` the folder is moved there on disk, otherwise
+    /// it must already be there.
+    pub async fn move_content(
+        pool: &Pool<Sqlite>,
+        id: i64,
+        scan_path_id: i64,
+        move_files: bool,
+    ) -> Result<Content> {
+        let content = Self::get_content(pool, id).await?;
+        let scan_path = ScanPathRepository::find_by_id(pool, scan_path_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound(
+                    t!("scan_queue.scan_path_not_found", id = scan_path_id).to_string(),
+                )
+            })?;
+        // Libraries being deleted take no new content
+        if LibraryRepository::find_by_id(pool, scan_path.library_id)
+            .await?
+            .is_none()
+        {
+            return Err(AppError::NotFound(
+                t!("library.id_not_found", id = scan_path.library_id).to_string(),
+            )
+            .with_code(ErrorCode::LibraryNotFound));
+        }
+
+        let source = PathBuf::from(&content.folder_path);
+        let Some(name) = source.file_name() else {
+            return Err(AppError::BadRequest(
+                t!("content.move_folder_missing", path = content.folder_path).to_string(),
+            ));
+        };
+        let destination = Path::new(&scan_path.path).join(name);
+        let folder_path = destination.to_string_lossy().to_string();
+        if destination == source && scan_path_id == content.scan_path_id {
+            return Ok(content);
+        }
+        if let Some(other) =
+            ContentRepository::find_by_folder_path(pool, scan_path.library_id, &folder_path).await?
+            && other.id != id
+        {
+            return Err(AppError::BadRequest(
+                t!("content.folder_path_exists_msg", path = folder_path).to_string(),
+            ));
+        }
+
+        let moved = move_files && destination != source;
+        if moved {
+            if tokio::fs::try_exists(&destination).await? {
+                return Err(AppError::BadRequest(
+                    t!("content.move_destination_exists", path = folder_path).to_string(),
+                ));
+            }
+            tokio::fs::rename(&source, &destination)
+                .await
+                .map_err(|e| move_error(e, &content.folder_path, &folder_path))?;
+        } else if !tokio::fs::try_exists(&destination).await? {
+            return Err(AppError::BadRequest(
+                t!("content.move_folder_missing", path = folder_path).to_string(),
+            ));
+        }
+
+        // Chapters keep their place inside the folder
+        let chapters = ChapterRepository::list_by_content(pool, id).await?;
+        let chapter_paths: Vec<(i64, String)> = chapters
+            .iter()
+            .filter_map(|chapter| {
+                let relative = Path::new(&chapter.file_path).strip_prefix(&source).ok()?;
+                let path = if relative.as_os_str().is_empty() {
+                    destination.clone()
+                } else {
+                    destination.join(relative)
+                };
+                Some((chapter.id, path.to_string_lossy().to_string()))
+            })
+            .collect();
+
+        if let Err(e) = ContentRepository::move_to_scan_path(
+            pool,
+            id,
+            scan_path.library_id,
+            scan_path_id,
+            &folder_path,
+            &chapter_paths,
+        )
+        .await
+        {
+            // Put the folder back so it matches the database again
+            if moved && let Err(e) = tokio::fs::rename(&destination, &source).await {
+                warn!(content_id = id, error = %e, "{}", t!("content.move_rollback_failed"));
+            }
+            return Err(e);
+        }
+        info!(
+            content_id = id,
+            from = %content.folder_path,
+            to = %folder_path,
+            "{}", t!("content.moved")
+        );
+
+        Self::get_content(pool, id).await
+    }
+
     /// List all chapters for a content.
     pub async fn list_chapters(pool: &Pool<Sqlite>, content_id: i64) -> Result<Vec<Chapter>> {
         // First verify the content exists
@@ -66,13 +429,14 @@ impl ContentService {
     /// * `page_index` - 0-based index of the page within the chapter
     ///
     /// # Returns
-    /// The raw image bytes for the requested page.
+    /// The raw bytes for the requested page and their MIME type, detected
+    /// from the data or the name of the archive entry.
     pub async fn get_page(
         pool: &Pool<Sqlite>,
         content_id: i64,
         chapter_id: i64,
         page_index: i64,
-    ) -> Result<Vec<u8>> {
+    ) -> Result<PageImage> {
         // Get the content to verify it exists
         let _content = Self::get_content(pool, content_id).await?;
 
@@ -85,6 +449,7 @@ impl ContentService {
             .find(|c| c.id == chapter_id)
             .ok_or_else(|| {
                 AppError::NotFound(t!("content.chapter_not_found", id = chapter_id).to_string())
+                    .with_code(ErrorCode::ChapterNotFound)
             })?;
 
         // Verify this is an image-based or text-based chapter
@@ -94,35 +459,583 @@ impl ContentService {
             ));
         }
 
+        let chapter = chapter.clone();
+
+        // Extraction runs on the blocking pool and stops early if the request
+        // is dropped, e.g. when a prefetching client disconnects
+        let (name, data) =
+            spawn_cancellable(move |cancel| Self::extract_named_page(&chapter, page_index, cancel))
+                .await?;
+
+        Ok(PageImage {
+            media_type: page_media_type(&name, &data),
+            data,
+            is_placeholder: false,
+        })
+    }
+
+    /// Extract a page from a chapter file, checking `cancel` between steps.
+    pub(crate) fn extract_page(
+        chapter: &Chapter,
+        page_index: i64,
+        cancel: &CancellationFlag,
+    ) -> Result<Vec<u8>> {
+        Self::extract_named_page(chapter, page_index, cancel).map(|(_, data)| data)
+    }
+
+    /// Extract a page from a chapter file along with its entry name.
+    ///
+    /// Comic archives and image folders are read through the shared reader
+    /// cache, so paging through a chapter opens the archive only once.
+    fn extract_named_page(
+        chapter: &Chapter,
+        page_index: i64,
+        cancel: &CancellationFlag,
+    ) -> Result<(String, Vec<u8>)> {
         let archive_path = Path::new(&chapter.file_path);
 
+        if chapter.is_audio() {
+            return Err(AppError::BadRequest(
+                t!("audio.no_pages", id = chapter.id).to_string(),
+            ));
+        }
+
+        if !chapter.is_text_based() && !PdfExtractor::is_supported(archive_path) {
+            return ARCHIVE_READERS.with_reader(archive_path, cancel, |reader| {
+                let file_name = Self::page_file(reader.files(), page_index)?.to_string();
+                cancel.check()?;
+                let data = reader.read_file(&file_name, cancel)?;
+                Ok((file_name, data))
+            });
+        }
+
+        // Plain text novels are paged by their virtual chapters
+        if TxtExtractor::is_supported(archive_path) {
+            let split = usize::try_from(page_index)
+                .map_err(|_| {
+                    AppError::NotFound(t!("komga.page_not_found", page = page_index).to_string())
+                        .with_code(ErrorCode::PageNotFound)
+                })
+                .and_then(|index| TxtExtractor::extract_split(archive_path, index))?;
+            return Ok((format!("{}.txt", split.index), split.text.into_bytes()));
+        }
+
+        // Kindle books are paged by their sections
+        if MobiExtractor::is_supported(archive_path) {
+            let index = usize::try_from(page_index).map_err(|_| {
+                AppError::NotFound(t!("komga.page_not_found", page = page_index).to_string())
+                    .with_code(ErrorCode::PageNotFound)
+            })?;
+            let text = MobiExtractor::extract_section(archive_path, index)?;
+            return Ok((format!("{}.txt", index), text.into_bytes()));
+        }
+
         // List files/images/sections in the archive
         let files = if chapter.is_text_based() {
             EpubExtractor::list_files(archive_path)?
-        } else if PdfExtractor::is_supported(archive_path) {
+        } else {
             PdfExtractor::list_files(archive_path)?
+        };
+
+        let file_name = Self::page_file(&files, page_index)?;
+        cancel.check()?;
+
+        // Extract and return the content
+        let data = if chapter.is_text_based() {
+            EpubExtractor::extract_file(archive_path, file_name)?.into_bytes()
         } else {
-            ArchiveExtractor::list_files(archive_path)?
+            PdfExtractor::extract_file(archive_path, file_name)?
         };
+        Ok((file_name.to_string(), data))
+    }
 
-        // Validate page index
-        if page_index < 0 || page_index as usize >= files.len() {
-            return Err(AppError::NotFound(
-                t!("komga.page_not_found", page = page_index).to_string(),
+    /// Name of the page at `page_index` in a page list.
+    fn page_file(files: &[String], page_index: i64) -> Result<&str> {
+        usize::try_from(page_index)
+            .ok()
+            .and_then(|index| files.get(index))
+            .map(String::as_str)
+            .ok_or_else(|| {
+                AppError::NotFound(t!("komga.page_not_found", page = page_index).to_string())
+                    .with_code(ErrorCode::PageNotFound)
+            })
+    }
+
+    /// List the pages of an image chapter with their sizes, marking
+    /// double-page spreads and tall strips.
+    ///
+    /// Sizes are read from the page headers on first use and stored until
+    /// the chapter file changes. With `split`, each spread is listed as two
+    /// virtual pages in reading order: the right half first for
+    /// right-to-left content. With `slice`, each tall page is listed as its
+    /// tiles.
+    pub async fn list_page_info(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        chapter_id: i64,
+        split: bool,
+        slice: bool,
+    ) -> Result<Vec<PageInfo>> {
+        let chapter = Self::find_chapter(pool, content_id, chapter_id).await?;
+        if !chapter.is_image_based() {
+            return Err(AppError::BadRequest(
+                t!("content.not_image_chapter", id = chapter_id).to_string(),
             ));
         }
 
-        let file_name = &files[page_index as usize];
+        let mut dimensions =
+            PageDimensionRepository::list_by_chapter(pool, chapter.id, chapter.size).await?;
+        if dimensions.is_empty() {
+            let reading = chapter.clone();
+            dimensions =
+                spawn_cancellable(move |cancel| Self::read_page_dimensions(&reading, cancel))
+                    .await?;
+            PageDimensionRepository::replace(pool, chapter.id, chapter.size, &dimensions).await?;
+        }
 
-        // Extract and return the content
-        if chapter.is_text_based() {
-            let text = EpubExtractor::extract_file(archive_path, file_name)?;
-            Ok(text.into_bytes())
-        } else if PdfExtractor::is_supported(archive_path) {
-            PdfExtractor::extract_file(archive_path, file_name)
+        let right_to_left = split
+            && ReaderSettingsService::get_for_content(pool, content_id)
+                .await?
+                .settings
+                .reading_direction
+                == ReadingDirection::RightToLeft;
+        let mut pages = Self::page_info_list(&dimensions, split, slice, right_to_left);
+
+        let blurhashes =
+            PageDimensionRepository::list_blurhashes(pool, chapter.id, chapter.size).await?;
+        for page in &mut pages {
+            if page.half.is_none() && page.tile.is_none() {
+                page.blurhash = blurhashes.get(&page.index).cloned();
+            }
+        }
+        Ok(pages)
+    }
+
+    /// Read the size of every page of an image chapter. Pages that cannot
+    /// be read are 0x0.
+    fn read_page_dimensions(
+        chapter: &Chapter,
+        cancel: &CancellationFlag,
+    ) -> Result<Vec<(u32, u32)>> {
+        let path = Path::new(&chapter.file_path);
+        let count = if PdfExtractor::is_supported(path) {
+            PdfExtractor::page_count(path)?
+        } else {
+            ARCHIVE_READERS.with_reader(path, cancel, |reader| Ok(reader.files().len()))?
+        };
+
+        (0..count as i64)
+            .map(|index| {
+                cancel.check()?;
+                Ok(Self::extract_page(chapter, index, cancel)
+                    .ok()
+                    .and_then(|data| page_image::dimensions(&data))
+                    .unwrap_or((0, 0)))
+            })
+            .collect()
+    }
+
+    /// Build the page list from page sizes, optionally splitting spreads
+    /// into their halves and slicing tall pages into tiles.
+    fn page_info_list(
+        dimensions: &[(u32, u32)],
+        split: bool,
+        slice: bool,
+        right_to_left: bool,
+    ) -> Vec<PageInfo> {
+        let halves = if right_to_left {
+            [SpreadHalf::Right, SpreadHalf::Left]
         } else {
-            ArchiveExtractor::extract_file(archive_path, file_name)
+            [SpreadHalf::Left, SpreadHalf::Right]
+        };
+
+        let mut pages = Vec::with_capacity(dimensions.len());
+        for (index, &(width, height)) in dimensions.iter().enumerate() {
+            let page = PageInfo {
+                index: index as i64,
+                width,
+                height,
+                spread: page_image::is_spread(width, height),
+                half: None,
+                tall: page_image::tile_count(height) > 1,
+                tile: None,
+                blurhash: None,
+            };
+            if slice && page.tall {
+                let tiles = (0..).map_while(|tile| page_image::tile_bounds(height, tile));
+                for (tile, (_, tile_height)) in tiles.enumerate() {
+                    pages.push(PageInfo {
+                        height: tile_height,
+                        tile: Some(tile as u32),
+                        ..page.clone()
+                    });
+                }
+                continue;
+            }
+            if !(split && page.spread) {
+                pages.push(page);
+                continue;
+            }
+            for half in halves {
+                let half_width = match half {
+                    SpreadHalf::Left => width / 2,
+                    SpreadHalf::Right => width - width / 2,
+                };
+                pages.push(PageInfo {
+                    width: half_width,
+                    half: Some(half),
+                    ..page.clone()
+                });
+            }
+        }
+        pages
+    }
+
+    /// Get a page as a page request asks for it: whole, one half of a
+    /// spread or one tile of a tall page, with its margins trimmed if the
+    /// request or the reader settings of the content ask for it.
+    pub async fn get_requested_page(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        chapter_id: i64,
+        page_index: i64,
+        query: PageQuery,
+    ) -> Result<PageImage> {
+        let part = match (query.half, query.tile) {
+            (Some(_), Some(_)) => {
+                return Err(AppError::BadRequest(
+                    t!("content.page_part_conflict").to_string(),
+                ));
+            }
+            (Some(half), None) => Some(PagePart::Half(half)),
+            (None, Some(tile)) => Some(PagePart::Tile(tile)),
+            (None, None) => None,
+        };
+        let trim = match query.trim {
+            Some(trim) => trim,
+            None => {
+                ReaderSettingsService::get_for_content(pool, content_id)
+                    .await?
+                    .settings
+                    .trim_margins
+            }
+        };
+        // Tiles of a strip have to line up, so their margins are kept
+        let trim = trim && !matches!(part, Some(PagePart::Tile(_)));
+
+        let page = if part.is_none() && !trim {
+            Self::get_page_or_placeholder(pool, content_id, chapter_id, page_index).await?
+        } else {
+            Self::get_processed_page(pool, content_id, chapter_id, page_index, part, trim).await?
+        };
+
+        if part.is_none() && !page.is_placeholder && PAGE_BLURHASH.load(Ordering::Relaxed) {
+            Self::record_page_blurhash(pool, chapter_id, page_index, &page).await;
+        }
+        Ok(page)
+    }
+
+    /// Compute the BlurHash of a served page in the background, if the page
+    /// has been listed and has none yet. Failures are only logged.
+    async fn record_page_blurhash(
+        pool: &Pool<Sqlite>,
+        chapter_id: i64,
+        page_index: i64,
+        page: &PageImage,
+    ) {
+        let needed = async {
+            let Some(chapter) = ChapterRepository::find_by_id(pool, chapter_id).await? else {
+                return Ok(None);
+            };
+            let needed =
+                PageDimensionRepository::needs_blurhash(pool, chapter_id, page_index, chapter.size)
+                    .await?;
+            Ok::<_, AppError>(needed.then_some(chapter.size))
+        };
+        let file_size = match needed.await {
+            Ok(Some(file_size)) => file_size,
+            Ok(None) => return,
+            Err(e) => {
+                warn!(chapter_id, page_index, error = %e, "{}", t!("content.page_blurhash_failed"));
+                return;
+            }
+        };
+
+        let pool = pool.clone();
+        let data = page.data.clone();
+        tokio::spawn(async move {
+            let hash = tokio::task::spawn_blocking(move || blurhash::from_image_data(&data)).await;
+            let Ok(Some(hash)) = hash else {
+                return;
+            };
+            if let Err(e) = PageDimensionRepository::set_blurhash(
+                &pool, chapter_id, page_index, file_size, &hash,
+            )
+            .await
+            {
+                warn!(chapter_id, page_index, error = %e, "{}", t!("content.page_blurhash_failed"));
+            }
+        });
+    }
+
+    /// Set whether pages get a BlurHash when first served.
+    pub fn set_page_blurhash(enabled: bool) {
+        PAGE_BLURHASH.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Compute the BlurHashes of thumbnails stored without one, such as
+    /// those stored before BlurHashes were introduced. Returns how many
+    /// were computed.
+    pub async fn backfill_thumbnail_blurhashes(pool: &Pool<Sqlite>) -> Result<u64> {
+        let mut after_id = 0;
+        let mut filled = 0;
+        loop {
+            let batch =
+                ContentRepository::list_missing_blurhashes(pool, after_id, BLURHASH_BACKFILL_BATCH)
+                    .await?;
+            let Some(&(last_id, _, _)) = batch.last() else {
+                break;
+            };
+            after_id = last_id;
+
+            let hashes = tokio::task::spawn_blocking(move || {
+                batch
+                    .into_iter()
+                    .filter_map(|(id, etag, thumbnail)| {
+                        Some((id, etag, blurhash::from_image_data(&thumbnail)?))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+
+            for (id, etag, hash) in hashes {
+                ContentRepository::set_thumbnail_blurhash(pool, id, etag.as_deref(), &hash).await?;
+                filled += 1;
+            }
+        }
+
+        if filled > 0 {
+            info!(
+                count = filled,
+                "{}",
+                t!("content.thumbnail_blurhashes_filled")
+            );
+        }
+        Ok(filled)
+    }
+
+    /// Get a processed page: cut to a part, trimmed, or both.
+    ///
+    /// Results are computed on first request and kept in memory, so readers
+    /// paging through a sliced strip decode it once. Placeholders for
+    /// broken pages are returned untouched, and a page that is not tall is
+    /// its own only tile.
+    async fn get_processed_page(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        chapter_id: i64,
+        page_index: i64,
+        part: Option<PagePart>,
+        trim: bool,
+    ) -> Result<PageImage> {
+        let chapter = Self::find_chapter(pool, content_id, chapter_id).await?;
+        let key = |part| ProcessedPageKey {
+            chapter_id,
+            chapter_size: chapter.size,
+            page_index,
+            part,
+            trim,
+        };
+        if let Some(cached) = PROCESSED_PAGES.get(&key(part)) {
+            return Ok(PageImage {
+                data: cached.data.as_ref().clone(),
+                media_type: cached.media_type,
+                is_placeholder: false,
+            });
+        }
+
+        let page = Self::get_page_or_placeholder(pool, content_id, chapter_id, page_index).await?;
+        if page.is_placeholder {
+            return Ok(page);
+        }
+        let tile_not_found = || {
+            AppError::NotFound(t!("content.tile_not_found", page = page_index).to_string())
+                .with_code(ErrorCode::PageNotFound)
+        };
+        if let Some(PagePart::Tile(tile)) = part {
+            let height = page_image::dimensions(&page.data).map_or(0, |(_, height)| height);
+            if page_image::tile_count(height) == 1 {
+                return if tile == 0 {
+                    Ok(page)
+                } else {
+                    Err(tile_not_found())
+                };
+            }
+        }
+
+        let parts = tokio::task::spawn_blocking(move || match part {
+            None => page_image::trim_margins(&page.data, None).map(|trimmed| {
+                // Pages without margins are cached as stored
+                vec![(part, trimmed.unwrap_or((page.data, page.media_type)))]
+            }),
+            Some(PagePart::Half(half)) if trim => page_image::trim_margins(&page.data, Some(half))
+                .map(|trimmed| trimmed.into_iter().map(|half| (part, half)).collect()),
+            Some(PagePart::Half(half)) => {
+                page_image::split_spread(&page.data, half).map(|half| vec![(part, half)])
+            }
+            Some(PagePart::Tile(_)) => page_image::slice_tiles(&page.data).map(|tiles| {
+                (0..)
+                    .map(|tile| Some(PagePart::Tile(tile)))
+                    .zip(tiles)
+                    .collect::<Vec<_>>()
+            }),
+        })
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))??;
+
+        // Every tile is cached, as the reader is about to ask for the rest
+        let mut requested = None;
+        for (cut, (data, media_type)) in parts {
+            let cached = CachedPage {
+                data: Arc::new(data),
+                media_type,
+            };
+            if cut == part {
+                requested = Some(cached.clone());
+            }
+            PROCESSED_PAGES.insert(key(cut), cached);
+        }
+
+        let requested = requested.ok_or_else(tile_not_found)?;
+        Ok(PageImage {
+            data: Arc::unwrap_or_clone(requested.data),
+            media_type: requested.media_type,
+            is_placeholder: false,
+        })
+    }
+
+    /// Set how many open archives are kept for page reads (0 disables the
+    /// cache). Readers beyond the new size are closed.
+    pub fn set_archive_cache_size(size: usize) {
+        ARCHIVE_READERS.set_capacity(size);
+    }
+
+    /// Get a page, substituting a placeholder image if it is broken.
+    ///
+    /// For image-based chapters, extraction failures and undecodable images
+    /// are recorded against the chapter and replaced by a generated PNG
+    /// showing the page number and error, so one corrupt page does not
+    /// break the reader. Lookup errors (missing content, chapter or page)
+    /// are still returned as errors.
+    pub async fn get_page_or_placeholder(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        chapter_id: i64,
+        page_index: i64,
+    ) -> Result<PageImage> {
+        let result = Self::get_page(pool, content_id, chapter_id, page_index).await;
+
+        let is_image_based = ChapterRepository::find_by_id(pool, chapter_id)
+            .await?
+            .is_some_and(|c| c.is_image_based());
+
+        let error = match result {
+            Ok(page) if !is_image_based => return Ok(page),
+            Ok(page) => match Self::image_decode_error(&page.data) {
+                None => return Ok(page),
+                Some(error) => error,
+            },
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    AppError::NotFound(_)
+                        | AppError::BadRequest(_)
+                        | AppError::Database(_)
+                        | AppError::Cancelled
+                ) =>
+            {
+                return Err(e);
+            }
+            Err(e) if !is_image_based => return Err(e),
+            Err(e) => e.to_string(),
+        };
+
+        warn!(chapter_id, page_index, error = %error, "{}", t!("content.page_broken"));
+        if let Err(e) = PageErrorRepository::record(pool, chapter_id, page_index, &error).await {
+            warn!(chapter_id, page_index, error = %e, "{}", t!("content.page_error_record_failed"));
         }
+
+        Ok(PageImage {
+            data: render_page_placeholder(page_index + 1, &error)?,
+            media_type: "image/png",
+            is_placeholder: true,
+        })
+    }
+
+    /// Check that image data has a readable header.
+    ///
+    /// Returns the decoding error for corrupt images. Formats the image
+    /// library does not recognize or support are passed through untouched.
+    fn image_decode_error(data: &[u8]) -> Option<String> {
+        if data.is_empty() {
+            return Some(t!("content.page_empty").to_string());
+        }
+
+        let reader = image::ImageReader::new(Cursor::new(data))
+            .with_guessed_format()
+            .ok()?;
+        reader.format()?;
+
+        match reader.into_dimensions() {
+            Err(image::ImageError::Decoding(e)) => Some(e.to_string()),
+            _ => None,
+        }
+    }
+
+    /// List pages of a chapter that failed to load.
+    pub async fn list_page_errors(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        chapter_id: i64,
+    ) -> Result<Vec<PageError>> {
+        Self::find_chapter(pool, content_id, chapter_id).await?;
+        PageErrorRepository::list_by_chapter(pool, chapter_id).await
+    }
+
+    /// Clear recorded page failures of a chapter, e.g. after the file was replaced.
+    pub async fn clear_page_errors(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        chapter_id: i64,
+    ) -> Result<()> {
+        Self::find_chapter(pool, content_id, chapter_id).await?;
+        PageErrorRepository::delete_by_chapter(pool, chapter_id).await?;
+        Ok(())
+    }
+
+    /// List chapters whose file cannot be read or had pages that failed to
+    /// load, so they can be replaced.
+    pub async fn list_media_errors(
+        pool: &Pool<Sqlite>,
+        library_id: Option<i64>,
+    ) -> Result<Vec<MediaError>> {
+        ChapterRepository::list_media_errors(pool, library_id).await
+    }
+
+    /// Find a chapter and check that it belongs to the given content.
+    pub(crate) async fn find_chapter(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        chapter_id: i64,
+    ) -> Result<Chapter> {
+        ChapterRepository::find_by_id(pool, chapter_id)
+            .await?
+            .filter(|c| c.content_id == content_id)
+            .ok_or_else(|| {
+                AppError::NotFound(t!("content.chapter_not_found", id = chapter_id).to_string())
+                    .with_code(ErrorCode::ChapterNotFound)
+            })
     }
 
     /// Get the text content of a novel chapter.
@@ -150,7 +1063,8 @@ impl ContentService {
             let chapter_id = chapter_index as i64; // Approximation for error message
             return Err(AppError::NotFound(
                 t!("content.chapter_not_found", id = chapter_id).to_string(),
-            ));
+            )
+            .with_code(ErrorCode::ChapterNotFound));
         }
 
         let chapter = &chapters[chapter_index as usize];
@@ -162,10 +1076,187 @@ impl ContentService {
             ));
         }
 
-        let archive_path = Path::new(&chapter.file_path);
+        Self::read_chapter_text(Path::new(&chapter.file_path))
+    }
+
+    /// Search the text of a novel's chapters for `query`, ignoring case.
+    /// Returns up to `limit` matches in reading order.
+    pub async fn search_text(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        query: &str,
+        limit: Option<usize>,
+    ) -> Result<Vec<TextSearchMatch>> {
+        let query = query.trim().to_string();
+        if query.is_empty() {
+            return Err(AppError::BadRequest(
+                t!("content.text_search_empty").to_string(),
+            ));
+        }
+        let limit = limit
+            .unwrap_or(DEFAULT_TEXT_SEARCH_RESULTS)
+            .clamp(1, MAX_TEXT_SEARCH_RESULTS);
+
+        Self::get_content(pool, content_id).await?;
+        let chapters = ChapterRepository::list_by_content(pool, content_id).await?;
+
+        spawn_cancellable(move |cancel| {
+            let mut matches = Vec::new();
+            for (index, chapter) in chapters.iter().enumerate() {
+                if !chapter.is_text_based() {
+                    continue;
+                }
+                cancel.check()?;
+
+                let path = Path::new(&chapter.file_path);
+                let text = match TEXT_INDEXES.get_or_index(path, Self::read_chapter_text) {
+                    Ok(text) => text,
+                    Err(e) => {
+                        let chapter_id = chapter.id;
+                        warn!(chapter_id, error = %e, "{}", t!("content.text_search_failed"));
+                        continue;
+                    }
+                };
+
+                let remaining = limit - matches.len();
+                matches.extend(
+                    text.find(&query, remaining, TEXT_SEARCH_CONTEXT)
+                        .into_iter()
+                        .map(|hit| TextSearchMatch {
+                            chapter_id: chapter.id,
+                            chapter_index: index as i32,
+                            chapter_title: chapter.title.clone(),
+                            offset: hit.offset,
+                            snippet: hit.snippet,
+                        }),
+                );
+                if matches.len() >= limit {
+                    break;
+                }
+            }
+            Ok(matches)
+        })
+        .await
+    }
+
+    /// Extract the plain text of a text-based chapter file.
+    fn read_chapter_text(path: &Path) -> Result<String> {
+        if TxtExtractor::is_supported(path) {
+            return TxtExtractor::read_text(path);
+        }
+        if MobiExtractor::is_supported(path) {
+            return MobiExtractor::extract_all_text(path);
+        }
 
         // Extract all text from the chapter archive
-        EpubExtractor::extract_all_text(archive_path)
+        EpubExtractor::extract_all_text(path)
+    }
+
+    /// Get the spine and table of contents of an EPUB chapter.
+    pub async fn get_epub_manifest(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        chapter_id: i64,
+    ) -> Result<EpubManifest> {
+        let path = Self::find_epub(pool, content_id, chapter_id).await?;
+        spawn_cancellable(move |_| EpubExtractor::manifest(&path)).await
+    }
+
+    /// Get a section of an EPUB chapter as sanitized HTML, with references
+    /// into the book rewritten to `urls`.
+    pub async fn get_epub_section(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        chapter_id: i64,
+        index: usize,
+        urls: EpubUrls,
+    ) -> Result<EpubSection> {
+        let path = Self::find_epub(pool, content_id, chapter_id).await?;
+        spawn_cancellable(move |_| EpubExtractor::section(&path, index, &urls)).await
+    }
+
+    /// Get an embedded image, stylesheet or font of an EPUB chapter and its
+    /// media type.
+    pub async fn get_epub_resource(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        chapter_id: i64,
+        resource_path: String,
+        urls: EpubUrls,
+    ) -> Result<(Vec<u8>, String)> {
+        let path = Self::find_epub(pool, content_id, chapter_id).await?;
+        spawn_cancellable(move |_| EpubExtractor::resource(&path, &resource_path, &urls)).await
+    }
+
+    /// File path of a chapter that must be an EPUB.
+    async fn find_epub(pool: &Pool<Sqlite>, content_id: i64, chapter_id: i64) -> Result<PathBuf> {
+        let chapter = Self::find_chapter(pool, content_id, chapter_id).await?;
+        let path = PathBuf::from(chapter.file_path);
+        if !EpubExtractor::is_supported(&path) {
+            return Err(AppError::BadRequest(
+                t!("epub.not_epub", id = chapter_id).to_string(),
+            ));
+        }
+        Ok(path)
+    }
+
+    /// List the virtual chapters a `.txt` chapter is split into at its
+    /// chapter headings.
+    pub async fn list_txt_splits(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        chapter_id: i64,
+    ) -> Result<Vec<TxtSplitInfo>> {
+        let path = Self::find_txt(pool, content_id, chapter_id).await?;
+        spawn_cancellable(move |_| TxtExtractor::list_splits(&path)).await
+    }
+
+    /// Get the text of a virtual chapter of a `.txt` chapter.
+    pub async fn get_txt_split(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        chapter_id: i64,
+        index: usize,
+    ) -> Result<TxtSplitText> {
+        let path = Self::find_txt(pool, content_id, chapter_id).await?;
+        spawn_cancellable(move |_| TxtExtractor::extract_split(&path, index)).await
+    }
+
+    /// File path of a chapter that must be a `.txt` file.
+    async fn find_txt(pool: &Pool<Sqlite>, content_id: i64, chapter_id: i64) -> Result<PathBuf> {
+        let chapter = Self::find_chapter(pool, content_id, chapter_id).await?;
+        let path = PathBuf::from(chapter.file_path);
+        if !TxtExtractor::is_supported(&path) {
+            return Err(AppError::BadRequest(
+                t!("txt.not_txt", id = chapter_id).to_string(),
+            ));
+        }
+        Ok(path)
+    }
+
+    /// Get the duration and chapter markers of an audiobook chapter.
+    pub async fn get_audio_info(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        chapter_id: i64,
+    ) -> Result<AudioInfo> {
+        let path = Self::find_audio(pool, content_id, chapter_id).await?;
+        spawn_cancellable(move |_| AudioExtractor::probe(&path)).await
+    }
+
+    /// File path of a chapter that must be an audiobook file.
+    pub(crate) async fn find_audio(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        chapter_id: i64,
+    ) -> Result<PathBuf> {
+        let chapter = Self::find_chapter(pool, content_id, chapter_id).await?;
+        if !chapter.is_audio() {
+            return Err(AppError::BadRequest(
+                t!("audio.not_audio", id = chapter_id).to_string(),
+            ));
+        }
+        Ok(PathBuf::from(chapter.file_path))
     }
 
     /// Get the page count for a specific chapter.
@@ -185,19 +1276,28 @@ impl ContentService {
             let chapter_id = chapter_index as i64; // Approximation for error message
             return Err(AppError::NotFound(
                 t!("content.chapter_not_found", id = chapter_id).to_string(),
-            ));
+            )
+            .with_code(ErrorCode::ChapterNotFound));
         }
 
         let chapter = &chapters[chapter_index as usize];
         let archive_path = Path::new(&chapter.file_path);
 
         // Branch based on file type
-        if chapter.is_text_based() {
+        if TxtExtractor::is_supported(archive_path) {
+            TxtExtractor::chapter_count(archive_path)
+        } else if MobiExtractor::is_supported(archive_path) {
+            MobiExtractor::chapter_count(archive_path)
+        } else if chapter.is_audio() {
+            AudioExtractor::chapter_count(archive_path)
+        } else if chapter.is_text_based() {
             EpubExtractor::chapter_count(archive_path)
         } else if PdfExtractor::is_supported(archive_path) {
             PdfExtractor::page_count(archive_path)
         } else {
-            ArchiveExtractor::page_count(archive_path)
+            ARCHIVE_READERS.with_reader(archive_path, &CancellationFlag::new(), |reader| {
+                Ok(reader.files().len())
+            })
         }
     }
 
@@ -231,8 +1331,24 @@ impl ContentService {
 
         // Convert metadata to Option<Option<Value>> for the repository
         let metadata_update = metadata.map(Some);
+        let resolves_failure = metadata_update.is_some();
 
-        ContentRepository::update_info(pool, id, title, metadata_update, thumbnail_update).await
+        let content =
+            ContentRepository::update_info(pool, id, title, metadata_update, thumbnail_update)
+                .await?;
+
+        // Metadata set by hand resolves a failed scrape
+        if resolves_failure {
+            MetadataFailureRepository::delete_by_content(pool, id).await?;
+
+            // New metadata replaces the manual edits that are not locked
+            if let Some(mut edits) = MetadataEditsRepository::find(pool, id).await? {
+                edits.clear_unlocked();
+                MetadataEditsRepository::save(pool, id, &edits).await?;
+            }
+        }
+
+        Ok(content)
     }
 
     /// Get thumbnail for a content.
@@ -242,12 +1358,395 @@ impl ContentService {
     /// * `content_id` - ID of the content
     ///
     /// # Returns
-    /// The thumbnail image bytes if available.
-    pub async fn get_thumbnail(pool: &Pool<Sqlite>, content_id: i64) -> Result<Vec<u8>> {
+    /// The thumbnail image bytes and their version tag if available.
+    pub async fn get_thumbnail(pool: &Pool<Sqlite>, content_id: i64) -> Result<(Vec<u8>, String)> {
         let content = Self::get_content(pool, content_id).await?;
 
-        content.thumbnail.ok_or_else(|| {
+        let thumbnail = content.thumbnail.ok_or_else(|| {
             AppError::NotFound(t!("content.thumbnail_not_found", id = content_id).to_string())
+                .with_code(ErrorCode::ThumbnailNotFound)
+        })?;
+        let etag = content
+            .thumbnail_etag
+            .unwrap_or_else(|| thumbnail_etag(&thumbnail));
+        Ok((thumbnail, etag))
+    }
+
+    /// Prepare a stored thumbnail for a client's `Accept` header.
+    ///
+    /// Thumbnails in a format the client does not accept are converted to
+    /// JPEG at `quality` on a blocking thread.
+    pub async fn negotiate_thumbnail(
+        thumbnail: Vec<u8>,
+        etag: String,
+        accept: Option<String>,
+        quality: u8,
+    ) -> Result<Thumbnail> {
+        tokio::task::spawn_blocking(move || {
+            Thumbnail::negotiate(thumbnail, etag, accept.as_deref(), quality)
+        })
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))
+    }
+
+    /// Get the thumbnail of a content as a JPEG fitting in `width` by
+    /// `height`, for clients that only show JPEG covers.
+    pub async fn thumbnail_jpeg(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        width: u32,
+        height: u32,
+        quality: u8,
+    ) -> Result<Vec<u8>> {
+        let (thumbnail, _) = Self::get_thumbnail(pool, content_id).await?;
+        let options = ThumbnailOptions {
+            width: width.clamp(MIN_THUMBNAIL_SIZE, MAX_THUMBNAIL_SIZE),
+            height: height.clamp(MIN_THUMBNAIL_SIZE, MAX_THUMBNAIL_SIZE),
+            format: ThumbnailFormat::Jpeg,
+            quality,
+        };
+        spawn_cancellable(move |_| options.encode(&thumbnail)).await
+    }
+
+    /// Small JPEG previews of the thumbnails of several contents, by content
+    /// ID, so list views need one request instead of one per content.
+    /// Contents without a thumbnail are left out, as are thumbnails that
+    /// cannot be decoded.
+    pub async fn thumbnail_previews(
+        pool: &Pool<Sqlite>,
+        ids: &[i64],
+        width: Option<u32>,
+        quality: u8,
+    ) -> Result<HashMap<i64, ThumbnailPreview>> {
+        if ids.len() > MAX_THUMBNAIL_BATCH {
+            return Err(AppError::BadRequest(
+                t!(
+                    "content.thumbnail_batch_too_large",
+                    max = MAX_THUMBNAIL_BATCH
+                )
+                .to_string(),
+            ));
+        }
+        let width = width
+            .unwrap_or(DEFAULT_PREVIEW_WIDTH)
+            .clamp(MIN_THUMBNAIL_SIZE, MAX_PREVIEW_WIDTH);
+        let options = ThumbnailOptions {
+            width,
+            height: width * 2,
+            format: ThumbnailFormat::Jpeg,
+            quality,
+        };
+
+        let thumbnails = ContentRepository::list_thumbnails(pool, ids).await?;
+        tokio::task::spawn_blocking(move || {
+            thumbnails
+                .into_iter()
+                .filter_map(|(id, etag, data)| {
+                    let preview = options.encode(&data).ok()?;
+                    let etag = etag.unwrap_or_else(|| thumbnail_etag(&data));
+                    Some((
+                        id,
+                        ThumbnailPreview {
+                            mime_type: ThumbnailFormat::Jpeg.mime_type().to_string(),
+                            etag: format!("{etag}-w{width}"),
+                            data: BASE64_STANDARD.encode(&preview),
+                        },
+                    ))
+                })
+                .collect()
         })
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))
+    }
+}
+
+/// A cached reader and the file state it was opened for.
+struct CachedReader {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    len: u64,
+    reader: Arc<Mutex<ArchiveReader>>,
+}
+
+/// Least recently used cache of open archive readers.
+///
+/// Readers are keyed by path and modification time (plus size, for
+/// filesystems with coarse timestamps); one whose file changed since it was
+/// opened is dropped on the next lookup. At most `capacity` readers, and so
+/// file handles, are kept open.
+pub(crate) struct ArchiveReaderCache {
+    capacity: AtomicUsize,
+    /// Least recently used first.
+    entries: Mutex<Vec<CachedReader>>,
+}
+
+impl ArchiveReaderCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity: AtomicUsize::new(capacity),
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Change the number of readers kept, closing the least recently used ones.
+    pub(crate) fn set_capacity(&self, capacity: usize) {
+        self.capacity.store(capacity, Ordering::Relaxed);
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let excess = entries.len().saturating_sub(capacity);
+        entries.drain(..excess);
+    }
+
+    /// Run `f` with a reader for `path`, opening and caching one if needed.
+    ///
+    /// A reader busy with another request is not waited for: the call opens
+    /// a temporary reader instead, so parallel page prefetches don't queue up
+    /// behind each other.
+    pub(crate) fn with_reader<T>(
+        &self,
+        path: &Path,
+        cancel: &CancellationFlag,
+        f: impl FnOnce(&mut ArchiveReader) -> Result<T>,
+    ) -> Result<T> {
+        let metadata = std::fs::metadata(path)?;
+        let modified = metadata.modified().ok();
+        let len = metadata.len();
+
+        if self.capacity.load(Ordering::Relaxed) == 0 {
+            return f(&mut ArchiveReader::open(path, cancel)?);
+        }
+
+        let reader = match self.lookup(path, modified, len) {
+            Some(reader) => reader,
+            None => {
+                let reader = Arc::new(Mutex::new(ArchiveReader::open(path, cancel)?));
+                self.insert(CachedReader {
+                    path: path.to_path_buf(),
+                    modified,
+                    len,
+                    reader: Arc::clone(&reader),
+                });
+                reader
+            }
+        };
+
+        match reader.try_lock() {
+            Ok(mut reader) => f(&mut reader),
+            Err(TryLockError::WouldBlock) => f(&mut ArchiveReader::open(path, cancel)?),
+            Err(TryLockError::Poisoned(_)) => {
+                // A read panicked midway; don't trust the reader's state
+                self.remove(path);
+                f(&mut ArchiveReader::open(path, cancel)?)
+            }
+        }
+    }
+
+    /// Find a current reader for `path` and mark it as most recently used.
+    /// A reader for an older version of the file is dropped.
+    fn lookup(
+        &self,
+        path: &Path,
+        modified: Option<SystemTime>,
+        len: u64,
+    ) -> Option<Arc<Mutex<ArchiveReader>>> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let index = entries.iter().position(|entry| entry.path == path)?;
+        let entry = entries.remove(index);
+        if entry.modified != modified || entry.len != len {
+            return None;
+        }
+        let reader = Arc::clone(&entry.reader);
+        entries.push(entry);
+        Some(reader)
+    }
+
+    fn insert(&self, entry: CachedReader) {
+        let capacity = self.capacity.load(Ordering::Relaxed);
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        // Another request may have opened the same file meanwhile
+        entries.retain(|cached| cached.path != entry.path);
+        entries.push(entry);
+        let excess = entries.len().saturating_sub(capacity);
+        entries.drain(..excess);
+    }
+
+    fn remove(&self, path: &Path) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|entry| entry.path != path);
+    }
+
+    #[cfg(test)]
+    fn cached_paths(&self) -> Vec<PathBuf> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.iter().map(|entry| entry.path.clone()).collect()
+    }
+}
+
+/// Turn a failed rename of a content folder into an error for the client.
+///
+/// A rename cannot cross filesystems, so moving to a scan path on another
+/// disk is a bad request rather than an internal error.
+fn move_error(error: std::io::Error, source: &str, destination: &str) -> AppError {
+    match error.kind() {
+        std::io::ErrorKind::CrossesDevices => {
+            AppError::BadRequest(t!("content.move_cross_device", path = destination).to_string())
+        }
+        std::io::ErrorKind::NotFound => {
+            AppError::BadRequest(t!("content.move_folder_missing", path = source).to_string())
+        }
+        _ => error.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{
+        create_test_content, create_test_db, create_test_library, create_test_scan_path,
+    };
+    use std::io::Write;
+
+    fn write_zip(path: &Path, pages: &[(&str, &[u8])]) {
+        let file = std::fs::File::create(path).unwrap();
+        let mut zip = zip::ZipWriter::new(file);
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        for (name, data) in pages {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    fn read_page(cache: &ArchiveReaderCache, path: &Path, index: i64) -> Result<Vec<u8>> {
+        let cancel = CancellationFlag::new();
+        cache.with_reader(path, &cancel, |reader| {
+            let name = ContentService::page_file(reader.files(), index)?.to_string();
+            reader.read_file(&name, &cancel)
+        })
+    }
+
+    #[test]
+    fn test_page_info_splits_spreads_in_reading_order() {
+        let dimensions = [(1000, 1400), (2001, 1400), (0, 0)];
+
+        let pages = ContentService::page_info_list(&dimensions, false, false, true);
+        assert_eq!(pages.len(), 3);
+        assert!(!pages[0].spread && pages[1].spread && !pages[2].spread);
+
+        let pages = ContentService::page_info_list(&dimensions, true, false, true);
+        let halves: Vec<_> = pages.iter().map(|p| (p.index, p.half, p.width)).collect();
+        assert_eq!(
+            halves,
+            vec![
+                (0, None, 1000),
+                (1, Some(SpreadHalf::Right), 1001),
+                (1, Some(SpreadHalf::Left), 1000),
+                (2, None, 0),
+            ]
+        );
+
+        let pages = ContentService::page_info_list(&dimensions, true, false, false);
+        assert_eq!(pages[1].half, Some(SpreadHalf::Left));
+    }
+
+    #[test]
+    fn test_page_info_slices_tall_pages() {
+        let dimensions = [(800, 1200), (800, 5000)];
+
+        let pages = ContentService::page_info_list(&dimensions, false, false, false);
+        assert_eq!(pages.len(), 2);
+        assert!(!pages[0].tall && pages[1].tall);
+
+        let pages = ContentService::page_info_list(&dimensions, false, true, false);
+        let tiles: Vec<_> = pages.iter().map(|p| (p.index, p.tile, p.height)).collect();
+        assert_eq!(
+            tiles,
+            vec![
+                (0, None, 1200),
+                (1, Some(0), 1667),
+                (1, Some(1), 1667),
+                (1, Some(2), 1666),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reader_is_reused_until_file_changes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("chapter.cbz");
+        write_zip(&path, &[("page2.jpg", b"2"), ("page10.jpg", b"10")]);
+
+        let cache = ArchiveReaderCache::new(4);
+        assert_eq!(read_page(&cache, &path, 1).unwrap(), b"10");
+        let metadata = path.metadata().unwrap();
+        let first = cache.lookup(&path, metadata.modified().ok(), metadata.len());
+        assert_eq!(read_page(&cache, &path, 0).unwrap(), b"2");
+        let second = cache.lookup(&path, metadata.modified().ok(), metadata.len());
+        assert!(Arc::ptr_eq(&first.unwrap(), &second.unwrap()));
+
+        write_zip(&path, &[("page1.jpg", b"new page")]);
+        assert_eq!(read_page(&cache, &path, 0).unwrap(), b"new page");
+        assert!(read_page(&cache, &path, 1).is_err());
+        assert_eq!(cache.cached_paths(), vec![path]);
+    }
+
+    #[test]
+    fn test_least_recently_used_reader_is_evicted() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let paths: Vec<PathBuf> = (0..3)
+            .map(|i| {
+                let folder = dir.path().join(format!("chapter{}", i));
+                std::fs::create_dir(&folder).unwrap();
+                std::fs::write(folder.join("page1.jpg"), [i as u8]).unwrap();
+                folder
+            })
+            .collect();
+
+        let cache = ArchiveReaderCache::new(2);
+        read_page(&cache, &paths[0], 0).unwrap();
+        read_page(&cache, &paths[1], 0).unwrap();
+        read_page(&cache, &paths[0], 0).unwrap();
+        read_page(&cache, &paths[2], 0).unwrap();
+        assert_eq!(
+            cache.cached_paths(),
+            vec![paths[0].clone(), paths[2].clone()]
+        );
+
+        cache.set_capacity(1);
+        assert_eq!(cache.cached_paths(), vec![paths[2].clone()]);
+
+        cache.set_capacity(0);
+        assert_eq!(read_page(&cache, &paths[1], 0).unwrap(), [1]);
+        assert!(cache.cached_paths().is_empty());
+    }
+
+    #[test]
+    fn test_move_across_filesystems_is_a_bad_request() {
+        let error = std::io::Error::from(std::io::ErrorKind::CrossesDevices);
+        let error = move_error(error, "/a/Series", "/mnt/b/Series");
+        assert!(matches!(error, AppError::BadRequest(_)));
+
+        let error = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        let error = move_error(error, "/a/Series", "/mnt/b/Series");
+        assert!(matches!(error, AppError::FileSystem(_)));
+    }
+
+    #[tokio::test]
+    async fn test_failed_move_keeps_content_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let (from, to) = (dir.path().join("a"), dir.path().join("b"));
+        std::fs::create_dir(&to).unwrap();
+        let pool = create_test_db().await;
+        create_test_library(&pool, 1, "One").await;
+        create_test_scan_path(&pool, 1, 1, &from.to_string_lossy()).await;
+        create_test_scan_path(&pool, 2, 1, &to.to_string_lossy()).await;
+        create_test_content(&pool, 1, 1, "Series").await;
+
+        // The folder to move is not on disk
+        let result = ContentService::move_content(&pool, 1, 2, true).await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+        let content = ContentService::get_content(&pool, 1).await.unwrap();
+        assert_eq!(content.scan_path_id, 1);
+        assert!(!to.join("Series").exists());
     }
 }
@@ -0,0 +1,82 @@
+//! Per-user content status.
+//!
+//! Each user can mark a content as a favorite and give it a reading status
+//! such as `reading` or `dropped`. The status records intent and is never
+//! changed by reading progress.
+
+use std::collections::HashMap;
+
+use sqlx::{Pool, Sqlite};
+
+use crate::error::Result;
+use crate::models::{Content, ContentStatus, ContentStatusFilter, UpdateContentStatusRequest};
+use crate::repository::content_status::ContentStatusRepository;
+use crate::services::content::ContentService;
+
+/// Service for per-user favorites and reading statuses.
+pub struct ContentStatusService;
+
+impl ContentStatusService {
+    /// Get a user's status for a content, unset if the user has not marked
+    /// it.
+    pub async fn get(pool: &Pool<Sqlite>, user_id: i64, content_id: i64) -> Result<ContentStatus> {
+        ContentService::get_content(pool, content_id).await?;
+        Ok(ContentStatusRepository::find(pool, user_id, content_id)
+            .await?
+            .unwrap_or_else(|| ContentStatus::unset(content_id)))
+    }
+
+    /// Replace a user's status for a content. Clearing both the favorite
+    /// flag and the status removes the entry.
+    pub async fn set(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        content_id: i64,
+        request: UpdateContentStatusRequest,
+    ) -> Result<ContentStatus> {
+        ContentService::get_content(pool, content_id).await?;
+
+        if !request.favorite && request.status.is_none() {
+            ContentStatusRepository::delete(pool, user_id, content_id).await?;
+            return Ok(ContentStatus::unset(content_id));
+        }
+        ContentStatusRepository::upsert(pool, user_id, content_id, request.favorite, request.status)
+            .await
+    }
+
+    /// List a user's marked contents matching the filter.
+    pub async fn list(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        filter: &ContentStatusFilter,
+    ) -> Result<Vec<ContentStatus>> {
+        ContentStatusRepository::list_by_user(pool, user_id, filter).await
+    }
+
+    /// Keep the contents whose status for the user passes the filter.
+    pub async fn filter_contents(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        filter: &ContentStatusFilter,
+        contents: Vec<Content>,
+    ) -> Result<Vec<Content>> {
+        if filter.is_empty() {
+            return Ok(contents);
+        }
+
+        let statuses: HashMap<i64, ContentStatus> =
+            ContentStatusRepository::list_by_user(pool, user_id, &ContentStatusFilter::default())
+                .await?
+                .into_iter()
+                .map(|status| (status.content_id, status))
+                .collect();
+
+        Ok(contents
+            .into_iter()
+            .filter(|content| match statuses.get(&content.id) {
+                Some(status) => filter.matches(status),
+                None => filter.matches(&ContentStatus::unset(content.id)),
+            })
+            .collect())
+    }
+}
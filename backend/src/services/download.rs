@@ -0,0 +1,420 @@
+//! Chapter and series downloads.
+//!
+//! Chapter files are sent as they are on disk. RAR archives and image
+//! folders can instead be repacked into CBZ while the response is sent:
+//! pages are stored uncompressed in a ZIP written straight into the
+//! response body, so no more than one page is held in memory at a time.
+//! Whole series are sent the same way, as a ZIP of their chapters.
+
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use sqlx::{Pool, Sqlite};
+use tokio::io::DuplexStream;
+use tokio_util::io::SyncIoBridge;
+use tracing::warn;
+use zip::write::{SimpleFileOptions, StreamWriter};
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::error::{AppError, Result};
+use crate::extractors::ArchiveReader;
+use crate::models::{Chapter, IMAGE_FOLDER_FILE_TYPE};
+use crate::repository::content::ChapterRepository;
+use crate::services::content::ContentService;
+use crate::t;
+use crate::utils::cancel::CancellationFlag;
+
+/// Bytes buffered between the ZIP writer and the response body.
+const PIPE_CAPACITY: usize = 256 * 1024;
+
+/// ZIP writer whose output is streamed to a response body.
+pub type ZipStreamWriter = ZipWriter<StreamWriter<SyncIoBridge<DuplexStream>>>;
+
+/// Format a chapter is downloaded in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadFormat {
+    /// The chapter file as stored. Image folders are sent as CBZ.
+    #[default]
+    Original,
+    /// A CBZ archive; RAR archives and image folders are repacked.
+    /// PDF and EPUB chapters are sent as they are.
+    Cbz,
+}
+
+/// How a chapter is sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChapterDownload {
+    /// Send the file at `path` unchanged.
+    File { path: PathBuf, file_name: String },
+    /// Repack the pages at `path` into a CBZ while sending it.
+    Repack { path: PathBuf, file_name: String },
+}
+
+impl ChapterDownload {
+    /// File name offered to the client.
+    pub fn file_name(&self) -> &str {
+        match self {
+            Self::File { file_name, .. } | Self::Repack { file_name, .. } => file_name,
+        }
+    }
+}
+
+/// A series ready to be downloaded as a ZIP of its chapters.
+#[derive(Debug, Clone)]
+pub struct SeriesDownload {
+    /// File name offered to the client.
+    pub file_name: String,
+    format: DownloadFormat,
+    /// Chapters in reading order with their size in bytes.
+    chapters: Vec<(ChapterDownload, u64)>,
+}
+
+/// Service for downloading chapter files.
+pub struct DownloadService;
+
+impl DownloadService {
+    /// Find a chapter of a content and decide how it is sent.
+    pub async fn chapter(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        chapter_id: i64,
+        format: DownloadFormat,
+    ) -> Result<ChapterDownload> {
+        let chapter = ContentService::find_chapter(pool, content_id, chapter_id).await?;
+        Ok(Self::plan(&chapter, format))
+    }
+
+    /// Decide how a chapter is sent in `format`.
+    pub fn plan(chapter: &Chapter, format: DownloadFormat) -> ChapterDownload {
+        let path = PathBuf::from(&chapter.file_path);
+        let name = |part: Option<&std::ffi::OsStr>| {
+            part.map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_else(|| chapter.title.clone())
+        };
+
+        let is_folder = chapter.file_type == IMAGE_FOLDER_FILE_TYPE;
+        let is_rar = matches!(chapter.file_type.as_str(), "cbr" | "rar");
+        let cbz = format == DownloadFormat::Cbz;
+
+        if is_folder {
+            // Folder names may contain dots that are not extensions
+            let file_name = format!("{}.cbz", name(path.file_name()));
+            ChapterDownload::Repack { path, file_name }
+        } else if cbz && is_rar {
+            let file_name = format!("{}.cbz", name(path.file_stem()));
+            ChapterDownload::Repack { path, file_name }
+        } else if cbz && chapter.file_type == "zip" {
+            // A ZIP of images already is a CBZ
+            let file_name = format!("{}.cbz", name(path.file_stem()));
+            ChapterDownload::File { path, file_name }
+        } else {
+            let file_name = name(path.file_name());
+            ChapterDownload::File { path, file_name }
+        }
+    }
+
+    /// Find the chapters of a content for a series download.
+    ///
+    /// Fails before anything is sent if a chapter file is missing, rather
+    /// than cutting the download short.
+    pub async fn series(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        format: DownloadFormat,
+    ) -> Result<SeriesDownload> {
+        let content = ContentService::get_content(pool, content_id).await?;
+        let chapters = ChapterRepository::list_by_content(pool, content_id).await?;
+
+        if let Some(missing) = chapters.iter().find(|c| !Path::new(&c.file_path).exists()) {
+            return Err(AppError::NotFound(
+                t!("download.file_missing", path = missing.file_path).to_string(),
+            ));
+        }
+
+        Ok(SeriesDownload {
+            file_name: format!("{}.zip", safe_file_name(&content.title)),
+            format,
+            chapters: chapters
+                .iter()
+                .map(|c| (Self::plan(c, format), c.size.max(0) as u64))
+                .collect(),
+        })
+    }
+
+    /// Write a series download as a ZIP streamed to the returned reader.
+    ///
+    /// Each chapter is stored under its file name. Image folders become a
+    /// folder of pages, or a nested CBZ when CBZ was requested.
+    pub fn stream_series(download: SeriesDownload) -> DuplexStream {
+        Self::stream_zip(move |zip| {
+            let mut names = HashSet::new();
+            for (chapter, size) in download.chapters {
+                let options = SimpleFileOptions::default()
+                    .compression_method(CompressionMethod::Stored)
+                    .large_file(size >= u64::from(u32::MAX));
+                let name = unique_name(&mut names, chapter.file_name());
+
+                match chapter {
+                    ChapterDownload::File { path, .. } => {
+                        zip.start_file(name, options).map_err(zip_error)?;
+                        std::io::copy(&mut std::fs::File::open(&path)?, zip)?;
+                    }
+                    ChapterDownload::Repack { path, .. }
+                        if download.format == DownloadFormat::Original =>
+                    {
+                        let folder = name.trim_end_matches(".cbz");
+                        Self::write_pages(zip, &path, Some(folder))?;
+                    }
+                    ChapterDownload::Repack { path, .. } => {
+                        zip.start_file(name, options).map_err(zip_error)?;
+                        let mut cbz = ZipWriter::new_stream(&mut *zip);
+                        Self::write_pages(&mut cbz, &path, None)?;
+                        cbz.finish().map_err(zip_error)?;
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Repack the pages of a chapter into a CBZ streamed to the returned reader.
+    pub fn stream_cbz(path: PathBuf) -> DuplexStream {
+        Self::stream_zip(move |zip| Self::write_pages(zip, &path, None))
+    }
+
+    /// Run `write` on the blocking pool, streaming the ZIP it produces to
+    /// the returned reader.
+    ///
+    /// Dropping the reader, e.g. when the client disconnects, makes the next
+    /// write fail and stops the writer. Other failures are logged and end the
+    /// stream early, since the response has already started.
+    pub fn stream_zip<F>(write: F) -> DuplexStream
+    where
+        F: FnOnce(&mut ZipStreamWriter) -> Result<()> + Send + 'static,
+    {
+        let (reader, writer) = tokio::io::duplex(PIPE_CAPACITY);
+        let bridge = SyncIoBridge::new(writer);
+
+        tokio::task::spawn_blocking(move || {
+            let mut zip = ZipWriter::new_stream(bridge);
+            let result = write(&mut zip).and_then(|()| {
+                let mut bridge = zip.finish().map_err(zip_error)?.into_inner();
+                bridge.flush()?;
+                bridge.shutdown()?;
+                Ok(())
+            });
+
+            match result {
+                Ok(()) => {}
+                Err(AppError::FileSystem(e)) if e.kind() == std::io::ErrorKind::BrokenPipe => {}
+                Err(e) => warn!(error = %e, "{}", t!("download.stream_failed")),
+            }
+        });
+
+        reader
+    }
+
+    /// Store the pages of the chapter at `path` in `zip`, inside the folder
+    /// `prefix` if given.
+    pub fn write_pages<W: Write>(
+        zip: &mut ZipWriter<StreamWriter<W>>,
+        path: &Path,
+        prefix: Option<&str>,
+    ) -> Result<()> {
+        let cancel = CancellationFlag::new();
+        let mut reader = ArchiveReader::open(path, &cancel)?;
+        // Images are already compressed
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+
+        for name in reader.files().to_vec() {
+            let data = reader.read_file(&name, &cancel)?;
+            let options = options.large_file(data.len() as u64 >= u64::from(u32::MAX));
+            let entry = match prefix {
+                Some(prefix) => format!("{}/{}", prefix, name),
+                None => name,
+            };
+            zip.start_file(entry, options).map_err(zip_error)?;
+            zip.write_all(&data)?;
+        }
+        Ok(())
+    }
+}
+
+/// `name` with path separators and control characters replaced, for use
+/// as a download file name.
+fn safe_file_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if matches!(c, '/' | '\\') || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    let name = name.trim();
+    if name.is_empty() {
+        "download".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// `name`, numbered if it was already used in the same archive.
+fn unique_name(used: &mut HashSet<String>, name: &str) -> String {
+    let path = Path::new(name);
+    let (stem, extension) = match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(ext)) => (
+            stem.to_string_lossy().into_owned(),
+            format!(".{}", ext.to_string_lossy()),
+        ),
+        _ => (name.to_string(), String::new()),
+    };
+
+    let mut candidate = name.to_string();
+    let mut n = 2;
+    while !used.insert(candidate.clone()) {
+        candidate = format!("{} ({}){}", stem, n, extension);
+        n += 1;
+    }
+    candidate
+}
+
+/// Convert a ZIP error, keeping IO errors recognizable.
+fn zip_error(error: zip::result::ZipError) -> AppError {
+    match error {
+        zip::result::ZipError::Io(e) => AppError::FileSystem(e),
+        e => AppError::Archive(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ChapterStatus;
+    use tokio::io::AsyncReadExt;
+
+    fn chapter(file_path: &str, file_type: &str) -> Chapter {
+        Chapter {
+            id: 1,
+            content_id: 1,
+            title: "Chapter 1".to_string(),
+            file_path: file_path.to_string(),
+            file_type: file_type.to_string(),
+            sort_order: 0,
+            page_count: 0,
+            size: 0,
+            volume: None,
+            number: None,
+            status: ChapterStatus::Ready,
+            error: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_plan() {
+        let original = DownloadFormat::Original;
+        let cbz = DownloadFormat::Cbz;
+
+        let plan = DownloadService::plan(&chapter("/lib/S/Ch 1.cbr", "cbr"), original);
+        assert!(matches!(plan, ChapterDownload::File { .. }));
+        assert_eq!(plan.file_name(), "Ch 1.cbr");
+
+        let plan = DownloadService::plan(&chapter("/lib/S/Ch 1.cbr", "cbr"), cbz);
+        assert!(matches!(plan, ChapterDownload::Repack { .. }));
+        assert_eq!(plan.file_name(), "Ch 1.cbz");
+
+        let plan = DownloadService::plan(&chapter("/lib/S/Ch 1.zip", "zip"), cbz);
+        assert!(matches!(plan, ChapterDownload::File { .. }));
+        assert_eq!(plan.file_name(), "Ch 1.cbz");
+
+        let plan =
+            DownloadService::plan(&chapter("/lib/S/Vol.1", IMAGE_FOLDER_FILE_TYPE), original);
+        assert!(matches!(plan, ChapterDownload::Repack { .. }));
+        assert_eq!(plan.file_name(), "Vol.1.cbz");
+
+        let plan = DownloadService::plan(&chapter("/lib/S/Book.pdf", "pdf"), cbz);
+        assert!(matches!(plan, ChapterDownload::File { .. }));
+        assert_eq!(plan.file_name(), "Book.pdf");
+    }
+
+    #[test]
+    fn test_unique_and_safe_names() {
+        let mut used = HashSet::new();
+        assert_eq!(unique_name(&mut used, "Ch 1.cbz"), "Ch 1.cbz");
+        assert_eq!(unique_name(&mut used, "Ch 1.cbz"), "Ch 1 (2).cbz");
+        assert_eq!(unique_name(&mut used, "Ch 1.cbz"), "Ch 1 (3).cbz");
+        assert_eq!(safe_file_name("AC/DC: Vol\\1 "), "AC_DC: Vol_1");
+        assert_eq!(safe_file_name("  "), "download");
+    }
+
+    #[tokio::test]
+    async fn test_stream_series() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let folder = dir.path().join("Vol.1");
+        std::fs::create_dir(&folder).unwrap();
+        std::fs::write(folder.join("page1.jpg"), b"1").unwrap();
+        let file = dir.path().join("Extra.pdf");
+        std::fs::write(&file, b"%PDF").unwrap();
+
+        let chapters = [
+            chapter(folder.to_str().unwrap(), IMAGE_FOLDER_FILE_TYPE),
+            chapter(file.to_str().unwrap(), "pdf"),
+        ];
+        let download = |format| SeriesDownload {
+            file_name: "Series.zip".to_string(),
+            format,
+            chapters: chapters
+                .iter()
+                .map(|c| (DownloadService::plan(c, format), 0))
+                .collect(),
+        };
+        let read = |download| async move {
+            let mut data = Vec::new();
+            DownloadService::stream_series(download)
+                .read_to_end(&mut data)
+                .await
+                .unwrap();
+            zip::ZipArchive::new(std::io::Cursor::new(data)).unwrap()
+        };
+
+        let mut archive = read(download(DownloadFormat::Original)).await;
+        let names: Vec<&str> = archive.file_names().collect();
+        assert_eq!(names.len(), 2);
+        assert!(names.contains(&"Vol.1/page1.jpg"));
+        assert!(names.contains(&"Extra.pdf"));
+        let mut pdf = Vec::new();
+        std::io::Read::read_to_end(&mut archive.by_name("Extra.pdf").unwrap(), &mut pdf).unwrap();
+        assert_eq!(pdf, b"%PDF");
+
+        let mut archive = read(download(DownloadFormat::Cbz)).await;
+        let mut cbz = Vec::new();
+        std::io::Read::read_to_end(&mut archive.by_name("Vol.1.cbz").unwrap(), &mut cbz).unwrap();
+        let mut inner = zip::ZipArchive::new(std::io::Cursor::new(cbz)).unwrap();
+        assert_eq!(inner.by_index(0).unwrap().name(), "page1.jpg");
+    }
+
+    #[tokio::test]
+    async fn test_stream_cbz_from_folder() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("page10.jpg"), b"10").unwrap();
+        std::fs::write(dir.path().join("page2.jpg"), b"2").unwrap();
+
+        let mut reader = DownloadService::stream_cbz(dir.path().to_path_buf());
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).await.unwrap();
+
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(data)).unwrap();
+        assert_eq!(archive.len(), 2);
+        assert_eq!(archive.by_index(0).unwrap().name(), "page2.jpg");
+        let mut page = Vec::new();
+        std::io::Read::read_to_end(&mut archive.by_name("page10.jpg").unwrap(), &mut page).unwrap();
+        assert_eq!(page, b"10");
+    }
+}
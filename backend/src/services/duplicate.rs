@@ -0,0 +1,241 @@
+//! Duplicate content detection.
+//!
+//! The same series often ends up in more than one scan path or library,
+//! for example once as CBZ files and once as image folders. Contents are
+//! compared by a SHA-1 of the first page of their first chapter, which is
+//! computed once per chapter and stored. Duplicates can be merged into one
+//! content: reading progress moves to the kept content and the others are
+//! hidden, so scans do not import them again.
+
+use std::collections::HashMap;
+
+use sha1::{Digest, Sha1};
+use sqlx::{Pool, Sqlite};
+use tracing::warn;
+
+use crate::error::{AppError, Result};
+use crate::models::{Chapter, DuplicateGroup, MergeDuplicatesResponse};
+use crate::repository::content::ChapterRepository;
+use crate::repository::duplicate::DuplicateRepository;
+use crate::services::content::ContentService;
+use crate::t;
+use crate::utils::cancel::spawn_cancellable;
+
+/// Service for finding and merging duplicate contents.
+pub struct DuplicateService;
+
+impl DuplicateService {
+    /// Find groups of contents that are likely the same series.
+    ///
+    /// First pages not hashed yet are hashed before comparing. Hashes are
+    /// stored as they are computed, so an interrupted call resumes where it
+    /// stopped.
+    pub async fn find_duplicates(pool: &Pool<Sqlite>) -> Result<Vec<DuplicateGroup>> {
+        Self::hash_first_pages(pool).await?;
+
+        let mut groups: Vec<DuplicateGroup> = Vec::new();
+        for (page_hash, content) in DuplicateRepository::find_duplicates(pool).await? {
+            match groups.last_mut() {
+                Some(group) if group.page_hash == page_hash => group.contents.push(content),
+                _ => groups.push(DuplicateGroup {
+                    page_hash,
+                    contents: vec![content],
+                }),
+            }
+        }
+
+        Ok(groups)
+    }
+
+    /// Merge contents into the one to keep.
+    ///
+    /// Reading progress is moved to the chapter of the kept content with
+    /// the same title, or else at the same position.
+    pub async fn merge(
+        pool: &Pool<Sqlite>,
+        keep_id: i64,
+        merge_ids: &[i64],
+    ) -> Result<MergeDuplicatesResponse> {
+        if merge_ids.is_empty() {
+            return Err(AppError::BadRequest(
+                t!("duplicates.nothing_to_merge").to_string(),
+            ));
+        }
+        if merge_ids.contains(&keep_id) || DuplicateRepository::is_merged(pool, keep_id).await? {
+            return Err(AppError::BadRequest(
+                t!("duplicates.invalid_keep", id = keep_id).to_string(),
+            ));
+        }
+
+        // Fails with NotFound for unknown contents before anything changes
+        ContentService::get_content(pool, keep_id).await?;
+        for &merge_id in merge_ids {
+            ContentService::get_content(pool, merge_id).await?;
+        }
+
+        let keep_chapters = ChapterRepository::list_by_content(pool, keep_id).await?;
+        let mut progress_moved = 0;
+        for &merge_id in merge_ids {
+            let chapters = ChapterRepository::list_by_content(pool, merge_id).await?;
+            let chapter_map = Self::map_chapters(&chapters, &keep_chapters);
+            progress_moved +=
+                DuplicateRepository::merge(pool, keep_id, merge_id, &chapter_map).await?;
+        }
+
+        Ok(MergeDuplicatesResponse {
+            kept: keep_id,
+            merged: merge_ids.to_vec(),
+            progress_moved,
+        })
+    }
+
+    /// Hash the first page of every first chapter without a hash.
+    pub(crate) async fn hash_first_pages(pool: &Pool<Sqlite>) -> Result<()> {
+        for chapter in DuplicateRepository::first_chapters_without_hash(pool).await? {
+            let chapter_id = chapter.id;
+            let file_path = chapter.file_path.clone();
+            let hash = spawn_cancellable(move |cancel| {
+                let data = ContentService::extract_page(&chapter, 0, cancel)?;
+                Ok(Self::page_hash(&data))
+            })
+            .await;
+
+            match hash {
+                Ok(hash) => DuplicateRepository::set_page_hash(pool, chapter_id, &hash).await?,
+                Err(e) => {
+                    warn!(path = %file_path, error = %e, "{}", t!("duplicates.hash_failed"));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Hex SHA-1 of page bytes.
+    fn page_hash(data: &[u8]) -> String {
+        Sha1::digest(data)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    /// Pair each chapter of `from` with a chapter of `to`, by title or else
+    /// by position.
+    fn map_chapters(from: &[Chapter], to: &[Chapter]) -> Vec<(i64, i64)> {
+        let by_title: HashMap<&str, i64> = to.iter().map(|c| (c.title.as_str(), c.id)).collect();
+
+        from.iter()
+            .enumerate()
+            .filter_map(|(idx, chapter)| {
+                let target = by_title
+                    .get(chapter.title.as_str())
+                    .copied()
+                    .or_else(|| to.get(idx).map(|c| c.id))?;
+                Some((chapter.id, target))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::content::ContentRepository;
+    use crate::test_fixtures::{
+        TestChapter, TestContent, create_test_content, create_test_db, create_test_library,
+        create_test_scan_path, create_test_user,
+    };
+
+    async fn setup() -> Pool<Sqlite> {
+        let pool = create_test_db().await;
+        create_test_user(&pool, 1, "reader").await;
+        create_test_library(&pool, 1, "First").await;
+        create_test_library(&pool, 2, "Second").await;
+        create_test_scan_path(&pool, 1, 1, "/a").await;
+        create_test_scan_path(&pool, 2, 2, "/b").await;
+        create_test_content(&pool, 1, 1, "Series").await;
+        TestContent::new(2, 2, "Series (CBZ)")
+            .folder("Series")
+            .insert(&pool)
+            .await;
+        create_test_content(&pool, 3, 1, "Other").await;
+        for (id, content_id, file_path, page_hash) in [
+            (1, 1, "/a/Series/Vol 1", "aaaa"),
+            (2, 1, "/a/Series/Vol 2", "bbbb"),
+            (3, 2, "/b/Series/Vol 1.cbz", "aaaa"),
+            (4, 2, "/b/Series/Volume 2.cbz", "cccc"),
+            (5, 3, "/a/Other/Vol 1", "dddd"),
+        ] {
+            TestChapter::new(id, content_id, file_path)
+                .page_hash(page_hash)
+                .insert(&pool)
+                .await;
+        }
+        pool
+    }
+
+    async fn insert_progress(
+        pool: &Pool<Sqlite>,
+        chapter_id: i64,
+        position: i32,
+        updated_at: &str,
+    ) {
+        sqlx::query(
+            r#"
+            INSERT INTO reading_progress (user_id, chapter_id, position, percentage, updated_at)
+            VALUES (1, ?, ?, 0.5, ?)
+            "#,
+        )
+        .bind(chapter_id)
+        .bind(position)
+        .bind(updated_at)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_groups_contents_by_first_page() {
+        let pool = setup().await;
+
+        let groups = DuplicateService::find_duplicates(&pool).await.unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].page_hash, "aaaa");
+        let ids: Vec<i64> = groups[0].contents.iter().map(|c| c.content_id).collect();
+        assert_eq!(ids, vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_merge_moves_progress_and_hides_content() {
+        let pool = setup().await;
+        // Matched by title, newer than the kept progress
+        insert_progress(&pool, 1, 3, "2026-01-01T00:00:00+00:00").await;
+        insert_progress(&pool, 3, 9, "2026-02-01T00:00:00+00:00").await;
+        // Matched by position
+        insert_progress(&pool, 4, 5, "2026-02-01T00:00:00+00:00").await;
+
+        let response = DuplicateService::merge(&pool, 1, &[2]).await.unwrap();
+        assert_eq!(response.merged, vec![2]);
+        assert_eq!(response.progress_moved, 2);
+
+        let rows: Vec<(i64, i32)> =
+            sqlx::query_as("SELECT chapter_id, position FROM reading_progress ORDER BY chapter_id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(rows, vec![(1, 9), (2, 5)]);
+
+        let listed = ContentRepository::list_by_library(&pool, 2).await.unwrap();
+        assert!(listed.is_empty());
+        assert!(
+            DuplicateService::find_duplicates(&pool)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // The merged content cannot be kept in a later merge
+        assert!(DuplicateService::merge(&pool, 2, &[3]).await.is_err());
+        assert!(DuplicateService::merge(&pool, 1, &[1]).await.is_err());
+    }
+}
@@ -0,0 +1,288 @@
+//! Executors of queued background tasks.
+//!
+//! Each kind of task in the scan queue is run by a [`TaskExecutor`]. The
+//! queue's workers hand every task to the executor registered for its kind
+//! in an [`ExecutorRegistry`], so a new kind of background job only needs
+//! an executor rather than a worker loop of its own.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use tokio::sync::watch;
+
+use crate::error::{AppError, Result};
+use crate::models::{AddedContent, TaskKind, TaskProgress, TaskResult};
+use crate::services::backup::BackupService;
+use crate::services::library::LibraryService;
+use crate::services::scan_queue::ScanService;
+use crate::t;
+
+/// Future returned by [`TaskExecutor::run`].
+pub type TaskFuture<'a> = Pin<Box<dyn Future<Output = Result<TaskResult>> + Send + 'a>>;
+
+/// Target of a running task, and where it publishes its progress.
+pub struct TaskContext {
+    /// Library the task works on; 0 for kinds not tied to a library.
+    pub library_id: i64,
+    /// The only scan path to work on, if limited to one.
+    pub scan_path_id: Option<i64>,
+    progress: watch::Sender<Option<TaskProgress>>,
+}
+
+impl TaskContext {
+    /// Create the context of a task publishing its progress to `progress`.
+    pub fn new(
+        library_id: i64,
+        scan_path_id: Option<i64>,
+        progress: watch::Sender<Option<TaskProgress>>,
+    ) -> Self {
+        Self {
+            library_id,
+            scan_path_id,
+            progress,
+        }
+    }
+
+    /// Publish the progress of the task.
+    pub fn report_progress(&self, progress: TaskProgress) {
+        self.progress.send_replace(Some(progress));
+    }
+}
+
+/// Runs the tasks of one kind.
+pub trait TaskExecutor: Send + Sync {
+    /// Kind of tasks this executor runs.
+    fn kind(&self) -> TaskKind;
+
+    /// Run a task, returning what it did.
+    fn run<'a>(&'a self, ctx: &'a TaskContext) -> TaskFuture<'a>;
+}
+
+/// Registered task executors, by task kind.
+#[derive(Default)]
+pub struct ExecutorRegistry {
+    executors: HashMap<TaskKind, Arc<dyn TaskExecutor>>,
+}
+
+impl ExecutorRegistry {
+    /// Create a registry without executors.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry with the executors of the tasks run by the scan
+    /// service: scans, thumbnails, page counts and metadata refreshes.
+    pub fn with_scan_service(scan_service: Arc<ScanService>) -> Self {
+        let mut registry = Self::new();
+        registry.register(Arc::new(ScanExecutor(Arc::clone(&scan_service))));
+        registry.register(Arc::new(ThumbnailExecutor(Arc::clone(&scan_service))));
+        registry.register(Arc::new(PageCountExecutor(Arc::clone(&scan_service))));
+        registry.register(Arc::new(MetadataRefreshExecutor(scan_service)));
+        registry
+    }
+
+    /// Register an executor, replacing any executor of the same kind.
+    pub fn register(&mut self, executor: Arc<dyn TaskExecutor>) {
+        self.executors.insert(executor.kind(), executor);
+    }
+
+    /// Get the executor of a kind of task.
+    pub fn get(&self, kind: TaskKind) -> Result<&Arc<dyn TaskExecutor>> {
+        self.executors.get(&kind).ok_or_else(|| {
+            AppError::Internal(t!("scan_queue.no_executor", kind = format!("{kind:?}")).to_string())
+        })
+    }
+
+    /// Whether no executor is registered.
+    pub fn is_empty(&self) -> bool {
+        self.executors.is_empty()
+    }
+}
+
+/// Scans a library, or one of its scan paths.
+struct ScanExecutor(Arc<ScanService>);
+
+impl TaskExecutor for ScanExecutor {
+    fn kind(&self) -> TaskKind {
+        TaskKind::Scan
+    }
+
+    fn run<'a>(&'a self, ctx: &'a TaskContext) -> TaskFuture<'a> {
+        Box::pin(async move {
+            let result = self.0.scan_target(ctx.library_id, ctx.scan_path_id).await?;
+            Ok(TaskResult {
+                added_count: result.added.len() as i32,
+                removed_count: result.removed.len() as i32,
+                moved_count: result.moved.len() as i32,
+                failed_scrape_count: result.failed_scrape.len() as i32,
+                added_contents: result
+                    .added
+                    .iter()
+                    .map(|c| AddedContent {
+                        content_name: c.title.clone(),
+                        path: c.folder_path.clone(),
+                    })
+                    .collect(),
+                added_chapters: result.added_chapters,
+                suspicious_archives: result.suspicious_archives,
+                scan_paths: result.scan_paths,
+                folder_errors: result.folder_errors,
+                ..TaskResult::default()
+            })
+        })
+    }
+}
+
+/// Regenerates the missing and outdated thumbnails of a library.
+struct ThumbnailExecutor(Arc<ScanService>);
+
+impl TaskExecutor for ThumbnailExecutor {
+    fn kind(&self) -> TaskKind {
+        TaskKind::Thumbnails
+    }
+
+    fn run<'a>(&'a self, ctx: &'a TaskContext) -> TaskFuture<'a> {
+        Box::pin(async move {
+            let result = self
+                .0
+                .regenerate_thumbnails(ctx.library_id, |p| ctx.report_progress(p))
+                .await?;
+            Ok(TaskResult {
+                thumbnails_regenerated: result.regenerated as i32,
+                thumbnails_failed: result.failed as i32,
+                ..TaskResult::default()
+            })
+        })
+    }
+}
+
+/// Counts the pages of a library's chapters that have none.
+struct PageCountExecutor(Arc<ScanService>);
+
+impl TaskExecutor for PageCountExecutor {
+    fn kind(&self) -> TaskKind {
+        TaskKind::PageCounts
+    }
+
+    fn run<'a>(&'a self, ctx: &'a TaskContext) -> TaskFuture<'a> {
+        Box::pin(async move {
+            let result = self
+                .0
+                .recalculate_page_counts(ctx.library_id, |p| ctx.report_progress(p))
+                .await?;
+            Ok(TaskResult {
+                page_counts_updated: result.updated as i32,
+                page_counts_failed: result.failed as i32,
+                ..TaskResult::default()
+            })
+        })
+    }
+}
+
+/// Fetches the metadata of a library's content again.
+struct MetadataRefreshExecutor(Arc<ScanService>);
+
+impl TaskExecutor for MetadataRefreshExecutor {
+    fn kind(&self) -> TaskKind {
+        TaskKind::MetadataRefresh
+    }
+
+    fn run<'a>(&'a self, ctx: &'a TaskContext) -> TaskFuture<'a> {
+        Box::pin(async move {
+            let result = self
+                .0
+                .refresh_metadata(ctx.library_id, |p| ctx.report_progress(p))
+                .await?;
+            Ok(TaskResult {
+                metadata_refreshed: result.refreshed as i32,
+                metadata_failed: result.failed as i32,
+                ..TaskResult::default()
+            })
+        })
+    }
+}
+
+/// Writes a backup of the database.
+pub struct BackupExecutor(Arc<BackupService>);
+
+impl BackupExecutor {
+    /// Create an executor writing backups with `backup_service`.
+    pub fn new(backup_service: Arc<BackupService>) -> Self {
+        Self(backup_service)
+    }
+}
+
+impl TaskExecutor for BackupExecutor {
+    fn kind(&self) -> TaskKind {
+        TaskKind::Backup
+    }
+
+    fn run<'a>(&'a self, _ctx: &'a TaskContext) -> TaskFuture<'a> {
+        Box::pin(async move {
+            let backup = self.0.create().await?;
+            Ok(TaskResult {
+                backup_name: Some(backup.name),
+                ..TaskResult::default()
+            })
+        })
+    }
+}
+
+/// Removes a library hidden for deletion with its contents.
+pub struct LibraryDeletionExecutor(Arc<LibraryService>);
+
+impl LibraryDeletionExecutor {
+    /// Create an executor deleting libraries with `library_service`.
+    pub fn new(library_service: Arc<LibraryService>) -> Self {
+        Self(library_service)
+    }
+}
+
+impl TaskExecutor for LibraryDeletionExecutor {
+    fn kind(&self) -> TaskKind {
+        TaskKind::LibraryDeletion
+    }
+
+    fn run<'a>(&'a self, ctx: &'a TaskContext) -> TaskFuture<'a> {
+        Box::pin(async move {
+            let result = self
+                .0
+                .delete_in_batches(ctx.library_id, |p| ctx.report_progress(p))
+                .await?;
+            Ok(TaskResult {
+                removed_count: result.contents as i32,
+                chapters_removed: result.chapters as i32,
+                ..TaskResult::default()
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::create_test_db;
+
+    #[tokio::test]
+    async fn test_registry_by_kind() {
+        let pool = create_test_db().await;
+
+        let mut registry = ExecutorRegistry::new();
+        assert!(registry.is_empty());
+        assert!(registry.get(TaskKind::Scan).is_err());
+
+        registry = ExecutorRegistry::with_scan_service(Arc::new(ScanService::new(pool)));
+        for kind in [
+            TaskKind::Scan,
+            TaskKind::Thumbnails,
+            TaskKind::PageCounts,
+            TaskKind::MetadataRefresh,
+        ] {
+            assert_eq!(registry.get(kind).unwrap().kind(), kind);
+        }
+        // Backups need the backup service
+        assert!(registry.get(TaskKind::Backup).is_err());
+    }
+}
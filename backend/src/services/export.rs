@@ -0,0 +1,363 @@
+//! Library export and import.
+//!
+//! Exports carry the contents of a library with their chapters, metadata
+//! and optionally the reading progress of all users, so a library can be
+//! moved to another server. The target server scans the same files first;
+//! importing then matches exported contents to its own contents by folder
+//! path, by path relative to a scan path (for a share mounted elsewhere),
+//! or by the hash of the first page.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use chrono::Utc;
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, ErrorCode, Result};
+use crate::models::{
+    Chapter, Content, ExportedChapter, ExportedContent, LIBRARY_EXPORT_VERSION, Library,
+    LibraryExport, LibraryImportResult,
+};
+use crate::repository::content::{ChapterRepository, ContentRepository};
+use crate::repository::duplicate::DuplicateRepository;
+use crate::repository::library::{LibraryRepository, ScanPathRepository};
+use crate::repository::progress::ProgressRepository;
+use crate::repository::user::UserRepository;
+use crate::services::duplicate::DuplicateService;
+use crate::t;
+
+/// Service for exporting and importing libraries.
+pub struct ExportService;
+
+impl ExportService {
+    /// Export the contents of a library.
+    pub async fn export_library(
+        pool: &Pool<Sqlite>,
+        library_id: i64,
+        include_progress: bool,
+    ) -> Result<LibraryExport> {
+        let library = Self::find_library(pool, library_id).await?;
+        DuplicateService::hash_first_pages(pool).await?;
+
+        let scan_paths = ScanPathRepository::list_by_library(pool, library_id)
+            .await?
+            .into_iter()
+            .map(|scan_path| scan_path.path)
+            .collect();
+
+        let mut contents = Vec::new();
+        for content in ContentRepository::list_by_library(pool, library_id).await? {
+            let mut progress: HashMap<i64, Vec<_>> = HashMap::new();
+            if include_progress {
+                for (chapter_id, entry) in
+                    ProgressRepository::list_by_content_with_usernames(pool, content.id).await?
+                {
+                    progress.entry(chapter_id).or_default().push(entry);
+                }
+            }
+
+            let chapters = ChapterRepository::list_by_content(pool, content.id)
+                .await?
+                .into_iter()
+                .map(|chapter| ExportedChapter {
+                    progress: progress.remove(&chapter.id).unwrap_or_default(),
+                    title: chapter.title,
+                    file_path: chapter.file_path,
+                    sort_order: chapter.sort_order,
+                })
+                .collect();
+
+            contents.push(ExportedContent {
+                page_hash: DuplicateRepository::first_page_hash(pool, content.id).await?,
+                metadata: content
+                    .metadata
+                    .as_deref()
+                    .and_then(|m| serde_json::from_slice(m).ok()),
+                title: content.title,
+                folder_path: content.folder_path,
+                chapters,
+            });
+        }
+
+        Ok(LibraryExport {
+            version: LIBRARY_EXPORT_VERSION,
+            exported_at: Utc::now(),
+            library_name: library.name,
+            scan_paths,
+            contents,
+        })
+    }
+
+    /// Import an export into a library.
+    ///
+    /// Matched contents take the exported title and metadata. Progress of
+    /// users with the same name on this server is imported unless they
+    /// have more recent progress on the chapter.
+    pub async fn import_library(
+        pool: &Pool<Sqlite>,
+        library_id: i64,
+        export: &LibraryExport,
+    ) -> Result<LibraryImportResult> {
+        if export.version > LIBRARY_EXPORT_VERSION {
+            return Err(AppError::BadRequest(
+                t!("export.unsupported_version", version = export.version).to_string(),
+            ));
+        }
+        Self::find_library(pool, library_id).await?;
+        DuplicateService::hash_first_pages(pool).await?;
+
+        let scan_paths: HashMap<i64, String> =
+            ScanPathRepository::list_by_library(pool, library_id)
+                .await?
+                .into_iter()
+                .map(|scan_path| (scan_path.id, scan_path.path))
+                .collect();
+
+        let contents = ContentRepository::list_by_library(pool, library_id).await?;
+        let mut by_path: HashMap<&str, usize> = HashMap::new();
+        let mut by_relative_path: HashMap<String, usize> = HashMap::new();
+        let mut by_hash: HashMap<String, Vec<usize>> = HashMap::new();
+        for (idx, content) in contents.iter().enumerate() {
+            by_path.insert(&content.folder_path, idx);
+            if let Some(relative) = scan_paths
+                .get(&content.scan_path_id)
+                .and_then(|base| Self::relative_path(&content.folder_path, base))
+            {
+                by_relative_path.insert(relative, idx);
+            }
+            if let Some(hash) = DuplicateRepository::first_page_hash(pool, content.id).await? {
+                by_hash.entry(hash).or_default().push(idx);
+            }
+        }
+
+        let mut result = LibraryImportResult::default();
+        let mut matched: HashSet<usize> = HashSet::new();
+        let mut users: HashMap<String, Option<i64>> = HashMap::new();
+        for exported in &export.contents {
+            let by_relative = || {
+                export
+                    .scan_paths
+                    .iter()
+                    .find_map(|base| Self::relative_path(&exported.folder_path, base))
+                    .and_then(|relative| by_relative_path.get(&relative).copied())
+            };
+            // A hash shared by several contents does not identify one
+            let by_first_page = || match exported.page_hash.as_ref().and_then(|h| by_hash.get(h)) {
+                Some(candidates) if candidates.len() == 1 => Some(candidates[0]),
+                _ => None,
+            };
+            let found = by_path
+                .get(exported.folder_path.as_str())
+                .copied()
+                .or_else(by_relative)
+                .or_else(by_first_page)
+                .filter(|idx| !matched.contains(idx));
+            let Some(idx) = found else {
+                result.unmatched_contents.push(exported.folder_path.clone());
+                continue;
+            };
+            matched.insert(idx);
+            result.matched_contents += 1;
+
+            let content = &contents[idx];
+            ContentRepository::update_info(
+                pool,
+                content.id,
+                Some(exported.title.clone()),
+                exported.metadata.clone().map(Some),
+                None,
+            )
+            .await?;
+
+            result.progress_imported +=
+                Self::import_progress(pool, content, &exported.chapters, &mut users).await?;
+        }
+
+        result.unknown_users = users
+            .into_iter()
+            .filter(|(_, user_id)| user_id.is_none())
+            .map(|(username, _)| username)
+            .collect();
+        result.unknown_users.sort();
+
+        Ok(result)
+    }
+
+    /// Import the progress of exported chapters into the chapters of a
+    /// content with the same file name, or else the same title. Users are
+    /// looked up by name once and cached in `users`.
+    async fn import_progress(
+        pool: &Pool<Sqlite>,
+        content: &Content,
+        exported: &[ExportedChapter],
+        users: &mut HashMap<String, Option<i64>>,
+    ) -> Result<u64> {
+        let chapters = ChapterRepository::list_by_content(pool, content.id).await?;
+        let mut imported = 0;
+
+        for exported_chapter in exported.iter().filter(|c| !c.progress.is_empty()) {
+            let Some(chapter) = Self::find_chapter(&chapters, exported_chapter) else {
+                continue;
+            };
+
+            for progress in &exported_chapter.progress {
+                let user_id = match users.get(&progress.username) {
+                    Some(user_id) => *user_id,
+                    None => {
+                        let user_id = UserRepository::find_by_username(pool, &progress.username)
+                            .await?
+                            .map(|user| user.id);
+                        users.insert(progress.username.clone(), user_id);
+                        user_id
+                    }
+                };
+                let Some(user_id) = user_id else {
+                    continue;
+                };
+
+                if ProgressRepository::import(pool, user_id, chapter.id, progress).await? {
+                    imported += 1;
+                }
+            }
+        }
+
+        Ok(imported)
+    }
+
+    /// The chapter matching an exported chapter.
+    fn find_chapter<'a>(
+        chapters: &'a [Chapter],
+        exported: &ExportedChapter,
+    ) -> Option<&'a Chapter> {
+        let file_name = Path::new(&exported.file_path).file_name();
+        chapters
+            .iter()
+            .find(|c| file_name.is_some() && Path::new(&c.file_path).file_name() == file_name)
+            .or_else(|| chapters.iter().find(|c| c.title == exported.title))
+    }
+
+    /// Path of `path` below the scan path `base`, with `/` separators.
+    fn relative_path(path: &str, base: &str) -> Option<String> {
+        let relative = Path::new(path).strip_prefix(base).ok()?;
+        let parts: Vec<String> = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect();
+        (!parts.is_empty()).then(|| parts.join("/"))
+    }
+
+    /// Get a library, failing with NotFound if it does not exist.
+    async fn find_library(pool: &Pool<Sqlite>, library_id: i64) -> Result<Library> {
+        LibraryRepository::find_by_id(pool, library_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound(t!("library.id_not_found", id = library_id).to_string())
+                    .with_code(ErrorCode::LibraryNotFound)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{
+        TestChapter, create_test_content, create_test_db, create_test_library,
+        create_test_scan_path, create_test_user,
+    };
+
+    /// A database with one library whose scan path is `root`, read by
+    /// `admin`.
+    async fn setup(root: &str) -> Pool<Sqlite> {
+        let pool = create_test_db().await;
+        create_test_user(&pool, 1, "admin").await;
+        create_test_library(&pool, 1, "Comics").await;
+        create_test_scan_path(&pool, 1, 1, root).await;
+        create_test_content(&pool, 1, 1, "Series").await;
+        create_test_content(&pool, 2, 1, "Renamed").await;
+        for (id, content_id, file_name, page_hash) in [
+            (1, 1, "Series/Vol 1.cbz", "aaaa"),
+            (2, 1, "Series/Vol 2.cbz", "bbbb"),
+            (3, 2, "Renamed/Vol 1.cbz", "cccc"),
+        ] {
+            TestChapter::new(id, content_id, &format!("{root}/{file_name}"))
+                .page_hash(page_hash)
+                .insert(&pool)
+                .await;
+        }
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_export_and_import_progress() {
+        let source = setup("/mnt/old").await;
+        create_test_user(&source, 2, "reader").await;
+        sqlx::query(
+            r#"
+            UPDATE contents SET title = 'Custom Title', metadata = CAST('{"name":"Series"}' AS BLOB)
+                WHERE id = 1;
+            INSERT INTO reading_progress (user_id, chapter_id, position, percentage, updated_at)
+            VALUES
+                (1, 2, 12, 50.0, '2026-01-01T00:00:00+00:00'),
+                (2, 1, 30, 100.0, '2026-01-01T00:00:00+00:00'),
+                (2, 3, 4, 10.0, '2026-01-01T00:00:00+00:00');
+            "#,
+        )
+        .execute(&source)
+        .await
+        .unwrap();
+
+        let export = ExportService::export_library(&source, 1, true)
+            .await
+            .unwrap();
+        assert_eq!(export.contents.len(), 2);
+        assert_eq!(export.contents[0].title, "Custom Title");
+        assert_eq!(export.contents[0].page_hash.as_deref(), Some("aaaa"));
+
+        // Without progress, chapters carry none
+        let bare = ExportService::export_library(&source, 1, false)
+            .await
+            .unwrap();
+        assert!(
+            bare.contents
+                .iter()
+                .flat_map(|c| &c.chapters)
+                .all(|c| c.progress.is_empty())
+        );
+
+        // The share is mounted elsewhere and one folder was renamed, so
+        // contents match by relative path and by first page
+        let target = setup("/data/comics").await;
+        sqlx::query("UPDATE contents SET folder_path = '/data/comics/Other' WHERE id = 2")
+            .execute(&target)
+            .await
+            .unwrap();
+
+        let result = ExportService::import_library(&target, 1, &export)
+            .await
+            .unwrap();
+        assert_eq!(result.matched_contents, 2);
+        assert!(result.unmatched_contents.is_empty());
+        assert_eq!(result.progress_imported, 1);
+        assert_eq!(result.unknown_users, vec!["reader".to_string()]);
+
+        let content = ContentRepository::find_by_id(&target, 1)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(content.title, "Custom Title");
+        assert!(content.metadata.is_some());
+
+        let rows: Vec<(i64, i64, i32)> =
+            sqlx::query_as("SELECT user_id, chapter_id, position FROM reading_progress")
+                .fetch_all(&target)
+                .await
+                .unwrap();
+        assert_eq!(rows, vec![(1, 2, 12)]);
+
+        // Importing again does not overwrite newer progress
+        let result = ExportService::import_library(&target, 1, &export)
+            .await
+            .unwrap();
+        assert_eq!(result.progress_imported, 0);
+    }
+}
@@ -0,0 +1,354 @@
+//! Server filesystem browsing.
+//!
+//! Administrators pick the folders of libraries from a directory browser.
+//! When allowed roots are configured, browsing and scan paths are limited
+//! to them. Paths are canonicalized before they are checked, so `..`
+//! components and symbolic links cannot lead outside the roots.
+
+use std::fs::{self, Metadata};
+use std::path::{Path, PathBuf};
+
+use tracing::warn;
+
+use crate::error::{AppError, ErrorCode, Result};
+use crate::models::{DirectoryEntry, EntryKind};
+use crate::t;
+
+/// What to list besides visible directories.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ListOptions {
+    /// Include hidden entries.
+    pub hidden: bool,
+    /// Include files.
+    pub files: bool,
+}
+
+/// Directory the browser is limited to.
+#[derive(Debug, Clone)]
+struct Root {
+    /// Path as configured, shown to users.
+    path: PathBuf,
+    /// Canonical path paths are checked against.
+    canonical: PathBuf,
+}
+
+/// Service for browsing the server filesystem.
+pub struct FilesystemService {
+    roots: Vec<Root>,
+    /// Whether roots were configured. Stays set when none of them can be
+    /// resolved, so an unavailable root does not open the whole filesystem.
+    restricted: bool,
+}
+
+impl FilesystemService {
+    /// Create a service limited to `roots`; an empty list allows the whole
+    /// filesystem.
+    pub fn new(roots: Vec<PathBuf>) -> Self {
+        let restricted = !roots.is_empty();
+        let roots = roots
+            .into_iter()
+            .filter_map(|path| match path.canonicalize() {
+                Ok(canonical) => Some(Root { path, canonical }),
+                Err(e) => {
+                    warn!(
+                        path = %path.display(),
+                        error = %e,
+                        "{}",
+                        t!("filesystem.root_unavailable")
+                    );
+                    None
+                }
+            })
+            .collect();
+        Self { roots, restricted }
+    }
+
+    /// Whether browsing is limited to allowed roots.
+    pub fn is_restricted(&self) -> bool {
+        self.restricted
+    }
+
+    /// Canonical form of `path`, if it exists inside the allowed roots.
+    pub fn check_allowed(&self, path: &Path) -> Result<PathBuf> {
+        let canonical = path.canonicalize().map_err(|_| {
+            AppError::NotFound(t!("filesystem.path_not_found", path = path.display()).to_string())
+        })?;
+        if !self.allows(&canonical) {
+            return Err(AppError::Forbidden(
+                t!("filesystem.path_not_allowed", path = path.display()).to_string(),
+            )
+            .with_code(ErrorCode::PathNotAllowed));
+        }
+        Ok(canonical)
+    }
+
+    /// List the entries of the directory at `path`, directories first.
+    ///
+    /// Without a path the allowed roots are listed; when the whole
+    /// filesystem is allowed, the drives on Windows and `/` elsewhere.
+    pub fn list(&self, path: Option<&str>, options: ListOptions) -> Result<Vec<DirectoryEntry>> {
+        let path = path.map(str::trim).unwrap_or_default();
+        if path.is_empty() {
+            if self.restricted {
+                return Ok(self
+                    .roots
+                    .iter()
+                    .map(|root| root_entry(&root.path))
+                    .collect());
+            }
+            if cfg!(target_os = "windows") {
+                return Ok(windows_drives());
+            }
+        }
+        let path = if path.is_empty() {
+            Path::new("/")
+        } else {
+            Path::new(path)
+        };
+
+        let canonical = self.check_allowed(path)?;
+        if !canonical.is_dir() {
+            return Err(AppError::BadRequest(
+                t!("filesystem.path_not_dir", path = path.display()).to_string(),
+            ));
+        }
+
+        // Going up is only offered while the parent is allowed too
+        let parent = path
+            .parent()
+            .filter(|parent| {
+                parent
+                    .canonicalize()
+                    .is_ok_and(|parent| self.allows(&parent))
+            })
+            .map(|parent| parent.to_string_lossy().to_string());
+
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(&canonical)?.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+
+            // Links are followed, unless they lead outside the roots
+            let symlink = file_type.is_symlink();
+            let target = if symlink {
+                match entry.path().canonicalize() {
+                    Ok(target) if self.allows(&target) => target,
+                    _ => continue,
+                }
+            } else {
+                entry.path()
+            };
+            let Ok(metadata) = fs::metadata(&target) else {
+                continue;
+            };
+            if !options.hidden && is_hidden(&name, &metadata) {
+                continue;
+            }
+
+            let kind = if metadata.is_dir() {
+                EntryKind::Directory
+            } else if options.files && metadata.is_file() {
+                EntryKind::File
+            } else {
+                continue;
+            };
+            entries.push(DirectoryEntry {
+                path: path.join(&name).to_string_lossy().to_string(),
+                name,
+                parent: parent.clone(),
+                kind,
+                size: (kind == EntryKind::File).then(|| metadata.len()),
+                symlink,
+                readable: is_readable(&target, kind),
+                readonly: metadata.permissions().readonly(),
+            });
+        }
+
+        // Directories first, each sorted by name case-insensitively
+        entries
+            .sort_by(|a, b| (a.kind, a.name.to_lowercase()).cmp(&(b.kind, b.name.to_lowercase())));
+        Ok(entries)
+    }
+
+    fn allows(&self, canonical: &Path) -> bool {
+        !self.restricted
+            || self
+                .roots
+                .iter()
+                .any(|root| canonical.starts_with(&root.canonical))
+    }
+}
+
+/// Entry for a directory listed at the top of the browser.
+fn root_entry(path: &Path) -> DirectoryEntry {
+    let metadata = fs::metadata(path).ok();
+    DirectoryEntry {
+        name: path.to_string_lossy().to_string(),
+        path: path.to_string_lossy().to_string(),
+        parent: None,
+        kind: EntryKind::Directory,
+        size: None,
+        symlink: false,
+        readable: is_readable(path, EntryKind::Directory),
+        readonly: metadata.is_some_and(|m| m.permissions().readonly()),
+    }
+}
+
+/// Drives present on a Windows server.
+fn windows_drives() -> Vec<DirectoryEntry> {
+    (b'A'..=b'Z')
+        .map(|letter| format!("{}:\\", letter as char))
+        .filter(|drive| Path::new(drive).exists())
+        .map(|drive| root_entry(Path::new(&drive)))
+        .collect()
+}
+
+/// Whether the server can open the entry.
+fn is_readable(path: &Path, kind: EntryKind) -> bool {
+    match kind {
+        EntryKind::Directory => fs::read_dir(path).is_ok(),
+        EntryKind::File => fs::File::open(path).is_ok(),
+    }
+}
+
+/// Whether an entry is hidden: its name starts with a dot, or on Windows it
+/// has the hidden attribute.
+fn is_hidden(name: &str, metadata: &Metadata) -> bool {
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+
+        const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+        if metadata.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0 {
+            return true;
+        }
+    }
+    #[cfg(not(windows))]
+    let _ = metadata;
+
+    name.starts_with('.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn names(entries: &[DirectoryEntry]) -> Vec<&str> {
+        entries.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_list_directories() {
+        let dir = tempdir().unwrap();
+        let dir_path = dir.path();
+
+        // Create subdirectories
+        fs::create_dir(dir_path.join("sub1")).unwrap();
+        fs::create_dir(dir_path.join("sub2")).unwrap();
+        fs::write(dir_path.join("file.txt"), "content").unwrap();
+
+        let service = FilesystemService::new(Vec::new());
+        let entries = service
+            .list(dir_path.to_str(), ListOptions::default())
+            .unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().any(|e| e.name == "sub1"));
+        assert!(entries.iter().any(|e| e.name == "sub2"));
+        // Ensure files are ignored
+        assert!(!entries.iter().any(|e| e.name == "file.txt"));
+    }
+
+    #[test]
+    fn test_hidden_entries_and_files() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join(".hidden")).unwrap();
+        fs::create_dir(dir.path().join("Comics")).unwrap();
+        fs::write(dir.path().join("a.cbz"), "12345").unwrap();
+
+        let service = FilesystemService::new(Vec::new());
+        let entries = service
+            .list(dir.path().to_str(), ListOptions::default())
+            .unwrap();
+        assert_eq!(names(&entries), ["Comics"]);
+
+        let options = ListOptions {
+            hidden: true,
+            files: true,
+        };
+        let entries = service.list(dir.path().to_str(), options).unwrap();
+        assert_eq!(names(&entries), [".hidden", "Comics", "a.cbz"]);
+        assert_eq!(entries[2].kind, EntryKind::File);
+        assert_eq!(entries[2].size, Some(5));
+        assert!(entries[2].readable);
+    }
+
+    #[test]
+    fn test_roots_limit_browsing() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().join("library");
+        let outside = dir.path().join("private");
+        fs::create_dir_all(root.join("Series")).unwrap();
+        fs::create_dir(&outside).unwrap();
+
+        let service = FilesystemService::new(vec![root.clone()]);
+        assert!(service.is_restricted());
+
+        let top = service.list(None, ListOptions::default()).unwrap();
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].path, root.to_string_lossy());
+
+        // The root itself offers no way up, its subdirectories do
+        let entries = service.list(root.to_str(), ListOptions::default()).unwrap();
+        assert_eq!(names(&entries), ["Series"]);
+        assert_eq!(entries[0].parent, None);
+        let series = service
+            .list(Some(&entries[0].path), ListOptions::default())
+            .unwrap();
+        assert!(series.is_empty());
+
+        for path in [outside.clone(), root.join("..")] {
+            let err = service
+                .list(path.to_str(), ListOptions::default())
+                .unwrap_err();
+            assert!(matches!(err.code(), ErrorCode::PathNotAllowed));
+        }
+        assert!(service.check_allowed(&root.join("Series")).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks_outside_roots_are_skipped() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().join("library");
+        let outside = dir.path().join("private");
+        fs::create_dir_all(root.join("Series")).unwrap();
+        fs::create_dir(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("escape")).unwrap();
+        std::os::unix::fs::symlink(root.join("Series"), root.join("alias")).unwrap();
+
+        let service = FilesystemService::new(vec![root.clone()]);
+        let entries = service.list(root.to_str(), ListOptions::default()).unwrap();
+        assert_eq!(names(&entries), ["alias", "Series"]);
+        assert!(entries[0].symlink);
+
+        let err = service.check_allowed(&root.join("escape")).unwrap_err();
+        assert!(matches!(err.code(), ErrorCode::PathNotAllowed));
+    }
+
+    #[test]
+    fn test_unavailable_roots_allow_nothing() {
+        let dir = tempdir().unwrap();
+        let service = FilesystemService::new(vec![dir.path().join("missing")]);
+        assert!(
+            service
+                .list(None, ListOptions::default())
+                .unwrap()
+                .is_empty()
+        );
+        assert!(service.check_allowed(dir.path()).is_err());
+    }
+}
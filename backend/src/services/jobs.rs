@@ -0,0 +1,87 @@
+//! Unified view over background job queues.
+//!
+//! Each job source keeps its own queue; this service merges them into one
+//! list and routes controls to the source that owns the job.
+
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use crate::error::{AppError, ErrorCode, Result};
+use crate::models::{Job, JobListQuery, TaskPriority};
+use crate::services::scan_queue::ScanQueueService;
+use crate::t;
+
+/// Default number of finished jobs returned by [`JobService::list`].
+const DEFAULT_HISTORY_LIMIT: usize = 50;
+
+/// Service for listing and controlling background jobs of all kinds.
+pub struct JobService {
+    scan_queue: Arc<ScanQueueService>,
+}
+
+impl JobService {
+    /// Create a new job service.
+    pub fn new(scan_queue: Arc<ScanQueueService>) -> Self {
+        Self { scan_queue }
+    }
+
+    /// List running, pending and recently finished jobs, in that order.
+    ///
+    /// Summaries of finished jobs are rendered in `locale`.
+    pub async fn list(&self, query: &JobListQuery, locale: &str) -> Vec<Job> {
+        let limit = query.limit.unwrap_or(DEFAULT_HISTORY_LIMIT);
+
+        let mut tasks = self.scan_queue.list_processing().await;
+        tasks.extend(self.scan_queue.list_pending().await);
+        tasks.extend(self.scan_queue.list_history(limit).await);
+
+        tasks
+            .into_iter()
+            .map(|task| Job::from(task.with_summary(locale)))
+            .filter(|job| query.kind.is_none_or(|kind| job.kind == kind))
+            .filter(|job| query.status.is_none_or(|status| job.status == status))
+            .collect()
+    }
+
+    /// Get a single job.
+    pub async fn get(&self, job_id: Uuid, locale: &str) -> Result<Job> {
+        self.scan_queue
+            .get_task(job_id)
+            .await
+            .map(|task| Job::from(task.with_summary(locale)))
+            .ok_or_else(|| {
+                AppError::NotFound(t!("jobs.not_found", id = job_id).to_string())
+                    .with_code(ErrorCode::JobNotFound)
+            })
+    }
+
+    /// Cancel a pending or running job.
+    pub async fn cancel(&self, job_id: Uuid) -> Result<()> {
+        self.require(job_id).await?;
+        self.scan_queue.cancel_task(job_id).await
+    }
+
+    /// Run a failed or cancelled job again, returning the new job's ID.
+    pub async fn retry(&self, job_id: Uuid) -> Result<Uuid> {
+        self.require(job_id).await?;
+        self.scan_queue.retry_task(job_id).await
+    }
+
+    /// Change the priority of a pending job.
+    pub async fn set_priority(&self, job_id: Uuid, priority: TaskPriority) -> Result<()> {
+        self.require(job_id).await?;
+        self.scan_queue.set_task_priority(job_id, priority).await
+    }
+
+    /// Fail with NotFound unless a job with this ID exists.
+    async fn require(&self, job_id: Uuid) -> Result<()> {
+        match self.scan_queue.get_task(job_id).await {
+            Some(_) => Ok(()),
+            None => Err(
+                AppError::NotFound(t!("jobs.not_found", id = job_id).to_string())
+                    .with_code(ErrorCode::JobNotFound),
+            ),
+        }
+    }
+}
@@ -0,0 +1,291 @@
+//! Kobo sync service.
+//!
+//! Kobo e-readers sync in batches: each sync returns the EPUB chapters and
+//! reading states that changed since the token of the previous one, and a
+//! new token. Progress read on the device is stored with the progress from
+//! the other readers, mapped to the EPUB section it was reached in.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, ErrorCode, Result};
+use crate::extractors::EpubExtractor;
+use crate::models::{
+    Chapter, Content, KoboLocation, KoboReadingState, KoboSyncToken, NovelPosition,
+    age_rating_allowed,
+};
+use crate::repository::content::{ChapterRepository, ContentRepository};
+use crate::repository::kobo::KoboRepository;
+use crate::repository::progress::ProgressRepository;
+use crate::services::age_rating::AgeRatingService;
+use crate::services::progress::ProgressService;
+use crate::t;
+use crate::utils::cancel::spawn_cancellable;
+
+/// Most books, and most reading states, sent in one sync.
+pub const KOBO_SYNC_BATCH: i64 = 100;
+
+/// Books and reading states to send to a device in one sync.
+#[derive(Debug)]
+pub struct KoboSync {
+    /// New books, with the content they belong to.
+    pub books: Vec<(Chapter, Arc<Content>)>,
+    /// Reading states that changed.
+    pub states: Vec<KoboReadingState>,
+    /// Token for the next sync.
+    pub token: KoboSyncToken,
+    /// Whether changes are left for another sync.
+    pub more: bool,
+}
+
+/// Reading state of a book sent by a device.
+#[derive(Debug, Clone, Default)]
+pub struct KoboStateUpdate {
+    /// Percentage of the book read (0.0 to 100.0).
+    pub percentage: Option<f32>,
+    /// Whether the book was marked as finished.
+    pub finished: bool,
+    pub location: KoboLocation,
+}
+
+/// Service for Kobo sync operations.
+pub struct KoboService;
+
+impl KoboService {
+    /// Get the books added and the user's reading states changed since
+    /// `token`, up to [`KOBO_SYNC_BATCH`] of each. Books above the user's age
+    /// rating cap are skipped.
+    pub async fn sync(pool: &Pool<Sqlite>, user_id: i64, token: KoboSyncToken) -> Result<KoboSync> {
+        let max_age_rating = AgeRatingService::max_for_user(pool, user_id).await?;
+        let chapters =
+            KoboRepository::list_books_after(pool, token.last_chapter_id, KOBO_SYNC_BATCH).await?;
+        let states = KoboRepository::list_states_since(
+            pool,
+            user_id,
+            token.states_since.as_deref(),
+            KOBO_SYNC_BATCH,
+        )
+        .await?;
+        let more =
+            chapters.len() as i64 >= KOBO_SYNC_BATCH || states.len() as i64 >= KOBO_SYNC_BATCH;
+
+        let mut next = token;
+        if let Some(chapter) = chapters.last() {
+            next.last_chapter_id = chapter.id;
+        }
+        if let Some(state) = states.last() {
+            next.states_since = Some(state.updated_at.clone());
+        }
+
+        let mut contents: HashMap<i64, Arc<Content>> = HashMap::new();
+        let mut books = Vec::with_capacity(chapters.len());
+        for chapter in chapters {
+            if !contents.contains_key(&chapter.content_id)
+                && let Some(content) =
+                    ContentRepository::find_by_id(pool, chapter.content_id).await?
+            {
+                contents.insert(content.id, Arc::new(content));
+            }
+            if let Some(content) = contents.get(&chapter.content_id)
+                && age_rating_allowed(max_age_rating, content.age_rating)
+            {
+                books.push((chapter, Arc::clone(content)));
+            }
+        }
+
+        Ok(KoboSync {
+            books,
+            states,
+            token: next,
+            more,
+        })
+    }
+
+    /// Get an EPUB chapter offered to Kobo devices.
+    pub async fn get_book(pool: &Pool<Sqlite>, chapter_id: i64) -> Result<(Chapter, Content)> {
+        let chapter = ChapterRepository::find_by_id(pool, chapter_id)
+            .await?
+            .filter(|c| c.file_type == "epub")
+            .ok_or_else(|| {
+                AppError::NotFound(t!("kobo.book_not_found", id = chapter_id).to_string())
+                    .with_code(ErrorCode::ChapterNotFound)
+            })?;
+        let content = ContentRepository::find_by_id(pool, chapter.content_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound(t!("kobo.book_not_found", id = chapter_id).to_string())
+                    .with_code(ErrorCode::ChapterNotFound)
+            })?;
+        Ok((chapter, content))
+    }
+
+    /// Record the reading state of a book sent by a device.
+    ///
+    /// The percentage is stored as the user's progress on the chapter, at
+    /// the EPUB section named by the location when it can be found, so the
+    /// web reader opens near the same place. Without a percentage the
+    /// stored one is kept.
+    pub async fn update_state(
+        pool: &Pool<Sqlite>,
+        progress_service: &ProgressService,
+        user_id: i64,
+        chapter_id: i64,
+        update: KoboStateUpdate,
+    ) -> Result<KoboReadingState> {
+        let (chapter, _) = Self::get_book(pool, chapter_id).await?;
+        let existing =
+            ProgressRepository::find_by_user_and_chapter(pool, user_id, chapter_id).await?;
+
+        let percentage = if update.finished {
+            100.0
+        } else {
+            update
+                .percentage
+                .or(existing.as_ref().map(|p| p.percentage))
+                .unwrap_or(0.0)
+                .clamp(0.0, 100.0)
+        };
+
+        let section = match update.location.source.clone() {
+            Some(source) => Self::find_section(&chapter, source).await,
+            None => None,
+        };
+        match section {
+            Some(section) => {
+                let novel = NovelPosition {
+                    section,
+                    offset: 0,
+                    cfi: None,
+                };
+                progress_service
+                    .update_novel_progress(user_id, chapter_id, section, Some(percentage), novel)
+                    .await?;
+            }
+            None => {
+                let position = existing.map(|p| p.position).unwrap_or(0);
+                progress_service
+                    .update_progress_with_percentage(user_id, chapter_id, position, percentage)
+                    .await?;
+            }
+        }
+
+        KoboRepository::save_location(pool, user_id, chapter_id, &update.location).await?;
+        KoboRepository::find_state(pool, user_id, chapter_id)
+            .await?
+            .ok_or_else(|| AppError::Internal("Failed to retrieve Kobo reading state".to_string()))
+    }
+
+    /// Spine index of the EPUB section at `source`, a path inside the book
+    /// that may be relative to a different root than the package's.
+    async fn find_section(chapter: &Chapter, source: String) -> Option<i32> {
+        let path = PathBuf::from(&chapter.file_path);
+        let manifest = spawn_cancellable(move |_| EpubExtractor::manifest(&path))
+            .await
+            .ok()?;
+        manifest
+            .sections
+            .iter()
+            .find(|s| {
+                s.path == source
+                    || s.path.ends_with(&format!("/{source}"))
+                    || source.ends_with(&format!("/{}", s.path))
+            })
+            .map(|s| s.index as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{
+        create_test_chapter, create_test_content, create_test_db, create_test_library,
+        create_test_scan_path, create_test_user,
+    };
+
+    async fn setup() -> Pool<Sqlite> {
+        let pool = create_test_db().await;
+        create_test_user(&pool, 1, "reader").await;
+        create_test_library(&pool, 1, "Novels").await;
+        create_test_scan_path(&pool, 1, 1, "/novels").await;
+        create_test_content(&pool, 1, 1, "Journey").await;
+        create_test_chapter(&pool, 1, 1, "/novels/Journey/Vol 1.epub").await;
+        create_test_chapter(&pool, 2, 1, "/novels/Journey/Vol 2.txt").await;
+        create_test_chapter(&pool, 3, 1, "/novels/Journey/Vol 3.epub").await;
+        pool
+    }
+
+    #[test]
+    fn test_sync_token_round_trip() {
+        let token = KoboSyncToken {
+            last_chapter_id: 42,
+            states_since: Some("2026-10-16T00:00:00+00:00".to_string()),
+        };
+        assert_eq!(KoboSyncToken::decode(&token.encode()), Some(token));
+        assert_eq!(KoboSyncToken::decode("not a token"), None);
+    }
+
+    #[tokio::test]
+    async fn test_sync_sends_new_epubs_once() {
+        let pool = setup().await;
+
+        let sync = KoboService::sync(&pool, 1, KoboSyncToken::default())
+            .await
+            .unwrap();
+        let ids: Vec<i64> = sync.books.iter().map(|(c, _)| c.id).collect();
+        assert_eq!(ids, vec![1, 3]);
+        assert!(!sync.more);
+        assert_eq!(sync.token.last_chapter_id, 3);
+
+        let again = KoboService::sync(&pool, 1, sync.token).await.unwrap();
+        assert!(again.books.is_empty());
+        assert!(again.states.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_update_state_keeps_location_and_percentage() {
+        let pool = setup().await;
+        let progress_service = ProgressService::new(pool.clone());
+        let token = KoboService::sync(&pool, 1, KoboSyncToken::default())
+            .await
+            .unwrap()
+            .token;
+
+        let update = KoboStateUpdate {
+            percentage: Some(42.0),
+            finished: false,
+            location: KoboLocation {
+                value: Some("kobo.3.1".to_string()),
+                kind: Some("KoboSpan".to_string()),
+                source: None,
+            },
+        };
+        let state = KoboService::update_state(&pool, &progress_service, 1, 1, update)
+            .await
+            .unwrap();
+        assert_eq!(state.percentage, 42.0);
+        assert_eq!(state.location.value.as_deref(), Some("kobo.3.1"));
+
+        // The change is sent with the next sync
+        let sync = KoboService::sync(&pool, 1, token).await.unwrap();
+        assert_eq!(sync.states, vec![state]);
+
+        let finished = KoboStateUpdate {
+            finished: true,
+            ..KoboStateUpdate::default()
+        };
+        let state = KoboService::update_state(&pool, &progress_service, 1, 1, finished)
+            .await
+            .unwrap();
+        assert_eq!(state.percentage, 100.0);
+
+        // Only EPUB chapters are synced
+        assert!(
+            KoboService::update_state(&pool, &progress_service, 1, 2, KoboStateUpdate::default())
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,430 @@
+//! KOReader sync service.
+//!
+//! KOReader's progress sync plugin talks to a kosync server: devices push
+//! the position reached in a document and pull the latest one, naming the
+//! document by a partial MD5 of its file. Positions are kept as devices
+//! sent them, and also stored as the progress of the chapter with the same
+//! file, so the other readers continue at the same place and the other way
+//! round.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use md5::{Digest, Md5};
+use sqlx::{Pool, Sqlite};
+use tracing::warn;
+
+use crate::error::{AppError, ErrorCode, Result};
+use crate::models::{Chapter, KoreaderProgress, NovelPosition, UpdateKoreaderProgress, User};
+use crate::repository::apikey::ApiKeyRepository;
+use crate::repository::koreader::KoreaderRepository;
+use crate::repository::progress::ProgressRepository;
+use crate::repository::user::UserRepository;
+use crate::services::progress::ProgressService;
+use crate::t;
+use crate::utils::cancel::spawn_cancellable;
+
+/// Chapters hashed at a time while looking for an unknown document.
+const HASH_BATCH: i64 = 100;
+
+/// Device name of positions derived from the progress of other readers.
+pub const SERVER_DEVICE: &str = "Ryuri";
+
+/// Service for KOReader sync operations.
+pub struct KoreaderService;
+
+impl KoreaderService {
+    /// Authenticate a device. Users log in with their username and one of
+    /// their API keys as the password, of which devices send the MD5.
+    pub async fn authenticate(pool: &Pool<Sqlite>, username: &str, key: &str) -> Result<User> {
+        let invalid = || {
+            AppError::Unauthorized(t!("koreader.invalid_credentials").to_string())
+                .with_code(ErrorCode::InvalidCredentials)
+        };
+
+        let user = UserRepository::find_by_username(pool, username)
+            .await?
+            .ok_or_else(invalid)?;
+        let key = key.to_ascii_lowercase();
+        let matches = ApiKeyRepository::list_by_user(pool, user.id)
+            .await?
+            .iter()
+            .any(|api_key| md5_hex(api_key.api_key.as_bytes()) == key);
+        if !matches {
+            warn!(username = %username, "{}", t!("koreader.invalid_credentials_log"));
+            return Err(invalid());
+        }
+
+        Ok(user)
+    }
+
+    /// Get the latest position in a document: the one a device sent, unless
+    /// the chapter with the same file was read in another reader since.
+    pub async fn get_progress(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        document: &str,
+    ) -> Result<Option<KoreaderProgress>> {
+        let sent = KoreaderRepository::find_progress(pool, user_id, document).await?;
+        let Some(chapter) = Self::find_chapter(pool, document).await? else {
+            return Ok(sent);
+        };
+        let Some(progress) =
+            ProgressRepository::find_by_user_and_chapter(pool, user_id, chapter.id).await?
+        else {
+            return Ok(sent);
+        };
+        if sent
+            .as_ref()
+            .is_some_and(|sent| sent.updated_at >= progress.updated_at)
+        {
+            return Ok(sent);
+        }
+
+        let position = if chapter.file_type == "epub" {
+            let section = progress.novel_section.unwrap_or(progress.position);
+            format!("/body/DocFragment[{}]", section + 1)
+        } else if !chapter.is_text_based() {
+            (progress.position + 1).to_string()
+        } else {
+            // Positions in other text can only be told apart by KOReader
+            return Ok(sent);
+        };
+
+        Ok(Some(KoreaderProgress {
+            document: document.to_string(),
+            progress: position,
+            percentage: progress.percentage / 100.0,
+            device: SERVER_DEVICE.to_string(),
+            device_id: SERVER_DEVICE.to_string(),
+            updated_at: progress.updated_at,
+        }))
+    }
+
+    /// Record the position a device sent for a document.
+    ///
+    /// When the document is the file of a chapter, the position is also
+    /// stored as the user's progress on it: at the EPUB section of the
+    /// XPointer, or at the page for comics and PDFs.
+    pub async fn update_progress(
+        pool: &Pool<Sqlite>,
+        progress_service: &ProgressService,
+        user_id: i64,
+        update: UpdateKoreaderProgress,
+    ) -> Result<KoreaderProgress> {
+        if update.document.is_empty() {
+            return Err(AppError::BadRequest(
+                t!("koreader.document_required").to_string(),
+            ));
+        }
+        if !(0.0..=1.0).contains(&update.percentage) {
+            return Err(AppError::BadRequest(
+                t!("koreader.invalid_percentage").to_string(),
+            ));
+        }
+
+        if let Some(chapter) = Self::find_chapter(pool, &update.document).await? {
+            let percentage = update.percentage * 100.0;
+            let section = xpointer_section(&update.progress)
+                .filter(|_| chapter.file_type == "epub")
+                .filter(|&s| chapter.page_count == 0 || s < chapter.page_count);
+            let page = page_index(&update.progress).filter(|_| !chapter.is_text_based());
+
+            if let Some(section) = section {
+                let novel = NovelPosition {
+                    section,
+                    offset: 0,
+                    cfi: None,
+                };
+                progress_service
+                    .update_novel_progress(user_id, chapter.id, section, Some(percentage), novel)
+                    .await?;
+            } else {
+                let position = match page {
+                    Some(page) => page,
+                    None => ProgressRepository::find_by_user_and_chapter(pool, user_id, chapter.id)
+                        .await?
+                        .map(|p| p.position)
+                        .unwrap_or(0),
+                };
+                progress_service
+                    .update_progress_with_percentage(user_id, chapter.id, position, percentage)
+                    .await?;
+            }
+        }
+
+        // Saved last, so the device's own position wins over the progress
+        // it just updated
+        KoreaderRepository::save_progress(pool, user_id, &update).await
+    }
+
+    /// Get the chapter whose file is the document, hashing the files not
+    /// hashed yet until it is found.
+    ///
+    /// Hashes are stored as they are computed, so each file is read once.
+    pub async fn find_chapter(pool: &Pool<Sqlite>, document: &str) -> Result<Option<Chapter>> {
+        if let Some(chapter) = KoreaderRepository::find_chapter_by_hash(pool, document).await? {
+            return Ok(Some(chapter));
+        }
+
+        let mut after_id = 0;
+        loop {
+            let chapters =
+                KoreaderRepository::list_chapters_without_hash(pool, after_id, HASH_BATCH).await?;
+            let Some(last) = chapters.last() else {
+                return Ok(None);
+            };
+            after_id = last.id;
+
+            let paths: Vec<(i64, PathBuf)> = chapters
+                .iter()
+                .map(|c| (c.id, PathBuf::from(&c.file_path)))
+                .collect();
+            let hashes = spawn_cancellable(move |cancel| {
+                let mut hashes = Vec::with_capacity(paths.len());
+                for (id, path) in paths {
+                    cancel.check()?;
+                    hashes.push((id, partial_md5(&path).map_err(|e| (path, e))));
+                }
+                Ok(hashes)
+            })
+            .await?;
+
+            let mut found = None;
+            for (chapter_id, hash) in hashes {
+                match hash {
+                    Ok(hash) => {
+                        KoreaderRepository::set_hash(pool, chapter_id, &hash).await?;
+                        if hash == document {
+                            found = Some(chapter_id);
+                        }
+                    }
+                    Err((path, e)) => {
+                        let path = path.display();
+                        warn!(path = %path, error = %e, "{}", t!("koreader.hash_failed"));
+                    }
+                }
+            }
+            if let Some(chapter_id) = found {
+                return Ok(chapters.into_iter().find(|c| c.id == chapter_id));
+            }
+        }
+    }
+}
+
+/// KOReader's partial MD5 of a file: the MD5 of up to 1 KiB read at the
+/// start of the file and at 1 KiB, 4 KiB, 16 KiB and so on up to 1 GiB,
+/// stopping at the end of the file.
+///
+/// KOReader meant the first sample to be at 256 bytes, but its shift by a
+/// negative amount wraps around to the start of the file.
+pub fn partial_md5(path: &Path) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+
+    let mut hasher = Md5::new();
+    let mut sample = Vec::with_capacity(1024);
+    for offset in std::iter::once(0).chain((0..=10).map(|i| 1024u64 << (2 * i))) {
+        if offset >= len {
+            break;
+        }
+        file.seek(SeekFrom::Start(offset))?;
+        sample.clear();
+        (&mut file).take(1024).read_to_end(&mut sample)?;
+        hasher.update(&sample);
+    }
+
+    Ok(hex(&hasher.finalize()))
+}
+
+/// Hex MD5 of bytes.
+fn md5_hex(data: &[u8]) -> String {
+    hex(&Md5::digest(data))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Spine index (0-based) in an XPointer such as
+/// `/body/DocFragment[12]/body/p[3]/text().0`, whose fragments count from 1.
+fn xpointer_section(progress: &str) -> Option<i32> {
+    let rest = progress.strip_prefix("/body/DocFragment[")?;
+    let fragment: i32 = rest[..rest.find(']')?].parse().ok()?;
+    (fragment >= 1).then(|| fragment - 1)
+}
+
+/// Page index (0-based) of a page number (1-based).
+fn page_index(progress: &str) -> Option<i32> {
+    let page: i32 = progress.trim().parse().ok()?;
+    (page >= 1).then(|| page - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{
+        TIMESTAMP, TestChapter, create_test_content, create_test_db, create_test_library,
+        create_test_scan_path, create_test_user,
+    };
+
+    async fn setup(dir: &Path) -> Pool<Sqlite> {
+        let pool = create_test_db().await;
+
+        std::fs::write(dir.join("Vol 1.cbz"), vec![b'a'; 2000]).unwrap();
+        std::fs::write(dir.join("Vol 2.epub"), "hello").unwrap();
+        create_test_user(&pool, 1, "reader").await;
+        sqlx::query(
+            r#"
+            INSERT INTO api_keys (id, user_id, name, api_key, created_at)
+            VALUES (1, 1, 'koreader', 'secret', ?)
+            "#,
+        )
+        .bind(TIMESTAMP)
+        .execute(&pool)
+        .await
+        .unwrap();
+        create_test_library(&pool, 1, "Books").await;
+        create_test_scan_path(&pool, 1, 1, "/books").await;
+        create_test_content(&pool, 1, 1, "Journey").await;
+        for (id, name, pages) in [(1, "Vol 1.cbz", 20), (2, "Vol 2.epub", 8)] {
+            TestChapter::new(id, 1, &dir.join(name).to_string_lossy())
+                .page_count(pages)
+                .insert(&pool)
+                .await;
+        }
+        pool
+    }
+
+    fn update(document: &str, progress: &str, percentage: f32) -> UpdateKoreaderProgress {
+        UpdateKoreaderProgress {
+            document: document.to_string(),
+            progress: progress.to_string(),
+            percentage,
+            device: "Kindle".to_string(),
+            device_id: "kindle-1".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_partial_md5() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("book");
+
+        std::fs::write(&path, "hello").unwrap();
+        assert_eq!(
+            partial_md5(&path).unwrap(),
+            "5d41402abc4b2a76b9719d911017c592"
+        );
+
+        // Samples at 0 and 1024 cover the whole file
+        std::fs::write(&path, vec![b'a'; 2000]).unwrap();
+        assert_eq!(partial_md5(&path).unwrap(), md5_hex(&[b'a'; 2000]));
+    }
+
+    #[test]
+    fn test_parse_positions() {
+        assert_eq!(
+            xpointer_section("/body/DocFragment[12]/body/p[3]/text().0"),
+            Some(11)
+        );
+        assert_eq!(xpointer_section("/body/DocFragment[0]"), None);
+        assert_eq!(xpointer_section("/body/pre[2]"), None);
+        assert_eq!(page_index("42"), Some(41));
+        assert_eq!(page_index("0"), None);
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_with_api_key_md5() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = setup(dir.path()).await;
+
+        let key = md5_hex(b"secret");
+        assert_eq!(
+            KoreaderService::authenticate(&pool, "reader", &key)
+                .await
+                .unwrap()
+                .id,
+            1
+        );
+        assert!(
+            KoreaderService::authenticate(&pool, "reader", &md5_hex(b"password"))
+                .await
+                .is_err()
+        );
+        assert!(
+            KoreaderService::authenticate(&pool, "nobody", &key)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_progress_syncs_with_chapters() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = setup(dir.path()).await;
+        let progress_service = ProgressService::new(pool.clone());
+        let comic = md5_hex(&[b'a'; 2000]);
+        let novel = md5_hex(b"hello");
+
+        // A page of a comic is stored as the chapter's position
+        let sent = KoreaderService::update_progress(
+            &pool,
+            &progress_service,
+            1,
+            update(&comic, "5", 0.25),
+        )
+        .await
+        .unwrap();
+        let progress = ProgressRepository::find_by_user_and_chapter(&pool, 1, 1)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(progress.position, 4);
+        assert_eq!(progress.percentage, 25.0);
+        let pulled = KoreaderService::get_progress(&pool, 1, &comic)
+            .await
+            .unwrap();
+        assert_eq!(pulled, Some(sent));
+
+        // Progress from another reader is sent as a section XPointer
+        progress_service
+            .update_novel_progress(
+                1,
+                2,
+                3,
+                Some(50.0),
+                NovelPosition {
+                    section: 3,
+                    offset: 120,
+                    cfi: None,
+                },
+            )
+            .await
+            .unwrap();
+        let pulled = KoreaderService::get_progress(&pool, 1, &novel)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(pulled.progress, "/body/DocFragment[4]");
+        assert_eq!(pulled.percentage, 0.5);
+        assert_eq!(pulled.device, SERVER_DEVICE);
+
+        // Unknown documents are kept as they are
+        let sent = KoreaderService::update_progress(
+            &pool,
+            &progress_service,
+            1,
+            update("0123456789abcdef", "/body/DocFragment[2]", 0.1),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            KoreaderService::get_progress(&pool, 1, "0123456789abcdef")
+                .await
+                .unwrap(),
+            Some(sent)
+        );
+    }
+}
@@ -2,26 +2,67 @@
 //!
 //! This module provides business logic for library and scan path management.
 
+use std::path::Path;
+use std::sync::Arc;
+
 use sqlx::{Pool, Sqlite};
-use tracing::instrument;
-use rust_i18n::t;
+use tracing::{info, instrument};
 
-use crate::error::{AppError, Result};
+use crate::error::{AppError, ErrorCode, Result};
 use crate::models::{
-    CreateLibraryRequest, Library, LibraryWithStats, NewLibrary, NewScanPath, ScanPath,
-    UpdateLibraryRequest,
+    CreateLibraryRequest, DEFAULT_METADATA_PROVIDERS, DEFAULT_SCAN_DEPTH, Library,
+    LibraryWithStats, MAX_SCAN_DEPTH, MetadataProviderKind, NewLibrary, NewScanPath, ScanPath,
+    TaskProgress, UpdateLibraryRequest,
+};
+use crate::repository::library::{
+    IgnorePatternRepository, LibraryRepository, MetadataProviderRepository, ScanPathRepository,
 };
-use crate::repository::library::{LibraryRepository, ScanPathRepository};
+use crate::services::filesystem::FilesystemService;
+use crate::services::settings::SettingsService;
+use crate::t;
+use crate::utils::ignore::{DEFAULT_IGNORE_PATTERNS, is_valid_pattern};
+
+/// Number of chapters or contents removed by each statement when a
+/// library is deleted.
+const DELETE_BATCH_SIZE: i64 = 500;
+
+/// Result of deleting a library.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct LibraryDeletionResult {
+    /// Number of chapters removed.
+    pub chapters: usize,
+    /// Number of contents removed.
+    pub contents: usize,
+}
 
 /// Service for library management operations.
 pub struct LibraryService {
     pool: Pool<Sqlite>,
+    /// Server settings with the metadata providers of new libraries.
+    settings: Option<Arc<SettingsService>>,
+    /// Allowed roots new scan paths must lie in.
+    filesystem: Option<Arc<FilesystemService>>,
 }
 
 impl LibraryService {
     /// Create a new library service.
     pub fn new(pool: Pool<Sqlite>) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            settings: None,
+            filesystem: None,
+        }
+    }
+
+    /// Set the server settings new libraries take their metadata providers
+    /// from. Without them, [`DEFAULT_METADATA_PROVIDERS`] are used.
+    pub fn set_settings(&mut self, settings: Arc<SettingsService>) {
+        self.settings = Some(settings);
+    }
+
+    /// Limit new scan paths to the allowed roots of `filesystem`.
+    pub fn set_filesystem(&mut self, filesystem: Arc<FilesystemService>) {
+        self.filesystem = Some(filesystem);
     }
 
     /// Create a new library.
@@ -36,13 +77,31 @@ impl LibraryService {
             ));
         }
 
+        let scan_depth = req.scan_depth.unwrap_or(DEFAULT_SCAN_DEPTH);
+        Self::validate_scan_depth(scan_depth)?;
+
         let new_library = NewLibrary {
             name: req.name.trim().to_string(),
             scan_interval: req.scan_interval.unwrap_or(0),
             watch_mode: req.watch_mode.unwrap_or(false),
+            scan_depth,
+            standalone_archives: req.standalone_archives.unwrap_or(false),
+        };
+
+        let library = LibraryRepository::create(&self.pool, new_library).await?;
+
+        let patterns: Vec<String> = DEFAULT_IGNORE_PATTERNS
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        IgnorePatternRepository::replace(&self.pool, library.id, &patterns).await?;
+        let providers = match &self.settings {
+            Some(settings) => settings.metadata_providers(),
+            None => DEFAULT_METADATA_PROVIDERS.to_vec(),
         };
+        MetadataProviderRepository::replace(&self.pool, library.id, &providers).await?;
 
-        LibraryRepository::create(&self.pool, new_library).await
+        Ok(library)
     }
 
     /// Get a library by ID.
@@ -52,9 +111,10 @@ impl LibraryService {
 
     /// Get a library by ID, returning an error if not found.
     pub async fn get_or_error(&self, id: i64) -> Result<Library> {
-        self.get(id)
-            .await?
-            .ok_or_else(|| AppError::NotFound(t!("library.id_not_found", id = id).to_string()))
+        self.get(id).await?.ok_or_else(|| {
+            AppError::NotFound(t!("library.id_not_found", id = id).to_string())
+                .with_code(ErrorCode::LibraryNotFound)
+        })
     }
 
     /// List all libraries with statistics.
@@ -77,23 +137,106 @@ impl LibraryService {
             ));
         }
 
+        if let Some(scan_depth) = req.scan_depth {
+            Self::validate_scan_depth(scan_depth)?;
+        }
+
         LibraryRepository::update(
             &self.pool,
             id,
             req.name.map(|n| n.trim().to_string()),
             req.scan_interval,
             req.watch_mode,
+            req.scan_depth,
+            req.standalone_archives,
         )
         .await
     }
 
+    /// Check that a scan depth is within 1 and [`MAX_SCAN_DEPTH`].
+    fn validate_scan_depth(scan_depth: i32) -> Result<()> {
+        if !(1..=MAX_SCAN_DEPTH).contains(&scan_depth) {
+            return Err(AppError::BadRequest(
+                t!("library.invalid_scan_depth", max = MAX_SCAN_DEPTH).to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Hide a library whose deletion is about to start, so it can no
+    /// longer be listed, scanned or changed.
+    #[instrument(skip(self), fields(library_id = id))]
+    pub async fn mark_deleting(&self, id: i64) -> Result<Library> {
+        let library = self.get_or_error(id).await?;
+        LibraryRepository::mark_deleting(&self.pool, id).await?;
+        Ok(library)
+    }
+
     /// Delete a library.
     ///
     /// This will cascade delete all associated scan paths and contents.
     /// Requirements: 1.6
-    #[instrument(skip(self), fields(library_id = id))]
-    pub async fn delete(&self, id: i64) -> Result<()> {
-        LibraryRepository::delete(&self.pool, id).await
+    pub async fn delete(&self, id: i64) -> Result<LibraryDeletionResult> {
+        self.delete_in_batches(id, |_| {}).await
+    }
+
+    /// Delete a library, also one already hidden by
+    /// [`mark_deleting`](Self::mark_deleting).
+    ///
+    /// Chapters, with their reading progress, and then contents are removed
+    /// in batches so no single statement holds the database for long; the
+    /// library and its scan paths go last. `on_progress` is called with the
+    /// number of removed chapters and contents after each batch.
+    #[instrument(skip(self, on_progress), fields(library_id = id))]
+    pub async fn delete_in_batches(
+        &self,
+        id: i64,
+        on_progress: impl Fn(TaskProgress),
+    ) -> Result<LibraryDeletionResult> {
+        if !LibraryRepository::mark_deleting(&self.pool, id).await? {
+            return Err(
+                AppError::NotFound(t!("library.id_not_found", id = id).to_string())
+                    .with_code(ErrorCode::LibraryNotFound),
+            );
+        }
+
+        let chapters = LibraryRepository::count_all_chapters(&self.pool, id).await?;
+        let contents = LibraryRepository::count_all_contents(&self.pool, id).await?;
+        let total = (chapters + contents) as i32;
+        let mut result = LibraryDeletionResult::default();
+        let progress = |result: &LibraryDeletionResult| TaskProgress {
+            scanned_paths: (result.chapters + result.contents) as i32,
+            total_paths: total,
+        };
+        on_progress(progress(&result));
+
+        loop {
+            let deleted =
+                LibraryRepository::delete_chapter_batch(&self.pool, id, DELETE_BATCH_SIZE).await?;
+            if deleted == 0 {
+                break;
+            }
+            result.chapters += deleted as usize;
+            on_progress(progress(&result));
+        }
+        loop {
+            let deleted =
+                LibraryRepository::delete_content_batch(&self.pool, id, DELETE_BATCH_SIZE).await?;
+            if deleted == 0 {
+                break;
+            }
+            result.contents += deleted as usize;
+            on_progress(progress(&result));
+        }
+
+        LibraryRepository::delete(&self.pool, id).await?;
+        info!(
+            chapters = result.chapters,
+            contents = result.contents,
+            "{}",
+            t!("library.deleted")
+        );
+        Ok(result)
     }
 
     /// Add a scan path to a library.
@@ -110,6 +253,11 @@ impl LibraryService {
                 "Scan path cannot be empty".to_string(),
             ));
         }
+        if let Some(filesystem) = &self.filesystem
+            && filesystem.is_restricted()
+        {
+            filesystem.check_allowed(Path::new(path.trim()))?;
+        }
 
         let new_scan_path = NewScanPath {
             library_id,
@@ -136,6 +284,80 @@ impl LibraryService {
         ScanPathRepository::list_by_library(&self.pool, library_id).await
     }
 
+    /// Get a scan path by ID.
+    pub async fn get_scan_path(&self, path_id: i64) -> Result<Option<ScanPath>> {
+        ScanPathRepository::find_by_id(&self.pool, path_id).await
+    }
+
+    /// List the ignore patterns of a library.
+    pub async fn list_ignore_patterns(&self, library_id: i64) -> Result<Vec<String>> {
+        // Verify library exists
+        self.get_or_error(library_id).await?;
+
+        IgnorePatternRepository::list_by_library(&self.pool, library_id).await
+    }
+
+    /// Replace the ignore patterns of a library.
+    ///
+    /// Patterns are trimmed and duplicates dropped. Returns the stored patterns.
+    pub async fn set_ignore_patterns(
+        &self,
+        library_id: i64,
+        patterns: Vec<String>,
+    ) -> Result<Vec<String>> {
+        // Verify library exists
+        self.get_or_error(library_id).await?;
+
+        let mut stored: Vec<String> = Vec::with_capacity(patterns.len());
+        for pattern in patterns {
+            let pattern = pattern.trim().to_string();
+            if !is_valid_pattern(&pattern) {
+                return Err(AppError::BadRequest(
+                    t!("library.invalid_ignore_pattern", pattern = pattern).to_string(),
+                ));
+            }
+            if !stored.contains(&pattern) {
+                stored.push(pattern);
+            }
+        }
+
+        IgnorePatternRepository::replace(&self.pool, library_id, &stored).await?;
+        Ok(stored)
+    }
+
+    /// List the metadata providers of a library in priority order.
+    pub async fn list_metadata_providers(
+        &self,
+        library_id: i64,
+    ) -> Result<Vec<MetadataProviderKind>> {
+        // Verify library exists
+        self.get_or_error(library_id).await?;
+
+        MetadataProviderRepository::list_by_library(&self.pool, library_id).await
+    }
+
+    /// Replace the metadata providers of a library.
+    ///
+    /// Duplicates are dropped. Returns the stored providers.
+    pub async fn set_metadata_providers(
+        &self,
+        library_id: i64,
+        providers: Vec<MetadataProviderKind>,
+    ) -> Result<Vec<MetadataProviderKind>> {
+        // Verify library exists
+        self.get_or_error(library_id).await?;
+
+        let mut stored: Vec<MetadataProviderKind> = Vec::with_capacity(providers.len());
+        for provider in providers {
+            if !stored.contains(&provider) {
+                stored.push(provider);
+            }
+        }
+
+        MetadataProviderRepository::replace(&self.pool, library_id, &stored).await?;
+        Ok(stored)
+    }
+
     /// Get library statistics (path count and content count).
     pub async fn get_stats(&self, library_id: i64) -> Result<(i64, i64)> {
         let path_count = LibraryRepository::count_scan_paths(&self.pool, library_id).await?;
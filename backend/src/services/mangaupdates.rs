@@ -0,0 +1,306 @@
+//! MangaUpdates metadata service.
+//!
+//! This module searches and fetches series from the MangaUpdates API and
+//! converts them to the Bangumi-shaped metadata used throughout the app.
+
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::error::{AppError, Result};
+use crate::models::{MetadataProviderKind, MetadataSearchResult};
+use crate::services::metadata::{MetadataProvider, ProviderFuture, tag_source};
+use crate::t;
+
+/// Base URL for the MangaUpdates API.
+const MANGAUPDATES_API_BASE: &str = "https://api.mangaupdates.com/v1";
+
+/// User agent for API requests.
+const USER_AGENT: &str = "ryuri/0.1.1 (https://github.com/tnzzzhlp/ryuri)";
+
+/// Genres of series for adults only.
+const ADULT_GENRES: [&str; 2] = ["Adult", "Hentai"];
+
+/// Response from the series search API.
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    results: Vec<SearchHit>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchHit {
+    record: MangaUpdatesSeries,
+}
+
+/// A series from the MangaUpdates API. Search results only carry the
+/// basic fields.
+#[derive(Debug, Default, Deserialize)]
+struct MangaUpdatesSeries {
+    series_id: i64,
+    title: String,
+    #[serde(default)]
+    associated: Vec<AssociatedTitle>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    image: Option<SeriesImage>,
+    #[serde(default)]
+    year: Option<String>,
+    #[serde(default)]
+    genres: Vec<Genre>,
+    #[serde(default)]
+    categories: Vec<Category>,
+    #[serde(default)]
+    authors: Vec<Author>,
+    #[serde(default)]
+    publishers: Vec<Publisher>,
+    #[serde(default)]
+    bayesian_rating: Option<f64>,
+    #[serde(default)]
+    url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AssociatedTitle {
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SeriesImage {
+    url: ImageUrls,
+}
+
+#[derive(Debug, Deserialize)]
+struct ImageUrls {
+    original: Option<String>,
+    thumb: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Genre {
+    genre: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Category {
+    category: String,
+    #[serde(default)]
+    votes: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Author {
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Publisher {
+    publisher_name: String,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+impl MangaUpdatesSeries {
+    fn cover(&self) -> Option<String> {
+        self.image
+            .as_ref()
+            .and_then(|i| i.url.original.clone().or_else(|| i.url.thumb.clone()))
+    }
+
+    /// Convert to Bangumi-shaped metadata.
+    fn into_metadata(self) -> serde_json::Value {
+        let cover = self.cover();
+
+        let mut infobox = Vec::new();
+        for author in &self.authors {
+            let key = match author.kind.as_str() {
+                "Author" => "作者",
+                "Artist" => "作画",
+                _ => continue,
+            };
+            infobox.push(json!({ "key": key, "value": author.name }));
+        }
+        if let Some(publisher) = self.publishers.iter().find(|p| p.kind == "Original") {
+            infobox.push(json!({ "key": "出版社", "value": publisher.publisher_name }));
+        }
+
+        // Adult genres mark the series as adult only, like Bangumi's flag
+        let nsfw = self
+            .genres
+            .iter()
+            .any(|genre| ADULT_GENRES.contains(&genre.genre.as_str()));
+        let tags: Vec<serde_json::Value> = self
+            .genres
+            .iter()
+            .map(|genre| json!({ "name": genre.genre, "count": 100 }))
+            .chain(
+                self.categories
+                    .iter()
+                    .map(|c| json!({ "name": c.category, "count": c.votes })),
+            )
+            .collect();
+
+        json!({
+            "id": self.series_id,
+            "name": self.title,
+            "name_cn": self.associated.first().map(|a| a.title.clone()),
+            "summary": self.description.unwrap_or_default(),
+            "date": self.year,
+            "tags": tags,
+            "infobox": infobox,
+            "images": { "common": cover, "large": cover },
+            "rating": { "score": self.bayesian_rating },
+            "nsfw": nsfw,
+            "url": self.url,
+        })
+    }
+}
+
+/// Service for fetching metadata from the MangaUpdates API.
+pub struct MangaUpdatesService {
+    client: Client,
+}
+
+impl MangaUpdatesService {
+    /// Create a new MangaUpdatesService instance.
+    pub fn new() -> Self {
+        let client = Client::builder()
+            .user_agent(USER_AGENT)
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self { client }
+    }
+}
+
+impl Default for MangaUpdatesService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetadataProvider for MangaUpdatesService {
+    fn kind(&self) -> MetadataProviderKind {
+        MetadataProviderKind::MangaUpdates
+    }
+
+    fn search<'a>(&'a self, query: &'a str) -> ProviderFuture<'a, Vec<MetadataSearchResult>> {
+        Box::pin(async move {
+            if query.trim().is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let url = format!("{}/series/search", MANGAUPDATES_API_BASE);
+            let response = self
+                .client
+                .post(&url)
+                .json(&json!({ "search": query, "perpage": 10 }))
+                .send()
+                .await
+                .map_err(|e| AppError::Internal(format!("Failed to search MangaUpdates: {}", e)))?;
+
+            if !response.status().is_success() {
+                return Err(AppError::Internal(format!(
+                    "MangaUpdates API returned error: {}",
+                    response.status()
+                )));
+            }
+
+            let search: SearchResponse = response.json().await.map_err(|e| {
+                AppError::Internal(format!("Failed to parse MangaUpdates response: {}", e))
+            })?;
+
+            Ok(search
+                .results
+                .into_iter()
+                .map(|hit| MetadataSearchResult {
+                    provider: MetadataProviderKind::MangaUpdates,
+                    id: hit.record.series_id.to_string(),
+                    image: hit.record.cover(),
+                    alt_title: hit.record.associated.first().map(|a| a.title.clone()),
+                    title: hit.record.title,
+                    summary: hit.record.description,
+                })
+                .collect())
+        })
+    }
+
+    fn fetch<'a>(&'a self, id: &'a str) -> ProviderFuture<'a, serde_json::Value> {
+        Box::pin(async move {
+            let series_id: i64 = id.parse().map_err(|_| {
+                AppError::BadRequest(t!("metadata.invalid_id", id = id).to_string())
+            })?;
+
+            let url = format!("{}/series/{}", MANGAUPDATES_API_BASE, series_id);
+            let response = self.client.get(&url).send().await.map_err(|e| {
+                AppError::Internal(format!("Failed to fetch MangaUpdates series: {}", e))
+            })?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                if status.as_u16() == 404 {
+                    return Err(AppError::NotFound(
+                        t!("metadata.not_found", provider = "mangaupdates").to_string(),
+                    ));
+                }
+                return Err(AppError::Internal(format!(
+                    "MangaUpdates API returned error: {}",
+                    status
+                )));
+            }
+
+            let series: MangaUpdatesSeries = response.json().await.map_err(|e| {
+                AppError::Internal(format!("Failed to parse MangaUpdates series: {}", e))
+            })?;
+
+            let mut metadata = series.into_metadata();
+            tag_source(&mut metadata, MetadataProviderKind::MangaUpdates, id);
+            Ok(metadata)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_series_into_metadata() {
+        let series: MangaUpdatesSeries = serde_json::from_value(json!({
+            "series_id": 55665802854,
+            "title": "Shingeki no Kyojin",
+            "associated": [{ "title": "Attack on Titan" }],
+            "description": "Humanity fights titans.",
+            "image": { "url": { "original": "https://example.com/cover.jpg", "thumb": null } },
+            "year": "2009",
+            "genres": [{ "genre": "Action" }],
+            "authors": [
+                { "name": "ISAYAMA Hajime", "type": "Author" },
+                { "name": "ISAYAMA Hajime", "type": "Artist" }
+            ],
+            "publishers": [
+                { "publisher_name": "Kodansha", "type": "Original" },
+                { "publisher_name": "Kodansha USA", "type": "English" }
+            ],
+            "bayesian_rating": 8.1
+        }))
+        .unwrap();
+
+        let metadata = series.into_metadata();
+        assert_eq!(metadata["name"], "Shingeki no Kyojin");
+        assert_eq!(metadata["name_cn"], "Attack on Titan");
+        assert_eq!(metadata["date"], "2009");
+        assert_eq!(
+            metadata["images"]["common"],
+            "https://example.com/cover.jpg"
+        );
+        assert_eq!(metadata["infobox"][0]["key"], "作者");
+        assert_eq!(metadata["infobox"][1]["key"], "作画");
+        assert_eq!(metadata["infobox"][2]["value"], "Kodansha");
+        assert_eq!(metadata["rating"]["score"], 8.1);
+        assert_eq!(metadata["nsfw"], false);
+    }
+}
@@ -0,0 +1,541 @@
+//! Metadata providers.
+//!
+//! Each external metadata source implements [`MetadataProvider`]. Providers
+//! return metadata in the shape of a Bangumi subject (`name`, `summary`,
+//! `tags`, `infobox`, `images.common`, ...), which the reader, the Komga
+//! API and thumbnail downloads understand, plus a `source` object naming
+//! the provider and the ID of the series there.
+//!
+//! [`MetadataManager`] holds the registered providers and tries them in a
+//! library's priority order when scraping. [`MetadataFailureService`] keeps
+//! track of content whose metadata could not be scraped.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use serde_json::json;
+use sqlx::{Pool, Sqlite};
+use tracing::{debug, warn};
+
+use crate::error::{AppError, Result};
+use crate::models::{
+    Content, MetadataFailure, MetadataProviderKind, MetadataRetryResult, MetadataSearchResult,
+};
+use crate::repository::library::MetadataProviderRepository;
+use crate::repository::metadata::MetadataFailureRepository;
+use crate::services::content::ContentService;
+use crate::services::scan_queue::ScanService;
+use crate::t;
+
+/// Future returned by [`MetadataProvider`] methods.
+pub type ProviderFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// How a scrape started by a user queries providers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScrapeOptions<'a> {
+    /// API key of the user asking, for providers that take one.
+    pub api_key: Option<&'a str>,
+    /// Query providers again instead of reusing cached responses.
+    pub refresh_cache: bool,
+}
+
+/// An external source of content metadata.
+pub trait MetadataProvider: Send + Sync {
+    /// Which provider this is.
+    fn kind(&self) -> MetadataProviderKind;
+
+    /// Search series by title, best matches first.
+    fn search<'a>(&'a self, query: &'a str) -> ProviderFuture<'a, Vec<MetadataSearchResult>>;
+
+    /// Fetch the metadata of a series by its ID at the provider.
+    fn fetch<'a>(&'a self, id: &'a str) -> ProviderFuture<'a, serde_json::Value>;
+
+    /// Search with the options of a user's scrape, for providers that
+    /// take an API key or cache responses. Others search as usual.
+    fn search_as<'a>(
+        &'a self,
+        query: &'a str,
+        _options: ScrapeOptions<'a>,
+    ) -> ProviderFuture<'a, Vec<MetadataSearchResult>> {
+        self.search(query)
+    }
+
+    /// Fetch with the options of a user's scrape, for providers that take
+    /// an API key or cache responses. Others fetch as usual.
+    fn fetch_as<'a>(
+        &'a self,
+        id: &'a str,
+        _options: ScrapeOptions<'a>,
+    ) -> ProviderFuture<'a, serde_json::Value> {
+        self.fetch(id)
+    }
+}
+
+/// Record which provider and series a metadata blob came from.
+pub fn tag_source(metadata: &mut serde_json::Value, kind: MetadataProviderKind, id: &str) {
+    if let Some(object) = metadata.as_object_mut() {
+        object.insert(
+            "source".to_string(),
+            json!({ "provider": kind.as_str(), "id": id }),
+        );
+    }
+}
+
+/// Provider and series ID the metadata of a content came from, as
+/// recorded by [`tag_source`].
+pub fn metadata_source(content: &Content) -> Option<(MetadataProviderKind, String)> {
+    let metadata: serde_json::Value = serde_json::from_slice(content.metadata.as_deref()?).ok()?;
+    let source = metadata.get("source")?;
+    let kind = MetadataProviderKind::from_name(source.get("provider")?.as_str()?)?;
+    Some((kind, source.get("id")?.as_str()?.to_string()))
+}
+
+/// Registered metadata providers.
+#[derive(Default)]
+pub struct MetadataManager {
+    providers: HashMap<MetadataProviderKind, Arc<dyn MetadataProvider>>,
+}
+
+impl MetadataManager {
+    /// Create a manager without providers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a provider, replacing any provider of the same kind.
+    pub fn register(&mut self, provider: Arc<dyn MetadataProvider>) {
+        self.providers.insert(provider.kind(), provider);
+    }
+
+    /// Get a registered provider.
+    pub fn provider(&self, kind: MetadataProviderKind) -> Result<&Arc<dyn MetadataProvider>> {
+        self.providers.get(&kind).ok_or_else(|| {
+            AppError::BadRequest(
+                t!("metadata.provider_unavailable", provider = kind.as_str()).to_string(),
+            )
+        })
+    }
+
+    /// Search `providers` in order and concatenate their results.
+    ///
+    /// Providers that fail are skipped, so one unreachable provider does not
+    /// hide the results of the others. Fails only if every provider failed.
+    pub async fn search(
+        &self,
+        providers: &[MetadataProviderKind],
+        query: &str,
+    ) -> Result<Vec<MetadataSearchResult>> {
+        self.search_as(providers, query, ScrapeOptions::default())
+            .await
+    }
+
+    /// [`Self::search`] with the options of a user's scrape.
+    pub async fn search_as(
+        &self,
+        providers: &[MetadataProviderKind],
+        query: &str,
+        options: ScrapeOptions<'_>,
+    ) -> Result<Vec<MetadataSearchResult>> {
+        let mut results = Vec::new();
+        let mut last_error = None;
+        let mut succeeded = false;
+
+        for &kind in providers {
+            let provider = self.provider(kind)?;
+            match provider.search_as(query, options).await {
+                Ok(found) => {
+                    succeeded = true;
+                    results.extend(found);
+                }
+                Err(e) => {
+                    warn!(
+                        provider = kind.as_str(),
+                        query = %query,
+                        error = %e,
+                        "{}", t!("metadata.provider_failed")
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        match last_error {
+            Some(e) if !succeeded => Err(e),
+            _ => Ok(results),
+        }
+    }
+
+    /// Fetch the metadata of a series from a provider.
+    pub async fn fetch(&self, kind: MetadataProviderKind, id: &str) -> Result<serde_json::Value> {
+        self.fetch_as(kind, id, ScrapeOptions::default()).await
+    }
+
+    /// [`Self::fetch`] with the options of a user's scrape.
+    pub async fn fetch_as(
+        &self,
+        kind: MetadataProviderKind,
+        id: &str,
+        options: ScrapeOptions<'_>,
+    ) -> Result<serde_json::Value> {
+        self.provider(kind)?.fetch_as(id, options).await
+    }
+
+    /// Scrape metadata for a title, trying `providers` in order.
+    ///
+    /// The first provider with a search result wins, and metadata of its
+    /// best match is returned. Returns None if no provider found anything,
+    /// or the last error if a provider failed and none found anything.
+    pub async fn auto_scrape(
+        &self,
+        providers: &[MetadataProviderKind],
+        title: &str,
+    ) -> Result<Option<serde_json::Value>> {
+        self.auto_scrape_as(providers, title, ScrapeOptions::default())
+            .await
+    }
+
+    /// [`Self::auto_scrape`] with the options of a user's scrape.
+    pub async fn auto_scrape_as(
+        &self,
+        providers: &[MetadataProviderKind],
+        title: &str,
+        options: ScrapeOptions<'_>,
+    ) -> Result<Option<serde_json::Value>> {
+        let mut last_error = None;
+
+        for &kind in providers {
+            let Some(provider) = self.providers.get(&kind) else {
+                continue;
+            };
+
+            let result = match provider.search_as(title, options).await {
+                Ok(results) => match results.first() {
+                    Some(best) => provider.fetch_as(&best.id, options).await.map(Some),
+                    None => Ok(None),
+                },
+                Err(e) => Err(e),
+            };
+
+            match result {
+                Ok(Some(metadata)) => return Ok(Some(metadata)),
+                Ok(None) => {
+                    let provider = kind.as_str();
+                    debug!(provider, title = %title, "{}", t!("metadata.no_results"));
+                }
+                Err(e) => {
+                    warn!(
+                        provider = kind.as_str(),
+                        title = %title,
+                        error = %e,
+                        "{}", t!("metadata.provider_failed")
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        match last_error {
+            Some(e) => Err(e),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Service for content whose metadata could not be scraped.
+pub struct MetadataFailureService;
+
+impl MetadataFailureService {
+    /// List all failed scrapes, most recent first.
+    pub async fn list(pool: &Pool<Sqlite>) -> Result<Vec<MetadataFailure>> {
+        MetadataFailureRepository::list(pool).await
+    }
+
+    /// Scrape the metadata of a failed content again with its library's
+    /// providers, with the options of the user retrying.
+    ///
+    /// On success the metadata is stored and the failure removed; otherwise
+    /// the failure is updated with the new error.
+    pub async fn retry(
+        pool: &Pool<Sqlite>,
+        manager: &MetadataManager,
+        id: i64,
+        options: ScrapeOptions<'_>,
+    ) -> Result<MetadataRetryResult> {
+        let failure = Self::get_or_error(pool, id).await?;
+        let content = ContentService::get_content(pool, failure.content_id).await?;
+
+        let providers =
+            MetadataProviderRepository::list_by_library(pool, content.library_id).await?;
+        if providers.is_empty() {
+            return Err(AppError::BadRequest(
+                t!("metadata.no_providers").to_string(),
+            ));
+        }
+
+        let title = ScanService::search_title(&content.title);
+        let (metadata, error) =
+            ScanService::auto_scrape_metadata(manager, &providers, title, options).await;
+
+        match metadata {
+            Some(metadata) => {
+                // Also removes the failure
+                ContentService::update_content(pool, content.id, None, Some(metadata)).await?;
+                Ok(MetadataRetryResult {
+                    resolved: true,
+                    failure: None,
+                })
+            }
+            None => {
+                let error = error.unwrap_or_else(|| failure.error.clone());
+                MetadataFailureRepository::record(pool, content.id, &error).await?;
+                Ok(MetadataRetryResult {
+                    resolved: false,
+                    failure: MetadataFailureRepository::find_by_id(pool, id).await?,
+                })
+            }
+        }
+    }
+
+    /// Dismiss a failure, leaving the content without metadata.
+    pub async fn dismiss(pool: &Pool<Sqlite>, id: i64) -> Result<()> {
+        if !MetadataFailureRepository::delete(pool, id).await? {
+            return Err(AppError::NotFound(
+                t!("metadata.failure_not_found", id = id).to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn get_or_error(pool: &Pool<Sqlite>, id: i64) -> Result<MetadataFailure> {
+        MetadataFailureRepository::find_by_id(pool, id)
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound(t!("metadata.failure_not_found", id = id).to_string())
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{
+        TestContent, create_test_content, create_test_db, create_test_library,
+        create_test_scan_path,
+    };
+
+    /// Provider answering from a fixed list of titles.
+    struct FakeProvider {
+        kind: MetadataProviderKind,
+        titles: Vec<&'static str>,
+        fail: bool,
+    }
+
+    impl MetadataProvider for FakeProvider {
+        fn kind(&self) -> MetadataProviderKind {
+            self.kind
+        }
+
+        fn search<'a>(&'a self, query: &'a str) -> ProviderFuture<'a, Vec<MetadataSearchResult>> {
+            Box::pin(async move {
+                if self.fail {
+                    return Err(AppError::Internal("unavailable".to_string()));
+                }
+                Ok(self
+                    .titles
+                    .iter()
+                    .filter(|title| **title == query)
+                    .map(|title| MetadataSearchResult {
+                        provider: self.kind,
+                        id: title.to_string(),
+                        title: title.to_string(),
+                        alt_title: None,
+                        summary: None,
+                        image: None,
+                    })
+                    .collect())
+            })
+        }
+
+        fn fetch<'a>(&'a self, id: &'a str) -> ProviderFuture<'a, serde_json::Value> {
+            Box::pin(async move {
+                let mut metadata = json!({ "name": id });
+                tag_source(&mut metadata, self.kind, id);
+                Ok(metadata)
+            })
+        }
+    }
+
+    fn manager() -> MetadataManager {
+        let mut manager = MetadataManager::new();
+        manager.register(Arc::new(FakeProvider {
+            kind: MetadataProviderKind::Bangumi,
+            titles: vec!["進撃の巨人"],
+            fail: false,
+        }));
+        manager.register(Arc::new(FakeProvider {
+            kind: MetadataProviderKind::AniList,
+            titles: vec!["Attack on Titan", "進撃の巨人"],
+            fail: false,
+        }));
+        manager.register(Arc::new(FakeProvider {
+            kind: MetadataProviderKind::MangaUpdates,
+            titles: vec![],
+            fail: true,
+        }));
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_tries_providers_in_order() {
+        let manager = manager();
+        let order = [MetadataProviderKind::Bangumi, MetadataProviderKind::AniList];
+
+        let metadata = manager
+            .auto_scrape(&order, "進撃の巨人")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(metadata["source"]["provider"], "bangumi");
+
+        // Bangumi has no match, AniList does
+        let metadata = manager
+            .auto_scrape(&order, "Attack on Titan")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(metadata["source"]["provider"], "anilist");
+
+        assert!(
+            manager
+                .auto_scrape(&order, "Unknown")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            manager
+                .auto_scrape(&[], "進撃の巨人")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    async fn setup_failures() -> Pool<Sqlite> {
+        let pool = create_test_db().await;
+        create_test_library(&pool, 1, "Manga").await;
+        sqlx::query(
+            r#"
+            INSERT INTO library_metadata_providers (library_id, provider, position)
+            VALUES (1, 'bangumi', 0)
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        create_test_scan_path(&pool, 1, 1, "/a").await;
+        TestContent::new(1, 1, "Isayama / 進撃の巨人")
+            .folder("Isayama/進撃の巨人")
+            .insert(&pool)
+            .await;
+        create_test_content(&pool, 2, 1, "Unknown").await;
+
+        for content_id in [1, 2] {
+            MetadataFailureRepository::record(&pool, content_id, "unavailable")
+                .await
+                .unwrap();
+        }
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_retry_resolves_or_updates_failure() {
+        let pool = setup_failures().await;
+        let manager = manager();
+
+        let failures = MetadataFailureService::list(&pool).await.unwrap();
+        assert_eq!(failures.len(), 2);
+
+        // Searched by the folder's own name, not the grouped title
+        let options = ScrapeOptions::default();
+        let found = failures.iter().find(|f| f.content_id == 1).unwrap();
+        let result = MetadataFailureService::retry(&pool, &manager, found.id, options)
+            .await
+            .unwrap();
+        assert!(result.resolved);
+        let content = ContentService::get_content(&pool, 1).await.unwrap();
+        assert!(content.metadata.is_some());
+
+        let missing = failures.iter().find(|f| f.content_id == 2).unwrap();
+        let result = MetadataFailureService::retry(&pool, &manager, missing.id, options)
+            .await
+            .unwrap();
+        assert!(!result.resolved);
+        assert_eq!(result.failure.unwrap().attempts, 2);
+
+        MetadataFailureService::dismiss(&pool, missing.id)
+            .await
+            .unwrap();
+        assert!(
+            MetadataFailureService::list(&pool)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            MetadataFailureService::dismiss(&pool, missing.id)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_skips_failed_providers() {
+        let manager = manager();
+
+        let results = manager
+            .search(&MetadataProviderKind::ALL, "進撃の巨人")
+            .await
+            .unwrap();
+        let providers: Vec<_> = results.iter().map(|r| r.provider).collect();
+        assert_eq!(
+            providers,
+            vec![MetadataProviderKind::Bangumi, MetadataProviderKind::AniList]
+        );
+
+        assert!(
+            manager
+                .search(&[MetadataProviderKind::MangaUpdates], "進撃の巨人")
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failure_is_reported_only_without_match() {
+        let manager = manager();
+
+        let order = [
+            MetadataProviderKind::MangaUpdates,
+            MetadataProviderKind::AniList,
+        ];
+        let metadata = manager
+            .auto_scrape(&order, "Attack on Titan")
+            .await
+            .unwrap();
+        assert!(metadata.is_some());
+
+        let order = [
+            MetadataProviderKind::MangaUpdates,
+            MetadataProviderKind::Bangumi,
+        ];
+        assert!(
+            manager
+                .auto_scrape(&order, "Attack on Titan")
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,175 @@
+//! Manual metadata edits.
+//!
+//! Users correct the title, summary, status, language, tags, authors and
+//! age rating of a content by hand. The edits are stored apart from the scraped metadata
+//! and win over it; locked edits also survive rescrapes.
+
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, Result};
+use crate::models::{
+    MAX_AGE_RATING, MetadataEdits, UpdateMetadataEditsRequest, metadata_age_rating,
+};
+use crate::repository::content::ContentRepository;
+use crate::repository::metadata_edits::MetadataEditsRepository;
+use crate::services::content::ContentService;
+use crate::t;
+
+/// Longest title, in characters.
+const MAX_TITLE_CHARS: usize = 500;
+
+/// Service for manual metadata edit operations.
+pub struct MetadataEditsService;
+
+impl MetadataEditsService {
+    /// Get the edits of a content.
+    pub async fn get(pool: &Pool<Sqlite>, content_id: i64) -> Result<MetadataEdits> {
+        ContentService::get_content(pool, content_id).await?;
+        Ok(MetadataEditsRepository::find(pool, content_id)
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// Apply a partial update to the edits of a content.
+    pub async fn update(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        request: UpdateMetadataEditsRequest,
+    ) -> Result<MetadataEdits> {
+        let mut edits = Self::get(pool, content_id).await?;
+
+        if matches!(request.age_rating, Some(Some(r)) if !(0..=MAX_AGE_RATING).contains(&r)) {
+            return Err(AppError::BadRequest(
+                t!("metadata.invalid_age_rating", max = MAX_AGE_RATING).to_string(),
+            ));
+        }
+
+        // The title lives on the content, which listings sort and search by
+        if let Some(title) = &request.title {
+            let title = title.trim();
+            if title.is_empty() || title.chars().count() > MAX_TITLE_CHARS {
+                return Err(AppError::BadRequest(
+                    t!("metadata.invalid_title").to_string(),
+                ));
+            }
+            ContentService::update_content(pool, content_id, Some(title.to_string()), None).await?;
+        }
+
+        // So is the age rating, which listings filter by; clearing it goes
+        // back to the rating in the metadata
+        if let Some(mut age_rating) = request.age_rating {
+            if age_rating.is_none() {
+                age_rating = ContentService::get_content(pool, content_id)
+                    .await?
+                    .metadata
+                    .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+                    .and_then(|metadata| metadata_age_rating(&metadata));
+            }
+            ContentRepository::set_age_rating(pool, content_id, age_rating).await?;
+        }
+
+        edits.apply(request);
+        Ok(MetadataEditsRepository::save(pool, content_id, &edits)
+            .await?
+            .unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ContentFilter, ContentSort, NewContent};
+    use crate::test_fixtures::{create_test_db, create_test_library, create_test_scan_path};
+    use serde_json::json;
+
+    async fn setup() -> Pool<Sqlite> {
+        let pool = create_test_db().await;
+        create_test_library(&pool, 1, "Comics").await;
+        create_test_scan_path(&pool, 1, 1, "/comics").await;
+        pool
+    }
+
+    async fn create(pool: &Pool<Sqlite>, title: &str, metadata: serde_json::Value) -> i64 {
+        let new_content = NewContent {
+            library_id: 1,
+            scan_path_id: 1,
+            title: title.to_string(),
+            folder_title: title.to_string(),
+            folder_path: format!("/comics/{title}"),
+            chapter_count: 1,
+            thumbnail: None,
+            thumbnail_spec: None,
+            metadata: Some(metadata),
+        };
+        ContentRepository::create(pool, new_content)
+            .await
+            .unwrap()
+            .id
+    }
+
+    async fn titles(pool: &Pool<Sqlite>, filter: ContentFilter<'_>) -> Vec<String> {
+        ContentRepository::list_matching(pool, &filter, ContentSort::Rating, 10, 0)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|content| content.title)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_listings_filter_by_metadata_fields() {
+        let pool = setup().await;
+        let author = |name: &str| json!([{ "key": "作者", "value": name }]);
+        let a = create(
+            &pool,
+            "A",
+            json!({ "infobox": author("Isayama"), "rating": { "score": 8.4 } }),
+        )
+        .await;
+        create(
+            &pool,
+            "B",
+            json!({ "infobox": author("Oda"), "rating": { "score": 9.1 } }),
+        )
+        .await;
+        create(&pool, "C", json!({ "summary": "Unrated" })).await;
+
+        let all = ContentFilter::default();
+        assert_eq!(titles(&pool, all).await, ["B", "A", "C"]);
+        let by_isayama = ContentFilter {
+            author: Some("isayama"),
+            ..Default::default()
+        };
+        assert_eq!(titles(&pool, by_isayama).await, ["A"]);
+        let rated = ContentFilter {
+            min_rating: Some(9.0),
+            ..Default::default()
+        };
+        assert_eq!(titles(&pool, rated).await, ["B"]);
+        assert_eq!(
+            ContentRepository::count_matching(&pool, &rated)
+                .await
+                .unwrap(),
+            1
+        );
+
+        // Authors edited by hand replace the scraped ones, and survive a
+        // rescrape
+        let request = serde_json::from_str(r#"{"authors": [{"name": "Someone"}]}"#).unwrap();
+        MetadataEditsService::update(&pool, a, request)
+            .await
+            .unwrap();
+        assert!(titles(&pool, by_isayama).await.is_empty());
+
+        let metadata = json!({ "infobox": author("Isayama"), "rating": { "score": 9.5 } });
+        ContentRepository::update_metadata(&pool, a, Some(metadata), None)
+            .await
+            .unwrap();
+        let by_someone = ContentFilter {
+            author: Some("Someone"),
+            ..Default::default()
+        };
+        assert_eq!(titles(&pool, by_someone).await, ["A"]);
+        assert_eq!(titles(&pool, rated).await, ["A", "B"]);
+    }
+}
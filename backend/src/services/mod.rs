@@ -3,11 +3,44 @@
 //! This module contains the service layer that implements the core business logic
 //! of the application, including library management, content scanning, and reading progress.
 
+pub mod age_rating;
+pub mod alias;
+pub mod anilist;
+pub mod audit;
 pub mod auth;
+pub mod backup;
 pub mod bangumi;
+pub mod bookmark;
+pub mod consistency;
 pub mod content;
+pub mod content_status;
+pub mod download;
+pub mod duplicate;
+pub mod executors;
+pub mod export;
+pub mod filesystem;
+pub mod jobs;
+pub mod kobo;
+pub mod koreader;
 pub mod library;
+pub mod mangaupdates;
+pub mod metadata;
+pub mod metadata_edits;
+pub mod oidc;
+pub mod preferences;
 pub mod progress;
+pub mod quota;
+pub mod reader_settings;
+pub mod release;
+pub mod resource_guard;
+pub mod review;
 pub mod scan_queue;
 pub mod scheduler;
+pub mod series_group;
+pub mod server_import;
+pub mod settings;
+pub mod share;
+pub mod shutdown;
+pub mod tachiyomi;
+pub mod tracker;
 pub mod watch;
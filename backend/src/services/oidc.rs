@@ -0,0 +1,476 @@
+//! OpenID Connect login.
+//!
+//! Delegates authentication to an external OIDC provider (Authelia, Keycloak,
+//! ...) using the authorization code flow. The provider's ID token is
+//! verified against its published JWKS, the external subject is mapped to a
+//! local user, and a regular Ryuri JWT is issued as for password logins.
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header, jwk::JwkSet};
+use reqwest::{Client, Url};
+use serde::Deserialize;
+use sqlx::{Pool, Sqlite};
+use tokio::sync::{OnceCell, RwLock};
+use tracing::{info, warn};
+
+use crate::error::{AppError, ErrorCode, Result};
+use crate::models::{NewUser, OidcStateClaims, User};
+use crate::repository::user::{UserIdentityRepository, UserRepository};
+use crate::services::auth::PasswordHashService;
+use crate::t;
+use crate::utils::oauth_state::StateSigner;
+
+/// Purpose marker for OIDC state tokens.
+const STATE_PURPOSE: &str = "oidc_state";
+
+/// Configuration for OIDC login.
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    /// Issuer URL of the provider, e.g. `https://auth.example.com`.
+    pub issuer_url: String,
+    /// Client ID registered at the provider.
+    pub client_id: String,
+    /// Client secret registered at the provider.
+    pub client_secret: String,
+    /// Callback URL registered at the provider, ending in `/api/auth/oidc/callback`.
+    pub redirect_url: String,
+    /// Space-separated scopes to request.
+    pub scopes: String,
+    /// Create a local user on first login for unknown subjects.
+    pub auto_provision: bool,
+    /// Frontend URL to send the browser to after the callback.
+    pub frontend_redirect: String,
+}
+
+impl OidcConfig {
+    /// Create a configuration with default scopes and redirects.
+    pub fn new(
+        issuer_url: String,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> Self {
+        Self {
+            issuer_url,
+            client_id,
+            client_secret,
+            redirect_url,
+            scopes: "openid profile email".to_string(),
+            auto_provision: false,
+            frontend_redirect: "/login".to_string(),
+        }
+    }
+}
+
+/// Subset of the provider's discovery document.
+#[derive(Debug, Clone, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+/// Token endpoint response; only the ID token is used.
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+/// Claims read from a verified ID token.
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdTokenClaims {
+    /// Subject identifier, stable per provider.
+    pub sub: String,
+    /// Nonce echoed back from the authorization request.
+    pub nonce: Option<String>,
+    /// Preferred username, used when provisioning.
+    pub preferred_username: Option<String>,
+    /// Email address, used when provisioning.
+    pub email: Option<String>,
+}
+
+/// Where to send the browser to start a login, and the state to remember.
+#[derive(Debug, Clone)]
+pub struct OidcAuthorization {
+    /// Provider authorization URL.
+    pub url: String,
+    /// Signed state token, to be stored in a cookie until the callback.
+    pub state: String,
+}
+
+/// Result of a completed OIDC callback.
+#[derive(Debug)]
+pub enum OidcOutcome {
+    /// The identity maps to a local user who is now logged in.
+    LoggedIn(User),
+    /// The identity was linked to the user who started the flow.
+    Linked(User),
+}
+
+/// Service implementing the OIDC authorization code flow.
+pub struct OidcService {
+    pool: Pool<Sqlite>,
+    config: OidcConfig,
+    client: Client,
+    state_signer: StateSigner,
+    metadata: OnceCell<ProviderMetadata>,
+    jwks: RwLock<Option<JwkSet>>,
+}
+
+impl OidcService {
+    /// Create a new OIDC service.
+    ///
+    /// `state_secret` signs the state parameter.
+    pub fn new(pool: Pool<Sqlite>, config: OidcConfig, state_secret: &str) -> Self {
+        let client = Client::builder()
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            pool,
+            config,
+            client,
+            state_signer: StateSigner::new(STATE_PURPOSE, state_secret),
+            metadata: OnceCell::new(),
+            jwks: RwLock::new(None),
+        }
+    }
+
+    /// Frontend URL to return to after the callback.
+    pub fn frontend_redirect(&self) -> &str {
+        &self.config.frontend_redirect
+    }
+
+    /// Whether cookies should be marked `Secure`.
+    pub fn secure_cookies(&self) -> bool {
+        self.config.redirect_url.starts_with("https://")
+    }
+
+    /// Build the provider authorization URL for a new login.
+    ///
+    /// When `link_user_id` is set, the callback links the identity to that
+    /// user instead of logging in.
+    pub async fn authorize(&self, link_user_id: Option<i64>) -> Result<OidcAuthorization> {
+        let metadata = self.metadata().await?;
+
+        let claims = OidcStateClaims {
+            nonce: random_token(),
+            link_user_id,
+        };
+        let state = self.state_signer.sign(&claims)?;
+
+        let url = Url::parse_with_params(
+            &metadata.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", self.config.client_id.as_str()),
+                ("redirect_uri", self.config.redirect_url.as_str()),
+                ("scope", self.config.scopes.as_str()),
+                ("state", state.as_str()),
+                ("nonce", claims.nonce.as_str()),
+            ],
+        )
+        .map_err(|e| AppError::Internal(t!("oidc.invalid_provider_url", error = e).to_string()))?;
+
+        Ok(OidcAuthorization {
+            url: url.to_string(),
+            state,
+        })
+    }
+
+    /// Complete the flow after the provider redirects back.
+    ///
+    /// `cookie_state` is the state remembered by the browser; it must match
+    /// the `state` query parameter.
+    pub async fn complete(
+        &self,
+        code: &str,
+        state: &str,
+        cookie_state: Option<&str>,
+    ) -> Result<OidcOutcome> {
+        let state_claims: OidcStateClaims = self
+            .state_signer
+            .verify(state, cookie_state)
+            .ok_or_else(|| AppError::Unauthorized(t!("oidc.invalid_state").to_string()))?;
+
+        let metadata = self.metadata().await?;
+        let id_token = self.exchange_code(metadata, code).await?;
+        let claims = self.verify_id_token(metadata, &id_token).await?;
+
+        if claims.nonce.as_deref() != Some(state_claims.nonce.as_str()) {
+            return Err(AppError::Unauthorized(t!("oidc.invalid_nonce").to_string()));
+        }
+
+        if let Some(user_id) = state_claims.link_user_id {
+            let user = UserRepository::find_by_id(&self.pool, user_id)
+                .await?
+                .ok_or_else(|| {
+                    AppError::NotFound(t!("auth.user_not_found_generic").to_string())
+                        .with_code(ErrorCode::UserNotFound)
+                })?;
+            UserIdentityRepository::create(&self.pool, user.id, &metadata.issuer, &claims.sub)
+                .await?;
+            info!(user_id = user.id, "{}", t!("oidc.identity_linked_log"));
+            return Ok(OidcOutcome::Linked(user));
+        }
+
+        let user = self.resolve_user(&metadata.issuer, &claims).await?;
+        Ok(OidcOutcome::LoggedIn(user))
+    }
+
+    /// Fetch and cache the provider's discovery document.
+    async fn metadata(&self) -> Result<&ProviderMetadata> {
+        self.metadata
+            .get_or_try_init(|| async {
+                let issuer = self.config.issuer_url.trim_end_matches('/');
+                let url = format!("{}/.well-known/openid-configuration", issuer);
+
+                let metadata: ProviderMetadata = self
+                    .client
+                    .get(&url)
+                    .send()
+                    .await
+                    .and_then(|r| r.error_for_status())
+                    .map_err(|e| {
+                        AppError::Internal(t!("oidc.discovery_failed", error = e).to_string())
+                    })?
+                    .json()
+                    .await
+                    .map_err(|e| {
+                        AppError::Internal(t!("oidc.discovery_failed", error = e).to_string())
+                    })?;
+
+                if metadata.issuer.trim_end_matches('/') != issuer {
+                    return Err(AppError::Internal(
+                        t!("oidc.issuer_mismatch", issuer = metadata.issuer).to_string(),
+                    ));
+                }
+
+                Ok(metadata)
+            })
+            .await
+    }
+
+    /// Exchange an authorization code for an ID token.
+    async fn exchange_code(&self, metadata: &ProviderMetadata, code: &str) -> Result<String> {
+        // client_secret_basic: credentials are form-encoded before base64
+        let response = self
+            .client
+            .post(&metadata.token_endpoint)
+            .basic_auth(
+                urlencoding::encode(&self.config.client_id),
+                Some(urlencoding::encode(&self.config.client_secret)),
+            )
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", self.config.redirect_url.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|e| {
+                AppError::Internal(t!("oidc.token_exchange_failed", error = e).to_string())
+            })?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            warn!(status = %status, "{}", t!("oidc.token_exchange_rejected_log"));
+            return Err(AppError::Unauthorized(
+                t!("oidc.token_exchange_failed", error = status).to_string(),
+            ));
+        }
+
+        let token: TokenResponse = response.json().await.map_err(|e| {
+            AppError::Internal(t!("oidc.token_exchange_failed", error = e).to_string())
+        })?;
+
+        Ok(token.id_token)
+    }
+
+    /// Verify an ID token's signature, issuer, audience and expiry.
+    async fn verify_id_token(
+        &self,
+        metadata: &ProviderMetadata,
+        id_token: &str,
+    ) -> Result<IdTokenClaims> {
+        let header = decode_header(id_token).map_err(|e| {
+            AppError::Unauthorized(t!("oidc.invalid_id_token", error = e).to_string())
+        })?;
+
+        // Symmetric algorithms would let anyone with the client secret forge tokens
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(AppError::Unauthorized(
+                t!("oidc.invalid_id_token", error = "unsupported algorithm").to_string(),
+            ));
+        }
+
+        let key = self.find_key(metadata, header.kid.as_deref()).await?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_issuer(&[&metadata.issuer]);
+
+        decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map(|data| data.claims)
+            .map_err(|e| AppError::Unauthorized(t!("oidc.invalid_id_token", error = e).to_string()))
+    }
+
+    /// Find the signing key for a key ID, refetching the JWKS once if the
+    /// key is unknown (providers rotate keys).
+    async fn find_key(
+        &self,
+        metadata: &ProviderMetadata,
+        kid: Option<&str>,
+    ) -> Result<DecodingKey> {
+        let mut fetched = false;
+        loop {
+            if self.jwks.read().await.is_none() {
+                let jwks = self.fetch_jwks(metadata).await?;
+                *self.jwks.write().await = Some(jwks);
+                fetched = true;
+            }
+
+            {
+                let jwks = self.jwks.read().await;
+                let jwk = jwks.as_ref().and_then(|jwks| match kid {
+                    Some(kid) => jwks.find(kid),
+                    None if jwks.keys.len() == 1 => jwks.keys.first(),
+                    None => None,
+                });
+
+                if let Some(jwk) = jwk {
+                    return DecodingKey::from_jwk(jwk).map_err(|e| {
+                        AppError::Unauthorized(t!("oidc.invalid_id_token", error = e).to_string())
+                    });
+                }
+            }
+
+            if fetched {
+                return Err(AppError::Unauthorized(
+                    t!("oidc.invalid_id_token", error = "unknown signing key").to_string(),
+                ));
+            }
+
+            // Unknown key with a cached set; drop it and fetch again
+            *self.jwks.write().await = None;
+        }
+    }
+
+    /// Download the provider's JSON Web Key Set.
+    async fn fetch_jwks(&self, metadata: &ProviderMetadata) -> Result<JwkSet> {
+        self.client
+            .get(&metadata.jwks_uri)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| AppError::Internal(t!("oidc.discovery_failed", error = e).to_string()))?
+            .json()
+            .await
+            .map_err(|e| AppError::Internal(t!("oidc.discovery_failed", error = e).to_string()))
+    }
+
+    /// Map an external identity to a local user, provisioning one if enabled.
+    ///
+    /// Existing local accounts are never matched by username, since anyone
+    /// able to choose their provider username could take them over; they
+    /// have to be linked from account settings instead.
+    async fn resolve_user(&self, issuer: &str, claims: &IdTokenClaims) -> Result<User> {
+        if let Some(identity) =
+            UserIdentityRepository::find(&self.pool, issuer, &claims.sub).await?
+        {
+            return UserRepository::find_by_id(&self.pool, identity.user_id)
+                .await?
+                .ok_or_else(|| AppError::Unauthorized(t!("oidc.account_not_linked").to_string()));
+        }
+
+        if !self.config.auto_provision {
+            return Err(AppError::Unauthorized(
+                t!("oidc.account_not_linked").to_string(),
+            ));
+        }
+
+        let base = provision_username(claims);
+        let mut username = base.clone();
+        let mut suffix = 1;
+        while UserRepository::username_exists(&self.pool, &username).await? {
+            suffix += 1;
+            username = format!("{}-{}", base, suffix);
+        }
+
+        // Provisioned users log in through the provider; the random password
+        // is never shown and can be changed later.
+        let password_hash = PasswordHashService::hash_password(&random_token())?;
+        let user = UserRepository::create(
+            &self.pool,
+            NewUser {
+                username,
+                password_hash,
+                bangumi_api_key: None,
+            },
+        )
+        .await?;
+
+        UserIdentityRepository::create(&self.pool, user.id, issuer, &claims.sub).await?;
+        info!(user_id = user.id, username = %user.username, "{}", t!("oidc.user_provisioned_log"));
+
+        Ok(user)
+    }
+}
+
+/// Pick a username for a provisioned user from the ID token claims.
+fn provision_username(claims: &IdTokenClaims) -> String {
+    let candidate = claims
+        .preferred_username
+        .as_deref()
+        .or_else(|| claims.email.as_deref().and_then(|e| e.split('@').next()))
+        .map(str::trim)
+        .filter(|s| !s.is_empty());
+
+    match candidate {
+        Some(name) => name.to_string(),
+        None => format!("oidc-{}", claims.sub.chars().take(16).collect::<String>()),
+    }
+}
+
+/// Generate a random URL-safe token.
+fn random_token() -> String {
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims(preferred_username: Option<&str>, email: Option<&str>) -> IdTokenClaims {
+        IdTokenClaims {
+            sub: "0123456789abcdef0123".to_string(),
+            nonce: None,
+            preferred_username: preferred_username.map(String::from),
+            email: email.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_provision_username_prefers_preferred_username() {
+        let claims = claims(Some("alice"), Some("bob@example.com"));
+        assert_eq!(provision_username(&claims), "alice");
+    }
+
+    #[test]
+    fn test_provision_username_falls_back_to_email_then_subject() {
+        assert_eq!(
+            provision_username(&claims(None, Some("bob@example.com"))),
+            "bob"
+        );
+        assert_eq!(
+            provision_username(&claims(Some("  "), None)),
+            "oidc-0123456789abcdef"
+        );
+    }
+}
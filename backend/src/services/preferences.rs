@@ -0,0 +1,166 @@
+//! Per-user preferences.
+//!
+//! Each user has their own locale, theme, reading defaults and page size,
+//! so users sharing a server do not override each other's choices.
+
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, ErrorCode, Result};
+use crate::models::{MAX_ITEMS_PER_PAGE, UpdatePreferencesRequest, User, UserPreferences};
+use crate::repository::preferences::PreferencesRepository;
+use crate::repository::user::UserRepository;
+use crate::t;
+use crate::utils::locale::supported_locale;
+
+/// Service for user preference operations.
+pub struct PreferencesService;
+
+impl PreferencesService {
+    /// Get the preferences of a user.
+    pub async fn get(pool: &Pool<Sqlite>, user_id: i64) -> Result<UserPreferences> {
+        let user = Self::user(pool, user_id).await?;
+        let interface = PreferencesRepository::find(pool, user_id).await?;
+        Ok(UserPreferences {
+            locale: user.locale,
+            interface,
+        })
+    }
+
+    /// Update the preferences of a user.
+    pub async fn update(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        request: UpdatePreferencesRequest,
+    ) -> Result<UserPreferences> {
+        let user = Self::user(pool, user_id).await?;
+
+        if let Some(items) = request.items_per_page
+            && !(1..=MAX_ITEMS_PER_PAGE).contains(&items)
+        {
+            return Err(AppError::BadRequest(
+                t!(
+                    "preferences.items_per_page",
+                    min = 1,
+                    max = MAX_ITEMS_PER_PAGE
+                )
+                .to_string(),
+            ));
+        }
+
+        let locale = match request.locale.as_ref().map(|l| l.as_deref().map(str::trim)) {
+            Some(Some(tag)) if !tag.is_empty() => {
+                let locale = supported_locale(tag).ok_or_else(|| {
+                    AppError::BadRequest(t!("auth.unsupported_locale", tag = tag).to_string())
+                })?;
+                Some(Some(locale.to_string()))
+            }
+            Some(_) => Some(None),
+            None => None,
+        };
+
+        let interface = request.apply(PreferencesRepository::find(pool, user_id).await?);
+        PreferencesRepository::save(pool, user_id, &interface).await?;
+
+        let locale = match locale {
+            Some(locale) => {
+                UserRepository::update(pool, user_id, None, None, None, Some(locale))
+                    .await?
+                    .locale
+            }
+            None => user.locale,
+        };
+
+        Ok(UserPreferences { locale, interface })
+    }
+
+    async fn user(pool: &Pool<Sqlite>, user_id: i64) -> Result<User> {
+        UserRepository::find_by_id(pool, user_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound(t!("auth.user_not_found", id = user_id).to_string())
+                    .with_code(ErrorCode::UserNotFound)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{InterfacePreferences, PageFit, Theme};
+    use crate::test_fixtures::{create_test_admin, create_test_db};
+
+    /// ID of the administrator the tests act as.
+    const ADMIN_ID: i64 = 1;
+
+    async fn setup() -> Pool<Sqlite> {
+        let pool = create_test_db().await;
+        create_test_admin(&pool, ADMIN_ID, "admin").await;
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_defaults_without_stored_preferences() {
+        let pool = setup().await;
+        let preferences = PreferencesService::get(&pool, ADMIN_ID).await.unwrap();
+        assert_eq!(preferences.interface, InterfacePreferences::default());
+    }
+
+    #[tokio::test]
+    async fn test_update_keeps_other_fields() {
+        let pool = setup().await;
+        let request = UpdatePreferencesRequest {
+            locale: Some(Some("zh".to_string())),
+            theme: Some(Theme::Dark),
+            ..Default::default()
+        };
+        PreferencesService::update(&pool, ADMIN_ID, request)
+            .await
+            .unwrap();
+
+        let request = UpdatePreferencesRequest {
+            page_fit: Some(PageFit::Width),
+            ..Default::default()
+        };
+        let preferences = PreferencesService::update(&pool, ADMIN_ID, request)
+            .await
+            .unwrap();
+        assert_eq!(preferences.locale.as_deref(), Some("zh-CN"));
+        assert_eq!(preferences.interface.theme, Theme::Dark);
+        assert_eq!(preferences.interface.page_fit, PageFit::Width);
+        assert_eq!(
+            PreferencesService::get(&pool, ADMIN_ID).await.unwrap(),
+            preferences
+        );
+
+        let request = UpdatePreferencesRequest {
+            locale: Some(None),
+            ..Default::default()
+        };
+        let preferences = PreferencesService::update(&pool, ADMIN_ID, request)
+            .await
+            .unwrap();
+        assert_eq!(preferences.locale, None);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_update_is_rejected() {
+        let pool = setup().await;
+        for request in [
+            UpdatePreferencesRequest {
+                items_per_page: Some(0),
+                ..Default::default()
+            },
+            UpdatePreferencesRequest {
+                locale: Some(Some("xx".to_string())),
+                ..Default::default()
+            },
+        ] {
+            assert!(
+                PreferencesService::update(&pool, ADMIN_ID, request)
+                    .await
+                    .is_err()
+            );
+        }
+        assert!(PreferencesService::get(&pool, 999).await.is_err());
+    }
+}
@@ -4,14 +4,17 @@
 //! on chapters and calculating overall content progress.
 
 use sqlx::{Pool, Sqlite};
-use rust_i18n::t;
 
-use crate::error::{AppError, Result};
+use crate::error::{AppError, ErrorCode, Result};
+use crate::extractors::{AudioExtractor, TxtExtractor};
 use crate::models::{
-    NewReadingProgress, ProgressResponse, ReadingProgress,
+    Chapter, NewReadingProgress, NovelPosition, ProgressResponse, ReadingProgress,
+    SplitProgressResponse,
 };
 use crate::repository::content::ChapterRepository;
 use crate::repository::progress::ProgressRepository;
+use crate::t;
+use crate::utils::cancel::spawn_cancellable;
 
 /// Service for reading progress operations.
 ///
@@ -39,6 +42,7 @@ impl ProgressService {
             .await?
             .ok_or_else(|| {
                 AppError::NotFound(t!("content.chapter_not_found", id = chapter_id).to_string())
+                    .with_code(ErrorCode::ChapterNotFound)
             })?;
 
         ProgressRepository::find_by_user_and_chapter(&self.pool, user_id, chapter_id).await
@@ -78,6 +82,7 @@ impl ProgressService {
             .await?
             .ok_or_else(|| {
                 AppError::NotFound(t!("content.chapter_not_found", id = chapter_id).to_string())
+                    .with_code(ErrorCode::ChapterNotFound)
             })?;
 
         // Calculate percentage based on position
@@ -92,6 +97,7 @@ impl ProgressService {
             chapter_id,
             position,
             percentage,
+            novel: None,
         };
 
         ProgressRepository::upsert(&self.pool, new_progress).await
@@ -101,11 +107,20 @@ impl ProgressService {
     ///
     /// For comics, this would be page_number / total_pages * 100
     /// For novels, this would be character_position / total_characters * 100
+    /// For audiobooks, this is seconds_played / duration * 100
     async fn calculate_percentage(
         &self,
-        _chapter: &crate::models::Chapter,
-        _position: i32,
+        chapter: &crate::models::Chapter,
+        position: i32,
     ) -> Result<f32> {
+        // Audiobook positions are seconds, and the duration is in the file's tags
+        if chapter.is_audio() {
+            let path = std::path::PathBuf::from(&chapter.file_path);
+            let info = spawn_cancellable(move |_| AudioExtractor::probe(&path)).await?;
+            let duration = i32::try_from(info.duration_ms / 1000).unwrap_or(i32::MAX);
+            return Ok(Self::calculate_percentage_from_total(position, duration));
+        }
+
         // For now, we return 0.0 and let the caller provide the percentage
         // In a full implementation, we would:
         // 1. For comics: count images in the archive and calculate page/total
@@ -143,6 +158,7 @@ impl ProgressService {
             .await?
             .ok_or_else(|| {
                 AppError::NotFound(t!("content.chapter_not_found", id = chapter_id).to_string())
+                    .with_code(ErrorCode::ChapterNotFound)
             })?;
 
         let new_progress = NewReadingProgress {
@@ -150,6 +166,67 @@ impl ProgressService {
             chapter_id,
             position,
             percentage,
+            novel: None,
+        };
+
+        ProgressRepository::upsert(&self.pool, new_progress).await
+    }
+
+    /// Update reading progress on a novel chapter with a place in its text.
+    ///
+    /// The section is a spine index for EPUB chapters and a virtual chapter
+    /// index for `.txt` chapters, so it must be below the chapter's page
+    /// count once that is known.
+    pub async fn update_novel_progress(
+        &self,
+        user_id: i64,
+        chapter_id: i64,
+        position: i32,
+        percentage: Option<f32>,
+        novel: NovelPosition,
+    ) -> Result<ReadingProgress> {
+        if position < 0 {
+            return Err(AppError::BadRequest(
+                "Position cannot be negative".to_string(),
+            ));
+        }
+        if percentage.is_some_and(|p| !(0.0..=100.0).contains(&p)) {
+            return Err(AppError::BadRequest(
+                "Percentage must be between 0 and 100".to_string(),
+            ));
+        }
+        novel
+            .validate()
+            .map_err(|reason| AppError::BadRequest(reason.to_string()))?;
+
+        let chapter = ChapterRepository::find_by_id(&self.pool, chapter_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound(t!("content.chapter_not_found", id = chapter_id).to_string())
+                    .with_code(ErrorCode::ChapterNotFound)
+            })?;
+
+        if !chapter.is_text_based() {
+            return Err(AppError::BadRequest(
+                t!("progress.novel_not_text", id = chapter_id).to_string(),
+            ));
+        }
+        if chapter.page_count > 0 && novel.section >= chapter.page_count {
+            return Err(AppError::BadRequest(
+                t!(
+                    "progress.novel_section_out_of_range",
+                    section = novel.section
+                )
+                .to_string(),
+            ));
+        }
+
+        let new_progress = NewReadingProgress {
+            user_id,
+            chapter_id,
+            position,
+            percentage: percentage.unwrap_or(0.0),
+            novel: Some(novel),
         };
 
         ProgressRepository::upsert(&self.pool, new_progress).await
@@ -176,9 +253,12 @@ impl ProgressService {
             .await?
             .ok_or_else(|| {
                 AppError::NotFound(t!("content.chapter_not_found", id = chapter_id).to_string())
+                    .with_code(ErrorCode::ChapterNotFound)
             })?;
 
-        let progresses = self.get_content_progress(user_id, chapter.content_id).await?;
+        let progresses = self
+            .get_content_progress(user_id, chapter.content_id)
+            .await?;
         Ok(progresses.into_iter().map(ProgressResponse::from).collect())
     }
 
@@ -190,12 +270,79 @@ impl ProgressService {
         user_id: i64,
         limit: i64,
     ) -> Result<Vec<crate::models::ContentResponse>> {
-        let contents = ProgressRepository::find_recent_contents_by_user(&self.pool, user_id, limit).await?;
+        let contents =
+            ProgressRepository::find_recent_contents_by_user(&self.pool, user_id, limit).await?;
         Ok(contents
             .into_iter()
             .map(crate::models::ContentResponse::from)
             .collect())
     }
+
+    /// Get the reading positions inside the virtual chapters of a `.txt`
+    /// chapter.
+    pub async fn get_split_progress(
+        &self,
+        user_id: i64,
+        chapter_id: i64,
+    ) -> Result<Vec<SplitProgressResponse>> {
+        self.find_txt_chapter(chapter_id).await?;
+        let progress = ProgressRepository::list_splits(&self.pool, user_id, chapter_id).await?;
+        Ok(progress
+            .into_iter()
+            .map(SplitProgressResponse::from)
+            .collect())
+    }
+
+    /// Update the reading position (character offset) inside a virtual
+    /// chapter of a `.txt` chapter.
+    pub async fn update_split_progress(
+        &self,
+        user_id: i64,
+        chapter_id: i64,
+        split_index: i32,
+        position: i32,
+    ) -> Result<SplitProgressResponse> {
+        if position < 0 {
+            return Err(AppError::BadRequest(
+                "Position cannot be negative".to_string(),
+            ));
+        }
+
+        let chapter = self.find_txt_chapter(chapter_id).await?;
+        // The split count is stored as the page count once scanned
+        if split_index < 0 || (chapter.page_count > 0 && split_index >= chapter.page_count) {
+            return Err(AppError::NotFound(
+                t!("txt.split_not_found", index = split_index).to_string(),
+            ));
+        }
+
+        let progress = ProgressRepository::upsert_split(
+            &self.pool,
+            user_id,
+            chapter_id,
+            split_index,
+            position,
+        )
+        .await?;
+        Ok(SplitProgressResponse::from(progress))
+    }
+
+    /// Find a chapter that must be a `.txt` file.
+    async fn find_txt_chapter(&self, chapter_id: i64) -> Result<crate::models::Chapter> {
+        let chapter = ChapterRepository::find_by_id(&self.pool, chapter_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound(t!("content.chapter_not_found", id = chapter_id).to_string())
+                    .with_code(ErrorCode::ChapterNotFound)
+            })?;
+
+        if !TxtExtractor::is_supported(std::path::Path::new(&chapter.file_path)) {
+            return Err(AppError::BadRequest(
+                t!("txt.not_txt", id = chapter_id).to_string(),
+            ));
+        }
+        Ok(chapter)
+    }
 }
 
 /// Utility functions for progress percentage calculation.
@@ -212,6 +359,26 @@ impl ProgressService {
         percentage.clamp(0.0, 100.0)
     }
 
+    /// Position and percentage of progress imported from another reader:
+    /// the last page of a finished chapter, or the page (0-based) reached in
+    /// a started comic chapter. Pages of other formats are not positions
+    /// here, so only finished ones are imported.
+    pub fn imported_position(chapter: &Chapter, completed: bool, page: i64) -> Option<(i32, f32)> {
+        let last_page = (chapter.page_count - 1).max(0);
+        if completed {
+            return Some((last_page, 100.0));
+        }
+        if chapter.is_text_based() || chapter.is_audio() || chapter.page_count == 0 {
+            return None;
+        }
+
+        let position = i32::try_from(page.max(0))
+            .unwrap_or(i32::MAX)
+            .min(last_page);
+        let percentage = Self::calculate_percentage_from_total(position + 1, chapter.page_count);
+        Some((position, percentage))
+    }
+
     /// Calculate overall content percentage from chapter progress.
     ///
     /// Takes into account completed chapters and partial progress on current chapter.
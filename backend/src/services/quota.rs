@@ -0,0 +1,397 @@
+//! Per-user daily quotas.
+//!
+//! The quota middleware asks [`QuotaService::begin`] before each
+//! authenticated request and reports the bytes of the request and response
+//! bodies once the response has been sent. Usage is counted in memory and
+//! written to the database at most every [`SAVE_INTERVAL`] per user; the
+//! counters of the current day are loaded back on the first request of
+//! each user, so a restart only loses what was not saved yet.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use chrono::{Days, NaiveDate, Utc};
+use serde::Deserialize;
+use sqlx::{Pool, Sqlite};
+use tracing::warn;
+
+use crate::error::{AppError, ErrorCode, Result};
+use crate::models::{QuotaLimits, QuotaUsage, UpdateUserQuotaRequest, UserQuota};
+use crate::repository::quota::{QuotaRepository, UserQuotaRow};
+use crate::t;
+
+/// Longest time counted usage stays in memory only.
+pub const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Default daily limits of users without their own.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QuotaConfig {
+    /// Megabytes each user may transfer per day (0 = unlimited).
+    pub daily_mb: u64,
+    /// Requests each user may make per day (0 = unlimited).
+    pub daily_requests: u64,
+}
+
+/// Limits and counters of a user for the current day.
+#[derive(Debug)]
+struct UserUsage {
+    limits: QuotaLimits,
+    usage: QuotaUsage,
+    /// Counted since the counters were last saved.
+    unsaved_bytes: u64,
+    unsaved_requests: u64,
+    saved_at: Instant,
+}
+
+impl UserUsage {
+    /// Usage not saved yet, as `(day, bytes, requests)`, resetting it.
+    fn take_unsaved(&mut self) -> (NaiveDate, u64, u64) {
+        let unsaved = (self.usage.day, self.unsaved_bytes, self.unsaved_requests);
+        self.unsaved_bytes = 0;
+        self.unsaved_requests = 0;
+        self.saved_at = Instant::now();
+        unsaved
+    }
+}
+
+/// Service counting and limiting the daily usage of each user.
+pub struct QuotaService {
+    pool: Pool<Sqlite>,
+    config: QuotaConfig,
+    users: Mutex<HashMap<i64, UserUsage>>,
+}
+
+impl QuotaService {
+    /// Create a new quota service with the given default limits.
+    pub fn new(pool: Pool<Sqlite>, config: QuotaConfig) -> Self {
+        Self {
+            pool,
+            config,
+            users: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Count a request of a user, or fail with 429 if they have reached one
+    /// of their daily limits.
+    pub async fn begin(&self, user_id: i64) -> Result<()> {
+        let today = Utc::now().date_naive();
+        self.ensure_loaded(user_id, today).await?;
+
+        let mut users = self.lock();
+        // Users deleted while their token is still valid are not counted
+        let Some(user) = users.get_mut(&user_id) else {
+            return Ok(());
+        };
+        if let Some(max) = user.limits.daily_requests
+            && user.usage.requests >= max
+        {
+            return Err(exceeded(
+                t!(
+                    "quota.requests_exceeded",
+                    count = max,
+                    seconds = seconds_until_tomorrow()
+                )
+                .to_string(),
+            ));
+        }
+        if let Some(max) = user.limits.daily_bytes()
+            && user.usage.bytes >= max
+        {
+            let mb = user.limits.daily_mb.unwrap_or_default();
+            return Err(exceeded(
+                t!(
+                    "quota.bytes_exceeded",
+                    mb = mb,
+                    seconds = seconds_until_tomorrow()
+                )
+                .to_string(),
+            ));
+        }
+        user.usage.requests += 1;
+        user.unsaved_requests += 1;
+        Ok(())
+    }
+
+    /// Count bytes transferred for a user, saving the counters when due.
+    pub fn record_bytes(&self, user_id: i64, bytes: u64) {
+        let mut users = self.lock();
+        let Some(user) = users.get_mut(&user_id) else {
+            return;
+        };
+        user.usage.bytes += bytes;
+        user.unsaved_bytes += bytes;
+        if user.saved_at.elapsed() >= SAVE_INTERVAL {
+            let unsaved = user.take_unsaved();
+            drop(users);
+            self.spawn_save(user_id, unsaved);
+        }
+    }
+
+    /// Save the usage of every user counted since it was last saved.
+    pub async fn save(&self) -> Result<()> {
+        let unsaved: Vec<_> = self
+            .lock()
+            .iter_mut()
+            .map(|(user_id, user)| (*user_id, user.take_unsaved()))
+            .collect();
+        for (user_id, (day, bytes, requests)) in unsaved {
+            if bytes > 0 || requests > 0 {
+                QuotaRepository::add_usage(
+                    &self.pool,
+                    user_id,
+                    day,
+                    to_i64(bytes),
+                    to_i64(requests),
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Limits and usage today of a user.
+    pub async fn get(&self, user_id: i64) -> Result<UserQuota> {
+        let row = QuotaRepository::find_limits(&self.pool, user_id)
+            .await?
+            .ok_or_else(|| user_not_found(user_id))?;
+        let today = Utc::now().date_naive();
+        self.ensure_loaded(user_id, today).await?;
+
+        let limits = self.limits_of(&row);
+        let mut users = self.lock();
+        let usage = match users.get_mut(&user_id) {
+            Some(user) => {
+                // Limits may have been changed since they were loaded
+                user.limits = limits;
+                user.usage
+            }
+            None => QuotaUsage {
+                day: today,
+                bytes: 0,
+                requests: 0,
+            },
+        };
+        Ok(UserQuota {
+            user_id,
+            daily_mb: row.quota_daily_mb.map(|mb| mb.max(0) as u64),
+            daily_requests: row.quota_daily_requests.map(|count| count.max(0) as u64),
+            limits,
+            usage,
+        })
+    }
+
+    /// Set the daily limits of a user.
+    pub async fn set_limits(
+        &self,
+        user_id: i64,
+        request: UpdateUserQuotaRequest,
+    ) -> Result<UserQuota> {
+        let updated = QuotaRepository::set_limits(
+            &self.pool,
+            user_id,
+            request.daily_mb.map(to_i64),
+            request.daily_requests.map(to_i64),
+        )
+        .await?;
+        if !updated {
+            return Err(user_not_found(user_id));
+        }
+        self.get(user_id).await
+    }
+
+    /// Load the limits and counters of a user for `today`, unless they are
+    /// loaded already. Counters of an earlier day are saved.
+    async fn ensure_loaded(&self, user_id: i64, today: NaiveDate) -> Result<()> {
+        let loaded = self
+            .lock()
+            .get(&user_id)
+            .is_some_and(|user| user.usage.day == today);
+        if loaded {
+            return Ok(());
+        }
+
+        let Some(row) = QuotaRepository::find_limits(&self.pool, user_id).await? else {
+            return Ok(());
+        };
+        let (bytes, requests) = QuotaRepository::usage(&self.pool, user_id, today).await?;
+        let user = UserUsage {
+            limits: self.limits_of(&row),
+            usage: QuotaUsage {
+                day: today,
+                bytes: bytes.max(0) as u64,
+                requests: requests.max(0) as u64,
+            },
+            unsaved_bytes: 0,
+            unsaved_requests: 0,
+            saved_at: Instant::now(),
+        };
+
+        let mut users = self.lock();
+        // Another request may have loaded them in the meantime
+        if users
+            .get(&user_id)
+            .is_some_and(|user| user.usage.day == today)
+        {
+            return Ok(());
+        }
+        if let Some(mut previous) = users.insert(user_id, user) {
+            let unsaved = previous.take_unsaved();
+            drop(users);
+            self.spawn_save(user_id, unsaved);
+        }
+        Ok(())
+    }
+
+    /// Limits in effect for a user: their own, or else the server defaults
+    /// unless they are an administrator.
+    fn limits_of(&self, row: &UserQuotaRow) -> QuotaLimits {
+        let resolve = |own: Option<i64>, default: u64| match own {
+            Some(limit) => (limit > 0).then_some(limit as u64),
+            None if row.is_admin => None,
+            None => (default > 0).then_some(default),
+        };
+        QuotaLimits {
+            daily_mb: resolve(row.quota_daily_mb, self.config.daily_mb),
+            daily_requests: resolve(row.quota_daily_requests, self.config.daily_requests),
+        }
+    }
+
+    /// Save counted usage in the background.
+    fn spawn_save(&self, user_id: i64, (day, bytes, requests): (NaiveDate, u64, u64)) {
+        if bytes == 0 && requests == 0 {
+            return;
+        }
+        let pool = self.pool.clone();
+        tokio::spawn(async move {
+            let result =
+                QuotaRepository::add_usage(&pool, user_id, day, to_i64(bytes), to_i64(requests))
+                    .await;
+            if let Err(e) = result {
+                warn!(user_id, error = %e, "{}", t!("quota.save_failed"));
+            }
+        });
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<i64, UserUsage>> {
+        self.users.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn to_i64(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
+
+/// Seconds until the daily counters start over at midnight UTC.
+fn seconds_until_tomorrow() -> u64 {
+    let now = Utc::now();
+    let tomorrow = now
+        .date_naive()
+        .checked_add_days(Days::new(1))
+        .and_then(|day| day.and_hms_opt(0, 0, 0))
+        .map(|midnight| midnight.and_utc())
+        .unwrap_or(now);
+    (tomorrow - now).num_seconds().max(1) as u64
+}
+
+fn exceeded(message: String) -> AppError {
+    AppError::TooManyRequests {
+        message,
+        retry_after_secs: seconds_until_tomorrow(),
+    }
+}
+
+fn user_not_found(user_id: i64) -> AppError {
+    AppError::NotFound(t!("auth.user_not_found", id = user_id).to_string())
+        .with_code(ErrorCode::UserNotFound)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{create_test_admin, create_test_db, create_test_user};
+
+    async fn setup() -> Pool<Sqlite> {
+        let pool = create_test_db().await;
+        create_test_user(&pool, 1, "friend").await;
+        create_test_admin(&pool, 2, "admin").await;
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_request_limit_spares_administrators() {
+        let config = QuotaConfig {
+            daily_mb: 0,
+            daily_requests: 2,
+        };
+        let service = QuotaService::new(setup().await, config);
+
+        service.begin(1).await.unwrap();
+        service.begin(1).await.unwrap();
+        let result = service.begin(1).await;
+        assert!(matches!(result, Err(AppError::TooManyRequests { .. })));
+
+        for _ in 0..5 {
+            service.begin(2).await.unwrap();
+        }
+        // Users that do not exist are not limited
+        service.begin(9).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_transfer_limit_and_own_limits() {
+        let config = QuotaConfig {
+            daily_mb: 1,
+            daily_requests: 0,
+        };
+        let service = QuotaService::new(setup().await, config);
+
+        service.begin(1).await.unwrap();
+        service.record_bytes(1, 1024 * 1024);
+        assert!(service.begin(1).await.is_err());
+
+        // 0 removes the limit
+        let request = UpdateUserQuotaRequest {
+            daily_mb: Some(0),
+            daily_requests: None,
+        };
+        let quota = service.set_limits(1, request).await.unwrap();
+        assert_eq!(quota.daily_mb, Some(0));
+        assert_eq!(quota.limits, QuotaLimits::default());
+        assert_eq!(quota.usage.bytes, 1024 * 1024);
+        service.begin(1).await.unwrap();
+
+        // Administrators are limited by their own limits
+        let request = UpdateUserQuotaRequest {
+            daily_mb: None,
+            daily_requests: Some(1),
+        };
+        service.set_limits(2, request).await.unwrap();
+        service.begin(2).await.unwrap();
+        assert!(service.begin(2).await.is_err());
+
+        assert!(
+            service
+                .set_limits(9, UpdateUserQuotaRequest::default())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_usage_is_saved_and_loaded_back() {
+        let pool = setup().await;
+        let service = QuotaService::new(pool.clone(), QuotaConfig::default());
+
+        service.begin(1).await.unwrap();
+        service.record_bytes(1, 500);
+        service.begin(1).await.unwrap();
+        service.save().await.unwrap();
+
+        let restarted = QuotaService::new(pool, QuotaConfig::default());
+        let quota = restarted.get(1).await.unwrap();
+        assert_eq!((quota.usage.bytes, quota.usage.requests), (500, 2));
+        assert_eq!(quota.usage.day, Utc::now().date_naive());
+    }
+}
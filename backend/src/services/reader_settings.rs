@@ -0,0 +1,117 @@
+//! Reader settings.
+//!
+//! Reading direction, page layout, webtoon mode and margin trimming decide
+//! how a content is shown. A library sets the defaults for its contents and
+//! each content may override them; anything left unset falls back to the
+//! built-in defaults.
+
+use std::collections::HashMap;
+
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, ErrorCode, Result};
+use crate::models::{Content, ReaderSettings, ReaderSettingsResponse, UpdateReaderSettingsRequest};
+use crate::repository::library::LibraryRepository;
+use crate::repository::reader_settings::ReaderSettingsRepository;
+use crate::services::content::ContentService;
+use crate::t;
+
+/// Service for reader settings operations.
+pub struct ReaderSettingsService;
+
+impl ReaderSettingsService {
+    /// Get the default settings of a library.
+    pub async fn get_for_library(
+        pool: &Pool<Sqlite>,
+        library_id: i64,
+    ) -> Result<ReaderSettingsResponse> {
+        Self::ensure_library(pool, library_id).await?;
+        let overrides = ReaderSettingsRepository::find_for_library(pool, library_id).await?;
+        Ok(ReaderSettingsResponse::new(
+            overrides,
+            ReaderSettings::default(),
+        ))
+    }
+
+    /// Update the default settings of a library.
+    pub async fn update_for_library(
+        pool: &Pool<Sqlite>,
+        library_id: i64,
+        request: UpdateReaderSettingsRequest,
+    ) -> Result<ReaderSettingsResponse> {
+        Self::ensure_library(pool, library_id).await?;
+        let stored = ReaderSettingsRepository::find_for_library(pool, library_id).await?;
+        let overrides = request.apply(stored);
+        ReaderSettingsRepository::save_for_library(pool, library_id, &overrides).await?;
+        Ok(ReaderSettingsResponse::new(
+            overrides,
+            ReaderSettings::default(),
+        ))
+    }
+
+    /// Get the settings of a content.
+    pub async fn get_for_content(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+    ) -> Result<ReaderSettingsResponse> {
+        let content = ContentService::get_content(pool, content_id).await?;
+        let inherited = Self::library_defaults(pool, content.library_id).await?;
+        let overrides = ReaderSettingsRepository::find_for_content(pool, content_id).await?;
+        Ok(ReaderSettingsResponse::new(overrides, inherited))
+    }
+
+    /// Update the settings of a content.
+    pub async fn update_for_content(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        request: UpdateReaderSettingsRequest,
+    ) -> Result<ReaderSettingsResponse> {
+        let content = ContentService::get_content(pool, content_id).await?;
+        let inherited = Self::library_defaults(pool, content.library_id).await?;
+        let stored = ReaderSettingsRepository::find_for_content(pool, content_id).await?;
+        let overrides = request.apply(stored);
+        ReaderSettingsRepository::save_for_content(pool, content_id, &overrides).await?;
+        Ok(ReaderSettingsResponse::new(overrides, inherited))
+    }
+
+    /// Settings in effect for each of the given contents, by content ID.
+    pub async fn resolve_for_contents(
+        pool: &Pool<Sqlite>,
+        contents: &[Content],
+    ) -> Result<HashMap<i64, ReaderSettings>> {
+        let libraries = ReaderSettingsRepository::list_for_libraries(pool).await?;
+        let overrides = ReaderSettingsRepository::list_for_contents(pool).await?;
+
+        Ok(contents
+            .iter()
+            .map(|content| {
+                let inherited = libraries
+                    .get(&content.library_id)
+                    .copied()
+                    .unwrap_or_default()
+                    .resolve(ReaderSettings::default());
+                let settings = overrides
+                    .get(&content.id)
+                    .copied()
+                    .unwrap_or_default()
+                    .resolve(inherited);
+                (content.id, settings)
+            })
+            .collect())
+    }
+
+    async fn library_defaults(pool: &Pool<Sqlite>, library_id: i64) -> Result<ReaderSettings> {
+        let overrides = ReaderSettingsRepository::find_for_library(pool, library_id).await?;
+        Ok(overrides.resolve(ReaderSettings::default()))
+    }
+
+    async fn ensure_library(pool: &Pool<Sqlite>, library_id: i64) -> Result<()> {
+        LibraryRepository::find_by_id(pool, library_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound(t!("library.id_not_found", id = library_id).to_string())
+                    .with_code(ErrorCode::LibraryNotFound)
+            })?;
+        Ok(())
+    }
+}
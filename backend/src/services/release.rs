@@ -0,0 +1,117 @@
+//! New chapter release service.
+//!
+//! Scans record the chapters they add to content already in a library.
+//! Users see those of the series they follow as a feed, newest first.
+
+use sqlx::{Pool, Sqlite};
+
+use crate::error::Result;
+use crate::models::{UpdatesPage, UpdatesQuery};
+use crate::repository::release::ReleaseRepository;
+use crate::utils::cursor::Cursor;
+
+/// Order name of update feed cursors.
+const UPDATES_ORDER: &str = "released";
+
+/// Default number of releases per page.
+const DEFAULT_PAGE_SIZE: i64 = 20;
+
+/// Largest number of releases per page.
+const MAX_PAGE_SIZE: i64 = 100;
+
+/// Service for new chapter releases.
+pub struct ReleaseService;
+
+impl ReleaseService {
+    /// Get a page of the new chapters in content a user follows.
+    pub async fn list_updates(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        query: &UpdatesQuery,
+    ) -> Result<UpdatesPage> {
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE);
+        let after = query
+            .after
+            .as_deref()
+            .map(|cursor| Cursor::decode(cursor, UPDATES_ORDER))
+            .transpose()?;
+
+        let items =
+            ReleaseRepository::list_followed(pool, user_id, after.map(|c| c.id), limit).await?;
+        let next_cursor = items
+            .last()
+            .filter(|_| items.len() as i64 == limit)
+            .map(|last| Cursor::new(UPDATES_ORDER, last.created_at.to_rfc3339(), last.id).encode());
+
+        Ok(UpdatesPage { items, next_cursor })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{
+        TIMESTAMP, create_test_chapter, create_test_content, create_test_db, create_test_library,
+        create_test_scan_path, create_test_user,
+    };
+
+    async fn setup() -> Pool<Sqlite> {
+        let pool = create_test_db().await;
+        create_test_user(&pool, 1, "reader").await;
+        create_test_library(&pool, 1, "Comics").await;
+        create_test_scan_path(&pool, 1, 1, "/comics").await;
+        create_test_content(&pool, 1, 1, "Followed").await;
+        create_test_content(&pool, 2, 1, "Dropped").await;
+        create_test_content(&pool, 3, 1, "Unmarked").await;
+        create_test_chapter(&pool, 1, 1, "/comics/Followed/Ch 1.cbz").await;
+        create_test_chapter(&pool, 2, 1, "/comics/Followed/Ch 2.cbz").await;
+        create_test_chapter(&pool, 3, 1, "/comics/Followed/Ch 3.cbz").await;
+        create_test_chapter(&pool, 4, 2, "/comics/Dropped/Ch 1.cbz").await;
+        create_test_chapter(&pool, 5, 3, "/comics/Unmarked/Ch 1.cbz").await;
+        sqlx::query(
+            r#"
+            INSERT INTO user_content_status (user_id, content_id, favorite, status, updated_at)
+            VALUES (1, 1, 0, 'reading', ?1), (1, 2, 0, 'dropped', ?1)
+            "#,
+        )
+        .bind(TIMESTAMP)
+        .execute(&pool)
+        .await
+        .unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_lists_followed_releases_by_page() {
+        let pool = setup().await;
+        ReleaseRepository::record(&pool, 1, &[1, 2, 3])
+            .await
+            .unwrap();
+        ReleaseRepository::record(&pool, 2, &[4]).await.unwrap();
+        ReleaseRepository::record(&pool, 3, &[5]).await.unwrap();
+
+        let query = UpdatesQuery {
+            limit: Some(2),
+            after: None,
+        };
+        let page = ReleaseService::list_updates(&pool, 1, &query)
+            .await
+            .unwrap();
+        let ids: Vec<i64> = page.items.iter().map(|r| r.chapter_id).collect();
+        assert_eq!(ids, [3, 2]);
+
+        let query = UpdatesQuery {
+            limit: Some(2),
+            after: page.next_cursor,
+        };
+        let page = ReleaseService::list_updates(&pool, 1, &query)
+            .await
+            .unwrap();
+        let ids: Vec<i64> = page.items.iter().map(|r| r.chapter_id).collect();
+        assert_eq!(ids, [1]);
+        assert!(page.next_cursor.is_none());
+    }
+}
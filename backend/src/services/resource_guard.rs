@@ -0,0 +1,250 @@
+//! Resource guard for background scan work.
+//!
+//! Scans walk whole directory trees and open every archive they find, which
+//! competes with page serving for disk and CPU. The guard optionally moves
+//! scan IO onto a dedicated low-priority thread (`nice`/idle IO class on
+//! Linux, background mode on Windows) and rate-limits filesystem operations
+//! with a token bucket.
+
+use std::panic::AssertUnwindSafe;
+use std::sync::Mutex;
+use std::sync::mpsc;
+use std::time::Instant;
+
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+use crate::error::{AppError, Result};
+use crate::t;
+use crate::utils::token_bucket::TokenBucket;
+
+/// Configuration for limiting the resources used by scans.
+#[derive(Debug, Clone, Default)]
+pub struct ScanResourceConfig {
+    /// Run scan IO on a dedicated thread with reduced CPU/IO priority.
+    pub low_priority: bool,
+    /// Maximum filesystem operations per second during scans (0 = unlimited).
+    pub fs_ops_per_second: u32,
+}
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Guards scan work so it does not starve interactive requests.
+///
+/// With the default configuration the guard is a no-op and work runs inline.
+pub struct ScanResourceGuard {
+    /// Sender to the low-priority worker thread, if enabled.
+    executor: Option<mpsc::Sender<Job>>,
+    /// Token bucket limiting filesystem operations, if enabled.
+    bucket: Option<Mutex<TokenBucket>>,
+}
+
+impl ScanResourceGuard {
+    /// Create a guard from configuration.
+    ///
+    /// When `low_priority` is set, a dedicated worker thread is spawned and
+    /// its scheduling priority is lowered once at startup.
+    pub fn new(config: ScanResourceConfig) -> Self {
+        let executor = if config.low_priority {
+            spawn_background_executor()
+        } else {
+            None
+        };
+
+        let bucket = (config.fs_ops_per_second > 0).then(|| {
+            Mutex::new(TokenBucket::per_second(
+                config.fs_ops_per_second,
+                Instant::now(),
+            ))
+        });
+
+        Self { executor, bucket }
+    }
+
+    /// Run blocking scan work under the guard.
+    ///
+    /// Work is sent to the low-priority thread when enabled. When only
+    /// throttling is enabled it runs on the blocking pool, since waiting for
+    /// tokens would otherwise stall an async worker thread.
+    pub async fn run_blocking<F, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce() -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        if let Some(ref sender) = self.executor {
+            let (tx, rx) = oneshot::channel();
+            sender
+                .send(Box::new(move || {
+                    let _ = tx.send(f());
+                }))
+                .map_err(|_| {
+                    AppError::Internal(t!("scan.resource_executor_stopped").to_string())
+                })?;
+
+            return rx.await.map_err(|_| {
+                AppError::Internal(t!("scan.resource_executor_stopped").to_string())
+            })?;
+        }
+
+        if self.bucket.is_some() {
+            return tokio::task::spawn_blocking(f)
+                .await
+                .map_err(|e| AppError::Internal(e.to_string()))?;
+        }
+
+        f()
+    }
+
+    /// Run blocking scan work under the guard, never inline.
+    ///
+    /// Unlike [`run_blocking`](Self::run_blocking), work always leaves the
+    /// calling task, so several calls can make progress in parallel. With the
+    /// low-priority thread enabled, work is still serialised on that thread.
+    pub async fn spawn_blocking<F, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce() -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        if self.executor.is_some() {
+            return self.run_blocking(f).await;
+        }
+
+        tokio::task::spawn_blocking(f)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?
+    }
+
+    /// Wait for a filesystem operation token.
+    ///
+    /// Must only be called from inside [`run_blocking`](Self::run_blocking),
+    /// as it sleeps the current thread while the bucket is empty.
+    pub fn acquire_fs_op(&self) {
+        let Some(ref bucket) = self.bucket else {
+            return;
+        };
+
+        loop {
+            let wait = {
+                let mut bucket = bucket.lock().unwrap_or_else(|e| e.into_inner());
+                bucket.try_take(Instant::now())
+            };
+
+            match wait {
+                None => return,
+                Some(duration) => std::thread::sleep(duration),
+            }
+        }
+    }
+}
+
+impl Default for ScanResourceGuard {
+    fn default() -> Self {
+        Self::new(ScanResourceConfig::default())
+    }
+}
+
+/// Spawn the low-priority worker thread and return a sender for jobs.
+fn spawn_background_executor() -> Option<mpsc::Sender<Job>> {
+    let (tx, rx) = mpsc::channel::<Job>();
+
+    let spawned = std::thread::Builder::new()
+        .name("ryuri-scan-io".to_string())
+        .spawn(move || {
+            match lower_current_thread_priority() {
+                Ok(()) => info!("{}", t!("scan.low_priority_enabled")),
+                Err(e) => warn!(error = %e, "{}", t!("scan.low_priority_failed")),
+            }
+
+            while let Ok(job) = rx.recv() {
+                // Keep the thread alive if a job panics; the caller sees a
+                // dropped result channel instead.
+                let _ = std::panic::catch_unwind(AssertUnwindSafe(job));
+            }
+        });
+
+    match spawned {
+        Ok(_) => Some(tx),
+        Err(e) => {
+            warn!(error = %e, "{}", t!("scan.low_priority_failed"));
+            None
+        }
+    }
+}
+
+/// Lower the CPU and IO priority of the calling thread.
+#[cfg(target_os = "linux")]
+fn lower_current_thread_priority() -> std::io::Result<()> {
+    const NICE_VALUE: libc::c_int = 10;
+    const IOPRIO_WHO_PROCESS: libc::c_long = 1;
+    const IOPRIO_CLASS_IDLE: libc::c_long = 3;
+    const IOPRIO_CLASS_SHIFT: libc::c_long = 13;
+
+    // SAFETY: plain syscalls on the calling thread; no memory is shared.
+    unsafe {
+        // On Linux, nice values and IO priorities are per-thread when
+        // addressed by thread ID, so this does not affect request handlers.
+        let tid = libc::syscall(libc::SYS_gettid) as libc::id_t;
+        if libc::setpriority(libc::PRIO_PROCESS, tid, NICE_VALUE) != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+
+        if libc::syscall(
+            libc::SYS_ioprio_set,
+            IOPRIO_WHO_PROCESS,
+            0 as libc::c_long,
+            IOPRIO_CLASS_IDLE << IOPRIO_CLASS_SHIFT,
+        ) != 0
+        {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+
+    Ok(())
+}
+
+/// Lower the CPU and IO priority of the calling thread.
+#[cfg(windows)]
+fn lower_current_thread_priority() -> std::io::Result<()> {
+    use windows_sys::Win32::System::Threading::{
+        GetCurrentThread, SetThreadPriority, THREAD_MODE_BACKGROUND_BEGIN,
+    };
+
+    // SAFETY: GetCurrentThread returns a pseudo handle that needs no cleanup.
+    let ok = unsafe { SetThreadPriority(GetCurrentThread(), THREAD_MODE_BACKGROUND_BEGIN) };
+    if ok == 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// Lower the CPU and IO priority of the calling thread.
+#[cfg(not(any(target_os = "linux", windows)))]
+fn lower_current_thread_priority() -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "thread priority control is not supported on this platform",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_default_guard_runs_inline() {
+        let guard = ScanResourceGuard::default();
+        let value = guard.run_blocking(|| Ok(42)).await.unwrap();
+        assert_eq!(value, 42);
+    }
+
+    #[tokio::test]
+    async fn test_spawn_blocking_runs_on_blocking_pool() {
+        let guard = ScanResourceGuard::default();
+        let value = guard
+            .spawn_blocking(|| Ok(std::thread::current().id()))
+            .await
+            .unwrap();
+        assert_ne!(value, std::thread::current().id());
+    }
+}
@@ -0,0 +1,213 @@
+//! Ratings and reviews.
+//!
+//! Users rate the contents they may see from 1 to 10, optionally with a
+//! short review. Ratings are visible to every user who may see the content,
+//! and their average is shown in content listings.
+
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, Result};
+use crate::models::{
+    ContentResponse, ContentReview, ContentReviews, MAX_REVIEW_CHARS, MAX_REVIEW_RATING,
+    MIN_REVIEW_RATING, RatingSummary, SaveReviewRequest,
+};
+use crate::repository::review::ReviewRepository;
+use crate::services::age_rating::AgeRatingService;
+use crate::services::content::ContentService;
+use crate::t;
+
+/// Service for rating and review operations.
+pub struct ReviewService;
+
+impl ReviewService {
+    /// List the ratings of a content with their average.
+    pub async fn list(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        content_id: i64,
+    ) -> Result<ContentReviews> {
+        Self::check_content(pool, user_id, content_id).await?;
+        let reviews = ReviewRepository::list_by_content(pool, content_id).await?;
+        let summary = ReviewRepository::summaries(pool, &[content_id])
+            .await?
+            .remove(&content_id)
+            .unwrap_or_default();
+        Ok(ContentReviews { summary, reviews })
+    }
+
+    /// Get the current user's rating of a content.
+    pub async fn get(pool: &Pool<Sqlite>, user_id: i64, content_id: i64) -> Result<ContentReview> {
+        Self::check_content(pool, user_id, content_id).await?;
+        ReviewRepository::find(pool, user_id, content_id)
+            .await?
+            .ok_or_else(|| Self::not_found(content_id))
+    }
+
+    /// Rate a content, replacing the user's earlier rating of it.
+    pub async fn save(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        content_id: i64,
+        request: SaveReviewRequest,
+    ) -> Result<ContentReview> {
+        if !(MIN_REVIEW_RATING..=MAX_REVIEW_RATING).contains(&request.rating) {
+            return Err(AppError::BadRequest(
+                t!(
+                    "review.invalid_rating",
+                    min = MIN_REVIEW_RATING,
+                    max = MAX_REVIEW_RATING
+                )
+                .to_string(),
+            ));
+        }
+        let review = request
+            .review
+            .as_deref()
+            .map(str::trim)
+            .filter(|review| !review.is_empty());
+        if review.is_some_and(|review| review.chars().count() > MAX_REVIEW_CHARS) {
+            return Err(AppError::BadRequest(
+                t!("review.too_long", max = MAX_REVIEW_CHARS).to_string(),
+            ));
+        }
+
+        Self::check_content(pool, user_id, content_id).await?;
+        ReviewRepository::save(pool, user_id, content_id, request.rating, review).await
+    }
+
+    /// Delete the current user's rating of a content.
+    pub async fn delete(pool: &Pool<Sqlite>, user_id: i64, content_id: i64) -> Result<()> {
+        if !ReviewRepository::delete(pool, user_id, content_id).await? {
+            return Err(Self::not_found(content_id));
+        }
+        Ok(())
+    }
+
+    /// Set the average rating of each content in a listing.
+    pub async fn fill_ratings(
+        pool: &Pool<Sqlite>,
+        responses: &mut [ContentResponse],
+    ) -> Result<()> {
+        let ids: Vec<i64> = responses.iter().map(|r| r.id).collect();
+        let summaries = ReviewRepository::summaries(pool, &ids).await?;
+        for response in responses {
+            response.ratings = Some(summaries.get(&response.id).copied().unwrap_or_default());
+        }
+        Ok(())
+    }
+
+    /// Fail unless the content exists and the user may see it.
+    async fn check_content(pool: &Pool<Sqlite>, user_id: i64, content_id: i64) -> Result<()> {
+        let content = ContentService::get_content(pool, content_id).await?;
+        AgeRatingService::check(pool, user_id, &content).await
+    }
+
+    fn not_found(content_id: i64) -> AppError {
+        AppError::NotFound(t!("review.not_found", id = content_id).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{
+        create_test_content, create_test_db, create_test_library, create_test_scan_path,
+        create_test_user,
+    };
+
+    async fn setup() -> Pool<Sqlite> {
+        let pool = create_test_db().await;
+        create_test_user(&pool, 1, "alice").await;
+        create_test_user(&pool, 2, "bob").await;
+        create_test_library(&pool, 1, "Comics").await;
+        create_test_scan_path(&pool, 1, 1, "/comics").await;
+        create_test_content(&pool, 1, 1, "Rated").await;
+        create_test_content(&pool, 2, 1, "Other").await;
+        pool
+    }
+
+    fn request(rating: i32, review: Option<&str>) -> SaveReviewRequest {
+        SaveReviewRequest {
+            rating,
+            review: review.map(String::from),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_replaces_earlier_rating() {
+        let pool = setup().await;
+
+        let review = ReviewService::save(&pool, 1, 1, request(6, Some(" Fine ")))
+            .await
+            .unwrap();
+        assert_eq!((review.rating, review.review.as_deref()), (6, Some("Fine")));
+        let review = ReviewService::save(&pool, 1, 1, request(8, Some(" ")))
+            .await
+            .unwrap();
+        assert_eq!((review.rating, review.review), (8, None));
+        assert_eq!(review.username, "alice");
+
+        ReviewService::save(&pool, 2, 1, request(5, None))
+            .await
+            .unwrap();
+        let reviews = ReviewService::list(&pool, 1, 1).await.unwrap();
+        assert_eq!(reviews.reviews.len(), 2);
+        assert_eq!(
+            reviews.summary,
+            RatingSummary {
+                average_rating: Some(6.5),
+                rating_count: 2,
+            }
+        );
+
+        ReviewService::delete(&pool, 1, 1).await.unwrap();
+        assert!(ReviewService::get(&pool, 1, 1).await.is_err());
+        assert!(ReviewService::delete(&pool, 1, 1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_save_rejects_invalid_reviews() {
+        let pool = setup().await;
+
+        for rating in [0, 11] {
+            let result = ReviewService::save(&pool, 1, 1, request(rating, None)).await;
+            assert!(matches!(result, Err(AppError::BadRequest(_))));
+        }
+        let long = "字".repeat(MAX_REVIEW_CHARS + 1);
+        let result = ReviewService::save(&pool, 1, 1, request(5, Some(&long))).await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+        assert!(
+            ReviewService::save(&pool, 1, 3, request(5, None))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fill_ratings() {
+        let pool = setup().await;
+        ReviewService::save(&pool, 1, 1, request(9, None))
+            .await
+            .unwrap();
+
+        let mut responses: Vec<ContentResponse> = ContentService::list_contents(&pool, 1)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(ContentResponse::from)
+            .collect();
+        ReviewService::fill_ratings(&pool, &mut responses)
+            .await
+            .unwrap();
+
+        let ratings: Vec<_> = responses.iter().map(|r| (r.id, r.ratings)).collect();
+        assert!(ratings.contains(&(
+            1,
+            Some(RatingSummary {
+                average_rating: Some(9.0),
+                rating_count: 1,
+            })
+        )));
+        assert!(ratings.contains(&(2, Some(RatingSummary::default()))));
+    }
+}
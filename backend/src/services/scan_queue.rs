@@ -6,28 +6,137 @@
 //! - `ScanQueueService`: Manages a queue of scan tasks with priority-based ordering,
 //!   deduplication, and task status tracking.
 
-use rust_i18n::t;
 use sqlx::{Pool, Sqlite};
 use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::sync::{RwLock, broadcast};
-use tokio::task::JoinHandle;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use tokio::sync::{RwLock, Semaphore, broadcast, watch};
+use tokio::task::{JoinHandle, JoinSet};
 use tracing::{debug, error, info, instrument, warn};
 use uuid::Uuid;
 
-use crate::error::{AppError, Result};
-use crate::extractors::{ArchiveExtractor, EpubExtractor, PdfExtractor, natural_sort_key};
+use crate::error::{AppError, ErrorCode, Result};
+use crate::extractors::{
+    ArchiveExtractor, AudioExtractor, EpubExtractor, MobiExtractor, PdfExtractor, TxtExtractor,
+    natural_sort_key,
+};
 use crate::models::{
-    Chapter, Content, NewChapter, NewContent, QueuedTask, ScanPath, ScanTask, TaskPriority,
-    TaskResult, TaskStatus, file_type_from_path,
+    Chapter, ChapterStatus, Content, DEFAULT_SCAN_DEPTH, FolderError, IMAGE_FOLDER_FILE_TYPE,
+    MetadataProviderKind, NewChapter, NewContent, PreviewContent, PreviewMove, PreviewUpdate,
+    QueuedTask, RunningTask, ScanPath, ScanPathStats, ScanPreview, ScanQueueState, ScanTask,
+    ScanTaskHistoryPage, ScanTaskHistoryQuery, SuspiciousArchive, TaskKind, TaskPriority,
+    TaskProgress, TaskResult, TaskStatus, TaskStatusCounts, file_type_from_path,
+    is_image_file_type,
 };
+use crate::repository::alias::AliasRepository;
 use crate::repository::content::{ChapterRepository, ContentRepository};
-use crate::repository::library::ScanPathRepository;
-use crate::services::bangumi::BangumiService;
+use crate::repository::library::{
+    IgnorePatternRepository, LibraryRepository, MetadataProviderRepository, ScanPathRepository,
+};
+use crate::repository::metadata::MetadataFailureRepository;
+use crate::repository::release::ReleaseRepository;
+use crate::repository::scan_task::ScanTaskRepository;
+use crate::services::content::ContentService;
+use crate::services::executors::{ExecutorRegistry, TaskContext};
+use crate::services::metadata::{MetadataManager, ScrapeOptions, metadata_source};
+use crate::services::resource_guard::ScanResourceGuard;
+use crate::t;
+use crate::utils::ignore::IgnoreMatcher;
+use crate::utils::numbering::ChapterNumbering;
+use crate::utils::thumbnail::ThumbnailOptions;
+use crate::utils::title::TitleNormalizer;
+
+/// (title, file_path, file_type, page_count, size, status, error)
+type ChapterEntry = (
+    String,
+    String,
+    String,
+    i32,
+    i64,
+    ChapterStatus,
+    Option<String>,
+);
+
+/// Pages counted in a chapter file, see [`ScanService::count_pages`].
+struct PageCount {
+    pages: i32,
+    status: ChapterStatus,
+    /// Why the file could not be read.
+    error: Option<String>,
+    /// Entries of an archive that were left out, see [`SuspiciousArchive`].
+    suspicious: Vec<String>,
+}
+
+/// File name and size of each chapter of a content, sorted.
+type Fingerprint = Vec<(String, i64)>;
+
+/// Identifies the target of an active task for deduplication:
+/// (kind, library_id, scan_path_id), where no scan path means the whole
+/// library.
+type TaskKey = (TaskKind, i64, Option<i64>);
+
+/// Deduplication key of a task.
+fn task_key(task: &ScanTask) -> TaskKey {
+    (task.kind, task.library_id, task.scan_path_id)
+}
+
+/// Default number of content folders inspected at the same time during import.
+pub const DEFAULT_IMPORT_CONCURRENCY: usize = 4;
+
+/// Default number of days missing content stays in the trash.
+pub const DEFAULT_TRASH_RETENTION_DAYS: u32 = 30;
+
+/// Default number of hours finished tasks stay in the history.
+pub const DEFAULT_HISTORY_RETENTION_HOURS: u32 = 24 * 7;
 
-/// (title, file_path, file_type, page_count, size)
-type ChapterEntry = (String, String, String, i32, i64);
+/// Default number of finished tasks kept in the history.
+pub const DEFAULT_HISTORY_MAX_ENTRIES: usize = 1000;
+
+/// How often finished tasks past the history limits are removed.
+const HISTORY_PRUNE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// Number of imported contents written to the database per transaction.
+const IMPORT_BATCH_SIZE: usize = 32;
+
+/// Folders of a scan path on disk compared with its content in the database.
+struct ScanPlan {
+    ignore: Arc<IgnoreMatcher>,
+    /// Known content whose folder is on disk, with that folder.
+    existing: Vec<(Content, PathBuf)>,
+    /// Folders on disk without content.
+    new_folders: Vec<PathBuf>,
+    /// Known content whose folder is gone, including content in the trash.
+    missing: Vec<Content>,
+}
+
+/// Missing content matched to the folder it was moved to.
+struct MovedContent {
+    /// Index into the missing content.
+    missing_idx: usize,
+    folder_path: PathBuf,
+    title: String,
+    folder_title: String,
+    /// New file path of each chapter, by chapter ID.
+    chapter_paths: Vec<(i64, String)>,
+}
+
+/// A new content folder inspected on disk, ready to be written to the database.
+struct PreparedFolder {
+    /// Display title, after normalization.
+    title: String,
+    /// Title derived from the folder name.
+    folder_title: String,
+    folder_path: PathBuf,
+    chapters: Vec<ChapterEntry>,
+    metadata: Option<serde_json::Value>,
+    thumbnail: Option<Vec<u8>>,
+    /// Settings the thumbnail was generated with.
+    thumbnail_spec: Option<String>,
+    scrape_error: Option<String>,
+    /// Chapter archives with entries that were skipped.
+    suspicious_archives: Vec<SuspiciousArchive>,
+}
 
 // ============================================================================
 // ScanResult
@@ -38,12 +147,62 @@ type ChapterEntry = (String, String, String, i32, i64);
 pub struct ScanResult {
     /// Newly added content items.
     pub added: Vec<Content>,
-    /// IDs of content items that were removed (folder no longer exists).
+    /// IDs of content items moved to the trash (folder no longer exists).
     pub removed: Vec<i64>,
+    /// IDs of content items relinked to a renamed or moved folder.
+    pub moved: Vec<i64>,
     /// Content items that failed metadata scraping, with error messages.
     pub failed_scrape: Vec<(Content, String)>,
     /// Newly added chapters.
     pub added_chapters: Vec<crate::models::AddedChapter>,
+    /// Archives with entries leading outside of them or symbolic links,
+    /// which were skipped.
+    pub suspicious_archives: Vec<SuspiciousArchive>,
+    /// Statistics of each scanned scan path.
+    pub scan_paths: Vec<ScanPathStats>,
+    /// Folders that could not be imported or rescanned.
+    pub folder_errors: Vec<FolderError>,
+}
+
+impl ScanResult {
+    /// Add the changes of another scan to this one.
+    fn merge(&mut self, other: ScanResult) {
+        self.added.extend(other.added);
+        self.removed.extend(other.removed);
+        self.moved.extend(other.moved);
+        self.failed_scrape.extend(other.failed_scrape);
+        self.added_chapters.extend(other.added_chapters);
+        self.suspicious_archives.extend(other.suspicious_archives);
+        self.scan_paths.extend(other.scan_paths);
+        self.folder_errors.extend(other.folder_errors);
+    }
+}
+
+/// Result of regenerating the thumbnails of a library.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ThumbnailResult {
+    /// Number of thumbnails written.
+    pub regenerated: usize,
+    /// Number of contents whose thumbnail could not be generated.
+    pub failed: usize,
+}
+
+/// Result of counting the pages of a library's chapters that had none.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PageCountResult {
+    /// Number of chapters pages were found in.
+    pub updated: usize,
+    /// Number of chapters that still cannot be read.
+    pub failed: usize,
+}
+
+/// Result of fetching the metadata of a library's content again.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MetadataRefreshResult {
+    /// Number of contents whose metadata was updated.
+    pub refreshed: usize,
+    /// Number of contents whose metadata could not be fetched.
+    pub failed: usize,
 }
 
 // ============================================================================
@@ -53,7 +212,18 @@ pub struct ScanResult {
 /// Service for scanning library paths and importing content.
 pub struct ScanService {
     pool: Pool<Sqlite>,
-    bangumi_service: Option<Arc<BangumiService>>,
+    metadata_manager: Option<Arc<MetadataManager>>,
+    resource_guard: Arc<ScanResourceGuard>,
+    /// Number of new content folders inspected at the same time.
+    import_concurrency: usize,
+    /// Days missing content stays in the trash before it is purged; 0 keeps
+    /// it until purged by an administrator.
+    trash_retention_days: u32,
+    /// Cleans folder names into the titles of new content.
+    title_normalizer: Arc<TitleNormalizer>,
+    /// Size and format of generated thumbnails, changed at runtime through
+    /// the server settings.
+    thumbnail_options: std::sync::RwLock<ThumbnailOptions>,
 }
 
 impl ScanService {
@@ -61,41 +231,137 @@ impl ScanService {
     pub fn new(pool: Pool<Sqlite>) -> Self {
         Self {
             pool,
-            bangumi_service: None,
+            metadata_manager: None,
+            resource_guard: Arc::new(ScanResourceGuard::default()),
+            import_concurrency: DEFAULT_IMPORT_CONCURRENCY,
+            trash_retention_days: DEFAULT_TRASH_RETENTION_DAYS,
+            title_normalizer: Arc::new(TitleNormalizer::default()),
+            thumbnail_options: std::sync::RwLock::new(ThumbnailOptions::default()),
         }
     }
 
-    /// Create a new scan service with Bangumi integration for auto-scraping.
-    pub fn with_bangumi(pool: Pool<Sqlite>, bangumi_service: Arc<BangumiService>) -> Self {
+    /// Create a new scan service with metadata providers for auto-scraping.
+    pub fn with_metadata(pool: Pool<Sqlite>, metadata_manager: Arc<MetadataManager>) -> Self {
         Self {
             pool,
-            bangumi_service: Some(bangumi_service),
+            metadata_manager: Some(metadata_manager),
+            resource_guard: Arc::new(ScanResourceGuard::default()),
+            import_concurrency: DEFAULT_IMPORT_CONCURRENCY,
+            trash_retention_days: DEFAULT_TRASH_RETENTION_DAYS,
+            title_normalizer: Arc::new(TitleNormalizer::default()),
+            thumbnail_options: std::sync::RwLock::new(ThumbnailOptions::default()),
         }
     }
 
-    /// Set the Bangumi service for auto-scraping.
-    pub fn set_bangumi_service(&mut self, bangumi_service: Arc<BangumiService>) {
-        self.bangumi_service = Some(bangumi_service);
+    /// Set the metadata providers used for auto-scraping.
+    pub fn set_metadata_manager(&mut self, metadata_manager: Arc<MetadataManager>) {
+        self.metadata_manager = Some(metadata_manager);
+    }
+
+    /// Set the resource guard used to limit scan IO.
+    pub fn set_resource_guard(&mut self, resource_guard: Arc<ScanResourceGuard>) {
+        self.resource_guard = resource_guard;
+    }
+
+    /// Set how many new content folders are inspected at the same time.
+    pub fn set_import_concurrency(&mut self, import_concurrency: usize) {
+        self.import_concurrency = import_concurrency.max(1);
+    }
+
+    /// Set how many days missing content stays in the trash (0 = until
+    /// purged by an administrator).
+    pub fn set_trash_retention_days(&mut self, days: u32) {
+        self.trash_retention_days = days;
+    }
+
+    /// Set the rules that clean folder names into content titles.
+    pub fn set_title_normalizer(&mut self, title_normalizer: TitleNormalizer) {
+        self.title_normalizer = Arc::new(title_normalizer);
+    }
+
+    /// Set the size and format of generated thumbnails. Applies to
+    /// thumbnails generated from then on.
+    pub fn set_thumbnail_options(&self, thumbnail_options: ThumbnailOptions) {
+        *self
+            .thumbnail_options
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = thumbnail_options;
+    }
+
+    /// Size and format of generated thumbnails.
+    pub fn thumbnail_options(&self) -> ThumbnailOptions {
+        *self
+            .thumbnail_options
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Run blocking filesystem work under the scan resource guard.
+    async fn run_guarded<F, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&ScanResourceGuard) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let guard = Arc::clone(&self.resource_guard);
+        self.resource_guard.run_blocking(move || f(&guard)).await
     }
 
     /// Scan all paths in a library and import/update content.
     ///
+    /// A scan path that cannot be scanned does not stop the others; its
+    /// error is recorded in its [`ScanPathStats`].
+    ///
     /// Requirements: 2.1
     #[instrument(skip(self), fields(library_id))]
     pub async fn scan_library(&self, library_id: i64) -> Result<ScanResult> {
         let scan_paths = ScanPathRepository::list_by_library(&self.pool, library_id).await?;
+        Ok(self.scan_paths(&scan_paths).await)
+    }
 
+    /// Scan each of `scan_paths`, recording the error of those that fail.
+    async fn scan_paths(&self, scan_paths: &[ScanPath]) -> ScanResult {
         let mut result = ScanResult::default();
 
         for scan_path in scan_paths {
-            let path_result = self.scan_path(&scan_path).await?;
-            result.added.extend(path_result.added);
-            result.removed.extend(path_result.removed);
-            result.failed_scrape.extend(path_result.failed_scrape);
-            result.added_chapters.extend(path_result.added_chapters);
+            let started = std::time::Instant::now();
+            match self.scan_path(scan_path).await {
+                Ok(path_result) => result.merge(path_result),
+                Err(e) => {
+                    error!(path = %scan_path.path, error = %e, "{}", t!("scan.scan_path_failed"));
+                    result.scan_paths.push(ScanPathStats {
+                        scan_path_id: scan_path.id,
+                        path: scan_path.path.clone(),
+                        duration_ms: started.elapsed().as_millis() as u64,
+                        error: Some(e.to_string()),
+                        ..ScanPathStats::default()
+                    });
+                }
+            }
         }
 
-        Ok(result)
+        result
+    }
+
+    /// Scan one scan path of a library, or the whole library when
+    /// `scan_path_id` is None.
+    pub async fn scan_target(
+        &self,
+        library_id: i64,
+        scan_path_id: Option<i64>,
+    ) -> Result<ScanResult> {
+        let Some(scan_path_id) = scan_path_id else {
+            return self.scan_library(library_id).await;
+        };
+
+        let scan_path = ScanPathRepository::find_by_id(&self.pool, scan_path_id)
+            .await?
+            .filter(|scan_path| scan_path.library_id == library_id)
+            .ok_or_else(|| {
+                AppError::NotFound(
+                    t!("scan_queue.scan_path_not_found", id = scan_path_id).to_string(),
+                )
+            })?;
+        Ok(self.scan_paths(&[scan_path]).await)
     }
 
     /// Scan a single scan path and import/update content.
@@ -103,8 +369,105 @@ impl ScanService {
     pub async fn scan_path(&self, scan_path: &ScanPath) -> Result<ScanResult> {
         info!(path = ?scan_path, "{}", t!("scan.scanning"));
 
+        let started = std::time::Instant::now();
         let mut result = ScanResult::default();
         let base_path = Path::new(&scan_path.path);
+        let ScanPlan {
+            ignore,
+            existing,
+            mut new_folders,
+            mut missing,
+        } = self.plan_scan_path(scan_path).await?;
+        let folders_discovered = existing.len() + new_folders.len();
+
+        // Existing content found, re-scan for chapter changes
+        for (content, folder_path) in existing {
+            // The folder is back, e.g. after a share was mounted again
+            if content.missing_since.is_some()
+                && ContentRepository::restore(&self.pool, content.id).await?
+            {
+                info!(content_id = content.id, "{}", t!("scan.content_restored"));
+            }
+
+            match self
+                .rescan_content_chapters(&content, &folder_path, &ignore)
+                .await
+            {
+                Ok((added_chapters, suspicious_archives)) => {
+                    result.added_chapters.extend(added_chapters);
+                    result.suspicious_archives.extend(suspicious_archives);
+                }
+                Err(e) => {
+                    error!(folder_path = ?folder_path, error = %e, "{}", t!("scan.rescan_failed"));
+                    result
+                        .folder_errors
+                        .push(Self::folder_error(scan_path, &folder_path, &e));
+                }
+            }
+        }
+
+        // Content whose folder was renamed or moved is relinked. The rest of
+        // the missing content goes to the trash rather than being deleted,
+        // since its folder may only be temporarily unavailable
+        if !missing.is_empty() && !new_folders.is_empty() {
+            let (moved, remaining) = self
+                .find_moved_content(base_path, new_folders, &missing, &ignore)
+                .await?;
+            new_folders = remaining;
+            self.relink_moved_content(&moved, &missing, &mut result)
+                .await?;
+
+            let relinked: HashSet<i64> = moved.iter().map(|m| missing[m.missing_idx].id).collect();
+            missing.retain(|content| !relinked.contains(&content.id));
+        }
+        for content in missing {
+            if content.missing_since.is_none() {
+                ContentRepository::mark_missing(&self.pool, content.id).await?;
+                result.removed.push(content.id);
+            }
+        }
+        self.purge_expired_trash().await?;
+
+        // Import in batches so thumbnails of a huge library are not all held
+        // in memory before the first insert
+        let providers: Arc<[MetadataProviderKind]> =
+            MetadataProviderRepository::list_by_library(&self.pool, scan_path.library_id)
+                .await?
+                .into();
+        for chunk in new_folders.chunks(IMPORT_BATCH_SIZE) {
+            let (prepared, failed) = self
+                .prepare_folders(base_path, chunk.to_vec(), &ignore, &providers)
+                .await;
+            result.folder_errors.extend(
+                failed
+                    .iter()
+                    .map(|(path, error)| Self::folder_error(scan_path, path, error)),
+            );
+            self.import_batch(scan_path, &prepared, &mut result).await;
+        }
+
+        // Keep failed scrapes so they can be retried or matched by hand
+        for (content, error) in &result.failed_scrape {
+            MetadataFailureRepository::record(&self.pool, content.id, error).await?;
+        }
+
+        result.scan_paths.push(ScanPathStats {
+            scan_path_id: scan_path.id,
+            path: scan_path.path.clone(),
+            folders_discovered: folders_discovered as i32,
+            added: result.added.len() as i32,
+            removed: result.removed.len() as i32,
+            errors: result.folder_errors.len() as i32,
+            duration_ms: started.elapsed().as_millis() as u64,
+            error: None,
+        });
+        Ok(result)
+    }
+
+    /// Compare the folders of a scan path on disk with its content in the
+    /// database, without changing anything.
+    async fn plan_scan_path(&self, scan_path: &ScanPath) -> Result<ScanPlan> {
+        let base_path = Path::new(&scan_path.path);
 
         // Check if the scan path exists
         if !base_path.exists() {
@@ -121,114 +484,1056 @@ impl ScanService {
                 .into_iter()
                 .collect();
 
-        // Scan for content folders
-        let discovered_folders = self.discover_content_folders(base_path)?;
+        // Files and folders matching the library's ignore patterns are skipped
+        let patterns =
+            IgnorePatternRepository::list_by_library(&self.pool, scan_path.library_id).await?;
+        let ignore = Arc::new(IgnoreMatcher::new(&patterns));
+        let library = LibraryRepository::find_by_id(&self.pool, scan_path.library_id).await?;
+        let scan_depth = library
+            .as_ref()
+            .map_or(DEFAULT_SCAN_DEPTH, |library| library.scan_depth);
+        let standalone_archives = library.is_some_and(|library| library.standalone_archives);
+
+        // Scan for content folders, and for standalone archives if enabled
+        let base = base_path.to_path_buf();
+        let matcher = Arc::clone(&ignore);
+        let discovered_folders = self
+            .run_guarded(move |guard| {
+                let mut found = Self::discover_content_folders(&base, scan_depth, &matcher, guard)?;
+                if standalone_archives {
+                    found.extend(Self::discover_standalone_archives(&base, &matcher, guard)?);
+                }
+                Ok(found)
+            })
+            .await?;
         let discovered_paths: HashSet<String> = discovered_folders
             .iter()
             .map(|p| p.to_string_lossy().to_string())
             .collect();
 
-        // Find removed content (exists in DB but not on disk)
+        // Find missing content (exists in DB but not on disk)
+        let mut missing = Vec::new();
         for existing_path in &existing_paths {
-            if !discovered_paths.contains(existing_path) {
-                // Content folder was removed from disk
-                if let Some(content) = ContentRepository::find_by_folder_path(
+            if !discovered_paths.contains(existing_path)
+                && let Some(content) = ContentRepository::find_by_folder_path(
                     &self.pool,
                     scan_path.library_id,
                     existing_path,
                 )
                 .await?
-                {
-                    ContentRepository::delete(&self.pool, content.id).await?;
-                    result.removed.push(content.id);
-                }
+            {
+                missing.push(content);
             }
         }
 
-        // Find new content (exists on disk but not in DB)
+        // Split folders on disk into known content and new content
+        let mut existing = Vec::new();
+        let mut new_folders = Vec::new();
         for folder_path in discovered_folders {
             let folder_path_str = folder_path.to_string_lossy().to_string();
 
             if !existing_paths.contains(&folder_path_str) {
-                // New content folder found
-                match self.import_content_folder(scan_path, &folder_path).await {
-                    Ok((content, added_chapters, scrape_error)) => {
-                        if let Some(error_msg) = scrape_error {
-                            // Content was imported but metadata scraping failed
-                            result.failed_scrape.push((content.clone(), error_msg));
-                        }
-                        result.added.push(content);
-                        result.added_chapters.extend(added_chapters);
-                    }
-                    Err(e) => {
-                        // Log error but continue scanning
-                        error!(folder_path = ?folder_path, error = %e, "{}", t!("scan.import_failed"));
-                    }
+                new_folders.push(folder_path);
+            } else if let Some(content) = ContentRepository::find_by_folder_path(
+                &self.pool,
+                scan_path.library_id,
+                &folder_path_str,
+            )
+            .await?
+            {
+                existing.push((content, folder_path));
+            }
+        }
+
+        Ok(ScanPlan {
+            ignore,
+            existing,
+            new_folders,
+            missing,
+        })
+    }
+
+    /// Work out what a scan of a library would change, without writing to
+    /// the database or generating thumbnails.
+    pub async fn preview_library(&self, library_id: i64) -> Result<ScanPreview> {
+        let scan_paths = ScanPathRepository::list_by_library(&self.pool, library_id).await?;
+        let mut preview = ScanPreview::default();
+
+        for scan_path in &scan_paths {
+            let base_path = Path::new(&scan_path.path);
+            let ScanPlan {
+                ignore,
+                existing,
+                new_folders,
+                missing,
+            } = self.plan_scan_path(scan_path).await?;
+
+            for (content, folder_path) in existing {
+                let folder = folder_path.clone();
+                let matcher = Arc::clone(&ignore);
+                let Ok(disk_chapters) = self
+                    .run_guarded(move |guard| Self::detect_chapters(&folder, &matcher, guard))
+                    .await
+                else {
+                    continue;
+                };
+                let db_chapters =
+                    ChapterRepository::list_by_content(&self.pool, content.id).await?;
+
+                let known: HashSet<&str> =
+                    db_chapters.iter().map(|c| c.file_path.as_str()).collect();
+                let on_disk: HashSet<&str> = disk_chapters
+                    .iter()
+                    .map(|(_, path, ..)| path.as_str())
+                    .collect();
+                let added_chapters: Vec<String> = disk_chapters
+                    .iter()
+                    .filter(|(_, path, ..)| !known.contains(path.as_str()))
+                    .map(|(title, ..)| title.clone())
+                    .collect();
+                let removed_chapters: Vec<String> = db_chapters
+                    .iter()
+                    .filter(|c| !on_disk.contains(c.file_path.as_str()))
+                    .map(|c| c.title.clone())
+                    .collect();
+
+                let restored = content.missing_since.is_some();
+                if restored || !added_chapters.is_empty() || !removed_chapters.is_empty() {
+                    preview.updated.push(PreviewUpdate {
+                        content_id: content.id,
+                        title: content.title,
+                        path: content.folder_path,
+                        restored,
+                        added_chapters,
+                        removed_chapters,
+                    });
                 }
+            }
+
+            let (moved, new_folders) = if missing.is_empty() || new_folders.is_empty() {
+                (Vec::new(), new_folders)
             } else {
-                // Existing content found, re-scan for chapter changes
-                if let Some(content) = ContentRepository::find_by_folder_path(
-                    &self.pool,
-                    scan_path.library_id,
-                    &folder_path_str,
+                self.find_moved_content(base_path, new_folders, &missing, &ignore)
+                    .await?
+            };
+            let relinked: HashSet<usize> = moved.iter().map(|m| m.missing_idx).collect();
+            for m in moved {
+                let content = &missing[m.missing_idx];
+                preview.moved.push(PreviewMove {
+                    content_id: content.id,
+                    title: m.title,
+                    from: content.folder_path.clone(),
+                    to: m.folder_path.to_string_lossy().to_string(),
+                });
+            }
+
+            for (idx, content) in missing.into_iter().enumerate() {
+                if content.missing_since.is_none() && !relinked.contains(&idx) {
+                    preview.removed.push(PreviewContent {
+                        content_id: Some(content.id),
+                        title: content.title,
+                        path: content.folder_path,
+                        chapter_count: content.chapter_count,
+                    });
+                }
+            }
+
+            for folder_path in new_folders {
+                let Some(title) = Self::content_title(base_path, &folder_path) else {
+                    continue;
+                };
+                let folder = folder_path.clone();
+                let matcher = Arc::clone(&ignore);
+                // Folders without chapters would fail to import
+                let Ok(chapters) = self
+                    .run_guarded(move |guard| Self::detect_chapters(&folder, &matcher, guard))
+                    .await
+                else {
+                    continue;
+                };
+                preview.added.push(PreviewContent {
+                    content_id: None,
+                    title: self.title_normalizer.normalize(&title),
+                    path: folder_path.to_string_lossy().to_string(),
+                    chapter_count: chapters.len() as i32,
+                });
+            }
+        }
+
+        Ok(preview)
+    }
+
+    /// Regenerate the thumbnails of a library's content that are missing or
+    /// were generated with other thumbnail settings.
+    ///
+    /// `on_progress` is called with the number of processed contents after
+    /// each one. A content whose thumbnail cannot be generated keeps its
+    /// current thumbnail.
+    #[instrument(skip(self, on_progress))]
+    pub async fn regenerate_thumbnails(
+        &self,
+        library_id: i64,
+        on_progress: impl Fn(TaskProgress),
+    ) -> Result<ThumbnailResult> {
+        if LibraryRepository::find_by_id(&self.pool, library_id)
+            .await?
+            .is_none()
+        {
+            return Err(AppError::NotFound(
+                t!("library.id_not_found", id = library_id).to_string(),
+            )
+            .with_code(ErrorCode::LibraryNotFound));
+        }
+
+        let spec = self.thumbnail_options().spec();
+        let content_ids =
+            ContentRepository::list_outdated_thumbnails(&self.pool, library_id, &spec).await?;
+        let patterns = IgnorePatternRepository::list_by_library(&self.pool, library_id).await?;
+        let ignore = Arc::new(IgnoreMatcher::new(&patterns));
+
+        let total = content_ids.len() as i32;
+        on_progress(TaskProgress {
+            scanned_paths: 0,
+            total_paths: total,
+        });
+
+        let mut result = ThumbnailResult::default();
+        for (idx, content_id) in content_ids.into_iter().enumerate() {
+            match self.regenerate_thumbnail(content_id, &ignore, &spec).await {
+                Ok(true) => result.regenerated += 1,
+                // Nothing to make a thumbnail from
+                Ok(false) => {}
+                Err(e) => {
+                    warn!(content_id, error = %e, "{}", t!("scan.thumbnail_failed"));
+                    result.failed += 1;
+                }
+            }
+            on_progress(TaskProgress {
+                scanned_paths: idx as i32 + 1,
+                total_paths: total,
+            });
+        }
+
+        info!(
+            library_id,
+            regenerated = result.regenerated,
+            failed = result.failed,
+            "{}",
+            t!("scan.thumbnails_regenerated")
+        );
+        Ok(result)
+    }
+
+    /// Count the pages of a library's chapters that have none, e.g. because
+    /// their file could not be read when it was scanned, and update their
+    /// status.
+    ///
+    /// `on_progress` is called with the number of processed chapters after
+    /// each one. Books without chapters and audiobooks without markers stay
+    /// at zero.
+    #[instrument(skip(self, on_progress))]
+    pub async fn recalculate_page_counts(
+        &self,
+        library_id: i64,
+        on_progress: impl Fn(TaskProgress),
+    ) -> Result<PageCountResult> {
+        if LibraryRepository::find_by_id(&self.pool, library_id)
+            .await?
+            .is_none()
+        {
+            return Err(AppError::NotFound(
+                t!("library.id_not_found", id = library_id).to_string(),
+            )
+            .with_code(ErrorCode::LibraryNotFound));
+        }
+
+        let chapters = ChapterRepository::list_without_pages(&self.pool, library_id).await?;
+        let total = chapters.len() as i32;
+        on_progress(TaskProgress {
+            scanned_paths: 0,
+            total_paths: total,
+        });
+
+        let mut result = PageCountResult::default();
+        for (idx, chapter) in chapters.into_iter().enumerate() {
+            let path = PathBuf::from(&chapter.file_path);
+            let file_type = chapter.file_type.clone();
+            let count = self
+                .run_guarded(move |guard| {
+                    guard.acquire_fs_op();
+                    Ok(Self::count_pages(&path, &file_type))
+                })
+                .await?;
+            ChapterRepository::update_page_count(
+                &self.pool,
+                chapter.id,
+                count.pages,
+                count.status,
+                count.error.as_deref(),
+            )
+            .await?;
+
+            if count.status != ChapterStatus::Ready {
+                result.failed += 1;
+            } else if count.pages > 0 {
+                result.updated += 1;
+            }
+            on_progress(TaskProgress {
+                scanned_paths: idx as i32 + 1,
+                total_paths: total,
+            });
+        }
+
+        info!(
+            library_id,
+            updated = result.updated,
+            failed = result.failed,
+            "{}",
+            t!("scan.page_counts_recalculated")
+        );
+        Ok(result)
+    }
+
+    /// Fetch the metadata of a library's content again from its providers.
+    ///
+    /// Content whose metadata names one of the library's providers is
+    /// fetched from it by series ID, other content is scraped by title. New
+    /// metadata replaces the manual edits that are not locked; content whose
+    /// metadata cannot be fetched keeps it and is recorded as a failed
+    /// scrape. `on_progress` is called with the number of processed contents
+    /// after each one.
+    #[instrument(skip(self, on_progress))]
+    pub async fn refresh_metadata(
+        &self,
+        library_id: i64,
+        on_progress: impl Fn(TaskProgress),
+    ) -> Result<MetadataRefreshResult> {
+        if LibraryRepository::find_by_id(&self.pool, library_id)
+            .await?
+            .is_none()
+        {
+            return Err(AppError::NotFound(
+                t!("library.id_not_found", id = library_id).to_string(),
+            )
+            .with_code(ErrorCode::LibraryNotFound));
+        }
+
+        let providers = MetadataProviderRepository::list_by_library(&self.pool, library_id).await?;
+        let manager = match &self.metadata_manager {
+            Some(manager) if !providers.is_empty() => manager,
+            _ => {
+                return Err(AppError::BadRequest(
+                    t!("metadata.no_providers").to_string(),
+                ));
+            }
+        };
+
+        let contents = ContentRepository::list_by_library(&self.pool, library_id).await?;
+        let total = contents.len() as i32;
+        on_progress(TaskProgress {
+            scanned_paths: 0,
+            total_paths: total,
+        });
+
+        let mut result = MetadataRefreshResult::default();
+        for (idx, content) in contents.into_iter().enumerate() {
+            let source = metadata_source(&content).filter(|(kind, _)| providers.contains(kind));
+            let (metadata, error) = match source {
+                Some((kind, id)) => match manager.fetch(kind, &id).await {
+                    Ok(metadata) => (Some(metadata), None),
+                    Err(e) => (None, Some(e.to_string())),
+                },
+                None => {
+                    let title = Self::search_title(&content.title);
+                    let options = ScrapeOptions::default();
+                    Self::auto_scrape_metadata(manager, &providers, title, options).await
+                }
+            };
+
+            match (metadata, error) {
+                (Some(metadata), _) => {
+                    ContentService::update_content(&self.pool, content.id, None, Some(metadata))
+                        .await?;
+                    result.refreshed += 1;
+                }
+                (None, Some(error)) => {
+                    warn!(
+                        content_id = content.id,
+                        error = %error,
+                        "{}",
+                        t!("scan.metadata_refresh_failed")
+                    );
+                    MetadataFailureRepository::record(&self.pool, content.id, &error).await?;
+                    result.failed += 1;
+                }
+                (None, None) => {}
+            }
+            on_progress(TaskProgress {
+                scanned_paths: idx as i32 + 1,
+                total_paths: total,
+            });
+        }
+
+        info!(
+            library_id,
+            refreshed = result.refreshed,
+            failed = result.failed,
+            "{}",
+            t!("scan.metadata_refreshed")
+        );
+        Ok(result)
+    }
+
+    /// Regenerate the thumbnail of one content. Returns false if there was
+    /// nothing to generate it from.
+    async fn regenerate_thumbnail(
+        &self,
+        content_id: i64,
+        ignore: &Arc<IgnoreMatcher>,
+        spec: &str,
+    ) -> Result<bool> {
+        let Some(content) = ContentRepository::find_by_id(&self.pool, content_id).await? else {
+            return Ok(false);
+        };
+        let metadata = content
+            .metadata
+            .as_deref()
+            .and_then(|m| serde_json::from_slice::<serde_json::Value>(m).ok());
+
+        let thumbnail = Self::build_thumbnail(
+            &self.resource_guard,
+            self.thumbnail_options(),
+            metadata.as_ref(),
+            Arc::clone(ignore),
+            Path::new(&content.folder_path),
+        )
+        .await?;
+
+        let Some(thumbnail) = thumbnail else {
+            return Ok(false);
+        };
+        ContentRepository::update_thumbnail(&self.pool, content_id, Some(thumbnail), Some(spec))
+            .await?;
+        Ok(true)
+    }
+
+    /// Delete content that has been in the trash longer than the retention
+    /// period.
+    async fn purge_expired_trash(&self) -> Result<()> {
+        if self.trash_retention_days == 0 {
+            return Ok(());
+        }
+
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(self.trash_retention_days as i64);
+        let purged = ContentRepository::purge_missing(&self.pool, Some(cutoff)).await?;
+        if purged > 0 {
+            info!(count = purged, "{}", t!("scan.trash_purged"));
+        }
+        Ok(())
+    }
+
+    /// Match missing content to new folders holding the same chapters.
+    ///
+    /// A renamed or moved folder shows up as one missing content and one new
+    /// folder. A new folder matches when its chapter fingerprint equals that
+    /// of exactly one missing content. Failing that, it matches when its name
+    /// is the title or an alternative title of exactly one missing content
+    /// that shares a chapter file with it, as when a folder is renamed to
+    /// another name of the series while chapters are added. Returns the
+    /// matches and the new folders left unmatched.
+    async fn find_moved_content(
+        &self,
+        base_path: &Path,
+        new_folders: Vec<PathBuf>,
+        missing: &[Content],
+        ignore: &Arc<IgnoreMatcher>,
+    ) -> Result<(Vec<MovedContent>, Vec<PathBuf>)> {
+        let mut missing_chapters = Vec::with_capacity(missing.len());
+        let mut fingerprints: HashMap<Fingerprint, Vec<usize>> = HashMap::new();
+        for (idx, content) in missing.iter().enumerate() {
+            let chapters = ChapterRepository::list_by_content(&self.pool, content.id).await?;
+            let fingerprint =
+                Self::fingerprint(chapters.iter().map(|c| (c.file_path.as_str(), c.size)));
+            if !fingerprint.is_empty() {
+                fingerprints.entry(fingerprint).or_default().push(idx);
+            }
+            missing_chapters.push(chapters);
+        }
+
+        let ids: Vec<i64> = missing.iter().map(|c| c.id).collect();
+        let aliases = AliasRepository::list_by_contents(&self.pool, &ids).await?;
+        let mut names: HashMap<String, Vec<usize>> = HashMap::new();
+        for (idx, content) in missing.iter().enumerate() {
+            let mut own: Vec<String> = [content.title.as_str(), content.folder_title.as_str()]
+                .into_iter()
+                .chain(
+                    aliases
+                        .get(&content.id)
+                        .into_iter()
+                        .flatten()
+                        .map(|a| a.title.as_str()),
                 )
-                .await?
-                {
-                    match self.rescan_content_chapters(&content, &folder_path).await {
-                        Ok(added_chapters) => {
-                            result.added_chapters.extend(added_chapters);
-                        }
-                        Err(e) => {
-                            error!(folder_path = ?folder_path, error = %e, "{}", t!("scan.rescan_failed"));
-                        }
+                .map(str::to_lowercase)
+                .collect();
+            own.sort();
+            own.dedup();
+            for name in own {
+                names.entry(name).or_default().push(idx);
+            }
+        }
+
+        let mut moved: Vec<MovedContent> = Vec::new();
+        let mut remaining = Vec::new();
+        for folder_path in new_folders {
+            let folder = folder_path.clone();
+            let matcher = Arc::clone(ignore);
+            let entries = self
+                .run_guarded(move |guard| Self::detect_chapters(&folder, &matcher, guard))
+                .await;
+
+            // Fingerprints or names shared by several missing contents are
+            // ambiguous
+            let matched = entries.ok().and_then(|entries| {
+                let fingerprint = Self::fingerprint(
+                    entries
+                        .iter()
+                        .map(|(_, path, _, _, size, ..)| (path.as_str(), *size)),
+                );
+                let (candidates, by_name) = match fingerprints.get(&fingerprint) {
+                    Some(candidates) => (candidates, false),
+                    None => {
+                        let title = Self::content_title(base_path, &folder_path)?;
+                        let normalized = self.title_normalizer.normalize(&title);
+                        let candidates = [title, normalized]
+                            .iter()
+                            .find_map(|name| names.get(&name.to_lowercase()))?;
+                        (candidates, true)
+                    }
+                };
+                match candidates.as_slice() {
+                    &[idx] if !moved.iter().any(|m| m.missing_idx == idx) => {
+                        Some((idx, entries, by_name))
+                    }
+                    _ => None,
+                }
+            });
+            let Some((idx, entries, by_name)) = matched else {
+                remaining.push(folder_path);
+                continue;
+            };
+
+            let content = &missing[idx];
+            let new_paths: HashMap<String, String> = entries
+                .into_iter()
+                .map(|(_, path, ..)| (Self::file_name(&path), path))
+                .collect();
+            let chapter_paths: Vec<(i64, String)> = missing_chapters[idx]
+                .iter()
+                .filter_map(|c| {
+                    let path = new_paths.get(&Self::file_name(&c.file_path))?;
+                    Some((c.id, path.clone()))
+                })
+                .collect();
+            // A folder of the same name holding none of the chapters is
+            // another copy of the series, not the renamed folder
+            if by_name && chapter_paths.is_empty() {
+                remaining.push(folder_path);
+                continue;
+            }
+
+            // Titles still derived from the old folder follow the new one,
+            // edited titles are kept
+            let old_path = Path::new(&content.folder_path);
+            let old_titles = [
+                Self::content_title(base_path, old_path),
+                old_path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .map(str::to_string),
+            ];
+            let derived = old_titles.iter().flatten().any(|old| {
+                *old == content.title || self.title_normalizer.normalize(old) == content.title
+            });
+            let (title, folder_title) = match Self::content_title(base_path, &folder_path) {
+                Some(folder_title) if derived => {
+                    (self.title_normalizer.normalize(&folder_title), folder_title)
+                }
+                Some(folder_title) => (content.title.clone(), folder_title),
+                None => (content.title.clone(), content.folder_title.clone()),
+            };
+
+            moved.push(MovedContent {
+                missing_idx: idx,
+                folder_path,
+                title,
+                folder_title,
+                chapter_paths,
+            });
+        }
+
+        Ok((moved, remaining))
+    }
+
+    /// Point missing content at the folders it was moved to, so reading
+    /// progress and metadata survive instead of the content being deleted
+    /// and imported again.
+    async fn relink_moved_content(
+        &self,
+        moved: &[MovedContent],
+        missing: &[Content],
+        result: &mut ScanResult,
+    ) -> Result<()> {
+        for m in moved {
+            let content = &missing[m.missing_idx];
+            let folder_path_str = m.folder_path.to_string_lossy().to_string();
+            ContentRepository::relocate(
+                &self.pool,
+                content.id,
+                &folder_path_str,
+                &m.title,
+                &m.folder_title,
+                &m.chapter_paths,
+            )
+            .await?;
+            info!(
+                content_id = content.id,
+                from = %content.folder_path,
+                to = %folder_path_str,
+                "{}", t!("scan.content_relinked")
+            );
+
+            result.moved.push(content.id);
+        }
+
+        Ok(())
+    }
+
+    /// Fingerprint of a content's chapters: the file name and size of each
+    /// chapter, sorted. Renaming or moving the content folder keeps it.
+    fn fingerprint<'a>(chapters: impl Iterator<Item = (&'a str, i64)>) -> Fingerprint {
+        let mut fingerprint: Fingerprint = chapters
+            .map(|(path, size)| (Self::file_name(path), size))
+            .collect();
+        fingerprint.sort();
+        fingerprint
+    }
+
+    /// Last component of a path, or the whole path if it has none.
+    fn file_name(path: &str) -> String {
+        Path::new(path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string())
+    }
+
+    /// Inspect new content folders with bounded concurrency.
+    ///
+    /// Archive work runs on blocking threads. Folders that fail are logged and
+    /// skipped; the rest are returned in discovery order.
+    async fn prepare_folders(
+        &self,
+        base_path: &Path,
+        folders: Vec<PathBuf>,
+        ignore: &Arc<IgnoreMatcher>,
+        providers: &Arc<[MetadataProviderKind]>,
+    ) -> (Vec<PreparedFolder>, Vec<(PathBuf, AppError)>) {
+        let semaphore = Arc::new(Semaphore::new(self.import_concurrency));
+        let mut tasks = JoinSet::new();
+
+        for (idx, folder_path) in folders.into_iter().enumerate() {
+            let semaphore = Arc::clone(&semaphore);
+            let guard = Arc::clone(&self.resource_guard);
+            let metadata = self
+                .metadata_manager
+                .clone()
+                .map(|m| (m, Arc::clone(providers)));
+            let ignore = Arc::clone(ignore);
+            let thumbnails = self.thumbnail_options();
+            let titles = Self::content_title(base_path, &folder_path)
+                .map(|folder_title| (self.title_normalizer.normalize(&folder_title), folder_title));
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                let prepared = match titles {
+                    Some(titles) => {
+                        Self::prepare_folder(
+                            guard,
+                            metadata,
+                            ignore,
+                            thumbnails,
+                            titles,
+                            &folder_path,
+                        )
+                        .await
+                    }
+                    None => Err(AppError::BadRequest(
+                        t!("scan.invalid_folder_name").to_string(),
+                    )),
+                };
+                (idx, folder_path, prepared)
+            });
+        }
+
+        let mut prepared = Vec::new();
+        let mut failed = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok((idx, _, Ok(folder))) => prepared.push((idx, folder)),
+                Ok((_, folder_path, Err(e))) => {
+                    // Log error but continue scanning
+                    error!(folder_path = ?folder_path, error = %e, "{}", t!("scan.import_failed"));
+                    failed.push((folder_path, e));
+                }
+                Err(e) => {
+                    error!(error = %e, "{}", t!("scan.import_failed"));
+                }
+            }
+        }
+
+        prepared.sort_by_key(|(idx, _)| *idx);
+        let prepared = prepared.into_iter().map(|(_, folder)| folder).collect();
+        (prepared, failed)
+    }
+
+    /// Inspect a new content folder: detect chapters, scrape metadata and
+    /// build the thumbnail.
+    async fn prepare_folder(
+        guard: Arc<ScanResourceGuard>,
+        metadata: Option<(Arc<MetadataManager>, Arc<[MetadataProviderKind]>)>,
+        ignore: Arc<IgnoreMatcher>,
+        thumbnails: ThumbnailOptions,
+        (title, folder_title): (String, String),
+        folder_path: &Path,
+    ) -> Result<PreparedFolder> {
+        // Detect chapters in the folder
+        let folder = folder_path.to_path_buf();
+        let inner = Arc::clone(&guard);
+        let matcher = Arc::clone(&ignore);
+        let (chapters, suspicious_archives) = guard
+            .spawn_blocking(move || Self::detect_chapters_audited(&folder, &matcher, &inner))
+            .await?;
+
+        // Auto-scrape metadata from the library's providers by the cleaned title
+        let (metadata, scrape_error) = match &metadata {
+            Some((manager, providers)) => {
+                let title = Self::search_title(&title);
+                Self::auto_scrape_metadata(manager, providers, title, ScrapeOptions::default())
+                    .await
+            }
+            None => (None, None),
+        };
+
+        let thumbnail =
+            Self::build_thumbnail(&guard, thumbnails, metadata.as_ref(), ignore, folder_path)
+                .await?;
+
+        Ok(PreparedFolder {
+            title,
+            folder_title,
+            folder_path: folder_path.to_path_buf(),
+            chapters,
+            metadata,
+            thumbnail_spec: thumbnail.as_ref().map(|_| thumbnails.spec()),
+            thumbnail,
+            scrape_error,
+            suspicious_archives,
+        })
+    }
+
+    /// Generate the thumbnail of a content, preferring the cover image from
+    /// its metadata and falling back to its folder if the cover cannot be
+    /// downloaded.
+    async fn build_thumbnail(
+        guard: &Arc<ScanResourceGuard>,
+        thumbnails: ThumbnailOptions,
+        metadata: Option<&serde_json::Value>,
+        ignore: Arc<IgnoreMatcher>,
+        folder_path: &Path,
+    ) -> Result<Option<Vec<u8>>> {
+        let cover_url = metadata
+            .and_then(|m| m.get("images"))
+            .and_then(|v| v.get("common"))
+            .and_then(|s| s.as_str());
+        if let Some(cover_url) = cover_url {
+            let cover = match crate::utils::download_image(cover_url).await {
+                Ok(cover) => {
+                    guard
+                        .spawn_blocking(move || thumbnails.encode(&cover))
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+            match cover {
+                Ok(cover) => return Ok(Some(cover)),
+                Err(e) => warn!(url = cover_url, error = %e, "{}", t!("scan.cover_failed")),
+            }
+        }
+
+        let folder = folder_path.to_path_buf();
+        let inner = Arc::clone(guard);
+        guard
+            .spawn_blocking(move || Self::generate_thumbnail(&folder, &ignore, &inner, thumbnails))
+            .await
+    }
+
+    /// Write prepared folders to the database in one transaction.
+    ///
+    /// If the batch fails, each folder is retried on its own so one bad
+    /// folder does not drop the rest.
+    async fn import_batch(
+        &self,
+        scan_path: &ScanPath,
+        batch: &[PreparedFolder],
+        result: &mut ScanResult,
+    ) {
+        if batch.is_empty() {
+            return;
+        }
+
+        let items: Vec<_> = batch
+            .iter()
+            .map(|folder| Self::new_content_with_chapters(scan_path, folder))
+            .collect();
+
+        let error = match ContentRepository::create_batch_with_chapters(&self.pool, items).await {
+            Ok(contents) => {
+                for (content, folder) in contents.into_iter().zip(batch) {
+                    Self::record_import(content, folder, result);
+                }
+                return;
+            }
+            Err(e) => e,
+        };
+
+        if let [folder] = batch {
+            let folder_path = &folder.folder_path;
+            error!(folder_path = ?folder_path, error = %error, "{}", t!("scan.import_failed"));
+            result
+                .folder_errors
+                .push(Self::folder_error(scan_path, folder_path, &error));
+            return;
+        }
+
+        warn!(error = %error, "{}", t!("scan.import_batch_failed"));
+        for folder in batch {
+            let item = vec![Self::new_content_with_chapters(scan_path, folder)];
+            match ContentRepository::create_batch_with_chapters(&self.pool, item).await {
+                Ok(contents) => {
+                    for content in contents {
+                        Self::record_import(content, folder, result);
+                    }
+                }
+                Err(e) => {
+                    let folder_path = &folder.folder_path;
+                    error!(folder_path = ?folder_path, error = %e, "{}", t!("scan.import_failed"));
+                    result
+                        .folder_errors
+                        .push(Self::folder_error(scan_path, folder_path, &e));
+                }
+            }
+        }
+    }
+
+    /// Record that a folder of a scan path could not be imported.
+    fn folder_error(scan_path: &ScanPath, folder_path: &Path, error: &AppError) -> FolderError {
+        FolderError {
+            scan_path_id: scan_path.id,
+            path: folder_path.to_string_lossy().to_string(),
+            error: error.to_string(),
+        }
+    }
+
+    /// Build the content and chapter records for a prepared folder.
+    fn new_content_with_chapters(
+        scan_path: &ScanPath,
+        folder: &PreparedFolder,
+    ) -> (NewContent, Vec<NewChapter>) {
+        let new_content = NewContent {
+            library_id: scan_path.library_id,
+            scan_path_id: scan_path.id,
+            title: folder.title.clone(),
+            folder_title: folder.folder_title.clone(),
+            folder_path: folder.folder_path.to_string_lossy().to_string(),
+            chapter_count: folder.chapters.len() as i32,
+            thumbnail: folder.thumbnail.clone(),
+            thumbnail_spec: folder.thumbnail_spec.clone(),
+            metadata: folder.metadata.clone(),
+        };
+
+        // content_id is filled in by the repository
+        let new_chapters = folder
+            .chapters
+            .iter()
+            .enumerate()
+            .map(
+                |(idx, (chapter_title, file_path, file_type, page_count, size, status, error))| {
+                    let numbering = ChapterNumbering::parse(chapter_title);
+                    NewChapter {
+                        content_id: 0,
+                        title: chapter_title.clone(),
+                        file_path: file_path.clone(),
+                        file_type: file_type.clone(),
+                        sort_order: idx as i32,
+                        page_count: *page_count,
+                        size: *size,
+                        volume: numbering.volume,
+                        number: numbering.number,
+                        status: *status,
+                        error: error.clone(),
                     }
+                },
+            )
+            .collect();
+
+        (new_content, new_chapters)
+    }
+
+    /// Add an imported content and its chapters to the scan result.
+    fn record_import(content: Content, folder: &PreparedFolder, result: &mut ScanResult) {
+        result
+            .added_chapters
+            .extend(folder.chapters.iter().map(|(title, file_path, ..)| {
+                crate::models::AddedChapter {
+                    content_name: content.title.clone(),
+                    chapter_name: title.clone(),
+                    path: file_path.clone(),
                 }
-            }
+            }));
+        if let Some(error_msg) = &folder.scrape_error {
+            // Content was imported but metadata scraping failed
+            result
+                .failed_scrape
+                .push((content.clone(), error_msg.clone()));
         }
-
-        Ok(result)
+        result
+            .suspicious_archives
+            .extend(folder.suspicious_archives.iter().cloned());
+        result.added.push(content);
     }
 
     /// Discover content folders within a scan path.
-    /// Content folders are immediate subdirectories that contain archive files.
-    fn discover_content_folders(&self, base_path: &Path) -> Result<Vec<PathBuf>> {
+    ///
+    /// Content folders are subdirectories up to `max_depth` levels below the
+    /// scan path that directly contain archive files; with a depth of 1 only
+    /// immediate subdirectories are considered. A folder whose archives all
+    /// sit in subfolders is only used for grouping, so for
+    /// `Author/Series/Volume.cbz` the content folder is `Author/Series`.
+    /// Folders and files matching `ignore` are skipped.
+    fn discover_content_folders(
+        base_path: &Path,
+        max_depth: i32,
+        ignore: &IgnoreMatcher,
+        guard: &ScanResourceGuard,
+    ) -> Result<Vec<PathBuf>> {
         let mut content_folders = Vec::new();
+        Self::collect_content_folders(
+            base_path,
+            max_depth.max(1),
+            ignore,
+            guard,
+            &mut content_folders,
+        )?;
+
+        // Sort folders by their path below the scan path using natural sort
+        content_folders.sort_by_key(|p| {
+            p.strip_prefix(base_path)
+                .unwrap_or(p)
+                .components()
+                .map(|c| natural_sort_key(&c.as_os_str().to_string_lossy()))
+                .collect::<Vec<_>>()
+        });
+
+        Ok(content_folders)
+    }
+
+    /// Discover chapter files lying directly in a scan path; each one is
+    /// imported as a single-chapter content.
+    fn discover_standalone_archives(
+        base_path: &Path,
+        ignore: &IgnoreMatcher,
+        guard: &ScanResourceGuard,
+    ) -> Result<Vec<PathBuf>> {
+        guard.acquire_fs_op();
+        let mut archives: Vec<PathBuf> = std::fs::read_dir(base_path)?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.is_file() && !ignore.is_ignored_path(p) && Self::is_chapter(p))
+            .collect();
+
+        archives.sort_by_key(|p| {
+            natural_sort_key(&p.file_name().unwrap_or_default().to_string_lossy())
+        });
 
-        let entries = std::fs::read_dir(base_path)?;
+        Ok(archives)
+    }
+
+    /// Add the content folders below `dir`, descending at most `depth` levels.
+    fn collect_content_folders(
+        dir: &Path,
+        depth: i32,
+        ignore: &IgnoreMatcher,
+        guard: &ScanResourceGuard,
+        content_folders: &mut Vec<PathBuf>,
+    ) -> Result<()> {
+        guard.acquire_fs_op();
+        let entries = std::fs::read_dir(dir)?;
 
         for entry in entries {
             let entry = entry?;
             let path = entry.path();
 
-            if path.is_dir() {
+            if path.is_dir() && !ignore.is_ignored_path(&path) {
                 // Check if this directory contains any supported archive files
-                if self.has_archive_files(&path)? {
-                    content_folders.push(path);
+                guard.acquire_fs_op();
+                if Self::has_archive_files(&path, ignore)? {
+                    content_folders.push(path.clone());
+                }
+                if depth > 1 {
+                    Self::collect_content_folders(
+                        &path,
+                        depth - 1,
+                        ignore,
+                        guard,
+                        content_folders,
+                    )?;
                 }
             }
         }
 
-        // Sort folders by name using natural sort
-        content_folders.sort_by_key(|p| {
-            natural_sort_key(&p.file_name().unwrap_or_default().to_string_lossy())
-        });
+        Ok(())
+    }
 
-        Ok(content_folders)
+    /// Title for a content folder: its path below the scan path, so grouping
+    /// folders stay visible (e.g. `Author / Series`). Standalone archives are
+    /// titled by their file name without the extension.
+    ///
+    /// Requirement 2.4: immediate subdirectories keep their folder name.
+    fn content_title(base_path: &Path, folder_path: &Path) -> Option<String> {
+        if folder_path.is_file() {
+            return folder_path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .map(str::to_string);
+        }
+
+        let parts = folder_path
+            .strip_prefix(base_path)
+            .ok()?
+            .components()
+            .map(|c| c.as_os_str().to_str())
+            .collect::<Option<Vec<_>>>()?;
+        (!parts.is_empty()).then(|| parts.join(" / "))
     }
 
-    /// Check if a directory contains any supported archive files.
-    fn has_archive_files(&self, dir: &Path) -> Result<bool> {
+    /// Check if a directory contains any chapters (supported archive files or
+    /// image folders) that are not ignored.
+    fn has_archive_files(dir: &Path, ignore: &IgnoreMatcher) -> Result<bool> {
         let entries = std::fs::read_dir(dir)?;
 
         for entry in entries {
             let entry = entry?;
             let path = entry.path();
 
-            if path.is_file()
-                && (ArchiveExtractor::is_supported(&path)
-                    || EpubExtractor::is_supported(&path)
-                    || PdfExtractor::is_supported(&path))
-            {
+            if !ignore.is_ignored_path(&path) && Self::is_chapter(&path) {
                 return Ok(true);
             }
         }
@@ -236,95 +1541,20 @@ impl ScanService {
         Ok(false)
     }
 
-    /// Import a content folder into the database.
-    ///
-    /// Returns the imported content and an optional error message if metadata scraping failed.
-    ///
-    async fn import_content_folder(
-        &self,
-        scan_path: &ScanPath,
-        folder_path: &Path,
-    ) -> Result<(Content, Vec<crate::models::AddedChapter>, Option<String>)> {
-        // Derive title from folder name (Requirement 2.4)
-        let title = folder_path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .ok_or_else(|| AppError::BadRequest(t!("scan.invalid_folder_name").to_string()))?
-            .to_string();
-
-        // Detect chapters in the folder
-        let chapters = self.detect_chapters(folder_path)?;
-
-        // Auto-scrape metadata from Bangumi if service is available
-        let (metadata, scrape_error) = self.auto_scrape_metadata(&title).await;
-
-        // Create the content record
-        let new_content = NewContent {
-            library_id: scan_path.library_id,
-            scan_path_id: scan_path.id,
-            title,
-            folder_path: folder_path.to_string_lossy().to_string(),
-            chapter_count: chapters.len() as i32,
-            thumbnail: None,
-            metadata: metadata.clone(),
-        };
-
-        let content = ContentRepository::create(&self.pool, new_content).await?;
-
-        // Create chapter records
-        let new_chapters: Vec<NewChapter> = chapters
-            .into_iter()
-            .enumerate()
-            .map(
-                |(idx, (chapter_title, file_path, file_type, page_count, size))| NewChapter {
-                    content_id: content.id,
-                    title: chapter_title,
-                    file_path,
-                    file_type,
-                    sort_order: idx as i32,
-                    page_count,
-                    size,
-                },
-            )
-            .collect();
-
-        ChapterRepository::create_batch(&self.pool, new_chapters.clone()).await?;
-
-        // Generate thumbnail
-        let thumbnail = if let Some(metadata) = metadata.clone() {
-            // If we have metadata with cover image, use it
-            if let Some(cover_data) = metadata
-                .get("images")
-                .and_then(|v| v.get("common"))
-                .and_then(|s| s.as_str())
-            {
-                crate::utils::download_image(cover_data).await.ok()
-            } else {
-                self.generate_thumbnail(&content, folder_path).await?
-            }
+    /// Whether a path is a chapter: a supported archive, PDF, EPUB, MOBI or
+    /// audiobook file, a text file large enough to be a novel, or a folder
+    /// of images.
+    fn is_chapter(path: &Path) -> bool {
+        if path.is_file() {
+            ArchiveExtractor::is_supported(path)
+                || EpubExtractor::is_supported(path)
+                || MobiExtractor::is_supported(path)
+                || AudioExtractor::is_supported(path)
+                || PdfExtractor::is_supported(path)
+                || TxtExtractor::is_novel(path)
         } else {
-            self.generate_thumbnail(&content, folder_path).await?
-        };
-
-        if let Some(thumb_data) = thumbnail {
-            ContentRepository::update_thumbnail(&self.pool, content.id, Some(thumb_data)).await?;
+            ArchiveExtractor::is_image_folder(path)
         }
-
-        // Fetch the updated content with thumbnail
-        let final_content = ContentRepository::find_by_id(&self.pool, content.id)
-            .await?
-            .ok_or_else(|| AppError::Internal(t!("scan.retrieve_content_failed").to_string()))?;
-
-        let added_chapters = new_chapters
-            .into_iter()
-            .map(|nc| crate::models::AddedChapter {
-                content_name: final_content.title.clone(),
-                chapter_name: nc.title,
-                path: nc.file_path,
-            })
-            .collect();
-
-        Ok((final_content, added_chapters, scrape_error))
     }
 
     /// Rescan existing content to detect added/removed chapters.
@@ -332,9 +1562,14 @@ impl ScanService {
         &self,
         content: &Content,
         folder_path: &Path,
-    ) -> Result<Vec<crate::models::AddedChapter>> {
+        ignore: &Arc<IgnoreMatcher>,
+    ) -> Result<(Vec<crate::models::AddedChapter>, Vec<SuspiciousArchive>)> {
         // Detect chapters on disk
-        let disk_chapters = self.detect_chapters(folder_path)?;
+        let folder = folder_path.to_path_buf();
+        let matcher = Arc::clone(ignore);
+        let (disk_chapters, suspicious_archives) = self
+            .run_guarded(move |guard| Self::detect_chapters_audited(&folder, &matcher, guard))
+            .await?;
         let total_chapters = disk_chapters.len() as i32;
 
         // Get existing chapters from DB
@@ -349,25 +1584,39 @@ impl ScanService {
         let mut new_chapters = Vec::new();
 
         // Iterate over disk chapters
-        for (idx, (title, file_path, file_type, page_count, size)) in
+        for (idx, (title, file_path, file_type, page_count, size, status, error)) in
             disk_chapters.into_iter().enumerate()
         {
             let sort_order = idx as i32;
+            let numbering = ChapterNumbering::parse(&title);
 
             if let Some(existing_chapter) = db_chapters_map.remove(&file_path) {
-                // Check if we need to update sort_order, page_count, size, or file_type
+                // Check if we need to update sort_order, page_count, size, file_type,
+                // the status or the numbering, which chapters imported before it
+                // existed lack
                 if existing_chapter.sort_order != sort_order
                     || existing_chapter.page_count != page_count
                     || existing_chapter.size != size
                     || existing_chapter.file_type != file_type
+                    || existing_chapter.volume != numbering.volume
+                    || existing_chapter.number != numbering.number
+                    || existing_chapter.status != status
+                    || existing_chapter.error != error
                 {
                     sqlx::query(
-                        "UPDATE chapters SET sort_order = ?, page_count = ?, size = ?, file_type = ? WHERE id = ?",
+                        "UPDATE chapters SET sort_order = ?, page_count = ?, size = ?, file_type = ?, volume = ?, number = ?, status = ?, error = ?, updated_at = CASE WHEN size = ? THEN updated_at ELSE ? END, koreader_hash = CASE WHEN size = ? THEN koreader_hash END WHERE id = ?",
                     )
                     .bind(sort_order)
                     .bind(page_count)
                     .bind(size)
                     .bind(&file_type)
+                    .bind(numbering.volume)
+                    .bind(numbering.number)
+                    .bind(status.as_str())
+                    .bind(&error)
+                    .bind(size)
+                    .bind(chrono::Utc::now().to_rfc3339())
+                    .bind(size)
                     .bind(existing_chapter.id)
                     .execute(&self.pool)
                     .await
@@ -383,6 +1632,10 @@ impl ScanService {
                     sort_order,
                     page_count,
                     size,
+                    volume: numbering.volume,
+                    number: numbering.number,
+                    status,
+                    error,
                 });
             }
         }
@@ -396,9 +1649,11 @@ impl ScanService {
                 .map_err(AppError::Database)?;
         }
 
-        // Insert new chapters
+        // Insert new chapters and record them as releases of the content
         if !new_chapters.is_empty() {
-            ChapterRepository::create_batch(&self.pool, new_chapters.clone()).await?;
+            let created = ChapterRepository::create_batch(&self.pool, new_chapters.clone()).await?;
+            let ids: Vec<i64> = created.iter().map(|c| c.id).collect();
+            ReleaseRepository::record(&self.pool, content.id, &ids).await?;
         }
 
         // Update chapter count
@@ -415,32 +1670,41 @@ impl ScanService {
             })
             .collect();
 
-        Ok(added_chapters)
+        Ok((added_chapters, suspicious_archives))
     }
 
-    /// Auto-scrape metadata from Bangumi for a content title.
+    /// Title to search metadata providers with: the folder's own name rather
+    /// than the grouped title.
+    pub(crate) fn search_title(title: &str) -> &str {
+        title.rsplit(" / ").next().unwrap_or(title)
+    }
+
+    /// Auto-scrape metadata for a content title, trying the library's
+    /// providers in order.
     ///
     /// Returns the metadata JSON blob if successful, or None with an error message if failed.
     ///
     /// Requirements: 8.1, 8.2, 8.3
-    async fn auto_scrape_metadata(
-        &self,
+    pub(crate) async fn auto_scrape_metadata(
+        manager: &MetadataManager,
+        providers: &[MetadataProviderKind],
         title: &str,
+        options: ScrapeOptions<'_>,
     ) -> (Option<serde_json::Value>, Option<String>) {
-        let Some(ref bangumi_service) = self.bangumi_service else {
-            // No Bangumi service configured, skip scraping
+        if providers.is_empty() {
+            // Scraping is disabled for the library
             return (None, None);
-        };
+        }
 
-        match bangumi_service.auto_scrape(title).await {
+        match manager.auto_scrape_as(providers, title, options).await {
             Ok(Some(metadata)) => {
                 // Successfully scraped metadata (Requirement 8.2)
                 (Some(metadata), None)
             }
             Ok(None) => {
                 // No results found (Requirement 8.3)
-                let error_msg = t!("scan.bangumi_no_results_msg", title = title).to_string();
-                debug!(title = %title, "{}", t!("scan.bangumi_no_results"));
+                let error_msg = t!("scan.metadata_no_results_msg", title = title).to_string();
+                debug!(title = %title, "{}", t!("scan.metadata_no_results"));
                 (None, Some(error_msg))
             }
             Err(e) => {
@@ -452,24 +1716,40 @@ impl ScanService {
         }
     }
 
-    /// Detect all supported archive files in a folder and return chapter entries.
+    /// Detect all supported archive files and image folders in a folder and
+    /// return chapter entries.
     ///
     /// Each chapter carries its own `file_type` (extension), so mixed folders are supported.
-    fn detect_chapters(&self, folder_path: &Path) -> Result<Vec<ChapterEntry>> {
-        let mut files = Vec::new();
+    /// A standalone archive is returned as its own single chapter.
+    fn detect_chapters(
+        folder_path: &Path,
+        ignore: &IgnoreMatcher,
+        guard: &ScanResourceGuard,
+    ) -> Result<Vec<ChapterEntry>> {
+        Self::detect_chapters_audited(folder_path, ignore, guard).map(|(chapters, _)| chapters)
+    }
 
-        let entries = std::fs::read_dir(folder_path)?;
+    /// Like [`Self::detect_chapters`], also returning the archives with
+    /// suspicious entries, which are left out of their page counts.
+    fn detect_chapters_audited(
+        folder_path: &Path,
+        ignore: &IgnoreMatcher,
+        guard: &ScanResourceGuard,
+    ) -> Result<(Vec<ChapterEntry>, Vec<SuspiciousArchive>)> {
+        let mut files = Vec::new();
 
-        for entry in entries {
-            let entry = entry?;
-            let path = entry.path();
+        guard.acquire_fs_op();
+        if folder_path.is_file() {
+            // A standalone archive is its own single chapter
+            files.push(folder_path.to_path_buf());
+        } else {
+            for entry in std::fs::read_dir(folder_path)? {
+                let entry = entry?;
+                let path = entry.path();
 
-            if path.is_file()
-                && (ArchiveExtractor::is_supported(&path)
-                    || PdfExtractor::is_supported(&path)
-                    || EpubExtractor::is_supported(&path))
-            {
-                files.push(path);
+                if !ignore.is_ignored_path(&path) && Self::is_chapter(&path) {
+                    files.push(path);
+                }
             }
         }
 
@@ -486,93 +1766,194 @@ impl ScanService {
 
         // Create chapter entries with per-file type detection
         let mut chapters: Vec<ChapterEntry> = Vec::with_capacity(files.len());
+        let mut suspicious_archives = Vec::new();
 
         for path in files {
-            let title = path
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("Unknown")
-                .to_string();
+            // Image folders keep their full name, dots included
+            let is_folder = path.is_dir();
+            let title = if is_folder {
+                path.file_name()
+            } else {
+                path.file_stem()
+            }
+            .and_then(|s| s.to_str())
+            .unwrap_or("Unknown")
+            .to_string();
             let file_path = path.to_string_lossy().to_string();
-            let file_type = file_type_from_path(&path);
-
-            // Calculate page count based on file type
-            let page_count = if EpubExtractor::is_supported(&path) {
-                match EpubExtractor::chapter_count(&path) {
-                    Ok(count) => count as i32,
-                    Err(e) => {
-                        warn!(path = ?path, error = %e, "{}", t!("scan.calc_novel_chapter_count_failed"));
-                        0
-                    }
-                }
-            } else if PdfExtractor::is_supported(&path) {
-                match PdfExtractor::page_count(&path) {
-                    Ok(count) => count as i32,
-                    Err(e) => {
-                        warn!(path = ?path, error = %e, "{}", t!("scan.calc_comic_page_count_failed"));
-                        0
-                    }
-                }
+            let file_type = if is_folder {
+                IMAGE_FOLDER_FILE_TYPE.to_string()
             } else {
-                match ArchiveExtractor::page_count(&path) {
-                    Ok(count) => count as i32,
-                    Err(e) => {
-                        warn!(path = ?path, error = %e, "{}", t!("scan.calc_comic_page_count_failed"));
-                        0
-                    }
-                }
+                file_type_from_path(&path)
             };
 
+            // Opening each archive is the expensive part of a scan
+            guard.acquire_fs_op();
+
+            let PageCount {
+                pages: page_count,
+                status,
+                error,
+                suspicious,
+            } = Self::count_pages(&path, &file_type);
+            if !suspicious.is_empty() {
+                warn!(
+                    path = ?path,
+                    entries = ?suspicious,
+                    "{}",
+                    t!("scan.suspicious_archive")
+                );
+                suspicious_archives.push(SuspiciousArchive {
+                    path: file_path.clone(),
+                    entries: suspicious,
+                });
+            }
+
             // Calculate file size
-            let size = match std::fs::metadata(&path) {
-                Ok(metadata) => metadata.len() as i64,
+            let size = if is_folder {
+                ArchiveExtractor::folder_size(&path)
+            } else {
+                std::fs::metadata(&path)
+                    .map(|m| m.len())
+                    .map_err(AppError::from)
+            };
+            let size = match size {
+                Ok(size) => size as i64,
                 Err(e) => {
                     warn!(path = ?path, error = %e, "{}", t!("scan.get_file_metadata_failed"));
                     0
                 }
             };
 
-            chapters.push((title, file_path, file_type, page_count, size));
+            chapters.push((title, file_path, file_type, page_count, size, status, error));
         }
 
-        Ok(chapters)
+        Ok((chapters, suspicious_archives))
+    }
+
+    /// Count the pages of a chapter file, chapters for books and markers for
+    /// audiobooks.
+    ///
+    /// Chapters that cannot be read are kept, so they can be found and fixed,
+    /// but count no pages.
+    fn count_pages(path: &Path, file_type: &str) -> PageCount {
+        let mut suspicious = Vec::new();
+        let (count, failure_key) = if TxtExtractor::is_supported(path) {
+            (
+                TxtExtractor::chapter_count(path),
+                "scan.calc_novel_chapter_count_failed",
+            )
+        } else if EpubExtractor::is_supported(path) {
+            (
+                EpubExtractor::chapter_count(path),
+                "scan.calc_novel_chapter_count_failed",
+            )
+        } else if MobiExtractor::is_supported(path) {
+            (
+                MobiExtractor::chapter_count(path),
+                "scan.calc_novel_chapter_count_failed",
+            )
+        } else if AudioExtractor::is_supported(path) {
+            // Audiobooks are "paged" by their chapter markers
+            (
+                AudioExtractor::chapter_count(path),
+                "scan.calc_audio_marker_count_failed",
+            )
+        } else if PdfExtractor::is_supported(path) {
+            (
+                PdfExtractor::page_count(path),
+                "scan.calc_comic_page_count_failed",
+            )
+        } else {
+            let count = ArchiveExtractor::entries(path).map(|entries| {
+                suspicious = entries.suspicious;
+                entries.files.len()
+            });
+            (count, "scan.calc_comic_page_count_failed")
+        };
+
+        let (pages, status, error) = match count {
+            Ok(0) if is_image_file_type(file_type) => (
+                0,
+                ChapterStatus::Error,
+                Some(t!("scan.chapter_without_pages").to_string()),
+            ),
+            Ok(count) => (count as i32, ChapterStatus::Ready, None),
+            Err(e) => {
+                warn!(path = ?path, error = %e, "{}", t!(failure_key));
+                let status = if e.code() == ErrorCode::UnsupportedFormat {
+                    ChapterStatus::Unsupported
+                } else {
+                    ChapterStatus::Error
+                };
+                (0, status, Some(e.to_string()))
+            }
+        };
+        PageCount {
+            pages,
+            status,
+            error,
+            suspicious,
+        }
     }
 
     /// Generate a thumbnail for content.
     ///
     /// Determines the thumbnail strategy based on the first chapter's file type.
-    async fn generate_thumbnail(
-        &self,
-        _content: &Content,
+    fn generate_thumbnail(
         folder_path: &Path,
+        ignore: &IgnoreMatcher,
+        guard: &ScanResourceGuard,
+        thumbnails: ThumbnailOptions,
     ) -> Result<Option<Vec<u8>>> {
-        // Check if there are any epub files (try novel thumbnail first for epub content)
-        let has_epub = std::fs::read_dir(folder_path)?
-            .filter_map(|e| e.ok())
-            .any(|e| EpubExtractor::is_supported(&e.path()));
+        guard.acquire_fs_op();
+
+        if folder_path.is_file() {
+            return Self::generate_archive_thumbnail(folder_path, thumbnails);
+        }
 
-        if has_epub {
-            // Try novel thumbnail (cover image or epub embedded cover)
-            if let Ok(Some(thumb)) = self.generate_novel_thumbnail(folder_path) {
+        // Check if there are any ebook or audiobook files (try novel thumbnail first for them)
+        let has_ebook = std::fs::read_dir(folder_path)?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .any(|p| {
+                (EpubExtractor::is_supported(&p)
+                    || MobiExtractor::is_supported(&p)
+                    || AudioExtractor::is_supported(&p))
+                    && !ignore.is_ignored_path(&p)
+            });
+
+        if has_ebook {
+            // Try novel thumbnail (cover image or embedded ebook/audiobook cover)
+            if let Ok(Some(thumb)) = Self::generate_novel_thumbnail(folder_path, ignore, thumbnails)
+            {
                 return Ok(Some(thumb));
             }
         }
 
         // Fall back to comic thumbnail (first page of first archive/pdf)
-        self.generate_comic_thumbnail(folder_path)
+        Self::generate_comic_thumbnail(folder_path, ignore, thumbnails)
     }
 
     /// Generate thumbnail for comics from the first page of the first chapter.
     ///
     /// Requirements: 2.5
-    fn generate_comic_thumbnail(&self, folder_path: &Path) -> Result<Option<Vec<u8>>> {
-        // Find the first comic archive file
+    fn generate_comic_thumbnail(
+        folder_path: &Path,
+        ignore: &IgnoreMatcher,
+        thumbnails: ThumbnailOptions,
+    ) -> Result<Option<Vec<u8>>> {
+        // Find the first comic archive file or image folder
         let entries = std::fs::read_dir(folder_path)?;
         let mut comic_files: Vec<PathBuf> = entries
             .filter_map(|e| e.ok())
             .map(|e| e.path())
             .filter(|p| {
-                p.is_file() && (ArchiveExtractor::is_supported(p) || PdfExtractor::is_supported(p))
+                !ignore.is_ignored_path(p)
+                    && if p.is_file() {
+                        ArchiveExtractor::is_supported(p) || PdfExtractor::is_supported(p)
+                    } else {
+                        ArchiveExtractor::is_image_folder(p)
+                    }
             })
             .collect();
 
@@ -595,15 +1976,49 @@ impl ScanService {
         };
 
         // Resize and compress the thumbnail
-        let thumbnail = self.compress_thumbnail(&image_data)?;
+        let thumbnail = thumbnails.encode(&image_data)?;
 
         Ok(Some(thumbnail))
     }
 
+    /// Generate a thumbnail for a standalone archive from its embedded EPUB,
+    /// MOBI or audiobook cover or its first page.
+    fn generate_archive_thumbnail(
+        archive_path: &Path,
+        thumbnails: ThumbnailOptions,
+    ) -> Result<Option<Vec<u8>>> {
+        let image_data = if EpubExtractor::is_supported(archive_path) {
+            match Self::extract_epub_cover(archive_path) {
+                Ok(Some(cover)) => cover,
+                _ => return Ok(None),
+            }
+        } else if MobiExtractor::is_supported(archive_path) {
+            match MobiExtractor::extract_cover(archive_path) {
+                Ok(Some(cover)) => cover,
+                _ => return Ok(None),
+            }
+        } else if AudioExtractor::is_supported(archive_path) {
+            match AudioExtractor::extract_cover(archive_path) {
+                Ok(Some(cover)) => cover,
+                _ => return Ok(None),
+            }
+        } else if PdfExtractor::is_supported(archive_path) {
+            PdfExtractor::extract_first_image(archive_path)?
+        } else {
+            ArchiveExtractor::extract_first_image(archive_path)?
+        };
+
+        Ok(Some(thumbnails.encode(&image_data)?))
+    }
+
     /// Generate default thumbnail for novels.
     ///
     /// Requirements: 2.6
-    fn generate_novel_thumbnail(&self, folder_path: &Path) -> Result<Option<Vec<u8>>> {
+    fn generate_novel_thumbnail(
+        folder_path: &Path,
+        ignore: &IgnoreMatcher,
+        thumbnails: ThumbnailOptions,
+    ) -> Result<Option<Vec<u8>>> {
         // Check if there's a cover image in the folder
         let cover_names = ["cover.jpg", "cover.jpeg", "cover.png", "cover.webp"];
 
@@ -611,26 +2026,45 @@ impl ScanService {
             let cover_path = folder_path.join(cover_name);
             if cover_path.exists() {
                 let image_data = std::fs::read(&cover_path)?;
-                let thumbnail = self.compress_thumbnail(&image_data)?;
+                let thumbnail = thumbnails.encode(&image_data)?;
                 return Ok(Some(thumbnail));
             }
         }
 
-        // Check for EPUB files which might have embedded covers
+        // Check for EPUB, MOBI and audiobook files which might have embedded covers
         let entries = std::fs::read_dir(folder_path)?;
         for entry in entries {
             let entry = entry?;
             let path = entry.path();
 
             let is_epub = path.is_file()
+                && !ignore.is_ignored_path(&path)
                 && path
                     .extension()
                     .and_then(|e| e.to_str())
                     .map(|ext| ext.to_lowercase() == "epub")
                     .unwrap_or(false);
 
-            if let (true, Ok(Some(cover))) = (is_epub, self.extract_epub_cover(&path)) {
-                let thumbnail = self.compress_thumbnail(&cover)?;
+            if let (true, Ok(Some(cover))) = (is_epub, Self::extract_epub_cover(&path)) {
+                let thumbnail = thumbnails.encode(&cover)?;
+                return Ok(Some(thumbnail));
+            }
+
+            let is_mobi = path.is_file()
+                && !ignore.is_ignored_path(&path)
+                && MobiExtractor::is_supported(&path);
+
+            if is_mobi && let Ok(Some(cover)) = MobiExtractor::extract_cover(&path) {
+                let thumbnail = thumbnails.encode(&cover)?;
+                return Ok(Some(thumbnail));
+            }
+
+            let is_audio = path.is_file()
+                && !ignore.is_ignored_path(&path)
+                && AudioExtractor::is_supported(&path);
+
+            if is_audio && let Ok(Some(cover)) = AudioExtractor::extract_cover(&path) {
+                let thumbnail = thumbnails.encode(&cover)?;
                 return Ok(Some(thumbnail));
             }
         }
@@ -640,7 +2074,7 @@ impl ScanService {
     }
 
     /// Extract cover image from an EPUB file.
-    fn extract_epub_cover(&self, epub_path: &Path) -> Result<Option<Vec<u8>>> {
+    fn extract_epub_cover(epub_path: &Path) -> Result<Option<Vec<u8>>> {
         let mut doc = epub::doc::EpubDoc::new(epub_path)
             .map_err(|e| AppError::Archive(t!("scan.epub_open_failed", error = e).to_string()))?;
 
@@ -651,37 +2085,6 @@ impl ScanService {
 
         Ok(None)
     }
-
-    /// Compress and resize an image for use as a thumbnail.
-    fn compress_thumbnail(&self, image_data: &[u8]) -> Result<Vec<u8>> {
-        use image::ImageReader;
-        use std::io::Cursor;
-
-        // Load the image
-        let img = ImageReader::new(Cursor::new(image_data))
-            .with_guessed_format()
-            .map_err(|e| {
-                AppError::Internal(t!("scan.read_image_format_failed", error = e).to_string())
-            })?
-            .decode()
-            .map_err(|e| {
-                AppError::Internal(t!("scan.decode_image_failed", error = e).to_string())
-            })?;
-
-        // Resize to thumbnail size (max 300px width, maintaining aspect ratio)
-        let thumbnail = img.thumbnail(300, 450);
-
-        // Encode as JPEG with quality 80
-        let mut buffer = Vec::new();
-        let mut cursor = Cursor::new(&mut buffer);
-        thumbnail
-            .write_to(&mut cursor, image::ImageFormat::Jpeg)
-            .map_err(|e| {
-                AppError::Internal(t!("scan.encode_thumbnail_failed", error = e).to_string())
-            })?;
-
-        Ok(buffer)
-    }
 }
 
 // ============================================================================
@@ -692,9 +2095,10 @@ impl ScanService {
 ///
 /// The `ScanQueueService` provides:
 /// - Task submission with automatic deduplication
-/// - Priority-based task ordering (High > Normal)
+/// - Priority-based task ordering (High > Normal > Low)
 /// - Task status tracking and querying
-/// - Task cancellation
+/// - Task cancellation, and clearing the whole queue
+/// - Pausing and resuming the workers
 /// - History retention for completed tasks
 /// - Background worker for processing tasks
 ///
@@ -704,105 +2108,248 @@ pub struct ScanQueueService {
     pending_queue: Arc<RwLock<BinaryHeap<QueuedTask>>>,
     /// All tasks indexed by task ID (includes history).
     tasks: Arc<RwLock<HashMap<Uuid, ScanTask>>>,
-    /// Mapping from library ID to active task ID for deduplication.
+    /// Mapping from scan target to active task ID for deduplication.
     /// Only contains pending or running tasks.
-    library_tasks: Arc<RwLock<HashMap<i64, Uuid>>>,
-    /// Scan service for executing scans.
-    scan_service: Option<Arc<ScanService>>,
+    library_tasks: Arc<RwLock<HashMap<TaskKey, Uuid>>>,
+    /// Executors running the tasks of each kind.
+    executors: Arc<ExecutorRegistry>,
     /// Broadcast sender for shutdown signal.
     shutdown_tx: broadcast::Sender<()>,
     /// Worker task handle.
     worker_handle: Arc<RwLock<Option<JoinHandle<()>>>>,
     /// Notify channel to wake up worker when new tasks are added.
     task_notify: Arc<tokio::sync::Notify>,
+    /// Number of workers processing tasks concurrently.
+    worker_count: AtomicUsize,
+    /// Tasks whose scan was cut short by shutdown, to be run again on restart.
+    interrupted: Arc<RwLock<HashSet<Uuid>>>,
+    /// Whether workers stop starting pending tasks.
+    paused: Arc<AtomicBool>,
+    /// How long and how many finished tasks stay in the history.
+    history_retention: std::sync::RwLock<HistoryRetention>,
+}
+
+/// Limits of the task history; finished tasks past either are removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryRetention {
+    /// Hours a finished task stays in the history (0 = no age limit).
+    pub max_age_hours: u32,
+    /// Number of finished tasks kept (0 = no count limit).
+    pub max_entries: usize,
+}
+
+impl Default for HistoryRetention {
+    fn default() -> Self {
+        Self {
+            max_age_hours: DEFAULT_HISTORY_RETENTION_HOURS,
+            max_entries: DEFAULT_HISTORY_MAX_ENTRIES,
+        }
+    }
+}
+
+impl HistoryRetention {
+    /// Remove the finished tasks of `tasks` past the limits, except those in
+    /// `keep`, returning how many were removed.
+    fn prune(&self, tasks: &mut HashMap<Uuid, ScanTask>, keep: &HashSet<Uuid>) -> usize {
+        let mut finished: Vec<(chrono::DateTime<chrono::Utc>, Uuid)> = tasks
+            .values()
+            .filter(|task| is_finished(task) && !keep.contains(&task.id))
+            .map(|task| (task.completed_at.unwrap_or(task.created_at), task.id))
+            .collect();
+        // Most recently finished first
+        finished.sort_by(|a, b| b.cmp(a));
+
+        let cutoff = (self.max_age_hours > 0)
+            .then(|| chrono::Utc::now() - chrono::Duration::hours(self.max_age_hours as i64));
+        let mut removed = 0;
+        for (i, (finished_at, id)) in finished.into_iter().enumerate() {
+            let too_many = self.max_entries > 0 && i >= self.max_entries;
+            let too_old = cutoff.is_some_and(|cutoff| finished_at < cutoff);
+            if too_many || too_old {
+                tasks.remove(&id);
+                removed += 1;
+            }
+        }
+        removed
+    }
+}
+
+/// Whether a task completed, failed or was cancelled.
+fn is_finished(task: &ScanTask) -> bool {
+    matches!(
+        task.status,
+        TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Cancelled
+    )
 }
 
 impl ScanQueueService {
-    /// Creates a new scan queue service without a scan service.
+    /// Creates a new scan queue service without executors.
     ///
-    /// This constructor is useful for testing or when the scan service
-    /// will be set later. The worker will not be started.
+    /// This constructor is useful for testing or when tasks only need to be
+    /// tracked. The worker will not be started.
     ///
     /// Requirements: 1.2
     pub fn new() -> Self {
-        let (shutdown_tx, _) = broadcast::channel(1);
-        Self {
-            pending_queue: Arc::new(RwLock::new(BinaryHeap::new())),
-            tasks: Arc::new(RwLock::new(HashMap::new())),
-            library_tasks: Arc::new(RwLock::new(HashMap::new())),
-            scan_service: None,
-            shutdown_tx,
-            worker_handle: Arc::new(RwLock::new(None)),
-            task_notify: Arc::new(tokio::sync::Notify::new()),
-        }
+        Self::with_executors(ExecutorRegistry::new())
     }
 
-    /// Creates a new scan queue service with a scan service and starts the worker.
+    /// Creates a new scan queue service running the tasks of the scan
+    /// service: scans, thumbnails, page counts and metadata refreshes.
     ///
     /// The worker will process tasks from the queue in priority order.
     ///
     /// Requirements: 1.2, 1.3
     pub fn with_scan_service(scan_service: Arc<ScanService>) -> Self {
+        Self::with_executors(ExecutorRegistry::with_scan_service(scan_service))
+    }
+
+    /// Creates a new scan queue service running each task with the executor
+    /// registered for its kind.
+    pub fn with_executors(executors: ExecutorRegistry) -> Self {
         let (shutdown_tx, _) = broadcast::channel(1);
         Self {
             pending_queue: Arc::new(RwLock::new(BinaryHeap::new())),
             tasks: Arc::new(RwLock::new(HashMap::new())),
             library_tasks: Arc::new(RwLock::new(HashMap::new())),
-            scan_service: Some(scan_service),
+            executors: Arc::new(executors),
             shutdown_tx,
             worker_handle: Arc::new(RwLock::new(None)),
             task_notify: Arc::new(tokio::sync::Notify::new()),
+            worker_count: AtomicUsize::new(1),
+            interrupted: Arc::new(RwLock::new(HashSet::new())),
+            paused: Arc::new(AtomicBool::new(false)),
+            history_retention: std::sync::RwLock::new(HistoryRetention::default()),
         }
     }
 
-    /// Starts the background worker that processes tasks from the queue.
+    /// Set how long and how many finished tasks stay in the history.
+    pub fn set_history_retention(&self, retention: HistoryRetention) {
+        *self
+            .history_retention
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = retention;
+    }
+
+    fn history_retention(&self) -> HistoryRetention {
+        *self
+            .history_retention
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Set the number of scans that may run at the same time (minimum 1).
+    ///
+    /// Takes effect when the worker is started.
+    pub fn set_worker_count(&self, worker_count: usize) {
+        self.worker_count
+            .store(worker_count.max(1), Ordering::Relaxed);
+    }
+
+    /// Starts the background workers that process tasks from the queue.
     ///
     /// This should be called after the service is created to begin processing.
-    /// The worker runs in a separate tokio task and processes tasks in priority order.
+    /// The workers run in separate tokio tasks and process tasks in priority order.
     ///
     /// Requirements: 1.3, 2.2, 6.1, 6.2
     pub async fn start_worker(&self) {
-        let Some(scan_service) = self.scan_service.clone() else {
+        if self.executors.is_empty() {
             warn!("{}", t!("scan_queue.worker_no_service"));
             return;
-        };
+        }
 
-        let pending_queue = Arc::clone(&self.pending_queue);
-        let tasks = Arc::clone(&self.tasks);
-        let library_tasks = Arc::clone(&self.library_tasks);
-        let mut shutdown_rx = self.shutdown_tx.subscribe();
-        let task_notify = Arc::clone(&self.task_notify);
+        let mut workers: Vec<JoinHandle<()>> = (0..self.worker_count.load(Ordering::Relaxed))
+            .map(|_| {
+                tokio::spawn(Self::run_worker(
+                    Arc::clone(&self.pending_queue),
+                    Arc::clone(&self.tasks),
+                    Arc::clone(&self.library_tasks),
+                    Arc::clone(&self.executors),
+                    self.shutdown_tx.subscribe(),
+                    Arc::clone(&self.task_notify),
+                    Arc::clone(&self.interrupted),
+                    Arc::clone(&self.paused),
+                ))
+            })
+            .collect();
+        workers.push(tokio::spawn(Self::run_history_pruner(
+            Arc::clone(&self.tasks),
+            self.history_retention(),
+            self.shutdown_tx.subscribe(),
+        )));
 
+        // A single handle covering all workers, finished once every worker is
         let handle = tokio::spawn(async move {
-            info!("{}", t!("scan_queue.worker_started"));
-
-            loop {
-                // Wait for either a new task notification or shutdown
-                tokio::select! {
-                    _ = shutdown_rx.recv() => {
-                        info!("{}", t!("scan_queue.worker_shutdown_signal"));
-                        break;
-                    }
-                    _ = task_notify.notified() => {
-                        // Process all available tasks
-                        Self::process_pending_tasks(
-                            &pending_queue,
-                            &tasks,
-                            &library_tasks,
-                            &scan_service,
-                            &mut shutdown_rx,
-                        ).await;
-                    }
+            for worker in workers {
+                if let Err(e) = worker.await {
+                    error!("{}", t!("scan_queue.worker_panicked", error = e));
                 }
             }
-
-            info!("{}", t!("scan_queue.worker_stopped"));
         });
 
         let mut worker_handle = self.worker_handle.write().await;
         *worker_handle = Some(handle);
     }
 
+    /// Worker loop: waits for new tasks and processes them until shutdown.
+    async fn run_worker(
+        pending_queue: Arc<RwLock<BinaryHeap<QueuedTask>>>,
+        tasks: Arc<RwLock<HashMap<Uuid, ScanTask>>>,
+        library_tasks: Arc<RwLock<HashMap<TaskKey, Uuid>>>,
+        executors: Arc<ExecutorRegistry>,
+        mut shutdown_rx: broadcast::Receiver<()>,
+        task_notify: Arc<tokio::sync::Notify>,
+        interrupted: Arc<RwLock<HashSet<Uuid>>>,
+        paused: Arc<AtomicBool>,
+    ) {
+        info!("{}", t!("scan_queue.worker_started"));
+
+        loop {
+            // Wait for either a new task notification or shutdown
+            tokio::select! {
+                _ = shutdown_rx.recv() => {
+                    info!("{}", t!("scan_queue.worker_shutdown_signal"));
+                    break;
+                }
+                _ = task_notify.notified() => {
+                    // Process all available tasks
+                    Self::process_pending_tasks(
+                        &pending_queue,
+                        &tasks,
+                        &library_tasks,
+                        &executors,
+                        &mut shutdown_rx,
+                        &task_notify,
+                        &interrupted,
+                        &paused,
+                    ).await;
+                }
+            }
+        }
+
+        info!("{}", t!("scan_queue.worker_stopped"));
+    }
+
+    /// Pruner loop: removes finished tasks past the history limits every
+    /// [`HISTORY_PRUNE_INTERVAL`] until shutdown.
+    async fn run_history_pruner(
+        tasks: Arc<RwLock<HashMap<Uuid, ScanTask>>>,
+        retention: HistoryRetention,
+        mut shutdown_rx: broadcast::Receiver<()>,
+    ) {
+        let mut interval = tokio::time::interval(HISTORY_PRUNE_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.recv() => break,
+                _ = interval.tick() => {
+                    let removed = retention.prune(&mut *tasks.write().await, &HashSet::new());
+                    if removed > 0 {
+                        debug!(count = removed, "{}", t!("scan_queue.history_pruned"));
+                    }
+                }
+            }
+        }
+    }
+
     /// Processes all pending tasks in the queue.
     ///
     /// Requirements: 1.3, 2.2, 6.1, 6.2
@@ -810,15 +2357,28 @@ impl ScanQueueService {
     async fn process_pending_tasks(
         pending_queue: &Arc<RwLock<BinaryHeap<QueuedTask>>>,
         tasks: &Arc<RwLock<HashMap<Uuid, ScanTask>>>,
-        library_tasks: &Arc<RwLock<HashMap<i64, Uuid>>>,
-        scan_service: &Arc<ScanService>,
+        library_tasks: &Arc<RwLock<HashMap<TaskKey, Uuid>>>,
+        executors: &ExecutorRegistry,
         shutdown_rx: &mut broadcast::Receiver<()>,
+        task_notify: &tokio::sync::Notify,
+        interrupted: &RwLock<HashSet<Uuid>>,
+        paused: &AtomicBool,
     ) {
         loop {
+            // Leave pending tasks queued while paused
+            if paused.load(Ordering::Relaxed) {
+                break;
+            }
+
             // Pop the next task from the queue
             let queued_task = {
                 let mut queue = pending_queue.write().await;
-                queue.pop()
+                let task = queue.pop();
+                // Hand remaining tasks to an idle worker, if there is one
+                if task.is_some() && !queue.is_empty() {
+                    task_notify.notify_one();
+                }
+                task
             };
 
             let Some(queued_task) = queued_task else {
@@ -827,19 +2387,20 @@ impl ScanQueueService {
             };
 
             // Check if task was cancelled before we start
-            let (task_id, library_id) = {
+            let (task_id, key) = {
                 let tasks_guard = tasks.read().await;
                 if let Some(task) = tasks_guard.get(&queued_task.task_id) {
                     if task.status == TaskStatus::Cancelled {
                         debug!(task_id = %queued_task.task_id, "{}", t!("scan_queue.skip_cancelled"));
                         continue;
                     }
-                    (task.id, task.library_id)
+                    (task.id, task_key(task))
                 } else {
                     // Task was removed, skip it
                     continue;
                 }
             };
+            let library_id = key.1;
 
             // Update task status to Running
             {
@@ -851,15 +2412,24 @@ impl ScanQueueService {
                 }
             }
 
-            // Execute the scan with cancellation support
-            let scan_result = tokio::select! {
-                result = scan_service.scan_library(library_id) => {
-                    Some(result)
-                }
-                _ = shutdown_rx.recv() => {
-                    // Shutdown requested during scan
-                    info!(task_id = %task_id, "{}", t!("scan_queue.scan_interrupted"));
-                    None
+            // Execute the task with cancellation support, recording its progress
+            let (progress_tx, mut progress_rx) = watch::channel(None);
+            let run = Self::run_task(executors, key, progress_tx);
+            tokio::pin!(run);
+            let task_result = loop {
+                tokio::select! {
+                    result = &mut run => break Some(result),
+                    Ok(()) = progress_rx.changed() => {
+                        let progress = progress_rx.borrow_and_update().clone();
+                        if let Some(task) = tasks.write().await.get_mut(&task_id) {
+                            task.progress = progress;
+                        }
+                    }
+                    _ = shutdown_rx.recv() => {
+                        // Shutdown requested during the task
+                        info!(task_id = %task_id, "{}", t!("scan_queue.scan_interrupted"));
+                        break None;
+                    }
                 }
             };
 
@@ -873,37 +2443,37 @@ impl ScanQueueService {
                     if task.status == TaskStatus::Cancelled {
                         debug!(task_id = %task_id, "{}", t!("scan_queue.task_cancelled_exec"));
                         // Already marked as cancelled, just clean up
-                        library_tasks_guard.remove(&library_id);
+                        library_tasks_guard.remove(&key);
                         continue;
                     }
 
                     task.completed_at = Some(chrono::Utc::now());
 
-                    match scan_result {
+                    match task_result {
                         Some(Ok(result)) => {
-                            // Scan completed successfully (Requirements: 6.1)
-                            task.status = TaskStatus::Completed;
-                            task.result = Some(TaskResult {
-                                added_count: result.added.len() as i32,
-                                removed_count: result.removed.len() as i32,
-                                failed_scrape_count: result.failed_scrape.len() as i32,
-                                added_contents: result
-                                    .added
-                                    .iter()
-                                    .map(|c| crate::models::AddedContent {
-                                        content_name: c.title.clone(),
-                                        path: c.folder_path.clone(),
-                                    })
-                                    .collect(),
-                                added_chapters: result.added_chapters.clone(),
-                            });
-                            info!(
-                                task_id = %task_id,
-                                library_id = library_id,
-                                added = result.added.len(),
-                                removed = result.removed.len(),
-                                "{}", t!("scan_queue.task_completed")
-                            );
+                            if let Some(error) = result.scan_path_error() {
+                                // Some scan paths could not be scanned, the
+                                // result tells which
+                                error!(
+                                    task_id = %task_id,
+                                    library_id = library_id,
+                                    error = %error,
+                                    "{}", t!("scan_queue.task_failed")
+                                );
+                                task.status = TaskStatus::Failed;
+                                task.error = Some(error);
+                            } else {
+                                // Task completed successfully (Requirements: 6.1)
+                                info!(
+                                    task_id = %task_id,
+                                    library_id = library_id,
+                                    added = result.added_count,
+                                    removed = result.removed_count,
+                                    "{}", t!("scan_queue.task_completed")
+                                );
+                                task.status = TaskStatus::Completed;
+                            }
+                            task.result = Some(result);
                         }
                         Some(Err(e)) => {
                             // Scan failed (Requirements: 6.2)
@@ -920,11 +2490,12 @@ impl ScanQueueService {
                             // Shutdown interrupted the scan
                             task.status = TaskStatus::Cancelled;
                             task.error = Some(t!("scan_queue.interrupted_by_shutdown").to_string());
+                            interrupted.write().await.insert(task_id);
                         }
                     }
 
                     // Remove from library_tasks mapping
-                    library_tasks_guard.remove(&library_id);
+                    library_tasks_guard.remove(&key);
                 }
             }
 
@@ -936,6 +2507,17 @@ impl ScanQueueService {
         }
     }
 
+    /// Runs the task identified by `key` with the executor registered for
+    /// its kind, publishing its progress to `progress`.
+    async fn run_task(
+        executors: &ExecutorRegistry,
+        (kind, library_id, scan_path_id): TaskKey,
+        progress: watch::Sender<Option<TaskProgress>>,
+    ) -> Result<TaskResult> {
+        let ctx = TaskContext::new(library_id, scan_path_id, progress);
+        executors.get(kind)?.run(&ctx).await
+    }
+
     /// Submits a scan task for a library.
     ///
     /// If a task already exists for the library (pending or running), returns
@@ -944,12 +2526,89 @@ impl ScanQueueService {
     ///
     /// Requirements: 1.1, 4.1, 4.2, 4.3
     pub async fn submit_task(&self, library_id: i64, priority: TaskPriority) -> Uuid {
+        self.submit(ScanTask::new(library_id, priority)).await
+    }
+
+    /// Submits a scan task for a single scan path of a library.
+    ///
+    /// Deduplicated like [`submit_task`](Self::submit_task); an active task
+    /// for the whole library also covers the scan path and is returned instead.
+    pub async fn submit_scan_path_task(
+        &self,
+        library_id: i64,
+        scan_path_id: i64,
+        priority: TaskPriority,
+    ) -> Uuid {
+        self.submit(ScanTask::for_scan_path(library_id, scan_path_id, priority))
+            .await
+    }
+
+    /// Submits a task regenerating the missing and outdated thumbnails of a
+    /// library.
+    ///
+    /// If a thumbnail task is already active for the library, returns its ID.
+    pub async fn submit_thumbnail_task(&self, library_id: i64, priority: TaskPriority) -> Uuid {
+        self.submit(ScanTask::thumbnails(library_id, priority))
+            .await
+    }
+
+    /// Submits a task counting the pages of a library's chapters that have
+    /// none.
+    ///
+    /// If a page count task is already active for the library, returns its
+    /// ID.
+    pub async fn submit_page_count_task(&self, library_id: i64, priority: TaskPriority) -> Uuid {
+        self.submit(ScanTask::page_counts(library_id, priority))
+            .await
+    }
+
+    /// Submits a task fetching the metadata of a library's content again.
+    ///
+    /// If a metadata refresh task is already active for the library, returns
+    /// its ID.
+    pub async fn submit_metadata_refresh_task(
+        &self,
+        library_id: i64,
+        priority: TaskPriority,
+    ) -> Uuid {
+        self.submit(ScanTask::metadata_refresh(library_id, priority))
+            .await
+    }
+
+    /// Submits a task removing a library hidden for deletion, with its
+    /// contents.
+    ///
+    /// If a deletion task is already active for the library, returns its ID.
+    pub async fn submit_library_deletion_task(
+        &self,
+        library_id: i64,
+        priority: TaskPriority,
+    ) -> Uuid {
+        self.submit(ScanTask::library_deletion(library_id, priority))
+            .await
+    }
+
+    /// Submits a task writing a backup of the database.
+    ///
+    /// If a backup task is already active, returns its ID.
+    pub async fn submit_backup_task(&self, priority: TaskPriority) -> Uuid {
+        self.submit(ScanTask::backup(priority)).await
+    }
+
+    /// Queues a new task unless an active task already covers its target.
+    async fn submit(&self, task: ScanTask) -> Uuid {
         let mut library_tasks = self.library_tasks.write().await;
         let mut tasks = self.tasks.write().await;
         let mut pending_queue = self.pending_queue.write().await;
 
+        let key = task_key(&task);
+        let priority = task.priority;
+
         // Check for existing task (deduplication)
-        if let Some(&existing_task_id) = library_tasks.get(&library_id)
+        let existing = library_tasks
+            .get(&key)
+            .or_else(|| library_tasks.get(&(task.kind, task.library_id, None)));
+        if let Some(&existing_task_id) = existing
             && let Some(existing_task) = tasks.get_mut(&existing_task_id)
         {
             // If existing task is pending and new priority is higher, upgrade
@@ -963,7 +2622,6 @@ impl ScanQueueService {
         }
 
         // Create new task
-        let task = ScanTask::new(library_id, priority);
         let task_id = task.id;
 
         // Add to pending queue
@@ -972,7 +2630,7 @@ impl ScanQueueService {
 
         // Store task and mapping
         tasks.insert(task_id, task);
-        library_tasks.insert(library_id, task_id);
+        library_tasks.insert(key, task_id);
 
         // Notify worker that a new task is available
         drop(pending_queue);
@@ -1008,13 +2666,14 @@ impl ScanQueueService {
 
     /// Gets the current task for a library.
     ///
-    /// Returns the active (pending or running) task for the library, if any.
+    /// Returns the active (pending or running) task scanning the whole
+    /// library, if any.
     pub async fn get_library_task(&self, library_id: i64) -> Option<ScanTask> {
         let library_tasks = self.library_tasks.read().await;
         let tasks = self.tasks.read().await;
 
         library_tasks
-            .get(&library_id)
+            .get(&(TaskKind::Scan, library_id, None))
             .and_then(|task_id| tasks.get(task_id).cloned())
     }
 
@@ -1032,6 +2691,7 @@ impl ScanQueueService {
 
         let task = tasks.get_mut(&task_id).ok_or_else(|| {
             AppError::NotFound(t!("scan_queue.task_not_found", id = task_id).to_string())
+                .with_code(ErrorCode::ScanTaskNotFound)
         })?;
 
         match task.status {
@@ -1041,7 +2701,7 @@ impl ScanQueueService {
                 task.completed_at = Some(chrono::Utc::now());
 
                 // Remove from library_tasks mapping
-                library_tasks.remove(&task.library_id);
+                library_tasks.remove(&task_key(task));
 
                 // Rebuild queue without the cancelled task
                 self.rebuild_queue_internal(&tasks, &mut pending_queue);
@@ -1055,7 +2715,7 @@ impl ScanQueueService {
                 task.completed_at = Some(chrono::Utc::now());
 
                 // Remove from library_tasks mapping
-                library_tasks.remove(&task.library_id);
+                library_tasks.remove(&task_key(task));
 
                 Ok(())
             }
@@ -1066,11 +2726,93 @@ impl ScanQueueService {
                         status = format!("{:?}", task.status)
                     )
                     .to_string(),
-                ))
+                )
+                .with_code(ErrorCode::TaskNotCancellable))
             }
         }
     }
 
+    /// Changes the priority of a pending task.
+    pub async fn set_task_priority(&self, task_id: Uuid, priority: TaskPriority) -> Result<()> {
+        let mut tasks = self.tasks.write().await;
+        let mut pending_queue = self.pending_queue.write().await;
+
+        let task = tasks.get_mut(&task_id).ok_or_else(|| {
+            AppError::NotFound(t!("scan_queue.task_not_found", id = task_id).to_string())
+                .with_code(ErrorCode::ScanTaskNotFound)
+        })?;
+
+        if task.status != TaskStatus::Pending {
+            return Err(AppError::BadRequest(
+                t!(
+                    "scan_queue.cannot_reprioritize_status",
+                    status = format!("{:?}", task.status)
+                )
+                .to_string(),
+            ));
+        }
+
+        task.priority = priority;
+        self.rebuild_queue_internal(&tasks, &mut pending_queue);
+        Ok(())
+    }
+
+    /// Moves a pending task to the front of the queue, ahead of every
+    /// priority and of tasks moved to the front before it.
+    pub async fn move_to_front(&self, task_id: Uuid) -> Result<()> {
+        let mut tasks = self.tasks.write().await;
+        let mut pending_queue = self.pending_queue.write().await;
+
+        let task = tasks.get_mut(&task_id).ok_or_else(|| {
+            AppError::NotFound(t!("scan_queue.task_not_found", id = task_id).to_string())
+                .with_code(ErrorCode::ScanTaskNotFound)
+        })?;
+
+        if task.status != TaskStatus::Pending {
+            return Err(AppError::BadRequest(
+                t!(
+                    "scan_queue.cannot_reorder_status",
+                    status = format!("{:?}", task.status)
+                )
+                .to_string(),
+            ));
+        }
+
+        task.moved_to_front_at = Some(chrono::Utc::now());
+        self.rebuild_queue_internal(&tasks, &mut pending_queue);
+        Ok(())
+    }
+
+    /// Submits a new task for the library (or scan path) of a failed or
+    /// cancelled task.
+    ///
+    /// Returns the ID of the new task, or of the task already queued for
+    /// the same target.
+    pub async fn retry_task(&self, task_id: Uuid) -> Result<Uuid> {
+        let task = self.get_task(task_id).await.ok_or_else(|| {
+            AppError::NotFound(t!("scan_queue.task_not_found", id = task_id).to_string())
+                .with_code(ErrorCode::ScanTaskNotFound)
+        })?;
+
+        if !matches!(task.status, TaskStatus::Failed | TaskStatus::Cancelled) {
+            return Err(AppError::BadRequest(
+                t!(
+                    "scan_queue.cannot_retry_status",
+                    status = format!("{:?}", task.status)
+                )
+                .to_string(),
+            ));
+        }
+
+        Ok(self
+            .submit(ScanTask {
+                kind: task.kind,
+                scan_path_id: task.scan_path_id,
+                ..ScanTask::new(task.library_id, task.priority)
+            })
+            .await)
+    }
+
     /// Lists all processing tasks.
     pub async fn list_processing(&self) -> Vec<ScanTask> {
         let tasks = self.tasks.read().await;
@@ -1098,32 +2840,21 @@ impl ScanQueueService {
             .cloned()
             .collect();
 
-        // Sort by priority (desc) then created_at (asc)
-        result.sort_by(|a, b| {
-            b.priority
-                .cmp(&a.priority)
-                .then_with(|| a.created_at.cmp(&b.created_at))
-        });
+        // Sort in the order the queue runs them
+        result.sort_by(|a, b| QueuedTask::from_scan_task(b).cmp(&QueuedTask::from_scan_task(a)));
 
         result
     }
 
     /// Lists task history (completed, failed, cancelled tasks).
     ///
-    /// Returns tasks from the last 24 hours by default.
+    /// Returns at most `limit` tasks, most recently finished first. How far
+    /// the history goes back depends on the [`HistoryRetention`].
     pub async fn list_history(&self, limit: usize) -> Vec<ScanTask> {
         let tasks = self.tasks.read().await;
 
-        let mut history: Vec<ScanTask> = tasks
-            .values()
-            .filter(|t| {
-                matches!(
-                    t.status,
-                    TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Cancelled
-                )
-            })
-            .cloned()
-            .collect();
+        let mut history: Vec<ScanTask> =
+            tasks.values().filter(|t| is_finished(t)).cloned().collect();
 
         // Sort by completed_at descending (most recent first)
         history.sort_by(|a, b| b.completed_at.cmp(&a.completed_at));
@@ -1132,20 +2863,167 @@ impl ScanQueueService {
         history
     }
 
+    /// Returns a page of the task history, most recently finished first,
+    /// limited to the tasks finished within the query's dates.
+    pub async fn history_page(&self, query: &ScanTaskHistoryQuery) -> ScanTaskHistoryPage {
+        let page = query.page.unwrap_or(1).max(1);
+        let page_size = query.page_size.unwrap_or(50).clamp(1, 200);
+
+        let tasks = self.tasks.read().await;
+        let mut history: Vec<&ScanTask> = tasks
+            .values()
+            .filter(|task| is_finished(task))
+            .filter(|task| query.status.is_none_or(|status| task.status == status))
+            .filter(|task| {
+                let finished_at = task.completed_at.unwrap_or(task.created_at);
+                query.from.is_none_or(|from| finished_at >= from)
+                    && query.to.is_none_or(|to| finished_at < to)
+            })
+            .collect();
+        history.sort_by(|a, b| b.completed_at.cmp(&a.completed_at));
+
+        let total = history.len();
+        let items = history
+            .into_iter()
+            .skip((page - 1) * page_size)
+            .take(page_size)
+            .cloned()
+            .collect();
+        ScanTaskHistoryPage {
+            items,
+            total,
+            page,
+            page_size,
+        }
+    }
+
+    /// Removes the finished tasks past the history limits, returning how
+    /// many were removed. Runs every hour while the worker is started.
+    pub async fn prune_history(&self) -> usize {
+        let interrupted = self.interrupted.read().await;
+        let mut tasks = self.tasks.write().await;
+        self.history_retention().prune(&mut tasks, &interrupted)
+    }
+
     /// Returns the number of pending tasks.
     pub async fn pending_count(&self) -> usize {
         let pending_queue = self.pending_queue.read().await;
         pending_queue.len()
     }
 
+    /// Returns the state of the workers, the number of tasks in each status
+    /// and the running tasks.
+    pub async fn state(&self) -> ScanQueueState {
+        let now = chrono::Utc::now();
+        let mut counts = TaskStatusCounts::default();
+        let mut current = Vec::new();
+        for task in self.tasks.read().await.values() {
+            match task.status {
+                TaskStatus::Pending => counts.pending += 1,
+                TaskStatus::Running => {
+                    counts.running += 1;
+                    let started_at = task.started_at.unwrap_or(now);
+                    current.push(RunningTask {
+                        task: task.clone(),
+                        elapsed_secs: (now - started_at).num_seconds().max(0),
+                    });
+                }
+                TaskStatus::Completed => counts.completed += 1,
+                TaskStatus::Failed => counts.failed += 1,
+                TaskStatus::Cancelled => counts.cancelled += 1,
+            }
+        }
+        current.sort_by(|a, b| a.task.started_at.cmp(&b.task.started_at));
+
+        ScanQueueState {
+            worker_running: self.is_worker_running().await,
+            paused: self.is_paused(),
+            worker_count: self.worker_count.load(Ordering::Relaxed),
+            counts,
+            current,
+        }
+    }
+
+    /// Whether workers stop starting pending tasks.
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Pauses the workers: running tasks finish, but pending tasks stay
+    /// queued until [`resume`](Self::resume) is called.
+    pub fn pause(&self) {
+        if !self.paused.swap(true, Ordering::Relaxed) {
+            info!("{}", t!("scan_queue.paused"));
+        }
+    }
+
+    /// Resumes paused workers, which start on the pending tasks.
+    pub fn resume(&self) {
+        if self.paused.swap(false, Ordering::Relaxed) {
+            info!("{}", t!("scan_queue.resumed"));
+            self.task_notify.notify_one();
+        }
+    }
+
+    /// Cancels every pending task. Running tasks are left to finish.
+    ///
+    /// Returns the number of cancelled tasks.
+    pub async fn clear_pending(&self) -> usize {
+        let mut tasks = self.tasks.write().await;
+        let mut library_tasks = self.library_tasks.write().await;
+        let mut pending_queue = self.pending_queue.write().await;
+
+        let now = chrono::Utc::now();
+        let mut cancelled = 0;
+        for task in tasks.values_mut() {
+            if task.status == TaskStatus::Pending {
+                task.status = TaskStatus::Cancelled;
+                task.completed_at = Some(now);
+                library_tasks.remove(&task_key(task));
+                cancelled += 1;
+            }
+        }
+        pending_queue.clear();
+
+        info!(count = cancelled, "{}", t!("scan_queue.cleared"));
+        cancelled
+    }
+
+    /// Cancels the pending and running tasks of a library, other than its
+    /// deletion, returning how many were cancelled.
+    pub async fn cancel_library_tasks(&self, library_id: i64) -> usize {
+        let mut tasks = self.tasks.write().await;
+        let mut library_tasks = self.library_tasks.write().await;
+        let mut pending_queue = self.pending_queue.write().await;
+
+        let now = chrono::Utc::now();
+        let mut cancelled = 0;
+        for task in tasks.values_mut() {
+            if task.library_id == library_id
+                && task.kind.targets_library()
+                && task.kind != TaskKind::LibraryDeletion
+                && matches!(task.status, TaskStatus::Pending | TaskStatus::Running)
+            {
+                task.status = TaskStatus::Cancelled;
+                task.completed_at = Some(now);
+                library_tasks.remove(&task_key(task));
+                cancelled += 1;
+            }
+        }
+        if cancelled > 0 {
+            self.rebuild_queue_internal(&tasks, &mut pending_queue);
+        }
+        cancelled
+    }
+
     /// Shuts down the scan queue service gracefully.
     ///
-    /// Sends a shutdown signal to the worker and waits for it to complete.
-    /// Any currently running task will be allowed to finish or will be
-    /// marked as cancelled.
+    /// Sends a shutdown signal to the worker and waits up to `timeout` for
+    /// it to complete. Any currently running task will be allowed to finish
+    /// or will be marked as cancelled.
     ///
     /// Requirements: 3.2
-    pub async fn shutdown(&self) {
+    pub async fn shutdown(&self, timeout: std::time::Duration) {
         info!("{}", t!("scan_queue.shutting_down"));
 
         // Send shutdown signal to worker
@@ -1155,7 +3033,7 @@ impl ScanQueueService {
         let mut worker_handle = self.worker_handle.write().await;
         if let Some(handle) = worker_handle.take() {
             // Give the worker some time to finish gracefully
-            match tokio::time::timeout(std::time::Duration::from_secs(30), handle).await {
+            match tokio::time::timeout(timeout, handle).await {
                 Ok(Ok(())) => {
                     info!("{}", t!("scan_queue.worker_shutdown_gracefully"));
                 }
@@ -1169,6 +3047,109 @@ impl ScanQueueService {
         }
     }
 
+    /// Saves all tasks to the database so they survive a restart.
+    ///
+    /// Pending tasks, and tasks that were running when the server stopped,
+    /// are marked to run again by [`restore_tasks`](Self::restore_tasks).
+    /// Finished tasks past the history limits are left out. Call after
+    /// [`shutdown`](Self::shutdown). Returns the number of saved tasks.
+    pub async fn persist_tasks(&self, pool: &Pool<Sqlite>) -> Result<usize> {
+        self.prune_history().await;
+        let interrupted = self.interrupted.read().await;
+        let tasks: Vec<(ScanTask, bool)> = self
+            .tasks
+            .read()
+            .await
+            .values()
+            .map(|task| {
+                let resume = matches!(task.status, TaskStatus::Pending | TaskStatus::Running)
+                    || interrupted.contains(&task.id);
+                (task.clone(), resume)
+            })
+            .collect();
+
+        ScanTaskRepository::replace_all(pool, &tasks).await?;
+        info!(count = tasks.len(), "{}", t!("scan_queue.tasks_persisted"));
+        Ok(tasks.len())
+    }
+
+    /// Loads the tasks saved by [`persist_tasks`](Self::persist_tasks).
+    ///
+    /// Finished tasks return to the history. Pending tasks are queued again
+    /// under their original ID; interrupted tasks stay in the history as
+    /// cancelled and a new task is queued for their library. Tasks for
+    /// libraries that no longer exist are not queued; tasks not tied to a
+    /// library are. Libraries whose deletion was interrupted get a deletion
+    /// task. Call before
+    /// [`start_worker`](Self::start_worker). Returns the number of queued tasks.
+    pub async fn restore_tasks(&self, pool: &Pool<Sqlite>) -> Result<usize> {
+        let mut requeue = Vec::new();
+        let mut queued = 0;
+        let deleting = LibraryRepository::list_deleting(pool).await?;
+
+        for (mut task, resume) in ScanTaskRepository::list(pool).await? {
+            let library_exists = match task.kind {
+                // Hidden while being deleted
+                TaskKind::LibraryDeletion => deleting.contains(&task.library_id),
+                kind if !kind.targets_library() => true,
+                _ => LibraryRepository::find_by_id(pool, task.library_id)
+                    .await?
+                    .is_some(),
+            };
+            let resume = resume && library_exists;
+
+            if resume && task.status == TaskStatus::Pending {
+                let mut library_tasks = self.library_tasks.write().await;
+                let mut tasks = self.tasks.write().await;
+                let mut pending_queue = self.pending_queue.write().await;
+
+                pending_queue.push(QueuedTask::from_scan_task(&task));
+                library_tasks.insert(task_key(&task), task.id);
+                tasks.insert(task.id, task);
+                queued += 1;
+                continue;
+            }
+
+            if matches!(task.status, TaskStatus::Pending | TaskStatus::Running) {
+                // Stopped before it could finish
+                task.status = TaskStatus::Cancelled;
+                task.completed_at = Some(chrono::Utc::now());
+                task.error = Some(t!("scan_queue.interrupted_by_shutdown").to_string());
+            }
+            if resume {
+                requeue.push(ScanTask {
+                    kind: task.kind,
+                    scan_path_id: task.scan_path_id,
+                    ..ScanTask::new(task.library_id, task.priority)
+                });
+            }
+            self.tasks.write().await.insert(task.id, task);
+        }
+
+        for library_id in deleting {
+            let task = ScanTask::library_deletion(library_id, TaskPriority::Normal);
+            let active = self
+                .library_tasks
+                .read()
+                .await
+                .contains_key(&task_key(&task));
+            if !active && !requeue.iter().any(|t| task_key(t) == task_key(&task)) {
+                requeue.push(task);
+            }
+        }
+
+        for task in requeue {
+            self.submit(task).await;
+            queued += 1;
+        }
+
+        if queued > 0 {
+            self.task_notify.notify_one();
+        }
+        info!(count = queued, "{}", t!("scan_queue.tasks_restored"));
+        Ok(queued)
+    }
+
     /// Checks if the worker is currently running.
     pub async fn is_worker_running(&self) -> bool {
         let worker_handle = self.worker_handle.read().await;
@@ -1202,7 +3183,7 @@ impl ScanQueueService {
         let mut pending_queue = self.pending_queue.write().await;
 
         if let Some(task) = tasks.get_mut(&task_id) {
-            let library_id = task.library_id;
+            let key = task_key(task);
             task.status = status;
             task.completed_at = Some(chrono::Utc::now());
             task.error = error;
@@ -1212,7 +3193,7 @@ impl ScanQueueService {
                 status,
                 TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Cancelled
             ) {
-                library_tasks.remove(&library_id);
+                library_tasks.remove(&key);
                 // Rebuild queue without this task
                 self.rebuild_queue_internal(&tasks, &mut pending_queue);
             }
@@ -1237,6 +3218,9 @@ impl ScanQueueService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_fixtures::{
+        TIMESTAMP, create_test_content, create_test_db, create_test_library, create_test_scan_path,
+    };
 
     #[tokio::test]
     async fn test_new_creates_empty_service() {
@@ -1265,6 +3249,178 @@ mod tests {
         assert_eq!(service.pending_count().await, 1);
     }
 
+    #[tokio::test]
+    async fn test_submit_scan_path_task_deduplicates() {
+        let service = ScanQueueService::new();
+        let path_task = service
+            .submit_scan_path_task(1, 10, TaskPriority::Normal)
+            .await;
+        let same_path = service
+            .submit_scan_path_task(1, 10, TaskPriority::Normal)
+            .await;
+        let other_path = service
+            .submit_scan_path_task(1, 11, TaskPriority::Normal)
+            .await;
+
+        assert_eq!(path_task, same_path);
+        assert_ne!(path_task, other_path);
+        assert_eq!(
+            service.get_task(path_task).await.unwrap().scan_path_id,
+            Some(10)
+        );
+
+        // A whole-library scan is queued separately and then covers every path
+        let library_task = service.submit_task(1, TaskPriority::Normal).await;
+        assert_ne!(library_task, path_task);
+        let covered = service
+            .submit_scan_path_task(1, 12, TaskPriority::Normal)
+            .await;
+        assert_eq!(covered, library_task);
+        assert_eq!(service.pending_count().await, 3);
+    }
+
+    #[tokio::test]
+    async fn test_thumbnail_task_is_separate_from_scans() {
+        let service = ScanQueueService::new();
+        let scan_task = service.submit_task(1, TaskPriority::Normal).await;
+        let thumbnail_task = service.submit_thumbnail_task(1, TaskPriority::Normal).await;
+        assert_ne!(scan_task, thumbnail_task);
+        assert_eq!(
+            service.submit_thumbnail_task(1, TaskPriority::Normal).await,
+            thumbnail_task
+        );
+        assert_eq!(
+            service.get_task(thumbnail_task).await.unwrap().kind,
+            TaskKind::Thumbnails
+        );
+        assert_eq!(service.get_library_task(1).await.unwrap().id, scan_task);
+    }
+
+    #[tokio::test]
+    async fn test_regenerate_thumbnails_skips_current() {
+        let pool = create_test_db().await;
+
+        // One image-folder chapter to build the thumbnail from
+        let dir = tempfile::tempdir().unwrap();
+        let chapter = dir.path().join("Series").join("Chapter 1");
+        std::fs::create_dir_all(&chapter).unwrap();
+        image::DynamicImage::new_rgb8(800, 1200)
+            .save(chapter.join("001.png"))
+            .unwrap();
+
+        create_test_library(&pool, 1, "One").await;
+        create_test_scan_path(&pool, 1, 1, &dir.path().to_string_lossy()).await;
+        create_test_content(&pool, 1, 1, "Series").await;
+
+        let scan_service = ScanService::new(pool.clone());
+        scan_service.set_thumbnail_options(ThumbnailOptions {
+            width: 100,
+            height: 100,
+            ..ThumbnailOptions::default()
+        });
+
+        let progress = std::sync::Mutex::new(Vec::new());
+        let result = scan_service
+            .regenerate_thumbnails(1, |p| progress.lock().unwrap().push(p))
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            ThumbnailResult {
+                regenerated: 1,
+                failed: 0
+            }
+        );
+        assert_eq!(
+            progress.lock().unwrap().last(),
+            Some(&TaskProgress {
+                scanned_paths: 1,
+                total_paths: 1
+            })
+        );
+
+        let content = ContentRepository::find_by_id(&pool, 1)
+            .await
+            .unwrap()
+            .unwrap();
+        let thumbnail = image::load_from_memory(&content.thumbnail.unwrap()).unwrap();
+        assert_eq!(thumbnail.height(), 100);
+        assert!(thumbnail.width() < 100);
+
+        // Thumbnails made with the current settings are left alone
+        let result = scan_service.regenerate_thumbnails(1, |_| {}).await.unwrap();
+        assert_eq!(result, ThumbnailResult::default());
+    }
+
+    #[tokio::test]
+    async fn test_recalculate_page_counts() {
+        let pool = create_test_db().await;
+
+        // A chapter counted while its folder was still empty, and one that
+        // is still unreadable
+        let dir = tempfile::tempdir().unwrap();
+        let chapter = dir.path().join("Chapter 1");
+        std::fs::create_dir(&chapter).unwrap();
+        image::DynamicImage::new_rgb8(10, 10)
+            .save(chapter.join("001.png"))
+            .unwrap();
+        let broken = dir.path().join("Chapter 2.cbz");
+        std::fs::write(&broken, b"not a zip file").unwrap();
+
+        create_test_library(&pool, 1, "One").await;
+        create_test_scan_path(&pool, 1, 1, "/a").await;
+        create_test_content(&pool, 1, 1, "Series").await;
+        for (sort_order, path, file_type) in [(0, &chapter, "folder"), (1, &broken, "cbz")] {
+            sqlx::query(
+                r#"
+                INSERT INTO chapters (content_id, title, file_path, file_type, sort_order, status)
+                VALUES (1, 'Chapter', ?, ?, ?, 'error')
+                "#,
+            )
+            .bind(path.to_string_lossy().to_string())
+            .bind(file_type)
+            .bind(sort_order)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let scan_service = ScanService::new(pool.clone());
+        let progress = std::sync::Mutex::new(Vec::new());
+        let result = scan_service
+            .recalculate_page_counts(1, |p| progress.lock().unwrap().push(p))
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            PageCountResult {
+                updated: 1,
+                failed: 1
+            }
+        );
+        assert_eq!(
+            progress.lock().unwrap().last(),
+            Some(&TaskProgress {
+                scanned_paths: 2,
+                total_paths: 2
+            })
+        );
+
+        let chapters = ChapterRepository::list_by_content(&pool, 1).await.unwrap();
+        assert_eq!(chapters[0].page_count, 1);
+        assert_eq!(chapters[0].status, ChapterStatus::Ready);
+        assert_eq!(chapters[1].page_count, 0);
+        assert_eq!(chapters[1].status, ChapterStatus::Error);
+        assert!(chapters[1].error.is_some());
+
+        assert!(
+            scan_service
+                .recalculate_page_counts(2, |_| {})
+                .await
+                .is_err()
+        );
+    }
+
     #[tokio::test]
     async fn test_submit_duplicate_with_higher_priority_upgrades() {
         let service = ScanQueueService::new();
@@ -1326,4 +3482,275 @@ mod tests {
         assert_eq!(processing[0].id, task_id);
         assert_eq!(processing[0].status, TaskStatus::Running);
     }
+
+    #[tokio::test]
+    async fn test_set_task_priority_only_for_pending() {
+        let service = ScanQueueService::new();
+        let task_id = service.submit_task(1, TaskPriority::Normal).await;
+
+        service
+            .set_task_priority(task_id, TaskPriority::High)
+            .await
+            .unwrap();
+        let task = service.get_task(task_id).await.unwrap();
+        assert_eq!(task.priority, TaskPriority::High);
+
+        service.cancel_task(task_id).await.unwrap();
+        assert!(
+            service
+                .set_task_priority(task_id, TaskPriority::Normal)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_move_to_front_only_for_pending() {
+        let service = ScanQueueService::new();
+        let high_id = service.submit_task(1, TaskPriority::High).await;
+        let low_id = service.submit_task(2, TaskPriority::Low).await;
+
+        service.move_to_front(low_id).await.unwrap();
+        let pending = service.list_pending().await;
+        assert_eq!(pending[0].id, low_id);
+        assert_eq!(pending[1].id, high_id);
+
+        service.cancel_task(low_id).await.unwrap();
+        assert!(service.move_to_front(low_id).await.is_err());
+        assert!(service.move_to_front(Uuid::new_v4()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_clear_pending_cancels_queued_tasks() {
+        let service = ScanQueueService::new();
+        let running_id = service.submit_task(1, TaskPriority::High).await;
+        service
+            .set_task_status_for_test(running_id, TaskStatus::Running, None)
+            .await;
+        let pending_id = service.submit_task(2, TaskPriority::Low).await;
+        service.submit_thumbnail_task(2, TaskPriority::Normal).await;
+
+        assert_eq!(service.clear_pending().await, 2);
+        assert_eq!(service.pending_count().await, 0);
+        let cancelled = service.get_task(pending_id).await.unwrap();
+        assert_eq!(cancelled.status, TaskStatus::Cancelled);
+
+        let state = service.state().await;
+        assert_eq!(state.counts.running, 1);
+        assert_eq!(state.counts.cancelled, 2);
+        assert_eq!(state.current[0].task.id, running_id);
+
+        // The library can be queued again
+        assert_ne!(service.submit_task(2, TaskPriority::Low).await, pending_id);
+    }
+
+    #[tokio::test]
+    async fn test_pause_and_resume() {
+        let service = ScanQueueService::new();
+        assert!(!service.state().await.paused);
+
+        service.pause();
+        service.submit_task(1, TaskPriority::High).await;
+        let state = service.state().await;
+        assert!(state.paused);
+        assert_eq!(state.counts.pending, 1);
+
+        service.resume();
+        assert!(!service.is_paused());
+    }
+
+    #[tokio::test]
+    async fn test_history_limits_and_pages() {
+        let service = ScanQueueService::new();
+        service.set_history_retention(HistoryRetention {
+            max_age_hours: 24,
+            max_entries: 2,
+        });
+
+        // Finished two days, then three, two and one hours ago
+        let now = chrono::Utc::now();
+        let mut ids = Vec::new();
+        for (library_id, hours) in [(1, 48), (2, 3), (3, 2), (4, 1)] {
+            let id = service.submit_task(library_id, TaskPriority::Normal).await;
+            service
+                .set_task_status_for_test(id, TaskStatus::Completed, None)
+                .await;
+            service
+                .tasks
+                .write()
+                .await
+                .get_mut(&id)
+                .unwrap()
+                .completed_at = Some(now - chrono::Duration::hours(hours));
+            ids.push(id);
+        }
+        let pending_id = service.submit_task(5, TaskPriority::Low).await;
+
+        assert_eq!(service.prune_history().await, 2);
+        assert!(service.get_task(ids[0]).await.is_none());
+        assert!(service.get_task(ids[1]).await.is_none());
+        assert!(service.get_task(pending_id).await.is_some());
+
+        let query = ScanTaskHistoryQuery {
+            page: Some(2),
+            page_size: Some(1),
+            ..Default::default()
+        };
+        let page = service.history_page(&query).await;
+        assert_eq!(page.total, 2);
+        assert_eq!(page.items[0].id, ids[2]);
+
+        let query = ScanTaskHistoryQuery {
+            from: Some(now - chrono::Duration::minutes(90)),
+            ..Default::default()
+        };
+        let page = service.history_page(&query).await;
+        assert_eq!(page.total, 1);
+        assert_eq!(page.items[0].id, ids[3]);
+    }
+
+    #[tokio::test]
+    async fn test_retry_cancelled_task_submits_new_task() {
+        let service = ScanQueueService::new();
+        let task_id = service.submit_task(1, TaskPriority::High).await;
+        assert!(service.retry_task(task_id).await.is_err());
+
+        service.cancel_task(task_id).await.unwrap();
+        let retry_id = service.retry_task(task_id).await.unwrap();
+
+        assert_ne!(retry_id, task_id);
+        let retried = service.get_task(retry_id).await.unwrap();
+        assert_eq!(retried.library_id, 1);
+        assert_eq!(retried.priority, TaskPriority::High);
+        assert_eq!(retried.status, TaskStatus::Pending);
+    }
+
+    #[tokio::test]
+    async fn test_library_deletion_cancels_and_resumes() {
+        let pool = create_test_db().await;
+        create_test_library(&pool, 1, "Deleted").await;
+        sqlx::query("UPDATE libraries SET deleting_since = ? WHERE id = 1")
+            .bind(TIMESTAMP)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let service = ScanQueueService::new();
+        let scan_id = service.submit_task(1, TaskPriority::High).await;
+        let deletion_id = service
+            .submit_library_deletion_task(1, TaskPriority::Normal)
+            .await;
+        assert_eq!(service.cancel_library_tasks(1).await, 1);
+        let scan = service.get_task(scan_id).await.unwrap();
+        assert_eq!(scan.status, TaskStatus::Cancelled);
+        assert_eq!(service.list_pending().await[0].id, deletion_id);
+
+        // A deletion interrupted without a saved task is queued again
+        let restored = ScanQueueService::new();
+        assert_eq!(restored.restore_tasks(&pool).await.unwrap(), 1);
+        let pending = restored.list_pending().await;
+        assert_eq!(pending[0].kind, TaskKind::LibraryDeletion);
+        assert_eq!(pending[0].library_id, 1);
+    }
+
+    /// Executor recording the tasks it runs.
+    struct FakeExecutor {
+        kind: TaskKind,
+        ran: Arc<std::sync::Mutex<Vec<i64>>>,
+    }
+
+    impl crate::services::executors::TaskExecutor for FakeExecutor {
+        fn kind(&self) -> TaskKind {
+            self.kind
+        }
+
+        fn run<'a>(&'a self, ctx: &'a TaskContext) -> crate::services::executors::TaskFuture<'a> {
+            Box::pin(async move {
+                self.ran.lock().unwrap().push(ctx.library_id);
+                Ok(TaskResult {
+                    backup_name: Some("ryuri-test.db".to_string()),
+                    ..TaskResult::default()
+                })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_worker_runs_tasks_with_their_executor() {
+        let ran = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut executors = ExecutorRegistry::new();
+        executors.register(Arc::new(FakeExecutor {
+            kind: TaskKind::Backup,
+            ran: Arc::clone(&ran),
+        }));
+        let service = ScanQueueService::with_executors(executors);
+
+        let backup_id = service.submit_backup_task(TaskPriority::Normal).await;
+        // Nothing runs scans
+        let scan_id = service.submit_task(1, TaskPriority::Normal).await;
+        service.start_worker().await;
+
+        let finished = |task: Option<ScanTask>| task.is_some_and(|task| is_finished(&task));
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while !finished(service.get_task(backup_id).await)
+                || !finished(service.get_task(scan_id).await)
+            {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        let backup = service.get_task(backup_id).await.unwrap();
+        assert_eq!(backup.status, TaskStatus::Completed);
+        assert_eq!(
+            backup.result.unwrap().backup_name.as_deref(),
+            Some("ryuri-test.db")
+        );
+        assert_eq!(*ran.lock().unwrap(), [0]);
+        let scan = service.get_task(scan_id).await.unwrap();
+        assert_eq!(scan.status, TaskStatus::Failed);
+
+        service.shutdown(std::time::Duration::from_secs(1)).await;
+    }
+
+    #[tokio::test]
+    async fn test_persist_and_restore_tasks() {
+        let pool = create_test_db().await;
+        for (id, name) in [(1, "One"), (2, "Two"), (3, "Three")] {
+            create_test_library(&pool, id, name).await;
+        }
+
+        let service = ScanQueueService::new();
+        let pending_id = service.submit_task(1, TaskPriority::High).await;
+        let completed_id = service.submit_task(2, TaskPriority::Normal).await;
+        service
+            .set_task_status_for_test(completed_id, TaskStatus::Completed, None)
+            .await;
+        let interrupted_id = service.submit_task(3, TaskPriority::Normal).await;
+        service
+            .set_task_status_for_test(interrupted_id, TaskStatus::Cancelled, None)
+            .await;
+        service.interrupted.write().await.insert(interrupted_id);
+
+        assert_eq!(service.persist_tasks(&pool).await.unwrap(), 3);
+
+        let restored = ScanQueueService::new();
+        assert_eq!(restored.restore_tasks(&pool).await.unwrap(), 2);
+
+        let pending = restored.get_task(pending_id).await.unwrap();
+        assert_eq!(pending.status, TaskStatus::Pending);
+        assert_eq!(pending.priority, TaskPriority::High);
+
+        let completed = restored.get_task(completed_id).await.unwrap();
+        assert_eq!(completed.status, TaskStatus::Completed);
+
+        let interrupted = restored.get_task(interrupted_id).await.unwrap();
+        assert_eq!(interrupted.status, TaskStatus::Cancelled);
+        let rerun = restored.get_library_task(3).await.unwrap();
+        assert_ne!(rerun.id, interrupted_id);
+        assert_eq!(rerun.status, TaskStatus::Pending);
+
+        assert_eq!(restored.pending_count().await, 2);
+    }
 }
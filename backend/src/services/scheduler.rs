@@ -1,23 +1,25 @@
 //! Scheduled scanning service.
 //!
 //! This module provides functionality to schedule periodic library scans
-//! based on configured scan intervals.
+//! based on configured scan intervals, the nightly database backup, and
+//! the periodic push of reading progress to linked trackers.
 //!
 //! Requirements: 1.8, 5.2
 
 use chrono::{DateTime, Utc};
+use sqlx::{Pool, Sqlite};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 use tokio::task::JoinHandle;
 use tracing::{debug, error, info, instrument, warn};
-use sqlx::{Pool, Sqlite};
-use rust_i18n::t;
 
 use crate::error::Result;
 use crate::models::TaskPriority;
 use crate::repository::library::LibraryRepository;
 use crate::services::scan_queue::ScanQueueService;
+use crate::services::tracker::TrackerService;
+use crate::t;
 
 /// Information about a scheduled scan task.
 #[derive(Debug, Clone)]
@@ -44,13 +46,17 @@ struct TaskHandle {
 ///
 /// The SchedulerService manages scheduled scan tasks for libraries
 /// with non-zero scan intervals. It submits tasks to the ScanQueueService
-/// with Normal priority for background processing.
+/// with Low priority for background processing.
 ///
 /// Requirements: 5.2
 pub struct SchedulerService {
     scan_queue_service: Arc<ScanQueueService>,
     /// Map of library_id to task handle.
     tasks: Arc<RwLock<HashMap<i64, TaskHandle>>>,
+    /// Cancels the nightly backup task, if one is scheduled.
+    backup_cancel: Mutex<Option<tokio::sync::oneshot::Sender<()>>>,
+    /// Cancels the tracker sync task, if one is scheduled.
+    tracker_sync_cancel: Mutex<Option<tokio::sync::oneshot::Sender<()>>>,
 }
 
 impl SchedulerService {
@@ -62,6 +68,8 @@ impl SchedulerService {
         Self {
             scan_queue_service,
             tasks: Arc::new(RwLock::new(HashMap::new())),
+            backup_cancel: Mutex::new(None),
+            tracker_sync_cancel: Mutex::new(None),
         }
     }
 
@@ -108,8 +116,8 @@ impl SchedulerService {
                             }
                         }
 
-                        // Submit scan task to queue with Normal priority (Requirements: 5.2)
-                        let task_id = scan_queue_service.submit_task(lib_id, TaskPriority::Normal).await;
+                        // Submit scan task to queue with Low priority (Requirements: 5.2)
+                        let task_id = scan_queue_service.submit_task(lib_id, TaskPriority::Low).await;
                         info!(library_id = lib_id, task_id = %task_id, "{}", t!("scheduler.task_submitted"));
                         debug!(library_id = lib_id, "{}", t!("scheduler.task_queued"));
                     }
@@ -213,11 +221,119 @@ impl SchedulerService {
         }
     }
 
-    /// Cancel all scheduled scans (for shutdown).
+    /// Back up the database every day at `hour` (UTC), through a backup
+    /// task in the scan queue.
+    ///
+    /// Replaces any previously scheduled backup.
+    #[instrument(skip(self))]
+    pub async fn schedule_backups(&self, hour: u32) {
+        let (cancel_tx, mut cancel_rx) = tokio::sync::oneshot::channel::<()>();
+        if let Some(previous) = self.backup_cancel.lock().await.replace(cancel_tx) {
+            let _ = previous.send(());
+        }
+
+        let scan_queue_service = Arc::clone(&self.scan_queue_service);
+        tokio::spawn(async move {
+            loop {
+                let now = Utc::now();
+                let wait = (next_daily_run(now, hour) - now)
+                    .to_std()
+                    .unwrap_or_default();
+
+                tokio::select! {
+                    _ = tokio::time::sleep(wait) => {
+                        let task_id = scan_queue_service
+                            .submit_backup_task(TaskPriority::Normal)
+                            .await;
+                        info!(task_id = %task_id, "{}", t!("scheduler.backup_submitted"));
+                    }
+                    _ = &mut cancel_rx => break,
+                }
+            }
+        });
+
+        info!(hour, "{}", t!("scheduler.backup_scheduled"));
+    }
+
+    /// Push reading progress to linked trackers every `interval_minutes`.
+    ///
+    /// Replaces any previously scheduled sync.
+    #[instrument(skip(self, tracker_service))]
+    pub async fn schedule_tracker_sync(
+        &self,
+        tracker_service: Arc<TrackerService>,
+        interval_minutes: u64,
+    ) {
+        let (cancel_tx, mut cancel_rx) = tokio::sync::oneshot::channel::<()>();
+        if let Some(previous) = self.tracker_sync_cancel.lock().await.replace(cancel_tx) {
+            let _ = previous.send(());
+        }
+
+        let interval = std::time::Duration::from_secs(interval_minutes * 60);
+        tokio::spawn(async move {
+            let mut interval_timer = tokio::time::interval(interval);
+            // Skip the first tick (immediate)
+            interval_timer.tick().await;
+
+            loop {
+                tokio::select! {
+                    _ = interval_timer.tick() => tracker_service.sync_all().await,
+                    _ = &mut cancel_rx => break,
+                }
+            }
+        });
+
+        info!(
+            interval_minutes,
+            "{}",
+            t!("scheduler.tracker_sync_scheduled")
+        );
+    }
+
+    /// Cancel all scheduled scans, backups and tracker syncs (for shutdown).
     pub async fn cancel_all(&self) {
         let mut tasks = self.tasks.write().await;
         for (_, handle) in tasks.drain() {
             let _ = handle.cancel_handle.send(());
         }
+        if let Some(cancel) = self.backup_cancel.lock().await.take() {
+            let _ = cancel.send(());
+        }
+        if let Some(cancel) = self.tracker_sync_cancel.lock().await.take() {
+            let _ = cancel.send(());
+        }
+    }
+}
+
+/// The next time after `now` when the UTC clock shows `hour`:00.
+fn next_daily_run(now: DateTime<Utc>, hour: u32) -> DateTime<Utc> {
+    let today = now
+        .date_naive()
+        .and_hms_opt(hour, 0, 0)
+        .unwrap_or_default()
+        .and_utc();
+    if today > now {
+        today
+    } else {
+        today + chrono::Duration::days(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_next_daily_run() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 5, 1, 30, 0).unwrap();
+        assert_eq!(
+            next_daily_run(now, 3),
+            Utc.with_ymd_and_hms(2026, 3, 5, 3, 0, 0).unwrap()
+        );
+        assert_eq!(
+            next_daily_run(now, 1),
+            Utc.with_ymd_and_hms(2026, 3, 6, 1, 0, 0).unwrap()
+        );
     }
 }
@@ -0,0 +1,78 @@
+//! Series groups.
+//!
+//! Related contents, such as the seasons or spin-offs of a series, are
+//! often imported from separate folders. A series group ties them together
+//! so clients can show them side by side.
+
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, ErrorCode, Result};
+use crate::models::{Content, SeriesGroup};
+use crate::repository::series_group::SeriesGroupRepository;
+use crate::services::content::ContentService;
+use crate::t;
+
+/// Longest series group name, in characters.
+pub const MAX_NAME_CHARS: usize = 200;
+
+/// Service for series group operations.
+pub struct SeriesGroupService;
+
+impl SeriesGroupService {
+    /// List all series groups.
+    pub async fn list(pool: &Pool<Sqlite>) -> Result<Vec<SeriesGroup>> {
+        SeriesGroupRepository::list(pool).await
+    }
+
+    /// Get a series group by ID.
+    pub async fn get(pool: &Pool<Sqlite>, id: i64) -> Result<SeriesGroup> {
+        SeriesGroupRepository::find_by_id(pool, id)
+            .await?
+            .ok_or_else(|| Self::not_found(id))
+    }
+
+    /// Create a series group.
+    pub async fn create(pool: &Pool<Sqlite>, name: &str) -> Result<SeriesGroup> {
+        let name = name.trim();
+        if name.is_empty() || name.chars().count() > MAX_NAME_CHARS {
+            return Err(AppError::BadRequest(
+                t!("series_group.invalid_name").to_string(),
+            ));
+        }
+        SeriesGroupRepository::create(pool, name).await
+    }
+
+    /// Delete a series group, leaving its contents ungrouped.
+    pub async fn delete(pool: &Pool<Sqlite>, id: i64) -> Result<()> {
+        if !SeriesGroupRepository::delete(pool, id).await? {
+            return Err(Self::not_found(id));
+        }
+        Ok(())
+    }
+
+    /// List the contents of a series group.
+    pub async fn list_contents(pool: &Pool<Sqlite>, id: i64) -> Result<Vec<Content>> {
+        Self::get(pool, id).await?;
+        SeriesGroupRepository::list_contents(pool, id).await
+    }
+
+    /// Put a content into a series group, or take it out with `None`.
+    pub async fn set_content_group(
+        pool: &Pool<Sqlite>,
+        content_id: i64,
+        group_id: Option<i64>,
+    ) -> Result<Content> {
+        ContentService::get_content(pool, content_id).await?;
+        if let Some(group_id) = group_id {
+            Self::get(pool, group_id).await?;
+        }
+
+        SeriesGroupRepository::set_content_group(pool, content_id, group_id).await?;
+        ContentService::get_content(pool, content_id).await
+    }
+
+    fn not_found(id: i64) -> AppError {
+        AppError::NotFound(t!("series_group.not_found", id = id).to_string())
+            .with_code(ErrorCode::SeriesGroupNotFound)
+    }
+}
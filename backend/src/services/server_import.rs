@@ -0,0 +1,1044 @@
+//! Komga and Kavita import.
+//!
+//! An uploaded database of either server is opened read-only and read into
+//! the same shape: series with their metadata, books with the progress of
+//! each user, and collections. Series are matched to contents by folder
+//! name, then by title or alternative title; books to chapters by file
+//! name, then by number or title. Matched series get the metadata as
+//! locked edits, users with the same name here get the progress unless
+//! they have more recent progress, and collections become series groups.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{Connection, Pool, Sqlite, SqliteConnection};
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::models::{
+    Chapter, Content, ExportedProgress, ImportSource, MAX_AGE_RATING, MetadataAuthor, SeriesStatus,
+    ServerImportResult, UpdateMetadataEditsRequest,
+};
+use crate::repository::alias::AliasRepository;
+use crate::repository::content::{ChapterRepository, ContentRepository};
+use crate::repository::library::LibraryRepository;
+use crate::repository::progress::ProgressRepository;
+use crate::repository::series_group::SeriesGroupRepository;
+use crate::repository::user::UserRepository;
+use crate::services::backup::SQLITE_HEADER;
+use crate::services::metadata_edits::MetadataEditsService;
+use crate::services::progress::ProgressService;
+use crate::services::series_group::MAX_NAME_CHARS;
+use crate::t;
+use crate::utils::title::match_key;
+
+/// A series read from the other server.
+#[derive(Debug, Default)]
+struct SourceSeries {
+    id: String,
+    name: String,
+    /// Names to match contents by.
+    titles: Vec<String>,
+    /// Name of the series folder.
+    folder: Option<String>,
+    /// Title edited on the other server.
+    title: Option<String>,
+    summary: Option<String>,
+    status: Option<SeriesStatus>,
+    language: Option<String>,
+    age_rating: Option<i32>,
+    tags: Vec<String>,
+    authors: Vec<MetadataAuthor>,
+    books: Vec<SourceBook>,
+}
+
+/// A book or chapter read from the other server.
+#[derive(Debug, Default)]
+struct SourceBook {
+    file_name: Option<String>,
+    title: String,
+    number: Option<f32>,
+    progress: Vec<SourceProgress>,
+}
+
+/// Progress of a user on a book.
+#[derive(Debug)]
+struct SourceProgress {
+    username: String,
+    /// 0-based page reached.
+    page: i64,
+    completed: bool,
+    updated_at: DateTime<Utc>,
+}
+
+/// A collection of series.
+#[derive(Debug)]
+struct SourceCollection {
+    name: String,
+    series_ids: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct SourceData {
+    series: Vec<SourceSeries>,
+    collections: Vec<SourceCollection>,
+}
+
+impl SourceData {
+    /// Index of the series by ID.
+    fn index(&self) -> HashMap<String, usize> {
+        self.series
+            .iter()
+            .enumerate()
+            .map(|(idx, series)| (series.id.clone(), idx))
+            .collect()
+    }
+}
+
+/// Service for importing Komga and Kavita databases.
+pub struct ServerImportService;
+
+impl ServerImportService {
+    /// Import a database of another server.
+    ///
+    /// With `dry_run` nothing is written and the result tells what would
+    /// be.
+    pub async fn import(
+        pool: &Pool<Sqlite>,
+        source: ImportSource,
+        data: &[u8],
+        dry_run: bool,
+    ) -> Result<ServerImportResult> {
+        if !data.starts_with(SQLITE_HEADER) {
+            return Err(AppError::BadRequest(
+                t!("server_import.not_sqlite").to_string(),
+            ));
+        }
+
+        // SQLite reads databases from files only
+        let path = std::env::temp_dir().join(format!("ryuri-import-{}.db", Uuid::new_v4()));
+        tokio::fs::write(&path, data).await?;
+        let read = Self::read_database(source, &path).await;
+        let _ = tokio::fs::remove_file(&path).await;
+
+        Self::apply(pool, source, read?, dry_run).await
+    }
+
+    async fn read_database(source: ImportSource, path: &Path) -> Result<SourceData> {
+        let invalid = |e: sqlx::Error| {
+            AppError::BadRequest(
+                t!(
+                    "server_import.invalid_database",
+                    source = source.as_str(),
+                    reason = e.to_string()
+                )
+                .to_string(),
+            )
+        };
+
+        let options = SqliteConnectOptions::new().filename(path).read_only(true);
+        let mut conn = SqliteConnection::connect_with(&options)
+            .await
+            .map_err(invalid)?;
+        let data = match source {
+            ImportSource::Komga => read_komga(&mut conn).await,
+            ImportSource::Kavita => read_kavita(&mut conn).await,
+        };
+        let _ = conn.close().await;
+        data.map_err(invalid)
+    }
+
+    async fn apply(
+        pool: &Pool<Sqlite>,
+        source: ImportSource,
+        data: SourceData,
+        dry_run: bool,
+    ) -> Result<ServerImportResult> {
+        let library_ids: Vec<i64> = LibraryRepository::list(pool)
+            .await?
+            .into_iter()
+            .map(|library| library.id)
+            .collect();
+        let contents = ContentRepository::list_by_libraries(pool, &library_ids).await?;
+        let content_ids: Vec<i64> = contents.iter().map(|c| c.id).collect();
+        let aliases = AliasRepository::list_by_contents(pool, &content_ids).await?;
+
+        let mut by_folder: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut by_title: HashMap<String, Vec<usize>> = HashMap::new();
+        for (idx, content) in contents.iter().enumerate() {
+            if let Some(folder) = Path::new(&content.folder_path).file_name() {
+                let folder = match_key(&folder.to_string_lossy());
+                by_folder.entry(folder).or_default().push(idx);
+            }
+            let mut titles = vec![match_key(&content.title), match_key(&content.folder_title)];
+            titles.extend(
+                aliases
+                    .get(&content.id)
+                    .into_iter()
+                    .flatten()
+                    .map(|alias| match_key(&alias.title)),
+            );
+            titles.sort();
+            titles.dedup();
+            for title in titles {
+                by_title.entry(title).or_default().push(idx);
+            }
+        }
+
+        let mut result = ServerImportResult {
+            source,
+            dry_run,
+            matched_series: 0,
+            unmatched_series: Vec::new(),
+            metadata_imported: 0,
+            progress_imported: 0,
+            unknown_users: Vec::new(),
+            contents_grouped: 0,
+            groups_created: 0,
+        };
+        let mut users: HashMap<String, Option<i64>> = HashMap::new();
+        let mut matched: HashMap<&str, usize> = HashMap::new();
+        let mut used: HashSet<usize> = HashSet::new();
+
+        for series in &data.series {
+            let found =
+                find_content(series, &by_folder, &by_title).filter(|idx| !used.contains(idx));
+            let Some(idx) = found else {
+                result.unmatched_series.push(series.name.clone());
+                continue;
+            };
+            used.insert(idx);
+            matched.insert(series.id.as_str(), idx);
+            result.matched_series += 1;
+
+            let content = &contents[idx];
+            if let Some(request) = metadata_request(series, content) {
+                if !dry_run {
+                    MetadataEditsService::update(pool, content.id, request).await?;
+                }
+                result.metadata_imported += 1;
+            }
+            result.progress_imported +=
+                Self::import_progress(pool, content, &series.books, &mut users, dry_run).await?;
+        }
+
+        // A content is in one group at most, so contents already in a group
+        // keep it
+        let mut groups: HashMap<String, Option<i64>> = SeriesGroupRepository::list(pool)
+            .await?
+            .into_iter()
+            .map(|group| (group.name, Some(group.id)))
+            .collect();
+        let mut grouped: HashSet<i64> = HashSet::new();
+        for collection in &data.collections {
+            let name = collection.name.trim();
+            if name.is_empty() || name.chars().count() > MAX_NAME_CHARS {
+                continue;
+            }
+            let members: Vec<&Content> = collection
+                .series_ids
+                .iter()
+                .filter_map(|id| matched.get(id.as_str()))
+                .map(|&idx| &contents[idx])
+                .filter(|c| c.series_group_id.is_none() && !grouped.contains(&c.id))
+                .collect();
+            if members.is_empty() {
+                continue;
+            }
+
+            let group_id = match groups.get(name) {
+                Some(group_id) => *group_id,
+                None => {
+                    let group_id = if dry_run {
+                        None
+                    } else {
+                        Some(SeriesGroupRepository::create(pool, name).await?.id)
+                    };
+                    groups.insert(name.to_string(), group_id);
+                    result.groups_created += 1;
+                    group_id
+                }
+            };
+            for content in members {
+                if let Some(group_id) = group_id {
+                    SeriesGroupRepository::set_content_group(pool, content.id, Some(group_id))
+                        .await?;
+                }
+                grouped.insert(content.id);
+                result.contents_grouped += 1;
+            }
+        }
+
+        result.unknown_users = users
+            .into_iter()
+            .filter(|(_, user_id)| user_id.is_none())
+            .map(|(username, _)| username)
+            .collect();
+        result.unknown_users.sort();
+
+        Ok(result)
+    }
+
+    /// Import the progress on the books of a series into the chapters of a
+    /// content. Users are looked up by name once and cached in `users`.
+    async fn import_progress(
+        pool: &Pool<Sqlite>,
+        content: &Content,
+        books: &[SourceBook],
+        users: &mut HashMap<String, Option<i64>>,
+        dry_run: bool,
+    ) -> Result<u64> {
+        let chapters = ChapterRepository::list_by_content(pool, content.id).await?;
+        let mut imported = 0;
+
+        for book in books.iter().filter(|b| !b.progress.is_empty()) {
+            let Some(chapter) = find_chapter(&chapters, book) else {
+                continue;
+            };
+
+            for progress in &book.progress {
+                let user_id = match users.get(&progress.username) {
+                    Some(user_id) => *user_id,
+                    None => {
+                        let user_id = find_user(pool, &progress.username).await?;
+                        users.insert(progress.username.clone(), user_id);
+                        user_id
+                    }
+                };
+                let Some(user_id) = user_id else {
+                    continue;
+                };
+                let Some((position, percentage)) =
+                    ProgressService::imported_position(chapter, progress.completed, progress.page)
+                else {
+                    continue;
+                };
+
+                let progress = ExportedProgress {
+                    username: progress.username.clone(),
+                    position,
+                    percentage,
+                    updated_at: progress.updated_at,
+                    novel: None,
+                };
+                let written = if dry_run {
+                    ProgressRepository::find_by_user_and_chapter(pool, user_id, chapter.id)
+                        .await?
+                        .is_none_or(|existing| progress.updated_at > existing.updated_at)
+                } else {
+                    ProgressRepository::import(pool, user_id, chapter.id, &progress).await?
+                };
+                if written {
+                    imported += 1;
+                }
+            }
+        }
+
+        Ok(imported)
+    }
+}
+
+/// The user with a name, or else with the part before the `@` of an email
+/// address, as Komga users log in with their email address.
+async fn find_user(pool: &Pool<Sqlite>, name: &str) -> Result<Option<i64>> {
+    if let Some(user) = UserRepository::find_by_username(pool, name).await? {
+        return Ok(Some(user.id));
+    }
+    match name.split_once('@') {
+        Some((local, _)) if !local.is_empty() => Ok(UserRepository::find_by_username(pool, local)
+            .await?
+            .map(|user| user.id)),
+        _ => Ok(None),
+    }
+}
+
+/// The only content with the folder name of a series, or else with one of
+/// its titles.
+fn find_content(
+    series: &SourceSeries,
+    by_folder: &HashMap<String, Vec<usize>>,
+    by_title: &HashMap<String, Vec<usize>>,
+) -> Option<usize> {
+    let unique = |candidates: Option<&Vec<usize>>| match candidates.map(Vec::as_slice) {
+        Some([idx]) => Some(*idx),
+        _ => None,
+    };
+    series
+        .folder
+        .as_deref()
+        .and_then(|folder| unique(by_folder.get(&match_key(folder))))
+        .or_else(|| {
+            series
+                .titles
+                .iter()
+                .find_map(|title| unique(by_title.get(&match_key(title))))
+        })
+}
+
+/// The chapter with the file name of a book, or else with its number or
+/// title.
+fn find_chapter<'a>(chapters: &'a [Chapter], book: &SourceBook) -> Option<&'a Chapter> {
+    let file_name = |c: &&Chapter| {
+        book.file_name.as_deref().is_some_and(|name| {
+            Path::new(&c.file_path)
+                .file_name()
+                .is_some_and(|f| f.to_string_lossy() == name)
+        })
+    };
+    let number = |c: &&Chapter| match (book.number, c.number) {
+        (Some(a), Some(b)) => (a - b).abs() < 0.001,
+        _ => false,
+    };
+    let title = match_key(&book.title);
+
+    chapters
+        .iter()
+        .find(file_name)
+        .or_else(|| chapters.iter().find(number))
+        .or_else(|| chapters.iter().find(|c| match_key(&c.title) == title))
+}
+
+/// Edits with the metadata of a series, if it has any.
+fn metadata_request(
+    series: &SourceSeries,
+    content: &Content,
+) -> Option<UpdateMetadataEditsRequest> {
+    let request = UpdateMetadataEditsRequest {
+        title: series.title.clone().filter(|title| *title != content.title),
+        summary: series.summary.clone().map(Some),
+        status: series.status.map(Some),
+        language: series.language.clone().map(Some),
+        tags: (!series.tags.is_empty()).then(|| Some(series.tags.clone())),
+        authors: (!series.authors.is_empty()).then(|| Some(series.authors.clone())),
+        age_rating: series.age_rating.map(Some),
+        ..Default::default()
+    };
+
+    let any = request.title.is_some()
+        || request.summary.is_some()
+        || request.status.is_some()
+        || request.language.is_some()
+        || request.tags.is_some()
+        || request.authors.is_some()
+        || request.age_rating.is_some();
+    any.then_some(request)
+}
+
+/// Text with blank text as no value.
+fn non_blank(text: Option<String>) -> Option<String> {
+    text.map(|t| t.trim().to_string()).filter(|t| !t.is_empty())
+}
+
+/// Age rating accepted by metadata edits.
+fn valid_age_rating(age_rating: Option<i64>) -> Option<i32> {
+    age_rating
+        .and_then(|r| i32::try_from(r).ok())
+        .filter(|r| (0..=MAX_AGE_RATING).contains(r))
+}
+
+/// Name of the last component of a path, or of a `file:` URL as Komga
+/// stores them.
+fn path_file_name(path: &str) -> Option<String> {
+    let path = match path.strip_prefix("file:") {
+        Some(url) => urlencoding::decode(url)
+            .map(|p| p.into_owned())
+            .unwrap_or_else(|_| url.to_string()),
+        None => path.to_string(),
+    };
+    Path::new(path.trim_end_matches(['/', '\\']))
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+}
+
+/// A date as either server stores it, in UTC. Unknown dates are the epoch,
+/// so they never win over progress made here.
+fn parse_date(value: Option<&str>) -> DateTime<Utc> {
+    let Some(value) = value else {
+        return DateTime::UNIX_EPOCH;
+    };
+    DateTime::parse_from_rfc3339(value)
+        .map(|date| date.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+                .iter()
+                .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+                .map(|date| date.and_utc())
+        })
+        .unwrap_or(DateTime::UNIX_EPOCH)
+}
+
+/// Whether a table exists in a database.
+async fn table_exists(
+    conn: &mut SqliteConnection,
+    table: &str,
+) -> std::result::Result<bool, sqlx::Error> {
+    let (count,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?")
+            .bind(table)
+            .fetch_one(conn)
+            .await?;
+    Ok(count > 0)
+}
+
+/// Whether a table has a column.
+async fn column_exists(
+    conn: &mut SqliteConnection,
+    table: &str,
+    column: &str,
+) -> std::result::Result<bool, sqlx::Error> {
+    let (count,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM pragma_table_info(?) WHERE name = ?")
+            .bind(table)
+            .bind(column)
+            .fetch_one(conn)
+            .await?;
+    Ok(count > 0)
+}
+
+/// Read a Komga database.
+async fn read_komga(conn: &mut SqliteConnection) -> std::result::Result<SourceData, sqlx::Error> {
+    type SeriesRow = (
+        String,
+        String,
+        String,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        Option<i64>,
+    );
+    let rows: Vec<SeriesRow> = sqlx::query_as(
+        r#"
+        SELECT s.ID, s.NAME, s.URL, m.TITLE, m.SUMMARY, m.STATUS, m.LANGUAGE,
+            CAST(m.AGE_RATING AS INTEGER)
+        FROM SERIES s
+        LEFT JOIN SERIES_METADATA m ON m.SERIES_ID = s.ID
+        ORDER BY s.NAME
+        "#,
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut data = SourceData::default();
+    for (id, name, url, title, summary, status, language, age_rating) in rows {
+        let title = non_blank(title).filter(|title| *title != name);
+        data.series.push(SourceSeries {
+            id,
+            titles: [Some(name.clone()), title.clone()]
+                .into_iter()
+                .flatten()
+                .collect(),
+            folder: path_file_name(&url),
+            title,
+            summary: non_blank(summary),
+            // Ongoing is the default, so it tells nothing
+            status: status
+                .as_deref()
+                .and_then(SeriesStatus::from_komga_name)
+                .filter(|status| *status != SeriesStatus::Ongoing),
+            language: non_blank(language),
+            age_rating: valid_age_rating(age_rating),
+            name,
+            ..Default::default()
+        });
+    }
+    let index = data.index();
+
+    let tags: Vec<(String, String)> = sqlx::query_as(
+        r#"
+        SELECT SERIES_ID, GENRE FROM SERIES_METADATA_GENRE
+        UNION ALL
+        SELECT SERIES_ID, TAG FROM SERIES_METADATA_TAG
+        "#,
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    add_tags(&mut data, &index, tags);
+
+    let authors: Vec<(String, String, String)> = sqlx::query_as(
+        r#"
+        SELECT DISTINCT b.SERIES_ID, a.NAME, a.ROLE
+        FROM BOOK_METADATA_AUTHOR a
+        INNER JOIN BOOK b ON b.ID = a.BOOK_ID
+        ORDER BY a.NAME
+        "#,
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    for (series_id, name, role) in authors {
+        if let Some(&idx) = index.get(&series_id) {
+            let author = MetadataAuthor { name, role };
+            if !data.series[idx].authors.contains(&author) {
+                data.series[idx].authors.push(author);
+            }
+        }
+    }
+
+    let books: Vec<(String, String, String, String, Option<String>, Option<f64>)> = sqlx::query_as(
+        r#"
+            SELECT b.ID, b.SERIES_ID, b.NAME, b.URL, m.TITLE, CAST(m.NUMBER_SORT AS REAL)
+            FROM BOOK b
+            LEFT JOIN BOOK_METADATA m ON m.BOOK_ID = b.ID
+            ORDER BY b.SERIES_ID, m.NUMBER_SORT
+            "#,
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    let mut book_index: HashMap<String, (usize, usize)> = HashMap::new();
+    for (id, series_id, name, url, title, number) in books {
+        let Some(&idx) = index.get(&series_id) else {
+            continue;
+        };
+        book_index.insert(id, (idx, data.series[idx].books.len()));
+        data.series[idx].books.push(SourceBook {
+            file_name: path_file_name(&url),
+            title: non_blank(title).unwrap_or(name),
+            number: number.map(|n| n as f32),
+            progress: Vec::new(),
+        });
+    }
+
+    let progress: Vec<(String, String, i64, i64, Option<String>)> = sqlx::query_as(
+        r#"
+        SELECT r.BOOK_ID, u.EMAIL, CAST(r.PAGE AS INTEGER), CAST(r.COMPLETED AS INTEGER),
+            CAST(r.LAST_MODIFIED_DATE AS TEXT)
+        FROM READ_PROGRESS r
+        INNER JOIN "USER" u ON u.ID = r.USER_ID
+        "#,
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    for (book_id, email, page, completed, updated_at) in progress {
+        if let Some(&(idx, book)) = book_index.get(&book_id) {
+            data.series[idx].books[book].progress.push(SourceProgress {
+                username: email,
+                // Komga counts pages from 1
+                page: page - 1,
+                completed: completed != 0,
+                updated_at: parse_date(updated_at.as_deref()),
+            });
+        }
+    }
+
+    let collections: Vec<(String, String)> = sqlx::query_as(
+        r#"
+        SELECT c.NAME, cs.SERIES_ID
+        FROM COLLECTION c
+        INNER JOIN COLLECTION_SERIES cs ON cs.COLLECTION_ID = c.ID
+        ORDER BY c.NAME, cs.NUMBER
+        "#,
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    add_collections(&mut data, collections);
+
+    Ok(data)
+}
+
+/// Read a Kavita database.
+///
+/// Tables that changed between Kavita versions are looked up first, and
+/// what is missing is left out.
+async fn read_kavita(conn: &mut SqliteConnection) -> std::result::Result<SourceData, sqlx::Error> {
+    type SeriesRow = (
+        String,
+        String,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        Option<i64>,
+        Option<String>,
+        Option<i64>,
+    );
+    let rows: Vec<SeriesRow> = sqlx::query_as(
+        r#"
+        SELECT CAST(s.Id AS TEXT), s.Name, s.LocalizedName, s.OriginalName, s.FolderPath,
+            m.Summary, CAST(m.PublicationStatus AS INTEGER), m.Language,
+            CAST(m.AgeRating AS INTEGER)
+        FROM Series s
+        LEFT JOIN SeriesMetadata m ON m.SeriesId = s.Id
+        ORDER BY s.Name
+        "#,
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut data = SourceData::default();
+    for (id, name, localized, original, folder, summary, status, language, age_rating) in rows {
+        let titles = [
+            Some(name.clone()),
+            non_blank(localized),
+            non_blank(original),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        data.series.push(SourceSeries {
+            id,
+            titles,
+            folder: folder.as_deref().and_then(path_file_name),
+            summary: non_blank(summary),
+            status: status.and_then(kavita_status),
+            language: non_blank(language),
+            age_rating: age_rating.and_then(kavita_age_rating),
+            name,
+            ..Default::default()
+        });
+    }
+    let index = data.index();
+
+    for (join, table, column) in [
+        ("GenreSeriesMetadata", "Genre", "GenresId"),
+        ("SeriesMetadataTag", "Tag", "TagsId"),
+    ] {
+        if !table_exists(conn, join).await? {
+            continue;
+        }
+        let tags: Vec<(String, String)> = sqlx::query_as(&format!(
+            r#"
+            SELECT CAST(m.SeriesId AS TEXT), t.Title
+            FROM {join} j
+            INNER JOIN {table} t ON t.Id = j.{column}
+            INNER JOIN SeriesMetadata m ON m.Id = j.SeriesMetadatasId
+            "#
+        ))
+        .fetch_all(&mut *conn)
+        .await?;
+        add_tags(&mut data, &index, tags);
+    }
+
+    let books: Vec<(
+        String,
+        String,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+    )> = sqlx::query_as(
+        r#"
+            SELECT CAST(c.Id AS TEXT), CAST(v.SeriesId AS TEXT), c.Title, c.Range,
+                (SELECT f.FilePath FROM MangaFile f WHERE f.ChapterId = c.Id ORDER BY f.Id LIMIT 1)
+            FROM Chapter c
+            INNER JOIN Volume v ON v.Id = c.VolumeId
+            ORDER BY v.SeriesId, c.Id
+            "#,
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    let mut book_index: HashMap<String, (usize, usize)> = HashMap::new();
+    for (id, series_id, title, range, file_path) in books {
+        let Some(&idx) = index.get(&series_id) else {
+            continue;
+        };
+        let file_name = file_path.as_deref().and_then(path_file_name);
+        book_index.insert(id, (idx, data.series[idx].books.len()));
+        data.series[idx].books.push(SourceBook {
+            title: non_blank(title)
+                .or_else(|| file_name.clone())
+                .unwrap_or_default(),
+            number: range.and_then(|r| r.trim().parse().ok()),
+            file_name,
+            progress: Vec::new(),
+        });
+    }
+
+    // Newer versions keep the modification time in UTC in its own column
+    let modified = if column_exists(conn, "AppUserProgresses", "LastModifiedUtc").await? {
+        "LastModifiedUtc"
+    } else {
+        "LastModified"
+    };
+    let progress: Vec<(String, String, i64, i64, Option<String>)> = sqlx::query_as(&format!(
+        r#"
+        SELECT CAST(p.ChapterId AS TEXT), u.UserName, CAST(p.PagesRead AS INTEGER),
+            CAST(c.Pages AS INTEGER), CAST(p.{modified} AS TEXT)
+        FROM AppUserProgresses p
+        INNER JOIN AspNetUsers u ON u.Id = p.AppUserId
+        INNER JOIN Chapter c ON c.Id = p.ChapterId
+        WHERE p.PagesRead > 0
+        "#
+    ))
+    .fetch_all(&mut *conn)
+    .await?;
+    for (chapter_id, username, pages_read, pages, updated_at) in progress {
+        if let Some(&(idx, book)) = book_index.get(&chapter_id) {
+            data.series[idx].books[book].progress.push(SourceProgress {
+                username,
+                page: pages_read - 1,
+                completed: pages > 0 && pages_read >= pages,
+                updated_at: parse_date(updated_at.as_deref()),
+            });
+        }
+    }
+
+    // Collections belong to users since Kavita 0.8, and were shared before
+    let collections_query = if table_exists(conn, "AppUserCollection").await? {
+        Some(
+            r#"
+            SELECT c.Title, CAST(cs.ItemsId AS TEXT)
+            FROM AppUserCollection c
+            INNER JOIN AppUserCollectionSeries cs ON cs.CollectionsId = c.Id
+            ORDER BY c.Title
+            "#,
+        )
+    } else if table_exists(conn, "CollectionTag").await? {
+        Some(
+            r#"
+            SELECT c.Title, CAST(m.SeriesId AS TEXT)
+            FROM CollectionTag c
+            INNER JOIN CollectionTagSeriesMetadata cm ON cm.CollectionTagsId = c.Id
+            INNER JOIN SeriesMetadata m ON m.Id = cm.SeriesMetadatasId
+            ORDER BY c.Title
+            "#,
+        )
+    } else {
+        None
+    };
+    if let Some(query) = collections_query {
+        let collections: Vec<(String, String)> =
+            sqlx::query_as(query).fetch_all(&mut *conn).await?;
+        add_collections(&mut data, collections);
+    }
+
+    Ok(data)
+}
+
+/// Add genres and tags, by series ID, to the tags of series.
+fn add_tags(data: &mut SourceData, index: &HashMap<String, usize>, tags: Vec<(String, String)>) {
+    for (series_id, tag) in tags {
+        let Some(&idx) = index.get(&series_id) else {
+            continue;
+        };
+        let tag = tag.trim().to_string();
+        if !tag.is_empty() && !data.series[idx].tags.contains(&tag) {
+            data.series[idx].tags.push(tag);
+        }
+    }
+}
+
+/// Add collections from rows of collection name and series ID, grouped by
+/// name.
+fn add_collections(data: &mut SourceData, rows: Vec<(String, String)>) {
+    for (name, series_id) in rows {
+        match data.collections.iter_mut().find(|c| c.name == name) {
+            Some(collection) => collection.series_ids.push(series_id),
+            None => data.collections.push(SourceCollection {
+                name,
+                series_ids: vec![series_id],
+            }),
+        }
+    }
+}
+
+/// Status of a value of Kavita's `PublicationStatus`. Ongoing is the
+/// default, so it tells nothing.
+fn kavita_status(value: i64) -> Option<SeriesStatus> {
+    match value {
+        1 => Some(SeriesStatus::Hiatus),
+        2 | 4 => Some(SeriesStatus::Ended),
+        3 => Some(SeriesStatus::Abandoned),
+        _ => None,
+    }
+}
+
+/// Minimum age of a value of Kavita's `AgeRating`.
+fn kavita_age_rating(value: i64) -> Option<i32> {
+    match value {
+        // Early childhood, everyone, G
+        2..=4 => Some(0),
+        // Everyone 10+
+        5 => Some(10),
+        // PG, kids to adults
+        6 | 7 => Some(8),
+        // Teen
+        8 => Some(13),
+        // Mature 15+
+        9 => Some(15),
+        // Mature 17+, mature
+        10 | 11 => Some(17),
+        // R18+, adults only, X18+
+        12..=14 => Some(18),
+        // Not applicable, unknown, rating pending
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::metadata_edits::MetadataEditsRepository;
+    use crate::test_fixtures::{
+        TestChapter, create_test_content, create_test_db, create_test_library,
+        create_test_scan_path, create_test_user,
+    };
+
+    async fn setup() -> Pool<Sqlite> {
+        let pool = create_test_db().await;
+        create_test_user(&pool, 1, "reader").await;
+        create_test_library(&pool, 1, "Comics").await;
+        create_test_scan_path(&pool, 1, 1, "/comics").await;
+        create_test_content(&pool, 1, 1, "Journey").await;
+        for (id, file_path) in [
+            (1, "/comics/Journey/Vol 1.cbz"),
+            (2, "/comics/Journey/Vol 2.cbz"),
+        ] {
+            TestChapter::new(id, 1, file_path)
+                .page_count(20)
+                .insert(&pool)
+                .await;
+        }
+        pool
+    }
+
+    /// A Komga database with the tables the import reads.
+    async fn komga_database(path: &Path) -> Vec<u8> {
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true);
+        let mut conn = SqliteConnection::connect_with(&options).await.unwrap();
+        sqlx::query(
+            r#"
+            CREATE TABLE SERIES (ID TEXT, NAME TEXT, URL TEXT);
+            CREATE TABLE SERIES_METADATA (
+                SERIES_ID TEXT, TITLE TEXT, SUMMARY TEXT, STATUS TEXT, LANGUAGE TEXT,
+                AGE_RATING INTEGER
+            );
+            CREATE TABLE SERIES_METADATA_GENRE (SERIES_ID TEXT, GENRE TEXT);
+            CREATE TABLE SERIES_METADATA_TAG (SERIES_ID TEXT, TAG TEXT);
+            CREATE TABLE BOOK (ID TEXT, SERIES_ID TEXT, NAME TEXT, URL TEXT);
+            CREATE TABLE BOOK_METADATA (BOOK_ID TEXT, TITLE TEXT, NUMBER_SORT REAL);
+            CREATE TABLE BOOK_METADATA_AUTHOR (BOOK_ID TEXT, NAME TEXT, ROLE TEXT);
+            CREATE TABLE "USER" (ID TEXT, EMAIL TEXT);
+            CREATE TABLE READ_PROGRESS (
+                BOOK_ID TEXT, USER_ID TEXT, PAGE INTEGER, COMPLETED BOOLEAN,
+                LAST_MODIFIED_DATE DATETIME
+            );
+            CREATE TABLE COLLECTION (ID TEXT, NAME TEXT);
+            CREATE TABLE COLLECTION_SERIES (COLLECTION_ID TEXT, SERIES_ID TEXT, NUMBER INTEGER);
+
+            INSERT INTO SERIES VALUES
+                ('s1', 'Journey', 'file:/data/Journey/'),
+                ('s2', 'Missing', 'file:/data/Missing/');
+            INSERT INTO SERIES_METADATA VALUES
+                ('s1', 'Journey', 'A long trip.', 'ENDED', 'en', 16),
+                ('s2', 'Missing', '', 'ONGOING', '', NULL);
+            INSERT INTO SERIES_METADATA_GENRE VALUES ('s1', 'Adventure');
+            INSERT INTO SERIES_METADATA_TAG VALUES ('s1', 'Travel'), ('s1', 'Adventure');
+            INSERT INTO BOOK VALUES
+                ('b1', 's1', 'Vol 1', 'file:/data/Journey/Vol%201.cbz'),
+                ('b2', 's1', 'Vol 2', 'file:/data/Journey/Vol%202.cbz');
+            INSERT INTO BOOK_METADATA VALUES ('b1', 'Vol 1', 1), ('b2', 'Vol 2', 2);
+            INSERT INTO BOOK_METADATA_AUTHOR VALUES ('b1', 'Ann', 'writer'), ('b2', 'Ann', 'writer');
+            INSERT INTO "USER" VALUES ('u1', 'reader@example.com'), ('u2', 'ghost@example.com');
+            INSERT INTO READ_PROGRESS VALUES
+                ('b1', 'u1', 20, 1, '2026-01-02 03:04:05.678'),
+                ('b2', 'u1', 5, 0, '2026-01-03 03:04:05'),
+                ('b1', 'u2', 3, 0, '2026-01-02 03:04:05');
+            INSERT INTO COLLECTION VALUES ('c1', 'Favourites');
+            INSERT INTO COLLECTION_SERIES VALUES ('c1', 's1', 0), ('c1', 's2', 1);
+            "#,
+        )
+        .execute(&mut conn)
+        .await
+        .unwrap();
+        conn.close().await.unwrap();
+        std::fs::read(path).unwrap()
+    }
+
+    #[test]
+    fn test_parse_source_values() {
+        assert_eq!(
+            path_file_name("file:/data/My%20Series/").as_deref(),
+            Some("My Series")
+        );
+        assert_eq!(
+            path_file_name("/manga/Series/Ch 1.cbz").as_deref(),
+            Some("Ch 1.cbz")
+        );
+        assert_eq!(
+            parse_date(Some("2026-01-02 03:04:05.678")).to_rfc3339(),
+            "2026-01-02T03:04:05.678+00:00"
+        );
+        assert_eq!(parse_date(Some("soon")), DateTime::UNIX_EPOCH);
+        assert_eq!(kavita_age_rating(12), Some(18));
+        assert_eq!(kavita_age_rating(0), None);
+        assert_eq!(kavita_status(0), None);
+    }
+
+    #[tokio::test]
+    async fn test_import_komga_database() {
+        let pool = setup().await;
+        let dir = tempfile::tempdir().unwrap();
+        let data = komga_database(&dir.path().join("database.sqlite")).await;
+
+        // A dry run reports without writing
+        let result = ServerImportService::import(&pool, ImportSource::Komga, &data, true)
+            .await
+            .unwrap();
+        assert_eq!(result.matched_series, 1);
+        assert_eq!(result.unmatched_series, ["Missing"]);
+        assert_eq!(result.metadata_imported, 1);
+        assert_eq!(result.progress_imported, 2);
+        assert_eq!((result.groups_created, result.contents_grouped), (1, 1));
+        assert_eq!(result.unknown_users, ["ghost@example.com"]);
+        assert!(SeriesGroupRepository::list(&pool).await.unwrap().is_empty());
+        assert!(
+            MetadataEditsRepository::find(&pool, 1)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let result = ServerImportService::import(&pool, ImportSource::Komga, &data, false)
+            .await
+            .unwrap();
+        assert_eq!(result.progress_imported, 2);
+
+        let edits = MetadataEditsRepository::find(&pool, 1)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(edits.summary.as_deref(), Some("A long trip."));
+        assert_eq!(edits.status, Some(SeriesStatus::Ended));
+        assert_eq!(
+            edits.tags,
+            Some(vec!["Adventure".to_string(), "Travel".to_string()])
+        );
+        assert_eq!(
+            edits.authors,
+            Some(vec![MetadataAuthor {
+                name: "Ann".to_string(),
+                role: "writer".to_string(),
+            }])
+        );
+
+        let content = ContentRepository::find_by_id(&pool, 1)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(content.age_rating, Some(16));
+        let groups = SeriesGroupRepository::list(&pool).await.unwrap();
+        assert_eq!(groups[0].name, "Favourites");
+        assert_eq!(content.series_group_id, Some(groups[0].id));
+
+        let progress = ProgressRepository::find_by_user_and_content(&pool, 1, 1)
+            .await
+            .unwrap();
+        assert_eq!(
+            progress
+                .iter()
+                .map(|p| (p.chapter_id, p.position, p.percentage))
+                .collect::<Vec<_>>(),
+            [(1, 19, 100.0), (2, 4, 25.0)]
+        );
+
+        assert!(
+            ServerImportService::import(&pool, ImportSource::Kavita, &data, true)
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,326 @@
+//! Runtime server settings.
+//!
+//! Settings start from the values of the environment and configuration
+//! file; values saved by administrators are stored in the database and
+//! override them. Services that cache a setting subscribe to changes
+//! instead of reading the database on every use.
+
+use std::fmt::Display;
+
+use serde_json::Value;
+use sqlx::{Pool, Sqlite};
+use tokio::sync::{Mutex, watch};
+use tracing::warn;
+
+use crate::config::MAX_SCAN_CONCURRENCY;
+use crate::error::{AppError, Result};
+use crate::models::{
+    MetadataProviderKind, RuntimeSettings, RuntimeSettingsResponse, UpdateRuntimeSettingsRequest,
+};
+use crate::repository::settings::SettingsRepository;
+use crate::t;
+use crate::utils::thumbnail::{MAX_THUMBNAIL_SIZE, MIN_THUMBNAIL_SIZE, ThumbnailOptions};
+
+/// Service for runtime settings.
+pub struct SettingsService {
+    pool: Pool<Sqlite>,
+    /// Values from the environment and configuration file.
+    defaults: RuntimeSettings,
+    /// Settings in effect, also notifying subscribers of changes.
+    current: watch::Sender<RuntimeSettings>,
+    /// Serializes updates so concurrent changes are not lost.
+    update_lock: Mutex<()>,
+}
+
+impl SettingsService {
+    /// Create a new settings service. Until [`Self::load`] is called the
+    /// defaults are in effect.
+    pub fn new(pool: Pool<Sqlite>, defaults: RuntimeSettings) -> Self {
+        Self {
+            pool,
+            current: watch::Sender::new(defaults.clone()),
+            defaults,
+            update_lock: Mutex::new(()),
+        }
+    }
+
+    /// Load the stored settings over the defaults. Stored values that are
+    /// unknown or no longer valid are skipped with a warning.
+    pub async fn load(&self) -> Result<RuntimeSettings> {
+        let mut settings = self.defaults.clone();
+        for (key, value) in SettingsRepository::list(&self.pool).await? {
+            let updated = serde_json::from_str(&value)
+                .map_err(|e| AppError::Internal(e.to_string()))
+                .and_then(|value| Self::with_value(&settings, &key, value));
+            match updated {
+                Ok(updated) => settings = updated,
+                Err(e) => warn!(key = %key, error = %e, "{}", t!("settings.invalid_stored")),
+            }
+        }
+
+        self.current.send_replace(settings.clone());
+        Ok(settings)
+    }
+
+    /// Settings in effect.
+    pub fn get(&self) -> RuntimeSettings {
+        self.current.borrow().clone()
+    }
+
+    /// Settings in effect with their defaults.
+    pub fn response(&self) -> RuntimeSettingsResponse {
+        RuntimeSettingsResponse {
+            settings: self.get(),
+            defaults: self.defaults.clone(),
+        }
+    }
+
+    /// Receiver notified whenever the settings change.
+    pub fn subscribe(&self) -> watch::Receiver<RuntimeSettings> {
+        self.current.subscribe()
+    }
+
+    /// Size, format and quality of generated thumbnails.
+    pub fn thumbnail_options(&self) -> ThumbnailOptions {
+        self.current.borrow().thumbnail_options()
+    }
+
+    /// Number of libraries scanned at the same time.
+    pub fn scan_concurrency(&self) -> usize {
+        self.current.borrow().scan_concurrency
+    }
+
+    /// Providers new libraries scrape metadata from, in order.
+    pub fn metadata_providers(&self) -> Vec<MetadataProviderKind> {
+        self.current.borrow().metadata_providers.clone()
+    }
+
+    /// Whether the Komga-compatible API is served.
+    pub fn komga_enabled(&self) -> bool {
+        self.current.borrow().komga_enabled
+    }
+
+    /// Whether the Kobo sync API is served.
+    pub fn kobo_enabled(&self) -> bool {
+        self.current.borrow().kobo_enabled
+    }
+
+    /// Whether the KOReader sync API is served.
+    pub fn koreader_enabled(&self) -> bool {
+        self.current.borrow().koreader_enabled
+    }
+
+    /// Whether public share links can be created and opened.
+    pub fn sharing_enabled(&self) -> bool {
+        self.current.borrow().sharing_enabled
+    }
+
+    /// Bangumi access token metadata is scraped with.
+    pub fn bangumi_api_key(&self) -> Option<String> {
+        self.current.borrow().bangumi_api_key.clone()
+    }
+
+    /// Validate and store the given changes, then notify subscribers.
+    pub async fn update(
+        &self,
+        mut request: UpdateRuntimeSettingsRequest,
+    ) -> Result<RuntimeSettingsResponse> {
+        if let Some(providers) = request.metadata_providers.as_mut() {
+            let mut seen = Vec::with_capacity(providers.len());
+            providers.retain(|provider| {
+                let first = !seen.contains(provider);
+                seen.push(*provider);
+                first
+            });
+        }
+        if let Some(key) = request.bangumi_api_key.as_mut() {
+            *key = key.trim().to_string();
+        }
+
+        // Fields left out of the request are skipped when serializing
+        let changes =
+            serde_json::to_value(&request).map_err(|e| AppError::Internal(e.to_string()))?;
+
+        let _guard = self.update_lock.lock().await;
+        let mut settings = self.get();
+        let mut values = Vec::new();
+        for (key, value) in changes.as_object().into_iter().flatten() {
+            settings = Self::with_value(&settings, key, value.clone())?;
+            values.push((key.clone(), value.to_string()));
+        }
+
+        SettingsRepository::save(&self.pool, &values).await?;
+        self.current.send_replace(settings);
+        Ok(self.response())
+    }
+
+    /// `settings` with the value of `key` replaced, if the result is valid.
+    fn with_value(settings: &RuntimeSettings, key: &str, value: Value) -> Result<RuntimeSettings> {
+        let mut object =
+            serde_json::to_value(settings).map_err(|e| AppError::Internal(e.to_string()))?;
+        let Some(field) = object.get_mut(key) else {
+            return Err(AppError::BadRequest(
+                t!("settings.unknown_key", key = key).to_string(),
+            ));
+        };
+        *field = value;
+
+        let mut settings: RuntimeSettings = serde_json::from_value(object).map_err(|e| {
+            AppError::BadRequest(t!("settings.invalid_value", key = key, error = e).to_string())
+        })?;
+        // An empty key clears it
+        settings.bangumi_api_key = settings.bangumi_api_key.filter(|key| !key.is_empty());
+        Self::validate(&settings)?;
+        Ok(settings)
+    }
+
+    /// Check that every setting is within its accepted range.
+    fn validate(settings: &RuntimeSettings) -> Result<()> {
+        for (key, value) in [
+            ("thumbnail_width", settings.thumbnail_width),
+            ("thumbnail_height", settings.thumbnail_height),
+        ] {
+            if !(MIN_THUMBNAIL_SIZE..=MAX_THUMBNAIL_SIZE).contains(&value) {
+                return Err(out_of_range(key, MIN_THUMBNAIL_SIZE, MAX_THUMBNAIL_SIZE));
+            }
+        }
+        if !(1..=100).contains(&settings.thumbnail_quality) {
+            return Err(out_of_range("thumbnail_quality", 1, 100));
+        }
+        if !(1..=MAX_SCAN_CONCURRENCY).contains(&settings.scan_concurrency) {
+            return Err(out_of_range("scan_concurrency", 1, MAX_SCAN_CONCURRENCY));
+        }
+        Ok(())
+    }
+}
+
+fn out_of_range(key: &str, min: impl Display, max: impl Display) -> AppError {
+    AppError::BadRequest(t!("settings.out_of_range", key = key, min = min, max = max).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::create_test_db;
+    use crate::utils::thumbnail::ThumbnailFormat;
+
+    fn defaults() -> RuntimeSettings {
+        RuntimeSettings {
+            thumbnail_width: 300,
+            thumbnail_height: 450,
+            thumbnail_format: ThumbnailFormat::Jpeg,
+            thumbnail_quality: 85,
+            scan_concurrency: 1,
+            metadata_providers: vec![MetadataProviderKind::Bangumi],
+            komga_enabled: true,
+            kobo_enabled: true,
+            koreader_enabled: true,
+            sharing_enabled: false,
+            bangumi_api_key: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_defaults_without_stored_values() {
+        let service = SettingsService::new(create_test_db().await, defaults());
+        assert_eq!(service.load().await.unwrap(), defaults());
+        assert!(service.komga_enabled());
+    }
+
+    #[tokio::test]
+    async fn test_update_is_stored_and_notified() {
+        let pool = create_test_db().await;
+        let service = SettingsService::new(pool.clone(), defaults());
+        let mut changes = service.subscribe();
+
+        let request = UpdateRuntimeSettingsRequest {
+            thumbnail_format: Some(ThumbnailFormat::Webp),
+            metadata_providers: Some(vec![
+                MetadataProviderKind::AniList,
+                MetadataProviderKind::AniList,
+                MetadataProviderKind::Bangumi,
+            ]),
+            komga_enabled: Some(false),
+            ..Default::default()
+        };
+        let response = service.update(request).await.unwrap();
+        assert_eq!(response.defaults, defaults());
+        assert_eq!(
+            response.settings.metadata_providers,
+            vec![MetadataProviderKind::AniList, MetadataProviderKind::Bangumi]
+        );
+
+        assert!(changes.has_changed().unwrap());
+        assert!(!changes.borrow_and_update().komga_enabled);
+
+        // A restarted server sees the stored values
+        let reloaded = SettingsService::new(pool, defaults());
+        let settings = reloaded.load().await.unwrap();
+        assert_eq!(settings.thumbnail_format, ThumbnailFormat::Webp);
+        assert!(!settings.komga_enabled);
+        assert_eq!(settings.thumbnail_width, 300);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_update_is_rejected() {
+        let service = SettingsService::new(create_test_db().await, defaults());
+        let request = UpdateRuntimeSettingsRequest {
+            thumbnail_quality: Some(0),
+            komga_enabled: Some(false),
+            ..Default::default()
+        };
+        assert!(service.update(request).await.is_err());
+        assert_eq!(service.get(), defaults());
+    }
+
+    #[tokio::test]
+    async fn test_bangumi_api_key_is_set_and_cleared() {
+        let pool = create_test_db().await;
+        let service = SettingsService::new(pool.clone(), defaults());
+
+        let request = UpdateRuntimeSettingsRequest {
+            bangumi_api_key: Some(" token ".to_string()),
+            ..Default::default()
+        };
+        service.update(request).await.unwrap();
+        assert_eq!(service.bangumi_api_key().as_deref(), Some("token"));
+
+        let request = UpdateRuntimeSettingsRequest {
+            bangumi_api_key: Some(String::new()),
+            ..Default::default()
+        };
+        service.update(request).await.unwrap();
+        assert_eq!(service.bangumi_api_key(), None);
+
+        // The cleared key does not come back over a configured one
+        let configured = RuntimeSettings {
+            bangumi_api_key: Some("configured".to_string()),
+            ..defaults()
+        };
+        let reloaded = SettingsService::new(pool, configured);
+        assert_eq!(reloaded.load().await.unwrap().bangumi_api_key, None);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_stored_values_are_skipped() {
+        let pool = create_test_db().await;
+        let values = [
+            ("scan_concurrency", "1000"),
+            ("removed_setting", "true"),
+            ("thumbnail_quality", "\"high\""),
+            ("thumbnail_width", "600"),
+        ]
+        .map(|(key, value)| (key.to_string(), value.to_string()));
+        SettingsRepository::save(&pool, &values).await.unwrap();
+
+        let service = SettingsService::new(pool, defaults());
+        let settings = service.load().await.unwrap();
+        assert_eq!(
+            settings,
+            RuntimeSettings {
+                thumbnail_width: 600,
+                ..defaults()
+            }
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! Public share links.
+//!
+//! Users share a content they may see, or one of its chapters, through a
+//! link carrying a random token. Anyone holding the token can read the
+//! shared pages and see the thumbnail without logging in, until the link
+//! expires or is revoked by its creator or an administrator. Whether links
+//! can be created and opened is a runtime setting, checked by the handlers.
+
+use chrono::{Duration, Utc};
+use sqlx::{Pool, Sqlite};
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::models::{
+    CreateShareLinkRequest, DEFAULT_SHARE_HOURS, MAX_SHARE_HOURS, ShareLink, SharedChapter,
+    SharedContent,
+};
+use crate::repository::share::ShareRepository;
+use crate::services::age_rating::AgeRatingService;
+use crate::services::content::ContentService;
+use crate::t;
+
+/// Service for share link operations.
+pub struct ShareService;
+
+impl ShareService {
+    /// Share a content, or only one of its chapters, for a limited time.
+    pub async fn create(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        content_id: i64,
+        request: CreateShareLinkRequest,
+    ) -> Result<ShareLink> {
+        let hours = request.expires_in_hours.unwrap_or(DEFAULT_SHARE_HOURS);
+        if !(1..=MAX_SHARE_HOURS).contains(&hours) {
+            return Err(AppError::BadRequest(
+                t!("share.invalid_expiry", max = MAX_SHARE_HOURS).to_string(),
+            ));
+        }
+
+        let content = ContentService::get_content(pool, content_id).await?;
+        AgeRatingService::check(pool, user_id, &content).await?;
+        if let Some(chapter_id) = request.chapter_id {
+            ContentService::find_chapter(pool, content_id, chapter_id).await?;
+        }
+
+        let token = Uuid::new_v4().simple().to_string();
+        let expires_at = Utc::now() + Duration::hours(hours);
+        ShareRepository::create(
+            pool,
+            &token,
+            user_id,
+            content_id,
+            request.chapter_id,
+            expires_at,
+        )
+        .await
+    }
+
+    /// List share links, newest first, only those of one user if given.
+    pub async fn list(pool: &Pool<Sqlite>, user_id: Option<i64>) -> Result<Vec<ShareLink>> {
+        ShareRepository::list(pool, user_id).await
+    }
+
+    /// Revoke a share link, only if it belongs to the given user when one
+    /// is given.
+    pub async fn revoke(pool: &Pool<Sqlite>, id: i64, user_id: Option<i64>) -> Result<()> {
+        if !ShareRepository::delete(pool, id, user_id).await? {
+            return Err(AppError::NotFound(
+                t!("share.id_not_found", id = id).to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Find the link of a token, unless it has expired.
+    pub async fn resolve(pool: &Pool<Sqlite>, token: &str) -> Result<ShareLink> {
+        ShareRepository::find_by_token(pool, token)
+            .await?
+            .filter(|link| !link.is_expired())
+            .ok_or_else(|| AppError::NotFound(t!("share.not_found").to_string()))
+    }
+
+    /// Title, thumbnail version and readable chapters of a shared content.
+    pub async fn shared_content(pool: &Pool<Sqlite>, link: &ShareLink) -> Result<SharedContent> {
+        let content = ContentService::get_content(pool, link.content_id).await?;
+        let chapters = ContentService::list_chapters(pool, link.content_id)
+            .await?
+            .into_iter()
+            .filter(|chapter| link.chapter_id.is_none_or(|id| id == chapter.id))
+            .map(SharedChapter::from)
+            .collect();
+        Ok(SharedContent {
+            title: content.title,
+            thumbnail_etag: content.thumbnail_etag,
+            chapters,
+            expires_at: link.expires_at,
+        })
+    }
+
+    /// Fail unless a chapter can be read through a link.
+    pub async fn check_chapter(
+        pool: &Pool<Sqlite>,
+        link: &ShareLink,
+        chapter_id: i64,
+    ) -> Result<()> {
+        if link.chapter_id.is_some_and(|id| id != chapter_id) {
+            return Err(AppError::NotFound(
+                t!("content.chapter_not_found", id = chapter_id).to_string(),
+            ));
+        }
+        ContentService::find_chapter(pool, link.content_id, chapter_id).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{
+        create_test_chapter, create_test_content, create_test_db, create_test_library,
+        create_test_scan_path, create_test_user,
+    };
+
+    async fn setup() -> Pool<Sqlite> {
+        let pool = create_test_db().await;
+        create_test_user(&pool, 1, "alice").await;
+        create_test_user(&pool, 2, "bob").await;
+        create_test_library(&pool, 1, "Comics").await;
+        create_test_scan_path(&pool, 1, 1, "/comics").await;
+        create_test_content(&pool, 1, 1, "Shared").await;
+        create_test_content(&pool, 2, 1, "Other").await;
+        create_test_chapter(&pool, 1, 1, "/comics/Shared/Ch 1.cbz").await;
+        create_test_chapter(&pool, 2, 1, "/comics/Shared/Ch 2.cbz").await;
+        create_test_chapter(&pool, 3, 2, "/comics/Other/Ch 1.cbz").await;
+        pool
+    }
+
+    fn request(chapter_id: Option<i64>, expires_in_hours: Option<i64>) -> CreateShareLinkRequest {
+        CreateShareLinkRequest {
+            chapter_id,
+            expires_in_hours,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_content_link_shares_every_chapter() {
+        let pool = setup().await;
+
+        let link = ShareService::create(&pool, 1, 1, request(None, None))
+            .await
+            .unwrap();
+        assert_eq!(
+            (link.username.as_str(), link.content_title.as_str()),
+            ("alice", "Shared")
+        );
+        let hours = (link.expires_at - link.created_at).num_hours();
+        assert!((DEFAULT_SHARE_HOURS - 1..=DEFAULT_SHARE_HOURS).contains(&hours));
+
+        let resolved = ShareService::resolve(&pool, &link.token).await.unwrap();
+        assert_eq!(resolved.id, link.id);
+        let shared = ShareService::shared_content(&pool, &resolved)
+            .await
+            .unwrap();
+        assert_eq!(shared.title, "Shared");
+        let ids: Vec<i64> = shared.chapters.iter().map(|c| c.id).collect();
+        assert_eq!(ids, [1, 2]);
+
+        assert!(
+            ShareService::check_chapter(&pool, &resolved, 2)
+                .await
+                .is_ok()
+        );
+        assert!(
+            ShareService::check_chapter(&pool, &resolved, 3)
+                .await
+                .is_err()
+        );
+        assert!(ShareService::resolve(&pool, "unknown").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_chapter_link_shares_only_that_chapter() {
+        let pool = setup().await;
+
+        let link = ShareService::create(&pool, 1, 1, request(Some(2), Some(1)))
+            .await
+            .unwrap();
+        let shared = ShareService::shared_content(&pool, &link).await.unwrap();
+        assert_eq!(shared.chapters.len(), 1);
+        assert_eq!(shared.chapters[0].id, 2);
+        assert!(ShareService::check_chapter(&pool, &link, 2).await.is_ok());
+        assert!(ShareService::check_chapter(&pool, &link, 1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_create_rejects_invalid_links() {
+        let pool = setup().await;
+
+        for hours in [0, MAX_SHARE_HOURS + 1] {
+            let result = ShareService::create(&pool, 1, 1, request(None, Some(hours))).await;
+            assert!(matches!(result, Err(AppError::BadRequest(_))));
+        }
+        // Chapter of another content
+        assert!(
+            ShareService::create(&pool, 1, 1, request(Some(3), None))
+                .await
+                .is_err()
+        );
+        assert!(
+            ShareService::create(&pool, 1, 9, request(None, None))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_expired_and_revoked_links_are_not_resolved() {
+        let pool = setup().await;
+
+        let expired = Utc::now() - Duration::hours(1);
+        ShareRepository::create(&pool, "expired", 1, 1, None, expired)
+            .await
+            .unwrap();
+        assert!(ShareService::resolve(&pool, "expired").await.is_err());
+
+        let link = ShareService::create(&pool, 1, 1, request(None, None))
+            .await
+            .unwrap();
+        assert_eq!(ShareService::list(&pool, Some(1)).await.unwrap().len(), 2);
+        assert!(ShareService::list(&pool, Some(2)).await.unwrap().is_empty());
+
+        // Only the creator, or an administrator, can revoke a link
+        assert!(ShareService::revoke(&pool, link.id, Some(2)).await.is_err());
+        ShareService::revoke(&pool, link.id, Some(1)).await.unwrap();
+        assert!(ShareService::resolve(&pool, &link.token).await.is_err());
+        ShareService::revoke(&pool, 1, None).await.unwrap();
+        assert!(ShareService::list(&pool, None).await.unwrap().is_empty());
+    }
+}
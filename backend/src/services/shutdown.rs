@@ -0,0 +1,113 @@
+//! Graceful shutdown coordination.
+//!
+//! When the server is asked to stop, the HTTP server drains first, then
+//! every background service is stopped in turn, each step bounded by a
+//! timeout so a stuck service cannot keep the process alive. Scan tasks and
+//! quota usage are written to the database last so queued and interrupted
+//! scans resume on the next start.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use sqlx::{Pool, Sqlite};
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use crate::services::quota::QuotaService;
+use crate::services::scan_queue::ScanQueueService;
+use crate::services::scheduler::SchedulerService;
+use crate::services::watch::WatchService;
+use crate::t;
+
+/// Coordinates stopping all background services.
+pub struct ShutdownCoordinator {
+    pool: Pool<Sqlite>,
+    scan_queue_service: Arc<ScanQueueService>,
+    scheduler_service: Arc<SchedulerService>,
+    watch_service: Arc<WatchService>,
+    quota_service: Arc<QuotaService>,
+    /// Time allowed for each shutdown step.
+    timeout: Duration,
+    /// Set to true once shutdown has been requested.
+    signal: watch::Sender<bool>,
+}
+
+impl ShutdownCoordinator {
+    /// Create a new shutdown coordinator.
+    pub fn new(
+        pool: Pool<Sqlite>,
+        scan_queue_service: Arc<ScanQueueService>,
+        scheduler_service: Arc<SchedulerService>,
+        watch_service: Arc<WatchService>,
+        quota_service: Arc<QuotaService>,
+        timeout: Duration,
+    ) -> Self {
+        Self {
+            pool,
+            scan_queue_service,
+            scheduler_service,
+            watch_service,
+            quota_service,
+            timeout,
+            signal: watch::Sender::new(false),
+        }
+    }
+
+    /// Request shutdown. Futures returned by [`requested`](Self::requested)
+    /// complete.
+    pub fn request(&self) {
+        self.signal.send_replace(true);
+    }
+
+    /// Completes once shutdown has been requested.
+    pub fn requested(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut rx = self.signal.subscribe();
+        async move {
+            let _ = rx.wait_for(|requested| *requested).await;
+        }
+    }
+
+    /// Stop all background services and save in-flight scan task state and
+    /// quota usage.
+    ///
+    /// Call after the HTTP server has stopped accepting requests.
+    pub async fn shutdown(&self) {
+        self.request();
+        info!("{}", t!("shutdown.started"));
+
+        // Stop producing new work first
+        self.scheduler_service.cancel_all().await;
+        self.watch_service.stop_all().await;
+
+        // Let running scans finish or mark them interrupted
+        self.scan_queue_service.shutdown(self.timeout).await;
+
+        match tokio::time::timeout(
+            self.timeout,
+            self.scan_queue_service.persist_tasks(&self.pool),
+        )
+        .await
+        {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => warn!(error = %e, "{}", t!("shutdown.persist_failed")),
+            Err(_) => warn!("{}", t!("shutdown.step_timeout", step = "persist_tasks")),
+        }
+
+        match tokio::time::timeout(self.timeout, self.quota_service.save()).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => warn!(error = %e, "{}", t!("quota.save_failed")),
+            Err(_) => warn!("{}", t!("shutdown.step_timeout", step = "save_quota_usage")),
+        }
+
+        // Checkpoints the WAL so the database file is complete on its own
+        if tokio::time::timeout(self.timeout, self.pool.close())
+            .await
+            .is_err()
+        {
+            warn!("{}", t!("shutdown.step_timeout", step = "close_database"));
+        }
+
+        info!("{}", t!("shutdown.finished"));
+    }
+}
@@ -0,0 +1,483 @@
+//! Tachiyomi backup import.
+//!
+//! Backups of Tachiyomi and its forks list the entries of the reader's
+//! library with the read markers of their chapters. Entries are matched to
+//! contents by title or alternative title, and their chapters to chapters
+//! by number or name; read chapters are then imported as finished, and
+//! started comic chapters at the page reached.
+
+use std::collections::HashMap;
+use std::io::Read;
+
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use sqlx::{Pool, Sqlite};
+
+use crate::error::{AppError, Result};
+use crate::models::{
+    Chapter, Content, ExportedProgress, TachiyomiBackup, TachiyomiChapter, TachiyomiHistory,
+    TachiyomiImportItem, TachiyomiImportResult, TachiyomiManga, TachiyomiMatch,
+};
+use crate::repository::alias::AliasRepository;
+use crate::repository::content::{ChapterRepository, ContentRepository};
+use crate::repository::library::LibraryRepository;
+use crate::repository::progress::ProgressRepository;
+use crate::repository::user::UserRepository;
+use crate::services::age_rating::AgeRatingService;
+use crate::services::progress::ProgressService;
+use crate::t;
+use crate::utils::numbering::ChapterNumbering;
+use crate::utils::protobuf::{DecodeError, Reader};
+use crate::utils::title::match_key;
+
+/// Largest backup accepted once decompressed, in bytes.
+const MAX_BACKUP_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Service for importing Tachiyomi backups.
+pub struct TachiyomiService;
+
+impl TachiyomiService {
+    /// Import the reading progress of a backup as the progress of a user.
+    ///
+    /// Progress is written unless the user has more recent progress on the
+    /// chapter; with `dry_run` nothing is written and the report tells what
+    /// would be. Contents above the user's age rating cap are not matched.
+    pub async fn import(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        data: &[u8],
+        dry_run: bool,
+    ) -> Result<TachiyomiImportResult> {
+        let backup = Self::parse(data)?;
+        let username = UserRepository::find_by_id(pool, user_id)
+            .await?
+            .map(|user| user.username)
+            .unwrap_or_default();
+
+        let library_ids: Vec<i64> = LibraryRepository::list(pool)
+            .await?
+            .into_iter()
+            .map(|library| library.id)
+            .collect();
+        let contents = ContentRepository::list_by_libraries(pool, &library_ids).await?;
+        let contents = AgeRatingService::filter_contents(pool, user_id, contents).await?;
+        let content_ids: Vec<i64> = contents.iter().map(|c| c.id).collect();
+        let aliases = AliasRepository::list_by_contents(pool, &content_ids).await?;
+
+        // Contents by each of their titles; a title of several contents
+        // does not identify one
+        let mut by_title: HashMap<String, Vec<usize>> = HashMap::new();
+        for (idx, content) in contents.iter().enumerate() {
+            let mut titles = vec![match_key(&content.title), match_key(&content.folder_title)];
+            titles.extend(
+                aliases
+                    .get(&content.id)
+                    .into_iter()
+                    .flatten()
+                    .map(|alias| match_key(&alias.title)),
+            );
+            titles.sort();
+            titles.dedup();
+            for title in titles {
+                by_title.entry(title).or_default().push(idx);
+            }
+        }
+
+        let mut result = TachiyomiImportResult {
+            dry_run,
+            matched: 0,
+            progress_imported: 0,
+            items: Vec::with_capacity(backup.backup_manga.len()),
+        };
+        for manga in &backup.backup_manga {
+            let mut item = TachiyomiImportItem {
+                title: manga.title.clone(),
+                matched: TachiyomiMatch::Unmatched,
+                content_id: None,
+                content_title: None,
+                chapters_with_progress: manga.chapters.iter().filter(|c| c.has_progress()).count(),
+                unmatched_chapters: Vec::new(),
+                progress_imported: 0,
+            };
+
+            match by_title.get(&match_key(&manga.title)).map(Vec::as_slice) {
+                Some([idx]) => {
+                    let content = &contents[*idx];
+                    item.matched = TachiyomiMatch::Matched;
+                    item.content_id = Some(content.id);
+                    item.content_title = Some(content.title.clone());
+                    Self::import_manga(
+                        pool, user_id, &username, content, manga, dry_run, &mut item,
+                    )
+                    .await?;
+                    result.matched += 1;
+                    result.progress_imported += item.progress_imported;
+                }
+                Some(_) => item.matched = TachiyomiMatch::Ambiguous,
+                None => {}
+            }
+            result.items.push(item);
+        }
+
+        Ok(result)
+    }
+
+    /// Import the chapters of an entry into the chapters of its content.
+    async fn import_manga(
+        pool: &Pool<Sqlite>,
+        user_id: i64,
+        username: &str,
+        content: &Content,
+        manga: &TachiyomiManga,
+        dry_run: bool,
+        item: &mut TachiyomiImportItem,
+    ) -> Result<()> {
+        let chapters = ChapterRepository::list_by_content(pool, content.id).await?;
+        let existing: HashMap<i64, DateTime<Utc>> =
+            ProgressRepository::find_by_user_and_content(pool, user_id, content.id)
+                .await?
+                .into_iter()
+                .map(|p| (p.chapter_id, p.updated_at))
+                .collect();
+        let last_read: HashMap<&str, i64> = manga
+            .history
+            .iter()
+            .map(|h| (h.url.as_str(), h.last_read))
+            .collect();
+
+        for backup_chapter in manga.chapters.iter().filter(|c| c.has_progress()) {
+            let Some(chapter) = find_chapter(&chapters, backup_chapter) else {
+                item.unmatched_chapters.push(backup_chapter.name.clone());
+                continue;
+            };
+            let Some((position, percentage)) = ProgressService::imported_position(
+                chapter,
+                backup_chapter.read,
+                backup_chapter.last_page_read,
+            ) else {
+                continue;
+            };
+            let progress = ExportedProgress {
+                username: username.to_string(),
+                position,
+                percentage,
+                updated_at: read_at(backup_chapter, last_read.get(backup_chapter.url.as_str())),
+                novel: None,
+            };
+
+            let imported = if dry_run {
+                existing
+                    .get(&chapter.id)
+                    .is_none_or(|updated_at| progress.updated_at > *updated_at)
+            } else {
+                ProgressRepository::import(pool, user_id, chapter.id, &progress).await?
+            };
+            if imported {
+                item.progress_imported += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Read a backup: gzipped or plain, protobuf or JSON.
+    pub fn parse(data: &[u8]) -> Result<TachiyomiBackup> {
+        let invalid = || AppError::BadRequest(t!("tachiyomi.invalid_backup").to_string());
+
+        let mut decompressed = Vec::new();
+        let data = if data.starts_with(&[0x1f, 0x8b]) {
+            GzDecoder::new(data)
+                .take(MAX_BACKUP_SIZE)
+                .read_to_end(&mut decompressed)
+                .map_err(|_| invalid())?;
+            &decompressed[..]
+        } else {
+            data
+        };
+
+        if data.trim_ascii_start().starts_with(b"{") {
+            serde_json::from_slice(data).map_err(|_| invalid())
+        } else {
+            decode_backup(data).map_err(|_| invalid())
+        }
+    }
+}
+
+/// The chapter with the number of a backup chapter, or else with its name.
+fn find_chapter<'a>(chapters: &'a [Chapter], backup: &TachiyomiChapter) -> Option<&'a Chapter> {
+    // Tachiyomi leaves the number at 0 or -1 when it could not parse one
+    let numbering = ChapterNumbering::parse(&backup.name);
+    let number = Some(backup.chapter_number)
+        .filter(|n| *n > 0.0)
+        .or(numbering.number);
+    let same_number = |c: &&Chapter| match (number, c.number) {
+        (Some(a), Some(b)) => (a - b).abs() < 0.001,
+        // Volumes without chapter numbers
+        (None, None) => numbering.volume.is_some() && c.volume == numbering.volume,
+        _ => false,
+    };
+
+    chapters.iter().find(same_number).or_else(|| {
+        let name = match_key(&backup.name);
+        chapters.iter().find(|c| match_key(&c.title) == name)
+    })
+}
+
+/// When a chapter was read: from the history, else when the chapter last
+/// changed, else now.
+fn read_at(chapter: &TachiyomiChapter, last_read_ms: Option<&i64>) -> DateTime<Utc> {
+    last_read_ms
+        .filter(|ms| **ms > 0)
+        .and_then(|ms| DateTime::from_timestamp_millis(*ms))
+        .or_else(|| {
+            (chapter.last_modified_at > 0)
+                .then(|| DateTime::from_timestamp(chapter.last_modified_at, 0))
+                .flatten()
+        })
+        .unwrap_or_else(Utc::now)
+}
+
+// Field numbers of the protobuf schema of Tachiyomi backups
+
+const BACKUP_MANGA: u32 = 1;
+const MANGA_TITLE: u32 = 3;
+const MANGA_CHAPTERS: u32 = 16;
+const MANGA_HISTORY: u32 = 104;
+const CHAPTER_URL: u32 = 1;
+const CHAPTER_NAME: u32 = 2;
+const CHAPTER_READ: u32 = 4;
+const CHAPTER_LAST_PAGE_READ: u32 = 6;
+const CHAPTER_NUMBER: u32 = 9;
+const CHAPTER_LAST_MODIFIED_AT: u32 = 11;
+const HISTORY_URL: u32 = 1;
+const HISTORY_LAST_READ: u32 = 2;
+
+fn decode_backup(data: &[u8]) -> std::result::Result<TachiyomiBackup, DecodeError> {
+    let mut backup = TachiyomiBackup::default();
+    let mut reader = Reader::new(data);
+    while let Some((number, value)) = reader.next_field()? {
+        if number == BACKUP_MANGA {
+            backup
+                .backup_manga
+                .push(decode_manga(value.as_bytes().ok_or(DecodeError)?)?);
+        }
+    }
+    Ok(backup)
+}
+
+fn decode_manga(data: &[u8]) -> std::result::Result<TachiyomiManga, DecodeError> {
+    let mut manga = TachiyomiManga::default();
+    let mut reader = Reader::new(data);
+    while let Some((number, value)) = reader.next_field()? {
+        match number {
+            MANGA_TITLE => manga.title = value.as_string().ok_or(DecodeError)?,
+            MANGA_CHAPTERS => manga
+                .chapters
+                .push(decode_chapter(value.as_bytes().ok_or(DecodeError)?)?),
+            MANGA_HISTORY => manga
+                .history
+                .push(decode_history(value.as_bytes().ok_or(DecodeError)?)?),
+            _ => {}
+        }
+    }
+    Ok(manga)
+}
+
+fn decode_chapter(data: &[u8]) -> std::result::Result<TachiyomiChapter, DecodeError> {
+    let mut chapter = TachiyomiChapter::default();
+    let mut reader = Reader::new(data);
+    while let Some((number, value)) = reader.next_field()? {
+        match number {
+            CHAPTER_URL => chapter.url = value.as_string().ok_or(DecodeError)?,
+            CHAPTER_NAME => chapter.name = value.as_string().ok_or(DecodeError)?,
+            CHAPTER_READ => chapter.read = value.as_bool().ok_or(DecodeError)?,
+            CHAPTER_LAST_PAGE_READ => chapter.last_page_read = value.as_i64().ok_or(DecodeError)?,
+            CHAPTER_NUMBER => chapter.chapter_number = value.as_f32().ok_or(DecodeError)?,
+            CHAPTER_LAST_MODIFIED_AT => {
+                chapter.last_modified_at = value.as_i64().ok_or(DecodeError)?
+            }
+            _ => {}
+        }
+    }
+    Ok(chapter)
+}
+
+fn decode_history(data: &[u8]) -> std::result::Result<TachiyomiHistory, DecodeError> {
+    let mut history = TachiyomiHistory::default();
+    let mut reader = Reader::new(data);
+    while let Some((number, value)) = reader.next_field()? {
+        match number {
+            HISTORY_URL => history.url = value.as_string().ok_or(DecodeError)?,
+            HISTORY_LAST_READ => history.last_read = value.as_i64().ok_or(DecodeError)?,
+            _ => {}
+        }
+    }
+    Ok(history)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+
+    use super::*;
+    use crate::test_fixtures::{
+        TIMESTAMP, TestChapter, create_test_content, create_test_db, create_test_library,
+        create_test_scan_path, create_test_user,
+    };
+
+    async fn setup() -> Pool<Sqlite> {
+        let pool = create_test_db().await;
+        create_test_user(&pool, 1, "reader").await;
+        create_test_library(&pool, 1, "Comics").await;
+        create_test_scan_path(&pool, 1, 1, "/comics").await;
+        create_test_content(&pool, 1, 1, "Journey").await;
+        sqlx::query(
+            r#"
+            INSERT INTO content_aliases (content_id, title, source, created_at)
+            VALUES (1, 'The Long Voyage', 'manual', ?)
+            "#,
+        )
+        .bind(TIMESTAMP)
+        .execute(&pool)
+        .await
+        .unwrap();
+        for number in [1, 2] {
+            TestChapter::new(number, 1, &format!("/comics/Journey/Chapter {number}.cbz"))
+                .page_count(20)
+                .number(number as f32)
+                .insert(&pool)
+                .await;
+        }
+        pool
+    }
+
+    /// Encode a protobuf field with its key.
+    fn field(number: u32, wire_type: u8, payload: &[u8]) -> Vec<u8> {
+        let mut out = varint(u64::from((number << 3) | u32::from(wire_type)));
+        if wire_type == 2 {
+            out.extend(varint(payload.len() as u64));
+        }
+        out.extend_from_slice(payload);
+        out
+    }
+
+    fn varint(mut value: u64) -> Vec<u8> {
+        let mut out = Vec::new();
+        while value >= 0x80 {
+            out.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        out.push(value as u8);
+        out
+    }
+
+    #[test]
+    fn test_parse_gzipped_protobuf() {
+        let chapter = [
+            field(CHAPTER_URL, 2, b"/chapter/1"),
+            field(CHAPTER_NAME, 2, b"Ch. 1"),
+            field(CHAPTER_READ, 0, &varint(1)),
+            field(CHAPTER_NUMBER, 5, &1.0f32.to_le_bytes()),
+        ]
+        .concat();
+        let history = [
+            field(HISTORY_URL, 2, b"/chapter/1"),
+            field(HISTORY_LAST_READ, 0, &varint(1_790_000_000_000)),
+        ]
+        .concat();
+        let manga = [
+            field(2, 2, b"/manga/1"),
+            field(MANGA_TITLE, 2, b"Journey"),
+            field(MANGA_CHAPTERS, 2, &chapter),
+            field(MANGA_HISTORY, 2, &history),
+            // Fields not read are skipped
+            field(100, 0, &varint(1)),
+        ]
+        .concat();
+        let backup = field(BACKUP_MANGA, 2, &manga);
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&backup).unwrap();
+        let parsed = TachiyomiService::parse(&encoder.finish().unwrap()).unwrap();
+
+        assert_eq!(
+            parsed.backup_manga,
+            [TachiyomiManga {
+                title: "Journey".to_string(),
+                chapters: vec![TachiyomiChapter {
+                    url: "/chapter/1".to_string(),
+                    name: "Ch. 1".to_string(),
+                    read: true,
+                    chapter_number: 1.0,
+                    ..Default::default()
+                }],
+                history: vec![TachiyomiHistory {
+                    url: "/chapter/1".to_string(),
+                    last_read: 1_790_000_000_000,
+                }],
+            }]
+        );
+        assert!(TachiyomiService::parse(b"not a backup").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_import_matches_titles_and_chapters() {
+        let pool = setup().await;
+        let backup = serde_json::json!({
+            "backupManga": [
+                {
+                    "title": "the long  voyage",
+                    "chapters": [
+                        { "url": "/c/1", "name": "Chapter 1", "read": true, "chapterNumber": 1.0 },
+                        { "url": "/c/2", "name": "Ch.2", "lastPageRead": 9, "chapterNumber": -1.0 },
+                        { "url": "/c/3", "name": "Chapter 3", "read": true, "chapterNumber": 3.0 },
+                        { "url": "/c/4", "name": "Chapter 4", "chapterNumber": 4.0 }
+                    ],
+                    "history": [{ "url": "/c/1", "lastRead": 1790000000000i64 }]
+                },
+                { "title": "Unknown", "chapters": [] }
+            ]
+        })
+        .to_string();
+
+        // A dry run reports without writing
+        let result = TachiyomiService::import(&pool, 1, backup.as_bytes(), true)
+            .await
+            .unwrap();
+        assert_eq!((result.matched, result.progress_imported), (1, 2));
+        let item = &result.items[0];
+        assert_eq!(item.matched, TachiyomiMatch::Matched);
+        assert_eq!(item.content_id, Some(1));
+        assert_eq!(item.chapters_with_progress, 3);
+        assert_eq!(item.unmatched_chapters, ["Chapter 3"]);
+        assert_eq!(result.items[1].matched, TachiyomiMatch::Unmatched);
+        assert!(
+            ProgressRepository::find_by_user_and_content(&pool, 1, 1)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let result = TachiyomiService::import(&pool, 1, backup.as_bytes(), false)
+            .await
+            .unwrap();
+        assert_eq!(result.progress_imported, 2);
+        let progress = ProgressRepository::find_by_user_and_content(&pool, 1, 1)
+            .await
+            .unwrap();
+        assert_eq!(
+            progress
+                .iter()
+                .map(|p| (p.chapter_id, p.position, p.percentage))
+                .collect::<Vec<_>>(),
+            [(1, 19, 100.0), (2, 9, 50.0)]
+        );
+        assert_eq!(
+            progress[0].updated_at,
+            DateTime::from_timestamp_millis(1_790_000_000_000).unwrap()
+        );
+    }
+}
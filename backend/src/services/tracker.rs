@@ -0,0 +1,1100 @@
+//! AniList, MyAnimeList and Bangumi progress sync.
+//!
+//! Users link an account through the tracker's OAuth authorization code
+//! flow, and the tokens are stored with the account. A background job then
+//! pushes, for each linked account, the number of chapters read of every
+//! series whose progress changed since its last run. Series are matched to
+//! tracker entries through their metadata: the ID of metadata scraped from
+//! AniList or Bangumi, and the MyAnimeList ID AniList records along with it.
+//!
+//! Bangumi entries are also marked as being read, or as done once every
+//! chapter is read, and a user's Bangumi collection can be pulled to
+//! favorite the series in it.
+
+use std::collections::{HashMap, HashSet};
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::{Duration, Utc};
+use reqwest::{Client, RequestBuilder, Url, header};
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::{Pool, Sqlite};
+use tracing::{info, warn};
+
+use crate::error::{AppError, Result};
+use crate::models::{
+    Content, ContentTracking, MetadataProviderKind, ReadingStatus, TrackerAccount, TrackerKind,
+    TrackerPullResult, TrackerStateClaims, TrackerStatus, TrackerStatusResponse, TrackerToken,
+};
+use crate::repository::content::ContentRepository;
+use crate::repository::content_status::ContentStatusRepository;
+use crate::repository::library::LibraryRepository;
+use crate::repository::tracker::TrackerRepository;
+use crate::services::age_rating::AgeRatingService;
+use crate::services::content::ContentService;
+use crate::services::metadata::metadata_source;
+use crate::t;
+use crate::utils::oauth_state::StateSigner;
+
+/// Bangumi rejects requests without a descriptive user agent.
+const USER_AGENT: &str = "ryuri/0.1.1 (https://github.com/tnzzzhlp/ryuri)";
+
+/// Purpose marker for tracker state tokens.
+const STATE_PURPOSE: &str = "tracker_state";
+
+const ANILIST_AUTHORIZE_URL: &str = "https://anilist.co/api/v2/oauth/authorize";
+const ANILIST_TOKEN_URL: &str = "https://anilist.co/api/v2/oauth/token";
+const ANILIST_API: &str = "https://graphql.anilist.co";
+
+const MAL_AUTHORIZE_URL: &str = "https://myanimelist.net/v1/oauth2/authorize";
+const MAL_TOKEN_URL: &str = "https://myanimelist.net/v1/oauth2/token";
+const MAL_API: &str = "https://api.myanimelist.net/v2";
+
+const BANGUMI_AUTHORIZE_URL: &str = "https://bgm.tv/oauth/authorize";
+const BANGUMI_TOKEN_URL: &str = "https://bgm.tv/oauth/access_token";
+const BANGUMI_API: &str = "https://api.bgm.tv/v0";
+
+/// Collection entries fetched per Bangumi request, the most it allows.
+const BANGUMI_PAGE_SIZE: usize = 100;
+
+/// Bangumi collection types: want to read, done, doing, on hold, dropped.
+const BANGUMI_WISH: i64 = 1;
+const BANGUMI_DONE: i64 = 2;
+const BANGUMI_DOING: i64 = 3;
+const BANGUMI_ON_HOLD: i64 = 4;
+const BANGUMI_DROPPED: i64 = 5;
+
+const ANILIST_VIEWER_QUERY: &str = "query { Viewer { name } }";
+
+const ANILIST_SAVE_MUTATION: &str = r#"
+mutation ($mediaId: Int, $progress: Int) {
+  SaveMediaListEntry(mediaId: $mediaId, progress: $progress) { id }
+}
+"#;
+
+/// OAuth client registered at a tracker.
+#[derive(Debug, Clone)]
+pub struct TrackerClientConfig {
+    pub client_id: String,
+    pub client_secret: String,
+    /// Callback URL registered at the tracker, ending in
+    /// `/api/trackers/{tracker}/callback`.
+    pub redirect_url: String,
+}
+
+/// Configuration for tracker sync.
+#[derive(Debug, Clone)]
+pub struct TrackerConfig {
+    /// AniList client; AniList cannot be linked when unset.
+    pub anilist: Option<TrackerClientConfig>,
+    /// MyAnimeList client; MyAnimeList cannot be linked when unset.
+    pub myanimelist: Option<TrackerClientConfig>,
+    /// Bangumi client; Bangumi cannot be linked when unset.
+    pub bangumi: Option<TrackerClientConfig>,
+    /// Frontend URL to send the browser to after linking.
+    pub frontend_redirect: String,
+    /// Minutes between background syncs (0 = only when users ask).
+    pub sync_interval_minutes: u64,
+}
+
+impl Default for TrackerConfig {
+    fn default() -> Self {
+        Self {
+            anilist: None,
+            myanimelist: None,
+            bangumi: None,
+            frontend_redirect: "/settings".to_string(),
+            sync_interval_minutes: 15,
+        }
+    }
+}
+
+impl TrackerConfig {
+    /// OAuth client of a tracker, if it is configured.
+    pub fn client(&self, tracker: TrackerKind) -> Option<&TrackerClientConfig> {
+        match tracker {
+            TrackerKind::AniList => self.anilist.as_ref(),
+            TrackerKind::MyAnimeList => self.myanimelist.as_ref(),
+            TrackerKind::Bangumi => self.bangumi.as_ref(),
+        }
+    }
+}
+
+/// Token endpoint response of any tracker.
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    /// Lifetime of the access token in seconds.
+    #[serde(default)]
+    expires_in: Option<i64>,
+}
+
+impl From<TokenResponse> for TrackerToken {
+    fn from(response: TokenResponse) -> Self {
+        Self {
+            access_token: response.access_token,
+            refresh_token: response.refresh_token,
+            expires_at: response
+                .expires_in
+                .map(|secs| Utc::now() + Duration::seconds(secs)),
+        }
+    }
+}
+
+/// Page of a Bangumi user's collection.
+#[derive(Debug, Deserialize)]
+struct BangumiCollectionPage {
+    data: Vec<BangumiCollectionEntry>,
+    total: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct BangumiCollectionEntry {
+    subject_id: i64,
+    /// Collection type, one of the `BANGUMI_*` constants.
+    #[serde(rename = "type")]
+    collection_type: i64,
+}
+
+/// Where to send the browser to link an account, and the state to
+/// remember.
+#[derive(Debug, Clone)]
+pub struct TrackerAuthorization {
+    /// Tracker authorization URL.
+    pub url: String,
+    /// Signed state token, to be stored in a cookie until the callback.
+    pub state: String,
+}
+
+/// Service linking tracker accounts and pushing progress to them.
+pub struct TrackerService {
+    pool: Pool<Sqlite>,
+    config: TrackerConfig,
+    client: Client,
+    state_signer: StateSigner,
+}
+
+impl TrackerService {
+    /// Create a new tracker service.
+    ///
+    /// Link requests carry a state signed with `state_secret`.
+    pub fn new(pool: Pool<Sqlite>, config: TrackerConfig, state_secret: &str) -> Self {
+        let client = Client::builder()
+            .user_agent(USER_AGENT)
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            pool,
+            config,
+            client,
+            state_signer: StateSigner::new(STATE_PURPOSE, state_secret),
+        }
+    }
+
+    /// Frontend URL to return to after the callback.
+    pub fn frontend_redirect(&self) -> &str {
+        &self.config.frontend_redirect
+    }
+
+    /// Minutes between background syncs (0 = only when users ask).
+    pub fn sync_interval_minutes(&self) -> u64 {
+        self.config.sync_interval_minutes
+    }
+
+    /// Trackers accounts can be linked on.
+    pub fn configured(&self) -> Vec<TrackerKind> {
+        TrackerKind::ALL
+            .into_iter()
+            .filter(|tracker| self.config.client(*tracker).is_some())
+            .collect()
+    }
+
+    /// Whether cookies for a tracker's callback should be marked `Secure`.
+    pub fn secure_cookies(&self, tracker: TrackerKind) -> bool {
+        self.config
+            .client(tracker)
+            .is_some_and(|client| client.redirect_url.starts_with("https://"))
+    }
+
+    /// Build the tracker authorization URL for linking an account to a
+    /// user.
+    pub fn authorize(&self, user_id: i64, tracker: TrackerKind) -> Result<TrackerAuthorization> {
+        let client = self.require_client(tracker)?;
+
+        let claims = TrackerStateClaims {
+            user_id,
+            tracker: tracker.as_str().to_string(),
+            verifier: random_verifier(),
+        };
+        let state = self.state_signer.sign(&claims)?;
+
+        let mut params = vec![
+            ("response_type", "code"),
+            ("client_id", client.client_id.as_str()),
+            ("redirect_uri", client.redirect_url.as_str()),
+            ("state", state.as_str()),
+        ];
+        let endpoint = match tracker {
+            TrackerKind::AniList => ANILIST_AUTHORIZE_URL,
+            TrackerKind::MyAnimeList => {
+                // MyAnimeList only supports the plain PKCE method
+                params.push(("code_challenge", claims.verifier.as_str()));
+                params.push(("code_challenge_method", "plain"));
+                MAL_AUTHORIZE_URL
+            }
+            TrackerKind::Bangumi => BANGUMI_AUTHORIZE_URL,
+        };
+        let url = Url::parse_with_params(endpoint, &params)
+            .map_err(|e| AppError::Internal(format!("Invalid tracker URL: {}", e)))?;
+
+        Ok(TrackerAuthorization {
+            url: url.to_string(),
+            state,
+        })
+    }
+
+    /// Complete linking after the tracker redirects back.
+    ///
+    /// `cookie_state` is the state remembered by the browser; it must match
+    /// the `state` query parameter.
+    pub async fn complete(
+        &self,
+        tracker: TrackerKind,
+        code: &str,
+        state: &str,
+        cookie_state: Option<&str>,
+    ) -> Result<TrackerAccount> {
+        let claims: TrackerStateClaims = self
+            .state_signer
+            .verify(state, cookie_state)
+            .ok_or_else(|| AppError::Unauthorized(t!("tracker.invalid_state").to_string()))?;
+        if claims.tracker != tracker.as_str() {
+            return Err(AppError::Unauthorized(
+                t!("tracker.invalid_state").to_string(),
+            ));
+        }
+
+        let token = self.exchange_code(tracker, code, &claims.verifier).await?;
+        let username = self.fetch_username(tracker, &token.access_token).await?;
+        let account = TrackerRepository::upsert_account(
+            &self.pool,
+            claims.user_id,
+            tracker,
+            &username,
+            &token,
+        )
+        .await?;
+
+        info!(
+            user_id = claims.user_id,
+            tracker = tracker.as_str(),
+            "{}",
+            t!("tracker.linked_log")
+        );
+        Ok(account)
+    }
+
+    /// Unlink a user's account on a tracker.
+    pub async fn unlink(&self, user_id: i64, tracker: TrackerKind) -> Result<()> {
+        if !TrackerRepository::delete_account(&self.pool, user_id, tracker).await? {
+            return Err(AppError::NotFound(
+                t!("tracker.not_linked", tracker = tracker.as_str()).to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Sync status of every tracker for a user.
+    pub async fn status(&self, user_id: i64) -> Result<TrackerStatusResponse> {
+        let accounts = TrackerRepository::list_accounts(&self.pool, Some(user_id)).await?;
+
+        let mut trackers = Vec::new();
+        for tracker in TrackerKind::ALL {
+            let account = accounts.iter().find(|a| a.tracker == tracker);
+            let synced_series = match account {
+                Some(_) => TrackerRepository::count_synced(&self.pool, user_id, tracker).await?,
+                None => 0,
+            };
+            trackers.push(TrackerStatus {
+                tracker,
+                configured: self.config.client(tracker).is_some(),
+                linked: account.is_some(),
+                remote_username: account.map(|a| a.remote_username.clone()),
+                linked_at: account.map(|a| a.created_at),
+                last_synced_at: account.and_then(|a| a.last_synced_at),
+                last_error: account.and_then(|a| a.last_error.clone()),
+                synced_series,
+            });
+        }
+
+        Ok(TrackerStatusResponse {
+            trackers,
+            opted_out: TrackerRepository::list_opt_outs(&self.pool, user_id).await?,
+        })
+    }
+
+    /// Whether a user's progress on a content is pushed to their trackers.
+    pub async fn get_tracking(&self, user_id: i64, content_id: i64) -> Result<ContentTracking> {
+        ContentService::get_content(&self.pool, content_id).await?;
+        let opted_out = TrackerRepository::is_opted_out(&self.pool, user_id, content_id).await?;
+        Ok(ContentTracking {
+            content_id,
+            enabled: !opted_out,
+        })
+    }
+
+    /// Opt a content in or out of a user's tracker sync.
+    pub async fn set_tracking(
+        &self,
+        user_id: i64,
+        content_id: i64,
+        enabled: bool,
+    ) -> Result<ContentTracking> {
+        ContentService::get_content(&self.pool, content_id).await?;
+        TrackerRepository::set_opt_out(&self.pool, user_id, content_id, !enabled).await?;
+        Ok(ContentTracking {
+            content_id,
+            enabled,
+        })
+    }
+
+    /// Favorite the contents in a user's collection on a tracker, and give
+    /// them the reading status of their entry when the user has not picked
+    /// one. Only Bangumi collections can be pulled.
+    pub async fn pull_collection(
+        &self,
+        user_id: i64,
+        tracker: TrackerKind,
+    ) -> Result<TrackerPullResult> {
+        if tracker != TrackerKind::Bangumi {
+            return Err(AppError::BadRequest(
+                t!("tracker.pull_unsupported", tracker = tracker.as_str()).to_string(),
+            ));
+        }
+        let account = TrackerRepository::find_account(&self.pool, user_id, tracker)
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound(t!("tracker.not_linked", tracker = tracker.as_str()).to_string())
+            })?;
+
+        let access_token = self.access_token(&account).await?;
+        let collection = self
+            .fetch_bangumi_collection(&account.remote_username, &access_token)
+            .await?;
+        self.apply_collection(user_id, tracker, &collection).await
+    }
+
+    /// Mark the contents matched to entries of a collection, given as
+    /// collection type by remote ID.
+    async fn apply_collection(
+        &self,
+        user_id: i64,
+        tracker: TrackerKind,
+        collection: &HashMap<String, i64>,
+    ) -> Result<TrackerPullResult> {
+        let library_ids: Vec<i64> = LibraryRepository::list(&self.pool)
+            .await?
+            .into_iter()
+            .map(|library| library.id)
+            .collect();
+        let contents = ContentRepository::list_by_libraries(&self.pool, &library_ids).await?;
+        let contents = AgeRatingService::filter_contents(&self.pool, user_id, contents).await?;
+
+        let mut result = TrackerPullResult {
+            tracker,
+            collected: collection.len(),
+            matched: 0,
+            favorited: 0,
+            statuses_set: 0,
+        };
+        for content in contents {
+            let Some(collection_type) =
+                remote_id(&content, tracker).and_then(|id| collection.get(&id))
+            else {
+                continue;
+            };
+            result.matched += 1;
+
+            let (favorite, status) =
+                match ContentStatusRepository::find(&self.pool, user_id, content.id).await? {
+                    Some(current) => (current.favorite, current.status),
+                    None => (false, None),
+                };
+            // A status the user picked is kept
+            let pulled = status.or_else(|| bangumi_status(*collection_type));
+            if favorite && pulled == status {
+                continue;
+            }
+
+            ContentStatusRepository::upsert(&self.pool, user_id, content.id, true, pulled).await?;
+            if !favorite {
+                result.favorited += 1;
+            }
+            if pulled != status {
+                result.statuses_set += 1;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Push the progress of every linked account. Failures are logged and
+    /// kept with the account.
+    pub async fn sync_all(&self) {
+        let accounts = match TrackerRepository::list_accounts(&self.pool, None).await {
+            Ok(accounts) => accounts,
+            Err(e) => {
+                warn!(error = %e, "{}", t!("tracker.sync_failed_log"));
+                return;
+            }
+        };
+
+        for account in accounts {
+            self.sync_logged(&account).await;
+        }
+    }
+
+    /// Push the progress of a user's linked accounts now, and return their
+    /// status afterwards.
+    pub async fn sync_user(&self, user_id: i64) -> Result<TrackerStatusResponse> {
+        for account in TrackerRepository::list_accounts(&self.pool, Some(user_id)).await? {
+            self.sync_logged(&account).await;
+        }
+        self.status(user_id).await
+    }
+
+    async fn sync_logged(&self, account: &TrackerAccount) {
+        let tracker = account.tracker.as_str();
+        match self.sync_account(account).await {
+            Ok(pushed) => {
+                info!(
+                    user_id = account.user_id,
+                    tracker,
+                    pushed,
+                    "{}",
+                    t!("tracker.synced_log")
+                )
+            }
+            Err(e) => {
+                let user_id = account.user_id;
+                warn!(user_id, tracker, error = %e, "{}", t!("tracker.sync_failed_log"))
+            }
+        }
+    }
+
+    /// Push the series of an account whose progress changed since its last
+    /// sync, returning how many were pushed.
+    ///
+    /// Stops at the first failure; series pushed before it are remembered,
+    /// and the rest are tried again on the next sync.
+    async fn sync_account(&self, account: &TrackerAccount) -> Result<usize> {
+        let started = Utc::now();
+        let result = self.push_changes(account).await;
+
+        let error = result.as_ref().err().map(|e| e.error_message());
+        TrackerRepository::finish_sync(
+            &self.pool,
+            account.user_id,
+            account.tracker,
+            started,
+            error.as_deref(),
+        )
+        .await?;
+        result
+    }
+
+    async fn push_changes(&self, account: &TrackerAccount) -> Result<usize> {
+        let (user_id, tracker) = (account.user_id, account.tracker);
+        let counts =
+            TrackerRepository::list_read_counts(&self.pool, user_id, account.last_synced_at)
+                .await?;
+        if counts.is_empty() {
+            return Ok(0);
+        }
+
+        let opted_out: HashSet<i64> = TrackerRepository::list_opt_outs(&self.pool, user_id)
+            .await?
+            .into_iter()
+            .collect();
+        let synced = TrackerRepository::list_sync_state(&self.pool, user_id, tracker).await?;
+        let mut access_token = None;
+        let mut pushed = 0;
+
+        for count in counts {
+            if opted_out.contains(&count.content_id) {
+                continue;
+            }
+            let Some(content) = ContentRepository::find_by_id(&self.pool, count.content_id).await?
+            else {
+                continue;
+            };
+            let Some(remote_id) = remote_id(&content, tracker) else {
+                continue;
+            };
+
+            let chapters_read = count.chapters_read();
+            let completed = content.chapter_count > 0 && count.read >= content.chapter_count as i64;
+            let unchanged = match synced.get(&content.id) {
+                Some((id, read)) => *id == remote_id && *read == chapters_read,
+                None => chapters_read == 0,
+            };
+            if unchanged {
+                continue;
+            }
+
+            // Refreshed only once there is something to push
+            if access_token.is_none() {
+                access_token = Some(self.access_token(account).await?);
+            }
+            let token = access_token.as_deref().unwrap_or_default();
+            self.push(tracker, token, &remote_id, chapters_read, completed)
+                .await?;
+            TrackerRepository::save_sync_state(
+                &self.pool,
+                user_id,
+                tracker,
+                content.id,
+                &remote_id,
+                chapters_read,
+            )
+            .await?;
+            pushed += 1;
+        }
+
+        Ok(pushed)
+    }
+
+    /// A valid access token of an account, refreshing it if it expired.
+    async fn access_token(&self, account: &TrackerAccount) -> Result<String> {
+        if !account.is_expired(Utc::now()) {
+            return Ok(account.access_token.clone());
+        }
+
+        // AniList tokens last a year and cannot be refreshed
+        let Some(refresh_token) = &account.refresh_token else {
+            return Err(AppError::Unauthorized(
+                t!("tracker.reauthorize", tracker = account.tracker.as_str()).to_string(),
+            ));
+        };
+        let client = self.require_client(account.tracker)?;
+        let mut form = vec![
+            ("client_id", client.client_id.as_str()),
+            ("client_secret", client.client_secret.as_str()),
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token.as_str()),
+        ];
+        let token_url = match account.tracker {
+            TrackerKind::Bangumi => {
+                // Bangumi wants the redirect URL again
+                form.push(("redirect_uri", client.redirect_url.as_str()));
+                BANGUMI_TOKEN_URL
+            }
+            TrackerKind::AniList | TrackerKind::MyAnimeList => MAL_TOKEN_URL,
+        };
+        let request = self.client.post(token_url).form(&form);
+        let token: TrackerToken = self
+            .send::<TokenResponse>(account.tracker, request)
+            .await?
+            .into();
+
+        TrackerRepository::update_token(&self.pool, account.user_id, account.tracker, &token)
+            .await?;
+        Ok(token.access_token)
+    }
+
+    /// Exchange an authorization code for tokens.
+    async fn exchange_code(
+        &self,
+        tracker: TrackerKind,
+        code: &str,
+        verifier: &str,
+    ) -> Result<TrackerToken> {
+        let client = self.require_client(tracker)?;
+        let request = match tracker {
+            TrackerKind::AniList => self
+                .client
+                .post(ANILIST_TOKEN_URL)
+                .header(header::ACCEPT, "application/json")
+                .json(&json!({
+                    "grant_type": "authorization_code",
+                    "client_id": client.client_id,
+                    "client_secret": client.client_secret,
+                    "redirect_uri": client.redirect_url,
+                    "code": code,
+                })),
+            TrackerKind::MyAnimeList => self.client.post(MAL_TOKEN_URL).form(&[
+                ("client_id", client.client_id.as_str()),
+                ("client_secret", client.client_secret.as_str()),
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", client.redirect_url.as_str()),
+                ("code_verifier", verifier),
+            ]),
+            TrackerKind::Bangumi => self.client.post(BANGUMI_TOKEN_URL).form(&[
+                ("client_id", client.client_id.as_str()),
+                ("client_secret", client.client_secret.as_str()),
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", client.redirect_url.as_str()),
+            ]),
+        };
+
+        let response: TokenResponse = self.send(tracker, request).await.map_err(|e| {
+            AppError::Unauthorized(
+                t!("tracker.token_exchange_failed", error = e.error_message()).to_string(),
+            )
+        })?;
+        Ok(response.into())
+    }
+
+    /// Name of the account an access token belongs to.
+    async fn fetch_username(&self, tracker: TrackerKind, access_token: &str) -> Result<String> {
+        let name = match tracker {
+            TrackerKind::AniList => {
+                let request = self
+                    .client
+                    .post(ANILIST_API)
+                    .bearer_auth(access_token)
+                    .json(&json!({ "query": ANILIST_VIEWER_QUERY }));
+                let body: serde_json::Value = self.send(tracker, request).await?;
+                body["data"]["Viewer"]["name"].as_str().map(String::from)
+            }
+            TrackerKind::MyAnimeList => {
+                let request = self
+                    .client
+                    .get(format!("{}/users/@me", MAL_API))
+                    .bearer_auth(access_token);
+                let body: serde_json::Value = self.send(tracker, request).await?;
+                body["name"].as_str().map(String::from)
+            }
+            TrackerKind::Bangumi => {
+                let request = self
+                    .client
+                    .get(format!("{}/me", BANGUMI_API))
+                    .bearer_auth(access_token);
+                let body: serde_json::Value = self.send(tracker, request).await?;
+                body["username"].as_str().map(String::from)
+            }
+        };
+
+        name.ok_or_else(|| request_failed(tracker, "no account name"))
+    }
+
+    /// Set the number of chapters read of an entry on a tracker. Bangumi
+    /// entries are also marked as being read, or as done when `completed`.
+    async fn push(
+        &self,
+        tracker: TrackerKind,
+        access_token: &str,
+        remote_id: &str,
+        chapters_read: i64,
+        completed: bool,
+    ) -> Result<()> {
+        match tracker {
+            TrackerKind::AniList => {
+                let media_id: i64 = remote_id
+                    .parse()
+                    .map_err(|_| request_failed(tracker, "invalid media ID"))?;
+                let request = self
+                    .client
+                    .post(ANILIST_API)
+                    .bearer_auth(access_token)
+                    .json(&json!({
+                        "query": ANILIST_SAVE_MUTATION,
+                        "variables": { "mediaId": media_id, "progress": chapters_read },
+                    }));
+                let body: serde_json::Value = self.send(tracker, request).await?;
+                if let Some(error) = body["errors"][0]["message"].as_str() {
+                    return Err(request_failed(tracker, error));
+                }
+            }
+            TrackerKind::MyAnimeList => {
+                let request = self
+                    .client
+                    .patch(format!("{}/manga/{}/my_list_status", MAL_API, remote_id))
+                    .bearer_auth(access_token)
+                    .form(&[("num_chapters_read", chapters_read.to_string())]);
+                self.send::<serde_json::Value>(tracker, request).await?;
+            }
+            TrackerKind::Bangumi => {
+                let collection_type = if completed {
+                    BANGUMI_DONE
+                } else {
+                    BANGUMI_DOING
+                };
+                let request = self
+                    .client
+                    .post(format!("{}/users/-/collections/{}", BANGUMI_API, remote_id))
+                    .bearer_auth(access_token)
+                    .json(&json!({ "type": collection_type, "ep_status": chapters_read }));
+                // Answered with an empty body
+                self.execute(tracker, request).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Collection type of every book in a Bangumi user's collection, by
+    /// subject ID.
+    async fn fetch_bangumi_collection(
+        &self,
+        username: &str,
+        access_token: &str,
+    ) -> Result<HashMap<String, i64>> {
+        let url = format!(
+            "{}/users/{}/collections",
+            BANGUMI_API,
+            urlencoding::encode(username)
+        );
+
+        let mut collection = HashMap::new();
+        let mut offset = 0;
+        loop {
+            let request = self.client.get(&url).bearer_auth(access_token).query(&[
+                ("subject_type", 1),
+                ("limit", BANGUMI_PAGE_SIZE),
+                ("offset", offset),
+            ]);
+            let page: BangumiCollectionPage = self.send(TrackerKind::Bangumi, request).await?;
+
+            let fetched = page.data.len();
+            for entry in page.data {
+                collection.insert(entry.subject_id.to_string(), entry.collection_type);
+            }
+            offset += fetched;
+            if fetched == 0 || offset >= page.total {
+                return Ok(collection);
+            }
+        }
+    }
+
+    /// Send a request to a tracker and parse the JSON response.
+    async fn send<T: serde::de::DeserializeOwned>(
+        &self,
+        tracker: TrackerKind,
+        request: RequestBuilder,
+    ) -> Result<T> {
+        self.execute(tracker, request)
+            .await?
+            .json()
+            .await
+            .map_err(|e| request_failed(tracker, e))
+    }
+
+    /// Send a request to a tracker and check its status. Rejected tokens
+    /// ask the user to link the account again.
+    async fn execute(
+        &self,
+        tracker: TrackerKind,
+        request: RequestBuilder,
+    ) -> Result<reqwest::Response> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| request_failed(tracker, e))?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(AppError::Unauthorized(
+                t!("tracker.reauthorize", tracker = tracker.as_str()).to_string(),
+            ));
+        }
+        if !status.is_success() {
+            return Err(request_failed(tracker, status));
+        }
+
+        Ok(response)
+    }
+
+    /// Get the OAuth client of a tracker or fail with NotFound when it is
+    /// not configured.
+    fn require_client(&self, tracker: TrackerKind) -> Result<&TrackerClientConfig> {
+        self.config.client(tracker).ok_or_else(|| {
+            AppError::NotFound(t!("tracker.not_configured", tracker = tracker.as_str()).to_string())
+        })
+    }
+}
+
+/// ID of the entry of a content on a tracker, from its metadata.
+pub fn remote_id(content: &Content, tracker: TrackerKind) -> Option<String> {
+    match tracker {
+        TrackerKind::AniList => metadata_source(content)
+            .filter(|(kind, id)| {
+                *kind == MetadataProviderKind::AniList && id.parse::<i64>().is_ok()
+            })
+            .map(|(_, id)| id),
+        TrackerKind::MyAnimeList => {
+            let metadata: serde_json::Value =
+                serde_json::from_slice(content.metadata.as_deref()?).ok()?;
+            metadata.get("mal_id")?.as_i64().map(|id| id.to_string())
+        }
+        TrackerKind::Bangumi => metadata_source(content)
+            .filter(|(kind, _)| *kind == MetadataProviderKind::Bangumi)
+            .map(|(_, id)| id),
+    }
+}
+
+/// Reading status matching a Bangumi collection type.
+fn bangumi_status(collection_type: i64) -> Option<ReadingStatus> {
+    match collection_type {
+        BANGUMI_WISH => Some(ReadingStatus::PlanToRead),
+        BANGUMI_DONE => Some(ReadingStatus::Completed),
+        BANGUMI_DOING => Some(ReadingStatus::Reading),
+        BANGUMI_ON_HOLD => Some(ReadingStatus::OnHold),
+        BANGUMI_DROPPED => Some(ReadingStatus::Dropped),
+        _ => None,
+    }
+}
+
+fn request_failed(tracker: TrackerKind, error: impl std::fmt::Display) -> AppError {
+    AppError::Internal(
+        t!(
+            "tracker.request_failed",
+            tracker = tracker.as_str(),
+            error = error
+        )
+        .to_string(),
+    )
+}
+
+/// Generate a PKCE code verifier of 64 hex characters.
+fn random_verifier() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{
+        TestChapter, TestContent, create_test_content, create_test_db, create_test_library,
+        create_test_scan_path, create_test_user,
+    };
+
+    async fn setup() -> Pool<Sqlite> {
+        let pool = create_test_db().await;
+        create_test_user(&pool, 1, "reader").await;
+        create_test_library(&pool, 1, "Comics").await;
+        create_test_scan_path(&pool, 1, 1, "/comics").await;
+        TestContent::new(1, 1, "Journey")
+            .metadata(json!({"source": {"provider": "anilist", "id": "30002"}, "mal_id": 2}))
+            .insert(&pool)
+            .await;
+        create_test_content(&pool, 2, 1, "Unmatched").await;
+        TestContent::new(3, 1, "Lantern")
+            .metadata(json!({"source": {"provider": "bangumi", "id": "328609"}}))
+            .insert(&pool)
+            .await;
+        for number in [1, 2, 3] {
+            TestChapter::new(number, 1, &format!("/comics/Journey/Ch {number}.cbz"))
+                .page_count(10)
+                .number(number as f32)
+                .insert(&pool)
+                .await;
+        }
+        TestChapter::new(4, 2, "/comics/Unmatched/Vol 1.cbz")
+            .page_count(10)
+            .insert(&pool)
+            .await;
+        sqlx::query(
+            r#"
+            INSERT INTO reading_progress (user_id, chapter_id, position, percentage, updated_at)
+            VALUES
+                (1, 1, 9, 100.0, '2026-10-16T10:00:00+00:00'),
+                (1, 2, 9, 100.0, '2026-10-16T11:00:00+00:00'),
+                (1, 3, 4, 50.0, '2026-10-16T12:00:00+00:00'),
+                (1, 4, 9, 100.0, '2026-10-16T09:00:00+00:00')
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        pool
+    }
+
+    fn service(pool: &Pool<Sqlite>) -> TrackerService {
+        TrackerService::new(pool.clone(), TrackerConfig::default(), "secret")
+    }
+
+    #[tokio::test]
+    async fn test_remote_id_from_metadata() {
+        let pool = setup().await;
+        let content = ContentRepository::find_by_id(&pool, 1)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            remote_id(&content, TrackerKind::AniList).as_deref(),
+            Some("30002")
+        );
+        assert_eq!(
+            remote_id(&content, TrackerKind::MyAnimeList).as_deref(),
+            Some("2")
+        );
+
+        let content = ContentRepository::find_by_id(&pool, 2)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(remote_id(&content, TrackerKind::AniList), None);
+
+        let content = ContentRepository::find_by_id(&pool, 3)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            remote_id(&content, TrackerKind::Bangumi).as_deref(),
+            Some("328609")
+        );
+        assert_eq!(remote_id(&content, TrackerKind::AniList), None);
+    }
+
+    #[tokio::test]
+    async fn test_read_counts_since_last_sync() {
+        let pool = setup().await;
+
+        let counts = TrackerRepository::list_read_counts(&pool, 1, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            counts
+                .iter()
+                .map(|c| (c.content_id, c.chapters_read()))
+                .collect::<Vec<_>>(),
+            [(1, 2), (2, 1)]
+        );
+
+        let since = "2026-10-16T10:30:00Z".parse().unwrap();
+        let counts = TrackerRepository::list_read_counts(&pool, 1, Some(since))
+            .await
+            .unwrap();
+        assert_eq!(counts.iter().map(|c| c.content_id).collect::<Vec<_>>(), [1]);
+    }
+
+    #[tokio::test]
+    async fn test_sync_skips_unchanged_and_opted_out_series() {
+        let pool = setup().await;
+        let token = TrackerToken {
+            access_token: "token".to_string(),
+            refresh_token: None,
+            expires_at: None,
+        };
+        let account =
+            TrackerRepository::upsert_account(&pool, 1, TrackerKind::AniList, "reader", &token)
+                .await
+                .unwrap();
+        let service = service(&pool);
+
+        // Already pushed with the same count: nothing to send
+        TrackerRepository::save_sync_state(&pool, 1, TrackerKind::AniList, 1, "30002", 2)
+            .await
+            .unwrap();
+        assert_eq!(service.sync_account(&account).await.unwrap(), 0);
+        let account = TrackerRepository::find_account(&pool, 1, TrackerKind::AniList)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(account.last_synced_at.is_some());
+        assert_eq!(account.last_error, None);
+
+        // Opted-out series are never sent
+        service.set_tracking(1, 1, false).await.unwrap();
+        sqlx::query("UPDATE reading_progress SET percentage = 100.0, updated_at = ? WHERE id = 3")
+            .bind(Utc::now().to_rfc3339())
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(service.sync_account(&account).await.unwrap(), 0);
+
+        let status = service.status(1).await.unwrap();
+        assert_eq!(status.opted_out, [1]);
+        assert!(status.trackers[0].linked);
+        assert!(!status.trackers[0].configured);
+        assert_eq!(status.trackers[0].synced_series, 1);
+        assert!(!status.trackers[1].linked);
+    }
+
+    #[tokio::test]
+    async fn test_pull_favorites_collected_series() {
+        let pool = setup().await;
+        let service = service(&pool);
+        ContentStatusRepository::upsert(&pool, 1, 3, false, Some(ReadingStatus::OnHold))
+            .await
+            .unwrap();
+
+        let collection = HashMap::from([
+            ("328609".to_string(), BANGUMI_DONE),
+            ("1".to_string(), BANGUMI_DOING),
+        ]);
+        let result = service
+            .apply_collection(1, TrackerKind::Bangumi, &collection)
+            .await
+            .unwrap();
+        assert_eq!(result.collected, 2);
+        assert_eq!(result.matched, 1);
+        assert_eq!(result.favorited, 1);
+        // The status the user picked is kept
+        assert_eq!(result.statuses_set, 0);
+        let status = ContentStatusRepository::find(&pool, 1, 3)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(status.favorite);
+        assert_eq!(status.status, Some(ReadingStatus::OnHold));
+
+        let result = service
+            .apply_collection(1, TrackerKind::Bangumi, &collection)
+            .await
+            .unwrap();
+        assert_eq!(result.favorited, 0);
+
+        let result = service.pull_collection(1, TrackerKind::AniList).await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_bangumi_status() {
+        assert_eq!(
+            bangumi_status(BANGUMI_WISH),
+            Some(ReadingStatus::PlanToRead)
+        );
+        assert_eq!(bangumi_status(BANGUMI_DONE), Some(ReadingStatus::Completed));
+        assert_eq!(
+            bangumi_status(BANGUMI_DROPPED),
+            Some(ReadingStatus::Dropped)
+        );
+        assert_eq!(bangumi_status(0), None);
+    }
+
+    #[tokio::test]
+    async fn test_state_must_match_cookie() {
+        let pool = setup().await;
+        let mut config = TrackerConfig::default();
+        config.myanimelist = Some(TrackerClientConfig {
+            client_id: "client".to_string(),
+            client_secret: "secret".to_string(),
+            redirect_url: "https://ryuri.example.com/api/trackers/myanimelist/callback".to_string(),
+        });
+        let service = TrackerService::new(pool, config, "secret");
+
+        assert!(service.authorize(1, TrackerKind::AniList).is_err());
+        let authorization = service.authorize(1, TrackerKind::MyAnimeList).unwrap();
+        assert!(authorization.url.starts_with(MAL_AUTHORIZE_URL));
+        assert!(authorization.url.contains("code_challenge_method=plain"));
+        assert!(service.secure_cookies(TrackerKind::MyAnimeList));
+
+        let result = service
+            .complete(
+                TrackerKind::MyAnimeList,
+                "code",
+                &authorization.state,
+                Some("other"),
+            )
+            .await;
+        assert!(matches!(result, Err(AppError::Unauthorized(_))));
+    }
+}
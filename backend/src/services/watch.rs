@@ -7,7 +7,6 @@
 //! Requirements: 1.9, 1.10, 1.11
 
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use rust_i18n::t;
 use sqlx::{Pool, Sqlite};
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -18,6 +17,7 @@ use tracing::{debug, error, instrument, warn};
 use crate::error::Result;
 use crate::repository::library::ScanPathRepository;
 use crate::services::scan_queue::ScanService;
+use crate::t;
 
 /// Handle for a running watcher that can be used to stop it.
 struct WatcherHandle {
@@ -132,10 +132,22 @@ impl WatchService {
 
                 // Trigger a rescan of the library
                 // Requirements: 1.10, 1.11
-                if let Err(e) = scan_service.scan_library(lib_id).await {
-                    error!(library_id = lib_id, error = %e, "{}", t!("watch.rescan_failed"));
-                } else {
-                    debug!(library_id = lib_id, "{}", t!("watch.rescan_completed"));
+                match scan_service.scan_library(lib_id).await {
+                    Ok(result) if result.scan_paths.iter().any(|p| p.error.is_some()) => {
+                        for stats in result.scan_paths.iter().filter(|p| p.error.is_some()) {
+                            error!(
+                                library_id = lib_id,
+                                path = %stats.path,
+                                error = stats.error.as_deref().unwrap_or_default(),
+                                "{}",
+                                t!("watch.rescan_failed")
+                            );
+                        }
+                    }
+                    Ok(_) => debug!(library_id = lib_id, "{}", t!("watch.rescan_completed")),
+                    Err(e) => {
+                        error!(library_id = lib_id, error = %e, "{}", t!("watch.rescan_failed"));
+                    }
                 }
             }
         });
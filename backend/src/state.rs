@@ -4,15 +4,40 @@
 //! across all handlers.
 
 use sqlx::{Pool, Sqlite};
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
+use crate::config::CorsConfig;
+use crate::middlewares::client_ip::{ClientIpConfig, ClientIpRules};
+use crate::middlewares::http_limits::HttpLimitsConfig;
+use crate::middlewares::rate_limit::{LoginRateLimitConfig, LoginRateLimiter};
+use crate::middlewares::security_headers::SecurityHeadersConfig;
+use crate::middlewares::timeout::RequestTimeoutConfig;
+use crate::models::{DEFAULT_METADATA_PROVIDERS, RuntimeSettings};
+use crate::services::anilist::AniListService;
+use crate::services::audit::AuditService;
 use crate::services::auth::{AuthConfig, AuthService};
+use crate::services::backup::{BackupConfig, BackupService};
 use crate::services::bangumi::BangumiService;
+use crate::services::executors::{BackupExecutor, ExecutorRegistry, LibraryDeletionExecutor};
+use crate::services::filesystem::FilesystemService;
+use crate::services::jobs::JobService;
 use crate::services::library::LibraryService;
+use crate::services::mangaupdates::MangaUpdatesService;
+use crate::services::metadata::MetadataManager;
+use crate::services::oidc::{OidcConfig, OidcService};
 use crate::services::progress::ProgressService;
-use crate::services::scan_queue::{ScanQueueService, ScanService};
+use crate::services::quota::{QuotaConfig, QuotaService};
+use crate::services::resource_guard::{ScanResourceConfig, ScanResourceGuard};
+use crate::services::scan_queue::{HistoryRetention, ScanQueueService, ScanService};
 use crate::services::scheduler::SchedulerService;
+use crate::services::settings::SettingsService;
+use crate::services::shutdown::ShutdownCoordinator;
+use crate::services::tracker::{TrackerConfig, TrackerService};
 use crate::services::watch::WatchService;
+use crate::utils::thumbnail::ThumbnailOptions;
+use crate::utils::title::TitleNormalizer;
 
 /// Unified application state containing all services.
 ///
@@ -32,12 +57,44 @@ pub struct AppState {
     pub progress_service: Arc<ProgressService>,
     /// Bangumi metadata service.
     pub bangumi_service: Arc<BangumiService>,
+    /// Metadata providers, including Bangumi.
+    pub metadata_manager: Arc<MetadataManager>,
     /// File system watch service for auto-detecting changes.
     pub watch_service: Arc<WatchService>,
     /// Scan queue service for asynchronous scan task management.
     pub scan_queue_service: Arc<ScanQueueService>,
     /// Scheduled scanning service.
     pub scheduler_service: Arc<SchedulerService>,
+    /// Unified view over all background jobs.
+    pub job_service: Arc<JobService>,
+    /// Rate limiter for login attempts.
+    pub login_rate_limiter: Arc<LoginRateLimiter>,
+    /// OIDC login service, if an identity provider is configured.
+    pub oidc_service: Option<Arc<OidcService>>,
+    /// Tracker account linking and progress sync.
+    pub tracker_service: Arc<TrackerService>,
+    /// Per-route request timeouts.
+    pub request_timeouts: RequestTimeoutConfig,
+    /// Audit log of security-relevant actions.
+    pub audit_service: Arc<AuditService>,
+    /// Database backup and restore service.
+    pub backup_service: Arc<BackupService>,
+    /// Per-user daily request and transfer quotas.
+    pub quota_service: Arc<QuotaService>,
+    /// Stops background services when the server shuts down.
+    pub shutdown: Arc<ShutdownCoordinator>,
+    /// Allowed cross-origin requests.
+    pub cors: CorsConfig,
+    /// Client IP resolution and allow/deny lists.
+    pub client_ip_rules: Arc<ClientIpRules>,
+    /// Security headers added to responses.
+    pub security_headers: SecurityHeadersConfig,
+    /// Response compression and request body limits.
+    pub http_limits: HttpLimitsConfig,
+    /// Runtime settings, stored in the database over configured defaults.
+    pub settings_service: Arc<SettingsService>,
+    /// Server filesystem browsing, limited to the allowed roots.
+    pub filesystem_service: Arc<FilesystemService>,
 }
 
 /// Configuration for the application.
@@ -45,6 +102,49 @@ pub struct AppState {
 pub struct AppConfig {
     /// Authentication configuration.
     pub auth: AuthConfig,
+    /// Resource limits for background scans.
+    pub scan: ScanResourceConfig,
+    /// Default number of libraries scanned at the same time.
+    pub scan_concurrency: usize,
+    /// Number of new content folders inspected at the same time per scan.
+    pub scan_import_concurrency: usize,
+    /// Days missing content stays in the trash (0 = until purged).
+    pub trash_retention_days: u32,
+    /// How long and how many finished scan tasks stay in the history.
+    pub task_history: HistoryRetention,
+    /// Cleans folder names into the titles of new content.
+    pub title_normalizer: TitleNormalizer,
+    /// Directories the filesystem browser and scan paths are limited to
+    /// (empty = the whole filesystem).
+    pub filesystem_roots: Vec<PathBuf>,
+    /// Default size, format and quality of generated thumbnails.
+    pub thumbnail_options: ThumbnailOptions,
+    /// Login rate limiting and lockout thresholds.
+    pub login_rate_limit: LoginRateLimitConfig,
+    /// External OIDC provider; OIDC login is disabled when unset.
+    pub oidc: Option<OidcConfig>,
+    /// Tracker clients and sync interval.
+    pub tracking: TrackerConfig,
+    /// Default Bangumi access token for scraping metadata.
+    pub bangumi_api_key: Option<String>,
+    /// Hours Bangumi responses are reused (0 = no cache).
+    pub bangumi_cache_ttl_hours: u32,
+    /// Per-route request timeouts.
+    pub request_timeouts: RequestTimeoutConfig,
+    /// Allowed cross-origin requests.
+    pub cors: CorsConfig,
+    /// Client IP resolution and allow/deny lists.
+    pub client_ip: ClientIpConfig,
+    /// Security headers added to responses.
+    pub security_headers: SecurityHeadersConfig,
+    /// Response compression and request body limits.
+    pub http_limits: HttpLimitsConfig,
+    /// Database backup settings.
+    pub backup: BackupConfig,
+    /// Default daily quotas of users.
+    pub quota: QuotaConfig,
+    /// Time allowed for each step of a graceful shutdown.
+    pub shutdown_timeout: Duration,
 }
 
 impl AppState {
@@ -54,20 +154,74 @@ impl AppState {
     /// * `pool` - SQLite database connection pool
     /// * `config` - Application configuration
     pub fn new(pool: Pool<Sqlite>, config: AppConfig) -> Self {
+        // Create OIDC service, signing its state with the JWT secret
+        let oidc_service = config.oidc.map(|oidc| {
+            Arc::new(OidcService::new(
+                pool.clone(),
+                oidc,
+                &config.auth.jwt_secret,
+            ))
+        });
+
+        // Create tracker service, signing its state with the JWT secret too
+        let tracker_service = Arc::new(TrackerService::new(
+            pool.clone(),
+            config.tracking,
+            &config.auth.jwt_secret,
+        ));
+
         // Create auth service
         let auth_service = Arc::new(AuthService::new(pool.clone(), config.auth));
 
+        // Create settings service over the configured defaults
+        let thumbnail_options = config.thumbnail_options;
+        let settings_service = Arc::new(SettingsService::new(
+            pool.clone(),
+            RuntimeSettings {
+                thumbnail_width: thumbnail_options.width,
+                thumbnail_height: thumbnail_options.height,
+                thumbnail_format: thumbnail_options.format,
+                thumbnail_quality: thumbnail_options.quality,
+                scan_concurrency: config.scan_concurrency,
+                metadata_providers: DEFAULT_METADATA_PROVIDERS.to_vec(),
+                komga_enabled: true,
+                kobo_enabled: true,
+                koreader_enabled: true,
+                sharing_enabled: false,
+                bangumi_api_key: config.bangumi_api_key,
+            },
+        ));
+
+        // Create filesystem service over the allowed roots
+        let filesystem_service = Arc::new(FilesystemService::new(config.filesystem_roots));
+
         // Create library service
-        let library_service = Arc::new(LibraryService::new(pool.clone()));
+        let mut library_service = LibraryService::new(pool.clone());
+        library_service.set_settings(Arc::clone(&settings_service));
+        library_service.set_filesystem(Arc::clone(&filesystem_service));
+        let library_service = Arc::new(library_service);
 
-        // Create Bangumi service
-        let bangumi_service = Arc::new(BangumiService::new(None));
+        // Create Bangumi service, caching its responses in the database
+        let mut bangumi_service = BangumiService::new(settings_service.bangumi_api_key());
+        bangumi_service.set_cache(pool.clone(), config.bangumi_cache_ttl_hours);
+        let bangumi_service = Arc::new(bangumi_service);
 
-        // Create scan service with Bangumi integration
-        let scan_service = Arc::new(ScanService::with_bangumi(
-            pool.clone(),
-            Arc::clone(&bangumi_service),
-        ));
+        // Create metadata manager over all providers
+        let mut metadata_manager = MetadataManager::new();
+        metadata_manager.register(Arc::clone(&bangumi_service));
+        metadata_manager.register(Arc::new(AniListService::new()));
+        metadata_manager.register(Arc::new(MangaUpdatesService::new()));
+        let metadata_manager = Arc::new(metadata_manager);
+
+        // Create scan service with metadata providers and resource limits
+        let mut scan_service =
+            ScanService::with_metadata(pool.clone(), Arc::clone(&metadata_manager));
+        scan_service.set_resource_guard(Arc::new(ScanResourceGuard::new(config.scan)));
+        scan_service.set_import_concurrency(config.scan_import_concurrency);
+        scan_service.set_trash_retention_days(config.trash_retention_days);
+        scan_service.set_title_normalizer(config.title_normalizer);
+        scan_service.set_thumbnail_options(config.thumbnail_options);
+        let scan_service = Arc::new(scan_service);
 
         // Create progress service
         let progress_service = Arc::new(ProgressService::new(pool.clone()));
@@ -75,14 +229,46 @@ impl AppState {
         // Create watch service
         let watch_service = Arc::new(WatchService::new(pool.clone(), Arc::clone(&scan_service)));
 
-        // Create scan queue service with scan service reference
-        let scan_queue_service = Arc::new(ScanQueueService::with_scan_service(Arc::clone(
-            &scan_service,
-        )));
+        // Create backup service
+        let backup_service = Arc::new(BackupService::new(pool.clone(), config.backup));
+
+        // Create scan queue service running the scan service's tasks, backups
+        // and library deletions
+        let mut executors = ExecutorRegistry::with_scan_service(Arc::clone(&scan_service));
+        executors.register(Arc::new(BackupExecutor::new(Arc::clone(&backup_service))));
+        executors.register(Arc::new(LibraryDeletionExecutor::new(Arc::clone(
+            &library_service,
+        ))));
+        let scan_queue_service = ScanQueueService::with_executors(executors);
+        scan_queue_service.set_worker_count(config.scan_concurrency);
+        scan_queue_service.set_history_retention(config.task_history);
+        let scan_queue_service = Arc::new(scan_queue_service);
 
         // Create scheduler service with scan queue for task submission
         let scheduler_service = Arc::new(SchedulerService::new(Arc::clone(&scan_queue_service)));
 
+        // Create job service over all background queues
+        let job_service = Arc::new(JobService::new(Arc::clone(&scan_queue_service)));
+
+        // Create audit service
+        let audit_service = Arc::new(AuditService::new(pool.clone()));
+
+        // Create quota service
+        let quota_service = Arc::new(QuotaService::new(pool.clone(), config.quota));
+
+        // Create shutdown coordinator over all background services
+        let shutdown = Arc::new(ShutdownCoordinator::new(
+            pool.clone(),
+            Arc::clone(&scan_queue_service),
+            Arc::clone(&scheduler_service),
+            Arc::clone(&watch_service),
+            Arc::clone(&quota_service),
+            config.shutdown_timeout,
+        ));
+
+        // Create login rate limiter
+        let login_rate_limiter = Arc::new(LoginRateLimiter::new(config.login_rate_limit));
+
         Self {
             pool,
             auth_service,
@@ -90,9 +276,36 @@ impl AppState {
             scan_service,
             progress_service,
             bangumi_service,
+            metadata_manager,
             watch_service,
             scan_queue_service,
             scheduler_service,
+            job_service,
+            login_rate_limiter,
+            oidc_service,
+            tracker_service,
+            request_timeouts: config.request_timeouts,
+            audit_service,
+            backup_service,
+            quota_service,
+            shutdown,
+            cors: config.cors,
+            client_ip_rules: config.client_ip.rules(),
+            security_headers: config.security_headers,
+            http_limits: config.http_limits,
+            settings_service,
+            filesystem_service,
         }
     }
+
+    /// Apply runtime settings to the services that cache them. The scan
+    /// concurrency only applies when the scan workers start.
+    pub fn apply_settings(&self, settings: &RuntimeSettings) {
+        self.scan_service
+            .set_thumbnail_options(settings.thumbnail_options());
+        self.scan_queue_service
+            .set_worker_count(settings.scan_concurrency);
+        self.bangumi_service
+            .set_api_key(settings.bangumi_api_key.clone());
+    }
 }
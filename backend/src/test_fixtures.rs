@@ -0,0 +1,248 @@
+//! Fixtures shared by unit tests.
+//!
+//! Tests start from an empty in-memory database and insert the rows they
+//! need with fixed IDs, so assertions can refer to them directly. Contents
+//! are placed in the library of their scan path and chapters are appended
+//! to their content, keeping its chapter count in step.
+
+use std::path::Path;
+
+use sqlx::{Pool, Sqlite};
+
+use crate::db::{DbConfig, init_db};
+
+/// Creation and update time of every fixture row.
+pub const TIMESTAMP: &str = "2026-10-16T00:00:00Z";
+
+/// Create an in-memory database with all migrations applied. The account
+/// seeded by the migrations is removed, so tests choose every user ID.
+pub async fn create_test_db() -> Pool<Sqlite> {
+    let config = DbConfig {
+        database_url: "sqlite::memory:".to_string(),
+        max_connections: 1,
+    };
+    let pool = init_db(&config)
+        .await
+        .expect("Failed to initialize test database");
+    sqlx::query("DELETE FROM users")
+        .execute(&pool)
+        .await
+        .expect("Should remove the seeded user");
+    pool
+}
+
+/// Insert a user who cannot log in with a password.
+pub async fn create_test_user(pool: &Pool<Sqlite>, id: i64, username: &str) {
+    insert_user(pool, id, username, false).await;
+}
+
+/// Insert an administrator who cannot log in with a password.
+pub async fn create_test_admin(pool: &Pool<Sqlite>, id: i64, username: &str) {
+    insert_user(pool, id, username, true).await;
+}
+
+async fn insert_user(pool: &Pool<Sqlite>, id: i64, username: &str, is_admin: bool) {
+    sqlx::query(
+        r#"
+        INSERT INTO users (id, username, password_hash, is_admin, created_at, updated_at)
+        VALUES (?, ?, '', ?, ?, ?)
+        "#,
+    )
+    .bind(id)
+    .bind(username)
+    .bind(is_admin)
+    .bind(TIMESTAMP)
+    .bind(TIMESTAMP)
+    .execute(pool)
+    .await
+    .expect("Should create test user");
+}
+
+/// Insert a library.
+pub async fn create_test_library(pool: &Pool<Sqlite>, id: i64, name: &str) {
+    sqlx::query(
+        r#"
+        INSERT INTO libraries (id, name, created_at, updated_at)
+        VALUES (?, ?, ?, ?)
+        "#,
+    )
+    .bind(id)
+    .bind(name)
+    .bind(TIMESTAMP)
+    .bind(TIMESTAMP)
+    .execute(pool)
+    .await
+    .expect("Should create test library");
+}
+
+/// Insert a scan path of a library.
+pub async fn create_test_scan_path(pool: &Pool<Sqlite>, id: i64, library_id: i64, path: &str) {
+    sqlx::query(
+        r#"
+        INSERT INTO scan_paths (id, library_id, path, created_at)
+        VALUES (?, ?, ?, ?)
+        "#,
+    )
+    .bind(id)
+    .bind(library_id)
+    .bind(path)
+    .bind(TIMESTAMP)
+    .execute(pool)
+    .await
+    .expect("Should create test scan path");
+}
+
+/// Insert a content in a folder named after its title.
+pub async fn create_test_content(pool: &Pool<Sqlite>, id: i64, scan_path_id: i64, title: &str) {
+    TestContent::new(id, scan_path_id, title).insert(pool).await;
+}
+
+/// Insert a chapter titled and typed after its file name.
+pub async fn create_test_chapter(pool: &Pool<Sqlite>, id: i64, content_id: i64, file_path: &str) {
+    TestChapter::new(id, content_id, file_path)
+        .insert(pool)
+        .await;
+}
+
+/// A content to insert, for tests that need more than
+/// [`create_test_content`] sets.
+pub struct TestContent {
+    id: i64,
+    scan_path_id: i64,
+    title: String,
+    folder: Option<String>,
+    metadata: Option<serde_json::Value>,
+}
+
+impl TestContent {
+    /// A content without metadata, in a folder named after its title.
+    pub fn new(id: i64, scan_path_id: i64, title: &str) -> Self {
+        Self {
+            id,
+            scan_path_id,
+            title: title.to_string(),
+            folder: None,
+            metadata: None,
+        }
+    }
+
+    /// Place the content in `folder`, relative to its scan path.
+    pub fn folder(mut self, folder: &str) -> Self {
+        self.folder = Some(folder.to_string());
+        self
+    }
+
+    /// Store scraped metadata with the content.
+    pub fn metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Insert the content into the library of its scan path.
+    pub async fn insert(self, pool: &Pool<Sqlite>) {
+        let folder = self.folder.as_deref().unwrap_or(&self.title);
+        sqlx::query(
+            r#"
+            INSERT INTO contents
+                (id, library_id, scan_path_id, title, folder_path, chapter_count, metadata,
+                created_at, updated_at)
+            SELECT ?, library_id, id, ?, path || '/' || ?, 0, ?, ?, ?
+            FROM scan_paths WHERE id = ?
+            "#,
+        )
+        .bind(self.id)
+        .bind(&self.title)
+        .bind(folder)
+        .bind(self.metadata.map(|m| m.to_string().into_bytes()))
+        .bind(TIMESTAMP)
+        .bind(TIMESTAMP)
+        .bind(self.scan_path_id)
+        .execute(pool)
+        .await
+        .expect("Should create test content");
+    }
+}
+
+/// A chapter to insert, for tests that need more than
+/// [`create_test_chapter`] sets.
+pub struct TestChapter {
+    id: i64,
+    content_id: i64,
+    file_path: String,
+    page_count: i32,
+    number: Option<f32>,
+    page_hash: Option<String>,
+}
+
+impl TestChapter {
+    /// A chapter without pages, titled after the stem of `file_path` and
+    /// typed after its extension. Folders have no type.
+    pub fn new(id: i64, content_id: i64, file_path: &str) -> Self {
+        Self {
+            id,
+            content_id,
+            file_path: file_path.to_string(),
+            page_count: 0,
+            number: None,
+            page_hash: None,
+        }
+    }
+
+    /// Set the number of pages.
+    pub fn page_count(mut self, page_count: i32) -> Self {
+        self.page_count = page_count;
+        self
+    }
+
+    /// Set the chapter number.
+    pub fn number(mut self, number: f32) -> Self {
+        self.number = Some(number);
+        self
+    }
+
+    /// Set the hash of the first page, which duplicate detection compares.
+    pub fn page_hash(mut self, page_hash: &str) -> Self {
+        self.page_hash = Some(page_hash.to_string());
+        self
+    }
+
+    /// Insert the chapter after the chapters its content already has.
+    pub async fn insert(self, pool: &Pool<Sqlite>) {
+        let path = Path::new(&self.file_path);
+        let title = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let file_type = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        sqlx::query(
+            r#"
+            INSERT INTO chapters
+                (id, content_id, title, file_path, file_type, sort_order, page_count, number,
+                page_hash)
+            VALUES (?, ?, ?, ?, ?, (SELECT COUNT(*) FROM chapters WHERE content_id = ?), ?, ?, ?)
+            "#,
+        )
+        .bind(self.id)
+        .bind(self.content_id)
+        .bind(title)
+        .bind(&self.file_path)
+        .bind(file_type)
+        .bind(self.content_id)
+        .bind(self.page_count)
+        .bind(self.number)
+        .bind(&self.page_hash)
+        .execute(pool)
+        .await
+        .expect("Should create test chapter");
+
+        sqlx::query("UPDATE contents SET chapter_count = chapter_count + 1 WHERE id = ?")
+            .bind(self.content_id)
+            .execute(pool)
+            .await
+            .expect("Should count test chapter");
+    }
+}
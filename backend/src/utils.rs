@@ -1,3 +1,23 @@
+pub mod blurhash;
+pub mod cancel;
+pub mod cursor;
+pub mod html;
+pub mod ignore;
+pub mod ip_network;
+pub mod locale;
+pub mod numbering;
+pub mod oauth_state;
+pub mod page_cache;
+pub mod page_image;
+pub mod patch;
+pub mod placeholder;
+pub mod protobuf;
+pub mod range;
+pub mod text_index;
+pub mod thumbnail;
+pub mod title;
+pub mod token_bucket;
+
 use crate::error::{AppError, Result};
 
 pub async fn download_image(url: &str) -> Result<Vec<u8>> {
@@ -0,0 +1,163 @@
+//! BlurHash placeholders for covers and pages.
+//!
+//! A BlurHash is a short string describing a blurred version of an image,
+//! which clients decode into a placeholder shown until the image loads.
+//! See <https://blurha.sh> for the format.
+
+use std::f32::consts::PI;
+
+use image::DynamicImage;
+
+/// Images are shrunk to fit this size before hashing. The hash only keeps
+/// a few colour components, so more pixels would only cost time.
+const HASH_INPUT_SIZE: u32 = 32;
+
+/// Colour components along the long side of an image.
+const LONG_SIDE_COMPONENTS: u32 = 4;
+
+/// Colour components along the short side of an image.
+const SHORT_SIDE_COMPONENTS: u32 = 3;
+
+const BASE83_DIGITS: &[u8; 83] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz#$%*+,-.:;=?@[]^_{|}~";
+
+/// Compute the BlurHash of encoded image data, with more components along
+/// the longer side. `None` if the image cannot be decoded.
+pub fn from_image_data(data: &[u8]) -> Option<String> {
+    let image = image::load_from_memory(data).ok()?;
+    if image.width() == 0 || image.height() == 0 {
+        return None;
+    }
+
+    let image = image.thumbnail(HASH_INPUT_SIZE, HASH_INPUT_SIZE);
+    let (x_components, y_components) = if image.height() > image.width() {
+        (SHORT_SIDE_COMPONENTS, LONG_SIDE_COMPONENTS)
+    } else {
+        (LONG_SIDE_COMPONENTS, SHORT_SIDE_COMPONENTS)
+    };
+    Some(encode(&image, x_components, y_components))
+}
+
+/// Compute the BlurHash of an image with the given number of colour
+/// components across and down, each 1 to 9.
+pub fn encode(image: &DynamicImage, x_components: u32, y_components: u32) -> String {
+    let x_components = x_components.clamp(1, 9);
+    let y_components = y_components.clamp(1, 9);
+    let image = image.to_rgb8();
+    let (width, height) = image.dimensions();
+    let pixels: Vec<[f32; 3]> = image
+        .pixels()
+        .map(|pixel| pixel.0.map(srgb_to_linear))
+        .collect();
+
+    let mut factors = Vec::with_capacity((x_components * y_components) as usize);
+    for j in 0..y_components {
+        for i in 0..x_components {
+            let normalisation = if i == 0 && j == 0 { 1.0 } else { 2.0 };
+            let mut factor = [0.0f32; 3];
+            for y in 0..height {
+                let basis_y = (PI * j as f32 * y as f32 / height as f32).cos();
+                for x in 0..width {
+                    let basis = basis_y * (PI * i as f32 * x as f32 / width as f32).cos();
+                    let pixel = pixels[(y * width + x) as usize];
+                    for (sum, value) in factor.iter_mut().zip(pixel) {
+                        *sum += basis * value;
+                    }
+                }
+            }
+            let scale = normalisation / (width * height) as f32;
+            factors.push(factor.map(|value| value * scale));
+        }
+    }
+
+    let (dc, ac) = factors.split_first().expect("at least one component");
+    let mut hash = String::with_capacity(6 + 2 * ac.len());
+    push_base83(&mut hash, (x_components - 1) + (y_components - 1) * 9, 1);
+
+    let maximum = if ac.is_empty() {
+        push_base83(&mut hash, 0, 1);
+        1.0
+    } else {
+        let actual = ac
+            .iter()
+            .flatten()
+            .fold(0.0f32, |max, value| max.max(value.abs()));
+        let quantised = (actual * 166.0 - 0.5).floor().clamp(0.0, 82.0) as u32;
+        push_base83(&mut hash, quantised, 1);
+        (quantised + 1) as f32 / 166.0
+    };
+
+    let [r, g, b] = dc.map(linear_to_srgb);
+    push_base83(&mut hash, (r << 16) | (g << 8) | b, 4);
+    for factor in ac {
+        let [r, g, b] = factor.map(|value| {
+            let value = sign_pow(value / maximum, 0.5) * 9.0 + 9.5;
+            value.floor().clamp(0.0, 18.0) as u32
+        });
+        push_base83(&mut hash, r * 19 * 19 + g * 19 + b, 2);
+    }
+
+    hash
+}
+
+fn push_base83(hash: &mut String, value: u32, digits: u32) {
+    for digit in (0..digits).rev() {
+        let index = value / 83u32.pow(digit) % 83;
+        hash.push(BASE83_DIGITS[index as usize] as char);
+    }
+}
+
+fn srgb_to_linear(value: u8) -> f32 {
+    let value = value as f32 / 255.0;
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> u32 {
+    let value = value.clamp(0.0, 1.0);
+    let srgb = if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
+    (srgb * 255.0 + 0.5) as u32
+}
+
+fn sign_pow(value: f32, exponent: f32) -> f32 {
+    value.abs().powf(exponent).copysign(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageFormat, Rgb, RgbImage};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_solid_colour() {
+        let white = DynamicImage::ImageRgb8(RgbImage::from_pixel(8, 6, Rgb([255, 255, 255])));
+        assert_eq!(encode(&white, 4, 3), "LsTSUA_3fQ_3~qt7fQt7fQfQfQfQ");
+
+        let black = DynamicImage::ImageRgb8(RgbImage::new(8, 6));
+        assert_eq!(encode(&black, 1, 1), "000000");
+    }
+
+    #[test]
+    fn test_components_follow_orientation() {
+        let mut data = Vec::new();
+        DynamicImage::ImageRgb8(RgbImage::from_fn(40, 60, |x, _| {
+            Rgb([(x * 6) as u8, 0, 90])
+        }))
+        .write_to(&mut Cursor::new(&mut data), ImageFormat::Png)
+        .unwrap();
+
+        let hash = from_image_data(&data).unwrap();
+        // 3 across, 4 down
+        assert!(hash.starts_with('T'));
+        assert_eq!(hash.len(), 6 + 2 * 11);
+        assert_eq!(from_image_data(b"not an image"), None);
+    }
+}
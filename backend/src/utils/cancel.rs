@@ -0,0 +1,108 @@
+//! Cancellation of blocking work tied to a request.
+//!
+//! Axum drops a handler's future when the client disconnects or a timeout
+//! fires, but work already handed to the blocking pool keeps running. Blocking
+//! work started with [`spawn_cancellable`] receives a [`CancellationFlag`] that
+//! is raised when the awaiting future is dropped, and should check it between
+//! expensive steps.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::error::{AppError, Result};
+
+/// Shared flag signalling that the result of some work is no longer wanted.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationFlag(Arc<AtomicBool>);
+
+impl CancellationFlag {
+    /// Create a flag that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Raise the flag.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true if the flag has been raised.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Fail with [`AppError::Cancelled`] if the flag has been raised.
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(AppError::Cancelled);
+        }
+        Ok(())
+    }
+}
+
+/// Raises the flag when dropped.
+struct CancelOnDrop(CancellationFlag);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+/// Run blocking work on the blocking pool, cancelling it if the returned
+/// future is dropped before completion.
+pub async fn spawn_cancellable<F, T>(f: F) -> Result<T>
+where
+    F: FnOnce(&CancellationFlag) -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let flag = CancellationFlag::new();
+    let guard = CancelOnDrop(flag.clone());
+
+    let result = tokio::task::spawn_blocking(move || f(&flag))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+    // Completed normally; raising the flag now is harmless
+    drop(guard);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_completed_work_returns_result() {
+        let value = spawn_cancellable(|cancel| {
+            cancel.check()?;
+            Ok(7)
+        })
+        .await
+        .unwrap();
+        assert_eq!(value, 7);
+    }
+
+    #[tokio::test]
+    async fn test_dropping_future_raises_flag() {
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let work = spawn_cancellable(move |cancel| {
+            while !cancel.is_cancelled() {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            let _ = tx.send(());
+            cancel.check()
+        });
+        let _ = tokio::time::timeout(Duration::from_millis(20), work).await;
+
+        let observed = tokio::task::spawn_blocking(move || rx.recv_timeout(Duration::from_secs(5)))
+            .await
+            .unwrap();
+        assert!(
+            observed.is_ok(),
+            "blocking work should see the cancellation"
+        );
+    }
+}
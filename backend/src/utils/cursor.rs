@@ -0,0 +1,83 @@
+//! Opaque cursors for keyset pagination.
+//!
+//! A cursor holds the sort key and ID of the last item of a page, and the
+//! next page starts after it, so deep pages cost no more than the first.
+//! Cursors are URL-safe Base64 JSON that clients pass back unchanged. Each
+//! names the order it was made for, so a cursor of one listing is rejected
+//! by another.
+
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, ErrorCode, Result};
+use crate::t;
+
+/// Position after the last item of a page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cursor {
+    /// Order of the listing the cursor belongs to.
+    #[serde(rename = "o")]
+    pub order: String,
+    /// Sort key of the last item.
+    #[serde(rename = "k")]
+    pub key: String,
+    /// ID of the last item, ordering items with equal keys.
+    #[serde(rename = "i")]
+    pub id: i64,
+}
+
+impl Cursor {
+    /// Create a cursor after the item with sort key `key` and ID `id` of a
+    /// listing in `order`.
+    pub fn new(order: &str, key: impl Into<String>, id: i64) -> Self {
+        Self {
+            order: order.to_string(),
+            key: key.into(),
+            id,
+        }
+    }
+
+    /// Encode the cursor for a client.
+    pub fn encode(&self) -> String {
+        BASE64_URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    /// Decode a cursor a client sent for a listing in `order`.
+    pub fn decode(cursor: &str, order: &str) -> Result<Self> {
+        BASE64_URL_SAFE_NO_PAD
+            .decode(cursor)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Cursor>(&bytes).ok())
+            .filter(|cursor| cursor.order == order)
+            .ok_or_else(|| {
+                AppError::BadRequest(t!("pagination.invalid_cursor").to_string())
+                    .with_code(ErrorCode::InvalidCursor)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let cursor = Cursor::new("title", "Ève & \"friends\"", 42);
+        let encoded = cursor.encode();
+        assert!(
+            encoded
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        );
+        assert_eq!(Cursor::decode(&encoded, "title").unwrap(), cursor);
+    }
+
+    #[test]
+    fn test_rejects_invalid_cursors() {
+        let other_order = Cursor::new("added", "2026-01-01", 1).encode();
+        for cursor in ["", "not a cursor", "e30", other_order.as_str()] {
+            let err = Cursor::decode(cursor, "title").unwrap_err();
+            assert_eq!(err.code(), ErrorCode::InvalidCursor);
+        }
+    }
+}
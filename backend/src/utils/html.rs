@@ -0,0 +1,317 @@
+//! HTML sanitizing for book sections shown in the reader.
+//!
+//! Sections are rendered inside the reader page, so they are parsed and
+//! rebuilt with [`ammonia`], keeping only an allow-list of elements and
+//! attributes. URL attributes are passed to a caller-supplied function,
+//! which rewrites references to files inside the book and drops anything
+//! else it does not allow.
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, LazyLock, Mutex};
+
+use ammonia::Builder;
+use regex::{Captures, Regex};
+
+/// Elements allowed on top of ammonia's defaults.
+const EXTRA_TAGS: &[&str] = &[
+    "section", "main", "audio", "video", "source", "track", "svg", "image",
+];
+
+/// Elements removed together with their content, on top of `script` and
+/// `style`. Other elements that are not allowed are replaced by their
+/// content.
+const REMOVED_ELEMENTS: &[&str] = &[
+    "iframe", "object", "embed", "frame", "frameset", "noscript", "noembed", "noframes",
+    "template", "title", "textarea", "select",
+];
+
+/// Attributes allowed on every element, on top of ammonia's defaults.
+const GENERIC_ATTRIBUTES: &[&str] = &["id", "class", "dir", "style"];
+
+/// Attributes allowed on specific elements, on top of ammonia's defaults.
+const TAG_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("audio", &["src", "controls", "loop", "preload"]),
+    (
+        "video",
+        &[
+            "src", "controls", "loop", "preload", "poster", "width", "height",
+        ],
+    ),
+    ("source", &["src", "type"]),
+    ("track", &["src", "kind", "srclang", "label", "default"]),
+    (
+        "svg",
+        &[
+            "viewBox",
+            "width",
+            "height",
+            "preserveAspectRatio",
+            "version",
+        ],
+    ),
+    ("image", &["href", "width", "height", "preserveAspectRatio"]),
+];
+
+/// Attributes holding a URL. `xlink:href` is seen by its local name.
+const URL_ATTRIBUTES: &[&str] = &["href", "src", "poster"];
+
+static START_TAG: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"<([a-zA-Z][a-zA-Z0-9:_-]*)((?:[^>"']|"[^"]*"|'[^']*')*?)(/?)>"#).unwrap()
+});
+
+static ATTRIBUTE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"([^\s/>"'=]+)(?:\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+)))?"#).unwrap()
+});
+
+static BODY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<body\b[^>]*>(.*?)(?:</body\s*>|$)").unwrap());
+
+static CSS_URL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?i)url\(\s*(?:"([^"]*)"|'([^']*)'|([^)\s]*))\s*\)"#).unwrap());
+
+/// Sanitize an HTML or XHTML document and return the inner HTML of its body.
+///
+/// `rewrite` receives the element name and value of every URL attribute
+/// that is not a script URL, and returns the URL to use or `None` to drop
+/// the attribute.
+pub fn sanitize_html(html: &str, rewrite: impl Fn(&str, &str) -> Option<String>) -> String {
+    let body = BODY
+        .captures(html)
+        .and_then(|c| c.get(1))
+        .map_or(html, |m| m.as_str());
+
+    // Attribute filters must be 'static, so the URLs are collected while
+    // cleaning, rewritten, and swapped in on a second pass over the output
+    let found = Arc::new(Mutex::new(HashSet::new()));
+    let recorder = Arc::clone(&found);
+    let clean = builder(move |element, attribute, value| {
+        if URL_ATTRIBUTES.contains(&attribute) {
+            let mut found = recorder.lock().unwrap_or_else(|e| e.into_inner());
+            found.insert((element.to_string(), value.to_string()));
+        } else if attribute == "style" {
+            return sanitize_style(value);
+        }
+        Some(value.into())
+    })
+    .clean(body)
+    .to_string();
+
+    let found = std::mem::take(&mut *found.lock().unwrap_or_else(|e| e.into_inner()));
+    let rewritten: HashMap<(String, String), String> = found
+        .into_iter()
+        .filter(|(_, url)| !is_script_url(url))
+        .filter_map(|(element, url)| {
+            let new_url = rewrite(&element, &url)?;
+            Some(((element, url), new_url))
+        })
+        .collect();
+
+    builder(move |element, attribute, value| {
+        if !URL_ATTRIBUTES.contains(&attribute) {
+            return Some(value.into());
+        }
+        rewritten
+            .get(&(element.to_string(), value.to_string()))
+            .map(|url| Cow::Owned(url.clone()))
+    })
+    .clean(&clean)
+    .to_string()
+}
+
+/// `href` values of the stylesheet `<link>` elements in a document.
+pub fn stylesheet_links(html: &str) -> Vec<String> {
+    START_TAG
+        .captures_iter(html)
+        .filter(|caps| caps[1].eq_ignore_ascii_case("link"))
+        .filter_map(|caps| {
+            let mut rel = None;
+            let mut href = None;
+            for attr in ATTRIBUTE.captures_iter(&caps[2]) {
+                let value = attr.get(2).or_else(|| attr.get(3)).or_else(|| attr.get(4));
+                match attr[1].to_lowercase().as_str() {
+                    "rel" => rel = value.map(|m| m.as_str().to_lowercase()),
+                    "href" => href = value.map(|m| decode_entities(m.as_str())),
+                    _ => {}
+                }
+            }
+            rel.filter(|rel| rel.split_whitespace().any(|r| r == "stylesheet"))
+                .and(href)
+        })
+        .collect()
+}
+
+/// Rewrite the `url(...)` references in a stylesheet. References `rewrite`
+/// returns `None` for are emptied.
+pub fn rewrite_css_urls(css: &str, rewrite: impl Fn(&str) -> Option<String>) -> String {
+    CSS_URL
+        .replace_all(css, |caps: &Captures| {
+            let url = caps
+                .get(1)
+                .or_else(|| caps.get(2))
+                .or_else(|| caps.get(3))
+                .map_or("", |m| m.as_str());
+            match rewrite(url) {
+                Some(url) => format!("url(\"{}\")", url.replace('"', "%22")),
+                None => "url(\"\")".to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// Allow-list sanitizer with the elements and attributes books use.
+fn builder(
+    filter: impl for<'u> Fn(&str, &str, &'u str) -> Option<Cow<'u, str>> + Send + Sync + 'static,
+) -> Builder<'static> {
+    let mut builder = Builder::default();
+    builder
+        .add_tags(EXTRA_TAGS)
+        .add_clean_content_tags(REMOVED_ELEMENTS)
+        .add_generic_attributes(GENERIC_ATTRIBUTES)
+        // Images embedded in books, checked by `is_script_url`
+        .add_url_schemes(&["data"])
+        .attribute_filter(filter);
+    for (tag, attributes) in TAG_ATTRIBUTES {
+        builder.add_tag_attributes(*tag, *attributes);
+    }
+    builder
+}
+
+/// Keep an inline style unless it can run script, emptying its `url(...)`
+/// references other than embedded images.
+fn sanitize_style(style: &str) -> Option<Cow<'_, str>> {
+    let lower = style.to_lowercase();
+    if lower.contains("expression(") || lower.contains("javascript:") {
+        return None;
+    }
+    let style = rewrite_css_urls(style, |url| {
+        url.starts_with("data:image/").then(|| url.to_string())
+    });
+    Some(style.into())
+}
+
+/// Whether a URL would run script or load an HTML document when followed.
+fn is_script_url(url: &str) -> bool {
+    let url: String = decode_entities(url)
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_lowercase();
+    url.starts_with("javascript:")
+        || url.starts_with("vbscript:")
+        || (url.starts_with("data:") && !url.starts_with("data:image/"))
+}
+
+/// Decode the character references that appear in URLs.
+fn decode_entities(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keep(_: &str, url: &str) -> Option<String> {
+        Some(url.to_string())
+    }
+
+    #[test]
+    fn test_removes_scripts_and_handlers() {
+        let html = r#"<html><head><title>T</title><script>alert(1)</script></head>
+<body><p onclick="alert(1)" class="a">Hi<script src="x.js"></script></p>
+<a href="javascript:alert(1)">x</a><iframe src="evil.html"></iframe><!-- note --></body></html>"#;
+        let result = sanitize_html(html, keep);
+
+        assert!(result.contains(r#"<p class="a">Hi</p>"#));
+        assert!(result.contains(">x</a>"));
+        assert!(!result.contains("script"));
+        assert!(!result.contains("iframe"));
+        assert!(!result.contains("note"));
+        assert!(!result.contains("<title>"));
+    }
+
+    #[test]
+    fn test_nested_tags_do_not_rebuild_removed_elements() {
+        for html in [
+            r#"<ifr<iframe></iframe>ame srcdoc="&lt;script&gt;alert(1)&lt;/script&gt;">"#,
+            "<scr<script></script>ipt>alert(1)</script>",
+            "<<script>x</script>script>alert(1)<</script>/script>",
+        ] {
+            let result = sanitize_html(html, keep).to_lowercase();
+            assert!(!result.contains("<iframe"), "{result}");
+            assert!(!result.contains("<script"), "{result}");
+        }
+    }
+
+    #[test]
+    fn test_drops_attributes_that_load_documents() {
+        let html = r#"<form action="https://evil.example"><button formaction="/x">Go</button>
+</form><object data="evil.swf"></object><table background="bg.png"><tr><td>1</td></tr>
+</table><svg><animate attributeName="href" to="javascript:alert(1)"/>
+<a xlink:href="javascript:alert(2)">y</a></svg>
+<img srcdoc="<b>x</b>" src="a.png"><a href="data:text/html,<b>x</b>">z</a>"#;
+        let result = sanitize_html(html, keep);
+
+        for needle in [
+            "srcdoc",
+            "action",
+            "data=",
+            "background",
+            "animate",
+            "javascript",
+            "data:text",
+            "<object",
+            "<form",
+        ] {
+            assert!(!result.contains(needle), "{needle} in {result}");
+        }
+        assert!(result.contains("Go"));
+        assert!(result.contains(r#"src="a.png""#));
+    }
+
+    #[test]
+    fn test_rewrites_urls() {
+        let html = r#"<body><img src="../images/a&amp;b.png" alt='A "cat"'/>
+<a href="ch2.xhtml#s1">next</a><img src="http://example.com/x.png"/>
+<p style="background: url(http://example.com/t.png); color: red">t</p></body>"#;
+        let result = sanitize_html(html, |element, url| {
+            if url.starts_with("http") {
+                return (element == "a").then(|| url.to_string());
+            }
+            Some(format!("/res/{}", url))
+        });
+
+        assert!(result.contains(r#"src="/res/../images/a&amp;b.png""#));
+        assert!(result.contains(r#"alt="A &quot;cat&quot;""#));
+        assert!(result.contains(r#"href="/res/ch2.xhtml#s1""#));
+        assert!(result.contains("<img>"));
+        assert!(!result.contains("example.com"));
+        assert!(result.contains("color: red"));
+    }
+
+    #[test]
+    fn test_stylesheet_links() {
+        let html = r#"<head><link rel="stylesheet" type="text/css" href="../css/book.css"/>
+<link rel="icon" href="icon.png"/><LINK REL='alternate stylesheet' HREF='alt.css'></head>"#;
+        assert_eq!(stylesheet_links(html), vec!["../css/book.css", "alt.css"]);
+    }
+
+    #[test]
+    fn test_rewrite_css_urls() {
+        let css = r#"body { background: url('bg.png'); } @font-face { src: url(font.otf) }
+.x { background: url("http://example.com/a.png") }"#;
+        let result = rewrite_css_urls(css, |url| {
+            (!url.starts_with("http")).then(|| format!("/res/{}", url))
+        });
+
+        assert!(result.contains(r#"url("/res/bg.png")"#));
+        assert!(result.contains(r#"url("/res/font.otf")"#));
+        assert!(result.contains(r#"url("")"#));
+    }
+}
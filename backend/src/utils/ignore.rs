@@ -0,0 +1,125 @@
+//! Ignore patterns for library scans.
+//!
+//! Patterns are matched against the name of each file or folder (not the
+//! full path) and support `*` (any run of characters) and `?` (any single
+//! character), for example `@eaDir`, `.*` or `*_raw`.
+
+/// Patterns given to new libraries: Synology thumbnail and recycle bin
+/// folders, and hidden files and folders.
+pub const DEFAULT_IGNORE_PATTERNS: &[&str] = &["@eaDir", "#recycle", ".*"];
+
+/// Maximum length of a single pattern.
+pub const MAX_PATTERN_LEN: usize = 255;
+
+/// Matches names against a set of ignore patterns.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IgnoreMatcher {
+    patterns: Vec<Vec<char>>,
+}
+
+impl IgnoreMatcher {
+    /// Build a matcher from patterns.
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Self {
+        Self {
+            patterns: patterns
+                .iter()
+                .map(|p| p.as_ref().chars().collect())
+                .collect(),
+        }
+    }
+
+    /// Whether a file or folder name matches any pattern.
+    pub fn is_ignored(&self, name: &str) -> bool {
+        if self.patterns.is_empty() {
+            return false;
+        }
+        let name: Vec<char> = name.chars().collect();
+        self.patterns.iter().any(|p| wildcard_match(p, &name))
+    }
+
+    /// Whether the last component of `path` matches any pattern.
+    pub fn is_ignored_path(&self, path: &std::path::Path) -> bool {
+        path.file_name()
+            .map(|name| self.is_ignored(&name.to_string_lossy()))
+            .unwrap_or(false)
+    }
+}
+
+/// Whether a pattern is acceptable: non-empty, not too long and without
+/// path separators, since it only ever sees a single name.
+pub fn is_valid_pattern(pattern: &str) -> bool {
+    !pattern.is_empty()
+        && pattern.len() <= MAX_PATTERN_LEN
+        && !pattern.contains(['/', '\\'])
+        && !pattern.chars().any(char::is_control)
+}
+
+/// Match `name` against a pattern with `*` and `?` wildcards.
+///
+/// Iterative with a single backtracking point, so it runs in O(n * m).
+fn wildcard_match(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            // Let the last `*` swallow one more character
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_literal_and_wildcards() {
+        let matcher = IgnoreMatcher::new(&["@eaDir", ".*", "*_raw", "vol?"]);
+
+        assert!(matcher.is_ignored("@eaDir"));
+        assert!(matcher.is_ignored(".hidden"));
+        assert!(matcher.is_ignored("Series_raw"));
+        assert!(matcher.is_ignored("_raw"));
+        assert!(matcher.is_ignored("vol1"));
+
+        assert!(!matcher.is_ignored("eaDir"));
+        assert!(!matcher.is_ignored("Series_raw2"));
+        assert!(!matcher.is_ignored("vol10"));
+        assert!(!matcher.is_ignored("Series"));
+    }
+
+    #[test]
+    fn test_empty_matches_nothing() {
+        let matcher = IgnoreMatcher::default();
+        assert!(!matcher.is_ignored(".hidden"));
+    }
+
+    #[test]
+    fn test_path_uses_last_component() {
+        let matcher = IgnoreMatcher::new(&["@eaDir"]);
+        assert!(matcher.is_ignored_path(std::path::Path::new("/library/Series/@eaDir")));
+        assert!(!matcher.is_ignored_path(std::path::Path::new("/@eaDir/Series")));
+    }
+
+    #[test]
+    fn test_valid_pattern() {
+        assert!(is_valid_pattern("*_raw"));
+        assert!(!is_valid_pattern(""));
+        assert!(!is_valid_pattern("a/b"));
+        assert!(!is_valid_pattern("a\\b"));
+        assert!(!is_valid_pattern(&"a".repeat(MAX_PATTERN_LEN + 1)));
+    }
+}
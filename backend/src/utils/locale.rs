@@ -0,0 +1,215 @@
+//! Locale negotiation and locale-aware formatting.
+//!
+//! Background work follows the server locale. While a request is handled,
+//! [`t!`](crate::t) translates into the locale negotiated for it, so users
+//! of one server each get messages in their own language. Text rendered
+//! for a particular user outside a request (such as scan reports) takes
+//! that user's locale explicitly. Number, date and plural formats are part
+//! of the translations so new locales only need entries in
+//! `locales/app.yml`.
+
+use chrono::{DateTime, Utc};
+
+use crate::t;
+
+tokio::task_local! {
+    static REQUEST_LOCALE: String;
+}
+
+/// Locale of the request handled by the current task, or the server locale
+/// outside of requests.
+pub fn current_locale() -> String {
+    REQUEST_LOCALE
+        .try_with(Clone::clone)
+        .unwrap_or_else(|_| rust_i18n::locale().to_string())
+}
+
+/// Run `future` with [`t!`](crate::t) translating into `locale`.
+pub async fn with_locale<F: Future>(locale: String, future: F) -> F::Output {
+    REQUEST_LOCALE.scope(locale, future).await
+}
+
+/// Best supported locale of an `Accept-Language` header, e.g. `zh-CN` for
+/// `fr;q=0.9, zh-TW;q=0.8, en;q=0.5`.
+///
+/// Languages are tried by decreasing quality; `*` and unparsable entries
+/// are skipped.
+pub fn negotiate_locale(accept_language: &str) -> Option<&'static str> {
+    let mut ranges: Vec<(&str, f32)> = accept_language
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';');
+            let tag = parts.next()?.trim();
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+            (!tag.is_empty() && tag != "*" && quality > 0.0).then_some((tag, quality))
+        })
+        .collect();
+    // Stable, so equal qualities keep the client's order
+    ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranges
+        .into_iter()
+        .find_map(|(tag, _)| supported_locale(tag))
+}
+
+/// Map a locale tag to a supported locale, e.g. `en-US` to `en`.
+///
+/// Matching ignores case and falls back to the language subtag.
+pub fn supported_locale(tag: &str) -> Option<&'static str> {
+    let tag = tag.trim();
+    let locales = rust_i18n::available_locales!();
+
+    if let Some(locale) = locales.iter().find(|l| l.eq_ignore_ascii_case(tag)) {
+        return Some(*locale);
+    }
+
+    let language = tag.split(['-', '_']).next().unwrap_or(tag);
+    locales.into_iter().find(|locale| {
+        locale
+            .split('-')
+            .next()
+            .is_some_and(|l| l.eq_ignore_ascii_case(language))
+    })
+}
+
+/// The user's preferred locale if supported, otherwise the locale of the
+/// current request.
+pub fn resolve_locale(preferred: Option<&str>) -> String {
+    preferred
+        .and_then(supported_locale)
+        .map(str::to_string)
+        .unwrap_or_else(current_locale)
+}
+
+/// CLDR plural category of `count` for the cardinal rules of `locale`.
+///
+/// Only the `one` and `other` categories are used by the supported languages.
+pub fn plural_category(locale: &str, count: i64) -> &'static str {
+    let language = locale.split('-').next().unwrap_or(locale);
+    match language {
+        // No grammatical plural
+        "zh" | "ja" | "ko" => "other",
+        // 0 and 1 are singular
+        "fr" | "pt" => {
+            if count == 0 || count == 1 {
+                "one"
+            } else {
+                "other"
+            }
+        }
+        _ => {
+            if count == 1 {
+                "one"
+            } else {
+                "other"
+            }
+        }
+    }
+}
+
+/// Translate a pluralized message.
+///
+/// Looks up `<key>.one` or `<key>.other` and fills `%{count}` with the
+/// formatted number.
+pub fn t_plural(key: &str, locale: &str, count: i64) -> String {
+    let key = format!("{}.{}", key, plural_category(locale, count));
+    t!(
+        key.as_str(),
+        locale = locale,
+        count = format_number(locale, count)
+    )
+    .to_string()
+}
+
+/// Format an integer with the locale's digit grouping.
+pub fn format_number(locale: &str, value: i64) -> String {
+    let separator = t!("format.thousands_separator", locale = locale);
+    let digits = value.unsigned_abs().to_string();
+
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3 + 1);
+    if value < 0 {
+        out.push('-');
+    }
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            out.push_str(&separator);
+        }
+        out.push(digit);
+    }
+    out
+}
+
+/// Format a timestamp using the locale's date-time pattern.
+pub fn format_datetime(locale: &str, value: DateTime<Utc>) -> String {
+    let pattern = t!("format.datetime", locale = locale);
+    value.format(&pattern).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_supported_locale_matches_language() {
+        assert_eq!(supported_locale("en-US"), Some("en"));
+        assert_eq!(supported_locale("zh-cn"), Some("zh-CN"));
+        assert_eq!(supported_locale("zh"), Some("zh-CN"));
+        assert_eq!(supported_locale("xx"), None);
+    }
+
+    #[test]
+    fn test_negotiate_locale() {
+        assert_eq!(negotiate_locale("zh-CN,zh;q=0.9,en;q=0.8"), Some("zh-CN"));
+        assert_eq!(negotiate_locale("fr, en-GB;q=0.5, zh;q=0.7"), Some("zh-CN"));
+        assert_eq!(negotiate_locale("zh;q=0, en"), Some("en"));
+        assert_eq!(negotiate_locale("*"), None);
+        assert_eq!(negotiate_locale(""), None);
+    }
+
+    #[tokio::test]
+    async fn test_with_locale_scopes_translations() {
+        let message = with_locale("zh-CN".to_string(), async {
+            t!("auth.unsupported_locale", tag = "xx").to_string()
+        })
+        .await;
+        assert_eq!(message, "不支持的语言：xx");
+        assert_eq!(current_locale(), rust_i18n::locale().to_string());
+    }
+
+    #[test]
+    fn test_plural_category() {
+        assert_eq!(plural_category("en", 1), "one");
+        assert_eq!(plural_category("en", 0), "other");
+        assert_eq!(plural_category("en", 2), "other");
+        assert_eq!(plural_category("zh-CN", 1), "other");
+    }
+
+    #[test]
+    fn test_t_plural_uses_category_and_grouping() {
+        assert_eq!(
+            t_plural("scan_report.chapters_added", "en", 1),
+            "1 chapter added"
+        );
+        assert_eq!(
+            t_plural("scan_report.chapters_added", "en", 1200),
+            "1,200 chapters added"
+        );
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number("en", 0), "0");
+        assert_eq!(format_number("en", 999), "999");
+        assert_eq!(format_number("en", 1234567), "1,234,567");
+        assert_eq!(format_number("en", -1000), "-1,000");
+    }
+
+    #[test]
+    fn test_format_datetime() {
+        let value = Utc.with_ymd_and_hms(2026, 3, 5, 14, 7, 0).unwrap();
+        assert_eq!(format_datetime("en", value), "Mar 5, 2026 14:07 UTC");
+        assert_eq!(format_datetime("zh-CN", value), "2026年3月5日 14:07 UTC");
+    }
+}
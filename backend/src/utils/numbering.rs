@@ -0,0 +1,170 @@
+//! Volume and chapter numbers parsed from chapter file names.
+//!
+//! Names such as `Vol.02 Ch.013.5`, `第3卷 第12话` or `Title - 045` carry
+//! the numbering readers and clients like Komga sort by. Bracketed release
+//! tags are ignored, an explicit marker (`Ch.`, `Chapter`, `第…话`) wins,
+//! and otherwise the last number left after the volume is the chapter
+//! number.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+/// Release tags such as `[Group]`, `(2019)` or `{v2}`.
+static BRACKETS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[[^\]]*\]|\([^)]*\)|\{[^}]*\}|【[^】]*】").unwrap());
+
+static VOLUME: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)(?:\b(?:vol(?:ume)?\.?|v|tome|t)\s*(\d+)\b|第\s*([0-9０-９零〇一二两三四五六七八九十百千]+)\s*[巻卷册冊部])",
+    )
+    .unwrap()
+});
+
+static CHAPTER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)(?:\b(?:ch(?:apter)?\.?|c|ep(?:isode)?\.?)\s*(\d+(?:\.\d+)?)\b|#\s*(\d+(?:\.\d+)?)\b|第\s*([0-9０-９.零〇一二两三四五六七八九十百千]+)\s*[话話回章])",
+    )
+    .unwrap()
+});
+
+static NUMBER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|[^\p{L}\d.])(\d+(?:\.\d+)?)(?:$|[^\p{L}\d])").unwrap());
+
+/// Volume and chapter number of a chapter, either of which may be unknown.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ChapterNumbering {
+    pub volume: Option<i32>,
+    pub number: Option<f32>,
+}
+
+impl ChapterNumbering {
+    /// Parse the numbering from a chapter name (a file name without its
+    /// extension, or a folder name).
+    pub fn parse(name: &str) -> Self {
+        let name = BRACKETS.replace_all(name, " ").replace('_', " ");
+
+        let volume_match = VOLUME.captures(&name);
+        let volume = volume_match.as_ref().and_then(|caps| {
+            caps.get(1)
+                .or_else(|| caps.get(2))
+                .and_then(|m| parse_number(m.as_str()))
+                .map(|v| v as i32)
+        });
+
+        let number = CHAPTER
+            .captures(&name)
+            .and_then(|caps| {
+                caps.get(1)
+                    .or_else(|| caps.get(2))
+                    .or_else(|| caps.get(3))
+                    .and_then(|m| parse_number(m.as_str()))
+            })
+            .or_else(|| {
+                // Without a marker, the last number after removing the volume
+                let rest = match &volume_match {
+                    Some(caps) => name.replace(caps.get(0).unwrap().as_str(), " "),
+                    None => name.clone(),
+                };
+                NUMBER
+                    .captures_iter(&rest)
+                    .last()
+                    .and_then(|caps| parse_number(&caps[1]))
+            });
+
+        Self { volume, number }
+    }
+}
+
+/// Parse Arabic, full-width or Chinese numerals.
+fn parse_number(text: &str) -> Option<f32> {
+    let ascii: String = text
+        .chars()
+        .map(|c| match c {
+            '０'..='９' => char::from_digit(c as u32 - '０' as u32, 10).unwrap(),
+            c => c,
+        })
+        .collect();
+    if let Ok(number) = ascii.parse::<f32>() {
+        return number.is_finite().then_some(number);
+    }
+    parse_chinese_number(&ascii).map(|n| n as f32)
+}
+
+/// Parse a Chinese numeral below ten thousand, e.g. `十二` or `一百零五`.
+fn parse_chinese_number(text: &str) -> Option<u32> {
+    let mut total = 0;
+    let mut digit = None;
+    for c in text.chars() {
+        let value = match c {
+            '零' | '〇' => 0,
+            '一' => 1,
+            '二' | '两' => 2,
+            '三' => 3,
+            '四' => 4,
+            '五' => 5,
+            '六' => 6,
+            '七' => 7,
+            '八' => 8,
+            '九' => 9,
+            '十' | '百' | '千' => {
+                let unit = match c {
+                    '十' => 10,
+                    '百' => 100,
+                    _ => 1000,
+                };
+                // `十二` means twelve, with the leading one left out
+                total += digit.take().unwrap_or(1) * unit;
+                continue;
+            }
+            _ => return None,
+        };
+        digit = Some(value);
+    }
+    let total = total + digit.unwrap_or(0);
+    (!text.is_empty()).then_some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(name: &str) -> (Option<i32>, Option<f32>) {
+        let numbering = ChapterNumbering::parse(name);
+        (numbering.volume, numbering.number)
+    }
+
+    #[test]
+    fn test_explicit_markers() {
+        assert_eq!(parse("Vol.02 Ch.013.5"), (Some(2), Some(13.5)));
+        assert_eq!(parse("Title v03 c021"), (Some(3), Some(21.0)));
+        assert_eq!(parse("Chapter 7 - The Return"), (None, Some(7.0)));
+        assert_eq!(parse("Title Episode 12"), (None, Some(12.0)));
+        assert_eq!(parse("Title #4"), (None, Some(4.0)));
+    }
+
+    #[test]
+    fn test_chinese_markers() {
+        assert_eq!(parse("第3卷 第12话"), (Some(3), Some(12.0)));
+        assert_eq!(parse("第十二话 归来"), (None, Some(12.0)));
+        assert_eq!(parse("第一百零五回"), (None, Some(105.0)));
+        assert_eq!(parse("第２巻"), (Some(2), None));
+    }
+
+    #[test]
+    fn test_bare_numbers() {
+        assert_eq!(parse("[Group] Title - 045 (2019)"), (None, Some(45.0)));
+        assert_eq!(parse("Vol.1 - 003"), (Some(1), Some(3.0)));
+        assert_eq!(parse("Title_12.5"), (None, Some(12.5)));
+        assert_eq!(parse("Volume 5"), (Some(5), None));
+        assert_eq!(parse("Prologue"), (None, None));
+    }
+
+    #[test]
+    fn test_chinese_numerals() {
+        assert_eq!(parse_chinese_number("十"), Some(10));
+        assert_eq!(parse_chinese_number("二十三"), Some(23));
+        assert_eq!(parse_chinese_number("三千零二"), Some(3002));
+        assert_eq!(parse_chinese_number("abc"), None);
+    }
+}
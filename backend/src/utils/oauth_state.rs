@@ -0,0 +1,157 @@
+//! Signed `state` parameters for OAuth redirect flows.
+//!
+//! The state sent to a provider is a short-lived JWT carrying what the
+//! callback needs. It is also stored in an HttpOnly cookie, and a callback
+//! is only accepted when its state matches the cookie, binding the flow to
+//! the browser that started it. OIDC login and tracker linking share this.
+
+use axum::http::{HeaderMap, header};
+use chrono::{Duration, Utc};
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::error::{AppError, Result};
+
+/// How long a flow may take at the provider, in minutes.
+const STATE_EXPIRATION_MINUTES: i64 = 10;
+
+/// Claims of a state token: the flow's own claims, its purpose and expiry.
+#[derive(Serialize, Deserialize)]
+struct StateToken<C> {
+    #[serde(flatten)]
+    claims: C,
+    purpose: String,
+    exp: i64,
+    iat: i64,
+}
+
+/// Signs and verifies the state tokens of one flow.
+pub struct StateSigner {
+    purpose: &'static str,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+}
+
+impl StateSigner {
+    /// Create a signer for tokens marked with `purpose`, signed with `secret`.
+    pub fn new(purpose: &'static str, secret: &str) -> Self {
+        Self {
+            purpose,
+            encoding_key: EncodingKey::from_secret(secret.as_bytes()),
+            decoding_key: DecodingKey::from_secret(secret.as_bytes()),
+        }
+    }
+
+    /// Sign claims into a state token.
+    pub fn sign<C: Serialize>(&self, claims: C) -> Result<String> {
+        let now = Utc::now();
+        let token = StateToken {
+            claims,
+            purpose: self.purpose.to_string(),
+            exp: (now + Duration::minutes(STATE_EXPIRATION_MINUTES)).timestamp(),
+            iat: now.timestamp(),
+        };
+        encode(&Header::default(), &token, &self.encoding_key)
+            .map_err(|e| AppError::Internal(format!("Token generation failed: {}", e)))
+    }
+
+    /// Check the state of a callback against the cookie and return its claims.
+    ///
+    /// Returns `None` when the state does not match the cookie, or is
+    /// expired, forged or issued for another purpose.
+    pub fn verify<C: DeserializeOwned>(&self, state: &str, cookie: Option<&str>) -> Option<C> {
+        if cookie != Some(state) {
+            return None;
+        }
+        decode::<StateToken<C>>(state, &self.decoding_key, &Validation::default())
+            .ok()
+            .map(|data| data.claims)
+            .filter(|token| token.purpose == self.purpose)
+            .map(|token| token.claims)
+    }
+}
+
+/// Build the `Set-Cookie` value storing a state token for the callbacks
+/// under `path`, or removing it when `value` is empty.
+pub fn state_cookie(name: &str, path: &str, value: &str, secure: bool) -> String {
+    let max_age = if value.is_empty() {
+        0
+    } else {
+        STATE_EXPIRATION_MINUTES * 60
+    };
+    // SameSite=Lax still sends the cookie on the provider's top-level redirect
+    let secure = if secure { "; Secure" } else { "" };
+    format!(
+        "{}={}; Path={}; Max-Age={}; HttpOnly; SameSite=Lax{}",
+        name, value, path, max_age, secure
+    )
+}
+
+/// Read a cookie value from the request headers.
+pub fn read_cookie(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Claims {
+        user_id: i64,
+    }
+
+    #[test]
+    fn test_state_round_trip() {
+        let signer = StateSigner::new("test_state", "secret");
+        let state = signer.sign(Claims { user_id: 7 }).unwrap();
+
+        assert_eq!(
+            signer.verify::<Claims>(&state, Some(&state)),
+            Some(Claims { user_id: 7 })
+        );
+        assert_eq!(signer.verify::<Claims>(&state, Some("other")), None);
+        assert_eq!(signer.verify::<Claims>(&state, None), None);
+
+        // Tokens of other flows or signed with other secrets are rejected
+        let other = StateSigner::new("other_state", "secret");
+        assert_eq!(other.verify::<Claims>(&state, Some(&state)), None);
+        let forged = StateSigner::new("test_state", "forged");
+        assert_eq!(forged.verify::<Claims>(&state, Some(&state)), None);
+    }
+
+    #[test]
+    fn test_state_cookie() {
+        assert_eq!(
+            state_cookie("ryuri_state", "/api/callback", "abc", true),
+            "ryuri_state=abc; Path=/api/callback; Max-Age=600; HttpOnly; SameSite=Lax; Secure"
+        );
+        assert_eq!(
+            state_cookie("ryuri_state", "/api/callback", "", false),
+            "ryuri_state=; Path=/api/callback; Max-Age=0; HttpOnly; SameSite=Lax"
+        );
+    }
+
+    #[test]
+    fn test_read_cookie_finds_value_among_others() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::COOKIE,
+            HeaderValue::from_static("theme=dark; ryuri_oidc_state=abc.def; lang=en"),
+        );
+
+        assert_eq!(
+            read_cookie(&headers, "ryuri_oidc_state").as_deref(),
+            Some("abc.def")
+        );
+        assert_eq!(read_cookie(&headers, "missing"), None);
+    }
+}
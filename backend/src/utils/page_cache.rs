@@ -0,0 +1,118 @@
+//! In-memory cache of processed page images.
+//!
+//! Splitting, slicing or trimming a page decodes the whole image, which is
+//! slow for large pages. The results are kept here, bounded by their total
+//! size in bytes, and the least recently used ones are dropped first.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// Default budget of the processed page cache, in bytes.
+pub const DEFAULT_PAGE_CACHE_BYTES: usize = 64 * 1024 * 1024;
+
+/// An encoded page image in the cache.
+#[derive(Debug, Clone)]
+pub struct CachedPage {
+    pub data: Arc<Vec<u8>>,
+    pub media_type: &'static str,
+}
+
+/// Least recently used cache of encoded images, bounded by total size.
+pub struct PageCache<K> {
+    capacity: usize,
+    inner: Mutex<Inner<K>>,
+}
+
+struct Inner<K> {
+    /// Least recently used first.
+    entries: VecDeque<(K, CachedPage)>,
+    bytes: usize,
+}
+
+impl<K: PartialEq> PageCache<K> {
+    /// Create a cache holding up to `capacity` bytes of images.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(Inner {
+                entries: VecDeque::new(),
+                bytes: 0,
+            }),
+        }
+    }
+
+    /// Look up an image, marking it as recently used.
+    pub fn get(&self, key: &K) -> Option<CachedPage> {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let position = inner.entries.iter().position(|(k, _)| k == key)?;
+        let entry = inner.entries.remove(position)?;
+        let page = entry.1.clone();
+        inner.entries.push_back(entry);
+        Some(page)
+    }
+
+    /// Store an image, evicting the least recently used ones beyond the
+    /// budget. Images larger than the whole budget are not stored.
+    pub fn insert(&self, key: K, page: CachedPage) {
+        let size = page.data.len();
+        if size > self.capacity {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(position) = inner.entries.iter().position(|(k, _)| *k == key)
+            && let Some((_, old)) = inner.entries.remove(position)
+        {
+            inner.bytes -= old.data.len();
+        }
+        inner.entries.push_back((key, page));
+        inner.bytes += size;
+
+        while inner.bytes > self.capacity {
+            let Some((_, evicted)) = inner.entries.pop_front() else {
+                break;
+            };
+            inner.bytes -= evicted.data.len();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(size: usize) -> CachedPage {
+        CachedPage {
+            data: Arc::new(vec![0; size]),
+            media_type: "image/png",
+        }
+    }
+
+    #[test]
+    fn test_least_recently_used_is_evicted() {
+        let cache = PageCache::new(30);
+        cache.insert(1, page(10));
+        cache.insert(2, page(10));
+        cache.insert(3, page(10));
+        assert!(cache.get(&1).is_some());
+
+        cache.insert(4, page(10));
+        assert!(cache.get(&2).is_none());
+        assert!(cache.get(&1).is_some());
+        assert!(cache.get(&3).is_some());
+        assert!(cache.get(&4).is_some());
+    }
+
+    #[test]
+    fn test_replacing_and_oversized_entries() {
+        let cache = PageCache::new(20);
+        cache.insert(1, page(15));
+        cache.insert(1, page(5));
+        cache.insert(2, page(15));
+        assert_eq!(cache.get(&1).unwrap().data.len(), 5);
+
+        cache.insert(3, page(21));
+        assert!(cache.get(&3).is_none());
+        assert!(cache.get(&2).is_some());
+    }
+}
@@ -0,0 +1,337 @@
+//! Geometry and cropping of page images.
+//!
+//! Pages are served as stored where possible. The helpers here read page
+//! sizes from image headers and cut pages into parts for clients that
+//! cannot handle them whole, re-encoding only the parts they return.
+
+use std::io::Cursor;
+
+use image::{DynamicImage, GrayImage, ImageFormat, ImageReader};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, Result};
+use crate::t;
+
+/// A page at least this much wider than tall is a double-page spread.
+/// Single pages are taller than wide, so anything clearly landscape is two
+/// pages scanned together.
+pub const SPREAD_ASPECT_RATIO: f32 = 1.2;
+
+/// Pages taller than this are sliced into tiles for clients that ask for
+/// it. Many mobile GPUs cannot hold a texture taller than 4096 pixels, and
+/// webtoon chapters are sometimes a single strip many times that.
+pub const TALL_PAGE_HEIGHT: u32 = 4096;
+
+/// Tallest tile a tall page is sliced into. Tiles of one page share the
+/// same height, except for a shorter last one.
+pub const MAX_TILE_HEIGHT: u32 = 2048;
+
+/// Largest difference in brightness from pure white or black that still
+/// counts as margin, to allow for paper tone and scanner noise.
+const MARGIN_TOLERANCE: u8 = 24;
+
+/// Share of pixels in a margin row or column that may differ from the
+/// margin colour, so dust and specks do not stop the trim.
+const MARGIN_SPECKS_PER_MILLE: usize = 5;
+
+/// JPEG quality of cropped pages cut from JPEG sources.
+const CROP_JPEG_QUALITY: u8 = 90;
+
+/// One half of a double-page spread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpreadHalf {
+    Left,
+    Right,
+}
+
+/// Part of a page served in place of the whole page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PagePart {
+    /// One half of a double-page spread.
+    Half(SpreadHalf),
+    /// One tile of a tall page, counted from the top.
+    Tile(u32),
+}
+
+/// Width and height of an image, read from its header without decoding
+/// the pixels. `None` if the format is not recognized.
+pub fn dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()
+}
+
+/// Whether a page of this size is a double-page spread.
+pub fn is_spread(width: u32, height: u32) -> bool {
+    height > 0 && width as f32 >= height as f32 * SPREAD_ASPECT_RATIO
+}
+
+/// Number of tiles a page of this height is sliced into, 1 for pages that
+/// are not tall.
+pub fn tile_count(height: u32) -> u32 {
+    if height > TALL_PAGE_HEIGHT {
+        height.div_ceil(MAX_TILE_HEIGHT)
+    } else {
+        1
+    }
+}
+
+/// Top edge and height of a tile of a page of this height. `None` if the
+/// page has fewer tiles.
+pub fn tile_bounds(height: u32, tile: u32) -> Option<(u32, u32)> {
+    let count = tile_count(height);
+    if tile >= count {
+        return None;
+    }
+    let tile_height = height.div_ceil(count);
+    let top = tile * tile_height;
+    Some((top, tile_height.min(height - top)))
+}
+
+/// Slice a tall page into tiles, top to bottom.
+///
+/// The page is decoded once and every tile is returned, encoded like
+/// [`split_spread`] does.
+pub fn slice_tiles(data: &[u8]) -> Result<Vec<(Vec<u8>, &'static str)>> {
+    let (image, format) = decode(data)?;
+    (0..tile_count(image.height()))
+        .filter_map(|tile| tile_bounds(image.height(), tile))
+        .map(|(top, height)| encode(&image.crop_imm(0, top, image.width(), height), format))
+        .collect()
+}
+
+/// Cut one half out of a spread.
+///
+/// Returns the encoded half and its MIME type. JPEG pages stay JPEG, all
+/// other formats are returned as PNG.
+pub fn split_spread(data: &[u8], half: SpreadHalf) -> Result<(Vec<u8>, &'static str)> {
+    let (image, format) = decode(data)?;
+    encode(&crop_half(&image, half), format)
+}
+
+/// Trim uniform white or black margins off a page, or off one half of a
+/// spread.
+///
+/// Returns `None` for a whole page without margins, or one that is blank,
+/// so it can be served as stored.
+pub fn trim_margins(
+    data: &[u8],
+    half: Option<SpreadHalf>,
+) -> Result<Option<(Vec<u8>, &'static str)>> {
+    let (mut image, format) = decode(data)?;
+    if let Some(half) = half {
+        image = crop_half(&image, half);
+    }
+
+    match content_bounds(&image.to_luma8()) {
+        Some((x, y, width, height)) if (width, height) != (image.width(), image.height()) => {
+            encode(&image.crop_imm(x, y, width, height), format).map(Some)
+        }
+        _ if half.is_some() => encode(&image, format).map(Some),
+        _ => Ok(None),
+    }
+}
+
+fn crop_half(image: &DynamicImage, half: SpreadHalf) -> DynamicImage {
+    let left = image.width() / 2;
+    match half {
+        SpreadHalf::Left => image.crop_imm(0, 0, left, image.height()),
+        SpreadHalf::Right => image.crop_imm(left, 0, image.width() - left, image.height()),
+    }
+}
+
+/// Left edge, top edge, width and height of the page inside its margins.
+/// `None` if the whole page is margin.
+fn content_bounds(image: &GrayImage) -> Option<(u32, u32, u32, u32)> {
+    let (width, height) = image.dimensions();
+    let row = |y: u32| is_margin((0..width).map(|x| image.get_pixel(x, y).0[0]));
+
+    let top = (0..height).find(|&y| !row(y))?;
+    let bottom = (top..height).rev().find(|&y| !row(y))?;
+    let column = |x: u32| is_margin((top..=bottom).map(|y| image.get_pixel(x, y).0[0]));
+    let left = (0..width).find(|&x| !column(x))?;
+    let right = (left..width).rev().find(|&x| !column(x))?;
+
+    Some((left, top, right - left + 1, bottom - top + 1))
+}
+
+/// Whether a row or column of pixels is all white or all black, apart from
+/// a few specks.
+fn is_margin(pixels: impl Iterator<Item = u8>) -> bool {
+    let (mut total, mut white, mut black) = (0, 0, 0);
+    for pixel in pixels {
+        total += 1;
+        if pixel >= u8::MAX - MARGIN_TOLERANCE {
+            white += 1;
+        } else if pixel <= MARGIN_TOLERANCE {
+            black += 1;
+        }
+    }
+    let specks = total * MARGIN_SPECKS_PER_MILLE / 1000;
+    total - white <= specks || total - black <= specks
+}
+
+/// Decode an image, keeping its format.
+fn decode(data: &[u8]) -> Result<(DynamicImage, Option<ImageFormat>)> {
+    let reader = ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|e| {
+            AppError::Internal(t!("scan.read_image_format_failed", error = e).to_string())
+        })?;
+    let format = reader.format();
+    let image = reader
+        .decode()
+        .map_err(|e| AppError::Internal(t!("scan.decode_image_failed", error = e).to_string()))?;
+    Ok((image, format))
+}
+
+/// Encode part of a page like its source: JPEG as JPEG, anything else as
+/// lossless PNG.
+fn encode(image: &DynamicImage, source: Option<ImageFormat>) -> Result<(Vec<u8>, &'static str)> {
+    use image::codecs::jpeg::JpegEncoder;
+
+    let mut buffer = Vec::new();
+    let (encoded, media_type) = if source == Some(ImageFormat::Jpeg) {
+        let encoded = DynamicImage::ImageRgb8(image.to_rgb8()).write_with_encoder(
+            JpegEncoder::new_with_quality(&mut buffer, CROP_JPEG_QUALITY),
+        );
+        (encoded, "image/jpeg")
+    } else {
+        let encoded = image.write_to(&mut Cursor::new(&mut buffer), ImageFormat::Png);
+        (encoded, "image/png")
+    };
+    encoded
+        .map_err(|e| AppError::Internal(t!("content.encode_page_failed", error = e).to_string()))?;
+
+    Ok((buffer, media_type))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    fn image(width: u32, height: u32, format: ImageFormat) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        DynamicImage::new_rgb8(width, height)
+            .write_to(&mut Cursor::new(&mut buffer), format)
+            .unwrap();
+        buffer
+    }
+
+    #[test]
+    fn test_dimensions_from_header() {
+        assert_eq!(dimensions(&image(30, 20, ImageFormat::Png)), Some((30, 20)));
+        assert_eq!(dimensions(b"not an image"), None);
+    }
+
+    #[test]
+    fn test_is_spread() {
+        assert!(is_spread(2000, 1400));
+        assert!(!is_spread(1000, 1400));
+        assert!(!is_spread(1100, 1000));
+        assert!(!is_spread(100, 0));
+    }
+
+    #[test]
+    fn test_split_spread_halves() {
+        let spread = image(101, 50, ImageFormat::Png);
+
+        let (left, media_type) = split_spread(&spread, SpreadHalf::Left).unwrap();
+        assert_eq!(media_type, "image/png");
+        assert_eq!(dimensions(&left), Some((50, 50)));
+
+        let (right, _) = split_spread(&spread, SpreadHalf::Right).unwrap();
+        assert_eq!(dimensions(&right), Some((51, 50)));
+    }
+
+    #[test]
+    fn test_tile_bounds() {
+        assert_eq!(tile_count(TALL_PAGE_HEIGHT), 1);
+        assert_eq!(tile_bounds(3000, 0), Some((0, 3000)));
+        assert_eq!(tile_bounds(3000, 1), None);
+
+        assert_eq!(tile_count(20000), 10);
+        assert_eq!(tile_bounds(20000, 0), Some((0, 2000)));
+        assert_eq!(tile_bounds(20000, 9), Some((18000, 2000)));
+
+        assert_eq!(tile_count(5000), 3);
+        assert_eq!(tile_bounds(5000, 1), Some((1667, 1667)));
+        assert_eq!(tile_bounds(5000, 2), Some((3334, 1666)));
+    }
+
+    #[test]
+    fn test_slice_tiles() {
+        let strip = image(10, 5000, ImageFormat::Png);
+        let tiles = slice_tiles(&strip).unwrap();
+        let sizes: Vec<_> = tiles.iter().map(|(data, _)| dimensions(data)).collect();
+        assert_eq!(
+            sizes,
+            vec![Some((10, 1667)), Some((10, 1667)), Some((10, 1666))]
+        );
+    }
+
+    fn framed(width: u32, height: u32, margin: u32, background: u8) -> Vec<u8> {
+        let page = RgbImage::from_fn(width, height, |x, y| {
+            let inside =
+                (margin..width - margin).contains(&x) && (margin..height - margin).contains(&y);
+            if inside && (x + y) % 2 == 0 {
+                Rgb([0, 0, 0])
+            } else if inside {
+                Rgb([255, 255, 255])
+            } else {
+                Rgb([background; 3])
+            }
+        });
+        let mut buffer = Vec::new();
+        DynamicImage::ImageRgb8(page)
+            .write_to(&mut Cursor::new(&mut buffer), ImageFormat::Png)
+            .unwrap();
+        buffer
+    }
+
+    #[test]
+    fn test_trim_white_and_black_margins() {
+        let (trimmed, media_type) = trim_margins(&framed(60, 80, 10, 250), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(media_type, "image/png");
+        assert_eq!(dimensions(&trimmed), Some((40, 60)));
+
+        let (trimmed, _) = trim_margins(&framed(60, 80, 5, 3), None).unwrap().unwrap();
+        assert_eq!(dimensions(&trimmed), Some((50, 70)));
+    }
+
+    #[test]
+    fn test_trim_leaves_pages_without_margins() {
+        assert!(
+            trim_margins(&framed(60, 80, 0, 255), None)
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            trim_margins(&image(60, 80, ImageFormat::Png), None)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_trim_half_of_spread() {
+        let (half, _) = trim_margins(&framed(120, 80, 10, 255), Some(SpreadHalf::Left))
+            .unwrap()
+            .unwrap();
+        assert_eq!(dimensions(&half), Some((50, 60)));
+    }
+
+    #[test]
+    fn test_split_keeps_jpeg() {
+        let spread = image(80, 40, ImageFormat::Jpeg);
+        let (half, media_type) = split_spread(&spread, SpreadHalf::Left).unwrap();
+        assert_eq!(media_type, "image/jpeg");
+        assert_eq!(dimensions(&half), Some((40, 40)));
+    }
+}
@@ -0,0 +1,17 @@
+//! Helpers for partial updates.
+
+use serde::{Deserialize, Deserializer};
+
+/// Deserialize a field of a PATCH body so that a missing field, `null` and
+/// a value can be told apart.
+///
+/// Use with `#[serde(default, deserialize_with = "...")]` on an
+/// `Option<Option<T>>` field: a missing field is `None` (leave unchanged),
+/// `null` is `Some(None)` (clear) and a value is `Some(Some(value))`.
+pub fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
@@ -0,0 +1,224 @@
+//! Placeholder images for pages that cannot be served.
+//!
+//! Rendered with a small built-in 5x7 bitmap font so no font files are
+//! needed. Characters outside the font are dropped.
+
+use std::io::Cursor;
+
+use image::{ImageFormat, Rgb, RgbImage};
+
+use crate::error::{AppError, Result};
+
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 1200;
+const MARGIN: u32 = 60;
+const GLYPH_WIDTH: u32 = 5;
+const GLYPH_HEIGHT: u32 = 7;
+const MAX_MESSAGE_LINES: usize = 12;
+
+const BACKGROUND: Rgb<u8> = Rgb([236, 236, 236]);
+const BORDER: Rgb<u8> = Rgb([200, 60, 60]);
+const TITLE: Rgb<u8> = Rgb([60, 60, 60]);
+const TEXT: Rgb<u8> = Rgb([110, 110, 110]);
+
+/// Render a PNG placeholder showing the page number and an error message.
+///
+/// `page_number` is 1-based, as shown to readers.
+pub fn render_page_placeholder(page_number: i64, message: &str) -> Result<Vec<u8>> {
+    let mut img = RgbImage::from_pixel(WIDTH, HEIGHT, BACKGROUND);
+
+    // Frame
+    fill_rect(&mut img, 0, 0, WIDTH, 12, BORDER);
+    fill_rect(&mut img, 0, HEIGHT - 12, WIDTH, 12, BORDER);
+    fill_rect(&mut img, 0, 0, 12, HEIGHT, BORDER);
+    fill_rect(&mut img, WIDTH - 12, 0, 12, HEIGHT, BORDER);
+
+    let mut y = HEIGHT / 3;
+    draw_centered(&mut img, &format!("PAGE {}", page_number), y, 10, TITLE);
+    y += GLYPH_HEIGHT * 10 + 40;
+    draw_centered(&mut img, "COULD NOT BE LOADED", y, 5, TITLE);
+    y += GLYPH_HEIGHT * 5 + 60;
+
+    let scale = 3;
+    let per_line = ((WIDTH - 2 * MARGIN) / ((GLYPH_WIDTH + 1) * scale)) as usize;
+    for line in wrap(&sanitize(message), per_line)
+        .into_iter()
+        .take(MAX_MESSAGE_LINES)
+    {
+        draw_centered(&mut img, &line, y, scale, TEXT);
+        y += (GLYPH_HEIGHT + 4) * scale;
+    }
+
+    let mut buffer = Vec::new();
+    img.write_to(&mut Cursor::new(&mut buffer), ImageFormat::Png)
+        .map_err(|e| AppError::Internal(format!("Failed to encode placeholder: {}", e)))?;
+
+    Ok(buffer)
+}
+
+/// Upper-case the message and replace characters the font cannot draw.
+fn sanitize(message: &str) -> String {
+    let mut out = String::with_capacity(message.len());
+    for c in message.chars() {
+        let c = c.to_ascii_uppercase();
+        let c = if glyph(c).is_some() { c } else { ' ' };
+        if c == ' ' && (out.is_empty() || out.ends_with(' ')) {
+            continue;
+        }
+        out.push(c);
+    }
+    out.trim_end().to_string()
+}
+
+/// Greedy word wrap; words longer than a line are split.
+fn wrap(text: &str, per_line: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+
+    for word in text.split(' ') {
+        let mut word = word.to_string();
+        while word.len() > per_line {
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+            let rest = word.split_off(per_line);
+            lines.push(word);
+            word = rest;
+        }
+
+        if !current.is_empty() && current.len() + 1 + word.len() > per_line {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(&word);
+    }
+
+    if !current.is_empty() {
+        lines.push(current);
+    }
+
+    lines
+}
+
+fn draw_centered(img: &mut RgbImage, text: &str, y: u32, scale: u32, color: Rgb<u8>) {
+    let advance = (GLYPH_WIDTH + 1) * scale;
+    let text_width = (text.chars().count() as u32 * advance).saturating_sub(scale);
+    let mut x = WIDTH.saturating_sub(text_width) / 2;
+
+    for c in text.chars() {
+        if let Some(rows) = glyph(c) {
+            for (row, bits) in rows.iter().enumerate() {
+                for col in 0..GLYPH_WIDTH {
+                    if bits & (1 << (GLYPH_WIDTH - 1 - col)) != 0 {
+                        fill_rect(
+                            img,
+                            x + col * scale,
+                            y + row as u32 * scale,
+                            scale,
+                            scale,
+                            color,
+                        );
+                    }
+                }
+            }
+        }
+        x += advance;
+    }
+}
+
+fn fill_rect(img: &mut RgbImage, x: u32, y: u32, w: u32, h: u32, color: Rgb<u8>) {
+    for py in y..(y + h).min(img.height()) {
+        for px in x..(x + w).min(img.width()) {
+            img.put_pixel(px, py, color);
+        }
+    }
+}
+
+/// 5x7 glyph rows, most significant of the low 5 bits is the leftmost pixel.
+fn glyph(c: char) -> Option<[u8; 7]> {
+    let rows = match c {
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        'A' => [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        ' ' => [0x00; 7],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        ',' => [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '_' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F],
+        '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+        '(' => [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02],
+        ')' => [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08],
+        '\'' => [0x0C, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00],
+        '"' => [0x0A, 0x0A, 0x00, 0x00, 0x00, 0x00, 0x00],
+        '!' => [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04],
+        '?' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+        '#' => [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A],
+        '%' => [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03],
+        '=' => [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00],
+        '+' => [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00],
+        _ => return None,
+    };
+    Some(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholder_is_valid_png() {
+        let data = render_page_placeholder(3, "Failed to extract: invalid zip").unwrap();
+        let img = image::load_from_memory(&data).unwrap();
+        assert_eq!(img.width(), WIDTH);
+        assert_eq!(img.height(), HEIGHT);
+    }
+
+    #[test]
+    fn test_sanitize_drops_unsupported_characters() {
+        assert_eq!(sanitize("打开失败: bad header"), ": BAD HEADER");
+        assert_eq!(sanitize("  a   b  "), "A B");
+    }
+
+    #[test]
+    fn test_wrap_respects_line_width() {
+        let lines = wrap("AAAA BBBB CCCCCCCCCC", 5);
+        assert_eq!(lines, vec!["AAAA", "BBBB", "CCCCC", "CCCCC"]);
+        assert!(lines.iter().all(|l| l.len() <= 5));
+    }
+}
@@ -0,0 +1,171 @@
+//! Reader of the Protocol Buffers wire format.
+//!
+//! Only what is needed to pick known fields out of a message: each field is
+//! read as its number and raw value, and embedded messages are read with a
+//! new reader over the bytes of the field. Unknown fields are skipped by
+//! ignoring them.
+
+use std::fmt;
+
+/// The input is not a valid message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeError;
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid protobuf message")
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Raw value of a field.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value<'a> {
+    /// `int32`, `int64`, `uint32`, `uint64`, `bool` and enums.
+    Varint(u64),
+    /// `fixed64`, `sfixed64` and `double`.
+    Fixed64(u64),
+    /// Strings, bytes, embedded messages and packed repeated fields.
+    Bytes(&'a [u8]),
+    /// `fixed32`, `sfixed32` and `float`.
+    Fixed32(u32),
+}
+
+impl<'a> Value<'a> {
+    /// The value of an integer field.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Value::Varint(v) | Value::Fixed64(v) => Some(v as i64),
+            Value::Fixed32(v) => Some(v as i32 as i64),
+            Value::Bytes(_) => None,
+        }
+    }
+
+    /// The value of a `bool` field.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Varint(v) => Some(v != 0),
+            _ => None,
+        }
+    }
+
+    /// The value of a `float` field.
+    pub fn as_f32(&self) -> Option<f32> {
+        match *self {
+            Value::Fixed32(v) => Some(f32::from_bits(v)),
+            _ => None,
+        }
+    }
+
+    /// The bytes of a length-delimited field.
+    pub fn as_bytes(&self) -> Option<&'a [u8]> {
+        match *self {
+            Value::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// The value of a `string` field, with invalid UTF-8 replaced.
+    pub fn as_string(&self) -> Option<String> {
+        self.as_bytes()
+            .map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+    }
+}
+
+/// Reads the fields of a message in order.
+pub struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    /// The next field as its number and value, or `None` at the end of the
+    /// message.
+    pub fn next_field(&mut self) -> Result<Option<(u32, Value<'a>)>, DecodeError> {
+        if self.pos >= self.data.len() {
+            return Ok(None);
+        }
+
+        let key = self.varint()?;
+        let number = u32::try_from(key >> 3).map_err(|_| DecodeError)?;
+        if number == 0 {
+            return Err(DecodeError);
+        }
+        let value = match key & 0x7 {
+            0 => Value::Varint(self.varint()?),
+            1 => Value::Fixed64(u64::from_le_bytes(self.take(8)?.try_into().unwrap())),
+            2 => {
+                let len = usize::try_from(self.varint()?).map_err(|_| DecodeError)?;
+                Value::Bytes(self.take(len)?)
+            }
+            5 => Value::Fixed32(u32::from_le_bytes(self.take(4)?.try_into().unwrap())),
+            // Groups are deprecated and not written by current encoders
+            _ => return Err(DecodeError),
+        };
+        Ok(Some((number, value)))
+    }
+
+    fn varint(&mut self) -> Result<u64, DecodeError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self.data.get(self.pos).ok_or(DecodeError)?;
+            self.pos += 1;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(DecodeError)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        let end = self.pos.checked_add(len).ok_or(DecodeError)?;
+        let bytes = self.data.get(self.pos..end).ok_or(DecodeError)?;
+        self.pos = end;
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_fields() {
+        // 1: 150, 2: "hi", 3: 1.5f, 4: { 1: 1 }
+        let data = [
+            0x08, 0x96, 0x01, 0x12, 0x02, b'h', b'i', 0x1d, 0x00, 0x00, 0xc0, 0x3f, 0x22, 0x02,
+            0x08, 0x01,
+        ];
+        let mut reader = Reader::new(&data);
+
+        let (number, value) = reader.next_field().unwrap().unwrap();
+        assert_eq!((number, value.as_i64()), (1, Some(150)));
+        let (number, value) = reader.next_field().unwrap().unwrap();
+        assert_eq!((number, value.as_string().as_deref()), (2, Some("hi")));
+        let (number, value) = reader.next_field().unwrap().unwrap();
+        assert_eq!((number, value.as_f32()), (3, Some(1.5)));
+        let (number, value) = reader.next_field().unwrap().unwrap();
+        assert_eq!(number, 4);
+        let mut embedded = Reader::new(value.as_bytes().unwrap());
+        let (number, value) = embedded.next_field().unwrap().unwrap();
+        assert_eq!((number, value.as_bool()), (1, Some(true)));
+        assert_eq!(embedded.next_field(), Ok(None));
+        assert_eq!(reader.next_field(), Ok(None));
+    }
+
+    #[test]
+    fn test_truncated_message() {
+        assert_eq!(Reader::new(&[0x08]).next_field(), Err(DecodeError));
+        assert_eq!(
+            Reader::new(&[0x12, 0x05, b'a']).next_field(),
+            Err(DecodeError)
+        );
+        assert_eq!(Reader::new(&[0x00, 0x01]).next_field(), Err(DecodeError));
+    }
+}
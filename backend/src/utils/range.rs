@@ -0,0 +1,121 @@
+//! HTTP `Range` header parsing for bodies held in memory.
+//!
+//! Files on disk are sent with `ServeFile`, which handles ranges itself.
+//! Only a single byte range is supported; requests for several ranges are
+//! answered with the whole body, which RFC 9110 allows.
+
+use std::ops::RangeInclusive;
+
+/// The part of a body a `Range` header asks for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ByteRange {
+    /// No usable range: send the whole body.
+    Full,
+    /// Send these bytes with 206 Partial Content.
+    Partial(RangeInclusive<u64>),
+    /// The range starts past the end: 416 Range Not Satisfiable.
+    Unsatisfiable,
+}
+
+impl ByteRange {
+    /// Match a `Range` header value against a body of `len` bytes.
+    ///
+    /// Headers that cannot be parsed are ignored rather than rejected.
+    pub fn parse(header: Option<&str>, len: u64) -> Self {
+        let Some(spec) = header.and_then(|h| h.trim().strip_prefix("bytes=")) else {
+            return Self::Full;
+        };
+        if spec.contains(',') {
+            return Self::Full;
+        }
+        let Some((start, end)) = spec.trim().split_once('-') else {
+            return Self::Full;
+        };
+
+        let (start, end) = (start.trim(), end.trim());
+        if start.is_empty() {
+            // Suffix range: the last `end` bytes
+            return match end.parse::<u64>() {
+                Ok(0) => Self::Unsatisfiable,
+                Ok(_) if len == 0 => Self::Unsatisfiable,
+                Ok(suffix) => Self::Partial(len.saturating_sub(suffix)..=len - 1),
+                Err(_) => Self::Full,
+            };
+        }
+
+        let Ok(start) = start.parse::<u64>() else {
+            return Self::Full;
+        };
+        let end = if end.is_empty() {
+            u64::MAX
+        } else {
+            match end.parse::<u64>() {
+                Ok(end) if end >= start => end,
+                _ => return Self::Full,
+            }
+        };
+
+        if start >= len {
+            Self::Unsatisfiable
+        } else {
+            Self::Partial(start..=end.min(len - 1))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_ranges() {
+        assert_eq!(
+            ByteRange::parse(Some("bytes=0-99"), 1000),
+            ByteRange::Partial(0..=99)
+        );
+        assert_eq!(
+            ByteRange::parse(Some("bytes=900-"), 1000),
+            ByteRange::Partial(900..=999)
+        );
+        assert_eq!(
+            ByteRange::parse(Some("bytes=-100"), 1000),
+            ByteRange::Partial(900..=999)
+        );
+        assert_eq!(
+            ByteRange::parse(Some("bytes=-5000"), 1000),
+            ByteRange::Partial(0..=999)
+        );
+        assert_eq!(
+            ByteRange::parse(Some("bytes=500-5000"), 1000),
+            ByteRange::Partial(500..=999)
+        );
+    }
+
+    #[test]
+    fn test_unsatisfiable_ranges() {
+        assert_eq!(
+            ByteRange::parse(Some("bytes=1000-"), 1000),
+            ByteRange::Unsatisfiable
+        );
+        assert_eq!(
+            ByteRange::parse(Some("bytes=-0"), 1000),
+            ByteRange::Unsatisfiable
+        );
+        assert_eq!(
+            ByteRange::parse(Some("bytes=0-"), 0),
+            ByteRange::Unsatisfiable
+        );
+    }
+
+    #[test]
+    fn test_ignored_ranges() {
+        assert_eq!(ByteRange::parse(None, 1000), ByteRange::Full);
+        assert_eq!(ByteRange::parse(Some("items=0-1"), 1000), ByteRange::Full);
+        assert_eq!(
+            ByteRange::parse(Some("bytes=0-1,5-6"), 1000),
+            ByteRange::Full
+        );
+        assert_eq!(ByteRange::parse(Some("bytes=9-1"), 1000), ByteRange::Full);
+        assert_eq!(ByteRange::parse(Some("bytes=a-b"), 1000), ByteRange::Full);
+    }
+}
@@ -0,0 +1,238 @@
+//! Plain-text index of novel chapters for searching inside them.
+//!
+//! Extracting the text of an EPUB or MOBI file means decompressing and
+//! stripping every section, so the extracted text is kept here along with a
+//! case-folded copy, keyed by the file's path, modification time and size.
+//! The cache is bounded by the total size of the texts, and the least
+//! recently used ones are dropped first.
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::error::Result;
+
+/// Default budget of the text index cache, in bytes.
+pub const DEFAULT_TEXT_INDEX_BYTES: usize = 32 * 1024 * 1024;
+
+/// Text of a chapter, ready for case-insensitive searches.
+#[derive(Debug)]
+pub struct TextIndex {
+    text: String,
+    /// `text` with every character lowercased, one character for each one
+    /// of `text` so character offsets match.
+    folded: String,
+}
+
+/// Occurrence of a search query in a text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextHit {
+    /// Offset of the match, in characters from the start of the text.
+    pub offset: usize,
+    /// The match with up to `context` characters around it, on one line.
+    pub snippet: String,
+}
+
+impl TextIndex {
+    /// Index the text of a chapter.
+    pub fn new(text: String) -> Self {
+        let folded = text.chars().map(fold).collect();
+        Self { text, folded }
+    }
+
+    /// Size of the index in bytes.
+    pub fn size(&self) -> usize {
+        self.text.len() + self.folded.len()
+    }
+
+    /// Find up to `limit` occurrences of `query`, ignoring case, with
+    /// `context` characters of text on each side in the snippets.
+    pub fn find(&self, query: &str, limit: usize, context: usize) -> Vec<TextHit> {
+        let query: String = query.chars().map(fold).collect();
+        if query.is_empty() || limit == 0 {
+            return Vec::new();
+        }
+        let query_chars = query.chars().count();
+
+        // Walk both strings forward once, counting the characters before
+        // each match in the folded text and finding where its snippet
+        // starts in the original
+        let mut hits = Vec::new();
+        let mut folded_byte = 0;
+        let mut char_offset = 0;
+        let mut original_byte = 0;
+        let mut original_char = 0;
+        while let Some(found) = self.folded[folded_byte..].find(&query) {
+            let start = folded_byte + found;
+            char_offset += self.folded[folded_byte..start].chars().count();
+
+            let snippet_start = char_offset.saturating_sub(context);
+            while original_char < snippet_start {
+                if let Some(c) = self.text[original_byte..].chars().next() {
+                    original_byte += c.len_utf8();
+                }
+                original_char += 1;
+            }
+            let snippet: String = self.text[original_byte..]
+                .chars()
+                .take(char_offset - snippet_start + query_chars + context)
+                .map(|c| if c.is_whitespace() { ' ' } else { c })
+                .collect();
+
+            hits.push(TextHit {
+                offset: char_offset,
+                snippet: snippet.trim().to_string(),
+            });
+            if hits.len() >= limit {
+                break;
+            }
+            char_offset += query_chars;
+            folded_byte = start + query.len();
+        }
+
+        hits
+    }
+}
+
+/// Lowercase a character, keeping it a single character.
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Identifies the file a text was extracted from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TextIndexKey {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+/// Least recently used cache of chapter text indexes, bounded by total size.
+pub struct TextIndexCache {
+    capacity: usize,
+    /// Least recently used first.
+    inner: Mutex<Inner>,
+}
+
+struct Inner {
+    entries: VecDeque<(TextIndexKey, Arc<TextIndex>)>,
+    bytes: usize,
+}
+
+impl TextIndexCache {
+    /// Create a cache holding up to `capacity` bytes of text.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(Inner {
+                entries: VecDeque::new(),
+                bytes: 0,
+            }),
+        }
+    }
+
+    /// Get the index of the file at `path`, extracting its text with
+    /// `extract` unless the file is unchanged since it was last indexed.
+    pub fn get_or_index(
+        &self,
+        path: &Path,
+        extract: impl FnOnce(&Path) -> Result<String>,
+    ) -> Result<Arc<TextIndex>> {
+        let metadata = std::fs::metadata(path)?;
+        let key = TextIndexKey {
+            path: path.to_path_buf(),
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+        };
+
+        if let Some(index) = self.get(&key) {
+            return Ok(index);
+        }
+
+        let index = Arc::new(TextIndex::new(extract(path)?));
+        self.insert(key, Arc::clone(&index));
+        Ok(index)
+    }
+
+    fn get(&self, key: &TextIndexKey) -> Option<Arc<TextIndex>> {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let position = inner.entries.iter().position(|(k, _)| k == key)?;
+        let entry = inner.entries.remove(position)?;
+        let index = Arc::clone(&entry.1);
+        inner.entries.push_back(entry);
+        Some(index)
+    }
+
+    fn insert(&self, key: TextIndexKey, index: Arc<TextIndex>) {
+        let size = index.size();
+        if size > self.capacity {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        // An older index of the same file is never looked up again
+        while let Some(position) = inner.entries.iter().position(|(k, _)| k.path == key.path) {
+            if let Some((_, old)) = inner.entries.remove(position) {
+                inner.bytes -= old.size();
+            }
+        }
+        inner.entries.push_back((key, index));
+        inner.bytes += size;
+
+        while inner.bytes > self.capacity {
+            let Some((_, evicted)) = inner.entries.pop_front() else {
+                break;
+            };
+            inner.bytes -= evicted.size();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_ignores_case() {
+        let index = TextIndex::new("Alice met the Rabbit.\nThe rabbit ran.".to_string());
+        let hits = index.find("RABBIT", 10, 4);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].offset, 14);
+        assert_eq!(hits[0].snippet, "the Rabbit. Th");
+        assert_eq!(hits[1].offset, 26);
+
+        assert_eq!(index.find("rabbit", 1, 0).len(), 1);
+        assert!(index.find("", 10, 4).is_empty());
+        assert!(index.find("hatter", 10, 4).is_empty());
+    }
+
+    #[test]
+    fn test_find_counts_characters() {
+        let index = TextIndex::new("少年は森で少女に出会った".to_string());
+        let hits = index.find("少女", 10, 2);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].offset, 5);
+        assert_eq!(hits[0].snippet, "森で少女に出");
+    }
+
+    #[test]
+    fn test_cache_reindexes_changed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chapter.txt");
+        std::fs::write(&path, "one").unwrap();
+
+        let cache = TextIndexCache::new(1024);
+        let read = |p: &Path| -> Result<String> { Ok(std::fs::read_to_string(p)?) };
+        let first = cache.get_or_index(&path, read).unwrap();
+        let again = cache
+            .get_or_index(&path, |_| panic!("not re-extracted"))
+            .unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+
+        std::fs::write(&path, "one two").unwrap();
+        let changed = cache.get_or_index(&path, read).unwrap();
+        assert_eq!(changed.find("two", 1, 0)[0].offset, 4);
+        assert_eq!(cache.inner.lock().unwrap().entries.len(), 1);
+    }
+}
@@ -0,0 +1,368 @@
+//! Thumbnail encoding and format negotiation.
+//!
+//! Every stored thumbnail carries the [`spec`](ThumbnailOptions::spec) of
+//! the settings it was made with, so thumbnails made before the settings
+//! changed can be found and regenerated.
+//!
+//! Thumbnails are stored in one configured format. WebP and AVIF are only
+//! sent to clients that list them in their `Accept` header; other clients
+//! get a JPEG converted on the fly.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{AppError, Result};
+use crate::t;
+
+/// Default largest thumbnail width, in pixels.
+pub const DEFAULT_THUMBNAIL_WIDTH: u32 = 300;
+
+/// Default largest thumbnail height, in pixels.
+pub const DEFAULT_THUMBNAIL_HEIGHT: u32 = 450;
+
+/// Smallest accepted thumbnail width or height.
+pub const MIN_THUMBNAIL_SIZE: u32 = 16;
+
+/// Largest accepted thumbnail width or height.
+pub const MAX_THUMBNAIL_SIZE: u32 = 2000;
+
+/// Default encoding quality for JPEG and AVIF thumbnails.
+pub const DEFAULT_THUMBNAIL_QUALITY: u8 = 80;
+
+/// AVIF encoder speed, 1 (slowest, smallest) to 10 (fastest).
+const AVIF_SPEED: u8 = 8;
+
+/// Image format of stored thumbnails.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThumbnailFormat {
+    #[default]
+    Jpeg,
+    /// Lossless WebP; the quality setting does not apply.
+    Webp,
+    Avif,
+}
+
+impl ThumbnailFormat {
+    /// Name used in configuration and thumbnail specs.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ThumbnailFormat::Jpeg => "jpeg",
+            ThumbnailFormat::Webp => "webp",
+            ThumbnailFormat::Avif => "avif",
+        }
+    }
+
+    /// MIME type for the `Content-Type` header.
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            ThumbnailFormat::Jpeg => "image/jpeg",
+            ThumbnailFormat::Webp => "image/webp",
+            ThumbnailFormat::Avif => "image/avif",
+        }
+    }
+
+    /// Detect the format of encoded thumbnail bytes. Thumbnails stored
+    /// before the format was configurable, or downloaded as they are, may
+    /// be in another format and return None.
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        match image::guess_format(data).ok()? {
+            image::ImageFormat::Jpeg => Some(ThumbnailFormat::Jpeg),
+            image::ImageFormat::WebP => Some(ThumbnailFormat::Webp),
+            image::ImageFormat::Avif => Some(ThumbnailFormat::Avif),
+            _ => None,
+        }
+    }
+
+    /// Whether an `Accept` header lists this format with a non-zero quality.
+    ///
+    /// JPEG is always accepted. Wildcards such as `image/*` do not count for
+    /// WebP and AVIF, since older clients send them without supporting
+    /// either.
+    pub fn is_accepted_by(self, accept: Option<&str>) -> bool {
+        if self == ThumbnailFormat::Jpeg {
+            return true;
+        }
+        let Some(accept) = accept else {
+            return false;
+        };
+
+        accept.split(',').any(|range| {
+            let mut parts = range.split(';');
+            let media_type = parts.next().unwrap_or_default().trim();
+            let rejected = parts.any(|param| {
+                let param = param.trim();
+                param
+                    .strip_prefix("q=")
+                    .and_then(|q| q.trim().parse::<f32>().ok())
+                    .is_some_and(|q| q <= 0.0)
+            });
+            media_type.eq_ignore_ascii_case(self.mime_type()) && !rejected
+        })
+    }
+}
+
+impl fmt::Display for ThumbnailFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ThumbnailFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "jpeg" | "jpg" => Ok(ThumbnailFormat::Jpeg),
+            "webp" => Ok(ThumbnailFormat::Webp),
+            "avif" => Ok(ThumbnailFormat::Avif),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Settings used to resize and encode thumbnails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThumbnailOptions {
+    /// Largest width; the aspect ratio is kept.
+    pub width: u32,
+    /// Largest height; the aspect ratio is kept.
+    pub height: u32,
+    /// Format of stored thumbnails.
+    pub format: ThumbnailFormat,
+    /// Encoding quality from 1 to 100, for JPEG and AVIF.
+    pub quality: u8,
+}
+
+impl Default for ThumbnailOptions {
+    fn default() -> Self {
+        Self {
+            width: DEFAULT_THUMBNAIL_WIDTH,
+            height: DEFAULT_THUMBNAIL_HEIGHT,
+            format: ThumbnailFormat::default(),
+            quality: DEFAULT_THUMBNAIL_QUALITY,
+        }
+    }
+}
+
+impl ThumbnailOptions {
+    /// Short description of these settings, stored with each thumbnail.
+    pub fn spec(&self) -> String {
+        match self.format {
+            ThumbnailFormat::Webp => format!("webp:{}x{}", self.width, self.height),
+            format => format!(
+                "{}:q{}:{}x{}",
+                format, self.quality, self.width, self.height
+            ),
+        }
+    }
+
+    /// Resize an image to fit these settings and encode it.
+    pub fn encode(&self, image_data: &[u8]) -> Result<Vec<u8>> {
+        let img = decode(image_data)?;
+        encode_image(
+            &img.thumbnail(self.width, self.height),
+            self.format,
+            self.quality,
+        )
+    }
+}
+
+/// A thumbnail ready to send to a client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Thumbnail {
+    /// Encoded image.
+    pub data: Vec<u8>,
+    /// MIME type of `data`.
+    pub mime_type: &'static str,
+    /// Version tag, distinct for each format the thumbnail is sent in.
+    pub etag: String,
+}
+
+impl Thumbnail {
+    /// Prepare a stored thumbnail for a client with the given `Accept`
+    /// header, converting it to JPEG (at `quality`) if the client does not
+    /// accept the stored format.
+    ///
+    /// Thumbnails that cannot be decoded, such as AVIF, are sent as they are.
+    pub fn negotiate(data: Vec<u8>, etag: String, accept: Option<&str>, quality: u8) -> Self {
+        let stored = ThumbnailFormat::detect(&data);
+        let convert = stored.is_some_and(|format| !format.is_accepted_by(accept));
+        if convert
+            && let Ok(jpeg) =
+                decode(&data).and_then(|img| encode_image(&img, ThumbnailFormat::Jpeg, quality))
+        {
+            return Self {
+                data: jpeg,
+                mime_type: ThumbnailFormat::Jpeg.mime_type(),
+                etag: format!("{}-jpeg", etag),
+            };
+        }
+
+        // Covers stored before thumbnails were re-encoded may be PNG or GIF
+        let mime_type = image::guess_format(&data)
+            .map(|format| format.to_mime_type())
+            .unwrap_or(ThumbnailFormat::Jpeg.mime_type());
+        Self {
+            data,
+            mime_type,
+            etag,
+        }
+    }
+}
+
+/// Decode an image in any supported format.
+fn decode(image_data: &[u8]) -> Result<image::DynamicImage> {
+    use image::ImageReader;
+    use std::io::Cursor;
+
+    ImageReader::new(Cursor::new(image_data))
+        .with_guessed_format()
+        .map_err(|e| {
+            AppError::Internal(t!("scan.read_image_format_failed", error = e).to_string())
+        })?
+        .decode()
+        .map_err(|e| AppError::Internal(t!("scan.decode_image_failed", error = e).to_string()))
+}
+
+/// Encode an image without resizing it.
+fn encode_image(
+    img: &image::DynamicImage,
+    format: ThumbnailFormat,
+    quality: u8,
+) -> Result<Vec<u8>> {
+    use image::codecs::avif::AvifEncoder;
+    use image::codecs::jpeg::JpegEncoder;
+    use image::codecs::webp::WebPEncoder;
+
+    let mut buffer = Vec::new();
+    let encoded = match format {
+        // JPEG has no alpha channel
+        ThumbnailFormat::Jpeg => image::DynamicImage::ImageRgb8(img.to_rgb8())
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut buffer, quality)),
+        ThumbnailFormat::Webp => image::DynamicImage::ImageRgba8(img.to_rgba8())
+            .write_with_encoder(WebPEncoder::new_lossless(&mut buffer)),
+        ThumbnailFormat::Avif => {
+            image::DynamicImage::ImageRgba8(img.to_rgba8()).write_with_encoder(
+                AvifEncoder::new_with_speed_quality(&mut buffer, AVIF_SPEED, quality),
+            )
+        }
+    };
+    encoded.map_err(|e| {
+        AppError::Internal(t!("scan.encode_thumbnail_failed", error = e).to_string())
+    })?;
+
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        image::DynamicImage::new_rgb8(width, height)
+            .write_to(
+                &mut std::io::Cursor::new(&mut buffer),
+                image::ImageFormat::Png,
+            )
+            .unwrap();
+        buffer
+    }
+
+    #[test]
+    fn test_encode_fits_size() {
+        let options = ThumbnailOptions {
+            width: 100,
+            height: 100,
+            ..ThumbnailOptions::default()
+        };
+        let thumbnail = options.encode(&png(400, 200)).unwrap();
+
+        let img = image::load_from_memory(&thumbnail).unwrap();
+        assert_eq!((img.width(), img.height()), (100, 50));
+        assert_eq!(
+            ThumbnailFormat::detect(&thumbnail),
+            Some(ThumbnailFormat::Jpeg)
+        );
+    }
+
+    #[test]
+    fn test_encode_in_configured_format() {
+        for format in [ThumbnailFormat::Webp, ThumbnailFormat::Avif] {
+            let options = ThumbnailOptions {
+                width: 32,
+                height: 32,
+                format,
+                ..ThumbnailOptions::default()
+            };
+            let thumbnail = options.encode(&png(64, 64)).unwrap();
+            assert_eq!(ThumbnailFormat::detect(&thumbnail), Some(format));
+        }
+    }
+
+    #[test]
+    fn test_encode_rejects_invalid_image() {
+        assert!(ThumbnailOptions::default().encode(b"not an image").is_err());
+    }
+
+    #[test]
+    fn test_spec_changes_with_settings() {
+        let default = ThumbnailOptions::default();
+        assert_eq!(default.spec(), "jpeg:q80:300x450");
+
+        let larger = ThumbnailOptions {
+            width: 600,
+            ..default
+        };
+        let webp = ThumbnailOptions {
+            format: ThumbnailFormat::Webp,
+            ..default
+        };
+        assert_ne!(default.spec(), larger.spec());
+        assert_eq!(webp.spec(), "webp:300x450");
+    }
+
+    #[test]
+    fn test_accept_header() {
+        let chrome = Some("image/avif,image/webp,image/apng,image/*,*/*;q=0.8");
+        assert!(ThumbnailFormat::Webp.is_accepted_by(chrome));
+        assert!(ThumbnailFormat::Avif.is_accepted_by(chrome));
+
+        let wildcard = Some("image/*,*/*;q=0.5");
+        assert!(!ThumbnailFormat::Webp.is_accepted_by(wildcard));
+        assert!(!ThumbnailFormat::Webp.is_accepted_by(None));
+        assert!(!ThumbnailFormat::Webp.is_accepted_by(Some("image/webp;q=0")));
+        assert!(ThumbnailFormat::Jpeg.is_accepted_by(None));
+    }
+
+    #[test]
+    fn test_negotiate_converts_unaccepted_format() {
+        let options = ThumbnailOptions {
+            format: ThumbnailFormat::Webp,
+            ..ThumbnailOptions::default()
+        };
+        let webp = options.encode(&png(64, 64)).unwrap();
+
+        let sent = Thumbnail::negotiate(webp.clone(), "v1".to_string(), Some("image/webp"), 80);
+        assert_eq!(sent.mime_type, "image/webp");
+        assert_eq!(sent.etag, "v1");
+        assert_eq!(sent.data, webp);
+
+        let sent = Thumbnail::negotiate(webp, "v1".to_string(), Some("image/*"), 80);
+        assert_eq!(sent.mime_type, "image/jpeg");
+        assert_eq!(sent.etag, "v1-jpeg");
+        assert_eq!(
+            ThumbnailFormat::detect(&sent.data),
+            Some(ThumbnailFormat::Jpeg)
+        );
+    }
+
+    #[test]
+    fn test_format_from_str() {
+        assert_eq!("WebP".parse(), Ok(ThumbnailFormat::Webp));
+        assert_eq!("jpg".parse(), Ok(ThumbnailFormat::Jpeg));
+        assert!("gif".parse::<ThumbnailFormat>().is_err());
+    }
+}
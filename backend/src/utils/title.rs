@@ -0,0 +1,131 @@
+//! Title normalization for scanned content.
+//!
+//! Folder names often carry release details that metadata providers do not
+//! know about, e.g. `[Group] Title v01 (Digital)`. Each rule is a regular
+//! expression whose matches are removed from the title; afterwards runs of
+//! whitespace are collapsed and separators left at either end are trimmed.
+
+use regex::Regex;
+
+/// Rules used when none are configured: bracketed tags, volume markers and
+/// trailing language tags.
+pub const DEFAULT_TITLE_RULES: &[&str] = &[
+    r"\[[^\]]*\]",
+    r"\([^)]*\)",
+    r"\{[^}]*\}",
+    r"【[^】]*】",
+    r"(?i)\b(?:vol(?:ume)?\.?|v)\s*\d+(?:\.\d+)?(?:\s*-\s*\d+(?:\.\d+)?)?\b",
+    r"第\s*\d+\s*[巻卷册冊]",
+    r"(?i)[\s_-]+(?:english|eng|japanese|jpn|chinese|chs|cht|raw)$",
+];
+
+/// Separator between the grouping folders of a title, see
+/// `ScanService::content_title`.
+const GROUP_SEPARATOR: &str = " / ";
+
+/// Cleans folder titles with a list of removal rules.
+#[derive(Debug, Clone)]
+pub struct TitleNormalizer {
+    rules: Vec<Regex>,
+}
+
+impl TitleNormalizer {
+    /// Build a normalizer, failing on the first rule that is not a valid
+    /// regular expression.
+    pub fn new<S: AsRef<str>>(rules: &[S]) -> Result<Self, regex::Error> {
+        let rules = rules
+            .iter()
+            .map(|rule| Regex::new(rule.as_ref()))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Clean a title. Each grouping folder of a grouped title is cleaned on
+    /// its own; parts that would end up empty are kept as they are.
+    pub fn normalize(&self, title: &str) -> String {
+        if self.rules.is_empty() {
+            return title.to_string();
+        }
+
+        title
+            .split(GROUP_SEPARATOR)
+            .map(|part| self.normalize_part(part))
+            .collect::<Vec<_>>()
+            .join(GROUP_SEPARATOR)
+    }
+
+    fn normalize_part(&self, part: &str) -> String {
+        let mut cleaned = part.replace('_', " ");
+        for rule in &self.rules {
+            cleaned = rule.replace_all(&cleaned, " ").into_owned();
+        }
+
+        let cleaned = cleaned.split_whitespace().collect::<Vec<_>>().join(" ");
+        let cleaned = cleaned.trim_matches(|c: char| c.is_whitespace() || "-.,~:".contains(c));
+        if cleaned.is_empty() {
+            part.to_string()
+        } else {
+            cleaned.to_string()
+        }
+    }
+}
+
+impl Default for TitleNormalizer {
+    fn default() -> Self {
+        Self::new(DEFAULT_TITLE_RULES).expect("default title rules are valid")
+    }
+}
+
+/// Title as compared when matching contents of another reader or server:
+/// lowercase with single spaces.
+pub fn match_key(title: &str) -> String {
+    title
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_rules() {
+        let normalizer = TitleNormalizer::default();
+
+        assert_eq!(normalizer.normalize("[Group] Title v01 (Digital)"), "Title");
+        assert_eq!(normalizer.normalize("Title Vol. 1-5 [English]"), "Title");
+        assert_eq!(normalizer.normalize("Title_Volume_03"), "Title");
+        assert_eq!(normalizer.normalize("Title - RAW"), "Title");
+        assert_eq!(
+            normalizer.normalize("【汉化】進撃の巨人 第01巻"),
+            "進撃の巨人"
+        );
+        assert_eq!(normalizer.normalize("Mob Psycho 100"), "Mob Psycho 100");
+        assert_eq!(
+            normalizer.normalize("Author / [Group] Series"),
+            "Author / Series"
+        );
+    }
+
+    #[test]
+    fn test_empty_result_keeps_title() {
+        let normalizer = TitleNormalizer::default();
+        assert_eq!(normalizer.normalize("[Only Tags]"), "[Only Tags]");
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        let normalizer = TitleNormalizer::new(&[r"(?i)\bcomplete\b"]).unwrap();
+        assert_eq!(
+            normalizer.normalize("[Group] Title Complete"),
+            "[Group] Title"
+        );
+
+        let none = TitleNormalizer::new::<&str>(&[]).unwrap();
+        assert_eq!(none.normalize("Title_v01"), "Title_v01");
+
+        assert!(TitleNormalizer::new(&["(unclosed"]).is_err());
+    }
+}
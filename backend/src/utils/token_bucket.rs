@@ -0,0 +1,114 @@
+//! Token bucket used for rate limiting.
+
+use std::time::{Duration, Instant};
+
+/// Token bucket refilled continuously at a fixed rate.
+///
+/// Callers pass the current time explicitly so the bucket can be tested
+/// without sleeping.
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Create a full bucket holding `capacity` tokens, refilled at `refill_per_sec`.
+    pub fn new(capacity: f64, refill_per_sec: f64, now: Instant) -> Self {
+        let capacity = capacity.max(1.0);
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_sec: refill_per_sec.max(f64::MIN_POSITIVE),
+            last_refill: now,
+        }
+    }
+
+    /// Bucket allowing `rate` operations per second with a burst of one second.
+    pub fn per_second(rate: u32, now: Instant) -> Self {
+        let rate = f64::from(rate.max(1));
+        Self::new(rate, rate, now)
+    }
+
+    /// Bucket allowing `rate` operations per minute with a burst of `rate`.
+    pub fn per_minute(rate: u32, now: Instant) -> Self {
+        let rate = f64::from(rate.max(1));
+        Self::new(rate, rate / 60.0, now)
+    }
+
+    /// Take one token, or return how long to wait before one is available.
+    pub fn try_take(&mut self, now: Instant) -> Option<Duration> {
+        self.refill(now);
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.refill_per_sec,
+            ))
+        }
+    }
+
+    /// Returns true if the bucket has refilled completely.
+    pub fn is_full(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        self.tokens >= self.capacity
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket_allows_burst_up_to_capacity() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::per_second(3, start);
+
+        assert!(bucket.try_take(start).is_none());
+        assert!(bucket.try_take(start).is_none());
+        assert!(bucket.try_take(start).is_none());
+        assert!(bucket.try_take(start).is_some());
+    }
+
+    #[test]
+    fn test_token_bucket_refills_over_time() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::per_second(2, start);
+
+        assert!(bucket.try_take(start).is_none());
+        assert!(bucket.try_take(start).is_none());
+
+        let wait = bucket.try_take(start).expect("bucket should be empty");
+        assert!(wait <= Duration::from_millis(500));
+
+        assert!(
+            bucket
+                .try_take(start + Duration::from_millis(500))
+                .is_none()
+        );
+        assert!(!bucket.is_full(start + Duration::from_millis(500)));
+        assert!(bucket.is_full(start + Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn test_per_minute_bucket_waits_for_refill() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::per_minute(1, start);
+
+        assert!(bucket.try_take(start).is_none());
+        let wait = bucket.try_take(start).expect("bucket should be empty");
+        assert!(wait > Duration::from_secs(59) && wait <= Duration::from_secs(60));
+    }
+}
@@ -54,7 +54,9 @@ async fn create_test_state(jwt_secret: String) -> AppState {
         auth: AuthConfig {
             jwt_secret,
             jwt_expiration_hours: 24,
-        }
+            ..Default::default()
+        },
+        ..Default::default()
     };
 
     AppState::new(pool, app_config)
@@ -92,7 +94,7 @@ proptest! {
             // Generate a valid token using JwtService with the same secret
             let jwt_service = JwtService::new(&jwt_secret, 24);
             let token = jwt_service
-                .generate_token(user_id, &username)
+                .generate_token(user_id, &username, 0)
                 .expect("Token generation should succeed");
 
             // Create a test router with the auth middleware
@@ -168,7 +170,7 @@ proptest! {
             // Generate a token with jwt_secret2 (wrong secret)
             let jwt_service = JwtService::new(&jwt_secret2, 24);
             let invalid_token = jwt_service
-                .generate_token(user_id, &username)
+                .generate_token(user_id, &username, 0)
                 .expect("Token generation should succeed");
 
             // Create a test router with the auth middleware
@@ -273,7 +275,7 @@ proptest! {
             // Generate a valid token
             let jwt_service = JwtService::new(&jwt_secret, 24);
             let token = jwt_service
-                .generate_token(user_id, &username)
+                .generate_token(user_id, &username, 0)
                 .expect("Token generation should succeed");
 
             // Create a test router with the auth middleware
@@ -482,7 +484,7 @@ mod example_tests {
         // Generate an expired token (expiration in the past)
         let jwt_service = JwtService::new("test-secret-key-for-testing", -1); // Negative hours = expired
         let expired_token = jwt_service
-            .generate_token(1, "testuser")
+            .generate_token(1, "testuser", 0)
             .expect("Token generation should succeed");
 
         // Create a test router with the auth middleware
@@ -774,7 +776,7 @@ mod integration_tests {
         let username = "testuser";
         let jwt_service = JwtService::new(&jwt_secret, 24);
         let token = jwt_service
-            .generate_token(user_id, username)
+            .generate_token(user_id, username, 0)
             .expect("Token generation should succeed");
 
         // Create router with protected route
@@ -857,7 +859,7 @@ mod integration_tests {
         // Test 2: Token with wrong secret
         let wrong_jwt_service = JwtService::new("wrong-secret", 24);
         let wrong_token = wrong_jwt_service
-            .generate_token(1, "testuser")
+            .generate_token(1, "testuser", 0)
             .expect("Token generation should succeed");
 
         let request = Request::builder()
@@ -918,7 +920,7 @@ mod integration_tests {
         let username = "multiuser";
         let jwt_service = JwtService::new(&jwt_secret, 24);
         let token = jwt_service
-            .generate_token(user_id, username)
+            .generate_token(user_id, username, 0)
             .expect("Token generation should succeed");
 
         // Create router with multiple protected routes
@@ -979,7 +981,7 @@ mod integration_tests {
         // Generate an expired token (negative expiration hours)
         let jwt_service = JwtService::new(&jwt_secret, -1);
         let expired_token = jwt_service
-            .generate_token(1, "testuser")
+            .generate_token(1, "testuser", 0)
             .expect("Token generation should succeed");
 
         // Create router with protected route
@@ -133,7 +133,7 @@ proptest! {
         let jwt_service = JwtService::new(&secret, 24);
 
         // Generate a token
-        let token = jwt_service.generate_token(user_id, &username)
+        let token = jwt_service.generate_token(user_id, &username, 0)
             .expect("Token generation should succeed");
 
         // Verify the token
@@ -169,7 +169,7 @@ proptest! {
         let jwt_service2 = JwtService::new(&secret2, 24);
 
         // Generate a token with secret1
-        let token = jwt_service1.generate_token(user_id, &username)
+        let token = jwt_service1.generate_token(user_id, &username, 0)
             .expect("Token generation should succeed");
 
         // Try to verify with secret2 - should fail
@@ -194,7 +194,7 @@ proptest! {
     ) {
         let jwt_service = JwtService::new(&secret, expiration_hours);
 
-        let token = jwt_service.generate_token(user_id, &username)
+        let token = jwt_service.generate_token(user_id, &username, 0)
             .expect("Token generation should succeed");
 
         let claims = jwt_service.verify_token(&token)
@@ -231,7 +231,7 @@ proptest! {
         let jwt_service = JwtService::new(&secret, 24);
 
         let before = chrono::Utc::now().timestamp();
-        let token = jwt_service.generate_token(user_id, &username)
+        let token = jwt_service.generate_token(user_id, &username, 0)
             .expect("Token generation should succeed");
         let after = chrono::Utc::now().timestamp();
 
@@ -991,3 +991,758 @@ proptest! {
         })?;
     }
 }
+
+// ============================================================================
+// Thumbnail Version Tag
+// ============================================================================
+
+use backend::repository::content::ContentRepository;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(20))]
+
+    /// The thumbnail version tag exposed in content responses is stable for
+    /// an unchanged thumbnail and changes when the thumbnail is replaced.
+    #[test]
+    fn property_thumbnail_etag_tracks_thumbnail(
+        library_name in arb_library_name(),
+        content_title in arb_content_title(),
+        first in prop::collection::vec(any::<u8>(), 1..256),
+        second in prop::collection::vec(any::<u8>(), 1..256),
+    ) {
+        prop_assume!(first != second);
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            let pool = create_test_db().await;
+
+            let library_id = create_test_library(&pool, &library_name).await;
+            let scan_path_id = create_test_scan_path(&pool, library_id, "/test/path").await;
+            let content_id = insert_test_content(
+                &pool,
+                library_id,
+                scan_path_id,
+                &content_title,
+            ).await;
+
+            let content = ContentService::get_content(&pool, content_id).await
+                .expect("Should get content");
+            prop_assert_eq!(ContentResponse::from(content).thumbnail_etag, None);
+
+            ContentRepository::update_thumbnail(&pool, content_id, Some(first.clone())).await
+                .expect("Should set thumbnail");
+            let content = ContentService::get_content(&pool, content_id).await
+                .expect("Should get content");
+            let first_etag = ContentResponse::from(content).thumbnail_etag;
+            prop_assert!(first_etag.is_some(), "Thumbnail should have a version tag");
+
+            ContentRepository::update_thumbnail(&pool, content_id, Some(first)).await
+                .expect("Should set thumbnail");
+            let content = ContentService::get_content(&pool, content_id).await
+                .expect("Should get content");
+            prop_assert_eq!(
+                &ContentResponse::from(content).thumbnail_etag,
+                &first_etag,
+                "Identical thumbnail should keep its version tag"
+            );
+
+            ContentRepository::update_thumbnail(&pool, content_id, Some(second)).await
+                .expect("Should set thumbnail");
+            let content = ContentService::get_content(&pool, content_id).await
+                .expect("Should get content");
+            prop_assert_ne!(
+                ContentResponse::from(content).thumbnail_etag,
+                first_etag,
+                "Regenerated thumbnail should change the version tag"
+            );
+
+            Ok(())
+        })?;
+    }
+}
+
+// ============================================================================
+// Content Feeds
+// ============================================================================
+
+use backend::models::{ContentFeed, ContentFeedQuery};
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(20))]
+
+    /// The recently added feed pages through every content newest first, and
+    /// the recently updated feed puts a changed content first.
+    #[test]
+    fn property_content_feeds_are_newest_first(
+        library_name in arb_library_name(),
+        num_contents in 1usize..12,
+        page_size in 1i64..5,
+    ) {
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            let pool = create_test_db().await;
+
+            let library_id = create_test_library(&pool, &library_name).await;
+            let scan_path_id = create_test_scan_path(&pool, library_id, "/test/path").await;
+
+            let start = Utc::now() - chrono::Duration::days(1);
+            let mut content_ids = Vec::new();
+            for i in 0..num_contents {
+                let content_id = insert_test_content(
+                    &pool,
+                    library_id,
+                    scan_path_id,
+                    &format!("Content_{}", i),
+                ).await;
+                let at = (start + chrono::Duration::minutes(i as i64)).to_rfc3339();
+                sqlx::query("UPDATE contents SET created_at = ?, updated_at = ? WHERE id = ?")
+                    .bind(&at)
+                    .bind(&at)
+                    .bind(content_id)
+                    .execute(&pool)
+                    .await
+                    .expect("Should set timestamps");
+                content_ids.push(content_id);
+            }
+
+            let mut listed = Vec::new();
+            let mut page = 1;
+            loop {
+                let query = ContentFeedQuery {
+                    library_id: None,
+                    page: Some(page),
+                    page_size: Some(page_size),
+                    after: None,
+                };
+                let result = ContentService::list_feed(&pool, ContentFeed::Added, &query, None).await
+                    .expect("Should list feed");
+                prop_assert_eq!(result.total, num_contents as i64);
+                if result.items.is_empty() {
+                    break;
+                }
+                prop_assert!(result.items.len() as i64 <= page_size);
+                listed.extend(result.items.into_iter().map(|c| c.id));
+                page += 1;
+            }
+            let newest_first: Vec<i64> = content_ids.iter().rev().copied().collect();
+            prop_assert_eq!(&listed, &newest_first);
+
+            let oldest = content_ids[0];
+            ContentRepository::update_chapter_count(&pool, oldest, 3).await
+                .expect("Should update content");
+            let query = ContentFeedQuery {
+                library_id: Some(library_id),
+                page: None,
+                page_size: None,
+                after: None,
+            };
+            let updated = ContentService::list_feed(&pool, ContentFeed::Updated, &query, None).await
+                .expect("Should list feed");
+            prop_assert_eq!(updated.items[0].id, oldest);
+
+            Ok(())
+        })?;
+    }
+}
+
+// ============================================================================
+// Moving content
+// ============================================================================
+
+/// Moving a content to a scan path of another library moves its folder and
+/// keeps its chapters.
+#[tokio::test]
+async fn move_content_keeps_chapters() {
+    let dir = tempfile::tempdir().unwrap();
+    let source_root = dir.path().join("comics");
+    let target_root = dir.path().join("archive");
+    std::fs::create_dir_all(source_root.join("Series")).unwrap();
+    std::fs::create_dir(&target_root).unwrap();
+    std::fs::write(source_root.join("Series").join("ch1.cbz"), b"zip").unwrap();
+
+    let pool = create_test_db().await;
+    let source_library = create_test_library(&pool, "Comics").await;
+    let source_path =
+        create_test_scan_path(&pool, source_library, &source_root.to_string_lossy()).await;
+    let target_library = create_test_library(&pool, "Archive").await;
+    let target_path =
+        create_test_scan_path(&pool, target_library, &target_root.to_string_lossy()).await;
+
+    let content_id = insert_test_content(&pool, source_library, source_path, "Series").await;
+    let folder = source_root.join("Series");
+    sqlx::query("UPDATE contents SET folder_path = ? WHERE id = ?")
+        .bind(folder.to_string_lossy().to_string())
+        .bind(content_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let chapter_id = insert_test_chapter(
+        &pool,
+        content_id,
+        "ch1",
+        &folder.join("ch1.cbz").to_string_lossy(),
+        0,
+        3,
+    )
+    .await;
+
+    // Without moving files the folder must already be in the scan path
+    assert!(
+        ContentService::move_content(&pool, content_id, target_path, false)
+            .await
+            .is_err()
+    );
+
+    let moved = ContentService::move_content(&pool, content_id, target_path, true)
+        .await
+        .expect("Should move content");
+    let destination = target_root.join("Series");
+    assert_eq!(moved.library_id, target_library);
+    assert_eq!(moved.scan_path_id, target_path);
+    assert_eq!(moved.folder_path, destination.to_string_lossy());
+    assert!(destination.join("ch1.cbz").exists());
+    assert!(!folder.exists());
+
+    let chapters = ContentService::list_chapters(&pool, content_id)
+        .await
+        .unwrap();
+    assert_eq!(chapters.len(), 1);
+    assert_eq!(chapters[0].id, chapter_id);
+    assert_eq!(
+        chapters[0].file_path,
+        destination.join("ch1.cbz").to_string_lossy()
+    );
+}
+
+// ============================================================================
+// Dashboard
+// ============================================================================
+
+use backend::repository::dashboard::DashboardRepository;
+use backend::repository::metadata::MetadataFailureRepository;
+
+/// The dashboard counts problems the way their listings do, leaving out
+/// content in the trash.
+#[tokio::test]
+async fn dashboard_counts_errors() {
+    let pool = create_test_db().await;
+    let library_id = create_test_library(&pool, "Comics").await;
+    let scan_path_id = create_test_scan_path(&pool, library_id, "/comics").await;
+    let readable = insert_test_content(&pool, library_id, scan_path_id, "Readable").await;
+    let broken = insert_test_content(&pool, library_id, scan_path_id, "Broken").await;
+    let trashed = insert_test_content(&pool, library_id, scan_path_id, "Trashed").await;
+
+    assert_eq!(
+        DashboardRepository::count_errors(&pool).await.unwrap(),
+        Default::default()
+    );
+
+    insert_test_chapter(&pool, readable, "ch1", "/comics/Readable/ch1.cbz", 0, 1).await;
+    let chapter_id =
+        insert_test_chapter(&pool, broken, "ch1", "/comics/Broken/ch1.cbz", 0, 1).await;
+    sqlx::query("UPDATE chapters SET status = 'error' WHERE id = ?")
+        .bind(chapter_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    MetadataFailureRepository::record(&pool, broken, "not found")
+        .await
+        .unwrap();
+    MetadataFailureRepository::record(&pool, trashed, "not found")
+        .await
+        .unwrap();
+    ContentRepository::mark_missing(&pool, trashed)
+        .await
+        .unwrap();
+
+    let errors = DashboardRepository::count_errors(&pool).await.unwrap();
+    assert_eq!(errors.metadata_failures, 1);
+    assert_eq!(errors.media_errors, 1);
+    assert_eq!(errors.trash, 1);
+    assert_eq!(errors.failed_tasks, 0);
+}
+
+// ============================================================================
+// Thumbnail previews
+// ============================================================================
+
+/// Previews are returned for contents with a thumbnail, keyed by ID.
+#[tokio::test]
+async fn thumbnail_previews_by_content() {
+    let pool = create_test_db().await;
+    let library_id = create_test_library(&pool, "Comics").await;
+    let scan_path_id = create_test_scan_path(&pool, library_id, "/comics").await;
+    let with_cover = insert_test_content(&pool, library_id, scan_path_id, "Cover").await;
+    let without_cover = insert_test_content(&pool, library_id, scan_path_id, "Plain").await;
+
+    let mut png = Vec::new();
+    image::DynamicImage::new_rgb8(300, 450)
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .unwrap();
+    ContentRepository::update_thumbnail(&pool, with_cover, Some(png), None)
+        .await
+        .unwrap();
+
+    let previews =
+        ContentService::thumbnail_previews(&pool, &[with_cover, without_cover, 999], Some(64), 80)
+            .await
+            .unwrap();
+    assert_eq!(previews.len(), 1);
+    let preview = &previews[&with_cover];
+    assert_eq!(preview.mime_type, "image/jpeg");
+    assert!(preview.etag.ends_with("-w64"));
+    assert!(preview.data.starts_with("/9j/"));
+
+    let too_many: Vec<i64> = (0..1000).collect();
+    assert!(
+        ContentService::thumbnail_previews(&pool, &too_many, None, 80)
+            .await
+            .is_err()
+    );
+}
+
+// ============================================================================
+// Cursor pagination
+// ============================================================================
+
+/// Following cursors visits every content once, in the listing's order.
+#[tokio::test]
+async fn cursor_pages_cover_listing() {
+    let pool = create_test_db().await;
+    let library_id = create_test_library(&pool, "Comics").await;
+    let scan_path_id = create_test_scan_path(&pool, library_id, "/comics").await;
+    let mut ids = Vec::new();
+    for title in ["Delta", "Alpha", "Charlie", "Echo", "Bravo"] {
+        ids.push(insert_test_content(&pool, library_id, scan_path_id, title).await);
+    }
+
+    let mut titles = Vec::new();
+    let mut after = None;
+    loop {
+        let (contents, next) =
+            ContentService::list_contents_page(&pool, library_id, after.as_deref(), Some(2))
+                .await
+                .unwrap();
+        titles.extend(contents.into_iter().map(|c| c.title));
+        match next {
+            Some(cursor) => after = Some(cursor),
+            None => break,
+        }
+    }
+    assert_eq!(titles, ["Alpha", "Bravo", "Charlie", "Delta", "Echo"]);
+
+    let mut listed = Vec::new();
+    let mut query = ContentFeedQuery {
+        page_size: Some(2),
+        ..ContentFeedQuery::default()
+    };
+    loop {
+        let page = ContentService::list_feed(&pool, ContentFeed::Added, &query, None)
+            .await
+            .unwrap();
+        listed.extend(page.items.iter().map(|c| c.id));
+        match page.next_cursor {
+            Some(cursor) => query.after = Some(cursor),
+            None => break,
+        }
+    }
+    let newest_first: Vec<i64> = ids.iter().rev().copied().collect();
+    assert_eq!(listed, newest_first);
+
+    // A cursor only works for the listing it came from
+    let (_, title_cursor) = ContentService::list_contents_page(&pool, library_id, None, Some(1))
+        .await
+        .unwrap();
+    query.after = title_cursor;
+    assert!(
+        ContentService::list_feed(&pool, ContentFeed::Added, &query, None)
+            .await
+            .is_err()
+    );
+}
+
+// ============================================================================
+// Novel Text Search Tests
+// ============================================================================
+
+/// Searching a novel finds matches in its text chapters in reading order,
+/// skipping chapters that are not text.
+#[tokio::test]
+async fn search_text_across_chapters() {
+    let dir = tempfile::tempdir().unwrap();
+    let first = dir.path().join("01.txt");
+    let second = dir.path().join("02.txt");
+    std::fs::write(&first, "The road was quiet.").unwrap();
+    std::fs::write(&second, "At the inn they met Mira.\nMIRA smiled.").unwrap();
+
+    let pool = create_test_db().await;
+    let library_id = create_test_library(&pool, "Novels").await;
+    let scan_path_id = create_test_scan_path(&pool, library_id, "/novels").await;
+    let content_id = insert_test_content(&pool, library_id, scan_path_id, "Journey").await;
+    let mut chapter_ids = Vec::new();
+    for (i, path) in [&first, &second].into_iter().enumerate() {
+        let path = path.to_string_lossy();
+        let id = insert_test_chapter(&pool, content_id, "Chapter", &path, i as i32, 1).await;
+        chapter_ids.push(id);
+    }
+    insert_test_chapter(&pool, content_id, "Pictures", "/novels/art.cbz", 2, 1).await;
+    sqlx::query("UPDATE chapters SET file_type = substr(file_path, -3)")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let matches = ContentService::search_text(&pool, content_id, "mira", None)
+        .await
+        .unwrap();
+    assert_eq!(matches.len(), 2);
+    assert!(matches.iter().all(|m| m.chapter_id == chapter_ids[1]));
+    assert_eq!(matches[0].chapter_index, 1);
+    assert_eq!(matches[0].offset, 20);
+    assert!(matches[0].snippet.contains("met Mira."));
+    assert_eq!(matches[1].offset, 26);
+
+    let limited = ContentService::search_text(&pool, content_id, "mira", Some(1))
+        .await
+        .unwrap();
+    assert_eq!(limited.len(), 1);
+
+    assert!(
+        ContentService::search_text(&pool, content_id, "  ", None)
+            .await
+            .is_err()
+    );
+}
+
+// ============================================================================
+// Filtered Content Listing
+// ============================================================================
+
+use backend::models::ContentSort;
+
+/// Listing by title filters by library and title and pages through the
+/// matches alphabetically.
+#[tokio::test]
+async fn list_matching_filters_and_pages() {
+    let pool = create_test_db().await;
+    let comics = create_test_library(&pool, "Comics").await;
+    let comics_path = create_test_scan_path(&pool, comics, "/comics").await;
+    let novels = create_test_library(&pool, "Novels").await;
+    let novels_path = create_test_scan_path(&pool, novels, "/novels").await;
+    for title in ["Blue Period", "akira", "Blue Giant"] {
+        insert_test_content(&pool, comics, comics_path, title).await;
+    }
+    insert_test_content(&pool, novels, novels_path, "Blue Lock").await;
+
+    let titles =
+        |contents: Vec<Content>| -> Vec<String> { contents.into_iter().map(|c| c.title).collect() };
+    let all = ContentRepository::list_matching(&pool, None, None, None, ContentSort::Title, 10, 0)
+        .await
+        .unwrap();
+    assert_eq!(
+        titles(all),
+        ["akira", "Blue Giant", "Blue Lock", "Blue Period"]
+    );
+
+    let page = ContentRepository::list_matching(
+        &pool,
+        Some(comics),
+        None,
+        Some("blue"),
+        ContentSort::Title,
+        1,
+        1,
+    )
+    .await
+    .unwrap();
+    assert_eq!(titles(page), ["Blue Period"]);
+    assert_eq!(
+        ContentRepository::count_matching(&pool, Some(comics), None, Some("blue"))
+            .await
+            .unwrap(),
+        2
+    );
+}
+
+// ============================================================================
+// Alternative Titles
+// ============================================================================
+
+use backend::repository::alias::AliasRepository;
+
+/// Search matches the alternative titles scraped from metadata and those
+/// added by hand, and new metadata only replaces the scraped ones.
+#[tokio::test]
+async fn search_matches_alternative_titles() {
+    let pool = create_test_db().await;
+    let library_id = create_test_library(&pool, "Comics").await;
+    let scan_path_id = create_test_scan_path(&pool, library_id, "/comics").await;
+    let content_id = insert_test_content(&pool, library_id, scan_path_id, "Shingeki").await;
+    insert_test_content(&pool, library_id, scan_path_id, "Other").await;
+
+    let metadata = serde_json::json!({ "name": "進撃の巨人", "name_cn": "进击的巨人" });
+    ContentRepository::update_metadata(&pool, content_id, Some(metadata), None)
+        .await
+        .unwrap();
+    AliasRepository::add_manual(&pool, content_id, "Attack on Titan")
+        .await
+        .unwrap();
+
+    for query in ["巨人", "titan"] {
+        let found = ContentRepository::search_by_title(&pool, library_id, query)
+            .await
+            .unwrap();
+        let ids: Vec<i64> = found.iter().map(|c| c.id).collect();
+        assert_eq!(ids, [content_id], "query {query}");
+    }
+    assert_eq!(
+        ContentRepository::count_matching(&pool, Some(library_id), None, Some("进击"))
+            .await
+            .unwrap(),
+        1
+    );
+
+    ContentRepository::update_metadata(&pool, content_id, None, None)
+        .await
+        .unwrap();
+    let titles: Vec<String> = AliasRepository::list_by_content(&pool, content_id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|a| a.title)
+        .collect();
+    assert_eq!(titles, ["Attack on Titan"]);
+}
+
+// ============================================================================
+// Age Ratings
+// ============================================================================
+
+use backend::models::UpdateMetadataEditsRequest;
+use backend::services::age_rating::AgeRatingService;
+use backend::services::metadata_edits::MetadataEditsService;
+
+/// Ratings come from the metadata unless set by hand, a rating set by hand
+/// survives rescrapes, and capped users do not see contents rated above
+/// their cap.
+#[tokio::test]
+async fn age_ratings_filter_capped_users() {
+    let pool = create_test_db().await;
+    let library_id = create_test_library(&pool, "Comics").await;
+    let scan_path_id = create_test_scan_path(&pool, library_id, "/comics").await;
+    let adult = insert_test_content(&pool, library_id, scan_path_id, "Adult").await;
+    let teen = insert_test_content(&pool, library_id, scan_path_id, "Teen").await;
+    let unrated = insert_test_content(&pool, library_id, scan_path_id, "Unrated").await;
+    let nsfw = serde_json::json!({ "name": "Adult", "nsfw": true });
+
+    let content = ContentRepository::update_metadata(&pool, adult, Some(nsfw.clone()), None)
+        .await
+        .unwrap();
+    assert_eq!(content.age_rating, Some(18));
+
+    let request = UpdateMetadataEditsRequest {
+        age_rating: Some(Some(12)),
+        ..Default::default()
+    };
+    let edits = MetadataEditsService::update(&pool, teen, request)
+        .await
+        .unwrap();
+    assert!(edits.age_rating_lock);
+    let content = ContentRepository::update_metadata(&pool, teen, Some(nsfw), None)
+        .await
+        .unwrap();
+    assert_eq!(content.age_rating, Some(12));
+
+    let now = Utc::now().to_rfc3339();
+    let user_id = sqlx::query(
+        r#"
+        INSERT INTO users (username, password_hash, created_at, updated_at)
+        VALUES ('kid', 'test_hash', ?, ?)
+        "#,
+    )
+    .bind(&now)
+    .bind(&now)
+    .execute(&pool)
+    .await
+    .unwrap()
+    .last_insert_rowid();
+    AgeRatingService::set_max_for_user(&pool, user_id, Some(12))
+        .await
+        .unwrap();
+    assert!(
+        AgeRatingService::set_max_for_user(&pool, user_id, Some(-1))
+            .await
+            .is_err()
+    );
+
+    let contents = ContentService::list_contents(&pool, library_id)
+        .await
+        .unwrap();
+    let visible = AgeRatingService::filter_contents(&pool, user_id, contents)
+        .await
+        .unwrap();
+    let mut ids: Vec<i64> = visible.iter().map(|c| c.id).collect();
+    ids.sort();
+    assert_eq!(ids, [teen, unrated]);
+
+    let max = AgeRatingService::max_for_user(&pool, user_id)
+        .await
+        .unwrap();
+    let query = ContentFeedQuery {
+        library_id: Some(library_id),
+        page: None,
+        page_size: None,
+        after: None,
+    };
+    let page = ContentService::list_feed(&pool, ContentFeed::Added, &query, max)
+        .await
+        .unwrap();
+    assert_eq!(page.total, 2);
+    assert!(page.items.iter().all(|c| c.id != adult));
+
+    // Clearing the rating set by hand goes back to the metadata
+    let request = UpdateMetadataEditsRequest {
+        age_rating: Some(None),
+        ..Default::default()
+    };
+    MetadataEditsService::update(&pool, teen, request)
+        .await
+        .unwrap();
+    let content = ContentService::get_content(&pool, teen).await.unwrap();
+    assert_eq!(content.age_rating, Some(18));
+    assert!(
+        AgeRatingService::check(&pool, user_id, &content)
+            .await
+            .is_err()
+    );
+}
+
+use axum::body::Body;
+use axum::extract::{Path, Query, Request, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use backend::handlers::{content as content_handlers, komga};
+use backend::middlewares::AuthUser;
+use backend::state::{AppConfig, AppState};
+
+/// Status a handler result would be answered with.
+fn status_of<T: IntoResponse>(result: backend::error::Result<T>) -> StatusCode {
+    match result {
+        Ok(response) => response.into_response().status(),
+        Err(e) => e.into_response().status(),
+    }
+}
+
+/// Capped users get 404 for the pages and files of contents rated above
+/// their cap, through the web and Komga APIs alike.
+#[tokio::test]
+async fn age_ratings_hide_pages_and_downloads() {
+    let pool = create_test_db().await;
+    let state = AppState::new(pool.clone(), AppConfig::default());
+    let library_id = create_test_library(&pool, "Comics").await;
+    let scan_path_id = create_test_scan_path(&pool, library_id, "/comics").await;
+    let content_id = insert_test_content(&pool, library_id, scan_path_id, "Adult").await;
+    ContentRepository::set_age_rating(&pool, content_id, Some(18))
+        .await
+        .unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("Adult 01.cbz");
+    std::fs::write(&file, b"chapter").unwrap();
+    let chapter_id =
+        insert_test_chapter(&pool, content_id, "Adult 01", file.to_str().unwrap(), 0, 7).await;
+
+    let now = Utc::now().to_rfc3339();
+    let mut users = Vec::new();
+    for username in ["adult", "kid"] {
+        let user_id = sqlx::query(
+            r#"
+            INSERT INTO users (username, password_hash, created_at, updated_at)
+            VALUES (?, 'test_hash', ?, ?)
+            "#,
+        )
+        .bind(username)
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .unwrap()
+        .last_insert_rowid();
+        users.push(AuthUser {
+            user_id,
+            username: username.to_string(),
+        });
+    }
+    let (adult, kid) = (users[0].clone(), users[1].clone());
+    AgeRatingService::set_max_for_user(&pool, kid.user_id, Some(12))
+        .await
+        .unwrap();
+
+    let download = |user: AuthUser| {
+        content_handlers::download_chapter(
+            State(state.clone()),
+            user,
+            Path(content_handlers::ChapterParams {
+                content_id,
+                chapter_id,
+            }),
+            Query(content_handlers::DownloadQuery::default()),
+            Request::new(Body::empty()),
+        )
+    };
+    assert_eq!(status_of(download(adult.clone()).await), StatusCode::OK);
+    assert_eq!(
+        status_of(download(kid.clone()).await),
+        StatusCode::NOT_FOUND
+    );
+
+    let book_file = |user: AuthUser| {
+        komga::get_book_file(
+            State(state.clone()),
+            user,
+            Path(chapter_id),
+            Request::new(Body::empty()),
+        )
+    };
+    assert_eq!(status_of(book_file(adult).await), StatusCode::OK);
+    assert_eq!(
+        status_of(book_file(kid.clone()).await),
+        StatusCode::NOT_FOUND
+    );
+
+    let page = content_handlers::get_page(
+        State(state.clone()),
+        kid.clone(),
+        Path(content_handlers::PageParams {
+            content_id,
+            chapter_id,
+            page: 0,
+        }),
+        Query(Default::default()),
+    )
+    .await;
+    assert_eq!(status_of(page), StatusCode::NOT_FOUND);
+
+    let pages = content_handlers::list_pages(
+        State(state.clone()),
+        kid.clone(),
+        Path(content_handlers::ChapterParams {
+            content_id,
+            chapter_id,
+        }),
+        Query(Default::default()),
+    )
+    .await;
+    assert_eq!(status_of(pages), StatusCode::NOT_FOUND);
+
+    let page = komga::get_page(State(state.clone()), kid.clone(), Path((chapter_id, 1))).await;
+    assert_eq!(status_of(page), StatusCode::NOT_FOUND);
+
+    let series = content_handlers::download_series(
+        State(state.clone()),
+        kid,
+        Path(content_id),
+        Query(content_handlers::DownloadQuery::default()),
+    )
+    .await;
+    assert_eq!(status_of(series), StatusCode::NOT_FOUND);
+}
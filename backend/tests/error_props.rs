@@ -3,7 +3,9 @@
 //! **Feature: comic-reader, Property 17: Error Response Structure**
 //! **Validates: Requirements 7.5**
 
-use backend::error::AppError;
+use axum::http::{StatusCode, header};
+use axum::response::IntoResponse;
+use backend::error::{AppError, ErrorCode};
 use proptest::prelude::*;
 
 /// Strategy to generate arbitrary AppError variants with random messages.
@@ -12,8 +14,16 @@ fn arb_app_error() -> impl Strategy<Value = AppError> {
         any::<String>().prop_map(AppError::NotFound),
         any::<String>().prop_map(AppError::BadRequest),
         any::<String>().prop_map(AppError::Unauthorized),
+        any::<String>().prop_map(AppError::Forbidden),
         any::<String>().prop_map(AppError::Archive),
         any::<String>().prop_map(AppError::Internal),
+        Just(AppError::Cancelled),
+        (any::<String>(), 1u64..3600).prop_map(|(message, retry_after_secs)| {
+            AppError::TooManyRequests {
+                message,
+                retry_after_secs,
+            }
+        }),
     ]
 }
 
@@ -68,8 +78,13 @@ proptest! {
             AppError::NotFound(msg.clone()),
             AppError::BadRequest(msg.clone()),
             AppError::Unauthorized(msg.clone()),
+            AppError::Forbidden(msg.clone()),
             AppError::Archive(msg.clone()),
             AppError::Internal(msg.clone()),
+            AppError::TooManyRequests {
+                message: msg.clone(),
+                retry_after_secs: 60,
+            },
         ];
 
         for error in errors {
@@ -106,3 +121,135 @@ fn error_response_serializes_to_json() {
     assert_eq!(error_obj.get("code").unwrap(), 404);
     assert_eq!(error_obj.get("message").unwrap(), "Resource not found");
 }
+
+/// Position of a code in [`ErrorCode::ALL`]. The match has no wildcard, so
+/// a new code does not compile until it is listed here.
+fn code_index(code: ErrorCode) -> usize {
+    match code {
+        ErrorCode::NotFound => 0,
+        ErrorCode::BadRequest => 1,
+        ErrorCode::Unauthorized => 2,
+        ErrorCode::Forbidden => 3,
+        ErrorCode::TooManyRequests => 4,
+        ErrorCode::RequestCancelled => 5,
+        ErrorCode::DatabaseError => 6,
+        ErrorCode::FileSystemError => 7,
+        ErrorCode::ArchiveError => 8,
+        ErrorCode::InternalError => 9,
+        ErrorCode::LibraryNotFound => 10,
+        ErrorCode::ContentNotFound => 11,
+        ErrorCode::ChapterNotFound => 12,
+        ErrorCode::PageNotFound => 13,
+        ErrorCode::ThumbnailNotFound => 14,
+        ErrorCode::ScanTaskNotFound => 15,
+        ErrorCode::TaskNotCancellable => 16,
+        ErrorCode::JobNotFound => 17,
+        ErrorCode::UserNotFound => 18,
+        ErrorCode::BookmarkNotFound => 19,
+        ErrorCode::SeriesGroupNotFound => 20,
+        ErrorCode::ApiKeyNotFound => 21,
+        ErrorCode::BackupNotFound => 22,
+        ErrorCode::UsernameTaken => 23,
+        ErrorCode::InvalidCredentials => 24,
+        ErrorCode::InvalidToken => 25,
+        ErrorCode::InvalidTotpCode => 26,
+        ErrorCode::AdminRequired => 27,
+        ErrorCode::SetupCompleted => 28,
+        ErrorCode::PathNotAllowed => 29,
+        ErrorCode::UnsupportedFormat => 30,
+    }
+}
+
+/// Every error code is listed once and is sent as its `as_str` name.
+#[test]
+fn error_codes_are_exhaustive_and_unique() {
+    for (index, code) in ErrorCode::ALL.iter().enumerate() {
+        assert_eq!(code_index(*code), index, "{code:?} is out of place in ALL");
+        assert_eq!(serde_json::to_value(code).unwrap(), code.as_str());
+        assert!(
+            code.as_str()
+                .split('_')
+                .all(|word| !word.is_empty() && word.chars().all(|c| c.is_ascii_uppercase())),
+            "{} is not SCREAMING_SNAKE_CASE",
+            code.as_str()
+        );
+    }
+
+    let names: std::collections::HashSet<_> = ErrorCode::ALL.iter().map(|c| c.as_str()).collect();
+    assert_eq!(names.len(), ErrorCode::ALL.len());
+}
+
+/// Every error kind has its generic code.
+#[test]
+fn error_kinds_map_to_generic_codes() {
+    let message = || "message".to_string();
+    let cases = [
+        (AppError::NotFound(message()), ErrorCode::NotFound),
+        (AppError::BadRequest(message()), ErrorCode::BadRequest),
+        (AppError::Unauthorized(message()), ErrorCode::Unauthorized),
+        (AppError::Forbidden(message()), ErrorCode::Forbidden),
+        (
+            AppError::TooManyRequests {
+                message: message(),
+                retry_after_secs: 1,
+            },
+            ErrorCode::TooManyRequests,
+        ),
+        (AppError::Cancelled, ErrorCode::RequestCancelled),
+        (
+            AppError::Database(sqlx::Error::RowNotFound),
+            ErrorCode::DatabaseError,
+        ),
+        (
+            AppError::FileSystem(std::io::Error::other("io")),
+            ErrorCode::FileSystemError,
+        ),
+        (AppError::Archive(message()), ErrorCode::ArchiveError),
+        (AppError::Internal(message()), ErrorCode::InternalError),
+    ];
+
+    for (error, code) in cases {
+        assert_eq!(error.code(), code);
+        assert_eq!(error.to_error_response().code, code);
+    }
+}
+
+/// A specific code replaces the generic one but keeps the status, message
+/// and kind of the error.
+#[test]
+fn specific_code_keeps_status_and_message() {
+    let error = AppError::NotFound("Library 3 not found".to_string())
+        .with_code(ErrorCode::LibraryNotFound)
+        .with_details(serde_json::json!({ "library_id": 3 }));
+
+    assert_eq!(error.code(), ErrorCode::LibraryNotFound);
+    assert_eq!(error.status_code(), StatusCode::NOT_FOUND);
+    assert!(matches!(error.kind(), AppError::NotFound(_)));
+
+    let json = serde_json::to_value(error.to_error_response()).unwrap();
+    assert_eq!(json["type"], "about:blank");
+    assert_eq!(json["title"], "Not Found");
+    assert_eq!(json["status"], 404);
+    assert_eq!(json["detail"], "Library 3 not found");
+    assert_eq!(json["code"], "LIBRARY_NOT_FOUND");
+    assert_eq!(json["details"]["library_id"], 3);
+    assert_eq!(json["error"]["code"], 404);
+    assert_eq!(json["error"]["message"], "Library 3 not found");
+}
+
+/// Errors are sent as `application/problem+json`.
+#[test]
+fn error_response_is_problem_json() {
+    let response = AppError::TooManyRequests {
+        message: "Slow down".to_string(),
+        retry_after_secs: 30,
+    }
+    .into_response();
+
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(
+        response.headers()[header::CONTENT_TYPE],
+        "application/problem+json"
+    );
+    assert_eq!(response.headers()[header::RETRY_AFTER], "30");
+}
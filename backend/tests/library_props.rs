@@ -73,6 +73,8 @@ proptest! {
                 name: name.clone(),
                 scan_interval: Some(scan_interval),
                 watch_mode: Some(watch_mode),
+                scan_depth: None,
+                standalone_archives: None,
             };
             let created = service.create(req).await.expect("Should create library");
 
@@ -120,6 +122,8 @@ proptest! {
                 name: initial_name,
                 scan_interval: Some(initial_interval),
                 watch_mode: Some(initial_watch),
+                scan_depth: None,
+                standalone_archives: None,
             };
             let created = service.create(req).await.expect("Should create library");
 
@@ -128,6 +132,8 @@ proptest! {
                 name: Some(updated_name.clone()),
                 scan_interval: Some(updated_interval),
                 watch_mode: Some(updated_watch),
+                scan_depth: None,
+                standalone_archives: None,
             };
             let updated = service.update(created.id, update_req).await
                 .expect("Should update library");
@@ -178,6 +184,8 @@ proptest! {
                 name: library_name,
                 scan_interval: None,
                 watch_mode: None,
+                scan_depth: None,
+                standalone_archives: None,
             };
             let library = service.create(req).await.expect("Should create library");
 
@@ -220,6 +228,8 @@ proptest! {
                 name: library_name,
                 scan_interval: None,
                 watch_mode: None,
+                scan_depth: None,
+                standalone_archives: None,
             };
             let library = service.create(req).await.expect("Should create library");
 
@@ -325,6 +335,8 @@ proptest! {
                 name: library_name,
                 scan_interval: None,
                 watch_mode: None,
+                scan_depth: None,
+                standalone_archives: None,
             };
             let library = service.create(req).await.expect("Should create library");
 
@@ -401,6 +413,8 @@ proptest! {
                 name: library_name,
                 scan_interval: None,
                 watch_mode: None,
+                scan_depth: None,
+                standalone_archives: None,
             };
             let library = service.create(req).await.expect("Should create library");
 
@@ -517,6 +531,8 @@ proptest! {
                 name: library_name,
                 scan_interval: None,
                 watch_mode: None,
+                scan_depth: None,
+                standalone_archives: None,
             };
             let library = service.create(req).await.expect("Should create library");
             let library_id = library.id;
@@ -565,3 +581,50 @@ proptest! {
         })?;
     }
 }
+
+/// A library marked for deletion is hidden at once, and its deletion
+/// reports the removed contents as progress.
+#[tokio::test]
+async fn library_deletion_hides_then_removes() {
+    let pool = create_test_db().await;
+    let service = LibraryService::new(pool.clone());
+    let req = CreateLibraryRequest {
+        name: "Deleted".to_string(),
+        scan_interval: None,
+        watch_mode: None,
+        scan_depth: None,
+        standalone_archives: None,
+    };
+    let library = service.create(req).await.expect("Should create library");
+    let scan_path = service
+        .add_scan_path(library.id, "/test/deleted".to_string())
+        .await
+        .expect("Should add scan path");
+    for title in ["One", "Two", "Three"] {
+        insert_test_content(&pool, library.id, scan_path.id, title).await;
+    }
+
+    service
+        .mark_deleting(library.id)
+        .await
+        .expect("Should mark library");
+    assert!(service.get(library.id).await.unwrap().is_none());
+    assert!(service.list().await.unwrap().is_empty());
+    assert!(library_exists(&pool, library.id).await);
+    // Already hidden
+    assert!(service.mark_deleting(library.id).await.is_err());
+
+    let progress = std::sync::Mutex::new(Vec::new());
+    let result = service
+        .delete_in_batches(library.id, |p| progress.lock().unwrap().push(p))
+        .await
+        .expect("Should delete library");
+    assert_eq!(result.contents, 3);
+    assert_eq!(result.chapters, 0);
+
+    let progress = progress.into_inner().unwrap();
+    let last = progress.last().unwrap();
+    assert_eq!((last.scanned_paths, last.total_paths), (3, 3));
+    assert!(!library_exists(&pool, library.id).await);
+    assert_eq!(count_contents_for_library(&pool, library.id).await, 0);
+}
@@ -55,6 +55,8 @@ async fn create_test_library_with_path(pool: &Pool<Sqlite>) -> (i64, i64) {
             name: "Test Library".to_string(),
             scan_interval: None,
             watch_mode: None,
+            scan_depth: None,
+            standalone_archives: None,
         })
         .await
         .expect("Should create library");
@@ -74,7 +76,7 @@ async fn create_test_content_with_chapters(
     scan_path_id: i64,
     num_chapters: i32,
 ) -> (i64, Vec<i64>) {
-    use backend::models::{NewChapter, NewContent};
+    use backend::models::{ChapterStatus, NewChapter, NewContent};
 
     let content = ContentRepository::create(
         pool,
@@ -83,12 +85,14 @@ async fn create_test_content_with_chapters(
             scan_path_id,
 
             title: "Test Content".to_string(),
+            folder_title: "Test Content".to_string(),
             folder_path: format!(
                 "/test/content_{}",
                 Utc::now().timestamp_nanos_opt().unwrap_or(0)
             ),
             chapter_count: num_chapters,
             thumbnail: None,
+            thumbnail_spec: None,
             metadata: None,
         },
     )
@@ -107,6 +111,10 @@ async fn create_test_content_with_chapters(
                 sort_order: i,
                 page_count: 10,
                 size: 1024,
+                volume: None,
+                number: Some((i + 1) as f32),
+                status: ChapterStatus::Ready,
+                error: None,
             },
         )
         .await
@@ -587,7 +595,7 @@ proptest! {
             prop_assert!(result.is_err(), "Should reject non-existent chapter");
             let err = result.unwrap_err();
             prop_assert!(
-                matches!(err, backend::error::AppError::NotFound(_)),
+                matches!(err.kind(), backend::error::AppError::NotFound(_)),
                 "Error should be NotFound"
             );
 
@@ -595,3 +603,48 @@ proptest! {
         })?;
     }
 }
+
+// ============================================================================
+// Unread Counts
+// ============================================================================
+
+use backend::repository::progress::ProgressRepository;
+
+/// Chapters are counted as read, in progress or unread for each user.
+#[tokio::test]
+async fn unread_counts_are_per_user() {
+    let pool = create_test_db().await;
+    let service = ProgressService::new(pool.clone());
+    let reader = create_test_user(&pool, "reader").await;
+    let other = create_test_user(&pool, "other").await;
+    let (library_id, scan_path_id) = create_test_library_with_path(&pool).await;
+    let (content_id, chapter_ids) =
+        create_test_content_with_chapters(&pool, library_id, scan_path_id, 3).await;
+
+    service
+        .update_progress_with_percentage(reader, chapter_ids[0], 10, 100.0)
+        .await
+        .expect("Should save progress");
+    service
+        .update_progress_with_percentage(reader, chapter_ids[1], 5, 50.0)
+        .await
+        .expect("Should save progress");
+
+    let counts = ProgressRepository::count_by_contents(&pool, reader, &[content_id])
+        .await
+        .expect("Should count chapters");
+    let counts = counts[&content_id];
+    assert_eq!((counts.read, counts.in_progress, counts.unread), (1, 1, 1));
+    assert_eq!(counts.unfinished(), 2);
+
+    let counts = ProgressRepository::count_by_contents(&pool, other, &[content_id])
+        .await
+        .expect("Should count chapters");
+    assert_eq!(counts[&content_id].unread, 3);
+
+    let chapter = ChapterRepository::find_by_id(&pool, chapter_ids[2])
+        .await
+        .expect("Should find chapter")
+        .expect("Chapter should exist");
+    assert!(chapter.created_at.is_some());
+}
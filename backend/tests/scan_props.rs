@@ -4,9 +4,10 @@
 //! title derivation, and scan path associations.
 
 use backend::db::{DbConfig, init_db};
-use backend::models::CreateLibraryRequest;
+use backend::models::{CreateLibraryRequest, UpdateLibraryRequest};
 use backend::services::library::LibraryService;
 use backend::services::scan_queue::ScanService;
+use backend::utils::title::TitleNormalizer;
 use proptest::prelude::*;
 use sqlx::{Pool, Sqlite};
 use std::fs;
@@ -56,6 +57,17 @@ fn create_test_content_folder(base_dir: &Path, folder_name: &str) -> PathBuf {
     content_folder
 }
 
+/// Minimal PNG: 1x1 transparent pixel
+const MINIMAL_PNG: [u8; 69] = [
+    0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, // PNG signature
+    0x00, 0x00, 0x00, 0x0D, 0x49, 0x48, 0x44, 0x52, // IHDR chunk
+    0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x02, 0x00, 0x00, 0x00, 0x90, 0x77, 0x53,
+    0xDE, 0x00, 0x00, 0x00, 0x0C, 0x49, 0x44, 0x41, // IDAT chunk
+    0x54, 0x08, 0xD7, 0x63, 0xF8, 0xFF, 0xFF, 0x3F, 0x00, 0x05, 0xFE, 0x02, 0xFE, 0xDC, 0xCC, 0x59,
+    0xE7, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4E, // IEND chunk
+    0x44, 0xAE, 0x42, 0x60, 0x82,
+];
+
 /// Create a minimal valid ZIP file with a dummy image.
 fn create_minimal_zip(path: &Path) {
     use std::io::Write;
@@ -70,18 +82,7 @@ fn create_minimal_zip(path: &Path) {
     zip.start_file("page001.png", options)
         .expect("Should start file in ZIP");
 
-    // Minimal PNG: 1x1 transparent pixel
-    let png_data: [u8; 69] = [
-        0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, // PNG signature
-        0x00, 0x00, 0x00, 0x0D, 0x49, 0x48, 0x44, 0x52, // IHDR chunk
-        0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x02, 0x00, 0x00, 0x00, 0x90, 0x77,
-        0x53, 0xDE, 0x00, 0x00, 0x00, 0x0C, 0x49, 0x44, 0x41, // IDAT chunk
-        0x54, 0x08, 0xD7, 0x63, 0xF8, 0xFF, 0xFF, 0x3F, 0x00, 0x05, 0xFE, 0x02, 0xFE, 0xDC, 0xCC,
-        0x59, 0xE7, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4E, // IEND chunk
-        0x44, 0xAE, 0x42, 0x60, 0x82,
-    ];
-
-    zip.write_all(&png_data).expect("Should write PNG data");
+    zip.write_all(&MINIMAL_PNG).expect("Should write PNG data");
     zip.finish().expect("Should finish ZIP");
 }
 
@@ -95,8 +96,9 @@ proptest! {
     /// **Feature: comic-reader, Property 8: Content Title Derivation**
     /// **Validates: Requirements 2.4**
     ///
-    /// For any imported content, the content title should equal the folder name
-    /// from which it was imported.
+    /// For any imported content, the folder title should equal the folder name
+    /// from which it was imported, and the title should be that name cleaned
+    /// by the title rules.
     #[test]
     fn content_title_derivation(
         library_name in arb_library_name(),
@@ -120,6 +122,8 @@ proptest! {
                 name: library_name,
                 scan_interval: None,
                 watch_mode: None,
+                scan_depth: None,
+                standalone_archives: None,
             };
             let library = library_service.create(req).await.expect("Should create library");
 
@@ -136,12 +140,17 @@ proptest! {
             // Verify content was added
             prop_assert_eq!(result.added.len(), 1, "Should have added exactly one content");
 
-            // Verify the content title matches the folder name
+            // Verify the content title is derived from the folder name
             let content = &result.added[0];
             prop_assert_eq!(
-                &content.title,
+                &content.folder_title,
                 &folder_name,
-                "Content title should equal folder name"
+                "Folder title should equal folder name"
+            );
+            prop_assert_eq!(
+                &content.title,
+                &TitleNormalizer::default().normalize(&folder_name),
+                "Content title should be the cleaned folder name"
             );
 
 
@@ -186,6 +195,8 @@ proptest! {
                 name: library_name,
                 scan_interval: None,
                 watch_mode: None,
+                scan_depth: None,
+                standalone_archives: None,
             };
             let library = library_service.create(req).await.expect("Should create library");
 
@@ -263,6 +274,8 @@ proptest! {
                 name: library_name,
                 scan_interval: None,
                 watch_mode: None,
+                scan_depth: None,
+                standalone_archives: None,
             };
             let library = library_service.create(req).await.expect("Should create library");
 
@@ -285,10 +298,10 @@ proptest! {
 
             // Find content from each scan path
             let content1 = result.added.iter()
-                .find(|c| c.title == folder_name1)
+                .find(|c| c.folder_title == folder_name1)
                 .expect("Should find content 1");
             let content2 = result.added.iter()
-                .find(|c| c.title == folder_name2)
+                .find(|c| c.folder_title == folder_name2)
                 .expect("Should find content 2");
 
             // Verify each content is associated with the correct scan path
@@ -307,3 +320,902 @@ proptest! {
         })?;
     }
 }
+
+/// New folders are inspected in parallel and written in batches; the result
+/// still lists every folder once, in natural order, with its chapters.
+#[test]
+fn parallel_import_keeps_discovery_order() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let pool = create_test_db().await;
+        let library_service = LibraryService::new(pool.clone());
+        let mut scan_service = ScanService::new(pool.clone());
+        scan_service.set_import_concurrency(3);
+
+        let temp_dir = TempDir::new().expect("Should create temp dir");
+        let base_path = temp_dir.path().to_path_buf();
+
+        // More folders than fit in one insert batch
+        let folder_count = 40;
+        for i in 1..=folder_count {
+            create_test_content_folder(&base_path, &format!("Series {}", i));
+        }
+
+        let req = CreateLibraryRequest {
+            name: "Library".to_string(),
+            scan_interval: None,
+            watch_mode: None,
+            scan_depth: None,
+            standalone_archives: None,
+        };
+        let library = library_service
+            .create(req)
+            .await
+            .expect("Should create library");
+        library_service
+            .add_scan_path(library.id, base_path.to_string_lossy().to_string())
+            .await
+            .expect("Should add scan path");
+
+        let result = scan_service
+            .scan_library(library.id)
+            .await
+            .expect("Should scan library");
+
+        let titles: Vec<String> = result.added.iter().map(|c| c.title.clone()).collect();
+        let expected: Vec<String> = (1..=folder_count)
+            .map(|i| format!("Series {}", i))
+            .collect();
+        assert_eq!(titles, expected);
+        assert_eq!(result.added_chapters.len(), folder_count);
+        assert!(result.added.iter().all(|c| c.chapter_count == 1));
+
+        // A second scan finds nothing new
+        let result = scan_service
+            .scan_library(library.id)
+            .await
+            .expect("Should rescan library");
+        assert!(result.added.is_empty());
+    });
+}
+
+/// Folders and archives matching the library's ignore patterns are skipped,
+/// and content already imported from a newly ignored folder is removed.
+#[test]
+fn ignore_patterns_skip_folders_and_files() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let pool = create_test_db().await;
+        let library_service = LibraryService::new(pool.clone());
+        let scan_service = ScanService::new(pool.clone());
+
+        let temp_dir = TempDir::new().expect("Should create temp dir");
+        let base_path = temp_dir.path().to_path_buf();
+
+        let series = create_test_content_folder(&base_path, "Series");
+        create_minimal_zip(&series.join("chapter02_raw.zip"));
+        create_test_content_folder(&base_path, "@eaDir");
+        create_test_content_folder(&base_path, ".hidden");
+        create_test_content_folder(&base_path, "Other");
+
+        let req = CreateLibraryRequest {
+            name: "Library".to_string(),
+            scan_interval: None,
+            watch_mode: None,
+            scan_depth: None,
+            standalone_archives: None,
+        };
+        let library = library_service
+            .create(req)
+            .await
+            .expect("Should create library");
+        library_service
+            .add_scan_path(library.id, base_path.to_string_lossy().to_string())
+            .await
+            .expect("Should add scan path");
+
+        // New libraries skip Synology and hidden folders by default
+        let result = scan_service
+            .scan_library(library.id)
+            .await
+            .expect("Should scan library");
+        let mut titles: Vec<&str> = result.added.iter().map(|c| c.title.as_str()).collect();
+        titles.sort();
+        assert_eq!(titles, vec!["Other", "Series"]);
+
+        let patterns = library_service
+            .set_ignore_patterns(
+                library.id,
+                vec![
+                    "@eaDir".to_string(),
+                    ".*".to_string(),
+                    "*_raw.zip".to_string(),
+                    " Other ".to_string(),
+                    "Other".to_string(),
+                ],
+            )
+            .await
+            .expect("Should set ignore patterns");
+        assert_eq!(patterns, vec!["@eaDir", ".*", "*_raw.zip", "Other"]);
+
+        let result = scan_service
+            .scan_library(library.id)
+            .await
+            .expect("Should rescan library");
+        assert_eq!(result.removed.len(), 1);
+
+        let contents =
+            backend::repository::content::ContentRepository::list_by_library(&pool, library.id)
+                .await
+                .unwrap();
+        assert_eq!(contents.len(), 1);
+        assert_eq!(contents[0].title, "Series");
+        assert_eq!(contents[0].chapter_count, 1);
+
+        let invalid = library_service
+            .set_ignore_patterns(library.id, vec!["a/b".to_string()])
+            .await;
+        assert!(invalid.is_err());
+    });
+}
+
+/// With a scan depth above 1, nested folders holding archives are imported
+/// and their title keeps the grouping folders; folders deeper than the
+/// configured depth are not discovered.
+#[test]
+fn scan_depth_discovers_nested_folders() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let pool = create_test_db().await;
+        let library_service = LibraryService::new(pool.clone());
+        let scan_service = ScanService::new(pool.clone());
+
+        let temp_dir = TempDir::new().expect("Should create temp dir");
+        let base_path = temp_dir.path().to_path_buf();
+
+        create_test_content_folder(&base_path, "Standalone");
+        create_test_content_folder(&base_path.join("Author"), "Series");
+        create_test_content_folder(&base_path.join("Author").join("Series"), "Season 1");
+        create_test_content_folder(&base_path.join("A").join("B"), "Too Deep");
+
+        let req = CreateLibraryRequest {
+            name: "Library".to_string(),
+            scan_interval: None,
+            watch_mode: None,
+            scan_depth: Some(2),
+            standalone_archives: None,
+        };
+        let library = library_service
+            .create(req)
+            .await
+            .expect("Should create library");
+        assert_eq!(library.scan_depth, 2);
+        library_service
+            .add_scan_path(library.id, base_path.to_string_lossy().to_string())
+            .await
+            .expect("Should add scan path");
+
+        let result = scan_service
+            .scan_library(library.id)
+            .await
+            .expect("Should scan library");
+        let titles: Vec<&str> = result.added.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, vec!["Author / Series", "Standalone"]);
+
+        let invalid = library_service
+            .create(CreateLibraryRequest {
+                name: "Invalid".to_string(),
+                scan_interval: None,
+                watch_mode: None,
+                scan_depth: Some(0),
+                standalone_archives: None,
+            })
+            .await;
+        assert!(invalid.is_err());
+    });
+}
+
+/// Folders of plain images are imported as chapters with their page count
+/// and a thumbnail, next to archive chapters in the same content folder.
+#[test]
+fn image_folders_are_imported_as_chapters() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let pool = create_test_db().await;
+        let library_service = LibraryService::new(pool.clone());
+        let scan_service = ScanService::new(pool.clone());
+
+        let temp_dir = TempDir::new().expect("Should create temp dir");
+        let base_path = temp_dir.path().to_path_buf();
+
+        let series = base_path.join("Series");
+        for (chapter, pages) in [("Vol.1", 2), ("Vol.2", 3)] {
+            let chapter_dir = series.join(chapter);
+            fs::create_dir_all(&chapter_dir).expect("Should create chapter folder");
+            for page in 1..=pages {
+                fs::write(chapter_dir.join(format!("{page:03}.png")), MINIMAL_PNG)
+                    .expect("Should write page");
+            }
+        }
+        create_minimal_zip(&series.join("Vol.3.zip"));
+        fs::create_dir_all(series.join("extras")).expect("Should create empty folder");
+
+        let req = CreateLibraryRequest {
+            name: "Library".to_string(),
+            scan_interval: None,
+            watch_mode: None,
+            scan_depth: None,
+            standalone_archives: None,
+        };
+        let library = library_service
+            .create(req)
+            .await
+            .expect("Should create library");
+        library_service
+            .add_scan_path(library.id, base_path.to_string_lossy().to_string())
+            .await
+            .expect("Should add scan path");
+
+        let result = scan_service
+            .scan_library(library.id)
+            .await
+            .expect("Should scan library");
+        assert_eq!(result.added.len(), 1);
+        assert!(result.added[0].thumbnail.is_some());
+
+        let chapters = backend::repository::content::ChapterRepository::list_by_content(
+            &pool,
+            result.added[0].id,
+        )
+        .await
+        .unwrap();
+        let summary: Vec<(&str, &str, i32)> = chapters
+            .iter()
+            .map(|c| (c.title.as_str(), c.file_type.as_str(), c.page_count))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Vol.1", "folder", 2),
+                ("Vol.2", "folder", 3),
+                ("Vol.3", "zip", 1)
+            ]
+        );
+    });
+}
+
+/// Archives lying directly in a scan path are only imported when the library
+/// enables standalone archives, each as a content with a single chapter.
+#[test]
+fn standalone_archives_are_imported_as_contents() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let pool = create_test_db().await;
+        let library_service = LibraryService::new(pool.clone());
+        let scan_service = ScanService::new(pool.clone());
+
+        let temp_dir = TempDir::new().expect("Should create temp dir");
+        let base_path = temp_dir.path().to_path_buf();
+
+        create_test_content_folder(&base_path, "Series");
+        create_minimal_zip(&base_path.join("One Shot.cbz"));
+
+        let req = CreateLibraryRequest {
+            name: "Library".to_string(),
+            scan_interval: None,
+            watch_mode: None,
+            scan_depth: None,
+            standalone_archives: None,
+        };
+        let library = library_service
+            .create(req)
+            .await
+            .expect("Should create library");
+        assert!(!library.standalone_archives);
+        library_service
+            .add_scan_path(library.id, base_path.to_string_lossy().to_string())
+            .await
+            .expect("Should add scan path");
+
+        let result = scan_service
+            .scan_library(library.id)
+            .await
+            .expect("Should scan library");
+        let titles: Vec<&str> = result.added.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, vec!["Series"]);
+
+        let update = UpdateLibraryRequest {
+            name: None,
+            scan_interval: None,
+            watch_mode: None,
+            scan_depth: None,
+            standalone_archives: Some(true),
+        };
+        library_service
+            .update(library.id, update)
+            .await
+            .expect("Should update library");
+
+        let result = scan_service
+            .scan_library(library.id)
+            .await
+            .expect("Should rescan library");
+        assert_eq!(result.added.len(), 1);
+        let one_shot = &result.added[0];
+        assert_eq!(one_shot.title, "One Shot");
+        assert_eq!(one_shot.chapter_count, 1);
+        assert!(one_shot.thumbnail.is_some());
+
+        let chapters =
+            backend::repository::content::ChapterRepository::list_by_content(&pool, one_shot.id)
+                .await
+                .unwrap();
+        assert_eq!(chapters.len(), 1);
+        assert_eq!(chapters[0].title, "One Shot");
+        assert_eq!(chapters[0].file_path, one_shot.folder_path);
+
+        // Rescanning keeps the standalone content as it is
+        let result = scan_service
+            .scan_library(library.id)
+            .await
+            .expect("Should rescan library");
+        assert!(result.added.is_empty());
+        assert!(result.removed.is_empty());
+    });
+}
+
+/// Renaming a content folder relinks the existing content and chapters to
+/// the new folder instead of deleting and importing them again.
+#[test]
+fn renamed_folder_keeps_content() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let pool = create_test_db().await;
+        let library_service = LibraryService::new(pool.clone());
+        let scan_service = ScanService::new(pool.clone());
+
+        let temp_dir = TempDir::new().expect("Should create temp dir");
+        let base_path = temp_dir.path().to_path_buf();
+
+        let series = create_test_content_folder(&base_path, "Series");
+        create_minimal_zip(&series.join("chapter02.zip"));
+        create_test_content_folder(&base_path, "Other");
+
+        let req = CreateLibraryRequest {
+            name: "Library".to_string(),
+            scan_interval: None,
+            watch_mode: None,
+            scan_depth: None,
+            standalone_archives: None,
+        };
+        let library = library_service
+            .create(req)
+            .await
+            .expect("Should create library");
+        library_service
+            .add_scan_path(library.id, base_path.to_string_lossy().to_string())
+            .await
+            .expect("Should add scan path");
+
+        let result = scan_service
+            .scan_library(library.id)
+            .await
+            .expect("Should scan library");
+        let original = result
+            .added
+            .iter()
+            .find(|c| c.title == "Series")
+            .expect("Should import series")
+            .clone();
+        let chapters =
+            backend::repository::content::ChapterRepository::list_by_content(&pool, original.id)
+                .await
+                .unwrap();
+
+        let renamed = base_path.join("Series Renamed");
+        fs::rename(&series, &renamed).expect("Should rename folder");
+
+        let result = scan_service
+            .scan_library(library.id)
+            .await
+            .expect("Should rescan library");
+        assert!(result.added.is_empty());
+        assert!(result.removed.is_empty());
+        assert_eq!(result.moved, vec![original.id]);
+
+        let content =
+            backend::repository::content::ContentRepository::find_by_id(&pool, original.id)
+                .await
+                .unwrap()
+                .expect("Content should still exist");
+        assert_eq!(content.title, "Series Renamed");
+        assert_eq!(content.folder_path, renamed.to_string_lossy());
+
+        let moved_chapters =
+            backend::repository::content::ChapterRepository::list_by_content(&pool, original.id)
+                .await
+                .unwrap();
+        assert_eq!(moved_chapters.len(), chapters.len());
+        for (before, after) in chapters.iter().zip(&moved_chapters) {
+            assert_eq!(before.id, after.id);
+            assert!(Path::new(&after.file_path).starts_with(&renamed));
+        }
+    });
+}
+
+/// A folder renamed to an alternative title of its content is relinked even
+/// when chapters were added at the same time.
+#[test]
+fn folder_renamed_to_alias_keeps_content() {
+    use backend::repository::alias::AliasRepository;
+    use backend::repository::content::{ChapterRepository, ContentRepository};
+
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let pool = create_test_db().await;
+        let library_service = LibraryService::new(pool.clone());
+        let scan_service = ScanService::new(pool.clone());
+
+        let temp_dir = TempDir::new().expect("Should create temp dir");
+        let base_path = temp_dir.path().to_path_buf();
+        let series = create_test_content_folder(&base_path, "Series");
+
+        let req = CreateLibraryRequest {
+            name: "Library".to_string(),
+            scan_interval: None,
+            watch_mode: None,
+            scan_depth: None,
+            standalone_archives: None,
+        };
+        let library = library_service
+            .create(req)
+            .await
+            .expect("Should create library");
+        library_service
+            .add_scan_path(library.id, base_path.to_string_lossy().to_string())
+            .await
+            .expect("Should add scan path");
+
+        let result = scan_service
+            .scan_library(library.id)
+            .await
+            .expect("Should scan library");
+        let original = result.added[0].clone();
+        let chapter = ChapterRepository::list_by_content(&pool, original.id)
+            .await
+            .unwrap()
+            .remove(0);
+        AliasRepository::add_manual(&pool, original.id, "Other Name")
+            .await
+            .expect("Should add alias");
+
+        let renamed = base_path.join("Other Name");
+        fs::rename(&series, &renamed).expect("Should rename folder");
+        create_minimal_zip(&renamed.join("chapter02.zip"));
+
+        let result = scan_service
+            .scan_library(library.id)
+            .await
+            .expect("Should rescan library");
+        assert!(result.added.is_empty());
+        assert!(result.removed.is_empty());
+        assert_eq!(result.moved, vec![original.id]);
+
+        let content = ContentRepository::find_by_id(&pool, original.id)
+            .await
+            .unwrap()
+            .expect("Content should still exist");
+        assert_eq!(content.title, "Other Name");
+        let relinked = ChapterRepository::find_by_id(&pool, chapter.id)
+            .await
+            .unwrap()
+            .expect("Chapter should be kept");
+        assert!(Path::new(&relinked.file_path).starts_with(&renamed));
+    });
+}
+
+/// Content whose folder disappears goes to the trash instead of being
+/// deleted, comes back when the folder reappears, and is only deleted once
+/// the trash is purged.
+#[test]
+fn missing_content_goes_to_trash() {
+    use backend::repository::content::ContentRepository;
+    use backend::services::content::ContentService;
+
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let pool = create_test_db().await;
+        let library_service = LibraryService::new(pool.clone());
+        let scan_service = ScanService::new(pool.clone());
+
+        let temp_dir = TempDir::new().expect("Should create temp dir");
+        let base_path = temp_dir.path().to_path_buf();
+        let series = create_test_content_folder(&base_path, "Series");
+
+        let req = CreateLibraryRequest {
+            name: "Library".to_string(),
+            scan_interval: None,
+            watch_mode: None,
+            scan_depth: None,
+            standalone_archives: None,
+        };
+        let library = library_service
+            .create(req)
+            .await
+            .expect("Should create library");
+        library_service
+            .add_scan_path(library.id, base_path.to_string_lossy().to_string())
+            .await
+            .expect("Should add scan path");
+
+        let result = scan_service
+            .scan_library(library.id)
+            .await
+            .expect("Should scan library");
+        let content_id = result.added[0].id;
+
+        // The folder disappears, e.g. because a share is not mounted
+        let outside = TempDir::new().expect("Should create temp dir");
+        let hidden = outside.path().join("Series");
+        fs::rename(&series, &hidden).expect("Should move folder away");
+        let result = scan_service
+            .scan_library(library.id)
+            .await
+            .expect("Should rescan library");
+        assert_eq!(result.removed, vec![content_id]);
+
+        let listed = ContentRepository::list_by_library(&pool, library.id)
+            .await
+            .unwrap();
+        assert!(listed.is_empty());
+        let trash = ContentService::list_trash(&pool).await.unwrap();
+        assert_eq!(trash.len(), 1);
+        assert!(trash[0].missing_since.is_some());
+
+        // Scanning again keeps it in the trash without reporting it twice
+        let result = scan_service
+            .scan_library(library.id)
+            .await
+            .expect("Should rescan library");
+        assert!(result.removed.is_empty());
+
+        // The folder comes back
+        fs::rename(&hidden, &series).expect("Should move folder back");
+        let result = scan_service
+            .scan_library(library.id)
+            .await
+            .expect("Should rescan library");
+        assert!(result.added.is_empty());
+        let content = ContentService::get_content(&pool, content_id)
+            .await
+            .unwrap();
+        assert!(content.missing_since.is_none());
+        assert!(
+            ContentService::restore_content(&pool, content_id)
+                .await
+                .is_err()
+        );
+
+        // Gone again, then purged by an administrator
+        fs::rename(&series, &hidden).expect("Should move folder away");
+        scan_service
+            .scan_library(library.id)
+            .await
+            .expect("Should rescan library");
+        assert_eq!(ContentService::purge_trash(&pool).await.unwrap(), 1);
+        assert!(
+            ContentRepository::find_by_id(&pool, content_id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    });
+}
+
+/// A scan preview reports what a scan would change without touching the
+/// database.
+#[test]
+fn scan_preview_does_not_write() {
+    use backend::repository::content::ContentRepository;
+
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let pool = create_test_db().await;
+        let library_service = LibraryService::new(pool.clone());
+        let scan_service = ScanService::new(pool.clone());
+
+        let temp_dir = TempDir::new().expect("Should create temp dir");
+        let base_path = temp_dir.path().to_path_buf();
+        let old_series = create_test_content_folder(&base_path, "Old Series");
+        let kept = create_test_content_folder(&base_path, "Kept");
+
+        let req = CreateLibraryRequest {
+            name: "Library".to_string(),
+            scan_interval: None,
+            watch_mode: None,
+            scan_depth: None,
+            standalone_archives: None,
+        };
+        let library = library_service
+            .create(req)
+            .await
+            .expect("Should create library");
+        library_service
+            .add_scan_path(library.id, base_path.to_string_lossy().to_string())
+            .await
+            .expect("Should add scan path");
+        scan_service
+            .scan_library(library.id)
+            .await
+            .expect("Should scan library");
+
+        // One folder disappears, one gains a chapter and one is new
+        fs::remove_dir_all(&old_series).expect("Should remove folder");
+        create_minimal_zip(&kept.join("chapter02.zip"));
+        create_test_content_folder(&base_path, "New Series");
+
+        let preview = scan_service
+            .preview_library(library.id)
+            .await
+            .expect("Should preview scan");
+        let added: Vec<&str> = preview.added.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(added, vec!["New Series"]);
+        assert_eq!(preview.added[0].chapter_count, 1);
+        let removed: Vec<&str> = preview.removed.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(removed, vec!["Old Series"]);
+        assert!(preview.moved.is_empty());
+        assert_eq!(preview.updated.len(), 1);
+        assert_eq!(preview.updated[0].title, "Kept");
+        assert_eq!(preview.updated[0].added_chapters, vec!["chapter02"]);
+
+        // Nothing was written
+        let listed = ContentRepository::list_by_library(&pool, library.id)
+            .await
+            .unwrap();
+        let mut titles: Vec<&str> = listed.iter().map(|c| c.title.as_str()).collect();
+        titles.sort();
+        assert_eq!(titles, vec!["Kept", "Old Series"]);
+        assert!(listed.iter().all(|c| c.chapter_count == 1));
+    });
+}
+
+/// Release tags in folder names are left out of content titles, while the
+/// folder name itself is kept.
+#[test]
+fn folder_titles_are_normalized() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let pool = create_test_db().await;
+        let library_service = LibraryService::new(pool.clone());
+        let scan_service = ScanService::new(pool.clone());
+
+        let temp_dir = TempDir::new().expect("Should create temp dir");
+        let base_path = temp_dir.path().to_path_buf();
+        create_test_content_folder(&base_path, "[Group] Series v01 (Digital)");
+
+        let req = CreateLibraryRequest {
+            name: "Library".to_string(),
+            scan_interval: None,
+            watch_mode: None,
+            scan_depth: None,
+            standalone_archives: None,
+        };
+        let library = library_service
+            .create(req)
+            .await
+            .expect("Should create library");
+        library_service
+            .add_scan_path(library.id, base_path.to_string_lossy().to_string())
+            .await
+            .expect("Should add scan path");
+
+        let result = scan_service
+            .scan_library(library.id)
+            .await
+            .expect("Should scan library");
+        assert_eq!(result.added.len(), 1);
+        assert_eq!(result.added[0].title, "Series");
+        assert_eq!(result.added[0].folder_title, "[Group] Series v01 (Digital)");
+    });
+}
+
+/// Entries of a chapter archive leading outside of it are left out of its
+/// page count and reported by the scan.
+#[test]
+fn suspicious_archive_entries_are_reported() {
+    use std::io::Write;
+
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let pool = create_test_db().await;
+        let library_service = LibraryService::new(pool.clone());
+        let scan_service = ScanService::new(pool.clone());
+
+        let temp_dir = TempDir::new().expect("Should create temp dir");
+        let base_path = temp_dir.path().to_path_buf();
+        let folder = create_test_content_folder(&base_path, "Series");
+
+        let file = fs::File::create(folder.join("chapter02.zip")).expect("Should create ZIP file");
+        let mut zip = zip::ZipWriter::new(file);
+        let options = zip::write::SimpleFileOptions::default();
+        for name in ["page001.png", "../escape.png"] {
+            zip.start_file(name, options)
+                .expect("Should start file in ZIP");
+            zip.write_all(&MINIMAL_PNG).expect("Should write PNG data");
+        }
+        zip.finish().expect("Should finish ZIP");
+
+        let req = CreateLibraryRequest {
+            name: "Library".to_string(),
+            scan_interval: None,
+            watch_mode: None,
+            scan_depth: None,
+            standalone_archives: None,
+        };
+        let library = library_service
+            .create(req)
+            .await
+            .expect("Should create library");
+        library_service
+            .add_scan_path(library.id, base_path.to_string_lossy().to_string())
+            .await
+            .expect("Should add scan path");
+
+        let result = scan_service
+            .scan_library(library.id)
+            .await
+            .expect("Should scan library");
+        assert_eq!(result.added.len(), 1);
+        assert_eq!(result.suspicious_archives.len(), 1);
+        assert!(
+            result.suspicious_archives[0]
+                .path
+                .ends_with("chapter02.zip")
+        );
+        assert_eq!(result.suspicious_archives[0].entries, vec!["../escape.png"]);
+
+        let chapters = backend::repository::content::ChapterRepository::list_by_content(
+            &pool,
+            result.added[0].id,
+        )
+        .await
+        .unwrap();
+        assert!(chapters.iter().all(|c| c.page_count == 1));
+    });
+}
+
+/// Chapters whose archive cannot be read are imported with an error status
+/// and no pages, and listed as media errors until the file is fixed.
+#[test]
+fn unreadable_chapters_are_reported() {
+    use backend::models::ChapterStatus;
+    use backend::repository::content::ChapterRepository;
+    use backend::services::content::ContentService;
+
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let pool = create_test_db().await;
+        let library_service = LibraryService::new(pool.clone());
+        let scan_service = ScanService::new(pool.clone());
+
+        let temp_dir = TempDir::new().expect("Should create temp dir");
+        let base_path = temp_dir.path().to_path_buf();
+        let folder = create_test_content_folder(&base_path, "Series");
+        let broken = folder.join("chapter02.zip");
+        fs::write(&broken, b"not a zip file").expect("Should write broken archive");
+
+        let req = CreateLibraryRequest {
+            name: "Library".to_string(),
+            scan_interval: None,
+            watch_mode: None,
+            scan_depth: None,
+            standalone_archives: None,
+        };
+        let library = library_service
+            .create(req)
+            .await
+            .expect("Should create library");
+        library_service
+            .add_scan_path(library.id, base_path.to_string_lossy().to_string())
+            .await
+            .expect("Should add scan path");
+
+        let result = scan_service
+            .scan_library(library.id)
+            .await
+            .expect("Should scan library");
+        assert_eq!(result.added.len(), 1);
+        let content_id = result.added[0].id;
+
+        let chapters = ChapterRepository::list_by_content(&pool, content_id)
+            .await
+            .unwrap();
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[0].status, ChapterStatus::Ready);
+        assert_eq!(chapters[1].status, ChapterStatus::Error);
+        assert_eq!(chapters[1].page_count, 0);
+        assert!(chapters[1].error.is_some());
+
+        let errors = ContentService::list_media_errors(&pool, Some(library.id))
+            .await
+            .unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].chapter_id, chapters[1].id);
+        assert_eq!(errors[0].content_title, "Series");
+
+        // Replacing the file and rescanning clears the error
+        create_minimal_zip(&broken);
+        scan_service
+            .scan_library(library.id)
+            .await
+            .expect("Should rescan library");
+        let chapters = ChapterRepository::list_by_content(&pool, content_id)
+            .await
+            .unwrap();
+        assert_eq!(chapters[1].status, ChapterStatus::Ready);
+        assert_eq!(chapters[1].page_count, 1);
+        assert_eq!(chapters[1].error, None);
+        assert!(
+            ContentService::list_media_errors(&pool, None)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    });
+}
+
+/// A scan path that cannot be scanned does not stop the others, and each
+/// scan path gets its own statistics.
+#[test]
+fn scan_paths_are_reported_separately() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let pool = create_test_db().await;
+        let library_service = LibraryService::new(pool.clone());
+        let scan_service = ScanService::new(pool.clone());
+
+        let temp_dir = TempDir::new().expect("Should create temp dir");
+        let available = temp_dir.path().join("available");
+        let unmounted = temp_dir.path().join("unmounted");
+        create_test_content_folder(&available, "Series");
+        create_test_content_folder(&unmounted, "Other");
+
+        let req = CreateLibraryRequest {
+            name: "Library".to_string(),
+            scan_interval: None,
+            watch_mode: None,
+            scan_depth: None,
+            standalone_archives: None,
+        };
+        let library = library_service
+            .create(req)
+            .await
+            .expect("Should create library");
+        for path in [&available, &unmounted] {
+            library_service
+                .add_scan_path(library.id, path.to_string_lossy().to_string())
+                .await
+                .expect("Should add scan path");
+        }
+        fs::remove_dir_all(&unmounted).expect("Should remove scan path folder");
+
+        let result = scan_service
+            .scan_library(library.id)
+            .await
+            .expect("Should scan library");
+        assert_eq!(result.added.len(), 1);
+        assert_eq!(result.scan_paths.len(), 2);
+
+        let stats = &result.scan_paths[0];
+        assert_eq!(stats.path, available.to_string_lossy());
+        assert_eq!(stats.folders_discovered, 1);
+        assert_eq!(stats.added, 1);
+        assert_eq!(stats.errors, 0);
+        assert_eq!(stats.error, None);
+
+        let failed = &result.scan_paths[1];
+        assert_eq!(failed.path, unmounted.to_string_lossy());
+        assert_eq!(failed.added, 0);
+        assert!(failed.error.is_some());
+    });
+}
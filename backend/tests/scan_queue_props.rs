@@ -17,7 +17,11 @@ use uuid::Uuid;
 
 /// Strategy to generate a random TaskPriority.
 fn arb_priority() -> impl Strategy<Value = TaskPriority> {
-    prop_oneof![Just(TaskPriority::Normal), Just(TaskPriority::High),]
+    prop_oneof![
+        Just(TaskPriority::Low),
+        Just(TaskPriority::Normal),
+        Just(TaskPriority::High),
+    ]
 }
 
 /// Strategy to generate a random time offset in seconds (for creating different timestamps).
@@ -159,8 +163,8 @@ proptest! {
     /// **Validates: Requirements 1.3, 5.3**
     ///
     /// For any sequence of random tasks added to the queue, the dequeue order
-    /// should always satisfy: no Normal priority task is dequeued while High
-    /// priority tasks remain in the queue.
+    /// should always satisfy: no task is dequeued while tasks of a higher
+    /// priority remain in the queue.
     #[test]
     fn no_priority_inversion(task_count in 2usize..20) {
         let base_time = Utc::now();
@@ -168,10 +172,10 @@ proptest! {
         // Generate random tasks
         let mut tasks: Vec<QueuedTask> = Vec::new();
         for i in 0..task_count {
-            let priority = if i % 3 == 0 {
-                TaskPriority::High
-            } else {
-                TaskPriority::Normal
+            let priority = match i % 3 {
+                0 => TaskPriority::High,
+                1 => TaskPriority::Normal,
+                _ => TaskPriority::Low,
             };
             tasks.push(QueuedTask::new(
                 Uuid::new_v4(),
@@ -186,17 +190,54 @@ proptest! {
             heap.push(task);
         }
 
-        // Track if we've seen a Normal priority task
-        let mut seen_normal = false;
+        // Track the lowest priority seen so far
+        let mut lowest_seen = TaskPriority::High;
 
         while let Some(task) = heap.pop() {
-            if task.priority == TaskPriority::Normal {
-                seen_normal = true;
+            prop_assert!(
+                task.priority <= lowest_seen,
+                "{:?} priority task found after {:?} priority task - priority inversion!",
+                task.priority,
+                lowest_seen
+            );
+            lowest_seen = task.priority;
+        }
+    }
+
+    /// **Feature: scan-queue, Property 2: Queue Ordering by Priority and Time**
+    /// **Validates: Requirements 1.3, 5.3**
+    ///
+    /// For any set of pending tasks, a task moved to the front of the queue
+    /// should be dequeued first, whatever its priority and the priorities of
+    /// the other tasks.
+    #[test]
+    fn moved_task_runs_first(
+        priorities in prop::collection::vec(arb_priority(), 2..10),
+        moved_index in any::<prop::sample::Index>(),
+    ) {
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            let service = ScanQueueService::new();
+
+            let mut task_ids = Vec::new();
+            for (i, priority) in priorities.iter().enumerate() {
+                task_ids.push(service.submit_task(i as i64 + 1, *priority).await);
             }
-            if seen_normal && task.priority == TaskPriority::High {
-                prop_assert!(false, "High priority task found after Normal priority task - priority inversion!");
+
+            let moved_id = task_ids[moved_index.index(task_ids.len())];
+            service.move_to_front(moved_id).await.unwrap();
+
+            let pending = service.list_pending().await;
+            prop_assert_eq!(pending.len(), task_ids.len());
+            prop_assert_eq!(pending[0].id, moved_id, "Moved task should run first");
+
+            // The other tasks keep their priority order
+            for pair in pending[1..].windows(2) {
+                prop_assert!(pair[0].priority >= pair[1].priority);
             }
-        }
+
+            Ok(())
+        })?;
     }
 }
 
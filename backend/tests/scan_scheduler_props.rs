@@ -187,6 +187,8 @@ async fn create_library_with_paths(pool: &Pool<Sqlite>, num_paths: usize) -> Tes
         name: format!("Test Library {}", uuid::Uuid::new_v4()),
         scan_interval: None,
         watch_mode: Some(true),
+        scan_depth: None,
+        standalone_archives: None,
     };
     let library = service.create(req).await.expect("Should create library");
 
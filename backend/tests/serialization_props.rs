@@ -7,9 +7,9 @@
 //! deserializing should produce an equivalent data structure.
 
 use backend::models::{
-    Chapter, Content, ContentResponse, CreateLibraryRequest, JwtClaims, Library, LibraryWithStats,
-    LoginRequest, LoginResponse, ProgressResponse, ReadingProgress, ScanPath, UpdateLibraryRequest,
-    UpdateProgressRequest, User, UserResponse,
+    Chapter, ChapterStatus, Content, ContentResponse, CreateLibraryRequest, JwtClaims, Library,
+    LibraryWithStats, LoginRequest, LoginResponse, ProgressResponse, ReadingProgress, ScanPath,
+    UpdateLibraryRequest, UpdateProgressRequest, User, UserResponse,
 };
 use chrono::{DateTime, TimeZone, Utc};
 use proptest::prelude::*;
@@ -57,15 +57,28 @@ fn arb_library() -> impl Strategy<Value = Library> {
         arb_name(),
         0i32..1440,
         any::<bool>(),
+        1i32..=8,
+        any::<bool>(),
         arb_datetime(),
         arb_datetime(),
     )
         .prop_map(
-            |(id, name, scan_interval, watch_mode, created_at, updated_at)| Library {
+            |(
                 id,
                 name,
                 scan_interval,
                 watch_mode,
+                scan_depth,
+                standalone_archives,
+                created_at,
+                updated_at,
+            )| Library {
+                id,
+                name,
+                scan_interval,
+                watch_mode,
+                scan_depth,
+                standalone_archives,
                 created_at,
                 updated_at,
             },
@@ -94,6 +107,7 @@ fn arb_content() -> impl Strategy<Value = Content> {
         arb_path(),
         0i32..1000,
         arb_metadata(),
+        prop::option::of(arb_datetime()),
         arb_datetime(),
         arb_datetime(),
     )
@@ -106,17 +120,24 @@ fn arb_content() -> impl Strategy<Value = Content> {
                 folder_path,
                 chapter_count,
                 metadata,
+                missing_since,
                 created_at,
                 updated_at,
             )| Content {
                 id,
                 library_id,
                 scan_path_id,
+                folder_title: title.clone(),
                 title,
                 folder_path,
                 chapter_count,
                 thumbnail: None, // Skip thumbnail for serialization tests
+                thumbnail_etag: None,
+                thumbnail_blurhash: None,
                 metadata: metadata.and_then(|m| serde_json::to_vec(&m).ok()),
+                missing_since,
+                series_group_id: None,
+                age_rating: None,
                 created_at,
                 updated_at,
             },
@@ -148,6 +169,12 @@ fn arb_chapter() -> impl Strategy<Value = Chapter> {
                 sort_order,
                 size,
                 page_count: 0, // Skip page_count for serialization tests
+                volume: None,
+                number: None,
+                status: ChapterStatus::Ready,
+                error: None,
+                created_at: None,
+                updated_at: None,
             },
         )
 }
@@ -168,6 +195,12 @@ fn arb_user() -> impl Strategy<Value = User> {
                 username,
                 password_hash,
                 bangumi_api_key,
+                totp_secret: None,
+                totp_enabled: false,
+                is_admin: false,
+                locale: None,
+                max_age_rating: None,
+                token_version: 0,
                 created_at,
                 updated_at,
             },
@@ -192,6 +225,9 @@ fn arb_reading_progress() -> impl Strategy<Value = ReadingProgress> {
                 position,
                 percentage,
                 updated_at,
+                novel_section: None,
+                novel_offset: None,
+                novel_cfi: None,
             },
         )
 }
@@ -209,6 +245,7 @@ fn arb_jwt_claims() -> impl Strategy<Value = JwtClaims> {
             username,
             exp,
             iat,
+            ver: 0,
         })
 }
 
@@ -389,12 +426,20 @@ fn arb_create_library_request() -> impl Strategy<Value = CreateLibraryRequest> {
         arb_name(),
         prop::option::of(0i32..1440),
         prop::option::of(any::<bool>()),
+        prop::option::of(1i32..=8),
+        prop::option::of(any::<bool>()),
     )
-        .prop_map(|(name, scan_interval, watch_mode)| CreateLibraryRequest {
-            name,
-            scan_interval,
-            watch_mode,
-        })
+        .prop_map(
+            |(name, scan_interval, watch_mode, scan_depth, standalone_archives)| {
+                CreateLibraryRequest {
+                    name,
+                    scan_interval,
+                    watch_mode,
+                    scan_depth,
+                    standalone_archives,
+                }
+            },
+        )
 }
 
 /// Strategy to generate arbitrary UpdateLibraryRequest instances.
@@ -403,12 +448,20 @@ fn arb_update_library_request() -> impl Strategy<Value = UpdateLibraryRequest> {
         prop::option::of(arb_name()),
         prop::option::of(0i32..1440),
         prop::option::of(any::<bool>()),
+        prop::option::of(1i32..=8),
+        prop::option::of(any::<bool>()),
     )
-        .prop_map(|(name, scan_interval, watch_mode)| UpdateLibraryRequest {
-            name,
-            scan_interval,
-            watch_mode,
-        })
+        .prop_map(
+            |(name, scan_interval, watch_mode, scan_depth, standalone_archives)| {
+                UpdateLibraryRequest {
+                    name,
+                    scan_interval,
+                    watch_mode,
+                    scan_depth,
+                    standalone_archives,
+                }
+            },
+        )
 }
 
 /// Strategy to generate arbitrary UpdateProgressRequest instances.
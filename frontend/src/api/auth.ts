@@ -8,10 +8,15 @@
 
 import { ApiClient } from "./client";
 import type {
+    ChangePasswordRequest,
     LoginRequest,
     LoginResponse,
+    PasswordResetResponse,
+    ResetPasswordRequest,
     UserResponse,
     UpdateUserRequest,
+    UserPreferences,
+    UpdatePreferencesRequest,
 } from "./types";
 
 /**
@@ -21,6 +26,16 @@ export interface AuthApi {
     login(username: string, password: string): Promise<LoginResponse>;
     getMe(): Promise<UserResponse>;
     updateMe(request: UpdateUserRequest): Promise<UserResponse>;
+    changePassword(
+        currentPassword: string,
+        newPassword: string
+    ): Promise<LoginResponse>;
+    resetPassword(token: string, newPassword: string): Promise<void>;
+    createPasswordReset(userId: number): Promise<PasswordResetResponse>;
+    getPreferences(): Promise<UserPreferences>;
+    updatePreferences(
+        request: UpdatePreferencesRequest
+    ): Promise<UserPreferences>;
 }
 
 /**
@@ -73,5 +88,82 @@ export function createAuthApi(client: ApiClient): AuthApi {
         async updateMe(request: UpdateUserRequest): Promise<UserResponse> {
             return client.put<UserResponse>("/api/auth/me", request);
         },
+
+        /**
+         * Changes the current user's password. Every other session is
+         * signed out.
+         *
+         * @param currentPassword - The current password
+         * @param newPassword - The new password
+         * @returns The user and a fresh token replacing the current one
+         */
+        async changePassword(
+            currentPassword: string,
+            newPassword: string
+        ): Promise<LoginResponse> {
+            const request: ChangePasswordRequest = {
+                current_password: currentPassword,
+                new_password: newPassword,
+            };
+            return client.post<LoginResponse>(
+                "/api/users/me/password",
+                request
+            );
+        },
+
+        /**
+         * Sets a new password with a reset token issued by an administrator.
+         * The user logs in again afterwards.
+         *
+         * @param token - The one-time reset token
+         * @param newPassword - The new password
+         */
+        async resetPassword(token: string, newPassword: string): Promise<void> {
+            const request: ResetPasswordRequest = {
+                token,
+                new_password: newPassword,
+            };
+            await client.post<void>("/api/auth/password-reset", request, {
+                requiresAuth: false,
+            });
+        },
+
+        /**
+         * Issues a one-time password reset token for a user (admin only).
+         *
+         * @param userId - The user ID
+         * @returns The token, shown only once, and when it expires
+         */
+        async createPasswordReset(
+            userId: number
+        ): Promise<PasswordResetResponse> {
+            return client.post<PasswordResetResponse>(
+                `/api/admin/users/${userId}/password-reset`
+            );
+        },
+
+        /**
+         * Retrieves the current user's preferences.
+         *
+         * @returns Locale, theme, reading defaults and page size
+         */
+        async getPreferences(): Promise<UserPreferences> {
+            return client.get<UserPreferences>("/api/users/me/preferences");
+        },
+
+        /**
+         * Updates the current user's preferences.
+         *
+         * @param request - Fields to change; a null locale uses the server locale
+         * @returns The updated preferences
+         */
+        async updatePreferences(
+            request: UpdatePreferencesRequest
+        ): Promise<UserPreferences> {
+            return client.patch<UserPreferences>(
+                "/api/users/me/preferences",
+                request
+            );
+        },
     };
 }
@@ -0,0 +1,102 @@
+/**
+ * Bookmark API Module
+ *
+ * Provides functions for creating, listing, editing and deleting the
+ * current user's bookmarks and notes.
+ */
+
+import { ApiClient } from "./client";
+import type {
+    BookmarkResponse,
+    CreateBookmarkRequest,
+} from "./types";
+
+/**
+ * Bookmark API interface.
+ */
+export interface BookmarkApi {
+    list(contentId?: number): Promise<BookmarkResponse[]>;
+    listForContent(contentId: number): Promise<BookmarkResponse[]>;
+    create(
+        chapterId: number,
+        data: CreateBookmarkRequest
+    ): Promise<BookmarkResponse>;
+    updateNote(id: number, note: string | null): Promise<BookmarkResponse>;
+    delete(id: number): Promise<void>;
+}
+
+/**
+ * Creates a Bookmark API instance using the provided API client.
+ *
+ * @param client - The API client to use for HTTP requests
+ * @returns A BookmarkApi implementation
+ */
+export function createBookmarkApi(client: ApiClient): BookmarkApi {
+    return {
+        /**
+         * Lists the current user's bookmarks, newest first.
+         *
+         * @param contentId - Optional content to limit the list to
+         * @returns Array of bookmarks
+         */
+        async list(contentId?: number): Promise<BookmarkResponse[]> {
+            return client.get<BookmarkResponse[]>("/api/bookmarks", {
+                params: { content_id: contentId },
+            });
+        },
+
+        /**
+         * Lists the current user's bookmarks in a content in reading order.
+         *
+         * @param contentId - The content ID
+         * @returns Array of bookmarks
+         */
+        async listForContent(contentId: number): Promise<BookmarkResponse[]> {
+            return client.get<BookmarkResponse[]>(
+                `/api/contents/${contentId}/bookmarks`
+            );
+        },
+
+        /**
+         * Creates a bookmark in a chapter.
+         *
+         * @param chapterId - The chapter ID
+         * @param data - Position, optional novel position and note
+         * @returns The created bookmark
+         */
+        async create(
+            chapterId: number,
+            data: CreateBookmarkRequest
+        ): Promise<BookmarkResponse> {
+            return client.post<BookmarkResponse>(
+                `/api/chapters/${chapterId}/bookmarks`,
+                data
+            );
+        },
+
+        /**
+         * Replaces the note of a bookmark.
+         *
+         * @param id - The bookmark ID
+         * @param note - The new note, or null to remove it
+         * @returns The updated bookmark
+         */
+        async updateNote(
+            id: number,
+            note: string | null
+        ): Promise<BookmarkResponse> {
+            return client.put<BookmarkResponse>(`/api/bookmarks/${id}`, {
+                note,
+            });
+        },
+
+        /**
+         * Deletes a bookmark.
+         *
+         * @param id - The bookmark ID
+         */
+        async delete(id: number): Promise<void> {
+            return client.delete<void>(`/api/bookmarks/${id}`);
+        },
+    };
+}
@@ -78,7 +78,8 @@ export function buildAuthHeader(token: string): string {
 
 /**
  * Parses an error response body and extracts the error message.
- * Backend returns errors in format: { "error": "message" }
+ * Backend returns RFC 7807 problem documents:
+ * { "status": 404, "detail": "message", "code": "LIBRARY_NOT_FOUND", ... }
  */
 async function parseErrorResponse(response: Response): Promise<ApiError> {
     try {
@@ -91,7 +92,23 @@ async function parseErrorResponse(response: Response): Promise<ApiError> {
         }
 
         try {
-            const json = JSON.parse(body) as { error?: string | { code?: number; message?: string } };
+            const json = JSON.parse(body) as {
+                status?: number;
+                detail?: string;
+                code?: string;
+                details?: unknown;
+                request_id?: string;
+                error?: string | { code?: number; message?: string };
+            };
+            if (typeof json.code === "string" && json.detail) {
+                return new ApiError(
+                    json.status ?? response.status,
+                    json.detail,
+                    json.details,
+                    json.code,
+                    json.request_id ?? response.headers.get("X-Request-Id") ?? undefined
+                );
+            }
             if (json.error) {
                 // Handle both string and object error formats
                 if (typeof json.error === 'string') {
@@ -180,6 +197,17 @@ export class ApiClient {
         return this.request<T>("PUT", path, body, options);
     }
 
+    /**
+     * Performs a PATCH request.
+     */
+    async patch<T>(
+        path: string,
+        body?: unknown,
+        options?: RequestOptions
+    ): Promise<T> {
+        return this.request<T>("PATCH", path, body, options);
+    }
+
     /**
      * Performs a DELETE request.
      */
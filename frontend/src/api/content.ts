@@ -9,27 +9,135 @@
 
 import { ApiClient } from "./client";
 import type {
+    AudioInfo,
+    ContentAlias,
+    ContentFeedQuery,
+    ContentPage,
     ContentResponse,
+    ContentStatus,
+    ContentStatusFilter,
     Chapter,
+    DownloadFormat,
+    EpubManifest,
+    EpubSection,
+    MetadataEdits,
+    MetadataFailure,
+    MetadataMatchRequest,
+    MetadataProviderKind,
+    MetadataRetryResult,
+    MetadataSearchResult,
+    MoveContentRequest,
+    ReaderSettingsResponse,
+    ScanPreview,
+    SeriesGroup,
     SubmitScanResponse,
+    TextSearchMatch,
+    ThumbnailPreview,
+    TxtSplitInfo,
+    TxtSplitText,
     UpdateContentRequest,
+    UpdateContentStatusRequest,
+    UpdateMetadataEditsRequest,
+    UpdateReaderSettingsRequest,
+    UpdatesPage,
+    UpdatesQuery,
 } from "./types";
 
 /**
  * Content API interface.
  */
 export interface ContentApi {
-    list(libraryId: number): Promise<ContentResponse[]>;
-    search(libraryId: number, query: string): Promise<ContentResponse[]>;
+    list(
+        libraryId: number,
+        filter?: ContentStatusFilter
+    ): Promise<ContentResponse[]>;
+    search(
+        libraryId: number,
+        query: string,
+        filter?: ContentStatusFilter
+    ): Promise<ContentResponse[]>;
+    listRecent(query?: ContentFeedQuery): Promise<ContentPage>;
+    listUpdated(query?: ContentFeedQuery): Promise<ContentPage>;
+    listFollowedUpdates(query?: UpdatesQuery): Promise<UpdatesPage>;
     get(id: number): Promise<ContentResponse>;
+    listSeriesGroups(): Promise<SeriesGroup[]>;
+    createSeriesGroup(name: string): Promise<SeriesGroup>;
+    deleteSeriesGroup(id: number): Promise<void>;
+    listSeriesGroupContents(groupId: number): Promise<ContentResponse[]>;
+    setSeriesGroup(id: number, groupId: number | null): Promise<ContentResponse>;
+    listStatuses(filter?: ContentStatusFilter): Promise<ContentStatus[]>;
+    getStatus(id: number): Promise<ContentStatus>;
+    updateStatus(
+        id: number,
+        data: UpdateContentStatusRequest
+    ): Promise<ContentStatus>;
+    getMetadataEdits(id: number): Promise<MetadataEdits>;
+    updateMetadataEdits(
+        id: number,
+        data: UpdateMetadataEditsRequest
+    ): Promise<MetadataEdits>;
+    getSettings(id: number): Promise<ReaderSettingsResponse>;
+    updateSettings(
+        id: number,
+        data: UpdateReaderSettingsRequest
+    ): Promise<ReaderSettingsResponse>;
     delete(id: number): Promise<void>;
+    move(id: number, data: MoveContentRequest): Promise<ContentResponse>;
     update(
         id: number,
         data: UpdateContentRequest
     ): Promise<ContentResponse>;
+    searchMetadata(
+        query: string,
+        provider?: MetadataProviderKind
+    ): Promise<MetadataSearchResult[]>;
+    matchMetadata(
+        id: number,
+        data: MetadataMatchRequest
+    ): Promise<ContentResponse>;
+    listMetadataFailures(): Promise<MetadataFailure[]>;
+    retryMetadataFailure(
+        id: number,
+        refreshCache?: boolean
+    ): Promise<MetadataRetryResult>;
+    dismissMetadataFailure(id: number): Promise<void>;
+    listAliases(id: number): Promise<ContentAlias[]>;
+    addAlias(id: number, title: string): Promise<ContentAlias>;
+    deleteAlias(id: number, aliasId: number): Promise<void>;
     listChapters(contentId: number): Promise<Chapter[]>;
+    getEpubManifest(contentId: number, chapterId: number): Promise<EpubManifest>;
+    getEpubSection(
+        contentId: number,
+        chapterId: number,
+        index: number
+    ): Promise<EpubSection>;
+    listTxtSplits(contentId: number, chapterId: number): Promise<TxtSplitInfo[]>;
+    getTxtSplit(
+        contentId: number,
+        chapterId: number,
+        index: number
+    ): Promise<TxtSplitText>;
+    searchText(
+        contentId: number,
+        query: string,
+        limit?: number
+    ): Promise<TextSearchMatch[]>;
+    getAudioInfo(contentId: number, chapterId: number): Promise<AudioInfo>;
+    getAudioStreamUrl(contentId: number, chapterId: number): string;
     triggerScan(libraryId: number): Promise<SubmitScanResponse>;
+    previewScan(libraryId: number): Promise<ScanPreview>;
+    regenerateThumbnails(libraryId: number): Promise<SubmitScanResponse>;
     getThumbnail(id: number): string;
+    getThumbnailPreviews(
+        ids: number[],
+        width?: number
+    ): Promise<Record<number, ThumbnailPreview>>;
+    getChapterDownloadUrl(
+        contentId: number,
+        chapterId: number,
+        format?: DownloadFormat
+    ): string;
+    getSeriesDownloadUrl(contentId: number, format?: DownloadFormat): string;
 }
 
 /**
@@ -46,11 +154,18 @@ export function createContentApi(client: ApiClient): ContentApi {
          * **Implements: Requirement 4.1**
          *
          * @param libraryId - The library ID
+         * @param filter - Optional favorite and reading status filter
          * @returns Array of content items
          */
-        async list(libraryId: number): Promise<ContentResponse[]> {
+        async list(
+            libraryId: number,
+            filter?: ContentStatusFilter
+        ): Promise<ContentResponse[]> {
             return client.get<ContentResponse[]>(
-                `/api/libraries/${libraryId}/contents`
+                `/api/libraries/${libraryId}/contents`,
+                {
+                    params: { ...filter },
+                }
             );
         },
 
@@ -61,20 +176,58 @@ export function createContentApi(client: ApiClient): ContentApi {
          *
          * @param libraryId - The library ID
          * @param query - The search query string
+         * @param filter - Optional favorite and reading status filter
          * @returns Array of matching content items
          */
         async search(
             libraryId: number,
-            query: string
+            query: string,
+            filter?: ContentStatusFilter
         ): Promise<ContentResponse[]> {
             return client.get<ContentResponse[]>(
                 `/api/libraries/${libraryId}/search`,
                 {
-                    params: { q: query },
+                    params: { ...filter, q: query },
                 }
             );
         },
 
+        /**
+         * Lists contents across all libraries, most recently added first.
+         *
+         * @param query - Optional library filter and paging
+         * @returns A page of content items
+         */
+        async listRecent(query?: ContentFeedQuery): Promise<ContentPage> {
+            return client.get<ContentPage>("/api/contents/recent", {
+                params: { ...query },
+            });
+        },
+
+        /**
+         * Lists contents across all libraries, most recently updated first.
+         *
+         * @param query - Optional library filter and paging
+         * @returns A page of content items
+         */
+        async listUpdated(query?: ContentFeedQuery): Promise<ContentPage> {
+            return client.get<ContentPage>("/api/contents/updated", {
+                params: { ...query },
+            });
+        },
+
+        /**
+         * Lists new chapters in the series the current user follows, newest first.
+         *
+         * @param query - Optional paging
+         * @returns A page of new chapters
+         */
+        async listFollowedUpdates(query?: UpdatesQuery): Promise<UpdatesPage> {
+            return client.get<UpdatesPage>("/api/users/me/updates", {
+                params: { ...query },
+            });
+        },
+
         /**
          * Gets a specific content by ID.
          *
@@ -87,6 +240,162 @@ export function createContentApi(client: ApiClient): ContentApi {
             return client.get<ContentResponse>(`/api/contents/${id}`);
         },
 
+        /**
+         * Lists all series groups by name.
+         *
+         * @returns Array of series groups
+         */
+        async listSeriesGroups(): Promise<SeriesGroup[]> {
+            return client.get<SeriesGroup[]>("/api/series-groups");
+        },
+
+        /**
+         * Creates a series group.
+         *
+         * @param name - The unique group name
+         * @returns The created group
+         */
+        async createSeriesGroup(name: string): Promise<SeriesGroup> {
+            return client.post<SeriesGroup>("/api/series-groups", { name });
+        },
+
+        /**
+         * Deletes a series group; its contents are kept ungrouped.
+         *
+         * @param id - The group ID
+         */
+        async deleteSeriesGroup(id: number): Promise<void> {
+            return client.delete<void>(`/api/series-groups/${id}`);
+        },
+
+        /**
+         * Lists the contents of a series group by title.
+         *
+         * @param groupId - The group ID
+         * @returns Array of content items
+         */
+        async listSeriesGroupContents(
+            groupId: number
+        ): Promise<ContentResponse[]> {
+            return client.get<ContentResponse[]>(
+                `/api/series-groups/${groupId}/contents`
+            );
+        },
+
+        /**
+         * Puts a content into a series group, or takes it out with null.
+         *
+         * @param id - The content ID
+         * @param groupId - The group ID, or null
+         * @returns The updated content
+         */
+        async setSeriesGroup(
+            id: number,
+            groupId: number | null
+        ): Promise<ContentResponse> {
+            return client.put<ContentResponse>(
+                `/api/contents/${id}/series-group`,
+                { group_id: groupId }
+            );
+        },
+
+        /**
+         * Lists the current user's favorites and reading statuses.
+         *
+         * @param filter - Optional favorite and reading status filter
+         * @returns Statuses, most recently changed first
+         */
+        async listStatuses(
+            filter?: ContentStatusFilter
+        ): Promise<ContentStatus[]> {
+            return client.get<ContentStatus[]>("/api/contents/status", {
+                params: { ...filter },
+            });
+        },
+
+        /**
+         * Gets the current user's favorite flag and reading status for a content.
+         *
+         * @param id - The content ID
+         * @returns The status, unset if the user has not marked the content
+         */
+        async getStatus(id: number): Promise<ContentStatus> {
+            return client.get<ContentStatus>(`/api/contents/${id}/status`);
+        },
+
+        /**
+         * Replaces the current user's favorite flag and reading status for a content.
+         *
+         * @param id - The content ID
+         * @param data - The favorite flag and status
+         * @returns The stored status
+         */
+        async updateStatus(
+            id: number,
+            data: UpdateContentStatusRequest
+        ): Promise<ContentStatus> {
+            return client.put<ContentStatus>(
+                `/api/contents/${id}/status`,
+                data
+            );
+        },
+
+        /**
+         * Gets the metadata of a content edited by hand, with field locks.
+         *
+         * @param id - The content ID
+         * @returns The edits; null fields show the scraped metadata
+         */
+        async getMetadataEdits(id: number): Promise<MetadataEdits> {
+            return client.get<MetadataEdits>(`/api/contents/${id}/metadata`);
+        },
+
+        /**
+         * Edits the metadata of a content by hand.
+         *
+         * @param id - The content ID
+         * @param data - Fields to change; null goes back to the scraped value
+         * @returns The stored edits
+         */
+        async updateMetadataEdits(
+            id: number,
+            data: UpdateMetadataEditsRequest
+        ): Promise<MetadataEdits> {
+            return client.patch<MetadataEdits>(
+                `/api/contents/${id}/metadata`,
+                data
+            );
+        },
+
+        /**
+         * Gets the reader settings of a content, with those inherited from its library.
+         *
+         * @param id - The content ID
+         * @returns Settings in effect, overrides and inherited settings
+         */
+        async getSettings(id: number): Promise<ReaderSettingsResponse> {
+            return client.get<ReaderSettingsResponse>(
+                `/api/contents/${id}/settings`
+            );
+        },
+
+        /**
+         * Updates the reader settings of a content.
+         *
+         * @param id - The content ID
+         * @param data - Fields to change; null goes back to the library default
+         * @returns The updated settings
+         */
+        async updateSettings(
+            id: number,
+            data: UpdateReaderSettingsRequest
+        ): Promise<ReaderSettingsResponse> {
+            return client.patch<ReaderSettingsResponse>(
+                `/api/contents/${id}/settings`,
+                data
+            );
+        },
+
         /**
          * Deletes a content and all associated chapters.
          *
@@ -98,6 +407,24 @@ export function createContentApi(client: ApiClient): ContentApi {
             await client.delete<void>(`/api/contents/${id}`);
         },
 
+        /**
+         * Moves a content to another scan path, keeping its chapters,
+         * reading progress and metadata. Requires admin.
+         *
+         * @param id - The content ID
+         * @param data - Target scan path and whether to move the folder
+         * @returns The moved content
+         */
+        async move(
+            id: number,
+            data: MoveContentRequest
+        ): Promise<ContentResponse> {
+            return client.post<ContentResponse>(
+                `/api/contents/${id}/move`,
+                data
+            );
+        },
+
         /**
          * Updates content information.
          *
@@ -117,6 +444,109 @@ export function createContentApi(client: ApiClient): ContentApi {
             );
         },
 
+        /**
+         * Searches metadata providers by title.
+         *
+         * @param query - The title to search for
+         * @param provider - Only search this provider
+         * @returns Matching series, grouped by provider
+         */
+        async searchMetadata(
+            query: string,
+            provider?: MetadataProviderKind
+        ): Promise<MetadataSearchResult[]> {
+            return client.get<MetadataSearchResult[]>("/api/metadata/search", {
+                params: { q: query, provider },
+            });
+        },
+
+        /**
+         * Replaces the metadata of a content with a series from a provider.
+         *
+         * @param id - The content ID
+         * @param data - The provider and the ID of the series there
+         * @returns The updated content
+         */
+        async matchMetadata(
+            id: number,
+            data: MetadataMatchRequest
+        ): Promise<ContentResponse> {
+            return client.post<ContentResponse>(
+                `/api/contents/${id}/metadata/match`,
+                data
+            );
+        },
+
+        /**
+         * Lists content whose metadata could not be scraped.
+         *
+         * @returns Failed scrapes, most recent first
+         */
+        async listMetadataFailures(): Promise<MetadataFailure[]> {
+            return client.get<MetadataFailure[]>("/api/metadata/failures");
+        },
+
+        /**
+         * Scrapes the metadata of a failed content again.
+         *
+         * @param id - The failure ID
+         * @param refreshCache - Ask providers again instead of using cached responses
+         * @returns Whether metadata was found, or the updated failure
+         */
+        async retryMetadataFailure(
+            id: number,
+            refreshCache = false
+        ): Promise<MetadataRetryResult> {
+            return client.post<MetadataRetryResult>(
+                `/api/metadata/failures/${id}/retry`,
+                undefined,
+                { params: { refresh_cache: refreshCache } }
+            );
+        },
+
+        /**
+         * Dismisses a failed scrape.
+         *
+         * @param id - The failure ID
+         */
+        async dismissMetadataFailure(id: number): Promise<void> {
+            await client.delete<void>(`/api/metadata/failures/${id}`);
+        },
+
+        /**
+         * Lists the alternative titles of a content.
+         *
+         * @param id - The content ID
+         * @returns Alternative titles, by title
+         */
+        async listAliases(id: number): Promise<ContentAlias[]> {
+            return client.get<ContentAlias[]>(`/api/contents/${id}/aliases`);
+        },
+
+        /**
+         * Adds an alternative title to a content. Titles added by hand
+         * survive metadata rescrapes.
+         *
+         * @param id - The content ID
+         * @param title - The alternative title
+         * @returns The stored alias
+         */
+        async addAlias(id: number, title: string): Promise<ContentAlias> {
+            return client.post<ContentAlias>(`/api/contents/${id}/aliases`, {
+                title,
+            });
+        },
+
+        /**
+         * Removes an alternative title from a content.
+         *
+         * @param id - The content ID
+         * @param aliasId - The alias ID
+         */
+        async deleteAlias(id: number, aliasId: number): Promise<void> {
+            await client.delete<void>(`/api/contents/${id}/aliases/${aliasId}`);
+        },
+
         /**
          * Lists all chapters for a content.
          *
@@ -129,6 +559,116 @@ export function createContentApi(client: ApiClient): ContentApi {
             return client.get<Chapter[]>(`/api/contents/${contentId}/chapters`);
         },
 
+        /**
+         * Gets the reading order and table of contents of an EPUB chapter.
+         *
+         * @param contentId - The content ID
+         * @param chapterId - The chapter ID
+         * @returns The EPUB manifest
+         */
+        async getEpubManifest(contentId: number, chapterId: number): Promise<EpubManifest> {
+            return client.get<EpubManifest>(
+                `/api/contents/${contentId}/chapters/${chapterId}/epub`
+            );
+        },
+
+        /**
+         * Gets a section of an EPUB chapter as sanitized HTML.
+         *
+         * @param contentId - The content ID
+         * @param chapterId - The chapter ID
+         * @param index - Position of the section in the reading order
+         * @returns The section HTML and its stylesheet URLs
+         */
+        async getEpubSection(
+            contentId: number,
+            chapterId: number,
+            index: number
+        ): Promise<EpubSection> {
+            return client.get<EpubSection>(
+                `/api/contents/${contentId}/chapters/${chapterId}/epub/sections/${index}`
+            );
+        },
+
+        /**
+         * Lists the virtual chapters a `.txt` novel is split into.
+         *
+         * @param contentId - The content ID
+         * @param chapterId - The chapter ID
+         * @returns The virtual chapters in reading order
+         */
+        async listTxtSplits(contentId: number, chapterId: number): Promise<TxtSplitInfo[]> {
+            return client.get<TxtSplitInfo[]>(
+                `/api/contents/${contentId}/chapters/${chapterId}/splits`
+            );
+        },
+
+        /**
+         * Gets the text of a virtual chapter of a `.txt` novel.
+         *
+         * @param contentId - The content ID
+         * @param chapterId - The chapter ID
+         * @param index - Index of the virtual chapter
+         * @returns The virtual chapter's title and text
+         */
+        async getTxtSplit(
+            contentId: number,
+            chapterId: number,
+            index: number
+        ): Promise<TxtSplitText> {
+            return client.get<TxtSplitText>(
+                `/api/contents/${contentId}/chapters/${chapterId}/splits/${index}`
+            );
+        },
+
+        /**
+         * Searches the text of a novel's chapters, ignoring case.
+         *
+         * @param contentId - The content ID
+         * @param query - The text to look for
+         * @param limit - Most matches to return (default 50)
+         * @returns Matches with their chapter, offset and snippet, in reading order
+         */
+        async searchText(
+            contentId: number,
+            query: string,
+            limit?: number
+        ): Promise<TextSearchMatch[]> {
+            return client.get<TextSearchMatch[]>(`/api/contents/${contentId}/search`, {
+                params: { q: query, limit },
+            });
+        },
+
+        /**
+         * Gets the duration and chapter markers of an audiobook chapter.
+         *
+         * @param contentId - The content ID
+         * @param chapterId - The chapter ID
+         * @returns The audiobook's duration, markers and title
+         */
+        async getAudioInfo(
+            contentId: number,
+            chapterId: number
+        ): Promise<AudioInfo> {
+            return client.get<AudioInfo>(
+                `/api/contents/${contentId}/chapters/${chapterId}/audio`
+            );
+        },
+
+        /**
+         * Gets the stream URL of an audiobook chapter, usable as an
+         * `<audio>` source. The server answers range requests for seeking.
+         *
+         * @param contentId - The content ID
+         * @param chapterId - The chapter ID
+         * @returns The audio stream URL
+         */
+        getAudioStreamUrl(contentId: number, chapterId: number): string {
+            return client.buildAuthenticatedUrl(
+                `/api/contents/${contentId}/chapters/${chapterId}/audio/stream`
+            );
+        },
+
         /**
          * Triggers a library scan with high priority.
          *
@@ -143,6 +683,30 @@ export function createContentApi(client: ApiClient): ContentApi {
             );
         },
 
+        /**
+         * Shows what a library scan would change, without changing anything.
+         *
+         * @param libraryId - The library ID to preview
+         * @returns The content a scan would add, remove, move or update
+         */
+        async previewScan(libraryId: number): Promise<ScanPreview> {
+            return client.post<ScanPreview>(
+                `/api/libraries/${libraryId}/scan/preview`
+            );
+        },
+
+        /**
+         * Queues regeneration of a library's missing and outdated thumbnails.
+         *
+         * @param libraryId - The library ID
+         * @returns The thumbnail task response with task ID
+         */
+        async regenerateThumbnails(libraryId: number): Promise<SubmitScanResponse> {
+            return client.post<SubmitScanResponse>(
+                `/api/libraries/${libraryId}/thumbnails/regenerate`
+            );
+        },
+
         /**
          * Gets the thumbnail image URL for a content.
          *
@@ -154,5 +718,58 @@ export function createContentApi(client: ApiClient): ContentApi {
         getThumbnail(id: number): string {
             return client.buildAuthenticatedUrl(`/api/contents/${id}/thumbnail`);
         },
+
+        /**
+         * Gets small previews of the thumbnails of up to 200 contents in one
+         * request. Contents without a thumbnail are left out.
+         *
+         * @param ids - The content IDs
+         * @param width - Largest preview width in pixels (default 96)
+         * @returns Base64 encoded previews by content ID
+         */
+        async getThumbnailPreviews(
+            ids: number[],
+            width?: number
+        ): Promise<Record<number, ThumbnailPreview>> {
+            return client.post<Record<number, ThumbnailPreview>>(
+                "/api/contents/thumbnails",
+                { ids, width }
+            );
+        },
+
+        /**
+         * Gets the download URL for a chapter file.
+         *
+         * @param contentId - The content ID
+         * @param chapterId - The chapter ID
+         * @param format - `cbz` to repack RAR chapters; image folders are always CBZ
+         * @returns The chapter download URL
+         */
+        getChapterDownloadUrl(
+            contentId: number,
+            chapterId: number,
+            format: DownloadFormat = "original"
+        ): string {
+            return client.buildAuthenticatedUrl(
+                `/api/contents/${contentId}/chapters/${chapterId}/download`,
+                { format }
+            );
+        },
+
+        /**
+         * Gets the download URL for a ZIP of all chapters of a content.
+         *
+         * @param contentId - The content ID
+         * @param format - `cbz` to store RAR chapters and image folders as CBZ
+         * @returns The series download URL
+         */
+        getSeriesDownloadUrl(
+            contentId: number,
+            format: DownloadFormat = "original"
+        ): string {
+            return client.buildAuthenticatedUrl(`/api/contents/${contentId}/download`, {
+                format,
+            });
+        },
     };
 }
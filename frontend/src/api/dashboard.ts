@@ -0,0 +1,38 @@
+/**
+ * Dashboard API Module
+ *
+ * Provides the home page summary in a single request.
+ */
+
+import { ApiClient } from "./client";
+import type { Dashboard } from "./types";
+
+/**
+ * Dashboard API interface.
+ */
+export interface DashboardApi {
+    get(limit?: number): Promise<Dashboard>;
+}
+
+/**
+ * Creates a Dashboard API instance using the provided API client.
+ *
+ * @param client - The API client to use for HTTP requests
+ * @returns A DashboardApi implementation
+ */
+export function createDashboardApi(client: ApiClient): DashboardApi {
+    return {
+        /**
+         * Gets the libraries, recently added and recently read contents,
+         * active tasks and, for administrators, error counts.
+         *
+         * @param limit - Max number of contents per list (default 10)
+         * @returns The dashboard
+         */
+        async get(limit?: number): Promise<Dashboard> {
+            return client.get<Dashboard>("/api/dashboard", {
+                params: { limit },
+            });
+        },
+    };
+}
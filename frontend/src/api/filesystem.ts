@@ -1,20 +1,41 @@
 import { ApiClient } from "./client";
 
+export type EntryKind = "directory" | "file";
+
 export interface DirectoryEntry {
     name: string;
     path: string;
-    parent?: string;
+    parent?: string | null;
+    kind: EntryKind;
+    /** Size in bytes, for files. */
+    size?: number | null;
+    symlink: boolean;
+    readable: boolean;
+    readonly: boolean;
+}
+
+export interface ListDirectoriesOptions {
+    /** Include hidden entries. */
+    hidden?: boolean;
+    /** Include files, not only directories. */
+    files?: boolean;
 }
 
 export interface FilesystemApi {
-    listDirectories(path?: string): Promise<DirectoryEntry[]>;
+    listDirectories(
+        path?: string,
+        options?: ListDirectoriesOptions,
+    ): Promise<DirectoryEntry[]>;
 }
 
 export function createFilesystemApi(client: ApiClient): FilesystemApi {
     return {
-        async listDirectories(path?: string): Promise<DirectoryEntry[]> {
+        async listDirectories(
+            path?: string,
+            options: ListDirectoriesOptions = {},
+        ): Promise<DirectoryEntry[]> {
             return client.get<DirectoryEntry[]>("/api/filesystem", {
-                params: { path },
+                params: { path, ...options },
             });
         },
     };
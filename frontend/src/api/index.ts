@@ -6,4 +6,9 @@ export * from './content';
 export * from './progress';
 export * from './bangumi';
 export * from './apikey';
-export * from './filesystem';
\ No newline at end of file
+export * from './filesystem';
+export * from './bookmark';
+export * from './review';
+export * from './share';
+export * from './quota';
+export * from './dashboard';
\ No newline at end of file
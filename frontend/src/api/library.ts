@@ -14,6 +14,9 @@ import type {
     CreateLibraryRequest,
     UpdateLibraryRequest,
     ScanPath,
+    ReaderSettingsResponse,
+    UpdateReaderSettingsRequest,
+    SubmitScanResponse,
 } from "./types";
 
 /**
@@ -31,10 +34,15 @@ export interface LibraryApi {
     create(request: CreateLibraryRequest): Promise<Library>;
     get(id: number): Promise<LibraryWithStats>;
     update(id: number, request: UpdateLibraryRequest): Promise<Library>;
-    delete(id: number): Promise<void>;
+    delete(id: number): Promise<SubmitScanResponse>;
     listScanPaths(libraryId: number): Promise<ScanPath[]>;
     addScanPath(libraryId: number, path: string): Promise<ScanPath>;
     removeScanPath(libraryId: number, pathId: number): Promise<void>;
+    getReaderSettings(libraryId: number): Promise<ReaderSettingsResponse>;
+    updateReaderSettings(
+        libraryId: number,
+        request: UpdateReaderSettingsRequest
+    ): Promise<ReaderSettingsResponse>;
 }
 
 /**
@@ -97,14 +105,15 @@ export function createLibraryApi(client: ApiClient): LibraryApi {
         },
 
         /**
-         * Deletes a library.
+         * Deletes a library. The library is hidden at once and removed by a
+         * background task, which is returned.
          *
          * **Implements: Requirement 3.5**
          *
          * @param id - The library ID
          */
-        async delete(id: number): Promise<void> {
-            await client.delete<void>(`/api/libraries/${id}`);
+        async delete(id: number): Promise<SubmitScanResponse> {
+            return client.delete<SubmitScanResponse>(`/api/libraries/${id}`);
         },
 
         /**
@@ -149,5 +158,36 @@ export function createLibraryApi(client: ApiClient): LibraryApi {
                 `/api/libraries/${libraryId}/paths/${pathId}`
             );
         },
+
+        /**
+         * Gets the reader defaults of a library.
+         *
+         * @param libraryId - The library ID
+         * @returns Settings in effect, overrides and built-in defaults
+         */
+        async getReaderSettings(
+            libraryId: number
+        ): Promise<ReaderSettingsResponse> {
+            return client.get<ReaderSettingsResponse>(
+                `/api/libraries/${libraryId}/settings`
+            );
+        },
+
+        /**
+         * Updates the reader defaults of a library.
+         *
+         * @param libraryId - The library ID
+         * @param request - Fields to change; null goes back to the built-in default
+         * @returns The updated settings
+         */
+        async updateReaderSettings(
+            libraryId: number,
+            request: UpdateReaderSettingsRequest
+        ): Promise<ReaderSettingsResponse> {
+            return client.patch<ReaderSettingsResponse>(
+                `/api/libraries/${libraryId}/settings`,
+                request
+            );
+        },
     };
 }
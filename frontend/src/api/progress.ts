@@ -8,7 +8,12 @@
  */
 
 import { ApiClient } from "./client";
-import type { ProgressResponse, ContentResponse } from "./types";
+import type {
+    ProgressResponse,
+    ContentResponse,
+    NovelPosition,
+    SplitProgressResponse,
+} from "./types";
 
 /**
  * Progress API interface.
@@ -20,8 +25,15 @@ export interface ProgressApi {
     updateChapterProgress(
         chapterId: number,
         position: number,
-        percentage?: number
+        percentage?: number,
+        novel?: NovelPosition
     ): Promise<ProgressResponse>;
+    getSplitProgress(chapterId: number): Promise<SplitProgressResponse[]>;
+    updateSplitProgress(
+        chapterId: number,
+        splitIndex: number,
+        position: number
+    ): Promise<SplitProgressResponse>;
 }
 
 /**
@@ -84,23 +96,66 @@ export function createProgressApi(client: ApiClient): ProgressApi {
          * @param chapterId - The chapter ID
          * @param position - Current position within the chapter
          * @param percentage - Optional percentage (0.0 to 100.0)
+         * @param novel - Optional place in the text of a novel chapter
          * @returns The updated progress
          */
         async updateChapterProgress(
             chapterId: number,
             position: number,
-            percentage?: number
+            percentage?: number,
+            novel?: NovelPosition
         ): Promise<ProgressResponse> {
-            const body: { position: number; percentage?: number } = {
+            const body: {
+                position: number;
+                percentage?: number;
+                novel?: NovelPosition;
+            } = {
                 position,
             };
             if (percentage !== undefined) {
                 body.percentage = percentage;
             }
+            if (novel !== undefined) {
+                body.novel = novel;
+            }
             return client.put<ProgressResponse>(
                 `/api/chapters/${chapterId}/progress`,
                 body
             );
         },
+
+        /**
+         * Gets the reading positions inside the virtual chapters of a
+         * `.txt` chapter.
+         *
+         * @param chapterId - The chapter ID
+         * @returns The position in each virtual chapter that was read
+         */
+        async getSplitProgress(
+            chapterId: number
+        ): Promise<SplitProgressResponse[]> {
+            return client.get<SplitProgressResponse[]>(
+                `/api/chapters/${chapterId}/splits/progress`
+            );
+        },
+
+        /**
+         * Updates the reading position inside a virtual chapter.
+         *
+         * @param chapterId - The chapter ID
+         * @param splitIndex - Index of the virtual chapter
+         * @param position - Character offset within the virtual chapter
+         * @returns The updated position
+         */
+        async updateSplitProgress(
+            chapterId: number,
+            splitIndex: number,
+            position: number
+        ): Promise<SplitProgressResponse> {
+            return client.put<SplitProgressResponse>(
+                `/api/chapters/${chapterId}/splits/${splitIndex}/progress`,
+                { position }
+            );
+        },
     };
 }
\ No newline at end of file
@@ -0,0 +1,54 @@
+/**
+ * Quota API Module
+ *
+ * Provides functions for reading and setting the daily request and
+ * transfer quotas of users (admin only).
+ */
+
+import { ApiClient } from "./client";
+import type { UpdateUserQuotaRequest, UserQuota } from "./types";
+
+/**
+ * Quota API interface.
+ */
+export interface QuotaApi {
+    get(userId: number): Promise<UserQuota>;
+    update(userId: number, data: UpdateUserQuotaRequest): Promise<UserQuota>;
+}
+
+/**
+ * Creates a Quota API instance using the provided API client.
+ *
+ * @param client - The API client to use for HTTP requests
+ * @returns A QuotaApi implementation
+ */
+export function createQuotaApi(client: ApiClient): QuotaApi {
+    return {
+        /**
+         * Gets the daily limits of a user and their usage today.
+         *
+         * @param userId - The user ID
+         * @returns The user's quota
+         */
+        async get(userId: number): Promise<UserQuota> {
+            return client.get<UserQuota>(`/api/admin/users/${userId}/quota`);
+        },
+
+        /**
+         * Sets the daily limits of a user.
+         *
+         * @param userId - The user ID
+         * @param data - The new limits
+         * @returns The updated quota
+         */
+        async update(
+            userId: number,
+            data: UpdateUserQuotaRequest
+        ): Promise<UserQuota> {
+            return client.put<UserQuota>(
+                `/api/admin/users/${userId}/quota`,
+                data
+            );
+        },
+    };
+}
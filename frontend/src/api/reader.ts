@@ -8,7 +8,7 @@
  */
 
 import { ApiClient, buildUrl } from "./client";
-import type { ChapterTextResponse } from "./types";
+import type { ChapterTextResponse, PageImageOptions, PageInfo } from "./types";
 
 /**
  * Reader API interface.
@@ -19,7 +19,18 @@ export interface ReaderApi {
         contentId: number,
         chapter: number
     ): Promise<ChapterTextResponse>;
-    getPageImage(contentId: number, chapterId: number, page: number): string;
+    getPageImage(
+        contentId: number,
+        chapterId: number,
+        page: number,
+        options?: PageImageOptions
+    ): string;
+    listPages(
+        contentId: number,
+        chapterId: number,
+        split?: boolean,
+        slice?: boolean
+    ): Promise<PageInfo[]>;
 }
 
 /**
@@ -66,13 +77,45 @@ export function createReaderApi(client: ApiClient): ReaderApi {
             );
         },
 
+        /**
+         * Gets the authenticated URL of a comic page image.
+         *
+         * @param contentId - The content ID
+         * @param chapterId - The chapter ID
+         * @param page - The page index (0-based)
+         * @param options - Optional processing, such as one half of a spread
+         * @returns The URL string for the page image
+         */
         getPageImage(
             contentId: number,
             chapterId: number,
-            page: number
+            page: number,
+            options?: PageImageOptions
         ): string {
             return client.buildAuthenticatedUrl(
-                `/api/contents/${contentId}/chapters/${chapterId}/pages/${page}`
+                `/api/contents/${contentId}/chapters/${chapterId}/pages/${page}`,
+                { ...options }
+            );
+        },
+
+        /**
+         * Lists the pages of a comic chapter with their sizes.
+         *
+         * @param contentId - The content ID
+         * @param chapterId - The chapter ID
+         * @param split - List double-page spreads as two virtual pages
+         * @param slice - List tall pages as their tiles
+         * @returns Pages in reading order
+         */
+        async listPages(
+            contentId: number,
+            chapterId: number,
+            split = false,
+            slice = false
+        ): Promise<PageInfo[]> {
+            return client.get<PageInfo[]>(
+                `/api/contents/${contentId}/chapters/${chapterId}/pages`,
+                { params: { split, slice } }
             );
         },
     };
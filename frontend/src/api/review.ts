@@ -0,0 +1,84 @@
+/**
+ * Review API Module
+ *
+ * Provides functions for rating contents and reading the ratings and
+ * reviews other users gave them.
+ */
+
+import { ApiClient } from "./client";
+import type {
+    ContentReview,
+    ContentReviews,
+    SaveReviewRequest,
+} from "./types";
+
+/**
+ * Review API interface.
+ */
+export interface ReviewApi {
+    list(contentId: number): Promise<ContentReviews>;
+    getMine(contentId: number): Promise<ContentReview>;
+    save(contentId: number, data: SaveReviewRequest): Promise<ContentReview>;
+    delete(contentId: number): Promise<void>;
+}
+
+/**
+ * Creates a Review API instance using the provided API client.
+ *
+ * @param client - The API client to use for HTTP requests
+ * @returns A ReviewApi implementation
+ */
+export function createReviewApi(client: ApiClient): ReviewApi {
+    return {
+        /**
+         * Lists every user's rating of a content with their average.
+         *
+         * @param contentId - The content ID
+         * @returns The ratings, most recently changed first
+         */
+        async list(contentId: number): Promise<ContentReviews> {
+            return client.get<ContentReviews>(
+                `/api/contents/${contentId}/reviews`
+            );
+        },
+
+        /**
+         * Gets the current user's rating of a content. Fails with 404 if
+         * they have not rated it.
+         *
+         * @param contentId - The content ID
+         * @returns The current user's rating
+         */
+        async getMine(contentId: number): Promise<ContentReview> {
+            return client.get<ContentReview>(
+                `/api/contents/${contentId}/reviews/me`
+            );
+        },
+
+        /**
+         * Rates a content, replacing the current user's earlier rating.
+         *
+         * @param contentId - The content ID
+         * @param data - Rating from 1 to 10 and optional review
+         * @returns The saved rating
+         */
+        async save(
+            contentId: number,
+            data: SaveReviewRequest
+        ): Promise<ContentReview> {
+            return client.put<ContentReview>(
+                `/api/contents/${contentId}/reviews/me`,
+                data
+            );
+        },
+
+        /**
+         * Removes the current user's rating of a content.
+         *
+         * @param contentId - The content ID
+         */
+        async delete(contentId: number): Promise<void> {
+            return client.delete<void>(`/api/contents/${contentId}/reviews/me`);
+        },
+    };
+}
@@ -0,0 +1,156 @@
+/**
+ * Share API Module
+ *
+ * Provides functions for creating and revoking public share links, and
+ * for reading what a link shares without logging in.
+ */
+
+import { ApiClient, buildUrl } from "./client";
+import type {
+    CreateShareLinkRequest,
+    PageInfo,
+    ShareLink,
+    SharedContent,
+} from "./types";
+
+/**
+ * Share API interface.
+ */
+export interface ShareApi {
+    create(
+        contentId: number,
+        data?: CreateShareLinkRequest
+    ): Promise<ShareLink>;
+    listMine(): Promise<ShareLink[]>;
+    revoke(id: number): Promise<void>;
+    listAll(): Promise<ShareLink[]>;
+    revokeAny(id: number): Promise<void>;
+    getShared(token: string): Promise<SharedContent>;
+    listSharedPages(token: string, chapterId: number): Promise<PageInfo[]>;
+    getSharedThumbnailUrl(token: string, etag?: string | null): string;
+    getSharedPageUrl(token: string, chapterId: number, page: number): string;
+}
+
+/**
+ * Creates a Share API instance using the provided API client.
+ *
+ * @param client - The API client to use for HTTP requests
+ * @returns A ShareApi implementation
+ */
+export function createShareApi(client: ApiClient): ShareApi {
+    return {
+        /**
+         * Shares a content, or only one of its chapters. Fails with 403
+         * while sharing is disabled on the server.
+         *
+         * @param contentId - The content ID
+         * @param data - Chapter to share and lifetime of the link
+         * @returns The created link
+         */
+        async create(
+            contentId: number,
+            data: CreateShareLinkRequest = {}
+        ): Promise<ShareLink> {
+            return client.post<ShareLink>(
+                `/api/contents/${contentId}/shares`,
+                data
+            );
+        },
+
+        /**
+         * Lists the current user's share links, including expired ones.
+         *
+         * @returns The links, newest first
+         */
+        async listMine(): Promise<ShareLink[]> {
+            return client.get<ShareLink[]>("/api/shares");
+        },
+
+        /**
+         * Revokes one of the current user's share links.
+         *
+         * @param id - The share link ID
+         */
+        async revoke(id: number): Promise<void> {
+            return client.delete<void>(`/api/shares/${id}`);
+        },
+
+        /**
+         * Lists the share links of every user (admin only).
+         *
+         * @returns The links, newest first
+         */
+        async listAll(): Promise<ShareLink[]> {
+            return client.get<ShareLink[]>("/api/admin/shares");
+        },
+
+        /**
+         * Revokes a share link of any user (admin only).
+         *
+         * @param id - The share link ID
+         */
+        async revokeAny(id: number): Promise<void> {
+            return client.delete<void>(`/api/admin/shares/${id}`);
+        },
+
+        /**
+         * Gets the title and readable chapters of a shared content.
+         *
+         * @param token - The share link token
+         * @returns The shared content
+         */
+        async getShared(token: string): Promise<SharedContent> {
+            return client.get<SharedContent>(`/api/shared/${token}`);
+        },
+
+        /**
+         * Lists the pages of a shared comic chapter.
+         *
+         * @param token - The share link token
+         * @param chapterId - The chapter ID
+         * @returns The pages with their sizes
+         */
+        async listSharedPages(
+            token: string,
+            chapterId: number
+        ): Promise<PageInfo[]> {
+            return client.get<PageInfo[]>(
+                `/api/shared/${token}/chapters/${chapterId}/pages`
+            );
+        },
+
+        /**
+         * Gets the URL of the thumbnail of a shared content.
+         *
+         * @param token - The share link token
+         * @param etag - Thumbnail version, to cache it for good
+         * @returns The thumbnail URL
+         */
+        getSharedThumbnailUrl(token: string, etag?: string | null): string {
+            const query = etag ? `?v=${encodeURIComponent(etag)}` : "";
+            return buildUrl(
+                client.baseUrl,
+                `/api/shared/${token}/thumbnail${query}`
+            );
+        },
+
+        /**
+         * Gets the URL of a page image of a shared comic chapter.
+         *
+         * @param token - The share link token
+         * @param chapterId - The chapter ID
+         * @param page - The page index (0-based)
+         * @returns The page URL
+         */
+        getSharedPageUrl(
+            token: string,
+            chapterId: number,
+            page: number
+        ): string {
+            return buildUrl(
+                client.baseUrl,
+                `/api/shared/${token}/chapters/${chapterId}/pages/${page}`
+            );
+        },
+    };
+}
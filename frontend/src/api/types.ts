@@ -15,12 +15,24 @@
 export class ApiError extends Error {
     readonly status: number;
     readonly details?: unknown;
+    /** Machine-readable error code, e.g. `LIBRARY_NOT_FOUND` */
+    readonly code?: string;
+    /** Server-side request ID, also found in the backend logs */
+    readonly requestId?: string;
 
-    constructor(status: number, message: string, details?: unknown) {
+    constructor(
+        status: number,
+        message: string,
+        details?: unknown,
+        code?: string,
+        requestId?: string
+    ) {
         super(message);
         this.name = "ApiError";
         this.status = status;
         this.details = details;
+        this.code = code;
+        this.requestId = requestId;
     }
 
     isUnauthorized(): boolean {
@@ -44,12 +56,27 @@ export class ApiError extends Error {
  * Task priority for scan operations.
  */
 export const TaskPriority = {
+    Low: "Low",
     Normal: "Normal",
     High: "High",
 } as const;
 
 export type TaskPriority = (typeof TaskPriority)[keyof typeof TaskPriority];
 
+/**
+ * Kind of work a queued task does.
+ */
+export const TaskKind = {
+    Scan: "Scan",
+    Thumbnails: "Thumbnails",
+    PageCounts: "PageCounts",
+    MetadataRefresh: "MetadataRefresh",
+    Backup: "Backup",
+    LibraryDeletion: "LibraryDeletion",
+} as const;
+
+export type TaskKind = (typeof TaskKind)[keyof typeof TaskKind];
+
 /**
  * Task status for scan operations.
  */
@@ -98,11 +125,33 @@ export interface UserResponse {
  */
 export interface UpdateUserRequest {
     username?: string;
-    password?: string;
-    old_password?: string;
     bangumi_api_key?: string | null;
 }
 
+/**
+ * Request to change the password of the current user.
+ */
+export interface ChangePasswordRequest {
+    current_password: string;
+    new_password: string;
+}
+
+/**
+ * One-time password reset token issued by an administrator.
+ */
+export interface PasswordResetResponse {
+    token: string;
+    expires_at: string;
+}
+
+/**
+ * Request to set a new password with a reset token.
+ */
+export interface ResetPasswordRequest {
+    token: string;
+    new_password: string;
+}
+
 /**
  * Request to create a new API key.
  */
@@ -132,6 +181,8 @@ export interface Library {
     name: string;
     scan_interval: number;
     watch_mode: boolean;
+    scan_depth: number;
+    standalone_archives: boolean;
     created_at: string;
     updated_at: string;
 }
@@ -151,6 +202,8 @@ export interface CreateLibraryRequest {
     name: string;
     scan_interval?: number;
     watch_mode?: boolean;
+    scan_depth?: number;
+    standalone_archives?: boolean;
 }
 
 /**
@@ -160,6 +213,8 @@ export interface UpdateLibraryRequest {
     name?: string;
     scan_interval?: number;
     watch_mode?: boolean;
+    scan_depth?: number;
+    standalone_archives?: boolean;
 }
 
 /**
@@ -185,10 +240,336 @@ export interface ContentResponse {
     title: string;
     chapter_count: number;
     has_thumbnail: boolean;
+    /** Changes when the thumbnail is regenerated; append as `?v=` for immutable caching. */
+    thumbnail_etag: string | null;
+    /** BlurHash of the thumbnail, to show as a placeholder while it loads. */
+    thumbnail_blurhash: string | null;
     metadata: unknown | null;
+    /** Set while the content is in the trash because its folder went missing. */
+    missing_since: string | null;
+    /** Group of related contents this content is in. */
+    series_group_id: number | null;
+    /** Minimum age of readers, from the metadata or set by hand. */
+    age_rating: number | null;
+    created_at: string;
+    /** Last time a scan or edit changed the content. */
+    updated_at: string;
+    /** Chapters the current user has not finished, where the endpoint counts them. */
+    unread_count?: number;
+    /** Average rating users gave, where the endpoint computes it. */
+    ratings?: RatingSummary;
+}
+
+/**
+ * What a user intends to do with a content; independent of progress.
+ */
+export type ReadingStatus =
+    | "reading"
+    | "plan_to_read"
+    | "completed"
+    | "on_hold"
+    | "dropped";
+
+/**
+ * The current user's favorite flag and reading status for a content.
+ */
+export interface ContentStatus {
+    content_id: number;
+    favorite: boolean;
+    status: ReadingStatus | null;
+    /** Null when nothing has been set yet. */
+    updated_at: string | null;
+}
+
+/**
+ * Request to replace the current user's status for a content.
+ */
+export interface UpdateContentStatusRequest {
+    favorite: boolean;
+    /** Null or omitted clears the status. */
+    status?: ReadingStatus | null;
+}
+
+/**
+ * Small JPEG preview of a content's thumbnail.
+ */
+export interface ThumbnailPreview {
+    mime_type: string;
+    /** Version tag, distinct for each preview width. */
+    etag: string;
+    /** Base64 encoded image, e.g. for a `data:` URL. */
+    data: string;
+}
+
+/**
+ * Request to move a content to another scan path, possibly of another
+ * library.
+ */
+export interface MoveContentRequest {
+    scan_path_id: number;
+    /** Move the folder on disk too; otherwise it must already be there. */
+    move_files?: boolean;
+}
+
+/**
+ * Filters on the current user's status for content listings.
+ */
+export interface ContentStatusFilter {
+    status?: ReadingStatus;
+    favorite?: boolean;
+}
+
+/**
+ * One half of a double-page spread.
+ */
+export type SpreadHalf = "left" | "right";
+
+/**
+ * Size of a page of a comic chapter.
+ */
+export interface PageInfo {
+    /** Page index to request. */
+    index: number;
+    /** Width in pixels, 0 if the page could not be read. */
+    width: number;
+    /** Height in pixels, 0 if the page could not be read. */
+    height: number;
+    /** The page is a landscape double-page spread. */
+    spread: boolean;
+    /** Half of the spread this entry shows, when spreads are split. */
+    half?: SpreadHalf;
+    /** The page is a tall strip, sliced into tiles on request. */
+    tall: boolean;
+    /** Tile of the tall page this entry shows, when tall pages are sliced. */
+    tile?: number;
+    /** BlurHash placeholder of the whole page, once it has been served. */
+    blurhash?: string;
+}
+
+/**
+ * Server-side processing of a page image.
+ */
+export interface PageImageOptions {
+    /** Only this half of the page. */
+    half?: SpreadHalf;
+    /** Only this tile of a tall page, counted from the top. */
+    tile?: number;
+    /** Trim page margins, overriding the reader settings. */
+    trim?: boolean;
+}
+
+/**
+ * Publication status of a series.
+ */
+export type SeriesStatus = "ongoing" | "ended" | "hiatus" | "abandoned";
+
+/**
+ * An author and their role, e.g. writer or penciller.
+ */
+export interface MetadataAuthor {
+    name: string;
+    role: string;
+}
+
+/**
+ * Where an alternative title comes from.
+ */
+export type AliasSource = "metadata" | "manual";
+
+/**
+ * An alternative title of a content, used by search and to recognize
+ * renamed folders.
+ */
+export interface ContentAlias {
+    id: number;
+    content_id: number;
+    title: string;
+    source: AliasSource;
+    created_at: string;
+}
+
+/**
+ * Metadata edited by hand. Null values fall back to the scraped metadata;
+ * locked fields survive rescrapes.
+ */
+export interface MetadataEdits {
+    title_lock: boolean;
+    summary: string | null;
+    summary_lock: boolean;
+    status: SeriesStatus | null;
+    status_lock: boolean;
+    language: string | null;
+    language_lock: boolean;
+    tags: string[] | null;
+    tags_lock: boolean;
+    authors: MetadataAuthor[] | null;
+    authors_lock: boolean;
+    /** The rating itself is on the content. */
+    age_rating_lock: boolean;
+    /** Null when nothing has been edited yet. */
+    updated_at: string | null;
+}
+
+/**
+ * Partial metadata edit. Omitted fields are kept, null goes back to the
+ * scraped value. Edited fields are locked unless their lock is sent as false.
+ */
+export interface UpdateMetadataEditsRequest {
+    title?: string;
+    title_lock?: boolean;
+    summary?: string | null;
+    summary_lock?: boolean;
+    status?: SeriesStatus | null;
+    status_lock?: boolean;
+    language?: string | null;
+    language_lock?: boolean;
+    tags?: string[] | null;
+    tags_lock?: boolean;
+    authors?: MetadataAuthor[] | null;
+    authors_lock?: boolean;
+    age_rating?: number | null;
+    age_rating_lock?: boolean;
+}
+
+/**
+ * Direction pages are turned in.
+ */
+export type ReadingDirection = "left_to_right" | "right_to_left" | "vertical";
+
+/**
+ * How many pages are shown at once.
+ */
+export type PageLayout = "single" | "double";
+
+/**
+ * Resolved reader settings.
+ */
+export interface ReaderSettings {
+    reading_direction: ReadingDirection;
+    page_layout: PageLayout;
+    /** Pages are shown as one continuous vertical strip. */
+    webtoon: boolean;
+    /** Uniform white or black page margins are trimmed on the server. */
+    trim_margins: boolean;
+}
+
+/**
+ * Reader settings stored on a library or content. Null inherits.
+ */
+export interface ReaderSettingsOverrides {
+    reading_direction: ReadingDirection | null;
+    page_layout: PageLayout | null;
+    webtoon: boolean | null;
+    trim_margins: boolean | null;
+}
+
+/**
+ * Reader settings of a library or content.
+ */
+export interface ReaderSettingsResponse {
+    /** Settings in effect. */
+    settings: ReaderSettings;
+    /** Values set at this level. */
+    overrides: ReaderSettingsOverrides;
+    /** Library defaults for a content, built-in defaults for a library. */
+    inherited: ReaderSettings;
+}
+
+/**
+ * Partial update of reader settings. Omitted fields are kept, null inherits.
+ */
+export type UpdateReaderSettingsRequest = Partial<ReaderSettingsOverrides>;
+
+/**
+ * Color theme of the web interface.
+ */
+export type Theme = "system" | "light" | "dark";
+
+/**
+ * How pages are scaled to the screen in the reader.
+ */
+export type PageFit = "screen" | "width" | "height" | "original";
+
+/**
+ * Preferences of the current user.
+ */
+export interface UserPreferences {
+    /** Preferred locale; null uses the server locale. */
+    locale: string | null;
+    theme: Theme;
+    /** Reading direction of contents without reader settings of their own. */
+    reading_direction: ReadingDirection;
+    page_fit: PageFit;
+    /** Number of items per page in lists (1-200). */
+    items_per_page: number;
+}
+
+/**
+ * Partial update of user preferences. Omitted fields are kept, a null
+ * locale goes back to the server locale.
+ */
+export type UpdatePreferencesRequest = Partial<UserPreferences>;
+
+/**
+ * Query parameters for the recently added and recently updated feeds.
+ */
+export interface ContentFeedQuery {
+    /** Only content in this library. */
+    library_id?: number;
+    /** 1-based page number (default 1). Ignored with `after`. */
+    page?: number;
+    /** Contents per page (default 20, max 100). */
+    page_size?: number;
+    /** `next_cursor` of the previous page, to continue after it. */
+    after?: string;
+}
+
+/**
+ * A page of a content feed.
+ */
+export interface ContentPage {
+    items: ContentResponse[];
+    total: number;
+    page: number;
+    page_size: number;
+    /** Cursor of the next page, null on the last page. */
+    next_cursor: string | null;
+}
+
+/**
+ * A chapter a scan added to a series already in a library.
+ */
+export interface ChapterRelease {
+    id: number;
+    content_id: number;
+    content_title: string;
+    chapter_id: number;
+    chapter_title: string;
+    volume: number | null;
+    number: number | null;
+    /** When the scan found the chapter. */
     created_at: string;
 }
 
+/**
+ * Query parameters for the new chapters of followed series.
+ */
+export interface UpdatesQuery {
+    /** Releases per page (default 20, max 100). */
+    limit?: number;
+    /** `next_cursor` of the previous page, to continue after it. */
+    after?: string;
+}
+
+/**
+ * A page of new chapters, newest first.
+ */
+export interface UpdatesPage {
+    items: ChapterRelease[];
+    /** Cursor of the next page, null on the last page. */
+    next_cursor: string | null;
+}
+
 /**
  * A chapter within a content item.
  */
@@ -201,6 +582,138 @@ export interface Chapter {
     sort_order: number;
     page_count: number;
     size: number;
+    /** Volume number parsed from the chapter name. */
+    volume: number | null;
+    /** Chapter number parsed from the chapter name, e.g. 12.5. */
+    number: number | null;
+    /** Whether the file could be read at the last scan. */
+    status: ChapterStatus;
+    /** Why the file could not be read, unless the chapter is ready. */
+    error: string | null;
+    /** When a scan first found the chapter. */
+    created_at: string | null;
+    /** When a scan last found the chapter's file changed. */
+    updated_at: string | null;
+}
+
+export type ChapterStatus = 'ready' | 'error' | 'unsupported';
+
+/**
+ * A group of related contents, such as the seasons of a series.
+ */
+export interface SeriesGroup {
+    id: number;
+    name: string;
+    content_count: number;
+    created_at: string;
+}
+
+/**
+ * Format a chapter is downloaded in: the stored file, or a CBZ repacked
+ * from RAR archives and image folders.
+ */
+export type DownloadFormat = "original" | "cbz";
+
+/**
+ * A spine item of an EPUB chapter.
+ */
+export interface EpubSectionInfo {
+    /** Position in the reading order, used to fetch the section. */
+    index: number;
+    id: string;
+    /** Path of the item inside the EPUB. */
+    path: string;
+    media_type: string;
+    /** False for auxiliary content outside the main reading order. */
+    linear: boolean;
+}
+
+/**
+ * A table of contents entry of an EPUB chapter.
+ */
+export interface EpubTocEntry {
+    label: string;
+    /** Index of the section the entry points to, if it is in the spine. */
+    section: number | null;
+    /** Element ID within the section. */
+    fragment: string | null;
+    children: EpubTocEntry[];
+}
+
+/**
+ * Reading order and table of contents of an EPUB chapter.
+ */
+export interface EpubManifest {
+    title: string | null;
+    sections: EpubSectionInfo[];
+    /** Empty if the book has no NCX table of contents. */
+    toc: EpubTocEntry[];
+}
+
+/**
+ * A sanitized EPUB section. Resource URLs in `html` and `stylesheets`
+ * already carry the access token; links to other sections point to
+ * `/epub/sections/{index}` URLs.
+ */
+export interface EpubSection {
+    index: number;
+    html: string;
+    stylesheets: string[];
+}
+
+/**
+ * A virtual chapter of a `.txt` novel, split at its chapter headings.
+ */
+export interface TxtSplitInfo {
+    index: number;
+    /** Heading line, or null for text before the first heading. */
+    title: string | null;
+    /** Length of the text in characters. */
+    length: number;
+}
+
+/**
+ * The text of a virtual chapter, without its heading line.
+ */
+export interface TxtSplitText {
+    index: number;
+    title: string | null;
+    text: string;
+}
+
+/**
+ * Occurrence of a search query in the text of a novel chapter.
+ */
+export interface TextSearchMatch {
+    chapter_id: number;
+    /** 0-based index of the chapter, as used by the chapter text endpoint */
+    chapter_index: number;
+    chapter_title: string;
+    /** Offset of the match in the chapter text, in characters */
+    offset: number;
+    snippet: string;
+}
+
+/**
+ * Duration, chapter markers and title of an audiobook chapter.
+ */
+export interface AudioInfo {
+    title: string | null;
+    duration_ms: number;
+    /** Media type the stream is sent with, e.g. `audio/mpeg`. */
+    media_type: string;
+    /** Always at least one marker; files without chapters have one covering the file. */
+    markers: AudioMarker[];
+}
+
+/**
+ * A chapter marker in an audiobook.
+ */
+export interface AudioMarker {
+    index: number;
+    title: string | null;
+    start_ms: number;
+    end_ms: number;
 }
 
 /**
@@ -218,6 +731,71 @@ export interface UpdateContentRequest {
     metadata?: unknown | null;
 }
 
+/**
+ * An external source of content metadata.
+ */
+export type MetadataProviderKind = "bangumi" | "anilist" | "mangaupdates";
+
+/**
+ * A series found at a metadata provider.
+ */
+export interface MetadataSearchResult {
+    provider: MetadataProviderKind;
+    id: string;
+    title: string;
+    alt_title: string | null;
+    summary: string | null;
+    image: string | null;
+}
+
+/**
+ * Request to replace content metadata with a series from a provider.
+ */
+export interface MetadataMatchRequest {
+    provider: MetadataProviderKind;
+    id: string;
+    /** Fetch the series again instead of using a cached response */
+    refresh_cache?: boolean;
+}
+
+/**
+ * A content whose metadata could not be scraped.
+ */
+export interface MetadataFailure {
+    id: number;
+    content_id: number;
+    library_id: number;
+    title: string;
+    error: string;
+    attempts: number;
+    created_at: string;
+    updated_at: string;
+}
+
+/**
+ * Result of retrying a failed scrape.
+ */
+export interface MetadataRetryResult {
+    resolved: boolean;
+    failure: MetadataFailure | null;
+}
+
+/**
+ * Request to check a Bangumi access token; without a token, the one in the
+ * user's profile is checked.
+ */
+export interface BangumiKeyCheckRequest {
+    api_key?: string;
+}
+
+/**
+ * Whether Bangumi accepts an access token.
+ */
+export interface BangumiKeyCheck {
+    valid: boolean;
+    username: string | null;
+}
+
 // ============================================================================
 // Progress Types
 // ============================================================================
@@ -230,6 +808,290 @@ export interface ProgressResponse {
     position: number;
     percentage: number;
     updated_at: string;
+    /** Place in the text, for novel chapters. */
+    novel?: NovelPosition | null;
+}
+
+/**
+ * A place in the text of a novel chapter.
+ */
+export interface NovelPosition {
+    /** Spine index (EPUB) or virtual chapter index (.txt). */
+    section: number;
+    /** Character offset within the section. */
+    offset: number;
+    /** EPUB canonical fragment identifier, e.g. `epubcfi(/6/4!/4/2/1:120)`. */
+    cfi?: string;
+}
+
+/**
+ * Reading position inside a virtual chapter of a `.txt` chapter.
+ */
+export interface SplitProgressResponse {
+    split_index: number;
+    /** Character offset within the virtual chapter. */
+    position: number;
+    updated_at: string;
+}
+
+/**
+ * How an entry of a Tachiyomi backup was matched to a content.
+ */
+export type TachiyomiMatch = "matched" | "unmatched" | "ambiguous";
+
+/**
+ * Import report of an entry of a Tachiyomi backup.
+ */
+export interface TachiyomiImportItem {
+    title: string;
+    match: TachiyomiMatch;
+    content_id: number | null;
+    content_title: string | null;
+    chapters_with_progress: number;
+    unmatched_chapters: string[];
+    /** Written, or that would be written in a dry run. */
+    progress_imported: number;
+}
+
+/**
+ * Result of importing the read chapters of a Tachiyomi backup.
+ */
+export interface TachiyomiImportResult {
+    dry_run: boolean;
+    matched: number;
+    progress_imported: number;
+    items: TachiyomiImportItem[];
+}
+
+/**
+ * Server whose database can be imported.
+ */
+export type ImportSource = 'komga' | 'kavita';
+
+/**
+ * Result of importing the database of a Komga or Kavita server.
+ */
+export interface ServerImportResult {
+    source: ImportSource;
+    dry_run: boolean;
+    matched_series: number;
+    unmatched_series: string[];
+    metadata_imported: number;
+    progress_imported: number;
+    unknown_users: string[];
+    contents_grouped: number;
+    groups_created: number;
+}
+
+/**
+ * Site reading progress can be pushed to.
+ */
+export type TrackerKind = "anilist" | "myanimelist" | "bangumi";
+
+/**
+ * Sync status of one tracker for the current user.
+ */
+export interface TrackerStatus {
+    tracker: TrackerKind;
+    /** Whether the server has OAuth credentials for the tracker. */
+    configured: boolean;
+    linked: boolean;
+    remote_username: string | null;
+    linked_at: string | null;
+    last_synced_at: string | null;
+    last_error: string | null;
+    /** Number of series whose progress was pushed. */
+    synced_series: number;
+}
+
+/**
+ * Tracker sync status of the current user.
+ */
+export interface TrackerStatusResponse {
+    trackers: TrackerStatus[];
+    /** IDs of the contents kept off all trackers. */
+    opted_out: number[];
+}
+
+/**
+ * Response for starting to link a tracker account.
+ */
+export interface TrackerLinkResponse {
+    authorization_url: string;
+}
+
+/**
+ * Result of pulling the current user's Bangumi collection.
+ */
+export interface TrackerPullResult {
+    tracker: TrackerKind;
+    /** Entries in the collection. */
+    collected: number;
+    /** Entries matched to a local content. */
+    matched: number;
+    /** Matched contents newly marked as favorites. */
+    favorited: number;
+    /** Matched contents given the reading status of their entry. */
+    statuses_set: number;
+}
+
+/**
+ * Whether a content's progress is pushed to the user's trackers.
+ */
+export interface ContentTracking {
+    content_id: number;
+    enabled: boolean;
+}
+
+/**
+ * A place the user marked in a chapter, optionally with a note.
+ */
+export interface BookmarkResponse {
+    id: number;
+    content_id: number;
+    chapter_id: number;
+    /** Page for comics, section for novels, seconds for audiobooks. */
+    position: number;
+    /** Place in the text, for novels. */
+    novel: NovelPosition | null;
+    note: string | null;
+    created_at: string;
+    updated_at: string;
+}
+
+/**
+ * Request to create a bookmark in a chapter.
+ */
+export interface CreateBookmarkRequest {
+    position: number;
+    novel?: NovelPosition;
+    note?: string;
+}
+
+/**
+ * A user's rating of a content from 1 to 10, with an optional review.
+ */
+export interface ContentReview {
+    id: number;
+    content_id: number;
+    user_id: number;
+    username: string;
+    rating: number;
+    review: string | null;
+    created_at: string;
+    updated_at: string;
+}
+
+/**
+ * Request to rate a content, replacing the current user's earlier rating.
+ */
+export interface SaveReviewRequest {
+    rating: number;
+    review?: string | null;
+}
+
+/**
+ * Average rating of a content.
+ */
+export interface RatingSummary {
+    /** Null until someone rates the content. */
+    average_rating: number | null;
+    rating_count: number;
+}
+
+/**
+ * Ratings of a content, most recently changed first, with their average.
+ */
+export interface ContentReviews extends RatingSummary {
+    reviews: ContentReview[];
+}
+
+/**
+ * Expiring link giving read-only access to a content, or one of its
+ * chapters, without logging in.
+ */
+export interface ShareLink {
+    id: number;
+    /** Secret part of the link, used in `/api/shared/{token}`. */
+    token: string;
+    user_id: number;
+    username: string;
+    content_id: number;
+    content_title: string;
+    /** The only chapter shared, or null for every chapter. */
+    chapter_id: number | null;
+    expires_at: string;
+    created_at: string;
+}
+
+/**
+ * Request to share a content or one of its chapters.
+ */
+export interface CreateShareLinkRequest {
+    chapter_id?: number | null;
+    /** Hours until the link expires, 72 by default and at most 720. */
+    expires_in_hours?: number;
+}
+
+/**
+ * A chapter readable through a share link.
+ */
+export interface SharedChapter {
+    id: number;
+    title: string;
+    file_type: string;
+    page_count: number;
+}
+
+/**
+ * What a share link shows to the people it was sent to.
+ */
+export interface SharedContent {
+    title: string;
+    thumbnail_etag: string | null;
+    chapters: SharedChapter[];
+    expires_at: string;
+}
+
+/**
+ * Daily limits in effect for a user, null for unlimited.
+ */
+export interface QuotaLimits {
+    daily_mb: number | null;
+    daily_requests: number | null;
+}
+
+/**
+ * Transfer and requests of a user on one UTC day.
+ */
+export interface QuotaUsage {
+    /** Day as YYYY-MM-DD. */
+    day: string;
+    bytes: number;
+    requests: number;
+}
+
+/**
+ * Daily quota and usage of a user (admin only).
+ */
+export interface UserQuota {
+    user_id: number;
+    /** Limits set for this user; null uses the server default. */
+    daily_mb: number | null;
+    daily_requests: number | null;
+    /** Limits in effect. */
+    limits: QuotaLimits;
+    /** Usage today. */
+    usage: QuotaUsage;
+}
+
+/**
+ * Request to set the daily limits of a user. null uses the server default
+ * and 0 removes the limit.
+ */
+export interface UpdateUserQuotaRequest {
+    daily_mb?: number | null;
+    daily_requests?: number | null;
 }
 
 /**
@@ -244,7 +1106,7 @@ export interface UpdateProgressRequest {
 // ============================================================================
 
 /**
- * Progress information for a running scan task.
+ * Progress information for a running task. Thumbnail tasks count contents.
  */
 export interface TaskProgress {
     scanned_paths: number;
@@ -268,15 +1130,101 @@ export interface AddedChapter {
     path: string;
 }
 
+/**
+ * A chapter archive with entries left out because they lead outside the
+ * archive or are symbolic links.
+ */
+export interface SuspiciousArchive {
+    path: string;
+    entries: string[];
+}
+
+/**
+ * Statistics of one scan path in a scan task.
+ */
+export interface ScanPathStats {
+    scan_path_id: number;
+    path: string;
+    folders_discovered: number;
+    added: number;
+    removed: number;
+    errors: number;
+    duration_ms: number;
+    error: string | null;
+}
+
+/**
+ * A folder a scan could not import or rescan.
+ */
+export interface FolderError {
+    scan_path_id: number;
+    path: string;
+    error: string;
+}
+
 /**
  * Result information for a completed scan task.
  */
 export interface TaskResult {
     added_count: number;
     removed_count: number;
+    moved_count?: number;
     failed_scrape_count: number;
     added_contents: AddedContent[];
     added_chapters: AddedChapter[];
+    thumbnails_regenerated?: number;
+    thumbnails_failed?: number;
+    page_counts_updated?: number;
+    page_counts_failed?: number;
+    metadata_refreshed?: number;
+    metadata_failed?: number;
+    backup_name?: string | null;
+    chapters_removed?: number;
+    suspicious_archives?: SuspiciousArchive[];
+    scan_paths?: ScanPathStats[];
+    folder_errors?: FolderError[];
+}
+
+/**
+ * A content item that a scan would add or remove.
+ */
+export interface PreviewContent {
+    content_id: number | null;
+    title: string;
+    path: string;
+    chapter_count: number;
+}
+
+/**
+ * A content item that a scan would relink to a new folder.
+ */
+export interface PreviewMove {
+    content_id: number;
+    title: string;
+    from: string;
+    to: string;
+}
+
+/**
+ * A content item whose chapters a scan would change.
+ */
+export interface PreviewUpdate {
+    content_id: number;
+    title: string;
+    path: string;
+    restored: boolean;
+    added_chapters: string[];
+    removed_chapters: string[];
+}
+
+/**
+ * Changes a library scan would make.
+ */
+export interface ScanPreview {
+    added: PreviewContent[];
+    removed: PreviewContent[];
+    moved: PreviewMove[];
+    updated: PreviewUpdate[];
 }
 
 /**
@@ -284,8 +1232,11 @@ export interface TaskResult {
  */
 export interface ScanTask {
     id: string;
+    kind: TaskKind;
     library_id: number;
+    scan_path_id: number | null;
     priority: TaskPriority;
+    moved_to_front_at: string | null;
     status: TaskStatus;
     created_at: string;
     started_at: string | null;
@@ -303,6 +1254,72 @@ export interface SubmitScanResponse {
     task: ScanTask;
 }
 
+/**
+ * A page of finished scan tasks, most recently finished first.
+ */
+export interface ScanTaskHistoryPage {
+    items: ScanTask[];
+    total: number;
+    page: number;
+    page_size: number;
+}
+
+/**
+ * Number of scan tasks in each status.
+ */
+export interface TaskStatusCounts {
+    pending: number;
+    running: number;
+    completed: number;
+    failed: number;
+    cancelled: number;
+}
+
+/**
+ * A running scan task with the seconds it has been running for.
+ */
+export interface RunningTask extends ScanTask {
+    elapsed_secs: number;
+}
+
+/**
+ * State of the whole scan queue.
+ */
+export interface ScanQueueState {
+    worker_running: boolean;
+    paused: boolean;
+    worker_count: number;
+    counts: TaskStatusCounts;
+    current: RunningTask[];
+}
+
+// ============================================================================
+// Dashboard Types
+// ============================================================================
+
+/**
+ * Counts of problems needing an administrator.
+ */
+export interface DashboardErrors {
+    metadata_failures: number;
+    media_errors: number;
+    trash: number;
+    failed_tasks: number;
+}
+
+/**
+ * Everything the home page shows, in one response.
+ */
+export interface Dashboard {
+    libraries: LibraryWithStats[];
+    recently_added: ContentResponse[];
+    continue_reading: ContentResponse[];
+    /** Running tasks, then pending tasks in queue order. */
+    active_tasks: ScanTask[];
+    /** Only sent to administrators. */
+    errors: DashboardErrors | null;
+}
+
 // ============================================================================
 // Bangumi Types
 // ============================================================================
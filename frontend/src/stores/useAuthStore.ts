@@ -92,6 +92,23 @@ export const useAuthStore = defineStore('auth', () => {
         }
     }
 
+    async function changePassword(currentPassword: string, newPassword: string): Promise<UserResponse> {
+        loading.value = true
+        error.value = null
+        try {
+            const response = await getAuthApi(() => token.value).changePassword(currentPassword, newPassword)
+            // Earlier tokens are revoked, keep the session with the new one
+            setToken(response.token)
+            user.value = response.user
+            return response.user
+        } catch (e) {
+            error.value = e instanceof Error ? e.message : 'Failed to change password'
+            throw e
+        } finally {
+            loading.value = false
+        }
+    }
+
     async function updateUser(request: UpdateUserRequest): Promise<UserResponse> {
         loading.value = true
         error.value = null
@@ -119,6 +136,7 @@ export const useAuthStore = defineStore('auth', () => {
         login,
         logout,
         fetchUser,
+        changePassword,
         updateUser,
     }
 })
@@ -7,7 +7,11 @@
 import { defineStore } from "pinia";
 import { ref, computed } from "vue";
 import { ApiClient } from "@/api/client";
-import type { ScanTask, SubmitScanResponse } from "@/api/types";
+import type {
+    ScanQueueState,
+    ScanTask,
+    SubmitScanResponse,
+} from "@/api/types";
 import { useAuthStore } from "./useAuthStore";
 
 /**
@@ -37,6 +41,7 @@ export const useScanTaskStore = defineStore("scanTask", () => {
     const pendingTasks = ref<ScanTask[]>([]);
     const processingTasks = ref<ScanTask[]>([]);
     const historyTasks = ref<ScanTask[]>([]);
+    const queueState = ref<ScanQueueState | null>(null);
     const loading = ref(false);
     const error = ref<string | null>(null);
 
@@ -181,6 +186,82 @@ export const useScanTaskStore = defineStore("scanTask", () => {
         }
     }
 
+    /**
+     * Moves a pending scan task to the front of the queue.
+     */
+    async function moveTaskToFront(taskId: string): Promise<ScanTask> {
+        try {
+            const client = getApiClient(getToken);
+            const task = await client.post<ScanTask>(
+                `/api/scan-tasks/${taskId}/move-to-front`
+            );
+
+            // The task now runs next
+            pendingTasks.value = [
+                task,
+                ...pendingTasks.value.filter((t) => t.id !== task.id),
+            ];
+
+            return task;
+        } catch (e) {
+            error.value =
+                e instanceof Error ? e.message : "Failed to move scan task";
+            throw e;
+        }
+    }
+
+    /**
+     * Fetches the state of the queue workers and task counts.
+     */
+    async function fetchQueueState(): Promise<ScanQueueState> {
+        try {
+            const client = getApiClient(getToken);
+            queueState.value = await client.get<ScanQueueState>(
+                "/api/scan-queue/state"
+            );
+            return queueState.value;
+        } catch (e) {
+            error.value =
+                e instanceof Error ? e.message : "Failed to fetch queue state";
+            throw e;
+        }
+    }
+
+    /**
+     * Pauses or resumes the queue. Running tasks finish while paused.
+     */
+    async function setQueuePaused(paused: boolean): Promise<ScanQueueState> {
+        try {
+            const client = getApiClient(getToken);
+            queueState.value = await client.post<ScanQueueState>(
+                paused ? "/api/scan-queue/pause" : "/api/scan-queue/resume"
+            );
+            return queueState.value;
+        } catch (e) {
+            error.value =
+                e instanceof Error ? e.message : "Failed to update the queue";
+            throw e;
+        }
+    }
+
+    /**
+     * Cancels all pending scan tasks.
+     */
+    async function clearQueue(): Promise<number> {
+        try {
+            const client = getApiClient(getToken);
+            const response = await client.post<{ cancelled: number }>(
+                "/api/scan-queue/clear"
+            );
+            await fetchTasks(50, true);
+            return response.cancelled;
+        } catch (e) {
+            error.value =
+                e instanceof Error ? e.message : "Failed to clear the queue";
+            throw e;
+        }
+    }
+
     /**
      * Updates a task in the local state based on its status.
      */
@@ -222,6 +303,7 @@ export const useScanTaskStore = defineStore("scanTask", () => {
         pendingTasks,
         processingTasks,
         historyTasks,
+        queueState,
         loading,
         error,
         // Getters
@@ -234,6 +316,10 @@ export const useScanTaskStore = defineStore("scanTask", () => {
         fetchTask,
         triggerScan,
         cancelTask,
+        moveTaskToFront,
+        fetchQueueState,
+        setQueuePaused,
+        clearQueue,
         clearTasks,
     };
 });